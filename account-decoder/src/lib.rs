@@ -17,8 +17,11 @@ pub mod validator_info;
 use {
     crate::parse_account_data::{parse_account_data, AccountAdditionalData, ParsedAccount},
     solana_sdk::{
-        account::ReadableAccount, account::WritableAccount, clock::Epoch,
-        fee_calculator::FeeCalculator, pubkey::Pubkey,
+        account::ReadableAccount,
+        account::WritableAccount,
+        clock::{Epoch, Slot},
+        fee_calculator::FeeCalculator,
+        pubkey::Pubkey,
     },
     std::{
         io::{Read, Write},
@@ -39,6 +42,9 @@ pub struct UiAccount {
     pub owner: String,
     pub executable: bool,
     pub rent_epoch: Epoch,
+    /// The slot the account was last modified in, only present when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_slot: Option<Slot>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -124,6 +130,7 @@ impl UiAccount {
             owner: account.owner().to_string(),
             executable: account.executable(),
             rent_epoch: account.rent_epoch(),
+            last_modified_slot: None,
         }
     }
 
@@ -186,7 +193,7 @@ pub struct UiDataSliceConfig {
     pub length: usize,
 }
 
-fn slice_data(data: &[u8], data_slice_config: Option<UiDataSliceConfig>) -> &[u8] {
+pub fn slice_data(data: &[u8], data_slice_config: Option<UiDataSliceConfig>) -> &[u8] {
     if let Some(UiDataSliceConfig { offset, length }) = data_slice_config {
         if offset >= data.len() {
             &[]
@@ -23,6 +23,25 @@ pub enum ReplicaAccountInfoVersions<'a> {
     V0_0_1(&'a ReplicaAccountInfo<'a>),
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReplicaEntryInfo<'a> {
+    /// The slot number of the block containing this Entry
+    pub slot: u64,
+    /// The Entry's index in the block
+    pub index: usize,
+    /// The number of hashes since the previous Entry
+    pub num_hashes: u64,
+    /// The Entry's SHA-256 hash, generated from the previous Entry's hash with
+    /// `solana_entry::entry::next_hash()`
+    pub hash: &'a [u8],
+    /// The number of executed transactions in the Entry
+    pub executed_transaction_count: u64,
+}
+
+pub enum ReplicaEntryInfoVersions<'a> {
+    V0_0_1(&'a ReplicaEntryInfo<'a>),
+}
+
 #[derive(Error, Debug)]
 pub enum AccountsDbPluginError {
     #[error("Error opening config file. Error detail: ({0}).")]
@@ -37,6 +56,12 @@ pub enum AccountsDbPluginError {
     #[error("Error updating slot status. Error message: ({msg})")]
     SlotStatusUpdateError { msg: String },
 
+    #[error("Error notifying entry. Error message: ({msg})")]
+    EntryNotifyError { msg: String },
+
+    #[error("Error notifying program event. Error message: ({msg})")]
+    ProgramEventNotifyError { msg: String },
+
     #[error("Plugin-defined custom error. Error message: ({0})")]
     Custom(Box<dyn error::Error + Send + Sync>),
 }
@@ -58,6 +83,24 @@ impl SlotStatus {
     }
 }
 
+/// A change the upgradeable loader made to a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramEvent {
+    Deployed,
+    Upgraded,
+    Closed,
+}
+
+impl ProgramEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProgramEvent::Deployed => "deployed",
+            ProgramEvent::Upgraded => "upgraded",
+            ProgramEvent::Closed => "closed",
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, AccountsDbPluginError>;
 
 pub trait AccountsDbPlugin: Any + Send + Sync + std::fmt::Debug {
@@ -95,4 +138,31 @@ pub trait AccountsDbPlugin: Any + Send + Sync + std::fmt::Debug {
         parent: Option<u64>,
         status: SlotStatus,
     ) -> Result<()>;
+
+    /// Called when an entry is executed during replay, before the block containing it is
+    /// frozen.
+    fn notify_entry(&mut self, _entry: ReplicaEntryInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
+    /// Check if the plugin is interested in entry data. Entry notifications are
+    /// only collected while at least one loaded plugin returns true.
+    /// Default is false -- if the plugin is interested in receiving entry data,
+    /// it must override this function to return true.
+    fn entry_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    /// Called when a transaction executed in a slot deploys, upgrades or closes a program, in
+    /// the same slot as the updates of the program's accounts.  Executors cached for the
+    /// program before are never executed again, so indexers can tell which binary a program
+    /// runs from here on.
+    fn notify_program_event(
+        &mut self,
+        _slot: u64,
+        _program_id: &[u8],
+        _event: ProgramEvent,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@ use {
     crate::accountsdb_plugin_manager::AccountsDbPluginManager,
     log::*,
     solana_accountsdb_plugin_interface::accountsdb_plugin_interface::{
-        ReplicaAccountInfo, ReplicaAccountInfoVersions, SlotStatus,
+        ProgramEvent, ReplicaAccountInfo, ReplicaAccountInfoVersions, SlotStatus,
     },
     solana_measure::measure::Measure,
     solana_metrics::*,
@@ -14,6 +14,7 @@ use {
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::Slot,
+        process_instruction,
         pubkey::Pubkey,
     },
     std::sync::{Arc, RwLock},
@@ -99,6 +100,32 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
     fn notify_slot_rooted(&self, slot: Slot, parent: Option<Slot>) {
         self.notify_slot_status(slot, parent, SlotStatus::Rooted);
     }
+
+    fn notify_program_event(
+        &self,
+        slot: Slot,
+        program_id: &Pubkey,
+        event: process_instruction::ProgramEvent,
+    ) {
+        let event = match event {
+            process_instruction::ProgramEvent::Deployed => ProgramEvent::Deployed,
+            process_instruction::ProgramEvent::Upgraded => ProgramEvent::Upgraded,
+            process_instruction::ProgramEvent::Closed => ProgramEvent::Closed,
+        };
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if let Err(err) = plugin.notify_program_event(slot, program_id.as_ref(), event) {
+                error!(
+                    "Failed to notify program {} {} at slot {}, error: {} to plugin {}",
+                    program_id,
+                    event.as_str(),
+                    slot,
+                    err,
+                    plugin.name()
+                );
+            }
+        }
+    }
 }
 
 impl AccountsUpdateNotifierImpl {
@@ -1,12 +1,13 @@
 use {
     crate::{
         accounts_update_notifier::AccountsUpdateNotifierImpl,
-        accountsdb_plugin_manager::AccountsDbPluginManager,
+        accountsdb_plugin_manager::AccountsDbPluginManager, entry_notifier::EntryNotifierImpl,
         slot_status_observer::SlotStatusObserver,
     },
     crossbeam_channel::Receiver,
     log::*,
     serde_json,
+    solana_ledger::entry_notifier_interface::EntryNotifierLock,
     solana_rpc::optimistically_confirmed_bank_tracker::BankNotification,
     solana_runtime::accounts_update_notifier_interface::AccountsUpdateNotifier,
     std::{
@@ -45,6 +46,7 @@ pub struct AccountsDbPluginService {
     slot_status_observer: SlotStatusObserver,
     plugin_manager: Arc<RwLock<AccountsDbPluginManager>>,
     accounts_update_notifier: AccountsUpdateNotifier,
+    entry_notifier: Option<EntryNotifierLock>,
 }
 
 impl AccountsDbPluginService {
@@ -75,18 +77,27 @@ impl AccountsDbPluginService {
             Self::load_plugin(&mut plugin_manager, accountsdb_plugin_config_file)?;
         }
 
+        let entry_notifications_enabled = plugin_manager.entry_notifications_enabled();
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
         let accounts_update_notifier = Arc::new(RwLock::new(AccountsUpdateNotifierImpl::new(
             plugin_manager.clone(),
         )));
         let slot_status_observer =
             SlotStatusObserver::new(confirmed_bank_receiver, accounts_update_notifier.clone());
+        let entry_notifier: Option<EntryNotifierLock> = if entry_notifications_enabled {
+            Some(Arc::new(RwLock::new(EntryNotifierImpl::new(
+                plugin_manager.clone(),
+            ))))
+        } else {
+            None
+        };
 
         info!("Started AccountsDbPluginService");
         Ok(AccountsDbPluginService {
             slot_status_observer,
             plugin_manager,
             accounts_update_notifier,
+            entry_notifier,
         })
     }
 
@@ -149,6 +160,11 @@ impl AccountsDbPluginService {
         self.accounts_update_notifier.clone()
     }
 
+    /// The notifier for executed entries, if any loaded plugin is interested in them
+    pub fn get_entry_notifier(&self) -> Option<EntryNotifierLock> {
+        self.entry_notifier.clone()
+    }
+
     pub fn join(mut self) -> thread::Result<()> {
         self.slot_status_observer.join()?;
         self.plugin_manager.write().unwrap().unload();
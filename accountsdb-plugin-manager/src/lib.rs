@@ -1,4 +1,5 @@
 pub mod accounts_update_notifier;
 pub mod accountsdb_plugin_manager;
 pub mod accountsdb_plugin_service;
+pub mod entry_notifier;
 pub mod slot_status_observer;
@@ -5,6 +5,9 @@ use log::*;
 use rand::{thread_rng, Rng};
 use rayon::prelude::*;
 use solana_core::banking_stage::BankingStage;
+use solana_core::banking_trace::{
+    BankingTraceReader, BankingTracer, TraceEventKind, TracedDecision,
+};
 use solana_gossip::{cluster_info::ClusterInfo, cluster_info::Node};
 use solana_ledger::{
     blockstore::Blockstore,
@@ -12,27 +15,35 @@ use solana_ledger::{
     get_tmp_ledger_path,
 };
 use solana_measure::measure::Measure;
-use solana_perf::packet::to_packets_chunked;
+use solana_perf::packet::{limited_deserialize, to_packets_chunked, Packet, Packets};
 use solana_poh::poh_recorder::{create_test_recorder, PohRecorder, WorkingBankEntry};
 use solana_runtime::{
     accounts_background_service::AbsRequestSender, bank::Bank, bank_forks::BankForks,
     cost_model::CostModel,
 };
 use solana_sdk::{
+    account::AccountSharedData,
     hash::Hash,
+    pubkey::Pubkey,
     signature::Keypair,
     signature::Signature,
-    system_transaction,
+    system_program, system_transaction,
     timing::{duration_as_us, timestamp},
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
 };
 use solana_streamer::socket::SocketAddrSpace;
 use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    process,
     sync::{atomic::Ordering, mpsc::Receiver, Arc, Mutex, RwLock},
     thread::sleep,
     time::{Duration, Instant},
 };
 
+// Lamports given to each fee payer of a replayed transaction
+const REPLAY_PAYER_LAMPORTS: u64 = 1_000_000_000;
+
 fn check_txs(
     receiver: &Arc<Receiver<WorkingBankEntry>>,
     ref_tx_count: usize,
@@ -103,6 +114,163 @@ fn bytes_as_usize(bytes: &[u8]) -> usize {
     bytes[0] as usize | (bytes[1] as usize) << 8
 }
 
+// Signatures were verified when the trace was recorded, so a traced transaction is rewritten to
+// use a blockhash the bench bank knows and its fee payer is funded
+fn prepare_replayed_packet(
+    packet: &mut Packet,
+    bank: &Bank,
+    funded_payers: &mut HashSet<Pubkey>,
+) -> Option<()> {
+    let mut tx: VersionedTransaction =
+        limited_deserialize(&packet.data[..packet.meta.size]).ok()?;
+    let payer = *tx.message.static_account_keys_iter().next()?;
+    if funded_payers.insert(payer) {
+        bank.store_account(
+            &payer,
+            &AccountSharedData::new(REPLAY_PAYER_LAMPORTS, 0, &system_program::id()),
+        );
+    }
+    tx.message.set_recent_blockhash(bank.last_blockhash());
+    Packet::populate_packet(packet, None, &tx).ok()
+}
+
+/// Feeds the packet batches of a banking trace recorded by `solana-validator
+/// --enable-banking-trace` to a banking stage, as far apart as they were received, and starts a
+/// new bank whenever the traced slot changes.  Votes fail to execute since the traced vote
+/// accounts don't exist in the bench bank
+fn replay_trace(trace_dir: &Path) {
+    let reader = BankingTraceReader::new(trace_dir).unwrap_or_else(|err| {
+        eprintln!("Unable to read banking trace {:?}: {}", trace_dir, err);
+        process::exit(1);
+    });
+
+    let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(1_000_000_000_000);
+    let (verified_sender, verified_receiver) = unbounded();
+    let (vote_sender, vote_receiver) = unbounded();
+    let (tpu_vote_sender, tpu_vote_receiver) = unbounded();
+    let (replay_vote_sender, _replay_vote_receiver) = unbounded();
+    let bank0 = Bank::new_for_benches(&genesis_config);
+    let mut bank_forks = BankForks::new(bank0);
+    let mut bank = bank_forks.working_bank();
+    let base_tx_count = bank.transaction_count();
+
+    let ledger_path = get_tmp_ledger_path!();
+    {
+        let blockstore = Arc::new(
+            Blockstore::open(&ledger_path).expect("Expected to be able to open database ledger"),
+        );
+        let (exit, poh_recorder, poh_service, _signal_receiver) =
+            create_test_recorder(&bank, &blockstore, None);
+        let cluster_info = Arc::new(ClusterInfo::new(
+            Node::new_localhost().info,
+            Arc::new(Keypair::new()),
+            SocketAddrSpace::Unspecified,
+        ));
+        let banking_stage = BankingStage::new(
+            &cluster_info,
+            &poh_recorder,
+            verified_receiver,
+            tpu_vote_receiver,
+            vote_receiver,
+            None,
+            replay_vote_sender,
+            Arc::new(RwLock::new(CostModel::default())),
+            Arc::new(BankingTracer::new_disabled()),
+        );
+        poh_recorder.lock().unwrap().set_bank(&bank);
+
+        let collector = solana_sdk::pubkey::new_rand();
+        let mut funded_payers = HashSet::new();
+        let mut traced_slot = None;
+        let mut first_timestamp_us = None;
+        let replay_start = Instant::now();
+        let mut num_batches = 0;
+        let mut num_packets = 0;
+        let mut decisions = HashMap::new();
+        for event in reader {
+            let event = event.unwrap_or_else(|err| {
+                eprintln!("Unable to read banking trace {:?}: {}", trace_dir, err);
+                process::exit(1);
+            });
+            let (slot, packets) = match event.kind {
+                TraceEventKind::PacketBatch { slot, packets } => (slot, packets),
+                TraceEventKind::Decision { decision, .. } => {
+                    *decisions.entry(decision).or_insert(0) += 1;
+                    continue;
+                }
+            };
+
+            let first_timestamp_us = *first_timestamp_us.get_or_insert(event.timestamp_us);
+            let offset =
+                Duration::from_micros(event.timestamp_us.saturating_sub(first_timestamp_us));
+            if let Some(remaining) = offset.checked_sub(replay_start.elapsed()) {
+                sleep(remaining);
+            }
+
+            if slot.is_some() && slot != traced_slot {
+                if traced_slot.is_some() {
+                    poh_recorder
+                        .lock()
+                        .unwrap()
+                        .reset(bank.clone(), Some((bank.slot(), bank.slot() + 1)));
+                    let new_bank = Bank::new_from_parent(&bank, &collector, bank.slot() + 1);
+                    bank_forks.insert(new_bank);
+                    bank = bank_forks.working_bank();
+                    poh_recorder.lock().unwrap().set_bank(&bank);
+                }
+                traced_slot = slot;
+            }
+
+            let packets: Vec<_> = packets
+                .iter()
+                .map(|traced_packet| {
+                    let mut packet = traced_packet.to_packet();
+                    if !packet.meta.discard
+                        && prepare_replayed_packet(&mut packet, &bank, &mut funded_payers).is_none()
+                    {
+                        packet.meta.discard = true;
+                    }
+                    packet
+                })
+                .collect();
+            num_batches += 1;
+            num_packets += packets.len();
+            let sender = match event.banking_thread_id {
+                0 => &vote_sender,
+                1 => &tpu_vote_sender,
+                _ => &verified_sender,
+            };
+            sender.send(vec![Packets::new(packets)]).unwrap();
+        }
+        // The banking threads process the packets left in their channels before they exit
+        drop(verified_sender);
+        drop(tpu_vote_sender);
+        drop(vote_sender);
+        banking_stage.join().unwrap();
+
+        let committed_txs = bank_forks.working_bank().transaction_count() - base_tx_count;
+        eprintln!(
+            "replayed {} packets in {} batches over {}ms, {} transactions committed in {} banks",
+            num_packets,
+            num_batches,
+            replay_start.elapsed().as_millis(),
+            committed_txs,
+            bank.slot() + 1,
+        );
+        eprintln!(
+            "traced decisions: {} consume, {} forward, {} forward and hold, {} hold",
+            decisions.get(&TracedDecision::Consume).unwrap_or(&0),
+            decisions.get(&TracedDecision::Forward).unwrap_or(&0),
+            decisions.get(&TracedDecision::ForwardAndHold).unwrap_or(&0),
+            decisions.get(&TracedDecision::Hold).unwrap_or(&0),
+        );
+
+        exit.store(true, Ordering::Relaxed);
+        poh_service.join().unwrap();
+    }
+    let _unused = Blockstore::destroy(&ledger_path);
+}
+
 #[allow(clippy::cognitive_complexity)]
 fn main() {
     solana_logger::setup();
@@ -148,8 +316,20 @@ fn main() {
                 .takes_value(true)
                 .help("Number of iterations"),
         )
+        .arg(
+            Arg::with_name("replay_trace")
+                .long("replay-trace")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Replay the banking trace in DIR instead of generating transfers"),
+        )
         .get_matches();
 
+    if let Some(trace_dir) = matches.value_of("replay_trace") {
+        replay_trace(Path::new(trace_dir));
+        return;
+    }
+
     let num_threads =
         value_t!(matches, "num_threads", usize).unwrap_or(BankingStage::num_threads() as usize);
     //   a multiple of packet chunk duplicates to avoid races
@@ -234,6 +414,7 @@ fn main() {
             None,
             replay_vote_sender,
             Arc::new(RwLock::new(CostModel::default())),
+            Arc::new(BankingTracer::new_disabled()),
         );
         poh_recorder.lock().unwrap().set_bank(&bank);
 
@@ -5,10 +5,14 @@
 //! but they are undocumented, may change over time, and are generally more
 //! cumbersome to use.
 
-pub use solana_banks_interface::{BanksClient as TarpcClient, TransactionStatus};
+pub use solana_banks_interface::{
+    BanksClient as TarpcClient, BanksTransactionResultWithMetadata,
+    BanksTransactionResultWithSimulation, TransactionMetadata, TransactionSimulationDetails,
+    TransactionStatus,
+};
 use {
     borsh::BorshDeserialize,
-    futures::{future::join_all, Future, FutureExt},
+    futures::{future::join_all, stream, Future, FutureExt, Stream},
     solana_banks_interface::{BanksRequest, BanksResponse},
     solana_program::{
         clock::Slot, fee_calculator::FeeCalculator, hash::Hash, program_pack::Pack, pubkey::Pubkey,
@@ -35,6 +39,11 @@ use {
 // This exists only for backward compatibility
 pub trait BanksClientExt {}
 
+// Delay before the first retry of a failed slot subscription poll, doubled on each consecutive
+// failure up to `MAX_SLOT_SUBSCRIBE_RETRY_DELAY`
+const MIN_SLOT_SUBSCRIBE_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_SLOT_SUBSCRIBE_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 #[derive(Clone)]
 pub struct BanksClient {
     inner: TarpcClient,
@@ -104,6 +113,25 @@ impl BanksClient {
             .process_transaction_with_commitment_and_context(ctx, transaction, commitment)
     }
 
+    pub fn process_transaction_with_metadata_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithMetadata>> + '_ {
+        self.inner
+            .process_transaction_with_metadata_and_context(ctx, transaction)
+    }
+
+    pub fn simulate_transaction_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithSimulation>> + '_ {
+        self.inner
+            .simulate_transaction_with_commitment_and_context(ctx, transaction, commitment)
+    }
+
     pub fn get_account_with_commitment_and_context(
         &mut self,
         ctx: Context,
@@ -114,6 +142,26 @@ impl BanksClient {
             .get_account_with_commitment_and_context(ctx, address, commitment)
     }
 
+    pub fn get_multiple_accounts_with_commitment_and_context(
+        &mut self,
+        ctx: Context,
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Vec<Option<Account>>>> + '_ {
+        self.inner
+            .get_multiple_accounts_with_commitment_and_context(ctx, addresses, commitment)
+    }
+
+    pub fn get_slot_update_with_context(
+        &mut self,
+        ctx: Context,
+        last_slot: Slot,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Slot>> + '_ {
+        self.inner
+            .get_slot_update_with_context(ctx, last_slot, commitment)
+    }
+
     /// Send a transaction and return immediately. The server will resend the
     /// transaction until either it is accepted by the cluster or the transaction's
     /// blockhash expires.
@@ -181,6 +229,40 @@ impl BanksClient {
         self.process_transaction_with_commitment(transaction, CommitmentLevel::default())
     }
 
+    /// Process a transaction and return its result along with its log messages and the compute
+    /// units consumed by each of its instructions.  The transaction is executed directly against
+    /// the server's working bank, so this is only useful for local testing.
+    pub fn process_transaction_with_metadata(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithMetadata>> + '_ {
+        let mut ctx = context::current();
+        ctx.deadline += Duration::from_secs(50);
+        self.process_transaction_with_metadata_and_context(ctx, transaction)
+    }
+
+    /// Simulate a transaction at the given commitment level, returning its result, log
+    /// messages and the compute units it consumed without committing it
+    pub fn simulate_transaction_with_commitment(
+        &mut self,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithSimulation>> + '_ {
+        self.simulate_transaction_with_commitment_and_context(
+            context::current(),
+            transaction,
+            commitment,
+        )
+    }
+
+    /// Simulate a transaction at the default commitment level
+    pub fn simulate_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> impl Future<Output = io::Result<BanksTransactionResultWithSimulation>> + '_ {
+        self.simulate_transaction_with_commitment(transaction, CommitmentLevel::default())
+    }
+
     pub async fn process_transactions_with_commitment(
         &mut self,
         transactions: Vec<Transaction>,
@@ -263,6 +345,75 @@ impl BanksClient {
         })
     }
 
+    /// Return the accounts at the given addresses at the slot corresponding to the given
+    /// commitment level. Accounts that are not found are returned as None.
+    pub fn get_multiple_accounts_with_commitment(
+        &mut self,
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> impl Future<Output = io::Result<Vec<Option<Account>>>> + '_ {
+        self.get_multiple_accounts_with_commitment_and_context(
+            context::current(),
+            addresses,
+            commitment,
+        )
+    }
+
+    /// Return the accounts at the given addresses at the time of the most recent root slot.
+    /// Accounts that are not found are returned as None.
+    pub fn get_multiple_accounts(
+        &mut self,
+        addresses: Vec<Pubkey>,
+    ) -> impl Future<Output = io::Result<Vec<Option<Account>>>> + '_ {
+        self.get_multiple_accounts_with_commitment(addresses, CommitmentLevel::default())
+    }
+
+    /// Subscribe to slot updates at the given commitment level. The stream yields each new
+    /// slot the server observes, although slots that pass between polls are skipped. After an
+    /// error, the next poll is delayed, backing off exponentially while errors persist.
+    pub fn slot_subscribe(
+        &self,
+        commitment: CommitmentLevel,
+    ) -> impl Stream<Item = io::Result<Slot>> {
+        stream::unfold(
+            (self.clone(), None::<Slot>, None::<Duration>),
+            move |(mut client, last_slot, retry_delay)| async move {
+                if let Some(retry_delay) = retry_delay {
+                    tokio::time::sleep(retry_delay).await;
+                }
+                loop {
+                    let result = match last_slot {
+                        None => {
+                            client
+                                .get_slot_with_context(context::current(), commitment)
+                                .await
+                        }
+                        Some(last_slot) => {
+                            client
+                                .get_slot_update_with_context(
+                                    context::current(),
+                                    last_slot,
+                                    commitment,
+                                )
+                                .await
+                        }
+                    };
+                    match result {
+                        Ok(slot) if Some(slot) == last_slot => continue,
+                        Ok(slot) => return Some((Ok(slot), (client, Some(slot), None))),
+                        Err(err) => {
+                            let retry_delay = retry_delay
+                                .map_or(MIN_SLOT_SUBSCRIBE_RETRY_DELAY, |retry_delay| {
+                                    (retry_delay * 2).min(MAX_SLOT_SUBSCRIBE_RETRY_DELAY)
+                                });
+                            return Some((Err(err), (client, last_slot, Some(retry_delay))));
+                        }
+                    }
+                }
+            },
+        )
+    }
+
     /// Return the balance in lamports of an account at the given address at the slot
     /// corresponding to the given commitment level.
     pub fn get_balance_with_commitment(
@@ -333,12 +484,20 @@ pub async fn start_tcp_client<T: ToSocketAddrs>(addr: T) -> io::Result<BanksClie
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt;
     use solana_banks_server::banks_server::start_local_server;
     use solana_runtime::{
         bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache,
         genesis_utils::create_genesis_config,
     };
-    use solana_sdk::{message::Message, signature::Signer, system_instruction};
+    use solana_sdk::{
+        account::{AccountSharedData, WritableAccount},
+        bpf_loader,
+        instruction::Instruction,
+        message::Message,
+        signature::Signer,
+        system_instruction,
+    };
     use std::sync::{Arc, RwLock};
     use tarpc::transport;
     use tokio::{runtime::Runtime, time::sleep};
@@ -426,4 +585,149 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_banks_server_transfer_with_metadata() -> io::Result<()> {
+        let genesis = create_genesis_config(10);
+        let bank = Bank::new_for_tests(&genesis.genesis_config);
+        let slot = bank.slot();
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(slot, slot),
+        ));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+
+        let mint_pubkey = &genesis.mint_keypair.pubkey();
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let instruction = system_instruction::transfer(mint_pubkey, &bob_pubkey, 1);
+        let message = Message::new(&[instruction], Some(mint_pubkey));
+
+        Runtime::new()?.block_on(async {
+            let client_transport =
+                start_local_server(bank_forks, block_commitment_cache, Duration::from_millis(1))
+                    .await;
+            let mut banks_client = start_client(client_transport).await?;
+            let recent_blockhash = banks_client.get_recent_blockhash().await?;
+            let transaction = Transaction::new(&[&genesis.mint_keypair], message, recent_blockhash);
+            let BanksTransactionResultWithMetadata { result, metadata } = banks_client
+                .process_transaction_with_metadata(transaction)
+                .await?;
+            assert_eq!(result, Ok(()));
+            let metadata = metadata.unwrap();
+            assert!(!metadata.log_messages.is_empty());
+            // The system program is not metered
+            assert_eq!(metadata.compute_units_consumed, vec![0]);
+            assert_eq!(banks_client.get_balance(bob_pubkey).await?, 1);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_banks_server_bpf_compute_units() -> io::Result<()> {
+        let genesis = create_genesis_config(1_000_000_000);
+        let mut bank = Bank::new_for_tests(&genesis.genesis_config);
+        bank.add_builtin(
+            "solana_bpf_loader_program",
+            &bpf_loader::id(),
+            solana_bpf_loader_program::process_instruction,
+        );
+        let program_id = solana_sdk::pubkey::new_rand();
+        let elf = include_bytes!("../../programs/bpf_loader/test_elfs/noop_aligned.so");
+        let mut program_account = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(elf.len()),
+            0,
+            &bpf_loader::id(),
+        );
+        program_account.set_data(elf.to_vec());
+        program_account.set_executable(true);
+        bank.store_account(&program_id, &program_account);
+        let slot = bank.slot();
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(slot, slot),
+        ));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+
+        let mint_pubkey = &genesis.mint_keypair.pubkey();
+        let instruction = Instruction::new_with_bytes(program_id, &[], vec![]);
+        let message = Message::new(&[instruction], Some(mint_pubkey));
+
+        Runtime::new()?.block_on(async {
+            let client_transport =
+                start_local_server(bank_forks, block_commitment_cache, Duration::from_millis(1))
+                    .await;
+            let mut banks_client = start_client(client_transport).await?;
+            let recent_blockhash = banks_client.get_recent_blockhash().await?;
+            let transaction = Transaction::new(&[&genesis.mint_keypair], message, recent_blockhash);
+            let BanksTransactionResultWithMetadata { result, metadata } = banks_client
+                .process_transaction_with_metadata(transaction)
+                .await?;
+            assert_eq!(result, Ok(()));
+            // The units come from the compute meter of the program's invoke context
+            let metadata = metadata.unwrap();
+            assert_eq!(metadata.compute_units_consumed.len(), 1);
+            assert!(metadata.compute_units_consumed[0] > 0);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_banks_server_simulate_transaction() -> io::Result<()> {
+        let genesis = create_genesis_config(10);
+        let bank = Bank::new_for_tests(&genesis.genesis_config);
+        let slot = bank.slot();
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(slot, slot),
+        ));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+
+        let mint_pubkey = genesis.mint_keypair.pubkey();
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let instruction = system_instruction::transfer(&mint_pubkey, &bob_pubkey, 1);
+        let message = Message::new(&[instruction], Some(&mint_pubkey));
+
+        Runtime::new()?.block_on(async {
+            let client_transport =
+                start_local_server(bank_forks, block_commitment_cache, Duration::from_millis(1))
+                    .await;
+            let mut banks_client = start_client(client_transport).await?;
+            let recent_blockhash = banks_client.get_recent_blockhash().await?;
+            let transaction = Transaction::new(&[&genesis.mint_keypair], message, recent_blockhash);
+
+            let BanksTransactionResultWithSimulation {
+                result,
+                simulation_details,
+            } = banks_client.simulate_transaction(transaction).await?;
+            assert_eq!(result, Some(Ok(())));
+            assert!(!simulation_details.unwrap().logs.is_empty());
+
+            // The simulated transfer is not committed
+            let accounts = banks_client
+                .get_multiple_accounts(vec![mint_pubkey, bob_pubkey])
+                .await?;
+            assert!(accounts[0].is_some());
+            assert_eq!(accounts[1], None);
+
+            let mut slots = Box::pin(banks_client.slot_subscribe(CommitmentLevel::default()));
+            assert_eq!(slots.next().await.unwrap()?, slot);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_banks_client_slot_subscribe_backs_off() -> io::Result<()> {
+        // No server is listening, so every poll fails
+        let (client_transport, server_transport) = transport::channel::unbounded();
+        drop(server_transport);
+
+        Runtime::new()?.block_on(async {
+            let banks_client = start_client(client_transport).await?;
+            let mut slots = Box::pin(banks_client.slot_subscribe(CommitmentLevel::default()));
+            let start = std::time::Instant::now();
+            for _ in 0..3 {
+                assert!(slots.next().await.unwrap().is_err());
+            }
+            // The second and third polls were delayed
+            assert!(start.elapsed() >= MIN_SLOT_SUBSCRIBE_RETRY_DELAY * 3);
+            Ok(())
+        })
+    }
 }
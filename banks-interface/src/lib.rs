@@ -27,6 +27,55 @@ pub struct TransactionStatus {
     pub confirmation_status: Option<TransactionConfirmationStatus>,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransactionMetadata {
+    pub log_messages: Vec<String>,
+    /// Compute units consumed by each instruction of the transaction, including the units
+    /// consumed by any programs it invoked
+    pub compute_units_consumed: Vec<u64>,
+}
+
+impl TransactionMetadata {
+    /// Compute units consumed by the whole transaction
+    pub fn total_compute_units_consumed(&self) -> u64 {
+        self.compute_units_consumed.iter().sum()
+    }
+
+    /// Human-readable table of the compute units consumed by each instruction
+    pub fn compute_units_report(&self) -> String {
+        let mut report = String::new();
+        for (index, units) in self.compute_units_consumed.iter().enumerate() {
+            report.push_str(&format!("Instruction {}: {} compute units\n", index, units));
+        }
+        report.push_str(&format!(
+            "Total: {} compute units\n",
+            self.total_compute_units_consumed()
+        ));
+        report
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BanksTransactionResultWithMetadata {
+    pub result: transaction::Result<()>,
+    /// `None` if the transaction could not be executed, for example because its fee payer could
+    /// not be loaded
+    pub metadata: Option<TransactionMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSimulationDetails {
+    pub logs: Vec<String>,
+    pub units_consumed: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BanksTransactionResultWithSimulation {
+    pub result: Option<transaction::Result<()>>,
+    /// `None` if the transaction could not be sanitized or verified, so was never simulated
+    pub simulation_details: Option<TransactionSimulationDetails>,
+}
+
 #[tarpc::service]
 pub trait Banks {
     async fn send_transaction_with_context(transaction: Transaction);
@@ -41,10 +90,24 @@ pub trait Banks {
         transaction: Transaction,
         commitment: CommitmentLevel,
     ) -> Option<transaction::Result<()>>;
+    async fn process_transaction_with_metadata_and_context(
+        transaction: Transaction,
+    ) -> BanksTransactionResultWithMetadata;
+    async fn simulate_transaction_with_commitment_and_context(
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation;
     async fn get_account_with_commitment_and_context(
         address: Pubkey,
         commitment: CommitmentLevel,
     ) -> Option<Account>;
+    async fn get_multiple_accounts_with_commitment_and_context(
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> Vec<Option<Account>>;
+    /// Wait for the slot at the given commitment level to advance past `last_slot`, returning
+    /// the new slot, or the current slot if it does not advance within a few seconds
+    async fn get_slot_update_with_context(last_slot: Slot, commitment: CommitmentLevel) -> Slot;
 }
 
 #[cfg(test)]
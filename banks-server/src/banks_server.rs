@@ -4,24 +4,32 @@ use {
     bincode::{deserialize, serialize},
     futures::{future, prelude::stream::StreamExt},
     solana_banks_interface::{
-        Banks, BanksRequest, BanksResponse, TransactionConfirmationStatus, TransactionStatus,
+        Banks, BanksRequest, BanksResponse, BanksTransactionResultWithMetadata,
+        BanksTransactionResultWithSimulation, TransactionConfirmationStatus, TransactionMetadata,
+        TransactionSimulationDetails, TransactionStatus,
+    },
+    solana_runtime::{
+        bank::{Bank, ExecuteTimings},
+        bank_forks::BankForks,
+        commitment::BlockCommitmentCache,
+        log_collector::log_messages,
     },
-    solana_runtime::{bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache},
     solana_sdk::{
         account::Account,
-        clock::Slot,
+        clock::{Slot, MAX_PROCESSING_AGE},
         commitment_config::CommitmentLevel,
         fee_calculator::FeeCalculator,
         hash::Hash,
         pubkey::Pubkey,
         signature::Signature,
-        transaction::{self, Transaction},
+        transaction::{self, SanitizedTransaction, Transaction},
     },
     solana_send_transaction_service::{
         send_transaction_service::{SendTransactionService, TransactionInfo},
         tpu_info::NullTpuInfo,
     },
     std::{
+        convert::TryFrom,
         io,
         net::{Ipv4Addr, SocketAddr},
         sync::{
@@ -29,7 +37,7 @@ use {
             Arc, RwLock,
         },
         thread::Builder,
-        time::Duration,
+        time::{Duration, Instant},
     },
     tarpc::{
         context::Context,
@@ -157,6 +165,9 @@ fn verify_transaction(
     }
 }
 
+/// How long `get_slot_update_with_context` waits for the slot to advance before returning
+const SLOT_UPDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[tarpc::server]
 impl Banks for BanksServer {
     async fn send_transaction_with_context(self, _: Context, transaction: Transaction) {
@@ -269,6 +280,85 @@ impl Banks for BanksServer {
             .await
     }
 
+    async fn process_transaction_with_metadata_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+    ) -> BanksTransactionResultWithMetadata {
+        let bank = self.bank_forks.read().unwrap().working_bank();
+        if let Err(err) = verify_transaction(&transaction, &bank.feature_set) {
+            return BanksTransactionResultWithMetadata {
+                result: Err(err),
+                metadata: None,
+            };
+        }
+
+        let batch = match bank.prepare_batch(vec![transaction]) {
+            Ok(batch) => batch,
+            Err(err) => {
+                return BanksTransactionResultWithMetadata {
+                    result: Err(err),
+                    metadata: None,
+                }
+            }
+        };
+        let (
+            results,
+            _balances,
+            _inner_instructions,
+            mut transaction_log_entries,
+            _return_data,
+            mut transaction_compute_units,
+        ) = bank.load_execute_and_commit_transactions(
+            &batch,
+            MAX_PROCESSING_AGE,
+            false, // collect_balances
+            false, // enable_cpi_recording
+            true,  // enable_log_recording
+            &mut ExecuteTimings::default(),
+        );
+        let result = results.execution_results[0].0.clone();
+        let metadata = transaction_log_entries
+            .pop()
+            .flatten()
+            .zip(transaction_compute_units.pop().flatten())
+            .map(
+                |(log_entries, compute_units_consumed)| TransactionMetadata {
+                    log_messages: log_messages(&log_entries),
+                    compute_units_consumed,
+                },
+            );
+        BanksTransactionResultWithMetadata { result, metadata }
+    }
+
+    async fn simulate_transaction_with_commitment_and_context(
+        self,
+        _: Context,
+        transaction: Transaction,
+        commitment: CommitmentLevel,
+    ) -> BanksTransactionResultWithSimulation {
+        let bank = self.bank(commitment);
+        let sanitized_transaction = match verify_transaction(&transaction, &bank.feature_set)
+            .and_then(|_| SanitizedTransaction::try_from(transaction))
+        {
+            Ok(sanitized_transaction) => sanitized_transaction,
+            Err(err) => {
+                return BanksTransactionResultWithSimulation {
+                    result: Some(Err(err)),
+                    simulation_details: None,
+                }
+            }
+        };
+        let simulation_result = bank.simulate_transaction_unchecked(sanitized_transaction);
+        BanksTransactionResultWithSimulation {
+            result: Some(simulation_result.result),
+            simulation_details: Some(TransactionSimulationDetails {
+                logs: log_messages(&simulation_result.log_entries),
+                units_consumed: simulation_result.units_consumed,
+            }),
+        }
+    }
+
     async fn get_account_with_commitment_and_context(
         self,
         _: Context,
@@ -278,6 +368,34 @@ impl Banks for BanksServer {
         let bank = self.bank(commitment);
         bank.get_account(&address).map(Account::from)
     }
+
+    async fn get_multiple_accounts_with_commitment_and_context(
+        self,
+        _: Context,
+        addresses: Vec<Pubkey>,
+        commitment: CommitmentLevel,
+    ) -> Vec<Option<Account>> {
+        let bank = self.bank(commitment);
+        addresses
+            .iter()
+            .map(|address| bank.get_account(address).map(Account::from))
+            .collect()
+    }
+
+    async fn get_slot_update_with_context(
+        self,
+        _: Context,
+        last_slot: Slot,
+        commitment: CommitmentLevel,
+    ) -> Slot {
+        let start = Instant::now();
+        let mut slot = self.slot(commitment);
+        while slot <= last_slot && start.elapsed() < SLOT_UPDATE_TIMEOUT {
+            sleep(self.poll_signature_status_sleep_duration).await;
+            slot = self.slot(commitment);
+        }
+        slot
+    }
 }
 
 pub async fn start_local_server(
@@ -1,4 +1,7 @@
-use crate::cli::Config;
+use crate::{
+    cli::Config,
+    scenario::{Scenario, TransactionKind},
+};
 use log::*;
 use rayon::prelude::*;
 use solana_client::perf_utils::{sample_txs, SampleStats};
@@ -15,7 +18,7 @@ use solana_sdk::{
     message::Message,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    system_instruction, system_transaction,
+    system_instruction, system_transaction, sysvar,
     timing::{duration_as_ms, duration_as_s, duration_as_us, timestamp},
     transaction::Transaction,
 };
@@ -36,6 +39,13 @@ const MAX_TX_QUEUE_AGE: u64 = (MAX_PROCESSING_AGE as f64 * DEFAULT_S_PER_SLOT) a
 
 pub const MAX_SPENDS_PER_TX: u64 = 4;
 
+const TOKEN_MINT_SEED: &str = "bench-tps-mint";
+const TOKEN_ACCOUNT_SEED: &str = "bench-tps-token";
+// Sizes of SPL Token mints and accounts
+const TOKEN_MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_AMOUNT_PER_ACCOUNT: u64 = 1_000_000_000;
+
 #[derive(Debug)]
 pub enum BenchTpsError {
     AirdropFailure,
@@ -103,6 +113,7 @@ where
         .unwrap()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_chunked_transfers(
     recent_blockhash: Arc<RwLock<Hash>>,
     shared_txs: &SharedTransactions,
@@ -112,6 +123,7 @@ fn generate_chunked_transfers(
     threads: usize,
     duration: Duration,
     sustained: bool,
+    scenario: Option<&Scenario>,
 ) {
     // generate and send transactions for the specified duration
     let start = Instant::now();
@@ -119,6 +131,7 @@ fn generate_chunked_transfers(
     let mut reclaim_lamports_back_to_source_account = false;
     let mut chunk_index = 0;
     while start.elapsed() < duration {
+        let chunk_start = Instant::now();
         generate_txs(
             shared_txs,
             &recent_blockhash,
@@ -126,6 +139,7 @@ fn generate_chunked_transfers(
             &dest_keypair_chunks[chunk_index],
             threads,
             reclaim_lamports_back_to_source_account,
+            scenario,
         );
 
         // In sustained mode, overlap the transfers with generation. This has higher average
@@ -143,6 +157,17 @@ fn generate_chunked_transfers(
             }
         }
 
+        // Hold back the next chunk until this one has taken as long as the scenario's target
+        // arrival rate allows
+        if let Some(tps) = scenario.and_then(|scenario| scenario.tps_at(start.elapsed())) {
+            let chunk_len = source_keypair_chunks[chunk_index].len();
+            let target_duration = Duration::from_secs_f64(chunk_len as f64 / tps as f64);
+            let elapsed = chunk_start.elapsed();
+            if elapsed < target_duration {
+                sleep(target_duration - elapsed);
+            }
+        }
+
         // Rotate destination keypairs so that the next round of transactions will have different
         // transaction signatures even when blockhash is reused.
         dest_keypair_chunks[chunk_index].rotate_left(1);
@@ -205,6 +230,7 @@ where
         tx_count,
         sustained,
         target_slots_per_epoch,
+        scenario,
         ..
     } = config;
 
@@ -277,6 +303,7 @@ where
         threads,
         duration,
         sustained,
+        scenario.as_ref(),
     );
 
     // Stop the sampling threads so it will collect the stats
@@ -345,6 +372,110 @@ fn generate_system_txs(
         .collect()
 }
 
+/// Address of the token account of `owner` created by `create_token_accounts`
+pub fn token_account_address(owner: &Pubkey, token_program_id: &Pubkey) -> Pubkey {
+    Pubkey::create_with_seed(owner, TOKEN_ACCOUNT_SEED, token_program_id).unwrap()
+}
+
+/// Address of the mint created by `create_token_accounts` for `mint_authority`
+pub fn token_mint_address(mint_authority: &Pubkey, token_program_id: &Pubkey) -> Pubkey {
+    Pubkey::create_with_seed(mint_authority, TOKEN_MINT_SEED, token_program_id).unwrap()
+}
+
+fn token_transfer_instruction(
+    token_program_id: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = vec![3]; // Transfer
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction::new_with_bytes(
+        *token_program_id,
+        &data,
+        vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+/// Addresses a program invocation draws its writable accounts from
+fn contended_account_addresses(program_id: &Pubkey, num_accounts: usize) -> Vec<Pubkey> {
+    (0..num_accounts)
+        .map(|i| {
+            Pubkey::create_with_seed(program_id, &format!("bench-tps-{}", i), program_id).unwrap()
+        })
+        .collect()
+}
+
+fn generate_scenario_txs(
+    scenario: &Scenario,
+    source: &[&Keypair],
+    dest: &VecDeque<&Keypair>,
+    reclaim: bool,
+    blockhash: &Hash,
+) -> Vec<(Transaction, u64)> {
+    let pairs: Vec<_> = if !reclaim {
+        source.iter().zip(dest.iter()).collect()
+    } else {
+        dest.iter().zip(source.iter()).collect()
+    };
+
+    let contended_accounts: Vec<Vec<Pubkey>> = scenario
+        .mix
+        .iter()
+        .map(|mix| match &mix.kind {
+            TransactionKind::ProgramInvocation {
+                program_id,
+                num_writable_accounts,
+                num_contended_accounts,
+                ..
+            } => contended_account_addresses(
+                program_id,
+                num_contended_accounts.unwrap_or(*num_writable_accounts),
+            ),
+            _ => vec![],
+        })
+        .collect();
+
+    pairs
+        .par_iter()
+        .enumerate()
+        .map(|(i, (from, to))| {
+            let mix_index = scenario.mix_index_at(i);
+            let instruction = match &scenario.mix[mix_index].kind {
+                TransactionKind::Transfer => {
+                    system_instruction::transfer(&from.pubkey(), &to.pubkey(), 1)
+                }
+                TransactionKind::TokenTransfer { program_id } => token_transfer_instruction(
+                    program_id,
+                    &token_account_address(&from.pubkey(), program_id),
+                    &token_account_address(&to.pubkey(), program_id),
+                    &from.pubkey(),
+                    1,
+                ),
+                TransactionKind::ProgramInvocation {
+                    program_id,
+                    num_writable_accounts,
+                    data,
+                    ..
+                } => {
+                    let pool = &contended_accounts[mix_index];
+                    let accounts = (0..*num_writable_accounts)
+                        .map(|j| AccountMeta::new(pool[(i + j) % pool.len()], false))
+                        .collect();
+                    Instruction::new_with_bytes(*program_id, data, accounts)
+                }
+            };
+            let message = Message::new(&[instruction], Some(&from.pubkey()));
+            (Transaction::new(&[*from], message, *blockhash), timestamp())
+        })
+        .collect()
+}
+
 fn generate_txs(
     shared_txs: &SharedTransactions,
     blockhash: &Arc<RwLock<Hash>>,
@@ -352,6 +483,7 @@ fn generate_txs(
     dest: &VecDeque<&Keypair>,
     threads: usize,
     reclaim: bool,
+    scenario: Option<&Scenario>,
 ) {
     let blockhash = *blockhash.read().unwrap();
     let tx_count = source.len();
@@ -361,7 +493,10 @@ fn generate_txs(
     );
     let signing_start = Instant::now();
 
-    let transactions = generate_system_txs(source, dest, reclaim, &blockhash);
+    let transactions = match scenario {
+        Some(scenario) => generate_scenario_txs(scenario, source, dest, reclaim, &blockhash),
+        None => generate_system_txs(source, dest, reclaim, &blockhash),
+    };
 
     let duration = signing_start.elapsed();
     let ns = duration.as_secs() * 1_000_000_000 + u64::from(duration.subsec_nanos());
@@ -927,6 +1062,95 @@ pub fn generate_and_fund_keypairs<T: 'static + Client + Send + Sync>(
     Ok(keypairs)
 }
 
+/// Create an SPL Token mint with `funding_key` as its authority, and a token account holding
+/// tokens of that mint for each of `keypairs`.  Accounts created by a previous run are reused
+pub fn create_token_accounts<T: 'static + Client + Send + Sync>(
+    client: &Arc<T>,
+    funding_key: &Keypair,
+    keypairs: &[Keypair],
+    token_program_id: &Pubkey,
+) {
+    let mint = token_mint_address(&funding_key.pubkey(), token_program_id);
+    if client.get_balance(&mint).unwrap_or(0) == 0 {
+        info!("Creating token mint {}", mint);
+        let mut initialize_mint_data = vec![0, 0]; // InitializeMint, 0 decimals
+        initialize_mint_data.extend_from_slice(funding_key.pubkey().as_ref());
+        initialize_mint_data.push(0); // No freeze authority
+        let instructions = [
+            system_instruction::create_account_with_seed(
+                &funding_key.pubkey(),
+                &mint,
+                &funding_key.pubkey(),
+                TOKEN_MINT_SEED,
+                client
+                    .get_minimum_balance_for_rent_exemption(TOKEN_MINT_LEN)
+                    .unwrap(),
+                TOKEN_MINT_LEN as u64,
+                token_program_id,
+            ),
+            Instruction::new_with_bytes(
+                *token_program_id,
+                &initialize_mint_data,
+                vec![
+                    AccountMeta::new(mint, false),
+                    AccountMeta::new_readonly(sysvar::rent::id(), false),
+                ],
+            ),
+        ];
+        let message = Message::new(&instructions, Some(&funding_key.pubkey()));
+        client
+            .send_and_confirm_message(&[funding_key], message)
+            .expect("create token mint");
+    }
+
+    let account_lamports = client
+        .get_minimum_balance_for_rent_exemption(TOKEN_ACCOUNT_LEN)
+        .unwrap();
+    info!("Creating {} token accounts...", keypairs.len());
+    keypairs.par_iter().for_each(|keypair| {
+        let account = token_account_address(&keypair.pubkey(), token_program_id);
+        if client.get_balance(&account).unwrap_or(0) > 0 {
+            return;
+        }
+        let mut mint_to_data = vec![7]; // MintTo
+        mint_to_data.extend_from_slice(&TOKEN_AMOUNT_PER_ACCOUNT.to_le_bytes());
+        let instructions = [
+            system_instruction::create_account_with_seed(
+                &keypair.pubkey(),
+                &account,
+                &keypair.pubkey(),
+                TOKEN_ACCOUNT_SEED,
+                account_lamports,
+                TOKEN_ACCOUNT_LEN as u64,
+                token_program_id,
+            ),
+            Instruction::new_with_bytes(
+                *token_program_id,
+                &[1], // InitializeAccount
+                vec![
+                    AccountMeta::new(account, false),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new_readonly(keypair.pubkey(), false),
+                    AccountMeta::new_readonly(sysvar::rent::id(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                *token_program_id,
+                &mint_to_data,
+                vec![
+                    AccountMeta::new(mint, false),
+                    AccountMeta::new(account, false),
+                    AccountMeta::new_readonly(funding_key.pubkey(), true),
+                ],
+            ),
+        ];
+        let message = Message::new(&instructions, Some(&keypair.pubkey()));
+        client
+            .send_and_confirm_message(&[keypair, funding_key], message)
+            .expect("create token account");
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -957,6 +1181,90 @@ mod tests {
         do_bench_tps(client, config, keypairs);
     }
 
+    #[test]
+    fn test_bench_tps_bank_client_with_scenario() {
+        let (genesis_config, id) = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let client = Arc::new(BankClient::new(bank));
+
+        let scenario = Scenario::from_toml(
+            r#"
+            [[mix]]
+            kind = "transfer"
+            weight = 3
+
+            [[mix]]
+            kind = "program-invocation"
+            program_id = "11111111111111111111111111111111"
+            num_writable_accounts = 2
+            num_contended_accounts = 3
+
+            [[rate]]
+            duration_secs = 5
+            tps = 10
+            "#,
+        )
+        .unwrap();
+        let config = Config {
+            id,
+            tx_count: 10,
+            duration: Duration::from_secs(5),
+            scenario: Some(scenario),
+            ..Config::default()
+        };
+
+        let keypair_count = config.tx_count * config.keypair_multiplier;
+        let keypairs =
+            generate_and_fund_keypairs(client.clone(), None, &config.id, keypair_count, 20)
+                .unwrap();
+
+        do_bench_tps(client, config, keypairs);
+    }
+
+    #[test]
+    fn test_generate_scenario_txs() {
+        let scenario = Scenario::from_toml(
+            r#"
+            [[mix]]
+            kind = "token-transfer"
+
+            [[mix]]
+            kind = "program-invocation"
+            program_id = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+            num_writable_accounts = 2
+            "#,
+        )
+        .unwrap();
+        let keypairs: Vec<_> = (0..4).map(|_| Keypair::new()).collect();
+        let source: Vec<_> = keypairs[..2].iter().collect();
+        let dest: VecDeque<_> = keypairs[2..].iter().collect();
+
+        let txs = generate_scenario_txs(&scenario, &source, &dest, false, &Hash::default());
+        assert_eq!(txs.len(), 2);
+
+        let token_program_id = scenario.token_program_id().unwrap();
+        let token_message = txs[0].0.message();
+        assert_eq!(
+            token_message.account_keys,
+            vec![
+                source[0].pubkey(),
+                token_account_address(&source[0].pubkey(), token_program_id),
+                token_account_address(&dest[0].pubkey(), token_program_id),
+                *token_program_id,
+            ]
+        );
+
+        // Every invocation writes the same contended accounts
+        let invocation_message = txs[1].0.message();
+        let memo_program_id = invocation_message.account_keys[3];
+        let contended_accounts = contended_account_addresses(&memo_program_id, 2);
+        let mut writable_accounts = invocation_message.account_keys[1..3].to_vec();
+        writable_accounts.sort();
+        let mut expected_accounts = contended_accounts;
+        expected_accounts.sort();
+        assert_eq!(writable_accounts, expected_accounts);
+    }
+
     #[test]
     fn test_bench_tps_fund_keys() {
         let (genesis_config, id) = create_genesis_config(10_000);
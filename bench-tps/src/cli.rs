@@ -1,3 +1,4 @@
+use crate::scenario::Scenario;
 use clap::{crate_description, crate_name, App, Arg, ArgMatches};
 use solana_faucet::faucet::FAUCET_PORT;
 use solana_sdk::fee_calculator::FeeRateGovernor;
@@ -5,7 +6,7 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::{read_keypair_file, Keypair},
 };
-use std::{net::SocketAddr, process::exit, time::Duration};
+use std::{net::SocketAddr, path::Path, process::exit, time::Duration};
 
 const NUM_LAMPORTS_PER_ACCOUNT_DEFAULT: u64 = solana_sdk::native_token::LAMPORTS_PER_SOL;
 
@@ -29,6 +30,7 @@ pub struct Config {
     pub num_lamports_per_account: u64,
     pub target_slots_per_epoch: u64,
     pub target_node: Option<Pubkey>,
+    pub scenario: Option<Scenario>,
 }
 
 impl Default for Config {
@@ -52,6 +54,7 @@ impl Default for Config {
             num_lamports_per_account: NUM_LAMPORTS_PER_ACCOUNT_DEFAULT,
             target_slots_per_epoch: 0,
             target_node: None,
+            scenario: None,
         }
     }
 }
@@ -189,6 +192,16 @@ pub fn build_args<'a, 'b>(version: &'b str) -> App<'a, 'b> {
                     "Wait until epochs are this many slots long.",
                 ),
         )
+        .arg(
+            Arg::with_name("scenario")
+                .long("scenario")
+                .value_name("FILE")
+                .takes_value(true)
+                .help(
+                    "TOML file describing the mix of transactions to send and their arrival rate; \
+                     defaults to sending lamport transfers as fast as possible",
+                ),
+        )
 }
 
 /// Parses a clap `ArgMatches` structure into a `Config`
@@ -285,5 +298,12 @@ pub fn extract_args(matches: &ArgMatches) -> Config {
             .expect("can't parse target slots per epoch");
     }
 
+    if let Some(path) = matches.value_of("scenario") {
+        args.scenario = Some(Scenario::load(Path::new(path)).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1)
+        }));
+    }
+
     args
 }
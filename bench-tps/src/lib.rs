@@ -1,3 +1,4 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod bench;
 pub mod cli;
+pub mod scenario;
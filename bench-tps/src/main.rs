@@ -1,6 +1,8 @@
 #![allow(clippy::integer_arithmetic)]
 use log::*;
-use solana_bench_tps::bench::{do_bench_tps, generate_and_fund_keypairs, generate_keypairs};
+use solana_bench_tps::bench::{
+    create_token_accounts, do_bench_tps, generate_and_fund_keypairs, generate_keypairs,
+};
 use solana_bench_tps::cli;
 use solana_genesis::Base64Account;
 use solana_gossip::gossip_service::{discover_cluster, get_client, get_multi_client};
@@ -147,5 +149,13 @@ fn main() {
         })
     };
 
+    if let Some(token_program_id) = cli_config
+        .scenario
+        .as_ref()
+        .and_then(|scenario| scenario.token_program_id())
+    {
+        create_token_accounts(&client, id, &keypairs, token_program_id);
+    }
+
     do_bench_tps(client, cli_config, keypairs);
 }
@@ -0,0 +1,450 @@
+//! Benchmark scenarios loaded from a TOML file, describing the mix of transactions to send and
+//! the rate at which to send them
+//!
+//! ```toml
+//! [[mix]]
+//! kind = "transfer"
+//! weight = 6
+//!
+//! [[mix]]
+//! kind = "token-transfer"
+//! weight = 3
+//!
+//! [[mix]]
+//! kind = "program-invocation"
+//! weight = 1
+//! program_id = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+//! num_writable_accounts = 4
+//! num_contended_accounts = 16
+//!
+//! [[rate]]
+//! duration_secs = 30
+//! tps = 1000
+//! end_tps = 5000
+//!
+//! [[rate]]
+//! duration_secs = 60
+//! tps = 5000
+//! ```
+use {
+    serde::{Deserialize, Deserializer},
+    solana_sdk::pubkey::Pubkey,
+    std::{convert::TryFrom, fs, path::Path, str::FromStr, time::Duration},
+};
+
+pub const DEFAULT_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Kinds of transactions to send, in proportion to their weights
+    pub mix: Vec<TransactionMix>,
+    /// Phases of the arrival rate curve; the last phase continues until the benchmark ends.
+    /// Transactions are sent as fast as possible if empty
+    #[serde(default)]
+    pub rate: Vec<RatePhase>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(try_from = "TransactionMixEntry")]
+pub struct TransactionMix {
+    pub weight: u32,
+    pub kind: TransactionKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TransactionKind {
+    /// Transfer one lamport between two bench accounts
+    Transfer,
+    /// Transfer one token between the token accounts of two bench accounts
+    TokenTransfer { program_id: Pubkey },
+    /// Invoke a program with writable accounts drawn from a pool shared by all transactions
+    ProgramInvocation {
+        program_id: Pubkey,
+        num_writable_accounts: usize,
+        /// Size of the pool writable accounts are drawn from, defaults to
+        /// `num_writable_accounts` so that every invocation contends for the same accounts
+        num_contended_accounts: Option<usize>,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum TransactionKindName {
+    Transfer,
+    TokenTransfer,
+    ProgramInvocation,
+}
+
+/// A `[[mix]]` table as written in the scenario file.  The keys of every kind are accepted here
+/// so that unknown keys are rejected, the keys that don't belong to the entry's kind are
+/// rejected when converting it into a `TransactionMix`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransactionMixEntry {
+    kind: TransactionKindName,
+    #[serde(default = "default_weight")]
+    weight: u32,
+    #[serde(default, deserialize_with = "deserialize_optional_pubkey")]
+    program_id: Option<Pubkey>,
+    num_writable_accounts: Option<usize>,
+    num_contended_accounts: Option<usize>,
+    data: Option<Vec<u8>>,
+}
+
+impl TryFrom<TransactionMixEntry> for TransactionMix {
+    type Error = String;
+
+    fn try_from(entry: TransactionMixEntry) -> Result<Self, Self::Error> {
+        let TransactionMixEntry {
+            kind,
+            weight,
+            program_id,
+            num_writable_accounts,
+            num_contended_accounts,
+            data,
+        } = entry;
+        // Rejects the keys that are set but don't belong to `kind`
+        let reject_keys =
+            |kind: &str, keys: &[(&str, bool)]| match keys.iter().find(|(_, is_set)| *is_set) {
+                Some((key, _)) => Err(format!("unexpected key `{}` for kind `{}`", key, kind)),
+                None => Ok(()),
+            };
+        let kind = match kind {
+            TransactionKindName::Transfer => {
+                reject_keys(
+                    "transfer",
+                    &[
+                        ("program_id", program_id.is_some()),
+                        ("num_writable_accounts", num_writable_accounts.is_some()),
+                        ("num_contended_accounts", num_contended_accounts.is_some()),
+                        ("data", data.is_some()),
+                    ],
+                )?;
+                TransactionKind::Transfer
+            }
+            TransactionKindName::TokenTransfer => {
+                reject_keys(
+                    "token-transfer",
+                    &[
+                        ("num_writable_accounts", num_writable_accounts.is_some()),
+                        ("num_contended_accounts", num_contended_accounts.is_some()),
+                        ("data", data.is_some()),
+                    ],
+                )?;
+                TransactionKind::TokenTransfer {
+                    program_id: program_id.unwrap_or_else(default_token_program_id),
+                }
+            }
+            TransactionKindName::ProgramInvocation => TransactionKind::ProgramInvocation {
+                program_id: program_id.ok_or_else(|| {
+                    "missing key `program_id` for kind `program-invocation`".to_string()
+                })?,
+                num_writable_accounts: num_writable_accounts.unwrap_or_default(),
+                num_contended_accounts,
+                data: data.unwrap_or_default(),
+            },
+        };
+        Ok(Self { weight, kind })
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RatePhase {
+    pub duration_secs: u64,
+    /// Transactions per second at the start of the phase
+    pub tps: u64,
+    /// Transactions per second at the end of the phase, the rate ramps linearly from `tps` if set
+    #[serde(default)]
+    pub end_tps: Option<u64>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_token_program_id() -> Pubkey {
+    Pubkey::from_str(DEFAULT_TOKEN_PROGRAM_ID).unwrap()
+}
+
+fn deserialize_optional_pubkey<'de, D>(deserializer: D) -> Result<Option<Pubkey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, String> {
+        let scenario: Self =
+            toml::from_str(contents).map_err(|err| format!("Invalid scenario: {}", err))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.total_weight() == 0 {
+            return Err("Invalid scenario: mix must have a positive total weight".to_string());
+        }
+        if let Some(token_program_id) = self.token_program_id() {
+            let multiple_token_programs = self.mix.iter().any(|mix| match &mix.kind {
+                TransactionKind::TokenTransfer { program_id } => program_id != token_program_id,
+                _ => false,
+            });
+            if multiple_token_programs {
+                return Err(
+                    "Invalid scenario: token transfers must use one token program".to_string(),
+                );
+            }
+        }
+        for mix in &self.mix {
+            if let TransactionKind::ProgramInvocation {
+                num_writable_accounts,
+                num_contended_accounts: Some(num_contended_accounts),
+                ..
+            } = mix.kind
+            {
+                if num_contended_accounts < num_writable_accounts {
+                    return Err(
+                        "Invalid scenario: num_contended_accounts is less than num_writable_accounts"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        for phase in &self.rate {
+            if phase.duration_secs == 0 || phase.tps == 0 || phase.end_tps == Some(0) {
+                return Err(
+                    "Invalid scenario: rate phases must have a positive duration and tps"
+                        .to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn total_weight(&self) -> u64 {
+        self.mix.iter().map(|mix| u64::from(mix.weight)).sum()
+    }
+
+    /// Index into `mix` of the `index`th transaction, interleaving kinds in proportion to their
+    /// weights
+    pub fn mix_index_at(&self, index: usize) -> usize {
+        let mut slot = index as u64 % self.total_weight();
+        for (mix_index, mix) in self.mix.iter().enumerate() {
+            if slot < u64::from(mix.weight) {
+                return mix_index;
+            }
+            slot -= u64::from(mix.weight);
+        }
+        unreachable!("slot is less than the total weight")
+    }
+
+    /// The kind of the `index`th transaction
+    pub fn kind_at(&self, index: usize) -> &TransactionKind {
+        &self.mix[self.mix_index_at(index)].kind
+    }
+
+    /// The target arrival rate `elapsed` into the benchmark, `None` if unlimited
+    pub fn tps_at(&self, elapsed: Duration) -> Option<u64> {
+        let mut phase_start = Duration::default();
+        for phase in &self.rate {
+            let phase_duration = Duration::from_secs(phase.duration_secs);
+            if elapsed < phase_start + phase_duration {
+                let end_tps = phase.end_tps.unwrap_or(phase.tps);
+                let progress = (elapsed - phase_start).as_secs_f64() / phase_duration.as_secs_f64();
+                let tps = phase.tps as f64 + (end_tps as f64 - phase.tps as f64) * progress;
+                return Some(tps.round() as u64);
+            }
+            phase_start += phase_duration;
+        }
+        self.rate
+            .last()
+            .map(|phase| phase.end_tps.unwrap_or(phase.tps))
+    }
+
+    /// The token program of the token transfers in the mix, if any
+    pub fn token_program_id(&self) -> Option<&Pubkey> {
+        self.mix.iter().find_map(|mix| match &mix.kind {
+            TransactionKind::TokenTransfer { program_id } => Some(program_id),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenario_from_toml() {
+        let scenario = Scenario::from_toml(
+            r#"
+            [[mix]]
+            kind = "transfer"
+            weight = 2
+
+            [[mix]]
+            kind = "token-transfer"
+
+            [[mix]]
+            kind = "program-invocation"
+            program_id = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+            num_writable_accounts = 2
+            num_contended_accounts = 4
+            data = [1, 2, 3]
+
+            [[rate]]
+            duration_secs = 10
+            tps = 100
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            scenario,
+            Scenario {
+                mix: vec![
+                    TransactionMix {
+                        weight: 2,
+                        kind: TransactionKind::Transfer,
+                    },
+                    TransactionMix {
+                        weight: 1,
+                        kind: TransactionKind::TokenTransfer {
+                            program_id: default_token_program_id(),
+                        },
+                    },
+                    TransactionMix {
+                        weight: 1,
+                        kind: TransactionKind::ProgramInvocation {
+                            program_id: Pubkey::from_str(
+                                "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+                            )
+                            .unwrap(),
+                            num_writable_accounts: 2,
+                            num_contended_accounts: Some(4),
+                            data: vec![1, 2, 3],
+                        },
+                    },
+                ],
+                rate: vec![RatePhase {
+                    duration_secs: 10,
+                    tps: 100,
+                    end_tps: None,
+                }],
+            }
+        );
+        assert_eq!(
+            scenario.token_program_id(),
+            Some(&default_token_program_id())
+        );
+    }
+
+    #[test]
+    fn test_scenario_invalid() {
+        assert!(Scenario::from_toml("mix = []").is_err());
+        assert!(Scenario::from_toml("[[mix]]\nkind = \"transfer\"\nweight = 0").is_err());
+        assert!(Scenario::from_toml("[[mix]]\nkind = \"unknown\"").is_err());
+        // unknown keys, and keys of another kind, are rejected
+        assert!(Scenario::from_toml("[[mix]]\nkind = \"transfer\"\nwieght = 2").is_err());
+        assert!(Scenario::from_toml("[[mix]]\nkind = \"token-transfer\"\ndata = [1]").is_err());
+        assert!(Scenario::from_toml(
+            "[[mix]]\nkind = \"transfer\"\nprogram_id = \"Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo\""
+        )
+        .is_err());
+        assert!(Scenario::from_toml("[[mix]]\nkind = \"transfer\"\n[[rate]]\ntsp = 1").is_err());
+        assert!(Scenario::from_toml(
+            "[[mix]]\nkind = \"program-invocation\"\nprogram_id = \"invalid\""
+        )
+        .is_err());
+        assert!(Scenario::from_toml(
+            r#"
+            [[mix]]
+            kind = "program-invocation"
+            program_id = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+            num_writable_accounts = 2
+            num_contended_accounts = 1
+            "#
+        )
+        .is_err());
+        assert!(Scenario::from_toml(
+            "[[mix]]\nkind = \"transfer\"\n[[rate]]\nduration_secs = 1\ntps = 0"
+        )
+        .is_err());
+        assert!(Scenario::from_toml(
+            r#"
+            [[mix]]
+            kind = "token-transfer"
+
+            [[mix]]
+            kind = "token-transfer"
+            program_id = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+            "#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_scenario_kind_at() {
+        let scenario = Scenario::from_toml(
+            "[[mix]]\nkind = \"transfer\"\nweight = 2\n[[mix]]\nkind = \"token-transfer\"",
+        )
+        .unwrap();
+        let kinds: Vec<_> = (0..6).map(|i| scenario.kind_at(i)).collect();
+        let transfer = &TransactionKind::Transfer;
+        let token_transfer = &TransactionKind::TokenTransfer {
+            program_id: default_token_program_id(),
+        };
+        assert_eq!(
+            kinds,
+            vec![
+                transfer,
+                transfer,
+                token_transfer,
+                transfer,
+                transfer,
+                token_transfer
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scenario_tps_at() {
+        let scenario = Scenario::from_toml("[[mix]]\nkind = \"transfer\"").unwrap();
+        assert_eq!(scenario.tps_at(Duration::from_secs(100)), None);
+
+        let scenario = Scenario::from_toml(
+            r#"
+            [[mix]]
+            kind = "transfer"
+
+            [[rate]]
+            duration_secs = 10
+            tps = 100
+            end_tps = 200
+
+            [[rate]]
+            duration_secs = 10
+            tps = 50
+            "#,
+        )
+        .unwrap();
+        assert_eq!(scenario.tps_at(Duration::from_secs(0)), Some(100));
+        assert_eq!(scenario.tps_at(Duration::from_secs(5)), Some(150));
+        assert_eq!(scenario.tps_at(Duration::from_secs(10)), Some(50));
+        assert_eq!(scenario.tps_at(Duration::from_secs(19)), Some(50));
+        assert_eq!(scenario.tps_at(Duration::from_secs(100)), Some(50));
+    }
+}
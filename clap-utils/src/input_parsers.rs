@@ -1,7 +1,10 @@
 use {
-    crate::keypair::{
-        keypair_from_seed_phrase, pubkey_from_path, resolve_signer_from_path, signer_from_path,
-        ASK_KEYWORD, SKIP_SEED_PHRASE_VALIDATION_ARG,
+    crate::{
+        keypair::{
+            keypair_from_seed_phrase, pubkey_from_path, resolve_signer_from_path, signer_from_path,
+            ASK_KEYWORD, SKIP_SEED_PHRASE_VALIDATION_ARG,
+        },
+        keystore::{read_keypair_file_or_keystore, read_keystore_pubkey},
     },
     chrono::DateTime,
     clap::ArgMatches,
@@ -12,7 +15,7 @@ use {
         genesis_config::ClusterType,
         native_token::sol_to_lamports,
         pubkey::Pubkey,
-        signature::{read_keypair_file, Keypair, Signature, Signer},
+        signature::{Keypair, Signature, Signer},
     },
     std::{str::FromStr, sync::Arc},
 };
@@ -62,7 +65,7 @@ pub fn keypair_of(matches: &ArgMatches<'_>, name: &str) -> Option<Keypair> {
             let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
             keypair_from_seed_phrase(name, skip_validation, true, None, true).ok()
         } else {
-            read_keypair_file(value).ok()
+            read_keypair_file_or_keystore(value).ok()
         }
     } else {
         None
@@ -77,7 +80,7 @@ pub fn keypairs_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Keypair>>
                     let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
                     keypair_from_seed_phrase(name, skip_validation, true, None, true).ok()
                 } else {
-                    read_keypair_file(value).ok()
+                    read_keypair_file_or_keystore(value).ok()
                 }
             })
             .collect()
@@ -85,9 +88,11 @@ pub fn keypairs_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Keypair>>
 }
 
 // Return a pubkey for an argument that can itself be parsed into a pubkey,
-// or is a filename that can be read as a keypair
+// or is a filename that can be read as a keypair or keystore
 pub fn pubkey_of(matches: &ArgMatches<'_>, name: &str) -> Option<Pubkey> {
-    value_of(matches, name).or_else(|| keypair_of(matches, name).map(|keypair| keypair.pubkey()))
+    value_of(matches, name)
+        .or_else(|| matches.value_of(name).and_then(read_keystore_pubkey))
+        .or_else(|| keypair_of(matches, name).map(|keypair| keypair.pubkey()))
 }
 
 pub fn pubkeys_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Pubkey>> {
@@ -95,9 +100,11 @@ pub fn pubkeys_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Pubkey>> {
         values
             .map(|value| {
                 value.parse::<Pubkey>().unwrap_or_else(|_| {
-                    read_keypair_file(value)
-                        .expect("read_keypair_file failed")
-                        .pubkey()
+                    read_keystore_pubkey(value).unwrap_or_else(|| {
+                        read_keypair_file_or_keystore(value)
+                            .expect("read_keypair_file failed")
+                            .pubkey()
+                    })
                 })
             })
             .collect()
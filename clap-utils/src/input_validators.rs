@@ -1,5 +1,8 @@
 use {
-    crate::keypair::{parse_signer_source, SignerSourceKind, ASK_KEYWORD},
+    crate::{
+        keypair::{parse_signer_source, SignerSourceKind, ASK_KEYWORD},
+        keystore::read_keystore_pubkey,
+    },
     chrono::DateTime,
     solana_sdk::{
         clock::{Epoch, Slot},
@@ -73,11 +76,15 @@ where
     is_parsable_generic::<Hash, _>(string)
 }
 
-// Return an error if a keypair file cannot be parsed.
+// Return an error if a keypair file cannot be parsed.  A keystore is accepted without being
+// unlocked
 pub fn is_keypair<T>(string: T) -> Result<(), String>
 where
     T: AsRef<str> + Display,
 {
+    if read_keystore_pubkey(string.as_ref()).is_some() {
+        return Ok(());
+    }
     read_keypair_file(string.as_ref())
         .map(|_| ())
         .map_err(|err| format!("{}", err))
@@ -91,9 +98,7 @@ where
     if string.as_ref() == ASK_KEYWORD {
         return Ok(());
     }
-    read_keypair_file(string.as_ref())
-        .map(|_| ())
-        .map_err(|err| format!("{}", err))
+    is_keypair(string)
 }
 
 // Return an error if a `SignerSourceKind::Prompt` cannot be parsed
@@ -12,6 +12,7 @@
 use {
     crate::{
         input_parsers::{pubkeys_sigs_of, STDOUT_OUTFILE_TOKEN},
+        keystore::{read_keypair_file_or_keystore, read_keystore_pubkey},
         offline::{SIGNER_ARG, SIGN_ONLY_ARG},
         ArgConstant,
     },
@@ -31,7 +32,7 @@ use {
         signature::{
             generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed,
             keypair_from_seed_and_derivation_path, keypair_from_seed_phrase_and_passphrase,
-            read_keypair, read_keypair_file, Keypair, NullSigner, Presigner, Signature, Signer,
+            read_keypair, Keypair, NullSigner, Presigner, Signature, Signer,
         },
     },
     std::{
@@ -772,7 +773,7 @@ pub fn signer_from_path_with_config(
                 legacy,
             )?))
         }
-        SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
+        SignerSourceKind::Filepath(path) => match read_keypair_file_or_keystore(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 format!("could not read keypair file \"{}\". Run \"solana-keygen new\" to create a keypair file: {}", path, e),
@@ -865,6 +866,12 @@ pub fn pubkey_from_path(
     let SignerSource { kind, .. } = parse_signer_source(path)?;
     match kind {
         SignerSourceKind::Pubkey(pubkey) => Ok(pubkey),
+        // A keystore records its pubkey, so it needn't be unlocked
+        SignerSourceKind::Filepath(keystore_path)
+            if read_keystore_pubkey(&keystore_path).is_some() =>
+        {
+            Ok(read_keystore_pubkey(&keystore_path).unwrap())
+        }
         _ => Ok(signer_from_path(matches, path, keypair_name, wallet_manager)?.pubkey()),
     }
 }
@@ -894,7 +901,8 @@ pub fn resolve_signer_from_path(
             )
             .map(|_| None)
         }
-        SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
+        SignerSourceKind::Filepath(path) if read_keystore_pubkey(&path).is_some() => Ok(Some(path)),
+        SignerSourceKind::Filepath(path) => match read_keypair_file_or_keystore(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 format!(
@@ -1017,7 +1025,7 @@ pub fn keypair_from_path(
                 legacy,
             )?)
         }
-        SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
+        SignerSourceKind::Filepath(path) => match read_keypair_file_or_keystore(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 format!(
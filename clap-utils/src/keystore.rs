@@ -0,0 +1,453 @@
+//! Password-encrypted keystore files, to keep a keypair off the disk in plain text.
+//!
+//! The keypair bytes are sealed with ChaCha20-Poly1305 under a key derived from the password
+//! with scrypt, or with PBKDF2-HMAC-SHA256 for keystores written by earlier versions. The pubkey
+//! is stored in the clear, and authenticated as associated data, so the keystore can be
+//! identified without the password.
+//!
+//! A keystore is accepted wherever a keypair file is.  Its password is taken from the
+//! `SOLANA_KEYSTORE_PASSWORD` environment variable, read from the first line of the file
+//! descriptor in `SOLANA_KEYSTORE_PASSWORD_FD`, which keeps it out of the environment of the
+//! process, or else prompted for.
+use {
+    lazy_static::lazy_static,
+    ring::{
+        aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN},
+        pbkdf2,
+        rand::{SecureRandom, SystemRandom},
+    },
+    rpassword::prompt_password_stderr,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{read_keypair, Keypair, Signer},
+    },
+    std::{
+        env, error,
+        fs::{self, File, OpenOptions},
+        io::{self, Write},
+        num::NonZeroU32,
+        path::Path,
+        str::FromStr,
+        sync::Mutex,
+    },
+};
+
+/// Environment variable holding the password of keystore files
+pub const KEYSTORE_PASSWORD_ENV: &str = "SOLANA_KEYSTORE_PASSWORD";
+/// Environment variable holding a file descriptor to read the password of keystore files from
+pub const KEYSTORE_PASSWORD_FD_ENV: &str = "SOLANA_KEYSTORE_PASSWORD_FD";
+
+const KEYSTORE_VERSION: u8 = 1;
+const CIPHER: &str = "chacha20-poly1305";
+const SCRYPT_LOG_N: u8 = 16;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+lazy_static! {
+    /// The password read from `KEYSTORE_PASSWORD_FD_ENV`, which can only be read once
+    static ref FD_PASSWORD: Mutex<Option<String>> = Mutex::default();
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kdf")]
+enum Kdf {
+    #[serde(rename = "scrypt", rename_all = "camelCase")]
+    Scrypt {
+        scrypt_log_n: u8,
+        scrypt_r: u32,
+        scrypt_p: u32,
+    },
+    #[serde(rename = "pbkdf2-hmac-sha256", rename_all = "camelCase")]
+    Pbkdf2 { kdf_iterations: u32 },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Keystore {
+    version: u8,
+    pubkey: String,
+    #[serde(flatten)]
+    kdf: Kdf,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(
+    kdf: &Kdf,
+    password: &str,
+    salt: &[u8],
+) -> Result<LessSafeKey, Box<dyn error::Error>> {
+    let mut key = [0u8; KEY_LEN];
+    match kdf {
+        Kdf::Scrypt {
+            scrypt_log_n,
+            scrypt_r,
+            scrypt_p,
+        } => {
+            let params = scrypt::Params::new(*scrypt_log_n, *scrypt_r, *scrypt_p)
+                .map_err(|_| "Invalid keystore scrypt parameters")?;
+            scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+                .map_err(|_| "Failed to derive keystore key")?;
+        }
+        Kdf::Pbkdf2 { kdf_iterations } => {
+            let iterations =
+                NonZeroU32::new(*kdf_iterations).ok_or("Invalid keystore kdfIterations")?;
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                iterations,
+                salt,
+                password.as_bytes(),
+                &mut key,
+            );
+        }
+    }
+    Ok(LessSafeKey::new(
+        UnboundKey::new(&aead::CHACHA20_POLY1305, &key).unwrap(),
+    ))
+}
+
+fn decode(name: &str, value: &str) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    bs58::decode(value)
+        .into_vec()
+        .map_err(|err| format!("Invalid keystore {}: {}", name, err).into())
+}
+
+fn encrypt_keypair(
+    keypair: &Keypair,
+    password: &str,
+    kdf: Kdf,
+) -> Result<Keystore, Box<dyn error::Error>> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| "Failed to generate random keystore salt")?;
+
+    let key = derive_key(&kdf, password, &salt)?;
+    let pubkey = keypair.pubkey();
+    let mut ciphertext = keypair.to_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(pubkey.as_ref()),
+        &mut ciphertext,
+    )
+    .map_err(|_| "Failed to encrypt keypair")?;
+
+    Ok(Keystore {
+        version: KEYSTORE_VERSION,
+        pubkey: pubkey.to_string(),
+        kdf,
+        salt: bs58::encode(salt).into_string(),
+        cipher: CIPHER.to_string(),
+        nonce: bs58::encode(nonce).into_string(),
+        ciphertext: bs58::encode(ciphertext).into_string(),
+    })
+}
+
+fn decrypt_keystore(keystore: &Keystore, password: &str) -> Result<Keypair, Box<dyn error::Error>> {
+    if keystore.version != KEYSTORE_VERSION || keystore.cipher != CIPHER {
+        return Err("Unsupported keystore format".into());
+    }
+    let pubkey = Pubkey::from_str(&keystore.pubkey)?;
+    let salt = decode("salt", &keystore.salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(&decode("nonce", &keystore.nonce)?)
+        .map_err(|_| "Invalid keystore nonce")?;
+    let mut ciphertext = decode("ciphertext", &keystore.ciphertext)?;
+
+    let key = derive_key(&keystore.kdf, password, &salt)?;
+    let keypair_bytes = key
+        .open_in_place(nonce, Aad::from(pubkey.as_ref()), &mut ciphertext)
+        .map_err(|_| "Incorrect password or corrupted keystore")?;
+
+    let keypair = Keypair::from_bytes(keypair_bytes)?;
+    if keypair.pubkey() != pubkey {
+        return Err("Keystore pubkey does not match its keypair".into());
+    }
+    Ok(keypair)
+}
+
+/// Writes `keypair` to `outfile` as a keystore encrypted with `password`
+pub fn write_encrypted_keypair_file<F: AsRef<Path>>(
+    keypair: &Keypair,
+    password: &str,
+    outfile: F,
+) -> Result<(), Box<dyn error::Error>> {
+    let keystore = encrypt_keypair(
+        keypair,
+        password,
+        Kdf::Scrypt {
+            scrypt_log_n: SCRYPT_LOG_N,
+            scrypt_r: SCRYPT_R,
+            scrypt_p: SCRYPT_P,
+        },
+    )?;
+    let mut file = create_private_file(outfile.as_ref())?;
+    file.write_all(&serde_json::to_vec_pretty(&keystore)?)?;
+    Ok(())
+}
+
+// Opens `path` for writing, truncated and readable only by its owner.  An existing file keeps
+// its permissions when opened, so they are restricted before anything is written to it
+fn create_private_file(path: &Path) -> io::Result<File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = {
+        #[cfg(not(unix))]
+        {
+            OpenOptions::new()
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            OpenOptions::new().mode(0o600)
+        }
+    }
+    .write(true)
+    .truncate(true)
+    .create(true)
+    .open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/// Reads a keypair from the keystore at `path`, decrypting it with `password`
+pub fn read_encrypted_keypair_file<F: AsRef<Path>>(
+    path: F,
+    password: &str,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let keystore: Keystore = serde_json::from_reader(File::open(path)?)?;
+    decrypt_keystore(&keystore, password)
+}
+
+/// The pubkey of the keystore at `path`, which doesn't need its password, or `None` if `path`
+/// isn't a keystore
+pub fn read_keystore_pubkey<F: AsRef<Path>>(path: F) -> Option<Pubkey> {
+    let keystore: Keystore = serde_json::from_reader(File::open(path).ok()?).ok()?;
+    Pubkey::from_str(&keystore.pubkey).ok()
+}
+
+#[cfg(unix)]
+fn read_password_fd(fd: &str) -> Result<String, Box<dyn error::Error>> {
+    use std::io::{BufRead, BufReader};
+
+    let fd: u32 = fd
+        .parse()
+        .map_err(|_| format!("Invalid {}: {}", KEYSTORE_PASSWORD_FD_ENV, fd))?;
+    if fd <= 2 {
+        return Err(format!(
+            "{} must not be stdin, stdout or stderr: {}",
+            KEYSTORE_PASSWORD_FD_ENV, fd
+        )
+        .into());
+    }
+    // Opened anew rather than taken over, so the descriptor itself is left open, and only the
+    // first line is read so that a writer needn't close its end
+    let file = File::open(format!("/dev/fd/{}", fd))?;
+    let mut password = String::new();
+    BufReader::new(file).read_line(&mut password)?;
+    Ok(password.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+#[cfg(not(unix))]
+fn read_password_fd(_fd: &str) -> Result<String, Box<dyn error::Error>> {
+    Err(format!("{} is only supported on unix", KEYSTORE_PASSWORD_FD_ENV).into())
+}
+
+/// The password of keystore files given by the environment, if any
+fn keystore_password_from_env() -> Option<Result<String, Box<dyn error::Error>>> {
+    if let Ok(password) = env::var(KEYSTORE_PASSWORD_ENV) {
+        return Some(Ok(password));
+    }
+    let fd = env::var(KEYSTORE_PASSWORD_FD_ENV).ok()?;
+    let mut fd_password = FD_PASSWORD.lock().unwrap();
+    if fd_password.is_none() {
+        match read_password_fd(&fd) {
+            Ok(password) => *fd_password = Some(password),
+            Err(err) => return Some(Err(err)),
+        }
+    }
+    Some(Ok(fd_password.clone().unwrap()))
+}
+
+/// The password of the keystore at `path`, see the module documentation for where it is taken
+/// from
+pub fn keystore_password(path: &str) -> Result<String, Box<dyn error::Error>> {
+    keystore_password_from_env().unwrap_or_else(|| {
+        Ok(prompt_password_stderr(&format!(
+            "Keystore password for {}: ",
+            path
+        ))?)
+    })
+}
+
+fn read_keypair_contents(
+    path: &Path,
+    password: impl FnOnce() -> Result<String, Box<dyn error::Error>>,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let contents = fs::read(path)?;
+    match serde_json::from_slice::<Keystore>(&contents) {
+        Ok(keystore) => decrypt_keystore(&keystore, &password()?),
+        Err(_) => read_keypair(&mut contents.as_slice()),
+    }
+}
+
+/// Reads a keypair file, or a keystore unlocked with [`keystore_password`]
+pub fn read_keypair_file_or_keystore<F: AsRef<Path>>(
+    path: F,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let path = path.as_ref();
+    read_keypair_contents(path, || keystore_password(&path.display().to_string()))
+}
+
+/// Reads a keypair file, or a keystore unlocked with the password given by the environment.
+/// Never prompts, for callers without a terminal
+pub fn read_keypair_file_or_keystore_without_prompt<F: AsRef<Path>>(
+    path: F,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    read_keypair_contents(path.as_ref(), || {
+        keystore_password_from_env().unwrap_or_else(|| {
+            Err(format!(
+                "Keystore password not set, set {} or {}",
+                KEYSTORE_PASSWORD_ENV, KEYSTORE_PASSWORD_FD_ENV
+            )
+            .into())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::signature::write_keypair_file};
+
+    fn test_kdf() -> Kdf {
+        Kdf::Scrypt {
+            scrypt_log_n: 1,
+            scrypt_r: 8,
+            scrypt_p: 1,
+        }
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let keypair = Keypair::new();
+        for kdf in [test_kdf(), Kdf::Pbkdf2 { kdf_iterations: 1 }] {
+            let keystore = encrypt_keypair(&keypair, "password", kdf.clone()).unwrap();
+            assert_eq!(keystore.pubkey, keypair.pubkey().to_string());
+            assert_eq!(
+                decrypt_keystore(&keystore, "password").unwrap().to_bytes(),
+                keypair.to_bytes()
+            );
+            assert!(decrypt_keystore(&keystore, "wrong password").is_err());
+
+            // The pubkey is authenticated along with the secret
+            let other_keystore = Keystore {
+                pubkey: Pubkey::new_unique().to_string(),
+                ..encrypt_keypair(&keypair, "password", kdf).unwrap()
+            };
+            assert!(decrypt_keystore(&other_keystore, "password").is_err());
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_keystore_format() {
+        // Keystores written before scrypt was the default are still read
+        let keypair = Keypair::new();
+        let keystore = serde_json::to_value(
+            encrypt_keypair(&keypair, "password", Kdf::Pbkdf2 { kdf_iterations: 1 }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(keystore["kdf"], "pbkdf2-hmac-sha256");
+        assert_eq!(keystore["kdfIterations"], 1);
+        let keystore: Keystore = serde_json::from_value(keystore).unwrap();
+        assert_eq!(
+            decrypt_keystore(&keystore, "password").unwrap().pubkey(),
+            keypair.pubkey()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_password_fd() {
+        use std::os::unix::io::AsRawFd;
+
+        assert!(read_password_fd("not a fd").is_err());
+        assert!(read_password_fd("0").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("password");
+        fs::write(&path, "password\nsecond line").unwrap();
+        let file = File::open(&path).unwrap();
+        let fd = file.as_raw_fd().to_string();
+        assert_eq!(read_password_fd(&fd).unwrap(), "password");
+        // The descriptor stays open
+        assert_eq!(read_password_fd(&fd).unwrap(), "password");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let path = dir.path().join("new").join("id.keystore.json");
+        create_private_file(&path).unwrap();
+        assert_eq!(mode(&path), 0o600);
+
+        // An existing file readable by others is restricted and truncated
+        let path = dir.path().join("existing.keystore.json");
+        fs::write(&path, "secret").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        create_private_file(&path).unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert!(fs::read(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_keypair_file_or_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let keypair = Keypair::new();
+
+        let keypair_path = dir.path().join("id.json");
+        write_keypair_file(&keypair, &keypair_path).unwrap();
+        assert_eq!(read_keystore_pubkey(&keypair_path), None);
+        assert_eq!(
+            read_keypair_file_or_keystore(&keypair_path)
+                .unwrap()
+                .pubkey(),
+            keypair.pubkey()
+        );
+
+        let keystore_path = dir.path().join("id.keystore.json");
+        let keystore = encrypt_keypair(&keypair, "password", test_kdf()).unwrap();
+        fs::write(&keystore_path, serde_json::to_vec(&keystore).unwrap()).unwrap();
+        assert_eq!(read_keystore_pubkey(&keystore_path), Some(keypair.pubkey()));
+        assert!(read_keypair_file_or_keystore_without_prompt(&keystore_path).is_err());
+        env::set_var(KEYSTORE_PASSWORD_ENV, "password");
+        assert_eq!(
+            read_keypair_file_or_keystore(&keystore_path)
+                .unwrap()
+                .pubkey(),
+            keypair.pubkey()
+        );
+        assert_eq!(
+            read_keypair_file_or_keystore_without_prompt(&keystore_path)
+                .unwrap()
+                .pubkey(),
+            keypair.pubkey()
+        );
+        env::remove_var(KEYSTORE_PASSWORD_ENV);
+    }
+}
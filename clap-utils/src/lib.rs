@@ -27,6 +27,7 @@ pub mod fee_payer;
 pub mod input_parsers;
 pub mod input_validators;
 pub mod keypair;
+pub mod keystore;
 pub mod memo;
 pub mod nonce;
 pub mod offline;
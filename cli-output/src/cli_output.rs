@@ -28,7 +28,7 @@ use {
         signature::Signature,
         stake::state::{Authorized, Lockup},
         stake_history::StakeHistoryEntry,
-        transaction::{Transaction, TransactionError},
+        transaction::{Transaction, TransactionError, VersionedTransaction},
     },
     solana_transaction_status::{
         EncodedConfirmedBlock, EncodedTransaction, TransactionConfirmationStatus,
@@ -682,6 +682,88 @@ impl CliValidator {
     }
 }
 
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliValidatorsDelinquencyReport {
+    pub first_epoch: Epoch,
+    pub last_epoch: Epoch,
+    pub first_slot: Slot,
+    pub last_slot: Slot,
+    pub total_leader_slots: u64,
+    pub total_skipped_slots: u64,
+    pub validators: Vec<CliValidatorDelinquencyReportEntry>,
+}
+
+impl QuietDisplay for CliValidatorsDelinquencyReport {}
+impl VerboseDisplay for CliValidatorsDelinquencyReport {}
+
+impl fmt::Display for CliValidatorsDelinquencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{}",
+            style(format!(
+                "  {:<44}  {:>15}  {:>15}  {:>23}  {:>10}",
+                "Identity",
+                "Leader Slots",
+                "Skipped Slots",
+                "Skipped Slot Percentage",
+                "Delinquent",
+            ))
+            .bold()
+        )?;
+        for validator in &self.validators {
+            writeln!(
+                f,
+                "  {:<44}  {:>15}  {:>15}  {:>22.2}%  {:>10}",
+                validator.identity_pubkey,
+                validator.leader_slots,
+                validator.skipped_slots,
+                validator.skip_rate,
+                match validator.delinquent {
+                    Some(true) => "yes",
+                    Some(false) => "no",
+                    None => "unknown",
+                },
+            )?;
+        }
+        writeln!(f)?;
+        let total_skip_rate = if self.total_leader_slots > 0 {
+            100. * self.total_skipped_slots as f64 / self.total_leader_slots as f64
+        } else {
+            0.
+        };
+        writeln!(
+            f,
+            "  {:<44}  {:>15}  {:>15}  {:>22.2}%",
+            "Total:", self.total_leader_slots, self.total_skipped_slots, total_skip_rate,
+        )?;
+        let epochs = if self.first_epoch == self.last_epoch {
+            format!("epoch {}", self.first_epoch)
+        } else {
+            format!("epochs {} to {}", self.first_epoch, self.last_epoch)
+        };
+        writeln!(
+            f,
+            "  (using data from {}: slots {} to {})",
+            epochs, self.first_slot, self.last_slot
+        )
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliValidatorDelinquencyReportEntry {
+    pub identity_pubkey: String,
+    pub leader_slots: u64,
+    pub blocks_produced: u64,
+    pub skipped_slots: u64,
+    pub skip_rate: f64,
+    /// Whether the validator is delinquent now, or None if it has no vote account
+    pub delinquent: Option<bool>,
+}
+
 #[derive(Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliNonceAccount {
@@ -1651,6 +1733,189 @@ impl fmt::Display for CliSignature {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliBalance {
+    pub lamports: u64,
+    #[serde(skip)]
+    pub config: BuildBalanceMessageConfig,
+}
+
+impl QuietDisplay for CliBalance {}
+impl VerboseDisplay for CliBalance {}
+
+impl fmt::Display for CliBalance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let balance_message = build_balance_message_with_config(self.lamports, &self.config);
+        write!(f, "{}", balance_message)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliAddress {
+    pub address: String,
+}
+
+impl QuietDisplay for CliAddress {}
+impl VerboseDisplay for CliAddress {}
+
+impl fmt::Display for CliAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.address)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliNonce {
+    /// The stored durable nonce, or None if the nonce account is uninitialized
+    pub nonce: Option<String>,
+}
+
+impl QuietDisplay for CliNonce {}
+impl VerboseDisplay for CliNonce {}
+
+impl fmt::Display for CliNonce {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.nonce {
+            Some(nonce) => write!(f, "{}", nonce),
+            None => write!(f, "Nonce account is uninitialized"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliEpoch {
+    pub epoch: Epoch,
+}
+
+impl QuietDisplay for CliEpoch {}
+impl VerboseDisplay for CliEpoch {}
+
+impl fmt::Display for CliEpoch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.epoch)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSlot {
+    pub slot: Slot,
+}
+
+impl QuietDisplay for CliSlot {}
+impl VerboseDisplay for CliSlot {}
+
+impl fmt::Display for CliSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.slot)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliBlockHeight {
+    pub block_height: u64,
+}
+
+impl QuietDisplay for CliBlockHeight {}
+impl VerboseDisplay for CliBlockHeight {}
+
+impl fmt::Display for CliBlockHeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.block_height)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliFirstAvailableBlock {
+    pub first_available_block: Slot,
+}
+
+impl QuietDisplay for CliFirstAvailableBlock {}
+impl VerboseDisplay for CliFirstAvailableBlock {}
+
+impl fmt::Display for CliFirstAvailableBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.first_available_block)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliGenesisHash {
+    pub genesis_hash: String,
+}
+
+impl QuietDisplay for CliGenesisHash {}
+impl VerboseDisplay for CliGenesisHash {}
+
+impl fmt::Display for CliGenesisHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.genesis_hash)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliTransactionCount {
+    pub transaction_count: u64,
+}
+
+impl QuietDisplay for CliTransactionCount {}
+impl VerboseDisplay for CliTransactionCount {}
+
+impl fmt::Display for CliTransactionCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.transaction_count)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliTotalSupply {
+    pub total: u64,
+}
+
+impl QuietDisplay for CliTotalSupply {}
+impl VerboseDisplay for CliTotalSupply {}
+
+impl fmt::Display for CliTotalSupply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} SOL", lamports_to_sol(self.total))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliClusterVersion {
+    pub solana_core: String,
+    pub feature_set: Option<u32>,
+}
+
+impl QuietDisplay for CliClusterVersion {}
+impl VerboseDisplay for CliClusterVersion {
+    fn write_str(&self, w: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(w, "{}", self.solana_core)
+    }
+}
+
+impl fmt::Display for CliClusterVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Display just the semver if possible
+        let version = self
+            .solana_core
+            .split_whitespace()
+            .next()
+            .unwrap_or(&self.solana_core);
+        write!(f, "{}", version)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliAccountBalances {
@@ -2088,6 +2353,15 @@ impl fmt::Display for CliUpgradeableBuffers {
                 )
             )?;
         }
+        if !self.buffers.is_empty() {
+            let total_lamports: u64 = self.buffers.iter().map(|buffer| buffer.lamports).sum();
+            writeln!(f)?;
+            writeln_name_value(
+                f,
+                "Total balance:",
+                &build_balance_message(total_lamports, self.use_lamports_unit, true),
+            )?;
+        }
         Ok(())
     }
 }
@@ -2216,11 +2490,13 @@ pub enum CliSignatureVerificationStatus {
 }
 
 impl CliSignatureVerificationStatus {
-    pub fn verify_transaction(tx: &Transaction) -> Vec<Self> {
-        tx.verify_with_results()
+    pub fn verify_transaction(tx: &VersionedTransaction) -> Vec<Self> {
+        let message_bytes = tx.message.serialize();
+        tx.signatures
             .iter()
-            .zip(&tx.signatures)
-            .map(|(stat, sig)| match stat {
+            .zip(tx.message.static_account_keys_iter())
+            .map(|(sig, pubkey)| (sig, sig.verify(pubkey.as_ref(), &message_bytes)))
+            .map(|(sig, stat)| match stat {
                 true => CliSignatureVerificationStatus::Pass,
                 false if sig == &Signature::default() => CliSignatureVerificationStatus::None,
                 false => CliSignatureVerificationStatus::Fail,
@@ -2351,7 +2627,7 @@ pub struct CliTransaction {
     #[serde(skip_serializing)]
     pub slot: Option<Slot>,
     #[serde(skip_serializing)]
-    pub decoded_transaction: Transaction,
+    pub decoded_transaction: VersionedTransaction,
     #[serde(skip_serializing)]
     pub prefix: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -4,8 +4,14 @@ use {
     console::style,
     indicatif::{ProgressBar, ProgressStyle},
     solana_sdk::{
-        clock::UnixTimestamp, hash::Hash, message::Message, native_token::lamports_to_sol,
-        program_utils::limited_deserialize, pubkey::Pubkey, stake, transaction::Transaction,
+        clock::UnixTimestamp,
+        hash::Hash,
+        message::{LoadedAddresses, LoadedMessage, Message, VersionedMessage},
+        native_token::lamports_to_sol,
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        stake,
+        transaction::VersionedTransaction,
     },
     solana_transaction_status::UiTransactionStatusMeta,
     spl_memo::id as spl_memo_id,
@@ -157,9 +163,48 @@ fn format_account_mode(message: &Message, index: usize) -> String {
     )
 }
 
+fn format_loaded_account_mode(message: &LoadedMessage, index: usize) -> String {
+    format!(
+        "{}r{}{}",
+        if index < usize::from(message.message.header.num_required_signatures) {
+            "s"
+        } else {
+            "-"
+        },
+        if message.is_writable(index, /*demote_program_write_locks=*/ true) {
+            "w"
+        } else {
+            "-"
+        },
+        if message.is_key_called_as_program(index) {
+            "x"
+        } else {
+            "-"
+        },
+    )
+}
+
+/// The addresses a v0 transaction loaded from its lookup tables, as recorded in its status
+fn loaded_addresses(transaction_status: &Option<UiTransactionStatusMeta>) -> LoadedAddresses {
+    let parse_addresses = |addresses: &[String]| {
+        addresses
+            .iter()
+            .filter_map(|key| key.parse().ok())
+            .collect()
+    };
+    transaction_status
+        .as_ref()
+        .and_then(|transaction_status| transaction_status.loaded_addresses.as_ref())
+        .map(|loaded_addresses| LoadedAddresses {
+            writable: parse_addresses(&loaded_addresses.writable),
+            readonly: parse_addresses(&loaded_addresses.readonly),
+        })
+        .unwrap_or_default()
+}
+
 pub fn write_transaction<W: io::Write>(
     w: &mut W,
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
@@ -177,7 +222,8 @@ pub fn write_transaction<W: io::Write>(
     writeln!(
         w,
         "{}Recent Blockhash: {:?}",
-        prefix, message.recent_blockhash
+        prefix,
+        message.recent_blockhash()
     )?;
     let sigverify_statuses = if let Some(sigverify_status) = sigverify_status {
         sigverify_status
@@ -199,17 +245,39 @@ pub fn write_transaction<W: io::Write>(
             prefix, signature_index, signature, sigverify_status,
         )?;
     }
-    let mut fee_payer_index = None;
-    for (account_index, account) in message.account_keys.iter().enumerate() {
-        if fee_payer_index.is_none() && message.is_non_loader_key(account_index) {
-            fee_payer_index = Some(account_index)
+    let (account_keys, account_modes, fee_payer_index) = match message {
+        VersionedMessage::Legacy(message) => (
+            message.account_keys.clone(),
+            (0..message.account_keys.len())
+                .map(|account_index| format_account_mode(message, account_index))
+                .collect::<Vec<_>>(),
+            (0..message.account_keys.len())
+                .find(|account_index| message.is_non_loader_key(*account_index)),
+        ),
+        VersionedMessage::V0(message) => {
+            // Without a status, the keys the instructions index into can't all be resolved
+            let message = LoadedMessage {
+                message: message.clone(),
+                loaded_addresses: loaded_addresses(transaction_status),
+            };
+            (
+                message.account_keys_iter().copied().collect(),
+                (0..message.account_keys_len())
+                    .map(|account_index| format_loaded_account_mode(&message, account_index))
+                    .collect(),
+                Some(0),
+            )
         }
+    };
+    for (account_index, (account, account_mode)) in
+        account_keys.iter().zip(&account_modes).enumerate()
+    {
         writeln!(
             w,
             "{}Account {}: {} {}{}",
             prefix,
             account_index,
-            format_account_mode(message, account_index),
+            account_mode,
             account,
             if Some(account_index) == fee_payer_index {
                 " (fee payer)"
@@ -218,8 +286,30 @@ pub fn write_transaction<W: io::Write>(
             },
         )?;
     }
-    for (instruction_index, instruction) in message.instructions.iter().enumerate() {
-        let program_pubkey = message.account_keys[instruction.program_id_index as usize];
+    for (lookup_index, lookup) in message
+        .address_table_lookups()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+    {
+        writeln!(
+            w,
+            "{}Address Table Lookup {}: {}",
+            prefix, lookup_index, lookup.account_key
+        )?;
+        writeln!(
+            w,
+            "{}  Writable Indexes: {:?}",
+            prefix, lookup.writable_indexes
+        )?;
+        writeln!(
+            w,
+            "{}  Readonly Indexes: {:?}",
+            prefix, lookup.readonly_indexes
+        )?;
+    }
+    for (instruction_index, instruction) in message.instructions().iter().enumerate() {
+        let program_pubkey = account_keys[instruction.program_id_index as usize];
         writeln!(w, "{}Instruction {}", prefix, instruction_index)?;
         writeln!(
             w,
@@ -227,12 +317,18 @@ pub fn write_transaction<W: io::Write>(
             prefix, program_pubkey, instruction.program_id_index
         )?;
         for (account_index, account) in instruction.accounts.iter().enumerate() {
-            let account_pubkey = message.account_keys[*account as usize];
-            writeln!(
-                w,
-                "{}  Account {}: {} ({})",
-                prefix, account_index, account_pubkey, account
-            )?;
+            match account_keys.get(*account as usize) {
+                Some(account_pubkey) => writeln!(
+                    w,
+                    "{}  Account {}: {} ({})",
+                    prefix, account_index, account_pubkey, account
+                )?,
+                None => writeln!(
+                    w,
+                    "{}  Account {}: unresolved lookup table address ({})",
+                    prefix, account_index, account
+                )?,
+            }
         }
 
         let mut raw = true;
@@ -361,7 +457,7 @@ pub fn write_transaction<W: io::Write>(
 }
 
 pub fn println_transaction(
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
@@ -386,7 +482,7 @@ pub fn println_transaction(
 
 pub fn writeln_transaction(
     f: &mut dyn fmt::Write,
-    transaction: &Transaction,
+    transaction: &VersionedTransaction,
     transaction_status: &Option<UiTransactionStatusMeta>,
     prefix: &str,
     sigverify_status: Option<&[CliSignatureVerificationStatus]>,
@@ -429,7 +525,14 @@ pub fn unix_timestamp_to_string(unix_timestamp: UnixTimestamp) -> String {
 #[cfg(test)]
 mod test {
     use super::*;
-    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::{
+        instruction::CompiledInstruction,
+        message::{v0, MessageHeader},
+        pubkey::Pubkey,
+        signature::Signature,
+        system_program,
+    };
+    use solana_transaction_status::TransactionStatusMeta;
 
     #[test]
     fn test_format_labeled_address() {
@@ -453,4 +556,54 @@ mod test {
             "abcdefghijklmnopqrstuvwxyz12345 (1111..1111)"
         );
     }
+
+    #[test]
+    fn test_write_v0_transaction() {
+        let payer = Pubkey::new_unique();
+        let lookup_table = Pubkey::new_unique();
+        let loaded_address = Pubkey::new_unique();
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![payer, system_program::id()],
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2])],
+                address_table_lookups: vec![v0::MessageAddressTableLookup {
+                    account_key: lookup_table,
+                    writable_indexes: vec![3],
+                    readonly_indexes: vec![],
+                }],
+                ..v0::Message::default()
+            }),
+        };
+        let transaction_status = Some(
+            TransactionStatusMeta {
+                loaded_addresses: LoadedAddresses {
+                    writable: vec![loaded_address],
+                    readonly: vec![],
+                },
+                ..TransactionStatusMeta::default()
+            }
+            .into(),
+        );
+
+        let mut w = Vec::new();
+        write_transaction(&mut w, &transaction, &transaction_status, "", None, None).unwrap();
+        let output = String::from_utf8(w).unwrap();
+        assert!(output.contains(&format!("Account 0: srw- {} (fee payer)", payer)));
+        assert!(output.contains(&format!("Account 1: -r-x {}", system_program::id())));
+        assert!(output.contains(&format!("Account 2: -rw- {}", loaded_address)));
+        assert!(output.contains(&format!("Address Table Lookup 0: {}", lookup_table)));
+        assert!(output.contains(&format!("  Account 1: {} (2)", loaded_address)));
+
+        // The loaded addresses are unknown without a status
+        let mut w = Vec::new();
+        write_transaction(&mut w, &transaction, &None, "", None, None).unwrap();
+        let output = String::from_utf8(w).unwrap();
+        assert!(output.contains("  Account 1: unresolved lookup table address (2)"));
+    }
 }
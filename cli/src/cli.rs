@@ -8,7 +8,7 @@ use num_traits::FromPrimitive;
 use serde_json::{self, Value};
 use solana_clap_utils::{self, input_parsers::*, input_validators::*, keypair::*};
 use solana_cli_output::{
-    display::println_name_value, CliSignature, CliValidatorsSortOrder, OutputFormat,
+    display::println_name_value, CliAddress, CliSignature, CliValidatorsSortOrder, OutputFormat,
 };
 use solana_client::{
     blockhash_query::BlockhashQuery,
@@ -24,10 +24,11 @@ use solana_sdk::{
     decode_error::DecodeError,
     hash::Hash,
     instruction::InstructionError,
+    offchain_message::OffchainMessage,
     pubkey::Pubkey,
     signature::{Signature, Signer, SignerError},
     stake::{instruction::LockupArgs, state::Lockup},
-    transaction::{Transaction, TransactionError},
+    transaction::{Transaction, TransactionError, VersionedTransaction},
 };
 use solana_vote_program::vote_state::VoteAuthorize;
 use std::{collections::HashMap, error, io::stdout, str::FromStr, sync::Arc, time::Duration};
@@ -106,6 +107,11 @@ pub enum CliCommand {
         number_validators: bool,
         keep_unstaked_delinquents: bool,
         delinquent_slot_distance: Option<Slot>,
+        epoch: Option<Epoch>,
+    },
+    ShowValidatorsDelinquencyReport {
+        epoch: Option<Epoch>,
+        window_epochs: u64,
     },
     Supply {
         print_accounts: bool,
@@ -117,6 +123,8 @@ pub enum CliCommand {
         until: Option<Signature>,
         limit: usize,
         show_transactions: bool,
+        cache_dir: Option<String>,
+        csv_file: Option<String>,
     },
     WaitForMaxStake {
         max_stake_percent: f32,
@@ -141,6 +149,15 @@ pub enum CliCommand {
         nonce_authority: SignerIndex,
         memo: Option<String>,
     },
+    NewNonces {
+        nonce_accounts: Vec<Pubkey>,
+        nonce_authority: SignerIndex,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        memo: Option<String>,
+        fee_payer: SignerIndex,
+    },
     ShowNonceAccount {
         nonce_account_pubkey: Pubkey,
         use_lamports_unit: bool,
@@ -178,6 +195,23 @@ pub enum CliCommand {
         fee_payer: SignerIndex,
         from: SignerIndex,
     },
+    CreateStakeAccounts {
+        base: SignerIndex,
+        start_index: usize,
+        num_accounts: usize,
+        staker: Option<Pubkey>,
+        withdrawer: Option<Pubkey>,
+        lockup: Lockup,
+        lamports: u64,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        memo: Option<String>,
+        fee_payer: SignerIndex,
+        from: SignerIndex,
+    },
     DeactivateStake {
         stake_account_pubkey: Pubkey,
         stake_authority: SignerIndex,
@@ -203,6 +237,36 @@ pub enum CliCommand {
         memo: Option<String>,
         fee_payer: SignerIndex,
     },
+    DelegateStakeMany {
+        base_pubkey: Pubkey,
+        start_index: usize,
+        num_accounts: usize,
+        vote_account_pubkey: Pubkey,
+        stake_authority: SignerIndex,
+        force: bool,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        memo: Option<String>,
+        fee_payer: SignerIndex,
+    },
+    RedelegateStake {
+        stake_account_pubkey: Pubkey,
+        vote_account_pubkey: Pubkey,
+        stake_authority: SignerIndex,
+        force: bool,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        memo: Option<String>,
+        redelegation_stake_account: SignerIndex,
+        seed: Option<String>,
+        fee_payer: SignerIndex,
+    },
     SplitStake {
         stake_account_pubkey: Pubkey,
         stake_authority: SignerIndex,
@@ -217,6 +281,21 @@ pub enum CliCommand {
         lamports: u64,
         fee_payer: SignerIndex,
     },
+    SplitStakeMany {
+        stake_account_pubkey: Pubkey,
+        stake_authority: SignerIndex,
+        base: SignerIndex,
+        start_index: usize,
+        num_accounts: usize,
+        lamports: u64,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        memo: Option<String>,
+        fee_payer: SignerIndex,
+    },
     MergeStake {
         stake_account_pubkey: Pubkey,
         source_stake_account_pubkey: Pubkey,
@@ -306,7 +385,13 @@ pub enum CliCommand {
         destination_account_pubkey: Pubkey,
         withdraw_authority: SignerIndex,
         withdraw_amount: SpendAmount,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
         memo: Option<String>,
+        fee_payer: SignerIndex,
     },
     CloseVoteAccount {
         vote_account_pubkey: Pubkey,
@@ -318,7 +403,13 @@ pub enum CliCommand {
         vote_account_pubkey: Pubkey,
         new_authorized_pubkey: Pubkey,
         vote_authorize: VoteAuthorize,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
         memo: Option<String>,
+        fee_payer: SignerIndex,
         authorized: SignerIndex,
         new_authorized: Option<SignerIndex>,
     },
@@ -350,7 +441,7 @@ pub enum CliCommand {
         seed: String,
         program_id: Pubkey,
     },
-    DecodeTransaction(Transaction),
+    DecodeTransaction(VersionedTransaction),
     ResolveSigner(Option<String>),
     ShowAccount {
         pubkey: Pubkey,
@@ -373,6 +464,14 @@ pub enum CliCommand {
         derived_address_seed: Option<String>,
         derived_address_program_id: Option<Pubkey>,
     },
+    SignOffchainMessage {
+        message: OffchainMessage,
+    },
+    VerifyOffchainSignature {
+        signer_pubkey: Option<Pubkey>,
+        signature: Signature,
+        message: OffchainMessage,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -403,6 +502,8 @@ pub enum CliError {
     RpcRequestError(String),
     #[error("Keypair file not found: {0}")]
     KeypairFileNotFound(String),
+    #[error("Invalid signature")]
+    InvalidSignature,
 }
 
 impl From<Box<dyn error::Error>> for CliError {
@@ -420,6 +521,47 @@ impl From<nonce_utils::Error> for CliError {
     }
 }
 
+/// Process exit codes, so that scripts can tell categories of failure apart without parsing
+/// error messages
+pub mod exit_code {
+    pub const GENERAL_ERROR: i32 = 1;
+    pub const BAD_PARAMETER: i32 = 2;
+    pub const INSUFFICIENT_FUNDS: i32 = 3;
+    pub const RPC_ERROR: i32 = 4;
+    pub const INVALID_NONCE: i32 = 5;
+    pub const INVALID_SIGNATURE: i32 = 6;
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::BadParameter(_)
+            | Self::CommandNotRecognized(_)
+            | Self::KeypairFileNotFound(_) => exit_code::BAD_PARAMETER,
+            Self::InsufficientFundsForFee(..)
+            | Self::InsufficientFundsForSpend(..)
+            | Self::InsufficientFundsForSpendAndFee(..) => exit_code::INSUFFICIENT_FUNDS,
+            Self::ClientError(_) | Self::RpcRequestError(_) => exit_code::RPC_ERROR,
+            Self::InvalidNonce(_) => exit_code::INVALID_NONCE,
+            Self::InvalidSignature => exit_code::INVALID_SIGNATURE,
+            Self::DynamicProgramError(_) => exit_code::GENERAL_ERROR,
+        }
+    }
+}
+
+/// Returns the process exit code for an error returned by `parse_command` or `process_command`
+pub fn error_exit_code(error: &(dyn error::Error + 'static)) -> i32 {
+    if let Some(cli_error) = error.downcast_ref::<CliError>() {
+        cli_error.exit_code()
+    } else if error.is::<ClientError>() {
+        exit_code::RPC_ERROR
+    } else if error.is::<nonce_utils::Error>() {
+        exit_code::INVALID_NONCE
+    } else {
+        exit_code::GENERAL_ERROR
+    }
+}
+
 pub enum SettingType {
     Explicit,
     Computed,
@@ -697,6 +839,7 @@ pub fn parse_command(
         }
         ("nonce", Some(matches)) => parse_get_nonce(matches, wallet_manager),
         ("new-nonce", Some(matches)) => parse_new_nonce(matches, default_signer, wallet_manager),
+        ("new-nonces", Some(matches)) => parse_new_nonces(matches, default_signer, wallet_manager),
         ("nonce-account", Some(matches)) => parse_show_nonce_account(matches, wallet_manager),
         ("withdraw-from-nonce-account", Some(matches)) => {
             parse_withdraw_from_nonce_account(matches, default_signer, wallet_manager)
@@ -737,18 +880,30 @@ pub fn parse_command(
         ("create-stake-account-checked", Some(matches)) => {
             parse_create_stake_account(matches, default_signer, wallet_manager, CHECKED)
         }
+        ("create-stake-accounts", Some(matches)) => {
+            parse_create_stake_accounts(matches, default_signer, wallet_manager)
+        }
         ("delegate-stake", Some(matches)) => {
             parse_stake_delegate_stake(matches, default_signer, wallet_manager)
         }
+        ("delegate-stake-many", Some(matches)) => {
+            parse_delegate_stake_many(matches, default_signer, wallet_manager)
+        }
         ("withdraw-stake", Some(matches)) => {
             parse_stake_withdraw_stake(matches, default_signer, wallet_manager)
         }
         ("deactivate-stake", Some(matches)) => {
             parse_stake_deactivate_stake(matches, default_signer, wallet_manager)
         }
+        ("redelegate-stake", Some(matches)) => {
+            parse_stake_redelegate_stake(matches, default_signer, wallet_manager)
+        }
         ("split-stake", Some(matches)) => {
             parse_split_stake(matches, default_signer, wallet_manager)
         }
+        ("split-stake-many", Some(matches)) => {
+            parse_split_stake_many(matches, default_signer, wallet_manager)
+        }
         ("merge-stake", Some(matches)) => {
             parse_merge_stake(matches, default_signer, wallet_manager)
         }
@@ -846,6 +1001,12 @@ pub fn parse_command(
             })
         }
         ("transfer", Some(matches)) => parse_transfer(matches, default_signer, wallet_manager),
+        ("sign-offchain-message", Some(matches)) => {
+            parse_sign_offchain_message(matches, default_signer, wallet_manager)
+        }
+        ("verify-offchain-signature", Some(matches)) => {
+            parse_verify_offchain_signature(matches, default_signer, wallet_manager)
+        }
         //
         ("", None) => {
             eprintln!("{}", matches.usage());
@@ -889,7 +1050,12 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
     match &config.command {
         // Cluster Query Commands
         // Get address of this client
-        CliCommand::Address => Ok(format!("{}", config.pubkey()?)),
+        CliCommand::Address => {
+            let address = CliAddress {
+                address: config.pubkey()?.to_string(),
+            };
+            Ok(config.output_format.formatted_string(&address))
+        }
         // Return software version of solana-cli and cluster entrypoint node
         CliCommand::Catchup {
             node_pubkey,
@@ -917,12 +1083,12 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
         CliCommand::Feature(feature_subcommand) => {
             process_feature_subcommand(&rpc_client, config, feature_subcommand)
         }
-        CliCommand::FirstAvailableBlock => process_first_available_block(&rpc_client),
+        CliCommand::FirstAvailableBlock => process_first_available_block(&rpc_client, config),
         CliCommand::GetBlock { slot } => process_get_block(&rpc_client, config, *slot),
         CliCommand::GetBlockTime { slot } => process_get_block_time(&rpc_client, config, *slot),
         CliCommand::GetEpoch => process_get_epoch(&rpc_client, config),
         CliCommand::GetEpochInfo => process_get_epoch_info(&rpc_client, config),
-        CliCommand::GetGenesisHash => process_get_genesis_hash(&rpc_client),
+        CliCommand::GetGenesisHash => process_get_genesis_hash(&rpc_client, config),
         CliCommand::GetSlot => process_get_slot(&rpc_client, config),
         CliCommand::GetBlockHeight => process_get_block_height(&rpc_client, config),
         CliCommand::LargestAccounts { filter } => {
@@ -981,6 +1147,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             number_validators,
             keep_unstaked_delinquents,
             delinquent_slot_distance,
+            epoch,
         } => process_show_validators(
             &rpc_client,
             config,
@@ -990,7 +1157,14 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *number_validators,
             *keep_unstaked_delinquents,
             *delinquent_slot_distance,
+            *epoch,
         ),
+        CliCommand::ShowValidatorsDelinquencyReport {
+            epoch,
+            window_epochs,
+        } => {
+            process_show_validators_delinquency_report(&rpc_client, config, *epoch, *window_epochs)
+        }
         CliCommand::Supply { print_accounts } => {
             process_supply(&rpc_client, config, *print_accounts)
         }
@@ -1001,6 +1175,8 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             until,
             limit,
             show_transactions,
+            cache_dir,
+            csv_file,
         } => process_transaction_history(
             &rpc_client,
             config,
@@ -1009,6 +1185,8 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *until,
             *limit,
             *show_transactions,
+            cache_dir.as_ref(),
+            csv_file.as_ref(),
         ),
 
         // Nonce Commands
@@ -1059,6 +1237,26 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *nonce_authority,
             memo.as_ref(),
         ),
+        // Advance several nonce accounts in one transaction
+        CliCommand::NewNonces {
+            nonce_accounts,
+            nonce_authority,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            memo,
+            fee_payer,
+        } => process_new_nonces(
+            &rpc_client,
+            config,
+            nonce_accounts,
+            *nonce_authority,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            memo.as_ref(),
+            *fee_payer,
+        ),
         // Show the contents of a nonce account
         CliCommand::ShowNonceAccount {
             nonce_account_pubkey,
@@ -1144,6 +1342,41 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *fee_payer,
             *from,
         ),
+        CliCommand::CreateStakeAccounts {
+            base,
+            start_index,
+            num_accounts,
+            staker,
+            withdrawer,
+            lockup,
+            lamports,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            memo,
+            fee_payer,
+            from,
+        } => process_create_stake_accounts(
+            &rpc_client,
+            config,
+            *base,
+            *start_index,
+            *num_accounts,
+            staker,
+            withdrawer,
+            lockup,
+            *lamports,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            nonce_account.as_ref(),
+            *nonce_authority,
+            memo.as_ref(),
+            *fee_payer,
+            *from,
+        ),
         CliCommand::DeactivateStake {
             stake_account_pubkey,
             stake_authority,
@@ -1196,6 +1429,68 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             memo.as_ref(),
             *fee_payer,
         ),
+        CliCommand::DelegateStakeMany {
+            base_pubkey,
+            start_index,
+            num_accounts,
+            vote_account_pubkey,
+            stake_authority,
+            force,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            memo,
+            fee_payer,
+        } => process_delegate_stake_many(
+            &rpc_client,
+            config,
+            base_pubkey,
+            *start_index,
+            *num_accounts,
+            vote_account_pubkey,
+            *stake_authority,
+            *force,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            nonce_account.as_ref(),
+            *nonce_authority,
+            memo.as_ref(),
+            *fee_payer,
+        ),
+        CliCommand::RedelegateStake {
+            stake_account_pubkey,
+            vote_account_pubkey,
+            stake_authority,
+            force,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            memo,
+            redelegation_stake_account,
+            seed,
+            fee_payer,
+        } => process_redelegate_stake(
+            &rpc_client,
+            config,
+            stake_account_pubkey,
+            vote_account_pubkey,
+            *stake_authority,
+            *force,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            *nonce_account,
+            *nonce_authority,
+            memo.as_ref(),
+            *redelegation_stake_account,
+            seed,
+            *fee_payer,
+        ),
         CliCommand::SplitStake {
             stake_account_pubkey,
             stake_authority,
@@ -1225,6 +1520,37 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *lamports,
             *fee_payer,
         ),
+        CliCommand::SplitStakeMany {
+            stake_account_pubkey,
+            stake_authority,
+            base,
+            start_index,
+            num_accounts,
+            lamports,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            memo,
+            fee_payer,
+        } => process_split_stake_many(
+            &rpc_client,
+            config,
+            stake_account_pubkey,
+            *stake_authority,
+            *base,
+            *start_index,
+            *num_accounts,
+            *lamports,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            nonce_account.as_ref(),
+            *nonce_authority,
+            memo.as_ref(),
+            *fee_payer,
+        ),
         CliCommand::MergeStake {
             stake_account_pubkey,
             source_stake_account_pubkey,
@@ -1408,7 +1734,13 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             withdraw_authority,
             withdraw_amount,
             destination_account_pubkey,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
             memo,
+            fee_payer,
         } => process_withdraw_from_vote_account(
             &rpc_client,
             config,
@@ -1416,7 +1748,13 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *withdraw_authority,
             *withdraw_amount,
             destination_account_pubkey,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            nonce_account.as_ref(),
+            *nonce_authority,
             memo.as_ref(),
+            *fee_payer,
         ),
         CliCommand::CloseVoteAccount {
             vote_account_pubkey,
@@ -1435,7 +1773,13 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             vote_account_pubkey,
             new_authorized_pubkey,
             vote_authorize,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
             memo,
+            fee_payer,
             authorized,
             new_authorized,
         } => process_vote_authorize(
@@ -1446,7 +1790,13 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *vote_authorize,
             *authorized,
             *new_authorized,
+            *sign_only,
+            *dump_transaction_message,
+            blockhash_query,
+            *nonce_account,
+            *nonce_authority,
             memo.as_ref(),
+            *fee_payer,
         ),
         CliCommand::VoteUpdateValidator {
             vote_account_pubkey,
@@ -1536,6 +1886,16 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             derived_address_seed.clone(),
             derived_address_program_id.as_ref(),
         ),
+        // Sign an off-chain message
+        CliCommand::SignOffchainMessage { message } => {
+            process_sign_offchain_message(config, message)
+        }
+        // Verify an off-chain message signature
+        CliCommand::VerifyOffchainSignature {
+            signer_pubkey,
+            signature,
+            message,
+        } => process_verify_offchain_signature(config, signer_pubkey, signature, message),
     }
 }
 
@@ -1611,6 +1971,7 @@ mod tests {
     use serde_json::{json, Value};
     use solana_client::{
         blockhash_query,
+        client_error::ClientErrorKind,
         mock_sender::SIGNATURE,
         rpc_request::RpcRequest,
         rpc_response::{Response, RpcResponseContext},
@@ -1920,6 +2281,46 @@ mod tests {
                 signers: vec![],
             }
         );
+
+        // Test SignOffchainMessage Subcommand
+        let test_sign_offchain_message = test_commands.clone().get_matches_from(vec![
+            "test",
+            "sign-offchain-message",
+            "Test Message",
+        ]);
+        let message = OffchainMessage::new(0, b"Test Message").unwrap();
+        assert_eq!(
+            parse_command(&test_sign_offchain_message, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::SignOffchainMessage {
+                    message: message.clone()
+                },
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+            }
+        );
+
+        // Test VerifyOffchainSignature Subcommand
+        let signature = Signature::new(&[1; 64]);
+        let signature_string = signature.to_string();
+        let test_verify_offchain_signature = test_commands.clone().get_matches_from(vec![
+            "test",
+            "verify-offchain-signature",
+            "Test Message",
+            &signature_string,
+            "--signer",
+            &pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&test_verify_offchain_signature, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VerifyOffchainSignature {
+                    signer_pubkey: Some(pubkey),
+                    signature,
+                    message,
+                },
+                signers: vec![],
+            }
+        );
     }
 
     #[test]
@@ -1938,6 +2339,28 @@ mod tests {
         config.command = CliCommand::Address;
         assert_eq!(process_command(&config).unwrap(), pubkey);
 
+        let message = OffchainMessage::new(0, b"Test Message").unwrap();
+        config.command = CliCommand::SignOffchainMessage {
+            message: message.clone(),
+        };
+        config.output_format = OutputFormat::JsonCompact;
+        let output: Value = serde_json::from_str(&process_command(&config).unwrap()).unwrap();
+        let signature: Signature = output["signature"].as_str().unwrap().parse().unwrap();
+        config.output_format = OutputFormat::Display;
+        config.command = CliCommand::VerifyOffchainSignature {
+            signer_pubkey: None,
+            signature,
+            message: message.clone(),
+        };
+        assert_eq!(process_command(&config).unwrap(), "Signature is valid");
+        config.command = CliCommand::VerifyOffchainSignature {
+            signer_pubkey: Some(solana_sdk::pubkey::new_rand()),
+            signature,
+            message,
+        };
+        let err = process_command(&config).unwrap_err();
+        assert_eq!(error_exit_code(err.as_ref()), exit_code::INVALID_SIGNATURE);
+
         config.command = CliCommand::Balance {
             pubkey: None,
             use_lamports_unit: true,
@@ -1950,6 +2373,15 @@ mod tests {
         };
         assert_eq!(process_command(&config).unwrap(), "0.00000005 SOL");
 
+        config.output_format = OutputFormat::JsonCompact;
+        assert_eq!(process_command(&config).unwrap(), r#"{"lamports":50}"#);
+        config.command = CliCommand::Address;
+        assert_eq!(
+            process_command(&config).unwrap(),
+            format!(r#"{{"address":"{}"}}"#, pubkey)
+        );
+        config.output_format = OutputFormat::Display;
+
         let good_signature = Signature::new(&bs58::decode(SIGNATURE).into_vec().unwrap());
         config.command = CliCommand::Confirm(good_signature);
         assert_eq!(
@@ -1998,7 +2430,13 @@ mod tests {
             vote_account_pubkey: bob_pubkey,
             new_authorized_pubkey,
             vote_authorize: VoteAuthorize::Withdrawer,
+            sign_only: false,
+            dump_transaction_message: false,
+            blockhash_query: BlockhashQuery::default(),
+            nonce_account: None,
+            nonce_authority: 0,
             memo: None,
+            fee_payer: 0,
             authorized: 0,
             new_authorized: None,
         };
@@ -2126,6 +2564,16 @@ mod tests {
         config.command = CliCommand::GetTransactionCount;
         assert_eq!(process_command(&config).unwrap(), "1234");
 
+        config.output_format = OutputFormat::JsonCompact;
+        config.command = CliCommand::GetSlot;
+        assert_eq!(process_command(&config).unwrap(), r#"{"slot":0}"#);
+        config.command = CliCommand::GetTransactionCount;
+        assert_eq!(
+            process_command(&config).unwrap(),
+            r#"{"transactionCount":1234}"#
+        );
+        config.output_format = OutputFormat::Display;
+
         // CreateAddressWithSeed
         let from_pubkey = solana_sdk::pubkey::new_rand();
         config.signers = vec![];
@@ -2196,7 +2644,13 @@ mod tests {
             vote_account_pubkey: bob_pubkey,
             new_authorized_pubkey: bob_pubkey,
             vote_authorize: VoteAuthorize::Voter,
+            sign_only: false,
+            dump_transaction_message: false,
+            blockhash_query: BlockhashQuery::default(),
+            nonce_account: None,
+            nonce_authority: 0,
             memo: None,
+            fee_payer: 0,
             authorized: 0,
             new_authorized: None,
         };
@@ -2217,6 +2671,22 @@ mod tests {
         assert!(process_command(&config).is_err());
     }
 
+    #[test]
+    fn test_error_exit_code() {
+        let err: Box<dyn error::Error> = CliError::BadParameter("foo".to_string()).into();
+        assert_eq!(error_exit_code(err.as_ref()), exit_code::BAD_PARAMETER);
+        let err: Box<dyn error::Error> =
+            CliError::InsufficientFundsForFee(1.0, Pubkey::default()).into();
+        assert_eq!(error_exit_code(err.as_ref()), exit_code::INSUFFICIENT_FUNDS);
+        let err: Box<dyn error::Error> = CliError::RpcRequestError("foo".to_string()).into();
+        assert_eq!(error_exit_code(err.as_ref()), exit_code::RPC_ERROR);
+        let err: Box<dyn error::Error> =
+            ClientError::from(ClientErrorKind::Custom("foo".to_string())).into();
+        assert_eq!(error_exit_code(err.as_ref()), exit_code::RPC_ERROR);
+        let err: Box<dyn error::Error> = "foo".into();
+        assert_eq!(error_exit_code(err.as_ref()), exit_code::GENERAL_ERROR);
+    }
+
     #[test]
     fn test_cli_deploy() {
         solana_logger::setup();
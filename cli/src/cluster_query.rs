@@ -23,13 +23,17 @@ use solana_client::{
     pubsub_client::PubsubClient,
     rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
     rpc_config::{
-        RpcAccountInfoConfig, RpcBlockConfig, RpcGetVoteAccountsConfig, RpcLargestAccountsConfig,
+        RpcAccountInfoConfig, RpcBlockConfig, RpcBlockProductionConfig,
+        RpcBlockProductionConfigRange, RpcGetVoteAccountsConfig, RpcLargestAccountsConfig,
         RpcLargestAccountsFilter, RpcProgramAccountsConfig, RpcTransactionConfig,
         RpcTransactionLogsConfig, RpcTransactionLogsFilter,
     },
     rpc_filter,
-    rpc_request::DELINQUENT_VALIDATOR_SLOT_DISTANCE,
-    rpc_response::SlotInfo,
+    rpc_request::{
+        DELINQUENT_VALIDATOR_SLOT_DISTANCE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
+        MAX_MULTIPLE_ACCOUNTS,
+    },
+    rpc_response::{RpcVoteAccountStatus, SlotInfo},
 };
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
@@ -40,7 +44,6 @@ use solana_sdk::{
     epoch_schedule::Epoch,
     hash::Hash,
     message::Message,
-    native_token::lamports_to_sol,
     nonce::State as NonceState,
     pubkey::{self, Pubkey},
     rent::Rent,
@@ -52,16 +55,21 @@ use solana_sdk::{
     sysvar::{
         self,
         slot_history::SlotHistory,
-        stake_history::{self},
+        stake_history::{self, StakeHistory},
     },
     timing,
     transaction::Transaction,
 };
-use solana_transaction_status::UiTransactionEncoding;
+use solana_transaction_status::{
+    EncodedConfirmedTransaction, TransactionConfirmationStatus, UiTransactionEncoding,
+};
 use solana_vote_program::vote_state::VoteState;
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
     fmt,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::Path,
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -402,6 +410,32 @@ impl ClusterQuerySubCommands for App<'_, '_> {
                                 DELINQUENT_VALIDATOR_SLOT_DISTANCE,
                                 "]",
                         ))
+                )
+                .arg(
+                    Arg::with_name("epoch")
+                        .long("epoch")
+                        .takes_value(true)
+                        .value_name("EPOCH")
+                        .validator(is_epoch)
+                        .help("Show validators as of this past epoch, reconstructing stake from \
+                               the stake history and credits from the vote accounts. \
+                               [default: current epoch]"),
+                )
+                .arg(
+                    Arg::with_name("delinquency_report")
+                        .long("delinquency-report")
+                        .takes_value(false)
+                        .help("Summarize the skipped slots of each validator identity instead"),
+                )
+                .arg(
+                    Arg::with_name("window_epochs")
+                        .long("window-epochs")
+                        .takes_value(true)
+                        .value_name("NUMBER")
+                        .requires("delinquency_report")
+                        .validator(is_epoch)
+                        .help("Number of epochs, ending at --epoch, covered by the delinquency \
+                               report [default: 1]"),
                 ),
         )
         .subcommand(
@@ -437,6 +471,22 @@ impl ClusterQuerySubCommands for App<'_, '_> {
                         .takes_value(false)
                         .help("Display the full transactions"),
                 )
+                .arg(
+                    Arg::with_name("cache_dir")
+                        .long("cache-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help("Keep a local cache of finalized transactions in this directory, \
+                               so that they are only fetched from the cluster once"),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .help("Write the balance change of ADDRESS in each transaction \
+                               to this CSV file"),
+                )
         )
         .subcommand(
             SubCommand::with_name("wait-for-max-stake")
@@ -639,6 +689,23 @@ pub fn parse_show_validators(matches: &ArgMatches<'_>) -> Result<CliCommandInfo,
     let reverse_sort = matches.is_present("reverse");
     let keep_unstaked_delinquents = matches.is_present("keep_unstaked_delinquents");
     let delinquent_slot_distance = value_of(matches, "delinquent_slot_distance");
+    let epoch = value_of(matches, "epoch");
+
+    if matches.is_present("delinquency_report") {
+        let window_epochs = value_of(matches, "window_epochs").unwrap_or(1);
+        if window_epochs == 0 {
+            return Err(CliError::BadParameter(
+                "--window-epochs must be at least 1".to_string(),
+            ));
+        }
+        return Ok(CliCommandInfo {
+            command: CliCommand::ShowValidatorsDelinquencyReport {
+                epoch,
+                window_epochs,
+            },
+            signers: vec![],
+        });
+    }
 
     let sort_order = match value_t_or_exit!(matches, "sort", String).as_str() {
         "delinquent" => CliValidatorsSortOrder::Delinquent,
@@ -661,6 +728,7 @@ pub fn parse_show_validators(matches: &ArgMatches<'_>) -> Result<CliCommandInfo,
             number_validators,
             keep_unstaked_delinquents,
             delinquent_slot_distance,
+            epoch,
         },
         signers: vec![],
     })
@@ -690,6 +758,8 @@ pub fn parse_transaction_history(
     };
     let limit = value_t_or_exit!(matches, "limit", usize);
     let show_transactions = matches.is_present("show_transactions");
+    let cache_dir = matches.value_of("cache_dir").map(String::from);
+    let csv_file = matches.value_of("csv").map(String::from);
 
     Ok(CliCommandInfo {
         command: CliCommand::TransactionHistory {
@@ -698,6 +768,8 @@ pub fn parse_transaction_history(
             until,
             limit,
             show_transactions,
+            cache_dir,
+            csv_file,
         },
         signers: vec![],
     })
@@ -936,12 +1008,11 @@ pub fn process_cluster_date(rpc_client: &RpcClient, config: &CliConfig) -> Proce
 
 pub fn process_cluster_version(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
     let remote_version = rpc_client.get_version()?;
-
-    if config.verbose {
-        Ok(format!("{:?}", remote_version))
-    } else {
-        Ok(remote_version.to_string())
-    }
+    let cluster_version = CliClusterVersion {
+        solana_core: remote_version.solana_core,
+        feature_set: remote_version.feature_set,
+    };
+    Ok(config.output_format.formatted_string(&cluster_version))
 }
 
 pub fn process_fees(
@@ -980,9 +1051,13 @@ pub fn process_fees(
     Ok(config.output_format.formatted_string(&fees))
 }
 
-pub fn process_first_available_block(rpc_client: &RpcClient) -> ProcessResult {
-    let first_available_block = rpc_client.get_first_available_block()?;
-    Ok(format!("{}", first_available_block))
+pub fn process_first_available_block(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let first_available_block = CliFirstAvailableBlock {
+        first_available_block: rpc_client.get_first_available_block()?,
+    };
+    Ok(config
+        .output_format
+        .formatted_string(&first_available_block))
 }
 
 pub fn parse_leader_schedule(matches: &ArgMatches<'_>) -> Result<CliCommandInfo, CliError> {
@@ -1058,6 +1133,7 @@ pub fn process_get_block(
             RpcBlockConfig {
                 encoding: Some(UiTransactionEncoding::Base64),
                 commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
                 ..RpcBlockConfig::default()
             },
         )?
@@ -1084,9 +1160,11 @@ pub fn process_get_block_time(
     Ok(config.output_format.formatted_string(&block_time))
 }
 
-pub fn process_get_epoch(rpc_client: &RpcClient, _config: &CliConfig) -> ProcessResult {
-    let epoch_info = rpc_client.get_epoch_info()?;
-    Ok(epoch_info.epoch.to_string())
+pub fn process_get_epoch(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let epoch = CliEpoch {
+        epoch: rpc_client.get_epoch_info()?.epoch,
+    };
+    Ok(config.output_format.formatted_string(&epoch))
 }
 
 pub fn process_get_epoch_info(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
@@ -1114,19 +1192,25 @@ pub fn process_get_epoch_info(rpc_client: &RpcClient, config: &CliConfig) -> Pro
     Ok(config.output_format.formatted_string(&epoch_info))
 }
 
-pub fn process_get_genesis_hash(rpc_client: &RpcClient) -> ProcessResult {
-    let genesis_hash = rpc_client.get_genesis_hash()?;
-    Ok(genesis_hash.to_string())
+pub fn process_get_genesis_hash(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let genesis_hash = CliGenesisHash {
+        genesis_hash: rpc_client.get_genesis_hash()?.to_string(),
+    };
+    Ok(config.output_format.formatted_string(&genesis_hash))
 }
 
-pub fn process_get_slot(rpc_client: &RpcClient, _config: &CliConfig) -> ProcessResult {
-    let slot = rpc_client.get_slot()?;
-    Ok(slot.to_string())
+pub fn process_get_slot(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let slot = CliSlot {
+        slot: rpc_client.get_slot()?,
+    };
+    Ok(config.output_format.formatted_string(&slot))
 }
 
-pub fn process_get_block_height(rpc_client: &RpcClient, _config: &CliConfig) -> ProcessResult {
-    let block_height = rpc_client.get_block_height()?;
-    Ok(block_height.to_string())
+pub fn process_get_block_height(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let block_height = CliBlockHeight {
+        block_height: rpc_client.get_block_height()?,
+    };
+    Ok(config.output_format.formatted_string(&block_height))
 }
 
 pub fn parse_show_block_production(matches: &ArgMatches<'_>) -> Result<CliCommandInfo, CliError> {
@@ -1343,14 +1427,18 @@ pub fn process_supply(
     Ok(config.output_format.formatted_string(&supply))
 }
 
-pub fn process_total_supply(rpc_client: &RpcClient, _config: &CliConfig) -> ProcessResult {
-    let supply = rpc_client.supply()?.value;
-    Ok(format!("{} SOL", lamports_to_sol(supply.total)))
+pub fn process_total_supply(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let total_supply = CliTotalSupply {
+        total: rpc_client.supply()?.value.total,
+    };
+    Ok(config.output_format.formatted_string(&total_supply))
 }
 
-pub fn process_get_transaction_count(rpc_client: &RpcClient, _config: &CliConfig) -> ProcessResult {
-    let transaction_count = rpc_client.get_transaction_count()?;
-    Ok(transaction_count.to_string())
+pub fn process_get_transaction_count(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let transaction_count = CliTransactionCount {
+        transaction_count: rpc_client.get_transaction_count()?,
+    };
+    Ok(config.output_format.formatted_string(&transaction_count))
 }
 
 pub fn process_ping(
@@ -1560,6 +1648,8 @@ pub fn process_logs(config: &CliConfig, filter: &RpcTransactionLogsFilter) -> Pr
             RpcTransactionLogsFilter::AllWithVotes => " (including votes)".into(),
             RpcTransactionLogsFilter::Mentions(addresses) =>
                 format!(" mentioning {}", addresses.join(",")),
+            RpcTransactionLogsFilter::ProgramIds(program_ids) =>
+                format!(" invoking {}", program_ids.join(",")),
         },
         config.commitment.commitment
     );
@@ -1569,6 +1659,7 @@ pub fn process_logs(config: &CliConfig, filter: &RpcTransactionLogsFilter) -> Pr
         filter.clone(),
         RpcTransactionLogsConfig {
             commitment: Some(config.commitment),
+            ..RpcTransactionLogsConfig::default()
         },
     )?;
 
@@ -1813,19 +1904,52 @@ pub fn process_show_validators(
     number_validators: bool,
     keep_unstaked_delinquents: bool,
     delinquent_slot_distance: Option<Slot>,
+    epoch: Option<Epoch>,
 ) -> ProcessResult {
     let progress_bar = new_spinner_progress_bar();
     progress_bar.set_message("Fetching vote accounts...");
     let epoch_info = rpc_client.get_epoch_info()?;
-    let vote_accounts = rpc_client.get_vote_accounts_with_config(RpcGetVoteAccountsConfig {
-        keep_unstaked_delinquents: Some(keep_unstaked_delinquents),
+    let epoch = epoch.unwrap_or(epoch_info.epoch);
+    if epoch > epoch_info.epoch {
+        return Err(format!(
+            "Epoch {} is in the future, the current epoch is {}",
+            epoch, epoch_info.epoch
+        )
+        .into());
+    }
+    let historical = epoch < epoch_info.epoch;
+
+    let mut vote_accounts = rpc_client.get_vote_accounts_with_config(RpcGetVoteAccountsConfig {
+        keep_unstaked_delinquents: Some(keep_unstaked_delinquents || historical),
         delinquent_slot_distance,
         ..RpcGetVoteAccountsConfig::default()
     })?;
 
+    let block_production_range = if historical {
+        progress_bar.set_message(format!(
+            "Reconstructing stake and credits of epoch {}...",
+            epoch
+        ));
+        vote_accounts =
+            vote_accounts_at_epoch(rpc_client, vote_accounts, epoch, keep_unstaked_delinquents)?;
+
+        let epoch_schedule = rpc_client.get_epoch_schedule()?;
+        Some(RpcBlockProductionConfigRange {
+            first_slot: epoch_schedule.get_first_slot_in_epoch(epoch),
+            last_slot: Some(epoch_schedule.get_last_slot_in_epoch(epoch)),
+        })
+    } else {
+        None
+    };
+
     progress_bar.set_message("Fetching block production...");
+    // Block production of an epoch that has left the slot history is no longer available, in
+    // which case skip rates are omitted
     let skip_rate: HashMap<_, _> = rpc_client
-        .get_block_production()
+        .get_block_production_with_config(RpcBlockProductionConfig {
+            range: block_production_range,
+            ..RpcBlockProductionConfig::default()
+        })
         .ok()
         .map(|result| {
             result
@@ -1877,7 +2001,7 @@ pub fn process_show_validators(
         .map(|vote_account| {
             CliValidator::new(
                 vote_account,
-                epoch_info.epoch,
+                epoch,
                 node_version
                     .get(&vote_account.node_pubkey)
                     .unwrap_or(&unknown_version)
@@ -1893,7 +2017,7 @@ pub fn process_show_validators(
         .map(|vote_account| {
             CliValidator::new_delinquent(
                 vote_account,
-                epoch_info.epoch,
+                epoch,
                 node_version
                     .get(&vote_account.node_pubkey)
                     .unwrap_or(&unknown_version)
@@ -1963,24 +2087,324 @@ pub fn process_show_validators(
     Ok(config.output_format.formatted_string(&cli_validators))
 }
 
+/// Rewrites `vote_accounts` as they stood in the past `epoch`. Stake is reconstructed from the
+/// delegations of the existing stake accounts and the stake history, so stake accounts closed
+/// since are missed. Credits come from the full epoch credits history of each vote account, and
+/// a validator that earned no credits in the epoch is counted as delinquent.
+fn vote_accounts_at_epoch(
+    rpc_client: &RpcClient,
+    vote_accounts: RpcVoteAccountStatus,
+    epoch: Epoch,
+    keep_unstaked_delinquents: bool,
+) -> Result<RpcVoteAccountStatus, Box<dyn std::error::Error>> {
+    let stake_history_account = rpc_client.get_account(&stake_history::id())?;
+    let stake_history: StakeHistory = from_account(&stake_history_account).ok_or_else(|| {
+        CliError::RpcRequestError("Failed to deserialize stake history".to_string())
+    })?;
+    if stake_history.get(&epoch).is_none() {
+        return Err(format!("Epoch {} is not in the stake history", epoch).into());
+    }
+
+    let stake_accounts = rpc_client.get_program_accounts_with_config(
+        &stake::program::id(),
+        RpcProgramAccountsConfig {
+            // Filter by `StakeState::Stake(_, _)`
+            filters: Some(vec![rpc_filter::RpcFilterType::Memcmp(
+                rpc_filter::Memcmp {
+                    offset: 0,
+                    bytes: rpc_filter::MemcmpEncodedBytes::Base58(
+                        bs58::encode([2, 0, 0, 0]).into_string(),
+                    ),
+                    encoding: Some(rpc_filter::MemcmpEncoding::Binary),
+                },
+            )]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    let mut stakes: HashMap<Pubkey, u64> = HashMap::new();
+    for (_stake_pubkey, stake_account) in stake_accounts {
+        if let Ok(StakeState::Stake(_, stake)) = stake_account.state() {
+            *stakes.entry(stake.delegation.voter_pubkey).or_default() +=
+                stake.stake(epoch, Some(&stake_history));
+        }
+    }
+
+    // `getVoteAccounts` only returns the most recent epoch credits
+    let vote_pubkeys = vote_accounts
+        .current
+        .iter()
+        .chain(vote_accounts.delinquent.iter())
+        .map(|vote_account| Pubkey::from_str(&vote_account.vote_pubkey))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut epoch_credits = HashMap::new();
+    for vote_pubkeys in vote_pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client.get_multiple_accounts(vote_pubkeys)?;
+        for (vote_pubkey, account) in vote_pubkeys.iter().zip(accounts) {
+            if let Some(vote_state) = account.as_ref().and_then(VoteState::from) {
+                epoch_credits.insert(*vote_pubkey, vote_state.epoch_credits().clone());
+            }
+        }
+    }
+
+    let mut current = vec![];
+    let mut delinquent = vec![];
+    for (mut vote_account, vote_pubkey) in vote_accounts
+        .current
+        .into_iter()
+        .chain(vote_accounts.delinquent.into_iter())
+        .zip(vote_pubkeys)
+    {
+        vote_account.activated_stake = stakes.get(&vote_pubkey).cloned().unwrap_or_default();
+        if let Some(epoch_credits) = epoch_credits.remove(&vote_pubkey) {
+            vote_account.epoch_credits = epoch_credits;
+        }
+        let earned_credits =
+            vote_account
+                .epoch_credits
+                .iter()
+                .any(|(credits_epoch, credits, prev_credits)| {
+                    *credits_epoch == epoch && credits > prev_credits
+                });
+        if earned_credits {
+            current.push(vote_account);
+        } else if vote_account.activated_stake > 0 || keep_unstaked_delinquents {
+            delinquent.push(vote_account);
+        }
+    }
+    Ok(RpcVoteAccountStatus {
+        current,
+        delinquent,
+    })
+}
+
+pub fn process_show_validators_delinquency_report(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    epoch: Option<Epoch>,
+    window_epochs: u64,
+) -> ProcessResult {
+    let progress_bar = new_spinner_progress_bar();
+    progress_bar.set_message("Fetching block production...");
+    let epoch_info = rpc_client.get_epoch_info()?;
+    let last_epoch = epoch.unwrap_or(epoch_info.epoch);
+    if last_epoch > epoch_info.epoch {
+        return Err(format!(
+            "Epoch {} is in the future, the current epoch is {}",
+            last_epoch, epoch_info.epoch
+        )
+        .into());
+    }
+    let first_epoch = last_epoch.saturating_sub(window_epochs.saturating_sub(1));
+    let epoch_schedule = rpc_client.get_epoch_schedule()?;
+    let first_slot = epoch_schedule.get_first_slot_in_epoch(first_epoch);
+    let last_slot = epoch_schedule
+        .get_last_slot_in_epoch(last_epoch)
+        .min(epoch_info.absolute_slot);
+    let block_production = rpc_client
+        .get_block_production_with_config(RpcBlockProductionConfig {
+            range: Some(RpcBlockProductionConfigRange {
+                first_slot,
+                last_slot: Some(last_slot),
+            }),
+            ..RpcBlockProductionConfig::default()
+        })?
+        .value;
+
+    progress_bar.set_message("Fetching vote accounts...");
+    let vote_accounts = rpc_client.get_vote_accounts_with_config(RpcGetVoteAccountsConfig {
+        keep_unstaked_delinquents: Some(true),
+        ..RpcGetVoteAccountsConfig::default()
+    })?;
+    progress_bar.finish_and_clear();
+
+    // An identity with several vote accounts is only delinquent if all of them are
+    let mut delinquent_by_identity = HashMap::new();
+    for vote_account in &vote_accounts.current {
+        delinquent_by_identity.insert(vote_account.node_pubkey.clone(), false);
+    }
+    for vote_account in &vote_accounts.delinquent {
+        delinquent_by_identity
+            .entry(vote_account.node_pubkey.clone())
+            .or_insert(true);
+    }
+
+    let mut validators: Vec<_> = block_production
+        .by_identity
+        .into_iter()
+        .map(|(identity, (leader_slots, blocks_produced))| {
+            let skipped_slots = leader_slots.saturating_sub(blocks_produced);
+            CliValidatorDelinquencyReportEntry {
+                identity_pubkey: format_labeled_address(&identity, &config.address_labels),
+                leader_slots: leader_slots as u64,
+                blocks_produced: blocks_produced as u64,
+                skipped_slots: skipped_slots as u64,
+                skip_rate: if leader_slots > 0 {
+                    100. * skipped_slots as f64 / leader_slots as f64
+                } else {
+                    0.
+                },
+                delinquent: delinquent_by_identity.get(&identity).cloned(),
+            }
+        })
+        .collect();
+    validators.sort_by(|a, b| {
+        b.skip_rate
+            .partial_cmp(&a.skip_rate)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.identity_pubkey.cmp(&b.identity_pubkey))
+    });
+
+    let report = CliValidatorsDelinquencyReport {
+        first_epoch,
+        last_epoch,
+        first_slot: block_production.range.first_slot,
+        last_slot: block_production.range.last_slot,
+        total_leader_slots: validators.iter().map(|v| v.leader_slots).sum(),
+        total_skipped_slots: validators.iter().map(|v| v.skipped_slots).sum(),
+        validators,
+    };
+    Ok(config.output_format.formatted_string(&report))
+}
+
+/// The balance change of one address in one transaction, as written to the
+/// `transaction-history --csv` export
+#[derive(Debug, PartialEq, Serialize)]
+struct BalanceChangeRecord {
+    signature: String,
+    slot: Slot,
+    block_time: Option<String>,
+    error: Option<String>,
+    fee_lamports: u64,
+    pre_balance_lamports: u64,
+    post_balance_lamports: u64,
+    change_lamports: i128,
+}
+
+fn balance_change_record(
+    address: &Pubkey,
+    signature: &str,
+    confirmed_transaction: &EncodedConfirmedTransaction,
+) -> Option<BalanceChangeRecord> {
+    let transaction = confirmed_transaction.transaction.transaction.decode()?;
+    let meta = confirmed_transaction.transaction.meta.as_ref()?;
+    // The balances of a v0 transaction also cover the addresses loaded from its lookup tables,
+    // which follow its static keys
+    let address = address.to_string();
+    let loaded_addresses = meta
+        .loaded_addresses
+        .iter()
+        .flat_map(|loaded| loaded.writable.iter().chain(loaded.readonly.iter()));
+    let index = transaction
+        .message
+        .static_account_keys_iter()
+        .map(|key| key.to_string())
+        .chain(loaded_addresses.cloned())
+        .position(|key| key == address)?;
+    let pre_balance = *meta.pre_balances.get(index)?;
+    let post_balance = *meta.post_balances.get(index)?;
+    Some(BalanceChangeRecord {
+        signature: signature.to_string(),
+        slot: confirmed_transaction.slot,
+        block_time: confirmed_transaction
+            .block_time
+            .map(unix_timestamp_to_string),
+        error: meta.err.as_ref().map(|err| format!("{:?}", err)),
+        fee_lamports: meta.fee,
+        pre_balance_lamports: pre_balance,
+        post_balance_lamports: post_balance,
+        change_lamports: i128::from(post_balance) - i128::from(pre_balance),
+    })
+}
+
+/// Fetch a transaction, first consulting `cache_dir` if provided.  Only finalized transactions
+/// are added to the cache since anything less may still be rolled back
+fn get_transaction_with_cache(
+    rpc_client: &RpcClient,
+    cache_dir: Option<&Path>,
+    signature: &Signature,
+    finalized: bool,
+) -> Result<EncodedConfirmedTransaction, Box<dyn std::error::Error>> {
+    let cache_file = cache_dir.map(|cache_dir| cache_dir.join(format!("{}.json", signature)));
+    if let Some(cache_file) = &cache_file {
+        if let Ok(file) = File::open(cache_file) {
+            match serde_json::from_reader(BufReader::new(file)) {
+                Ok(confirmed_transaction) => return Ok(confirmed_transaction),
+                Err(err) => eprintln!("Ignoring corrupt cache entry {:?}: {}", cache_file, err),
+            }
+        }
+    }
+
+    let confirmed_transaction = rpc_client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+
+    if let (Some(cache_file), true) = (&cache_file, finalized) {
+        let file = File::create(cache_file)?;
+        serde_json::to_writer(BufWriter::new(file), &confirmed_transaction)?;
+    }
+    Ok(confirmed_transaction)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn process_transaction_history(
     rpc_client: &RpcClient,
     config: &CliConfig,
     address: &Pubkey,
-    before: Option<Signature>,
+    mut before: Option<Signature>,
     until: Option<Signature>,
     limit: usize,
     show_transactions: bool,
+    cache_dir: Option<&String>,
+    csv_file: Option<&String>,
 ) -> ProcessResult {
-    let results = rpc_client.get_signatures_for_address_with_config(
-        address,
-        GetConfirmedSignaturesForAddress2Config {
-            before,
-            until,
-            limit: Some(limit),
-            commitment: Some(CommitmentConfig::confirmed()),
-        },
-    )?;
+    let cache_dir = match cache_dir {
+        Some(cache_dir) => {
+            let cache_dir = Path::new(cache_dir).join(address.to_string());
+            fs::create_dir_all(&cache_dir).map_err(|err| {
+                CliError::BadParameter(format!(
+                    "Unable to create cache directory {:?}: {}",
+                    cache_dir, err
+                ))
+            })?;
+            Some(cache_dir)
+        }
+        None => None,
+    };
+    let mut csv_writer = csv_file.map(csv::Writer::from_path).transpose()?;
+
+    // Page through the signatures, as each getSignaturesForAddress request is capped
+    let mut results = vec![];
+    while results.len() < limit {
+        let page_limit =
+            (limit - results.len()).min(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT);
+        let page = rpc_client.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(page_limit),
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        let last_page = page.len() < page_limit;
+        before = match page.last() {
+            Some(result) => Some(result.signature.parse()?),
+            None => break,
+        };
+        results.extend(page);
+        if last_page {
+            break;
+        }
+    }
 
     let transactions_found = format!("{} transactions found", results.len());
 
@@ -2000,7 +2424,7 @@ pub fn process_transaction_history(
                 } else {
                     match result.confirmation_status {
                         None => "Finalized".to_string(),
-                        Some(status) => format!("{:?}", status),
+                        Some(ref status) => format!("{:?}", status),
                     }
                 },
                 result.memo.unwrap_or_else(|| "".to_string()),
@@ -2009,34 +2433,52 @@ pub fn process_transaction_history(
             println!("{}", result.signature);
         }
 
-        if show_transactions {
+        if show_transactions || csv_writer.is_some() {
             if let Ok(signature) = result.signature.parse::<Signature>() {
-                match rpc_client.get_transaction_with_config(
+                let finalized = matches!(
+                    result.confirmation_status,
+                    None | Some(TransactionConfirmationStatus::Finalized)
+                );
+                match get_transaction_with_cache(
+                    rpc_client,
+                    cache_dir.as_deref(),
                     &signature,
-                    RpcTransactionConfig {
-                        encoding: Some(UiTransactionEncoding::Base64),
-                        commitment: Some(CommitmentConfig::confirmed()),
-                    },
+                    finalized,
                 ) {
                     Ok(confirmed_transaction) => {
-                        println_transaction(
-                            &confirmed_transaction
-                                .transaction
-                                .transaction
-                                .decode()
-                                .expect("Successful decode"),
-                            &confirmed_transaction.transaction.meta,
-                            "  ",
-                            None,
-                            None,
-                        );
+                        if let Some(csv_writer) = csv_writer.as_mut() {
+                            if let Some(record) = balance_change_record(
+                                address,
+                                &result.signature,
+                                &confirmed_transaction,
+                            ) {
+                                csv_writer.serialize(record)?;
+                            }
+                        }
+                        if show_transactions {
+                            match confirmed_transaction.transaction.transaction.decode() {
+                                Some(transaction) => println_transaction(
+                                    &transaction,
+                                    &confirmed_transaction.transaction.meta,
+                                    "  ",
+                                    None,
+                                    None,
+                                ),
+                                None => println!("  Unable to decode transaction"),
+                            }
+                        }
                     }
                     Err(err) => println!("  Unable to get confirmed transaction details: {}", err),
                 }
             }
-            println!();
+            if show_transactions {
+                println!();
+            }
         }
     }
+    if let Some(mut csv_writer) = csv_writer {
+        csv_writer.flush()?;
+    }
     Ok(transactions_found)
 }
 
@@ -2141,7 +2583,16 @@ pub fn process_calculate_rent(
 mod tests {
     use super::*;
     use crate::{clap_app::get_clap_app, cli::parse_command};
-    use solana_sdk::signature::{write_keypair, Keypair};
+    use solana_sdk::{
+        instruction::CompiledInstruction,
+        message::{v0, MessageHeader, VersionedMessage},
+        signature::{write_keypair, Keypair, Signer},
+        transaction::VersionedTransaction,
+    };
+    use solana_transaction_status::{
+        EncodedTransaction, EncodedTransactionWithStatusMeta, UiLoadedAddresses,
+        UiTransactionStatusMeta,
+    };
     use std::str::FromStr;
     use tempfile::NamedTempFile;
 
@@ -2312,5 +2763,201 @@ mod tests {
                 signers: vec![default_keypair.into()],
             }
         );
+
+        let address = solana_sdk::pubkey::new_rand();
+        let test_transaction_history = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transaction-history",
+            &address.to_string(),
+            "--limit",
+            "5000",
+            "--cache-dir",
+            "cache",
+            "--csv",
+            "history.csv",
+        ]);
+        assert_eq!(
+            parse_command(&test_transaction_history, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::TransactionHistory {
+                    address,
+                    before: None,
+                    until: None,
+                    limit: 5000,
+                    show_transactions: false,
+                    cache_dir: Some("cache".to_string()),
+                    csv_file: Some("history.csv".to_string()),
+                },
+                signers: vec![],
+            }
+        );
+
+        let test_validators =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "validators", "--epoch", "42"]);
+        assert_eq!(
+            parse_command(&test_validators, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::ShowValidators {
+                    use_lamports_unit: false,
+                    sort_order: CliValidatorsSortOrder::Stake,
+                    reverse_sort: false,
+                    number_validators: false,
+                    keep_unstaked_delinquents: false,
+                    delinquent_slot_distance: None,
+                    epoch: Some(42),
+                },
+                signers: vec![],
+            }
+        );
+
+        let test_delinquency_report = test_commands.clone().get_matches_from(vec![
+            "test",
+            "validators",
+            "--delinquency-report",
+            "--window-epochs",
+            "3",
+        ]);
+        assert_eq!(
+            parse_command(&test_delinquency_report, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::ShowValidatorsDelinquencyReport {
+                    epoch: None,
+                    window_epochs: 3,
+                },
+                signers: vec![],
+            }
+        );
+        let test_delinquency_report = test_commands.clone().get_matches_from(vec![
+            "test",
+            "validators",
+            "--delinquency-report",
+            "--window-epochs",
+            "0",
+        ]);
+        assert!(parse_command(&test_delinquency_report, &default_signer, &mut None).is_err());
+    }
+
+    #[test]
+    fn test_balance_change_record() {
+        let from = Keypair::new();
+        let to = solana_sdk::pubkey::new_rand();
+        let transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&from.pubkey(), &to, 55)],
+            Some(&from.pubkey()),
+            &[&from],
+            Hash::default(),
+        );
+        let confirmed_transaction = EncodedConfirmedTransaction {
+            slot: 42,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::encode(
+                    transaction.into(),
+                    UiTransactionEncoding::Base64,
+                ),
+                meta: Some(UiTransactionStatusMeta {
+                    err: None,
+                    status: Ok(()),
+                    fee: 5,
+                    pre_balances: vec![100, 0, 1],
+                    post_balances: vec![40, 55, 1],
+                    inner_instructions: None,
+                    log_messages: None,
+                    pre_token_balances: None,
+                    post_token_balances: None,
+                    rewards: None,
+                    return_data: None,
+                    loaded_addresses: None,
+                    log_entries: None,
+                }),
+            },
+            block_time: Some(0),
+        };
+
+        assert_eq!(
+            balance_change_record(&from.pubkey(), "sig", &confirmed_transaction),
+            Some(BalanceChangeRecord {
+                signature: "sig".to_string(),
+                slot: 42,
+                block_time: Some("1970-01-01T00:00:00Z".to_string()),
+                error: None,
+                fee_lamports: 5,
+                pre_balance_lamports: 100,
+                post_balance_lamports: 40,
+                change_lamports: -60,
+            })
+        );
+        assert_eq!(
+            balance_change_record(&to, "sig", &confirmed_transaction).map(|r| r.change_lamports),
+            Some(55)
+        );
+        assert_eq!(
+            balance_change_record(
+                &solana_sdk::pubkey::new_rand(),
+                "sig",
+                &confirmed_transaction
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_balance_change_record_v0() {
+        let payer = solana_sdk::pubkey::new_rand();
+        let loaded_address = solana_sdk::pubkey::new_rand();
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![payer, system_program::id()],
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2])],
+                address_table_lookups: vec![v0::MessageAddressTableLookup {
+                    account_key: solana_sdk::pubkey::new_rand(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![],
+                }],
+                ..v0::Message::default()
+            }),
+        };
+        let confirmed_transaction = EncodedConfirmedTransaction {
+            slot: 42,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::encode(transaction, UiTransactionEncoding::Base64),
+                meta: Some(UiTransactionStatusMeta {
+                    err: None,
+                    status: Ok(()),
+                    fee: 5,
+                    pre_balances: vec![100, 1, 0],
+                    post_balances: vec![40, 1, 55],
+                    inner_instructions: None,
+                    log_messages: None,
+                    pre_token_balances: None,
+                    post_token_balances: None,
+                    rewards: None,
+                    return_data: None,
+                    loaded_addresses: Some(UiLoadedAddresses {
+                        writable: vec![loaded_address.to_string()],
+                        readonly: vec![],
+                    }),
+                    log_entries: None,
+                }),
+            },
+            block_time: None,
+        };
+
+        assert_eq!(
+            balance_change_record(&payer, "sig", &confirmed_transaction).map(|r| r.change_lamports),
+            Some(-60)
+        );
+        assert_eq!(
+            balance_change_record(&loaded_address, "sig", &confirmed_transaction)
+                .map(|r| r.change_lamports),
+            Some(55)
+        );
     }
 }
@@ -7,11 +7,14 @@ use console::style;
 use serde::{Deserialize, Serialize};
 use solana_clap_utils::{input_parsers::*, input_validators::*, keypair::*};
 use solana_cli_output::{QuietDisplay, VerboseDisplay};
-use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_client::{
+    client_error::ClientError, rpc_client::RpcClient, rpc_request::MAX_MULTIPLE_ACCOUNTS,
+    rpc_response::RpcFeatureActivationStatus,
+};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     account::Account,
-    clock::Slot,
+    clock::{Epoch, Slot},
     feature::{self, Feature},
     feature_set::FEATURE_NAMES,
     message::Message,
@@ -37,6 +40,7 @@ pub enum FeatureCliCommand {
     Status {
         features: Vec<Pubkey>,
     },
+    StatusAll,
     Activate {
         feature: Pubkey,
         force: ForceActivation,
@@ -58,6 +62,11 @@ pub struct CliFeature {
     pub description: String,
     #[serde(flatten)]
     pub status: CliFeatureStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation_epoch: Option<Epoch>,
+    /// Whether this software implements the feature, only reported by `feature status --all`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -87,13 +96,28 @@ impl fmt::Display for CliFeatures {
                 f,
                 "{:<44} | {:<27} | {}",
                 feature.id,
-                match feature.status {
-                    CliFeatureStatus::Inactive => style("inactive".to_string()).red(),
-                    CliFeatureStatus::Pending => style("activation pending".to_string()).yellow(),
-                    CliFeatureStatus::Active(activation_slot) =>
+                match (&feature.status, feature.activation_epoch) {
+                    (CliFeatureStatus::Inactive, _) => style("inactive".to_string()).red(),
+                    (CliFeatureStatus::Pending, _) =>
+                        style("activation pending".to_string()).yellow(),
+                    (CliFeatureStatus::Active(activation_slot), None) =>
                         style(format!("active since slot {}", activation_slot)).green(),
+                    (CliFeatureStatus::Active(activation_slot), Some(activation_epoch)) =>
+                        style(format!(
+                            "active since slot {} (epoch {})",
+                            activation_slot, activation_epoch
+                        ))
+                        .green(),
+                },
+                if feature.supported == Some(false) {
+                    style(format!(
+                        "{} (not supported by this software)",
+                        feature.description
+                    ))
+                    .red()
+                } else {
+                    style(feature.description.clone())
                 },
-                feature.description,
             )?;
         }
         if self.inactive && !self.feature_activation_allowed {
@@ -132,6 +156,16 @@ impl FeatureSubCommands for App<'_, '_> {
                                 .index(1)
                                 .multiple(true)
                                 .help("Feature status to query [default: all known features]"),
+                        )
+                        .arg(
+                            Arg::with_name("all")
+                                .long("all")
+                                .takes_value(false)
+                                .conflicts_with("features")
+                                .help(
+                                    "Query the status of every feature on the cluster, including \
+                                     features unknown to this software, with activation epochs",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -194,6 +228,10 @@ pub fn parse_feature_subcommand(
                 signers,
             }
         }
+        ("status", Some(matches)) if matches.is_present("all") => CliCommandInfo {
+            command: CliCommand::Feature(FeatureCliCommand::StatusAll),
+            signers: vec![],
+        },
         ("status", Some(matches)) => {
             let mut features = if let Some(features) = pubkeys_of(matches, "features") {
                 for feature in &features {
@@ -221,6 +259,7 @@ pub fn process_feature_subcommand(
 ) -> ProcessResult {
     match feature_subcommand {
         FeatureCliCommand::Status { features } => process_status(rpc_client, config, features),
+        FeatureCliCommand::StatusAll => process_status_all(rpc_client, config),
         FeatureCliCommand::Activate { feature, force } => {
             process_activate(rpc_client, config, *feature, *force)
         }
@@ -536,6 +575,8 @@ fn process_status(
                     id: feature_id.to_string(),
                     description: feature_name.to_string(),
                     status: feature_status,
+                    activation_epoch: None,
+                    supported: None,
                 });
                 continue;
             }
@@ -545,6 +586,8 @@ fn process_status(
             id: feature_id.to_string(),
             description: feature_name.to_string(),
             status: CliFeatureStatus::Inactive,
+            activation_epoch: None,
+            supported: None,
         });
     }
 
@@ -557,6 +600,76 @@ fn process_status(
     Ok(config.output_format.formatted_string(&feature_set))
 }
 
+fn process_status_all(rpc_client: &RpcClient, config: &CliConfig) -> ProcessResult {
+    let mut features: Vec<(Pubkey, CliFeature)> = vec![];
+    let mut inactive = false;
+    for feature_status in rpc_client.get_feature_statuses()? {
+        let feature_id: Pubkey = feature_status.id.parse()?;
+        let status = match feature_status.status {
+            RpcFeatureActivationStatus::Inactive => {
+                inactive = true;
+                CliFeatureStatus::Inactive
+            }
+            RpcFeatureActivationStatus::Pending => CliFeatureStatus::Pending,
+            RpcFeatureActivationStatus::Active => {
+                CliFeatureStatus::Active(feature_status.activation_slot.unwrap_or_default())
+            }
+        };
+        let description = FEATURE_NAMES
+            .get(&feature_id)
+            .map(|description| description.to_string())
+            .unwrap_or(feature_status.description);
+        features.push((
+            feature_id,
+            CliFeature {
+                id: feature_status.id,
+                description,
+                status,
+                activation_epoch: feature_status.activation_epoch,
+                supported: Some(FEATURE_NAMES.contains_key(&feature_id)),
+            },
+        ));
+    }
+
+    // Features this software knows but the node doesn't are looked up here
+    let reported_features: HashSet<Pubkey> = features.iter().map(|(id, _)| *id).collect();
+    let unreported_features: Vec<Pubkey> = FEATURE_NAMES
+        .keys()
+        .filter(|feature_id| !reported_features.contains(feature_id))
+        .copied()
+        .collect();
+    for feature_ids in unreported_features.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client.get_multiple_accounts(feature_ids)?;
+        for (feature_id, account) in feature_ids.iter().zip(accounts) {
+            let status = account
+                .and_then(status_from_account)
+                .unwrap_or(CliFeatureStatus::Inactive);
+            if let CliFeatureStatus::Inactive = status {
+                inactive = true;
+            }
+            features.push((
+                *feature_id,
+                CliFeature {
+                    id: feature_id.to_string(),
+                    description: FEATURE_NAMES[feature_id].to_string(),
+                    status,
+                    activation_epoch: None,
+                    supported: Some(true),
+                },
+            ));
+        }
+    }
+    features.sort_by_key(|(feature_id, _)| *feature_id);
+
+    let feature_activation_allowed = feature_activation_allowed(rpc_client, false)?;
+    let feature_set = CliFeatures {
+        features: features.into_iter().map(|(_, feature)| feature).collect(),
+        feature_activation_allowed,
+        inactive,
+    };
+    Ok(config.output_format.formatted_string(&feature_set))
+}
+
 fn process_activate(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -3,17 +3,18 @@ use console::style;
 use solana_clap_utils::{
     input_validators::normalize_to_url_if_moniker,
     keypair::{CliSigners, DefaultSigner},
-    DisplayError,
 };
 use solana_cli::{
     clap_app::get_clap_app,
-    cli::{parse_command, process_command, CliCommandInfo, CliConfig, SettingType},
+    cli::{
+        error_exit_code, parse_command, process_command, CliCommandInfo, CliConfig, SettingType,
+    },
 };
 use solana_cli_config::Config;
 use solana_cli_output::{display::println_name_value, OutputFormat};
 use solana_client::rpc_config::RpcSendTransactionConfig;
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
-use std::{collections::HashMap, error, path::PathBuf, sync::Arc, time::Duration};
+use std::{collections::HashMap, error, path::PathBuf, process::exit, sync::Arc, time::Duration};
 
 pub fn println_name_value_or(name: &str, value: &str, setting_type: SettingType) {
     let description = match setting_type {
@@ -233,7 +234,7 @@ pub fn parse_args<'a>(
     ))
 }
 
-fn main() -> Result<(), Box<dyn error::Error>> {
+fn main() {
     solana_logger::setup_with_default("off");
     let matches = get_clap_app(
         crate_name!(),
@@ -242,7 +243,19 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     )
     .get_matches();
 
-    do_main(&matches).map_err(|err| DisplayError::new_as_boxed(err).into())
+    if let Err(err) = do_main(&matches) {
+        match OutputFormat::from_matches(&matches, "output_format", false) {
+            OutputFormat::Json | OutputFormat::JsonCompact => {
+                let error = serde_json::json!({
+                    "error": err.to_string(),
+                    "exitCode": error_exit_code(err.as_ref()),
+                });
+                eprintln!("{}", error);
+            }
+            _ => eprintln!("Error: {}", err),
+        }
+        exit(error_exit_code(err.as_ref()));
+    }
 }
 
 fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
@@ -10,14 +10,18 @@ use crate::{
 };
 use clap::{App, Arg, ArgMatches, SubCommand};
 use solana_clap_utils::{
+    fee_payer::{fee_payer_arg, FEE_PAYER_ARG},
     input_parsers::*,
     input_validators::*,
     keypair::{DefaultSigner, SignerIndex},
     memo::{memo_arg, MEMO_ARG},
     nonce::*,
+    offline::*,
 };
-use solana_cli_output::CliNonceAccount;
-use solana_client::{nonce_utils::*, rpc_client::RpcClient};
+use solana_cli_output::{
+    return_signers_with_config, CliNonce, CliNonceAccount, ReturnSignersConfig,
+};
+use solana_client::{blockhash_query::BlockhashQuery, nonce_utils::*, rpc_client::RpcClient};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     account::Account,
@@ -26,6 +30,7 @@ use solana_sdk::{
     instruction::InstructionError,
     message::Message,
     nonce::{self, State},
+    packet::PACKET_DATA_SIZE,
     pubkey::Pubkey,
     system_instruction::{
         advance_nonce_account, authorize_nonce_account, create_nonce_account,
@@ -35,7 +40,7 @@ use solana_sdk::{
     system_program,
     transaction::{Transaction, TransactionError},
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 pub trait NonceSubCommands {
     fn nonce_subcommands(self) -> Self;
@@ -124,6 +129,23 @@ impl NonceSubCommands for App<'_, '_> {
                 .arg(nonce_authority_arg())
                 .arg(memo_arg()),
         )
+        .subcommand(
+            SubCommand::with_name("new-nonces")
+                .about("Generate new nonces for several nonce accounts in a single transaction")
+                .alias("advance-nonce-accounts")
+                .arg(
+                    pubkey!(Arg::with_name("nonce_account_pubkeys")
+                        .index(1)
+                        .value_name("NONCE_ACCOUNT_ADDRESS")
+                        .multiple(true)
+                        .required(true),
+                        "Addresses of the nonce accounts, which must share a nonce authority. "),
+                )
+                .arg(nonce_authority_arg())
+                .offline_args()
+                .arg(fee_payer_arg())
+                .arg(memo_arg()),
+        )
         .subcommand(
             SubCommand::with_name("nonce-account")
                 .about("Show the contents of a nonce account")
@@ -274,6 +296,43 @@ pub fn parse_new_nonce(
     })
 }
 
+pub fn parse_new_nonces(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let mut nonce_accounts =
+        pubkeys_of_multiple_signers(matches, "nonce_account_pubkeys", wallet_manager)?.unwrap();
+    let mut seen = HashSet::new();
+    nonce_accounts.retain(|nonce_account| seen.insert(*nonce_account));
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let signer_info = default_signer.generate_unique_signers(
+        vec![fee_payer, nonce_authority],
+        matches,
+        wallet_manager,
+    )?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::NewNonces {
+            nonce_accounts,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            memo,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
 pub fn parse_show_nonce_account(
     matches: &ArgMatches<'_>,
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
@@ -510,12 +569,14 @@ pub fn process_get_nonce(
     nonce_account_pubkey: &Pubkey,
 ) -> ProcessResult {
     #[allow(clippy::redundant_closure)]
-    match get_account_with_commitment(rpc_client, nonce_account_pubkey, config.commitment)
-        .and_then(|ref a| state_from_account(a))?
-    {
-        State::Uninitialized => Ok("Nonce account is uninitialized".to_string()),
-        State::Initialized(ref data) => Ok(format!("{:?}", data.blockhash)),
-    }
+    let nonce =
+        match get_account_with_commitment(rpc_client, nonce_account_pubkey, config.commitment)
+            .and_then(|ref a| state_from_account(a))?
+        {
+            State::Uninitialized => None,
+            State::Initialized(ref data) => Some(data.blockhash.to_string()),
+        };
+    Ok(config.output_format.formatted_string(&CliNonce { nonce }))
 }
 
 pub fn process_new_nonce(
@@ -571,6 +632,110 @@ pub fn process_new_nonce(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn process_new_nonces(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    nonce_accounts: &[Pubkey],
+    nonce_authority: SignerIndex,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    memo: Option<&String>,
+    fee_payer: SignerIndex,
+) -> ProcessResult {
+    let nonce_authority = config.signers[nonce_authority];
+    let fee_payer = config.signers[fee_payer];
+    for nonce_account in nonce_accounts {
+        check_unique_pubkeys(
+            (&fee_payer.pubkey(), "fee-payer keypair".to_string()),
+            (nonce_account, "nonce_account_pubkey".to_string()),
+        )?;
+    }
+
+    if !sign_only {
+        for nonce_account in nonce_accounts {
+            let authority =
+                get_account_with_commitment(rpc_client, nonce_account, config.commitment)
+                    .and_then(|ref account| data_from_account(account).map(|data| data.authority))
+                    .map_err(|err| {
+                        CliError::BadParameter(format!(
+                            "Unable to advance nonce account {}. error: {}",
+                            nonce_account, err
+                        ))
+                    })?;
+            if authority != nonce_authority.pubkey() {
+                return Err(CliError::BadParameter(format!(
+                    "Nonce account {} is not controlled by nonce authority {}",
+                    nonce_account,
+                    nonce_authority.pubkey()
+                ))
+                .into());
+            }
+        }
+    }
+
+    let ixs = nonce_accounts
+        .iter()
+        .map(|nonce_account| advance_nonce_account(nonce_account, &nonce_authority.pubkey()))
+        .collect::<Vec<_>>()
+        .with_memo(memo);
+    let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
+    let message = Message::new(&ixs, Some(&fee_payer.pubkey()));
+    let mut tx = Transaction::new_unsigned(message);
+    check_transaction_fits_in_packet(&tx, nonce_accounts.len())?;
+
+    if sign_only {
+        tx.try_partial_sign(&config.signers, recent_blockhash)?;
+        return_signers_with_config(
+            &tx,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+            },
+        )
+    } else {
+        tx.try_sign(&config.signers, recent_blockhash)?;
+        check_account_for_fee_with_commitment(
+            rpc_client,
+            &fee_payer.pubkey(),
+            &tx.message,
+            config.commitment,
+        )?;
+        let merge_errors =
+            get_feature_is_active(rpc_client, &merge_nonce_error_into_system_error::id())?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+
+        if merge_errors {
+            log_instruction_custom_error::<SystemError>(result, config)
+        } else {
+            log_instruction_custom_error_ex::<NonceError, _>(result, config, |ix_error| {
+                if let InstructionError::Custom(_) = ix_error {
+                    instruction_to_nonce_error(ix_error, merge_errors)
+                } else {
+                    None
+                }
+            })
+        }
+    }
+}
+
+fn check_transaction_fits_in_packet(
+    tx: &Transaction,
+    num_nonce_accounts: usize,
+) -> Result<(), CliError> {
+    let tx_size = bincode::serialized_size(tx)
+        .map_err(|err| CliError::BadParameter(err.to_string()))? as usize;
+    if tx_size > PACKET_DATA_SIZE {
+        return Err(CliError::BadParameter(format!(
+            "Advancing {} nonce accounts needs a {} byte transaction, over the {} byte limit. \
+             Split the nonce accounts into smaller batches",
+            num_nonce_accounts, tx_size, PACKET_DATA_SIZE
+        )));
+    }
+    Ok(())
+}
+
 pub fn process_show_nonce_account(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -650,6 +815,7 @@ pub fn process_withdraw_from_nonce_account(
 mod tests {
     use super::*;
     use crate::{clap_app::get_clap_app, cli::parse_command};
+    use solana_client::blockhash_query;
     use solana_sdk::{
         account::Account,
         account_utils::StateMut,
@@ -834,6 +1000,63 @@ mod tests {
             }
         );
 
+        // Test NewNonces SubCommand, signing offline
+        let other_nonce_account_pubkey = solana_sdk::pubkey::new_rand();
+        let blockhash = Hash::new(&[1u8; 32]);
+        let test_new_nonces = test_commands.clone().get_matches_from(vec![
+            "test",
+            "advance-nonce-accounts",
+            &nonce_account_string,
+            &other_nonce_account_pubkey.to_string(),
+            "--nonce-authority",
+            &authority_keypair_file,
+            "--blockhash",
+            &blockhash.to_string(),
+            "--sign-only",
+        ]);
+        assert_eq!(
+            parse_command(&test_new_nonces, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::NewNonces {
+                    nonce_accounts: vec![nonce_account_pubkey, other_nonce_account_pubkey],
+                    nonce_authority: 1,
+                    sign_only: true,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::None(blockhash),
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into()
+                ],
+            }
+        );
+
+        // Test NewNonces SubCommand, duplicate nonce accounts are advanced once
+        let test_new_nonces = test_commands.clone().get_matches_from(vec![
+            "test",
+            "new-nonces",
+            &nonce_account_string,
+            &other_nonce_account_pubkey.to_string(),
+            &nonce_account_string,
+        ]);
+        assert_eq!(
+            parse_command(&test_new_nonces, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::NewNonces {
+                    nonce_accounts: vec![nonce_account_pubkey, other_nonce_account_pubkey],
+                    nonce_authority: 0,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
         // Test ShowNonceAccount Subcommand
         let test_show_nonce_account = test_commands.clone().get_matches_from(vec![
             "test",
@@ -12,9 +12,9 @@ use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_bpf_loader_program::{syscalls::register_syscalls, BpfError, ThisInstructionMeter};
 use solana_clap_utils::{self, input_parsers::*, input_validators::*, keypair::*};
 use solana_cli_output::{
-    display::new_spinner_progress_bar, CliProgram, CliProgramAccountType, CliProgramAuthority,
-    CliProgramBuffer, CliProgramId, CliUpgradeableBuffer, CliUpgradeableBuffers,
-    CliUpgradeableProgram, CliUpgradeableProgramClosed, CliUpgradeablePrograms,
+    CliProgram, CliProgramAccountType, CliProgramAuthority, CliProgramBuffer, CliProgramId,
+    CliUpgradeableBuffer, CliUpgradeableBuffers, CliUpgradeableProgram,
+    CliUpgradeableProgramClosed, CliUpgradeablePrograms,
 };
 use solana_client::{
     client_error::ClientErrorKind,
@@ -22,7 +22,6 @@ use solana_client::{
     rpc_config::RpcSendTransactionConfig,
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
-    rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
     tpu_client::{TpuClient, TpuClientConfig},
 };
 use solana_rbpf::{
@@ -44,15 +43,12 @@ use solana_sdk::{
     process_instruction::MockInvokeContext,
     pubkey::Pubkey,
     signature::{keypair_from_seed, read_keypair_file, Keypair, Signature, Signer},
-    signers::Signers,
     system_instruction::{self, SystemError},
     system_program,
     transaction::Transaction,
     transaction::TransactionError,
 };
-use solana_transaction_status::TransactionConfirmationStatus;
 use std::{
-    collections::HashMap,
     error,
     fs::File,
     io::{Read, Write},
@@ -60,8 +56,6 @@ use std::{
     path::PathBuf,
     str::FromStr,
     sync::Arc,
-    thread::sleep,
-    time::Duration,
 };
 
 #[derive(Debug, PartialEq)]
@@ -112,6 +106,12 @@ pub enum ProgramCliCommand {
         authority_index: SignerIndex,
         use_lamports_unit: bool,
     },
+    CloseBuffers {
+        buffer_pubkeys: Option<Vec<Pubkey>>,
+        recipient_pubkey: Pubkey,
+        authority_index: SignerIndex,
+        use_lamports_unit: bool,
+    },
 }
 
 pub trait ProgramSubCommands {
@@ -383,6 +383,68 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .help("Display balance in lamports instead of SOL"),
                         ),
                 )
+                .subcommand(
+                    SubCommand::with_name("list-buffers")
+                        .about("List buffer accounts and the balance closing them would recover")
+                        .arg(
+                            Arg::with_name("all")
+                                .long("all")
+                                .conflicts_with("buffer_authority")
+                                .help("List buffers for all authorities")
+                        )
+                        .arg(
+                            pubkey!(Arg::with_name("buffer_authority")
+                                .long("buffer-authority")
+                                .value_name("AUTHORITY")
+                                .conflicts_with("all"),
+                                "Authority [default: the default configured keypair]"),
+                        )
+                        .arg(
+                            Arg::with_name("lamports")
+                                .long("lamports")
+                                .takes_value(false)
+                                .help("Display balance in lamports instead of SOL"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("close-buffers")
+                        .about("Close buffer accounts and withdraw their lamports")
+                        .arg(
+                            pubkey!(Arg::with_name("buffers")
+                                .index(1)
+                                .value_name("BUFFER_ADDRESS")
+                                .multiple(true)
+                                .required_unless("all"),
+                                "Addresses of the buffer accounts to close"),
+                        )
+                        .arg(
+                            Arg::with_name("all")
+                                .long("all")
+                                .conflicts_with("buffers")
+                                .help("Close every buffer account that matches the authority")
+                        )
+                        .arg(
+                            Arg::with_name("authority")
+                                .long("authority")
+                                .alias("buffer-authority")
+                                .value_name("AUTHORITY_SIGNER")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("Buffer authority [default: the default configured keypair]")
+                        )
+                        .arg(
+                            pubkey!(Arg::with_name("recipient_account")
+                                .long("recipient")
+                                .value_name("RECIPIENT_ADDRESS"),
+                                "Address of the account to deposit the closed accounts' lamports [default: the default configured keypair]"),
+                        )
+                        .arg(
+                            Arg::with_name("lamports")
+                                .long("lamports")
+                                .takes_value(false)
+                                .help("Display balance in lamports instead of SOL"),
+                        ),
+                )
         )
         .subcommand(
             SubCommand::with_name("deploy")
@@ -658,6 +720,68 @@ pub fn parse_program_subcommand(
                 signers: signer_info.signers,
             }
         }
+        ("list-buffers", Some(matches)) => {
+            let authority_pubkey = if let Some(authority_pubkey) =
+                pubkey_of_signer(matches, "buffer_authority", wallet_manager)?
+            {
+                authority_pubkey
+            } else {
+                default_signer
+                    .signer_from_path(matches, wallet_manager)?
+                    .pubkey()
+            };
+
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: None,
+                    authority_pubkey,
+                    get_programs: false,
+                    get_buffers: true,
+                    all: matches.is_present("all"),
+                    use_lamports_unit: matches.is_present("lamports"),
+                }),
+                signers: vec![],
+            }
+        }
+        ("close-buffers", Some(matches)) => {
+            let buffer_pubkeys = if matches.is_present("all") {
+                None
+            } else {
+                pubkeys_of(matches, "buffers")
+            };
+
+            let recipient_pubkey = if let Some(recipient_pubkey) =
+                pubkey_of_signer(matches, "recipient_account", wallet_manager)?
+            {
+                recipient_pubkey
+            } else {
+                default_signer
+                    .signer_from_path(matches, wallet_manager)?
+                    .pubkey()
+            };
+
+            let (authority_signer, authority_pubkey) =
+                signer_of(matches, "authority", wallet_manager)?;
+
+            let signer_info = default_signer.generate_unique_signers(
+                vec![
+                    Some(default_signer.signer_from_path(matches, wallet_manager)?),
+                    authority_signer,
+                ],
+                matches,
+                wallet_manager,
+            )?;
+
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::CloseBuffers {
+                    buffer_pubkeys,
+                    recipient_pubkey,
+                    authority_index: signer_info.index_of(authority_pubkey).unwrap(),
+                    use_lamports_unit: matches.is_present("lamports"),
+                }),
+                signers: signer_info.signers,
+            }
+        }
         _ => unreachable!(),
     };
     Ok(response)
@@ -765,6 +889,19 @@ pub fn process_program_subcommand(
             *authority_index,
             *use_lamports_unit,
         ),
+        ProgramCliCommand::CloseBuffers {
+            buffer_pubkeys,
+            recipient_pubkey,
+            authority_index,
+            use_lamports_unit,
+        } => process_close_buffers(
+            &rpc_client,
+            config,
+            buffer_pubkeys.as_deref(),
+            *recipient_pubkey,
+            *authority_index,
+            *use_lamports_unit,
+        ),
     }
 }
 
@@ -805,17 +942,18 @@ fn process_program_deploy(
     allow_excessive_balance: bool,
 ) -> ProcessResult {
     let (words, mnemonic, buffer_keypair) = create_ephemeral_keypair()?;
-    let (buffer_provided, buffer_signer, buffer_pubkey) = if let Some(i) = buffer_signer_index {
-        (true, Some(config.signers[i]), config.signers[i].pubkey())
-    } else if let Some(pubkey) = buffer_pubkey {
-        (true, None, pubkey)
-    } else {
-        (
-            false,
-            Some(&buffer_keypair as &dyn Signer),
-            buffer_keypair.pubkey(),
-        )
-    };
+    let (buffer_provided, mut buffer_signer, mut buffer_pubkey) =
+        if let Some(i) = buffer_signer_index {
+            (true, Some(config.signers[i]), config.signers[i].pubkey())
+        } else if let Some(pubkey) = buffer_pubkey {
+            (true, None, pubkey)
+        } else {
+            (
+                false,
+                Some(&buffer_keypair as &dyn Signer),
+                buffer_keypair.pubkey(),
+            )
+        };
     let upgrade_authority_signer = config.signers[upgrade_authority_signer_index];
 
     let default_program_keypair = get_default_program_keypair(program_location);
@@ -923,6 +1061,28 @@ fn process_program_deploy(
     } else {
         return Err("Program location required if buffer not supplied".into());
     };
+
+    // Pick up where an interrupted deploy of the same program left off rather than abandoning
+    // its buffer
+    let mut resumed = false;
+    if !buffer_provided {
+        let resumable_buffer = find_resumable_buffer(
+            &rpc_client,
+            &upgrade_authority_signer.pubkey(),
+            &program_data,
+        )
+        .unwrap_or_else(|err| {
+            debug!("Unable to search for a resumable buffer: {}", err);
+            None
+        });
+        if let Some(resumable_buffer) = resumable_buffer {
+            eprintln!("Resuming deploy from buffer {}", resumable_buffer);
+            buffer_signer = None;
+            buffer_pubkey = resumable_buffer;
+            resumed = true;
+        }
+    }
+
     let buffer_data_len = program_len;
     let programdata_len = if let Some(len) = max_len {
         if program_len > len {
@@ -979,7 +1139,7 @@ fn process_program_deploy(
             None,
         )?;
     }
-    if result.is_err() && buffer_signer_index.is_none() {
+    if result.is_err() && buffer_signer_index.is_none() && !resumed {
         report_ephemeral_mnemonic(words, mnemonic);
     }
     result
@@ -1608,34 +1768,87 @@ fn process_close(
             return Err(format!("Unable to find the account {}", account_pubkey).into());
         }
     } else {
-        let buffers = get_buffers(
+        process_close_buffers(
             rpc_client,
-            Some(authority_signer.pubkey()),
+            config,
+            None,
+            recipient_pubkey,
+            authority_index,
             use_lamports_unit,
-        )?;
+        )
+    }
+}
 
-        let mut closed = vec![];
-        for buffer in buffers.buffers.iter() {
-            if close(
-                rpc_client,
-                config,
-                &Pubkey::from_str(&buffer.address)?,
-                &recipient_pubkey,
-                authority_signer,
-                None,
-            )
-            .is_ok()
-            {
-                closed.push(buffer.clone());
+/// Close the given buffer accounts, or every buffer of the authority if none are given
+fn process_close_buffers(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    buffer_pubkeys: Option<&[Pubkey]>,
+    recipient_pubkey: Pubkey,
+    authority_index: SignerIndex,
+    use_lamports_unit: bool,
+) -> ProcessResult {
+    let authority_signer = config.signers[authority_index];
+
+    let buffers = if let Some(buffer_pubkeys) = buffer_pubkeys {
+        let mut buffers = vec![];
+        for buffer_pubkey in buffer_pubkeys {
+            let account = rpc_client
+                .get_account_with_commitment(buffer_pubkey, config.commitment)?
+                .value
+                .ok_or_else(|| format!("Unable to find the account {}", buffer_pubkey))?;
+            if let Ok(UpgradeableLoaderState::Buffer { authority_address }) = account.state() {
+                if authority_address != Some(authority_signer.pubkey()) {
+                    return Err(format!(
+                        "Buffer account {} authority {:?} does not match {:?}",
+                        buffer_pubkey,
+                        authority_address,
+                        Some(authority_signer.pubkey())
+                    )
+                    .into());
+                }
+                buffers.push(CliUpgradeableBuffer {
+                    address: buffer_pubkey.to_string(),
+                    authority: authority_signer.pubkey().to_string(),
+                    data_len: 0,
+                    lamports: account.lamports,
+                    use_lamports_unit,
+                });
+            } else {
+                return Err(format!("{} is not a Buffer account", buffer_pubkey).into());
             }
         }
-        Ok(config
-            .output_format
-            .formatted_string(&CliUpgradeableBuffers {
-                buffers: closed,
-                use_lamports_unit,
-            }))
+        buffers
+    } else {
+        get_buffers(
+            rpc_client,
+            Some(authority_signer.pubkey()),
+            use_lamports_unit,
+        )?
+        .buffers
+    };
+
+    let mut closed = vec![];
+    for buffer in buffers.iter() {
+        if close(
+            rpc_client,
+            config,
+            &Pubkey::from_str(&buffer.address)?,
+            &recipient_pubkey,
+            authority_signer,
+            None,
+        )
+        .is_ok()
+        {
+            closed.push(buffer.clone());
+        }
     }
+    Ok(config
+        .output_format
+        .formatted_string(&CliUpgradeableBuffers {
+            buffers: closed,
+            use_lamports_unit,
+        }))
 }
 
 /// Deploy using non-upgradeable loader
@@ -1721,15 +1934,15 @@ fn do_process_program_write_and_deploy(
     // Initialize buffer account or complete if already partially initialized
     let (initial_message, write_messages, balance_needed) =
         if let Some(buffer_authority_signer) = buffer_authority_signer {
-            let (initial_instructions, balance_needed) = if let Some(account) = rpc_client
+            let buffer_account = rpc_client
                 .get_account_with_commitment(buffer_pubkey, config.commitment)?
-                .value
-            {
+                .value;
+            let (initial_instructions, balance_needed) = if let Some(account) = &buffer_account {
                 complete_partial_program_init(
                     loader_id,
                     &config.signers[0].pubkey(),
                     buffer_pubkey,
-                    &account,
+                    account,
                     if loader_id == &bpf_loader_upgradeable::id() {
                         UpgradeableLoaderState::buffer_len(buffer_data_len)?
                     } else {
@@ -1787,10 +2000,17 @@ fn do_process_program_write_and_deploy(
                 Message::new(&[instruction], Some(&payer_pubkey))
             };
 
+            let written_data = buffer_account
+                .as_ref()
+                .map(|account| written_program_data(loader_id, account))
+                .unwrap_or_default();
             let mut write_messages = vec![];
             let chunk_size = calculate_max_chunk_size(&create_msg);
             for (chunk, i) in program_data.chunks(chunk_size).zip(0..) {
-                write_messages.push(create_msg((i * chunk_size) as u32, chunk.to_vec()));
+                let offset = i * chunk_size;
+                if !chunk_already_written(written_data, offset, chunk) {
+                    write_messages.push(create_msg(offset as u32, chunk.to_vec()));
+                }
             }
 
             (initial_message, Some(write_messages), balance_needed)
@@ -1885,17 +2105,17 @@ fn do_process_program_upgrade(
     let mut messages: Vec<&Message> = Vec::new();
 
     let (initial_message, write_messages, balance_needed) =
-        if let Some(buffer_signer) = buffer_signer {
+        if buffer_signer.is_some() || !program_data.is_empty() {
             // Check Buffer account to see if partial initialization has occurred
-            let (initial_instructions, balance_needed) = if let Some(account) = rpc_client
-                .get_account_with_commitment(&buffer_signer.pubkey(), config.commitment)?
-                .value
-            {
+            let buffer_account = rpc_client
+                .get_account_with_commitment(buffer_pubkey, config.commitment)?
+                .value;
+            let (initial_instructions, balance_needed) = if let Some(account) = &buffer_account {
                 complete_partial_program_init(
                     &loader_id,
                     &config.signers[0].pubkey(),
-                    &buffer_signer.pubkey(),
-                    &account,
+                    buffer_pubkey,
+                    account,
                     UpgradeableLoaderState::buffer_len(data_len)?,
                     minimum_balance,
                     true,
@@ -1922,12 +2142,11 @@ fn do_process_program_upgrade(
                 None
             };
 
-            let buffer_signer_pubkey = buffer_signer.pubkey();
             let upgrade_authority_pubkey = upgrade_authority.pubkey();
             let payer_pubkey = config.signers[0].pubkey();
             let create_msg = |offset: u32, bytes: Vec<u8>| {
                 let instruction = bpf_loader_upgradeable::write(
-                    &buffer_signer_pubkey,
+                    buffer_pubkey,
                     &upgrade_authority_pubkey,
                     offset,
                     bytes,
@@ -1935,11 +2154,18 @@ fn do_process_program_upgrade(
                 Message::new(&[instruction], Some(&payer_pubkey))
             };
 
-            // Create and add write messages
+            // Create and add write messages, skipping any already written by an earlier attempt
+            let written_data = buffer_account
+                .as_ref()
+                .map(|account| written_program_data(&loader_id, account))
+                .unwrap_or_default();
             let mut write_messages = vec![];
             let chunk_size = calculate_max_chunk_size(&create_msg);
             for (chunk, i) in program_data.chunks(chunk_size).zip(0..) {
-                write_messages.push(create_msg((i * chunk_size) as u32, chunk.to_vec()));
+                let offset = i * chunk_size;
+                if !chunk_already_written(written_data, offset, chunk) {
+                    write_messages.push(create_msg(offset as u32, chunk.to_vec()));
+                }
             }
 
             (initial_message, Some(write_messages), balance_needed)
@@ -2066,6 +2292,78 @@ fn complete_partial_program_init(
     Ok((instructions, balance_needed))
 }
 
+/// The program bytes held by an existing buffer account, empty if it has not been initialized
+fn written_program_data<'a>(loader_id: &Pubkey, account: &'a Account) -> &'a [u8] {
+    if account.owner != *loader_id {
+        &[]
+    } else if loader_id == &bpf_loader_upgradeable::id() {
+        UpgradeableLoaderState::buffer_data_offset()
+            .ok()
+            .and_then(|offset| account.data.get(offset..))
+            .unwrap_or_default()
+    } else {
+        &account.data
+    }
+}
+
+fn chunk_already_written(written_data: &[u8], offset: usize, chunk: &[u8]) -> bool {
+    written_data.get(offset..offset + chunk.len()) == Some(chunk)
+}
+
+/// Finds a buffer account left behind by an interrupted deploy of `program_data` by
+/// `authority_pubkey`: one of the right size whose written bytes all agree with the program
+fn find_resumable_buffer(
+    rpc_client: &RpcClient,
+    authority_pubkey: &Pubkey,
+    program_data: &[u8],
+) -> Result<Option<Pubkey>, Box<dyn std::error::Error>> {
+    let buffer_len = UpgradeableLoaderState::buffer_len(program_data.len())?;
+    let results = rpc_client.get_program_accounts_with_config(
+        &bpf_loader_upgradeable::id(),
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(buffer_len as u64),
+                RpcFilterType::Memcmp(Memcmp {
+                    offset: 0,
+                    bytes: MemcmpEncodedBytes::Base58(bs58::encode(vec![1, 0, 0, 0]).into_string()),
+                    encoding: None,
+                }),
+                RpcFilterType::Memcmp(Memcmp {
+                    offset: ACCOUNT_TYPE_SIZE,
+                    bytes: MemcmpEncodedBytes::Base58(bs58::encode(vec![1]).into_string()),
+                    encoding: None,
+                }),
+                RpcFilterType::Memcmp(Memcmp {
+                    offset: ACCOUNT_TYPE_SIZE + OPTION_SIZE,
+                    bytes: MemcmpEncodedBytes::Base58(
+                        bs58::encode(authority_pubkey.as_ref()).into_string(),
+                    ),
+                    encoding: None,
+                }),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+
+    Ok(results.into_iter().find_map(|(address, account)| {
+        let written_data = written_program_data(&bpf_loader_upgradeable::id(), &account);
+        let resumable = written_data.len() == program_data.len()
+            && written_data
+                .iter()
+                .zip(program_data)
+                .all(|(written, byte)| *written == 0 || written == byte);
+        if resumable {
+            Some(address)
+        } else {
+            None
+        }
+    }))
+}
+
 fn check_payer(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -2121,12 +2419,17 @@ fn send_deploy_messages(
     if let Some(write_messages) = write_messages {
         if let Some(write_signer) = write_signer {
             trace!("Writing program data");
-            let transaction_errors = send_and_confirm_messages_with_spinner(
+            let transaction_errors = TpuClient::new(
                 rpc_client.clone(),
                 &config.websocket_url,
-                write_messages,
-                &[payer_signer, write_signer],
+                TpuClientConfig::default(),
             )
+            .and_then(|tpu_client| {
+                tpu_client.send_and_confirm_messages_with_spinner(
+                    write_messages,
+                    &[payer_signer, write_signer],
+                )
+            })
             .map_err(|err| format!("Data writes to account failed: {}", err))?
             .into_iter()
             .flatten()
@@ -2200,146 +2503,6 @@ fn report_ephemeral_mnemonic(words: usize, mnemonic: bip39::Mnemonic) {
     );
 }
 
-fn send_and_confirm_messages_with_spinner<T: Signers>(
-    rpc_client: Arc<RpcClient>,
-    websocket_url: &str,
-    messages: &[Message],
-    signers: &T,
-) -> Result<Vec<Option<TransactionError>>, Box<dyn error::Error>> {
-    let commitment = rpc_client.commitment();
-
-    let progress_bar = new_spinner_progress_bar();
-    let send_transaction_interval = Duration::from_millis(10); /* ~100 TPS */
-    let mut send_retries = 5;
-
-    let (blockhash, mut last_valid_block_height) =
-        rpc_client.get_latest_blockhash_with_commitment(commitment)?;
-
-    let mut transactions = vec![];
-    let mut transaction_errors = vec![None; messages.len()];
-    for (i, message) in messages.iter().enumerate() {
-        let mut transaction = Transaction::new_unsigned(message.clone());
-        transaction.try_sign(signers, blockhash)?;
-        transactions.push((i, transaction));
-    }
-
-    progress_bar.set_message("Finding leader nodes...");
-    let tpu_client = TpuClient::new(
-        rpc_client.clone(),
-        websocket_url,
-        TpuClientConfig::default(),
-    )?;
-    loop {
-        // Send all transactions
-        let mut pending_transactions = HashMap::new();
-        let num_transactions = transactions.len();
-        for (i, transaction) in transactions {
-            if !tpu_client.send_transaction(&transaction) {
-                let _result = rpc_client
-                    .send_transaction_with_config(
-                        &transaction,
-                        RpcSendTransactionConfig {
-                            preflight_commitment: Some(commitment.commitment),
-                            ..RpcSendTransactionConfig::default()
-                        },
-                    )
-                    .ok();
-            }
-            pending_transactions.insert(transaction.signatures[0], (i, transaction));
-            progress_bar.set_message(format!(
-                "[{}/{}] Transactions sent",
-                pending_transactions.len(),
-                num_transactions
-            ));
-
-            sleep(send_transaction_interval);
-        }
-
-        // Collect statuses for all the transactions, drop those that are confirmed
-        loop {
-            let mut block_height = 0;
-            let pending_signatures = pending_transactions.keys().cloned().collect::<Vec<_>>();
-            for pending_signatures_chunk in
-                pending_signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS)
-            {
-                if let Ok(result) = rpc_client.get_signature_statuses(pending_signatures_chunk) {
-                    let statuses = result.value;
-                    for (signature, status) in
-                        pending_signatures_chunk.iter().zip(statuses.into_iter())
-                    {
-                        if let Some(status) = status {
-                            if let Some(confirmation_status) = &status.confirmation_status {
-                                if *confirmation_status != TransactionConfirmationStatus::Processed
-                                {
-                                    if let Some((i, _)) = pending_transactions.remove(signature) {
-                                        transaction_errors[i] = status.err;
-                                    }
-                                }
-                            } else if status.confirmations.is_none()
-                                || status.confirmations.unwrap() > 1
-                            {
-                                if let Some((i, _)) = pending_transactions.remove(signature) {
-                                    transaction_errors[i] = status.err;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                block_height = rpc_client.get_block_height()?;
-                progress_bar.set_message(format!(
-                    "[{}/{}] Transactions confirmed. Retrying in {} blocks",
-                    num_transactions - pending_transactions.len(),
-                    num_transactions,
-                    last_valid_block_height.saturating_sub(block_height)
-                ));
-            }
-
-            if pending_transactions.is_empty() {
-                return Ok(transaction_errors);
-            }
-
-            if block_height > last_valid_block_height {
-                break;
-            }
-
-            for (_i, transaction) in pending_transactions.values() {
-                if !tpu_client.send_transaction(transaction) {
-                    let _result = rpc_client
-                        .send_transaction_with_config(
-                            transaction,
-                            RpcSendTransactionConfig {
-                                preflight_commitment: Some(commitment.commitment),
-                                ..RpcSendTransactionConfig::default()
-                            },
-                        )
-                        .ok();
-                }
-            }
-
-            if cfg!(not(test)) {
-                // Retry twice a second
-                sleep(Duration::from_millis(500));
-            }
-        }
-
-        if send_retries == 0 {
-            return Err("Transactions failed".into());
-        }
-        send_retries -= 1;
-
-        // Re-sign any failed transactions with a new blockhash and retry
-        let (blockhash, new_last_valid_block_height) =
-            rpc_client.get_latest_blockhash_with_commitment(commitment)?;
-        last_valid_block_height = new_last_valid_block_height;
-        transactions = vec![];
-        for (_, (i, mut transaction)) in pending_transactions.into_iter() {
-            transaction.try_sign(signers, blockhash)?;
-            transactions.push((i, transaction));
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3119,6 +3282,93 @@ mod tests {
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into(),],
             }
         );
+
+        // close-buffers with explicit buffers
+        let other_buffer_pubkey = Pubkey::new_unique();
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "close-buffers",
+            &buffer_pubkey.to_string(),
+            &other_buffer_pubkey.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::CloseBuffers {
+                    buffer_pubkeys: Some(vec![buffer_pubkey, other_buffer_pubkey]),
+                    recipient_pubkey: default_keypair.pubkey(),
+                    authority_index: 0,
+                    use_lamports_unit: false,
+                }),
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into(),],
+            }
+        );
+
+        // close-buffers --all
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "close-buffers",
+            "--all",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::CloseBuffers {
+                    buffer_pubkeys: None,
+                    recipient_pubkey: default_keypair.pubkey(),
+                    authority_index: 0,
+                    use_lamports_unit: false,
+                }),
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into(),],
+            }
+        );
+
+        // list-buffers
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "list-buffers",
+            "--all",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: None,
+                    authority_pubkey: default_keypair.pubkey(),
+                    get_programs: false,
+                    get_buffers: true,
+                    all: true,
+                    use_lamports_unit: false,
+                }),
+                signers: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_written_program_data() {
+        let loader_id = bpf_loader_upgradeable::id();
+        let program_data = vec![7u8; 10];
+        let offset = UpgradeableLoaderState::buffer_data_offset().unwrap();
+        let mut account = Account::new(
+            1,
+            UpgradeableLoaderState::buffer_len(program_data.len()).unwrap(),
+            &loader_id,
+        );
+        account.data[offset..offset + 4].copy_from_slice(&program_data[..4]);
+
+        let written_data = written_program_data(&loader_id, &account);
+        assert_eq!(written_data.len(), program_data.len());
+        assert!(chunk_already_written(written_data, 0, &program_data[..4]));
+        assert!(!chunk_already_written(written_data, 4, &program_data[4..8]));
+        assert!(!chunk_already_written(written_data, 8, &[7u8; 4]));
+
+        // an account not yet owned by the loader holds no program data
+        let account = Account::new(1, 0, &system_program::id());
+        assert!(written_program_data(&loader_id, &account).is_empty());
     }
 
     #[test]
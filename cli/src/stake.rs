@@ -8,7 +8,7 @@ use crate::{
     nonce::check_nonce_account,
     spend_utils::{resolve_spend_tx_and_check_account_balances, SpendAmount},
 };
-use clap::{value_t, App, Arg, ArgGroup, ArgMatches, SubCommand};
+use clap::{value_t, value_t_or_exit, App, Arg, ArgGroup, ArgMatches, SubCommand};
 use solana_clap_utils::{
     fee_payer::{fee_payer_arg, FEE_PAYER_ARG},
     input_parsers::*,
@@ -94,6 +94,32 @@ fn custodian_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(CUSTODIAN_ARG.help)
 }
 
+fn num_accounts_arg<'a, 'b>(index: u64) -> Arg<'a, 'b> {
+    Arg::with_name("num_accounts")
+        .index(index)
+        .value_name("NUM_ACCOUNTS")
+        .takes_value(true)
+        .required(true)
+        .validator(is_parsable::<usize>)
+        .help("Number of derived stake accounts to operate on")
+}
+
+fn start_index_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("start_index")
+        .long("start-index")
+        .value_name("NUMBER")
+        .takes_value(true)
+        .default_value("0")
+        .validator(is_parsable::<usize>)
+        .help("Index of the first derived stake account")
+}
+
+/// Address of the `index`th stake account derived from `base`, using the decimal index as the
+/// seed so that the accounts match those managed by `solana-stake-accounts`
+pub fn derived_stake_account_address(base: &Pubkey, index: usize) -> Pubkey {
+    Pubkey::create_with_seed(base, &index.to_string(), &stake::program::id()).unwrap()
+}
+
 pub(crate) struct StakeAuthorization {
     authorization_type: StakeAuthorize,
     new_authority_pubkey: Pubkey,
@@ -383,6 +409,58 @@ impl StakeSubCommands for App<'_, '_> {
                 .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
+        .subcommand(
+            SubCommand::with_name("redelegate-stake")
+                .about("Redelegate active stake to another vote account")
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .takes_value(false)
+                        .hidden(true) // Don't document this argument to discourage its use
+                        .help("Override vote account sanity checks (use carefully!)")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("stake_account_pubkey")
+                        .index(1)
+                        .value_name("STAKE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "Existing delegated stake account that has been fully activated. \
+                        On success this stake account will be scheduled for deactivation and the rent-exempt balance \
+                        may be withdrawn once fully deactivated")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("vote_account_pubkey")
+                        .index(2)
+                        .value_name("REDELEGATED_VOTE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "The vote account to which the stake will be redelegated")
+                )
+                .arg(
+                    Arg::with_name("redelegation_stake_account")
+                        .index(3)
+                        .value_name("REDELEGATION_STAKE_ACCOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .help("Stake account to create for the redelegation. \
+                               On success this stake account will be created and scheduled for activation with all \
+                               the stake in the existing stake account, exclusive of the rent-exempt balance retained \
+                               in the existing account")
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .value_name("STRING")
+                        .takes_value(true)
+                        .help("Seed for address generation; if specified, the resulting account \
+                               will be at a derived address of REDELEGATION_STAKE_ACCOUNT")
+                )
+                .arg(stake_authority_arg())
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
+                .arg(memo_arg())
+        )
         .subcommand(
             SubCommand::with_name("split-stake")
                 .about("Duplicate a stake account, splitting the tokens between the two")
@@ -449,6 +527,158 @@ impl StakeSubCommands for App<'_, '_> {
                 .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
+        .subcommand(
+            SubCommand::with_name("create-stake-accounts")
+                .about("Create a batch of stake accounts at addresses derived from a base keypair")
+                .after_help("One transaction is created per stake account. With --sign-only, \
+                             each transaction's signers are printed in turn and may be submitted \
+                             with `create-stake-account --seed INDEX --signer ...`")
+                .arg(
+                    Arg::with_name("base_keypair")
+                        .index(1)
+                        .value_name("BASE_KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .help("Base of the derived stake account addresses")
+                )
+                .arg(num_accounts_arg(2))
+                .arg(
+                    Arg::with_name("amount")
+                        .index(3)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .validator(is_amount)
+                        .required(true)
+                        .help("The amount to send to each stake account, in SOL")
+                )
+                .arg(start_index_arg())
+                .arg(
+                    pubkey!(Arg::with_name("custodian")
+                        .long("custodian")
+                        .value_name("PUBKEY"),
+                        "Authority to modify lockups. ")
+                )
+                .arg(
+                    Arg::with_name("lockup_epoch")
+                        .long("lockup-epoch")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .help("The epoch height at which these accounts will be available for withdrawal")
+                )
+                .arg(
+                    Arg::with_name("lockup_date")
+                        .long("lockup-date")
+                        .value_name("RFC3339 DATETIME")
+                        .validator(is_rfc3339_datetime)
+                        .takes_value(true)
+                        .help("The date and time at which these accounts will be available for withdrawal")
+                )
+                .arg(
+                    Arg::with_name(STAKE_AUTHORITY_ARG.name)
+                        .long(STAKE_AUTHORITY_ARG.long)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help(STAKE_AUTHORITY_ARG.help)
+                )
+                .arg(
+                    Arg::with_name(WITHDRAW_AUTHORITY_ARG.name)
+                        .long(WITHDRAW_AUTHORITY_ARG.long)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help(WITHDRAW_AUTHORITY_ARG.help)
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .help("Source account of funds [default: cli config keypair]"),
+                )
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
+                .arg(memo_arg())
+        )
+        .subcommand(
+            SubCommand::with_name("split-stake-many")
+                .alias("split-many")
+                .about("Split a stake account into a batch of stake accounts at addresses derived from a base keypair")
+                .after_help("One transaction is created per split. With --sign-only, \
+                             each transaction's signers are printed in turn and may be submitted \
+                             with `split-stake --seed INDEX --signer ...`")
+                .arg(
+                    pubkey!(Arg::with_name("stake_account_pubkey")
+                        .index(1)
+                        .value_name("STAKE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "Stake account to split. ")
+                )
+                .arg(
+                    Arg::with_name("base_keypair")
+                        .index(2)
+                        .value_name("BASE_KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_signer)
+                        .help("Base of the derived addresses of the new stake accounts")
+                )
+                .arg(num_accounts_arg(3))
+                .arg(
+                    Arg::with_name("amount")
+                        .index(4)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .validator(is_amount)
+                        .required(true)
+                        .help("The amount to move into each new stake account, in SOL")
+                )
+                .arg(start_index_arg())
+                .arg(stake_authority_arg())
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
+                .arg(memo_arg())
+        )
+        .subcommand(
+            SubCommand::with_name("delegate-stake-many")
+                .alias("delegate-many")
+                .about("Delegate a batch of stake accounts at addresses derived from a base address to a vote account")
+                .after_help("One transaction is created per stake account. With --sign-only, \
+                             each transaction's signers are printed in turn and may be submitted \
+                             with `delegate-stake --signer ...`")
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .takes_value(false)
+                        .hidden(true) // Don't document this argument to discourage its use
+                        .help("Override vote account sanity checks (use carefully!)")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("base_pubkey")
+                        .index(1)
+                        .value_name("BASE_ADDRESS")
+                        .required(true),
+                        "Base of the derived stake account addresses. ")
+                )
+                .arg(num_accounts_arg(2))
+                .arg(
+                    pubkey!(Arg::with_name("vote_account_pubkey")
+                        .index(3)
+                        .value_name("VOTE_ACCOUNT_ADDRESS")
+                        .required(true),
+                        "The vote account to which the stake will be delegated")
+                )
+                .arg(start_index_arg())
+                .arg(stake_authority_arg())
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
+                .arg(memo_arg())
+        )
         .subcommand(
             SubCommand::with_name("withdraw-stake")
                 .about("Withdraw the unstaked SOL from the stake account")
@@ -769,6 +999,59 @@ pub fn parse_stake_delegate_stake(
     })
 }
 
+pub fn parse_stake_redelegate_stake(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let stake_account_pubkey =
+        pubkey_of_signer(matches, "stake_account_pubkey", wallet_manager)?.unwrap();
+    let vote_account_pubkey =
+        pubkey_of_signer(matches, "vote_account_pubkey", wallet_manager)?.unwrap();
+    let (redelegation_stake_account, redelegation_stake_account_pubkey) =
+        signer_of(matches, "redelegation_stake_account", wallet_manager)?;
+    let seed = matches.value_of("seed").map(|s| s.to_string());
+    let force = matches.is_present("force");
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (stake_authority, stake_authority_pubkey) =
+        signer_of(matches, STAKE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let mut bulk_signers = vec![stake_authority, fee_payer, redelegation_stake_account];
+    if nonce_account.is_some() {
+        bulk_signers.push(nonce_authority);
+    }
+    let signer_info =
+        default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::RedelegateStake {
+            stake_account_pubkey,
+            vote_account_pubkey,
+            stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
+            force,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            memo,
+            redelegation_stake_account: signer_info
+                .index_of(redelegation_stake_account_pubkey)
+                .unwrap(),
+            seed,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
 pub fn parse_stake_authorize(
     matches: &ArgMatches<'_>,
     default_signer: &DefaultSigner,
@@ -940,6 +1223,163 @@ pub fn parse_split_stake(
     })
 }
 
+pub fn parse_create_stake_accounts(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let num_accounts = value_t_or_exit!(matches, "num_accounts", usize);
+    let start_index = value_t_or_exit!(matches, "start_index", usize);
+    let lamports = lamports_of_sol(matches, "amount").unwrap();
+    let epoch = value_of(matches, "lockup_epoch").unwrap_or(0);
+    let unix_timestamp = unix_timestamp_from_rfc3339_datetime(matches, "lockup_date").unwrap_or(0);
+    let custodian = pubkey_of_signer(matches, "custodian", wallet_manager)?.unwrap_or_default();
+    let staker = pubkey_of_signer(matches, STAKE_AUTHORITY_ARG.name, wallet_manager)?;
+    let withdrawer = pubkey_of_signer(matches, WITHDRAW_AUTHORITY_ARG.name, wallet_manager)?;
+
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+    let (from, from_pubkey) = signer_of(matches, "from", wallet_manager)?;
+    let (base, base_pubkey) = signer_of(matches, "base_keypair", wallet_manager)?;
+
+    let mut bulk_signers = vec![fee_payer, from, base];
+    if nonce_account.is_some() {
+        bulk_signers.push(nonce_authority);
+    }
+    let signer_info =
+        default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::CreateStakeAccounts {
+            base: signer_info.index_of(base_pubkey).unwrap(),
+            start_index,
+            num_accounts,
+            staker,
+            withdrawer,
+            lockup: Lockup {
+                unix_timestamp,
+                epoch,
+                custodian,
+            },
+            lamports,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            memo,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+            from: signer_info.index_of(from_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
+pub fn parse_split_stake_many(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let stake_account_pubkey =
+        pubkey_of_signer(matches, "stake_account_pubkey", wallet_manager)?.unwrap();
+    let num_accounts = value_t_or_exit!(matches, "num_accounts", usize);
+    let start_index = value_t_or_exit!(matches, "start_index", usize);
+    let lamports = lamports_of_sol(matches, "amount").unwrap();
+
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (stake_authority, stake_authority_pubkey) =
+        signer_of(matches, STAKE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+    let (base, base_pubkey) = signer_of(matches, "base_keypair", wallet_manager)?;
+
+    let mut bulk_signers = vec![stake_authority, fee_payer, base];
+    if nonce_account.is_some() {
+        bulk_signers.push(nonce_authority);
+    }
+    let signer_info =
+        default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::SplitStakeMany {
+            stake_account_pubkey,
+            stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
+            base: signer_info.index_of(base_pubkey).unwrap(),
+            start_index,
+            num_accounts,
+            lamports,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            memo,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
+pub fn parse_delegate_stake_many(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let base_pubkey = pubkey_of_signer(matches, "base_pubkey", wallet_manager)?.unwrap();
+    let num_accounts = value_t_or_exit!(matches, "num_accounts", usize);
+    let start_index = value_t_or_exit!(matches, "start_index", usize);
+    let vote_account_pubkey =
+        pubkey_of_signer(matches, "vote_account_pubkey", wallet_manager)?.unwrap();
+    let force = matches.is_present("force");
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let memo = matches.value_of(MEMO_ARG.name).map(String::from);
+    let (stake_authority, stake_authority_pubkey) =
+        signer_of(matches, STAKE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let mut bulk_signers = vec![stake_authority, fee_payer];
+    if nonce_account.is_some() {
+        bulk_signers.push(nonce_authority);
+    }
+    let signer_info =
+        default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::DelegateStakeMany {
+            base_pubkey,
+            start_index,
+            num_accounts,
+            vote_account_pubkey,
+            stake_authority: signer_info.index_of(stake_authority_pubkey).unwrap(),
+            force,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            memo,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+        },
+        signers: signer_info.signers,
+    })
+}
+
 pub fn parse_merge_stake(
     matches: &ArgMatches<'_>,
     default_signer: &DefaultSigner,
@@ -1334,6 +1774,100 @@ pub fn process_create_stake_account(
     }
 }
 
+/// Every transaction of a bulk command advances the nonce, so only one of them can be signed
+/// offline against it
+fn check_bulk_nonce_account(
+    sign_only: bool,
+    nonce_account: Option<&Pubkey>,
+    num_accounts: usize,
+) -> Result<(), CliError> {
+    if sign_only && nonce_account.is_some() && num_accounts > 1 {
+        return Err(CliError::BadParameter(
+            "Each transaction advances the nonce account, so only one stake account can be \
+             signed offline with --nonce"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reports the outcome of every transaction of a bulk command. Failures don't stop the batch, so
+/// the signatures of the transactions that were sent are always reported
+fn report_bulk_results(results: Vec<(Pubkey, ProcessResult)>) -> ProcessResult {
+    let num_transactions = results.len();
+    let mut num_failed = 0;
+    let report = results
+        .into_iter()
+        .map(|(stake_account_pubkey, result)| match result {
+            Ok(output) => format!("{}: {}", stake_account_pubkey, output),
+            Err(err) => {
+                num_failed += 1;
+                format!("{}: failed: {}", stake_account_pubkey, err)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if num_failed == 0 {
+        Ok(report)
+    } else {
+        Err(format!(
+            "{} of {} transactions failed\n{}",
+            num_failed, num_transactions, report
+        )
+        .into())
+    }
+}
+
+/// Creates stake accounts at the derived addresses `start_index..start_index + num_accounts` of
+/// `base`, one transaction per account
+#[allow(clippy::too_many_arguments)]
+pub fn process_create_stake_accounts(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    base: SignerIndex,
+    start_index: usize,
+    num_accounts: usize,
+    staker: &Option<Pubkey>,
+    withdrawer: &Option<Pubkey>,
+    lockup: &Lockup,
+    lamports: u64,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<&Pubkey>,
+    nonce_authority: SignerIndex,
+    memo: Option<&String>,
+    fee_payer: SignerIndex,
+    from: SignerIndex,
+) -> ProcessResult {
+    check_bulk_nonce_account(sign_only, nonce_account, num_accounts)?;
+    let base_pubkey = config.signers[base].pubkey();
+    let mut results = Vec::with_capacity(num_accounts);
+    for index in start_index..start_index + num_accounts {
+        let result = process_create_stake_account(
+            rpc_client,
+            config,
+            base,
+            &Some(index.to_string()),
+            staker,
+            withdrawer,
+            None,
+            lockup,
+            SpendAmount::Some(lamports),
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            memo,
+            fee_payer,
+            from,
+        );
+        results.push((derived_stake_account_address(&base_pubkey, index), result));
+    }
+    report_bulk_results(results)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn process_stake_authorize(
     rpc_client: &RpcClient,
@@ -1781,6 +2315,51 @@ pub fn process_split_stake(
     }
 }
 
+/// Splits `lamports` from `stake_account_pubkey` into each of the derived addresses
+/// `start_index..start_index + num_accounts` of `base`, one transaction per split
+#[allow(clippy::too_many_arguments)]
+pub fn process_split_stake_many(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    stake_account_pubkey: &Pubkey,
+    stake_authority: SignerIndex,
+    base: SignerIndex,
+    start_index: usize,
+    num_accounts: usize,
+    lamports: u64,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<&Pubkey>,
+    nonce_authority: SignerIndex,
+    memo: Option<&String>,
+    fee_payer: SignerIndex,
+) -> ProcessResult {
+    check_bulk_nonce_account(sign_only, nonce_account, num_accounts)?;
+    let base_pubkey = config.signers[base].pubkey();
+    let mut results = Vec::with_capacity(num_accounts);
+    for index in start_index..start_index + num_accounts {
+        let result = process_split_stake(
+            rpc_client,
+            config,
+            stake_account_pubkey,
+            stake_authority,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account.copied(),
+            nonce_authority,
+            memo,
+            base,
+            &Some(index.to_string()),
+            lamports,
+            fee_payer,
+        );
+        results.push((derived_stake_account_address(&base_pubkey, index), result));
+    }
+    report_bulk_results(results)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn process_merge_stake(
     rpc_client: &RpcClient,
@@ -2257,49 +2836,181 @@ pub fn process_show_stake_account(
             }
             Ok(config.output_format.formatted_string(&state))
         }
-        Err(err) => Err(CliError::RpcRequestError(format!(
-            "Account data could not be deserialized to stake state: {}",
-            err
-        ))
-        .into()),
+        Err(err) => Err(CliError::RpcRequestError(format!(
+            "Account data could not be deserialized to stake state: {}",
+            err
+        ))
+        .into()),
+    }
+}
+
+pub fn process_show_stake_history(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    use_lamports_unit: bool,
+    limit_results: usize,
+) -> ProcessResult {
+    let stake_history_account = rpc_client.get_account(&stake_history::id())?;
+    let stake_history =
+        from_account::<StakeHistory, _>(&stake_history_account).ok_or_else(|| {
+            CliError::RpcRequestError("Failed to deserialize stake history".to_string())
+        })?;
+
+    let limit_results = match config.output_format {
+        OutputFormat::Json | OutputFormat::JsonCompact => std::usize::MAX,
+        _ => {
+            if limit_results == 0 {
+                std::usize::MAX
+            } else {
+                limit_results
+            }
+        }
+    };
+    let mut entries: Vec<CliStakeHistoryEntry> = vec![];
+    for entry in stake_history.deref().iter().take(limit_results) {
+        entries.push(entry.into());
+    }
+    let stake_history_output = CliStakeHistory {
+        entries,
+        use_lamports_unit,
+    };
+    Ok(config.output_format.formatted_string(&stake_history_output))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_delegate_stake(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    stake_account_pubkey: &Pubkey,
+    vote_account_pubkey: &Pubkey,
+    stake_authority: SignerIndex,
+    force: bool,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<Pubkey>,
+    nonce_authority: SignerIndex,
+    memo: Option<&String>,
+    fee_payer: SignerIndex,
+) -> ProcessResult {
+    check_unique_pubkeys(
+        (&config.signers[0].pubkey(), "cli keypair".to_string()),
+        (stake_account_pubkey, "stake_account_pubkey".to_string()),
+    )?;
+    let stake_authority = config.signers[stake_authority];
+
+    if !sign_only {
+        check_vote_account_for_delegation(rpc_client, vote_account_pubkey, force)?;
+    }
+
+    let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
+
+    let ixs = vec![stake_instruction::delegate_stake(
+        stake_account_pubkey,
+        &stake_authority.pubkey(),
+        vote_account_pubkey,
+    )]
+    .with_memo(memo);
+    let nonce_authority = config.signers[nonce_authority];
+    let fee_payer = config.signers[fee_payer];
+
+    let message = if let Some(nonce_account) = &nonce_account {
+        Message::new_with_nonce(
+            ixs,
+            Some(&fee_payer.pubkey()),
+            nonce_account,
+            &nonce_authority.pubkey(),
+        )
+    } else {
+        Message::new(&ixs, Some(&fee_payer.pubkey()))
+    };
+    let mut tx = Transaction::new_unsigned(message);
+
+    if sign_only {
+        tx.try_partial_sign(&config.signers, recent_blockhash)?;
+        return_signers_with_config(
+            &tx,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+            },
+        )
+    } else {
+        tx.try_sign(&config.signers, recent_blockhash)?;
+        if let Some(nonce_account) = &nonce_account {
+            let nonce_account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                nonce_account,
+                config.commitment,
+            )?;
+            check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
+        }
+        check_account_for_fee_with_commitment(
+            rpc_client,
+            &tx.message.account_keys[0],
+            &tx.message,
+            config.commitment,
+        )?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+        log_instruction_custom_error::<StakeError>(result, config)
     }
 }
 
-pub fn process_show_stake_history(
+/// Sanity check the vote account to ensure it is attached to a validator that has recently
+/// voted at the tip of the ledger, only reporting the problem if `force` is set
+fn check_vote_account_for_delegation(
     rpc_client: &RpcClient,
-    config: &CliConfig,
-    use_lamports_unit: bool,
-    limit_results: usize,
-) -> ProcessResult {
-    let stake_history_account = rpc_client.get_account(&stake_history::id())?;
-    let stake_history =
-        from_account::<StakeHistory, _>(&stake_history_account).ok_or_else(|| {
-            CliError::RpcRequestError("Failed to deserialize stake history".to_string())
-        })?;
+    vote_account_pubkey: &Pubkey,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vote_account_data = rpc_client
+        .get_account(vote_account_pubkey)
+        .map_err(|err| {
+            CliError::RpcRequestError(format!(
+                "Vote account not found: {}. error: {}",
+                vote_account_pubkey, err,
+            ))
+        })?
+        .data;
 
-    let limit_results = match config.output_format {
-        OutputFormat::Json | OutputFormat::JsonCompact => std::usize::MAX,
-        _ => {
-            if limit_results == 0 {
-                std::usize::MAX
+    let vote_state = VoteState::deserialize(&vote_account_data).map_err(|_| {
+        CliError::RpcRequestError(
+            "Account data could not be deserialized to vote state".to_string(),
+        )
+    })?;
+
+    let sanity_check_result = match vote_state.root_slot {
+        None => Err(CliError::BadParameter(
+            "Unable to delegate. Vote account has no root slot".to_string(),
+        )),
+        Some(root_slot) => {
+            let min_root_slot = rpc_client
+                .get_slot()?
+                .saturating_sub(DELINQUENT_VALIDATOR_SLOT_DISTANCE);
+            if root_slot < min_root_slot {
+                Err(CliError::DynamicProgramError(format!(
+                    "Unable to delegate.  Vote account appears delinquent \
+                             because its current root slot, {}, is less than {}",
+                    root_slot, min_root_slot
+                )))
             } else {
-                limit_results
+                Ok(())
             }
         }
     };
-    let mut entries: Vec<CliStakeHistoryEntry> = vec![];
-    for entry in stake_history.deref().iter().take(limit_results) {
-        entries.push(entry.into());
+
+    if let Err(err) = &sanity_check_result {
+        if !force {
+            sanity_check_result?;
+        } else {
+            println!("--force supplied, ignoring: {}", err);
+        }
     }
-    let stake_history_output = CliStakeHistory {
-        entries,
-        use_lamports_unit,
-    };
-    Ok(config.output_format.formatted_string(&stake_history_output))
+    Ok(())
 }
 
 #[allow(clippy::too_many_arguments)]
-pub fn process_delegate_stake(
+pub fn process_redelegate_stake(
     rpc_client: &RpcClient,
     config: &CliConfig,
     stake_account_pubkey: &Pubkey,
@@ -2312,72 +3023,88 @@ pub fn process_delegate_stake(
     nonce_account: Option<Pubkey>,
     nonce_authority: SignerIndex,
     memo: Option<&String>,
+    redelegation_stake_account: SignerIndex,
+    redelegation_stake_account_seed: &Option<String>,
     fee_payer: SignerIndex,
 ) -> ProcessResult {
+    let redelegation_stake_account = config.signers[redelegation_stake_account];
+    let fee_payer = config.signers[fee_payer];
+
+    if redelegation_stake_account_seed.is_none() {
+        check_unique_pubkeys(
+            (&fee_payer.pubkey(), "fee-payer keypair".to_string()),
+            (
+                &redelegation_stake_account.pubkey(),
+                "redelegation_stake_account".to_string(),
+            ),
+        )?;
+    }
     check_unique_pubkeys(
-        (&config.signers[0].pubkey(), "cli keypair".to_string()),
-        (stake_account_pubkey, "stake_account_pubkey".to_string()),
+        (&fee_payer.pubkey(), "fee-payer keypair".to_string()),
+        (stake_account_pubkey, "stake_account".to_string()),
+    )?;
+    check_unique_pubkeys(
+        (stake_account_pubkey, "stake_account".to_string()),
+        (
+            &redelegation_stake_account.pubkey(),
+            "redelegation_stake_account".to_string(),
+        ),
     )?;
-    let stake_authority = config.signers[stake_authority];
-
-    if !sign_only {
-        // Sanity check the vote account to ensure it is attached to a validator that has recently
-        // voted at the tip of the ledger
-        let vote_account_data = rpc_client
-            .get_account(vote_account_pubkey)
-            .map_err(|err| {
-                CliError::RpcRequestError(format!(
-                    "Vote account not found: {}. error: {}",
-                    vote_account_pubkey, err,
-                ))
-            })?
-            .data;
 
-        let vote_state = VoteState::deserialize(&vote_account_data).map_err(|_| {
-            CliError::RpcRequestError(
-                "Account data could not be deserialized to vote state".to_string(),
-            )
-        })?;
+    let stake_authority = config.signers[stake_authority];
 
-        let sanity_check_result = match vote_state.root_slot {
-            None => Err(CliError::BadParameter(
-                "Unable to delegate. Vote account has no root slot".to_string(),
-            )),
-            Some(root_slot) => {
-                let min_root_slot = rpc_client
-                    .get_slot()?
-                    .saturating_sub(DELINQUENT_VALIDATOR_SLOT_DISTANCE);
-                if root_slot < min_root_slot {
-                    Err(CliError::DynamicProgramError(format!(
-                        "Unable to delegate.  Vote account appears delinquent \
-                                 because its current root slot, {}, is less than {}",
-                        root_slot, min_root_slot
-                    )))
-                } else {
-                    Ok(())
-                }
-            }
-        };
+    let redelegation_stake_account_address = if let Some(seed) = redelegation_stake_account_seed {
+        Pubkey::create_with_seed(
+            &redelegation_stake_account.pubkey(),
+            seed,
+            &stake::program::id(),
+        )?
+    } else {
+        redelegation_stake_account.pubkey()
+    };
 
-        if let Err(err) = &sanity_check_result {
-            if !force {
-                sanity_check_result?;
+    if !sign_only {
+        if let Ok(stake_account) = rpc_client.get_account(&redelegation_stake_account_address) {
+            let err_msg = if stake_account.owner == stake::program::id() {
+                format!(
+                    "Stake account {} already exists",
+                    redelegation_stake_account_address
+                )
             } else {
-                println!("--force supplied, ignoring: {}", err);
-            }
+                format!(
+                    "Account {} already exists and is not a stake account",
+                    redelegation_stake_account_address
+                )
+            };
+            return Err(CliError::BadParameter(err_msg).into());
         }
+
+        check_vote_account_for_delegation(rpc_client, vote_account_pubkey, force)?;
     }
 
     let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
 
-    let ixs = vec![stake_instruction::delegate_stake(
-        stake_account_pubkey,
-        &stake_authority.pubkey(),
-        vote_account_pubkey,
-    )]
-    .with_memo(memo);
+    let ixs = if let Some(seed) = redelegation_stake_account_seed {
+        stake_instruction::redelegate_with_seed(
+            stake_account_pubkey,
+            &stake_authority.pubkey(),
+            vote_account_pubkey,
+            &redelegation_stake_account_address,
+            &redelegation_stake_account.pubkey(),
+            seed,
+        )
+        .with_memo(memo)
+    } else {
+        stake_instruction::redelegate(
+            stake_account_pubkey,
+            &stake_authority.pubkey(),
+            vote_account_pubkey,
+            &redelegation_stake_account_address,
+        )
+        .with_memo(memo)
+    };
+
     let nonce_authority = config.signers[nonce_authority];
-    let fee_payer = config.signers[fee_payer];
 
     let message = if let Some(nonce_account) = &nonce_account {
         Message::new_with_nonce(
@@ -2421,6 +3148,50 @@ pub fn process_delegate_stake(
     }
 }
 
+/// Delegates each of the stake accounts at the derived addresses
+/// `start_index..start_index + num_accounts` of `base_pubkey`, one transaction per account
+#[allow(clippy::too_many_arguments)]
+pub fn process_delegate_stake_many(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    base_pubkey: &Pubkey,
+    start_index: usize,
+    num_accounts: usize,
+    vote_account_pubkey: &Pubkey,
+    stake_authority: SignerIndex,
+    force: bool,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<&Pubkey>,
+    nonce_authority: SignerIndex,
+    memo: Option<&String>,
+    fee_payer: SignerIndex,
+) -> ProcessResult {
+    check_bulk_nonce_account(sign_only, nonce_account, num_accounts)?;
+    let mut results = Vec::with_capacity(num_accounts);
+    for index in start_index..start_index + num_accounts {
+        let stake_account_pubkey = derived_stake_account_address(base_pubkey, index);
+        let result = process_delegate_stake(
+            rpc_client,
+            config,
+            &stake_account_pubkey,
+            vote_account_pubkey,
+            stake_authority,
+            force,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account.copied(),
+            nonce_authority,
+            memo,
+            fee_payer,
+        );
+        results.push((stake_account_pubkey, result));
+    }
+    report_bulk_results(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4389,6 +5160,48 @@ mod tests {
             }
         );
 
+        // Test RedelegateStake SubCommand
+        let (redelegation_stake_account_keypair_file, mut tmp_file) = make_tmp_file();
+        let redelegation_stake_account_keypair = Keypair::new();
+        write_keypair(&redelegation_stake_account_keypair, tmp_file.as_file_mut()).unwrap();
+        let redelegation_vote_account_pubkey = solana_sdk::pubkey::new_rand();
+
+        let test_redelegate_stake = test_commands.clone().get_matches_from(vec![
+            "test",
+            "redelegate-stake",
+            &stake_account_pubkey.to_string(),
+            &redelegation_vote_account_pubkey.to_string(),
+            &redelegation_stake_account_keypair_file,
+            "--seed",
+            "redelegation",
+        ]);
+        assert_eq!(
+            parse_command(&test_redelegate_stake, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::RedelegateStake {
+                    stake_account_pubkey,
+                    vote_account_pubkey: redelegation_vote_account_pubkey,
+                    stake_authority: 0,
+                    force: false,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    redelegation_stake_account: 1,
+                    seed: Some("redelegation".to_string()),
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&redelegation_stake_account_keypair_file)
+                        .unwrap()
+                        .into()
+                ],
+            }
+        );
+
         // Test SplitStake SubCommand
         let (keypair_file, mut tmp_file) = make_tmp_file();
         let stake_account_keypair = Keypair::new();
@@ -4497,6 +5310,127 @@ mod tests {
             }
         );
 
+        // Test SplitStakeMany SubCommand
+        let test_split_stake_many = test_commands.clone().get_matches_from(vec![
+            "test",
+            "split-many",
+            &keypair_file,
+            &split_stake_account_keypair_file,
+            "10",
+            "5",
+            "--start-index",
+            "3",
+        ]);
+        assert_eq!(
+            parse_command(&test_split_stake_many, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::SplitStakeMany {
+                    stake_account_pubkey: stake_account_keypair.pubkey(),
+                    stake_authority: 0,
+                    base: 1,
+                    start_index: 3,
+                    num_accounts: 10,
+                    lamports: 5_000_000_000,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&split_stake_account_keypair_file)
+                        .unwrap()
+                        .into()
+                ],
+            }
+        );
+
+        // Test CreateStakeAccounts SubCommand
+        let test_create_stake_accounts = test_commands.clone().get_matches_from(vec![
+            "test",
+            "create-stake-accounts",
+            &split_stake_account_keypair_file,
+            "100",
+            "2",
+        ]);
+        assert_eq!(
+            parse_command(&test_create_stake_accounts, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::CreateStakeAccounts {
+                    base: 1,
+                    start_index: 0,
+                    num_accounts: 100,
+                    staker: None,
+                    withdrawer: None,
+                    lockup: Lockup::default(),
+                    lamports: 2_000_000_000,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    fee_payer: 0,
+                    from: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&split_stake_account_keypair_file)
+                        .unwrap()
+                        .into()
+                ],
+            }
+        );
+
+        // Test DelegateStakeMany SubCommand, signing offline
+        let base_pubkey = solana_sdk::pubkey::new_rand();
+        let vote_account_pubkey = solana_sdk::pubkey::new_rand();
+        let test_delegate_stake_many = test_commands.clone().get_matches_from(vec![
+            "test",
+            "delegate-stake-many",
+            &base_pubkey.to_string(),
+            "20",
+            &vote_account_pubkey.to_string(),
+            "--stake-authority",
+            &stake_auth_string,
+            "--blockhash",
+            &nonce_hash_string,
+            "--sign-only",
+            "--signer",
+            &stake_signer,
+        ]);
+        assert_eq!(
+            parse_command(&test_delegate_stake_many, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::DelegateStakeMany {
+                    base_pubkey,
+                    start_index: 0,
+                    num_accounts: 20,
+                    vote_account_pubkey,
+                    stake_authority: 1,
+                    force: false,
+                    sign_only: true,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::None(nonce_hash),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    Presigner::new(&stake_auth_pubkey, &stake_sig).into(),
+                ],
+            }
+        );
+        assert_eq!(
+            derived_stake_account_address(&base_pubkey, 7),
+            Pubkey::create_with_seed(&base_pubkey, "7", &stake::program::id()).unwrap()
+        );
+
         // Test MergeStake SubCommand
         let (keypair_file, mut tmp_file) = make_tmp_file();
         let stake_account_keypair = Keypair::new();
@@ -5,18 +5,28 @@ use crate::{
         ProcessResult,
     },
     memo::WithMemo,
+    nonce::check_nonce_account,
     spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
     stake::check_current_authority,
 };
 use clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand};
 use solana_clap_utils::{
+    fee_payer::{fee_payer_arg, FEE_PAYER_ARG},
     input_parsers::*,
     input_validators::*,
     keypair::{DefaultSigner, SignerIndex},
     memo::{memo_arg, MEMO_ARG},
+    nonce::*,
+    offline::*,
+};
+use solana_cli_output::{
+    return_signers_with_config, CliEpochVotingHistory, CliLockout, CliVoteAccount,
+    ReturnSignersConfig,
+};
+use solana_client::{
+    blockhash_query::BlockhashQuery, nonce_utils, rpc_client::RpcClient,
+    rpc_config::RpcGetVoteAccountsConfig,
 };
-use solana_cli_output::{CliEpochVotingHistory, CliLockout, CliVoteAccount};
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcGetVoteAccountsConfig};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     account::Account, commitment_config::CommitmentConfig, message::Message,
@@ -121,6 +131,9 @@ impl VoteSubCommands for App<'_, '_> {
                         .required(true),
                         "New authorized vote signer. "),
                 )
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
         .subcommand(
@@ -148,6 +161,9 @@ impl VoteSubCommands for App<'_, '_> {
                         .required(true),
                         "New authorized withdrawer. "),
                 )
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
         .subcommand(
@@ -177,6 +193,9 @@ impl VoteSubCommands for App<'_, '_> {
                         .validator(is_valid_signer)
                         .help("New authorized vote signer."),
                 )
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
         .subcommand(
@@ -206,6 +225,9 @@ impl VoteSubCommands for App<'_, '_> {
                         .validator(is_valid_signer)
                         .help("New authorized withdrawer."),
                 )
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
                 .arg(memo_arg())
         )
         .subcommand(
@@ -336,6 +358,9 @@ impl VoteSubCommands for App<'_, '_> {
                         .validator(is_valid_signer)
                         .help("Authorized withdrawer [default: cli config keypair]"),
                 )
+                .offline_args()
+                .nonce_args(false)
+                .arg(fee_payer_arg())
                 .arg(memo_arg()
             )
         )
@@ -435,8 +460,18 @@ pub fn parse_vote_authorize(
         pubkey_of_signer(matches, "vote_account_pubkey", wallet_manager)?.unwrap();
     let (authorized, authorized_pubkey) = signer_of(matches, "authorized", wallet_manager)?;
 
-    let payer_provided = None;
-    let mut signers = vec![payer_provided, authorized];
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let mut signers = vec![fee_payer, authorized];
+    if nonce_account.is_some() {
+        signers.push(nonce_authority);
+    }
 
     let new_authorized_pubkey = if checked {
         let (new_authorized_signer, new_authorized_pubkey) =
@@ -462,6 +497,12 @@ pub fn parse_vote_authorize(
             } else {
                 None
             },
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
         },
         signers: signer_info.signers,
     })
@@ -564,12 +605,19 @@ pub fn parse_withdraw_from_vote_account(
     let (withdraw_authority, withdraw_authority_pubkey) =
         signer_of(matches, "authorized_withdrawer", wallet_manager)?;
 
-    let payer_provided = None;
-    let signer_info = default_signer.generate_unique_signers(
-        vec![payer_provided, withdraw_authority],
-        matches,
-        wallet_manager,
-    )?;
+    let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
+    let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
+    let blockhash_query = BlockhashQuery::new_from_matches(matches);
+    let nonce_account = pubkey_of(matches, NONCE_ARG.name);
+    let (nonce_authority, nonce_authority_pubkey) =
+        signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+    let mut signers = vec![fee_payer, withdraw_authority];
+    if nonce_account.is_some() {
+        signers.push(nonce_authority);
+    }
+    let signer_info = default_signer.generate_unique_signers(signers, matches, wallet_manager)?;
     let memo = matches.value_of(MEMO_ARG.name).map(String::from);
 
     Ok(CliCommandInfo {
@@ -578,7 +626,13 @@ pub fn parse_withdraw_from_vote_account(
             destination_account_pubkey,
             withdraw_authority: signer_info.index_of(withdraw_authority_pubkey).unwrap(),
             withdraw_amount,
+            sign_only,
+            dump_transaction_message,
+            blockhash_query,
+            nonce_account,
+            nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
             memo,
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
         },
         signers: signer_info.signers,
     })
@@ -722,7 +776,13 @@ pub fn process_vote_authorize(
     vote_authorize: VoteAuthorize,
     authorized: SignerIndex,
     new_authorized: Option<SignerIndex>,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<Pubkey>,
+    nonce_authority: SignerIndex,
     memo: Option<&String>,
+    fee_payer: SignerIndex,
 ) -> ProcessResult {
     let authorized = config.signers[authorized];
     let new_authorized_signer = new_authorized.map(|index| config.signers[index]);
@@ -731,26 +791,28 @@ pub fn process_vote_authorize(
         (&authorized.pubkey(), "authorized_account".to_string()),
         (new_authorized_pubkey, "new_authorized_pubkey".to_string()),
     )?;
-    let (_, vote_state) = get_vote_account(rpc_client, vote_account_pubkey, config.commitment)?;
-    match vote_authorize {
-        VoteAuthorize::Voter => {
-            let current_authorized_voter = vote_state
-                .authorized_voters()
-                .last()
-                .ok_or_else(|| {
-                    CliError::RpcRequestError(
-                        "Invalid vote account state; no authorized voters found".to_string(),
-                    )
-                })?
-                .1;
-            check_current_authority(current_authorized_voter, &authorized.pubkey())?
-        }
-        VoteAuthorize::Withdrawer => {
-            check_current_authority(&vote_state.authorized_withdrawer, &authorized.pubkey())?
+    if !sign_only {
+        let (_, vote_state) = get_vote_account(rpc_client, vote_account_pubkey, config.commitment)?;
+        match vote_authorize {
+            VoteAuthorize::Voter => {
+                let current_authorized_voter = vote_state
+                    .authorized_voters()
+                    .last()
+                    .ok_or_else(|| {
+                        CliError::RpcRequestError(
+                            "Invalid vote account state; no authorized voters found".to_string(),
+                        )
+                    })?
+                    .1;
+                check_current_authority(current_authorized_voter, &authorized.pubkey())?
+            }
+            VoteAuthorize::Withdrawer => {
+                check_current_authority(&vote_state.authorized_withdrawer, &authorized.pubkey())?
+            }
         }
     }
 
-    let latest_blockhash = rpc_client.get_latest_blockhash()?;
+    let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
     let vote_ix = if new_authorized_signer.is_some() {
         vote_instruction::authorize_checked(
             vote_account_pubkey,   // vote account to update
@@ -768,17 +830,48 @@ pub fn process_vote_authorize(
     };
     let ixs = vec![vote_ix].with_memo(memo);
 
-    let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
+    let nonce_authority = config.signers[nonce_authority];
+    let fee_payer = config.signers[fee_payer];
+    let message = if let Some(nonce_account) = &nonce_account {
+        Message::new_with_nonce(
+            ixs,
+            Some(&fee_payer.pubkey()),
+            nonce_account,
+            &nonce_authority.pubkey(),
+        )
+    } else {
+        Message::new(&ixs, Some(&fee_payer.pubkey()))
+    };
     let mut tx = Transaction::new_unsigned(message);
-    tx.try_sign(&config.signers, latest_blockhash)?;
-    check_account_for_fee_with_commitment(
-        rpc_client,
-        &config.signers[0].pubkey(),
-        &tx.message,
-        config.commitment,
-    )?;
-    let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
-    log_instruction_custom_error::<VoteError>(result, config)
+
+    if sign_only {
+        tx.try_partial_sign(&config.signers, recent_blockhash)?;
+        return_signers_with_config(
+            &tx,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+            },
+        )
+    } else {
+        tx.try_sign(&config.signers, recent_blockhash)?;
+        if let Some(nonce_account) = &nonce_account {
+            let nonce_account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                nonce_account,
+                config.commitment,
+            )?;
+            check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
+        }
+        check_account_for_fee_with_commitment(
+            rpc_client,
+            &fee_payer.pubkey(),
+            &tx.message,
+            config.commitment,
+        )?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&tx);
+        log_instruction_custom_error::<VoteError>(result, config)
+    }
 }
 
 pub fn process_vote_update_validator(
@@ -942,24 +1035,44 @@ pub fn process_withdraw_from_vote_account(
     withdraw_authority: SignerIndex,
     withdraw_amount: SpendAmount,
     destination_account_pubkey: &Pubkey,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<&Pubkey>,
+    nonce_authority: SignerIndex,
     memo: Option<&String>,
+    fee_payer: SignerIndex,
 ) -> ProcessResult {
-    let latest_blockhash = rpc_client.get_latest_blockhash()?;
+    let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
     let withdraw_authority = config.signers[withdraw_authority];
 
-    let current_balance = rpc_client.get_balance(vote_account_pubkey)?;
-    let minimum_balance = rpc_client.get_minimum_balance_for_rent_exemption(VoteState::size_of())?;
-
-    let lamports = match withdraw_amount {
-        SpendAmount::All => current_balance.saturating_sub(minimum_balance),
-        SpendAmount::Some(withdraw_amount) => {
-            if current_balance.saturating_sub(withdraw_amount) < minimum_balance {
-                return Err(CliError::BadParameter(format!(
-                    "Withdraw amount too large. The vote account balance must be at least {} SOL to remain rent exempt", lamports_to_sol(minimum_balance)
-                ))
+    let lamports = if sign_only {
+        // The vote account balance isn't available offline
+        match withdraw_amount {
+            SpendAmount::All => {
+                return Err(CliError::BadParameter(
+                    "Withdraw amount ALL is not supported with --sign-only".to_string(),
+                )
                 .into());
             }
-            withdraw_amount
+            SpendAmount::Some(withdraw_amount) => withdraw_amount,
+        }
+    } else {
+        let current_balance = rpc_client.get_balance(vote_account_pubkey)?;
+        let minimum_balance =
+            rpc_client.get_minimum_balance_for_rent_exemption(VoteState::size_of())?;
+
+        match withdraw_amount {
+            SpendAmount::All => current_balance.saturating_sub(minimum_balance),
+            SpendAmount::Some(withdraw_amount) => {
+                if current_balance.saturating_sub(withdraw_amount) < minimum_balance {
+                    return Err(CliError::BadParameter(format!(
+                        "Withdraw amount too large. The vote account balance must be at least {} SOL to remain rent exempt", lamports_to_sol(minimum_balance)
+                    ))
+                    .into());
+                }
+                withdraw_amount
+            }
         }
     };
 
@@ -971,17 +1084,48 @@ pub fn process_withdraw_from_vote_account(
     )]
     .with_memo(memo);
 
-    let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
+    let nonce_authority = config.signers[nonce_authority];
+    let fee_payer = config.signers[fee_payer];
+    let message = if let Some(nonce_account) = nonce_account {
+        Message::new_with_nonce(
+            ixs,
+            Some(&fee_payer.pubkey()),
+            nonce_account,
+            &nonce_authority.pubkey(),
+        )
+    } else {
+        Message::new(&ixs, Some(&fee_payer.pubkey()))
+    };
     let mut transaction = Transaction::new_unsigned(message);
-    transaction.try_sign(&config.signers, latest_blockhash)?;
-    check_account_for_fee_with_commitment(
-        rpc_client,
-        &config.signers[0].pubkey(),
-        &transaction.message,
-        config.commitment,
-    )?;
-    let result = rpc_client.send_and_confirm_transaction_with_spinner(&transaction);
-    log_instruction_custom_error::<VoteError>(result, config)
+
+    if sign_only {
+        transaction.try_partial_sign(&config.signers, recent_blockhash)?;
+        return_signers_with_config(
+            &transaction,
+            &config.output_format,
+            &ReturnSignersConfig {
+                dump_transaction_message,
+            },
+        )
+    } else {
+        transaction.try_sign(&config.signers, recent_blockhash)?;
+        if let Some(nonce_account) = nonce_account {
+            let nonce_account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                nonce_account,
+                config.commitment,
+            )?;
+            check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
+        }
+        check_account_for_fee_with_commitment(
+            rpc_client,
+            &fee_payer.pubkey(),
+            &transaction.message,
+            config.commitment,
+        )?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner(&transaction);
+        log_instruction_custom_error::<VoteError>(result, config)
+    }
 }
 
 pub fn process_close_vote_account(
@@ -1043,7 +1187,10 @@ pub fn process_close_vote_account(
 mod tests {
     use super::*;
     use crate::{clap_app::get_clap_app, cli::parse_command};
-    use solana_sdk::signature::{read_keypair_file, write_keypair, Keypair, Signer};
+    use solana_sdk::{
+        hash::Hash,
+        signature::{read_keypair_file, write_keypair, Keypair, Signer},
+    };
     use tempfile::NamedTempFile;
 
     fn make_tmp_file() -> (String, NamedTempFile) {
@@ -1080,7 +1227,13 @@ mod tests {
                     vote_account_pubkey: pubkey,
                     new_authorized_pubkey: pubkey2,
                     vote_authorize: VoteAuthorize::Voter,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     memo: None,
+                    fee_payer: 0,
                     authorized: 0,
                     new_authorized: None,
                 },
@@ -1106,7 +1259,13 @@ mod tests {
                     vote_account_pubkey: pubkey,
                     new_authorized_pubkey: pubkey2,
                     vote_authorize: VoteAuthorize::Voter,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     memo: None,
+                    fee_payer: 0,
                     authorized: 1,
                     new_authorized: None,
                 },
@@ -1135,7 +1294,13 @@ mod tests {
                     vote_account_pubkey: pubkey,
                     new_authorized_pubkey: voter_keypair.pubkey(),
                     vote_authorize: VoteAuthorize::Voter,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     memo: None,
+                    fee_payer: 0,
                     authorized: 0,
                     new_authorized: Some(1),
                 },
@@ -1160,7 +1325,13 @@ mod tests {
                     vote_account_pubkey: pubkey,
                     new_authorized_pubkey: voter_keypair.pubkey(),
                     vote_authorize: VoteAuthorize::Voter,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     memo: None,
+                    fee_payer: 0,
                     authorized: 1,
                     new_authorized: Some(2),
                 },
@@ -1377,9 +1548,15 @@ mod tests {
                 command: CliCommand::WithdrawFromVoteAccount {
                     vote_account_pubkey: read_keypair_file(&keypair_file).unwrap().pubkey(),
                     destination_account_pubkey: pubkey,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     withdraw_authority: 0,
                     withdraw_amount: SpendAmount::Some(42_000_000_000),
                     memo: None,
+                    fee_payer: 0,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -1399,9 +1576,15 @@ mod tests {
                 command: CliCommand::WithdrawFromVoteAccount {
                     vote_account_pubkey: read_keypair_file(&keypair_file).unwrap().pubkey(),
                     destination_account_pubkey: pubkey,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     withdraw_authority: 0,
                     withdraw_amount: SpendAmount::All,
                     memo: None,
+                    fee_payer: 0,
                 },
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
             }
@@ -1426,9 +1609,15 @@ mod tests {
                 command: CliCommand::WithdrawFromVoteAccount {
                     vote_account_pubkey: read_keypair_file(&keypair_file).unwrap().pubkey(),
                     destination_account_pubkey: pubkey,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
                     withdraw_authority: 1,
                     withdraw_amount: SpendAmount::Some(42_000_000_000),
                     memo: None,
+                    fee_payer: 0,
                 },
                 signers: vec![
                     read_keypair_file(&default_keypair_file).unwrap().into(),
@@ -1437,6 +1626,42 @@ mod tests {
             }
         );
 
+        // Test WithdrawFromVoteAccount subcommand, signing offline with a separate fee payer
+        let blockhash = Hash::new_unique();
+        let test_withdraw_from_vote_account = test_commands.clone().get_matches_from(vec![
+            "test",
+            "withdraw-from-vote-account",
+            &keypair_file,
+            &pubkey_string,
+            "42",
+            "--authorized-withdrawer",
+            &withdraw_authority_file,
+            "--fee-payer",
+            &withdraw_authority_file,
+            "--sign-only",
+            "--blockhash",
+            &blockhash.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_withdraw_from_vote_account, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::WithdrawFromVoteAccount {
+                    vote_account_pubkey: read_keypair_file(&keypair_file).unwrap().pubkey(),
+                    destination_account_pubkey: pubkey,
+                    sign_only: true,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::None(blockhash),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    withdraw_authority: 0,
+                    withdraw_amount: SpendAmount::Some(42_000_000_000),
+                    memo: None,
+                    fee_payer: 0,
+                },
+                signers: vec![read_keypair_file(&withdraw_authority_file).unwrap().into()],
+            }
+        );
+
         // Test CloseVoteAccount subcommand
         let test_close_vote_account = test_commands.clone().get_matches_from(vec![
             "test",
@@ -19,7 +19,8 @@ use solana_clap_utils::{
     offline::*,
 };
 use solana_cli_output::{
-    display::build_balance_message, return_signers_with_config, CliAccount,
+    display::{build_balance_message, BuildBalanceMessageConfig},
+    return_signers_with_config, CliAccount, CliAddress, CliBalance, CliSignature,
     CliSignatureVerificationStatus, CliTransaction, CliTransactionConfirmation, OutputFormat,
     ReturnSignersConfig,
 };
@@ -31,12 +32,13 @@ use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     message::Message,
+    offchain_message::OffchainMessage,
     pubkey::Pubkey,
     signature::Signature,
     stake,
     system_instruction::{self, SystemError},
     system_program,
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
 };
 use solana_transaction_status::{EncodedTransaction, UiTransactionEncoding};
 use std::{fmt::Write as FmtWrite, fs::File, io::Write, sync::Arc};
@@ -207,6 +209,72 @@ impl WalletSubCommands for App<'_, '_> {
                         .help("The signer path to resolve")
                 )
         )
+        .subcommand(
+            SubCommand::with_name("sign-offchain-message")
+                .about("Sign off-chain message")
+                .arg(
+                    Arg::with_name("message")
+                        .index(1)
+                        .takes_value(true)
+                        .value_name("STRING")
+                        .required(true)
+                        .help("The message text to be signed")
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .value_name("VERSION")
+                        .required(false)
+                        .default_value("0")
+                        .validator(|p| match p.parse::<u8>() {
+                            Err(_) => Err(String::from("Must be unsigned integer")),
+                            Ok(_) => Ok(()),
+                        })
+                        .help("The off-chain message version")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("verify-offchain-signature")
+                .about("Verify off-chain message signature")
+                .alias("verify-offchain-message")
+                .arg(
+                    Arg::with_name("message")
+                        .index(1)
+                        .takes_value(true)
+                        .value_name("STRING")
+                        .required(true)
+                        .help("The text of the original message")
+                )
+                .arg(
+                    Arg::with_name("signature")
+                        .index(2)
+                        .value_name("SIGNATURE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The message signature to verify")
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .value_name("VERSION")
+                        .required(false)
+                        .default_value("0")
+                        .validator(|p| match p.parse::<u8>() {
+                            Err(_) => Err(String::from("Must be unsigned integer")),
+                            Ok(_) => Ok(()),
+                        })
+                        .help("The off-chain message version")
+                )
+                .arg(
+                    pubkey!(Arg::with_name("signer")
+                        .long("signer")
+                        .value_name("PUBKEY")
+                        .required(false),
+                        "The pubkey of the message signer (if different from config default). ")
+                )
+        )
         .subcommand(
             SubCommand::with_name("transfer")
                 .about("Transfer funds between system accounts")
@@ -355,6 +423,54 @@ pub fn parse_decode_transaction(matches: &ArgMatches<'_>) -> Result<CliCommandIn
     }
 }
 
+pub fn parse_sign_offchain_message(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let version: u8 = value_of(matches, "version").unwrap();
+    let message_text: String = value_of(matches, "message")
+        .ok_or_else(|| CliError::BadParameter("MESSAGE".to_string()))?;
+    let message = OffchainMessage::new(version, message_text.as_bytes())
+        .map_err(|_| CliError::BadParameter("VERSION or MESSAGE".to_string()))?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::SignOffchainMessage { message },
+        signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+    })
+}
+
+pub fn parse_verify_offchain_signature(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let version: u8 = value_of(matches, "version").unwrap();
+    let message_text: String = value_of(matches, "message")
+        .ok_or_else(|| CliError::BadParameter("MESSAGE".to_string()))?;
+    let message = OffchainMessage::new(version, message_text.as_bytes())
+        .map_err(|_| CliError::BadParameter("VERSION or MESSAGE".to_string()))?;
+
+    let signer_pubkey = pubkey_of_signer(matches, "signer", wallet_manager)?;
+    let signers = if signer_pubkey.is_some() {
+        vec![]
+    } else {
+        vec![default_signer.signer_from_path(matches, wallet_manager)?]
+    };
+
+    let signature = value_of(matches, "signature")
+        .ok_or_else(|| CliError::BadParameter("SIGNATURE".to_string()))?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::VerifyOffchainSignature {
+            signer_pubkey,
+            signature,
+            message,
+        },
+        signers,
+    })
+}
+
 pub fn parse_create_address_with_seed(
     matches: &ArgMatches<'_>,
     default_signer: &DefaultSigner,
@@ -526,7 +642,15 @@ pub fn process_balance(
         config.pubkey()?
     };
     let balance = rpc_client.get_balance(&pubkey)?;
-    Ok(build_balance_message(balance, use_lamports_unit, true))
+    let balance_output = CliBalance {
+        lamports: balance,
+        config: BuildBalanceMessageConfig {
+            use_lamports_unit,
+            show_unit: true,
+            ..BuildBalanceMessageConfig::default()
+        },
+    };
+    Ok(config.output_format.formatted_string(&balance_output))
 }
 
 pub fn process_confirm(
@@ -545,28 +669,32 @@ pub fn process_confirm(
                         RpcTransactionConfig {
                             encoding: Some(UiTransactionEncoding::Base64),
                             commitment: Some(CommitmentConfig::confirmed()),
+                            max_supported_transaction_version: Some(0),
                         },
                     ) {
                         Ok(confirmed_transaction) => {
-                            let decoded_transaction = confirmed_transaction
-                                .transaction
-                                .transaction
-                                .decode()
-                                .expect("Successful decode");
-                            let json_transaction = EncodedTransaction::encode(
-                                decoded_transaction.clone(),
-                                UiTransactionEncoding::Json,
-                            );
-
-                            transaction = Some(CliTransaction {
-                                transaction: json_transaction,
-                                meta: confirmed_transaction.transaction.meta,
-                                block_time: confirmed_transaction.block_time,
-                                slot: Some(confirmed_transaction.slot),
-                                decoded_transaction,
-                                prefix: "  ".to_string(),
-                                sigverify_status: vec![],
-                            });
+                            match confirmed_transaction.transaction.transaction.decode() {
+                                Some(decoded_transaction) => {
+                                    let json_transaction = EncodedTransaction::encode(
+                                        decoded_transaction.clone(),
+                                        UiTransactionEncoding::Json,
+                                    );
+
+                                    transaction = Some(CliTransaction {
+                                        transaction: json_transaction,
+                                        meta: confirmed_transaction.transaction.meta,
+                                        block_time: confirmed_transaction.block_time,
+                                        slot: Some(confirmed_transaction.slot),
+                                        decoded_transaction,
+                                        prefix: "  ".to_string(),
+                                        sigverify_status: vec![],
+                                    });
+                                }
+                                None => {
+                                    get_transaction_error =
+                                        Some("Unable to decode transaction".to_string());
+                                }
+                            }
                         }
                         Err(err) => {
                             get_transaction_error = Some(format!("{:?}", err));
@@ -594,7 +722,10 @@ pub fn process_confirm(
 }
 
 #[allow(clippy::unnecessary_wraps)]
-pub fn process_decode_transaction(config: &CliConfig, transaction: &Transaction) -> ProcessResult {
+pub fn process_decode_transaction(
+    config: &CliConfig,
+    transaction: &VersionedTransaction,
+) -> ProcessResult {
     let sigverify_status = CliSignatureVerificationStatus::verify_transaction(transaction);
     let decode_transaction = CliTransaction {
         decoded_transaction: transaction.clone(),
@@ -608,6 +739,35 @@ pub fn process_decode_transaction(config: &CliConfig, transaction: &Transaction)
     Ok(config.output_format.formatted_string(&decode_transaction))
 }
 
+pub fn process_sign_offchain_message(
+    config: &CliConfig,
+    message: &OffchainMessage,
+) -> ProcessResult {
+    let signature = CliSignature {
+        signature: message.sign(config.signers[0])?.to_string(),
+    };
+    Ok(config.output_format.formatted_string(&signature))
+}
+
+pub fn process_verify_offchain_signature(
+    config: &CliConfig,
+    signer_pubkey: &Option<Pubkey>,
+    signature: &Signature,
+    message: &OffchainMessage,
+) -> ProcessResult {
+    let signer = if let Some(pubkey) = signer_pubkey {
+        *pubkey
+    } else {
+        config.signers[0].pubkey()
+    };
+
+    if message.verify(&signer, signature)? {
+        Ok("Signature is valid".to_string())
+    } else {
+        Err(CliError::InvalidSignature.into())
+    }
+}
+
 pub fn process_create_address_with_seed(
     config: &CliConfig,
     from_pubkey: Option<&Pubkey>,
@@ -620,7 +780,10 @@ pub fn process_create_address_with_seed(
         config.pubkey()?
     };
     let address = Pubkey::create_with_seed(&from_pubkey, seed, program_id)?;
-    Ok(address.to_string())
+    let address_output = CliAddress {
+        address: address.to_string(),
+    };
+    Ok(config.output_format.formatted_string(&address_output))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -46,6 +46,12 @@ impl ClientErrorKind {
             _ => None,
         }
     }
+
+    /// Whether the request failed to reach the node or get its response, so that sending it
+    /// again may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::Reqwest(_))
+    }
 }
 
 impl From<TransportError> for ClientErrorKind {
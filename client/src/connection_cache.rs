@@ -0,0 +1,334 @@
+//! A cache of connections to the TPUs of leaders, so that clients sending transactions reuse a
+//! connection per leader instead of creating sockets as they go.  How a connection is made is
+//! up to a `TpuConnectionFactory`, the only one provided sends over UDP from a single shared
+//! socket.  Other transports, like QUIC, are left to factories outside of this crate.
+
+use {
+    crate::rpc_response::RpcContactInfo,
+    solana_sdk::{
+        clock::Slot,
+        pubkey::Pubkey,
+        transport::{Result as TransportResult, TransportError},
+    },
+    std::{
+        collections::HashMap,
+        fmt,
+        net::{SocketAddr, UdpSocket},
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Number of leaders the cache keeps a connection to before evicting the least recently used
+pub const DEFAULT_MAX_CONNECTIONS: usize = 1024;
+
+/// A connection to the TPU of one leader
+pub trait TpuConnection: Send + Sync {
+    fn tpu_addr(&self) -> &SocketAddr;
+
+    fn send_wire_transaction(&self, wire_transaction: &[u8]) -> TransportResult<()>;
+
+    fn send_wire_transaction_batch(&self, wire_transactions: &[Vec<u8>]) -> TransportResult<()> {
+        wire_transactions
+            .iter()
+            .try_for_each(|wire_transaction| self.send_wire_transaction(wire_transaction))
+    }
+}
+
+/// Makes the connections of a `ConnectionCache`, one per transport
+pub trait TpuConnectionFactory: Send + Sync {
+    fn new_connection(&self, tpu_addr: SocketAddr) -> TransportResult<Arc<dyn TpuConnection>>;
+}
+
+pub struct UdpTpuConnection {
+    socket: Arc<UdpSocket>,
+    tpu_addr: SocketAddr,
+}
+
+impl TpuConnection for UdpTpuConnection {
+    fn tpu_addr(&self) -> &SocketAddr {
+        &self.tpu_addr
+    }
+
+    fn send_wire_transaction(&self, wire_transaction: &[u8]) -> TransportResult<()> {
+        self.socket.send_to(wire_transaction, self.tpu_addr)?;
+        Ok(())
+    }
+}
+
+/// UDP is connectionless, so every connection sends from the same socket
+pub struct UdpConnectionFactory {
+    socket: Arc<UdpSocket>,
+}
+
+impl UdpConnectionFactory {
+    pub fn new() -> TransportResult<Self> {
+        Ok(Self {
+            socket: Arc::new(UdpSocket::bind("0.0.0.0:0")?),
+        })
+    }
+}
+
+impl TpuConnectionFactory for UdpConnectionFactory {
+    fn new_connection(&self, tpu_addr: SocketAddr) -> TransportResult<Arc<dyn TpuConnection>> {
+        Ok(Arc::new(UdpTpuConnection {
+            socket: self.socket.clone(),
+            tpu_addr,
+        }))
+    }
+}
+
+struct CachedConnection {
+    connection: Arc<dyn TpuConnection>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CachedConnections {
+    clock: u64,
+    connections: HashMap<SocketAddr, CachedConnection>,
+}
+
+/// LRU cache of connections, keyed by TPU address
+pub struct ConnectionCache {
+    factory: Box<dyn TpuConnectionFactory>,
+    max_connections: usize,
+    cached: Mutex<CachedConnections>,
+}
+
+impl fmt::Debug for ConnectionCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConnectionCache")
+            .field("max_connections", &self.max_connections)
+            .field(
+                "num_connections",
+                &self.cached.lock().unwrap().connections.len(),
+            )
+            .finish()
+    }
+}
+
+impl Default for ConnectionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONNECTIONS)
+    }
+}
+
+impl ConnectionCache {
+    /// A cache of UDP connections
+    pub fn new(max_connections: usize) -> Self {
+        let factory = UdpConnectionFactory::new().expect("bind UDP socket");
+        Self::new_with_factory(Box::new(factory), max_connections)
+    }
+
+    pub fn new_with_factory(
+        factory: Box<dyn TpuConnectionFactory>,
+        max_connections: usize,
+    ) -> Self {
+        Self {
+            factory,
+            max_connections: max_connections.max(1),
+            cached: Mutex::default(),
+        }
+    }
+
+    /// The connection to `tpu_addr`, made if it isn't cached
+    pub fn get_connection(&self, tpu_addr: &SocketAddr) -> TransportResult<Arc<dyn TpuConnection>> {
+        let mut cached = self.cached.lock().unwrap();
+        cached.clock += 1;
+        let clock = cached.clock;
+        if let Some(cached_connection) = cached.connections.get_mut(tpu_addr) {
+            cached_connection.last_used = clock;
+            return Ok(cached_connection.connection.clone());
+        }
+
+        let connection = self.factory.new_connection(*tpu_addr)?;
+        if cached.connections.len() >= self.max_connections {
+            let least_recently_used = cached
+                .connections
+                .iter()
+                .min_by_key(|(_, cached_connection)| cached_connection.last_used)
+                .map(|(tpu_addr, _)| *tpu_addr);
+            if let Some(least_recently_used) = least_recently_used {
+                cached.connections.remove(&least_recently_used);
+            }
+        }
+        cached.connections.insert(
+            *tpu_addr,
+            CachedConnection {
+                connection: connection.clone(),
+                last_used: clock,
+            },
+        );
+        Ok(connection)
+    }
+
+    pub fn send_wire_transaction(
+        &self,
+        tpu_addr: &SocketAddr,
+        wire_transaction: &[u8],
+    ) -> TransportResult<()> {
+        self.get_connection(tpu_addr)?
+            .send_wire_transaction(wire_transaction)
+    }
+
+    pub fn send_wire_transaction_batch(
+        &self,
+        tpu_addr: &SocketAddr,
+        wire_transactions: &[Vec<u8>],
+    ) -> TransportResult<()> {
+        self.get_connection(tpu_addr)?
+            .send_wire_transaction_batch(wire_transactions)
+    }
+
+    /// Send from tokio's blocking thread pool, as connections only have a blocking API
+    pub async fn send_wire_transaction_async(
+        self: &Arc<Self>,
+        tpu_addr: SocketAddr,
+        wire_transaction: Vec<u8>,
+    ) -> TransportResult<()> {
+        let connection_cache = self.clone();
+        tokio::task::spawn_blocking(move || {
+            connection_cache.send_wire_transaction(&tpu_addr, &wire_transaction)
+        })
+        .await
+        .map_err(|err| TransportError::Custom(err.to_string()))?
+    }
+}
+
+/// The leaders of upcoming slots and their TPU addresses, as last fetched from an RPC node, for
+/// clients sending transactions straight to the leaders
+#[derive(Default)]
+pub(crate) struct LeaderTpus {
+    first_slot: Slot,
+    leaders: Vec<Pubkey>,
+    tpu_addrs: HashMap<Pubkey, SocketAddr>,
+}
+
+impl LeaderTpus {
+    /// The TPU addresses of the leaders of the `fanout_slots` slots from `slot`, or `None` if
+    /// the cached leaders don't cover `slot`.  Leaders without a known TPU are skipped
+    pub(crate) fn get(&self, slot: Slot, fanout_slots: u64) -> Option<Vec<SocketAddr>> {
+        let start = slot.checked_sub(self.first_slot)? as usize;
+        if start >= self.leaders.len() {
+            return None;
+        }
+        let end = start
+            .saturating_add(fanout_slots as usize)
+            .min(self.leaders.len());
+        let mut tpu_addrs = vec![];
+        for leader in &self.leaders[start..end] {
+            if let Some(tpu_addr) = self.tpu_addrs.get(leader) {
+                if !tpu_addrs.contains(tpu_addr) {
+                    tpu_addrs.push(*tpu_addr);
+                }
+            }
+        }
+        Some(tpu_addrs)
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        first_slot: Slot,
+        leaders: Vec<Pubkey>,
+        cluster_nodes: Vec<RpcContactInfo>,
+    ) {
+        self.first_slot = first_slot;
+        self.leaders = leaders;
+        self.tpu_addrs = cluster_nodes
+            .into_iter()
+            .filter_map(|contact_info| Some((contact_info.pubkey.parse().ok()?, contact_info.tpu?)))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::time::Duration};
+
+    fn tpu_addrs(num: usize) -> Vec<SocketAddr> {
+        (0..num)
+            .map(|port| SocketAddr::from(([127, 0, 0, 1], 8000 + port as u16)))
+            .collect()
+    }
+
+    #[test]
+    fn test_connection_cache_lru() {
+        let connection_cache = ConnectionCache::new(2);
+        let tpu_addrs = tpu_addrs(3);
+        let connection = connection_cache.get_connection(&tpu_addrs[0]).unwrap();
+        assert_eq!(connection.tpu_addr(), &tpu_addrs[0]);
+        connection_cache.get_connection(&tpu_addrs[1]).unwrap();
+        // The same connection is returned while it is cached
+        assert!(Arc::ptr_eq(
+            &connection,
+            &connection_cache.get_connection(&tpu_addrs[0]).unwrap()
+        ));
+
+        // Evicts the least recently used connection
+        connection_cache.get_connection(&tpu_addrs[2]).unwrap();
+        let cached = connection_cache.cached.lock().unwrap();
+        assert_eq!(cached.connections.len(), 2);
+        assert!(cached.connections.contains_key(&tpu_addrs[0]));
+        assert!(!cached.connections.contains_key(&tpu_addrs[1]));
+    }
+
+    #[test]
+    fn test_leader_tpus() {
+        let leaders: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let tpu_addrs = tpu_addrs(2);
+        let cluster_nodes = leaders[..2]
+            .iter()
+            .zip(&tpu_addrs)
+            .map(|(leader, tpu_addr)| RpcContactInfo {
+                pubkey: leader.to_string(),
+                gossip: None,
+                tpu: Some(*tpu_addr),
+                rpc: None,
+                version: None,
+                feature_set: None,
+                shred_version: None,
+            })
+            .collect();
+        let mut leader_tpus = LeaderTpus::default();
+        assert_eq!(leader_tpus.get(0, 1), None);
+
+        leader_tpus.update(
+            10,
+            vec![leaders[0], leaders[0], leaders[1], leaders[2]],
+            cluster_nodes,
+        );
+        assert_eq!(leader_tpus.get(9, 1), None);
+        assert_eq!(leader_tpus.get(10, 2), Some(vec![tpu_addrs[0]]));
+        assert_eq!(
+            leader_tpus.get(11, 2),
+            Some(vec![tpu_addrs[0], tpu_addrs[1]])
+        );
+        // The last leader has no TPU
+        assert_eq!(leader_tpus.get(13, 2), Some(vec![]));
+        assert_eq!(leader_tpus.get(14, 1), None);
+    }
+
+    #[test]
+    fn test_connection_cache_send() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let tpu_addr = receiver.local_addr().unwrap();
+        let connection_cache = Arc::new(ConnectionCache::default());
+
+        connection_cache
+            .send_wire_transaction(&tpu_addr, &[1, 2, 3])
+            .unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime
+            .block_on(connection_cache.send_wire_transaction_async(tpu_addr, vec![4, 5]))
+            .unwrap();
+
+        let mut buf = [0; 8];
+        assert_eq!(receiver.recv(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(receiver.recv(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], &[4, 5]);
+    }
+}
@@ -11,7 +11,7 @@ use {
     log::*,
     reqwest::{
         self,
-        header::{CONTENT_TYPE, RETRY_AFTER},
+        header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER},
         StatusCode,
     },
     std::{
@@ -24,11 +24,107 @@ use {
     },
 };
 
+/// Retry-After values at least this long are ignored in favor of the backoff delay
+const MAX_RETRY_AFTER_SECS: u64 = 120;
+
+/// How many times a class of failed requests is retried, and how long to wait in between.  The
+/// delay doubles after every retry, up to `max_delay`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    pub max_retries: usize,
+    pub delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Backoff {
+    fn delay(&self, retries: usize) -> Duration {
+        let multiplier = 2u32.saturating_pow(retries.min(u32::MAX as usize) as u32);
+        self.delay
+            .checked_mul(multiplier)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Which failed requests an [`HttpSender`] retries, by class of error.  `None` returns the
+/// error to the caller right away
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// HTTP 429 responses.  The delay requested by the server with Retry-After is honored when
+    /// it is under two minutes
+    pub rate_limited: Option<Backoff>,
+    /// HTTP 5xx responses
+    pub server_error: Option<Backoff>,
+    /// Requests that timed out or failed to connect
+    pub transport_error: Option<Backoff>,
+    /// Requests the node rejected because it is behind the cluster
+    pub node_unhealthy: Option<Backoff>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            rate_limited: Some(Backoff {
+                max_retries: 5,
+                delay: Duration::from_millis(500),
+                max_delay: Duration::from_millis(500),
+            }),
+            server_error: None,
+            transport_error: None,
+            node_unhealthy: None,
+        }
+    }
+}
+
+/// The outcome of one attempt of a request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequestOutcome {
+    Response(StatusCode),
+    TransportError(String),
+}
+
+/// Hooks run around every attempt of every request an [`HttpSender`] makes, for example to add
+/// authentication headers, log requests or record metrics
+pub trait HttpSenderMiddleware: Send + Sync {
+    /// Called before each attempt, `headers` are added to the request
+    fn before_request(&self, _request: RpcRequest, _headers: &mut HeaderMap) {}
+
+    /// Called after each attempt, `attempt` counts from zero
+    fn after_response(
+        &self,
+        _request: RpcRequest,
+        _attempt: usize,
+        _elapsed: Duration,
+        _outcome: &RequestOutcome,
+    ) {
+    }
+}
+
+#[derive(Clone)]
+pub struct HttpSenderConfig {
+    pub timeout: Duration,
+    pub retry_policy: RetryPolicy,
+    /// Run in order before each attempt, and in order after it
+    pub middleware: Vec<Arc<dyn HttpSenderMiddleware>>,
+}
+
+impl Default for HttpSenderConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retry_policy: RetryPolicy::default(),
+            middleware: vec![],
+        }
+    }
+}
+
 pub struct HttpSender {
     client: Arc<reqwest::blocking::Client>,
     url: String,
     request_id: AtomicU64,
     stats: RwLock<RpcTransportStats>,
+    retry_policy: RetryPolicy,
+    middleware: Vec<Arc<dyn HttpSenderMiddleware>>,
 }
 
 /// The standard [`RpcSender`] over HTTP.
@@ -38,13 +134,31 @@ impl HttpSender {
     /// The URL is an HTTP URL, usually for port 8899, as in
     /// "http://localhost:8899". The sender has a default timeout of 30 seconds.
     pub fn new(url: String) -> Self {
-        Self::new_with_timeout(url, Duration::from_secs(30))
+        Self::new_with_config(url, HttpSenderConfig::default())
     }
 
     /// Create an HTTP RPC sender.
     ///
     /// The URL is an HTTP URL, usually for port 8899.
     pub fn new_with_timeout(url: String, timeout: Duration) -> Self {
+        Self::new_with_config(
+            url,
+            HttpSenderConfig {
+                timeout,
+                ..HttpSenderConfig::default()
+            },
+        )
+    }
+
+    /// Create an HTTP RPC sender with a retry policy and middleware.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899.
+    pub fn new_with_config(url: String, config: HttpSenderConfig) -> Self {
+        let HttpSenderConfig {
+            timeout,
+            retry_policy,
+            middleware,
+        } = config;
         // `reqwest::blocking::Client` panics if run in a tokio async context.  Shuttle the
         // request to a different tokio thread to avoid this
         let client = Arc::new(
@@ -61,6 +175,78 @@ impl HttpSender {
             url,
             request_id: AtomicU64::new(0),
             stats: RwLock::new(RpcTransportStats::default()),
+            retry_policy,
+            middleware,
+        }
+    }
+}
+
+/// Retries made so far of each class of error
+#[derive(Default)]
+pub(crate) struct Retries {
+    rate_limited: usize,
+    server_error: usize,
+    transport_error: usize,
+    node_unhealthy: usize,
+}
+
+/// The delay before the next retry under `backoff`, `None` if the retries are used up
+fn next_retry_delay(backoff: Option<Backoff>, retries: &mut usize) -> Option<Duration> {
+    let backoff = backoff?;
+    if *retries >= backoff.max_retries {
+        return None;
+    }
+    let delay = backoff.delay(*retries);
+    *retries += 1;
+    Some(delay)
+}
+
+impl RetryPolicy {
+    /// The delay before retrying a request that failed to reach the server
+    pub(crate) fn transport_error_delay(
+        &self,
+        err: &reqwest::Error,
+        retries: &mut Retries,
+    ) -> Option<Duration> {
+        if err.is_timeout() || err.is_connect() {
+            next_retry_delay(self.transport_error, &mut retries.transport_error)
+        } else {
+            None
+        }
+    }
+
+    /// The delay before retrying a request the server answered with an error status
+    pub(crate) fn status_delay(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        retries: &mut Retries,
+    ) -> Option<Duration> {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let delay = next_retry_delay(self.rate_limited, &mut retries.rate_limited)?;
+            let retry_after = headers
+                .get(RETRY_AFTER)
+                .and_then(|retry_after| retry_after.to_str().ok())
+                .and_then(|retry_after| retry_after.parse::<u64>().ok())
+                .filter(|retry_after| *retry_after < MAX_RETRY_AFTER_SECS);
+            Some(retry_after.map(Duration::from_secs).unwrap_or(delay))
+        } else if status.is_server_error() {
+            next_retry_delay(self.server_error, &mut retries.server_error)
+        } else {
+            None
+        }
+    }
+
+    /// The delay before retrying a request the node rejected with the JSON-RPC error `json`
+    pub(crate) fn rpc_error_delay(
+        &self,
+        json: &serde_json::Value,
+        retries: &mut Retries,
+    ) -> Option<Duration> {
+        if json["error"]["code"] == rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY {
+            next_retry_delay(self.node_unhealthy, &mut retries.node_unhealthy)
+        } else {
+            None
         }
     }
 }
@@ -71,14 +257,65 @@ struct RpcErrorObject {
     message: String,
 }
 
-struct StatsUpdater<'a> {
+/// The result of a JSON-RPC response, or the error it holds
+pub(crate) fn parse_json_rpc_response(mut json: serde_json::Value) -> Result<serde_json::Value> {
+    if !json["error"].is_object() {
+        return Ok(json["result"].take());
+    }
+    match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
+        Ok(rpc_error_object) => {
+            let data = match rpc_error_object.code {
+                rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                    match serde_json::from_value::<RpcSimulateTransactionResult>(
+                        json["error"]["data"].clone(),
+                    ) {
+                        Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
+                        Err(err) => {
+                            debug!(
+                                "Failed to deserialize RpcSimulateTransactionResult: {:?}",
+                                err
+                            );
+                            RpcResponseErrorData::Empty
+                        }
+                    }
+                }
+                rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+                    match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(
+                        json["error"]["data"].clone(),
+                    ) {
+                        Ok(rpc_custom_error::NodeUnhealthyErrorData { num_slots_behind }) => {
+                            RpcResponseErrorData::NodeUnhealthy { num_slots_behind }
+                        }
+                        Err(_err) => RpcResponseErrorData::Empty,
+                    }
+                }
+                _ => RpcResponseErrorData::Empty,
+            };
+
+            Err(RpcError::RpcResponseError {
+                code: rpc_error_object.code,
+                message: rpc_error_object.message,
+                data,
+            }
+            .into())
+        }
+        Err(err) => Err(RpcError::RpcRequestError(format!(
+            "Failed to deserialize RPC error response: {} [{}]",
+            serde_json::to_string(&json["error"]).unwrap(),
+            err
+        ))
+        .into()),
+    }
+}
+
+pub(crate) struct StatsUpdater<'a> {
     stats: &'a RwLock<RpcTransportStats>,
     request_start_time: Instant,
     rate_limited_time: Duration,
 }
 
 impl<'a> StatsUpdater<'a> {
-    fn new(stats: &'a RwLock<RpcTransportStats>) -> Self {
+    pub(crate) fn new(stats: &'a RwLock<RpcTransportStats>) -> Self {
         Self {
             stats,
             request_start_time: Instant::now(),
@@ -86,7 +323,7 @@ impl<'a> StatsUpdater<'a> {
         }
     }
 
-    fn add_rate_limited_time(&mut self, duration: Duration) {
+    pub(crate) fn add_rate_limited_time(&mut self, duration: Duration) {
         self.rate_limited_time += duration;
     }
 }
@@ -111,8 +348,13 @@ impl RpcSender for HttpSender {
         let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
         let request_json = request.build_request_json(request_id, params).to_string();
 
-        let mut too_many_requests_retries = 5;
-        loop {
+        let mut retries = Retries::default();
+        for attempt in 0.. {
+            let mut headers = HeaderMap::new();
+            for middleware in &self.middleware {
+                middleware.before_request(request, &mut headers);
+            }
+            let attempt_start_time = Instant::now();
             // `reqwest::blocking::Client` panics if run in a tokio async context.  Shuttle the
             // request to a different tokio thread to avoid this
             let response = {
@@ -122,88 +364,152 @@ impl RpcSender for HttpSender {
                     client
                         .post(&self.url)
                         .header(CONTENT_TYPE, "application/json")
+                        .headers(headers)
                         .body(request_json)
                         .send()
                 })
-            }?;
+            };
+            let outcome = match &response {
+                Ok(response) => RequestOutcome::Response(response.status()),
+                Err(err) => RequestOutcome::TransportError(err.to_string()),
+            };
+            for middleware in &self.middleware {
+                middleware.after_response(request, attempt, attempt_start_time.elapsed(), &outcome);
+            }
 
-            if !response.status().is_success() {
-                if response.status() == StatusCode::TOO_MANY_REQUESTS
-                    && too_many_requests_retries > 0
-                {
-                    let mut duration = Duration::from_millis(500);
-                    if let Some(retry_after) = response.headers().get(RETRY_AFTER) {
-                        if let Ok(retry_after) = retry_after.to_str() {
-                            if let Ok(retry_after) = retry_after.parse::<u64>() {
-                                if retry_after < 120 {
-                                    duration = Duration::from_secs(retry_after);
-                                }
-                            }
-                        }
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    if let Some(delay) = self.retry_policy.transport_error_delay(&err, &mut retries)
+                    {
+                        debug!("Request failed: {:?}, pausing for {:?}", err, delay);
+                        sleep(delay);
+                        continue;
                     }
+                    return Err(err.into());
+                }
+            };
 
-                    too_many_requests_retries -= 1;
+            if !response.status().is_success() {
+                if let Some(delay) = self.retry_policy.status_delay(
+                    response.status(),
+                    response.headers(),
+                    &mut retries,
+                ) {
                     debug!(
-                                "Too many requests: server responded with {:?}, {} retries left, pausing for {:?}",
-                                response, too_many_requests_retries, duration
-                            );
-
-                    sleep(duration);
-                    stats_updater.add_rate_limited_time(duration);
+                        "Server responded with {:?}, pausing for {:?}",
+                        response, delay
+                    );
+                    sleep(delay);
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        stats_updater.add_rate_limited_time(delay);
+                    }
                     continue;
                 }
                 return Err(response.error_for_status().unwrap_err().into());
             }
 
-            let mut json =
-                tokio::task::block_in_place(move || response.json::<serde_json::Value>())?;
-            if json["error"].is_object() {
-                return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
-                    Ok(rpc_error_object) => {
-                        let data = match rpc_error_object.code {
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
-                                        match serde_json::from_value::<RpcSimulateTransactionResult>(json["error"]["data"].clone()) {
-                                            Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
-                                            Err(err) => {
-                                                debug!("Failed to deserialize RpcSimulateTransactionResult: {:?}", err);
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
-                                        match serde_json::from_value::<rpc_custom_error::NodeUnhealthyErrorData>(json["error"]["data"].clone()) {
-                                            Ok(rpc_custom_error::NodeUnhealthyErrorData {num_slots_behind}) => RpcResponseErrorData::NodeUnhealthy {num_slots_behind},
-                                            Err(_err) => {
-                                                RpcResponseErrorData::Empty
-                                            }
-                                        }
-                                    },
-                                    _ => RpcResponseErrorData::Empty
-                                };
-
-                        Err(RpcError::RpcResponseError {
-                            code: rpc_error_object.code,
-                            message: rpc_error_object.message,
-                            data,
-                        }
-                        .into())
-                    }
-                    Err(err) => Err(RpcError::RpcRequestError(format!(
-                        "Failed to deserialize RPC error response: {} [{}]",
-                        serde_json::to_string(&json["error"]).unwrap(),
-                        err
-                    ))
-                    .into()),
-                };
+            let json = tokio::task::block_in_place(move || response.json::<serde_json::Value>())?;
+            if let Some(delay) = self.retry_policy.rpc_error_delay(&json, &mut retries) {
+                debug!("Node is unhealthy, pausing for {:?}", delay);
+                sleep(delay);
+                continue;
             }
-            return Ok(json["result"].take());
+            return parse_json_rpc_response(json);
         }
+        unreachable!()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {
+        super::*,
+        std::{net::TcpListener, sync::Mutex},
+    };
+
+    #[derive(Default)]
+    struct RecordingMiddleware {
+        outcomes: Mutex<Vec<(usize, RequestOutcome)>>,
+    }
+
+    impl HttpSenderMiddleware for RecordingMiddleware {
+        fn after_response(
+            &self,
+            _request: RpcRequest,
+            attempt: usize,
+            _elapsed: Duration,
+            outcome: &RequestOutcome,
+        ) {
+            self.outcomes
+                .lock()
+                .unwrap()
+                .push((attempt, outcome.clone()));
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff {
+            max_retries: 3,
+            delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+        };
+        let mut retries = 0;
+        let delays: Vec<_> =
+            std::iter::from_fn(|| next_retry_delay(Some(backoff), &mut retries)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300)
+            ]
+        );
+        assert_eq!(backoff.delay(usize::MAX), backoff.max_delay);
+        assert_eq!(next_retry_delay(None, &mut 0), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_http_sender_retry_transport_error() {
+        // Nothing listens on the port once the listener is dropped, so every attempt fails to
+        // connect
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let middleware = Arc::new(RecordingMiddleware::default());
+        let http_sender = HttpSender::new_with_config(
+            format!("http://{}", addr),
+            HttpSenderConfig {
+                retry_policy: RetryPolicy {
+                    transport_error: Some(Backoff {
+                        max_retries: 2,
+                        delay: Duration::from_millis(1),
+                        max_delay: Duration::from_millis(1),
+                    }),
+                    ..RetryPolicy::default()
+                },
+                middleware: vec![middleware.clone()],
+                ..HttpSenderConfig::default()
+            },
+        );
+        assert!(http_sender
+            .send(RpcRequest::GetVersion, serde_json::Value::Null)
+            .is_err());
+
+        let outcomes = middleware.outcomes.lock().unwrap();
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|(attempt, _)| *attempt)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert!(outcomes
+            .iter()
+            .all(|(_, outcome)| matches!(outcome, RequestOutcome::TransportError(_))));
+    }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn http_sender_on_tokio_multi_thread() {
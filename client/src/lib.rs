@@ -4,8 +4,10 @@ extern crate serde_derive;
 
 pub mod blockhash_query;
 pub mod client_error;
+pub mod connection_cache;
 pub mod http_sender;
 pub mod mock_sender;
+pub mod nonblocking;
 pub mod nonce_utils;
 pub mod perf_utils;
 pub mod pubsub_client;
@@ -3,11 +3,13 @@
 use {
     crate::{
         client_error::Result,
+        nonblocking,
         rpc_config::RpcBlockProductionConfig,
         rpc_request::RpcRequest,
         rpc_response::{
             Response, RpcAccountBalance, RpcBlockProduction, RpcBlockProductionRange, RpcBlockhash,
-            RpcConfirmedTransactionStatusWithSignature, RpcContactInfo, RpcFees, RpcIdentity,
+            RpcConfirmedTransactionStatusWithSignature, RpcContactInfo, RpcEquivocationEvidence,
+            RpcFeatureActivationStatus, RpcFeatureStatus, RpcFees, RpcIdentity,
             RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcKeyedAccount,
             RpcPerfSample, RpcResponseContext, RpcSimulateTransactionResult, RpcSnapshotSlotInfo,
             RpcStakeActivation, RpcSupply, RpcVersionInfo, RpcVoteAccountInfo,
@@ -15,6 +17,7 @@ use {
         },
         rpc_sender::*,
     },
+    async_trait::async_trait,
     serde_json::{json, Number, Value},
     solana_account_decoder::{UiAccount, UiAccountEncoding},
     solana_sdk::{
@@ -209,6 +212,7 @@ impl RpcSender for MockSender {
                                         accounts: vec![0, 1],
                                         data: "3Bxs49DitAvXtoDR".to_string(),
                                     }],
+                                    address_table_lookups: None,
                                 })
                         }),
                     meta: Some(UiTransactionStatusMeta {
@@ -222,6 +226,9 @@ impl RpcSender for MockSender {
                             pre_token_balances: None,
                             post_token_balances: None,
                             rewards: None,
+                            return_data: None,
+                            loaded_addresses: None,
+                            log_entries: None,
                         }),
                 },
                 block_time: Some(1628633791),
@@ -308,6 +315,7 @@ impl RpcSender for MockSender {
                         node_pubkey: PUBKEY.to_string(),
                         activated_stake: 0,
                         commission: 0,
+                        epoch_start_commission: None,
                         epoch_vote_account: false,
                         epoch_credits: vec![],
                         last_vote: 0,
@@ -333,6 +341,9 @@ impl RpcSender for MockSender {
                     logs: None,
                     accounts: None,
                     units_consumed: None,
+                    return_data: None,
+                    log_entries: None,
+                    inner_instructions_invoke_units: None,
                 },
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
@@ -396,6 +407,9 @@ impl RpcSender for MockSender {
             }
             "getBlockTime" => serde_json::to_value(UnixTimestamp::default())?,
             "getEpochSchedule" => serde_json::to_value(EpochSchedule::default())?,
+            "getEquivocationEvidence" => {
+                serde_json::to_value(Vec::<RpcEquivocationEvidence>::new())?
+            }
             "getRecentPerformanceSamples" => serde_json::to_value(vec![RpcPerfSample {
                 slot: 347873,
                 num_transactions: 125,
@@ -428,13 +442,23 @@ impl RpcSender for MockSender {
                     post_balance: 499999442500,
                     commission: None,
                 })])?,
+            "getFeatureStatuses" => serde_json::to_value(Response {
+                context: RpcResponseContext { slot: 1 },
+                value: vec![RpcFeatureStatus {
+                    id: PUBKEY.to_string(),
+                    description: "feature".to_string(),
+                    status: RpcFeatureActivationStatus::Active,
+                    activation_slot: Some(0),
+                    activation_epoch: Some(0),
+                }],
+            })?,
             "minimumLedgerSlot" => json![123],
             "getMaxRetransmitSlot" => json![123],
             "getMultipleAccounts" => serde_json::to_value(Response {
                 context: RpcResponseContext { slot: 1 },
                 value: vec![Value::Null, Value::Null]
             })?,
-            "getProgramAccounts" => {
+            "getProgramAccounts" | "getProgramAccountsByPrefix" => {
                 let pubkey = Pubkey::from_str(&PUBKEY.to_string()).unwrap();
                 let account = Account {
                     lamports: 1_000_000,
@@ -461,3 +485,19 @@ impl RpcSender for MockSender {
         Ok(val)
     }
 }
+
+/// The mocks respond right away, so the async sender answers with the blocking one
+#[async_trait]
+impl nonblocking::rpc_sender::RpcSender for MockSender {
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcSender::get_transport_stats(self)
+    }
+
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        RpcSender::send(self, request, params)
+    }
+}
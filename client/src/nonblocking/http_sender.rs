@@ -0,0 +1,203 @@
+//! The standard async [`RpcSender`] over HTTP.
+
+use {
+    crate::{
+        client_error::Result,
+        http_sender::{
+            parse_json_rpc_response, HttpSenderConfig, HttpSenderMiddleware, RequestOutcome,
+            Retries, RetryPolicy, StatsUpdater,
+        },
+        nonblocking::rpc_sender::RpcSender,
+        rpc_request::RpcRequest,
+        rpc_sender::RpcTransportStats,
+    },
+    async_trait::async_trait,
+    log::*,
+    reqwest::{
+        self,
+        header::{HeaderMap, CONTENT_TYPE},
+        StatusCode,
+    },
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+        time::{Duration, Instant},
+    },
+    tokio::time::sleep,
+};
+
+pub struct HttpSender {
+    client: reqwest::Client,
+    url: String,
+    request_id: AtomicU64,
+    stats: RwLock<RpcTransportStats>,
+    retry_policy: RetryPolicy,
+    middleware: Vec<Arc<dyn HttpSenderMiddleware>>,
+}
+
+/// The standard async [`RpcSender`] over HTTP.
+impl HttpSender {
+    /// Create an HTTP RPC sender.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899, as in
+    /// "http://localhost:8899". The sender has a default timeout of 30 seconds.
+    pub fn new(url: String) -> Self {
+        Self::new_with_config(url, HttpSenderConfig::default())
+    }
+
+    /// Create an HTTP RPC sender.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899.
+    pub fn new_with_timeout(url: String, timeout: Duration) -> Self {
+        Self::new_with_config(
+            url,
+            HttpSenderConfig {
+                timeout,
+                ..HttpSenderConfig::default()
+            },
+        )
+    }
+
+    /// Create an HTTP RPC sender with a retry policy and middleware, see the
+    /// blocking [`HttpSender`](crate::http_sender::HttpSender).
+    ///
+    /// The URL is an HTTP URL, usually for port 8899.
+    pub fn new_with_config(url: String, config: HttpSenderConfig) -> Self {
+        let HttpSenderConfig {
+            timeout,
+            retry_policy,
+            middleware,
+        } = config;
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("build rpc client");
+
+        Self {
+            client,
+            url,
+            request_id: AtomicU64::new(0),
+            stats: RwLock::new(RpcTransportStats::default()),
+            retry_policy,
+            middleware,
+        }
+    }
+}
+
+#[async_trait]
+impl RpcSender for HttpSender {
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.stats.read().unwrap().clone()
+    }
+
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let mut stats_updater = StatsUpdater::new(&self.stats);
+
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let request_json = request.build_request_json(request_id, params).to_string();
+
+        let mut retries = Retries::default();
+        for attempt in 0.. {
+            let mut headers = HeaderMap::new();
+            for middleware in &self.middleware {
+                middleware.before_request(request, &mut headers);
+            }
+            let attempt_start_time = Instant::now();
+            let response = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .headers(headers)
+                .body(request_json.clone())
+                .send()
+                .await;
+            let outcome = match &response {
+                Ok(response) => RequestOutcome::Response(response.status()),
+                Err(err) => RequestOutcome::TransportError(err.to_string()),
+            };
+            for middleware in &self.middleware {
+                middleware.after_response(request, attempt, attempt_start_time.elapsed(), &outcome);
+            }
+
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    if let Some(delay) = self.retry_policy.transport_error_delay(&err, &mut retries)
+                    {
+                        debug!("Request failed: {:?}, pausing for {:?}", err, delay);
+                        sleep(delay).await;
+                        continue;
+                    }
+                    return Err(err.into());
+                }
+            };
+
+            if !response.status().is_success() {
+                if let Some(delay) = self.retry_policy.status_delay(
+                    response.status(),
+                    response.headers(),
+                    &mut retries,
+                ) {
+                    debug!(
+                        "Server responded with {:?}, pausing for {:?}",
+                        response, delay
+                    );
+                    sleep(delay).await;
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        stats_updater.add_rate_limited_time(delay);
+                    }
+                    continue;
+                }
+                return Err(response.error_for_status().unwrap_err().into());
+            }
+
+            let json = response.json::<serde_json::Value>().await?;
+            if let Some(delay) = self.retry_policy.rpc_error_delay(&json, &mut retries) {
+                debug!("Node is unhealthy, pausing for {:?}", delay);
+                sleep(delay).await;
+                continue;
+            }
+            return parse_json_rpc_response(json);
+        }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::http_sender::Backoff, std::net::TcpListener};
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_http_sender_on_tokio_current_thread() {
+        // Unlike the blocking sender, the async one works on any runtime
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let http_sender = HttpSender::new_with_config(
+            format!("http://{}", addr),
+            HttpSenderConfig {
+                retry_policy: RetryPolicy {
+                    transport_error: Some(Backoff {
+                        max_retries: 1,
+                        delay: Duration::from_millis(1),
+                        max_delay: Duration::from_millis(1),
+                    }),
+                    ..RetryPolicy::default()
+                },
+                ..HttpSenderConfig::default()
+            },
+        );
+        assert!(http_sender
+            .send(RpcRequest::GetVersion, serde_json::Value::Null)
+            .await
+            .is_err());
+        assert_eq!(http_sender.get_transport_stats().request_count, 1);
+    }
+}
@@ -0,0 +1,7 @@
+//! Async counterparts of the blocking clients, for services that already run on a tokio
+//! runtime and would otherwise wrap every call in `spawn_blocking`.
+
+pub mod http_sender;
+pub mod pubsub_client;
+pub mod rpc_client;
+pub mod rpc_sender;
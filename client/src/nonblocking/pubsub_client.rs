@@ -0,0 +1,504 @@
+//! An async client for the Solana PubSub API.
+//!
+//! A single websocket connection is shared by every subscription of a
+//! [`PubsubClient`].  When the connection is lost the client reconnects, with
+//! a backoff, and subscribes again to everything that was subscribed to, so
+//! the subscription streams carry on with the notifications sent after the
+//! reconnection.  Notifications sent while disconnected are lost.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use futures_util::StreamExt;
+//! # use solana_client::nonblocking::pubsub_client::PubsubClient;
+//! # async fn print_slots() -> Result<(), solana_client::pubsub_client::PubsubClientError> {
+//! let pubsub_client = PubsubClient::new("ws://localhost:8900").await?;
+//! let mut slots = pubsub_client.slot_subscribe().await?;
+//! while let Some(slot_info) = slots.next().await {
+//!     println!("{:?}", slot_info);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use {
+    crate::{
+        pubsub_client::PubsubClientError,
+        rpc_config::{
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+        },
+        rpc_response::{
+            Response as RpcResponse, RpcLogsResponse, RpcSignatureResult, SlotInfo, SlotUpdate,
+        },
+    },
+    futures_util::{
+        stream::Stream,
+        task::{Context, Poll},
+        SinkExt, StreamExt,
+    },
+    log::*,
+    serde::de::DeserializeOwned,
+    serde_json::{json, Value},
+    solana_sdk::signature::Signature,
+    std::{collections::HashMap, marker::PhantomData, pin::Pin, time::Duration},
+    tokio::{
+        net::TcpStream,
+        sync::{mpsc, oneshot},
+        task::JoinHandle,
+        time::sleep,
+    },
+    tokio_tungstenite::{
+        connect_async,
+        tungstenite::{self, Message},
+        MaybeTlsStream, WebSocketStream,
+    },
+    url::Url,
+};
+
+pub type PubsubClientResult<T> = Result<T, PubsubClientError>;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const MIN_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+async fn connect_with_retry(url: Url) -> Result<WsStream, tungstenite::Error> {
+    let mut connection_retries = 5;
+    loop {
+        let result = connect_async(url.clone()).await.map(|(socket, _)| socket);
+        if let Err(tungstenite::Error::Http(response)) = &result {
+            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && connection_retries > 0
+            {
+                let mut duration = Duration::from_millis(500);
+                if let Some(retry_after) = response.headers().get(reqwest::header::RETRY_AFTER) {
+                    if let Ok(retry_after) = retry_after.to_str() {
+                        if let Ok(retry_after) = retry_after.parse::<u64>() {
+                            if retry_after < 120 {
+                                duration = Duration::from_secs(retry_after);
+                            }
+                        }
+                    }
+                }
+
+                connection_retries -= 1;
+                debug!(
+                    "Too many requests: server responded with {:?}, {} retries left, pausing for {:?}",
+                    response, connection_retries, duration
+                );
+
+                sleep(duration).await;
+                continue;
+            }
+        }
+        return result;
+    }
+}
+
+struct ActiveSubscription {
+    /// The subscription is made with `{operation}Subscribe` and ended with `{operation}Unsubscribe`
+    operation: &'static str,
+    params: Value,
+    notifications: mpsc::UnboundedSender<Value>,
+    /// Answered once the first subscribe request is
+    response: Option<oneshot::Sender<PubsubClientResult<u64>>>,
+    /// The server ends the subscription itself after its first processed notification, as it
+    /// does signature subscriptions, so it is not subscribed again after a reconnection
+    one_shot: bool,
+}
+
+enum Command {
+    Subscribe(ActiveSubscription),
+    Unsubscribe(u64),
+    Shutdown,
+}
+
+enum Event {
+    Command(Option<Command>),
+    Message(Option<Result<Message, tungstenite::Error>>),
+    /// Disconnected and the reconnect delay has passed
+    Reconnect,
+}
+
+/// Owns the connection, sends the requests of the client and routes the notifications to the
+/// subscriptions.  Subscriptions are identified by ids of their own, since the ids the server
+/// assigns change with every connection
+struct SubscriptionTask {
+    url: Url,
+    ws: Option<WsStream>,
+    next_request_id: u64,
+    next_subscription_id: u64,
+    subscriptions: HashMap<u64, ActiveSubscription>,
+    /// Subscriptions awaiting the response to their subscribe request, by request id
+    pending: HashMap<u64, (u64, &'static str)>,
+    /// Subscriptions by the id the server assigned them
+    server_ids: HashMap<u64, u64>,
+}
+
+impl SubscriptionTask {
+    async fn run(mut self, mut commands: mpsc::UnboundedReceiver<Command>) {
+        let mut reconnect_delay = MIN_RECONNECT_DELAY;
+        loop {
+            let event = if let Some(ws) = &mut self.ws {
+                tokio::select! {
+                    command = commands.recv() => Event::Command(command),
+                    message = ws.next() => Event::Message(message),
+                }
+            } else {
+                tokio::select! {
+                    command = commands.recv() => Event::Command(command),
+                    _ = sleep(reconnect_delay) => Event::Reconnect,
+                }
+            };
+
+            match event {
+                Event::Reconnect => match connect_with_retry(self.url.clone()).await {
+                    Ok(ws) => {
+                        info!("pubsub: reconnected to {}", self.url);
+                        self.ws = Some(ws);
+                        reconnect_delay = MIN_RECONNECT_DELAY;
+                        self.resubscribe().await;
+                    }
+                    Err(err) => {
+                        warn!("pubsub: failed to reconnect to {}: {}", self.url, err);
+                        reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+                    }
+                },
+                Event::Command(Some(Command::Subscribe(subscription))) => {
+                    let id = self.next_subscription_id;
+                    self.next_subscription_id += 1;
+                    self.subscriptions.insert(id, subscription);
+                    self.send_subscribe(id).await;
+                }
+                Event::Command(Some(Command::Unsubscribe(id))) => self.unsubscribe(id).await,
+                Event::Command(Some(Command::Shutdown)) | Event::Command(None) => {
+                    if let Some(mut ws) = self.ws.take() {
+                        let _ = ws.close(None).await;
+                    }
+                    return;
+                }
+                Event::Message(Some(Ok(Message::Text(text)))) => self.handle_message(&text).await,
+                Event::Message(Some(Ok(Message::Close(_)))) | Event::Message(None) => {
+                    warn!("pubsub: connection to {} closed", self.url);
+                    self.disconnect();
+                }
+                Event::Message(Some(Err(err))) => {
+                    warn!("pubsub: connection to {} lost: {}", self.url, err);
+                    self.disconnect();
+                }
+                Event::Message(Some(Ok(_))) => (),
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.ws = None;
+        self.pending.clear();
+        self.server_ids.clear();
+    }
+
+    /// Send a request, dropping it if disconnected
+    async fn send_request(&mut self, method: String, params: Value) -> Option<u64> {
+        let ws = self.ws.as_mut()?;
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let request = json!({"jsonrpc":"2.0","id":request_id,"method":method,"params":params});
+        if let Err(err) = ws.send(Message::Text(request.to_string())).await {
+            warn!("pubsub: connection to {} lost: {}", self.url, err);
+            self.disconnect();
+            return None;
+        }
+        Some(request_id)
+    }
+
+    async fn send_subscribe(&mut self, id: u64) {
+        let (operation, params) = match self.subscriptions.get(&id) {
+            Some(subscription) => (subscription.operation, subscription.params.clone()),
+            None => return,
+        };
+        if let Some(request_id) = self
+            .send_request(format!("{}Subscribe", operation), params)
+            .await
+        {
+            self.pending.insert(request_id, (id, operation));
+        }
+    }
+
+    async fn send_unsubscribe(&mut self, operation: &str, server_id: u64) {
+        self.send_request(format!("{}Unsubscribe", operation), json!([server_id]))
+            .await;
+    }
+
+    async fn resubscribe(&mut self) {
+        let mut ids: Vec<_> = self.subscriptions.keys().copied().collect();
+        ids.sort_unstable();
+        for id in ids {
+            self.send_subscribe(id).await;
+        }
+    }
+
+    async fn unsubscribe(&mut self, id: u64) {
+        let subscription = match self.subscriptions.remove(&id) {
+            Some(subscription) => subscription,
+            None => return,
+        };
+        let server_id = self
+            .server_ids
+            .iter()
+            .find(|(_, subscription_id)| **subscription_id == id)
+            .map(|(server_id, _)| *server_id);
+        if let Some(server_id) = server_id {
+            self.server_ids.remove(&server_id);
+            self.send_unsubscribe(subscription.operation, server_id)
+                .await;
+        }
+    }
+
+    async fn handle_message(&mut self, text: &str) {
+        let mut json: Value = match serde_json::from_str(text) {
+            Ok(json) => json,
+            Err(err) => {
+                warn!("pubsub: invalid message {}: {}", text, err);
+                return;
+            }
+        };
+
+        if let Some(server_id) = json["params"]["subscription"].as_u64() {
+            if let Some(&id) = self.server_ids.get(&server_id) {
+                if let Some(subscription) = self.subscriptions.get(&id) {
+                    let result = json["params"]["result"].take();
+                    // A "receivedSignature" notification is a plain string, the processed one
+                    // carrying the transaction error is the last
+                    let last = subscription.one_shot && result["value"].is_object();
+                    // The subscription unsubscribes when dropped, until then drop its notifications
+                    let _ = subscription.notifications.send(result);
+                    if last {
+                        // Dropping the sender ends the stream once the notification is read
+                        self.subscriptions.remove(&id);
+                        self.server_ids.remove(&server_id);
+                    }
+                }
+            }
+            return;
+        }
+
+        let (id, operation) = match json["id"]
+            .as_u64()
+            .and_then(|request_id| self.pending.remove(&request_id))
+        {
+            Some(pending) => pending,
+            // The response to an unsubscribe request
+            None => return,
+        };
+        let result = json["result"]
+            .as_u64()
+            .ok_or_else(|| PubsubClientError::SubscribeFailed(json["error"].to_string()));
+        match (self.subscriptions.contains_key(&id), result) {
+            (true, Ok(server_id)) => {
+                self.server_ids.insert(server_id, id);
+                let subscription = self.subscriptions.get_mut(&id).unwrap();
+                if let Some(response) = subscription.response.take() {
+                    let _ = response.send(Ok(id));
+                }
+            }
+            (true, Err(err)) => {
+                let subscription = self.subscriptions.remove(&id).unwrap();
+                match subscription.response {
+                    Some(response) => {
+                        let _ = response.send(Err(err));
+                    }
+                    None => warn!("pubsub: failed to resubscribe to {}: {}", operation, err),
+                }
+            }
+            // Unsubscribed before the server answered
+            (false, Ok(server_id)) => self.send_unsubscribe(operation, server_id).await,
+            (false, Err(_)) => (),
+        }
+    }
+}
+
+/// A stream of the notifications of a subscription, which unsubscribes when dropped
+pub struct PubsubClientSubscription<T> {
+    id: u64,
+    notifications: mpsc::UnboundedReceiver<Value>,
+    commands: mpsc::UnboundedSender<Command>,
+    message_type: PhantomData<fn() -> T>,
+}
+
+impl<T> Drop for PubsubClientSubscription<T> {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Unsubscribe(self.id));
+    }
+}
+
+impl<T: DeserializeOwned> Stream for PubsubClientSubscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        loop {
+            match self.notifications.poll_recv(cx) {
+                Poll::Ready(Some(notification)) => {
+                    match serde_json::from_value::<T>(notification) {
+                        Ok(notification) => return Poll::Ready(Some(notification)),
+                        Err(err) => warn!("pubsub: invalid notification: {}", err),
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+pub struct PubsubClient {
+    commands: mpsc::UnboundedSender<Command>,
+    task: JoinHandle<()>,
+}
+
+impl PubsubClient {
+    /// Connect to the PubSub websocket, usually port 8900, as in "ws://localhost:8900"
+    pub async fn new(url: &str) -> PubsubClientResult<Self> {
+        let url = Url::parse(url)?;
+        let ws = connect_with_retry(url.clone()).await?;
+        let (commands, commands_receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(
+            SubscriptionTask {
+                url,
+                ws: Some(ws),
+                next_request_id: 0,
+                next_subscription_id: 0,
+                subscriptions: HashMap::new(),
+                pending: HashMap::new(),
+                server_ids: HashMap::new(),
+            }
+            .run(commands_receiver),
+        );
+        Ok(Self { commands, task })
+    }
+
+    /// Close the connection, ending every subscription stream
+    pub async fn shutdown(self) -> PubsubClientResult<()> {
+        let _ = self.commands.send(Command::Shutdown);
+        self.task
+            .await
+            .map_err(|_| PubsubClientError::ConnectionClosed)
+    }
+
+    async fn subscribe<T: DeserializeOwned>(
+        &self,
+        operation: &'static str,
+        params: Value,
+        one_shot: bool,
+    ) -> PubsubClientResult<PubsubClientSubscription<T>> {
+        let (notifications_sender, notifications) = mpsc::unbounded_channel();
+        let (response_sender, response) = oneshot::channel();
+        self.commands
+            .send(Command::Subscribe(ActiveSubscription {
+                operation,
+                params,
+                notifications: notifications_sender,
+                response: Some(response_sender),
+                one_shot,
+            }))
+            .map_err(|_| PubsubClientError::ConnectionClosed)?;
+        let id = response
+            .await
+            .map_err(|_| PubsubClientError::ConnectionClosed)??;
+        Ok(PubsubClientSubscription {
+            id,
+            notifications,
+            commands: self.commands.clone(),
+            message_type: PhantomData,
+        })
+    }
+
+    pub async fn logs_subscribe(
+        &self,
+        filter: RpcTransactionLogsFilter,
+        config: RpcTransactionLogsConfig,
+    ) -> PubsubClientResult<PubsubClientSubscription<RpcResponse<RpcLogsResponse>>> {
+        self.subscribe("logs", json!([filter, config]), false)
+            .await
+    }
+
+    pub async fn slot_subscribe(&self) -> PubsubClientResult<PubsubClientSubscription<SlotInfo>> {
+        self.subscribe("slot", json!([]), false).await
+    }
+
+    pub async fn signature_subscribe(
+        &self,
+        signature: &Signature,
+        config: Option<RpcSignatureSubscribeConfig>,
+    ) -> PubsubClientResult<PubsubClientSubscription<RpcResponse<RpcSignatureResult>>> {
+        self.subscribe("signature", json!([signature.to_string(), config]), true)
+            .await
+    }
+
+    pub async fn slot_updates_subscribe(
+        &self,
+    ) -> PubsubClientResult<PubsubClientSubscription<SlotUpdate>> {
+        self.subscribe("slotsUpdates", json!([]), false)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_task() -> SubscriptionTask {
+        SubscriptionTask {
+            url: Url::parse("ws://localhost:8900").unwrap(),
+            ws: None,
+            next_request_id: 0,
+            next_subscription_id: 0,
+            subscriptions: HashMap::new(),
+            pending: HashMap::new(),
+            server_ids: HashMap::new(),
+        }
+    }
+
+    fn notification(server_id: u64, value: Value) -> String {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "signatureNotification",
+            "params": {
+                "result": {"context": {"slot": 5}, "value": value},
+                "subscription": server_id,
+            },
+        })
+        .to_string()
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_one_shot_subscription_ends_after_processed_notification() {
+        let mut task = new_task();
+        let (notifications, mut receiver) = mpsc::unbounded_channel();
+        task.subscriptions.insert(
+            0,
+            ActiveSubscription {
+                operation: "signature",
+                params: json!([]),
+                notifications,
+                response: None,
+                one_shot: true,
+            },
+        );
+        task.server_ids.insert(7, 0);
+
+        task.handle_message(&notification(7, json!("receivedSignature")))
+            .await;
+        assert!(task.subscriptions.contains_key(&0));
+        assert!(receiver.recv().await.is_some());
+
+        task.handle_message(&notification(7, json!({"err": null})))
+            .await;
+        assert!(task.subscriptions.is_empty());
+        assert!(task.server_ids.is_empty());
+        assert!(receiver.recv().await.is_some());
+        assert!(receiver.recv().await.is_none());
+
+        // Nothing is left to subscribe again after a reconnection
+        task.resubscribe().await;
+        assert!(task.pending.is_empty());
+    }
+}
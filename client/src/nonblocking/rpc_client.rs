@@ -0,0 +1,2736 @@
+//! An async client for the Solana JSON RPC API.
+//!
+//! The methods have the same names, arguments and results as those of the
+//! blocking [`RpcClient`](crate::rpc_client::RpcClient), whose documentation
+//! describes them in detail, but return futures instead of blocking the
+//! calling thread.
+
+#[allow(deprecated)]
+use crate::rpc_deprecated_config::{
+    RpcConfirmedBlockConfig, RpcConfirmedTransactionConfig,
+    RpcGetConfirmedSignaturesForAddress2Config,
+};
+use {
+    crate::{
+        client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+        connection_cache::{ConnectionCache, LeaderTpus},
+        http_sender::HttpSenderConfig,
+        mock_sender::{MockSender, Mocks},
+        nonblocking::{
+            http_sender::HttpSender, pubsub_client::PubsubClient, rpc_sender::RpcSender,
+        },
+        nonce_utils,
+        rpc_client::{
+            get_rpc_request_str, new_spinner_progress_bar, parse_keyed_accounts,
+            serialize_and_encode, ConfirmTransactionConfig,
+            GetConfirmedSignaturesForAddress2Config, TransactionConfirmation,
+        },
+        rpc_config::*,
+        rpc_request::{RpcError, RpcRequest, RpcResponseErrorData, TokenAccountsFilter},
+        rpc_response::*,
+        rpc_sender::RpcTransportStats,
+        tpu_client::{DEFAULT_FANOUT_SLOTS, MAX_FANOUT_SLOTS},
+    },
+    bincode::serialize,
+    futures_util::StreamExt,
+    indicatif::ProgressBar,
+    log::*,
+    serde_json::{json, Value},
+    solana_account_decoder::{
+        parse_token::{TokenAccountType, UiTokenAccount, UiTokenAmount},
+        UiAccount, UiAccountData, UiAccountEncoding,
+    },
+    solana_sdk::{
+        account::Account,
+        clock::{Epoch, Slot, UnixTimestamp, DEFAULT_MS_PER_SLOT, MAX_HASH_AGE_IN_SECONDS},
+        commitment_config::{CommitmentConfig, CommitmentLevel},
+        epoch_info::EpochInfo,
+        epoch_schedule::EpochSchedule,
+        fee_calculator::{FeeCalculator, FeeRateGovernor},
+        hash::Hash,
+        message::{Message, VersionedMessage},
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::{self, uses_durable_nonce, Transaction},
+    },
+    solana_transaction_status::{
+        EncodedConfirmedBlock, EncodedConfirmedTransaction, TransactionStatus, UiConfirmedBlock,
+        UiTransactionEncoding,
+    },
+    solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY,
+    std::{
+        cmp::min,
+        net::SocketAddr,
+        str::FromStr,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    tokio::{
+        sync::RwLock,
+        time::{sleep, timeout},
+    },
+};
+
+/// An async client of a remote Solana node.
+///
+/// # Examples
+///
+/// ```
+/// # use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+/// # async fn get_slot() -> Result<(), ClientError> {
+/// let rpc_client = RpcClient::new("http://localhost:8899".to_string());
+/// let slot = rpc_client.get_slot().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RpcClient {
+    sender: Box<dyn RpcSender + Send + Sync + 'static>,
+    commitment_config: CommitmentConfig,
+    confirm_transaction_initial_timeout: Option<Duration>,
+    node_version: RwLock<Option<semver::Version>>,
+    connection_cache: Option<Arc<ConnectionCache>>,
+    leader_tpus: RwLock<LeaderTpus>,
+}
+
+impl RpcClient {
+    fn new_sender<T: RpcSender + Send + Sync + 'static>(
+        sender: T,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self {
+            sender: Box::new(sender),
+            commitment_config,
+            confirm_transaction_initial_timeout: None,
+            node_version: RwLock::new(None),
+            connection_cache: None,
+            leader_tpus: RwLock::default(),
+        }
+    }
+
+    /// Create an HTTP `RpcClient`, with a default timeout of 30 seconds and a
+    /// default commitment level of [`Finalized`](CommitmentLevel::Finalized).
+    pub fn new(url: String) -> Self {
+        Self::new_with_commitment(url, CommitmentConfig::default())
+    }
+
+    /// Create an HTTP `RpcClient` with specified commitment level.
+    pub fn new_with_commitment(url: String, commitment_config: CommitmentConfig) -> Self {
+        Self::new_sender(HttpSender::new(url), commitment_config)
+    }
+
+    /// Create an HTTP `RpcClient` with specified timeout.
+    pub fn new_with_timeout(url: String, timeout: Duration) -> Self {
+        Self::new_with_timeout_and_commitment(url, timeout, CommitmentConfig::default())
+    }
+
+    /// Create an HTTP `RpcClient` with specified timeout and commitment level.
+    pub fn new_with_timeout_and_commitment(
+        url: String,
+        timeout: Duration,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self::new_sender(
+            HttpSender::new_with_timeout(url, timeout),
+            commitment_config,
+        )
+    }
+
+    /// Create an HTTP `RpcClient` with specified timeout and commitment level,
+    /// and a timeout for transactions to be found before their confirmation is
+    /// given up on.
+    pub fn new_with_timeouts_and_commitment(
+        url: String,
+        timeout: Duration,
+        commitment_config: CommitmentConfig,
+        confirm_transaction_initial_timeout: Duration,
+    ) -> Self {
+        Self {
+            confirm_transaction_initial_timeout: Some(confirm_transaction_initial_timeout),
+            ..Self::new_with_timeout_and_commitment(url, timeout, commitment_config)
+        }
+    }
+
+    /// Create an HTTP `RpcClient` from a [`SocketAddr`].
+    pub fn new_socket(addr: SocketAddr) -> Self {
+        Self::new(get_rpc_request_str(addr, false))
+    }
+
+    /// Create an HTTP `RpcClient` from a [`SocketAddr`] with specified
+    /// commitment level.
+    pub fn new_socket_with_commitment(
+        addr: SocketAddr,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self::new_with_commitment(get_rpc_request_str(addr, false), commitment_config)
+    }
+
+    /// Create an HTTP `RpcClient` from a [`SocketAddr`] with specified timeout.
+    pub fn new_socket_with_timeout(addr: SocketAddr, timeout: Duration) -> Self {
+        let url = get_rpc_request_str(addr, false);
+        Self::new_with_timeout(url, timeout)
+    }
+
+    /// Create an HTTP `RpcClient` with specified [`HttpSenderConfig`] and
+    /// commitment level.
+    pub fn new_with_http_sender_config(
+        url: String,
+        http_sender_config: HttpSenderConfig,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self::new_sender(
+            HttpSender::new_with_config(url, http_sender_config),
+            commitment_config,
+        )
+    }
+
+    /// Create an HTTP `RpcClient` with specified commitment level, that sends
+    /// transactions straight to the TPUs of the upcoming leaders through
+    /// `connection_cache`, see [`send_transaction`](Self::send_transaction).
+    pub fn new_with_connection_cache(
+        url: String,
+        commitment_config: CommitmentConfig,
+        connection_cache: Arc<ConnectionCache>,
+    ) -> Self {
+        Self {
+            connection_cache: Some(connection_cache),
+            ..Self::new_with_commitment(url, commitment_config)
+        }
+    }
+
+    /// Create a mock `RpcClient`, see [`MockSender`].
+    pub fn new_mock(url: String) -> Self {
+        Self::new_mock_with_mocks(url, Mocks::default())
+    }
+
+    /// Create a mock `RpcClient` with custom responses, see [`MockSender`].
+    pub fn new_mock_with_mocks(url: String, mocks: Mocks) -> Self {
+        Self::new_sender(
+            MockSender::new_with_mocks(url, mocks),
+            CommitmentConfig::default(),
+        )
+    }
+
+    async fn get_node_version(&self) -> Result<semver::Version, RpcError> {
+        let r_node_version = self.node_version.read().await;
+        if let Some(version) = &*r_node_version {
+            return Ok(version.clone());
+        }
+        drop(r_node_version);
+        let mut w_node_version = self.node_version.write().await;
+        let node_version = self.get_version().await.map_err(|e| {
+            RpcError::RpcRequestError(format!("cluster version query failed: {}", e))
+        })?;
+        let node_version = semver::Version::parse(&node_version.solana_core).map_err(|e| {
+            RpcError::RpcRequestError(format!("failed to parse cluster version: {}", e))
+        })?;
+        *w_node_version = Some(node_version.clone());
+        Ok(node_version)
+    }
+
+    /// Get the configured default commitment level.
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment_config
+    }
+
+    async fn maybe_map_commitment(
+        &self,
+        requested_commitment: CommitmentConfig,
+    ) -> Result<CommitmentConfig, RpcError> {
+        if matches!(
+            requested_commitment.commitment,
+            CommitmentLevel::Finalized | CommitmentLevel::Confirmed | CommitmentLevel::Processed
+        ) && self.get_node_version().await? < semver::Version::new(1, 5, 5)
+        {
+            return Ok(CommitmentConfig::use_deprecated_commitment(
+                requested_commitment,
+            ));
+        }
+        Ok(requested_commitment)
+    }
+
+    #[allow(deprecated)]
+    async fn maybe_map_request(&self, mut request: RpcRequest) -> Result<RpcRequest, RpcError> {
+        if self.get_node_version().await? < semver::Version::new(1, 7, 0) {
+            request = match request {
+                RpcRequest::GetBlock => RpcRequest::GetConfirmedBlock,
+                RpcRequest::GetBlocks => RpcRequest::GetConfirmedBlocks,
+                RpcRequest::GetBlocksWithLimit => RpcRequest::GetConfirmedBlocksWithLimit,
+                RpcRequest::GetSignaturesForAddress => {
+                    RpcRequest::GetConfirmedSignaturesForAddress2
+                }
+                RpcRequest::GetTransaction => RpcRequest::GetConfirmedTransaction,
+                _ => request,
+            };
+        }
+        Ok(request)
+    }
+
+    async fn default_cluster_transaction_encoding(
+        &self,
+    ) -> Result<UiTransactionEncoding, RpcError> {
+        if self.get_node_version().await? < semver::Version::new(1, 3, 16) {
+            Ok(UiTransactionEncoding::Base58)
+        } else {
+            Ok(UiTransactionEncoding::Base64)
+        }
+    }
+
+    pub async fn send<T>(&self, request: RpcRequest, params: Value) -> ClientResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        assert!(params.is_array() || params.is_null());
+
+        let response = self
+            .sender
+            .send(request, params)
+            .await
+            .map_err(|err| err.into_with_request(request))?;
+        serde_json::from_value(response)
+            .map_err(|err| ClientError::new_with_request(err.into(), request))
+    }
+
+    /// Submit a transaction and wait for confirmation.
+    pub async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ClientResult<Signature> {
+        let signature = self.send_transaction(transaction).await?;
+
+        let recent_blockhash = if uses_durable_nonce(transaction).is_some() {
+            let (recent_blockhash, ..) = self
+                .get_latest_blockhash_with_commitment(CommitmentConfig::processed())
+                .await?;
+            recent_blockhash
+        } else {
+            transaction.message.recent_blockhash
+        };
+
+        loop {
+            match self.get_signature_status(&signature).await? {
+                Some(Ok(_)) => return Ok(signature),
+                Some(Err(e)) => return Err(e.into()),
+                None => {
+                    if !self
+                        .is_blockhash_valid(&recent_blockhash, CommitmentConfig::processed())
+                        .await?
+                    {
+                        // Block hash is not found by some reason
+                        break;
+                    } else if cfg!(not(test)) {
+                        // Retry twice a second
+                        sleep(Duration::from_millis(500)).await;
+                    }
+                }
+            }
+        }
+
+        Err(RpcError::ForUser(
+            "unable to confirm transaction. \
+             This can happen in situations such as transaction expiration \
+             and insufficient fee-payer funds"
+                .to_string(),
+        )
+        .into())
+    }
+
+    pub async fn send_and_confirm_transaction_with_spinner(
+        &self,
+        transaction: &Transaction,
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_transaction_with_spinner_and_commitment(
+            transaction,
+            self.commitment(),
+        )
+        .await
+    }
+
+    pub async fn send_and_confirm_transaction_with_spinner_and_commitment(
+        &self,
+        transaction: &Transaction,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<Signature> {
+        self.send_and_confirm_transaction_with_spinner_and_config(
+            transaction,
+            commitment,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn send_and_confirm_transaction_with_spinner_and_config(
+        &self,
+        transaction: &Transaction,
+        commitment: CommitmentConfig,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let signature = self
+            .send_transaction_with_config(transaction, config)
+            .await?;
+        let confirmation = self
+            .confirm_transaction_with_spinner_and_config(
+                transaction,
+                ConfirmTransactionConfig {
+                    commitment,
+                    ..ConfirmTransactionConfig::default()
+                },
+            )
+            .await?;
+        match confirmation.err {
+            Some(err) => Err(err.into()),
+            None => Ok(signature),
+        }
+    }
+
+    pub async fn confirm_transaction_with_spinner(
+        &self,
+        signature: &Signature,
+        recent_blockhash: &Hash,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<()> {
+        let desired_confirmations = if commitment.is_finalized() {
+            MAX_LOCKOUT_HISTORY + 1
+        } else {
+            1
+        };
+        let mut confirmations = 0;
+        let progress_bar = new_spinner_progress_bar();
+        progress_bar.set_message(format!(
+            "[{}/{}] Finalizing transaction {}",
+            confirmations, desired_confirmations, signature,
+        ));
+        let now = Instant::now();
+        let confirm_transaction_initial_timeout =
+            self.confirm_transaction_initial_timeout.unwrap_or_default();
+        let (signature, status) = loop {
+            // Get recent commitment in order to count confirmations for successful transactions
+            let status = self
+                .get_signature_status_with_commitment(signature, CommitmentConfig::processed())
+                .await?;
+            if status.is_none() {
+                let blockhash_not_found = !self
+                    .is_blockhash_valid(recent_blockhash, CommitmentConfig::processed())
+                    .await?;
+                if blockhash_not_found && now.elapsed() >= confirm_transaction_initial_timeout {
+                    break (signature, status);
+                }
+            } else {
+                break (signature, status);
+            }
+            if cfg!(not(test)) {
+                sleep(Duration::from_millis(500)).await;
+            }
+        };
+        if let Some(result) = status {
+            if let Err(err) = result {
+                return Err(err.into());
+            }
+        } else {
+            return Err(RpcError::ForUser(
+                "unable to confirm transaction. \
+                                      This can happen in situations such as transaction expiration \
+                                      and insufficient fee-payer funds"
+                    .to_string(),
+            )
+            .into());
+        }
+        let now = Instant::now();
+        loop {
+            // Return when specified commitment is reached
+            // Failed transactions have already been eliminated, `is_some` check is sufficient
+            if self
+                .get_signature_status_with_commitment(signature, commitment)
+                .await?
+                .is_some()
+            {
+                progress_bar.set_message("Transaction confirmed");
+                progress_bar.finish_and_clear();
+                return Ok(());
+            }
+            progress_bar.set_message(format!(
+                "[{}/{}] Finalizing transaction {}",
+                min(confirmations + 1, desired_confirmations),
+                desired_confirmations,
+                signature,
+            ));
+            sleep(Duration::from_millis(500)).await;
+            confirmations = self
+                .get_num_blocks_since_signature_confirmation(signature)
+                .await
+                .unwrap_or(confirmations);
+            if now.elapsed().as_secs() >= MAX_HASH_AGE_IN_SECONDS as u64 {
+                return Err(
+                    RpcError::ForUser("transaction not finalized. \
+                                      This can happen when a transaction lands in an abandoned fork. \
+                                      Please retry.".to_string()).into(),
+                );
+            }
+        }
+    }
+
+    pub async fn confirm_transaction_with_spinner_and_config(
+        &self,
+        transaction: &Transaction,
+        config: ConfirmTransactionConfig,
+    ) -> ClientResult<TransactionConfirmation> {
+        let signature = *transaction
+            .signatures
+            .get(0)
+            .ok_or_else(|| RpcError::ForUser("transaction is not signed".to_string()))?;
+        let recent_blockhash = &transaction.message.recent_blockhash;
+        let nonce_pubkey = uses_durable_nonce(transaction)
+            .and_then(|instruction| instruction.accounts.get(0))
+            .and_then(|index| transaction.message.account_keys.get(usize::from(*index)));
+        let progress_bar = new_spinner_progress_bar();
+        progress_bar.set_message(format!("Confirming transaction {}", signature));
+        let pubsub_client = match &config.websocket_url {
+            Some(websocket_url) => PubsubClient::new(websocket_url)
+                .await
+                .map_err(|err| warn!("Unable to subscribe to {}, polling: {}", signature, err))
+                .ok(),
+            None => None,
+        };
+        let mut subscription = match &pubsub_client {
+            Some(pubsub_client) => pubsub_client
+                .signature_subscribe(
+                    &signature,
+                    Some(RpcSignatureSubscribeConfig {
+                        commitment: Some(config.commitment),
+                        enable_received_notification: Some(false),
+                    }),
+                )
+                .await
+                .map_err(|err| warn!("Unable to subscribe to {}, polling: {}", signature, err))
+                .ok(),
+            None => None,
+        };
+        let start = Instant::now();
+        let mut last_reached = start;
+        let result = loop {
+            match self
+                .check_transaction_confirmation(
+                    &signature,
+                    recent_blockhash,
+                    nonce_pubkey,
+                    config.commitment,
+                    start,
+                    &progress_bar,
+                )
+                .await
+            {
+                Ok(Some(confirmation)) => break Ok(confirmation),
+                Ok(None) => last_reached = Instant::now(),
+                // Give up once the transaction would have expired anyway
+                Err(err)
+                    if err.kind().is_transient()
+                        && last_reached.elapsed().as_secs() < MAX_HASH_AGE_IN_SECONDS as u64 =>
+                {
+                    warn!(
+                        "Unable to check transaction {}, retrying: {}",
+                        signature, err
+                    );
+                }
+                Err(err) => break Err(err),
+            }
+            if let Some(timeout) = config.timeout {
+                if start.elapsed() >= timeout {
+                    break Err(RpcError::ForUser(format!(
+                        "transaction {} not confirmed after {:?}",
+                        signature, timeout
+                    ))
+                    .into());
+                }
+            }
+            match subscription.as_mut() {
+                Some(notifications) => {
+                    // Polling, also once the subscription's stream has ended
+                    if let Ok(None) = timeout(config.poll_interval, notifications.next()).await {
+                        subscription = None;
+                    }
+                }
+                None => sleep(config.poll_interval).await,
+            }
+        };
+        drop(subscription);
+        if let Some(pubsub_client) = pubsub_client {
+            pubsub_client
+                .shutdown()
+                .await
+                .unwrap_or_else(|_| warn!("Unable to shut down signature subscription"));
+        }
+        match &result {
+            Ok(_) => progress_bar.set_message("Transaction confirmed"),
+            Err(_) => progress_bar.set_message("Transaction not confirmed"),
+        }
+        progress_bar.finish_and_clear();
+        result
+    }
+
+    async fn check_transaction_confirmation(
+        &self,
+        signature: &Signature,
+        recent_blockhash: &Hash,
+        nonce_pubkey: Option<&Pubkey>,
+        commitment: CommitmentConfig,
+        start: Instant,
+        progress_bar: &ProgressBar,
+    ) -> ClientResult<Option<TransactionConfirmation>> {
+        match self.get_signature_statuses(&[*signature]).await?.value[0].take() {
+            Some(status) if status.satisfies_commitment(commitment) => {
+                return Ok(Some(TransactionConfirmation::from(status)));
+            }
+            Some(status) => progress_bar.set_message(format!(
+                "[{} confirmations] Confirming transaction {}",
+                status.confirmations.unwrap_or_default(),
+                signature
+            )),
+            None => {
+                let expired = match nonce_pubkey {
+                    Some(nonce_pubkey) => {
+                        self.is_nonce_advanced(nonce_pubkey, recent_blockhash)
+                            .await?
+                    }
+                    None => {
+                        start.elapsed()
+                            >= self.confirm_transaction_initial_timeout.unwrap_or_default()
+                            && !self
+                                .is_blockhash_valid(recent_blockhash, CommitmentConfig::processed())
+                                .await?
+                    }
+                };
+                // The transaction may have landed just before it expired
+                if expired && self.get_signature_statuses(&[*signature]).await?.value[0].is_none() {
+                    return Err(RpcError::ForUser(format!(
+                        "transaction {} expired before it was processed",
+                        signature
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn is_nonce_advanced(
+        &self,
+        nonce_pubkey: &Pubkey,
+        blockhash: &Hash,
+    ) -> ClientResult<bool> {
+        let account = self
+            .get_account_with_commitment(nonce_pubkey, CommitmentConfig::processed())
+            .await?
+            .value
+            .ok_or_else(|| {
+                ClientErrorKind::Custom(format!(
+                    "Invalid nonce account: {} not found",
+                    nonce_pubkey
+                ))
+            })?;
+        let data = nonce_utils::data_from_account(&account)
+            .map_err(|err| ClientErrorKind::Custom(format!("Invalid nonce account: {}", err)))?;
+        Ok(data.blockhash != *blockhash)
+    }
+
+    /// Submits a signed transaction to the RPC node, or, if the client was
+    /// created with [`new_with_connection_cache`](Self::new_with_connection_cache),
+    /// sends it straight to the TPUs of the leaders of the next
+    /// [`DEFAULT_FANOUT_SLOTS`] slots, without a preflight check.
+    pub async fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        if let Some(connection_cache) = &self.connection_cache {
+            return self
+                .send_transaction_to_leaders(connection_cache, transaction)
+                .await;
+        }
+        self.send_transaction_with_config(
+            transaction,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(
+                    self.maybe_map_commitment(self.commitment())
+                        .await?
+                        .commitment,
+                ),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .await
+    }
+
+    async fn send_transaction_to_leaders(
+        &self,
+        connection_cache: &Arc<ConnectionCache>,
+        transaction: &Transaction,
+    ) -> ClientResult<Signature> {
+        let wire_transaction = serialize(transaction).map_err(|err| {
+            ClientErrorKind::Custom(format!("transaction serialization failed: {}", err))
+        })?;
+        let slot = self
+            .get_slot_with_commitment(CommitmentConfig::processed())
+            .await?;
+        let cached_tpu_addrs = self
+            .leader_tpus
+            .read()
+            .await
+            .get(slot, DEFAULT_FANOUT_SLOTS);
+        let tpu_addrs = match cached_tpu_addrs {
+            Some(tpu_addrs) => tpu_addrs,
+            None => {
+                let leaders = self.get_slot_leaders(slot, MAX_FANOUT_SLOTS).await?;
+                let cluster_nodes = self.get_cluster_nodes().await?;
+                let mut leader_tpus = self.leader_tpus.write().await;
+                leader_tpus.update(slot, leaders, cluster_nodes);
+                leader_tpus
+                    .get(slot, DEFAULT_FANOUT_SLOTS)
+                    .unwrap_or_default()
+            }
+        };
+        if tpu_addrs.is_empty() {
+            return Err(ClientErrorKind::Custom(format!(
+                "no TPU address for the leaders of slot {}",
+                slot
+            ))
+            .into());
+        }
+        for tpu_addr in tpu_addrs {
+            connection_cache
+                .send_wire_transaction_async(tpu_addr, wire_transaction.clone())
+                .await?;
+        }
+        Ok(transaction.signatures[0])
+    }
+
+    pub async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding().await?
+        };
+        let preflight_commitment = CommitmentConfig {
+            commitment: config.preflight_commitment.unwrap_or_default(),
+        };
+        let preflight_commitment = self.maybe_map_commitment(preflight_commitment).await?;
+        let config = RpcSendTransactionConfig {
+            encoding: Some(encoding),
+            preflight_commitment: Some(preflight_commitment.commitment),
+            ..config
+        };
+        let serialized_encoded = serialize_and_encode::<Transaction>(transaction, encoding)?;
+        let signature_base58_str: String = match self
+            .send(
+                RpcRequest::SendTransaction,
+                json!([serialized_encoded, config]),
+            )
+            .await
+        {
+            Ok(signature_base58_str) => signature_base58_str,
+            Err(err) => {
+                if let ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    code,
+                    message,
+                    data,
+                }) = &err.kind
+                {
+                    debug!("{} {}", code, message);
+                    if let RpcResponseErrorData::SendTransactionPreflightFailure(
+                        RpcSimulateTransactionResult {
+                            logs: Some(logs), ..
+                        },
+                    ) = data
+                    {
+                        for (i, log) in logs.iter().enumerate() {
+                            debug!("{:>3}: {}", i + 1, log);
+                        }
+                        debug!("");
+                    }
+                }
+                return Err(err);
+            }
+        };
+
+        let signature = signature_base58_str
+            .parse::<Signature>()
+            .map_err(|err| Into::<ClientError>::into(RpcError::ParseError(err.to_string())))?;
+        // A mismatching RPC response signature indicates an issue with the RPC node, and
+        // should not be passed along to confirmation methods. The transaction may or may
+        // not have been submitted to the cluster, so callers should verify the success of
+        // the correct transaction signature independently.
+        if signature != transaction.signatures[0] {
+            Err(RpcError::RpcRequestError(format!(
+                "RPC node returned mismatched signature {:?}, expected {:?}",
+                signature, transaction.signatures[0]
+            ))
+            .into())
+        } else {
+            Ok(transaction.signatures[0])
+        }
+    }
+
+    pub async fn confirm_transaction(&self, signature: &Signature) -> ClientResult<bool> {
+        Ok(self
+            .confirm_transaction_with_commitment(signature, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn confirm_transaction_with_commitment(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<bool> {
+        let Response { context, value } = self.get_signature_statuses(&[*signature]).await?;
+
+        Ok(Response {
+            context,
+            value: value[0]
+                .as_ref()
+                .filter(|result| result.satisfies_commitment(commitment_config))
+                .map(|result| result.status.is_ok())
+                .unwrap_or_default(),
+        })
+    }
+
+    pub async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                commitment: Some(self.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding().await?
+        };
+        let commitment = config.commitment.unwrap_or_default();
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(encoding),
+            commitment: Some(commitment),
+            ..config
+        };
+        let serialized_encoded = serialize_and_encode::<Transaction>(transaction, encoding)?;
+        self.send(
+            RpcRequest::SimulateTransaction,
+            json!([serialized_encoded, config]),
+        )
+        .await
+    }
+
+    pub async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        self.get_signature_status_with_commitment(signature, self.commitment())
+            .await
+    }
+
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        let signatures: Vec<_> = signatures.iter().map(|s| s.to_string()).collect();
+        self.send(RpcRequest::GetSignatureStatuses, json!([signatures]))
+            .await
+    }
+
+    pub async fn get_signature_status_with_commitment(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        let result: Response<Vec<Option<TransactionStatus>>> = self
+            .send(
+                RpcRequest::GetSignatureStatuses,
+                json!([[signature.to_string()]]),
+            )
+            .await?;
+        Ok(result.value[0]
+            .clone()
+            .filter(|result| result.satisfies_commitment(commitment_config))
+            .map(|status_meta| status_meta.status))
+    }
+
+    pub async fn get_slot(&self) -> ClientResult<Slot> {
+        self.get_slot_with_commitment(self.commitment()).await
+    }
+
+    pub async fn get_slot_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Slot> {
+        self.send(
+            RpcRequest::GetSlot,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn get_block_height(&self) -> ClientResult<u64> {
+        self.get_block_height_with_commitment(self.commitment())
+            .await
+    }
+
+    pub async fn get_block_height_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        self.send(
+            RpcRequest::GetBlockHeight,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn get_epoch_info(&self) -> ClientResult<EpochInfo> {
+        self.get_epoch_info_with_commitment(self.commitment()).await
+    }
+
+    pub async fn get_epoch_info_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<EpochInfo> {
+        self.send(
+            RpcRequest::GetEpochInfo,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn get_identity(&self) -> ClientResult<Pubkey> {
+        let rpc_identity: RpcIdentity = self.send(RpcRequest::GetIdentity, Value::Null).await?;
+
+        rpc_identity.identity.parse::<Pubkey>().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Pubkey".to_string()).into(),
+                RpcRequest::GetIdentity,
+            )
+        })
+    }
+
+    pub async fn get_version(&self) -> ClientResult<RpcVersionInfo> {
+        self.send(RpcRequest::GetVersion, Value::Null).await
+    }
+
+    pub async fn get_genesis_hash(&self) -> ClientResult<Hash> {
+        let hash_str: String = self.send(RpcRequest::GetGenesisHash, Value::Null).await?;
+        let hash = hash_str.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Hash".to_string()).into(),
+                RpcRequest::GetGenesisHash,
+            )
+        })?;
+        Ok(hash)
+    }
+
+    pub async fn get_health(&self) -> ClientResult<()> {
+        self.send::<String>(RpcRequest::GetHealth, Value::Null)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.get_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value
+            .ok_or_else(|| RpcError::ForUser(format!("AccountNotFound: pubkey={}", pubkey)).into())
+    }
+
+    pub async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Option<Account>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64Zstd),
+            commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+            data_slice: None,
+            with_last_modified_slot: None,
+        };
+        let response = self
+            .sender
+            .send(
+                RpcRequest::GetAccountInfo,
+                json!([pubkey.to_string(), config]),
+            )
+            .await;
+
+        response
+            .map(|result_json| {
+                if result_json.is_null() {
+                    return Err(
+                        RpcError::ForUser(format!("AccountNotFound: pubkey={}", pubkey)).into(),
+                    );
+                }
+                let Response {
+                    context,
+                    value: rpc_account,
+                } = serde_json::from_value::<Response<Option<UiAccount>>>(result_json)?;
+                trace!("Response account {:?} {:?}", pubkey, rpc_account);
+                let account = rpc_account.and_then(|rpc_account| rpc_account.decode());
+
+                Ok(Response {
+                    context,
+                    value: account,
+                })
+            })
+            .map_err(|err| {
+                Into::<ClientError>::into(RpcError::ForUser(format!(
+                    "AccountNotFound: pubkey={}: {}",
+                    pubkey, err
+                )))
+            })?
+    }
+
+    pub async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        Ok(self
+            .get_multiple_accounts_with_commitment(pubkeys, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_multiple_accounts_with_commitment(
+        &self,
+        pubkeys: &[Pubkey],
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        self.get_multiple_accounts_with_config(
+            pubkeys,
+            RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64Zstd),
+                commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+                data_slice: None,
+                with_last_modified_slot: None,
+            },
+        )
+        .await
+    }
+
+    pub async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        let config = RpcAccountInfoConfig {
+            commitment: config.commitment.or_else(|| Some(self.commitment())),
+            ..config
+        };
+        let pubkeys: Vec<_> = pubkeys.iter().map(|pubkey| pubkey.to_string()).collect();
+        let response = self
+            .send(RpcRequest::GetMultipleAccounts, json!([pubkeys, config]))
+            .await?;
+        let Response {
+            context,
+            value: accounts,
+        } = serde_json::from_value::<Response<Vec<Option<UiAccount>>>>(response)?;
+        let accounts: Vec<Option<Account>> = accounts
+            .into_iter()
+            .map(|rpc_account| rpc_account.and_then(|a| a.decode()))
+            .collect();
+        Ok(Response {
+            context,
+            value: accounts,
+        })
+    }
+
+    pub async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        Ok(self.get_account(pubkey).await?.data)
+    }
+
+    pub async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> ClientResult<u64> {
+        self.send(
+            RpcRequest::GetMinimumBalanceForRentExemption,
+            json!([data_len]),
+        )
+        .await
+    }
+
+    pub async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> {
+        Ok(self
+            .get_balance_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<u64> {
+        self.send(
+            RpcRequest::GetBalance,
+            json!([
+                pubkey.to_string(),
+                self.maybe_map_commitment(commitment_config).await?
+            ]),
+        )
+        .await
+    }
+
+    pub async fn get_transaction_count(&self) -> ClientResult<u64> {
+        self.get_transaction_count_with_commitment(self.commitment())
+            .await
+    }
+
+    pub async fn get_transaction_count_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        self.send(
+            RpcRequest::GetTransactionCount,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> ClientResult<Signature> {
+        self.request_airdrop_with_config(
+            pubkey,
+            lamports,
+            RpcRequestAirdropConfig {
+                commitment: Some(self.commitment()),
+                ..RpcRequestAirdropConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn request_airdrop_with_config(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+        config: RpcRequestAirdropConfig,
+    ) -> ClientResult<Signature> {
+        let commitment = config.commitment.unwrap_or_default();
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        let config = RpcRequestAirdropConfig {
+            commitment: Some(commitment),
+            ..config
+        };
+        self.send(
+            RpcRequest::RequestAirdrop,
+            json!([pubkey.to_string(), lamports, config]),
+        )
+        .await
+        .and_then(|signature: String| {
+            Signature::from_str(&signature).map_err(|err| {
+                ClientErrorKind::Custom(format!("signature deserialization failed: {}", err)).into()
+            })
+        })
+        .map_err(|_| {
+            RpcError::ForUser(
+                "airdrop request failed. \
+                This can happen when the rate limit is reached."
+                    .to_string(),
+            )
+            .into()
+        })
+    }
+
+    pub async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        let (blockhash, _) = self
+            .get_latest_blockhash_with_commitment(self.commitment())
+            .await?;
+        Ok(blockhash)
+    }
+
+    #[allow(deprecated)]
+    pub async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        let (request, blockhash, last_valid_block_height) =
+            if self.get_node_version().await? < semver::Version::new(1, 8, 0) {
+                let RpcFees {
+                    blockhash,
+                    last_valid_block_height,
+                    ..
+                } = self
+                    .send::<Response<RpcFees>>(
+                        RpcRequest::GetFees,
+                        json!([self.maybe_map_commitment(commitment).await?]),
+                    )
+                    .await?
+                    .value;
+                (RpcRequest::GetFees, blockhash, last_valid_block_height)
+            } else {
+                let RpcBlockhash {
+                    blockhash,
+                    last_valid_block_height,
+                } = self
+                    .send::<Response<RpcBlockhash>>(
+                        RpcRequest::GetLatestBlockhash,
+                        json!([self.maybe_map_commitment(commitment).await?]),
+                    )
+                    .await?
+                    .value;
+                (
+                    RpcRequest::GetLatestBlockhash,
+                    blockhash,
+                    last_valid_block_height,
+                )
+            };
+        let blockhash = blockhash.parse().map_err(|_| {
+            ClientError::new_with_request(RpcError::ParseError("Hash".to_string()).into(), request)
+        })?;
+        Ok((blockhash, last_valid_block_height))
+    }
+
+    #[allow(deprecated)]
+    pub async fn is_blockhash_valid(
+        &self,
+        blockhash: &Hash,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<bool> {
+        let result = if self.get_node_version().await? < semver::Version::new(1, 8, 0) {
+            self.send::<Response<Option<RpcFeeCalculator>>>(
+                RpcRequest::GetFeeCalculatorForBlockhash,
+                json!([
+                    blockhash.to_string(),
+                    self.maybe_map_commitment(commitment).await?
+                ]),
+            )
+            .await?
+            .value
+            .is_some()
+        } else {
+            self.send::<Response<bool>>(
+                RpcRequest::IsBlockhashValid,
+                json!([blockhash.to_string(), commitment,]),
+            )
+            .await?
+            .value
+        };
+        Ok(result)
+    }
+
+    pub async fn get_fee_for_message(&self, message: &Message) -> ClientResult<u64> {
+        let serialized_encoded =
+            serialize_and_encode::<Message>(message, UiTransactionEncoding::Base64)?;
+        let result = self
+            .send::<Response<Option<u64>>>(
+                RpcRequest::GetFeeForMessage,
+                json!([serialized_encoded, self.commitment()]),
+            )
+            .await?;
+        result
+            .value
+            .ok_or_else(|| ClientErrorKind::Custom("Invalid blockhash".to_string()).into())
+    }
+
+    pub async fn get_highest_snapshot_slot(&self) -> ClientResult<RpcSnapshotSlotInfo> {
+        if self.get_node_version().await? < semver::Version::new(1, 8, 0) {
+            #[allow(deprecated)]
+            self.get_snapshot_slot()
+                .await
+                .map(|full| RpcSnapshotSlotInfo {
+                    full,
+                    incremental: None,
+                })
+        } else {
+            self.send(RpcRequest::GetHighestSnapshotSlot, Value::Null)
+                .await
+        }
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please use RpcClient::get_highest_snapshot_slot() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_snapshot_slot(&self) -> ClientResult<Slot> {
+        self.send(RpcRequest::GetSnapshotSlot, Value::Null).await
+    }
+
+    pub async fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        let signatures: Vec<_> = signatures.iter().map(|s| s.to_string()).collect();
+        self.send(
+            RpcRequest::GetSignatureStatuses,
+            json!([signatures, {
+                "searchTransactionHistory": true
+            }]),
+        )
+        .await
+    }
+
+    pub async fn get_signature_status_with_commitment_and_history(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+        search_transaction_history: bool,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        let result: Response<Vec<Option<TransactionStatus>>> = self
+            .send(
+                RpcRequest::GetSignatureStatuses,
+                json!([[signature.to_string()], {
+                    "searchTransactionHistory": search_transaction_history
+                }]),
+            )
+            .await?;
+        Ok(result.value[0]
+            .clone()
+            .filter(|result| result.satisfies_commitment(commitment_config))
+            .map(|status_meta| status_meta.status))
+    }
+
+    pub async fn get_slot_leaders(
+        &self,
+        start_slot: Slot,
+        limit: u64,
+    ) -> ClientResult<Vec<Pubkey>> {
+        self.send(RpcRequest::GetSlotLeaders, json!([start_slot, limit]))
+            .await
+            .and_then(|slot_leaders: Vec<String>| {
+                slot_leaders
+                    .iter()
+                    .map(|slot_leader| {
+                        Pubkey::from_str(slot_leader).map_err(|err| {
+                            ClientErrorKind::Custom(format!(
+                                "pubkey deserialization failed: {}",
+                                err
+                            ))
+                            .into()
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    pub async fn get_block_production(&self) -> RpcResult<RpcBlockProduction> {
+        self.send(RpcRequest::GetBlockProduction, Value::Null).await
+    }
+
+    pub async fn get_block_production_with_config(
+        &self,
+        config: RpcBlockProductionConfig,
+    ) -> RpcResult<RpcBlockProduction> {
+        self.send(RpcRequest::GetBlockProduction, json!([config]))
+            .await
+    }
+
+    pub async fn get_stake_activation(
+        &self,
+        stake_account: Pubkey,
+        epoch: Option<Epoch>,
+    ) -> ClientResult<RpcStakeActivation> {
+        self.send(
+            RpcRequest::GetStakeActivation,
+            json!([
+                stake_account.to_string(),
+                RpcEpochConfig {
+                    epoch,
+                    commitment: Some(self.commitment()),
+                }
+            ]),
+        )
+        .await
+    }
+
+    pub async fn supply(&self) -> RpcResult<RpcSupply> {
+        self.supply_with_commitment(self.commitment()).await
+    }
+
+    pub async fn supply_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<RpcSupply> {
+        self.send(
+            RpcRequest::GetSupply,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn get_largest_accounts_with_config(
+        &self,
+        config: RpcLargestAccountsConfig,
+    ) -> RpcResult<Vec<RpcAccountBalance>> {
+        let commitment = config.commitment.unwrap_or_default();
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        let config = RpcLargestAccountsConfig {
+            commitment: Some(commitment),
+            ..config
+        };
+        self.send(RpcRequest::GetLargestAccounts, json!([config]))
+            .await
+    }
+
+    pub async fn get_vote_accounts(&self) -> ClientResult<RpcVoteAccountStatus> {
+        self.get_vote_accounts_with_commitment(self.commitment())
+            .await
+    }
+
+    pub async fn get_vote_accounts_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<RpcVoteAccountStatus> {
+        self.get_vote_accounts_with_config(RpcGetVoteAccountsConfig {
+            commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+            ..RpcGetVoteAccountsConfig::default()
+        })
+        .await
+    }
+
+    pub async fn get_vote_accounts_with_config(
+        &self,
+        config: RpcGetVoteAccountsConfig,
+    ) -> ClientResult<RpcVoteAccountStatus> {
+        self.send(RpcRequest::GetVoteAccounts, json!([config]))
+            .await
+    }
+
+    pub async fn wait_for_max_stake(
+        &self,
+        commitment: CommitmentConfig,
+        max_stake_percent: f32,
+    ) -> ClientResult<()> {
+        let mut current_percent;
+        loop {
+            let vote_accounts = self.get_vote_accounts_with_commitment(commitment).await?;
+            let mut max = 0;
+            let total_active_stake = vote_accounts
+                .current
+                .iter()
+                .chain(vote_accounts.delinquent.iter())
+                .map(|vote_account| {
+                    max = std::cmp::max(max, vote_account.activated_stake);
+                    vote_account.activated_stake
+                })
+                .sum::<u64>();
+            current_percent = 100f32 * max as f32 / total_active_stake as f32;
+            if current_percent < max_stake_percent {
+                break;
+            }
+            info!(
+                "Waiting for stake to drop below {} current: {:.1}",
+                max_stake_percent, current_percent
+            );
+            sleep(Duration::from_secs(10)).await;
+        }
+        Ok(())
+    }
+
+    pub async fn get_cluster_nodes(&self) -> ClientResult<Vec<RpcContactInfo>> {
+        self.send(RpcRequest::GetClusterNodes, Value::Null).await
+    }
+
+    pub async fn get_block(&self, slot: Slot) -> ClientResult<EncodedConfirmedBlock> {
+        self.get_block_with_encoding(slot, UiTransactionEncoding::Json)
+            .await
+    }
+
+    pub async fn get_block_with_encoding(
+        &self,
+        slot: Slot,
+        encoding: UiTransactionEncoding,
+    ) -> ClientResult<EncodedConfirmedBlock> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetBlock).await?,
+            json!([slot, encoding]),
+        )
+        .await
+    }
+
+    pub async fn get_block_with_config(
+        &self,
+        slot: Slot,
+        config: RpcBlockConfig,
+    ) -> ClientResult<UiConfirmedBlock> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetBlock).await?,
+            json!([slot, config]),
+        )
+        .await
+    }
+
+    #[deprecated(since = "1.7.0", note = "Please use RpcClient::get_block() instead")]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_block(&self, slot: Slot) -> ClientResult<EncodedConfirmedBlock> {
+        self.get_confirmed_block_with_encoding(slot, UiTransactionEncoding::Json)
+            .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_block_with_encoding() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_block_with_encoding(
+        &self,
+        slot: Slot,
+        encoding: UiTransactionEncoding,
+    ) -> ClientResult<EncodedConfirmedBlock> {
+        self.send(RpcRequest::GetConfirmedBlock, json!([slot, encoding]))
+            .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_block_with_config() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_block_with_config(
+        &self,
+        slot: Slot,
+        config: RpcConfirmedBlockConfig,
+    ) -> ClientResult<UiConfirmedBlock> {
+        self.send(RpcRequest::GetConfirmedBlock, json!([slot, config]))
+            .await
+    }
+
+    pub async fn get_blocks(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetBlocks).await?,
+            json!([start_slot, end_slot]),
+        )
+        .await
+    }
+
+    pub async fn get_blocks_with_commitment(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Vec<Slot>> {
+        let json = if end_slot.is_some() {
+            json!([
+                start_slot,
+                end_slot,
+                self.maybe_map_commitment(commitment_config).await?
+            ])
+        } else {
+            json!([
+                start_slot,
+                self.maybe_map_commitment(commitment_config).await?
+            ])
+        };
+        self.send(self.maybe_map_request(RpcRequest::GetBlocks).await?, json)
+            .await
+    }
+
+    pub async fn get_blocks_with_limit(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetBlocksWithLimit)
+                .await?,
+            json!([start_slot, limit]),
+        )
+        .await
+    }
+
+    pub async fn get_blocks_with_limit_and_commitment(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetBlocksWithLimit)
+                .await?,
+            json!([
+                start_slot,
+                limit,
+                self.maybe_map_commitment(commitment_config).await?
+            ]),
+        )
+        .await
+    }
+
+    #[deprecated(since = "1.7.0", note = "Please use RpcClient::get_blocks() instead")]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_blocks(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            RpcRequest::GetConfirmedBlocks,
+            json!([start_slot, end_slot]),
+        )
+        .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_blocks_with_commitment() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_blocks_with_commitment(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Vec<Slot>> {
+        let json = if end_slot.is_some() {
+            json!([
+                start_slot,
+                end_slot,
+                self.maybe_map_commitment(commitment_config).await?
+            ])
+        } else {
+            json!([
+                start_slot,
+                self.maybe_map_commitment(commitment_config).await?
+            ])
+        };
+        self.send(RpcRequest::GetConfirmedBlocks, json).await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_blocks_with_limit() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_blocks_with_limit(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            RpcRequest::GetConfirmedBlocksWithLimit,
+            json!([start_slot, limit]),
+        )
+        .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_blocks_with_limit_and_commitment() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_blocks_with_limit_and_commitment(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Vec<Slot>> {
+        self.send(
+            RpcRequest::GetConfirmedBlocksWithLimit,
+            json!([
+                start_slot,
+                limit,
+                self.maybe_map_commitment(commitment_config).await?
+            ]),
+        )
+        .await
+    }
+
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config::default(),
+        )
+        .await
+    }
+
+    pub async fn get_signatures_for_address_with_config(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let config = RpcSignaturesForAddressConfig {
+            before: config.before.map(|signature| signature.to_string()),
+            until: config.until.map(|signature| signature.to_string()),
+            limit: config.limit,
+            memo: None,
+            commitment: config.commitment,
+        };
+        let result: Vec<RpcConfirmedTransactionStatusWithSignature> = self
+            .send(
+                self.maybe_map_request(RpcRequest::GetSignaturesForAddress)
+                    .await?,
+                json!([address.to_string(), config]),
+            )
+            .await?;
+        Ok(result)
+    }
+
+    pub async fn get_signatures_for_address_with_memo(
+        &self,
+        address: &Pubkey,
+        memo: &str,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<RpcSignaturesForAddressPage> {
+        let config = RpcSignaturesForAddressConfig {
+            before: config.before.map(|signature| signature.to_string()),
+            until: config.until.map(|signature| signature.to_string()),
+            limit: config.limit,
+            memo: Some(memo.to_string()),
+            commitment: config.commitment,
+        };
+        self.send(
+            self.maybe_map_request(RpcRequest::GetSignaturesForAddress)
+                .await?,
+            json!([address.to_string(), config]),
+        )
+        .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_signatures_for_address() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_signatures_for_address2(
+        &self,
+        address: &Pubkey,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.get_confirmed_signatures_for_address2_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config::default(),
+        )
+        .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_signatures_for_address_with_config() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_signatures_for_address2_with_config(
+        &self,
+        address: &Pubkey,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let config = RpcGetConfirmedSignaturesForAddress2Config {
+            before: config.before.map(|signature| signature.to_string()),
+            until: config.until.map(|signature| signature.to_string()),
+            limit: config.limit,
+            commitment: config.commitment,
+        };
+        let result: Vec<RpcConfirmedTransactionStatusWithSignature> = self
+            .send(
+                RpcRequest::GetConfirmedSignaturesForAddress2,
+                json!([address.to_string(), config]),
+            )
+            .await?;
+        Ok(result)
+    }
+
+    pub async fn get_transaction(
+        &self,
+        signature: &Signature,
+        encoding: UiTransactionEncoding,
+    ) -> ClientResult<EncodedConfirmedTransaction> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetTransaction).await?,
+            json!([signature.to_string(), encoding]),
+        )
+        .await
+    }
+
+    pub async fn get_transaction_with_config(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> ClientResult<EncodedConfirmedTransaction> {
+        self.send(
+            self.maybe_map_request(RpcRequest::GetTransaction).await?,
+            json!([signature.to_string(), config]),
+        )
+        .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_transaction() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+        encoding: UiTransactionEncoding,
+    ) -> ClientResult<EncodedConfirmedTransaction> {
+        self.send(
+            RpcRequest::GetConfirmedTransaction,
+            json!([signature.to_string(), encoding]),
+        )
+        .await
+    }
+
+    #[deprecated(
+        since = "1.7.0",
+        note = "Please use RpcClient::get_transaction_with_config() instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_confirmed_transaction_with_config(
+        &self,
+        signature: &Signature,
+        config: RpcConfirmedTransactionConfig,
+    ) -> ClientResult<EncodedConfirmedTransaction> {
+        self.send(
+            RpcRequest::GetConfirmedTransaction,
+            json!([signature.to_string(), config]),
+        )
+        .await
+    }
+
+    pub async fn get_block_time(&self, slot: Slot) -> ClientResult<UnixTimestamp> {
+        let request = RpcRequest::GetBlockTime;
+        let response = self.sender.send(request, json!([slot])).await;
+        response
+            .map(|result_json| {
+                if result_json.is_null() {
+                    return Err(RpcError::ForUser(format!("Block Not Found: slot={}", slot)).into());
+                }
+                let result = serde_json::from_value(result_json)
+                    .map_err(|err| ClientError::new_with_request(err.into(), request))?;
+                trace!("Response block timestamp {:?} {:?}", slot, result);
+                Ok(result)
+            })
+            .map_err(|err| err.into_with_request(request))?
+    }
+
+    pub async fn get_epoch_accounts_hash_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<RpcEpochAccountsHash>> {
+        self.send(
+            RpcRequest::GetEpochAccountsHash,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn get_leader_schedule(
+        &self,
+        slot: Option<Slot>,
+    ) -> ClientResult<Option<RpcLeaderSchedule>> {
+        self.get_leader_schedule_with_commitment(slot, self.commitment())
+            .await
+    }
+
+    pub async fn get_leader_schedule_with_commitment(
+        &self,
+        slot: Option<Slot>,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<RpcLeaderSchedule>> {
+        self.get_leader_schedule_with_config(
+            slot,
+            RpcLeaderScheduleConfig {
+                commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+                ..RpcLeaderScheduleConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn get_leader_schedule_with_config(
+        &self,
+        slot: Option<Slot>,
+        config: RpcLeaderScheduleConfig,
+    ) -> ClientResult<Option<RpcLeaderSchedule>> {
+        self.send(RpcRequest::GetLeaderSchedule, json!([slot, config]))
+            .await
+    }
+
+    pub async fn get_epoch_schedule(&self) -> ClientResult<EpochSchedule> {
+        self.send(RpcRequest::GetEpochSchedule, Value::Null).await
+    }
+
+    pub async fn get_feature_statuses(&self) -> ClientResult<Vec<RpcFeatureStatus>> {
+        Ok(self
+            .get_feature_statuses_with_commitment(self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_feature_statuses_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Vec<RpcFeatureStatus>> {
+        self.send(
+            RpcRequest::GetFeatureStatuses,
+            json!([self.maybe_map_commitment(commitment_config).await?]),
+        )
+        .await
+    }
+
+    pub async fn get_recent_performance_samples(
+        &self,
+        limit: Option<usize>,
+    ) -> ClientResult<Vec<RpcPerfSample>> {
+        self.send(RpcRequest::GetRecentPerformanceSamples, json!([limit]))
+            .await
+    }
+
+    pub async fn get_equivocation_evidence(
+        &self,
+        start_slot: Option<Slot>,
+        limit: Option<usize>,
+    ) -> ClientResult<Vec<RpcEquivocationEvidence>> {
+        self.send(
+            RpcRequest::GetEquivocationEvidence,
+            json!([start_slot, limit]),
+        )
+        .await
+    }
+
+    pub async fn get_inflation_governor(&self) -> ClientResult<RpcInflationGovernor> {
+        self.send(RpcRequest::GetInflationGovernor, Value::Null)
+            .await
+    }
+
+    pub async fn get_inflation_rate(&self) -> ClientResult<RpcInflationRate> {
+        self.send(RpcRequest::GetInflationRate, Value::Null).await
+    }
+
+    pub async fn get_inflation_reward(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<Epoch>,
+    ) -> ClientResult<Vec<Option<RpcInflationReward>>> {
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        self.send(
+            RpcRequest::GetInflationReward,
+            json!([
+                addresses,
+                RpcEpochConfig {
+                    epoch,
+                    commitment: Some(self.commitment()),
+                }
+            ]),
+        )
+        .await
+    }
+
+    pub async fn minimum_ledger_slot(&self) -> ClientResult<Slot> {
+        self.send(RpcRequest::MinimumLedgerSlot, Value::Null).await
+    }
+
+    pub async fn get_max_retransmit_slot(&self) -> ClientResult<Slot> {
+        self.send(RpcRequest::GetMaxRetransmitSlot, Value::Null)
+            .await
+    }
+
+    pub async fn get_max_shred_insert_slot(&self) -> ClientResult<Slot> {
+        self.send(RpcRequest::GetMaxShredInsertSlot, Value::Null)
+            .await
+    }
+
+    pub async fn get_program_accounts(
+        &self,
+        pubkey: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.get_program_accounts_with_config(
+            pubkey,
+            RpcProgramAccountsConfig {
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64Zstd),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn get_program_accounts_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let commitment = config
+            .account_config
+            .commitment
+            .unwrap_or_else(|| self.commitment());
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        let account_config = RpcAccountInfoConfig {
+            commitment: Some(commitment),
+            ..config.account_config
+        };
+        let config = RpcProgramAccountsConfig {
+            account_config,
+            ..config
+        };
+        let accounts: Vec<RpcKeyedAccount> = self
+            .send(
+                RpcRequest::GetProgramAccounts,
+                json!([pubkey.to_string(), config]),
+            )
+            .await?;
+        parse_keyed_accounts(accounts, RpcRequest::GetProgramAccounts)
+    }
+
+    pub async fn get_program_accounts_by_prefix_with_config(
+        &self,
+        pubkey: &Pubkey,
+        prefix: &[u8],
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let commitment = config
+            .account_config
+            .commitment
+            .unwrap_or_else(|| self.commitment());
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        let account_config = RpcAccountInfoConfig {
+            commitment: Some(commitment),
+            ..config.account_config
+        };
+        let config = RpcProgramAccountsConfig {
+            account_config,
+            ..config
+        };
+        let accounts: Vec<RpcKeyedAccount> = self
+            .send(
+                RpcRequest::GetProgramAccountsByPrefix,
+                json!([
+                    pubkey.to_string(),
+                    bs58::encode(prefix).into_string(),
+                    config
+                ]),
+            )
+            .await?;
+        parse_keyed_accounts(accounts, RpcRequest::GetProgramAccountsByPrefix)
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please use `get_latest_blockhash` and `get_fee_for_message` instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_fees(&self) -> ClientResult<Fees> {
+        #[allow(deprecated)]
+        Ok(self
+            .get_fees_with_commitment(self.commitment())
+            .await?
+            .value)
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please use `get_latest_blockhash_with_commitment` and `get_fee_for_message` instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_fees_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Fees> {
+        let Response {
+            context,
+            value: fees,
+        } = self
+            .send::<Response<RpcFees>>(
+                RpcRequest::GetFees,
+                json!([self.maybe_map_commitment(commitment_config).await?]),
+            )
+            .await?;
+        let blockhash = fees.blockhash.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Hash".to_string()).into(),
+                RpcRequest::GetFees,
+            )
+        })?;
+        Ok(Response {
+            context,
+            value: Fees {
+                blockhash,
+                fee_calculator: fees.fee_calculator,
+                last_valid_block_height: fees.last_valid_block_height,
+            },
+        })
+    }
+
+    #[deprecated(since = "1.8.0", note = "Please use `get_latest_blockhash` instead")]
+    #[allow(deprecated)]
+    pub async fn get_recent_blockhash(&self) -> ClientResult<(Hash, FeeCalculator)> {
+        #[allow(deprecated)]
+        let (blockhash, fee_calculator, _last_valid_slot) = self
+            .get_recent_blockhash_with_commitment(self.commitment())
+            .await?
+            .value;
+        Ok((blockhash, fee_calculator))
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please use `get_latest_blockhash_with_commitment` instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_recent_blockhash_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<(Hash, FeeCalculator, Slot)> {
+        let (context, blockhash, fee_calculator, last_valid_slot) = if let Ok(Response {
+            context,
+            value:
+                RpcFees {
+                    blockhash,
+                    fee_calculator,
+                    last_valid_slot,
+                    ..
+                },
+        }) = self
+            .send::<Response<RpcFees>>(
+                RpcRequest::GetFees,
+                json!([self.maybe_map_commitment(commitment_config).await?]),
+            )
+            .await
+        {
+            (context, blockhash, fee_calculator, last_valid_slot)
+        } else if let Ok(Response {
+            context,
+            value:
+                DeprecatedRpcFees {
+                    blockhash,
+                    fee_calculator,
+                    last_valid_slot,
+                },
+        }) = self
+            .send::<Response<DeprecatedRpcFees>>(
+                RpcRequest::GetFees,
+                json!([self.maybe_map_commitment(commitment_config).await?]),
+            )
+            .await
+        {
+            (context, blockhash, fee_calculator, last_valid_slot)
+        } else if let Ok(Response {
+            context,
+            value:
+                RpcBlockhashFeeCalculator {
+                    blockhash,
+                    fee_calculator,
+                },
+        }) = self
+            .send::<Response<RpcBlockhashFeeCalculator>>(
+                RpcRequest::GetRecentBlockhash,
+                json!([self.maybe_map_commitment(commitment_config).await?]),
+            )
+            .await
+        {
+            (context, blockhash, fee_calculator, 0)
+        } else {
+            return Err(ClientError::new_with_request(
+                RpcError::ParseError("RpcBlockhashFeeCalculator or RpcFees".to_string()).into(),
+                RpcRequest::GetRecentBlockhash,
+            ));
+        };
+        let blockhash = blockhash.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Hash".to_string()).into(),
+                RpcRequest::GetRecentBlockhash,
+            )
+        })?;
+        Ok(Response {
+            context,
+            value: (blockhash, fee_calculator, last_valid_slot),
+        })
+    }
+
+    #[deprecated(since = "1.8.0", note = "Please `get_fee_for_message` instead")]
+    #[allow(deprecated)]
+    pub async fn get_fee_calculator_for_blockhash(
+        &self,
+        blockhash: &Hash,
+    ) -> ClientResult<Option<FeeCalculator>> {
+        #[allow(deprecated)]
+        Ok(self
+            .get_fee_calculator_for_blockhash_with_commitment(blockhash, self.commitment())
+            .await?
+            .value)
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please `get_latest_blockhash_with_commitment` and `get_fee_for_message` instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_fee_calculator_for_blockhash_with_commitment(
+        &self,
+        blockhash: &Hash,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Option<FeeCalculator>> {
+        let Response { context, value } = self
+            .send::<Response<Option<RpcFeeCalculator>>>(
+                RpcRequest::GetFeeCalculatorForBlockhash,
+                json!([
+                    blockhash.to_string(),
+                    self.maybe_map_commitment(commitment_config).await?
+                ]),
+            )
+            .await?;
+        Ok(Response {
+            context,
+            value: value.map(|rf| rf.fee_calculator),
+        })
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please do not use, will no longer be available in the future"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_fee_rate_governor(&self) -> RpcResult<FeeRateGovernor> {
+        let Response {
+            context,
+            value: RpcFeeRateGovernor { fee_rate_governor },
+        } = self
+            .send::<Response<RpcFeeRateGovernor>>(RpcRequest::GetFeeRateGovernor, Value::Null)
+            .await?;
+        Ok(Response {
+            context,
+            value: fee_rate_governor,
+        })
+    }
+
+    #[deprecated(
+        since = "1.8.0",
+        note = "Please use `get_new_latest_blockhash` instead"
+    )]
+    #[allow(deprecated)]
+    pub async fn get_new_blockhash(&self, blockhash: &Hash) -> ClientResult<(Hash, FeeCalculator)> {
+        let mut num_retries = 0;
+        let start = Instant::now();
+        while start.elapsed().as_secs() < 5 {
+            #[allow(deprecated)]
+            if let Ok((new_blockhash, fee_calculator)) = self.get_recent_blockhash().await {
+                if new_blockhash != *blockhash {
+                    return Ok((new_blockhash, fee_calculator));
+                }
+            }
+            debug!("Got same blockhash ({:?}), will retry...", blockhash);
+            // Retry ~twice during a slot
+            sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT / 2)).await;
+            num_retries += 1;
+        }
+        Err(RpcError::ForUser(format!(
+            "Unable to get new blockhash after {}ms (retried {} times), stuck at {}",
+            start.elapsed().as_millis(),
+            num_retries,
+            blockhash
+        ))
+        .into())
+    }
+
+    pub async fn get_first_available_block(&self) -> ClientResult<Slot> {
+        self.send(RpcRequest::GetFirstAvailableBlock, Value::Null)
+            .await
+    }
+
+    pub async fn get_token_account(&self, pubkey: &Pubkey) -> ClientResult<Option<UiTokenAccount>> {
+        Ok(self
+            .get_token_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_token_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Option<UiTokenAccount>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::JsonParsed),
+            commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+            data_slice: None,
+            with_last_modified_slot: None,
+        };
+        let response = self
+            .sender
+            .send(
+                RpcRequest::GetAccountInfo,
+                json!([pubkey.to_string(), config]),
+            )
+            .await;
+        response
+            .map(|result_json| {
+                if result_json.is_null() {
+                    return Err(
+                        RpcError::ForUser(format!("AccountNotFound: pubkey={}", pubkey)).into(),
+                    );
+                }
+                let Response {
+                    context,
+                    value: rpc_account,
+                } = serde_json::from_value::<Response<Option<UiAccount>>>(result_json)?;
+                trace!("Response account {:?} {:?}", pubkey, rpc_account);
+                let response = {
+                    if let Some(rpc_account) = rpc_account {
+                        if let UiAccountData::Json(account_data) = rpc_account.data {
+                            let token_account_type: TokenAccountType =
+                                serde_json::from_value(account_data.parsed)?;
+                            if let TokenAccountType::Account(token_account) = token_account_type {
+                                return Ok(Response {
+                                    context,
+                                    value: Some(token_account),
+                                });
+                            }
+                        }
+                    }
+                    Err(Into::<ClientError>::into(RpcError::ForUser(format!(
+                        "Account could not be parsed as token account: pubkey={}",
+                        pubkey
+                    ))))
+                };
+                response?
+            })
+            .map_err(|err| {
+                Into::<ClientError>::into(RpcError::ForUser(format!(
+                    "AccountNotFound: pubkey={}: {}",
+                    pubkey, err
+                )))
+            })?
+    }
+
+    pub async fn get_token_account_balance(&self, pubkey: &Pubkey) -> ClientResult<UiTokenAmount> {
+        Ok(self
+            .get_token_account_balance_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_token_account_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<UiTokenAmount> {
+        self.send(
+            RpcRequest::GetTokenAccountBalance,
+            json!([
+                pubkey.to_string(),
+                self.maybe_map_commitment(commitment_config).await?
+            ]),
+        )
+        .await
+    }
+
+    pub async fn get_token_accounts_by_delegate(
+        &self,
+        delegate: &Pubkey,
+        token_account_filter: TokenAccountsFilter,
+    ) -> ClientResult<Vec<RpcKeyedAccount>> {
+        Ok(self
+            .get_token_accounts_by_delegate_with_commitment(
+                delegate,
+                token_account_filter,
+                self.commitment(),
+            )
+            .await?
+            .value)
+    }
+
+    pub async fn get_token_accounts_by_delegate_with_commitment(
+        &self,
+        delegate: &Pubkey,
+        token_account_filter: TokenAccountsFilter,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Vec<RpcKeyedAccount>> {
+        let token_account_filter = match token_account_filter {
+            TokenAccountsFilter::Mint(mint) => RpcTokenAccountsFilter::Mint(mint.to_string()),
+            TokenAccountsFilter::ProgramId(program_id) => {
+                RpcTokenAccountsFilter::ProgramId(program_id.to_string())
+            }
+        };
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::JsonParsed),
+            commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+            data_slice: None,
+            with_last_modified_slot: None,
+        };
+        self.send(
+            RpcRequest::GetTokenAccountsByOwner,
+            json!([delegate.to_string(), token_account_filter, config]),
+        )
+        .await
+    }
+
+    pub async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        token_account_filter: TokenAccountsFilter,
+    ) -> ClientResult<Vec<RpcKeyedAccount>> {
+        Ok(self
+            .get_token_accounts_by_owner_with_commitment(
+                owner,
+                token_account_filter,
+                self.commitment(),
+            )
+            .await?
+            .value)
+    }
+
+    pub async fn get_token_accounts_by_owner_with_commitment(
+        &self,
+        owner: &Pubkey,
+        token_account_filter: TokenAccountsFilter,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Vec<RpcKeyedAccount>> {
+        let token_account_filter = match token_account_filter {
+            TokenAccountsFilter::Mint(mint) => RpcTokenAccountsFilter::Mint(mint.to_string()),
+            TokenAccountsFilter::ProgramId(program_id) => {
+                RpcTokenAccountsFilter::ProgramId(program_id.to_string())
+            }
+        };
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::JsonParsed),
+            commitment: Some(self.maybe_map_commitment(commitment_config).await?),
+            data_slice: None,
+            with_last_modified_slot: None,
+        };
+        self.send(
+            RpcRequest::GetTokenAccountsByOwner,
+            json!([owner.to_string(), token_account_filter, config]),
+        )
+        .await
+    }
+
+    pub async fn get_token_supply(&self, mint: &Pubkey) -> ClientResult<UiTokenAmount> {
+        Ok(self
+            .get_token_supply_with_commitment(mint, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_token_supply_with_commitment(
+        &self,
+        mint: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<UiTokenAmount> {
+        self.send(
+            RpcRequest::GetTokenSupply,
+            json!([
+                mint.to_string(),
+                self.maybe_map_commitment(commitment_config).await?
+            ]),
+        )
+        .await
+    }
+
+    pub async fn request_airdrop_with_blockhash(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+        recent_blockhash: &Hash,
+    ) -> ClientResult<Signature> {
+        self.request_airdrop_with_config(
+            pubkey,
+            lamports,
+            RpcRequestAirdropConfig {
+                commitment: Some(self.commitment()),
+                recent_blockhash: Some(recent_blockhash.to_string()),
+            },
+        )
+        .await
+    }
+
+    async fn poll_balance_with_timeout_and_commitment(
+        &self,
+        pubkey: &Pubkey,
+        polling_frequency: &Duration,
+        timeout: &Duration,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        let now = Instant::now();
+        loop {
+            match self
+                .get_balance_with_commitment(pubkey, commitment_config)
+                .await
+            {
+                Ok(bal) => {
+                    return Ok(bal.value);
+                }
+                Err(e) => {
+                    sleep(*polling_frequency).await;
+                    if now.elapsed() > *timeout {
+                        return Err(e);
+                    }
+                }
+            };
+        }
+    }
+
+    pub async fn poll_get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        self.poll_balance_with_timeout_and_commitment(
+            pubkey,
+            &Duration::from_millis(100),
+            &Duration::from_secs(1),
+            commitment_config,
+        )
+        .await
+    }
+
+    pub async fn wait_for_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        expected_balance: Option<u64>,
+        commitment_config: CommitmentConfig,
+    ) -> Option<u64> {
+        const LAST: usize = 30;
+        for run in 0..LAST {
+            let balance_result = self
+                .poll_get_balance_with_commitment(pubkey, commitment_config)
+                .await;
+            if expected_balance.is_none() {
+                return balance_result.ok();
+            }
+            trace!(
+                "wait_for_balance_with_commitment [{}] {:?} {:?}",
+                run,
+                balance_result,
+                expected_balance
+            );
+            if let (Some(expected_balance), Ok(balance_result)) = (expected_balance, balance_result)
+            {
+                if expected_balance == balance_result {
+                    return Some(balance_result);
+                }
+            }
+        }
+        None
+    }
+
+    pub async fn poll_for_signature(&self, signature: &Signature) -> ClientResult<()> {
+        self.poll_for_signature_with_commitment(signature, self.commitment())
+            .await
+    }
+
+    pub async fn poll_for_signature_with_commitment(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<()> {
+        let now = Instant::now();
+        loop {
+            if let Ok(Some(_)) = self
+                .get_signature_status_with_commitment(signature, commitment_config)
+                .await
+            {
+                break;
+            }
+            if now.elapsed().as_secs() > 15 {
+                return Err(RpcError::ForUser(format!(
+                    "signature not found after {} seconds",
+                    now.elapsed().as_secs()
+                ))
+                .into());
+            }
+            sleep(Duration::from_millis(250)).await;
+        }
+        Ok(())
+    }
+
+    pub async fn poll_for_signature_confirmation(
+        &self,
+        signature: &Signature,
+        min_confirmed_blocks: usize,
+    ) -> ClientResult<usize> {
+        let mut now = Instant::now();
+        let mut confirmed_blocks = 0;
+        loop {
+            let response = self
+                .get_num_blocks_since_signature_confirmation(signature)
+                .await;
+            match response {
+                Ok(count) => {
+                    if confirmed_blocks != count {
+                        info!(
+                            "signature {} confirmed {} out of {} after {} ms",
+                            signature,
+                            count,
+                            min_confirmed_blocks,
+                            now.elapsed().as_millis()
+                        );
+                        now = Instant::now();
+                        confirmed_blocks = count;
+                    }
+                    if count >= min_confirmed_blocks {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    debug!("check_confirmations request failed: {:?}", err);
+                }
+            };
+            if now.elapsed().as_secs() > 20 {
+                info!(
+                    "signature {} confirmed {} out of {} failed after {} ms",
+                    signature,
+                    confirmed_blocks,
+                    min_confirmed_blocks,
+                    now.elapsed().as_millis()
+                );
+                if confirmed_blocks > 0 {
+                    return Ok(confirmed_blocks);
+                } else {
+                    return Err(RpcError::ForUser(format!(
+                        "signature not found after {} seconds",
+                        now.elapsed().as_secs()
+                    ))
+                    .into());
+                }
+            }
+            sleep(Duration::from_millis(250)).await;
+        }
+        Ok(confirmed_blocks)
+    }
+
+    pub async fn get_num_blocks_since_signature_confirmation(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<usize> {
+        let result: Response<Vec<Option<TransactionStatus>>> = self
+            .send(
+                RpcRequest::GetSignatureStatuses,
+                json!([[signature.to_string()]]),
+            )
+            .await?;
+        let confirmations = result.value[0]
+            .clone()
+            .ok_or_else(|| {
+                ClientError::new_with_request(
+                    ClientErrorKind::Custom("signature not found".to_string()),
+                    RpcRequest::GetSignatureStatuses,
+                )
+            })?
+            .confirmations
+            .unwrap_or(MAX_LOCKOUT_HISTORY + 1);
+        Ok(confirmations)
+    }
+
+    pub async fn get_fee_for_versioned_message(
+        &self,
+        message: &VersionedMessage,
+    ) -> ClientResult<u64> {
+        let serialized_encoded =
+            serialize_and_encode::<VersionedMessage>(message, UiTransactionEncoding::Base64)?;
+        let result = self
+            .send::<Response<Option<u64>>>(
+                RpcRequest::GetFeeForMessage,
+                json!([serialized_encoded, self.commitment()]),
+            )
+            .await?;
+        result
+            .value
+            .ok_or_else(|| ClientErrorKind::Custom("Invalid blockhash".to_string()).into())
+    }
+
+    pub async fn get_new_latest_blockhash(&self, blockhash: &Hash) -> ClientResult<Hash> {
+        let mut num_retries = 0;
+        let start = Instant::now();
+        while start.elapsed().as_secs() < 5 {
+            if let Ok(new_blockhash) = self.get_latest_blockhash().await {
+                if new_blockhash != *blockhash {
+                    return Ok(new_blockhash);
+                }
+            }
+            debug!("Got same blockhash ({:?}), will retry...", blockhash);
+            // Retry ~twice during a slot
+            sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT / 2)).await;
+            num_retries += 1;
+        }
+        Err(RpcError::ForUser(format!(
+            "Unable to get new blockhash after {}ms (retried {} times), stuck at {}",
+            start.elapsed().as_millis(),
+            num_retries,
+            blockhash
+        ))
+        .into())
+    }
+
+    pub fn get_transport_stats(&self) -> RpcTransportStats {
+        self.sender.get_transport_stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::mock_sender::PUBKEY,
+        solana_sdk::{signature::Keypair, system_transaction},
+    };
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_send_transaction() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let key = Keypair::new();
+        let to = solana_sdk::pubkey::new_rand();
+        let blockhash = rpc_client.get_latest_blockhash().await.unwrap();
+        let tx = system_transaction::transfer(&key, &to, 50, blockhash);
+
+        let signature = rpc_client.send_transaction(&tx).await;
+        assert_eq!(signature.unwrap(), tx.signatures[0]);
+        assert_eq!(
+            rpc_client.send_and_confirm_transaction(&tx).await.unwrap(),
+            tx.signatures[0]
+        );
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.send_transaction(&tx).await.is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_balance_and_custom_request() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let pubkey = Pubkey::from_str(PUBKEY).unwrap();
+        assert_eq!(rpc_client.get_balance(&pubkey).await.unwrap(), 50);
+
+        let mut mocks = Mocks::default();
+        mocks.insert(RpcRequest::GetSlot, json!(42));
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        assert_eq!(rpc_client.get_slot().await.unwrap(), 42);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_blocking_api_counterparts() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let pubkey = Pubkey::from_str(PUBKEY).unwrap();
+
+        assert!(rpc_client.get_block(1).await.is_ok());
+        assert!(rpc_client.get_blocks(1, Some(5)).await.is_ok());
+        assert!(rpc_client
+            .get_transaction(&Signature::default(), UiTransactionEncoding::Json)
+            .await
+            .is_ok());
+        assert!(rpc_client.get_signatures_for_address(&pubkey).await.is_ok());
+        assert!(rpc_client.get_program_accounts(&pubkey).await.is_ok());
+        assert!(rpc_client.get_vote_accounts().await.is_ok());
+        assert!(rpc_client.get_cluster_nodes().await.is_ok());
+        assert!(rpc_client.get_epoch_schedule().await.is_ok());
+        assert!(rpc_client.supply().await.is_ok());
+    }
+}
@@ -0,0 +1,24 @@
+//! A transport for async RPC calls.
+
+use {
+    crate::{client_error::Result, rpc_request::RpcRequest, rpc_sender::RpcTransportStats},
+    async_trait::async_trait,
+};
+
+/// A transport for async RPC calls, used by the nonblocking [`RpcClient`].
+///
+/// It is typically implemented by [`HttpSender`] in production, and
+/// [`MockSender`] in unit tests.
+///
+/// [`RpcClient`]: crate::nonblocking::rpc_client::RpcClient
+/// [`HttpSender`]: crate::nonblocking::http_sender::HttpSender
+/// [`MockSender`]: crate::mock_sender::MockSender
+#[async_trait]
+pub trait RpcSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value>;
+    fn get_transport_stats(&self) -> RpcTransportStats;
+}
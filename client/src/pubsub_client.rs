@@ -16,6 +16,7 @@ use {
     },
     solana_sdk::signature::Signature,
     std::{
+        io,
         marker::PhantomData,
         net::TcpStream,
         sync::{
@@ -31,6 +32,10 @@ use {
     url::{ParseError, Url},
 };
 
+/// How long a subscription thread blocks reading the socket, before releasing it so that the
+/// subscription can be unsubscribed and closed
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
 #[derive(Debug, Error)]
 pub enum PubsubClientError {
     #[error("url parse error")]
@@ -39,11 +44,20 @@ pub enum PubsubClientError {
     #[error("unable to connect to server")]
     ConnectionError(#[from] tungstenite::Error),
 
+    #[error("unable to connect to server")]
+    WsError(#[from] tokio_tungstenite::tungstenite::Error),
+
     #[error("json parse error")]
     JsonParseError(#[from] serde_json::error::Error),
 
     #[error("unexpected message format: {0}")]
     UnexpectedMessageError(String),
+
+    #[error("subscribe failed: {0}")]
+    SubscribeFailed(String),
+
+    #[error("connection closed")]
+    ConnectionClosed,
 }
 
 pub struct PubsubClientSubscription<T>
@@ -85,8 +99,13 @@ where
             .write()
             .unwrap()
             .write_message(Message::Text(body))?;
-        let message = writable_socket.write().unwrap().read_message()?;
-        Self::extract_subscription_id(message)
+        loop {
+            match writable_socket.write().unwrap().read_message() {
+                Ok(message) => return Self::extract_subscription_id(message),
+                Err(err) if is_read_timeout(&err) => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
     }
 
     fn extract_subscription_id(message: Message) -> Result<u64, PubsubClientError> {
@@ -119,17 +138,22 @@ where
             .map_err(|err| err.into())
     }
 
+    /// Read the next notification, `None` if there was none before the read timed out
     fn read_message(
         writable_socket: &Arc<RwLock<WebSocket<MaybeTlsStream<TcpStream>>>>,
-    ) -> Result<T, PubsubClientError> {
-        let message = writable_socket.write().unwrap().read_message()?;
+    ) -> Result<Option<T>, PubsubClientError> {
+        let message = match writable_socket.write().unwrap().read_message() {
+            Ok(message) => message,
+            Err(err) if is_read_timeout(&err) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
         let message_text = &message.into_text().unwrap();
         let json_msg: Map<String, Value> = serde_json::from_str(message_text)?;
 
         if let Some(Object(params)) = json_msg.get("params") {
             if let Some(result) = params.get("result") {
                 let x: T = serde_json::from_value::<T>(result.clone()).unwrap();
-                return Ok(x);
+                return Ok(Some(x));
             }
         }
 
@@ -166,6 +190,22 @@ pub type SignatureSubscription = (
 
 pub struct PubsubClient {}
 
+fn is_read_timeout(err: &tungstenite::Error) -> bool {
+    matches!(
+        err,
+        tungstenite::Error::Io(err)
+            if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+    )
+}
+
+fn set_read_timeout(socket: &WebSocket<MaybeTlsStream<TcpStream>>) -> io::Result<()> {
+    match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(READ_TIMEOUT)),
+        MaybeTlsStream::Rustls(stream) => stream.sock.set_read_timeout(Some(READ_TIMEOUT)),
+        _ => Ok(()),
+    }
+}
+
 fn connect_with_retry(
     url: Url,
 ) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, tungstenite::Error> {
@@ -196,6 +236,11 @@ fn connect_with_retry(
                 continue;
             }
         }
+        if let Ok(socket) = &result {
+            if let Err(err) = set_read_timeout(socket) {
+                warn!("unable to set websocket read timeout: {}", err);
+            }
+        }
         return result;
     }
 }
@@ -231,13 +276,14 @@ impl PubsubClient {
                 }
 
                 match PubsubClientSubscription::read_message(&socket_clone) {
-                    Ok(message) => match sender.send(message) {
+                    Ok(Some(message)) => match sender.send(message) {
                         Ok(_) => (),
                         Err(err) => {
                             info!("receive error: {:?}", err);
                             break;
                         }
                     },
+                    Ok(None) => (),
                     Err(err) => {
                         info!("receive error: {:?}", err);
                         break;
@@ -283,13 +329,14 @@ impl PubsubClient {
                     break;
                 }
                 match PubsubClientSubscription::read_message(&socket_clone) {
-                    Ok(message) => match sender.send(message) {
+                    Ok(Some(message)) => match sender.send(message) {
                         Ok(_) => (),
                         Err(err) => {
                             info!("receive error: {:?}", err);
                             break;
                         }
                     },
+                    Ok(None) => (),
                     Err(err) => {
                         info!("receive error: {:?}", err);
                         break;
@@ -347,20 +394,22 @@ impl PubsubClient {
                     break;
                 }
 
-                let message: Result<RpcResponse<RpcSignatureResult>, PubsubClientError> =
+                let message: Result<Option<RpcResponse<RpcSignatureResult>>, PubsubClientError> =
                     PubsubClientSubscription::read_message(&socket_clone);
 
-                if let Ok(msg) = message {
-                    match sender.send(msg.clone()) {
+                match message {
+                    Ok(Some(msg)) => match sender.send(msg) {
                         Ok(_) => (),
                         Err(err) => {
                             info!("receive error: {:?}", err);
                             break;
                         }
+                    },
+                    Ok(None) => (),
+                    Err(err) => {
+                        info!("receive error: {:?}", err);
+                        break;
                     }
-                } else {
-                    info!("receive error: {:?}", message);
-                    break;
                 }
             }
 
@@ -405,7 +454,8 @@ impl PubsubClient {
                         break;
                     }
                     match PubsubClientSubscription::read_message(&socket) {
-                        Ok(message) => handler(message),
+                        Ok(Some(message)) => handler(message),
+                        Ok(None) => (),
                         Err(err) => {
                             info!("receive error: {:?}", err);
                             break;
@@ -14,13 +14,17 @@ use crate::rpc_deprecated_config::{
 use {
     crate::{
         client_error::{ClientError, ClientErrorKind, Result as ClientResult},
-        http_sender::HttpSender,
+        connection_cache::{ConnectionCache, LeaderTpus},
+        http_sender::{HttpSender, HttpSenderConfig},
         mock_sender::{MockSender, Mocks},
+        nonce_utils,
+        pubsub_client::PubsubClient,
         rpc_config::RpcAccountInfoConfig,
         rpc_config::*,
         rpc_request::{RpcError, RpcRequest, RpcResponseErrorData, TokenAccountsFilter},
         rpc_response::*,
         rpc_sender::*,
+        tpu_client::{DEFAULT_FANOUT_SLOTS, MAX_FANOUT_SLOTS},
     },
     bincode::serialize,
     indicatif::{ProgressBar, ProgressStyle},
@@ -38,21 +42,21 @@ use {
         epoch_schedule::EpochSchedule,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         hash::Hash,
-        message::Message,
+        message::{Message, VersionedMessage},
         pubkey::Pubkey,
         signature::Signature,
-        transaction::{self, uses_durable_nonce, Transaction},
+        transaction::{self, uses_durable_nonce, Transaction, TransactionError},
     },
     solana_transaction_status::{
-        EncodedConfirmedBlock, EncodedConfirmedTransaction, TransactionStatus, UiConfirmedBlock,
-        UiTransactionEncoding,
+        EncodedConfirmedBlock, EncodedConfirmedTransaction, TransactionConfirmationStatus,
+        TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
     },
     solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY,
     std::{
         cmp::min,
         net::SocketAddr,
         str::FromStr,
-        sync::RwLock,
+        sync::{mpsc::RecvTimeoutError, Arc, RwLock},
         thread::sleep,
         time::{Duration, Instant},
     },
@@ -62,6 +66,7 @@ use {
 pub struct RpcClientConfig {
     commitment_config: CommitmentConfig,
     confirm_transaction_initial_timeout: Option<Duration>,
+    connection_cache: Option<Arc<ConnectionCache>>,
 }
 
 impl RpcClientConfig {
@@ -155,6 +160,7 @@ pub struct RpcClient {
     sender: Box<dyn RpcSender + Send + Sync + 'static>,
     config: RpcClientConfig,
     node_version: RwLock<Option<semver::Version>>,
+    leader_tpus: RwLock<LeaderTpus>,
 }
 
 impl RpcClient {
@@ -171,6 +177,7 @@ impl RpcClient {
         Self {
             sender: Box::new(sender),
             node_version: RwLock::new(None),
+            leader_tpus: RwLock::default(),
             config,
         }
     }
@@ -324,6 +331,110 @@ impl RpcClient {
             RpcClientConfig {
                 commitment_config,
                 confirm_transaction_initial_timeout: Some(confirm_transaction_initial_timeout),
+                ..RpcClientConfig::default()
+            },
+        )
+    }
+
+    /// Create an HTTP `RpcClient` with specified [`HttpSenderConfig`] and
+    /// [commitment level][cl].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    ///
+    /// The URL is an HTTP URL, usually for port 8899, as in
+    /// "http://localhost:8899".
+    ///
+    /// The `http_sender_config` argument specifies the request timeout, which
+    /// failed requests are retried and with what backoff, and middleware that
+    /// is run around every request, for example to add authentication
+    /// headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::{sync::Arc, time::Duration};
+    /// # use solana_client::{
+    /// #     client_error::reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    /// #     http_sender::{Backoff, HttpSenderConfig, HttpSenderMiddleware, RetryPolicy},
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_request::RpcRequest,
+    /// # };
+    /// # use solana_sdk::commitment_config::CommitmentConfig;
+    /// struct BearerAuth(HeaderValue);
+    ///
+    /// impl HttpSenderMiddleware for BearerAuth {
+    ///     fn before_request(&self, _request: RpcRequest, headers: &mut HeaderMap) {
+    ///         headers.insert(AUTHORIZATION, self.0.clone());
+    ///     }
+    /// }
+    ///
+    /// let url = "http://localhost::8899".to_string();
+    /// let backoff = Backoff {
+    ///     max_retries: 3,
+    ///     delay: Duration::from_millis(100),
+    ///     max_delay: Duration::from_secs(1),
+    /// };
+    /// let http_sender_config = HttpSenderConfig {
+    ///     retry_policy: RetryPolicy {
+    ///         server_error: Some(backoff),
+    ///         transport_error: Some(backoff),
+    ///         ..RetryPolicy::default()
+    ///     },
+    ///     middleware: vec![Arc::new(BearerAuth(HeaderValue::from_static("Bearer token")))],
+    ///     ..HttpSenderConfig::default()
+    /// };
+    /// let client = RpcClient::new_with_http_sender_config(
+    ///     url,
+    ///     http_sender_config,
+    ///     CommitmentConfig::processed(),
+    /// );
+    /// ```
+    pub fn new_with_http_sender_config(
+        url: String,
+        http_sender_config: HttpSenderConfig,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self::new_sender(
+            HttpSender::new_with_config(url, http_sender_config),
+            RpcClientConfig::with_commitment(commitment_config),
+        )
+    }
+
+    /// Create an HTTP `RpcClient` with specified [commitment level][cl] that
+    /// sends transactions straight to the TPUs of the upcoming leaders through
+    /// `connection_cache`, instead of submitting them to the RPC node.
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    ///
+    /// Only [`send_transaction`] is routed through the cache, see its
+    /// documentation.
+    ///
+    /// [`send_transaction`]: RpcClient::send_transaction
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use solana_client::{connection_cache::ConnectionCache, rpc_client::RpcClient};
+    /// # use solana_sdk::commitment_config::CommitmentConfig;
+    /// let url = "http://localhost:8899".to_string();
+    /// let connection_cache = Arc::new(ConnectionCache::default());
+    /// let client = RpcClient::new_with_connection_cache(
+    ///     url,
+    ///     CommitmentConfig::processed(),
+    ///     connection_cache,
+    /// );
+    /// ```
+    pub fn new_with_connection_cache(
+        url: String,
+        commitment_config: CommitmentConfig,
+        connection_cache: Arc<ConnectionCache>,
+    ) -> Self {
+        Self::new_sender(
+            HttpSender::new(url),
+            RpcClientConfig {
+                connection_cache: Some(connection_cache),
+                ..RpcClientConfig::with_commitment(commitment_config)
             },
         )
     }
@@ -669,15 +780,18 @@ impl RpcClient {
         commitment: CommitmentConfig,
         config: RpcSendTransactionConfig,
     ) -> ClientResult<Signature> {
-        let recent_blockhash = if uses_durable_nonce(transaction).is_some() {
-            self.get_latest_blockhash_with_commitment(CommitmentConfig::processed())?
-                .0
-        } else {
-            transaction.message.recent_blockhash
-        };
         let signature = self.send_transaction_with_config(transaction, config)?;
-        self.confirm_transaction_with_spinner(&signature, &recent_blockhash, commitment)?;
-        Ok(signature)
+        let confirmation = self.confirm_transaction_with_spinner_and_config(
+            transaction,
+            ConfirmTransactionConfig {
+                commitment,
+                ..ConfirmTransactionConfig::default()
+            },
+        )?;
+        match confirmation.err {
+            Some(err) => Err(err.into()),
+            None => Ok(signature),
+        }
     }
 
     /// Submits a signed transaction to the network.
@@ -719,6 +833,20 @@ impl RpcClient {
     /// [`JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE`]: crate::rpc_custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE
     /// [`JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY`]: crate::rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
     ///
+    /// # Connection cache
+    ///
+    /// If the client was created with [`new_with_connection_cache`], the
+    /// transaction is instead sent straight to the TPUs of the leaders of the
+    /// next [`DEFAULT_FANOUT_SLOTS`] slots, without a preflight check. The
+    /// leaders and their TPU addresses are fetched from the RPC node, with the
+    /// [`getSlotLeaders`] and [`getClusterNodes`] RPC methods, as the cached
+    /// ones run out.
+    ///
+    /// [`new_with_connection_cache`]: RpcClient::new_with_connection_cache
+    /// [`DEFAULT_FANOUT_SLOTS`]: crate::tpu_client::DEFAULT_FANOUT_SLOTS
+    /// [`getSlotLeaders`]: https://docs.solana.com/developing/clients/jsonrpc-api#getslotleaders
+    /// [`getClusterNodes`]: https://docs.solana.com/developing/clients/jsonrpc-api#getclusternodes
+    ///
     /// # RPC Reference
     ///
     /// This method is built on the [`sendTransaction`] RPC method.
@@ -750,6 +878,9 @@ impl RpcClient {
     /// # Ok::<(), ClientError>(())
     /// ```
     pub fn send_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        if let Some(connection_cache) = &self.config.connection_cache {
+            return self.send_transaction_to_leaders(connection_cache, transaction);
+        }
         self.send_transaction_with_config(
             transaction,
             RpcSendTransactionConfig {
@@ -761,6 +892,45 @@ impl RpcClient {
         )
     }
 
+    fn send_transaction_to_leaders(
+        &self,
+        connection_cache: &ConnectionCache,
+        transaction: &Transaction,
+    ) -> ClientResult<Signature> {
+        let wire_transaction = serialize(transaction).map_err(|err| {
+            ClientErrorKind::Custom(format!("transaction serialization failed: {}", err))
+        })?;
+        let slot = self.get_slot_with_commitment(CommitmentConfig::processed())?;
+        let cached_tpu_addrs = self
+            .leader_tpus
+            .read()
+            .unwrap()
+            .get(slot, DEFAULT_FANOUT_SLOTS);
+        let tpu_addrs = match cached_tpu_addrs {
+            Some(tpu_addrs) => tpu_addrs,
+            None => {
+                let leaders = self.get_slot_leaders(slot, MAX_FANOUT_SLOTS)?;
+                let cluster_nodes = self.get_cluster_nodes()?;
+                let mut leader_tpus = self.leader_tpus.write().unwrap();
+                leader_tpus.update(slot, leaders, cluster_nodes);
+                leader_tpus
+                    .get(slot, DEFAULT_FANOUT_SLOTS)
+                    .unwrap_or_default()
+            }
+        };
+        if tpu_addrs.is_empty() {
+            return Err(ClientErrorKind::Custom(format!(
+                "no TPU address for the leaders of slot {}",
+                slot
+            ))
+            .into());
+        }
+        for tpu_addr in tpu_addrs {
+            connection_cache.send_wire_transaction(&tpu_addr, &wire_transaction)?;
+        }
+        Ok(transaction.signatures[0])
+    }
+
     /// Submits a signed transaction to the network.
     ///
     /// Before a transaction is processed, the receiving node runs a "preflight
@@ -1138,6 +1308,172 @@ impl RpcClient {
         }
     }
 
+    /// Wait for `transaction`, which has already been sent, to reach `config.commitment`,
+    /// showing progress on a spinner.
+    ///
+    /// The signature status is checked every `config.poll_interval`, or as soon as the
+    /// `signatureSubscribe` notification arrives if `config.websocket_url` is set.  Checks that
+    /// fail to reach the node are retried.  A transaction expires once its recent blockhash is
+    /// no longer valid, but no sooner than the client's `confirm_transaction_initial_timeout`,
+    /// or for a transaction using a durable nonce, once the nonce account has been advanced
+    /// past it.
+    ///
+    /// A transaction that landed but failed is confirmed all the same, with its error in
+    /// [`TransactionConfirmation::err`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with kind [`RpcError`] if the transaction expires before it was
+    /// processed, or if it doesn't reach the commitment within `config.timeout`.  Returns the
+    /// error of the last check if the node could not be reached for as long as a blockhash
+    /// stays valid.
+    pub fn confirm_transaction_with_spinner_and_config(
+        &self,
+        transaction: &Transaction,
+        config: ConfirmTransactionConfig,
+    ) -> ClientResult<TransactionConfirmation> {
+        let signature = *transaction
+            .signatures
+            .get(0)
+            .ok_or_else(|| RpcError::ForUser("transaction is not signed".to_string()))?;
+        let recent_blockhash = &transaction.message.recent_blockhash;
+        let nonce_pubkey = uses_durable_nonce(transaction)
+            .and_then(|instruction| instruction.accounts.get(0))
+            .and_then(|index| transaction.message.account_keys.get(usize::from(*index)));
+
+        let progress_bar = new_spinner_progress_bar();
+        progress_bar.set_message(format!("Confirming transaction {}", signature));
+
+        let mut subscription = config.websocket_url.as_ref().and_then(|websocket_url| {
+            PubsubClient::signature_subscribe(
+                websocket_url,
+                &signature,
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(config.commitment),
+                    enable_received_notification: Some(false),
+                }),
+            )
+            .map_err(|err| warn!("Unable to subscribe to {}, polling: {}", signature, err))
+            .ok()
+        });
+
+        let start = Instant::now();
+        let mut last_reached = start;
+        let result = loop {
+            match self.check_transaction_confirmation(
+                &signature,
+                recent_blockhash,
+                nonce_pubkey,
+                config.commitment,
+                start,
+                &progress_bar,
+            ) {
+                Ok(Some(confirmation)) => break Ok(confirmation),
+                Ok(None) => last_reached = Instant::now(),
+                // Give up once the transaction would have expired anyway
+                Err(err)
+                    if err.kind().is_transient()
+                        && last_reached.elapsed().as_secs() < MAX_HASH_AGE_IN_SECONDS as u64 =>
+                {
+                    warn!(
+                        "Unable to check transaction {}, retrying: {}",
+                        signature, err
+                    );
+                }
+                Err(err) => break Err(err),
+            }
+
+            if let Some(timeout) = config.timeout {
+                if start.elapsed() >= timeout {
+                    break Err(RpcError::ForUser(format!(
+                        "transaction {} not confirmed after {:?}",
+                        signature, timeout
+                    ))
+                    .into());
+                }
+            }
+            match subscription
+                .as_ref()
+                .map(|(_, receiver)| receiver.recv_timeout(config.poll_interval))
+            {
+                Some(Ok(_)) | Some(Err(RecvTimeoutError::Timeout)) => (),
+                // Polling, also once the subscription's websocket is gone
+                None | Some(Err(RecvTimeoutError::Disconnected)) => sleep(config.poll_interval),
+            }
+        };
+
+        if let Some((subscription, _)) = subscription.as_mut() {
+            subscription
+                .shutdown()
+                .unwrap_or_else(|_| warn!("Unable to shut down signature subscription"));
+        }
+        match &result {
+            Ok(_) => progress_bar.set_message("Transaction confirmed"),
+            Err(_) => progress_bar.set_message("Transaction not confirmed"),
+        }
+        progress_bar.finish_and_clear();
+        result
+    }
+
+    /// Check the status of the transaction with `signature`, `None` if it didn't reach
+    /// `commitment` yet
+    fn check_transaction_confirmation(
+        &self,
+        signature: &Signature,
+        recent_blockhash: &Hash,
+        nonce_pubkey: Option<&Pubkey>,
+        commitment: CommitmentConfig,
+        start: Instant,
+        progress_bar: &ProgressBar,
+    ) -> ClientResult<Option<TransactionConfirmation>> {
+        match self.get_signature_statuses(&[*signature])?.value[0].take() {
+            Some(status) if status.satisfies_commitment(commitment) => {
+                return Ok(Some(TransactionConfirmation::from(status)));
+            }
+            Some(status) => progress_bar.set_message(format!(
+                "[{} confirmations] Confirming transaction {}",
+                status.confirmations.unwrap_or_default(),
+                signature
+            )),
+            None => {
+                let expired = match nonce_pubkey {
+                    Some(nonce_pubkey) => self.is_nonce_advanced(nonce_pubkey, recent_blockhash)?,
+                    None => {
+                        start.elapsed()
+                            >= self
+                                .config
+                                .confirm_transaction_initial_timeout
+                                .unwrap_or_default()
+                            && !self.is_blockhash_valid(
+                                recent_blockhash,
+                                CommitmentConfig::processed(),
+                            )?
+                    }
+                };
+                // The transaction may have landed just before it expired
+                if expired && self.get_signature_statuses(&[*signature])?.value[0].is_none() {
+                    return Err(RpcError::ForUser(format!(
+                        "transaction {} expired before it was processed",
+                        signature
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn is_nonce_advanced(&self, nonce_pubkey: &Pubkey, blockhash: &Hash) -> ClientResult<bool> {
+        let data = nonce_utils::get_account_with_commitment(
+            self,
+            nonce_pubkey,
+            CommitmentConfig::processed(),
+        )
+        .and_then(|account| nonce_utils::data_from_account(&account))
+        .map_err(|err| ClientErrorKind::Custom(format!("Invalid nonce account: {}", err)))?;
+        Ok(data.blockhash != *blockhash)
+    }
+
     fn default_cluster_transaction_encoding(&self) -> Result<UiTransactionEncoding, RpcError> {
         if self.get_node_version()? < semver::Version::new(1, 3, 16) {
             Ok(UiTransactionEncoding::Base58)
@@ -2391,6 +2727,7 @@ impl RpcClient {
     ///     transaction_details: Some(TransactionDetails::None),
     ///     rewards: Some(true),
     ///     commitment: None,
+    ///     max_supported_transaction_version: Some(0),
     /// };
     /// let block = rpc_client.get_block_with_config(
     ///     slot,
@@ -2846,6 +3183,7 @@ impl RpcClient {
             before: config.before.map(|signature| signature.to_string()),
             until: config.until.map(|signature| signature.to_string()),
             limit: config.limit,
+            memo: None,
             commitment: config.commitment,
         };
 
@@ -2857,6 +3195,34 @@ impl RpcClient {
         Ok(result)
     }
 
+    /// Like [`get_signatures_for_address_with_config`], but only returns transactions whose
+    /// memo contains `memo`.
+    ///
+    /// The server only reads so many memos per request, so a page may come short of
+    /// `config.limit` while matching transactions remain. The search continues from the `before`
+    /// signature of the page, if set.
+    ///
+    /// [`get_signatures_for_address_with_config`]: RpcClient::get_signatures_for_address_with_config
+    pub fn get_signatures_for_address_with_memo(
+        &self,
+        address: &Pubkey,
+        memo: &str,
+        config: GetConfirmedSignaturesForAddress2Config,
+    ) -> ClientResult<RpcSignaturesForAddressPage> {
+        let config = RpcSignaturesForAddressConfig {
+            before: config.before.map(|signature| signature.to_string()),
+            until: config.until.map(|signature| signature.to_string()),
+            limit: config.limit,
+            memo: Some(memo.to_string()),
+            commitment: config.commitment,
+        };
+
+        self.send(
+            self.maybe_map_request(RpcRequest::GetSignaturesForAddress)?,
+            json!([address.to_string(), config]),
+        )
+    }
+
     #[deprecated(
         since = "1.7.0",
         note = "Please use RpcClient::get_signatures_for_address() instead"
@@ -2996,6 +3362,7 @@ impl RpcClient {
     /// let config = RpcTransactionConfig {
     ///     encoding: Some(UiTransactionEncoding::Json),
     ///     commitment: Some(CommitmentConfig::confirmed()),
+    ///     max_supported_transaction_version: Some(0),
     /// };
     /// let transaction = rpc_client.get_transaction_with_config(
     ///     &signature,
@@ -3144,6 +3511,39 @@ impl RpcClient {
         )
     }
 
+    /// Returns the most recent epoch accounts hash included in a bank hash, if any.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getEpochAccountsHash`] RPC method.
+    ///
+    /// [`getEpochAccountsHash`]: https://docs.solana.com/developing/clients/jsonrpc-api#getepochaccountshash
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # use solana_sdk::commitment_config::CommitmentConfig;
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let commitment_config = CommitmentConfig::finalized();
+    /// let epoch_accounts_hash = rpc_client.get_epoch_accounts_hash_with_commitment(
+    ///     commitment_config,
+    /// )?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_epoch_accounts_hash_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<RpcEpochAccountsHash>> {
+        self.send(
+            RpcRequest::GetEpochAccountsHash,
+            json!([self.maybe_map_commitment(commitment_config)?]),
+        )
+    }
+
     /// Returns the leader schedule for an epoch.
     ///
     /// This method uses the configured default [commitment level][cl].
@@ -3278,6 +3678,66 @@ impl RpcClient {
         self.send(RpcRequest::GetEpochSchedule, Value::Null)
     }
 
+    /// Returns the activation status of every feature known to the node.
+    ///
+    /// This method uses the configured [commitment level][cl].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getFeatureStatuses`] RPC method.
+    ///
+    /// [`getFeatureStatuses`]: https://docs.solana.com/developing/clients/jsonrpc-api#getfeaturestatuses
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let feature_statuses = rpc_client.get_feature_statuses()?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_feature_statuses(&self) -> ClientResult<Vec<RpcFeatureStatus>> {
+        Ok(self
+            .get_feature_statuses_with_commitment(self.commitment())?
+            .value)
+    }
+
+    /// Returns the activation status of every feature known to the node.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getFeatureStatuses`] RPC method.
+    ///
+    /// [`getFeatureStatuses`]: https://docs.solana.com/developing/clients/jsonrpc-api#getfeaturestatuses
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # use solana_sdk::commitment_config::CommitmentConfig;
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let commitment_config = CommitmentConfig::finalized();
+    /// let feature_statuses = rpc_client.get_feature_statuses_with_commitment(commitment_config)?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_feature_statuses_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Vec<RpcFeatureStatus>> {
+        self.send(
+            RpcRequest::GetFeatureStatuses,
+            json!([self.maybe_map_commitment(commitment_config)?]),
+        )
+    }
+
     /// Returns a list of recent performance samples, in reverse slot order.
     ///
     /// Performance samples are taken every 60 seconds and include the number of
@@ -3310,6 +3770,41 @@ impl RpcClient {
         self.send(RpcRequest::GetRecentPerformanceSamples, json!([limit]))
     }
 
+    /// Returns equivocations observed by the node, in ascending slot order.
+    ///
+    /// Each entry identifies the slot and offending validator, along with the two conflicting
+    /// shreds of a duplicate block or the two conflicting vote transactions.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getEquivocationEvidence`] RPC method.
+    ///
+    /// [`getEquivocationEvidence`]: https://docs.solana.com/developing/clients/jsonrpc-api#getequivocationevidence
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// let start_slot = 100;
+    /// let limit = 10;
+    /// let evidence = rpc_client.get_equivocation_evidence(Some(start_slot), Some(limit))?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_equivocation_evidence(
+        &self,
+        start_slot: Option<Slot>,
+        limit: Option<usize>,
+    ) -> ClientResult<Vec<RpcEquivocationEvidence>> {
+        self.send(
+            RpcRequest::GetEquivocationEvidence,
+            json!([start_slot, limit]),
+        )
+    }
+
     /// Returns the identity pubkey for the current node.
     ///
     /// # RPC Reference
@@ -3596,6 +4091,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::Base64Zstd),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            with_last_modified_slot: None,
         };
         let response = self.sender.send(
             RpcRequest::GetAccountInfo,
@@ -3753,6 +4249,7 @@ impl RpcClient {
                 encoding: Some(UiAccountEncoding::Base64Zstd),
                 commitment: Some(self.maybe_map_commitment(commitment_config)?),
                 data_slice: None,
+                with_last_modified_slot: None,
             },
         )
     }
@@ -4063,6 +4560,7 @@ impl RpcClient {
     ///             length: 5,
     ///         }),
     ///         commitment: Some(CommitmentConfig::processed()),
+    ///         with_last_modified_slot: None,
     ///     },
     ///     with_context: Some(false),
     /// };
@@ -4097,6 +4595,72 @@ impl RpcClient {
         parse_keyed_accounts(accounts, RpcRequest::GetProgramAccounts)
     }
 
+    /// Returns the accounts owned by the provided program pubkey whose addresses start with
+    /// `prefix`, ordered by address.
+    ///
+    /// Accounts under distinct prefixes of the same length never overlap, so a large scan can
+    /// be split into several requests, one per prefix.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getProgramAccountsByPrefix`] RPC method.
+    ///
+    /// [`getProgramAccountsByPrefix`]: https://docs.solana.com/developing/clients/jsonrpc-api#getprogramaccountsbyprefix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     client_error::ClientError,
+    /// #     rpc_config::RpcProgramAccountsConfig,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let alice = Keypair::new();
+    /// // Split the scan into 256 requests by the first byte of the addresses
+    /// for first_byte in 0..=u8::MAX {
+    ///     let accounts = rpc_client.get_program_accounts_by_prefix_with_config(
+    ///         &alice.pubkey(),
+    ///         &[first_byte],
+    ///         RpcProgramAccountsConfig::default(),
+    ///     )?;
+    /// }
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_program_accounts_by_prefix_with_config(
+        &self,
+        pubkey: &Pubkey,
+        prefix: &[u8],
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let commitment = config
+            .account_config
+            .commitment
+            .unwrap_or_else(|| self.commitment());
+        let commitment = self.maybe_map_commitment(commitment)?;
+        let account_config = RpcAccountInfoConfig {
+            commitment: Some(commitment),
+            ..config.account_config
+        };
+        let config = RpcProgramAccountsConfig {
+            account_config,
+            ..config
+        };
+        let accounts: Vec<RpcKeyedAccount> = self.send(
+            RpcRequest::GetProgramAccountsByPrefix,
+            json!([
+                pubkey.to_string(),
+                bs58::encode(prefix).into_string(),
+                config
+            ]),
+        )?;
+        parse_keyed_accounts(accounts, RpcRequest::GetProgramAccountsByPrefix)
+    }
+
     /// Request the transaction count.
     pub fn get_transaction_count(&self) -> ClientResult<u64> {
         self.get_transaction_count_with_commitment(self.commitment())
@@ -4348,6 +4912,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            with_last_modified_slot: None,
         };
         let response = self.sender.send(
             RpcRequest::GetAccountInfo,
@@ -4445,6 +5010,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            with_last_modified_slot: None,
         };
 
         self.send(
@@ -4484,6 +5050,7 @@ impl RpcClient {
             encoding: Some(UiAccountEncoding::JsonParsed),
             commitment: Some(self.maybe_map_commitment(commitment_config)?),
             data_slice: None,
+            with_last_modified_slot: None,
         };
 
         self.send(
@@ -4819,6 +5386,20 @@ impl RpcClient {
         }
     }
 
+    /// Like `get_fee_for_message`, for a legacy or v0 message.  The address lookup tables of a v0
+    /// message must exist on the node
+    pub fn get_fee_for_versioned_message(&self, message: &VersionedMessage) -> ClientResult<u64> {
+        let serialized_encoded =
+            serialize_and_encode::<VersionedMessage>(message, UiTransactionEncoding::Base64)?;
+        let result = self.send::<Response<Option<u64>>>(
+            RpcRequest::GetFeeForMessage,
+            json!([serialized_encoded, self.commitment()]),
+        )?;
+        result
+            .value
+            .ok_or_else(|| ClientErrorKind::Custom("Invalid blockhash".to_string()).into())
+    }
+
     pub fn get_new_latest_blockhash(&self, blockhash: &Hash) -> ClientResult<Hash> {
         let mut num_retries = 0;
         let start = Instant::now();
@@ -4876,7 +5457,50 @@ pub struct GetConfirmedSignaturesForAddress2Config {
     pub commitment: Option<CommitmentConfig>,
 }
 
-fn new_spinner_progress_bar() -> ProgressBar {
+/// Configuration of [`RpcClient::confirm_transaction_with_spinner_and_config`]
+#[derive(Debug, Clone)]
+pub struct ConfirmTransactionConfig {
+    pub commitment: CommitmentConfig,
+    /// Wait for a `signatureSubscribe` notification from this websocket between polls
+    pub websocket_url: Option<String>,
+    /// Give up on a transaction that hasn't expired after this long
+    pub timeout: Option<Duration>,
+    pub poll_interval: Duration,
+}
+
+impl Default for ConfirmTransactionConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::default(),
+            websocket_url: None,
+            timeout: None,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The status of a transaction once it reached the requested commitment
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionConfirmation {
+    pub slot: Slot,
+    pub err: Option<TransactionError>,
+    /// Number of blocks confirmed on top of the transaction's, `None` once it is rooted
+    pub confirmations: Option<usize>,
+    pub confirmation_status: Option<TransactionConfirmationStatus>,
+}
+
+impl From<TransactionStatus> for TransactionConfirmation {
+    fn from(status: TransactionStatus) -> Self {
+        Self {
+            slot: status.slot,
+            err: status.err,
+            confirmations: status.confirmations,
+            confirmation_status: status.confirmation_status,
+        }
+    }
+}
+
+pub(crate) fn new_spinner_progress_bar() -> ProgressBar {
     let progress_bar = ProgressBar::new(42);
     progress_bar
         .set_style(ProgressStyle::default_spinner().template("{spinner:.green} {wide_msg}"));
@@ -4884,7 +5508,7 @@ fn new_spinner_progress_bar() -> ProgressBar {
     progress_bar
 }
 
-fn get_rpc_request_str(rpc_addr: SocketAddr, tls: bool) -> String {
+pub(crate) fn get_rpc_request_str(rpc_addr: SocketAddr, tls: bool) -> String {
     if tls {
         format!("https://{}", rpc_addr)
     } else {
@@ -4892,7 +5516,7 @@ fn get_rpc_request_str(rpc_addr: SocketAddr, tls: bool) -> String {
     }
 }
 
-fn parse_keyed_accounts(
+pub(crate) fn parse_keyed_accounts(
     accounts: Vec<RpcKeyedAccount>,
     request: RpcRequest,
 ) -> ClientResult<Vec<(Pubkey, Account)>> {
@@ -4954,11 +5578,12 @@ mod tests {
     use serde_json::Number;
     use solana_sdk::{
         instruction::InstructionError,
+        packet::PACKET_DATA_SIZE,
         signature::{Keypair, Signer},
         system_transaction,
         transaction::TransactionError,
     };
-    use std::{io, sync::mpsc::channel, thread};
+    use std::{collections::HashMap, io, net::UdpSocket, sync::mpsc::channel, thread};
 
     #[test]
     fn test_send() {
@@ -5133,6 +5758,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_confirm_transaction_with_spinner_and_config() {
+        let key = Keypair::new();
+        let to = solana_sdk::pubkey::new_rand();
+        let tx = system_transaction::transfer(&key, &to, 50, Hash::default());
+        let config = ConfirmTransactionConfig {
+            poll_interval: Duration::from_millis(1),
+            ..ConfirmTransactionConfig::default()
+        };
+
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let confirmation = rpc_client
+            .confirm_transaction_with_spinner_and_config(&tx, config.clone())
+            .unwrap();
+        assert_eq!(confirmation.slot, 1);
+        assert_eq!(confirmation.err, None);
+        assert_eq!(confirmation.confirmations, None);
+
+        // A failed transaction is confirmed with its error
+        let rpc_client = RpcClient::new_mock("instruction_error".to_string());
+        let confirmation = rpc_client
+            .confirm_transaction_with_spinner_and_config(&tx, config.clone())
+            .unwrap();
+        assert_eq!(
+            confirmation.err,
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::UninitializedAccount
+            ))
+        );
+
+        // Expires once the blockhash is no longer valid
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::IsBlockhashValid,
+            json!(Response {
+                context: RpcResponseContext { slot: 1 },
+                value: false,
+            }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+        assert_matches!(
+            rpc_client
+                .confirm_transaction_with_spinner_and_config(&tx, config.clone())
+                .unwrap_err()
+                .kind(),
+            ClientErrorKind::RpcError(RpcError::ForUser(_))
+        );
+
+        // Gives up after the timeout
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::IsBlockhashValid,
+            json!(Response {
+                context: RpcResponseContext { slot: 1 },
+                value: true,
+            }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks);
+        let config = ConfirmTransactionConfig {
+            timeout: Some(Duration::from_millis(0)),
+            ..config
+        };
+        assert!(rpc_client
+            .confirm_transaction_with_spinner_and_config(&tx, config.clone())
+            .is_err());
+
+        // Retries checks that fail to reach the node, until the timeout
+        let rpc_client = RpcClient::new("http://127.0.0.1:1".to_string());
+        let config = ConfirmTransactionConfig {
+            timeout: Some(Duration::from_millis(10)),
+            ..config
+        };
+        assert_matches!(
+            rpc_client
+                .confirm_transaction_with_spinner_and_config(&tx, config)
+                .unwrap_err()
+                .kind(),
+            ClientErrorKind::RpcError(RpcError::ForUser(message))
+                if message.contains("not confirmed after")
+        );
+    }
+
+    #[test]
+    fn test_send_transaction_with_connection_cache() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetClusterNodes,
+            json!([RpcContactInfo {
+                pubkey: PUBKEY.to_string(),
+                gossip: None,
+                tpu: Some(receiver.local_addr().unwrap()),
+                rpc: None,
+                version: None,
+                feature_set: None,
+                shred_version: None,
+            }]),
+        );
+        let rpc_client = RpcClient::new_sender(
+            MockSender::new_with_mocks("succeeds".to_string(), mocks),
+            RpcClientConfig {
+                connection_cache: Some(Arc::new(ConnectionCache::default())),
+                ..RpcClientConfig::default()
+            },
+        );
+
+        let key = Keypair::new();
+        let tx = system_transaction::transfer(&key, &Pubkey::new_unique(), 50, Hash::default());
+        assert_eq!(rpc_client.send_transaction(&tx).unwrap(), tx.signatures[0]);
+        let mut buf = [0; PACKET_DATA_SIZE];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], &serialize(&tx).unwrap()[..]);
+    }
+
     #[test]
     fn test_rpc_client_thread() {
         let rpc_client = RpcClient::new_mock("succeeds".to_string());
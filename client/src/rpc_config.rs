@@ -117,6 +117,16 @@ pub struct RpcLargestAccountsConfig {
     pub filter: Option<RpcLargestAccountsFilter>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTokenLargestAccountsConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub limit: Option<usize>,
+    /// Return the accounts following this token account, the last one of the previous page
+    pub after: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSupplyConfig {
@@ -141,6 +151,18 @@ pub struct RpcAccountInfoConfig {
     pub data_slice: Option<UiDataSliceConfig>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// Include the slot each account was last modified in
+    pub with_last_modified_slot: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountSubscribeConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Only notify when the account's data, owner or executable flag change, not when only its
+    /// lamports do.  With a `data_slice`, only changes within the slice are notified
+    pub changed_data_only: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -157,14 +179,18 @@ pub struct RpcProgramAccountsConfig {
 pub enum RpcTransactionLogsFilter {
     All,
     AllWithVotes,
-    Mentions(Vec<String>), // base58-encoded list of addresses
+    Mentions(Vec<String>),   // base58-encoded list of addresses
+    ProgramIds(Vec<String>), // base58-encoded list of invoked program ids
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcTransactionLogsConfig {
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// Only notify of transactions with a log message containing one of these substrings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -188,6 +214,9 @@ pub struct RpcSignaturesForAddressConfig {
     pub before: Option<String>, // Signature as base-58 string
     pub until: Option<String>,  // Signature as base-58 string
     pub limit: Option<usize>,
+    /// Only return transactions whose memo contains this text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
 }
@@ -231,6 +260,9 @@ pub struct RpcBlockConfig {
     pub rewards: Option<bool>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// The highest transaction version to return, blocks holding versioned transactions are
+    /// only returned to requesters that opt in
+    pub max_supported_transaction_version: Option<u8>,
 }
 
 impl EncodingConfig for RpcBlockConfig {
@@ -271,6 +303,9 @@ pub struct RpcTransactionConfig {
     pub encoding: Option<UiTransactionEncoding>,
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
+    /// The highest transaction version to return, versioned transactions are only returned to
+    /// requesters that opt in
+    pub max_supported_transaction_version: Option<u8>,
 }
 
 impl EncodingConfig for RpcTransactionConfig {
@@ -40,13 +40,16 @@ pub enum RpcRequest {
         note = "Please use RpcRequest::GetTransaction instead"
     )]
     GetConfirmedTransaction,
+    GetEpochAccountsHash,
     GetEpochInfo,
     GetEpochSchedule,
+    GetEquivocationEvidence,
     #[deprecated(
         since = "1.8.0",
         note = "Please use RpcRequest::GetFeeForMessage instead"
     )]
     GetFeeCalculatorForBlockhash,
+    GetFeatureStatuses,
     GetFeeForMessage,
     #[deprecated(
         since = "1.8.0",
@@ -73,6 +76,7 @@ pub enum RpcRequest {
     GetMinimumBalanceForRentExemption,
     GetMultipleAccounts,
     GetProgramAccounts,
+    GetProgramAccountsByPrefix,
     #[deprecated(
         since = "1.8.0",
         note = "Please use RpcRequest::GetLatestBlockhash instead"
@@ -133,9 +137,12 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetConfirmedBlocksWithLimit => "getConfirmedBlocksWithLimit",
             RpcRequest::GetConfirmedSignaturesForAddress2 => "getConfirmedSignaturesForAddress2",
             RpcRequest::GetConfirmedTransaction => "getConfirmedTransaction",
+            RpcRequest::GetEpochAccountsHash => "getEpochAccountsHash",
             RpcRequest::GetEpochInfo => "getEpochInfo",
             RpcRequest::GetEpochSchedule => "getEpochSchedule",
+            RpcRequest::GetEquivocationEvidence => "getEquivocationEvidence",
             RpcRequest::GetFeeCalculatorForBlockhash => "getFeeCalculatorForBlockhash",
+            RpcRequest::GetFeatureStatuses => "getFeatureStatuses",
             RpcRequest::GetFeeForMessage => "getFeeForMessage",
             RpcRequest::GetFeeRateGovernor => "getFeeRateGovernor",
             RpcRequest::GetFees => "getFees",
@@ -154,6 +161,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetMinimumBalanceForRentExemption => "getMinimumBalanceForRentExemption",
             RpcRequest::GetMultipleAccounts => "getMultipleAccounts",
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
+            RpcRequest::GetProgramAccountsByPrefix => "getProgramAccountsByPrefix",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
@@ -194,8 +202,11 @@ pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE: u64 = 10_000;
 pub const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
+/// Memos read by a `getSignaturesForAddress` request filtered by memo before it returns a page
+pub const MAX_GET_SIGNATURES_FOR_ADDRESS_MEMO_SCAN: usize = 10_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
+pub const MAX_GET_TOKEN_LARGEST_ACCOUNTS_LIMIT: usize = 1_000;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
 
@@ -10,6 +10,7 @@ use {
     },
     solana_transaction_status::{
         ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus,
+        UiTransactionLogEntry, UiTransactionReturnData,
     },
     std::{collections::HashMap, fmt, net::SocketAddr},
 };
@@ -202,6 +203,8 @@ pub struct RpcLogsResponse {
     pub signature: String, // Signature as base58 string
     pub err: Option<TransactionError>,
     pub logs: Vec<String>,
+    #[serde(default)]
+    pub log_entries: Vec<UiTransactionLogEntry>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -308,6 +311,11 @@ pub struct RpcVoteAccountInfo {
     /// An 8-bit integer used as a fraction (commission/MAX_U8) for rewards payout
     pub commission: u8,
 
+    /// The commission at the start of the current epoch, or `None` if it hasn't changed since.
+    /// The epoch's rewards are paid at `commission`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch_start_commission: Option<u8>,
+
     /// Whether this account is staked for the current epoch
     pub epoch_vote_account: bool,
 
@@ -336,6 +344,14 @@ pub struct RpcSimulateTransactionResult {
     pub logs: Option<Vec<String>>,
     pub accounts: Option<Vec<Option<UiAccount>>>,
     pub units_consumed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_entries: Option<Vec<UiTransactionLogEntry>>,
+    /// The compute units charged for each cross-program invocation made by each instruction,
+    /// not including the units consumed by the invoked programs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_instructions_invoke_units: Option<Vec<Vec<u64>>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -397,6 +413,24 @@ pub struct RpcConfirmedTransactionStatusWithSignature {
     pub confirmation_status: Option<TransactionConfirmationStatus>,
 }
 
+/// A page of `getSignaturesForAddress` results filtered by memo.  The search for matching
+/// transactions is bounded, so a page may come short of the limit while more remain
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignaturesForAddressPage {
+    pub signatures: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    /// Signature to pass as `before` to continue the search, unset once it reached the end
+    pub before: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcSignaturesForAddress {
+    Signatures(Vec<RpcConfirmedTransactionStatusWithSignature>),
+    /// If filtered by memo
+    Page(RpcSignaturesForAddressPage),
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcPerfSample {
@@ -406,6 +440,43 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcEquivocationKind {
+    DuplicateBlock,
+    ConflictingVotes,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEquivocationEvidence {
+    pub slot: Slot,
+    /// The leader identity for a duplicate block, or the vote account for conflicting votes
+    pub offender: String,
+    pub kind: RpcEquivocationKind,
+    /// The two conflicting shreds or bincode serialized vote transactions, base64 encoded
+    pub proof: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcFeatureActivationStatus {
+    Inactive,
+    /// The feature account has been created and the feature activates at the next epoch boundary
+    Pending,
+    Active,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFeatureStatus {
+    pub id: String,
+    pub description: String,
+    pub status: RpcFeatureActivationStatus,
+    pub activation_slot: Option<Slot>,
+    pub activation_epoch: Option<Epoch>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcInflationReward {
@@ -441,3 +512,9 @@ pub struct RpcSnapshotSlotInfo {
     pub full: Slot,
     pub incremental: Option<Slot>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcEpochAccountsHash {
+    pub slot: Slot,
+    pub hash: String,
+}
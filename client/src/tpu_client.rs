@@ -1,22 +1,31 @@
 use crate::{
+    connection_cache::ConnectionCache,
     pubsub_client::{PubsubClient, PubsubClientError, PubsubClientSubscription},
-    rpc_client::RpcClient,
+    rpc_client::{new_spinner_progress_bar, RpcClient},
+    rpc_config::RpcSendTransactionConfig,
+    rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
     rpc_response::SlotUpdate,
 };
 use bincode::serialize;
 use log::*;
 use solana_sdk::{
-    clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, transaction::Transaction,
+    clock::Slot,
+    commitment_config::CommitmentConfig,
+    message::Message,
+    pubkey::Pubkey,
+    signature::SignerError,
+    signers::Signers,
+    transaction::{Transaction, TransactionError},
 };
 use std::{
     collections::{HashMap, HashSet, VecDeque},
-    net::{SocketAddr, UdpSocket},
+    net::SocketAddr,
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock,
     },
-    thread::JoinHandle,
+    thread::{sleep, JoinHandle},
     time::{Duration, Instant},
 };
 use thiserror::Error;
@@ -29,6 +38,10 @@ pub enum TpuSenderError {
     RpcError(#[from] crate::client_error::ClientError),
     #[error("IO error: {0:?}")]
     IoError(#[from] std::io::Error),
+    #[error("Signer error: {0:?}")]
+    SignerError(#[from] SignerError),
+    #[error("Custom error: {0}")]
+    Custom(String),
 }
 
 type Result<T> = std::result::Result<T, TpuSenderError>;
@@ -36,30 +49,44 @@ type Result<T> = std::result::Result<T, TpuSenderError>;
 /// Default number of slots used to build TPU socket fanout set
 pub const DEFAULT_FANOUT_SLOTS: u64 = 12;
 
+/// Times `send_and_confirm_messages_with_spinner` re-signs the transactions that expired
+/// unconfirmed with a new blockhash
+const SEND_RETRIES: usize = 5;
+
 /// Maximum number of slots used to build TPU socket fanout set
 pub const MAX_FANOUT_SLOTS: u64 = 100;
 
+/// Default interval between checks of the statuses of unconfirmed transactions
+pub const DEFAULT_CONFIRM_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
 /// Config params for `TpuClient`
 #[derive(Clone, Debug)]
 pub struct TpuClientConfig {
     /// The range of upcoming slots to include when determining which
     /// leaders to send transactions to (min: 1, max: `MAX_FANOUT_SLOTS`)
     pub fanout_slots: u64,
+    /// How long `send_and_confirm_messages_with_spinner` waits between checks of the statuses
+    /// of the transactions that are not confirmed yet
+    pub confirm_retry_interval: Duration,
 }
 
 impl Default for TpuClientConfig {
     fn default() -> Self {
         Self {
             fanout_slots: DEFAULT_FANOUT_SLOTS,
+            confirm_retry_interval: DEFAULT_CONFIRM_RETRY_INTERVAL,
         }
     }
 }
 
-/// Client which sends transactions directly to the current leader's TPU port over UDP.
-/// The client uses RPC to determine the current leader and fetch node contact info
+/// Client which sends transactions directly to the current leader's TPU port, over the
+/// connections of a `ConnectionCache`.  The client uses RPC to determine the current leader and
+/// fetch node contact info
 pub struct TpuClient {
-    send_socket: UdpSocket,
+    rpc_client: Arc<RpcClient>,
+    connection_cache: Arc<ConnectionCache>,
     fanout_slots: u64,
+    confirm_retry_interval: Duration,
     leader_tpu_service: LeaderTpuService,
     exit: Arc<AtomicBool>,
 }
@@ -80,8 +107,8 @@ impl TpuClient {
             .leader_tpu_sockets(self.fanout_slots)
         {
             if self
-                .send_socket
-                .send_to(wire_transaction, tpu_address)
+                .connection_cache
+                .send_wire_transaction(&tpu_address, wire_transaction)
                 .is_ok()
             {
                 sent = true;
@@ -90,18 +117,172 @@ impl TpuClient {
         sent
     }
 
+    /// Send a transaction to the leader TPUs, falling back to the RPC node if none of them can
+    /// be reached
+    fn send_transaction_or_forward(&self, transaction: &Transaction) {
+        if !self.send_transaction(transaction) {
+            let _result = self
+                .rpc_client
+                .send_transaction_with_config(
+                    transaction,
+                    RpcSendTransactionConfig {
+                        preflight_commitment: Some(self.rpc_client.commitment().commitment),
+                        ..RpcSendTransactionConfig::default()
+                    },
+                )
+                .ok();
+        }
+    }
+
+    /// Sign `messages` with `signers`, send them to the leader TPUs and wait until they are
+    /// confirmed at the commitment of the RPC client, showing the progress with a spinner.  Transactions that are not confirmed
+    /// before their blockhash expires are signed with a new blockhash and sent again.  Returns
+    /// the error of each transaction, in the order of `messages`
+    pub fn send_and_confirm_messages_with_spinner<T: Signers>(
+        &self,
+        messages: &[Message],
+        signers: &T,
+    ) -> Result<Vec<Option<TransactionError>>> {
+        let commitment = self.rpc_client.commitment();
+
+        let progress_bar = new_spinner_progress_bar();
+        let send_transaction_interval = Duration::from_millis(10); /* ~100 TPS */
+        let mut send_retries = SEND_RETRIES;
+
+        let (blockhash, mut last_valid_block_height) = self
+            .rpc_client
+            .get_latest_blockhash_with_commitment(commitment)?;
+
+        let mut transactions = vec![];
+        let mut transaction_errors = vec![None; messages.len()];
+        for (i, message) in messages.iter().enumerate() {
+            let mut transaction = Transaction::new_unsigned(message.clone());
+            transaction.try_sign(signers, blockhash)?;
+            transactions.push((i, transaction));
+        }
+
+        loop {
+            // Send all transactions
+            let mut pending_transactions = HashMap::new();
+            let num_transactions = transactions.len();
+            for (i, transaction) in transactions {
+                self.send_transaction_or_forward(&transaction);
+                pending_transactions.insert(transaction.signatures[0], (i, transaction));
+                progress_bar.set_message(format!(
+                    "[{}/{}] Transactions sent",
+                    pending_transactions.len(),
+                    num_transactions
+                ));
+
+                sleep(send_transaction_interval);
+            }
+
+            // Collect statuses for all the transactions, drop those that are confirmed
+            loop {
+                let mut block_height = 0;
+                let pending_signatures = pending_transactions.keys().cloned().collect::<Vec<_>>();
+                for pending_signatures_chunk in
+                    pending_signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS)
+                {
+                    if let Ok(result) = self
+                        .rpc_client
+                        .get_signature_statuses(pending_signatures_chunk)
+                    {
+                        let statuses = result.value;
+                        for (signature, status) in
+                            pending_signatures_chunk.iter().zip(statuses.into_iter())
+                        {
+                            if let Some(status) = status {
+                                if status.satisfies_commitment(commitment) {
+                                    if let Some((i, _)) = pending_transactions.remove(signature) {
+                                        transaction_errors[i] = status.err;
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    block_height = self.rpc_client.get_block_height()?;
+                    progress_bar.set_message(format!(
+                        "[{}/{}] Transactions confirmed. Retrying in {} blocks",
+                        num_transactions - pending_transactions.len(),
+                        num_transactions,
+                        last_valid_block_height.saturating_sub(block_height)
+                    ));
+                }
+
+                if pending_transactions.is_empty() {
+                    progress_bar.finish_and_clear();
+                    return Ok(transaction_errors);
+                }
+
+                if block_height > last_valid_block_height {
+                    break;
+                }
+
+                for (_i, transaction) in pending_transactions.values() {
+                    self.send_transaction_or_forward(transaction);
+                }
+
+                sleep(self.confirm_retry_interval);
+            }
+
+            if send_retries == 0 {
+                progress_bar.finish_and_clear();
+                return Err(TpuSenderError::Custom(format!(
+                    "{} transactions failed to confirm",
+                    pending_transactions.len()
+                )));
+            }
+            send_retries -= 1;
+
+            // Re-sign any failed transactions with a new blockhash and retry
+            let (blockhash, new_last_valid_block_height) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(commitment)?;
+            last_valid_block_height = new_last_valid_block_height;
+            transactions = vec![];
+            for (_, (i, mut transaction)) in pending_transactions.into_iter() {
+                transaction.try_sign(signers, blockhash)?;
+                transactions.push((i, transaction));
+            }
+        }
+    }
+
+    pub fn rpc_client(&self) -> &RpcClient {
+        &self.rpc_client
+    }
+
     /// Create a new client that disconnects when dropped
     pub fn new(
         rpc_client: Arc<RpcClient>,
         websocket_url: &str,
         config: TpuClientConfig,
+    ) -> Result<Self> {
+        Self::new_with_connection_cache(
+            rpc_client,
+            websocket_url,
+            config,
+            Arc::new(ConnectionCache::default()),
+        )
+    }
+
+    /// Create a new client that sends over the connections of `connection_cache`
+    pub fn new_with_connection_cache(
+        rpc_client: Arc<RpcClient>,
+        websocket_url: &str,
+        config: TpuClientConfig,
+        connection_cache: Arc<ConnectionCache>,
     ) -> Result<Self> {
         let exit = Arc::new(AtomicBool::new(false));
-        let leader_tpu_service = LeaderTpuService::new(rpc_client, websocket_url, exit.clone())?;
+        let leader_tpu_service =
+            LeaderTpuService::new(rpc_client.clone(), websocket_url, exit.clone())?;
 
         Ok(Self {
-            send_socket: UdpSocket::bind("0.0.0.0:0").unwrap(),
+            rpc_client,
+            connection_cache,
             fanout_slots: config.fanout_slots.min(MAX_FANOUT_SLOTS).max(1),
+            confirm_retry_interval: config.confirm_retry_interval,
             leader_tpu_service,
             exit,
         })
@@ -402,7 +583,72 @@ impl LeaderTpuService {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {
+        super::*,
+        assert_matches::assert_matches,
+        solana_sdk::{
+            signature::{Keypair, Signer},
+            system_instruction,
+        },
+    };
+
+    #[test]
+    fn test_send_and_confirm_messages() {
+        let rpc_client = Arc::new(RpcClient::new_mock("succeeds".to_string()));
+        let tpu_client = TpuClient::new(rpc_client, "", TpuClientConfig::default()).unwrap();
+        let payer = Keypair::new();
+        let messages: Vec<_> = (1..=3)
+            .map(|lamports| {
+                Message::new(
+                    &[system_instruction::transfer(
+                        &payer.pubkey(),
+                        &Pubkey::new_unique(),
+                        lamports,
+                    )],
+                    Some(&payer.pubkey()),
+                )
+            })
+            .collect();
+
+        let transaction_errors = tpu_client
+            .send_and_confirm_messages_with_spinner(&messages, &[&payer])
+            .unwrap();
+        assert_eq!(transaction_errors, vec![None; 3]);
+
+        let rpc_client = Arc::new(RpcClient::new_mock("instruction_error".to_string()));
+        let tpu_client = TpuClient::new(rpc_client, "", TpuClientConfig::default()).unwrap();
+        let transaction_errors = tpu_client
+            .send_and_confirm_messages_with_spinner(&messages[..1], &[&payer])
+            .unwrap();
+        assert!(transaction_errors[0].is_some());
+    }
+
+    #[test]
+    fn test_send_and_confirm_messages_unconfirmed() {
+        let rpc_client = Arc::new(RpcClient::new_mock("sig_not_found".to_string()));
+        let tpu_client = TpuClient::new(
+            rpc_client,
+            "",
+            TpuClientConfig {
+                confirm_retry_interval: Duration::ZERO,
+                ..TpuClientConfig::default()
+            },
+        )
+        .unwrap();
+        let payer = Keypair::new();
+        let message = Message::new(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &Pubkey::new_unique(),
+                1,
+            )],
+            Some(&payer.pubkey()),
+        );
+        assert_matches!(
+            tpu_client.send_and_confirm_messages_with_spinner(&[message], &[&payer]),
+            Err(TpuSenderError::Custom(message)) if message == "1 transactions failed to confirm"
+        );
+    }
 
     fn assert_slot(recent_slots: RecentLeaderSlots, expected_slot: Slot) {
         assert_eq!(recent_slots.estimated_current_slot(), expected_slot);
@@ -19,6 +19,7 @@ use solana_perf::{
 use solana_runtime::bank::Bank;
 use solana_sdk::{
     clock::{Slot, SLOT_MS},
+    hash::Hash,
     pubkey::Pubkey,
     timing::timestamp,
 };
@@ -57,6 +58,10 @@ pub type AncestorHashesReplayUpdateReceiver = Receiver<AncestorHashesReplayUpdat
 
 type RetryableSlotsSender = Sender<Slot>;
 type RetryableSlotsReceiver = Receiver<Slot>;
+// The agreed upon ancestors of a dead slot, all of which were missing or mismatched, to continue
+// the search from the earliest of
+type ContinueSearchSender = Sender<Vec<(Slot, Hash)>>;
+type ContinueSearchReceiver = Receiver<Vec<(Slot, Hash)>>;
 type OutstandingAncestorHashesRepairs = OutstandingRequests<AncestorHashesRepairType>;
 
 #[derive(Default)]
@@ -159,6 +164,7 @@ impl AncestorHashesService {
         let ancestor_hashes_request_statuses: Arc<DashMap<Slot, DeadSlotAncestorRequestStatus>> =
             Arc::new(DashMap::new());
         let (retryable_slots_sender, retryable_slots_receiver) = unbounded();
+        let (continue_search_sender, continue_search_receiver) = unbounded();
 
         // Listen for responses to our ancestor requests
         let t_ancestor_hashes_responses = Self::run_responses_listener(
@@ -169,6 +175,7 @@ impl AncestorHashesService {
             exit.clone(),
             repair_info.duplicate_slots_reset_sender.clone(),
             retryable_slots_sender,
+            continue_search_sender,
         );
 
         // Generate ancestor requests for dead slots that are repairable
@@ -180,6 +187,7 @@ impl AncestorHashesService {
             exit,
             ancestor_hashes_replay_update_receiver,
             retryable_slots_receiver,
+            continue_search_receiver,
         );
         let thread_hdls = vec![t_receiver, t_ancestor_hashes_responses, t_ancestor_requests];
         Self { thread_hdls }
@@ -201,6 +209,7 @@ impl AncestorHashesService {
         exit: Arc<AtomicBool>,
         duplicate_slots_reset_sender: DuplicateSlotsResetSender,
         retryable_slots_sender: RetryableSlotsSender,
+        continue_search_sender: ContinueSearchSender,
     ) -> JoinHandle<()> {
         Builder::new()
             .name("solana-ancestor-hashes-responses-service".to_string())
@@ -218,6 +227,7 @@ impl AncestorHashesService {
                         &mut max_packets,
                         &duplicate_slots_reset_sender,
                         &retryable_slots_sender,
+                        &continue_search_sender,
                     );
                     match result {
                         Err(Error::RecvTimeout(_)) | Ok(_) => {}
@@ -245,6 +255,7 @@ impl AncestorHashesService {
         max_packets: &mut usize,
         duplicate_slots_reset_sender: &DuplicateSlotsResetSender,
         retryable_slots_sender: &RetryableSlotsSender,
+        continue_search_sender: &ContinueSearchSender,
     ) -> Result<()> {
         let timeout = Duration::new(1, 0);
         let mut responses = vec![response_receiver.recv_timeout(timeout)?];
@@ -274,6 +285,7 @@ impl AncestorHashesService {
                 blockstore,
                 duplicate_slots_reset_sender,
                 retryable_slots_sender,
+                continue_search_sender,
             );
         }
         time.stop();
@@ -295,6 +307,7 @@ impl AncestorHashesService {
         blockstore: &Blockstore,
         duplicate_slots_reset_sender: &DuplicateSlotsResetSender,
         retryable_slots_sender: &RetryableSlotsSender,
+        continue_search_sender: &ContinueSearchSender,
     ) {
         packets.packets.iter().for_each(|packet| {
             let decision = Self::verify_and_process_ancestor_response(
@@ -310,6 +323,7 @@ impl AncestorHashesService {
                     decision,
                     duplicate_slots_reset_sender,
                     retryable_slots_sender,
+                    continue_search_sender,
                 );
             }
         });
@@ -382,29 +396,23 @@ impl AncestorHashesService {
         decision: DuplicateAncestorDecision,
         duplicate_slots_reset_sender: &DuplicateSlotsResetSender,
         retryable_slots_sender: &RetryableSlotsSender,
+        continue_search_sender: &ContinueSearchSender,
     ) {
         if decision.is_retryable() {
             let _ = retryable_slots_sender.send(slot);
         }
-        let potential_slots_to_dump = {
-            // TODO: In the case of DuplicateAncestorDecision::ContinueSearch
-            // This means all the ancestors were mismatched, which
-            // means the earliest mismatched ancestor has yet to be found.
-            //
-            // In the best case scenario, this means after ReplayStage dumps
-            // the earliest known ancestor `A` here, and then repairs `A`,
-            // because we may still have the incorrect version of some ancestor
-            // of `A`, we will mark `A` as dead and then continue the search
-            // protocol through another round of ancestor repairs.
-            //
-            // However this process is a bit slow, so in an ideal world, the
-            // protocol could be extended to keep searching by making
-            // another ancestor repair request from the earliest returned
-            // ancestor from this search.
-            decision
-                .repair_status()
-                .map(|status| status.correct_ancestors_to_repair.clone())
-        };
+        if let DuplicateAncestorDecision::ContinueSearch(status) = decision {
+            // All the ancestors were missing or mismatched, which means the earliest
+            // mismatched ancestor has yet to be found. Rather than dumping the earliest known
+            // ancestor `A`, repairing it, marking it dead and starting over from `A`, keep
+            // searching by requesting the ancestors of `A` right away. The ancestors found so
+            // far are dumped once that search completes.
+            let _ = continue_search_sender.send(status.correct_ancestors_to_repair);
+            return;
+        }
+        let potential_slots_to_dump = decision
+            .repair_status()
+            .map(|status| status.correct_ancestors_to_repair.clone());
 
         // Now signal ReplayStage about the new updated slots. It's important to do this
         // AFTER we've removed the ancestor_hashes_status_ref in case replay
@@ -455,6 +463,7 @@ impl AncestorHashesService {
         exit: Arc<AtomicBool>,
         ancestor_hashes_replay_update_receiver: AncestorHashesReplayUpdateReceiver,
         retryable_slots_receiver: RetryableSlotsReceiver,
+        continue_search_receiver: ContinueSearchReceiver,
     ) -> JoinHandle<()> {
         let serve_repair = ServeRepair::new(repair_info.cluster_info.clone());
         let mut repair_stats = AncestorRepairRequestsStats::default();
@@ -479,6 +488,7 @@ impl AncestorHashesService {
                     &outstanding_requests,
                     &ancestor_hashes_replay_update_receiver,
                     &retryable_slots_receiver,
+                    &continue_search_receiver,
                     &serve_repair,
                     &mut repair_stats,
                     &mut dead_slot_pool,
@@ -499,6 +509,7 @@ impl AncestorHashesService {
         outstanding_requests: &RwLock<OutstandingAncestorHashesRepairs>,
         ancestor_hashes_replay_update_receiver: &AncestorHashesReplayUpdateReceiver,
         retryable_slots_receiver: &RetryableSlotsReceiver,
+        continue_search_receiver: &ContinueSearchReceiver,
         serve_repair: &ServeRepair,
         repair_stats: &mut AncestorRepairRequestsStats,
         dead_slot_pool: &mut HashSet<Slot>,
@@ -534,14 +545,47 @@ impl AncestorHashesService {
             if *slot <= root_bank.slot() {
                 false
             } else if status.is_expired() {
-                // Add the slot back to the repairable pool to retry
-                repairable_dead_slot_pool.insert(*slot);
+                if status.prior_ancestors().is_empty() {
+                    // Add the slot back to the repairable pool to retry
+                    repairable_dead_slot_pool.insert(*slot);
+                } else {
+                    // Stop searching and repair the ancestors found so far
+                    let _ = repair_info
+                        .duplicate_slots_reset_sender
+                        .send(status.prior_ancestors().to_vec());
+                }
                 false
             } else {
                 true
             }
         });
 
+        for prior_ancestors in continue_search_receiver.try_iter() {
+            let slot = prior_ancestors.last().unwrap().0;
+            datapoint_info!("ancestor-repair-continue", ("slot", slot, i64));
+            if slot > root_bank.slot()
+                && !ancestor_hashes_request_statuses.contains_key(&slot)
+                && Self::initiate_ancestor_hashes_requests_for_duplicate_slot(
+                    ancestor_hashes_request_statuses,
+                    ancestor_hashes_request_socket,
+                    &repair_info.cluster_slots,
+                    serve_repair,
+                    &repair_info.repair_validators,
+                    slot,
+                    prior_ancestors.clone(),
+                    repair_stats,
+                    outstanding_requests,
+                )
+            {
+                request_throttle.push(timestamp());
+            } else {
+                // The search can't continue, repair the ancestors found so far
+                let _ = repair_info
+                    .duplicate_slots_reset_sender
+                    .send(prior_ancestors);
+            }
+        }
+
         // Keep around the last second of requests in the throttler.
         request_throttle.retain(|request_time| *request_time > (timestamp() - 1000));
 
@@ -566,6 +610,7 @@ impl AncestorHashesService {
                     serve_repair,
                     &repair_info.repair_validators,
                     slot,
+                    vec![],
                     repair_stats,
                     outstanding_requests,
                 ) {
@@ -631,7 +676,9 @@ impl AncestorHashesService {
     }
 
     /// Returns true if a request was successfully made and the status
-    /// added to `ancestor_hashes_request_statuses`
+    /// added to `ancestor_hashes_request_statuses`. A non-empty `prior_ancestors`
+    /// continues an earlier search, see `DeadSlotAncestorRequestStatus::new_continued_search`
+    #[allow(clippy::too_many_arguments)]
     fn initiate_ancestor_hashes_requests_for_duplicate_slot(
         ancestor_hashes_request_statuses: &DashMap<Slot, DeadSlotAncestorRequestStatus>,
         ancestor_hashes_request_socket: &UdpSocket,
@@ -639,6 +686,7 @@ impl AncestorHashesService {
         serve_repair: &ServeRepair,
         repair_validators: &Option<HashSet<Pubkey>>,
         duplicate_slot: Slot,
+        prior_ancestors: Vec<(Slot, Hash)>,
         repair_stats: &mut AncestorRepairRequestsStats,
         outstanding_requests: &RwLock<OutstandingAncestorHashesRepairs>,
     ) -> bool {
@@ -664,12 +712,17 @@ impl AncestorHashesService {
                 }
             }
 
-            let ancestor_request_status = DeadSlotAncestorRequestStatus::new(
-                sampled_validators
-                    .into_iter()
-                    .map(|(_pk, socket_addr)| socket_addr),
-                duplicate_slot,
-            );
+            let sampled_validators = sampled_validators
+                .into_iter()
+                .map(|(_pk, socket_addr)| socket_addr);
+            let ancestor_request_status = if prior_ancestors.is_empty() {
+                DeadSlotAncestorRequestStatus::new(sampled_validators, duplicate_slot)
+            } else {
+                DeadSlotAncestorRequestStatus::new_continued_search(
+                    sampled_validators,
+                    prior_ancestors,
+                )
+            };
             assert!(!ancestor_hashes_request_statuses.contains_key(&duplicate_slot));
             ancestor_hashes_request_statuses.insert(duplicate_slot, ancestor_request_status);
             true
@@ -684,6 +737,7 @@ mod test {
     use super::*;
     use crate::{
         cluster_slot_state_verifier::DuplicateSlotsToRepair,
+        duplicate_repair_status::DuplicateSlotRepairStatus,
         repair_service::DuplicateSlotsResetReceiver,
         replay_stage::{
             tests::{replay_blockstore_components, ReplayBlockstoreComponents},
@@ -954,6 +1008,8 @@ mod test {
         _duplicate_slots_reset_receiver: DuplicateSlotsResetReceiver,
         retryable_slots_sender: RetryableSlotsSender,
         retryable_slots_receiver: RetryableSlotsReceiver,
+        continue_search_sender: ContinueSearchSender,
+        continue_search_receiver: ContinueSearchReceiver,
         ancestor_hashes_replay_update_sender: AncestorHashesReplayUpdateSender,
         ancestor_hashes_replay_update_receiver: AncestorHashesReplayUpdateReceiver,
     }
@@ -982,6 +1038,7 @@ mod test {
             let (ancestor_hashes_replay_update_sender, ancestor_hashes_replay_update_receiver) =
                 unbounded();
             let (retryable_slots_sender, retryable_slots_receiver) = unbounded();
+            let (continue_search_sender, continue_search_receiver) = unbounded();
             Self {
                 ancestor_hashes_request_statuses,
                 ancestor_hashes_request_socket,
@@ -999,6 +1056,8 @@ mod test {
                 ancestor_hashes_replay_update_receiver,
                 retryable_slots_sender,
                 retryable_slots_receiver,
+                continue_search_sender,
+                continue_search_receiver,
             }
         }
     }
@@ -1096,6 +1155,7 @@ mod test {
             &requester_serve_repair,
             &repair_validators,
             dead_slot,
+            vec![],
             &mut repair_stats,
             &outstanding_requests,
         );
@@ -1113,6 +1173,7 @@ mod test {
             &requester_serve_repair,
             &repair_validators,
             dead_slot,
+            vec![],
             &mut repair_stats,
             &outstanding_requests,
         );
@@ -1172,6 +1233,7 @@ mod test {
             ancestor_hashes_replay_update_sender,
             ancestor_hashes_replay_update_receiver,
             retryable_slots_receiver,
+            continue_search_receiver,
             ..
         } = ManageAncestorHashesState::new(vote_simulator.bank_forks);
         let responder_node = Node::new_localhost();
@@ -1190,6 +1252,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1225,6 +1288,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1256,6 +1320,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1284,6 +1349,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1318,6 +1384,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1354,6 +1421,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1401,6 +1469,7 @@ mod test {
             ancestor_hashes_replay_update_sender,
             ancestor_hashes_replay_update_receiver,
             retryable_slots_receiver,
+            continue_search_receiver,
             ..
         } = ManageAncestorHashesState::new(bank_forks.clone());
 
@@ -1443,6 +1512,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1505,6 +1575,8 @@ mod test {
             ancestor_hashes_replay_update_receiver,
             retryable_slots_receiver,
             retryable_slots_sender,
+            continue_search_sender,
+            continue_search_receiver,
             ..
         } = ManageAncestorHashesState::new(vote_simulator.bank_forks);
 
@@ -1519,6 +1591,7 @@ mod test {
             decision,
             &repair_info.duplicate_slots_reset_sender,
             &retryable_slots_sender,
+            &continue_search_sender,
         );
 
         // Simulate ancestor request thread getting the retry signal
@@ -1531,6 +1604,7 @@ mod test {
             &outstanding_requests,
             &ancestor_hashes_replay_update_receiver,
             &retryable_slots_receiver,
+            &continue_search_receiver,
             &requester_serve_repair,
             &mut AncestorRepairRequestsStats::default(),
             &mut dead_slot_pool,
@@ -1541,4 +1615,66 @@ mod test {
         assert!(dead_slot_pool.is_empty());
         assert!(repairable_dead_slot_pool.contains(&request_slot));
     }
+
+    #[test]
+    fn test_ancestor_hashes_service_continue_search_decision() {
+        let vote_simulator = VoteSimulator::new(1);
+        let ManageAncestorHashesState {
+            ancestor_hashes_request_statuses,
+            ancestor_hashes_request_socket,
+            requester_serve_repair,
+            repair_info,
+            outstanding_requests,
+            mut dead_slot_pool,
+            mut repairable_dead_slot_pool,
+            mut request_throttle,
+            ancestor_hashes_replay_update_receiver,
+            retryable_slots_receiver,
+            retryable_slots_sender,
+            continue_search_sender,
+            continue_search_receiver,
+            _duplicate_slots_reset_receiver: duplicate_slots_reset_receiver,
+            ..
+        } = ManageAncestorHashesState::new(vote_simulator.bank_forks);
+
+        // All the agreed upon ancestors of the dead slot are mismatched
+        let correct_ancestors_to_repair = vec![(10, Hash::new_unique()), (9, Hash::new_unique())];
+        let decision = DuplicateAncestorDecision::ContinueSearch(DuplicateSlotRepairStatus {
+            correct_ancestors_to_repair: correct_ancestors_to_repair.clone(),
+            ..DuplicateSlotRepairStatus::default()
+        });
+        assert!(!decision.is_retryable());
+        AncestorHashesService::handle_ancestor_request_decision(
+            10,
+            decision,
+            &repair_info.duplicate_slots_reset_sender,
+            &retryable_slots_sender,
+            &continue_search_sender,
+        );
+
+        // Nothing is dumped until the search for the ancestors of slot 9 completes
+        assert!(duplicate_slots_reset_receiver.try_recv().is_err());
+
+        // There are no validators to sample for slot 9, so the search stops and the
+        // ancestors found so far are dumped
+        AncestorHashesService::manage_ancestor_requests(
+            &ancestor_hashes_request_statuses,
+            &ancestor_hashes_request_socket,
+            &repair_info,
+            &outstanding_requests,
+            &ancestor_hashes_replay_update_receiver,
+            &retryable_slots_receiver,
+            &continue_search_receiver,
+            &requester_serve_repair,
+            &mut AncestorRepairRequestsStats::default(),
+            &mut dead_slot_pool,
+            &mut repairable_dead_slot_pool,
+            &mut request_throttle,
+        );
+        assert!(ancestor_hashes_request_statuses.is_empty());
+        assert_eq!(
+            duplicate_slots_reset_receiver.try_recv().unwrap(),
+            correct_ancestors_to_repair
+        );
+    }
 }
@@ -1,10 +1,13 @@
 //! The `banking_stage` processes Transaction messages. It is intended to be used
 //! to contruct a software pipeline. The stage uses all available CPU cores and
 //! can do its processing in parallel with signature verification on the GPU.
+use crate::banking_trace::BankingTracer;
+use crate::packet_drops::{self, Stage};
 use crate::packet_hasher::PacketHasher;
 use crossbeam_channel::{Receiver as CrossbeamReceiver, RecvTimeoutError};
 use itertools::Itertools;
 use lru::LruCache;
+use rayon::prelude::*;
 use retain_mut::RetainMut;
 use solana_entry::entry::hash_transactions;
 use solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo};
@@ -35,8 +38,9 @@ use solana_sdk::{
         Slot, DEFAULT_TICKS_PER_SLOT, MAX_PROCESSING_AGE, MAX_TRANSACTION_FORWARDING_DELAY,
         MAX_TRANSACTION_FORWARDING_DELAY_GPU,
     },
+    compute_budget::ComputeBudget,
     feature_set,
-    message::Message,
+    message::{v0::MessageAddressTableLookup, LoadedAddresses, Message},
     pubkey::Pubkey,
     short_vec::decode_shortu16_len,
     signature::Signature,
@@ -67,6 +71,14 @@ type PacketsAndOffsets = (Packets, Vec<usize>, bool);
 
 pub type UnprocessedPackets = VecDeque<PacketsAndOffsets>;
 
+// Packets buffered across all banking threads of this process, for operator monitoring
+static BUFFERED_PACKETS_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of packets currently buffered by the banking threads of this process
+pub fn buffered_packets_count() -> usize {
+    BUFFERED_PACKETS_COUNT.load(Ordering::Relaxed)
+}
+
 /// Transaction forwarding
 pub const FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET: u64 = 2;
 pub const HOLD_TRANSACTIONS_SLOT_OFFSET: u64 = 20;
@@ -112,6 +124,7 @@ pub struct BankingStageStats {
     cost_tracker_update_elapsed: AtomicU64,
     cost_tracker_clone_elapsed: AtomicU64,
     cost_tracker_check_elapsed: AtomicU64,
+    precompile_verify_elapsed: AtomicU64,
 }
 
 impl BankingStageStats {
@@ -253,6 +266,11 @@ impl BankingStageStats {
                     self.cost_tracker_check_elapsed.swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "precompile_verify_elapsed",
+                    self.precompile_verify_elapsed.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
             );
         }
     }
@@ -290,6 +308,7 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: ReplayVoteSender,
         cost_model: Arc<RwLock<CostModel>>,
+        banking_tracer: Arc<BankingTracer>,
     ) -> Self {
         Self::new_num_threads(
             cluster_info,
@@ -301,6 +320,7 @@ impl BankingStage {
             transaction_status_sender,
             gossip_vote_sender,
             cost_model,
+            banking_tracer,
         )
     }
 
@@ -314,6 +334,7 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: ReplayVoteSender,
         cost_model: Arc<RwLock<CostModel>>,
+        banking_tracer: Arc<BankingTracer>,
     ) -> Self {
         let batch_limit = TOTAL_BUFFERED_PACKETS / ((num_threads - 1) as usize * PACKETS_PER_BATCH);
         // Single thread to generate entries from many banks.
@@ -349,6 +370,7 @@ impl BankingStage {
                 let duplicates = duplicates.clone();
                 let data_budget = data_budget.clone();
                 let cost_model = cost_model.clone();
+                let banking_tracer = banking_tracer.clone();
                 Builder::new()
                     .name("solana-banking-stage-tx".to_string())
                     .spawn(move || {
@@ -365,6 +387,7 @@ impl BankingStage {
                             &duplicates,
                             &data_budget,
                             cost_model,
+                            &banking_tracer,
                         );
                     })
                     .unwrap()
@@ -707,11 +730,13 @@ impl BankingStage {
         duplicates: &Arc<Mutex<(LruCache<u64, ()>, PacketHasher)>>,
         data_budget: &DataBudget,
         cost_model: Arc<RwLock<CostModel>>,
+        banking_tracer: &BankingTracer,
     ) {
         let recorder = poh_recorder.lock().unwrap().recorder();
         let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
         let mut buffered_packets = VecDeque::with_capacity(batch_limit);
         let banking_stage_stats = BankingStageStats::new(id);
+        let mut reported_buffered_packets_count = 0;
         loop {
             let my_pubkey = cluster_info.id();
             while !buffered_packets.is_empty() {
@@ -729,6 +754,13 @@ impl BankingStage {
                     data_budget,
                     &cost_model,
                 );
+                if banking_tracer.is_enabled()
+                    && !matches!(decision, BufferedPacketsDecision::Forward)
+                {
+                    let buffered_packets_count =
+                        buffered_packets.iter().map(|packets| packets.1.len()).sum();
+                    banking_tracer.trace_decision(id, &decision, buffered_packets_count);
+                }
                 if matches!(decision, BufferedPacketsDecision::Hold)
                     || matches!(decision, BufferedPacketsDecision::ForwardAndHold)
                 {
@@ -763,13 +795,30 @@ impl BankingStage {
                 duplicates,
                 &recorder,
                 &cost_model,
+                banking_tracer,
             ) {
                 Ok(()) | Err(RecvTimeoutError::Timeout) => (),
                 Err(RecvTimeoutError::Disconnected) => break,
             }
 
+            let buffered_packets_count =
+                buffered_packets.iter().map(|packets| packets.1.len()).sum();
+            Self::update_buffered_packets_count(
+                &mut reported_buffered_packets_count,
+                buffered_packets_count,
+            );
             banking_stage_stats.report(1000);
         }
+        Self::update_buffered_packets_count(&mut reported_buffered_packets_count, 0);
+    }
+
+    fn update_buffered_packets_count(reported_count: &mut usize, count: usize) {
+        if count > *reported_count {
+            BUFFERED_PACKETS_COUNT.fetch_add(count - *reported_count, Ordering::Relaxed);
+        } else {
+            BUFFERED_PACKETS_COUNT.fetch_sub(*reported_count - count, Ordering::Relaxed);
+        }
+        *reported_count = count;
     }
 
     pub fn num_threads() -> u32 {
@@ -859,8 +908,14 @@ impl BankingStage {
 
         let mut mint_decimals: HashMap<Pubkey, u8> = HashMap::new();
 
-        let pre_token_balances = if transaction_status_sender.is_some() {
-            collect_token_balances(bank, batch, &mut mint_decimals)
+        let pre_token_balances = if let Some(transaction_status_sender) = &transaction_status_sender
+        {
+            collect_token_balances(
+                bank,
+                batch,
+                &mut mint_decimals,
+                &transaction_status_sender.token_program_ids,
+            )
         } else {
             vec![]
         };
@@ -870,7 +925,10 @@ impl BankingStage {
             mut loaded_accounts,
             results,
             inner_instructions,
+            _inner_instructions_invoke_units,
             transaction_logs,
+            transaction_return_data,
+            _compute_units,
             mut retryable_txs,
             tx_count,
             signature_count,
@@ -919,7 +977,12 @@ impl BankingStage {
             if let Some(transaction_status_sender) = transaction_status_sender {
                 let txs = batch.sanitized_transactions().to_vec();
                 let post_balances = bank.collect_balances(batch);
-                let post_token_balances = collect_token_balances(bank, batch, &mut mint_decimals);
+                let post_token_balances = collect_token_balances(
+                    bank,
+                    batch,
+                    &mut mint_decimals,
+                    &transaction_status_sender.token_program_ids,
+                );
                 transaction_status_sender.send_transaction_status_batch(
                     bank.clone(),
                     txs,
@@ -928,6 +991,7 @@ impl BankingStage {
                     TransactionTokenBalancesSet::new(pre_token_balances, post_token_balances),
                     inner_instructions,
                     transaction_logs,
+                    transaction_return_data,
                     tx_results.rent_debits,
                 );
             }
@@ -1088,8 +1152,8 @@ impl BankingStage {
     }
 
     // This function deserializes packets into transactions, computes the blake3 hash of transaction messages,
-    // and verifies secp256k1 instructions. A list of valid transactions are returned with their message hashes
-    // and packet indexes.
+    // and verifies secp256k1 and ed25519 instructions in parallel. A list of valid transactions are returned
+    // with their message hashes and packet indexes.
     // Also returned is packet indexes for transaction should be retried due to cost limits.
     #[allow(clippy::needless_collect)]
     fn transactions_from_packets(
@@ -1101,10 +1165,11 @@ impl BankingStage {
         demote_program_write_locks: bool,
         votes_only: bool,
         cost_model: &Arc<RwLock<CostModel>>,
+        address_loader: impl Fn(&[MessageAddressTableLookup]) -> transaction::Result<LoadedAddresses>,
     ) -> (Vec<SanitizedTransaction>, Vec<usize>, Vec<usize>) {
         let mut retryable_transaction_packet_indexes: Vec<usize> = vec![];
 
-        let verified_transactions_with_packet_indexes: Vec<_> = transaction_indexes
+        let sanitized_transactions_with_packet_indexes: Vec<_> = transaction_indexes
             .iter()
             .filter_map(|tx_index| {
                 let p = &msgs.packets[*tx_index];
@@ -1115,14 +1180,30 @@ impl BankingStage {
                 let tx: VersionedTransaction = limited_deserialize(&p.data[0..p.meta.size]).ok()?;
                 let message_bytes = Self::packet_message(p)?;
                 let message_hash = Message::hash_raw_message(message_bytes);
-                let tx = SanitizedTransaction::try_create(tx, message_hash, |_| {
-                    Err(TransactionError::UnsupportedVersion)
-                })
-                .ok()?;
-                tx.verify_precompiles(feature_set).ok()?;
+                let tx =
+                    SanitizedTransaction::try_create(tx, message_hash, &address_loader).ok()?;
                 Some((tx, *tx_index))
             })
             .collect();
+
+        // Verifying the signatures in precompile instructions dominates the conversion of
+        // transactions that have them, spread it over the rayon pool instead of serializing it
+        // on this banking thread
+        let mut precompile_verify_time = Measure::start("precompile_verify_time");
+        let mut verified_transactions_with_packet_indexes: Vec<_> =
+            sanitized_transactions_with_packet_indexes
+                .into_par_iter()
+                .filter(|(tx, _)| tx.verify_precompiles(feature_set).is_ok())
+                .collect();
+        precompile_verify_time.stop();
+        banking_stage_stats
+            .precompile_verify_elapsed
+            .fetch_add(precompile_verify_time.as_us(), Ordering::Relaxed);
+        if feature_set.is_active(&feature_set::add_set_compute_unit_price_ix::id()) {
+            // process transactions which set a higher compute unit price first
+            verified_transactions_with_packet_indexes
+                .sort_by_cached_key(|(tx, _)| cmp::Reverse(Self::transaction_priority(tx)));
+        }
         banking_stage_stats.cost_tracker_check_count.fetch_add(
             verified_transactions_with_packet_indexes.len(),
             Ordering::Relaxed,
@@ -1139,10 +1220,11 @@ impl BankingStage {
                         && read_cost_tracker
                             .would_transaction_fit(
                                 &tx,
-                                &cost_model
-                                    .read()
-                                    .unwrap()
-                                    .calculate_cost(&tx, demote_program_write_locks),
+                                &cost_model.read().unwrap().calculate_cost(
+                                    &tx,
+                                    demote_program_write_locks,
+                                    feature_set.is_active(&feature_set::tx_wide_compute_cap::id()),
+                                ),
                             )
                             .is_err()
                     {
@@ -1169,6 +1251,15 @@ impl BankingStage {
         )
     }
 
+    /// Returns the compute unit price a transaction set with its compute budget
+    /// instructions, or zero if it did not set a valid one
+    fn transaction_priority(transaction: &SanitizedTransaction) -> u64 {
+        ComputeBudget::new()
+            .process_message(transaction.message(), true, true)
+            .map(|prioritization_fee_details| prioritization_fee_details.get_priority())
+            .unwrap_or_default()
+    }
+
     /// This function filters pending packets that are still valid
     /// # Arguments
     /// * `transactions` - a batch of transactions deserialized from packets
@@ -1231,6 +1322,7 @@ impl BankingStage {
                 bank.demote_program_write_locks(),
                 bank.vote_only_bank(),
                 cost_model,
+                |address_table_lookups| bank.load_lookup_table_addresses(address_table_lookups),
             );
         packet_conversion_time.stop();
         inc_new_counter_info!("banking_stage-packet_conversion", 1);
@@ -1269,10 +1361,11 @@ impl BankingStage {
             if unprocessed_tx_indexes.iter().all(|&i| i != index) {
                 bank.write_cost_tracker().unwrap().add_transaction_cost(
                     tx,
-                    &cost_model
-                        .read()
-                        .unwrap()
-                        .calculate_cost(tx, bank.demote_program_write_locks()),
+                    &cost_model.read().unwrap().calculate_cost(
+                        tx,
+                        bank.demote_program_write_locks(),
+                        bank.tx_wide_compute_cap(),
+                    ),
                 );
             }
         });
@@ -1287,10 +1380,13 @@ impl BankingStage {
         );
         filter_pending_packets_time.stop();
 
+        let num_dropped_txs =
+            unprocessed_tx_count.saturating_sub(filtered_unprocessed_packet_indexes.len());
         inc_new_counter_info!(
             "banking_stage-dropped_tx_before_forwarding",
-            unprocessed_tx_count.saturating_sub(filtered_unprocessed_packet_indexes.len())
+            num_dropped_txs
         );
+        packet_drops::record_dropped(Stage::Banking, num_dropped_txs);
 
         // combine cost-related unprocessed transactions with bank determined unprocessed for
         // buffering
@@ -1342,6 +1438,7 @@ impl BankingStage {
                 bank.demote_program_write_locks(),
                 bank.vote_only_bank(),
                 cost_model,
+                |address_table_lookups| bank.load_lookup_table_addresses(address_table_lookups),
             );
         unprocessed_packet_conversion_time.stop();
 
@@ -1357,10 +1454,12 @@ impl BankingStage {
 
         filtered_unprocessed_packet_indexes.extend(retry_packet_indexes);
 
+        let num_dropped_txs = tx_count.saturating_sub(filtered_unprocessed_packet_indexes.len());
         inc_new_counter_info!(
             "banking_stage-dropped_tx_before_forwarding",
-            tx_count.saturating_sub(filtered_unprocessed_packet_indexes.len())
+            num_dropped_txs
         );
+        packet_drops::record_dropped(Stage::Banking, num_dropped_txs);
         banking_stage_stats
             .unprocessed_packet_conversion_elapsed
             .fetch_add(
@@ -1386,6 +1485,27 @@ impl BankingStage {
             .collect()
     }
 
+    // Traces the received batches while this node is leader, or will be soon enough to hold them
+    fn trace_packet_batches(
+        banking_tracer: &BankingTracer,
+        id: u32,
+        poh: &Mutex<PohRecorder>,
+        mms: &[Packets],
+    ) {
+        let (slot, would_be_leader) = {
+            let poh = poh.lock().unwrap();
+            (
+                poh.bank().map(|bank| bank.slot()),
+                poh.would_be_leader(HOLD_TRANSACTIONS_SLOT_OFFSET * DEFAULT_TICKS_PER_SLOT),
+            )
+        };
+        if would_be_leader {
+            for msgs in mms {
+                banking_tracer.trace_packet_batch(id, slot, msgs);
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     /// Process the incoming packets
     fn process_packets(
@@ -1403,10 +1523,14 @@ impl BankingStage {
         duplicates: &Arc<Mutex<(LruCache<u64, ()>, PacketHasher)>>,
         recorder: &TransactionRecorder,
         cost_model: &Arc<RwLock<CostModel>>,
+        banking_tracer: &BankingTracer,
     ) -> Result<(), RecvTimeoutError> {
         let mut recv_time = Measure::start("process_packets_recv");
         let mms = verified_receiver.recv_timeout(recv_timeout)?;
         recv_time.stop();
+        if banking_tracer.is_enabled() {
+            Self::trace_packet_batches(banking_tracer, id, poh, &mms);
+        }
 
         let mms_len = mms.len();
         let count: usize = mms.iter().map(|x| x.packets.len()).sum();
@@ -1540,6 +1664,7 @@ impl BankingStage {
         banking_stage_stats
             .dropped_packets_count
             .fetch_add(dropped_packets_count, Ordering::Relaxed);
+        packet_drops::record_overrun(Stage::Banking, dropped_packets_count);
         banking_stage_stats
             .newly_buffered_packets_count
             .fetch_add(newly_buffered_packets_count, Ordering::Relaxed);
@@ -1584,12 +1709,12 @@ impl BankingStage {
             banking_stage_stats
                 .packet_duplicate_check_elapsed
                 .fetch_add(packet_duplicate_check_time.as_us(), Ordering::Relaxed);
+            let num_duplicated_packets =
+                original_packets_count.saturating_sub(packet_indexes.len());
             banking_stage_stats
                 .dropped_duplicated_packets_count
-                .fetch_add(
-                    original_packets_count.saturating_sub(packet_indexes.len()),
-                    Ordering::Relaxed,
-                );
+                .fetch_add(num_duplicated_packets, Ordering::Relaxed);
+            packet_drops::record_dropped(Stage::Banking, num_duplicated_packets);
         }
         if Self::packet_has_more_unprocessed_transactions(&packet_indexes) {
             if unprocessed_packets.len() >= batch_limit {
@@ -1664,6 +1789,7 @@ mod tests {
     use solana_gossip::{cluster_info::Node, contact_info::ContactInfo};
     use solana_ledger::{
         blockstore::{entries_to_test_shreds, Blockstore},
+        blockstore_processor::TransactionStatusOverflowPolicy,
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
         get_tmp_ledger_path,
         leader_schedule_cache::LeaderScheduleCache,
@@ -1680,12 +1806,14 @@ mod tests {
         instruction::InstructionError,
         poh_config::PohConfig,
         signature::{Keypair, Signer},
-        system_instruction::SystemError,
+        system_instruction::{self, SystemError},
         system_transaction,
         transaction::{Transaction, TransactionError},
     };
     use solana_streamer::socket::SocketAddrSpace;
-    use solana_transaction_status::TransactionWithStatusMeta;
+    use solana_transaction_status::{
+        token_balances::default_token_program_ids, TransactionWithStatusMeta,
+    };
     use solana_vote_program::vote_transaction;
     use std::{
         convert::{TryFrom, TryInto},
@@ -1734,6 +1862,7 @@ mod tests {
                 None,
                 gossip_vote_sender,
                 Arc::new(RwLock::new(CostModel::default())),
+                Arc::new(BankingTracer::new_disabled()),
             );
             drop(verified_sender);
             drop(gossip_verified_vote_sender);
@@ -1783,6 +1912,7 @@ mod tests {
                 None,
                 gossip_vote_sender,
                 Arc::new(RwLock::new(CostModel::default())),
+                Arc::new(BankingTracer::new_disabled()),
             );
             trace!("sending bank");
             drop(verified_sender);
@@ -1856,6 +1986,7 @@ mod tests {
                 None,
                 gossip_vote_sender,
                 Arc::new(RwLock::new(CostModel::default())),
+                Arc::new(BankingTracer::new_disabled()),
             );
 
             // fund another account so we can send 2 good transactions in a single batch.
@@ -2007,6 +2138,7 @@ mod tests {
                     None,
                     gossip_vote_sender,
                     Arc::new(RwLock::new(CostModel::default())),
+                    Arc::new(BankingTracer::new_disabled()),
                 );
 
                 // wait for banking_stage to eat the packets
@@ -2663,6 +2795,8 @@ mod tests {
                 transaction_status_receiver,
                 Arc::new(AtomicU64::default()),
                 blockstore.clone(),
+                None,
+                Arc::default(),
                 &Arc::new(AtomicBool::new(false)),
             );
 
@@ -2676,6 +2810,10 @@ mod tests {
                 Some(TransactionStatusSender {
                     sender: transaction_status_sender,
                     enable_cpi_and_log_storage: false,
+                    token_program_ids: Arc::new(default_token_program_ids()),
+                    overflow_policy: TransactionStatusOverflowPolicy::Block,
+                    spill: None,
+                    dropped_slots: Arc::default(),
                 }),
                 &gossip_vote_sender,
             );
@@ -3148,6 +3286,7 @@ mod tests {
                     false,
                     votes_only,
                     &Arc::new(RwLock::new(CostModel::default())),
+                    |_| Err(TransactionError::UnsupportedVersion),
                 );
             assert_eq!(2, txs.len());
             assert_eq!(vec![0, 1], tx_packet_index);
@@ -3163,6 +3302,7 @@ mod tests {
                     false,
                     votes_only,
                     &Arc::new(RwLock::new(CostModel::default())),
+                    |_| Err(TransactionError::UnsupportedVersion),
                 );
             assert_eq!(0, txs.len());
             assert_eq!(0, tx_packet_index.len());
@@ -3187,6 +3327,7 @@ mod tests {
                     false,
                     votes_only,
                     &Arc::new(RwLock::new(CostModel::default())),
+                    |_| Err(TransactionError::UnsupportedVersion),
                 );
             assert_eq!(3, txs.len());
             assert_eq!(vec![0, 1, 2], tx_packet_index);
@@ -3202,6 +3343,7 @@ mod tests {
                     false,
                     votes_only,
                     &Arc::new(RwLock::new(CostModel::default())),
+                    |_| Err(TransactionError::UnsupportedVersion),
                 );
             assert_eq!(2, txs.len());
             assert_eq!(vec![0, 2], tx_packet_index);
@@ -3226,6 +3368,7 @@ mod tests {
                     false,
                     votes_only,
                     &Arc::new(RwLock::new(CostModel::default())),
+                    |_| Err(TransactionError::UnsupportedVersion),
                 );
             assert_eq!(3, txs.len());
             assert_eq!(vec![0, 1, 2], tx_packet_index);
@@ -3241,9 +3384,53 @@ mod tests {
                     false,
                     votes_only,
                     &Arc::new(RwLock::new(CostModel::default())),
+                    |_| Err(TransactionError::UnsupportedVersion),
                 );
             assert_eq!(3, txs.len());
             assert_eq!(vec![0, 1, 2], tx_packet_index);
         }
     }
+
+    #[test]
+    fn test_transactions_from_packets_prioritization() {
+        use solana_sdk::{compute_budget::ComputeBudgetInstruction, feature_set::FeatureSet};
+        let keypair = Keypair::new();
+        let transfer_tx =
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default());
+        let prioritized_transfer_tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(10),
+                system_instruction::transfer(&keypair.pubkey(), &keypair.pubkey(), 1),
+            ],
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            Hash::default(),
+        );
+        let (packets, packet_indexes) =
+            make_test_packets(vec![transfer_tx, prioritized_transfer_tx], vec![]);
+
+        let transactions_from_packets = |feature_set: FeatureSet| {
+            BankingStage::transactions_from_packets(
+                &packets,
+                &packet_indexes,
+                &Arc::new(feature_set),
+                &RwLock::new(CostTracker::default()).read().unwrap(),
+                &BankingStageStats::default(),
+                false,
+                false,
+                &Arc::new(RwLock::new(CostModel::default())),
+                |_| Err(TransactionError::UnsupportedVersion),
+            )
+        };
+
+        let (txs, tx_packet_index, _retryable_packet_indexes) =
+            transactions_from_packets(FeatureSet::default());
+        assert_eq!(2, txs.len());
+        assert_eq!(vec![0, 1], tx_packet_index);
+
+        let (txs, tx_packet_index, _retryable_packet_indexes) =
+            transactions_from_packets(FeatureSet::all_enabled());
+        assert_eq!(2, txs.len());
+        assert_eq!(vec![1, 0], tx_packet_index);
+    }
 }
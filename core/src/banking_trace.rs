@@ -0,0 +1,414 @@
+//! Scheduler issues in banking stage depend on the exact packets a leader receives and when it
+//! receives them, which is hard to reproduce off a production node.  The `BankingTracer` records
+//! every packet batch that enters banking stage around this node's leader slots, along with the
+//! decisions the banking threads make about their buffered packets, to files in a trace
+//! directory.  The oldest files are deleted once the traces exceed a total size.
+//! `BankingTraceReader` reads the events back, see `solana-banking-bench --replay-trace` to feed
+//! them to a banking stage offline.
+
+use {
+    crate::banking_stage::BufferedPacketsDecision,
+    crossbeam_channel::{bounded, Receiver, Sender, TrySendError},
+    solana_perf::packet::{Packet, Packets},
+    solana_sdk::clock::Slot,
+    std::{
+        fs::{self, File},
+        io::{self, BufReader, BufWriter, Write},
+        path::{Path, PathBuf},
+        thread::{Builder, JoinHandle},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+pub const DEFAULT_BANKING_TRACE_DIR_BYTE_LIMIT: u64 = 4 * 1024 * 1024 * 1024;
+// A new trace file is started once the current one reaches this size
+const TRACE_FILE_BYTE_LIMIT: u64 = 64 * 1024 * 1024;
+// Events are dropped rather than blocking banking threads once the writer falls this far behind
+const TRACE_CHANNEL_CAPACITY: usize = 10_000;
+const TRACE_FILE_PREFIX: &str = "events-";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TracedPacket {
+    pub data: Vec<u8>,
+    pub forwarded: bool,
+    pub discard: bool,
+    pub is_simple_vote_tx: bool,
+}
+
+impl From<&Packet> for TracedPacket {
+    fn from(packet: &Packet) -> Self {
+        Self {
+            data: packet.data[..packet.meta.size].to_vec(),
+            forwarded: packet.meta.forward,
+            discard: packet.meta.discard,
+            is_simple_vote_tx: packet.meta.is_simple_vote_tx,
+        }
+    }
+}
+
+impl TracedPacket {
+    pub fn to_packet(&self) -> Packet {
+        let mut packet = Packet::default();
+        let size = self.data.len().min(packet.data.len());
+        packet.data[..size].copy_from_slice(&self.data[..size]);
+        packet.meta.size = size;
+        packet.meta.forward = self.forwarded;
+        packet.meta.discard = self.discard;
+        packet.meta.is_simple_vote_tx = self.is_simple_vote_tx;
+        packet
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TracedDecision {
+    Consume,
+    Forward,
+    ForwardAndHold,
+    Hold,
+}
+
+impl From<&BufferedPacketsDecision> for TracedDecision {
+    fn from(decision: &BufferedPacketsDecision) -> Self {
+        match decision {
+            BufferedPacketsDecision::Consume(_) => Self::Consume,
+            BufferedPacketsDecision::Forward => Self::Forward,
+            BufferedPacketsDecision::ForwardAndHold => Self::ForwardAndHold,
+            BufferedPacketsDecision::Hold => Self::Hold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TraceEventKind {
+    /// A batch of verified packets received by a banking thread, along with the slot of the
+    /// working bank it was received during, if any
+    PacketBatch {
+        slot: Option<Slot>,
+        packets: Vec<TracedPacket>,
+    },
+    /// What a banking thread decided to do with its buffered packets
+    Decision {
+        decision: TracedDecision,
+        num_buffered_packets: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    /// Microseconds since the UNIX epoch
+    pub timestamp_us: u64,
+    pub banking_thread_id: u32,
+    pub kind: TraceEventKind,
+}
+
+#[derive(Debug, Default)]
+pub struct BankingTracer {
+    sender: Option<Sender<TraceEvent>>,
+}
+
+impl BankingTracer {
+    pub fn new_disabled() -> Self {
+        Self::default()
+    }
+
+    /// Start writing traces to `trace_dir`, keeping at most about `dir_byte_limit` bytes of
+    /// them.  The writer thread exits once every clone of the returned tracer is dropped
+    pub fn new(trace_dir: PathBuf, dir_byte_limit: u64) -> io::Result<(Self, JoinHandle<()>)> {
+        fs::create_dir_all(&trace_dir)?;
+        let (sender, receiver) = bounded(TRACE_CHANNEL_CAPACITY);
+        let mut writer = TraceWriter::new(trace_dir, dir_byte_limit, TRACE_FILE_BYTE_LIMIT)?;
+        let thread_hdl = Builder::new()
+            .name("solana-banking-trace".to_string())
+            .spawn(move || {
+                if let Err(err) = writer.write_events(&receiver) {
+                    error!("banking trace writer failed: {}", err);
+                }
+            })?;
+        Ok((
+            Self {
+                sender: Some(sender),
+            },
+            thread_hdl,
+        ))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    pub fn trace_packet_batch(&self, banking_thread_id: u32, slot: Option<Slot>, msgs: &Packets) {
+        if self.is_enabled() {
+            let packets = msgs.packets.iter().map(TracedPacket::from).collect();
+            self.trace(
+                banking_thread_id,
+                TraceEventKind::PacketBatch { slot, packets },
+            );
+        }
+    }
+
+    pub fn trace_decision(
+        &self,
+        banking_thread_id: u32,
+        decision: &BufferedPacketsDecision,
+        num_buffered_packets: usize,
+    ) {
+        self.trace(
+            banking_thread_id,
+            TraceEventKind::Decision {
+                decision: decision.into(),
+                num_buffered_packets,
+            },
+        );
+    }
+
+    fn trace(&self, banking_thread_id: u32, kind: TraceEventKind) {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return,
+        };
+        let timestamp_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as u64)
+            .unwrap_or_default();
+        let event = TraceEvent {
+            timestamp_us,
+            banking_thread_id,
+            kind,
+        };
+        if let Err(TrySendError::Full(_)) = sender.try_send(event) {
+            inc_new_counter_info!("banking_trace-dropped_events", 1);
+        }
+    }
+}
+
+struct TraceWriter {
+    trace_dir: PathBuf,
+    dir_byte_limit: u64,
+    file_byte_limit: u64,
+    /// Index and size of the trace files, oldest first, the last one being written to
+    files: Vec<(u64, u64)>,
+    writer: BufWriter<File>,
+}
+
+impl TraceWriter {
+    fn new(trace_dir: PathBuf, dir_byte_limit: u64, file_byte_limit: u64) -> io::Result<Self> {
+        let mut files: Vec<_> = trace_file_indexes(&trace_dir)?
+            .into_iter()
+            .map(|index| {
+                let len = fs::metadata(trace_file_path(&trace_dir, index))?.len();
+                Ok((index, len))
+            })
+            .collect::<io::Result<_>>()?;
+        // Never append to the traces of a previous run
+        let index = files.last().map(|(index, _)| index + 1).unwrap_or_default();
+        files.push((index, 0));
+        let writer = BufWriter::new(File::create(trace_file_path(&trace_dir, index))?);
+        Ok(Self {
+            trace_dir,
+            dir_byte_limit,
+            file_byte_limit,
+            files,
+            writer,
+        })
+    }
+
+    fn write_events(&mut self, receiver: &Receiver<TraceEvent>) -> io::Result<()> {
+        for event in receiver.iter() {
+            self.write_event(&event)?;
+            if receiver.is_empty() {
+                self.writer.flush()?;
+            }
+        }
+        self.writer.flush()
+    }
+
+    fn write_event(&mut self, event: &TraceEvent) -> io::Result<()> {
+        let bytes =
+            bincode::serialize(event).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        self.writer.write_all(&bytes)?;
+        let (index, len) = self.files.last_mut().unwrap();
+        *len += bytes.len() as u64;
+        if *len >= self.file_byte_limit {
+            let index = *index + 1;
+            self.writer.flush()?;
+            self.writer = BufWriter::new(File::create(trace_file_path(&self.trace_dir, index))?);
+            self.files.push((index, 0));
+            self.remove_oldest_files()?;
+        }
+        Ok(())
+    }
+
+    fn remove_oldest_files(&mut self) -> io::Result<()> {
+        let mut total_len: u64 = self.files.iter().map(|(_, len)| len).sum();
+        while total_len > self.dir_byte_limit && self.files.len() > 1 {
+            let (index, len) = self.files.remove(0);
+            fs::remove_file(trace_file_path(&self.trace_dir, index))?;
+            total_len -= len;
+        }
+        Ok(())
+    }
+}
+
+fn trace_file_path(trace_dir: &Path, index: u64) -> PathBuf {
+    trace_dir.join(format!("{}{}", TRACE_FILE_PREFIX, index))
+}
+
+// The indexes of the trace files in `trace_dir`, oldest first
+fn trace_file_indexes(trace_dir: &Path) -> io::Result<Vec<u64>> {
+    let mut indexes = vec![];
+    for entry in fs::read_dir(trace_dir)? {
+        let file_name = entry?.file_name();
+        if let Some(index) = file_name
+            .to_str()
+            .and_then(|file_name| file_name.strip_prefix(TRACE_FILE_PREFIX))
+            .and_then(|index| index.parse().ok())
+        {
+            indexes.push(index);
+        }
+    }
+    indexes.sort_unstable();
+    Ok(indexes)
+}
+
+/// Iterates over the events of the trace files in a directory, oldest first
+pub struct BankingTraceReader {
+    trace_dir: PathBuf,
+    indexes: std::vec::IntoIter<u64>,
+    reader: Option<BufReader<File>>,
+}
+
+impl BankingTraceReader {
+    pub fn new(trace_dir: &Path) -> io::Result<Self> {
+        Ok(Self {
+            trace_dir: trace_dir.to_path_buf(),
+            indexes: trace_file_indexes(trace_dir)?.into_iter(),
+            reader: None,
+        })
+    }
+}
+
+impl Iterator for BankingTraceReader {
+    type Item = io::Result<TraceEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(reader) = self.reader.as_mut() {
+                match bincode::deserialize_from(reader) {
+                    Ok(event) => return Some(Ok(event)),
+                    // The end of the file, or an event cut short by a crash
+                    Err(err) if matches!(*err, bincode::ErrorKind::Io(_)) => self.reader = None,
+                    Err(err) => {
+                        self.reader = None;
+                        return Some(Err(io::Error::new(io::ErrorKind::InvalidData, err)));
+                    }
+                }
+            }
+            let index = self.indexes.next()?;
+            match File::open(trace_file_path(&self.trace_dir, index)) {
+                Ok(file) => self.reader = Some(BufReader::new(file)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    fn packet_batch(num_packets: usize) -> Packets {
+        let packets = (0..num_packets)
+            .map(|i| {
+                let mut packet = Packet::default();
+                packet.data[..4].copy_from_slice(&(i as u32).to_le_bytes());
+                packet.meta.size = 4;
+                packet.meta.discard = i % 2 == 1;
+                packet
+            })
+            .collect();
+        Packets::new(packets)
+    }
+
+    #[test]
+    fn test_banking_trace_roundtrip() {
+        let trace_dir = TempDir::new().unwrap();
+        let (tracer, thread_hdl) =
+            BankingTracer::new(trace_dir.path().to_path_buf(), u64::MAX).unwrap();
+        let msgs = packet_batch(3);
+        tracer.trace_packet_batch(2, Some(5), &msgs);
+        tracer.trace_decision(2, &BufferedPacketsDecision::Consume(400), 7);
+        drop(tracer);
+        thread_hdl.join().unwrap();
+
+        let events: Vec<_> = BankingTraceReader::new(trace_dir.path())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].banking_thread_id, 2);
+        match &events[0].kind {
+            TraceEventKind::PacketBatch { slot, packets } => {
+                assert_eq!(*slot, Some(5));
+                assert_eq!(packets.len(), 3);
+                for (packet, traced_packet) in msgs.packets.iter().zip(packets) {
+                    let replayed_packet = traced_packet.to_packet();
+                    assert_eq!(replayed_packet.meta.size, packet.meta.size);
+                    assert_eq!(replayed_packet.meta.discard, packet.meta.discard);
+                    assert_eq!(replayed_packet.data[..4], packet.data[..4]);
+                }
+            }
+            kind => panic!("unexpected event {:?}", kind),
+        }
+        assert_eq!(
+            events[1].kind,
+            TraceEventKind::Decision {
+                decision: TracedDecision::Consume,
+                num_buffered_packets: 7,
+            }
+        );
+        assert!(events[0].timestamp_us <= events[1].timestamp_us);
+
+        // A disabled tracer records nothing
+        let tracer = BankingTracer::new_disabled();
+        assert!(!tracer.is_enabled());
+        tracer.trace_packet_batch(2, None, &msgs);
+    }
+
+    #[test]
+    fn test_banking_trace_rotation() {
+        let trace_dir = TempDir::new().unwrap();
+        let event = TraceEvent {
+            timestamp_us: 0,
+            banking_thread_id: 2,
+            kind: TraceEventKind::PacketBatch {
+                slot: None,
+                packets: vec![TracedPacket::from(&packet_batch(1).packets[0])],
+            },
+        };
+        let event_len = bincode::serialized_size(&event).unwrap();
+        // Ten events per file, at most 25 events in the directory
+        let mut writer = TraceWriter::new(
+            trace_dir.path().to_path_buf(),
+            25 * event_len,
+            10 * event_len,
+        )
+        .unwrap();
+        for _ in 0..50 {
+            writer.write_event(&event).unwrap();
+        }
+        writer.writer.flush().unwrap();
+
+        // The oldest files are removed
+        assert_eq!(trace_file_indexes(trace_dir.path()).unwrap(), vec![3, 4, 5]);
+        let events: Vec<_> = BankingTraceReader::new(trace_dir.path())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(events, vec![event; 20]);
+
+        // Traces of a later run start in a new file
+        drop(writer);
+        let writer = TraceWriter::new(trace_dir.path().to_path_buf(), u64::MAX, u64::MAX).unwrap();
+        assert_eq!(writer.files.last().unwrap().0, 6);
+    }
+}
@@ -427,12 +427,15 @@ pub fn broadcast_shreds(
             update_peer_stats(&cluster_nodes, last_datapoint_submit);
             let root_bank = root_bank.clone();
             shreds.flat_map(move |shred| {
-                repeat(&shred.payload).zip(cluster_nodes.get_broadcast_addrs(
+                let addrs = cluster_nodes.get_broadcast_addrs(
                     shred,
                     &root_bank,
                     DATA_PLANE_FANOUT,
                     socket_addr_space,
-                ))
+                );
+                #[cfg(feature = "fault-injection")]
+                let addrs = crate::fault_injection::filter_shred_addrs(cluster_info, shred, addrs);
+                repeat(&shred.payload).zip(addrs)
             })
         })
         .collect();
@@ -3,7 +3,7 @@ use solana_entry::entry::Entry;
 use solana_ledger::shred::Shred;
 use solana_poh::poh_recorder::WorkingBankEntry;
 use solana_runtime::bank::Bank;
-use solana_sdk::clock::Slot;
+use solana_sdk::{clock::Slot, hash::Hash};
 use std::{
     sync::mpsc::Receiver,
     sync::Arc,
@@ -26,6 +26,8 @@ pub struct UnfinishedSlotInfo {
     // MAX_DATA_SHREDS_PER_FEC_BLOCK.
     pub(crate) data_shreds_buffer: Vec<Shred>,
     pub(crate) fec_set_offset: u32, // See Shredder::fec_set_index.
+    // Merkle root of the last FEC set, if the slot is shredded into merkle shreds.
+    pub(crate) chained_merkle_root: Option<Hash>,
 }
 
 /// This parameter tunes how many entries are received in one iteration of recv loop
@@ -10,10 +10,11 @@ use {
     },
     solana_entry::entry::Entry,
     solana_ledger::shred::{
-        ProcessShredsStats, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK,
+        merkle_shreds_enabled, ProcessShredsStats, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK,
         SHRED_TICK_REFERENCE_MASK,
     },
     solana_sdk::{
+        hash::Hash,
         signature::Keypair,
         timing::{duration_as_us, AtomicInterval},
     },
@@ -70,6 +71,25 @@ impl StandardBroadcastRun {
             Some(ref mut state) => {
                 let parent_offset = state.slot - state.parent;
                 let reference_tick = max_ticks_in_slot & SHRED_TICK_REFERENCE_MASK;
+                if let Some(chained_merkle_root) = state.chained_merkle_root.as_mut() {
+                    // There are no data shreds buffered, the last FEC set is only the shred
+                    // indicating the last shred in the slot, and its coding shreds
+                    let (mut shreds, coding_shreds, _) =
+                        Shredder::new(state.slot, state.parent, reference_tick, self.shred_version)
+                            .unwrap()
+                            .entries_to_merkle_shreds(
+                                keypair,
+                                &[],  // entries
+                                true, // is_last_in_slot
+                                state.next_shred_index,
+                                chained_merkle_root,
+                                stats,
+                            );
+                    shreds.extend(coding_shreds);
+                    self.report_and_reset_stats(true);
+                    self.unfinished_slot = None;
+                    return shreds;
+                }
                 let fec_set_index =
                     Shredder::fec_set_index(state.next_shred_index, state.fec_set_offset);
                 let mut shred = Shred::new_from_data(
@@ -106,8 +126,9 @@ impl StandardBroadcastRun {
         blockstore: &Blockstore,
         reference_tick: u8,
         is_slot_end: bool,
+        merkle_variant: bool,
         process_stats: &mut ProcessShredsStats,
-    ) -> Vec<Shred> {
+    ) -> (Vec<Shred>, Vec<Shred>) {
         let (slot, parent_slot) = self.current_slot_and_parent.unwrap();
         let (next_shred_index, fec_set_offset) = match &self.unfinished_slot {
             Some(state) => (state.next_shred_index, state.fec_set_offset),
@@ -119,10 +140,25 @@ impl StandardBroadcastRun {
                 None => (0, 0),
             },
         };
-        let (data_shreds, next_shred_index) =
-            Shredder::new(slot, parent_slot, reference_tick, self.shred_version)
-                .unwrap()
-                .entries_to_data_shreds(
+        let mut chained_merkle_root = match &self.unfinished_slot {
+            Some(state) => state.chained_merkle_root,
+            // The merkle shreds of a slot chain FEC sets starting from the default hash
+            None => merkle_variant.then(Hash::default),
+        };
+        let shredder =
+            Shredder::new(slot, parent_slot, reference_tick, self.shred_version).unwrap();
+        // Merkle shreds come with the coding shreds of their FEC sets, which are all complete
+        let (data_shreds, coding_shreds, next_shred_index) = match chained_merkle_root.as_mut() {
+            Some(chained_merkle_root) => shredder.entries_to_merkle_shreds(
+                keypair,
+                entries,
+                is_slot_end,
+                next_shred_index,
+                chained_merkle_root,
+                process_stats,
+            ),
+            None => {
+                let (data_shreds, next_shred_index) = shredder.entries_to_data_shreds(
                     keypair,
                     entries,
                     is_slot_end,
@@ -130,6 +166,9 @@ impl StandardBroadcastRun {
                     fec_set_offset,
                     process_stats,
                 );
+                (data_shreds, Vec::default(), next_shred_index)
+            }
+        };
         let mut data_shreds_buffer = match &mut self.unfinished_slot {
             Some(state) => {
                 assert_eq!(state.slot, slot);
@@ -137,15 +176,18 @@ impl StandardBroadcastRun {
             }
             None => Vec::default(),
         };
-        data_shreds_buffer.extend(data_shreds.clone());
+        if chained_merkle_root.is_none() {
+            data_shreds_buffer.extend(data_shreds.clone());
+        }
         self.unfinished_slot = Some(UnfinishedSlotInfo {
             next_shred_index,
             slot,
             parent: parent_slot,
             data_shreds_buffer,
             fec_set_offset,
+            chained_merkle_root,
         });
-        data_shreds
+        (data_shreds, coding_shreds)
     }
 
     #[cfg(test)]
@@ -211,12 +253,13 @@ impl StandardBroadcastRun {
         // 2) Convert entries to shreds and coding shreds
         let is_last_in_slot = last_tick_height == bank.max_tick_height();
         let reference_tick = bank.tick_height() % bank.ticks_per_slot();
-        let data_shreds = self.entries_to_data_shreds(
+        let (data_shreds, merkle_coding_shreds) = self.entries_to_data_shreds(
             keypair,
             &receive_results.entries,
             blockstore,
             reference_tick as u8,
             is_last_in_slot,
+            merkle_shreds_enabled(bank.slot(), &bank),
             &mut process_stats,
         );
         // Insert the first shred so blockstore stores that the leader started this block
@@ -276,12 +319,13 @@ impl StandardBroadcastRun {
         blockstore_sender.send((data_shreds, batch_info.clone()))?;
 
         // Create and send coding shreds
-        let coding_shreds = make_coding_shreds(
+        let mut coding_shreds = make_coding_shreds(
             keypair,
             &mut self.unfinished_slot,
             is_last_in_slot,
             &mut process_stats,
         );
+        coding_shreds.extend(merkle_coding_shreds);
         let coding_shreds = Arc::new(coding_shreds);
         debug_assert!(coding_shreds
             .iter()
@@ -583,6 +627,7 @@ mod test {
             parent,
             data_shreds_buffer: Vec::default(),
             fec_set_offset: next_shred_index,
+            chained_merkle_root: None,
         });
         run.slot_broadcast_start = Some(Instant::now());
 
@@ -1,4 +1,5 @@
 use crate::{
+    conflicting_vote_detector::ConflictingVoteDetector,
     optimistic_confirmation_verifier::OptimisticConfirmationVerifier,
     replay_stage::DUPLICATE_THRESHOLD,
     result::{Error, Result},
@@ -442,6 +443,8 @@ impl ClusterInfoVoteListener {
     ) -> Result<()> {
         let mut confirmation_verifier =
             OptimisticConfirmationVerifier::new(bank_forks.read().unwrap().root());
+        let mut conflicting_vote_detector =
+            ConflictingVoteDetector::new(blockstore.clone(), bank_forks.read().unwrap().root());
         let mut last_process_root = Instant::now();
         let cluster_confirmed_slot_sender = Some(cluster_confirmed_slot_sender);
         loop {
@@ -462,6 +465,7 @@ impl ClusterInfoVoteListener {
                     &unrooted_optimistic_slots,
                 );
                 vote_tracker.progress_with_new_root_bank(&root_bank);
+                conflicting_vote_detector.set_root(root_bank.slot());
                 last_process_root = Instant::now();
             }
             let confirmed_slots = Self::listen_and_confirm_votes(
@@ -474,6 +478,7 @@ impl ClusterInfoVoteListener {
                 &replay_votes_receiver,
                 &bank_notification_sender,
                 &cluster_confirmed_slot_sender,
+                Some(&mut conflicting_vote_detector),
             );
             match confirmed_slots {
                 Ok(confirmed_slots) => {
@@ -511,6 +516,7 @@ impl ClusterInfoVoteListener {
             replay_votes_receiver,
             &None,
             &None,
+            None,
         )
     }
 
@@ -524,6 +530,7 @@ impl ClusterInfoVoteListener {
         replay_votes_receiver: &ReplayVoteReceiver,
         bank_notification_sender: &Option<BankNotificationSender>,
         cluster_confirmed_slot_sender: &Option<GossipDuplicateConfirmedSlotsSender>,
+        conflicting_vote_detector: Option<&mut ConflictingVoteDetector>,
     ) -> Result<ThresholdConfirmedSlots> {
         let mut sel = Select::new();
         sel.recv(gossip_vote_txs_receiver);
@@ -555,6 +562,7 @@ impl ClusterInfoVoteListener {
                     verified_vote_sender,
                     bank_notification_sender,
                     cluster_confirmed_slot_sender,
+                    conflicting_vote_detector,
                 ));
             } else {
                 remaining_wait_time = remaining_wait_time
@@ -721,22 +729,32 @@ impl ClusterInfoVoteListener {
         verified_vote_sender: &VerifiedVoteSender,
         bank_notification_sender: &Option<BankNotificationSender>,
         cluster_confirmed_slot_sender: &Option<GossipDuplicateConfirmedSlotsSender>,
+        mut conflicting_vote_detector: Option<&mut ConflictingVoteDetector>,
     ) -> ThresholdConfirmedSlots {
         let mut diff: HashMap<Slot, HashMap<Pubkey, bool>> = HashMap::new();
         let mut new_optimistic_confirmed_slots = vec![];
 
         // Process votes from gossip and ReplayStage
-        for (is_gossip, (vote_pubkey, vote, _)) in gossip_vote_txs
+        for (is_gossip, vote_pubkey, vote, vote_tx) in gossip_vote_txs
             .iter()
             .filter_map(|gossip_tx| {
                 vote_transaction::parse_vote_transaction(gossip_tx)
                     .filter(|(vote_pubkey, vote, _)| {
                         Self::filter_gossip_votes(vote_tracker, vote_pubkey, vote, gossip_tx)
                     })
-                    .map(|v| (true, v))
+                    .map(|(vote_pubkey, vote, _)| (true, vote_pubkey, vote, gossip_tx))
             })
-            .chain(replayed_votes.into_iter().map(|v| (false, v)))
+            .chain(
+                replayed_votes
+                    .iter()
+                    .map(|(vote_pubkey, vote, _, vote_tx)| {
+                        (false, *vote_pubkey, vote.clone(), vote_tx)
+                    }),
+            )
         {
+            if let Some(detector) = conflicting_vote_detector.as_mut() {
+                detector.check_vote(&vote_pubkey, &vote, vote_tx);
+            }
             Self::track_new_votes_and_notify_confirmations(
                 vote,
                 &vote_pubkey,
@@ -1065,6 +1083,7 @@ mod tests {
             &replay_votes_receiver,
             &None,
             &None,
+            None,
         )
         .unwrap();
 
@@ -1096,6 +1115,7 @@ mod tests {
             &replay_votes_receiver,
             &None,
             &None,
+            None,
         )
         .unwrap();
 
@@ -1132,6 +1152,7 @@ mod tests {
                         vote_keypair.pubkey(),
                         replay_vote.clone(),
                         switch_proof_hash,
+                        Transaction::default(),
                     ))
                     .unwrap();
             }
@@ -1177,6 +1198,7 @@ mod tests {
             &replay_votes_receiver,
             &None,
             &None,
+            None,
         )
         .unwrap();
 
@@ -1334,6 +1356,7 @@ mod tests {
             &replay_votes_receiver,
             &None,
             &None,
+            None,
         )
         .unwrap();
 
@@ -1419,6 +1442,7 @@ mod tests {
                             vote_keypair.pubkey(),
                             Vote::new(vec![vote_slot], Hash::default()),
                             switch_proof_hash,
+                            Transaction::default(),
                         ))
                         .unwrap();
                 }
@@ -1432,6 +1456,7 @@ mod tests {
                     &replay_votes_receiver,
                     &None,
                     &None,
+                    None,
                 );
             }
             let slot_vote_tracker = vote_tracker.get_slot_vote_tracker(vote_slot).unwrap();
@@ -1572,6 +1597,7 @@ mod tests {
             &verified_vote_sender,
             &None,
             &None,
+            None,
         );
 
         // Setup next epoch
@@ -1628,6 +1654,7 @@ mod tests {
             &verified_vote_sender,
             &None,
             &None,
+            None,
         );
     }
 
@@ -0,0 +1,229 @@
+//! Compares the software versions staked nodes advertise in gossip against this node's, and
+//! warns the operator when a large share of the stake runs a newer release with a different
+//! feature set.  Such a release may support features this build lacks, which forks this node
+//! off the cluster once they are activated
+use {
+    solana_gossip::cluster_info::ClusterInfo,
+    solana_runtime::bank_forks::BankForks,
+    solana_sdk::pubkey::Pubkey,
+    solana_version::Version,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+const SLEEP_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Warn once the stake on newer releases with a different feature set reaches this percentage
+pub const DEFAULT_NEWER_FEATURE_SET_WARN_STAKE_PERCENT: u8 = 33;
+
+#[derive(Debug, Default, PartialEq)]
+struct ClusterVersionStats {
+    total_stake: u64,
+    /// Stake on nodes advertising the same feature set as this node
+    my_feature_set_stake: u64,
+    /// Stake on nodes running a newer release than this node, with a different feature set
+    newer_feature_set_stake: u64,
+    /// Stake on nodes that don't advertise a version
+    unknown_version_stake: u64,
+    num_feature_sets: usize,
+    /// The newest release advertised with a different feature set
+    newest_version: Option<Version>,
+}
+
+impl ClusterVersionStats {
+    fn new<F>(my_version: &Version, staked_nodes: &HashMap<Pubkey, u64>, get_version: F) -> Self
+    where
+        F: Fn(&Pubkey) -> Option<Version>,
+    {
+        let release = |version: &Version| (version.major, version.minor, version.patch);
+        let mut stats = Self::default();
+        let mut feature_sets = HashSet::new();
+        for (pubkey, stake) in staked_nodes.iter().filter(|(_, stake)| **stake > 0) {
+            stats.total_stake += stake;
+            let version = match get_version(pubkey) {
+                Some(version) => version,
+                None => {
+                    stats.unknown_version_stake += stake;
+                    continue;
+                }
+            };
+            feature_sets.insert(version.feature_set);
+            if version.feature_set == my_version.feature_set {
+                stats.my_feature_set_stake += stake;
+            } else if release(&version) > release(my_version) {
+                stats.newer_feature_set_stake += stake;
+                let is_newest = stats
+                    .newest_version
+                    .as_ref()
+                    .map(|newest_version| release(&version) > release(newest_version))
+                    .unwrap_or(true);
+                if is_newest {
+                    stats.newest_version = Some(version);
+                }
+            }
+        }
+        stats.num_feature_sets = feature_sets.len();
+        stats
+    }
+
+    fn stake_percent(&self, stake: u64) -> f64 {
+        if self.total_stake == 0 {
+            0.0
+        } else {
+            stake as f64 * 100.0 / self.total_stake as f64
+        }
+    }
+}
+
+pub struct ClusterVersionMonitorService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl ClusterVersionMonitorService {
+    pub fn new(
+        cluster_info: &Arc<ClusterInfo>,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        warn_stake_percent: u8,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        let cluster_info = cluster_info.clone();
+        let bank_forks = bank_forks.clone();
+        let exit = exit.clone();
+
+        info!("Starting ClusterVersionMonitor service");
+        let thread_hdl = Builder::new()
+            .name("cluster-version-monitor".to_string())
+            .spawn(move || {
+                Self::run(&cluster_info, &bank_forks, warn_stake_percent, &exit);
+            })
+            .unwrap();
+
+        Self { thread_hdl }
+    }
+
+    fn run(
+        cluster_info: &ClusterInfo,
+        bank_forks: &RwLock<BankForks>,
+        warn_stake_percent: u8,
+        exit: &AtomicBool,
+    ) {
+        let my_pubkey = cluster_info.id();
+        let my_version = Version::default();
+        let mut last_sample = Instant::now();
+        while !exit.load(Ordering::Relaxed) {
+            if last_sample.elapsed() < SAMPLE_INTERVAL {
+                sleep(SLEEP_INTERVAL);
+                continue;
+            }
+            last_sample = Instant::now();
+
+            let staked_nodes = bank_forks.read().unwrap().root_bank().staked_nodes();
+            let stats = ClusterVersionStats::new(&my_version, &staked_nodes, |pubkey| {
+                if *pubkey == my_pubkey {
+                    Some(my_version.clone())
+                } else {
+                    cluster_info.get_node_version(pubkey)
+                }
+            });
+            Self::report(&my_version, &stats, warn_stake_percent);
+        }
+    }
+
+    fn report(my_version: &Version, stats: &ClusterVersionStats, warn_stake_percent: u8) {
+        let newer_feature_set_stake_percent = stats.stake_percent(stats.newer_feature_set_stake);
+        datapoint_info!(
+            "cluster_version_monitor",
+            (
+                "my_feature_set_stake_percent",
+                stats.stake_percent(stats.my_feature_set_stake),
+                f64
+            ),
+            (
+                "newer_feature_set_stake_percent",
+                newer_feature_set_stake_percent,
+                f64
+            ),
+            (
+                "unknown_version_stake_percent",
+                stats.stake_percent(stats.unknown_version_stake),
+                f64
+            ),
+            ("num_feature_sets", stats.num_feature_sets, i64),
+        );
+        if newer_feature_set_stake_percent >= f64::from(warn_stake_percent) {
+            warn!(
+                "{:.1}% of the stake runs a newer release than this node ({:?}), up to {:?}, \
+                with a different feature set. Upgrade before the features this release \
+                lacks are activated",
+                newer_feature_set_stake_percent,
+                my_version,
+                stats.newest_version.as_ref().unwrap(),
+            );
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(minor: u16, feature_set: u32) -> Version {
+        Version {
+            major: 1,
+            minor,
+            patch: 0,
+            commit: None,
+            feature_set,
+        }
+    }
+
+    #[test]
+    fn test_cluster_version_stats() {
+        let my_version = version(9, 1);
+        let nodes: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let staked_nodes: HashMap<_, _> = nodes
+            .iter()
+            .zip([10, 20, 30, 40, 0].iter())
+            .map(|(pubkey, stake)| (*pubkey, *stake))
+            .collect();
+        let versions: HashMap<_, _> = vec![
+            (nodes[0], version(9, 1)),
+            (nodes[1], version(8, 2)),
+            (nodes[2], version(10, 3)),
+            (nodes[4], version(11, 4)),
+        ]
+        .into_iter()
+        .collect();
+
+        let stats = ClusterVersionStats::new(&my_version, &staked_nodes, |pubkey| {
+            versions.get(pubkey).cloned()
+        });
+        assert_eq!(
+            stats,
+            ClusterVersionStats {
+                total_stake: 100,
+                my_feature_set_stake: 10,
+                // The older release with a different feature set can't have features this
+                // node lacks, and the node without stake is ignored
+                newer_feature_set_stake: 30,
+                unknown_version_stake: 40,
+                num_feature_sets: 3,
+                newest_version: Some(version(10, 3)),
+            }
+        );
+        assert!((stats.stake_percent(stats.newer_feature_set_stake) - 30.0).abs() < f64::EPSILON);
+        assert!(ClusterVersionStats::default().stake_percent(0).abs() < f64::EPSILON);
+    }
+}
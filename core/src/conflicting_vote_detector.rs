@@ -0,0 +1,166 @@
+//! Detects vote accounts voting for more than one version of the same slot, and records the
+//! conflicting vote transactions as equivocation evidence in the blockstore
+use solana_ledger::{blockstore::Blockstore, blockstore_meta::EquivocationEvidence};
+use solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey, transaction::Transaction};
+use solana_vote_program::vote_state::Vote;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+// Votes are tracked for the slots up to this far past the root, which bounds the votes held
+// while the root is stuck
+const MAX_VOTE_SLOTS_AHEAD_OF_ROOT: Slot = 500;
+
+pub struct ConflictingVoteDetector {
+    blockstore: Arc<Blockstore>,
+    root: Slot,
+    // The first vote seen from each vote account, keyed by the last slot of the vote
+    votes: BTreeMap<Slot, HashMap<Pubkey, (Hash, Transaction)>>,
+}
+
+impl ConflictingVoteDetector {
+    pub fn new(blockstore: Arc<Blockstore>, root: Slot) -> Self {
+        Self {
+            blockstore,
+            root,
+            votes: BTreeMap::default(),
+        }
+    }
+
+    // Checks a vote transaction signed by the authorized voter of `vote_pubkey` against the
+    // earlier votes of that account. Returns true if the vote conflicts with an earlier vote
+    pub fn check_vote(
+        &mut self,
+        vote_pubkey: &Pubkey,
+        vote: &Vote,
+        vote_transaction: &Transaction,
+    ) -> bool {
+        let last_vote_slot = match vote.slots.last() {
+            Some(last_vote_slot)
+                if *last_vote_slot > self.root
+                    && *last_vote_slot
+                        <= self.root.saturating_add(MAX_VOTE_SLOTS_AHEAD_OF_ROOT) =>
+            {
+                *last_vote_slot
+            }
+            _ => return false,
+        };
+        let (first_hash, first_transaction) = self
+            .votes
+            .entry(last_vote_slot)
+            .or_default()
+            .entry(*vote_pubkey)
+            .or_insert_with(|| (vote.hash, vote_transaction.clone()));
+        if *first_hash == vote.hash {
+            return false;
+        }
+
+        let evidence = EquivocationEvidence::ConflictingVotes {
+            vote_transaction1: first_transaction.clone(),
+            vote_transaction2: vote_transaction.clone(),
+        };
+        match self
+            .blockstore
+            .store_equivocation_evidence(last_vote_slot, vote_pubkey, &evidence)
+        {
+            Ok(true) => {
+                datapoint_warn!(
+                    "conflicting_vote_detected",
+                    ("slot", last_vote_slot, i64),
+                    ("vote_pubkey", vote_pubkey.to_string(), String),
+                );
+            }
+            Ok(false) => (),
+            Err(err) => warn!("failed to store conflicting vote evidence: {:?}", err),
+        }
+        true
+    }
+
+    // Forget the votes for slots at or below the new root
+    pub fn set_root(&mut self, root: Slot) {
+        if root > self.root {
+            self.root = root;
+            self.votes = self.votes.split_off(&(root + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_ledger::get_tmp_ledger_path_auto_delete;
+    use solana_sdk::signature::{Keypair, Signer};
+    use solana_vote_program::vote_transaction;
+
+    fn new_vote_transaction(
+        slots: Vec<Slot>,
+        bank_hash: Hash,
+        vote_keypair: &Keypair,
+    ) -> (Vote, Transaction) {
+        let vote = Vote::new(slots.clone(), bank_hash);
+        let node_keypair = Keypair::new();
+        let vote_transaction = vote_transaction::new_vote_transaction(
+            slots,
+            bank_hash,
+            Hash::default(),
+            &node_keypair,
+            vote_keypair,
+            vote_keypair,
+            None,
+        );
+        (vote, vote_transaction)
+    }
+
+    #[test]
+    fn test_check_vote() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let mut detector = ConflictingVoteDetector::new(blockstore.clone(), 0);
+
+        let vote_keypair = Keypair::new();
+        let vote_pubkey = vote_keypair.pubkey();
+        let hash1 = Hash::new_unique();
+        let hash2 = Hash::new_unique();
+        let (vote1, vote_transaction1) = new_vote_transaction(vec![1, 2], hash1, &vote_keypair);
+        let (vote2, vote_transaction2) = new_vote_transaction(vec![2], hash2, &vote_keypair);
+        let (vote3, vote_transaction3) = new_vote_transaction(vec![2, 3], hash2, &vote_keypair);
+
+        assert!(!detector.check_vote(&vote_pubkey, &vote1, &vote_transaction1));
+        // Seeing the same vote again is not an equivocation
+        assert!(!detector.check_vote(&vote_pubkey, &vote1, &vote_transaction1));
+        assert!(!detector.check_vote(&vote_pubkey, &vote3, &vote_transaction3));
+        assert_eq!(
+            blockstore
+                .get_equivocation_evidence(2, &vote_pubkey)
+                .unwrap(),
+            None
+        );
+
+        // A vote for slot 2 on a different hash conflicts with the first vote
+        assert!(detector.check_vote(&vote_pubkey, &vote2, &vote_transaction2));
+        assert_eq!(
+            blockstore
+                .get_equivocation_evidence(2, &vote_pubkey)
+                .unwrap(),
+            Some(EquivocationEvidence::ConflictingVotes {
+                vote_transaction1,
+                vote_transaction2,
+            })
+        );
+
+        // Votes at or below the root are ignored
+        detector.set_root(2);
+        assert!(detector.votes.keys().all(|slot| *slot > 2));
+        let (vote4, vote_transaction4) =
+            new_vote_transaction(vec![2], Hash::new_unique(), &vote_keypair);
+        assert!(!detector.check_vote(&vote_pubkey, &vote4, &vote_transaction4));
+
+        // So are votes too far past the root
+        let far_slot = 2 + MAX_VOTE_SLOTS_AHEAD_OF_ROOT + 1;
+        let (vote5, vote_transaction5) =
+            new_vote_transaction(vec![far_slot], Hash::new_unique(), &vote_keypair);
+        assert!(!detector.check_vote(&vote_pubkey, &vote5, &vote_transaction5));
+        assert!(!detector.votes.contains_key(&far_slot));
+    }
+}
@@ -25,7 +25,7 @@ use {
     },
     std::{
         cmp::Ordering,
-        collections::{HashMap, HashSet},
+        collections::{HashMap, HashSet, VecDeque},
         ops::{
             Bound::{Included, Unbounded},
             Deref,
@@ -34,7 +34,7 @@ use {
     thiserror::Error,
 };
 
-#[derive(PartialEq, Clone, Debug, AbiExample)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, AbiExample)]
 pub enum SwitchForkDecision {
     SwitchProof(Hash),
     SameFork,
@@ -84,6 +84,41 @@ impl SwitchForkDecision {
 pub const VOTE_THRESHOLD_DEPTH: usize = 8;
 pub const SWITCH_FORK_THRESHOLD: f64 = 0.38;
 
+/// The stake thresholds a tower checks before voting.  Only test clusters should change these
+/// from their defaults, every validator in a cluster must agree on them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TowerThresholds {
+    /// The depth in the tower of the vote that must be confirmed by `vote_threshold_size` of the
+    /// stake before voting again
+    pub vote_threshold_depth: usize,
+    pub vote_threshold_size: f64,
+    /// The fraction of stake that must be locked out on other forks to switch to one of them
+    pub switch_fork_threshold: f64,
+}
+
+impl Default for TowerThresholds {
+    fn default() -> Self {
+        Self {
+            vote_threshold_depth: VOTE_THRESHOLD_DEPTH,
+            vote_threshold_size: VOTE_THRESHOLD_SIZE,
+            switch_fork_threshold: SWITCH_FORK_THRESHOLD,
+        }
+    }
+}
+
+/// How the stake for a switching proof was gathered, reported with each new switch threshold
+/// decision
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SwitchForkStats {
+    /// The latest frozen banks of other forks whose lockouts were counted
+    pub candidate_forks: usize,
+    pub locked_out_vote_accounts: usize,
+    /// Stake locked out by votes that landed in the banks of other forks
+    pub lockout_interval_stake: u64,
+    /// Stake locked out by the latest votes for other forks seen in gossip
+    pub gossip_vote_stake: u64,
+}
+
 pub type Result<T> = std::result::Result<T, TowerError>;
 
 pub type Stake = u64;
@@ -127,6 +162,12 @@ pub struct Tower {
     stray_restored_slot: Option<Slot>,
     #[serde(skip)]
     pub last_switch_threshold_check: Option<(Slot, SwitchForkDecision)>,
+    #[serde(skip, default = "default_switch_fork_threshold")]
+    switch_fork_threshold: f64,
+}
+
+fn default_switch_fork_threshold() -> f64 {
+    SWITCH_FORK_THRESHOLD
 }
 
 impl Default for Tower {
@@ -141,6 +182,7 @@ impl Default for Tower {
             last_vote_tx_blockhash: Hash::default(),
             stray_restored_slot: Option::default(),
             last_switch_threshold_check: Option::default(),
+            switch_fork_threshold: SWITCH_FORK_THRESHOLD,
         };
         // VoteState::root_slot is ensured to be Some in Tower
         tower.vote_state.root_slot = Some(Slot::default());
@@ -163,6 +205,26 @@ impl Tower {
         tower
     }
 
+    /// Replace the thresholds, which a restored tower otherwise keeps from when it was saved
+    pub fn set_thresholds(&mut self, thresholds: TowerThresholds) {
+        self.threshold_depth = thresholds.vote_threshold_depth;
+        self.threshold_size = thresholds.vote_threshold_size;
+        self.switch_fork_threshold = thresholds.switch_fork_threshold;
+    }
+
+    pub fn thresholds(&self) -> TowerThresholds {
+        TowerThresholds {
+            vote_threshold_depth: self.threshold_depth,
+            vote_threshold_size: self.threshold_size,
+            switch_fork_threshold: self.switch_fork_threshold,
+        }
+    }
+
+    /// Whether `locked_out_stake` on other forks is enough to switch to one of them
+    pub fn exceeds_switch_fork_threshold(&self, locked_out_stake: u64, total_stake: u64) -> bool {
+        (locked_out_stake as f64 / total_stake as f64) > self.switch_fork_threshold
+    }
+
     #[cfg(test)]
     pub fn new_for_tests(threshold_depth: usize, threshold_size: f64) -> Self {
         Self {
@@ -351,6 +413,10 @@ impl Tower {
         self.vote_state.tower()
     }
 
+    pub fn tower_lockouts(&self) -> &VecDeque<Lockout> {
+        &self.vote_state.votes
+    }
+
     pub fn last_vote_tx_blockhash(&self) -> Hash {
         self.last_vote_tx_blockhash
     }
@@ -554,6 +620,7 @@ impl Tower {
         epoch_vote_accounts: &HashMap<Pubkey, (u64, VoteAccount)>,
         latest_validator_votes_for_frozen_banks: &LatestValidatorVotesForFrozenBanks,
         heaviest_subtree_fork_choice: &HeaviestSubtreeForkChoice,
+        stats: &mut SwitchForkStats,
     ) -> SwitchForkDecision {
         self.last_voted_slot_hash()
             .map(|(last_voted_slot, last_voted_hash)| {
@@ -719,6 +786,7 @@ impl Tower {
                     // should have been filtered out, as they all have a descendant,
                     // namely the `last_vote` itself.
                     assert!(!last_vote_ancestors.contains(candidate_slot));
+                    stats.candidate_forks += 1;
 
                     // Evaluate which vote accounts in the bank are locked out
                     // in the interval candidate_slot..last_vote, which means
@@ -754,7 +822,9 @@ impl Tower {
                                     .map(|(stake, _)| *stake)
                                     .unwrap_or(0);
                                 locked_out_stake += stake;
-                                if (locked_out_stake as f64 / total_stake as f64) > SWITCH_FORK_THRESHOLD {
+                                stats.lockout_interval_stake += stake;
+                                stats.locked_out_vote_accounts += 1;
+                                if self.exceeds_switch_fork_threshold(locked_out_stake, total_stake) {
                                     return SwitchForkDecision::SwitchProof(switch_proof);
                                 }
                                 locked_out_vote_accounts.insert(vote_account_pubkey);
@@ -799,7 +869,9 @@ impl Tower {
                                 .map(|(stake, _)| *stake)
                                 .unwrap_or(0);
                         locked_out_stake += stake;
-                        if (locked_out_stake as f64 / total_stake as f64) > SWITCH_FORK_THRESHOLD {
+                        stats.gossip_vote_stake += stake;
+                        stats.locked_out_vote_accounts += 1;
+                        if self.exceeds_switch_fork_threshold(locked_out_stake, total_stake) {
                             return SwitchForkDecision::SwitchProof(switch_proof);
                         }
                         locked_out_vote_accounts.insert(vote_account_pubkey);
@@ -825,6 +897,7 @@ impl Tower {
         latest_validator_votes_for_frozen_banks: &LatestValidatorVotesForFrozenBanks,
         heaviest_subtree_fork_choice: &HeaviestSubtreeForkChoice,
     ) -> SwitchForkDecision {
+        let mut stats = SwitchForkStats::default();
         let decision = self.make_check_switch_threshold_decision(
             switch_slot,
             ancestors,
@@ -834,6 +907,7 @@ impl Tower {
             epoch_vote_accounts,
             latest_validator_votes_for_frozen_banks,
             heaviest_subtree_fork_choice,
+            &mut stats,
         );
         let new_check = Some((switch_slot, decision.clone()));
         if new_check != self.last_switch_threshold_check {
@@ -842,11 +916,60 @@ impl Tower {
                 switch_slot,
                 decision,
             );
+            self.report_switch_threshold_check(switch_slot, &decision, total_stake, &stats);
             self.last_switch_threshold_check = new_check;
         }
         decision
     }
 
+    fn report_switch_threshold_check(
+        &self,
+        switch_slot: Slot,
+        decision: &SwitchForkDecision,
+        total_stake: u64,
+        stats: &SwitchForkStats,
+    ) {
+        let (decision, locked_out_stake) = match decision {
+            SwitchForkDecision::SwitchProof(_) => (
+                "switch_proof",
+                stats.lockout_interval_stake + stats.gossip_vote_stake,
+            ),
+            SwitchForkDecision::FailedSwitchThreshold(locked_out_stake, _) => {
+                ("failed_switch_threshold", *locked_out_stake)
+            }
+            // No stake was gathered
+            SwitchForkDecision::SameFork | SwitchForkDecision::FailedSwitchDuplicateRollback(_) => {
+                return
+            }
+        };
+        datapoint_info!(
+            "tower-switch-threshold",
+            ("switch_slot", switch_slot, i64),
+            ("last_voted_slot", self.last_voted_slot().unwrap_or(0), i64),
+            ("decision", decision, String),
+            ("locked_out_stake", locked_out_stake, i64),
+            ("total_stake", total_stake, i64),
+            (
+                "locked_out_stake_pct",
+                locked_out_stake as f64 * 100.0 / total_stake.max(1) as f64,
+                f64
+            ),
+            (
+                "switch_fork_threshold_pct",
+                self.switch_fork_threshold * 100.0,
+                f64
+            ),
+            ("lockout_interval_stake", stats.lockout_interval_stake, i64),
+            ("gossip_vote_stake", stats.gossip_vote_stake, i64),
+            (
+                "locked_out_vote_accounts",
+                stats.locked_out_vote_accounts,
+                i64
+            ),
+            ("candidate_forks", stats.candidate_forks, i64),
+        );
+    }
+
     fn is_first_switch_check(&self) -> bool {
         self.last_switch_threshold_check.is_none()
     }
@@ -1722,6 +1845,52 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn test_switch_threshold_tunable() {
+        let (bank0, mut vote_simulator, total_stake) = setup_switch_test(2);
+        let ancestors = vote_simulator.bank_forks.read().unwrap().ancestors();
+        let descendants = vote_simulator
+            .bank_forks
+            .read()
+            .unwrap()
+            .descendants()
+            .clone();
+        let mut tower = Tower::default();
+        let other_vote_account = vote_simulator.vote_pubkeys[1];
+        tower.record_vote(47, Hash::default());
+
+        // Half the stake is locked out on a different fork
+        vote_simulator.simulate_lockout_interval(14, (12, 47), &other_vote_account);
+        let check_switch_threshold = |tower: &mut Tower| {
+            tower.check_switch_threshold(
+                110,
+                &ancestors,
+                &descendants,
+                &vote_simulator.progress,
+                total_stake,
+                bank0.epoch_vote_accounts(0).unwrap(),
+                &vote_simulator.latest_validator_votes_for_frozen_banks,
+                &vote_simulator.heaviest_subtree_fork_choice,
+            )
+        };
+        assert_eq!(
+            check_switch_threshold(&mut tower),
+            SwitchForkDecision::SwitchProof(Hash::default())
+        );
+
+        // Which isn't enough once the threshold is raised to half the stake
+        let thresholds = TowerThresholds {
+            switch_fork_threshold: 0.5,
+            ..TowerThresholds::default()
+        };
+        tower.set_thresholds(thresholds);
+        assert_eq!(tower.thresholds(), thresholds);
+        assert_eq!(
+            check_switch_threshold(&mut tower),
+            SwitchForkDecision::FailedSwitchThreshold(10000, 20000)
+        );
+    }
+
     #[test]
     fn test_switch_threshold_use_gossip_votes() {
         let num_validators = 2;
@@ -111,6 +111,9 @@ pub struct DeadSlotAncestorRequestStatus {
     //
     // TODO: Trie may be more efficient
     ancestor_request_responses: HashMap<Vec<(Slot, Hash)>, Vec<SocketAddr>>,
+    // If this request continues the search of an earlier request whose agreed upon ancestors
+    // were all missing or mismatched, those ancestors, ending with `requested_mismatched_slot`
+    prior_ancestors: Vec<(Slot, Hash)>,
 }
 
 impl DeadSlotAncestorRequestStatus {
@@ -126,6 +129,23 @@ impl DeadSlotAncestorRequestStatus {
         }
     }
 
+    /// A request for the ancestors of the earliest of `prior_ancestors`, the ancestors agreed
+    /// upon for a descendant, to keep searching for the earliest mismatched ancestor
+    pub fn new_continued_search(
+        sampled_validators: impl Iterator<Item = SocketAddr>,
+        prior_ancestors: Vec<(Slot, Hash)>,
+    ) -> Self {
+        let requested_mismatched_slot = prior_ancestors.last().unwrap().0;
+        DeadSlotAncestorRequestStatus {
+            prior_ancestors,
+            ..Self::new(sampled_validators, requested_mismatched_slot)
+        }
+    }
+
+    pub fn prior_ancestors(&self) -> &[(Slot, Hash)] {
+        &self.prior_ancestors
+    }
+
     /// Record the response from `from_addr`. Returns Some(DuplicateAncestorDecision)
     /// if we have finalized a decision based on the responses. We can finalize a decision when
     /// one of the following conditions is met:
@@ -165,9 +185,9 @@ impl DeadSlotAncestorRequestStatus {
         {
             // When we reach MINIMUM_ANCESTOR_AGREEMENT_SIZE of the same responses,
             // check for mismatches.
-            return Some(
-                self.handle_sampled_validators_reached_agreement(blockstore, response_slot_hashes),
-            );
+            let decision =
+                self.handle_sampled_validators_reached_agreement(blockstore, response_slot_hashes);
+            return Some(self.extend_prior_ancestors(decision));
         }
 
         // If everyone responded and we still haven't agreed upon a set of
@@ -179,12 +199,35 @@ impl DeadSlotAncestorRequestStatus {
                 "{} return invalid sample no agreement",
                 self.requested_mismatched_slot
             );
-            return Some(DuplicateAncestorDecision::InvalidSample);
+            return Some(self.extend_prior_ancestors(DuplicateAncestorDecision::InvalidSample));
         }
 
         None
     }
 
+    // Prepends the ancestors found by the request this one continues to the ancestors to repair.
+    // If this request didn't find any, the search stops and the prior ancestors are repaired as
+    // they were found
+    fn extend_prior_ancestors(
+        &self,
+        mut decision: DuplicateAncestorDecision,
+    ) -> DuplicateAncestorDecision {
+        if self.prior_ancestors.is_empty() {
+            return decision;
+        }
+        if let Some(repair_status) = decision.repair_status_mut() {
+            let mut correct_ancestors_to_repair =
+                self.prior_ancestors[..self.prior_ancestors.len() - 1].to_vec();
+            correct_ancestors_to_repair.append(&mut repair_status.correct_ancestors_to_repair);
+            repair_status.correct_ancestors_to_repair = correct_ancestors_to_repair;
+            decision
+        } else {
+            DuplicateAncestorDecision::EarliestAncestorNotFrozen(DuplicateSlotRepairStatus::new(
+                self.prior_ancestors.clone(),
+            ))
+        }
+    }
+
     fn handle_sampled_validators_reached_agreement(
         &mut self,
         blockstore: &Blockstore,
@@ -202,6 +245,16 @@ impl DeadSlotAncestorRequestStatus {
             return DuplicateAncestorDecision::InvalidSample;
         }
 
+        if !self.prior_ancestors.is_empty()
+            && agreed_response.first() != self.prior_ancestors.last()
+        {
+            info!(
+                "{} return invalid sample disagrees with prior ancestors",
+                self.requested_mismatched_slot
+            );
+            return DuplicateAncestorDecision::InvalidSample;
+        }
+
         // Recall:
         // 1) *correct* validators only respond to `AncestorHashes(slot)` repair requests IFF they
         // saw the ancestors of `slot` get duplicate confirmed, AND
@@ -829,6 +882,67 @@ pub mod tests {
         };
     }
 
+    #[test]
+    fn test_add_multiple_responses_continued_search() {
+        let request_slot = 90;
+        let mut test_setup = setup_add_response_test(request_slot, 10);
+        let prior_ancestors: Vec<(Slot, Hash)> = (91..=100)
+            .rev()
+            .map(|slot| (slot, Hash::new_unique()))
+            .chain(std::iter::once(test_setup.correct_ancestors_response[0]))
+            .collect();
+        test_setup.status = DeadSlotAncestorRequestStatus::new_continued_search(
+            test_setup.sampled_addresses.iter().cloned(),
+            prior_ancestors.clone(),
+        );
+
+        // Our versions of slots 80 through 84 are correct, the rest are mismatched
+        for &(slot, correct_hash) in &test_setup.correct_ancestors_response {
+            let hash = if slot <= 84 {
+                correct_hash
+            } else {
+                Hash::new_unique()
+            };
+            test_setup.blockstore.insert_bank_hash(slot, hash, false);
+        }
+
+        match run_add_multiple_correct_and_incorrect_responses(vec![], &mut test_setup) {
+            DuplicateAncestorDecision::EarliestMismatchFound(repair_status) => {
+                let expected_mismatched_slots: Vec<(Slot, Hash)> = prior_ancestors
+                    .iter()
+                    .chain(test_setup.correct_ancestors_response.iter().skip(1))
+                    .filter(|(slot, _)| *slot > 84)
+                    .cloned()
+                    .collect();
+                assert_eq!(
+                    repair_status.correct_ancestors_to_repair,
+                    expected_mismatched_slots
+                );
+            }
+            x => panic!("Incorrect decision {:?}", x),
+        };
+    }
+
+    #[test]
+    fn test_add_multiple_responses_continued_search_no_agreement() {
+        let request_slot = 90;
+        let mut test_setup = setup_add_response_test(request_slot, 10);
+        let prior_ancestors = vec![(91, Hash::new_unique()), (90, Hash::new_unique())];
+        test_setup.status = DeadSlotAncestorRequestStatus::new_continued_search(
+            test_setup.sampled_addresses.iter().cloned(),
+            prior_ancestors.clone(),
+        );
+
+        // The agreed upon version of slot 90 differs from the one the prior request agreed on,
+        // so the search stops at the prior ancestors
+        match run_add_multiple_correct_and_incorrect_responses(vec![], &mut test_setup) {
+            DuplicateAncestorDecision::EarliestAncestorNotFrozen(repair_status) => {
+                assert_eq!(repair_status.correct_ancestors_to_repair, prior_ancestors);
+            }
+            x => panic!("Incorrect decision {:?}", x),
+        };
+    }
+
     #[test]
     fn test_add_multiple_responses_ancestors_all_match() {
         let request_slot = 100;
@@ -0,0 +1,149 @@
+//! Faults that local cluster tests inject into running validators, to exercise consensus edge
+//! cases.  The faults are registered by the identity of the validator they are injected into,
+//! and only exist in builds with the `fault-injection` feature.
+
+use {
+    lazy_static::lazy_static,
+    solana_gossip::cluster_info::ClusterInfo,
+    solana_ledger::shred::Shred,
+    solana_sdk::{hash::hashv, pubkey::Pubkey},
+    std::{collections::HashMap, convert::TryInto, net::SocketAddr, sync::RwLock},
+};
+
+#[derive(Debug, Default)]
+struct Faults {
+    /// Percentage of the turbine shreds sent to each node that are dropped
+    shred_drop_percentages: HashMap<Pubkey, u8>,
+    snapshots_stalled: bool,
+}
+
+lazy_static! {
+    static ref FAULTS: RwLock<HashMap<Pubkey, Faults>> = RwLock::default();
+}
+
+/// Make `from` drop `percentage` of the shreds it broadcasts or retransmits to `to`, zero to
+/// stop dropping them.  Which shreds are dropped only depends on the shreds and `to`, so a test
+/// drops the same shreds on every run.  Repair is unaffected, so `to` can still repair them
+pub fn drop_shreds(from: &Pubkey, to: &Pubkey, percentage: u8) {
+    let mut faults = FAULTS.write().unwrap();
+    let shred_drop_percentages = &mut faults.entry(*from).or_default().shred_drop_percentages;
+    if percentage == 0 {
+        shred_drop_percentages.remove(to);
+    } else {
+        shred_drop_percentages.insert(*to, percentage.min(100));
+    }
+}
+
+/// Hold off archiving the snapshot packages of `node` while stalled, as if its disk were too
+/// slow
+pub fn stall_snapshots(node: &Pubkey, stalled: bool) {
+    FAULTS
+        .write()
+        .unwrap()
+        .entry(*node)
+        .or_default()
+        .snapshots_stalled = stalled;
+}
+
+pub(crate) fn snapshots_stalled(node: &Pubkey) -> bool {
+    FAULTS
+        .read()
+        .unwrap()
+        .get(node)
+        .map(|faults| faults.snapshots_stalled)
+        .unwrap_or_default()
+}
+
+fn should_drop_shred(shred: &Shred, to: &Pubkey, percentage: u8) -> bool {
+    let hash = hashv(&[
+        &shred.slot().to_le_bytes(),
+        &shred.index().to_le_bytes(),
+        &[shred.is_data() as u8],
+        to.as_ref(),
+    ]);
+    let sample = u64::from_le_bytes(hash.as_ref()[..8].try_into().unwrap()) % 100;
+    sample < u64::from(percentage)
+}
+
+/// The addresses of `addrs` that the node of `cluster_info` still sends `shred` to, without the
+/// TVU addresses of the nodes it drops the shred for
+pub(crate) fn filter_shred_addrs(
+    cluster_info: &ClusterInfo,
+    shred: &Shred,
+    mut addrs: Vec<SocketAddr>,
+) -> Vec<SocketAddr> {
+    let faults = FAULTS.read().unwrap();
+    let shred_drop_percentages = match faults.get(&cluster_info.id()) {
+        Some(faults) => &faults.shred_drop_percentages,
+        None => return addrs,
+    };
+    for (to, percentage) in shred_drop_percentages {
+        if !should_drop_shred(shred, to, *percentage) {
+            continue;
+        }
+        if let Some((tvu, tvu_forwards)) =
+            cluster_info.lookup_contact_info(to, |node| (node.tvu, node.tvu_forwards))
+        {
+            addrs.retain(|addr| *addr != tvu && *addr != tvu_forwards);
+        }
+    }
+    addrs
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_gossip::contact_info::ContactInfo,
+        solana_sdk::{signature::Keypair, timing::timestamp},
+        solana_streamer::socket::SocketAddrSpace,
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn test_drop_shreds() {
+        let node = ContactInfo::new_localhost(&Pubkey::new_unique(), timestamp());
+        let cluster_info =
+            ClusterInfo::new(node, Arc::new(Keypair::new()), SocketAddrSpace::Unspecified);
+        let to = ContactInfo::new_with_socketaddr(&"127.0.0.1:1234".parse().unwrap());
+        let other = ContactInfo::new_with_socketaddr(&"127.0.0.1:1240".parse().unwrap());
+        cluster_info.insert_info(to.clone());
+        cluster_info.insert_info(other.clone());
+        let shreds: Vec<_> = (0..1_000)
+            .map(|index| Shred::new_from_data(7, index, 0, None, false, false, 0, 0, 0))
+            .collect();
+        let num_dropped = |node: &ContactInfo| {
+            shreds
+                .iter()
+                .filter(|shred| {
+                    filter_shred_addrs(&cluster_info, shred, vec![node.tvu, node.tvu_forwards])
+                        .is_empty()
+                })
+                .count()
+        };
+        assert_eq!(num_dropped(&to), 0);
+
+        drop_shreds(&cluster_info.id(), &to.id, 30);
+        let dropped = num_dropped(&to);
+        assert!((200..400).contains(&dropped));
+        // The same shreds are dropped every time
+        assert_eq!(num_dropped(&to), dropped);
+        assert_eq!(num_dropped(&other), 0);
+
+        drop_shreds(&cluster_info.id(), &to.id, 100);
+        assert_eq!(num_dropped(&to), shreds.len());
+        drop_shreds(&cluster_info.id(), &to.id, 0);
+        assert_eq!(num_dropped(&to), 0);
+    }
+
+    #[test]
+    fn test_stall_snapshots() {
+        let node = Pubkey::new_unique();
+        assert!(!snapshots_stalled(&node));
+        stall_snapshots(&node, true);
+        assert!(snapshots_stalled(&node));
+        assert!(!snapshots_stalled(&Pubkey::new_unique()));
+        stall_snapshots(&node, false);
+        assert!(!snapshots_stalled(&node));
+    }
+}
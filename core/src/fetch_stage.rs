@@ -1,6 +1,7 @@
 //! The `fetch_stage` batches input from a UDP socket and sends it to a channel.
 
 use crate::banking_stage::HOLD_TRANSACTIONS_SLOT_OFFSET;
+use crate::packet_drops::{self, Stage};
 use crate::result::{Error, Result};
 use solana_metrics::{inc_new_counter_debug, inc_new_counter_info};
 use solana_perf::packet::PacketsRecycler;
@@ -102,6 +103,7 @@ impl FetchStage {
             }
         } else {
             inc_new_counter_info!("fetch_stage-discard_forwards", len);
+            packet_drops::record_dropped(Stage::Fetch, len);
         }
 
         Ok(())
@@ -119,42 +121,36 @@ impl FetchStage {
     ) -> Self {
         let recycler: PacketsRecycler = Recycler::warmed(1000, 1024);
 
-        let tpu_threads = sockets.into_iter().map(|socket| {
-            streamer::receiver(
-                socket,
-                exit,
-                sender.clone(),
-                recycler.clone(),
-                "fetch_stage",
-                coalesce_ms,
-                true,
-            )
-        });
+        let tpu_threads = streamer::receivers(
+            sockets,
+            exit,
+            sender.clone(),
+            recycler.clone(),
+            "fetch_stage",
+            coalesce_ms,
+            true,
+        );
 
         let (forward_sender, forward_receiver) = channel();
-        let tpu_forwards_threads = tpu_forwards_sockets.into_iter().map(|socket| {
-            streamer::receiver(
-                socket,
-                exit,
-                forward_sender.clone(),
-                recycler.clone(),
-                "fetch_forward_stage",
-                coalesce_ms,
-                true,
-            )
-        });
+        let tpu_forwards_threads = streamer::receivers(
+            tpu_forwards_sockets,
+            exit,
+            forward_sender,
+            recycler.clone(),
+            "fetch_forward_stage",
+            coalesce_ms,
+            true,
+        );
 
-        let tpu_vote_threads = tpu_vote_sockets.into_iter().map(|socket| {
-            streamer::receiver(
-                socket,
-                exit,
-                vote_sender.clone(),
-                recycler.clone(),
-                "fetch_vote_stage",
-                coalesce_ms,
-                true,
-            )
-        });
+        let tpu_vote_threads = streamer::receivers(
+            tpu_vote_sockets,
+            exit,
+            vote_sender.clone(),
+            recycler,
+            "fetch_vote_stage",
+            coalesce_ms,
+            true,
+        );
 
         let sender = sender.clone();
         let poh_recorder = poh_recorder.clone();
@@ -177,6 +173,7 @@ impl FetchStage {
             .unwrap();
 
         let mut thread_hdls: Vec<_> = tpu_threads
+            .into_iter()
             .chain(tpu_forwards_threads)
             .chain(tpu_vote_threads)
             .collect();
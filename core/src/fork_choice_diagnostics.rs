@@ -0,0 +1,228 @@
+//! The `fork_choice_diagnostics` module captures a snapshot of ReplayStage's fork choice and
+//! voting state so that it can be surfaced to the node operator over the admin RPC channel.
+use {
+    crate::{
+        consensus::{SwitchForkDecision, Tower},
+        heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
+        replay_stage::HeaviestForkFailures,
+    },
+    solana_sdk::clock::Slot,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Condvar, Mutex,
+        },
+        time::Duration,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkWeight {
+    pub slot: Slot,
+    pub hash: String,
+    // Amount of stake that has voted for exactly this slot
+    pub stake_voted_at: u64,
+    // Amount of stake that has voted for this slot and the subtree rooted at this slot
+    pub stake_voted_subtree: u64,
+    // The latest ancestor of this slot that has been marked invalid, if any
+    pub latest_invalid_ancestor: Option<Slot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TowerLockout {
+    pub slot: Slot,
+    pub confirmation_count: u32,
+    pub last_locked_out_slot: Slot,
+}
+
+/// `ForkChoiceDiagnostics` is built by ReplayStage when requested, right after it selects the
+/// vote and reset forks, so that "my validator is stuck not voting" can be debugged without log
+/// spelunking.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkChoiceDiagnostics {
+    pub root: Slot,
+    pub heaviest_slot: Slot,
+    pub heaviest_slot_on_same_voted_fork: Option<Slot>,
+    pub last_voted_slot: Option<Slot>,
+    pub last_switch_threshold_check: Option<(Slot, SwitchForkDecision)>,
+    pub heaviest_fork_failures: Vec<HeaviestForkFailures>,
+    pub lockouts: Vec<TowerLockout>,
+    pub fork_weights: Vec<ForkWeight>,
+}
+
+impl ForkChoiceDiagnostics {
+    pub fn new(
+        tower: &Tower,
+        heaviest_subtree_fork_choice: &HeaviestSubtreeForkChoice,
+        heaviest_slot: Slot,
+        heaviest_slot_on_same_voted_fork: Option<Slot>,
+        heaviest_fork_failures: &[HeaviestForkFailures],
+    ) -> Self {
+        let lockouts = tower
+            .tower_lockouts()
+            .iter()
+            .map(|lockout| TowerLockout {
+                slot: lockout.slot,
+                confirmation_count: lockout.confirmation_count,
+                last_locked_out_slot: lockout.last_locked_out_slot(),
+            })
+            .collect();
+
+        let mut fork_weights: Vec<_> = heaviest_subtree_fork_choice
+            .all_slots_stake_voted_subtree()
+            .map(|(slot_hash_key, stake_voted_subtree)| ForkWeight {
+                slot: slot_hash_key.0,
+                hash: slot_hash_key.1.to_string(),
+                stake_voted_at: heaviest_subtree_fork_choice
+                    .stake_voted_at(slot_hash_key)
+                    .unwrap_or_default(),
+                stake_voted_subtree,
+                latest_invalid_ancestor: heaviest_subtree_fork_choice
+                    .latest_invalid_ancestor(slot_hash_key),
+            })
+            .collect();
+        fork_weights.sort_by_key(|fork_weight| fork_weight.slot);
+
+        Self {
+            root: tower.root(),
+            heaviest_slot,
+            heaviest_slot_on_same_voted_fork,
+            last_voted_slot: tower.last_voted_slot(),
+            last_switch_threshold_check: tower.last_switch_threshold_check.clone(),
+            heaviest_fork_failures: heaviest_fork_failures.to_vec(),
+            lockouts,
+            fork_weights,
+        }
+    }
+}
+
+/// Hands `ForkChoiceDiagnostics` from ReplayStage to the admin RPC.  Walking every fork is too
+/// costly to do on each ReplayStage iteration, so the diagnostics are only built once requested
+#[derive(Debug, Default)]
+pub struct SharedForkChoiceDiagnostics {
+    requested: AtomicBool,
+    // The last diagnostics built, and the number of times they were built
+    diagnostics: Mutex<(u64, ForkChoiceDiagnostics)>,
+    built: Condvar,
+}
+
+impl SharedForkChoiceDiagnostics {
+    /// Whether diagnostics were requested since ReplayStage last built them
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+
+    /// Hands freshly built diagnostics to the pending requests
+    pub fn fulfill(&self, diagnostics: ForkChoiceDiagnostics) {
+        let mut current = self.diagnostics.lock().unwrap();
+        self.requested.store(false, Ordering::Relaxed);
+        current.0 += 1;
+        current.1 = diagnostics;
+        self.built.notify_all();
+    }
+
+    /// Asks ReplayStage to build the diagnostics and waits up to `timeout` for them.  Returns
+    /// None if ReplayStage doesn't get to it in time, rather than older diagnostics
+    pub fn request(&self, timeout: Duration) -> Option<ForkChoiceDiagnostics> {
+        let current = self.diagnostics.lock().unwrap();
+        let generation = current.0;
+        self.requested.store(true, Ordering::Relaxed);
+        let (current, wait_result) = self
+            .built
+            .wait_timeout_while(current, timeout, |current| current.0 == generation)
+            .unwrap();
+        (!wait_result.timed_out()).then(|| current.1.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::hash::Hash,
+        std::{sync::Arc, thread},
+        trees::tr,
+    };
+
+    #[test]
+    fn test_fork_choice_diagnostics() {
+        let forks = tr(0) / (tr(1) / (tr(2) / tr(3)) / (tr(4) / tr(5)));
+        let heaviest_subtree_fork_choice = HeaviestSubtreeForkChoice::new_from_tree(forks);
+        let mut tower = Tower::new_for_tests(8, 0.67);
+        tower.record_vote(1, Hash::default());
+        tower.record_vote(2, Hash::default());
+
+        let diagnostics = ForkChoiceDiagnostics::new(
+            &tower,
+            &heaviest_subtree_fork_choice,
+            5,
+            Some(3),
+            &[HeaviestForkFailures::FailedSwitchThreshold(5)],
+        );
+
+        assert_eq!(diagnostics.last_voted_slot, Some(2));
+        assert_eq!(diagnostics.heaviest_slot, 5);
+        assert_eq!(diagnostics.heaviest_slot_on_same_voted_fork, Some(3));
+        assert_eq!(
+            diagnostics.heaviest_fork_failures,
+            vec![HeaviestForkFailures::FailedSwitchThreshold(5)]
+        );
+        assert_eq!(
+            diagnostics
+                .lockouts
+                .iter()
+                .map(|lockout| (lockout.slot, lockout.confirmation_count))
+                .collect::<Vec<_>>(),
+            vec![(1, 2), (2, 1)]
+        );
+        assert_eq!(
+            diagnostics
+                .fork_weights
+                .iter()
+                .map(|fork_weight| fork_weight.slot)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert!(diagnostics
+            .fork_weights
+            .iter()
+            .all(|fork_weight| fork_weight.stake_voted_subtree == 0));
+    }
+
+    #[test]
+    fn test_shared_fork_choice_diagnostics() {
+        let shared = Arc::new(SharedForkChoiceDiagnostics::default());
+        assert!(!shared.is_requested());
+
+        // nothing is built until requested, and nothing is returned on timeout
+        assert_eq!(shared.request(Duration::from_millis(10)), None);
+        assert!(shared.is_requested());
+
+        let replay = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                while !shared.is_requested() {
+                    thread::yield_now();
+                }
+                shared.fulfill(ForkChoiceDiagnostics {
+                    heaviest_slot: 42,
+                    ..ForkChoiceDiagnostics::default()
+                });
+            })
+        };
+        assert_eq!(
+            shared
+                .request(Duration::from_secs(60))
+                .map(|diagnostics| diagnostics.heaviest_slot),
+            Some(42)
+        );
+        replay.join().unwrap();
+        assert!(!shared.is_requested());
+
+        // diagnostics built for an earlier request aren't returned again
+        assert_eq!(shared.request(Duration::from_millis(10)), None);
+    }
+}
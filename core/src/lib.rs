@@ -10,6 +10,7 @@
 pub mod accounts_hash_verifier;
 pub mod ancestor_hashes_service;
 pub mod banking_stage;
+pub mod banking_trace;
 pub mod broadcast_stage;
 pub mod cache_block_meta_service;
 pub mod cluster_info_vote_listener;
@@ -17,21 +18,29 @@ pub mod cluster_nodes;
 pub mod cluster_slot_state_verifier;
 pub mod cluster_slots;
 pub mod cluster_slots_service;
+pub mod cluster_version_monitor_service;
 pub mod commitment_service;
 pub mod completed_data_sets_service;
+pub mod conflicting_vote_detector;
 pub mod consensus;
 pub mod cost_update_service;
 pub mod duplicate_repair_status;
+#[cfg(any(test, feature = "fault-injection"))]
+pub mod fault_injection;
 pub mod fetch_stage;
 pub mod fork_choice;
+pub mod fork_choice_diagnostics;
 pub mod gen_keys;
 pub mod heaviest_subtree_fork_choice;
 pub mod latest_validator_votes_for_frozen_banks;
 pub mod ledger_cleanup_service;
 pub mod optimistic_confirmation_verifier;
+pub mod packet_drops;
 pub mod outstanding_requests;
 pub mod packet_hasher;
 pub mod progress_map;
+#[cfg(unix)]
+pub mod remote_signer;
 pub mod repair_response;
 pub mod repair_service;
 pub mod repair_weight;
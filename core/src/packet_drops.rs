@@ -0,0 +1,143 @@
+//! Counts of the packets each stage of the pipeline drops, reported together as the
+//! `packet-drops` datapoint so that operators can locate where packets die under load.  A stage
+//! either drops a packet, rejecting it as a duplicate, a failed signature or a packet it can't
+//! use, or overruns, shedding it because the stage fell behind.  Packets the kernel drops before
+//! they reach a socket are reported as `rcvbuf_errors_delta` of the `net-stats` datapoint.
+
+use {
+    solana_sdk::timing::timestamp,
+    std::sync::atomic::{AtomicU64, Ordering},
+};
+
+const REPORT_INTERVAL_MS: u64 = 2_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Fetch,
+    SigVerify,
+    Banking,
+    Retransmit,
+}
+
+const NUM_STAGES: usize = 4;
+
+struct StageDrops {
+    dropped: AtomicU64,
+    overrun: AtomicU64,
+}
+
+impl StageDrops {
+    const fn new() -> Self {
+        Self {
+            dropped: AtomicU64::new(0),
+            overrun: AtomicU64::new(0),
+        }
+    }
+
+    fn take(&self) -> (u64, u64) {
+        (
+            self.dropped.swap(0, Ordering::Relaxed),
+            self.overrun.swap(0, Ordering::Relaxed),
+        )
+    }
+}
+
+struct PacketDrops {
+    last_report: AtomicU64,
+    stages: [StageDrops; NUM_STAGES],
+}
+
+static PACKET_DROPS: PacketDrops = PacketDrops::new();
+
+impl PacketDrops {
+    const fn new() -> Self {
+        Self {
+            last_report: AtomicU64::new(0),
+            stages: [
+                StageDrops::new(),
+                StageDrops::new(),
+                StageDrops::new(),
+                StageDrops::new(),
+            ],
+        }
+    }
+
+    fn stage(&self, stage: Stage) -> &StageDrops {
+        &self.stages[stage as usize]
+    }
+
+    fn take(&self) -> [(u64, u64); NUM_STAGES] {
+        let mut drops = [(0, 0); NUM_STAGES];
+        for (drops, stage) in drops.iter_mut().zip(&self.stages) {
+            *drops = stage.take();
+        }
+        drops
+    }
+
+    fn maybe_report(&self) {
+        let now = timestamp();
+        let last_report = self.last_report.load(Ordering::Relaxed);
+        if now.saturating_sub(last_report) < REPORT_INTERVAL_MS
+            || self
+                .last_report
+                .compare_exchange(last_report, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        let [fetch, sigverify, banking, retransmit] = self.take();
+        datapoint_info!(
+            "packet-drops",
+            ("fetch_dropped", fetch.0, i64),
+            ("fetch_overrun", fetch.1, i64),
+            ("sigverify_dropped", sigverify.0, i64),
+            ("sigverify_overrun", sigverify.1, i64),
+            ("banking_dropped", banking.0, i64),
+            ("banking_overrun", banking.1, i64),
+            ("retransmit_dropped", retransmit.0, i64),
+            ("retransmit_overrun", retransmit.1, i64),
+        );
+    }
+}
+
+/// Record packets `stage` rejected
+pub fn record_dropped(stage: Stage, num_packets: usize) {
+    if num_packets > 0 {
+        PACKET_DROPS
+            .stage(stage)
+            .dropped
+            .fetch_add(num_packets as u64, Ordering::Relaxed);
+    }
+    PACKET_DROPS.maybe_report();
+}
+
+/// Record packets `stage` shed because it fell behind
+pub fn record_overrun(stage: Stage, num_packets: usize) {
+    if num_packets > 0 {
+        PACKET_DROPS
+            .stage(stage)
+            .overrun
+            .fetch_add(num_packets as u64, Ordering::Relaxed);
+    }
+    PACKET_DROPS.maybe_report();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_drops() {
+        let packet_drops = PacketDrops::new();
+        packet_drops
+            .stage(Stage::SigVerify)
+            .overrun
+            .fetch_add(3, Ordering::Relaxed);
+        packet_drops
+            .stage(Stage::Retransmit)
+            .dropped
+            .fetch_add(2, Ordering::Relaxed);
+        assert_eq!(packet_drops.take(), [(0, 0), (0, 3), (0, 0), (2, 0)]);
+        assert_eq!(packet_drops.take(), [(0, 0); NUM_STAGES]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Vote signing by an external signing service, so that the authorized voter keypair needn't
+//! live on the validator host.  The service listens on a unix socket, which may be forwarded to
+//! another host, and answers `RemoteSignerRequest`s framed by `write_frame`.  Votes are signed on
+//! the voting service thread, and a signature that doesn't arrive within the latency budget fails
+//! so that a slow service skips votes rather than queuing them.  Gossip messages are still signed
+//! with the local identity keypair.
+
+use {
+    serde::{de::DeserializeOwned, Serialize},
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::Signature,
+        signer::{Signer, SignerError},
+    },
+    std::{
+        fmt,
+        io::{self, Read, Write},
+        os::unix::net::UnixStream,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+pub const DEFAULT_LATENCY_BUDGET_MS: u64 = 100;
+
+/// Time the service has to list its keys when the validator starts
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest frame either side accepts, well above the size of a vote transaction
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum RemoteSignerRequest {
+    GetPubkeys,
+    SignMessage { pubkey: Pubkey, message: Vec<u8> },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum RemoteSignerResponse {
+    Pubkeys(Vec<Pubkey>),
+    Signature(Signature),
+    Error(String),
+}
+
+/// Write `value` bincode encoded, after its length as a little endian u32
+pub fn write_frame<W: Write, T: Serialize>(writer: &mut W, value: &T) -> io::Result<()> {
+    let frame =
+        bincode::serialize(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    writer.write_all(&(frame.len() as u32).to_le_bytes())?;
+    writer.write_all(&frame)
+}
+
+pub fn read_frame<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes is too large", len),
+        ));
+    }
+    let mut frame = vec![0; len];
+    reader.read_exact(&mut frame)?;
+    bincode::deserialize(&frame).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn request(
+    stream: &mut UnixStream,
+    request: &RemoteSignerRequest,
+    timeout: Duration,
+) -> io::Result<RemoteSignerResponse> {
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_read_timeout(Some(timeout))?;
+    write_frame(stream, request)?;
+    read_frame(stream)
+}
+
+/// A connection to a signing service
+pub struct RemoteSigner {
+    socket_path: PathBuf,
+    latency_budget: Duration,
+    pubkeys: Vec<Pubkey>,
+    // An idle connection, taken out for the duration of a request so that concurrent requests
+    // open their own.  Dropped after a failed request, as a late response would otherwise be read
+    // as the answer to the next one
+    stream: Mutex<Option<UnixStream>>,
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("socket_path", &self.socket_path)
+            .field("latency_budget", &self.latency_budget)
+            .field("pubkeys", &self.pubkeys)
+            .finish()
+    }
+}
+
+impl RemoteSigner {
+    /// Connect to the service at `socket_path` and list the keys it holds
+    pub fn connect<P: AsRef<Path>>(socket_path: P, latency_budget: Duration) -> io::Result<Self> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+        let mut stream = UnixStream::connect(&socket_path)?;
+        let pubkeys = match request(
+            &mut stream,
+            &RemoteSignerRequest::GetPubkeys,
+            CONNECT_TIMEOUT,
+        )? {
+            RemoteSignerResponse::Pubkeys(pubkeys) => pubkeys,
+            response => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected response {:?}", response),
+                ))
+            }
+        };
+        Ok(Self {
+            socket_path,
+            latency_budget,
+            pubkeys,
+            stream: Mutex::new(Some(stream)),
+        })
+    }
+
+    pub fn pubkeys(&self) -> &[Pubkey] {
+        &self.pubkeys
+    }
+
+    pub fn latency_budget(&self) -> Duration {
+        self.latency_budget
+    }
+
+    /// The signer for `pubkey`, if the service holds it
+    pub fn signer(self: &Arc<Self>, pubkey: &Pubkey) -> Option<RemoteKey> {
+        self.pubkeys.contains(pubkey).then(|| RemoteKey {
+            remote_signer: self.clone(),
+            pubkey: *pubkey,
+        })
+    }
+
+    /// A signer for each of the keys the service holds
+    pub fn signers(self: &Arc<Self>) -> Vec<RemoteKey> {
+        self.pubkeys
+            .iter()
+            .filter_map(|pubkey| self.signer(pubkey))
+            .collect()
+    }
+
+    fn budget_exceeded(&self) -> SignerError {
+        SignerError::Connection(format!(
+            "no signature within the latency budget of {}ms",
+            self.latency_budget.as_millis()
+        ))
+    }
+
+    fn sign_message(&self, pubkey: &Pubkey, message: &[u8]) -> Result<Signature, SignerError> {
+        let start = Instant::now();
+        let idle_connection = self.stream.lock().unwrap().take();
+        let mut connection = match idle_connection {
+            Some(connection) => connection,
+            None => UnixStream::connect(&self.socket_path)
+                .map_err(|err| SignerError::Connection(err.to_string()))?,
+        };
+        let remaining = self
+            .latency_budget
+            .checked_sub(start.elapsed())
+            .filter(|remaining| *remaining > Duration::ZERO)
+            .ok_or_else(|| self.budget_exceeded())?;
+        let response = request(
+            &mut connection,
+            &RemoteSignerRequest::SignMessage {
+                pubkey: *pubkey,
+                message: message.to_vec(),
+            },
+            remaining,
+        )
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => self.budget_exceeded(),
+            _ => SignerError::Connection(err.to_string()),
+        })?;
+        // The read timeout applies to each read, so the response may still be late
+        if start.elapsed() > self.latency_budget {
+            return Err(self.budget_exceeded());
+        }
+        *self.stream.lock().unwrap() = Some(connection);
+
+        match response {
+            RemoteSignerResponse::Signature(signature)
+                if signature.verify(pubkey.as_ref(), message) =>
+            {
+                Ok(signature)
+            }
+            RemoteSignerResponse::Signature(_) => Err(SignerError::Custom(format!(
+                "remote signer returned an invalid signature for {}",
+                pubkey
+            ))),
+            RemoteSignerResponse::Error(err) => Err(SignerError::Custom(err)),
+            response => Err(SignerError::Protocol(format!(
+                "unexpected response {:?}",
+                response
+            ))),
+        }
+    }
+}
+
+/// One of the keys of a `RemoteSigner`
+pub struct RemoteKey {
+    remote_signer: Arc<RemoteSigner>,
+    pubkey: Pubkey,
+}
+
+impl Signer for RemoteKey {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.remote_signer.sign_message(&self.pubkey, message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::signature::Keypair,
+        std::{os::unix::net::UnixListener, thread},
+    };
+
+    // Serve one connection at a time, taking `delay` to sign
+    fn serve(listener: UnixListener, keypair: Keypair, delay: Duration) {
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                while let Ok(request) = read_frame(&mut stream) {
+                    let response = match request {
+                        RemoteSignerRequest::GetPubkeys => {
+                            RemoteSignerResponse::Pubkeys(vec![keypair.pubkey()])
+                        }
+                        RemoteSignerRequest::SignMessage { pubkey, message } => {
+                            thread::sleep(delay);
+                            if pubkey == keypair.pubkey() {
+                                RemoteSignerResponse::Signature(keypair.sign_message(&message))
+                            } else {
+                                RemoteSignerResponse::Error(format!("unknown key {}", pubkey))
+                            }
+                        }
+                    };
+                    if write_frame(&mut stream, &response).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("signer.sock");
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        serve(
+            UnixListener::bind(&socket_path).unwrap(),
+            keypair,
+            Duration::ZERO,
+        );
+
+        let remote_signer =
+            Arc::new(RemoteSigner::connect(&socket_path, Duration::from_secs(5)).unwrap());
+        assert_eq!(remote_signer.pubkeys(), &[pubkey]);
+        assert!(remote_signer.signer(&Pubkey::new_unique()).is_none());
+        assert_eq!(remote_signer.signers().len(), 1);
+
+        let signer = remote_signer.signer(&pubkey).unwrap();
+        let signature = signer.try_sign_message(b"vote").unwrap();
+        assert!(signature.verify(pubkey.as_ref(), b"vote"));
+
+        assert!(matches!(
+            remote_signer.sign_message(&Pubkey::new_unique(), b"vote"),
+            Err(SignerError::Custom(_))
+        ));
+    }
+
+    #[test]
+    fn test_remote_signer_latency_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("signer.sock");
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        serve(
+            UnixListener::bind(&socket_path).unwrap(),
+            keypair,
+            Duration::from_millis(200),
+        );
+
+        let remote_signer =
+            Arc::new(RemoteSigner::connect(&socket_path, Duration::from_millis(50)).unwrap());
+        let signer = remote_signer.signer(&pubkey).unwrap();
+        assert!(matches!(
+            signer.try_sign_message(b"vote"),
+            Err(SignerError::Connection(_))
+        ));
+        // The late connection is dropped rather than reused
+        assert!(remote_signer.stream.lock().unwrap().is_none());
+    }
+}
@@ -16,6 +16,7 @@ use {
         },
         cost_update_service::CostUpdate,
         fork_choice::{ForkChoice, SelectVoteAndResetForkResult},
+        fork_choice_diagnostics::{ForkChoiceDiagnostics, SharedForkChoiceDiagnostics},
         heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
         latest_validator_votes_for_frozen_banks::LatestValidatorVotesForFrozenBanks,
         progress_map::{ForkProgress, ProgressMap, PropagatedStats},
@@ -33,6 +34,7 @@ use {
         block_error::BlockError,
         blockstore::Blockstore,
         blockstore_processor::{self, BlockstoreProcessorError, TransactionStatusSender},
+        entry_notifier_interface::EntryNotifierLock,
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_measure::measure::Measure,
@@ -79,7 +81,7 @@ pub const DUPLICATE_THRESHOLD: f64 = 1.0 - SWITCH_FORK_THRESHOLD - DUPLICATE_LIV
 const MAX_VOTE_SIGNATURES: usize = 200;
 const MAX_VOTE_REFRESH_INTERVAL_MILLIS: usize = 5000;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum HeaviestForkFailures {
     LockedOut(u64),
     FailedThreshold(u64),
@@ -130,10 +132,17 @@ pub struct ReplayStageConfig {
     pub rewards_recorder_sender: Option<RewardsRecorderSender>,
     pub cache_block_meta_sender: Option<CacheBlockMetaSender>,
     pub bank_notification_sender: Option<BankNotificationSender>,
+    pub entry_notifier: Option<EntryNotifierLock>,
     pub wait_for_vote_to_start_leader: bool,
     pub ancestor_hashes_replay_update_sender: AncestorHashesReplayUpdateSender,
     pub tower_storage: Arc<dyn TowerStorage>,
     pub disable_epoch_boundary_optimization: bool,
+    pub fork_choice_diagnostics: Arc<SharedForkChoiceDiagnostics>,
+    /// Authorized voters that aren't in `authorized_voter_keypairs`, whose votes are signed by
+    /// the voting service
+    pub remote_authorized_voters: Vec<Pubkey>,
+    /// Signatures of the votes signed by the voting service
+    pub remote_vote_signature_receiver: Receiver<Signature>,
 }
 
 #[derive(Default)]
@@ -340,10 +349,14 @@ impl ReplayStage {
             rewards_recorder_sender,
             cache_block_meta_sender,
             bank_notification_sender,
+            entry_notifier,
             wait_for_vote_to_start_leader,
             ancestor_hashes_replay_update_sender,
             tower_storage,
             disable_epoch_boundary_optimization,
+            fork_choice_diagnostics,
+            remote_authorized_voters,
+            remote_vote_signature_receiver,
         } = config;
 
         trace!("replay stage");
@@ -393,6 +406,14 @@ impl ReplayStage {
                         break;
                     }
 
+                    for signature in remote_vote_signature_receiver.try_iter() {
+                        Self::record_vote_signature(
+                            &mut voted_signatures,
+                            signature,
+                            has_new_vote_been_rooted,
+                        );
+                    }
+
                     let mut generate_new_bank_forks_time =
                         Measure::start("generate_new_bank_forks_time");
                     Self::generate_new_bank_forks(
@@ -420,6 +441,7 @@ impl ReplayStage {
                         &verify_recyclers,
                         &mut heaviest_subtree_fork_choice,
                         &replay_vote_sender,
+                        entry_notifier.as_ref(),
                         &bank_notification_sender,
                         &rewards_recorder_sender,
                         &rpc_subscriptions,
@@ -558,6 +580,7 @@ impl ReplayStage {
                                                     &vote_account,
                                                     &identity_keypair,
                                                     &authorized_voter_keypairs.read().unwrap(),
+                                                    &remote_authorized_voters,
                                                     &mut voted_signatures,
                                                     has_new_vote_been_rooted, &mut
                                                     last_vote_refresh_time,
@@ -584,6 +607,16 @@ impl ReplayStage {
                     );
                     select_vote_and_reset_forks_time.stop();
 
+                    if fork_choice_diagnostics.is_requested() {
+                        fork_choice_diagnostics.fulfill(ForkChoiceDiagnostics::new(
+                            &tower,
+                            &heaviest_subtree_fork_choice,
+                            heaviest_bank.slot(),
+                            heaviest_bank_on_same_voted_fork.as_ref().map(|bank| bank.slot()),
+                            &heaviest_fork_failures,
+                        ));
+                    }
+
                     let mut heaviest_fork_failures_time = Measure::start("heaviest_fork_failures_time");
                     if tower.is_recent(heaviest_bank.slot()) && !heaviest_fork_failures.is_empty() {
                         info!(
@@ -627,6 +660,7 @@ impl ReplayStage {
                             &vote_account,
                             &identity_keypair,
                             &authorized_voter_keypairs.read().unwrap(),
+                            &remote_authorized_voters,
                             &blockstore,
                             &leader_schedule_cache,
                             &lockouts_sender,
@@ -1186,6 +1220,13 @@ impl ReplayStage {
                     return;
                 }
 
+                if let Some(bank) = bank_forks.read().unwrap().get(confirmed_slot) {
+                    Self::check_epoch_accounts_hash_agrees_with_cluster(
+                        bank,
+                        duplicate_confirmed_hash,
+                    );
+                }
+
                 let duplicate_confirmed_state = DuplicateConfirmedState::new_from_state(
                     duplicate_confirmed_hash,
                     || progress.is_dead(confirmed_slot).unwrap_or(false),
@@ -1206,6 +1247,39 @@ impl ReplayStage {
         }
     }
 
+    // Alert if the cluster confirmed another version of the slot our epoch accounts hash was
+    // mixed into, as our accounts state has likely diverged from the cluster
+    fn check_epoch_accounts_hash_agrees_with_cluster(bank: &Bank, duplicate_confirmed_hash: Hash) {
+        if !bank.is_frozen() || bank.hash() == duplicate_confirmed_hash {
+            return;
+        }
+        if let Some(epoch_accounts_hash) = bank.integrated_epoch_accounts_hash() {
+            error!(
+                "Cluster confirmed slot {} with hash {}, but our version has hash {} and \
+                 epoch accounts hash {}",
+                bank.slot(),
+                duplicate_confirmed_hash,
+                bank.hash(),
+                epoch_accounts_hash.hash
+            );
+            datapoint_error!(
+                "epoch_accounts_hash_mismatch",
+                ("slot", bank.slot(), i64),
+                ("bank_hash", bank.hash().to_string(), String),
+                (
+                    "cluster_bank_hash",
+                    duplicate_confirmed_hash.to_string(),
+                    String
+                ),
+                (
+                    "epoch_accounts_hash",
+                    epoch_accounts_hash.hash.to_string(),
+                    String
+                ),
+            );
+        }
+    }
+
     fn process_gossip_verified_vote_hashes(
         gossip_verified_vote_hash_receiver: &GossipVerifiedVoteHashReceiver,
         unfrozen_gossip_verified_vote_hashes: &mut UnfrozenGossipVerifiedVoteHashes,
@@ -1493,6 +1567,7 @@ impl ReplayStage {
         bank_progress: &mut ForkProgress,
         transaction_status_sender: Option<&TransactionStatusSender>,
         replay_vote_sender: &ReplayVoteSender,
+        entry_notifier: Option<&EntryNotifierLock>,
         verify_recyclers: &VerifyRecyclers,
     ) -> result::Result<usize, BlockstoreProcessorError> {
         let tx_count_before = bank_progress.replay_progress.num_txs;
@@ -1504,6 +1579,7 @@ impl ReplayStage {
             false,
             transaction_status_sender,
             Some(replay_vote_sender),
+            entry_notifier,
             None,
             verify_recyclers,
             false,
@@ -1598,6 +1674,7 @@ impl ReplayStage {
         vote_account_pubkey: &Pubkey,
         identity_keypair: &Keypair,
         authorized_voter_keypairs: &[Arc<Keypair>],
+        remote_authorized_voters: &[Pubkey],
         blockstore: &Arc<Blockstore>,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
         lockouts_sender: &Sender<CommitmentAggregationData>,
@@ -1638,11 +1715,13 @@ impl ReplayStage {
             let mut rooted_banks = root_bank.parents();
             rooted_banks.push(root_bank.clone());
             let rooted_slots: Vec<_> = rooted_banks.iter().map(|bank| bank.slot()).collect();
-            // Call leader schedule_cache.set_root() before blockstore.set_root() because
-            // bank_forks.root is consumed by repair_service to update gossip, so we don't want to
-            // get shreds for repair on gossip before we update leader schedule, otherwise they may
-            // get dropped.
-            leader_schedule_cache.set_root(rooted_banks.last().unwrap());
+            // Call leader schedule_cache.set_root_and_precompute() before blockstore.set_root()
+            // because bank_forks.root is consumed by repair_service to update gossip, so we don't
+            // want to get shreds for repair on gossip before we update leader schedule, otherwise
+            // they may get dropped. The epoch stakes and schedule of a newly rooted epoch are
+            // computed in the background so that replay doesn't stall at epoch boundaries, and
+            // lookups made before that's done compute the schedule from the root bank.
+            leader_schedule_cache.set_root_and_precompute(rooted_banks.last().unwrap());
             blockstore
                 .set_roots(rooted_slots.iter())
                 .expect("Ledger set roots failed");
@@ -1695,6 +1774,7 @@ impl ReplayStage {
             vote_account_pubkey,
             identity_keypair,
             authorized_voter_keypairs,
+            remote_authorized_voters,
             tower,
             saved_tower,
             switch_fork_decision,
@@ -1705,11 +1785,13 @@ impl ReplayStage {
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn generate_vote_tx(
         node_keypair: &Keypair,
         bank: &Bank,
         vote_account_pubkey: &Pubkey,
         authorized_voter_keypairs: &[Arc<Keypair>],
+        remote_authorized_voters: &[Pubkey],
         vote: Vote,
         switch_fork_decision: &SwitchForkDecision,
         vote_signatures: &mut Vec<Signature>,
@@ -1761,43 +1843,64 @@ impl ReplayStage {
                 return None;
             };
 
-        let authorized_voter_keypair = match authorized_voter_keypairs
+        let authorized_voter_keypair = authorized_voter_keypairs
             .iter()
-            .find(|keypair| keypair.pubkey() == authorized_voter_pubkey)
+            .find(|keypair| keypair.pubkey() == authorized_voter_pubkey);
+        if authorized_voter_keypair.is_none()
+            && !remote_authorized_voters.contains(&authorized_voter_pubkey)
         {
-            None => {
-                warn!("The authorized keypair {} for vote account {} is not available.  Unable to vote",
-                      authorized_voter_pubkey, vote_account_pubkey);
-                return None;
-            }
-            Some(authorized_voter_keypair) => authorized_voter_keypair,
-        };
+            warn!(
+                "The authorized keypair {} for vote account {} is not available.  Unable to vote",
+                authorized_voter_pubkey, vote_account_pubkey
+            );
+            return None;
+        }
 
         // Send our last few votes along with the new one
         let vote_ix = switch_fork_decision
-            .to_vote_instruction(
-                vote,
-                vote_account_pubkey,
-                &authorized_voter_keypair.pubkey(),
-            )
+            .to_vote_instruction(vote, vote_account_pubkey, &authorized_voter_pubkey)
             .expect("Switch threshold failure should not lead to voting");
 
-        let mut vote_tx = Transaction::new_with_payer(&[vote_ix], Some(&node_keypair.pubkey()));
-
         let blockhash = bank.last_blockhash();
-        vote_tx.partial_sign(&[node_keypair], blockhash);
-        vote_tx.partial_sign(&[authorized_voter_keypair.as_ref()], blockhash);
+        let vote_tx = match authorized_voter_keypair {
+            Some(authorized_voter_keypair) => {
+                let mut vote_tx =
+                    Transaction::new_with_payer(&[vote_ix], Some(&node_keypair.pubkey()));
+                vote_tx.partial_sign(&[node_keypair], blockhash);
+                vote_tx.partial_sign(&[authorized_voter_keypair.as_ref()], blockhash);
+                Self::record_vote_signature(
+                    vote_signatures,
+                    vote_tx.signatures[0],
+                    has_new_vote_been_rooted,
+                );
+                vote_tx
+            }
+            None => {
+                // The remote authorized voter pays for and signs the vote on the voting service,
+                // which sends the signature back to be recorded
+                let mut vote_tx =
+                    Transaction::new_with_payer(&[vote_ix], Some(&authorized_voter_pubkey));
+                vote_tx.message.recent_blockhash = blockhash;
+                vote_tx
+            }
+        };
+
+        Some(vote_tx)
+    }
 
+    fn record_vote_signature(
+        vote_signatures: &mut Vec<Signature>,
+        signature: Signature,
+        has_new_vote_been_rooted: bool,
+    ) {
         if !has_new_vote_been_rooted {
-            vote_signatures.push(vote_tx.signatures[0]);
+            vote_signatures.push(signature);
             if vote_signatures.len() > MAX_VOTE_SIGNATURES {
                 vote_signatures.remove(0);
             }
         } else {
             vote_signatures.clear();
         }
-
-        Some(vote_tx)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1808,6 +1911,7 @@ impl ReplayStage {
         vote_account_pubkey: &Pubkey,
         identity_keypair: &Keypair,
         authorized_voter_keypairs: &[Arc<Keypair>],
+        remote_authorized_voters: &[Pubkey],
         vote_signatures: &mut Vec<Signature>,
         has_new_vote_been_rooted: bool,
         last_vote_refresh_time: &mut LastVoteRefreshTime,
@@ -1850,6 +1954,7 @@ impl ReplayStage {
             heaviest_bank_on_same_fork,
             vote_account_pubkey,
             authorized_voter_keypairs,
+            remote_authorized_voters,
             tower.last_vote(),
             &SwitchForkDecision::SameFork,
             vote_signatures,
@@ -1884,6 +1989,7 @@ impl ReplayStage {
         vote_account_pubkey: &Pubkey,
         identity_keypair: &Keypair,
         authorized_voter_keypairs: &[Arc<Keypair>],
+        remote_authorized_voters: &[Pubkey],
         tower: &mut Tower,
         saved_tower: SavedTower,
         switch_fork_decision: &SwitchForkDecision,
@@ -1898,6 +2004,7 @@ impl ReplayStage {
             bank,
             vote_account_pubkey,
             authorized_voter_keypairs,
+            remote_authorized_voters,
             tower.last_vote(),
             switch_fork_decision,
             vote_signatures,
@@ -1978,6 +2085,7 @@ impl ReplayStage {
         verify_recyclers: &VerifyRecyclers,
         heaviest_subtree_fork_choice: &mut HeaviestSubtreeForkChoice,
         replay_vote_sender: &ReplayVoteSender,
+        entry_notifier: Option<&EntryNotifierLock>,
         bank_notification_sender: &Option<BankNotificationSender>,
         rewards_recorder_sender: &Option<RewardsRecorderSender>,
         rpc_subscriptions: &Arc<RpcSubscriptions>,
@@ -1998,6 +2106,7 @@ impl ReplayStage {
         trace!("active banks {:?}", active_banks);
 
         for bank_slot in &active_banks {
+            let _slot_context = solana_logger::slot_context(*bank_slot);
             // If the fork was marked as dead, don't replay it
             if progress.get(bank_slot).map(|p| p.is_dead).unwrap_or(false) {
                 debug!("bank_slot {:?} is marked dead", *bank_slot);
@@ -2039,6 +2148,7 @@ impl ReplayStage {
                     bank_progress,
                     transaction_status_sender,
                     replay_vote_sender,
+                    entry_notifier,
                     verify_recyclers,
                 );
                 match replay_result {
@@ -3559,6 +3669,7 @@ pub mod tests {
                 bank1_progress,
                 None,
                 &replay_vote_sender,
+                None,
                 &VerifyRecyclers::default(),
             );
             let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
@@ -5603,6 +5714,7 @@ pub mod tests {
         let bank0 = bank_forks.read().unwrap().get(0).unwrap().clone();
 
         let (voting_sender, voting_receiver) = channel();
+        let (vote_signature_sender, _vote_signature_receiver) = channel();
 
         // Simulate landing a vote for slot 0 landing in slot 1
         let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
@@ -5613,6 +5725,7 @@ pub mod tests {
             &my_vote_pubkey,
             &identity_keypair,
             &my_vote_keypair,
+            &[],
             &mut tower,
             SavedTower::default(),
             &SwitchForkDecision::SameFork,
@@ -5628,6 +5741,8 @@ pub mod tests {
             &cluster_info,
             &poh_recorder,
             &tower_storage,
+            &[],
+            &vote_signature_sender,
             vote_info,
             false,
         );
@@ -5655,6 +5770,7 @@ pub mod tests {
                 &my_vote_pubkey,
                 &identity_keypair,
                 &my_vote_keypair,
+                &[],
                 &mut voted_signatures,
                 has_new_vote_been_rooted,
                 &mut last_vote_refresh_time,
@@ -5677,6 +5793,7 @@ pub mod tests {
             &my_vote_pubkey,
             &identity_keypair,
             &my_vote_keypair,
+            &[],
             &mut tower,
             SavedTower::default(),
             &SwitchForkDecision::SameFork,
@@ -5692,6 +5809,8 @@ pub mod tests {
             &cluster_info,
             &poh_recorder,
             &tower_storage,
+            &[],
+            &vote_signature_sender,
             vote_info,
             false,
         );
@@ -5711,6 +5830,7 @@ pub mod tests {
             &my_vote_pubkey,
             &identity_keypair,
             &my_vote_keypair,
+            &[],
             &mut voted_signatures,
             has_new_vote_been_rooted,
             &mut last_vote_refresh_time,
@@ -5748,6 +5868,7 @@ pub mod tests {
             &my_vote_pubkey,
             &identity_keypair,
             &my_vote_keypair,
+            &[],
             &mut voted_signatures,
             has_new_vote_been_rooted,
             &mut last_vote_refresh_time,
@@ -5760,6 +5881,8 @@ pub mod tests {
             &cluster_info,
             &poh_recorder,
             &tower_storage,
+            &[],
+            &vote_signature_sender,
             vote_info,
             false,
         );
@@ -5815,6 +5938,7 @@ pub mod tests {
             &my_vote_pubkey,
             &identity_keypair,
             &my_vote_keypair,
+            &[],
             &mut voted_signatures,
             has_new_vote_been_rooted,
             &mut last_vote_refresh_time,
@@ -9,6 +9,7 @@ use {
         cluster_slots::ClusterSlots,
         cluster_slots_service::{ClusterSlotsService, ClusterSlotsUpdateReceiver},
         completed_data_sets_service::CompletedDataSetsSender,
+        packet_drops::{self, Stage},
         packet_hasher::PacketHasher,
         repair_service::{DuplicateSlotsResetSender, RepairInfo},
         window_service::{should_retransmit_and_persist, WindowService},
@@ -249,6 +250,7 @@ fn retransmit(
     let retransmit_shred = |shred: &Shred, socket: &UdpSocket| {
         if should_skip_retransmit(shred, shreds_received) {
             stats.num_shreds_skipped.fetch_add(1, Ordering::Relaxed);
+            packet_drops::record_dropped(Stage::Retransmit, 1);
             return 0;
         }
         let shred_slot = shred.slot();
@@ -277,6 +279,7 @@ fn retransmit(
                     stats
                         .unknown_shred_slot_leader
                         .fetch_add(1, Ordering::Relaxed);
+                    packet_drops::record_dropped(Stage::Retransmit, 1);
                     return 0;
                 }
             };
@@ -287,6 +290,8 @@ fn retransmit(
             .into_iter()
             .filter(|addr| ContactInfo::is_valid_address(addr, socket_addr_space))
             .collect();
+        #[cfg(feature = "fault-injection")]
+        let addrs = crate::fault_injection::filter_shred_addrs(cluster_info, shred, addrs);
         compute_turbine_peers.stop();
         stats
             .compute_turbine_peers_total
@@ -298,6 +303,7 @@ fn retransmit(
             Err(SendPktsError::IoError(ioerr, num_failed)) => {
                 inc_new_counter_info!("cluster_info-retransmit-packets", addrs.len(), 1);
                 inc_new_counter_error!("cluster_info-retransmit-error", num_failed, 1);
+                packet_drops::record_overrun(Stage::Retransmit, num_failed);
                 error!(
                     "retransmit_to multi_target_send error: {:?}, {}/{} packets failed",
                     ioerr,
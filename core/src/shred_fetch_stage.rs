@@ -140,20 +140,15 @@ impl ShredFetchStage {
         F: Fn(&mut Packet) + Send + 'static,
     {
         let (packet_sender, packet_receiver) = channel();
-        let streamers = sockets
-            .into_iter()
-            .map(|s| {
-                streamer::receiver(
-                    s,
-                    exit,
-                    packet_sender.clone(),
-                    recycler.clone(),
-                    "packet_modifier",
-                    1,
-                    true,
-                )
-            })
-            .collect();
+        let streamers = streamer::receivers(
+            sockets,
+            exit,
+            packet_sender,
+            recycler,
+            "packet_modifier",
+            1,
+            true,
+        );
 
         let modifier_hdl = Builder::new()
             .name("solana-tvu-fetch-stage-packet-modifier".to_string())
@@ -6,6 +6,7 @@ use solana_ledger::shred::Shred;
 use solana_ledger::sigverify_shreds::verify_shreds_gpu;
 use solana_perf::{self, packet::Packets, recycler_cache::RecyclerCache};
 use solana_runtime::bank_forks::BankForks;
+use solana_sdk::clock::Slot;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
@@ -34,14 +35,44 @@ impl ShredSigVerifier {
             .flat_map(|batch| batch.packets.iter().filter_map(Shred::get_slot_from_packet))
             .collect()
     }
+
+    // Discards the shreds which can't pass verification without checking their signatures:
+    // those that don't parse, are for slots older than the root, or for slots with no known
+    // leader. Returns the number of shreds discarded
+    fn discard_unverifiable(
+        batches: &mut [Packets],
+        slot_leaders: &HashMap<Slot, [u8; 32]>,
+    ) -> usize {
+        let mut num_discarded = 0;
+        for packet in batches
+            .iter_mut()
+            .flat_map(|batch| batch.packets.iter_mut())
+        {
+            if packet.meta.discard {
+                continue;
+            }
+            let verifiable = Shred::get_slot_from_packet(packet)
+                .map(|slot| slot_leaders.contains_key(&slot))
+                .unwrap_or(false);
+            if !verifiable {
+                packet.meta.discard = true;
+                num_discarded += 1;
+            }
+        }
+        num_discarded
+    }
 }
 
 impl SigVerifier for ShredSigVerifier {
     fn verify_batch(&self, mut batches: Vec<Packets>) -> Vec<Packets> {
-        let r_bank = self.bank_forks.read().unwrap().working_bank();
+        let (r_bank, root) = {
+            let bank_forks = self.bank_forks.read().unwrap();
+            (bank_forks.working_bank(), bank_forks.root())
+        };
         let slots: HashSet<u64> = Self::read_slots(&batches);
         let mut leader_slots: HashMap<u64, [u8; 32]> = slots
             .into_iter()
+            .filter(|slot| *slot >= root)
             .filter_map(|slot| {
                 let key = self
                     .leader_schedule_cache
@@ -49,10 +80,21 @@ impl SigVerifier for ShredSigVerifier {
                 Some((slot, key.to_bytes()))
             })
             .collect();
+        let num_unverifiable = Self::discard_unverifiable(&mut batches, &leader_slots);
         leader_slots.insert(std::u64::MAX, [0u8; 32]);
 
         let r = verify_shreds_gpu(&batches, &leader_slots, &self.recycler_cache);
         solana_perf::sigverify::mark_disabled(&mut batches, &r);
+        let num_discarded = batches
+            .iter()
+            .flat_map(|batch| batch.packets.iter())
+            .filter(|packet| packet.meta.discard)
+            .count();
+        inc_new_counter_info!("shred_sigverify-unverifiable_shreds", num_unverifiable);
+        inc_new_counter_info!(
+            "shred_sigverify-invalid_signatures",
+            num_discarded.saturating_sub(num_unverifiable)
+        );
         batches
     }
 }
@@ -157,4 +199,31 @@ pub mod tests {
         assert!(!rv[0].packets[0].meta.discard);
         assert!(rv[0].packets[1].meta.discard);
     }
+
+    #[test]
+    fn test_sigverify_shreds_discard_unverifiable() {
+        let leader_keypair = Keypair::new();
+        let mut batch = vec![Packets::default()];
+        batch[0].packets.resize(3, Packet::default());
+        for (i, slot) in [5, 7].iter().enumerate() {
+            let mut shred =
+                Shred::new_from_data(*slot, 0xc0de, 1, Some(&[1, 2, 3, 4]), true, true, 0, 0, 0);
+            Shredder::sign_shred(&leader_keypair, &mut shred);
+            batch[0].packets[i].data[0..shred.payload.len()].copy_from_slice(&shred.payload);
+            batch[0].packets[i].meta.size = shred.payload.len();
+        }
+        // The last packet is too short to be a shred
+
+        let slot_leaders: HashMap<Slot, [u8; 32]> = [(5, leader_keypair.pubkey().to_bytes())]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(
+            ShredSigVerifier::discard_unverifiable(&mut batch, &slot_leaders),
+            2
+        );
+        assert!(!batch[0].packets[0].meta.discard);
+        assert!(batch[0].packets[1].meta.discard);
+        assert!(batch[0].packets[2].meta.discard);
+    }
 }
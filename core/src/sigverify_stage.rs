@@ -5,6 +5,7 @@
 //! transaction. All processing is done on the CPU by default and on a GPU
 //! if perf-libs are available
 
+use crate::packet_drops::{self, Stage};
 use crate::sigverify;
 use crossbeam_channel::{SendError, Sender as CrossbeamSender};
 use solana_measure::measure::Measure;
@@ -180,13 +181,25 @@ impl SigVerifyStage {
             timing::timestamp(),
             num_packets,
         );
-        if num_packets > MAX_SIGVERIFY_BATCH {
+        let num_excess_packets = num_packets.saturating_sub(MAX_SIGVERIFY_BATCH);
+        if num_excess_packets > 0 {
             Self::discard_excess_packets(&mut batches, MAX_SIGVERIFY_BATCH);
         }
+        packet_drops::record_overrun(Stage::SigVerify, num_excess_packets);
 
         let mut verify_batch_time = Measure::start("sigverify_batch_time");
-        sendr.send(verifier.verify_batch(batches))?;
+        let batches = verifier.verify_batch(batches);
         verify_batch_time.stop();
+        let num_discarded_packets = batches
+            .iter()
+            .flat_map(|batch| &batch.packets)
+            .filter(|packet| packet.meta.discard)
+            .count();
+        packet_drops::record_dropped(
+            Stage::SigVerify,
+            num_discarded_packets.saturating_sub(num_excess_packets),
+        );
+        sendr.send(batches)?;
 
         debug!(
             "@{:?} verifier: done. batches: {} total verify time: {:?} verified: {} v/s {}",
@@ -35,6 +35,8 @@ impl SnapshotPackagerService {
         enable_gossip_push: bool,
     ) -> Self {
         let exit = exit.clone();
+        #[cfg(feature = "fault-injection")]
+        let id = cluster_info.id();
         let cluster_info = cluster_info.clone();
         let max_full_snapshot_hashes = std::cmp::min(
             MAX_SNAPSHOT_HASHES,
@@ -68,6 +70,12 @@ impl SnapshotPackagerService {
                         break;
                     }
 
+                    #[cfg(feature = "fault-injection")]
+                    if crate::fault_injection::snapshots_stalled(&id) {
+                        std::thread::sleep(Duration::from_millis(100));
+                        continue;
+                    }
+
                     let snapshot_package = pending_snapshot_package.lock().unwrap().take();
                     if snapshot_package.is_none() {
                         std::thread::sleep(Duration::from_millis(100));
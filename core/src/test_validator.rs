@@ -1,5 +1,6 @@
 use {
     crate::{
+        fork_choice_diagnostics::SharedForkChoiceDiagnostics,
         tower_storage::TowerStorage,
         validator::{Validator, ValidatorConfig, ValidatorStartProgress},
     },
@@ -13,15 +14,17 @@ use {
     solana_net_utils::PortRange,
     solana_rpc::rpc::JsonRpcConfig,
     solana_runtime::{
-        genesis_utils::create_genesis_config_with_leader_ex,
+        bank::Bank, bank_forks::BankForks, genesis_utils::create_genesis_config_with_leader_ex,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE, snapshot_config::SnapshotConfig,
     },
     solana_sdk::{
         account::{Account, AccountSharedData},
-        clock::{Slot, DEFAULT_MS_PER_SLOT},
+        clock::{Epoch, Slot, DEFAULT_MS_PER_SLOT},
         commitment_config::CommitmentConfig,
         epoch_schedule::EpochSchedule,
         exit::Exit,
+        feature::{self, Feature},
+        feature_set::FEATURE_NAMES,
         fee_calculator::{FeeCalculator, FeeRateGovernor},
         hash::Hash,
         instruction::{AccountMeta, Instruction},
@@ -33,7 +36,7 @@ use {
     },
     solana_streamer::socket::SocketAddrSpace,
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         fs::remove_dir_all,
         net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
@@ -85,11 +88,13 @@ pub struct TestValidatorGenesis {
     no_bpf_jit: bool,
     accounts: HashMap<Pubkey, AccountSharedData>,
     programs: Vec<ProgramInfo>,
+    deactivate_feature_set: HashSet<Pubkey>,
     epoch_schedule: Option<EpochSchedule>,
     node_config: TestValidatorNodeConfig,
     pub validator_exit: Arc<RwLock<Exit>>,
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
+    pub fork_choice_diagnostics: Arc<SharedForkChoiceDiagnostics>,
     pub max_ledger_shreds: Option<u64>,
 }
 
@@ -144,6 +149,12 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Leave the given features inactive in genesis, rather than activating every feature
+    pub fn deactivate_features(&mut self, deactivate_list: &[Pubkey]) -> &mut Self {
+        self.deactivate_feature_set.extend(deactivate_list);
+        self
+    }
+
     pub fn bpf_jit(&mut self, bpf_jit: bool) -> &mut Self {
         self.no_bpf_jit = !bpf_jit;
         self
@@ -415,6 +426,16 @@ impl TestValidator {
         genesis_config.epoch_schedule = config
             .epoch_schedule
             .unwrap_or_else(EpochSchedule::without_warmup);
+        for deactivate_feature_pk in &config.deactivate_feature_set {
+            if FEATURE_NAMES.contains_key(deactivate_feature_pk) {
+                genesis_config.accounts.remove(deactivate_feature_pk);
+            } else {
+                warn!(
+                    "Feature {} is unknown and cannot be deactivated",
+                    deactivate_feature_pk
+                );
+            }
+        }
 
         let ledger_path = match &config.ledger_path {
             None => create_new_tmp_ledger!(&genesis_config).0,
@@ -527,6 +548,7 @@ impl TestValidator {
             warp_slot: config.warp_slot,
             bpf_jit: !config.no_bpf_jit,
             validator_exit: config.validator_exit.clone(),
+            fork_choice_diagnostics: config.fork_choice_diagnostics.clone(),
             rocksdb_compaction_interval: Some(100), // Compact every 100 slots
             max_ledger_shreds: config.max_ledger_shreds,
             no_wait_for_vote_to_start_leader: true,
@@ -662,6 +684,102 @@ impl TestValidator {
     pub fn cluster_info(&self) -> Arc<ClusterInfo> {
         self.validator.as_ref().unwrap().cluster_info.clone()
     }
+
+    pub fn bank_forks(&self) -> Arc<RwLock<BankForks>> {
+        self.validator.as_ref().unwrap().bank_forks.clone()
+    }
+
+    /// Request activation of a feature, which takes effect at the next epoch boundary.
+    ///
+    /// Returns the epoch the feature will be active in.
+    pub fn activate_feature(&self, feature_id: &Pubkey) -> Result<Epoch, String> {
+        activate_feature(&self.bank_forks(), feature_id)
+    }
+
+    /// Cancel a pending feature activation
+    pub fn deactivate_feature(&self, feature_id: &Pubkey) -> Result<(), String> {
+        deactivate_feature(&self.bank_forks(), feature_id)
+    }
+}
+
+/// Run `f` against the working bank once it is known not to be freezing, so that accounts can
+/// be stored directly into it
+fn with_unfrozen_working_bank<T>(
+    bank_forks: &RwLock<BankForks>,
+    f: impl Fn(&Bank) -> Result<T, String>,
+) -> Result<T, String> {
+    const MAX_TRIES: u64 = 10;
+    for _ in 0..MAX_TRIES {
+        let bank = bank_forks.read().unwrap().working_bank();
+        // Holding the freeze lock keeps the bank from being frozen while it is modified
+        let freeze_lock = bank.freeze_lock();
+        if *freeze_lock == Hash::default() {
+            return f(&bank);
+        }
+        drop(freeze_lock);
+        sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT / 4));
+    }
+    Err("Unable to find an unfrozen working bank".to_string())
+}
+
+/// Store a pending feature account into the working bank of `bank_forks`.  The runtime activates
+/// it at the next epoch boundary, exactly as if it had been requested by a feature proposal.
+///
+/// Returns the epoch the feature will be active in.
+pub fn activate_feature(
+    bank_forks: &RwLock<BankForks>,
+    feature_id: &Pubkey,
+) -> Result<Epoch, String> {
+    if !FEATURE_NAMES.contains_key(feature_id) {
+        return Err(format!("Unknown feature: {}", feature_id));
+    }
+    with_unfrozen_working_bank(bank_forks, |bank| {
+        if bank.feature_set.is_active(feature_id) {
+            return Err(format!("Feature {} is already active", feature_id));
+        }
+        if bank.get_account(feature_id).is_none() {
+            let account = feature::create_account(
+                &Feature::default(),
+                bank.get_minimum_balance_for_rent_exemption(Feature::size_of()),
+            );
+            bank.store_account_and_update_capitalization(feature_id, &account);
+            info!(
+                "Feature {} activation requested in slot {}",
+                feature_id,
+                bank.slot()
+            );
+        }
+        Ok(bank.epoch() + 1)
+    })
+}
+
+/// Remove a pending feature account from the working bank of `bank_forks`, cancelling its
+/// activation.  Features that are already active can only be left inactive when the ledger is
+/// created, with `TestValidatorGenesis::deactivate_features`.
+pub fn deactivate_feature(
+    bank_forks: &RwLock<BankForks>,
+    feature_id: &Pubkey,
+) -> Result<(), String> {
+    if !FEATURE_NAMES.contains_key(feature_id) {
+        return Err(format!("Unknown feature: {}", feature_id));
+    }
+    with_unfrozen_working_bank(bank_forks, |bank| {
+        if bank.feature_set.is_active(feature_id) {
+            return Err(format!(
+                "Feature {} is already active and can only be deactivated when the ledger is created",
+                feature_id
+            ));
+        }
+        if bank.get_account(feature_id).is_some() {
+            bank.store_account_and_update_capitalization(feature_id, &AccountSharedData::default());
+            info!(
+                "Feature {} activation cancelled in slot {}",
+                feature_id,
+                bank.slot()
+            );
+        }
+        Ok(())
+    })
 }
 
 impl Drop for TestValidator {
@@ -13,6 +13,7 @@ use crate::{
     completed_data_sets_service::CompletedDataSetsSender,
     consensus::Tower,
     cost_update_service::CostUpdateService,
+    fork_choice_diagnostics::SharedForkChoiceDiagnostics,
     ledger_cleanup_service::LedgerCleanupService,
     replay_stage::{ReplayStage, ReplayStageConfig},
     retransmit_stage::RetransmitStage,
@@ -27,7 +28,7 @@ use crossbeam_channel::unbounded;
 use solana_gossip::cluster_info::ClusterInfo;
 use solana_ledger::{
     blockstore::Blockstore, blockstore_processor::TransactionStatusSender,
-    leader_schedule_cache::LeaderScheduleCache,
+    entry_notifier_interface::EntryNotifierLock, leader_schedule_cache::LeaderScheduleCache,
 };
 use solana_poh::poh_recorder::PohRecorder;
 use solana_rpc::{
@@ -46,7 +47,11 @@ use solana_runtime::{
     snapshot_package::{AccountsPackageReceiver, AccountsPackageSender, PendingSnapshotPackage},
     vote_sender_types::ReplayVoteSender,
 };
-use solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Keypair};
+use solana_sdk::{
+    clock::Slot,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
 use std::{
     boxed::Box,
     collections::HashSet,
@@ -94,6 +99,7 @@ pub struct TvuConfig {
     pub wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
     pub disable_epoch_boundary_optimization: bool,
+    pub remote_authorized_voters: Vec<Arc<dyn Signer + Send + Sync>>,
 }
 
 impl Tvu {
@@ -116,6 +122,7 @@ impl Tvu {
         poh_recorder: &Arc<Mutex<PohRecorder>>,
         tower: Tower,
         tower_storage: Arc<dyn TowerStorage>,
+        fork_choice_diagnostics: Arc<SharedForkChoiceDiagnostics>,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
         exit: &Arc<AtomicBool>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
@@ -131,6 +138,7 @@ impl Tvu {
         replay_vote_sender: ReplayVoteSender,
         completed_data_sets_sender: CompletedDataSetsSender,
         bank_notification_sender: Option<BankNotificationSender>,
+        entry_notifier: Option<EntryNotifierLock>,
         gossip_confirmed_slots_receiver: GossipDuplicateConfirmedSlotsReceiver,
         tvu_config: TvuConfig,
         max_slots: &Arc<MaxSlots>,
@@ -266,6 +274,7 @@ impl Tvu {
             pruned_banks_receiver,
         };
 
+        let (remote_vote_signature_sender, remote_vote_signature_receiver) = channel();
         let replay_stage_config = ReplayStageConfig {
             vote_account: *vote_account,
             authorized_voter_keypairs,
@@ -279,10 +288,18 @@ impl Tvu {
             rewards_recorder_sender,
             cache_block_meta_sender,
             bank_notification_sender,
+            entry_notifier,
             wait_for_vote_to_start_leader: tvu_config.wait_for_vote_to_start_leader,
             ancestor_hashes_replay_update_sender,
             tower_storage: tower_storage.clone(),
             disable_epoch_boundary_optimization: tvu_config.disable_epoch_boundary_optimization,
+            fork_choice_diagnostics,
+            remote_authorized_voters: tvu_config
+                .remote_authorized_voters
+                .iter()
+                .map(|signer| signer.pubkey())
+                .collect(),
+            remote_vote_signature_receiver,
         };
 
         let (voting_sender, voting_receiver) = channel();
@@ -292,6 +309,8 @@ impl Tvu {
             poh_recorder.clone(),
             tower_storage,
             bank_forks.clone(),
+            tvu_config.remote_authorized_voters,
+            remote_vote_signature_sender,
         );
 
         let (cost_update_sender, cost_update_receiver) = channel();
@@ -460,6 +479,7 @@ pub mod tests {
             &poh_recorder,
             tower,
             Arc::new(crate::tower_storage::FileTowerStorage::default()),
+            Arc::<SharedForkChoiceDiagnostics>::default(),
             &leader_schedule_cache,
             &exit,
             block_commitment_cache,
@@ -475,6 +495,7 @@ pub mod tests {
             replay_vote_sender,
             completed_data_sets_sender,
             None,
+            None,
             gossip_confirmed_slots_receiver,
             TvuConfig::default(),
             &Arc::new(MaxSlots::default()),
@@ -3,11 +3,16 @@
 pub use solana_perf::report_target_features;
 use {
     crate::{
+        banking_trace::BankingTracer,
         broadcast_stage::BroadcastStageType,
         cache_block_meta_service::{CacheBlockMetaSender, CacheBlockMetaService},
         cluster_info_vote_listener::VoteTracker,
+        cluster_version_monitor_service::{
+            ClusterVersionMonitorService, DEFAULT_NEWER_FEATURE_SET_WARN_STAKE_PERCENT,
+        },
         completed_data_sets_service::CompletedDataSetsService,
-        consensus::{reconcile_blockstore_roots_with_tower, Tower},
+        consensus::{reconcile_blockstore_roots_with_tower, Tower, TowerThresholds},
+        fork_choice_diagnostics::SharedForkChoiceDiagnostics,
         rewards_recorder_service::{RewardsRecorderSender, RewardsRecorderService},
         sample_performance_service::SamplePerformanceService,
         serve_repair::ServeRepair,
@@ -36,9 +41,14 @@ use {
         bank_forks_utils,
         blockstore::{Blockstore, BlockstoreSignals, CompletedSlotsReceiver, PurgeType},
         blockstore_db::BlockstoreRecoveryMode,
-        blockstore_processor::{self, TransactionStatusSender},
+        blockstore_processor::{
+            self, BlockstoreProcessorError, DroppedTransactionStatusSlots,
+            TransactionStatusOverflowPolicy, TransactionStatusSender,
+        },
+        entry_notifier_interface::EntryNotifierLock,
         leader_schedule::FixedSchedule,
         leader_schedule_cache::LeaderScheduleCache,
+        transaction_status_spill::TransactionStatusSpill,
     },
     solana_measure::measure::Measure,
     solana_metrics::datapoint_info,
@@ -75,7 +85,7 @@ use {
         snapshot_config::SnapshotConfig,
         snapshot_hash::StartingSnapshotHashes,
         snapshot_package::{AccountsPackageSender, PendingSnapshotPackage},
-        snapshot_utils,
+        snapshot_utils::{self, SnapshotLoadProgress, SnapshotVerification},
     },
     solana_sdk::{
         clock::Slot,
@@ -90,9 +100,11 @@ use {
     },
     solana_send_transaction_service::send_transaction_service,
     solana_streamer::socket::SocketAddrSpace,
+    solana_transaction_status::token_balances::default_token_program_ids,
     solana_vote_program::vote_state::VoteState,
     std::{
         collections::{HashMap, HashSet},
+        fmt,
         net::SocketAddr,
         ops::Deref,
         path::{Path, PathBuf},
@@ -109,6 +121,20 @@ use {
 const MAX_COMPLETED_DATA_SETS_IN_CHANNEL: usize = 100_000;
 const WAIT_FOR_SUPERMAJORITY_THRESHOLD_PERCENT: u64 = 80;
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PohSpeedTestConfig {
+    /// How much faster than the target hash rate this node must hash, in percent
+    pub margin_percent: u64,
+    /// Hashes per second to test against, instead of the rate the genesis config targets
+    pub target_hash_rate: Option<u64>,
+    /// Warn instead of aborting when this node hashes too slowly
+    pub warn_only: bool,
+}
+
+/// Fetches fresh snapshot archives from the cluster once none of the local ones could be
+/// loaded, returning whether any were fetched
+pub type SnapshotArchivesFetcher = Arc<dyn Fn(&ValidatorConfig) -> bool + Send + Sync>;
+
 pub struct ValidatorConfig {
     pub dev_halt_at_slot: Option<Slot>,
     pub expected_genesis_hash: Option<Hash>,
@@ -151,20 +177,40 @@ pub struct ValidatorConfig {
     pub bpf_jit: bool,
     pub send_transaction_service_config: send_transaction_service::Config,
     pub no_poh_speed_test: bool,
+    pub poh_speed_test: PohSpeedTestConfig,
     pub poh_pinned_cpu_core: usize,
     pub poh_hashes_per_batch: u64,
+    pub poh_tick_spin_threshold: Option<Duration>,
     pub account_indexes: AccountSecondaryIndexes,
+    pub token_program_ids: HashSet<Pubkey>,
+    /// Batches of transaction statuses queued for the transaction status service, unbounded if
+    /// unset
+    pub transaction_status_channel_capacity: Option<usize>,
+    pub transaction_status_overflow_policy: TransactionStatusOverflowPolicy,
     pub accounts_db_caching_enabled: bool,
     pub accounts_db_config: Option<AccountsDbConfig>,
     pub warp_slot: Option<Slot>,
     pub accounts_db_test_hash_calculation: bool,
     pub accounts_db_skip_shrink: bool,
     pub accounts_db_use_index_hash_calculation: bool,
+    pub snapshot_verification: SnapshotVerification,
     pub tpu_coalesce_ms: u64,
     pub validator_exit: Arc<RwLock<Exit>>,
     pub no_wait_for_vote_to_start_leader: bool,
     pub accounts_shrink_ratio: AccountShrinkThreshold,
     pub disable_epoch_boundary_optimization: bool,
+    pub fork_choice_diagnostics: Arc<SharedForkChoiceDiagnostics>,
+    /// Record the packets entering banking stage around leader slots to the ledger's
+    /// `banking_trace` directory, deleting the oldest traces past this many bytes
+    pub banking_trace_dir_byte_limit: Option<u64>,
+    /// Compute units the cost model charges per account read lock when packing blocks
+    pub cost_model_read_lock_units: u64,
+    pub tower_thresholds: TowerThresholds,
+    /// Signers for the authorized voters that have no keypair on this host
+    pub remote_authorized_voters: Vec<Arc<dyn Signer + Send + Sync>>,
+    pub snapshot_archives_fetcher: Option<SnapshotArchivesFetcher>,
+    /// Warn once this percentage of the stake runs a newer release with a different feature set
+    pub newer_feature_set_warn_stake_percent: u8,
 }
 
 impl Default for ValidatorConfig {
@@ -211,20 +257,33 @@ impl Default for ValidatorConfig {
             bpf_jit: false,
             send_transaction_service_config: send_transaction_service::Config::default(),
             no_poh_speed_test: true,
+            poh_speed_test: PohSpeedTestConfig::default(),
             poh_pinned_cpu_core: poh_service::DEFAULT_PINNED_CPU_CORE,
             poh_hashes_per_batch: poh_service::DEFAULT_HASHES_PER_BATCH,
+            poh_tick_spin_threshold: None,
             account_indexes: AccountSecondaryIndexes::default(),
+            token_program_ids: default_token_program_ids(),
+            transaction_status_channel_capacity: None,
+            transaction_status_overflow_policy: TransactionStatusOverflowPolicy::default(),
             accounts_db_caching_enabled: false,
             warp_slot: None,
             accounts_db_test_hash_calculation: false,
             accounts_db_skip_shrink: false,
             accounts_db_use_index_hash_calculation: true,
+            snapshot_verification: SnapshotVerification::default(),
             tpu_coalesce_ms: DEFAULT_TPU_COALESCE_MS,
             validator_exit: Arc::new(RwLock::new(Exit::default())),
             no_wait_for_vote_to_start_leader: true,
             accounts_shrink_ratio: AccountShrinkThreshold::default(),
             accounts_db_config: None,
             disable_epoch_boundary_optimization: false,
+            fork_choice_diagnostics: Arc::<SharedForkChoiceDiagnostics>::default(),
+            banking_trace_dir_byte_limit: None,
+            cost_model_read_lock_units: 0,
+            tower_thresholds: TowerThresholds::default(),
+            remote_authorized_voters: Vec::new(),
+            snapshot_archives_fetcher: None,
+            newer_feature_set_warn_stake_percent: DEFAULT_NEWER_FEATURE_SET_WARN_STAKE_PERCENT,
         }
     }
 }
@@ -236,10 +295,26 @@ impl Default for ValidatorConfig {
 pub enum ValidatorStartProgress {
     Initializing, // Catch all, default state
     SearchingForRpcService,
-    DownloadingSnapshot { slot: Slot, rpc_addr: SocketAddr },
+    DownloadingSnapshot {
+        slot: Slot,
+        rpc_addr: SocketAddr,
+        #[serde(default)]
+        downloaded_bytes: u64,
+        #[serde(default)]
+        total_bytes: u64,
+    },
     CleaningBlockStore,
     CleaningAccounts,
     LoadingLedger,
+    UnpackingSnapshot {
+        slot: Slot,
+    },
+    BuildingAccountsIndex,
+    VerifyingSnapshot,
+    ProcessingLedger {
+        slot: Slot,
+        max_root: Slot,
+    },
     StartingServices,
     Halted, // Validator halted due to `--dev-halt-at-slot` argument
     WaitingForSupermajority,
@@ -255,6 +330,49 @@ impl Default for ValidatorStartProgress {
     }
 }
 
+impl fmt::Display for ValidatorStartProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Initializing => write!(f, "Initializing"),
+            Self::SearchingForRpcService => write!(f, "Searching for an RPC service"),
+            Self::DownloadingSnapshot {
+                slot,
+                rpc_addr,
+                downloaded_bytes,
+                total_bytes,
+            } => {
+                write!(
+                    f,
+                    "Downloading snapshot for slot {} from {}",
+                    slot, rpc_addr
+                )?;
+                if *total_bytes > 0 {
+                    write!(
+                        f,
+                        ": {:.1}% of {} bytes",
+                        100. * *downloaded_bytes as f64 / *total_bytes as f64,
+                        total_bytes
+                    )?;
+                }
+                Ok(())
+            }
+            Self::CleaningBlockStore => write!(f, "Cleaning the blockstore"),
+            Self::CleaningAccounts => write!(f, "Cleaning accounts"),
+            Self::LoadingLedger => write!(f, "Loading the ledger"),
+            Self::UnpackingSnapshot { slot } => write!(f, "Unpacking snapshot for slot {}", slot),
+            Self::BuildingAccountsIndex => write!(f, "Building the accounts index"),
+            Self::VerifyingSnapshot => write!(f, "Verifying the snapshot"),
+            Self::ProcessingLedger { slot, max_root } => {
+                write!(f, "Processing the ledger: slot {} of {}", slot, max_root)
+            }
+            Self::StartingServices => write!(f, "Starting services"),
+            Self::Halted => write!(f, "Halted"),
+            Self::WaitingForSupermajority => write!(f, "Waiting for a supermajority of stake"),
+            Self::Running => write!(f, "Running"),
+        }
+    }
+}
+
 #[derive(Default)]
 struct TransactionHistoryServices {
     transaction_status_sender: Option<TransactionStatusSender>,
@@ -276,6 +394,7 @@ pub struct Validator {
     rewards_recorder_service: Option<RewardsRecorderService>,
     cache_block_meta_service: Option<CacheBlockMetaService>,
     system_monitor_service: Option<SystemMonitorService>,
+    cluster_version_monitor_service: ClusterVersionMonitorService,
     sample_performance_service: Option<SamplePerformanceService>,
     gossip_service: GossipService,
     serve_repair_service: ServeRepairService,
@@ -287,8 +406,10 @@ pub struct Validator {
     tvu: Tvu,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
+    pub bank_forks: Arc<RwLock<BankForks>>,
     accountsdb_repl_service: Option<AccountsDbReplService>,
     accountsdb_plugin_service: Option<AccountsDbPluginService>,
+    banking_tracer_thread: Option<JoinHandle<()>>,
 }
 
 // in the distant future, get rid of ::new()/exit() and use Result properly...
@@ -353,6 +474,12 @@ impl Validator {
             for authorized_voter_keypair in authorized_voter_keypairs.read().unwrap().iter() {
                 warn!("authorized voter: {}", authorized_voter_keypair.pubkey());
             }
+            for remote_authorized_voter in &config.remote_authorized_voters {
+                warn!(
+                    "remote authorized voter: {}",
+                    remote_authorized_voter.pubkey()
+                );
+            }
         }
 
         for cluster_entrypoint in &cluster_entrypoints {
@@ -411,6 +538,9 @@ impl Validator {
         }
 
         let accounts_package_channel = channel();
+        let entry_notifier = accountsdb_plugin_service
+            .as_ref()
+            .and_then(|plugin_service| plugin_service.get_entry_notifier());
 
         let (
             genesis_config,
@@ -445,6 +575,7 @@ impl Validator {
             accountsdb_plugin_service
                 .as_ref()
                 .map(|plugin_service| plugin_service.get_accounts_update_notifier()),
+            entry_notifier.clone(),
         );
 
         *start_progress.write().unwrap() = ValidatorStartProgress::StartingServices;
@@ -679,6 +810,12 @@ impl Validator {
             should_check_duplicate_instance,
             &exit,
         );
+        let cluster_version_monitor_service = ClusterVersionMonitorService::new(
+            &cluster_info,
+            &bank_forks,
+            config.newer_feature_set_warn_stake_percent,
+            &exit,
+        );
         let serve_repair = Arc::new(RwLock::new(ServeRepair::new(cluster_info.clone())));
         let serve_repair_service = ServeRepairService::new(
             &serve_repair,
@@ -748,6 +885,7 @@ impl Validator {
             bank.ticks_per_slot(),
             config.poh_pinned_cpu_core,
             config.poh_hashes_per_batch,
+            config.poh_tick_spin_threshold,
             record_receiver,
         );
         assert_eq!(
@@ -762,6 +900,7 @@ impl Validator {
 
         let mut cost_model = CostModel::default();
         cost_model.initialize_cost_table(&blockstore.read_program_costs().unwrap());
+        cost_model.set_read_lock_units(config.cost_model_read_lock_units);
         let cost_model = Arc::new(RwLock::new(cost_model));
 
         let (retransmit_slots_sender, retransmit_slots_receiver) = unbounded();
@@ -809,6 +948,7 @@ impl Validator {
             &poh_recorder,
             tower,
             config.tower_storage.clone(),
+            config.fork_choice_diagnostics.clone(),
             &leader_schedule_cache,
             &exit,
             block_commitment_cache,
@@ -824,6 +964,7 @@ impl Validator {
             replay_vote_sender.clone(),
             completed_data_sets_sender,
             bank_notification_sender.clone(),
+            entry_notifier,
             cluster_confirmed_slot_receiver,
             TvuConfig {
                 max_ledger_shreds: config.max_ledger_shreds,
@@ -841,6 +982,11 @@ impl Validator {
                 wait_for_vote_to_start_leader,
                 accounts_shrink_ratio: config.accounts_shrink_ratio,
                 disable_epoch_boundary_optimization: config.disable_epoch_boundary_optimization,
+                remote_authorized_voters: if config.voting_disabled {
+                    Vec::new()
+                } else {
+                    config.remote_authorized_voters.clone()
+                },
             },
             &max_slots,
             &cost_model,
@@ -848,6 +994,22 @@ impl Validator {
             last_full_snapshot_slot,
         );
 
+        let (banking_tracer, banking_tracer_thread) = match config.banking_trace_dir_byte_limit {
+            Some(dir_byte_limit) => {
+                let trace_dir = ledger_path.join("banking_trace");
+                let (banking_tracer, banking_tracer_thread) =
+                    BankingTracer::new(trace_dir.clone(), dir_byte_limit).unwrap_or_else(|err| {
+                        error!(
+                            "Failed to create banking trace directory {:?}: {:?}",
+                            trace_dir, err
+                        );
+                        abort();
+                    });
+                (banking_tracer, Some(banking_tracer_thread))
+            }
+            None => (BankingTracer::new_disabled(), None),
+        };
+
         let tpu = Tpu::new(
             &cluster_info,
             &poh_recorder,
@@ -864,7 +1026,7 @@ impl Validator {
             &exit,
             node.info.shred_version,
             vote_tracker,
-            bank_forks,
+            bank_forks.clone(),
             verified_vote_sender,
             gossip_verified_vote_hash_sender,
             replay_vote_receiver,
@@ -873,6 +1035,7 @@ impl Validator {
             config.tpu_coalesce_ms,
             cluster_confirmed_slot_sender,
             &cost_model,
+            Arc::new(banking_tracer),
         );
 
         datapoint_info!("validator-new", ("id", id.to_string(), String));
@@ -889,6 +1052,7 @@ impl Validator {
             rewards_recorder_service,
             cache_block_meta_service,
             system_monitor_service,
+            cluster_version_monitor_service,
             sample_performance_service,
             snapshot_packager_service,
             completed_data_sets_service,
@@ -899,8 +1063,10 @@ impl Validator {
             ip_echo_server,
             validator_exit: config.validator_exit.clone(),
             cluster_info,
+            bank_forks,
             accountsdb_repl_service,
             accountsdb_plugin_service,
+            banking_tracer_thread,
         }
     }
 
@@ -989,6 +1155,10 @@ impl Validator {
                 .expect("system_monitor_service");
         }
 
+        self.cluster_version_monitor_service
+            .join()
+            .expect("cluster_version_monitor_service");
+
         if let Some(sample_performance_service) = self.sample_performance_service {
             sample_performance_service
                 .join()
@@ -1004,6 +1174,9 @@ impl Validator {
             .join()
             .expect("serve_repair_service");
         self.tpu.join().expect("tpu");
+        if let Some(banking_tracer_thread) = self.banking_tracer_thread {
+            banking_tracer_thread.join().expect("banking_tracer_thread");
+        }
         self.tvu.join().expect("tvu");
         self.completed_data_sets_service
             .join()
@@ -1035,29 +1208,44 @@ fn active_vote_account_exists_in_bank(bank: &Arc<Bank>, vote_account: &Pubkey) -
     false
 }
 
-fn check_poh_speed(genesis_config: &GenesisConfig, maybe_hash_samples: Option<u64>) {
+fn check_poh_speed(
+    genesis_config: &GenesisConfig,
+    poh_speed_test: &PohSpeedTestConfig,
+    maybe_hash_samples: Option<u64>,
+) {
     if let Some(hashes_per_tick) = genesis_config.hashes_per_tick() {
         let ticks_per_slot = genesis_config.ticks_per_slot();
         let hashes_per_slot = hashes_per_tick * ticks_per_slot;
 
         let hash_samples = maybe_hash_samples.unwrap_or(hashes_per_slot);
-        let hash_time_ns = compute_hash_time_ns(hash_samples);
+        let hash_time_ns = compute_hash_time_ns(hash_samples).max(1);
 
-        let my_ns_per_slot = (hash_time_ns * hashes_per_slot) / hash_samples;
-        debug!("computed: ns_per_slot: {}", my_ns_per_slot);
-        let target_ns_per_slot = genesis_config.ns_per_slot() as u64;
+        let my_hash_rate = (hash_samples as u128 * 1_000_000_000 / hash_time_ns as u128) as u64;
+        debug!("computed: hashes per second: {}", my_hash_rate);
+        let target_hash_rate = poh_speed_test.target_hash_rate.unwrap_or_else(|| {
+            (hashes_per_slot as u128 * 1_000_000_000 / genesis_config.ns_per_slot()) as u64
+        });
+        let required_hash_rate =
+            target_hash_rate.saturating_mul(100 + poh_speed_test.margin_percent) / 100;
         debug!(
-            "cluster ns_per_hash: {}ns ns_per_slot: {}",
-            target_ns_per_slot / hashes_per_slot,
-            target_ns_per_slot
+            "cluster hashes per second: {} required: {}",
+            target_hash_rate, required_hash_rate
         );
-        if my_ns_per_slot < target_ns_per_slot {
-            let extra_ns = target_ns_per_slot - my_ns_per_slot;
-            info!("PoH speed check: Will sleep {}ns per slot.", extra_ns);
+        if my_hash_rate >= required_hash_rate {
+            info!(
+                "PoH speed check: {} hashes per second, {} required",
+                my_hash_rate, required_hash_rate
+            );
+        } else if poh_speed_test.warn_only {
+            warn!(
+                "PoH is slower than required! mine: {} hashes per second, required: {}. \
+                This node may produce short leader slots",
+                my_hash_rate, required_hash_rate,
+            );
         } else {
             error!(
-                "PoH is slower than cluster target tick rate! mine: {} cluster: {}. If you wish to continue, try --no-poh-speed-test",
-                my_ns_per_slot, target_ns_per_slot,
+                "PoH is slower than required! mine: {} hashes per second, required: {}. If you wish to continue, try --no-poh-speed-test",
+                my_hash_rate, required_hash_rate,
             );
             abort();
         }
@@ -1166,6 +1354,7 @@ fn new_banks_from_ledger(
     no_poh_speed_test: bool,
     accounts_package_sender: AccountsPackageSender,
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    entry_notifier: Option<EntryNotifierLock>,
 ) -> (
     GenesisConfig,
     BankForks,
@@ -1201,7 +1390,7 @@ fn new_banks_from_ledger(
     }
 
     if !no_poh_speed_test {
-        check_poh_speed(&genesis_config, None);
+        check_poh_speed(&genesis_config, &config.poh_speed_test, None);
     }
 
     let BlockstoreSignals {
@@ -1255,45 +1444,76 @@ fn new_banks_from_ledger(
         shrink_ratio: config.accounts_shrink_ratio,
         accounts_db_test_hash_calculation: config.accounts_db_test_hash_calculation,
         accounts_db_skip_shrink: config.accounts_db_skip_shrink,
+        snapshot_verification: config.snapshot_verification.clone(),
+        quarantine_corrupted_snapshot_archives: true,
+        snapshot_load_progress_callback: Some({
+            let start_progress = start_progress.clone();
+            Arc::new(move |progress| {
+                *start_progress.write().unwrap() = match progress {
+                    SnapshotLoadProgress::Unarchiving { slot } => {
+                        ValidatorStartProgress::UnpackingSnapshot { slot }
+                    }
+                    SnapshotLoadProgress::BuildingAccountsIndex => {
+                        ValidatorStartProgress::BuildingAccountsIndex
+                    }
+                    SnapshotLoadProgress::Verifying => ValidatorStartProgress::VerifyingSnapshot,
+                };
+            })
+        }),
+        replay_progress_callback: Some({
+            let start_progress = start_progress.clone();
+            Arc::new(move |slot, max_root| {
+                *start_progress.write().unwrap() =
+                    ValidatorStartProgress::ProcessingLedger { slot, max_root };
+            })
+        }),
+        entry_notifier,
         ..blockstore_processor::ProcessOptions::default()
     };
 
-    let transaction_history_services =
-        if config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history {
-            initialize_rpc_transaction_history_services(
-                blockstore.clone(),
-                exit,
-                config.rpc_config.enable_cpi_and_log_storage,
-            )
-        } else {
-            TransactionHistoryServices::default()
-        };
+    let transaction_history_services = if config.rpc_addrs.is_some()
+        && config.rpc_config.enable_rpc_transaction_history
+    {
+        initialize_rpc_transaction_history_services(blockstore.clone(), ledger_path, exit, config)
+    } else {
+        TransactionHistoryServices::default()
+    };
 
     let (
         mut bank_forks,
         mut leader_schedule_cache,
         last_full_snapshot_slot,
         starting_snapshot_hashes,
-    ) = bank_forks_utils::load(
-        &genesis_config,
-        &blockstore,
-        config.account_paths.clone(),
-        config.account_shrink_paths.clone(),
-        config.snapshot_config.as_ref(),
-        process_options,
-        transaction_history_services
-            .transaction_status_sender
-            .as_ref(),
-        transaction_history_services
-            .cache_block_meta_sender
-            .as_ref(),
-        accounts_package_sender,
-        accounts_update_notifier,
-    )
-    .unwrap_or_else(|err| {
-        error!("Failed to load ledger: {:?}", err);
-        abort()
-    });
+    ) = loop {
+        let load_result = bank_forks_utils::load(
+            &genesis_config,
+            &blockstore,
+            config.account_paths.clone(),
+            config.account_shrink_paths.clone(),
+            config.snapshot_config.as_ref(),
+            process_options.clone(),
+            transaction_history_services
+                .transaction_status_sender
+                .as_ref(),
+            transaction_history_services
+                .cache_block_meta_sender
+                .as_ref(),
+            accounts_package_sender.clone(),
+            accounts_update_notifier.clone(),
+        );
+        // Every local snapshot archive was corrupted and has been quarantined
+        if let Err(BlockstoreProcessorError::NoLoadableSnapshotArchive) = load_result {
+            if let Some(fetch_snapshot_archives) = config.snapshot_archives_fetcher.as_ref() {
+                if fetch_snapshot_archives(config) {
+                    continue;
+                }
+            }
+        }
+        break load_result.unwrap_or_else(|err| {
+            error!("Failed to load ledger: {:?}", err);
+            abort()
+        });
+    };
 
     if let Some(warp_slot) = config.warp_slot {
         let snapshot_config = config.snapshot_config.as_ref().unwrap_or_else(|| {
@@ -1344,13 +1564,14 @@ fn new_banks_from_ledger(
         );
     }
 
-    let tower = post_process_restored_tower(
+    let mut tower = post_process_restored_tower(
         restored_tower,
         validator_identity,
         vote_account,
         config,
         &bank_forks,
     );
+    tower.set_thresholds(config.tower_thresholds);
 
     info!("Tower state: {:?}", tower);
 
@@ -1453,19 +1674,48 @@ fn backup_and_clear_blockstore(ledger_path: &Path, start_slot: Slot, shred_versi
 
 fn initialize_rpc_transaction_history_services(
     blockstore: Arc<Blockstore>,
+    ledger_path: &Path,
     exit: &Arc<AtomicBool>,
-    enable_cpi_and_log_storage: bool,
+    config: &ValidatorConfig,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
-    let (transaction_status_sender, transaction_status_receiver) = unbounded();
+    let (transaction_status_sender, transaction_status_receiver) =
+        match config.transaction_status_channel_capacity {
+            Some(capacity) => bounded(capacity),
+            None => unbounded(),
+        };
+    let dropped_slots = DroppedTransactionStatusSlots::default();
+    let spill = (config.transaction_status_overflow_policy
+        == TransactionStatusOverflowPolicy::SpillToDisk)
+        .then(|| {
+            let spill_path = ledger_path.join("transaction_status_spill");
+            // As many messages wait to be spilled as wait in the channel, which only overflows if
+            // bounded
+            let queue_capacity = config.transaction_status_channel_capacity.unwrap_or(1);
+            Arc::new(
+                TransactionStatusSpill::new(&spill_path, queue_capacity, dropped_slots.clone())
+                    .unwrap_or_else(|err| {
+                        panic!(
+                            "Failed to create transaction status spill directory {:?}: {:?}",
+                            spill_path, err
+                        )
+                    }),
+            )
+        });
     let transaction_status_sender = Some(TransactionStatusSender {
         sender: transaction_status_sender,
-        enable_cpi_and_log_storage,
+        enable_cpi_and_log_storage: config.rpc_config.enable_cpi_and_log_storage,
+        token_program_ids: Arc::new(config.token_program_ids.clone()),
+        overflow_policy: config.transaction_status_overflow_policy,
+        spill: spill.clone(),
+        dropped_slots: dropped_slots.clone(),
     });
     let transaction_status_service = Some(TransactionStatusService::new(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
         blockstore.clone(),
+        spill,
+        dropped_slots,
         exit,
     ));
 
@@ -1750,6 +2000,39 @@ mod tests {
         remove_dir_all(validator_ledger_path).unwrap();
     }
 
+    #[test]
+    fn test_validator_start_progress_display() {
+        let rpc_addr = "127.0.0.1:8899".parse().unwrap();
+        assert_eq!(
+            ValidatorStartProgress::DownloadingSnapshot {
+                slot: 42,
+                rpc_addr,
+                downloaded_bytes: 0,
+                total_bytes: 0,
+            }
+            .to_string(),
+            "Downloading snapshot for slot 42 from 127.0.0.1:8899"
+        );
+        assert_eq!(
+            ValidatorStartProgress::DownloadingSnapshot {
+                slot: 42,
+                rpc_addr,
+                downloaded_bytes: 25,
+                total_bytes: 100,
+            }
+            .to_string(),
+            "Downloading snapshot for slot 42 from 127.0.0.1:8899: 25.0% of 100 bytes"
+        );
+        assert_eq!(
+            ValidatorStartProgress::ProcessingLedger {
+                slot: 10,
+                max_root: 20
+            }
+            .to_string(),
+            "Processing the ledger: slot 10 of 20"
+        );
+    }
+
     #[test]
     fn test_backup_and_clear_blockstore() {
         use std::time::Instant;
@@ -1955,7 +2238,52 @@ mod tests {
             poh_config,
             ..GenesisConfig::default()
         };
-        check_poh_speed(&genesis_config, Some(10_000));
+        check_poh_speed(
+            &genesis_config,
+            &PohSpeedTestConfig::default(),
+            Some(10_000),
+        );
+    }
+
+    #[test]
+    fn test_poh_speed_warn_only() {
+        solana_logger::setup();
+        let poh_config = PohConfig {
+            target_tick_duration: Duration::from_millis(solana_sdk::clock::MS_PER_TICK),
+            hashes_per_tick: Some(100 * solana_sdk::clock::DEFAULT_HASHES_PER_TICK),
+            ..PohConfig::default()
+        };
+        let genesis_config = GenesisConfig {
+            poh_config,
+            ..GenesisConfig::default()
+        };
+        let poh_speed_test = PohSpeedTestConfig {
+            warn_only: true,
+            ..PohSpeedTestConfig::default()
+        };
+        check_poh_speed(&genesis_config, &poh_speed_test, Some(10_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_poh_speed_target_hash_rate() {
+        solana_logger::setup();
+        // The genesis config doesn't require PoH to be fast, but the override does
+        let poh_config = PohConfig {
+            target_tick_duration: Duration::from_millis(solana_sdk::clock::MS_PER_TICK),
+            hashes_per_tick: Some(1),
+            ..PohConfig::default()
+        };
+        let genesis_config = GenesisConfig {
+            poh_config,
+            ..GenesisConfig::default()
+        };
+        let poh_speed_test = PohSpeedTestConfig {
+            target_hash_rate: Some(u64::MAX / 200),
+            margin_percent: 10,
+            ..PohSpeedTestConfig::default()
+        };
+        check_poh_speed(&genesis_config, &poh_speed_test, Some(10_000));
     }
 
     #[test]
@@ -1969,6 +2297,10 @@ mod tests {
             poh_config,
             ..GenesisConfig::default()
         };
-        check_poh_speed(&genesis_config, Some(10_000));
+        check_poh_speed(
+            &genesis_config,
+            &PohSpeedTestConfig::default(),
+            Some(10_000),
+        );
     }
 }
@@ -3,9 +3,17 @@ use solana_gossip::cluster_info::ClusterInfo;
 use solana_measure::measure::Measure;
 use solana_poh::poh_recorder::PohRecorder;
 use solana_runtime::bank_forks::BankForks;
-use solana_sdk::{clock::Slot, transaction::Transaction};
+use solana_sdk::{
+    clock::Slot,
+    signature::Signature,
+    signer::{Signer, SignerError},
+    transaction::Transaction,
+};
 use std::{
-    sync::{mpsc::Receiver, Arc, Mutex, RwLock},
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex, RwLock,
+    },
     thread::{self, Builder, JoinHandle},
 };
 
@@ -28,6 +36,13 @@ impl VoteOp {
             VoteOp::RefreshVote { tx, .. } => tx,
         }
     }
+
+    fn tx_mut(&mut self) -> &mut Transaction {
+        match self {
+            VoteOp::PushVote { tx, .. } => tx,
+            VoteOp::RefreshVote { tx, .. } => tx,
+        }
+    }
 }
 
 pub struct VotingService {
@@ -41,6 +56,8 @@ impl VotingService {
         poh_recorder: Arc<Mutex<PohRecorder>>,
         tower_storage: Arc<dyn TowerStorage>,
         bank_forks: Arc<RwLock<BankForks>>,
+        remote_authorized_voters: Vec<Arc<dyn Signer + Send + Sync>>,
+        vote_signature_sender: Sender<Signature>,
     ) -> Self {
         let thread_hdl = Builder::new()
             .name("sol-vote-service".to_string())
@@ -52,6 +69,8 @@ impl VotingService {
                         &cluster_info,
                         &poh_recorder,
                         tower_storage.as_ref(),
+                        &remote_authorized_voters,
+                        &vote_signature_sender,
                         vote_op,
                         send_to_tpu_vote_port,
                     );
@@ -65,7 +84,9 @@ impl VotingService {
         cluster_info: &ClusterInfo,
         poh_recorder: &Mutex<PohRecorder>,
         tower_storage: &dyn TowerStorage,
-        vote_op: VoteOp,
+        remote_authorized_voters: &[Arc<dyn Signer + Send + Sync>],
+        vote_signature_sender: &Sender<Signature>,
+        mut vote_op: VoteOp,
         send_to_tpu_vote_port: bool,
     ) {
         if let VoteOp::PushVote { saved_tower, .. } = &vote_op {
@@ -78,6 +99,17 @@ impl VotingService {
             inc_new_counter_info!("tower_save-ms", measure.as_ms() as usize);
         }
 
+        // Votes for a remote authorized voter are left unsigned by replay
+        if !vote_op.tx().is_signed() {
+            if let Err(err) = Self::sign_vote(vote_op.tx_mut(), remote_authorized_voters) {
+                // The vote is retried when the last vote is refreshed
+                warn!("Unable to sign vote: {}", err);
+                inc_new_counter_warn!("voting_service-vote_signing_failed", 1);
+                return;
+            }
+            let _ = vote_signature_sender.send(vote_op.tx().signatures[0]);
+        }
+
         let target_address = if send_to_tpu_vote_port {
             crate::banking_stage::next_leader_tpu_vote(cluster_info, poh_recorder)
         } else {
@@ -100,6 +132,20 @@ impl VotingService {
         }
     }
 
+    fn sign_vote(
+        tx: &mut Transaction,
+        remote_authorized_voters: &[Arc<dyn Signer + Send + Sync>],
+    ) -> Result<(), SignerError> {
+        let authorized_voter_pubkey = tx.message.account_keys[0];
+        let authorized_voter: &dyn Signer = remote_authorized_voters
+            .iter()
+            .find(|signer| signer.pubkey() == authorized_voter_pubkey)
+            .ok_or(SignerError::KeypairPubkeyMismatch)?
+            .as_ref();
+        let recent_blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[authorized_voter], recent_blockhash)
+    }
+
     pub fn join(self) -> thread::Result<()> {
         self.thread_hdl.join()
     }
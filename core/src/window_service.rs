@@ -17,6 +17,7 @@ use {
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         blockstore::{self, Blockstore, BlockstoreInsertionMetrics, MAX_DATA_SHREDS_PER_SLOT},
+        blockstore_meta::EquivocationEvidence,
         leader_schedule_cache::LeaderScheduleCache,
         shred::{Nonce, Shred},
     },
@@ -210,6 +211,7 @@ pub(crate) fn should_retransmit_and_persist(
 fn run_check_duplicate(
     cluster_info: &ClusterInfo,
     blockstore: &Blockstore,
+    leader_schedule_cache: &LeaderScheduleCache,
     shred_receiver: &CrossbeamReceiver<Shred>,
     duplicate_slot_sender: &DuplicateSlotSender,
 ) -> Result<()> {
@@ -223,6 +225,25 @@ fn run_check_duplicate(
                 shred.is_data(),
             ) {
                 cluster_info.push_duplicate_shred(&shred, &existing_shred_payload)?;
+                if let Some(leader_pubkey) = leader_schedule_cache.slot_leader_at(shred_slot, None)
+                {
+                    let evidence = EquivocationEvidence::DuplicateBlock {
+                        shred1: existing_shred_payload.clone(),
+                        shred2: shred.payload.clone(),
+                    };
+                    // The evidence is only informational, failing to store it must not keep
+                    // the duplicate slot from being recorded and reported
+                    if let Err(err) = blockstore.store_equivocation_evidence(
+                        shred_slot,
+                        &leader_pubkey,
+                        &evidence,
+                    ) {
+                        warn!(
+                            "failed to store duplicate block evidence for slot {}: {:?}",
+                            shred_slot, err
+                        );
+                    }
+                }
                 blockstore.store_duplicate_slot(
                     shred_slot,
                     existing_shred_payload,
@@ -498,6 +519,7 @@ impl WindowService {
             cluster_info,
             exit.clone(),
             blockstore.clone(),
+            leader_schedule_cache.clone(),
             duplicate_receiver,
             duplicate_slots_sender,
         );
@@ -536,6 +558,7 @@ impl WindowService {
         cluster_info: Arc<ClusterInfo>,
         exit: Arc<AtomicBool>,
         blockstore: Arc<Blockstore>,
+        leader_schedule_cache: Arc<LeaderScheduleCache>,
         duplicate_receiver: CrossbeamReceiver<Shred>,
         duplicate_slot_sender: DuplicateSlotSender,
     ) -> JoinHandle<()> {
@@ -553,6 +576,7 @@ impl WindowService {
                 if let Err(e) = run_check_duplicate(
                     &cluster_info,
                     &blockstore,
+                    &leader_schedule_cache,
                     &duplicate_receiver,
                     &duplicate_slot_sender,
                 ) {
@@ -889,6 +913,11 @@ mod test {
         blockstore
             .insert_shreds(shreds.clone(), None, false)
             .unwrap();
+        let leader_pubkey = solana_sdk::pubkey::new_rand();
+        let bank = Bank::new_for_tests(
+            &create_genesis_config_with_leader(100, &leader_pubkey, 10).genesis_config,
+        );
+        let leader_schedule_cache = LeaderScheduleCache::new_from_bank(&bank);
         let mut duplicate_shred = shreds[1].clone();
         duplicate_shred.set_slot(shreds[0].slot());
         let duplicate_shred_slot = duplicate_shred.slot();
@@ -904,6 +933,7 @@ mod test {
         run_check_duplicate(
             &cluster_info,
             &blockstore,
+            &leader_schedule_cache,
             &receiver,
             &duplicate_slot_sender,
         )
@@ -913,6 +943,12 @@ mod test {
             duplicate_slot_receiver.try_recv().unwrap(),
             duplicate_shred_slot
         );
+        assert!(matches!(
+            blockstore
+                .get_equivocation_evidence(duplicate_shred_slot, &leader_pubkey)
+                .unwrap(),
+            Some(EquivocationEvidence::DuplicateBlock { .. })
+        ));
     }
 
     #[test]
@@ -1,4 +1,7 @@
-use solana_client::{pubsub_client::PubsubClient, rpc_client::RpcClient, rpc_response::SlotInfo};
+use futures_util::StreamExt;
+use solana_client::{
+    nonblocking, pubsub_client::PubsubClient, rpc_client::RpcClient, rpc_response::SlotInfo,
+};
 use solana_core::test_validator::TestValidator;
 use solana_rpc::{
     optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
@@ -144,3 +147,73 @@ fn test_slot_subscription() {
 
     assert_eq!(errors, [].to_vec());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_nonblocking_slot_subscription_reconnects() {
+    let ip_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let pubsub_addr = SocketAddr::new(
+        ip_addr,
+        solana_net_utils::find_available_port_in_range(ip_addr, (10_000, 11_000)).unwrap(),
+    );
+    let exit = Arc::new(AtomicBool::new(false));
+    let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+    let bank = Bank::new_for_tests(&genesis_config);
+    let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+    let optimistically_confirmed_bank =
+        OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+    let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+        &exit,
+        bank_forks,
+        Arc::new(RwLock::new(BlockCommitmentCache::default())),
+        optimistically_confirmed_bank,
+    ));
+    let (trigger, pubsub_service) =
+        PubSubService::new(PubSubConfig::default(), &subscriptions, pubsub_addr);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+
+    let client = nonblocking::pubsub_client::PubsubClient::new(&format!("ws://{}/", pubsub_addr))
+        .await
+        .unwrap();
+    let mut slots = client.slot_subscribe().await.unwrap();
+
+    subscriptions.notify_slot(1, 0, 0);
+    let actual = tokio::time::timeout(Duration::from_secs(5), slots.next())
+        .await
+        .unwrap();
+    assert_eq!(
+        actual,
+        Some(SlotInfo {
+            slot: 1,
+            parent: 0,
+            root: 0,
+        })
+    );
+
+    // Restart the service, the client reconnects and subscribes again
+    trigger.cancel();
+    pubsub_service.close().unwrap();
+    let (trigger, pubsub_service) =
+        PubSubService::new(PubSubConfig::default(), &subscriptions, pubsub_addr);
+
+    let expected = SlotInfo {
+        slot: 2,
+        parent: 1,
+        root: 1,
+    };
+    let now = Instant::now();
+    loop {
+        // Notifications sent before the client subscribed again are lost
+        subscriptions.notify_slot(expected.slot, expected.parent, expected.root);
+        if let Ok(actual) = tokio::time::timeout(Duration::from_millis(500), slots.next()).await {
+            assert_eq!(actual, Some(expected));
+            break;
+        }
+        assert!(now.elapsed() < Duration::from_secs(30));
+    }
+
+    exit.store(true, Ordering::Relaxed);
+    trigger.cancel();
+    drop(slots);
+    client.shutdown().await.unwrap();
+    pubsub_service.close().unwrap();
+}
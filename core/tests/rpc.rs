@@ -9,7 +9,7 @@ use solana_account_decoder::UiAccount;
 use solana_client::{
     client_error::{ClientErrorKind, Result as ClientResult},
     rpc_client::RpcClient,
-    rpc_config::{RpcAccountInfoConfig, RpcSignatureSubscribeConfig},
+    rpc_config::{RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcSignatureSubscribeConfig},
     rpc_request::RpcError,
     rpc_response::{Response as RpcResponse, RpcSignatureResult, SlotUpdate},
     tpu_client::{TpuClient, TpuClientConfig},
@@ -114,6 +114,7 @@ fn test_rpc_send_tx() {
         encoding: Some(UiAccountEncoding::Base64),
         commitment: None,
         data_slice: None,
+        with_last_modified_slot: None,
     };
     let req = json_req!(
         "getAccountInfo",
@@ -297,9 +298,12 @@ fn test_rpc_subscriptions() {
             let mut client_sub = client
                 .account_subscribe(
                     pubkey,
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::confirmed()),
-                        ..RpcAccountInfoConfig::default()
+                    Some(RpcAccountSubscribeConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        changed_data_only: None,
                     }),
                 )
                 .unwrap_or_else(|err| panic!("acct sub err: {:#?}", err));
@@ -69,7 +69,7 @@ mod tests {
         snapshot_package::{
             AccountsPackage, PendingSnapshotPackage, SnapshotPackage, SnapshotType,
         },
-        snapshot_utils::{self, ArchiveFormat, SnapshotVersion},
+        snapshot_utils::{self, ArchiveFormat, SnapshotVerification, SnapshotVersion},
         status_cache::MAX_CACHE_ENTRIES,
     };
     use solana_sdk::{
@@ -100,6 +100,7 @@ mod tests {
     DEFINE_SNAPSHOT_VERSION_PARAMETERIZED_TEST_FUNCTIONS!(V1_2_0, Devnet, V1_2_0_Devnet);
     DEFINE_SNAPSHOT_VERSION_PARAMETERIZED_TEST_FUNCTIONS!(V1_2_0, Testnet, V1_2_0_Testnet);
     DEFINE_SNAPSHOT_VERSION_PARAMETERIZED_TEST_FUNCTIONS!(V1_2_0, MainnetBeta, V1_2_0_MainnetBeta);
+    DEFINE_SNAPSHOT_VERSION_PARAMETERIZED_TEST_FUNCTIONS!(V1_3_0, Development, V1_3_0_Development);
 
     struct SnapshotTestConfig {
         accounts_dir: TempDir,
@@ -204,6 +205,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
 
@@ -841,6 +844,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )?;
 
         assert_eq!(bank, &deserialized_bank);
@@ -1021,6 +1026,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
 
@@ -7,11 +7,32 @@ use solana_core::serve_repair::RepairProtocol;
 use solana_gossip::{contact_info::ContactInfo, gossip_service::discover};
 use solana_sdk::pubkey::Pubkey;
 use solana_streamer::socket::SocketAddrSpace;
+use std::fs::File;
 use std::net::{SocketAddr, UdpSocket};
 use std::process::exit;
 use std::str::FromStr;
+use std::thread::sleep;
 use std::time::{Duration, Instant};
 
+mod pcap;
+
+/// Options for replaying a packet capture with the "pcap" data type
+struct PcapReplayConfig {
+    /// Only replay packets captured with this destination port
+    dst_port: Option<u16>,
+    /// Multiple of the captured packet rate to replay at, as fast as possible if 0
+    speed: f64,
+}
+
+impl Default for PcapReplayConfig {
+    fn default() -> Self {
+        Self {
+            dst_port: None,
+            speed: 1.0,
+        }
+    }
+}
+
 fn get_repair_contact(nodes: &[ContactInfo]) -> ContactInfo {
     let source = thread_rng().gen_range(0, nodes.len());
     let mut contact = nodes[source].clone();
@@ -19,6 +40,7 @@ fn get_repair_contact(nodes: &[ContactInfo]) -> ContactInfo {
     contact
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_dos(
     nodes: &[ContactInfo],
     iterations: usize,
@@ -27,6 +49,7 @@ fn run_dos(
     data_size: usize,
     mode: String,
     data_input: Option<String>,
+    pcap_replay: PcapReplayConfig,
 ) {
     let mut target = None;
     let mut rpc_client = None;
@@ -62,6 +85,7 @@ fn run_dos(
     let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
 
     let mut data = Vec::new();
+    let mut packets = Vec::new();
 
     match data_type.as_str() {
         "repair_highest" => {
@@ -88,6 +112,15 @@ fn run_dos(
             info!("{:?}", tx);
             data = bincode::serialize(&tx).unwrap();
         }
+        "pcap" => {
+            let path = data_input.as_ref().expect("pcap file to replay");
+            let file = File::open(path).expect("open pcap file");
+            packets = pcap::read_udp_packets(file, pcap_replay.dst_port).expect("read pcap file");
+            if packets.is_empty() {
+                panic!("no UDP packets to replay in {}", path);
+            }
+            info!("Replaying {} packets from {}", packets.len(), path);
+        }
         "get_account_info" => {}
         "get_program_accounts" => {}
         &_ => {
@@ -98,6 +131,8 @@ fn run_dos(
     let mut last_log = Instant::now();
     let mut count = 0;
     let mut error_count = 0;
+    let mut packet_index = 0;
+    let mut replay_start = Instant::now();
     loop {
         if mode == "rpc" {
             match data_type.as_str() {
@@ -122,6 +157,24 @@ fn run_dos(
                     panic!("unsupported data type");
                 }
             }
+        } else if data_type == "pcap" {
+            // Start over from the beginning of the capture once it has been fully replayed
+            if packet_index == packets.len() {
+                packet_index = 0;
+                replay_start = Instant::now();
+            }
+            let packet = &packets[packet_index];
+            if let Some(offset) = pcap::replay_offset(&packets[0], packet, pcap_replay.speed) {
+                let elapsed = replay_start.elapsed();
+                if elapsed < offset {
+                    sleep(offset - elapsed);
+                }
+            }
+            let res = socket.send_to(&packet.payload, target);
+            if res.is_err() {
+                error_count += 1;
+            }
+            packet_index += 1;
         } else {
             if data_type == "random" {
                 thread_rng().fill(&mut data[..]);
@@ -192,6 +245,7 @@ fn main() {
                     "get_account_info",
                     "get_program_accounts",
                     "transaction",
+                    "pcap",
                 ])
                 .help("Type of data to send"),
         )
@@ -200,7 +254,25 @@ fn main() {
                 .long("data-input")
                 .takes_value(true)
                 .value_name("TYPE")
-                .help("Data to send"),
+                .help("Data to send, or the pcap file to replay with the pcap data type"),
+        )
+        .arg(
+            Arg::with_name("replay_speed")
+                .long("replay-speed")
+                .takes_value(true)
+                .value_name("MULTIPLE")
+                .default_value("1.0")
+                .help(
+                    "Replay the pcap file at this multiple of its captured packet rate, \
+                     0 replays as fast as possible",
+                ),
+        )
+        .arg(
+            Arg::with_name("pcap_port")
+                .long("pcap-port")
+                .takes_value(true)
+                .value_name("PORT")
+                .help("Only replay captured UDP packets sent to this port, e.g. the TPU port"),
         )
         .arg(
             Arg::with_name("skip_gossip")
@@ -229,6 +301,10 @@ fn main() {
     let mode = value_t_or_exit!(matches, "mode", String);
     let data_type = value_t_or_exit!(matches, "data_type", String);
     let data_input = value_t!(matches, "data_input", String).ok();
+    let pcap_replay = PcapReplayConfig {
+        dst_port: value_t!(matches, "pcap_port", u16).ok(),
+        speed: value_t_or_exit!(matches, "replay_speed", f64),
+    };
 
     let mut nodes = vec![];
     if !skip_gossip {
@@ -262,6 +338,7 @@ fn main() {
         data_size,
         mode,
         data_input,
+        pcap_replay,
     );
 }
 
@@ -285,6 +362,7 @@ pub mod test {
             10,
             "tvu".to_string(),
             None,
+            PcapReplayConfig::default(),
         );
 
         run_dos(
@@ -295,6 +373,7 @@ pub mod test {
             10,
             "repair".to_string(),
             None,
+            PcapReplayConfig::default(),
         );
 
         run_dos(
@@ -305,6 +384,7 @@ pub mod test {
             10,
             "serve_repair".to_string(),
             None,
+            PcapReplayConfig::default(),
         );
     }
 }
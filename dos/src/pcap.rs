@@ -0,0 +1,262 @@
+//! Minimal reader for libpcap capture files, extracting the UDP datagrams to replay
+use std::{
+    convert::TryInto,
+    io::{self, Read},
+    time::Duration,
+};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAP_GLOBAL_HEADER_LEN: usize = 24;
+const PCAP_RECORD_HEADER_LEN: usize = 16;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const IP_PROTOCOL_UDP: u8 = 17;
+const UDP_HEADER_LEN: usize = 8;
+
+/// A UDP datagram read from a capture
+#[derive(Debug, PartialEq)]
+pub struct CapturedPacket {
+    /// Capture time of the packet, relative to the unix epoch
+    pub timestamp: Duration,
+    pub dst_port: u16,
+    pub payload: Vec<u8>,
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Read the UDP datagrams of a capture, only keeping those sent to `dst_port` if set.  Packets
+/// that are not UDP over IPv4 or IPv6, or are fragmented, are skipped
+pub fn read_udp_packets<R: Read>(
+    mut reader: R,
+    dst_port: Option<u16>,
+) -> io::Result<Vec<CapturedPacket>> {
+    let mut header = [0u8; PCAP_GLOBAL_HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    let (big_endian, nanos) = match magic {
+        PCAP_MAGIC_MICROS => (false, false),
+        PCAP_MAGIC_NANOS => (false, true),
+        _ if magic.swap_bytes() == PCAP_MAGIC_MICROS => (true, false),
+        _ if magic.swap_bytes() == PCAP_MAGIC_NANOS => (true, true),
+        _ => return Err(invalid_data("not a pcap file")),
+    };
+    let read_u32 = |bytes: &[u8]| {
+        let bytes = bytes.try_into().unwrap();
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+    let link_type = read_u32(&header[20..24]);
+
+    let mut packets = Vec::new();
+    let mut record_header = [0u8; PCAP_RECORD_HEADER_LEN];
+    loop {
+        match reader.read_exact(&mut record_header) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err),
+        }
+        let seconds = read_u32(&record_header[0..4]);
+        let fraction = read_u32(&record_header[4..8]);
+        let captured_len = read_u32(&record_header[8..12]) as usize;
+
+        let mut frame = vec![0u8; captured_len];
+        reader.read_exact(&mut frame)?;
+
+        let timestamp = Duration::from_secs(u64::from(seconds))
+            + if nanos {
+                Duration::from_nanos(u64::from(fraction))
+            } else {
+                Duration::from_micros(u64::from(fraction))
+            };
+        if let Some((port, payload)) = parse_udp(link_type, &frame)? {
+            if dst_port.map(|dst_port| dst_port == port).unwrap_or(true) {
+                packets.push(CapturedPacket {
+                    timestamp,
+                    dst_port: port,
+                    payload: payload.to_vec(),
+                });
+            }
+        }
+    }
+    Ok(packets)
+}
+
+/// Extract the destination port and payload of a UDP datagram from a link layer frame
+fn parse_udp(link_type: u32, frame: &[u8]) -> io::Result<Option<(u16, &[u8])>> {
+    let ip_offset = match link_type {
+        LINKTYPE_NULL => 4,
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            while frame.len() >= offset + 2
+                && u16::from_be_bytes([frame[offset], frame[offset + 1]]) == ETHERTYPE_VLAN
+            {
+                offset += 4;
+            }
+            offset + 2
+        }
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => 0,
+        LINKTYPE_LINUX_SLL => 16,
+        _ => return Err(invalid_data("unsupported pcap link type")),
+    };
+    let ip = match frame.get(ip_offset..) {
+        Some(ip) if !ip.is_empty() => ip,
+        _ => return Ok(None),
+    };
+
+    let udp = match ip[0] >> 4 {
+        4 => {
+            let header_len = usize::from(ip[0] & 0x0f) * 4;
+            if ip.len() < 20 || ip.len() < header_len || ip[9] != IP_PROTOCOL_UDP {
+                return Ok(None);
+            }
+            // Skip fragments, they can't be replayed on their own
+            let flags_and_offset = u16::from_be_bytes([ip[6], ip[7]]);
+            if flags_and_offset & 0x3fff != 0 {
+                return Ok(None);
+            }
+            &ip[header_len..]
+        }
+        6 => {
+            if ip.len() < 40 || ip[6] != IP_PROTOCOL_UDP {
+                return Ok(None);
+            }
+            &ip[40..]
+        }
+        _ => return Ok(None),
+    };
+    if udp.len() < UDP_HEADER_LEN {
+        return Ok(None);
+    }
+    let dst_port = u16::from_be_bytes([udp[2], udp[3]]);
+    let udp_len = usize::from(u16::from_be_bytes([udp[4], udp[5]]));
+    if udp_len < UDP_HEADER_LEN || udp_len > udp.len() {
+        // Truncated by the capture's snap length
+        return Ok(None);
+    }
+    Ok(Some((dst_port, &udp[UDP_HEADER_LEN..udp_len])))
+}
+
+/// How long after the start of a replay `packet` should be sent to reproduce the capture's
+/// timing at `speed` times the original rate, or `None` to send as fast as possible
+pub fn replay_offset(
+    first_packet: &CapturedPacket,
+    packet: &CapturedPacket,
+    speed: f64,
+) -> Option<Duration> {
+    if speed <= 0.0 {
+        return None;
+    }
+    let offset = packet.timestamp.saturating_sub(first_packet.timestamp);
+    Some(Duration::from_secs_f64(offset.as_secs_f64() / speed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udp_frame(protocol: u8, dst_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&0x0800u16.to_be_bytes());
+        let total_len = (20 + UDP_HEADER_LEN + payload.len()) as u16;
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0, 64, protocol, 0, 0]);
+        frame.extend_from_slice(&[127, 0, 0, 1, 127, 0, 0, 1]);
+        frame.extend_from_slice(&1234u16.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&((UDP_HEADER_LEN + payload.len()) as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn pcap(frames: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut pcap = Vec::new();
+        pcap.extend_from_slice(&PCAP_MAGIC_MICROS.to_le_bytes());
+        pcap.extend_from_slice(&2u16.to_le_bytes());
+        pcap.extend_from_slice(&4u16.to_le_bytes());
+        pcap.extend_from_slice(&[0; 8]);
+        pcap.extend_from_slice(&65535u32.to_le_bytes());
+        pcap.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+        for (seconds, micros, frame) in frames {
+            pcap.extend_from_slice(&seconds.to_le_bytes());
+            pcap.extend_from_slice(&micros.to_le_bytes());
+            pcap.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            pcap.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            pcap.extend_from_slice(frame);
+        }
+        pcap
+    }
+
+    #[test]
+    fn test_read_udp_packets() {
+        let pcap = pcap(&[
+            (1, 500, udp_frame(IP_PROTOCOL_UDP, 8003, &[1, 2, 3])),
+            (1, 700, udp_frame(6, 8003, &[4, 5, 6])),
+            (2, 0, udp_frame(IP_PROTOCOL_UDP, 8004, &[7, 8])),
+        ]);
+
+        let packets = read_udp_packets(&pcap[..], None).unwrap();
+        assert_eq!(
+            packets,
+            vec![
+                CapturedPacket {
+                    timestamp: Duration::from_micros(1_000_500),
+                    dst_port: 8003,
+                    payload: vec![1, 2, 3],
+                },
+                CapturedPacket {
+                    timestamp: Duration::from_secs(2),
+                    dst_port: 8004,
+                    payload: vec![7, 8],
+                },
+            ]
+        );
+
+        let packets = read_udp_packets(&pcap[..], Some(8004)).unwrap();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].payload, vec![7, 8]);
+    }
+
+    #[test]
+    fn test_read_udp_packets_invalid() {
+        assert!(read_udp_packets(&[0u8; PCAP_GLOBAL_HEADER_LEN][..], None).is_err());
+
+        // Truncated record
+        let mut pcap = pcap(&[(1, 0, udp_frame(IP_PROTOCOL_UDP, 8003, &[1, 2, 3]))]);
+        pcap.pop();
+        assert!(read_udp_packets(&pcap[..], None).is_err());
+    }
+
+    #[test]
+    fn test_replay_offset() {
+        let packet = |micros| CapturedPacket {
+            timestamp: Duration::from_micros(micros),
+            dst_port: 0,
+            payload: vec![],
+        };
+        let first_packet = packet(1_000_000);
+        assert_eq!(
+            replay_offset(&first_packet, &packet(1_500_000), 1.0),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            replay_offset(&first_packet, &packet(1_500_000), 2.0),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(replay_offset(&first_packet, &packet(1_500_000), 0.0), None);
+    }
+}
@@ -267,6 +267,7 @@ pub fn download_snapshot_archive<'a, 'b>(
 
     for archive_format in [
         ArchiveFormat::TarZstd,
+        ArchiveFormat::TarZstdSeekable,
         ArchiveFormat::TarGzip,
         ArchiveFormat::TarBzip2,
         ArchiveFormat::Tar, // `solana-test-validator` creates uncompressed snapshots
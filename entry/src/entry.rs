@@ -5,6 +5,7 @@
 use crate::poh::Poh;
 use dlopen::symbor::{Container, SymBorApi, Symbol};
 use dlopen_derive::SymBorApi;
+use lazy_static::lazy_static;
 use log::*;
 use rand::{thread_rng, Rng};
 use rayon::prelude::*;
@@ -22,7 +23,7 @@ use solana_sdk::timing;
 use solana_sdk::transaction::{Result, SanitizedTransaction, Transaction, VersionedTransaction};
 use std::cell::RefCell;
 use std::ffi::OsStr;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::Once;
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
@@ -35,6 +36,16 @@ thread_local!(static PAR_THREAD_POOL: RefCell<ThreadPool> = RefCell::new(rayon::
                     .build()
                     .unwrap()));
 
+lazy_static! {
+    // Pipelined PoH verification runs on its own pool, concurrently with the replay of the
+    // entries' transactions on the caller's thread pools
+    static ref PIPELINED_VERIFY_THREAD_POOL: ThreadPool = rayon::ThreadPoolBuilder::new()
+        .num_threads(get_thread_count())
+        .thread_name(|ix| format!("entry_verify_{}", ix))
+        .build()
+        .unwrap();
+}
+
 pub type EntrySender = Sender<Vec<Entry>>;
 pub type EntryReceiver = Receiver<Vec<Entry>>;
 
@@ -233,9 +244,15 @@ pub struct GpuVerificationData {
     verifications: Option<Vec<(VerifyAction, Hash)>>,
 }
 
+pub struct PipelinedVerificationData {
+    // Receives the verification result and the time spent hashing, in microseconds
+    receiver: Option<Receiver<(bool, u64)>>,
+}
+
 pub enum DeviceVerificationData {
     Cpu(),
     Gpu(GpuVerificationData),
+    Pipelined(PipelinedVerificationData),
 }
 
 pub struct EntryVerificationState {
@@ -306,6 +323,22 @@ impl EntryVerificationState {
                 };
                 res
             }
+            DeviceVerificationData::Pipelined(verification_state) => {
+                let (res, verify_time_us) = verification_state
+                    .receiver
+                    .take()
+                    .unwrap()
+                    .recv()
+                    .expect("pipelined PoH verification result");
+                self.poh_duration_us += verify_time_us;
+
+                self.verification_status = if res {
+                    EntryVerificationStatus::Success
+                } else {
+                    EntryVerificationStatus::Failure
+                };
+                res
+            }
             DeviceVerificationData::Cpu() => {
                 self.verification_status == EntryVerificationStatus::Success
             }
@@ -361,6 +394,14 @@ pub trait EntrySlice {
     fn verify_cpu_x86_simd(&self, start_hash: &Hash, simd_len: usize) -> EntryVerificationState;
     fn start_verify(&self, start_hash: &Hash, recyclers: VerifyRecyclers)
         -> EntryVerificationState;
+    /// Like `start_verify`, but when no GPU is available the CPU verification runs in the
+    /// background, so the caller can replay the entries while their hashes are verified.
+    /// `finish_verify()` must be called to obtain the result.
+    fn start_verify_pipelined(
+        &self,
+        start_hash: &Hash,
+        recyclers: VerifyRecyclers,
+    ) -> EntryVerificationState;
     fn verify(&self, start_hash: &Hash) -> bool;
     /// Checks that each entry tick has the correct number of hashes. Entry slices do not
     /// necessarily end in a tick, so `tick_hash_count` is used to carry over the hash count
@@ -613,6 +654,86 @@ impl EntrySlice for [Entry] {
         }
     }
 
+    fn start_verify_pipelined(
+        &self,
+        start_hash: &Hash,
+        recyclers: VerifyRecyclers,
+    ) -> EntryVerificationState {
+        if perf_libs::api().is_some() {
+            // The GPU path is already asynchronous
+            return self.start_verify(start_hash, recyclers);
+        }
+        let start = Instant::now();
+
+        // Gather everything needed to verify each entry up front, so the background
+        // verification doesn't borrow the entries
+        let verifications: Vec<_> = PAR_THREAD_POOL.with(|thread_pool| {
+            thread_pool.borrow().install(|| {
+                self.par_iter()
+                    .enumerate()
+                    .map(|(i, entry)| {
+                        let entry_start_hash = if i == 0 {
+                            *start_hash
+                        } else {
+                            self[i - 1].hash
+                        };
+                        let action = if entry.transactions.is_empty() {
+                            if entry.num_hashes == 0 {
+                                VerifyAction::None
+                            } else {
+                                VerifyAction::Tick
+                            }
+                        } else {
+                            VerifyAction::Mixin(hash_transactions(&entry.transactions))
+                        };
+                        (entry_start_hash, entry.num_hashes, action, entry.hash)
+                    })
+                    .collect()
+            })
+        });
+
+        let (sender, receiver) = channel();
+        PIPELINED_VERIFY_THREAD_POOL.spawn(move || {
+            let verify_start = Instant::now();
+            let res = verifications.into_par_iter().all(
+                |(entry_start_hash, num_hashes, action, expected)| {
+                    let actual = match action {
+                        VerifyAction::Mixin(mixin) => {
+                            let mut poh = Poh::new(entry_start_hash, None);
+                            poh.hash(num_hashes.saturating_sub(1));
+                            poh.record(mixin).unwrap().hash
+                        }
+                        VerifyAction::Tick => {
+                            let mut poh = Poh::new(entry_start_hash, None);
+                            poh.hash(num_hashes.saturating_sub(1));
+                            poh.tick().unwrap().hash
+                        }
+                        VerifyAction::None => entry_start_hash,
+                    };
+                    if actual != expected {
+                        warn!(
+                            "entry invalid!: start: {:?}, expected: {:?}, actual: {:?}",
+                            entry_start_hash, expected, actual
+                        );
+                    }
+                    actual == expected
+                },
+            );
+            // The receiver is gone if the caller bailed out before finishing verification
+            let _ = sender.send((res, timing::duration_as_us(&verify_start.elapsed())));
+        });
+
+        EntryVerificationState {
+            verification_status: EntryVerificationStatus::Pending,
+            poh_duration_us: timing::duration_as_us(&start.elapsed()),
+            device_verification_data: DeviceVerificationData::Pipelined(
+                PipelinedVerificationData {
+                    receiver: Some(receiver),
+                },
+            ),
+        }
+    }
+
     fn verify_tick_hash_count(&self, tick_hash_count: &mut u64, hashes_per_tick: u64) -> bool {
         // When hashes_per_tick is 0, hashing is disabled.
         if hashes_per_tick == 0 {
@@ -843,6 +964,31 @@ mod tests {
         assert!(!bad_ticks.verify(&one)); // inductive step, bad
     }
 
+    #[test]
+    fn test_verify_pipelined() {
+        solana_logger::setup();
+        let zero = Hash::default();
+        let one = hash(zero.as_ref());
+        let keypair = Keypair::new();
+        let tx = system_transaction::transfer(&keypair, &keypair.pubkey(), 1, zero);
+        let verify_pipelined = |entries: &[Entry], start_hash: &Hash| {
+            let mut entry_state =
+                entries.start_verify_pipelined(start_hash, VerifyRecyclers::default());
+            entry_state.status() != EntryVerificationStatus::Failure && entry_state.finish_verify()
+        };
+
+        assert!(verify_pipelined(&[], &zero)); // base case
+        let mut entries = create_ticks(4, 3, zero);
+        entries.push(next_entry(&entries.last().unwrap().hash, 2, vec![tx]));
+        entries.push(next_entry(&entries.last().unwrap().hash, 0, vec![]));
+        assert!(verify_pipelined(&entries, &zero));
+        assert!(!verify_pipelined(&entries, &one));
+
+        let mut bad_entries = entries;
+        bad_entries[4].hash = one;
+        assert!(!verify_pipelined(&bad_entries, &zero));
+    }
+
     #[test]
     fn test_verify_tick_hash_count() {
         let hashes_per_tick = 10;
@@ -1,6 +1,7 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod address_generator;
 pub mod genesis_accounts;
+pub mod manifest;
 pub mod stakes;
 pub mod unlocks;
 
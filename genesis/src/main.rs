@@ -7,7 +7,9 @@ use solana_clap_utils::{
     input_validators::{is_pubkey_or_keypair, is_rfc3339_datetime, is_slot, is_valid_percentage},
 };
 use solana_entry::poh::compute_hashes_per_tick;
-use solana_genesis::{genesis_accounts::add_genesis_accounts, Base64Account};
+use solana_genesis::{
+    genesis_accounts::add_genesis_accounts, manifest::GenesisManifest, Base64Account,
+};
 use solana_ledger::{blockstore::create_new_ledger, blockstore_db::AccessType};
 use solana_runtime::hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE;
 use solana_sdk::{
@@ -32,7 +34,7 @@ use std::{
     error,
     fs::File,
     io::{self, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process,
     str::FromStr,
     time::Duration,
@@ -378,6 +380,17 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .possible_values(&["pico", "full", "none"])
                 .help("Selects inflation"),
         )
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .value_name("FILENAME")
+                .takes_value(true)
+                .help(
+                    "TOML manifest declaring accounts, programs, features, inflation and the \
+                     epoch schedule of the cluster. Its settings take precedence over the \
+                     equivalent arguments",
+                ),
+        )
         .get_matches();
 
     let ledger_path = PathBuf::from(matches.value_of("ledger_path").unwrap());
@@ -569,6 +582,11 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         }
     }
 
+    if let Some(manifest_path) = matches.value_of("manifest").map(Path::new) {
+        let base_dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+        GenesisManifest::load(manifest_path)?.apply(&mut genesis_config, base_dir)?;
+    }
+
     let max_genesis_archive_unpacked_size =
         value_t_or_exit!(matches, "max_genesis_archive_unpacked_size", u64);
 
@@ -0,0 +1,399 @@
+//! A genesis manifest declares the initial accounts, programs, features, inflation and epoch
+//! schedule of a cluster in a TOML file, so that a custom cluster can be recreated from the same
+//! file instead of a long list of command-line arguments.
+//!
+//! ```toml
+//! activate_all_features = false
+//! features = ["BKCPBQQBZqggVnFso5nQ8rQ4RwwogYwjuUt9biBjxwNF"]
+//!
+//! [inflation]
+//! preset = "pico"
+//! foundation = 0.0
+//!
+//! [epoch_schedule]
+//! slots_per_epoch = 8192
+//! warmup = false
+//!
+//! [[accounts]]
+//! address = "path/to/keypair.json"
+//! lamports = 1000000000
+//! owner = "11111111111111111111111111111111"
+//! data_file = "path/to/data.bin"
+//!
+//! [[programs]]
+//! address = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"
+//! path = "path/to/spl_memo.so"
+//!
+//! [[native_programs]]
+//! name = "solana_config_program"
+//! address = "Config1111111111111111111111111111111111111"
+//! ```
+//!
+//! Relative paths are resolved against the directory of the manifest.
+
+use {
+    serde::Deserialize,
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        bpf_loader,
+        epoch_schedule::EpochSchedule,
+        feature::{self, Feature},
+        genesis_config::GenesisConfig,
+        inflation::Inflation,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Signer},
+    },
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisManifest {
+    /// Activate every feature known to this software at genesis
+    #[serde(default)]
+    pub activate_all_features: bool,
+    /// Features to activate at genesis
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub inflation: Option<ManifestInflation>,
+    pub epoch_schedule: Option<ManifestEpochSchedule>,
+    #[serde(default)]
+    pub accounts: Vec<ManifestAccount>,
+    #[serde(default)]
+    pub programs: Vec<ManifestProgram>,
+    #[serde(default)]
+    pub native_programs: Vec<ManifestNativeProgram>,
+}
+
+/// Inflation starting from a preset, with any of its parameters overridden
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestInflation {
+    /// One of "default", "pico", "full" or "none"
+    pub preset: Option<String>,
+    pub initial: Option<f64>,
+    pub terminal: Option<f64>,
+    pub taper: Option<f64>,
+    pub foundation: Option<f64>,
+    pub foundation_term: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEpochSchedule {
+    pub slots_per_epoch: u64,
+    /// Defaults to `slots_per_epoch`
+    pub leader_schedule_slot_offset: Option<u64>,
+    #[serde(default)]
+    pub warmup: bool,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestAccount {
+    /// A pubkey, or the path of a keypair file
+    pub address: String,
+    pub lamports: u64,
+    /// Defaults to the system program
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub executable: bool,
+    /// The account data, base64 encoded
+    pub data: Option<String>,
+    /// A file holding the account data
+    pub data_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestProgram {
+    /// A pubkey, or the path of a keypair file
+    pub address: String,
+    /// The program's shared object
+    pub path: PathBuf,
+    /// Defaults to the BPF loader
+    pub loader: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestNativeProgram {
+    pub name: String,
+    pub address: String,
+}
+
+fn invalid_manifest(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn parse_pubkey(value: &str, base_dir: &Path) -> io::Result<Pubkey> {
+    Pubkey::from_str(value).or_else(|_| {
+        read_keypair_file(base_dir.join(value))
+            .map(|keypair| keypair.pubkey())
+            .map_err(|err| invalid_manifest(format!("Invalid pubkey/keypair {}: {}", value, err)))
+    })
+}
+
+impl GenesisManifest {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let manifest = fs::read_to_string(path)?;
+        toml::from_str(&manifest).map_err(|err| {
+            invalid_manifest(format!("Invalid manifest {}: {}", path.display(), err))
+        })
+    }
+
+    /// Add the declared accounts, programs and features to `genesis_config` and replace its
+    /// inflation and epoch schedule if they are declared.  Relative paths are resolved against
+    /// `base_dir`
+    pub fn apply(&self, genesis_config: &mut GenesisConfig, base_dir: &Path) -> io::Result<()> {
+        if let Some(inflation) = &self.inflation {
+            genesis_config.inflation = inflation.to_inflation()?;
+        }
+        if let Some(epoch_schedule) = &self.epoch_schedule {
+            genesis_config.epoch_schedule = EpochSchedule::custom(
+                epoch_schedule.slots_per_epoch,
+                epoch_schedule
+                    .leader_schedule_slot_offset
+                    .unwrap_or(epoch_schedule.slots_per_epoch),
+                epoch_schedule.warmup,
+            );
+        }
+
+        if self.activate_all_features {
+            solana_runtime::genesis_utils::activate_all_features(genesis_config);
+        }
+        for feature_id in &self.features {
+            let feature_id = Pubkey::from_str(feature_id).map_err(|err| {
+                invalid_manifest(format!("Invalid feature {}: {}", feature_id, err))
+            })?;
+            let lamports = genesis_config
+                .rent
+                .minimum_balance(Feature::size_of())
+                .max(1);
+            genesis_config.add_account(
+                feature_id,
+                feature::create_account(
+                    &Feature {
+                        activated_at: Some(0),
+                    },
+                    lamports,
+                ),
+            );
+        }
+
+        for account in &self.accounts {
+            let address = parse_pubkey(&account.address, base_dir)?;
+            let owner = match &account.owner {
+                Some(owner) => Pubkey::from_str(owner)
+                    .map_err(|err| invalid_manifest(format!("Invalid owner {}: {}", owner, err)))?,
+                None => solana_sdk::system_program::id(),
+            };
+            let data = match (&account.data, &account.data_file) {
+                (Some(_), Some(_)) => {
+                    return Err(invalid_manifest(format!(
+                        "Account {} has both data and data_file",
+                        account.address
+                    )))
+                }
+                (Some(data), None) => base64::decode(data).map_err(|err| {
+                    invalid_manifest(format!("Invalid account data {}: {}", data, err))
+                })?,
+                (None, Some(data_file)) => fs::read(base_dir.join(data_file))?,
+                (None, None) => vec![],
+            };
+            genesis_config.add_account(
+                address,
+                AccountSharedData::from(Account {
+                    lamports: account.lamports,
+                    data,
+                    owner,
+                    executable: account.executable,
+                    rent_epoch: 0,
+                }),
+            );
+        }
+
+        for program in &self.programs {
+            let address = parse_pubkey(&program.address, base_dir)?;
+            let loader = match &program.loader {
+                Some(loader) => Pubkey::from_str(loader).map_err(|err| {
+                    invalid_manifest(format!("Invalid loader {}: {}", loader, err))
+                })?,
+                None => bpf_loader::id(),
+            };
+            let program_data = fs::read(base_dir.join(&program.path))?;
+            genesis_config.add_account(
+                address,
+                AccountSharedData::from(Account {
+                    lamports: genesis_config.rent.minimum_balance(program_data.len()),
+                    data: program_data,
+                    executable: true,
+                    owner: loader,
+                    rent_epoch: 0,
+                }),
+            );
+        }
+
+        for native_program in &self.native_programs {
+            let address = Pubkey::from_str(&native_program.address).map_err(|err| {
+                invalid_manifest(format!(
+                    "Invalid native program address {}: {}",
+                    native_program.address, err
+                ))
+            })?;
+            genesis_config.add_native_instruction_processor(native_program.name.clone(), address);
+        }
+        Ok(())
+    }
+}
+
+impl ManifestInflation {
+    fn to_inflation(&self) -> io::Result<Inflation> {
+        let mut inflation = match self.preset.as_deref() {
+            None | Some("default") => Inflation::default(),
+            Some("pico") => Inflation::pico(),
+            Some("full") => Inflation::full(),
+            Some("none") => Inflation::new_disabled(),
+            Some(preset) => {
+                return Err(invalid_manifest(format!(
+                    "Invalid inflation preset {}",
+                    preset
+                )))
+            }
+        };
+        if let Some(initial) = self.initial {
+            inflation.initial = initial;
+        }
+        if let Some(terminal) = self.terminal {
+            inflation.terminal = terminal;
+        }
+        if let Some(taper) = self.taper {
+            inflation.taper = taper;
+        }
+        if let Some(foundation) = self.foundation {
+            inflation.foundation = foundation;
+        }
+        if let Some(foundation_term) = self.foundation_term {
+            inflation.foundation_term = foundation_term;
+        }
+        Ok(inflation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{signature::Keypair, signer::keypair::write_keypair_file},
+    };
+
+    #[test]
+    fn test_genesis_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let keypair = Keypair::new();
+        write_keypair_file(&keypair, dir.path().join("account.json")).unwrap();
+        fs::write(dir.path().join("data.bin"), [1, 2, 3]).unwrap();
+        fs::write(dir.path().join("program.so"), [4; 10]).unwrap();
+        let feature_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let native_program_id = Pubkey::new_unique();
+
+        let manifest = format!(
+            r#"
+features = ["{}"]
+
+[inflation]
+preset = "pico"
+foundation = 0.0
+
+[epoch_schedule]
+slots_per_epoch = 64
+
+[[accounts]]
+address = "account.json"
+lamports = 42
+owner = "{}"
+data_file = "data.bin"
+
+[[programs]]
+address = "{}"
+path = "program.so"
+
+[[native_programs]]
+name = "native"
+address = "{}"
+"#,
+            feature_id, owner, program_id, native_program_id
+        );
+        let manifest_path = dir.path().join("genesis.toml");
+        fs::write(&manifest_path, manifest).unwrap();
+
+        let mut genesis_config = GenesisConfig::default();
+        GenesisManifest::load(&manifest_path)
+            .unwrap()
+            .apply(&mut genesis_config, dir.path())
+            .unwrap();
+
+        let mut expected_inflation = Inflation::pico();
+        expected_inflation.foundation = 0.0;
+        assert_eq!(genesis_config.inflation, expected_inflation);
+        assert_eq!(
+            genesis_config.epoch_schedule,
+            EpochSchedule::custom(64, 64, false)
+        );
+
+        assert_eq!(
+            feature::from_account(&genesis_config.accounts[&feature_id]),
+            Some(Feature {
+                activated_at: Some(0)
+            })
+        );
+
+        let account = &genesis_config.accounts[&keypair.pubkey()];
+        assert_eq!(account.lamports, 42);
+        assert_eq!(account.owner, owner);
+        assert_eq!(account.data, vec![1, 2, 3]);
+
+        let program = &genesis_config.accounts[&program_id];
+        assert!(program.executable);
+        assert_eq!(program.owner, bpf_loader::id());
+        assert_eq!(program.data, vec![4; 10]);
+
+        assert!(genesis_config
+            .native_instruction_processors
+            .contains(&("native".to_string(), native_program_id)));
+    }
+
+    #[test]
+    fn test_genesis_manifest_errors() {
+        assert!(toml::from_str::<GenesisManifest>("unknown_field = 1").is_err());
+
+        let manifest: GenesisManifest = toml::from_str(
+            r#"
+[inflation]
+preset = "huge"
+"#,
+        )
+        .unwrap();
+        assert!(manifest
+            .apply(&mut GenesisConfig::default(), Path::new("."))
+            .is_err());
+
+        let manifest: GenesisManifest = toml::from_str(
+            r#"
+[[accounts]]
+address = "not a pubkey"
+lamports = 1
+"#,
+        )
+        .unwrap();
+        assert!(manifest
+            .apply(&mut GenesisConfig::default(), Path::new("."))
+            .is_err());
+    }
+}
@@ -2712,12 +2712,60 @@ pub struct Sockets {
     pub serve_repair: UdpSocket,
 }
 
+impl Sockets {
+    /// Set the receive and send buffer sizes of every socket
+    pub fn set_buffer_sizes(
+        &self,
+        recv_buffer_size: Option<usize>,
+        send_buffer_size: Option<usize>,
+    ) -> std::io::Result<()> {
+        std::iter::once(&self.gossip)
+            .chain(&self.tvu)
+            .chain(&self.tvu_forwards)
+            .chain(&self.tpu)
+            .chain(&self.tpu_forwards)
+            .chain(&self.tpu_vote)
+            .chain(&self.broadcast)
+            .chain(std::iter::once(&self.repair))
+            .chain(&self.retransmit_sockets)
+            .chain(std::iter::once(&self.serve_repair))
+            .try_for_each(|socket| {
+                solana_net_utils::set_socket_buffer_sizes(
+                    socket,
+                    recv_buffer_size,
+                    send_buffer_size,
+                )
+            })
+    }
+}
+
 #[derive(Debug)]
 pub struct Node {
     pub info: ContactInfo,
     pub sockets: Sockets,
 }
 
+/// How many sockets are bound with SO_REUSEPORT to each receive port.  The kernel spreads the
+/// packets sent to a port over the receive queues of its sockets, each drained by its own thread
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumReceiveSockets {
+    pub tvu: usize,
+    pub tvu_forwards: usize,
+    pub tpu: usize,
+    pub tpu_forwards: usize,
+}
+
+impl Default for NumReceiveSockets {
+    fn default() -> Self {
+        Self {
+            tvu: 8,
+            tvu_forwards: 8,
+            tpu: 32,
+            tpu_forwards: 8,
+        }
+    }
+}
+
 impl Node {
     pub fn new_localhost() -> Self {
         let pubkey = solana_sdk::pubkey::new_rand();
@@ -2857,21 +2905,41 @@ impl Node {
         gossip_addr: &SocketAddr,
         port_range: PortRange,
         bind_ip_addr: IpAddr,
+    ) -> Node {
+        Self::new_with_external_ip_and_receive_sockets(
+            pubkey,
+            gossip_addr,
+            port_range,
+            bind_ip_addr,
+            NumReceiveSockets::default(),
+        )
+    }
+
+    pub fn new_with_external_ip_and_receive_sockets(
+        pubkey: &Pubkey,
+        gossip_addr: &SocketAddr,
+        port_range: PortRange,
+        bind_ip_addr: IpAddr,
+        num_receive_sockets: NumReceiveSockets,
     ) -> Node {
         let (gossip_port, (gossip, ip_echo)) =
             Self::get_gossip_port(gossip_addr, port_range, bind_ip_addr);
 
         let (tvu_port, tvu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu multi_bind");
+            multi_bind_in_range(bind_ip_addr, port_range, num_receive_sockets.tvu)
+                .expect("tvu multi_bind");
 
         let (tvu_forwards_port, tvu_forwards_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tvu_forwards multi_bind");
+            multi_bind_in_range(bind_ip_addr, port_range, num_receive_sockets.tvu_forwards)
+                .expect("tvu_forwards multi_bind");
 
         let (tpu_port, tpu_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 32).expect("tpu multi_bind");
+            multi_bind_in_range(bind_ip_addr, port_range, num_receive_sockets.tpu)
+                .expect("tpu multi_bind");
 
         let (tpu_forwards_port, tpu_forwards_sockets) =
-            multi_bind_in_range(bind_ip_addr, port_range, 8).expect("tpu_forwards multi_bind");
+            multi_bind_in_range(bind_ip_addr, port_range, num_receive_sockets.tpu_forwards)
+                .expect("tpu_forwards multi_bind");
 
         let (tpu_vote_port, tpu_vote_sockets) =
             multi_bind_in_range(bind_ip_addr, port_range, 1).expect("tpu_vote multi_bind");
@@ -3474,6 +3542,32 @@ mod tests {
         assert_eq!(node.sockets.gossip.local_addr().unwrap().port(), port);
     }
 
+    #[test]
+    fn new_with_external_ip_and_receive_sockets_test() {
+        let ip = Ipv4Addr::from(0);
+        let num_receive_sockets = NumReceiveSockets {
+            tvu: 2,
+            tvu_forwards: 1,
+            tpu: 3,
+            tpu_forwards: 1,
+        };
+        let node = Node::new_with_external_ip_and_receive_sockets(
+            &solana_sdk::pubkey::new_rand(),
+            &socketaddr!(ip, 0),
+            VALIDATOR_PORT_RANGE,
+            IpAddr::V4(ip),
+            num_receive_sockets,
+        );
+
+        check_node_sockets(&node, IpAddr::V4(ip), VALIDATOR_PORT_RANGE);
+        if cfg!(not(windows)) {
+            assert_eq!(node.sockets.tvu.len(), 2);
+            assert_eq!(node.sockets.tvu_forwards.len(), 1);
+            assert_eq!(node.sockets.tpu.len(), 3);
+            assert_eq!(node.sockets.tpu_forwards.len(), 1);
+        }
+    }
+
     //test that all cluster_info objects only generate signed messages
     //when constructed with keypairs
     #[test]
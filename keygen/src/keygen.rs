@@ -11,6 +11,10 @@ use solana_clap_utils::{
         keypair_from_path, keypair_from_seed_phrase, prompt_passphrase, signer_from_path,
         SKIP_SEED_PHRASE_VALIDATION_ARG,
     },
+    keystore::{
+        keystore_password, read_encrypted_keypair_file, write_encrypted_keypair_file,
+        KEYSTORE_PASSWORD_ENV, KEYSTORE_PASSWORD_FD_ENV,
+    },
     ArgConstant, DisplayError,
 };
 use solana_cli_config::{Config, CONFIG_FILE};
@@ -19,11 +23,13 @@ use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     message::Message,
     pubkey::{write_pubkey_file, Pubkey},
-    signature::{keypair_from_seed, write_keypair, write_keypair_file, Keypair, Signer},
+    signature::{
+        keypair_from_seed, read_keypair_file, write_keypair, write_keypair_file, Keypair, Signer,
+    },
 };
 use std::{
     collections::HashSet,
-    error,
+    env, error,
     path::Path,
     process::exit,
     sync::{
@@ -31,17 +37,38 @@ use std::{
         Arc,
     },
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 const NO_PASSPHRASE: &str = "";
 
+/// Number of attempts each grind thread makes before adding them to the shared total
+const GRIND_ATTEMPTS_BATCH: u64 = 10_000;
+/// Number of attempts between grind progress reports
+const GRIND_REPORT_INTERVAL: u64 = 1_000_000;
+
 struct GrindMatch {
     starts: String,
     ends: String,
     count: AtomicU64,
 }
 
+impl GrindMatch {
+    fn matches(&self, pubkey: &str) -> bool {
+        pubkey.starts_with(&self.starts) && pubkey.ends_with(&self.ends)
+    }
+
+    /// Claims one of the keypairs still wanted for this match, returning false if none remain.
+    /// Threads that find a match at the same moment can't both claim the last keypair.
+    fn claim(&self) -> bool {
+        self.count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+    }
+}
+
 const WORD_COUNT_ARG: ArgConstant<'static> = ArgConstant {
     long: "word-count",
     name: "word_count",
@@ -146,12 +173,20 @@ fn get_keypair_from_matches(
     signer_from_path(matches, path, "pubkey recovery", wallet_manager)
 }
 
+/// Writes `keypair` to `outfile`, as a keystore encrypted with `keystore_password` if it is set
 fn output_keypair(
     keypair: &Keypair,
     outfile: &str,
     source: &str,
+    keystore_password: Option<&str>,
 ) -> Result<(), Box<dyn error::Error>> {
-    if outfile == STDOUT_OUTFILE_TOKEN {
+    if let Some(keystore_password) = keystore_password {
+        if outfile == STDOUT_OUTFILE_TOKEN {
+            return Err("Refusing to write an encrypted keystore to stdout".into());
+        }
+        write_encrypted_keypair_file(keypair, keystore_password, outfile)?;
+        println!("Wrote {} encrypted keypair to {}", source, outfile);
+    } else if outfile == STDOUT_OUTFILE_TOKEN {
         let mut stdout = std::io::stdout();
         write_keypair(keypair, &mut stdout)?;
     } else {
@@ -161,6 +196,29 @@ fn output_keypair(
     Ok(())
 }
 
+/// The password to encrypt a new keystore with, from the environment if it is set there, or else
+/// prompted for twice
+fn acquire_keystore_password() -> Result<String, Box<dyn error::Error>> {
+    if env::var_os(KEYSTORE_PASSWORD_ENV).is_some()
+        || env::var_os(KEYSTORE_PASSWORD_FD_ENV).is_some()
+    {
+        return keystore_password("the new keystore");
+    }
+    let password = prompt_passphrase("Keystore password: ")?;
+    if password != prompt_passphrase("Confirm keystore password: ")? {
+        return Err("Keystore passwords do not match".into());
+    }
+    Ok(password)
+}
+
+fn encrypt_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("encrypt").long("encrypt").help(
+        "Write the keypair to a password-encrypted keystore file instead of a plain text \
+             keypair file. The password is read from the SOLANA_KEYSTORE_PASSWORD environment \
+             variable, or the file descriptor in SOLANA_KEYSTORE_PASSWORD_FD, if either is set",
+    )
+}
+
 fn grind_validator_starts_with(v: String) -> Result<(), String> {
     if v.matches(':').count() != 1 || (v.starts_with(':') || v.ends_with(':')) {
         return Err(String::from("Expected : between PREFIX and COUNT"));
@@ -275,6 +333,17 @@ fn grind_print_info(grind_matches: &[GrindMatch], num_threads: usize) {
     }
 }
 
+fn grind_print_progress(attempts: u64, elapsed: Duration, found: u64) {
+    let rate = attempts as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "Searched {} keypairs in {}s ({:.0} keypairs/s). {} matches found.",
+        attempts,
+        elapsed.as_secs(),
+        rate,
+        found,
+    );
+}
+
 fn grind_parse_args(
     ignore_case: bool,
     starts_with_args: HashSet<String>,
@@ -390,6 +459,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .long("silent")
                         .help("Do not display seed phrase. Useful when piping output to other programs that prompt for user input, like gpg"),
                 )
+                .arg(encrypt_arg().conflicts_with(NO_OUTFILE_ARG.name))
                 .key_generation_common_args()
                 .arg(no_outfile_arg())
         )
@@ -446,6 +516,14 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .long("use-mnemonic")
                         .help("Generate using a mnemonic key phrase.  Expect a significant slowdown in this mode"),
                 )
+                .arg(
+                    Arg::with_name("encrypt")
+                        .long("encrypt")
+                        .conflicts_with(NO_OUTFILE_ARG.name)
+                        .help("Write each keypair to a password-encrypted keystore file instead \
+                               of a plain text keypair file. Recover the keypair with \
+                               `solana-keygen recover --keystore`"),
+                )
                 .key_generation_common_args()
                 .arg(
                     no_outfile_arg()
@@ -497,6 +575,15 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .validator(is_prompt_signer_source)
                         .help("`prompt:` URI scheme or `ASK` keyword"),
                 )
+                .arg(
+                    Arg::with_name("keystore")
+                        .long("keystore")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .conflicts_with("prompt_signer")
+                        .help("Recover the keypair from a password-encrypted keystore file"),
+                )
+                .arg(encrypt_arg())
                 .arg(
                     Arg::with_name("outfile")
                         .short("o")
@@ -518,6 +605,34 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 ),
 
         )
+        .subcommand(
+            SubCommand::with_name("encrypt")
+                .about("Encrypt a keypair file into a password-encrypted keystore file")
+                .setting(AppSettings::DisableVersion)
+                .arg(
+                    Arg::with_name("keypair")
+                        .index(1)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Filepath of the keypair to encrypt"),
+                )
+                .arg(
+                    Arg::with_name("outfile")
+                        .short("o")
+                        .long("outfile")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to the keystore file"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .short("f")
+                        .long("force")
+                        .help("Overwrite the output file if it exists"),
+                )
+        )
         .get_matches();
 
     do_main(&matches).map_err(|err| DisplayError::new_as_boxed(err).into())
@@ -577,7 +692,12 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
             let keypair = keypair_from_seed(seed.as_bytes())?;
 
             if let Some(outfile) = outfile {
-                output_keypair(&keypair, outfile, "new")
+                let keystore_password = if matches.is_present("encrypt") {
+                    Some(acquire_keystore_password()?)
+                } else {
+                    None
+                };
+                output_keypair(&keypair, outfile, "new", keystore_password.as_deref())
                     .map_err(|err| format!("Unable to write {}: {}", outfile, err))?;
             }
 
@@ -604,13 +724,35 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
             }
 
             let keypair_name = "recover";
-            let keypair = if let Some(path) = matches.value_of("prompt_signer") {
+            let keypair = if let Some(keystore) = matches.value_of("keystore") {
+                let password = keystore_password(keystore)?;
+                read_encrypted_keypair_file(keystore, &password)?
+            } else if let Some(path) = matches.value_of("prompt_signer") {
                 keypair_from_path(matches, path, keypair_name, true)?
             } else {
                 let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
                 keypair_from_seed_phrase(keypair_name, skip_validation, true, None, true)?
             };
-            output_keypair(&keypair, outfile, "recovered")?;
+            let keystore_password = if matches.is_present("encrypt") {
+                Some(acquire_keystore_password()?)
+            } else {
+                None
+            };
+            output_keypair(&keypair, outfile, "recovered", keystore_password.as_deref())?;
+        }
+        ("encrypt", Some(matches)) => {
+            let keypair_path = matches.value_of("keypair").unwrap();
+            let outfile = matches.value_of("outfile").unwrap();
+            check_for_overwrite(outfile, matches);
+
+            let keypair = read_keypair_file(keypair_path)
+                .map_err(|err| format!("Unable to read {}: {}", keypair_path, err))?;
+            let keystore_password = acquire_keystore_password()?;
+            output_keypair(&keypair, outfile, "encrypted", Some(&keystore_password))?;
+            println!(
+                "Remove the plain text keypair {} once the keystore is backed up",
+                keypair_path
+            );
         }
         ("grind", Some(matches)) => {
             let ignore_case = matches.is_present("ignore_case");
@@ -672,9 +814,14 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
                 no_passphrase_and_message()
             };
             let no_outfile = matches.is_present(NO_OUTFILE_ARG.name);
+            let keystore_password = if matches.is_present("encrypt") {
+                Some(acquire_keystore_password()?)
+            } else {
+                None
+            };
 
             let grind_matches_thread_safe = Arc::new(grind_matches);
-            let attempts = Arc::new(AtomicU64::new(1));
+            let attempts = Arc::new(AtomicU64::new(0));
             let found = Arc::new(AtomicU64::new(0));
             let start = Instant::now();
             let done = Arc::new(AtomicBool::new(false));
@@ -687,75 +834,87 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
                     let grind_matches_thread_safe = grind_matches_thread_safe.clone();
                     let passphrase = passphrase.clone();
                     let passphrase_message = passphrase_message.clone();
+                    let keystore_password = keystore_password.clone();
+
+                    thread::spawn(move || {
+                        // Attempts are added to the shared total in batches, so that threads
+                        // don't contend on it for every keypair
+                        let mut batch_attempts = 0;
+                        while !done.load(Ordering::Relaxed) {
+                            batch_attempts += 1;
+                            if batch_attempts == GRIND_ATTEMPTS_BATCH {
+                                let previous_attempts =
+                                    attempts.fetch_add(batch_attempts, Ordering::Relaxed);
+                                let total_attempts = previous_attempts + batch_attempts;
+                                batch_attempts = 0;
+                                if total_attempts / GRIND_REPORT_INTERVAL
+                                    != previous_attempts / GRIND_REPORT_INTERVAL
+                                {
+                                    grind_print_progress(
+                                        total_attempts,
+                                        start.elapsed(),
+                                        found.load(Ordering::Relaxed),
+                                    );
+                                }
+                            }
 
-                    thread::spawn(move || loop {
-                        if done.load(Ordering::Relaxed) {
-                            break;
-                        }
-                        let attempts = attempts.fetch_add(1, Ordering::Relaxed);
-                        if attempts % 1_000_000 == 0 {
-                            println!(
-                                "Searched {} keypairs in {}s. {} matches found.",
-                                attempts,
-                                start.elapsed().as_secs(),
-                                found.load(Ordering::Relaxed),
-                            );
-                        }
-                        let (keypair, phrase) = if use_mnemonic {
-                            let mnemonic = Mnemonic::new(mnemonic_type, language);
-                            let seed = Seed::new(&mnemonic, &passphrase);
-                            (keypair_from_seed(seed.as_bytes()).unwrap(), mnemonic.phrase().to_string())
-                        } else {
-                            (Keypair::new(), "".to_string())
-                        };
-                        let mut pubkey = bs58::encode(keypair.pubkey()).into_string();
-                        if ignore_case {
-                            pubkey = pubkey.to_lowercase();
-                        }
-                        let mut total_matches_found = 0;
-                        for i in 0..grind_matches_thread_safe.len() {
-                            if grind_matches_thread_safe[i].count.load(Ordering::Relaxed) == 0 {
-                                total_matches_found += 1;
-                                continue;
+                            let (keypair, phrase) = if use_mnemonic {
+                                let mnemonic = Mnemonic::new(mnemonic_type, language);
+                                let seed = Seed::new(&mnemonic, &passphrase);
+                                (
+                                    keypair_from_seed(seed.as_bytes()).unwrap(),
+                                    mnemonic.phrase().to_string(),
+                                )
+                            } else {
+                                (Keypair::new(), "".to_string())
+                            };
+                            let mut pubkey = bs58::encode(keypair.pubkey()).into_string();
+                            if ignore_case {
+                                pubkey = pubkey.to_lowercase();
                             }
-                            if (!grind_matches_thread_safe[i].starts.is_empty()
-                                && grind_matches_thread_safe[i].ends.is_empty()
-                                && pubkey.starts_with(&grind_matches_thread_safe[i].starts))
-                                || (grind_matches_thread_safe[i].starts.is_empty()
-                                    && !grind_matches_thread_safe[i].ends.is_empty()
-                                    && pubkey.ends_with(&grind_matches_thread_safe[i].ends))
-                                || (!grind_matches_thread_safe[i].starts.is_empty()
-                                    && !grind_matches_thread_safe[i].ends.is_empty()
-                                    && pubkey.starts_with(&grind_matches_thread_safe[i].starts)
-                                    && pubkey.ends_with(&grind_matches_thread_safe[i].ends))
-                            {
-                                let _found = found.fetch_add(1, Ordering::Relaxed);
-                                grind_matches_thread_safe[i]
-                                    .count
-                                    .fetch_sub(1, Ordering::Relaxed);
-                                if !no_outfile {
-                                    write_keypair_file(&keypair, &format!("{}.json", keypair.pubkey()))
+                            let mut total_matches_found = 0;
+                            for grind_match in grind_matches_thread_safe.iter() {
+                                if grind_match.count.load(Ordering::Relaxed) == 0 {
+                                    total_matches_found += 1;
+                                    continue;
+                                }
+                                if !grind_match.matches(&pubkey) || !grind_match.claim() {
+                                    continue;
+                                }
+                                found.fetch_add(1, Ordering::Relaxed);
+                                if let Some(ref keystore_password) = keystore_password {
+                                    let outfile = format!("{}.keystore.json", keypair.pubkey());
+                                    write_encrypted_keypair_file(
+                                        &keypair,
+                                        keystore_password,
+                                        &outfile,
+                                    )
                                     .unwrap();
-                                    println!(
-                                        "Wrote keypair to {}",
-                                        &format!("{}.json", keypair.pubkey())
-                                    );
+                                    println!("Wrote encrypted keypair to {}", outfile);
+                                } else if !no_outfile {
+                                    let outfile = format!("{}.json", keypair.pubkey());
+                                    write_keypair_file(&keypair, &outfile).unwrap();
+                                    println!("Wrote keypair to {}", outfile);
                                 }
                                 if use_mnemonic {
-                                    let divider = String::from_utf8(vec![b'='; phrase.len()]).unwrap();
+                                    let divider =
+                                        String::from_utf8(vec![b'='; phrase.len()]).unwrap();
                                     println!(
                                         "{}\nFound matching key {}",
-                                        &divider, keypair.pubkey());
+                                        &divider,
+                                        keypair.pubkey()
+                                    );
                                     println!(
                                         "\nSave this seed phrase{} to recover your new keypair:\n{}\n{}",
                                         passphrase_message, phrase, &divider
                                     );
                                 }
                             }
+                            if total_matches_found == grind_matches_thread_safe.len() {
+                                done.store(true, Ordering::Relaxed);
+                            }
                         }
-                        if total_matches_found == grind_matches_thread_safe.len() {
-                            done.store(true, Ordering::Relaxed);
-                        }
+                        attempts.fetch_add(batch_attempts, Ordering::Relaxed);
                     })
                 })
                 .collect();
@@ -763,6 +922,11 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
             for thread_handle in thread_handles {
                 thread_handle.join().unwrap();
             }
+            grind_print_progress(
+                attempts.load(Ordering::Relaxed),
+                start.elapsed(),
+                found.load(Ordering::Relaxed),
+            );
         }
         ("verify", Some(matches)) => {
             let keypair = get_keypair_from_matches(matches, config, &mut wallet_manager)?;
@@ -4,17 +4,19 @@ use clap::{
 };
 use solana_clap_utils::{
     input_parsers::pubkey_of,
-    input_validators::{is_slot, is_valid_pubkey},
+    input_validators::{is_parsable, is_slot, is_valid_pubkey},
 };
 use solana_cli_output::{
     display::println_transaction, CliBlock, CliTransaction, CliTransactionConfirmation,
     OutputFormat,
 };
-use solana_ledger::{blockstore::Blockstore, blockstore_db::AccessType};
+use solana_ledger::{
+    bigtable_upload::BackfillConfig, blockstore::Blockstore, blockstore_db::AccessType,
+};
 use solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature};
 use solana_transaction_status::{ConfirmedBlock, EncodedTransaction, UiTransactionEncoding};
 use std::{
-    path::Path,
+    path::{Path, PathBuf},
     process::exit,
     result::Result,
     sync::{atomic::AtomicBool, Arc},
@@ -26,21 +28,40 @@ async fn upload(
     ending_slot: Option<Slot>,
     allow_missing_metadata: bool,
     force_reupload: bool,
+    backfill_config: Option<BackfillConfig>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let bigtable = solana_storage_bigtable::LedgerStorage::new(false, None)
         .await
         .map_err(|err| format!("Failed to connect to storage: {:?}", err))?;
 
-    solana_ledger::bigtable_upload::upload_confirmed_blocks(
-        Arc::new(blockstore),
-        bigtable,
-        starting_slot,
-        ending_slot,
-        allow_missing_metadata,
-        force_reupload,
-        Arc::new(AtomicBool::new(false)),
-    )
-    .await
+    match backfill_config {
+        Some(backfill_config) => {
+            let ending_slot = ending_slot.unwrap_or_else(|| blockstore.max_root());
+            solana_ledger::bigtable_upload::backfill_confirmed_blocks(
+                Arc::new(blockstore),
+                bigtable,
+                starting_slot,
+                ending_slot,
+                allow_missing_metadata,
+                force_reupload,
+                backfill_config,
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+        }
+        None => {
+            solana_ledger::bigtable_upload::upload_confirmed_blocks(
+                Arc::new(blockstore),
+                bigtable,
+                starting_slot,
+                ending_slot,
+                allow_missing_metadata,
+                force_reupload,
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+        }
+    }
 }
 
 async fn delete_slots(slots: Vec<Slot>, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
@@ -70,7 +91,7 @@ async fn block(slot: Slot, output_format: OutputFormat) -> Result<(), Box<dyn st
     let block = bigtable.get_confirmed_block(slot).await?;
 
     let cli_block = CliBlock {
-        encoded_confirmed_block: block.encode(UiTransactionEncoding::Base64),
+        encoded_confirmed_block: block.encode(UiTransactionEncoding::Base64, Some(0))?,
         slot,
     };
     println!("{}", output_format.formatted_string(&cli_block));
@@ -105,15 +126,16 @@ async fn confirm(
     if verbose {
         match bigtable.get_confirmed_transaction(signature).await {
             Ok(Some(confirmed_transaction)) => {
+                let decoded_transaction = confirmed_transaction.transaction.transaction;
                 transaction = Some(CliTransaction {
                     transaction: EncodedTransaction::encode(
-                        confirmed_transaction.transaction.transaction.clone(),
+                        decoded_transaction.clone(),
                         UiTransactionEncoding::Json,
                     ),
                     meta: confirmed_transaction.transaction.meta.map(|m| m.into()),
                     block_time: confirmed_transaction.block_time,
                     slot: Some(confirmed_transaction.slot),
-                    decoded_transaction: confirmed_transaction.transaction.transaction,
+                    decoded_transaction,
                     prefix: "  ".to_string(),
                     sigverify_status: vec![],
                 });
@@ -272,6 +294,39 @@ impl BigTableSubCommand for App<'_, '_> {
                                     Note: reupload will *not* delete any data from the tx-by-addr table;\
                                     Use with care.",
                                 ),
+                        )
+                        .arg(
+                            Arg::with_name("num_workers")
+                                .long("workers")
+                                .value_name("COUNT")
+                                .takes_value(true)
+                                .validator(is_parsable::<usize>)
+                                .help(
+                                    "Upload this many slot ranges concurrently \
+                                    [default: 1, or 4 with --checkpoint]",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("slots_per_range")
+                                .long("slots-per-range")
+                                .value_name("COUNT")
+                                .takes_value(true)
+                                .default_value("10000")
+                                .validator(is_parsable::<u64>)
+                                .help(
+                                    "Number of slots in each range uploaded by a worker \
+                                    when uploading with --workers or --checkpoint",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("checkpoint")
+                                .long("checkpoint")
+                                .value_name("FILE")
+                                .takes_value(true)
+                                .help(
+                                    "Save the uploaded slot ranges to this file, and skip the \
+                                    ranges already saved in it when resuming an upload",
+                                ),
                         ),
                 )
                 .subcommand(
@@ -426,6 +481,17 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
             let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
             let allow_missing_metadata = arg_matches.is_present("allow_missing_metadata");
             let force_reupload = arg_matches.is_present("force_reupload");
+            let checkpoint_path = value_t!(arg_matches, "checkpoint", PathBuf).ok();
+            let num_workers = value_t!(arg_matches, "num_workers", usize).ok();
+            let backfill_config = if checkpoint_path.is_some() || num_workers.unwrap_or(1) > 1 {
+                Some(BackfillConfig {
+                    num_workers: num_workers.unwrap_or(4),
+                    slots_per_range: value_t_or_exit!(arg_matches, "slots_per_range", u64),
+                    checkpoint_path,
+                })
+            } else {
+                None
+            };
             let blockstore =
                 crate::open_blockstore(ledger_path, AccessType::TryPrimaryThenSecondary, None);
 
@@ -435,6 +501,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 ending_slot,
                 allow_missing_metadata,
                 force_reupload,
+                backfill_config,
             ))
         }
         ("delete-slots", Some(arg_matches)) => {
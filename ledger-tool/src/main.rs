@@ -144,16 +144,13 @@ fn output_entry(
                     })
                     .map(|transaction_status| transaction_status.into());
 
-                if let Some(legacy_tx) = transaction.into_legacy_transaction() {
-                    solana_cli_output::display::println_transaction(
-                        &legacy_tx, &tx_status, "      ", None, None,
-                    );
-                } else {
-                    eprintln!(
-                        "Failed to print unsupported transaction for {} at slot {}",
-                        tx_signature, slot
-                    );
-                }
+                solana_cli_output::display::println_transaction(
+                    &transaction,
+                    &tx_status,
+                    "      ",
+                    None,
+                    None,
+                );
             }
         }
         LedgerOutputMethod::Json => {
@@ -793,6 +790,7 @@ fn compute_slot_cost(blockstore: &Blockstore, slot: Slot) -> Result<(), String>
                 let tx_cost = cost_model.calculate_cost(
                     &transaction,
                     true, // demote_program_write_locks
+                    true, // tx_wide_compute_cap
                 );
                 let result = cost_tracker.try_add(&transaction, &tx_cost);
                 if result.is_err() {
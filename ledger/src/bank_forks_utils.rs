@@ -16,10 +16,14 @@ use solana_runtime::{
     snapshot_config::SnapshotConfig,
     snapshot_hash::{FullSnapshotHash, IncrementalSnapshotHash, StartingSnapshotHashes},
     snapshot_package::AccountsPackageSender,
-    snapshot_utils,
+    snapshot_utils::{self, SnapshotError},
 };
 use solana_sdk::{clock::Slot, genesis_config::GenesisConfig};
-use std::{fs, path::PathBuf, process, result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process, result,
+};
 
 pub type LoadResult = result::Result<
     (
@@ -145,6 +149,30 @@ fn load_from_genesis(
     )
 }
 
+/// Quarantine a corrupted snapshot archive, failing if no other full snapshot archive is left to
+/// load from
+fn quarantine_snapshot_archive(
+    snapshot_config: &SnapshotConfig,
+    snapshot_archive_path: &Path,
+) -> result::Result<(), BlockstoreProcessorError> {
+    if let Err(err) = snapshot_utils::quarantine_snapshot_archive(snapshot_archive_path) {
+        error!(
+            "Unable to quarantine {}: {}",
+            snapshot_archive_path.display(),
+            err
+        );
+        process::exit(1);
+    }
+    if snapshot_utils::get_highest_full_snapshot_archive_info(
+        &snapshot_config.snapshot_archives_dir,
+    )
+    .is_none()
+    {
+        return Err(BlockstoreProcessorError::NoLoadableSnapshotArchive);
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 fn load_from_snapshot(
     genesis_config: &GenesisConfig,
@@ -164,8 +192,8 @@ fn load_from_snapshot(
         process::exit(1);
     }
 
-    let (deserialized_bank, timings, full_snapshot_archive_info, incremental_snapshot_archive_info) =
-        snapshot_utils::bank_from_latest_snapshot_archives(
+    let loaded_snapshot = loop {
+        match snapshot_utils::bank_from_latest_snapshot_archives(
             &snapshot_config.bank_snapshots_dir,
             &snapshot_config.snapshot_archives_dir,
             &account_paths,
@@ -181,9 +209,53 @@ fn load_from_snapshot(
             process_options.accounts_db_skip_shrink,
             process_options.verify_index,
             process_options.accounts_db_config.clone(),
-            accounts_update_notifier,
-        )
-        .expect("Load from snapshot failed");
+            accounts_update_notifier.clone(),
+            &process_options.snapshot_verification,
+            process_options.snapshot_load_progress_callback.as_ref(),
+        ) {
+            // Quarantine the corrupted archive, so that an older one is loaded instead, or the
+            // caller fetches a fresh one
+            Err(SnapshotError::MismatchedStorageChecksum(snapshot_archive_path, file_name))
+                if process_options.quarantine_corrupted_snapshot_archives =>
+            {
+                error!(
+                    "Quarantining snapshot archive {} with corrupted account storage file {}",
+                    snapshot_archive_path.display(),
+                    file_name
+                );
+                datapoint_error!(
+                    "snapshot-storage-checksum-mismatch",
+                    (
+                        "snapshot_archive",
+                        snapshot_archive_path.display().to_string(),
+                        String
+                    ),
+                    ("storage_file", file_name, String)
+                );
+                quarantine_snapshot_archive(snapshot_config, &snapshot_archive_path)?;
+            }
+            Err(SnapshotError::MissingStorageChecksums(snapshot_archive_path))
+                if process_options.quarantine_corrupted_snapshot_archives =>
+            {
+                error!(
+                    "Quarantining snapshot archive {} with missing account storage checksums",
+                    snapshot_archive_path.display()
+                );
+                datapoint_error!(
+                    "snapshot-storage-checksums-missing",
+                    (
+                        "snapshot_archive",
+                        snapshot_archive_path.display().to_string(),
+                        String
+                    )
+                );
+                quarantine_snapshot_archive(snapshot_config, &snapshot_archive_path)?;
+            }
+            result => break result.expect("Load from snapshot failed"),
+        }
+    };
+    let (deserialized_bank, timings, full_snapshot_archive_info, incremental_snapshot_archive_info) =
+        loaded_snapshot;
 
     if let Some(shrink_paths) = shrink_paths {
         deserialized_bank.set_shrink_paths(shrink_paths);
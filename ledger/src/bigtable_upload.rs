@@ -1,13 +1,16 @@
 use crate::blockstore::Blockstore;
 use log::*;
+use serde::{Deserialize, Serialize};
 use solana_measure::measure::Measure;
 use solana_sdk::clock::Slot;
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
     result::Result,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
     },
     time::Duration,
 };
@@ -219,3 +222,260 @@ pub async fn upload_confirmed_blocks(
         Ok(())
     }
 }
+
+/// Progress of a backfill, saved after every uploaded slot range so that an interrupted
+/// backfill resumes where it stopped
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct UploadCheckpoint {
+    // The last slot of each completely uploaded range, by the first slot of the range
+    completed_ranges: BTreeMap<Slot, Slot>,
+}
+
+impl UploadCheckpoint {
+    /// Load the checkpoint at `path`, or an empty checkpoint if there is none yet
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read(path)
+            .map_err(|err| format!("Failed to read checkpoint {:?}: {}", path, err))?;
+        bincode::deserialize(&data)
+            .map_err(|err| format!("Invalid checkpoint {:?}: {}", path, err).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        // Write to a temporary file first so that a crash never leaves a truncated checkpoint
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bincode::serialize(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn is_completed(&self, start_slot: Slot, end_slot: Slot) -> bool {
+        self.completed_ranges
+            .range(..=start_slot)
+            .next_back()
+            .map(|(_, completed_end_slot)| *completed_end_slot >= end_slot)
+            .unwrap_or(false)
+    }
+
+    pub fn mark_completed(&mut self, start_slot: Slot, end_slot: Slot) {
+        let completed_end_slot = self.completed_ranges.entry(start_slot).or_default();
+        *completed_end_slot = end_slot.max(*completed_end_slot);
+    }
+}
+
+/// Split the slots from `starting_slot` to `ending_slot` inclusive into ranges of at most
+/// `slots_per_range` slots.  The ranges are aligned to multiples of `slots_per_range` so that
+/// backfills started from different slots checkpoint the same ranges
+pub fn split_slot_range(
+    starting_slot: Slot,
+    ending_slot: Slot,
+    slots_per_range: u64,
+) -> Vec<(Slot, Slot)> {
+    let slots_per_range = slots_per_range.max(1);
+    let mut ranges = vec![];
+    let mut start_slot = starting_slot;
+    while start_slot <= ending_slot {
+        let end_slot = (start_slot - start_slot % slots_per_range)
+            .saturating_add(slots_per_range - 1)
+            .min(ending_slot);
+        ranges.push((start_slot, end_slot));
+        if end_slot == Slot::MAX {
+            break;
+        }
+        start_slot = end_slot + 1;
+    }
+    ranges
+}
+
+pub struct BackfillConfig {
+    /// Number of slot ranges uploaded concurrently
+    pub num_workers: usize,
+    pub slots_per_range: u64,
+    /// Where to save the progress of the backfill, and resume it from
+    pub checkpoint_path: Option<PathBuf>,
+}
+
+/// Upload the confirmed blocks from `starting_slot` to `ending_slot`, sharding the slots into
+/// ranges uploaded by `config.num_workers` concurrent workers.  Ranges already completed in the
+/// checkpoint are skipped, even with `force_reupload`, and failed ranges are left out of the
+/// checkpoint so that the next run retries them
+#[allow(clippy::too_many_arguments)]
+pub async fn backfill_confirmed_blocks(
+    blockstore: Arc<Blockstore>,
+    bigtable: solana_storage_bigtable::LedgerStorage,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    allow_missing_metadata: bool,
+    force_reupload: bool,
+    config: BackfillConfig,
+    exit: Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut measure = Measure::start("entire backfill");
+
+    let checkpoint = match &config.checkpoint_path {
+        Some(checkpoint_path) => UploadCheckpoint::load(checkpoint_path)?,
+        None => UploadCheckpoint::default(),
+    };
+    let ranges: VecDeque<_> = split_slot_range(starting_slot, ending_slot, config.slots_per_range)
+        .into_iter()
+        .filter(|(start_slot, end_slot)| !checkpoint.is_completed(*start_slot, *end_slot))
+        .collect();
+    if ranges.is_empty() {
+        info!(
+            "All slots from {} to {} are already uploaded",
+            starting_slot, ending_slot
+        );
+        return Ok(());
+    }
+    info!(
+        "Backfilling {} slot ranges from {} to {} with {} workers",
+        ranges.len(),
+        starting_slot,
+        ending_slot,
+        config.num_workers
+    );
+
+    let num_ranges = ranges.len();
+    let ranges = Arc::new(Mutex::new(ranges));
+    let checkpoint = Arc::new(Mutex::new(checkpoint));
+    let checkpoint_path = Arc::new(config.checkpoint_path);
+    let num_completed = Arc::new(AtomicUsize::default());
+    let num_failed = Arc::new(AtomicUsize::default());
+
+    let workers: Vec<_> = (0..config.num_workers.max(1))
+        .map(|_| {
+            let blockstore = blockstore.clone();
+            let bigtable = bigtable.clone();
+            let ranges = ranges.clone();
+            let checkpoint = checkpoint.clone();
+            let checkpoint_path = checkpoint_path.clone();
+            let num_completed = num_completed.clone();
+            let num_failed = num_failed.clone();
+            let exit = exit.clone();
+            tokio::spawn(async move {
+                while !exit.load(Ordering::Relaxed) {
+                    let (start_slot, end_slot) = match ranges.lock().unwrap().pop_front() {
+                        Some(range) => range,
+                        None => break,
+                    };
+                    let result = if has_slots(&blockstore, start_slot, end_slot) {
+                        upload_confirmed_blocks(
+                            blockstore.clone(),
+                            bigtable.clone(),
+                            start_slot,
+                            Some(end_slot),
+                            allow_missing_metadata,
+                            force_reupload,
+                            exit.clone(),
+                        )
+                        .await
+                        .map_err(|err| err.to_string())
+                    } else {
+                        Ok(())
+                    };
+                    // An upload interrupted by `exit` returns early, don't checkpoint it
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Err(err) = result {
+                        error!(
+                            "Failed to upload slots {} to {}: {}",
+                            start_slot, end_slot, err
+                        );
+                        num_failed.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+
+                    let mut checkpoint = checkpoint.lock().unwrap();
+                    checkpoint.mark_completed(start_slot, end_slot);
+                    if let Some(checkpoint_path) = checkpoint_path.as_ref() {
+                        if let Err(err) = checkpoint.save(checkpoint_path) {
+                            warn!("Failed to save checkpoint {:?}: {}", checkpoint_path, err);
+                        }
+                    }
+                    let num_completed = num_completed.fetch_add(1, Ordering::Relaxed) + 1;
+                    info!(
+                        "Uploaded slots {} to {}, {}/{} ranges done",
+                        start_slot, end_slot, num_completed, num_ranges
+                    );
+                }
+            })
+        })
+        .collect();
+    for result in futures::future::join_all(workers).await {
+        result.map_err(|err| format!("Backfill worker failed: {}", err))?;
+    }
+
+    measure.stop();
+    info!("{}", measure);
+    let num_failed = num_failed.load(Ordering::Relaxed);
+    if num_failed > 0 {
+        Err(format!(
+            "Incomplete backfill, {} of {} slot ranges failed",
+            num_failed, num_ranges
+        )
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+// Whether the blockstore has any slots from `start_slot` to `end_slot`
+fn has_slots(blockstore: &Blockstore, start_slot: Slot, end_slot: Slot) -> bool {
+    blockstore
+        .slot_meta_iterator(start_slot)
+        .ok()
+        .and_then(|mut slot_metas| slot_metas.next())
+        .map(|(slot, _slot_meta)| slot <= end_slot)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_slot_range() {
+        assert_eq!(split_slot_range(0, 9, 10), vec![(0, 9)]);
+        assert_eq!(
+            split_slot_range(5, 27, 10),
+            vec![(5, 9), (10, 19), (20, 27)]
+        );
+        assert_eq!(split_slot_range(10, 9, 10), vec![]);
+        assert_eq!(split_slot_range(3, 3, 10), vec![(3, 3)]);
+        assert_eq!(
+            split_slot_range(Slot::MAX - 1, Slot::MAX, 10),
+            vec![(Slot::MAX - 1, Slot::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_upload_checkpoint() {
+        let checkpoint_dir = tempfile::TempDir::new().unwrap();
+        let checkpoint_path = checkpoint_dir.path().join("checkpoint");
+
+        let mut checkpoint = UploadCheckpoint::load(&checkpoint_path).unwrap();
+        assert_eq!(checkpoint, UploadCheckpoint::default());
+        assert!(!checkpoint.is_completed(0, 9));
+
+        checkpoint.mark_completed(5, 9);
+        checkpoint.mark_completed(10, 19);
+        assert!(checkpoint.is_completed(5, 9));
+        assert!(checkpoint.is_completed(10, 19));
+        assert!(checkpoint.is_completed(12, 15));
+        // A range starting earlier than the completed one still needs uploading
+        assert!(!checkpoint.is_completed(0, 9));
+        assert!(!checkpoint.is_completed(20, 29));
+
+        checkpoint.save(&checkpoint_path).unwrap();
+        assert_eq!(
+            UploadCheckpoint::load(&checkpoint_path).unwrap(),
+            checkpoint
+        );
+
+        fs::write(&checkpoint_path, b"invalid").unwrap();
+        assert!(UploadCheckpoint::load(&checkpoint_path).is_err());
+    }
+}
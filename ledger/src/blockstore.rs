@@ -13,7 +13,10 @@ use {
         erasure::ErasureConfig,
         leader_schedule_cache::LeaderScheduleCache,
         next_slots_iterator::NextSlotsIterator,
-        shred::{Result as ShredResult, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK},
+        shred::{
+            Result as ShredResult, Shred, Shredder, MAX_DATA_SHREDS_PER_FEC_BLOCK,
+            SIZE_OF_MERKLE_DATA_SHRED_STORED,
+        },
     },
     bincode::deserialize,
     log::*,
@@ -22,6 +25,7 @@ use {
         ThreadPool,
     },
     rocksdb::DBRawIterator,
+    serde::{Deserialize, Serialize},
     solana_entry::entry::{create_ticks, Entry},
     solana_measure::measure::Measure,
     solana_metrics::{datapoint_debug, datapoint_error},
@@ -120,6 +124,48 @@ pub struct CompletedDataSetInfo {
     pub end_index: u32,
 }
 
+/// The status of a committed transaction and the addresses it's indexed under
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionStatusRecord {
+    pub slot: Slot,
+    pub signature: Signature,
+    pub writable_keys: Vec<Pubkey>,
+    pub readonly_keys: Vec<Pubkey>,
+    pub memos: Option<String>,
+    pub status: TransactionStatusMeta,
+}
+
+/// Filters `get_confirmed_signatures_for_address_with_memo` by memo
+#[derive(Clone, Copy, Debug)]
+pub struct MemoFilter<'a> {
+    /// Only transactions whose memo contains this are returned
+    pub memo: &'a str,
+    /// How many memos are read before the search stops
+    pub max_scanned: usize,
+}
+
+struct MemoScan<'a> {
+    memo: &'a str,
+    max_scanned: usize,
+    num_scanned: usize,
+    last_scanned: Option<Signature>,
+}
+
+impl MemoScan<'_> {
+    fn is_exhausted(memo_scan: &Option<Self>) -> bool {
+        memo_scan.as_ref().map_or(false, |memo_scan| {
+            memo_scan.num_scanned >= memo_scan.max_scanned
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConfirmedSignaturesForAddressPage {
+    pub infos: Vec<ConfirmedTransactionStatusWithSignature>,
+    /// Where to continue the search from, as `before`, if it stopped before reaching the end
+    pub before: Option<Signature>,
+}
+
 pub struct BlockstoreSignals {
     pub blockstore: Blockstore,
     pub ledger_signal_receiver: Receiver<bool>,
@@ -148,6 +194,7 @@ pub struct Blockstore {
     perf_samples_cf: LedgerColumn<cf::PerfSamples>,
     block_height_cf: LedgerColumn<cf::BlockHeight>,
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
+    equivocation_evidence_cf: LedgerColumn<cf::EquivocationEvidence>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     last_root: Arc<RwLock<Slot>>,
     insert_shreds_lock: Arc<Mutex<()>>,
@@ -178,6 +225,30 @@ struct SlotStats {
     num_recovered: usize,
 }
 
+/// A working copy of a column entry, and whether it has to be written back when the working set
+/// is committed
+enum WorkingEntry<T> {
+    // Created or modified since it was read
+    Dirty(T),
+    // As read from the blockstore
+    Clean(T),
+}
+
+impl<T> WorkingEntry<T> {
+    fn should_write(&self) -> bool {
+        matches!(self, Self::Dirty(_))
+    }
+}
+
+impl<T> AsRef<T> for WorkingEntry<T> {
+    fn as_ref(&self) -> &T {
+        match self {
+            Self::Dirty(value) => value,
+            Self::Clean(value) => value,
+        }
+    }
+}
+
 pub struct IndexMetaWorkingSetEntry {
     index: Index,
     // true only if at least one shred for this Index was inserted since the time this
@@ -390,6 +461,7 @@ impl Blockstore {
         let perf_samples_cf = db.column();
         let block_height_cf = db.column();
         let program_costs_cf = db.column();
+        let equivocation_evidence_cf = db.column();
         let bank_hash_cf = db.column();
 
         let db = Arc::new(db);
@@ -441,6 +513,7 @@ impl Blockstore {
             perf_samples_cf,
             block_height_cf,
             program_costs_cf,
+            equivocation_evidence_cf,
             bank_hash_cf,
             new_shreds_signals: vec![],
             completed_slots_senders: vec![],
@@ -771,7 +844,7 @@ impl Blockstore {
 
     fn try_shred_recovery(
         db: &Database,
-        erasure_metas: &HashMap<(u64, u64), ErasureMeta>,
+        erasure_metas: &HashMap<(u64, u64), WorkingEntry<ErasureMeta>>,
         index_working_set: &mut HashMap<u64, IndexMetaWorkingSetEntry>,
         prev_inserted_datas: &mut HashMap<(u64, u64), Shred>,
         prev_inserted_codes: &mut HashMap<(u64, u64), Shred>,
@@ -785,6 +858,7 @@ impl Blockstore {
         // 3. Before trying recovery, check if enough number of shreds have been received
         // 3a. Enough number of shreds = (#data + #coding shreds) > erasure.num_data
         for (&(slot, set_index), erasure_meta) in erasure_metas.iter() {
+            let erasure_meta = erasure_meta.as_ref();
             let index_meta_entry = index_working_set.get_mut(&slot).expect("Index");
             let index = &mut index_meta_entry.index;
             match erasure_meta.status(index) {
@@ -1011,8 +1085,11 @@ impl Blockstore {
             &mut write_batch,
         )?;
 
+        // Erasure metas are never modified once written, only the new ones need writing
         for ((slot, set_index), erasure_meta) in erasure_metas {
-            write_batch.put::<cf::ErasureMeta>((slot, set_index), &erasure_meta)?;
+            if erasure_meta.should_write() {
+                write_batch.put::<cf::ErasureMeta>((slot, set_index), erasure_meta.as_ref())?;
+            }
         }
 
         for (&slot, index_working_set_entry) in index_working_set.iter() {
@@ -1116,7 +1193,7 @@ impl Blockstore {
     fn check_cache_coding_shred<F>(
         &self,
         shred: Shred,
-        erasure_metas: &mut HashMap<(u64, u64), ErasureMeta>,
+        erasure_metas: &mut HashMap<(u64, u64), WorkingEntry<ErasureMeta>>,
         index_working_set: &mut HashMap<u64, IndexMetaWorkingSetEntry>,
         just_received_coding_shreds: &mut HashMap<(u64, u64), Shred>,
         index_meta_time: &mut u64,
@@ -1158,12 +1235,19 @@ impl Blockstore {
             shred.coding_header.num_coding_shreds as usize,
         );
 
-        let erasure_meta = erasure_metas.entry((slot, set_index)).or_insert_with(|| {
-            self.erasure_meta_cf
-                .get((slot, set_index))
-                .expect("Expect database get to succeed")
-                .unwrap_or_else(|| ErasureMeta::new(set_index, erasure_config))
-        });
+        let erasure_meta = erasure_metas
+            .entry((slot, set_index))
+            .or_insert_with(|| {
+                match self
+                    .erasure_meta_cf
+                    .get((slot, set_index))
+                    .expect("Expect database get to succeed")
+                {
+                    Some(erasure_meta) => WorkingEntry::Clean(erasure_meta),
+                    None => WorkingEntry::Dirty(ErasureMeta::new(set_index, erasure_config)),
+                }
+            })
+            .as_ref();
 
         if erasure_config != erasure_meta.config {
             metrics.num_coding_shreds_invalid_erasure_config += 1;
@@ -1252,7 +1336,7 @@ impl Blockstore {
     fn check_insert_data_shred<F>(
         &self,
         shred: Shred,
-        erasure_metas: &mut HashMap<(u64, u64), ErasureMeta>,
+        erasure_metas: &mut HashMap<(u64, u64), WorkingEntry<ErasureMeta>>,
         index_working_set: &mut HashMap<u64, IndexMetaWorkingSetEntry>,
         slot_meta_working_set: &mut HashMap<u64, SlotMetaWorkingSetEntry>,
         write_batch: &mut WriteBatch,
@@ -1328,7 +1412,7 @@ impl Blockstore {
                 .get((slot, set_index))
                 .expect("Expect database get to succeed")
             {
-                erasure_metas.insert((slot, set_index), meta);
+                erasure_metas.insert((slot, set_index), WorkingEntry::Clean(meta));
             }
         }
         Ok(newly_completed_data_sets)
@@ -1456,6 +1540,7 @@ impl Blockstore {
                 last_index,
             );
 
+            self.store_duplicate_block_evidence(slot, leader_pubkey, &ending_shred, &shred.payload);
             if self
                 .store_duplicate_if_not_existing(
                     slot,
@@ -1492,6 +1577,7 @@ impl Blockstore {
                 slot_meta.received - 1,
             );
 
+            self.store_duplicate_block_evidence(slot, leader_pubkey, &ending_shred, &shred.payload);
             if self
                 .store_duplicate_if_not_existing(
                     slot,
@@ -1517,10 +1603,84 @@ impl Blockstore {
             return false;
         }
 
+        if let Some(conflicting_shred) =
+            self.find_chained_merkle_root_conflict(shred, just_inserted_data_shreds)
+        {
+            let leader_pubkey = leader_schedule
+                .and_then(|leader_schedule| leader_schedule.slot_leader_at(slot, None));
+
+            self.store_duplicate_block_evidence(
+                slot,
+                leader_pubkey,
+                &conflicting_shred,
+                &shred.payload,
+            );
+            if self
+                .store_duplicate_if_not_existing(slot, conflicting_shred, shred.payload.clone())
+                .is_err()
+            {
+                warn!("store duplicate error");
+            }
+
+            datapoint_error!(
+                "blockstore_error",
+                (
+                    "error",
+                    format!(
+                        "Leader {:?}, slot {}: received index {} inconsistent with the chained merkle root of the erasure set {}, shred_source: {:?}",
+                        leader_pubkey, slot, shred_index, shred.common_header.fec_set_index, shred_source
+                    ),
+                    String
+                )
+            );
+            return false;
+        }
+
         let last_root = *last_root.read().unwrap();
         verify_shred_slots(slot, slot_meta.parent_slot, last_root)
     }
 
+    // Merkle data shreds chain the merkle root of the previous erasure set of their slot, and the
+    // data shreds of a slot are either all merkle shreds or none of them are. Returns the data
+    // shred of an adjacent erasure set which is inconsistent with `shred`, if there is one.
+    // Shreds of other variants carry no chained merkle root, so they are only checked against
+    // merkle shreds.
+    fn find_chained_merkle_root_conflict(
+        &self,
+        shred: &Shred,
+        just_inserted_data_shreds: &HashMap<(u64, u64), Shred>,
+    ) -> Option<Vec<u8>> {
+        let slot = shred.slot();
+        let shred_index = u64::from(shred.index());
+        let fec_set_index = u64::from(shred.common_header.fec_set_index);
+        let get_data_shred = |index: u64| -> Option<(Shred, Vec<u8>)> {
+            let payload = match just_inserted_data_shreds.get(&(slot, index)) {
+                Some(shred) => shred.payload.clone(),
+                None => self.get_data_shred(slot, index).ok().flatten()?,
+            };
+            let shred = Shred::new_from_serialized_shred(payload.clone()).ok()?;
+            Some((shred, payload))
+        };
+        // The last data shred of the previous erasure set
+        if let Some((prev_shred, payload)) = fec_set_index
+            .checked_sub(1)
+            .and_then(|index| get_data_shred(index))
+        {
+            if prev_shred.merkle_root() != shred.chained_merkle_root() {
+                return Some(payload);
+            }
+        }
+        // The first data shred of the next erasure set, if `shred` is the last of its own
+        if let Some((next_shred, payload)) = get_data_shred(shred_index + 1) {
+            if u64::from(next_shred.common_header.fec_set_index) == shred_index + 1
+                && next_shred.chained_merkle_root() != shred.merkle_root()
+            {
+                return Some(payload);
+            }
+        }
+        None
+    }
+
     fn insert_data_shred(
         &self,
         slot_meta: &mut SlotMeta,
@@ -1562,12 +1722,15 @@ impl Blockstore {
 
         // Commit step: commit all changes to the mutable structures at once, or none at all.
         // We don't want only a subset of these changes going through.
-        write_batch.put_bytes::<cf::ShredData>(
-            (slot, index),
-            // Payload will be padded out to SHRED_PAYLOAD_SIZE
-            // But only need to store the bytes within data_header.size
-            &shred.payload[..shred.data_header.size as usize],
-        )?;
+        // Payload will be padded out to SHRED_PAYLOAD_SIZE
+        // But only need to store the bytes within data_header.size, or up to the
+        // merkle proof for merkle shreds
+        let stored_size = if shred.is_merkle() {
+            SIZE_OF_MERKLE_DATA_SHRED_STORED
+        } else {
+            shred.data_header.size as usize
+        };
+        write_batch.put_bytes::<cf::ShredData>((slot, index), &shred.payload[..stored_size])?;
         data_index.set_present(index, true);
         let newly_completed_data_sets = update_slot_meta(
             last_in_slot,
@@ -2016,20 +2179,15 @@ impl Blockstore {
         iterator: impl Iterator<Item = VersionedTransaction>,
     ) -> Result<Vec<TransactionWithStatusMeta>> {
         iterator
-            .map(|versioned_tx| {
-                // TODO: add support for versioned transactions
-                if let Some(transaction) = versioned_tx.into_legacy_transaction() {
-                    let signature = transaction.signatures[0];
-                    Ok(TransactionWithStatusMeta {
-                        transaction,
-                        meta: self
-                            .read_transaction_status((signature, slot))
-                            .ok()
-                            .flatten(),
-                    })
-                } else {
-                    Err(BlockstoreError::UnsupportedTransactionVersion)
-                }
+            .map(|transaction| {
+                let signature = transaction.signatures[0];
+                Ok(TransactionWithStatusMeta {
+                    transaction,
+                    meta: self
+                        .read_transaction_status((signature, slot))
+                        .ok()
+                        .flatten(),
+                })
             })
             .collect()
     }
@@ -2174,6 +2332,51 @@ impl Blockstore {
         Ok(())
     }
 
+    /// Write the statuses, and memos, of many transactions in a single write batch
+    pub fn write_transaction_statuses(&self, records: Vec<TransactionStatusRecord>) -> Result<()> {
+        let max_slot = match records.iter().map(|record| record.slot).max() {
+            Some(max_slot) => max_slot,
+            None => return Ok(()),
+        };
+        // This write lock prevents interleaving issues with the transaction_status_index_cf by gating
+        // writes to that column
+        let w_active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+        let primary_index =
+            self.get_primary_index_to_write(max_slot, &w_active_transaction_status_index)?;
+        let mut write_batch = self.db.batch()?;
+        for record in records {
+            let TransactionStatusRecord {
+                slot,
+                signature,
+                writable_keys,
+                readonly_keys,
+                memos,
+                status,
+            } = record;
+            if let Some(memos) = memos {
+                write_batch.put::<cf::TransactionMemos>(signature, &memos)?;
+            }
+            write_batch.put_protobuf::<cf::TransactionStatus>(
+                (primary_index, signature, slot),
+                &status.into(),
+            )?;
+            for address in writable_keys {
+                write_batch.put::<cf::AddressSignatures>(
+                    (primary_index, address, slot, signature),
+                    &AddressSignatureMeta { writeable: true },
+                )?;
+            }
+            for address in readonly_keys {
+                write_batch.put::<cf::AddressSignatures>(
+                    (primary_index, address, slot, signature),
+                    &AddressSignatureMeta { writeable: false },
+                )?;
+            }
+        }
+        self.db.write(write_batch)
+    }
+
     pub fn read_transaction_memos(&self, signature: Signature) -> Result<Option<String>> {
         self.transaction_memos_cf.get(signature)
     }
@@ -2319,11 +2522,6 @@ impl Blockstore {
                 .find_transaction_in_slot(slot, signature)?
                 .ok_or(BlockstoreError::TransactionStatusSlotMismatch)?; // Should not happen
 
-            // TODO: support retrieving versioned transactions
-            let transaction = transaction
-                .into_legacy_transaction()
-                .ok_or(BlockstoreError::UnsupportedTransactionVersion)?;
-
             let block_time = self.get_block_time(slot)?;
             Ok(Some(ConfirmedTransaction {
                 slot,
@@ -2484,6 +2682,40 @@ impl Blockstore {
         Ok(slot_signatures)
     }
 
+    fn memo_matches(&self, signature: Signature, memo_scan: &mut Option<MemoScan>) -> Result<bool> {
+        match memo_scan {
+            None => Ok(true),
+            Some(memo_scan) => {
+                memo_scan.num_scanned += 1;
+                memo_scan.last_scanned = Some(signature);
+                Ok(self
+                    .read_transaction_memos(signature)?
+                    .map(|memo| memo.contains(memo_scan.memo))
+                    .unwrap_or(false))
+            }
+        }
+    }
+
+    fn filter_signatures_by_memo(
+        &self,
+        signatures: Vec<(Slot, Signature)>,
+        memo_scan: &mut Option<MemoScan>,
+    ) -> Result<Vec<(Slot, Signature)>> {
+        if memo_scan.is_none() {
+            return Ok(signatures);
+        }
+        let mut filtered_signatures = vec![];
+        for (slot, signature) in signatures {
+            if MemoScan::is_exhausted(memo_scan) {
+                break;
+            }
+            if self.memo_matches(signature, memo_scan)? {
+                filtered_signatures.push((slot, signature));
+            }
+        }
+        Ok(filtered_signatures)
+    }
+
     pub fn get_confirmed_signatures_for_address2(
         &self,
         address: Pubkey,
@@ -2492,6 +2724,36 @@ impl Blockstore {
         until: Option<Signature>,
         limit: usize,
     ) -> Result<Vec<ConfirmedTransactionStatusWithSignature>> {
+        self.get_confirmed_signatures_for_address_with_memo(
+            address,
+            highest_slot,
+            before,
+            until,
+            limit,
+            None,
+        )
+        .map(|page| page.infos)
+    }
+
+    /// Like `get_confirmed_signatures_for_address2`, but only returns transactions whose memo
+    /// contains that of `memo_filter`, if set. The memos are read from the TransactionMemos
+    /// column, so the transactions themselves are never deserialized, and the search stops after
+    /// reading `memo_filter.max_scanned` of them
+    pub fn get_confirmed_signatures_for_address_with_memo(
+        &self,
+        address: Pubkey,
+        highest_slot: Slot, // highest_confirmed_root or highest_confirmed_slot
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        memo_filter: Option<MemoFilter>,
+    ) -> Result<ConfirmedSignaturesForAddressPage> {
+        let mut memo_scan = memo_filter.map(|memo_filter| MemoScan {
+            memo: memo_filter.memo,
+            max_scanned: memo_filter.max_scanned,
+            num_scanned: 0,
+            last_scanned: None,
+        });
         datapoint_info!(
             "blockstore-rpc-api",
             (
@@ -2515,7 +2777,7 @@ impl Blockstore {
                 let transaction_status =
                     self.get_transaction_status(before, &confirmed_unrooted_slots)?;
                 match transaction_status {
-                    None => return Ok(vec![]),
+                    None => return Ok(ConfirmedSignaturesForAddressPage::default()),
                     Some((slot, _)) => {
                         let mut slot_signatures = self.get_sorted_block_signatures(slot)?;
                         if let Some(pos) = slot_signatures.iter().position(|&x| x == before) {
@@ -2564,14 +2826,9 @@ impl Blockstore {
         let mut signatures = self.find_address_signatures_for_slot(address, slot)?;
         signatures.reverse();
         if let Some(excluded_signatures) = before_excluded_signatures.take() {
-            address_signatures.extend(
-                signatures
-                    .into_iter()
-                    .filter(|(_, signature)| !excluded_signatures.contains(signature)),
-            )
-        } else {
-            address_signatures.append(&mut signatures);
+            signatures.retain(|(_, signature)| !excluded_signatures.contains(signature));
         }
+        address_signatures.extend(self.filter_signatures_by_memo(signatures, &mut memo_scan)?);
         get_initial_slot_timer.stop();
 
         // Check the active_transaction_status_index to see if it contains slot. If so, start with
@@ -2592,7 +2849,7 @@ impl Blockstore {
             ))?;
 
             // Iterate through starting_iterator until limit is reached
-            while address_signatures.len() < limit {
+            while address_signatures.len() < limit && !MemoScan::is_exhausted(&memo_scan) {
                 if let Some(((i, key_address, slot, signature), _)) = starting_iterator.next() {
                     if slot == next_max_slot || slot < lowest_slot {
                         break;
@@ -2601,7 +2858,9 @@ impl Blockstore {
                         && key_address == address
                         && slot >= first_available_block
                     {
-                        if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                        if (self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+                            && self.memo_matches(signature, &mut memo_scan)?
+                        {
                             address_signatures.push((slot, signature));
                         }
                         continue;
@@ -2615,7 +2874,8 @@ impl Blockstore {
                 let mut signatures =
                     self.find_address_signatures_for_slot(address, next_max_slot)?;
                 signatures.reverse();
-                address_signatures.append(&mut signatures);
+                address_signatures
+                    .extend(self.filter_signatures_by_memo(signatures, &mut memo_scan)?);
             }
         }
         starting_primary_index_iter_timer.stop();
@@ -2626,7 +2886,7 @@ impl Blockstore {
             (next_primary_index, address, slot, Signature::default()),
             IteratorDirection::Reverse,
         ))?;
-        while address_signatures.len() < limit {
+        while address_signatures.len() < limit && !MemoScan::is_exhausted(&memo_scan) {
             if let Some(((i, key_address, slot, signature), _)) = next_iterator.next() {
                 // Skip next_max_slot, which is already included
                 if slot == next_max_slot {
@@ -2639,7 +2899,9 @@ impl Blockstore {
                     && key_address == address
                     && slot >= first_available_block
                 {
-                    if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                    if (self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+                        && self.memo_matches(signature, &mut memo_scan)?
+                    {
                         address_signatures.push((slot, signature));
                     }
                     continue;
@@ -2653,6 +2915,14 @@ impl Blockstore {
             .filter(|(_, signature)| !until_excluded_signatures.contains(signature))
             .collect();
         address_signatures.truncate(limit);
+        // The search stopped either at the limit or at the memo scan bound
+        let next_before = if address_signatures.len() == limit {
+            address_signatures.last().map(|(_, signature)| *signature)
+        } else if MemoScan::is_exhausted(&memo_scan) {
+            memo_scan.and_then(|memo_scan| memo_scan.last_scanned)
+        } else {
+            None
+        };
 
         // Fill in the status information for each found transaction
         let mut get_status_info_timer = Measure::start("get_status_info_timer");
@@ -2707,7 +2977,10 @@ impl Blockstore {
             )
         );
 
-        Ok(infos)
+        Ok(ConfirmedSignaturesForAddressPage {
+            infos,
+            before: next_before,
+        })
     }
 
     pub fn read_rewards(&self, index: Slot) -> Result<Option<Rewards>> {
@@ -3098,12 +3371,82 @@ impl Blockstore {
         self.duplicate_slots_cf.put(slot, &duplicate_slot_proof)
     }
 
+    fn store_duplicate_block_evidence(
+        &self,
+        slot: Slot,
+        leader_pubkey: Option<Pubkey>,
+        shred1: &[u8],
+        shred2: &[u8],
+    ) {
+        if let Some(leader_pubkey) = leader_pubkey {
+            let evidence = EquivocationEvidence::DuplicateBlock {
+                shred1: shred1.to_vec(),
+                shred2: shred2.to_vec(),
+            };
+            if self
+                .store_equivocation_evidence(slot, &leader_pubkey, &evidence)
+                .is_err()
+            {
+                warn!("store equivocation evidence error");
+            }
+        }
+    }
+
     pub fn get_duplicate_slot(&self, slot: u64) -> Option<DuplicateSlotProof> {
         self.duplicate_slots_cf
             .get(slot)
             .expect("fetch from DuplicateSlots column family failed")
     }
 
+    /// Record `evidence` that `offender` equivocated in `slot`. Only the first evidence
+    /// recorded for a slot and offender is kept; returns whether `evidence` was stored
+    pub fn store_equivocation_evidence(
+        &self,
+        slot: Slot,
+        offender: &Pubkey,
+        evidence: &EquivocationEvidence,
+    ) -> Result<bool> {
+        if self
+            .equivocation_evidence_cf
+            .get((slot, *offender))?
+            .is_some()
+        {
+            return Ok(false);
+        }
+        self.equivocation_evidence_cf
+            .put((slot, *offender), evidence)?;
+        Ok(true)
+    }
+
+    pub fn get_equivocation_evidence(
+        &self,
+        slot: Slot,
+        offender: &Pubkey,
+    ) -> Result<Option<EquivocationEvidence>> {
+        self.equivocation_evidence_cf.get((slot, *offender))
+    }
+
+    /// Returns up to `limit` recorded equivocations in ascending slot order, starting at
+    /// `start_slot`
+    pub fn get_equivocation_evidence_from_slot(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+    ) -> Result<Vec<(Slot, Pubkey, EquivocationEvidence)>> {
+        Ok(self
+            .db
+            .iter::<cf::EquivocationEvidence>(IteratorMode::From(
+                (start_slot, Pubkey::default()),
+                IteratorDirection::Forward,
+            ))?
+            .take(limit)
+            .map(|((slot, offender), data)| {
+                let evidence = deserialize(&data).unwrap();
+                (slot, offender, evidence)
+            })
+            .collect())
+    }
+
     // `new_shred` is assumed to have slot and index equal to the given slot and index.
     // Returns the existing shred if `new_shred` is not equal to the existing shred at the
     // given slot and index as this implies the leader generated two different shreds with
@@ -4076,7 +4419,7 @@ pub mod tests {
     use crate::{
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
         leader_schedule::{FixedSchedule, LeaderSchedule},
-        shred::{max_ticks_per_n_shreds, DataShredHeader},
+        shred::{max_ticks_per_n_shreds, DataShredHeader, ProcessShredsStats},
     };
     use assert_matches::assert_matches;
     use bincode::serialize;
@@ -4084,10 +4427,14 @@ pub mod tests {
     use rand::{seq::SliceRandom, thread_rng};
     use solana_account_decoder::parse_token::UiTokenAmount;
     use solana_entry::entry::{next_entry, next_entry_mut};
-    use solana_runtime::bank::{Bank, RewardType};
+    use solana_runtime::bank::{Bank, RewardType, TransactionReturnData};
     use solana_sdk::{
         hash::{self, hash, Hash},
         instruction::CompiledInstruction,
+        message::{
+            v0::{self, MessageAddressTableLookup},
+            LoadedAddresses, MessageHeader, VersionedMessage,
+        },
         packet::PACKET_DATA_SIZE,
         pubkey::Pubkey,
         signature::Signature,
@@ -5670,6 +6017,91 @@ pub mod tests {
         ));
     }
 
+    #[test]
+    fn test_should_insert_data_shred_chained_merkle_root() {
+        solana_logger::setup();
+        let slot = 1;
+        let keypair = Keypair::new();
+        let entries = create_ticks(max_ticks_per_n_shreds(40, None), 0, Hash::default());
+        let make_merkle_data_shreds = |mut chained_merkle_root: Hash| {
+            let (data_shreds, _, _) = Shredder::new(slot, 0, 0, 0)
+                .unwrap()
+                .entries_to_merkle_shreds(
+                    &keypair,
+                    &entries,
+                    false, // is_last_in_slot
+                    0,     // next_shred_index
+                    &mut chained_merkle_root,
+                    &mut ProcessShredsStats::default(),
+                );
+            data_shreds
+        };
+        // Two versions of the slot, whose first erasure sets chain different merkle roots
+        let shreds = make_merkle_data_shreds(Hash::default());
+        let other_shreds = make_merkle_data_shreds(Hash::new_unique());
+        let next_fec_set_index = shreds
+            .iter()
+            .map(|shred| shred.common_header.fec_set_index)
+            .find(|fec_set_index| *fec_set_index > 0)
+            .unwrap();
+        let (first_set, second_set): (Vec<_>, Vec<_>) = shreds
+            .into_iter()
+            .partition(|shred| shred.common_header.fec_set_index < next_fec_set_index);
+        let last_root = RwLock::new(0);
+
+        // A shred chaining a different merkle root than the previous erasure set is rejected
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore
+            .insert_shreds(first_set.clone(), None, false)
+            .unwrap();
+        let slot_meta = blockstore.meta(slot).unwrap().unwrap();
+        assert!(blockstore.should_insert_data_shred(
+            &second_set[0],
+            &slot_meta,
+            &HashMap::new(),
+            &last_root,
+            None,
+            ShredSource::Turbine,
+        ));
+        assert!(!blockstore.has_duplicate_shreds_in_slot(slot));
+        let other_shred = &other_shreds[next_fec_set_index as usize];
+        assert!(!blockstore.should_insert_data_shred(
+            other_shred,
+            &slot_meta,
+            &HashMap::new(),
+            &last_root,
+            None,
+            ShredSource::Turbine,
+        ));
+        assert!(blockstore.has_duplicate_shreds_in_slot(slot));
+
+        // So is the last shred of an erasure set whose merkle root the next erasure set
+        // doesn't chain
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore.insert_shreds(second_set, None, false).unwrap();
+        let slot_meta = blockstore.meta(slot).unwrap().unwrap();
+        assert!(blockstore.should_insert_data_shred(
+            first_set.last().unwrap(),
+            &slot_meta,
+            &HashMap::new(),
+            &last_root,
+            None,
+            ShredSource::Turbine,
+        ));
+        let other_shred = &other_shreds[next_fec_set_index as usize - 1];
+        assert!(!blockstore.should_insert_data_shred(
+            other_shred,
+            &slot_meta,
+            &HashMap::new(),
+            &last_root,
+            None,
+            ShredSource::Repaired,
+        ));
+        assert!(blockstore.has_duplicate_shreds_in_slot(slot));
+    }
+
     #[test]
     pub fn test_is_data_shred_present() {
         let (shreds, _) = make_slot_entries(0, 0, 200);
@@ -6170,15 +6602,10 @@ pub mod tests {
             .cloned()
             .filter(|entry| !entry.is_tick())
             .flat_map(|entry| entry.transactions)
-            .map(|transaction| {
-                transaction
-                    .into_legacy_transaction()
-                    .expect("versioned transactions not supported")
-            })
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for i in 0..transaction.message.static_account_keys_len() {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -6193,6 +6620,9 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    log_entries: None,
                 }
                 .into();
                 blockstore
@@ -6209,6 +6639,9 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    log_entries: None,
                 }
                 .into();
                 blockstore
@@ -6225,6 +6658,9 @@ pub mod tests {
                     pre_token_balances: Some(vec![]),
                     post_token_balances: Some(vec![]),
                     rewards: Some(vec![]),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    log_entries: None,
                 }
                 .into();
                 blockstore
@@ -6243,6 +6679,9 @@ pub mod tests {
                         pre_token_balances: Some(vec![]),
                         post_token_balances: Some(vec![]),
                         rewards: Some(vec![]),
+                        return_data: None,
+                        loaded_addresses: LoadedAddresses::default(),
+                        log_entries: None,
                     }),
                 }
             })
@@ -6350,6 +6789,10 @@ pub mod tests {
         let pre_token_balances_vec = vec![];
         let post_token_balances_vec = vec![];
         let rewards_vec = vec![];
+        let test_loaded_addresses = LoadedAddresses {
+            writable: vec![Pubkey::new_unique()],
+            readonly: vec![Pubkey::new_unique()],
+        };
 
         // result not found
         assert!(transaction_status_cf
@@ -6368,6 +6811,9 @@ pub mod tests {
             pre_token_balances: Some(pre_token_balances_vec.clone()),
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
+            return_data: None,
+            loaded_addresses: test_loaded_addresses.clone(),
+            log_entries: None,
         }
         .into();
         assert!(transaction_status_cf
@@ -6385,6 +6831,9 @@ pub mod tests {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            ..
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((0, Signature::default(), 0))
             .unwrap()
@@ -6400,6 +6849,7 @@ pub mod tests {
         assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
+        assert_eq!(loaded_addresses, test_loaded_addresses);
 
         // insert value
         let status = TransactionStatusMeta {
@@ -6412,6 +6862,9 @@ pub mod tests {
             pre_token_balances: Some(pre_token_balances_vec.clone()),
             post_token_balances: Some(post_token_balances_vec.clone()),
             rewards: Some(rewards_vec.clone()),
+            return_data: None,
+            loaded_addresses: test_loaded_addresses.clone(),
+            log_entries: None,
         }
         .into();
         assert!(transaction_status_cf
@@ -6429,6 +6882,9 @@ pub mod tests {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            ..
         } = transaction_status_cf
             .get_protobuf_or_bincode::<StoredTransactionStatusMeta>((
                 0,
@@ -6450,6 +6906,7 @@ pub mod tests {
         assert_eq!(pre_token_balances.unwrap(), pre_token_balances_vec);
         assert_eq!(post_token_balances.unwrap(), post_token_balances_vec);
         assert_eq!(rewards.unwrap(), rewards_vec);
+        assert_eq!(loaded_addresses, test_loaded_addresses);
     }
 
     #[test]
@@ -6657,6 +7114,64 @@ pub mod tests {
         assert_eq!(first_address_entry.2, slot1);
     }
 
+    #[test]
+    fn test_write_transaction_statuses() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore.write_transaction_statuses(vec![]).unwrap();
+
+        let writable_key = Pubkey::new_unique();
+        let readonly_key = Pubkey::new_unique();
+        let records: Vec<_> = (10..13)
+            .map(|slot| TransactionStatusRecord {
+                slot,
+                signature: Signature::new_unique(),
+                writable_keys: vec![writable_key],
+                readonly_keys: vec![readonly_key],
+                memos: (slot == 11).then(|| "memo".to_string()),
+                status: TransactionStatusMeta {
+                    fee: slot,
+                    ..TransactionStatusMeta::default()
+                },
+            })
+            .collect();
+        blockstore
+            .write_transaction_statuses(records.clone())
+            .unwrap();
+
+        // The primary index covers the highest slot written
+        assert_eq!(
+            blockstore
+                .transaction_status_index_cf
+                .get(0)
+                .unwrap()
+                .unwrap()
+                .max_slot,
+            12
+        );
+        for record in &records {
+            assert_eq!(
+                blockstore
+                    .read_transaction_status((record.signature, record.slot))
+                    .unwrap(),
+                Some(record.status.clone())
+            );
+            assert_eq!(
+                blockstore.read_transaction_memos(record.signature).unwrap(),
+                record.memos
+            );
+        }
+        for key in [writable_key, readonly_key] {
+            let signatures: Vec<_> = blockstore
+                .find_address_signatures(key, 0, 12)
+                .unwrap()
+                .into_iter()
+                .map(|(_slot, signature)| signature)
+                .collect();
+            assert_eq!(signatures.len(), 3);
+        }
+    }
+
     #[test]
     fn test_get_transaction_status() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -6677,6 +7192,9 @@ pub mod tests {
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            log_entries: None,
         }
         .into();
 
@@ -6871,6 +7389,9 @@ pub mod tests {
             pre_token_balances: Some(vec![]),
             post_token_balances: Some(vec![]),
             rewards: Some(vec![]),
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            log_entries: None,
         }
         .into();
 
@@ -7026,14 +7547,10 @@ pub mod tests {
             .cloned()
             .filter(|entry| !entry.is_tick())
             .flat_map(|entry| entry.transactions)
-            .map(|tx| {
-                tx.into_legacy_transaction()
-                    .expect("versioned transactions not supported")
-            })
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for i in 0..transaction.message.static_account_keys_len() {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -7056,6 +7573,9 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    log_entries: None,
                 }
                 .into();
                 blockstore
@@ -7074,6 +7594,9 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        return_data: None,
+                        loaded_addresses: LoadedAddresses::default(),
+                        log_entries: None,
                     }),
                 }
             })
@@ -7130,14 +7653,10 @@ pub mod tests {
             .cloned()
             .filter(|entry| !entry.is_tick())
             .flat_map(|entry| entry.transactions)
-            .map(|tx| {
-                tx.into_legacy_transaction()
-                    .expect("versioned transactions not supported")
-            })
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                for i in 0..transaction.message.static_account_keys_len() {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -7160,6 +7679,9 @@ pub mod tests {
                     pre_token_balances: pre_token_balances.clone(),
                     post_token_balances: post_token_balances.clone(),
                     rewards: rewards.clone(),
+                    return_data: None,
+                    loaded_addresses: LoadedAddresses::default(),
+                    log_entries: None,
                 }
                 .into();
                 blockstore
@@ -7178,6 +7700,9 @@ pub mod tests {
                         pre_token_balances,
                         post_token_balances,
                         rewards,
+                        return_data: None,
+                        loaded_addresses: LoadedAddresses::default(),
+                        log_entries: None,
                     }),
                 }
             })
@@ -7861,6 +8386,132 @@ pub mod tests {
         assert!(results2.len() < results.len());
     }
 
+    #[test]
+    fn test_get_confirmed_signatures_for_address_with_memo() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let address = solana_sdk::pubkey::new_rand();
+        let mut signatures = vec![];
+        for slot in 1..=3 {
+            for i in 0..2 {
+                let signature = Signature::new(&[(slot * 2 + i) as u8; 64]);
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        signature,
+                        vec![&address],
+                        vec![],
+                        TransactionStatusMeta::default(),
+                    )
+                    .unwrap();
+                if i == 0 {
+                    blockstore
+                        .write_transaction_memos(&signature, format!("[5] hello {}", slot))
+                        .unwrap();
+                }
+                signatures.push(signature);
+            }
+        }
+        blockstore.set_roots(vec![1, 2, 3].iter()).unwrap();
+
+        let all = blockstore
+            .get_confirmed_signatures_for_address2(address, 3, None, None, usize::MAX)
+            .unwrap();
+        assert_eq!(all.len(), 6);
+
+        let memo_filter = |memo, max_scanned| Some(MemoFilter { memo, max_scanned });
+        let page = blockstore
+            .get_confirmed_signatures_for_address_with_memo(
+                address,
+                3,
+                None,
+                None,
+                usize::MAX,
+                memo_filter("hello", usize::MAX),
+            )
+            .unwrap();
+        assert_eq!(
+            page.infos
+                .iter()
+                .map(|result| (result.slot, result.memo.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, Some("[5] hello 3".to_string())),
+                (2, Some("[5] hello 2".to_string())),
+                (1, Some("[5] hello 1".to_string())),
+            ]
+        );
+        assert_eq!(page.before, None);
+
+        // The limit applies to matching transactions only
+        let page = blockstore
+            .get_confirmed_signatures_for_address_with_memo(
+                address,
+                3,
+                None,
+                None,
+                2,
+                memo_filter("hello", usize::MAX),
+            )
+            .unwrap();
+        assert_eq!(
+            page.infos
+                .iter()
+                .map(|result| result.slot)
+                .collect::<Vec<_>>(),
+            vec![3, 2]
+        );
+        assert_eq!(page.before, Some(signatures[2]));
+
+        let page = blockstore
+            .get_confirmed_signatures_for_address_with_memo(
+                address,
+                3,
+                None,
+                None,
+                usize::MAX,
+                memo_filter("hello 2", usize::MAX),
+            )
+            .unwrap();
+        assert_eq!(page.infos.len(), 1);
+        assert_eq!(page.infos[0].signature, signatures[2]);
+
+        let page = blockstore
+            .get_confirmed_signatures_for_address_with_memo(
+                address,
+                3,
+                None,
+                None,
+                usize::MAX,
+                memo_filter("goodbye", usize::MAX),
+            )
+            .unwrap();
+        assert!(page.infos.is_empty());
+        assert_eq!(page.before, None);
+
+        // The search stops once it's read as many memos as allowed, and continues from the last
+        // one read
+        let page = blockstore
+            .get_confirmed_signatures_for_address_with_memo(
+                address,
+                3,
+                None,
+                None,
+                usize::MAX,
+                memo_filter("hello", 3),
+            )
+            .unwrap();
+        assert_eq!(
+            page.infos
+                .iter()
+                .map(|result| result.signature)
+                .collect::<Vec<_>>(),
+            vec![signatures[4]]
+        );
+        assert_eq!(page.before, Some(signatures[3]));
+    }
+
     #[test]
     #[allow(clippy::same_item_push)]
     fn test_get_last_hash() {
@@ -7907,6 +8558,9 @@ pub mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: LoadedAddresses::default(),
+                log_entries: None,
             }
             .into();
             transaction_status_cf
@@ -7925,15 +8579,109 @@ pub mod tests {
             )
             .into(),
         );
+        // Push a v0 transaction, its status records the addresses it loaded
+        let v0_transaction = make_v0_transaction();
+        let v0_status = TransactionStatusMeta {
+            loaded_addresses: LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![],
+            },
+            ..TransactionStatusMeta::default()
+        };
+        transaction_status_cf
+            .put_protobuf(
+                (0, v0_transaction.signatures[0], slot),
+                &v0_status.clone().into(),
+            )
+            .unwrap();
+        transactions.push(v0_transaction.clone());
 
         let map_result = blockstore.map_transactions_to_statuses(slot, transactions.into_iter());
         assert!(map_result.is_ok());
         let map = map_result.unwrap();
-        assert_eq!(map.len(), 5);
+        assert_eq!(map.len(), 6);
         for (x, m) in map.iter().take(4).enumerate() {
             assert_eq!(m.meta.as_ref().unwrap().fee, x as u64);
         }
         assert_eq!(map[4].meta, None);
+        assert_eq!(map[5].transaction, v0_transaction);
+        assert_eq!(
+            map[5].meta.as_ref().unwrap().loaded_addresses,
+            v0_status.loaded_addresses
+        );
+    }
+
+    fn make_v0_transaction() -> VersionedTransaction {
+        let payer = Keypair::new();
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer.pubkey(), solana_sdk::pubkey::new_rand()],
+            recent_blockhash: Hash::default(),
+            instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2])],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: solana_sdk::pubkey::new_rand(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        });
+        VersionedTransaction::try_new(message, &[&payer]).unwrap()
+    }
+
+    #[test]
+    fn test_get_rooted_v0_transaction() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let slot = 2;
+        let transaction = make_v0_transaction();
+        let entry = Entry {
+            num_hashes: 1,
+            hash: Hash::default(),
+            transactions: vec![transaction.clone()],
+        };
+        let shreds = entries_to_test_shreds(vec![entry], slot, slot - 1, true, 0);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots(vec![slot - 1, slot].iter()).unwrap();
+
+        let loaded_address = solana_sdk::pubkey::new_rand();
+        let status = TransactionStatusMeta {
+            fee: 42,
+            loaded_addresses: LoadedAddresses {
+                writable: vec![loaded_address],
+                readonly: vec![],
+            },
+            ..TransactionStatusMeta::default()
+        };
+        let signature = transaction.signatures[0];
+        blockstore
+            .write_transaction_status(
+                slot,
+                signature,
+                transaction
+                    .message
+                    .static_account_keys_iter()
+                    .chain(std::iter::once(&loaded_address))
+                    .collect(),
+                vec![],
+                status.clone(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            blockstore.get_rooted_transaction(signature).unwrap(),
+            Some(ConfirmedTransaction {
+                slot,
+                transaction: TransactionWithStatusMeta {
+                    transaction,
+                    meta: Some(status),
+                },
+                block_time: None,
+            })
+        );
     }
 
     #[test]
@@ -8263,6 +9011,58 @@ pub mod tests {
         assert_eq!(duplicate_proof.shred2, duplicate_shred.payload);
     }
 
+    #[test]
+    fn test_equivocation_evidence() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let leader = Pubkey::new_unique();
+        let other_leader = Pubkey::new_unique();
+        let evidence = |byte| EquivocationEvidence::DuplicateBlock {
+            shred1: vec![byte; 4],
+            shred2: vec![byte + 1; 4],
+        };
+        assert_eq!(
+            blockstore.get_equivocation_evidence(5, &leader).unwrap(),
+            None
+        );
+
+        assert!(blockstore
+            .store_equivocation_evidence(5, &leader, &evidence(1))
+            .unwrap());
+        // The first evidence for a slot and offender is kept
+        assert!(!blockstore
+            .store_equivocation_evidence(5, &leader, &evidence(3))
+            .unwrap());
+        assert_eq!(
+            blockstore.get_equivocation_evidence(5, &leader).unwrap(),
+            Some(evidence(1))
+        );
+
+        assert!(blockstore
+            .store_equivocation_evidence(3, &other_leader, &evidence(5))
+            .unwrap());
+        assert!(blockstore
+            .store_equivocation_evidence(7, &leader, &evidence(7))
+            .unwrap());
+        assert_eq!(
+            blockstore
+                .get_equivocation_evidence_from_slot(0, 10)
+                .unwrap(),
+            vec![
+                (3, other_leader, evidence(5)),
+                (5, leader, evidence(1)),
+                (7, leader, evidence(7)),
+            ]
+        );
+        assert_eq!(
+            blockstore
+                .get_equivocation_evidence_from_slot(4, 1)
+                .unwrap(),
+            vec![(5, leader, evidence(1))]
+        );
+    }
+
     #[test]
     fn test_clear_unconfirmed_slot() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -8427,6 +9227,7 @@ pub mod tests {
                     ui_amount_string: "1.1".to_string(),
                 },
                 owner: Pubkey::new_unique().to_string(),
+                program_id: Pubkey::new_unique().to_string(),
             }]),
             post_token_balances: Some(vec![TransactionTokenBalance {
                 account_index: 0,
@@ -8438,6 +9239,7 @@ pub mod tests {
                     ui_amount_string: "1.1".to_string(),
                 },
                 owner: Pubkey::new_unique().to_string(),
+                program_id: Pubkey::new_unique().to_string(),
             }]),
             rewards: Some(vec![Reward {
                 pubkey: "My11111111111111111111111111111111111111111".to_string(),
@@ -8446,6 +9248,15 @@ pub mod tests {
                 reward_type: Some(RewardType::Rent),
                 commission: None,
             }]),
+            return_data: Some(TransactionReturnData {
+                program_id: Pubkey::new_unique(),
+                data: vec![1, 2, 3],
+            }),
+            loaded_addresses: LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            },
+            log_entries: None,
         };
         let deprecated_status: StoredTransactionStatusMeta = status.clone().into();
         let protobuf_status: generated::TransactionStatusMeta = status.into();
@@ -77,6 +77,8 @@ const PERF_SAMPLES_CF: &str = "perf_samples";
 const BLOCK_HEIGHT_CF: &str = "block_height";
 /// Column family for ProgramCosts
 const PROGRAM_COSTS_CF: &str = "program_costs";
+/// Column family for equivocation evidence
+const EQUIVOCATION_EVIDENCE_CF: &str = "equivocation_evidence";
 
 // 1 day is chosen for the same reasoning of DEFAULT_COMPACTION_SLOT_INTERVAL
 const PERIODIC_COMPACTION_SECONDS: u64 = 60 * 60 * 24;
@@ -193,6 +195,14 @@ pub mod columns {
     #[derive(Debug)]
     // The program costs column
     pub struct ProgramCosts;
+
+    #[derive(Debug)]
+    /// The equivocation evidence column
+    ///
+    /// index type: (Slot, Pubkey) where the Pubkey is the offending validator identity or vote
+    /// account
+    /// value type: blockstore_meta::EquivocationEvidence
+    pub struct EquivocationEvidence;
 }
 
 pub enum AccessType {
@@ -367,6 +377,10 @@ impl Rocks {
             ProgramCosts::NAME,
             get_cf_options::<ProgramCosts>(&access_type, &oldest_slot),
         );
+        let equivocation_evidence_cf_descriptor = ColumnFamilyDescriptor::new(
+            EquivocationEvidence::NAME,
+            get_cf_options::<EquivocationEvidence>(&access_type, &oldest_slot),
+        );
         // Don't forget to add to both run_purge_with_stats() and
         // compact_storage() in ledger/src/blockstore/blockstore_purge.rs!!
 
@@ -393,6 +407,10 @@ impl Rocks {
             (PerfSamples::NAME, perf_samples_cf_descriptor),
             (BlockHeight::NAME, block_height_cf_descriptor),
             (ProgramCosts::NAME, program_costs_cf_descriptor),
+            (
+                EquivocationEvidence::NAME,
+                equivocation_evidence_cf_descriptor,
+            ),
         ];
         let cf_names: Vec<_> = cfs.iter().map(|c| c.0).collect();
 
@@ -513,6 +531,7 @@ impl Rocks {
             PerfSamples::NAME,
             BlockHeight::NAME,
             ProgramCosts::NAME,
+            EquivocationEvidence::NAME,
         ]
     }
 
@@ -852,6 +871,38 @@ impl Column for columns::ProgramCosts {
     }
 }
 
+impl ColumnName for columns::EquivocationEvidence {
+    const NAME: &'static str = EQUIVOCATION_EVIDENCE_CF;
+}
+impl TypedColumn for columns::EquivocationEvidence {
+    type Type = blockstore_meta::EquivocationEvidence;
+}
+impl Column for columns::EquivocationEvidence {
+    type Index = (Slot, Pubkey);
+
+    fn key((slot, pubkey): (Slot, Pubkey)) -> Vec<u8> {
+        let mut key = vec![0; 8 + 32]; // size_of Slot + size_of Pubkey
+        BigEndian::write_u64(&mut key[..8], slot);
+        key[8..40].clone_from_slice(&pubkey.as_ref()[0..32]);
+        key
+    }
+
+    fn index(key: &[u8]) -> (Slot, Pubkey) {
+        let slot = BigEndian::read_u64(&key[..8]);
+        let pubkey = Pubkey::new(&key[8..40]);
+        (slot, pubkey)
+    }
+
+    fn primary_index(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, Pubkey::default())
+    }
+}
+
 impl Column for columns::ShredCode {
     type Index = (u64, u64);
 
@@ -1286,6 +1337,18 @@ impl<'a> WriteBatch<'a> {
         Ok(())
     }
 
+    pub fn put_protobuf<C: ProtobufColumn + ColumnName>(
+        &mut self,
+        key: C::Index,
+        value: &C::Type,
+    ) -> Result<()> {
+        let mut buf = Vec::with_capacity(value.encoded_len());
+        value.encode(&mut buf)?;
+        self.write_batch
+            .put_cf(self.get_cf::<C>(), &C::key(key), &buf);
+        Ok(())
+    }
+
     #[inline]
     fn get_cf<C: Column + ColumnName>(&self) -> &'a ColumnFamily {
         self.map[C::NAME]
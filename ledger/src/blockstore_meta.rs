@@ -1,6 +1,6 @@
 use crate::erasure::ErasureConfig;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{clock::Slot, hash::Hash};
+use solana_sdk::{clock::Slot, hash::Hash, transaction::Transaction};
 use std::{collections::BTreeSet, ops::RangeBounds};
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
@@ -68,6 +68,23 @@ pub struct DuplicateSlotProof {
     pub shred2: Vec<u8>,
 }
 
+/// Proof that a validator equivocated in a slot
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum EquivocationEvidence {
+    /// Two different shreds the leader signed for the same slot and index
+    DuplicateBlock {
+        #[serde(with = "serde_bytes")]
+        shred1: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        shred2: Vec<u8>,
+    },
+    /// Two votes signed by the same vote account for the same slot on different bank hashes
+    ConflictingVotes {
+        vote_transaction1: Transaction,
+        vote_transaction2: Transaction,
+    },
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ErasureMetaStatus {
     CanRecover,
@@ -1,10 +1,15 @@
 use crate::{
-    block_error::BlockError, blockstore::Blockstore, blockstore_db::BlockstoreError,
-    blockstore_meta::SlotMeta, leader_schedule_cache::LeaderScheduleCache,
+    block_error::BlockError,
+    blockstore::{Blockstore, TransactionStatusRecord},
+    blockstore_db::BlockstoreError,
+    blockstore_meta::SlotMeta,
+    entry_notifier_interface::{EntryNotifierLock, EntrySummary},
+    leader_schedule_cache::LeaderScheduleCache,
+    transaction_status_spill::TransactionStatusSpill,
 };
 use chrono_humanize::{Accuracy, HumanTime, Tense};
-use crossbeam_channel::Sender;
-use itertools::Itertools;
+use crossbeam_channel::{Sender, TrySendError};
+use itertools::{izip, Itertools};
 use log::*;
 use rand::{seq::SliceRandom, thread_rng};
 use rayon::{prelude::*, ThreadPool};
@@ -12,7 +17,7 @@ use solana_entry::entry::{
     self, create_ticks, Entry, EntrySlice, EntryType, EntryVerificationStatus, VerifyRecyclers,
 };
 use solana_measure::measure::Measure;
-use solana_metrics::{datapoint_error, inc_new_counter_debug};
+use solana_metrics::{datapoint_error, inc_new_counter_debug, inc_new_counter_info};
 use solana_rayon_threadlimit::get_thread_count;
 use solana_runtime::{
     accounts_db::{AccountShrinkThreshold, AccountsDbConfig},
@@ -20,15 +25,19 @@ use solana_runtime::{
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     bank::{
         Bank, ExecuteTimings, InnerInstructionsList, RentDebits, TransactionBalancesSet,
-        TransactionExecutionResult, TransactionLogMessages, TransactionResults,
+        TransactionExecutionResult, TransactionLogEntries, TransactionResults,
+        TransactionReturnData,
     },
     bank_forks::BankForks,
     bank_utils,
     block_cost_limits::*,
     commitment::VOTE_THRESHOLD_SIZE,
+    log_collector,
     snapshot_config::SnapshotConfig,
     snapshot_package::{AccountsPackageSender, SnapshotType},
-    snapshot_utils::{self, BankFromArchiveTimings},
+    snapshot_utils::{
+        self, BankFromArchiveTimings, SnapshotLoadProgressCallback, SnapshotVerification,
+    },
     transaction_batch::TransactionBatch,
     vote_account::VoteAccount,
     vote_sender_types::ReplayVoteSender,
@@ -43,15 +52,17 @@ use solana_sdk::{
     timing,
     transaction::{Result, SanitizedTransaction, TransactionError, VersionedTransaction},
 };
-use solana_transaction_status::token_balances::{
-    collect_token_balances, TransactionTokenBalancesSet,
+use solana_transaction_status::{
+    extract_and_fmt_memos,
+    token_balances::{collect_token_balances, TransactionTokenBalancesSet},
+    InnerInstructions, Reward, TransactionStatusMeta,
 };
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
     path::PathBuf,
     result,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
     time::{Duration, Instant},
 };
 use thiserror::Error;
@@ -158,19 +169,22 @@ fn execute_batch(
     timings: &mut ExecuteTimings,
     cost_capacity_meter: Arc<RwLock<BlockCostCapacityMeter>>,
 ) -> Result<()> {
-    let record_token_balances = transaction_status_sender.is_some();
-
     let mut mint_decimals: HashMap<Pubkey, u8> = HashMap::new();
 
-    let pre_token_balances = if record_token_balances {
-        collect_token_balances(bank, batch, &mut mint_decimals)
+    let pre_token_balances = if let Some(transaction_status_sender) = transaction_status_sender {
+        collect_token_balances(
+            bank,
+            batch,
+            &mut mint_decimals,
+            &transaction_status_sender.token_program_ids,
+        )
     } else {
         vec![]
     };
 
     let pre_process_units: u64 = aggregate_total_execution_units(timings);
 
-    let (tx_results, balances, inner_instructions, transaction_logs) =
+    let (tx_results, balances, inner_instructions, transaction_logs, transaction_return_data, _) =
         batch.bank().load_execute_and_commit_transactions(
             batch,
             MAX_PROCESSING_AGE,
@@ -218,11 +232,12 @@ fn execute_batch(
 
     if let Some(transaction_status_sender) = transaction_status_sender {
         let transactions = batch.sanitized_transactions().to_vec();
-        let post_token_balances = if record_token_balances {
-            collect_token_balances(bank, batch, &mut mint_decimals)
-        } else {
-            vec![]
-        };
+        let post_token_balances = collect_token_balances(
+            bank,
+            batch,
+            &mut mint_decimals,
+            &transaction_status_sender.token_program_ids,
+        );
 
         let token_balances =
             TransactionTokenBalancesSet::new(pre_token_balances, post_token_balances);
@@ -235,6 +250,7 @@ fn execute_batch(
             token_balances,
             inner_instructions,
             transaction_logs,
+            transaction_return_data,
             rent_debits,
         );
     }
@@ -322,6 +338,121 @@ pub fn process_entries(
     result
 }
 
+/// The most recent waves in which an account is written and read by scheduled entries
+#[derive(Default)]
+struct AccountWaves {
+    write: Option<usize>,
+    read: Option<usize>,
+}
+
+/// Schedules consecutive entries into waves of mutually non-conflicting entries. Every entry is
+/// scheduled in a later wave than all of the earlier entries it conflicts with, so executing the
+/// waves in order preserves the recorded order semantics while an entry doesn't wait for earlier
+/// entries it doesn't conflict with.  The transactions of an entry are executed together, in
+/// their recorded order.
+#[derive(Default)]
+struct ReplayScheduler {
+    account_waves: HashMap<Pubkey, AccountWaves>,
+    num_waves: usize,
+}
+
+impl ReplayScheduler {
+    /// Returns the wave of the entry, or `None` if the entry conflicts with itself, which should
+    /// not happen if generated by a properly functioning leader
+    fn schedule_entry(
+        &mut self,
+        transactions: &[SanitizedTransaction],
+        demote_program_write_locks: bool,
+    ) -> Option<usize> {
+        let account_locks: Vec<_> = transactions
+            .iter()
+            .map(|tx| tx.get_account_locks(demote_program_write_locks))
+            .collect();
+
+        let mut entry_writes = HashSet::new();
+        let mut entry_reads = HashSet::new();
+        for locks in &account_locks {
+            for key in locks.writable.iter().copied() {
+                if !entry_writes.insert(key) || entry_reads.contains(key) {
+                    return None;
+                }
+            }
+            for key in locks.readonly.iter().copied() {
+                if entry_writes.contains(key) {
+                    return None;
+                }
+                entry_reads.insert(key);
+            }
+        }
+
+        let after_writes_and_reads = account_locks
+            .iter()
+            .flat_map(|locks| locks.writable.iter())
+            .filter_map(|key| self.account_waves.get(*key))
+            .flat_map(|account_waves| account_waves.write.max(account_waves.read));
+        let after_writes = account_locks
+            .iter()
+            .flat_map(|locks| locks.readonly.iter())
+            .filter_map(|key| self.account_waves.get(*key))
+            .flat_map(|account_waves| account_waves.write);
+        let wave = after_writes_and_reads
+            .chain(after_writes)
+            .map(|wave| wave + 1)
+            .max()
+            .unwrap_or(0);
+
+        for locks in &account_locks {
+            for key in locks.writable.iter().copied() {
+                let account_waves = self.account_waves.entry(*key).or_default();
+                account_waves.write = account_waves.write.max(Some(wave));
+            }
+            for key in locks.readonly.iter().copied() {
+                let account_waves = self.account_waves.entry(*key).or_default();
+                account_waves.read = account_waves.read.max(Some(wave));
+            }
+        }
+        self.num_waves = self.num_waves.max(wave + 1);
+        Some(wave)
+    }
+}
+
+type ScheduledEntry<'a> = (&'a [SanitizedTransaction], usize);
+
+/// Executes scheduled entries wave by wave, the entries of each wave in parallel
+#[allow(clippy::too_many_arguments)]
+fn execute_scheduled_entries(
+    bank: &Arc<Bank>,
+    scheduled_entries: &[ScheduledEntry],
+    num_waves: usize,
+    entry_callback: Option<&ProcessCallback>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    replay_vote_sender: Option<&ReplayVoteSender>,
+    timings: &mut ExecuteTimings,
+    cost_capacity_meter: Arc<RwLock<BlockCostCapacityMeter>>,
+) -> Result<()> {
+    for wave in 0..num_waves {
+        let mut batches = vec![];
+        for (transactions, entry_wave) in scheduled_entries {
+            if *entry_wave != wave {
+                continue;
+            }
+            let batch = bank.prepare_sanitized_batch(transactions);
+            first_err(batch.lock_results())?;
+            batches.push(batch);
+        }
+        execute_batches(
+            bank,
+            &batches,
+            entry_callback,
+            transaction_status_sender,
+            replay_vote_sender,
+            timings,
+            cost_capacity_meter.clone(),
+        )?;
+    }
+    Ok(())
+}
+
 // Note: If randomize is true this will shuffle entries' transactions in-place.
 fn process_entries_with_callback(
     bank: &Arc<Bank>,
@@ -333,10 +464,12 @@ fn process_entries_with_callback(
     timings: &mut ExecuteTimings,
     cost_capacity_meter: Arc<RwLock<BlockCostCapacityMeter>>,
 ) -> Result<()> {
-    // accumulator for entries that can be processed in parallel
-    let mut batches = vec![];
+    // accumulator for entries that are scheduled to be processed together
+    let mut scheduler = ReplayScheduler::default();
+    let mut scheduled_entries = vec![];
     let mut tick_hashes = vec![];
     let mut rng = thread_rng();
+    let demote_program_write_locks = bank.demote_program_write_locks();
 
     for entry in entries {
         match entry {
@@ -345,17 +478,19 @@ fn process_entries_with_callback(
                 tick_hashes.push(hash);
                 if bank.is_block_boundary(bank.tick_height() + tick_hashes.len() as u64) {
                     // If it's a tick that will cause a new blockhash to be created,
-                    // execute the group and register the tick
-                    execute_batches(
+                    // execute the scheduled entries and register the tick
+                    execute_scheduled_entries(
                         bank,
-                        &batches,
+                        &scheduled_entries,
+                        scheduler.num_waves,
                         entry_callback,
                         transaction_status_sender,
                         replay_vote_sender,
                         timings,
                         cost_capacity_meter.clone(),
                     )?;
-                    batches.clear();
+                    scheduled_entries.clear();
+                    scheduler = ReplayScheduler::default();
                     for hash in &tick_hashes {
                         bank.register_tick(hash);
                     }
@@ -367,55 +502,65 @@ fn process_entries_with_callback(
                     transactions.shuffle(&mut rng);
                 }
 
-                loop {
-                    // try to lock the accounts
-                    let batch = bank.prepare_sanitized_batch(transactions);
-                    let first_lock_err = first_err(batch.lock_results());
+                let transactions: &[SanitizedTransaction] = transactions;
 
-                    // if locking worked
-                    if first_lock_err.is_ok() {
-                        batches.push(batch);
-                        // done with this entry
-                        break;
-                    }
-                    // else we failed to lock, 2 possible reasons
-                    if batches.is_empty() {
-                        // An entry has account lock conflicts with *itself*, which should not happen
-                        // if generated by a properly functioning leader
-                        datapoint_error!(
-                            "validator_process_entry_error",
-                            (
-                                "error",
-                                format!(
-                                    "Lock accounts error, entry conflicts with itself, txs: {:?}",
-                                    transactions
-                                ),
-                                String
-                            )
-                        );
-                        // bail
-                        first_lock_err?;
-                    } else {
-                        // else we have an entry that conflicts with a prior entry
-                        // execute the current queue and try to process this entry again
-                        execute_batches(
-                            bank,
-                            &batches,
-                            entry_callback,
-                            transaction_status_sender,
-                            replay_vote_sender,
-                            timings,
-                            cost_capacity_meter.clone(),
-                        )?;
-                        batches.clear();
-                    }
+                if let Some(wave) =
+                    scheduler.schedule_entry(transactions, demote_program_write_locks)
+                {
+                    scheduled_entries.push((transactions, wave));
+                    continue;
                 }
+
+                // the entry conflicts with itself, execute the prior entries and try to lock it
+                // on its own for the lock error
+                execute_scheduled_entries(
+                    bank,
+                    &scheduled_entries,
+                    scheduler.num_waves,
+                    entry_callback,
+                    transaction_status_sender,
+                    replay_vote_sender,
+                    timings,
+                    cost_capacity_meter.clone(),
+                )?;
+                scheduled_entries.clear();
+                scheduler = ReplayScheduler::default();
+
+                let batch = bank.prepare_sanitized_batch(transactions);
+                let first_lock_err = first_err(batch.lock_results());
+                if first_lock_err.is_err() {
+                    // An entry has account lock conflicts with *itself*, which should not happen
+                    // if generated by a properly functioning leader
+                    datapoint_error!(
+                        "validator_process_entry_error",
+                        (
+                            "error",
+                            format!(
+                                "Lock accounts error, entry conflicts with itself, txs: {:?}",
+                                transactions
+                            ),
+                            String
+                        )
+                    );
+                    // bail
+                    first_lock_err?;
+                }
+                execute_batches(
+                    bank,
+                    &[batch],
+                    entry_callback,
+                    transaction_status_sender,
+                    replay_vote_sender,
+                    timings,
+                    cost_capacity_meter.clone(),
+                )?;
             }
         }
     }
-    execute_batches(
+    execute_scheduled_entries(
         bank,
-        &batches,
+        &scheduled_entries,
+        scheduler.num_waves,
         entry_callback,
         transaction_status_sender,
         replay_vote_sender,
@@ -450,11 +595,18 @@ pub enum BlockstoreProcessorError {
 
     #[error("root bank with mismatched capitalization at {0}")]
     RootBankWithMismatchedCapitalization(Slot),
+
+    #[error("no loadable snapshot archive left")]
+    NoLoadableSnapshotArchive,
 }
 
 /// Callback for accessing bank state while processing the blockstore
 pub type ProcessCallback = Arc<dyn Fn(&Bank) + Sync + Send>;
 
+/// Callback notified of the replay progress while processing the blockstore, with the slot being
+/// replayed and the highest root in the blockstore
+pub type ReplayProgressCallback = Arc<dyn Fn(Slot, Slot) + Sync + Send>;
+
 #[derive(Default, Clone)]
 pub struct ProcessOptions {
     pub bpf_jit: bool,
@@ -475,6 +627,13 @@ pub struct ProcessOptions {
     pub accounts_db_config: Option<AccountsDbConfig>,
     pub verify_index: bool,
     pub shrink_ratio: AccountShrinkThreshold,
+    pub snapshot_verification: SnapshotVerification,
+    /// Move snapshot archives with corrupted account storages out of the way and load the next
+    /// one instead, rather than failing
+    pub quarantine_corrupted_snapshot_archives: bool,
+    pub snapshot_load_progress_callback: Option<SnapshotLoadProgressCallback>,
+    pub replay_progress_callback: Option<ReplayProgressCallback>,
+    pub entry_notifier: Option<EntryNotifierLock>,
 }
 
 pub fn process_blockstore(
@@ -790,6 +949,7 @@ fn confirm_full_slot(
         skip_verification,
         transaction_status_sender,
         replay_vote_sender,
+        opts.entry_notifier.as_ref(),
         opts.entry_callback.as_ref(),
         recyclers,
         opts.allow_dead_slots,
@@ -857,11 +1017,13 @@ pub fn confirm_slot(
     skip_verification: bool,
     transaction_status_sender: Option<&TransactionStatusSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
+    entry_notifier: Option<&EntryNotifierLock>,
     entry_callback: Option<&ProcessCallback>,
     recyclers: &VerifyRecyclers,
     allow_dead_slots: bool,
 ) -> result::Result<(), BlockstoreProcessorError> {
     let slot = bank.slot();
+    let _slot_context = solana_logger::slot_context(slot);
 
     let (entries, num_shreds, slot_full) = {
         let mut load_elapsed = Measure::start("load_elapsed");
@@ -907,9 +1069,23 @@ pub fn confirm_slot(
     }
 
     let last_entry_hash = entries.last().map(|e| e.hash);
+    // Summarize the entries before they are consumed by replay, the notifications are only
+    // sent once the entries are executed and verified
+    let entry_summaries: Option<Vec<_>> = entry_notifier.map(|_| {
+        entries
+            .iter()
+            .map(|entry| EntrySummary {
+                num_hashes: entry.num_hashes,
+                hash: entry.hash,
+                num_transactions: entry.transactions.len() as u64,
+            })
+            .collect()
+    });
     let verifier = if !skip_verification {
         datapoint_debug!("verify-batch-size", ("size", num_entries as i64, i64));
-        let entry_state = entries.start_verify(&progress.last_entry, recyclers.clone());
+        // PoH verification runs concurrently with the replay of the entries below, and is
+        // only waited on once replay is done
+        let entry_state = entries.start_verify_pipelined(&progress.last_entry, recyclers.clone());
         if entry_state.status() == EntryVerificationStatus::Failure {
             warn!("Ledger proof of history failed at slot: {}", slot);
             return Err(BlockError::InvalidEntryHash.into());
@@ -961,6 +1137,13 @@ pub fn confirm_slot(
 
     process_result?;
 
+    if let (Some(entry_notifier), Some(entry_summaries)) = (entry_notifier, entry_summaries) {
+        let entry_notifier = entry_notifier.read().unwrap();
+        for (offset, entry_summary) in entry_summaries.iter().enumerate() {
+            entry_notifier.notify_entry(slot, progress.num_entries + offset, entry_summary);
+        }
+    }
+
     progress.num_shreds += num_shreds;
     progress.num_entries += num_entries;
     progress.num_txs += num_txs;
@@ -1100,6 +1283,9 @@ fn load_frozen_forks(
             if last_status_report.elapsed() > Duration::from_secs(2) {
                 let secs = last_status_report.elapsed().as_secs() as f32;
                 last_status_report = Instant::now();
+                if let Some(replay_progress_callback) = &opts.replay_progress_callback {
+                    replay_progress_callback(slot, max_root);
+                }
                 info!(
                     "processing ledger: slot={}, last root slot={} slots={} slots/s={:?} txs/s={}",
                     slot,
@@ -1368,14 +1554,177 @@ pub struct TransactionStatusBatch {
     pub balances: TransactionBalancesSet,
     pub token_balances: TransactionTokenBalancesSet,
     pub inner_instructions: Option<Vec<Option<InnerInstructionsList>>>,
-    pub transaction_logs: Option<Vec<Option<TransactionLogMessages>>>,
+    pub transaction_logs: Option<Vec<Option<TransactionLogEntries>>>,
+    pub transaction_return_data: Vec<Option<TransactionReturnData>>,
     pub rent_debits: Vec<RentDebits>,
 }
 
+impl TransactionStatusBatch {
+    /// The statuses of the committed transactions of the batch, as written to the blockstore
+    pub fn into_records(self) -> Vec<TransactionStatusRecord> {
+        let TransactionStatusBatch {
+            bank,
+            transactions,
+            statuses,
+            balances,
+            token_balances,
+            inner_instructions,
+            transaction_logs,
+            transaction_return_data,
+            rent_debits,
+        } = self;
+        let slot = bank.slot();
+        let inner_instructions_iter: Box<dyn Iterator<Item = Option<InnerInstructionsList>>> =
+            if let Some(inner_instructions) = inner_instructions {
+                Box::new(inner_instructions.into_iter())
+            } else {
+                Box::new(std::iter::repeat_with(|| None))
+            };
+        let transaction_logs_iter: Box<dyn Iterator<Item = Option<TransactionLogEntries>>> =
+            if let Some(transaction_logs) = transaction_logs {
+                Box::new(transaction_logs.into_iter())
+            } else {
+                Box::new(std::iter::repeat_with(|| None))
+            };
+        let mut records = Vec::with_capacity(transactions.len());
+        for (
+            transaction,
+            (status, nonce_rollback),
+            pre_balances,
+            post_balances,
+            pre_token_balances,
+            post_token_balances,
+            inner_instructions,
+            log_entries,
+            return_data,
+            rent_debits,
+        ) in izip!(
+            transactions,
+            statuses,
+            balances.pre_balances,
+            balances.post_balances,
+            token_balances.pre_token_balances,
+            token_balances.post_token_balances,
+            inner_instructions_iter,
+            transaction_logs_iter,
+            transaction_return_data,
+            rent_debits,
+        ) {
+            if !Bank::can_commit(&status) {
+                continue;
+            }
+            let lamports_per_signature = nonce_rollback
+                .map(|nonce_rollback| nonce_rollback.lamports_per_signature())
+                .unwrap_or_else(|| {
+                    bank.get_lamports_per_signature_for_blockhash(
+                        transaction.message().recent_blockhash(),
+                    )
+                })
+                .expect("lamports_per_signature must be available");
+            let fee = bank.get_fee_for_message_with_lamports_per_signature(
+                transaction.message(),
+                lamports_per_signature,
+            );
+            let tx_account_locks = transaction.get_account_locks(bank.demote_program_write_locks());
+            let loaded_addresses = transaction
+                .message()
+                .loaded_addresses()
+                .cloned()
+                .unwrap_or_default();
+
+            let inner_instructions = inner_instructions.map(|inner_instructions| {
+                inner_instructions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, instructions)| InnerInstructions {
+                        index: index as u8,
+                        instructions,
+                    })
+                    .filter(|i| !i.instructions.is_empty())
+                    .collect()
+            });
+
+            let log_messages = log_entries
+                .as_ref()
+                .map(|log_entries| log_collector::log_messages(log_entries));
+            let pre_token_balances = Some(pre_token_balances);
+            let post_token_balances = Some(post_token_balances);
+            let rewards = Some(
+                rent_debits
+                    .0
+                    .into_iter()
+                    .map(|(pubkey, reward_info)| Reward {
+                        pubkey: pubkey.to_string(),
+                        lamports: reward_info.lamports,
+                        post_balance: reward_info.post_balance,
+                        reward_type: Some(reward_info.reward_type),
+                        commission: reward_info.commission,
+                    })
+                    .collect(),
+            );
+
+            records.push(TransactionStatusRecord {
+                slot,
+                signature: *transaction.signature(),
+                writable_keys: tx_account_locks.writable.into_iter().copied().collect(),
+                readonly_keys: tx_account_locks.readonly.into_iter().copied().collect(),
+                memos: extract_and_fmt_memos(transaction.message()),
+                status: TransactionStatusMeta {
+                    status,
+                    fee,
+                    pre_balances,
+                    post_balances,
+                    inner_instructions,
+                    log_messages,
+                    pre_token_balances,
+                    post_token_balances,
+                    rewards,
+                    return_data,
+                    loaded_addresses,
+                    log_entries,
+                },
+            });
+        }
+        records
+    }
+}
+
+/// What `TransactionStatusSender` does with a message when the transaction status channel is full
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionStatusOverflowPolicy {
+    /// Wait for the transaction status service to make room
+    Block,
+    /// Drop the message, the statuses of its transactions aren't recorded and its slot is never
+    /// reported complete
+    Drop,
+    /// Queue the message to be written to the `TransactionStatusSpill` for the transaction status
+    /// service to catch up on, dropping it if the spill can't keep up either
+    SpillToDisk,
+}
+
+impl Default for TransactionStatusOverflowPolicy {
+    fn default() -> Self {
+        Self::Block
+    }
+}
+
+/// Slots some of whose transaction statuses were dropped by the overflow policy, which the
+/// transaction status service doesn't report complete
+pub type DroppedTransactionStatusSlots = Arc<Mutex<HashSet<Slot>>>;
+
 #[derive(Clone)]
 pub struct TransactionStatusSender {
     pub sender: Sender<TransactionStatusMessage>,
     pub enable_cpi_and_log_storage: bool,
+    /// Programs whose token accounts are recorded in the pre and post token balances
+    pub token_program_ids: Arc<HashSet<Pubkey>>,
+    /// Only matters if the channel is bounded
+    pub overflow_policy: TransactionStatusOverflowPolicy,
+    /// Where messages are spilled under `TransactionStatusOverflowPolicy::SpillToDisk`, the
+    /// sender blocks instead if unset
+    pub spill: Option<Arc<TransactionStatusSpill>>,
+    /// Slots some of whose statuses were dropped, shared with the transaction status service
+    pub dropped_slots: DroppedTransactionStatusSlots,
 }
 
 impl TransactionStatusSender {
@@ -1387,7 +1736,8 @@ impl TransactionStatusSender {
         balances: TransactionBalancesSet,
         token_balances: TransactionTokenBalancesSet,
         inner_instructions: Vec<Option<InnerInstructionsList>>,
-        transaction_logs: Vec<Option<TransactionLogMessages>>,
+        transaction_logs: Vec<Option<TransactionLogEntries>>,
+        transaction_return_data: Vec<Option<TransactionReturnData>>,
         rent_debits: Vec<RentDebits>,
     ) {
         let slot = bank.slot();
@@ -1396,35 +1746,78 @@ impl TransactionStatusSender {
         } else {
             (Some(inner_instructions), Some(transaction_logs))
         };
-        if let Err(e) = self
-            .sender
-            .send(TransactionStatusMessage::Batch(TransactionStatusBatch {
-                bank,
-                transactions,
-                statuses,
-                balances,
-                token_balances,
-                inner_instructions,
-                transaction_logs,
-                rent_debits,
-            }))
-        {
-            trace!(
-                "Slot {} transaction_status send batch failed: {:?}",
-                slot,
-                e
-            );
-        }
+        let batch = TransactionStatusBatch {
+            bank,
+            transactions,
+            statuses,
+            balances,
+            token_balances,
+            inner_instructions,
+            transaction_logs,
+            transaction_return_data,
+            rent_debits,
+        };
+        self.send(slot, TransactionStatusMessage::Batch(batch));
     }
 
+    // The freeze message is what tells the service every status of the slot has been sent, it
+    // follows them through the same overflow policy
     pub fn send_transaction_status_freeze_message(&self, bank: &Arc<Bank>) {
-        let slot = bank.slot();
-        if let Err(e) = self.sender.send(TransactionStatusMessage::Freeze(slot)) {
-            trace!(
-                "Slot {} transaction_status send freeze message failed: {:?}",
-                slot,
-                e
-            );
+        self.send(bank.slot(), TransactionStatusMessage::Freeze(bank.slot()));
+    }
+
+    fn send(&self, slot: Slot, message: TransactionStatusMessage) {
+        let spill = match self.overflow_policy {
+            TransactionStatusOverflowPolicy::SpillToDisk => self.spill.as_ref(),
+            _ => None,
+        };
+        // Once a message is spilled the ones after it are too, until the service has caught up
+        // on the spill, so that the statuses of a slot are still followed by its freeze message
+        let message = if spill.map(|spill| spill.is_spilling()).unwrap_or_default() {
+            message
+        } else {
+            match self.sender.try_send(message) {
+                Ok(()) => return,
+                Err(TrySendError::Full(message)) => message,
+                Err(e) => {
+                    trace!("Slot {} transaction_status send failed: {:?}", slot, e);
+                    return;
+                }
+            }
+        };
+
+        let message = match (self.overflow_policy, spill) {
+            (TransactionStatusOverflowPolicy::Drop, _) => message,
+            (TransactionStatusOverflowPolicy::SpillToDisk, Some(spill)) => {
+                match spill.try_spill(message) {
+                    Ok(()) => {
+                        inc_new_counter_info!("transaction_status_sender-spilled_messages", 1);
+                        return;
+                    }
+                    // The spill can't keep up either
+                    Err(message) => message,
+                }
+            }
+            _ => {
+                inc_new_counter_info!("transaction_status_sender-blocked_messages", 1);
+                if let Err(e) = self.sender.send(message) {
+                    trace!("Slot {} transaction_status send failed: {:?}", slot, e);
+                }
+                return;
+            }
+        };
+
+        inc_new_counter_info!("transaction_status_sender-dropped_messages", 1);
+        let mut dropped_slots = self.dropped_slots.lock().unwrap();
+        match message {
+            // The service mustn't report the slot complete
+            TransactionStatusMessage::Batch(_) => {
+                dropped_slots.insert(slot);
+            }
+            // Without its freeze message the slot is never reported complete anyway
+            TransactionStatusMessage::Freeze(_) => {
+                dropped_slots.remove(&slot);
+            }
         }
     }
 }
@@ -1473,8 +1866,11 @@ pub fn fill_blockstore_slot_with_ticks(
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::genesis_utils::{
-        create_genesis_config, create_genesis_config_with_leader, GenesisConfigInfo,
+    use crate::{
+        entry_notifier_interface::EntryNotifier,
+        genesis_utils::{
+            create_genesis_config, create_genesis_config_with_leader, GenesisConfigInfo,
+        },
     };
     use crossbeam_channel::unbounded;
     use matches::assert_matches;
@@ -1500,7 +1896,8 @@ pub mod tests {
     };
     use std::{
         collections::BTreeSet,
-        sync::{mpsc::channel, RwLock},
+        convert::TryFrom,
+        sync::{mpsc::channel, Mutex, RwLock},
     };
     use tempfile::TempDir;
     use trees::tr;
@@ -2255,6 +2652,94 @@ pub mod tests {
         assert_eq!(bank.last_blockhash(), last_blockhash);
     }
 
+    #[derive(Debug, Default)]
+    struct RecordingEntryNotifier {
+        entries: Mutex<Vec<(Slot, usize, EntrySummary)>>,
+    }
+
+    impl EntryNotifier for RecordingEntryNotifier {
+        fn notify_entry(&self, slot: Slot, index: usize, entry: &EntrySummary) {
+            self.entries
+                .lock()
+                .unwrap()
+                .push((slot, index, entry.clone()));
+        }
+    }
+
+    #[test]
+    fn test_process_ledger_entry_notifier() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let (ledger_path, mut last_entry_hash) =
+            create_new_tmp_ledger_auto_delete!(&genesis_config);
+
+        let blockhash = genesis_config.hash();
+        let tx = system_transaction::transfer(
+            &mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            1,
+            blockhash,
+        );
+        let mut entries = vec![next_entry_mut(&mut last_entry_hash, 1, vec![tx])];
+        entries.extend(create_ticks(
+            genesis_config.ticks_per_slot,
+            0,
+            last_entry_hash,
+        ));
+        let expected: Vec<_> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let summary = EntrySummary {
+                    num_hashes: entry.num_hashes,
+                    hash: entry.hash,
+                    num_transactions: entry.transactions.len() as u64,
+                };
+                (1, index, summary)
+            })
+            .collect();
+
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore
+            .write_entries(
+                1,
+                0,
+                0,
+                genesis_config.ticks_per_slot,
+                None,
+                true,
+                &Arc::new(Keypair::new()),
+                entries,
+                0,
+            )
+            .unwrap();
+        let entry_notifier = Arc::new(RwLock::new(RecordingEntryNotifier::default()));
+        let opts = ProcessOptions {
+            poh_verify: true,
+            accounts_db_test_hash_calculation: true,
+            entry_notifier: Some(entry_notifier.clone()),
+            ..ProcessOptions::default()
+        };
+        let (bank_forks, ..) = test_process_blockstore(&genesis_config, &blockstore, opts);
+        assert_eq!(frozen_bank_slots(&bank_forks), vec![0, 1]);
+
+        let notified: Vec<_> = entry_notifier
+            .read()
+            .unwrap()
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(slot, ..)| *slot == 1)
+            .cloned()
+            .collect();
+        assert_eq!(notified, expected);
+        assert_eq!(notified[0].2.num_transactions, 1);
+    }
+
     #[test]
     fn test_process_ledger_with_one_tick_per_slot() {
         let GenesisConfigInfo {
@@ -2665,6 +3150,123 @@ pub mod tests {
         assert_eq!(bank.get_balance(&keypair3.pubkey()), 2);
     }
 
+    #[test]
+    fn test_replay_scheduler() {
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        let keypair4 = Keypair::new();
+        let keypair5 = Keypair::new();
+        let transfer = |from: &Keypair, to: &Keypair| {
+            SanitizedTransaction::try_from(system_transaction::transfer(
+                from,
+                &to.pubkey(),
+                1,
+                Hash::default(),
+            ))
+            .unwrap()
+        };
+
+        let mut scheduler = ReplayScheduler::default();
+        assert_eq!(
+            scheduler.schedule_entry(&[transfer(&keypair1, &keypair2)], false),
+            Some(0)
+        );
+        // An entry waits for the earlier entries it conflicts with
+        assert_eq!(
+            scheduler.schedule_entry(&[transfer(&keypair2, &keypair3)], false),
+            Some(1)
+        );
+        // but not for the ones it doesn't conflict with
+        assert_eq!(
+            scheduler.schedule_entry(&[transfer(&keypair4, &keypair5)], false),
+            Some(0)
+        );
+        // The whole entry waits for the latest wave any of its transactions conflicts with
+        assert_eq!(
+            scheduler.schedule_entry(
+                &[
+                    transfer(&keypair5, &keypair1),
+                    transfer(&keypair3, &keypair4)
+                ],
+                false
+            ),
+            Some(2)
+        );
+        assert_eq!(scheduler.num_waves, 3);
+
+        // An entry conflicting with itself is rejected and doesn't affect the schedule
+        assert_eq!(
+            scheduler.schedule_entry(
+                &[
+                    transfer(&keypair1, &keypair2),
+                    transfer(&keypair1, &keypair3)
+                ],
+                false
+            ),
+            None
+        );
+        assert_eq!(
+            scheduler.schedule_entry(&[transfer(&keypair2, &keypair3)], false),
+            Some(3)
+        );
+        assert_eq!(scheduler.num_waves, 4);
+    }
+
+    #[test]
+    fn test_process_entries_scheduled_across_conflicts() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        assert_matches!(bank.transfer(4, &mint_keypair, &keypair1.pubkey()), Ok(_));
+        assert_matches!(bank.transfer(4, &mint_keypair, &keypair2.pubkey()), Ok(_));
+
+        // the second entry conflicts with the first one, the third with neither
+        let entry_1 = next_entry(
+            &bank.last_blockhash(),
+            1,
+            vec![system_transaction::transfer(
+                &keypair1,
+                &keypair3.pubkey(),
+                1,
+                bank.last_blockhash(),
+            )],
+        );
+        let entry_2 = next_entry(
+            &entry_1.hash,
+            1,
+            vec![system_transaction::transfer(
+                &keypair3,
+                &keypair1.pubkey(),
+                1,
+                bank.last_blockhash(),
+            )],
+        );
+        let entry_3 = next_entry(
+            &entry_2.hash,
+            1,
+            vec![system_transaction::transfer(
+                &keypair2,
+                &mint_keypair.pubkey(),
+                2,
+                bank.last_blockhash(),
+            )],
+        );
+        assert_eq!(
+            process_entries(&bank, vec![entry_1, entry_2, entry_3], false, None, None),
+            Ok(())
+        );
+        assert_eq!(bank.get_balance(&keypair1.pubkey()), 4);
+        assert_eq!(bank.get_balance(&keypair2.pubkey()), 2);
+        assert_eq!(bank.get_balance(&keypair3.pubkey()), 0);
+    }
+
     #[test]
     fn test_process_entries_2_entries_par() {
         let GenesisConfigInfo {
@@ -3443,6 +4045,8 @@ pub mod tests {
             _balances,
             _inner_instructions,
             _log_messages,
+            _return_data,
+            _compute_units,
         ) = batch.bank().load_execute_and_commit_transactions(
             &batch,
             MAX_PROCESSING_AGE,
@@ -3532,7 +4136,7 @@ pub mod tests {
         let _ = process_entries(&bank1, vec![entry], true, None, Some(&replay_vote_sender));
         let successes: BTreeSet<Pubkey> = replay_vote_receiver
             .try_iter()
-            .map(|(vote_pubkey, _, _)| vote_pubkey)
+            .map(|(vote_pubkey, _, _, _)| vote_pubkey)
             .collect();
         assert_eq!(successes, expected_successful_voter_pubkeys);
     }
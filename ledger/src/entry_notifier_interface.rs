@@ -0,0 +1,19 @@
+use {
+    solana_sdk::{clock::Slot, hash::Hash},
+    std::sync::{Arc, RwLock},
+};
+
+/// The parts of an executed `Entry` reported to entry notifiers
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntrySummary {
+    pub num_hashes: u64,
+    pub hash: Hash,
+    pub num_transactions: u64,
+}
+
+pub trait EntryNotifier: std::fmt::Debug {
+    /// Notified when the entry at `index` of `slot` is executed during replay
+    fn notify_entry(&self, slot: Slot, index: usize, entry: &EntrySummary);
+}
+
+pub type EntryNotifierLock = Arc<RwLock<dyn EntryNotifier + Sync + Send>>;
@@ -5,6 +5,7 @@ use crate::{
 };
 use itertools::Itertools;
 use log::*;
+use solana_measure::measure::Measure;
 use solana_runtime::bank::Bank;
 use solana_sdk::{
     clock::{Epoch, Slot},
@@ -13,7 +14,8 @@ use solana_sdk::{
 };
 use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
+    thread::{Builder, JoinHandle},
 };
 
 type CachedSchedules = (HashMap<Epoch, Arc<LeaderSchedule>>, VecDeque<u64>);
@@ -34,6 +36,10 @@ pub struct LeaderScheduleCache {
     max_epoch: RwLock<Epoch>,
     max_schedules: CacheCapacity,
     fixed_schedule: Option<Arc<FixedSchedule>>,
+    // The root bank of the epoch schedule being precomputed, to compute it from on demand if it's
+    // needed before the precompute thread is done
+    precompute_bank: RwLock<Option<Arc<Bank>>>,
+    precompute_thread: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl LeaderScheduleCache {
@@ -48,6 +54,8 @@ impl LeaderScheduleCache {
             max_epoch: RwLock::new(0),
             max_schedules: CacheCapacity::default(),
             fixed_schedule: None,
+            precompute_bank: RwLock::default(),
+            precompute_thread: Mutex::default(),
         };
 
         // This sets the root and calculates the schedule at leader_schedule_epoch(root)
@@ -73,30 +81,91 @@ impl LeaderScheduleCache {
     }
 
     pub fn set_root(&self, root_bank: &Bank) {
-        let new_max_epoch = self
-            .epoch_schedule
-            .get_leader_schedule_epoch(root_bank.slot());
-        let old_max_epoch = {
-            let mut max_epoch = self.max_epoch.write().unwrap();
-            let old_max_epoch = *max_epoch;
-            *max_epoch = new_max_epoch;
-            assert!(new_max_epoch >= old_max_epoch);
-            old_max_epoch
-        };
-
         // Calculate the epoch as soon as it's rooted
-        if new_max_epoch > old_max_epoch {
+        if let Some(new_max_epoch) = self.update_max_epoch(root_bank) {
             self.compute_epoch_schedule(new_max_epoch, root_bank);
         }
     }
 
+    /// Same as `set_root()`, except that the epoch stakes and schedule of a newly rooted epoch are
+    /// computed by a background thread, so that the caller doesn't stall at epoch boundaries.
+    /// Until it's done, lookups for slots in that epoch compute the schedule on demand from
+    /// `root_bank`.
+    pub fn set_root_and_precompute(self: &Arc<Self>, root_bank: &Arc<Bank>) {
+        let new_max_epoch = match self.update_max_epoch(root_bank) {
+            Some(new_max_epoch) => new_max_epoch,
+            None => return,
+        };
+        let mut precompute_thread = self.precompute_thread.lock().unwrap();
+        // The previous epoch's schedule was precomputed long ago
+        if let Some(thread) = precompute_thread.take() {
+            thread.join().unwrap();
+        }
+        *self.precompute_bank.write().unwrap() = Some(root_bank.clone());
+        let cache = self.clone();
+        let root_bank = root_bank.clone();
+        *precompute_thread = Some(
+            Builder::new()
+                .name("solana-leader-schedule-precompute".to_string())
+                .spawn(move || {
+                    // The staked nodes are cached by the epoch stakes, for turbine to reuse
+                    let mut staked_nodes_time = Measure::start("epoch_staked_nodes");
+                    let staked_nodes = root_bank.epoch_staked_nodes(new_max_epoch);
+                    staked_nodes_time.stop();
+                    let mut compute_time = Measure::start("compute_epoch_schedule");
+                    let leader_schedule =
+                        cache.get_epoch_schedule_else_compute(new_max_epoch, &root_bank);
+                    compute_time.stop();
+                    cache.precompute_bank.write().unwrap().take();
+                    datapoint_info!(
+                        "leader_schedule_cache-precompute",
+                        ("epoch", new_max_epoch, i64),
+                        ("root_slot", root_bank.slot(), i64),
+                        (
+                            "staked_nodes",
+                            staked_nodes
+                                .map(|staked_nodes| staked_nodes.len())
+                                .unwrap_or(0),
+                            i64
+                        ),
+                        ("computed", leader_schedule.is_some(), bool),
+                        ("staked_nodes_us", staked_nodes_time.as_us(), i64),
+                        ("compute_us", compute_time.as_us(), i64),
+                    );
+                })
+                .unwrap(),
+        );
+    }
+
+    #[cfg(test)]
+    fn wait_for_precompute(&self) {
+        if let Some(thread) = self.precompute_thread.lock().unwrap().take() {
+            thread.join().unwrap();
+        }
+    }
+
+    // Returns the new leader schedule epoch if rooting `root_bank` advances it
+    fn update_max_epoch(&self, root_bank: &Bank) -> Option<Epoch> {
+        let new_max_epoch = self
+            .epoch_schedule
+            .get_leader_schedule_epoch(root_bank.slot());
+        let mut max_epoch = self.max_epoch.write().unwrap();
+        let old_max_epoch = *max_epoch;
+        *max_epoch = new_max_epoch;
+        assert!(new_max_epoch >= old_max_epoch);
+        (new_max_epoch > old_max_epoch).then(|| new_max_epoch)
+    }
+
     pub fn slot_leader_at(&self, slot: Slot, bank: Option<&Bank>) -> Option<Pubkey> {
         if let Some(bank) = bank {
             self.slot_leader_at_else_compute(slot, bank)
         } else if self.epoch_schedule.slots_per_epoch == 0 {
             None
         } else {
-            self.slot_leader_at_no_compute(slot)
+            self.slot_leader_at_no_compute(slot).or_else(|| {
+                let precompute_bank = self.precompute_bank.read().unwrap().clone()?;
+                self.slot_leader_at_else_compute(slot, &precompute_bank)
+            })
         }
     }
 
@@ -600,6 +669,34 @@ mod tests {
         assert!(cache.slot_leader_at(224, Some(&bank2)).is_none());
     }
 
+    #[test]
+    fn test_set_root_and_precompute() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let cache = Arc::new(LeaderScheduleCache::new_from_bank(&bank));
+
+        // Rooting a bank in an already confirmed leader schedule epoch computes nothing
+        cache.set_root_and_precompute(&bank);
+        assert!(cache.precompute_thread.lock().unwrap().is_none());
+
+        let bank2 = Arc::new(Bank::new_from_parent(
+            &bank,
+            &solana_sdk::pubkey::new_rand(),
+            95,
+        ));
+        cache.set_root_and_precompute(&bank2);
+        assert_eq!(*cache.max_epoch.read().unwrap(), 2);
+        // Lookups don't have to wait for the precompute thread
+        assert!(cache.slot_leader_at(96, None).is_some());
+
+        cache.wait_for_precompute();
+        assert!(cache.precompute_bank.read().unwrap().is_none());
+        // The schedule of epoch 2 is available without a bank to compute it from
+        assert!(cache.get_epoch_leader_schedule(2).is_some());
+        assert!(cache.slot_leader_at(96, None).is_some());
+        assert!(cache.slot_leader_at(224, None).is_none());
+    }
+
     #[test]
     fn test_set_max_schedules() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
@@ -15,6 +15,7 @@ pub mod blockstore_db;
 pub mod blockstore_meta;
 pub mod blockstore_processor;
 pub mod builtins;
+pub mod entry_notifier_interface;
 pub mod erasure;
 pub mod genesis_utils;
 pub mod leader_schedule;
@@ -25,6 +26,7 @@ pub mod rooted_slot_iterator;
 pub mod shred;
 pub mod sigverify_shreds;
 pub mod staking_utils;
+pub mod transaction_status_spill;
 
 #[macro_use]
 extern crate solana_metrics;
@@ -48,6 +48,13 @@
 //!
 //! So, given a) - c), we must restrict data shred's payload length such that the entire coding
 //! payload can fit into one coding shred / packet.
+//!
+//! Merkle shreds are a variant of both shred types in which the leader signs, once per FEC set,
+//! the root of a merkle tree over all the data and coding shreds of the set, instead of signing
+//! each shred. Every merkle shred carries its merkle proof after the erasure coded part of the
+//! payload, so that any shred, including coding shreds, can be authenticated on its own. Merkle
+//! data shreds also carry the merkle root of the previous FEC set of the slot, chaining the FEC
+//! sets of a slot together.
 
 use {
     crate::{blockstore::MAX_DATA_SHREDS_PER_SLOT, erasure::Session},
@@ -124,6 +131,27 @@ pub const SIZE_OF_DATA_SHRED_PAYLOAD: usize = PACKET_DATA_SIZE
     - SIZE_OF_CODING_SHRED_HEADERS
     - SIZE_OF_NONCE;
 
+pub const SIZE_OF_MERKLE_ROOT: usize = 32;
+const SIZE_OF_MERKLE_PROOF_ENTRY: usize = 20;
+/// Merkle trees are built over the most shreds a FEC set can have, so that every merkle proof
+/// has the same number of entries
+const MERKLE_TREE_HEIGHT: usize = 6;
+const MERKLE_TREE_NUM_LEAVES: usize = 1 << MERKLE_TREE_HEIGHT;
+pub const SIZE_OF_MERKLE_PROOF: usize = MERKLE_TREE_HEIGHT * SIZE_OF_MERKLE_PROOF_ENTRY;
+/// The erasure coded part of merkle shreds: the payload after the signature, or after the
+/// coding shred headers, up to the merkle proof
+const SIZE_OF_MERKLE_SHARD: usize =
+    SHRED_PAYLOAD_SIZE - SIZE_OF_CODING_SHRED_HEADERS - SIZE_OF_MERKLE_PROOF;
+pub const SIZE_OF_MERKLE_DATA_SHRED_PAYLOAD: usize = SIZE_OF_MERKLE_SHARD
+    - (SIZE_OF_COMMON_SHRED_HEADER - SIZE_OF_SIGNATURE)
+    - SIZE_OF_DATA_SHRED_HEADER
+    - SIZE_OF_MERKLE_ROOT;
+/// Merkle data shreds are stored in the blockstore up to the end of their merkle proof
+pub const SIZE_OF_MERKLE_DATA_SHRED_STORED: usize =
+    SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD + SIZE_OF_MERKLE_PROOF;
+const MERKLE_HASH_PREFIX_LEAF: &[u8] = b"\x00SOLANA_MERKLE_SHREDS_LEAF";
+const MERKLE_HASH_PREFIX_NODE: &[u8] = b"\x01SOLANA_MERKLE_SHREDS_NODE";
+
 pub const OFFSET_OF_SHRED_TYPE: usize = SIZE_OF_SIGNATURE;
 pub const OFFSET_OF_SHRED_SLOT: usize = SIZE_OF_SIGNATURE + SIZE_OF_SHRED_TYPE;
 pub const OFFSET_OF_SHRED_INDEX: usize = OFFSET_OF_SHRED_SLOT + SIZE_OF_SHRED_SLOT;
@@ -138,6 +166,8 @@ thread_local!(static PAR_THREAD_POOL: RefCell<ThreadPool> = RefCell::new(rayon::
 /// The constants that define if a shred is data or coding
 pub const DATA_SHRED: u8 = 0b1010_0101;
 pub const CODING_SHRED: u8 = 0b0101_1010;
+pub const MERKLE_DATA_SHRED: u8 = 0b1001_0110;
+pub const MERKLE_CODING_SHRED: u8 = 0b0110_1001;
 
 pub const MAX_DATA_SHREDS_PER_FEC_BLOCK: u32 = 32;
 
@@ -163,6 +193,11 @@ pub enum ShredError {
         "invalid parent offset; parent_offset {parent_offset} must be larger than slot {slot}"
     )]
     InvalidParentOffset { slot: Slot, parent_offset: u16 },
+
+    #[error(
+        "invalid data size {size}; merkle data shred payload overlaps the chained merkle root"
+    )]
+    InvalidDataSize { size: u16 },
 }
 
 pub type Result<T> = std::result::Result<T, ShredError>;
@@ -175,6 +210,29 @@ impl Default for ShredType {
     }
 }
 
+impl ShredType {
+    pub fn is_data(self) -> bool {
+        self.0 == DATA_SHRED || self.0 == MERKLE_DATA_SHRED
+    }
+
+    pub fn is_code(self) -> bool {
+        self.0 == CODING_SHRED || self.0 == MERKLE_CODING_SHRED
+    }
+
+    pub fn is_merkle(self) -> bool {
+        self.0 == MERKLE_DATA_SHRED || self.0 == MERKLE_CODING_SHRED
+    }
+
+    // Offset of the merkle proof in the payload of merkle shreds
+    fn merkle_proof_offset(self) -> Option<usize> {
+        match self.0 {
+            MERKLE_DATA_SHRED => Some(SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD),
+            MERKLE_CODING_SHRED => Some(SIZE_OF_CODING_SHRED_HEADERS + SIZE_OF_MERKLE_SHARD),
+            _ => None,
+        }
+    }
+}
+
 /// A common header that is present in data and code shred headers
 #[derive(Serialize, Clone, Deserialize, Default, PartialEq, Debug)]
 pub struct ShredCommonHeader {
@@ -323,7 +381,7 @@ impl Shred {
         // so that erasure generation/recovery works correctly
         // But only the data_header.size is stored in blockstore.
         payload.resize(SHRED_PAYLOAD_SIZE, 0);
-        let shred = if common_header.shred_type == ShredType(CODING_SHRED) {
+        let shred = if common_header.shred_type.is_code() {
             let coding_header: CodingShredHeader =
                 Self::deserialize_obj(&mut start, SIZE_OF_CODING_SHRED_HEADER, &payload)?;
             Self {
@@ -332,7 +390,7 @@ impl Shred {
                 coding_header,
                 payload,
             }
-        } else if common_header.shred_type == ShredType(DATA_SHRED) {
+        } else if common_header.shred_type.is_data() {
             let data_header: DataShredHeader =
                 Self::deserialize_obj(&mut start, SIZE_OF_DATA_SHRED_HEADER, &payload)?;
             if u64::from(data_header.parent_offset) > common_header.slot {
@@ -341,6 +399,18 @@ impl Shred {
                     parent_offset: data_header.parent_offset,
                 });
             }
+            if common_header.shred_type.is_merkle() {
+                if usize::from(data_header.size)
+                    > SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD - SIZE_OF_MERKLE_ROOT
+                {
+                    return Err(ShredError::InvalidDataSize {
+                        size: data_header.size,
+                    });
+                }
+                // The bytes after the merkle proof are not signed, so they are always zeroed
+                payload.truncate(SIZE_OF_MERKLE_DATA_SHRED_STORED);
+                payload.resize(SHRED_PAYLOAD_SIZE, 0);
+            }
             Self {
                 common_header,
                 data_header,
@@ -373,6 +443,32 @@ impl Shred {
         Shred::new_empty_from_header(header, DataShredHeader::default(), coding_header)
     }
 
+    fn new_merkle_coding(
+        slot: Slot,
+        index: u32,
+        fec_set_index: u32,
+        num_data: usize,
+        num_code: usize,
+        version: u16,
+        parity: &[u8],
+    ) -> Self {
+        let (mut header, coding_header) = Shredder::new_coding_shred_header(
+            slot,
+            index,
+            fec_set_index,
+            num_data,
+            num_code,
+            version,
+        );
+        header.shred_type = ShredType(MERKLE_CODING_SHRED);
+        let mut shred =
+            Shred::new_empty_from_header(header, DataShredHeader::default(), coding_header);
+        shred.payload
+            [SIZE_OF_CODING_SHRED_HEADERS..SIZE_OF_CODING_SHRED_HEADERS + SIZE_OF_MERKLE_SHARD]
+            .copy_from_slice(parity);
+        shred
+    }
+
     pub fn new_empty_from_header(
         common_header: ShredCommonHeader,
         data_header: DataShredHeader,
@@ -387,7 +483,7 @@ impl Shred {
             &common_header,
         )
         .expect("Failed to write header into shred buffer");
-        if common_header.shred_type == ShredType(DATA_SHRED) {
+        if common_header.shred_type.is_data() {
             Self::serialize_obj_into(
                 &mut start,
                 SIZE_OF_DATA_SHRED_HEADER,
@@ -395,7 +491,7 @@ impl Shred {
                 &data_header,
             )
             .expect("Failed to write data header into shred buffer");
-        } else if common_header.shred_type == ShredType(CODING_SHRED) {
+        } else if common_header.shred_type.is_code() {
             Self::serialize_obj_into(
                 &mut start,
                 SIZE_OF_CODING_SHRED_HEADER,
@@ -462,6 +558,23 @@ impl Shred {
         .unwrap();
     }
 
+    fn set_shred_type(&mut self, shred_type: ShredType) {
+        self.common_header.shred_type = shred_type;
+        Self::serialize_obj_into(
+            &mut 0,
+            SIZE_OF_COMMON_SHRED_HEADER,
+            &mut self.payload,
+            &self.common_header,
+        )
+        .unwrap();
+    }
+
+    fn set_signature(&mut self, signature: Signature) {
+        bincode::serialize_into(&mut self.payload[..SIZE_OF_SIGNATURE], &signature)
+            .expect("Failed to generate serialized signature");
+        self.common_header.signature = signature;
+    }
+
     pub fn signature(&self) -> Signature {
         self.common_header.signature
     }
@@ -482,10 +595,72 @@ impl Shred {
     }
 
     pub fn is_data(&self) -> bool {
-        self.common_header.shred_type == ShredType(DATA_SHRED)
+        self.common_header.shred_type.is_data()
     }
     pub fn is_code(&self) -> bool {
-        self.common_header.shred_type == ShredType(CODING_SHRED)
+        self.common_header.shred_type.is_code()
+    }
+    pub fn is_merkle(&self) -> bool {
+        self.common_header.shred_type.is_merkle()
+    }
+
+    // Position of a merkle shred among the leaves of the merkle tree of its FEC set: data
+    // shreds come first, followed by the coding shreds
+    fn merkle_position(&self) -> Option<usize> {
+        let offset = self
+            .common_header
+            .index
+            .checked_sub(self.common_header.fec_set_index)? as usize;
+        let position = if self.is_data() {
+            offset
+        } else {
+            usize::from(self.coding_header.num_data_shreds) + offset
+        };
+        (position < MERKLE_TREE_NUM_LEAVES).then(|| position)
+    }
+
+    // The erasure coded part of the payload of a merkle shred
+    fn merkle_shard(&self) -> Option<&[u8]> {
+        let offset = self.common_header.shred_type.merkle_proof_offset()?;
+        Some(&self.payload[offset - SIZE_OF_MERKLE_SHARD..offset])
+    }
+
+    // A merkle leaf covers the whole payload except for the signature and the merkle proof
+    fn merkle_leaf(&self) -> Option<Hash> {
+        let offset = self.common_header.shred_type.merkle_proof_offset()?;
+        Some(hashv(&[
+            MERKLE_HASH_PREFIX_LEAF,
+            &self.payload[SIZE_OF_SIGNATURE..offset],
+        ]))
+    }
+
+    fn set_merkle_proof(&mut self, proof: &[u8]) {
+        let offset = self.common_header.shred_type.merkle_proof_offset().unwrap();
+        self.payload[offset..offset + SIZE_OF_MERKLE_PROOF].copy_from_slice(proof);
+    }
+
+    /// The merkle root of the FEC set of a merkle shred, as given by the shred's merkle proof
+    pub fn merkle_root(&self) -> Option<Hash> {
+        let offset = self.common_header.shred_type.merkle_proof_offset()?;
+        get_merkle_root(
+            self.merkle_position()?,
+            self.merkle_leaf()?,
+            &self.payload[offset..offset + SIZE_OF_MERKLE_PROOF],
+        )
+    }
+
+    /// The merkle root of the previous FEC set of the slot, carried by merkle data shreds
+    pub fn chained_merkle_root(&self) -> Option<Hash> {
+        if self.common_header.shred_type != ShredType(MERKLE_DATA_SHRED) {
+            return None;
+        }
+        let end = SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD;
+        Some(Hash::new(&self.payload[end - SIZE_OF_MERKLE_ROOT..end]))
+    }
+
+    fn set_chained_merkle_root(&mut self, chained_merkle_root: &Hash) {
+        let end = SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD;
+        self.payload[end - SIZE_OF_MERKLE_ROOT..end].copy_from_slice(chained_merkle_root.as_ref());
     }
 
     pub fn last_in_slot(&self) -> bool {
@@ -558,11 +733,97 @@ impl Shred {
     }
 
     pub fn verify(&self, pubkey: &Pubkey) -> bool {
+        if self.is_merkle() {
+            // The signature of a merkle shred is over the merkle root of its FEC set
+            return match self.merkle_root() {
+                Some(merkle_root) => self
+                    .signature()
+                    .verify(pubkey.as_ref(), merkle_root.as_ref()),
+                None => false,
+            };
+        }
         self.signature()
             .verify(pubkey.as_ref(), &self.payload[SIZE_OF_SIGNATURE..])
     }
 }
 
+fn join_merkle_nodes(lhs: &[u8], rhs: &[u8]) -> Hash {
+    hashv(&[
+        MERKLE_HASH_PREFIX_NODE,
+        &lhs[..SIZE_OF_MERKLE_PROOF_ENTRY],
+        &rhs[..SIZE_OF_MERKLE_PROOF_ENTRY],
+    ])
+}
+
+// Builds the merkle tree over the leaves, padded to MERKLE_TREE_NUM_LEAVES. The nodes are
+// returned level by level, from the leaves up to the root
+fn make_merkle_tree(mut leaves: Vec<Hash>) -> Vec<Hash> {
+    debug_assert!(leaves.len() <= MERKLE_TREE_NUM_LEAVES);
+    leaves.resize(MERKLE_TREE_NUM_LEAVES, Hash::default());
+    let mut tree = leaves;
+    let mut level_start = 0;
+    let mut level_size = MERKLE_TREE_NUM_LEAVES;
+    while level_size > 1 {
+        for index in (level_start..level_start + level_size).step_by(2) {
+            let node = join_merkle_nodes(tree[index].as_ref(), tree[index + 1].as_ref());
+            tree.push(node);
+        }
+        level_start += level_size;
+        level_size /= 2;
+    }
+    tree
+}
+
+fn make_merkle_proof(mut index: usize, tree: &[Hash]) -> Vec<u8> {
+    let mut proof = Vec::with_capacity(SIZE_OF_MERKLE_PROOF);
+    let mut level_start = 0;
+    let mut level_size = MERKLE_TREE_NUM_LEAVES;
+    while level_size > 1 {
+        let sibling = &tree[level_start + (index ^ 1)];
+        proof.extend_from_slice(&sibling.as_ref()[..SIZE_OF_MERKLE_PROOF_ENTRY]);
+        level_start += level_size;
+        level_size /= 2;
+        index /= 2;
+    }
+    proof
+}
+
+fn get_merkle_root(mut index: usize, leaf: Hash, proof: &[u8]) -> Option<Hash> {
+    if index >= MERKLE_TREE_NUM_LEAVES || proof.len() != SIZE_OF_MERKLE_PROOF {
+        return None;
+    }
+    let root = proof
+        .chunks(SIZE_OF_MERKLE_PROOF_ENTRY)
+        .fold(leaf, |node, entry| {
+            let node = if index % 2 == 0 {
+                join_merkle_nodes(node.as_ref(), entry)
+            } else {
+                join_merkle_nodes(entry, node.as_ref())
+            };
+            index /= 2;
+            node
+        });
+    Some(root)
+}
+
+/// Leaders shred the slots of the epochs after the one `merkle_shreds` was activated in into
+/// merkle shreds. Receivers go by the variant of the shreds themselves, and the blockstore
+/// rejects slots mixing variants through their chained merkle roots
+pub fn merkle_shreds_enabled(shred_slot: Slot, bank: &Bank) -> bool {
+    let feature_slot = bank
+        .feature_set
+        .activated_slot(&feature_set::merkle_shreds::id());
+    match feature_slot {
+        None => false,
+        Some(feature_slot) => {
+            let epoch_schedule = bank.epoch_schedule();
+            let feature_epoch = epoch_schedule.get_epoch(feature_slot);
+            let shred_epoch = epoch_schedule.get_epoch(shred_slot);
+            feature_epoch < shred_epoch
+        }
+    }
+}
+
 fn enable_deterministic_seed(shred_slot: Slot, bank: &Bank) -> bool {
     let feature_slot = bank
         .feature_set
@@ -694,6 +955,139 @@ impl Shredder {
         (data_shreds, last_shred_index + 1)
     }
 
+    /// Converts entries into merkle data shreds and their coding shreds. Since the merkle root
+    /// of a FEC set covers all of its data and coding shreds, every FEC set is completed and
+    /// signed here. `chained_merkle_root` is the merkle root of the previous FEC set of the
+    /// slot, and is updated to the merkle root of the last FEC set generated.
+    pub fn entries_to_merkle_shreds(
+        &self,
+        keypair: &Keypair,
+        entries: &[Entry],
+        is_last_in_slot: bool,
+        next_shred_index: u32,
+        chained_merkle_root: &mut Hash,
+        process_stats: &mut ProcessShredsStats,
+    ) -> (Vec<Shred>, Vec<Shred>, u32) {
+        let mut serialize_time = Measure::start("shred_serialize");
+        let serialized_shreds =
+            bincode::serialize(entries).expect("Expect to serialize all entries");
+        serialize_time.stop();
+
+        let mut gen_data_time = Measure::start("shred_gen_data_time");
+        let payload_capacity = SIZE_OF_MERKLE_DATA_SHRED_PAYLOAD;
+        // Integer division to ensure we have enough shreds to fit all the data
+        let num_shreds = (serialized_shreds.len() + payload_capacity - 1) / payload_capacity;
+        let last_shred_index = next_shred_index + num_shreds as u32 - 1;
+        let parent_offset = (self.slot - self.parent_slot) as u16;
+        let mut data_shreds: Vec<Shred> = serialized_shreds
+            .chunks(payload_capacity)
+            .zip(next_shred_index..)
+            .map(|(data, shred_index)| {
+                let is_last_data = shred_index == last_shred_index;
+                let mut shred = Shred::new_from_data(
+                    self.slot,
+                    shred_index,
+                    parent_offset,
+                    Some(data),
+                    is_last_data,
+                    is_last_data && is_last_in_slot,
+                    self.reference_tick,
+                    self.version,
+                    Self::fec_set_index(shred_index, next_shred_index).unwrap(),
+                );
+                shred.set_shred_type(ShredType(MERKLE_DATA_SHRED));
+                shred
+            })
+            .collect();
+        gen_data_time.stop();
+
+        let mut gen_coding_time = Measure::start("gen_coding_shreds");
+        // FEC sets are generated in order, each chaining the merkle root of the previous one
+        let mut coding_shreds = Vec::new();
+        for data_shreds in data_shreds.chunks_mut(MAX_DATA_SHREDS_PER_FEC_BLOCK as usize) {
+            for shred in data_shreds.iter_mut() {
+                shred.set_chained_merkle_root(chained_merkle_root);
+            }
+            let mut fec_set_coding_shreds =
+                Self::generate_merkle_coding_shreds(data_shreds, is_last_in_slot);
+            *chained_merkle_root =
+                Self::sign_merkle_fec_set(keypair, data_shreds, &mut fec_set_coding_shreds);
+            coding_shreds.extend(fec_set_coding_shreds);
+        }
+        gen_coding_time.stop();
+
+        process_stats.serialize_elapsed += serialize_time.as_us();
+        process_stats.gen_data_elapsed += gen_data_time.as_us();
+        process_stats.gen_coding_elapsed += gen_coding_time.as_us();
+        (data_shreds, coding_shreds, last_shred_index + 1)
+    }
+
+    // Generates the coding shreds of a FEC set of merkle data shreds. Only the merkle shards
+    // are erasure coded, which leaves out the signatures and merkle proofs, neither of which
+    // is known before the coding shreds are.
+    fn generate_merkle_coding_shreds(data: &[Shred], is_last_in_slot: bool) -> Vec<Shred> {
+        let ShredCommonHeader {
+            slot,
+            index,
+            version,
+            fec_set_index,
+            ..
+        } = data.first().unwrap().common_header;
+        assert_eq!(fec_set_index, index);
+        let num_data = data.len();
+        let num_coding = Self::num_coding_shreds(num_data, is_last_in_slot);
+        let data: Vec<_> = data
+            .iter()
+            .map(|shred| shred.merkle_shard().unwrap())
+            .collect();
+        let mut parity = vec![vec![0u8; SIZE_OF_MERKLE_SHARD]; num_coding];
+        Session::new(num_data, num_coding)
+            .unwrap()
+            .encode(&data, &mut parity[..])
+            .unwrap();
+        parity
+            .iter()
+            .zip(fec_set_index..)
+            .map(|(parity, index)| {
+                Shred::new_merkle_coding(
+                    slot,
+                    index,
+                    fec_set_index,
+                    num_data,
+                    num_coding,
+                    version,
+                    parity,
+                )
+            })
+            .collect()
+    }
+
+    // Signs the merkle root of a FEC set, and writes the signature and the merkle proof of each
+    // shred into the shreds. Returns the merkle root.
+    fn sign_merkle_fec_set(
+        keypair: &Keypair,
+        data_shreds: &mut [Shred],
+        coding_shreds: &mut [Shred],
+    ) -> Hash {
+        let leaves = data_shreds
+            .iter()
+            .chain(coding_shreds.iter())
+            .map(|shred| shred.merkle_leaf().unwrap())
+            .collect();
+        let tree = make_merkle_tree(leaves);
+        let merkle_root = *tree.last().unwrap();
+        let signature = keypair.sign_message(merkle_root.as_ref());
+        for (position, shred) in data_shreds
+            .iter_mut()
+            .chain(coding_shreds.iter_mut())
+            .enumerate()
+        {
+            shred.set_merkle_proof(&make_merkle_proof(position, &tree));
+            shred.set_signature(signature);
+        }
+        merkle_root
+    }
+
     pub fn data_shreds_to_coding_shreds(
         keypair: &Keypair,
         data_shreds: &[Shred],
@@ -781,13 +1175,7 @@ impl Shredder {
             && shred.common_header.version == version
             && shred.common_header.fec_set_index == fec_set_index));
         let num_data = data.len();
-        let num_coding = if is_last_in_slot {
-            (2 * MAX_DATA_SHREDS_PER_FEC_BLOCK as usize)
-                .saturating_sub(num_data)
-                .max(num_data)
-        } else {
-            num_data
-        };
+        let num_coding = Self::num_coding_shreds(num_data, is_last_in_slot);
         let data: Vec<_> = data
             .iter()
             .map(|shred| &shred.payload[..PAYLOAD_ENCODE_SIZE])
@@ -815,6 +1203,16 @@ impl Shredder {
             .collect()
     }
 
+    fn num_coding_shreds(num_data: usize, is_last_in_slot: bool) -> usize {
+        if is_last_in_slot {
+            (2 * MAX_DATA_SHREDS_PER_FEC_BLOCK as usize)
+                .saturating_sub(num_data)
+                .max(num_data)
+        } else {
+            num_data
+        }
+    }
+
     fn fill_in_missing_shreds(
         num_data: usize,
         num_coding: usize,
@@ -853,6 +1251,9 @@ impl Shredder {
         slot: Slot,
     ) -> std::result::Result<Vec<Shred>, reed_solomon_erasure::Error> {
         Self::verify_consistent_shred_payload_sizes("try_recovery()", &shreds)?;
+        if shreds[0].is_merkle() {
+            return Self::try_merkle_recovery(shreds, num_data, num_coding, first_index, slot);
+        }
         let mut recovered_data = vec![];
         let fec_set_size = num_data + num_coding;
 
@@ -941,6 +1342,122 @@ impl Shredder {
         Ok(recovered_data)
     }
 
+    // Recovers the missing data shreds of a FEC set of merkle shreds. The merkle tree of the
+    // FEC set is rebuilt from the recovered data shreds and the coding shreds re-encoded from
+    // them, and the recovered shreds are only returned if its root is the one signed for the
+    // shreds received, in which case the tree also provides their merkle proofs.
+    fn try_merkle_recovery(
+        shreds: Vec<Shred>,
+        num_data: usize,
+        num_coding: usize,
+        first_index: usize,
+        slot: Slot,
+    ) -> std::result::Result<Vec<Shred>, reed_solomon_erasure::Error> {
+        let fec_set_size = num_data + num_coding;
+        if num_coding == 0 || fec_set_size > MERKLE_TREE_NUM_LEAVES {
+            return Ok(Vec::default());
+        }
+        let mut shards: Vec<Option<Vec<u8>>> = vec![None; fec_set_size];
+        let mut merkle_root = None;
+        let (signature, version) = (shreds[0].signature(), shreds[0].version());
+        for shred in &shreds {
+            let offset = if shred.is_data() { 0 } else { num_data };
+            let position = (offset + shred.index() as usize)
+                .checked_sub(first_index)
+                .filter(|position| *position < fec_set_size);
+            let position = match position {
+                Some(position) if shred.is_merkle() && shred.slot() == slot => position,
+                _ => continue,
+            };
+            // Shreds which don't belong to the same FEC set can't be recovered from
+            let shred_merkle_root = match shred.merkle_root() {
+                Some(shred_merkle_root) => shred_merkle_root,
+                None => return Ok(Vec::default()),
+            };
+            if *merkle_root.get_or_insert(shred_merkle_root) != shred_merkle_root {
+                return Ok(Vec::default());
+            }
+            shards[position] = shred.merkle_shard().map(<[u8]>::to_vec);
+        }
+        let present: Vec<bool> = shards.iter().map(Option::is_some).collect();
+        if present[..num_data].iter().all(|present| *present) {
+            return Ok(Vec::default());
+        }
+        let mut shards: Vec<Vec<u8>> = shards
+            .into_iter()
+            .map(|shard| shard.unwrap_or_else(|| vec![0u8; SIZE_OF_MERKLE_SHARD]))
+            .collect();
+        let session = Session::new(num_data, num_coding)?;
+        let mut blocks: Vec<(&mut [u8], bool)> = shards
+            .iter_mut()
+            .map(Vec::as_mut_slice)
+            .zip(present.iter().copied())
+            .collect();
+        session.decode_blocks(&mut blocks)?;
+        // Only the data shards are recovered, the coding shards are needed for the merkle tree
+        let (data, parity) = shards.split_at_mut(num_data);
+        session.encode(data, parity)?;
+
+        let mut data_shreds = Vec::with_capacity(num_data);
+        for shard in &shards[..num_data] {
+            let mut payload = vec![0u8; SHRED_PAYLOAD_SIZE];
+            payload[SIZE_OF_SIGNATURE..SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD]
+                .copy_from_slice(shard);
+            match Shred::new_from_serialized_shred(payload) {
+                Ok(shred) => data_shreds.push(shred),
+                Err(_) => return Ok(Vec::default()),
+            }
+        }
+        let coding_shreds =
+            shards[num_data..]
+                .iter()
+                .zip(first_index as u32..)
+                .map(|(parity, index)| {
+                    Shred::new_merkle_coding(
+                        slot,
+                        index,
+                        first_index as u32,
+                        num_data,
+                        num_coding,
+                        version,
+                        parity,
+                    )
+                });
+        let leaves = data_shreds
+            .iter()
+            .map(|shred| shred.merkle_leaf())
+            .chain(coding_shreds.map(|shred| shred.merkle_leaf()))
+            .collect::<Option<Vec<_>>>();
+        let tree = match leaves {
+            Some(leaves) => make_merkle_tree(leaves),
+            None => return Ok(Vec::default()),
+        };
+        if merkle_root.as_ref() != tree.last() {
+            return Ok(Vec::default());
+        }
+        let recovered_data = data_shreds
+            .into_iter()
+            .zip(present)
+            .enumerate()
+            .filter(|(_, (_, was_present))| !was_present)
+            .filter_map(|(position, (mut shred, _))| {
+                // A valid data shred must be a merkle data shred of the FEC set
+                if !shred.is_merkle()
+                    || !shred.is_data()
+                    || shred.slot() != slot
+                    || shred.index() as usize != first_index + position
+                    || shred.common_header.fec_set_index as usize != first_index
+                {
+                    return None;
+                }
+                shred.set_merkle_proof(&make_merkle_proof(position, &tree));
+                shred.set_signature(signature);
+                Some(shred)
+            })
+            .collect();
+        Ok(recovered_data)
+    }
+
     /// Combines all shreds to recreate the original buffer
     pub fn deshred(shreds: &[Shred]) -> std::result::Result<Vec<u8>, reed_solomon_erasure::Error> {
         use reed_solomon_erasure::Error::TooFewDataShards;
@@ -1055,9 +1572,9 @@ pub fn get_shred_slot_index_type(
         }
     }
 
-    let shred_type = p.data[OFFSET_OF_SHRED_TYPE];
-    if shred_type == DATA_SHRED || shred_type == CODING_SHRED {
-        return Some((slot, index, shred_type == DATA_SHRED));
+    let shred_type = ShredType(p.data[OFFSET_OF_SHRED_TYPE]);
+    if shred_type.is_data() || shred_type.is_code() {
+        return Some((slot, index, shred_type.is_data()));
     } else {
         stats.bad_shred_type += 1;
     }
@@ -1955,4 +2472,157 @@ pub mod tests {
         assert_eq!(None, get_shred_slot_index_type(&packet, &mut stats));
         assert_eq!(1, stats.bad_shred_type);
     }
+
+    #[test]
+    fn test_merkle_tree() {
+        let leaves: Vec<_> = repeat_with(Hash::new_unique).take(37).collect();
+        let tree = make_merkle_tree(leaves.clone());
+        let root = *tree.last().unwrap();
+        for (index, leaf) in leaves.into_iter().enumerate() {
+            let proof = make_merkle_proof(index, &tree);
+            assert_eq!(proof.len(), SIZE_OF_MERKLE_PROOF);
+            assert_eq!(get_merkle_root(index, leaf, &proof), Some(root));
+            assert_ne!(get_merkle_root(index ^ 1, leaf, &proof), Some(root));
+        }
+        assert_eq!(
+            get_merkle_root(
+                MERKLE_TREE_NUM_LEAVES,
+                Hash::default(),
+                &[0u8; SIZE_OF_MERKLE_PROOF]
+            ),
+            None
+        );
+    }
+
+    fn make_merkle_shreds_entries(num_data_shreds: u64) -> Vec<Entry> {
+        let make_entry = || {
+            let tx = system_transaction::transfer(
+                &Keypair::new(),
+                &Pubkey::new_unique(),
+                1,
+                Hash::default(),
+            );
+            Entry::new(&Hash::default(), 1, vec![tx])
+        };
+        let num_entries = max_entries_per_n_shred(
+            &make_entry(),
+            num_data_shreds,
+            Some(SIZE_OF_MERKLE_DATA_SHRED_PAYLOAD),
+        );
+        repeat_with(make_entry).take(num_entries as usize).collect()
+    }
+
+    fn run_test_merkle_shreds(is_last_in_slot: bool) {
+        let keypair = Keypair::new();
+        let slot = 0x1234_5678_9abc_def0;
+        let shredder = Shredder::new(slot, slot - 5, 0, 42).unwrap();
+        let entries = make_merkle_shreds_entries(40);
+        let first_chained_merkle_root = Hash::new_unique();
+        let mut chained_merkle_root = first_chained_merkle_root;
+        let (data_shreds, coding_shreds, next_shred_index) = shredder.entries_to_merkle_shreds(
+            &keypair,
+            &entries,
+            is_last_in_slot,
+            7, // next_shred_index
+            &mut chained_merkle_root,
+            &mut ProcessShredsStats::default(),
+        );
+        assert_eq!(data_shreds.len(), 40);
+        assert_eq!(next_shred_index, 47);
+        assert_eq!(data_shreds.last().unwrap().last_in_slot(), is_last_in_slot);
+        assert!(data_shreds.last().unwrap().data_complete());
+
+        let other_keypair = Keypair::new();
+        for shred in data_shreds.iter().chain(coding_shreds.iter()) {
+            assert!(shred.is_merkle());
+            assert_eq!(shred.payload.len(), SHRED_PAYLOAD_SIZE);
+            assert!(shred.verify(&keypair.pubkey()));
+            assert!(!shred.verify(&other_keypair.pubkey()));
+            let serialized = Shred::new_from_serialized_shred(shred.payload.clone()).unwrap();
+            assert_eq!(&serialized, shred);
+        }
+
+        // The first FEC set chains the given merkle root, the second one chains the merkle root
+        // of the first
+        let first_merkle_root = data_shreds[0].merkle_root().unwrap();
+        let last_merkle_root = data_shreds.last().unwrap().merkle_root().unwrap();
+        assert_ne!(first_merkle_root, last_merkle_root);
+        assert_eq!(chained_merkle_root, last_merkle_root);
+        for shred in &data_shreds {
+            let (merkle_root, chained_merkle_root) = if shred.index() < 7 + 32 {
+                (first_merkle_root, first_chained_merkle_root)
+            } else {
+                (last_merkle_root, first_merkle_root)
+            };
+            assert_eq!(shred.merkle_root(), Some(merkle_root));
+            assert_eq!(shred.chained_merkle_root(), Some(chained_merkle_root));
+        }
+        for shred in &coding_shreds {
+            assert_eq!(shred.chained_merkle_root(), None);
+            assert!(
+                shred.merkle_root() == Some(first_merkle_root)
+                    || shred.merkle_root() == Some(last_merkle_root)
+            );
+        }
+
+        let deshred_payload = Shredder::deshred(&data_shreds).unwrap();
+        let deshred_entries: Vec<Entry> = bincode::deserialize(&deshred_payload).unwrap();
+        assert_eq!(deshred_entries, entries);
+
+        // Tampering with the erasure coded part or the merkle proof of a shred is detected
+        let mut shred = coding_shreds[3].clone();
+        shred.payload[SIZE_OF_CODING_SHRED_HEADERS + 10] ^= 1;
+        assert!(!shred.verify(&keypair.pubkey()));
+        let mut shred = data_shreds[3].clone();
+        shred.payload[SIZE_OF_SIGNATURE + SIZE_OF_MERKLE_SHARD + 1] ^= 1;
+        assert!(!shred.verify(&keypair.pubkey()));
+    }
+
+    #[test]
+    fn test_merkle_shreds() {
+        run_test_merkle_shreds(false);
+        run_test_merkle_shreds(true);
+    }
+
+    #[test]
+    fn test_merkle_shreds_recovery() {
+        let keypair = Keypair::new();
+        let slot = 71489660;
+        let shredder = Shredder::new(slot, slot - 1, 0, 42).unwrap();
+        let entries = make_merkle_shreds_entries(20);
+        let (data_shreds, coding_shreds, _) = shredder.entries_to_merkle_shreds(
+            &keypair,
+            &entries,
+            true, // is_last_in_slot
+            0,    // next_shred_index
+            &mut Hash::default(),
+            &mut ProcessShredsStats::default(),
+        );
+        let num_data = data_shreds.len();
+        let num_coding = coding_shreds.len();
+        assert_eq!(num_data, 20);
+        assert_eq!(num_coding, 44);
+
+        let (kept, missing): (Vec<_>, Vec<_>) = data_shreds
+            .iter()
+            .cloned()
+            .partition(|shred| shred.index() % 3 == 0);
+        let mut shreds = kept;
+        shreds.extend(coding_shreds.iter().cloned());
+        let recovered =
+            Shredder::try_recovery(shreds.clone(), num_data, num_coding, 0, slot).unwrap();
+        assert_eq!(recovered, missing);
+        assert!(recovered
+            .iter()
+            .all(|shred| shred.verify(&keypair.pubkey())));
+
+        // Shreds with a different merkle root don't recover anything
+        let mut shred = shreds.pop().unwrap();
+        shred.payload[SIZE_OF_CODING_SHRED_HEADERS + 10] ^= 1;
+        shreds.push(shred);
+        assert_eq!(
+            Shredder::try_recovery(shreds, num_data, num_coding, 0, slot).unwrap(),
+            vec![]
+        );
+    }
 }
@@ -1,5 +1,5 @@
 #![allow(clippy::implicit_hasher)]
-use crate::shred::{ShredType, SIZE_OF_NONCE};
+use crate::shred::{Shred, ShredType, OFFSET_OF_SHRED_TYPE, SIZE_OF_NONCE};
 use rayon::{
     iter::{
         IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
@@ -67,6 +67,11 @@ pub fn verify_shred_cpu(packet: &Packet, slot_leaders: &HashMap<u64, [u8; 32]>)
     if packet.meta.size < sig_end {
         return Some(0);
     }
+    if is_merkle_shred_packet(packet) {
+        // Merkle shreds are signed over the merkle root of their FEC set
+        let shred = Shred::new_from_serialized_shred(packet.data[..msg_end].to_vec()).ok()?;
+        return Some(u8::from(shred.verify(&Pubkey::new(pubkey))));
+    }
     let signature = Signature::new(&packet.data[sig_start..sig_end]);
     trace!("signature {}", signature);
     if !signature.verify(pubkey, &packet.data[msg_start..msg_end]) {
@@ -75,6 +80,11 @@ pub fn verify_shred_cpu(packet: &Packet, slot_leaders: &HashMap<u64, [u8; 32]>)
     Some(1)
 }
 
+fn is_merkle_shred_packet(packet: &Packet) -> bool {
+    packet.meta.size > OFFSET_OF_SHRED_TYPE
+        && ShredType(packet.data[OFFSET_OF_SHRED_TYPE]).is_merkle()
+}
+
 fn verify_shreds_cpu(batches: &[Packets], slot_leaders: &HashMap<u64, [u8; 32]>) -> Vec<Vec<u8>> {
     use rayon::prelude::*;
     let count = batch_size(batches);
@@ -288,6 +298,14 @@ pub fn verify_shreds_gpu(
     trace!("out buf {:?}", out);
 
     sigverify::copy_return_values(&v_sig_lens, &out, &mut rvs);
+    // The gpu verifies signatures over the packet contents, merkle shreds are verified on the cpu
+    for (batch, rv) in batches.iter().zip(rvs.iter_mut()) {
+        for (packet, v) in batch.packets.iter().zip(rv.iter_mut()) {
+            if is_merkle_shred_packet(packet) {
+                *v = verify_shred_cpu(packet, slot_leaders).unwrap_or(0);
+            }
+        }
+    }
 
     inc_new_counter_debug!("ed25519_shred_verify_gpu", count);
     rvs
@@ -452,8 +470,11 @@ pub fn sign_shreds_gpu(
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::shred::{Shred, Shredder, SIZE_OF_DATA_SHRED_PAYLOAD};
-    use solana_sdk::signature::{Keypair, Signer};
+    use crate::shred::{ProcessShredsStats, Shred, Shredder, SIZE_OF_DATA_SHRED_PAYLOAD};
+    use solana_sdk::{
+        hash::Hash,
+        signature::{Keypair, Signer},
+    };
 
     fn run_test_sigverify_shred_cpu(slot: Slot) {
         solana_logger::setup();
@@ -501,6 +522,37 @@ pub mod tests {
         run_test_sigverify_shred_cpu(0xdead_c0de);
     }
 
+    #[test]
+    fn test_sigverify_merkle_shred_cpu() {
+        let slot = 0xdead_c0de;
+        let keypair = Keypair::new();
+        let shredder = Shredder::new(slot, slot - 1, 0, 0).unwrap();
+        let (data_shreds, coding_shreds, _) = shredder.entries_to_merkle_shreds(
+            &keypair,
+            &[],
+            true, // is_last_in_slot
+            0,    // next_shred_index
+            &mut Hash::default(),
+            &mut ProcessShredsStats::default(),
+        );
+        let leader_slots = [(slot, keypair.pubkey().to_bytes())]
+            .iter()
+            .cloned()
+            .collect();
+        let wrong_leader_slots = [(slot, Keypair::new().pubkey().to_bytes())]
+            .iter()
+            .cloned()
+            .collect();
+        for shred in data_shreds.iter().chain(coding_shreds.iter()) {
+            let mut packet = Packet::default();
+            shred.copy_to_packet(&mut packet);
+            assert_eq!(verify_shred_cpu(&packet, &leader_slots), Some(1));
+            assert_eq!(verify_shred_cpu(&packet, &wrong_leader_slots), Some(0));
+            packet.data[100] ^= 1;
+            assert_eq!(verify_shred_cpu(&packet, &leader_slots), Some(0));
+        }
+    }
+
     fn run_test_sigverify_shreds_cpu(slot: Slot) {
         solana_logger::setup();
         let mut batch = [Packets::default()];
@@ -0,0 +1,293 @@
+//! Transaction statuses sent while the transaction status channel was full, written to disk for
+//! the transaction status service to catch up on rather than being dropped or blocking the
+//! sender
+use {
+    crate::{
+        blockstore::TransactionStatusRecord,
+        blockstore_processor::{DroppedTransactionStatusSlots, TransactionStatusMessage},
+    },
+    crossbeam_channel::{bounded, Receiver, Sender, TrySendError},
+    serde::{Deserialize, Serialize},
+    solana_sdk::clock::Slot,
+    std::{
+        fs::{self, File},
+        io::{self, BufReader, BufWriter, Write},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::Builder,
+    },
+};
+
+const TMP_FILE_EXTENSION: &str = "tmp";
+
+/// A message of the transaction status channel as written to disk
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpilledTransactionStatusMessage {
+    Records(Vec<TransactionStatusRecord>),
+    Freeze(Slot),
+}
+
+/// A directory of spilled messages, one file per message named after its sequence number
+#[derive(Debug)]
+struct SpillDir {
+    dir: PathBuf,
+    next_file_id: AtomicU64,
+    /// Messages queued or written but not yet consumed
+    num_pending: AtomicUsize,
+}
+
+impl SpillDir {
+    fn file_id(path: &Path) -> Option<u64> {
+        path.file_name()?.to_str()?.parse().ok()
+    }
+
+    /// Write a message to a file of its own.  The file is only visible to `spilled_files()` once
+    /// completely written
+    fn write(&self, message: &SpilledTransactionStatusMessage) -> io::Result<()> {
+        let file_id = self.next_file_id.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(file_id.to_string());
+        let tmp_path = path.with_extension(TMP_FILE_EXTENSION);
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        bincode::serialize_into(&mut writer, message)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&tmp_path, &path)
+    }
+}
+
+/// Spills transaction status messages to disk.  Messages are queued and written by a thread of
+/// their own so that the sender doesn't wait on the disk either
+#[derive(Debug)]
+pub struct TransactionStatusSpill {
+    spill_dir: Arc<SpillDir>,
+    sender: Sender<TransactionStatusMessage>,
+}
+
+impl TransactionStatusSpill {
+    /// Files spilled before a restart are kept, and consumed before the ones spilled after it.
+    /// At most `queue_capacity` messages wait to be written, the batches of the slots whose
+    /// messages can't be written are dropped into `dropped_slots`
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        queue_capacity: usize,
+        dropped_slots: DroppedTransactionStatusSlots,
+    ) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut next_file_id = 0;
+        let mut num_pending = 0;
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some(TMP_FILE_EXTENSION) {
+                // A message the previous run didn't finish spilling
+                fs::remove_file(&path)?;
+            } else if let Some(file_id) = SpillDir::file_id(&path) {
+                next_file_id = next_file_id.max(file_id + 1);
+                num_pending += 1;
+            }
+        }
+        let spill_dir = Arc::new(SpillDir {
+            dir,
+            next_file_id: AtomicU64::new(next_file_id),
+            num_pending: AtomicUsize::new(num_pending),
+        });
+        let (sender, receiver) = bounded(queue_capacity);
+        {
+            let spill_dir = spill_dir.clone();
+            // Exits once the spill, and with it the sending half of the queue, is dropped
+            Builder::new()
+                .name("solana-transaction-status-spill".to_string())
+                .spawn(move || {
+                    Self::write_spilled_messages(&spill_dir, receiver, &dropped_slots)
+                })?;
+        }
+        Ok(Self { spill_dir, sender })
+    }
+
+    fn write_spilled_messages(
+        spill_dir: &SpillDir,
+        receiver: Receiver<TransactionStatusMessage>,
+        dropped_slots: &DroppedTransactionStatusSlots,
+    ) {
+        for message in receiver {
+            let (slot, message) = match message {
+                TransactionStatusMessage::Batch(batch) => (
+                    batch.bank.slot(),
+                    SpilledTransactionStatusMessage::Records(batch.into_records()),
+                ),
+                TransactionStatusMessage::Freeze(slot) => {
+                    (slot, SpilledTransactionStatusMessage::Freeze(slot))
+                }
+            };
+            if let Err(err) = spill_dir.write(&message) {
+                warn!("Slot {} transaction_status spill failed: {:?}", slot, err);
+                inc_new_counter_info!("transaction_status_spill-dropped_messages", 1);
+                spill_dir.num_pending.fetch_sub(1, Ordering::Relaxed);
+                let mut dropped_slots = dropped_slots.lock().unwrap();
+                match message {
+                    SpilledTransactionStatusMessage::Records(_) => {
+                        dropped_slots.insert(slot);
+                    }
+                    // The slot is never reported complete without its freeze message
+                    SpilledTransactionStatusMessage::Freeze(_) => {
+                        dropped_slots.remove(&slot);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether messages spilled earlier are yet to be consumed.  Until they are, the messages that
+    /// follow them have to be spilled too to stay in order
+    pub fn is_spilling(&self) -> bool {
+        self.spill_dir.num_pending.load(Ordering::Relaxed) > 0
+    }
+
+    /// Queue a message to be spilled, handing it back if the queue is full
+    pub fn try_spill(
+        &self,
+        message: TransactionStatusMessage,
+    ) -> Result<(), TransactionStatusMessage> {
+        self.spill_dir.num_pending.fetch_add(1, Ordering::Relaxed);
+        self.sender.try_send(message).map_err(|err| {
+            self.spill_dir.num_pending.fetch_sub(1, Ordering::Relaxed);
+            match err {
+                TrySendError::Full(message) | TrySendError::Disconnected(message) => message,
+            }
+        })
+    }
+
+    /// The spilled files, oldest first
+    pub fn spilled_files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = fs::read_dir(&self.spill_dir.dir)?
+            .filter_map(|entry| {
+                entry
+                    .map(|entry| {
+                        let path = entry.path();
+                        SpillDir::file_id(&path).map(|file_id| (file_id, path))
+                    })
+                    .transpose()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        files.sort_unstable();
+        Ok(files.into_iter().map(|(_file_id, path)| path).collect())
+    }
+
+    pub fn read_spilled_file(path: &Path) -> io::Result<SpilledTransactionStatusMessage> {
+        bincode::deserialize_from(BufReader::new(File::open(path)?))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Remove a spilled file once consumed
+    pub fn remove_spilled_file(&self, path: &Path) -> io::Result<()> {
+        self.spill_dir.num_pending.fetch_sub(1, Ordering::Relaxed);
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{pubkey::Pubkey, signature::Signature},
+        solana_transaction_status::TransactionStatusMeta,
+        std::{
+            thread::sleep,
+            time::{Duration, Instant},
+        },
+    };
+
+    fn new_record(slot: u64) -> TransactionStatusRecord {
+        TransactionStatusRecord {
+            slot,
+            signature: Signature::new_unique(),
+            writable_keys: vec![Pubkey::new_unique()],
+            readonly_keys: vec![],
+            memos: Some("memo".to_string()),
+            status: TransactionStatusMeta {
+                fee: 5000,
+                pre_balances: vec![1],
+                post_balances: vec![2],
+                ..TransactionStatusMeta::default()
+            },
+        }
+    }
+
+    fn wait_for_spilled_files(spill: &TransactionStatusSpill, num_files: usize) -> Vec<PathBuf> {
+        let start = Instant::now();
+        loop {
+            let spilled_files = spill.spilled_files().unwrap();
+            if spilled_files.len() >= num_files || start.elapsed() > Duration::from_secs(10) {
+                return spilled_files;
+            }
+            sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_transaction_status_spill() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let dropped_slots = DroppedTransactionStatusSlots::default();
+        let spill = TransactionStatusSpill::new(dir.path(), 16, dropped_slots.clone()).unwrap();
+        assert!(spill.spilled_files().unwrap().is_empty());
+        assert!(!spill.is_spilling());
+
+        for slot in 0..10 {
+            spill
+                .try_spill(TransactionStatusMessage::Freeze(slot))
+                .unwrap();
+        }
+        assert!(spill.is_spilling());
+        // a message left half written is ignored, and removed on restart
+        fs::write(dir.path().join("10.tmp"), [1, 2, 3]).unwrap();
+        let spilled_files = wait_for_spilled_files(&spill, 10);
+        assert_eq!(spilled_files.len(), 10);
+        let spilled: Vec<_> = spilled_files
+            .iter()
+            .map(|path| TransactionStatusSpill::read_spilled_file(path).unwrap())
+            .collect();
+        let expected: Vec<_> = (0..12)
+            .map(SpilledTransactionStatusMessage::Freeze)
+            .collect();
+        assert_eq!(spilled, expected[..10]);
+        spill.remove_spilled_file(&spilled_files[0]).unwrap();
+        drop(spill);
+
+        // messages spilled after a restart come after the ones spilled before it, which are still
+        // pending
+        let spill = TransactionStatusSpill::new(dir.path(), 16, dropped_slots.clone()).unwrap();
+        assert!(!dir.path().join("10.tmp").exists());
+        assert!(spill.is_spilling());
+        spill
+            .try_spill(TransactionStatusMessage::Freeze(10))
+            .unwrap();
+        spill
+            .try_spill(TransactionStatusMessage::Freeze(11))
+            .unwrap();
+        let spilled_files = wait_for_spilled_files(&spill, 11);
+        let spilled: Vec<_> = spilled_files
+            .iter()
+            .map(|path| TransactionStatusSpill::read_spilled_file(path).unwrap())
+            .collect();
+        assert_eq!(spilled, expected[1..]);
+        for path in &spilled_files {
+            spill.remove_spilled_file(path).unwrap();
+        }
+        assert!(!spill.is_spilling());
+        assert!(dropped_slots.lock().unwrap().is_empty());
+
+        // the records round trip through the files too
+        let records = vec![new_record(12), new_record(12)];
+        let message = SpilledTransactionStatusMessage::Records(records);
+        spill.spill_dir.write(&message).unwrap();
+        let spilled_files = spill.spilled_files().unwrap();
+        assert_eq!(
+            TransactionStatusSpill::read_spilled_file(&spilled_files[0]).unwrap(),
+            message
+        );
+    }
+}
@@ -46,20 +46,31 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         bpf_jit: config.bpf_jit,
         send_transaction_service_config: config.send_transaction_service_config.clone(),
         no_poh_speed_test: config.no_poh_speed_test,
+        poh_speed_test: config.poh_speed_test.clone(),
         poh_pinned_cpu_core: config.poh_pinned_cpu_core,
         account_indexes: config.account_indexes.clone(),
+        token_program_ids: config.token_program_ids.clone(),
         accounts_db_caching_enabled: config.accounts_db_caching_enabled,
         warp_slot: config.warp_slot,
         accounts_db_test_hash_calculation: config.accounts_db_test_hash_calculation,
         accounts_db_skip_shrink: config.accounts_db_skip_shrink,
+        snapshot_verification: config.snapshot_verification.clone(),
         accounts_db_use_index_hash_calculation: config.accounts_db_use_index_hash_calculation,
         tpu_coalesce_ms: config.tpu_coalesce_ms,
         validator_exit: Arc::new(RwLock::new(Exit::default())),
         poh_hashes_per_batch: config.poh_hashes_per_batch,
+        poh_tick_spin_threshold: config.poh_tick_spin_threshold,
         no_wait_for_vote_to_start_leader: config.no_wait_for_vote_to_start_leader,
         accounts_shrink_ratio: config.accounts_shrink_ratio,
         accounts_db_config: config.accounts_db_config.clone(),
         disable_epoch_boundary_optimization: config.disable_epoch_boundary_optimization,
+        fork_choice_diagnostics: config.fork_choice_diagnostics.clone(),
+        banking_trace_dir_byte_limit: config.banking_trace_dir_byte_limit,
+        cost_model_read_lock_units: config.cost_model_read_lock_units,
+        tower_thresholds: config.tower_thresholds,
+        remote_authorized_voters: config.remote_authorized_voters.clone(),
+        snapshot_archives_fetcher: config.snapshot_archives_fetcher.clone(),
+        newer_feature_set_warn_stake_percent: config.newer_feature_set_warn_stake_percent,
     }
 }
 
@@ -17,6 +17,7 @@ use {
             broadcast_duplicates_run::BroadcastDuplicatesConfig, BroadcastStageType,
         },
         consensus::{Tower, SWITCH_FORK_THRESHOLD, VOTE_THRESHOLD_DEPTH},
+        fault_injection,
         optimistic_confirmation_verifier::OptimisticConfirmationVerifier,
         replay_stage::DUPLICATE_THRESHOLD,
         tower_storage::{FileTowerStorage, SavedTower, TowerStorage},
@@ -172,6 +173,29 @@ fn test_spend_and_verify_all_nodes_3() {
     );
 }
 
+#[test]
+#[serial]
+fn test_repair_shreds_dropped_by_turbine() {
+    solana_logger::setup_with_default(RUST_LOG_FILTER);
+    error!("test_repair_shreds_dropped_by_turbine");
+    let num_nodes = 2;
+    let cluster =
+        LocalCluster::new_with_equal_stakes(num_nodes, 10_000, 100, SocketAddrSpace::Unspecified);
+    // Neither node gets the shreds of the other's blocks through turbine, so they have to repair
+    // them to keep rooting
+    let node_pubkeys = cluster.get_node_pubkeys();
+    for from in &node_pubkeys {
+        for to in node_pubkeys.iter().filter(|to| *to != from) {
+            fault_injection::drop_shreds(from, to, 100);
+        }
+    }
+    cluster.check_for_new_roots(
+        16,
+        "test_repair_shreds_dropped_by_turbine",
+        SocketAddrSpace::Unspecified,
+    );
+}
+
 #[test]
 #[serial]
 fn test_local_cluster_signature_subscribe() {
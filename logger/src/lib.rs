@@ -1,11 +1,99 @@
 //! The `logger` module configures `env_logger`
 
 use lazy_static::lazy_static;
-use std::sync::{Arc, RwLock};
+use serde_derive::Serialize;
+use std::{
+    cell::Cell,
+    env,
+    io::Write,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    thread,
+    time::Duration,
+};
 
 lazy_static! {
     static ref LOGGER: Arc<RwLock<env_logger::Logger>> =
         Arc::new(RwLock::new(env_logger::Logger::from_default_env()));
+    static ref FILTER: RwLock<LogFilter> = RwLock::new(LogFilter {
+        spec: env::var("RUST_LOG").unwrap_or_default(),
+        generation: 0,
+    });
+    static ref FORMAT: RwLock<LogFormat> = RwLock::new(LogFormat::Text);
+}
+
+thread_local! {
+    static SLOT_CONTEXT: Cell<Option<u64>> = Cell::new(None);
+}
+
+/// How each log line is written
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    /// `env_logger`'s human readable format
+    Text,
+    /// One JSON object per line, for log aggregation pipelines
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown log format: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    timestamp: String,
+    level: &'a str,
+    module: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slot: Option<u64>,
+    message: String,
+}
+
+impl<'a> JsonRecord<'a> {
+    // Attributes `record` to the slot context of the current thread, if any
+    fn new(timestamp: String, record: &'a log::Record) -> Self {
+        Self {
+            timestamp,
+            level: record.level().as_str(),
+            module: record.target(),
+            slot: SLOT_CONTEXT.with(|slot| slot.get()),
+            message: record.args().to_string(),
+        }
+    }
+}
+
+/// Restores the previous slot context of the thread when dropped
+pub struct SlotContextGuard {
+    previous_slot: Option<u64>,
+}
+
+impl Drop for SlotContextGuard {
+    fn drop(&mut self) {
+        SLOT_CONTEXT.with(|slot| slot.set(self.previous_slot));
+    }
+}
+
+// Attributes the lines the current thread logs to `slot` until the returned guard is dropped.
+// Only the JSON format includes the slot
+pub fn slot_context(slot: u64) -> SlotContextGuard {
+    SlotContextGuard {
+        previous_slot: SLOT_CONTEXT.with(|context| context.replace(Some(slot))),
+    }
+}
+
+// The filter of the current logger, in the RUST_LOG format, and the number of times it has been
+// replaced
+struct LogFilter {
+    spec: String,
+    generation: u64,
 }
 
 struct LoggerShim {}
@@ -22,29 +110,81 @@ impl log::Log for LoggerShim {
     fn flush(&self) {}
 }
 
-fn replace_logger(logger: env_logger::Logger) {
+fn replace_logger(logger: env_logger::Logger, spec: String) -> u64 {
+    let generation = {
+        let mut filter = FILTER.write().unwrap();
+        filter.spec = spec;
+        filter.generation += 1;
+        filter.generation
+    };
     log::set_max_level(logger.filter());
     *LOGGER.write().unwrap() = logger;
     let _ = log::set_boxed_logger(Box::new(LoggerShim {}));
+    generation
+}
+
+fn spec_from_env(var: &str, default: &str) -> String {
+    env::var(var).unwrap_or_else(|_| default.to_string())
+}
+
+// Returns the filter currently in effect, in the same format as the RUST_LOG environment variable
+pub fn current_filter() -> String {
+    FILTER.read().unwrap().spec.clone()
+}
+
+// Selects the format of the loggers configured from now on.  Call before the `setup*` functions
+pub fn set_format(format: LogFormat) {
+    *FORMAT.write().unwrap() = format;
+}
+
+fn build_logger(builder: &mut env_logger::Builder) -> env_logger::Logger {
+    match *FORMAT.read().unwrap() {
+        LogFormat::Text => builder.format_timestamp_nanos(),
+        LogFormat::Json => builder.format(|buf, record| {
+            let json_record = JsonRecord::new(buf.timestamp_nanos().to_string(), record);
+            serde_json::to_writer(&mut *buf, &json_record)?;
+            writeln!(buf)
+        }),
+    };
+    builder.build()
 }
 
 // Configures logging with a specific filter overriding RUST_LOG.  _RUST_LOG is used instead
 // so if set it takes precedence.
 // May be called at any time to re-configure the log filter
 pub fn setup_with(filter: &str) {
-    let logger =
-        env_logger::Builder::from_env(env_logger::Env::new().filter_or("_RUST_LOG", filter))
-            .format_timestamp_nanos()
-            .build();
-    replace_logger(logger);
+    setup_with_generation(filter);
+}
+
+fn setup_with_generation(filter: &str) -> u64 {
+    let logger = build_logger(&mut env_logger::Builder::from_env(
+        env_logger::Env::new().filter_or("_RUST_LOG", filter),
+    ));
+    replace_logger(logger, spec_from_env("_RUST_LOG", filter))
+}
+
+// Configures logging with a specific filter like `setup_with`, then restores the filter that was
+// in effect before once `duration` has elapsed, unless the filter was changed in the meantime
+pub fn setup_with_for_duration(filter: &str, duration: Duration) {
+    let previous_filter = current_filter();
+    let generation = setup_with_generation(filter);
+    thread::Builder::new()
+        .name("solana-log-filter-reset".to_string())
+        .spawn(move || {
+            thread::sleep(duration);
+            if FILTER.read().unwrap().generation == generation {
+                setup_with(&previous_filter);
+            }
+        })
+        .unwrap();
 }
 
 // Configures logging with a default filter if RUST_LOG is not set
 pub fn setup_with_default(filter: &str) {
-    let logger = env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(filter))
-        .format_timestamp_nanos()
-        .build();
-    replace_logger(logger);
+    let logger = build_logger(&mut env_logger::Builder::from_env(
+        env_logger::Env::new().default_filter_or(filter),
+    ));
+    replace_logger(logger, spec_from_env("RUST_LOG", filter));
 }
 
 // Configures logging with the default filter "error" if RUST_LOG is not set
@@ -63,9 +203,83 @@ pub fn setup_file_with_default(logfile: &str, filter: &str) {
         .append(true)
         .open(logfile)
         .unwrap();
-    let logger = env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(filter))
-        .format_timestamp_nanos()
-        .target(env_logger::Target::Pipe(Box::new(file)))
-        .build();
-    replace_logger(logger);
+    let logger = build_logger(
+        env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(filter))
+            .target(env_logger::Target::Pipe(Box::new(file))),
+    );
+    replace_logger(logger, spec_from_env("RUST_LOG", filter));
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::time::Instant};
+
+    fn wait_for_filter(filter: &str) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(10) {
+            if current_filter() == filter {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn test_json_record() {
+        let json_record = |timestamp: &str| {
+            serde_json::to_value(JsonRecord::new(
+                timestamp.to_string(),
+                &log::Record::builder()
+                    .args(format_args!("replayed {} entries", 3))
+                    .level(log::Level::Info)
+                    .target("solana_core::replay_stage")
+                    .build(),
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            json_record("2021-11-01T00:00:00.000000000Z"),
+            serde_json::json!({
+                "timestamp": "2021-11-01T00:00:00.000000000Z",
+                "level": "INFO",
+                "module": "solana_core::replay_stage",
+                "message": "replayed 3 entries",
+            })
+        );
+
+        // The slot context of the thread is included while set
+        {
+            let _slot_context = slot_context(42);
+            assert_eq!(json_record("")["slot"], 42);
+            {
+                let _slot_context = slot_context(43);
+                assert_eq!(json_record("")["slot"], 43);
+            }
+            assert_eq!(json_record("")["slot"], 42);
+        }
+        assert!(json_record("").get("slot").is_none());
+    }
+
+    #[test]
+    fn test_setup_with_for_duration() {
+        setup_with("info");
+        assert_eq!(current_filter(), "info");
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+        // the previous filter is restored once the duration has elapsed
+        setup_with_for_duration("debug", Duration::from_millis(100));
+        assert_eq!(current_filter(), "debug");
+        assert_eq!(log::max_level(), log::LevelFilter::Debug);
+        assert!(wait_for_filter("info"));
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+        // a filter set in the meantime is kept
+        setup_with_for_duration("trace", Duration::from_millis(100));
+        setup_with("warn");
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(current_filter(), "warn");
+        assert_eq!(log::max_level(), log::LevelFilter::Warn);
+    }
 }
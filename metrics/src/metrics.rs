@@ -1,7 +1,7 @@
 //! The `metrics` module enables sending measurements to an `InfluxDB` instance
 
 use {
-    crate::{counter::CounterPoint, datapoint::DataPoint},
+    crate::{counter::CounterPoint, datapoint::DataPoint, prometheus},
     gethostname::gethostname,
     lazy_static::lazy_static,
     log::*,
@@ -247,6 +247,7 @@ impl MetricsAgent {
                     }
                     MetricsCommand::Submit(point, level) => {
                         log!(level, "{}", point);
+                        prometheus::record_point(&point);
                         let (_, points) = points_map
                             .entry(level)
                             .or_insert((HashMap::new(), Vec::new()));
@@ -254,6 +255,7 @@ impl MetricsAgent {
                     }
                     MetricsCommand::SubmitCounter(counter, level, bucket) => {
                         debug!("{:?}", counter);
+                        prometheus::record_counter(&counter);
                         let (counters, _) = points_map
                             .entry(level)
                             .or_insert((HashMap::new(), Vec::new()));
@@ -0,0 +1,210 @@
+//! The `prometheus` module exposes the datapoints and counters submitted to the metrics agent
+//! over HTTP in the Prometheus text exposition format, for operators who scrape their nodes
+//! instead of running InfluxDB.  Every numeric datapoint field becomes a gauge holding its last
+//! submitted value, and every counter becomes a Prometheus counter of its accumulated count.
+
+use {
+    crate::{counter::CounterPoint, datapoint::DataPoint},
+    lazy_static::lazy_static,
+    log::*,
+    std::{
+        collections::BTreeMap,
+        fmt::Write as _,
+        io::{self, BufRead, BufReader, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            RwLock,
+        },
+        thread::Builder,
+        time::Duration,
+    },
+};
+
+const METRIC_PREFIX: &str = "solana_";
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Points are only recorded once the exporter is started, so processes that don't serve
+// Prometheus don't pay for it
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref REGISTRY: RwLock<Registry> = RwLock::new(Registry::default());
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    gauges: BTreeMap<String, f64>,
+    counters: BTreeMap<String, f64>,
+}
+
+impl Registry {
+    fn record_point(&mut self, point: &DataPoint) {
+        for (field, value) in &point.fields {
+            if let Some(value) = parse_field_value(value) {
+                self.gauges
+                    .insert(metric_name(&[point.name, *field]), value);
+            }
+        }
+    }
+
+    fn record_counter(&mut self, counter: &CounterPoint) {
+        *self
+            .counters
+            .entry(metric_name(&[counter.name, "total"]))
+            .or_default() += counter.count as f64;
+    }
+
+    fn render(&self) -> String {
+        let mut output = String::new();
+        for (metric_type, metrics) in [("gauge", &self.gauges), ("counter", &self.counters)] {
+            for (name, value) in metrics {
+                let _ = writeln!(output, "# TYPE {} {}", name, metric_type);
+                let _ = writeln!(output, "{} {}", name, value);
+            }
+        }
+        output
+    }
+}
+
+// Datapoint fields hold InfluxDB line protocol values; string fields can't be exported
+fn parse_field_value(value: &str) -> Option<f64> {
+    match value {
+        "true" => Some(1.0),
+        "false" => Some(0.0),
+        _ => value
+            .strip_suffix('i')
+            .unwrap_or(value)
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite()),
+    }
+}
+
+// Prometheus metric names may only contain ASCII letters, digits and underscores
+fn metric_name(parts: &[&str]) -> String {
+    let mut name = METRIC_PREFIX.to_string();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            name.push('_');
+        }
+        name.extend(part.chars().map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        }));
+    }
+    name
+}
+
+pub(crate) fn record_point(point: &DataPoint) {
+    if ENABLED.load(Ordering::Relaxed) {
+        REGISTRY.write().unwrap().record_point(point);
+    }
+}
+
+pub(crate) fn record_counter(counter: &CounterPoint) {
+    if ENABLED.load(Ordering::Relaxed) {
+        REGISTRY.write().unwrap().record_counter(counter);
+    }
+}
+
+/// Serves the submitted metrics at `http://<bind_addr>/metrics` for the rest of the process
+/// lifetime
+pub fn start_exporter(bind_addr: SocketAddr) -> io::Result<()> {
+    let listener = TcpListener::bind(bind_addr)?;
+    ENABLED.store(true, Ordering::Relaxed);
+    info!("serving Prometheus metrics on http://{}/metrics", bind_addr);
+
+    Builder::new()
+        .name("solana-metrics-prometheus".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream.and_then(handle_request);
+                if let Err(err) = result {
+                    debug!("Prometheus request failed: {}", err);
+                }
+            }
+        })?;
+    Ok(())
+}
+
+fn handle_request(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = if path == "/metrics" || path.starts_with("/metrics?") {
+        ("200 OK", REGISTRY.read().unwrap().render())
+    } else {
+        ("404 Not Found", String::new())
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+        Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, std::time::UNIX_EPOCH};
+
+    #[test]
+    fn test_parse_field_value() {
+        assert_eq!(parse_field_value("42i"), Some(42.0));
+        assert_eq!(parse_field_value("-1.5"), Some(-1.5));
+        assert_eq!(parse_field_value("true"), Some(1.0));
+        assert_eq!(parse_field_value("false"), Some(0.0));
+        assert_eq!(parse_field_value("\"text\""), None);
+        assert_eq!(parse_field_value("NaN"), None);
+    }
+
+    #[test]
+    fn test_metric_name() {
+        assert_eq!(
+            metric_name(&["replay-slot-stats", "total_entries"]),
+            "solana_replay_slot_stats_total_entries"
+        );
+        assert_eq!(
+            metric_name(&["Bank Hash", "total"]),
+            "solana_bank_hash_total"
+        );
+    }
+
+    #[test]
+    fn test_registry_render() {
+        let mut registry = Registry::default();
+        registry.record_point(
+            DataPoint::new("bank-stats")
+                .add_field_i64("slot", 5)
+                .add_field_str("leader", "abc")
+                .add_field_bool("is_frozen", true),
+        );
+        registry.record_point(DataPoint::new("bank-stats").add_field_i64("slot", 6));
+        for count in [3, 4] {
+            registry.record_counter(&CounterPoint {
+                name: "packets",
+                count,
+                timestamp: UNIX_EPOCH,
+            });
+        }
+
+        assert_eq!(
+            registry.render(),
+            "# TYPE solana_bank_stats_is_frozen gauge\n\
+            solana_bank_stats_is_frozen 1\n\
+            # TYPE solana_bank_stats_slot gauge\n\
+            solana_bank_stats_slot 6\n\
+            # TYPE solana_packets_total counter\n\
+            solana_packets_total 7\n"
+        );
+    }
+}
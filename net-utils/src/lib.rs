@@ -3,7 +3,7 @@
 use {
     log::*,
     rand::{thread_rng, Rng},
-    socket2::{Domain, SockAddr, Socket, Type},
+    socket2::{Domain, SockAddr, SockRef, Socket, Type},
     std::{
         collections::{BTreeMap, HashSet},
         io::{self, Read, Write},
@@ -485,6 +485,23 @@ pub fn bind_to(ip_addr: IpAddr, port: u16, reuseaddr: bool) -> io::Result<UdpSoc
     sock.bind(&SockAddr::from(addr)).map(|_| sock.into())
 }
 
+/// Set the SO_RCVBUF and SO_SNDBUF sizes of `socket`, the kernel may round them or cap them at
+/// `net.core.rmem_max` and `net.core.wmem_max`
+pub fn set_socket_buffer_sizes(
+    socket: &UdpSocket,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+) -> io::Result<()> {
+    let socket = SockRef::from(socket);
+    if let Some(recv_buffer_size) = recv_buffer_size {
+        socket.set_recv_buffer_size(recv_buffer_size)?;
+    }
+    if let Some(send_buffer_size) = send_buffer_size {
+        socket.set_send_buffer_size(send_buffer_size)?;
+    }
+    Ok(())
+}
+
 // binds both a UdpSocket and a TcpListener
 pub fn bind_common(
     ip_addr: IpAddr,
@@ -641,6 +658,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_set_socket_buffer_sizes() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        set_socket_buffer_sizes(&socket, Some(32 * 1024), Some(48 * 1024)).unwrap();
+        let socket = SockRef::from(&socket);
+        assert!(socket.recv_buffer_size().unwrap() >= 32 * 1024);
+        assert!(socket.send_buffer_size().unwrap() >= 48 * 1024);
+    }
+
     #[test]
     fn test_bind_in_range_nil() {
         let ip_addr = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
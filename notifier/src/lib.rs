@@ -11,6 +11,17 @@
 /// export TELEGRAM_CHAT_ID=...
 /// ```
 ///
+/// To post notifications as JSON `{"text": ...}` to any other webhook:
+/// ```bash
+/// export WEBHOOK=...
+/// ```
+///
+/// To trigger PagerDuty incidents, and resolve them with `Notifier::resolve`, define the
+/// integration key of an Events API v2 integration:
+/// ```bash
+/// export PAGERDUTY_INTEGRATION_KEY=...
+/// ```
+///
 /// To receive a Twilio SMS notification on failure, having a Twilio account,
 /// and a sending number owned by that account,
 /// define environment variable before running `solana-watchtower`:
@@ -27,6 +38,14 @@ struct TelegramWebHook {
     chat_id: String,
 }
 
+struct PagerDutyIntegration {
+    integration_key: String,
+    // Identifies the incident triggered by this notifier, so that it can be resolved
+    dedup_key: String,
+}
+
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
 #[derive(Debug, Default)]
 struct TwilioWebHook {
     account: String,
@@ -80,6 +99,8 @@ enum NotificationType {
     Slack(String),
     Telegram(TelegramWebHook),
     Twilio(TwilioWebHook),
+    Webhook(String),
+    PagerDuty(PagerDutyIntegration),
     Log(Level),
 }
 
@@ -119,6 +140,17 @@ impl Notifier {
             notifiers.push(NotificationType::Twilio(webhook));
         }
 
+        if let Ok(webhook) = env::var(format!("{}WEBHOOK", env_prefix)) {
+            notifiers.push(NotificationType::Webhook(webhook));
+        }
+
+        if let Ok(integration_key) = env::var(format!("{}PAGERDUTY_INTEGRATION_KEY", env_prefix)) {
+            notifiers.push(NotificationType::PagerDuty(PagerDutyIntegration {
+                integration_key,
+                dedup_key: format!("{}solana-notifier", env_prefix.to_lowercase()),
+            }));
+        }
+
         if let Ok(log_level) = env::var(format!("{}LOG_NOTIFIER_LEVEL", env_prefix)) {
             match Level::from_str(&log_level) {
                 Ok(level) => notifiers.push(NotificationType::Log(level)),
@@ -142,6 +174,16 @@ impl Notifier {
     }
 
     pub fn send(&self, msg: &str) {
+        self.notify(msg, false)
+    }
+
+    /// Send `msg` as the resolution of the previous notifications.  It is sent like any other
+    /// message, except that it resolves the PagerDuty incident instead of triggering one
+    pub fn resolve(&self, msg: &str) {
+        self.notify(msg, true)
+    }
+
+    fn notify(&self, msg: &str, resolve: bool) {
         for notifier in &self.notifiers {
             match notifier {
                 NotificationType::Discord(webhook) => {
@@ -202,6 +244,30 @@ impl Notifier {
                         warn!("Failed to send Twilio message: {:?}", err);
                     }
                 }
+                NotificationType::Webhook(webhook) => {
+                    let data = json!({ "text": msg });
+                    if let Err(err) = self.client.post(webhook).json(&data).send() {
+                        warn!("Failed to send webhook message: {:?}", err);
+                    }
+                }
+                NotificationType::PagerDuty(PagerDutyIntegration {
+                    integration_key,
+                    dedup_key,
+                }) => {
+                    let data = json!({
+                        "routing_key": integration_key,
+                        "dedup_key": dedup_key,
+                        "event_action": if resolve { "resolve" } else { "trigger" },
+                        "payload": {
+                            "summary": msg,
+                            "source": dedup_key,
+                            "severity": "critical",
+                        },
+                    });
+                    if let Err(err) = self.client.post(PAGERDUTY_EVENTS_URL).json(&data).send() {
+                        warn!("Failed to send PagerDuty event: {:?}", err);
+                    }
+                }
                 NotificationType::Log(level) => {
                     log!(*level, "{}", msg)
                 }
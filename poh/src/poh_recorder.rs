@@ -814,6 +814,7 @@ pub fn create_test_recorder(
         bank.ticks_per_slot(),
         crate::poh_service::DEFAULT_PINNED_CPU_CORE,
         crate::poh_service::DEFAULT_HASHES_PER_BATCH,
+        None,
         record_receiver,
     );
 
@@ -8,6 +8,7 @@ use {
     solana_measure::measure::Measure,
     solana_sdk::poh_config::PohConfig,
     std::{
+        fs,
         sync::{
             atomic::{AtomicBool, Ordering},
             Arc, Mutex,
@@ -31,6 +32,9 @@ pub const DEFAULT_HASHES_PER_BATCH: u64 = 64;
 
 pub const DEFAULT_PINNED_CPU_CORE: usize = 0;
 
+// Cores reserved with the `isolcpus` kernel parameter, which the scheduler runs no other threads on
+const ISOLATED_CPUS_PATH: &str = "/sys/devices/system/cpu/isolated";
+
 const TARGET_SLOT_ADJUSTMENT_NS: u64 = 50_000_000;
 
 #[derive(Debug)]
@@ -43,6 +47,10 @@ struct PohTiming {
     total_tick_time_ns: u64,
     last_metric: Instant,
     total_record_time_us: u64,
+    last_tick: Option<Instant>,
+    // How far the time between consecutive ticks strayed from the target
+    total_tick_jitter_us: u64,
+    max_tick_jitter_us: u64,
 }
 
 impl PohTiming {
@@ -56,7 +64,25 @@ impl PohTiming {
             total_tick_time_ns: 0,
             last_metric: Instant::now(),
             total_record_time_us: 0,
+            last_tick: None,
+            total_tick_jitter_us: 0,
+            max_tick_jitter_us: 0,
+        }
+    }
+    fn record_tick(&mut self, target_ns_per_tick: u64) {
+        let now = Instant::now();
+        if let Some(last_tick) = self.last_tick {
+            let tick_ns = now.duration_since(last_tick).as_nanos() as u64;
+            let jitter_us = if tick_ns > target_ns_per_tick {
+                tick_ns - target_ns_per_tick
+            } else {
+                target_ns_per_tick - tick_ns
+            } / 1000;
+            self.total_tick_jitter_us += jitter_us;
+            self.max_tick_jitter_us = self.max_tick_jitter_us.max(jitter_us);
         }
+        self.last_tick = Some(now);
+        self.num_ticks += 1;
     }
     fn report(&mut self, ticks_per_slot: u64) {
         if self.last_metric.elapsed().as_millis() > 1000 {
@@ -72,6 +98,8 @@ impl PohTiming {
                 ("total_lock_time_us", self.total_lock_time_ns / 1000, i64),
                 ("total_hash_time_us", self.total_hash_time_ns / 1000, i64),
                 ("total_record_time_us", self.total_record_time_us, i64),
+                ("total_tick_jitter_us", self.total_tick_jitter_us, i64),
+                ("max_tick_jitter_us", self.max_tick_jitter_us, i64),
             );
             self.total_sleep_us = 0;
             self.num_ticks = 0;
@@ -81,6 +109,8 @@ impl PohTiming {
             self.total_hash_time_ns = 0;
             self.last_metric = Instant::now();
             self.total_record_time_us = 0;
+            self.total_tick_jitter_us = 0;
+            self.max_tick_jitter_us = 0;
         }
     }
 }
@@ -93,6 +123,7 @@ impl PohService {
         ticks_per_slot: u64,
         pinned_cpu_core: usize,
         hashes_per_batch: u64,
+        tick_spin_threshold: Option<Duration>,
         record_receiver: Receiver<Record>,
     ) -> Self {
         let poh_exit_ = poh_exit.clone();
@@ -122,13 +153,16 @@ impl PohService {
                     // Let's dedicate one of the CPU cores to this thread so that it can gain
                     // from cache performance.
                     if let Some(cores) = core_affinity::get_core_ids() {
-                        core_affinity::set_for_current(cores[pinned_cpu_core]);
+                        let core = cores[pinned_cpu_core];
+                        core_affinity::set_for_current(core);
+                        Self::check_isolated_cpu(core.id);
                     }
                     Self::tick_producer(
                         poh_recorder,
                         &poh_exit_,
                         ticks_per_slot,
                         hashes_per_batch,
+                        tick_spin_threshold,
                         record_receiver,
                         Self::target_ns_per_tick(
                             ticks_per_slot,
@@ -143,6 +177,24 @@ impl PohService {
         Self { tick_producer }
     }
 
+    // Other threads scheduled on the PoH core delay its ticks, so recommend isolating it
+    fn check_isolated_cpu(cpu: usize) {
+        let isolated_cpus = match fs::read_to_string(ISOLATED_CPUS_PATH) {
+            Ok(isolated_cpus) => isolated_cpus,
+            Err(_) => return,
+        };
+        match parse_cpu_list(&isolated_cpus) {
+            Some(isolated_cpus) if isolated_cpus.contains(&cpu) => {
+                info!("PoH is pinned to isolated CPU {}", cpu);
+            }
+            _ => warn!(
+                "PoH is pinned to CPU {}, which is not isolated. Reserve it with the \
+                isolcpus kernel parameter to reduce tick jitter",
+                cpu
+            ),
+        }
+    }
+
     pub fn target_ns_per_tick(ticks_per_slot: u64, target_tick_duration_ns: u64) -> u64 {
         // Account for some extra time outside of PoH generation to account
         // for processing time outside PoH.
@@ -221,6 +273,7 @@ impl PohService {
         timing: &mut PohTiming,
         record_receiver: &Receiver<Record>,
         hashes_per_batch: u64,
+        tick_spin_threshold: Option<Duration>,
         poh: &Arc<Mutex<Poh>>,
         target_ns_per_tick: u64,
     ) -> bool {
@@ -287,11 +340,26 @@ impl PohService {
                         continue;
                     }
 
-                    // busy wait, polling for new records and after dropping poh lock (reset can occur, for example)
+                    // wait for new records after dropping poh lock (reset can occur, for example).
+                    // Until the spin threshold, block on the channel so the core is yielded,
+                    // then busy wait to hit the ideal time precisely
                     drop(poh_l);
-                    while ideal_time > Instant::now() {
+                    loop {
+                        let now = Instant::now();
+                        if ideal_time <= now {
+                            break;
+                        }
+                        let remaining = ideal_time - now;
                         // check to see if a record request has been sent
-                        if let Ok(record) = record_receiver.try_recv() {
+                        let record = match tick_spin_threshold {
+                            Some(tick_spin_threshold) if remaining > tick_spin_threshold => {
+                                record_receiver
+                                    .recv_timeout(remaining - tick_spin_threshold)
+                                    .ok()
+                            }
+                            _ => record_receiver.try_recv().ok(),
+                        };
+                        if let Some(record) = record {
                             // remember the record we just received as the next record to occur
                             *next_record = Some(record);
                             break;
@@ -310,6 +378,7 @@ impl PohService {
         poh_exit: &AtomicBool,
         ticks_per_slot: u64,
         hashes_per_batch: u64,
+        tick_spin_threshold: Option<Duration>,
         record_receiver: Receiver<Record>,
         target_ns_per_tick: u64,
     ) {
@@ -323,6 +392,7 @@ impl PohService {
                 &mut timing,
                 &record_receiver,
                 hashes_per_batch,
+                tick_spin_threshold,
                 &poh,
                 target_ns_per_tick,
             );
@@ -338,7 +408,7 @@ impl PohService {
                     tick_time.stop();
                     timing.total_tick_time_ns += tick_time.as_ns();
                 }
-                timing.num_ticks += 1;
+                timing.record_tick(target_ns_per_tick);
 
                 timing.report(ticks_per_slot);
                 if poh_exit.load(Ordering::Relaxed) {
@@ -353,6 +423,18 @@ impl PohService {
     }
 }
 
+// Parses a list of CPUs in the kernel's format, like "0-2,5"
+fn parse_cpu_list(cpu_list: &str) -> Option<Vec<usize>> {
+    let mut cpus = vec![];
+    for range in cpu_list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -472,6 +554,7 @@ mod tests {
                 0,
                 DEFAULT_PINNED_CPU_CORE,
                 hashes_per_batch,
+                None,
                 record_receiver,
             );
             poh_recorder.lock().unwrap().set_bank(&bank);
@@ -547,4 +630,27 @@ mod tests {
         }
         Blockstore::destroy(&ledger_path).unwrap();
     }
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("\n"), Some(vec![]));
+        assert_eq!(parse_cpu_list("3\n"), Some(vec![3]));
+        assert_eq!(parse_cpu_list("0-2,5,7-8\n"), Some(vec![0, 1, 2, 5, 7, 8]));
+        assert_eq!(parse_cpu_list("0-x"), None);
+    }
+
+    #[test]
+    fn test_poh_timing_record_tick() {
+        let mut timing = PohTiming::new();
+        timing.record_tick(0);
+        assert_eq!(timing.num_ticks, 1);
+        assert_eq!(timing.max_tick_jitter_us, 0);
+
+        // A tick later than the target counts as jitter
+        timing.last_tick = Some(Instant::now() - Duration::from_millis(10));
+        timing.record_tick(1_000_000);
+        assert_eq!(timing.num_ticks, 2);
+        assert!(timing.max_tick_jitter_us >= 9_000);
+        assert_eq!(timing.total_tick_jitter_us, timing.max_tick_jitter_us);
+    }
 }
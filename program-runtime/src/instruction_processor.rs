@@ -11,7 +11,7 @@ use solana_sdk::{
     instruction::{Instruction, InstructionError},
     keyed_account::keyed_account_at_index,
     message::Message,
-    process_instruction::{Executor, InvokeContext, ProcessInstructionWithContext},
+    process_instruction::{Executor, InvokeContext, ProcessInstructionWithContext, ProgramEvent},
     pubkey::Pubkey,
     rent::Rent,
     system_instruction::MAX_PERMITTED_DATA_LENGTH,
@@ -28,12 +28,21 @@ use std::{
 pub struct Executors {
     pub executors: HashMap<Pubkey, Arc<dyn Executor>>,
     pub is_dirty: bool,
+    /// Programs deployed, upgraded or closed by the transaction, in order
+    pub program_events: Vec<(Pubkey, ProgramEvent)>,
 }
 impl Executors {
     pub fn insert(&mut self, key: Pubkey, executor: Arc<dyn Executor>) {
         let _ = self.executors.insert(key, executor);
         self.is_dirty = true;
     }
+    pub fn record_program_event(&mut self, key: Pubkey, event: ProgramEvent) {
+        if event == ProgramEvent::Closed {
+            let _ = self.executors.remove(&key);
+        }
+        self.program_events.push((key, event));
+        self.is_dirty = true;
+    }
     pub fn get(&self, key: &Pubkey) -> Option<Arc<dyn Executor>> {
         self.executors.get(key).cloned()
     }
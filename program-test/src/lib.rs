@@ -65,7 +65,10 @@ use {
 };
 
 // Export types so test clients can limit their solana crate dependencies
-pub use solana_banks_client::BanksClient;
+pub use solana_banks_client::{
+    BanksClient, BanksTransactionResultWithMetadata, BanksTransactionResultWithSimulation,
+    TransactionMetadata,
+};
 
 // Export tokio for test clients
 pub use tokio;
@@ -83,6 +86,42 @@ pub enum ProgramTestError {
     InvalidWarpSlot,
 }
 
+/// Assert that an instruction of a processed transaction consumed no more than `max_units`
+/// compute units, to catch compute regressions.  The transaction metadata is available from
+/// `BanksClient::process_transaction_with_metadata`.
+#[track_caller]
+pub fn assert_instruction_compute_units(
+    metadata: &TransactionMetadata,
+    instruction_index: usize,
+    max_units: u64,
+) {
+    let units = *metadata
+        .compute_units_consumed
+        .get(instruction_index)
+        .unwrap_or_else(|| panic!("Transaction has no instruction {}", instruction_index));
+    assert!(
+        units <= max_units,
+        "Instruction {} consumed {} compute units, exceeding its budget of {}\n{}",
+        instruction_index,
+        units,
+        max_units,
+        metadata.compute_units_report()
+    );
+}
+
+/// Assert that a processed transaction consumed no more than `max_units` compute units in total
+#[track_caller]
+pub fn assert_transaction_compute_units(metadata: &TransactionMetadata, max_units: u64) {
+    let units = metadata.total_compute_units_consumed();
+    assert!(
+        units <= max_units,
+        "Transaction consumed {} compute units, exceeding its budget of {}\n{}",
+        units,
+        max_units,
+        metadata.compute_units_report()
+    );
+}
+
 thread_local! {
     static INVOKE_CONTEXT: RefCell<Option<(usize, usize)>> = RefCell::new(None);
 }
@@ -380,6 +419,16 @@ impl solana_sdk::program_stubs::SyscallStubs for SyscallStubs {
     fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
         get_sysvar::<Rent>(&rent::id(), var_addr)
     }
+
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        get_invoke_context()
+            .get_processed_sibling_instruction(index)
+            .cloned()
+    }
+
+    fn sol_get_minimum_stake_delegation(&self) -> u64 {
+        process_instruction::get_minimum_stake_delegation(get_invoke_context())
+    }
 }
 
 pub fn find_file(filename: &str) -> Option<PathBuf> {
@@ -0,0 +1,67 @@
+use {
+    solana_program_test::{
+        assert_instruction_compute_units, assert_transaction_compute_units, processor, ProgramTest,
+        TransactionMetadata,
+    },
+    solana_sdk::{
+        account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction, msg,
+        pubkey::Pubkey, signature::Signer, transaction::Transaction,
+    },
+};
+
+fn noop_process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    msg!("noop");
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_transaction_with_metadata() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("noop", program_id, processor!(noop_process_instruction));
+
+    let mut context = program_test.start_with_context().await;
+    let instructions = vec![
+        Instruction::new_with_bincode(program_id, &(), vec![]),
+        Instruction::new_with_bincode(program_id, &(), vec![]),
+    ];
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result.result, Ok(()));
+    let metadata = result.metadata.unwrap();
+    assert!(metadata
+        .log_messages
+        .contains(&"Program log: noop".to_string()));
+
+    // Native processors are not metered
+    assert_eq!(metadata.compute_units_consumed, vec![0, 0]);
+    assert_instruction_compute_units(&metadata, 1, 0);
+    assert_transaction_compute_units(&metadata, 0);
+}
+
+#[test]
+#[should_panic(
+    expected = "Instruction 1 consumed 5000 compute units, exceeding its budget of 4000"
+)]
+fn instruction_over_budget() {
+    let metadata = TransactionMetadata {
+        log_messages: vec![],
+        compute_units_consumed: vec![1000, 5000],
+    };
+    assert_transaction_compute_units(&metadata, 6000);
+    assert_instruction_compute_units(&metadata, 0, 4000);
+    assert_instruction_compute_units(&metadata, 1, 4000);
+}
@@ -23,13 +23,16 @@ use solana_rbpf::{
     vm::{Config, Executable, Tracer},
 };
 use solana_runtime::{
-    bank::{Bank, ExecuteTimings, TransactionBalancesSet, TransactionResults},
+    bank::{
+        Bank, ExecuteTimings, TransactionBalancesSet, TransactionResults, TransactionReturnData,
+    },
     bank_client::BankClient,
     genesis_utils::{create_genesis_config, GenesisConfigInfo},
     loader_utils::{
         load_buffer_account, load_program, load_upgradeable_program, set_upgrade_authority,
         upgrade_program,
     },
+    log_collector,
 };
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
@@ -42,7 +45,7 @@ use solana_sdk::{
     instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
     keyed_account::KeyedAccount,
     loader_instruction,
-    message::{Message, SanitizedMessage},
+    message::{LoadedAddresses, Message, SanitizedMessage},
     process_instruction::{InvokeContext, MockInvokeContext},
     pubkey::Pubkey,
     signature::{keypair_from_seed, Keypair, Signer},
@@ -52,8 +55,9 @@ use solana_sdk::{
     transaction::{Transaction, TransactionError},
 };
 use solana_transaction_status::{
-    token_balances::collect_token_balances, ConfirmedTransaction, InnerInstructions,
-    TransactionStatusMeta, TransactionWithStatusMeta, UiTransactionEncoding,
+    token_balances::{collect_token_balances, default_token_program_ids},
+    ConfirmedTransaction, InnerInstructions, TransactionStatusMeta, TransactionWithStatusMeta,
+    UiTransactionEncoding,
 };
 use std::{
     cell::RefCell, collections::HashMap, convert::TryFrom, convert::TryInto, env, fs::File,
@@ -302,7 +306,8 @@ fn process_transaction_and_record_inner(
     let signature = tx.signatures.get(0).unwrap().clone();
     let txs = vec![tx];
     let tx_batch = bank.prepare_batch(txs).unwrap();
-    let (mut results, _, mut inner_instructions, _transaction_logs) = bank
+    let (mut results, _, mut inner_instructions, _transaction_logs, _return_data, _compute_units) =
+        bank
         .load_execute_and_commit_transactions(
             &tx_batch,
             MAX_PROCESSING_AGE,
@@ -327,7 +332,9 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
     let batch = bank.prepare_batch(txs.clone()).unwrap();
     let mut timings = ExecuteTimings::default();
     let mut mint_decimals = HashMap::new();
-    let tx_pre_token_balances = collect_token_balances(&bank, &batch, &mut mint_decimals);
+    let token_program_ids = default_token_program_ids();
+    let tx_pre_token_balances =
+        collect_token_balances(&bank, &batch, &mut mint_decimals, &token_program_ids);
     let (
         TransactionResults {
             execution_results, ..
@@ -339,6 +346,8 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
         },
         inner_instructions,
         transaction_logs,
+        transaction_return_data,
+        _compute_units,
     ) = bank.load_execute_and_commit_transactions(
         &batch,
         std::usize::MAX,
@@ -347,7 +356,8 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
         true,
         &mut timings,
     );
-    let tx_post_token_balances = collect_token_balances(&bank, &batch, &mut mint_decimals);
+    let tx_post_token_balances =
+        collect_token_balances(&bank, &batch, &mut mint_decimals, &token_program_ids);
 
     izip!(
         txs.iter(),
@@ -358,6 +368,7 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
         tx_pre_token_balances.into_iter(),
         tx_post_token_balances.into_iter(),
         transaction_logs.into_iter(),
+        transaction_return_data.into_iter(),
     )
     .map(
         |(
@@ -368,7 +379,8 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
             post_balances,
             pre_token_balances,
             post_token_balances,
-            log_messages,
+            log_entries,
+            return_data,
         )| {
             let lamports_per_signature = nonce_rollback
                 .map(|nonce_rollback| nonce_rollback.lamports_per_signature())
@@ -376,7 +388,7 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
                     bank.get_lamports_per_signature_for_blockhash(&tx.message().recent_blockhash)
                 })
                 .expect("lamports_per_signature must exist");
-            let fee = Bank::get_fee_for_message_with_lamports_per_signature(
+            let fee = bank.get_fee_for_message_with_lamports_per_signature(
                 &SanitizedMessage::try_from(tx.message().clone()).unwrap(),
                 lamports_per_signature,
             );
@@ -401,14 +413,19 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
                 pre_token_balances: Some(pre_token_balances),
                 post_token_balances: Some(post_token_balances),
                 inner_instructions,
-                log_messages,
+                log_messages: log_entries
+                    .as_ref()
+                    .map(|log_entries| log_collector::log_messages(log_entries)),
                 rewards: None,
+                return_data,
+                loaded_addresses: LoadedAddresses::default(),
+                log_entries,
             };
 
             ConfirmedTransaction {
                 slot: bank.slot(),
                 transaction: TransactionWithStatusMeta {
-                    transaction: tx.clone(),
+                    transaction: tx.clone().into(),
                     meta: Some(tx_status_meta),
                 },
                 block_time: None,
@@ -421,7 +438,9 @@ fn execute_transactions(bank: &Bank, txs: Vec<Transaction>) -> Vec<ConfirmedTran
 fn print_confirmed_tx(name: &str, confirmed_tx: ConfirmedTransaction) {
     let block_time = confirmed_tx.block_time;
     let tx = confirmed_tx.transaction.transaction.clone();
-    let encoded = confirmed_tx.encode(UiTransactionEncoding::JsonParsed);
+    let encoded = confirmed_tx
+        .encode(UiTransactionEncoding::JsonParsed, Some(0))
+        .unwrap();
     println!("EXECUTE {} (slot {})", name, encoded.slot);
     println_transaction(&tx, &encoded.transaction.meta, "  ", None, block_time);
 }
@@ -791,12 +810,20 @@ fn test_return_data_and_log_data_syscall() {
 
         assert!(result.result.is_ok());
 
-        assert_eq!(result.logs[1], "Program data: AQID BAUG");
+        assert_eq!(result.log_entries[1].message, "Program data: AQID BAUG");
 
         assert_eq!(
-            result.logs[3],
+            result.log_entries[3].message,
             format!("Program return: {} CAFE", program_id)
         );
+
+        assert_eq!(
+            result.return_data,
+            Some(TransactionReturnData {
+                program_id,
+                data: vec![0x08, 0x01, 0x44],
+            })
+        );
     }
 }
 
@@ -0,0 +1,241 @@
+//! Verifying and JIT compiling a program dominates the cost of its first invocation in a bank.
+//! The banks' executor caches are per fork and keyed by program address, so a redeployment, an
+//! eviction or a fork switch makes a bank load the program again even when the same bytes were
+//! compiled before.  This process-wide cache keeps the loaded programs across transactions and
+//! slots, keyed by the hash of their ELF and of the environment they were loaded in, and evicts
+//! the least recently used ones once their ELFs and JIT compiled code exceed a total size.
+
+use {
+    crate::BpfExecutor,
+    lazy_static::lazy_static,
+    solana_metrics::datapoint_info,
+    solana_rbpf::{ebpf, vm::Config},
+    solana_sdk::{
+        hash::{hashv, Hash},
+        pubkey::Pubkey,
+        timing::AtomicInterval,
+    },
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// The cached programs are bounded by the total size of their ELFs and JIT compiled code
+pub const DEFAULT_MAX_CACHED_BYTES: usize = 256 * 1024 * 1024;
+// Machine code the JIT compiler reserves per eBPF instruction, and for the whole program
+const JIT_CODE_BYTES_PER_INSTRUCTION: usize = 256;
+const JIT_CODE_BYTES_PER_PROGRAM: usize = 4096;
+const STATS_REPORT_INTERVAL_MS: u64 = 10_000;
+
+lazy_static! {
+    static ref EXECUTABLE_CACHE: Mutex<ExecutableCache<BpfExecutor>> =
+        Mutex::new(ExecutableCache::new(DEFAULT_MAX_CACHED_BYTES));
+    static ref SYSCALL_FEATURES: Vec<Pubkey> = crate::syscalls::syscall_features();
+}
+
+/// The key a program is cached under.  Programs loaded with a different config, with or
+/// without JIT, or with a different set of syscall features active are not interchangeable
+pub fn cache_key(elf: &[u8], config: &Config, use_jit: bool, syscall_features: &[bool]) -> Hash {
+    let environment: Vec<u8> = [
+        config.max_call_depth as u64,
+        config.stack_frame_size as u64,
+        config.enable_instruction_tracing as u64,
+        config.verify_mul64_imm_nonzero as u64,
+        use_jit as u64,
+    ]
+    .iter()
+    .flat_map(|value| value.to_le_bytes())
+    .chain(syscall_features.iter().map(|is_active| *is_active as u8))
+    .collect();
+    hashv(&[&environment, elf])
+}
+
+/// Features that change the loaded form of a program, see `syscalls::register_syscalls`
+pub fn active_syscall_features(is_feature_active: impl Fn(&Pubkey) -> bool) -> Vec<bool> {
+    SYSCALL_FEATURES.iter().map(is_feature_active).collect()
+}
+
+/// The memory the JIT compiler maps for a program with `text_len` bytes of eBPF instructions:
+/// a table of the machine code offset of each instruction, and the machine code itself, which
+/// is sized upfront for the longest translation of each instruction
+pub fn jit_code_len(text_len: usize, config: &Config) -> usize {
+    let num_instructions = text_len / ebpf::INSN_SIZE;
+    let pc_table_len = (num_instructions + 1) * std::mem::size_of::<u64>();
+    let machine_code_len =
+        num_instructions * JIT_CODE_BYTES_PER_INSTRUCTION + JIT_CODE_BYTES_PER_PROGRAM;
+    let noop_len = (machine_code_len as f64 * config.noop_instruction_ratio) as usize;
+    pc_table_len + machine_code_len + noop_len
+}
+
+pub(crate) fn get(key: &Hash) -> Option<Arc<BpfExecutor>> {
+    EXECUTABLE_CACHE.lock().unwrap().get(key)
+}
+
+pub(crate) fn put(key: Hash, executor: Arc<BpfExecutor>, len: usize) {
+    EXECUTABLE_CACHE.lock().unwrap().put(key, executor, len);
+}
+
+#[derive(Debug)]
+struct ExecutableCacheEntry<T> {
+    executor: Arc<T>,
+    // The size of the program's ELF and JIT compiled code
+    len: usize,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct ExecutableCacheStats {
+    last_report: AtomicInterval,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+/// LRU cache of loaded programs, bounded by the total size of their ELFs and JIT compiled code
+#[derive(Debug)]
+pub struct ExecutableCache<T> {
+    max_bytes: usize,
+    cached_bytes: usize,
+    clock: u64,
+    entries: HashMap<Hash, ExecutableCacheEntry<T>>,
+    stats: ExecutableCacheStats,
+}
+
+impl<T> ExecutableCache<T> {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            cached_bytes: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            stats: ExecutableCacheStats::default(),
+        }
+    }
+
+    pub fn get(&mut self, key: &Hash) -> Option<Arc<T>> {
+        self.clock += 1;
+        let executor = match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = self.clock;
+                self.stats.hits += 1;
+                Some(entry.executor.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        };
+        self.maybe_report_stats();
+        executor
+    }
+
+    /// Caches `executor`, whose ELF and JIT compiled code take `len` bytes
+    pub fn put(&mut self, key: Hash, executor: Arc<T>, len: usize) {
+        if len > self.max_bytes || self.entries.contains_key(&key) {
+            return;
+        }
+        while self.cached_bytes + len > self.max_bytes {
+            let least_recently_used = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+                .unwrap();
+            let entry = self.entries.remove(&least_recently_used).unwrap();
+            self.cached_bytes -= entry.len;
+            self.stats.evictions += 1;
+        }
+        self.clock += 1;
+        self.cached_bytes += len;
+        self.entries.insert(
+            key,
+            ExecutableCacheEntry {
+                executor,
+                len,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    fn maybe_report_stats(&mut self) {
+        if !self
+            .stats
+            .last_report
+            .should_update(STATS_REPORT_INTERVAL_MS)
+        {
+            return;
+        }
+        let lookups = self.stats.hits + self.stats.misses;
+        datapoint_info!(
+            "bpf-executable-cache",
+            ("hits", self.stats.hits, i64),
+            ("misses", self.stats.misses, i64),
+            (
+                "hit_rate",
+                self.stats.hits as f64 / lookups.max(1) as f64,
+                f64
+            ),
+            ("evictions", self.stats.evictions, i64),
+            ("cached_programs", self.entries.len(), i64),
+            ("cached_bytes", self.cached_bytes, i64),
+        );
+        self.stats.hits = 0;
+        self.stats.misses = 0;
+        self.stats.evictions = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_executable_cache_lru_by_bytes() {
+        let mut cache = ExecutableCache::new(100);
+        let keys: Vec<_> = (0..4).map(|_| Hash::new_unique()).collect();
+        cache.put(keys[0], Arc::new(0), 40);
+        cache.put(keys[1], Arc::new(1), 40);
+        assert_eq!(cache.get(&keys[0]), Some(Arc::new(0)));
+
+        // Evicts the least recently used program to make room
+        cache.put(keys[2], Arc::new(2), 40);
+        assert_eq!(cache.get(&keys[1]), None);
+        assert_eq!(cache.get(&keys[0]), Some(Arc::new(0)));
+        assert_eq!(cache.get(&keys[2]), Some(Arc::new(2)));
+        assert_eq!(cache.cached_bytes, 80);
+
+        // Programs larger than the whole cache are not cached
+        cache.put(keys[3], Arc::new(3), 101);
+        assert_eq!(cache.get(&keys[3]), None);
+        assert_eq!(cache.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_jit_code_len() {
+        let config = Config {
+            noop_instruction_ratio: 0.0,
+            ..Config::default()
+        };
+        assert_eq!(jit_code_len(0, &config), 8 + 4096);
+        assert_eq!(jit_code_len(80, &config), 11 * 8 + 10 * 256 + 4096);
+        // The JIT compiled code dwarfs the program it was compiled from
+        assert!(jit_code_len(1024 * 1024, &Config::default()) > 32 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let config = Config::default();
+        let features = [true, false];
+        let key = cache_key(b"elf", &config, true, &features);
+        assert_eq!(key, cache_key(b"elf", &config, true, &features));
+        assert_ne!(key, cache_key(b"elf2", &config, true, &features));
+        assert_ne!(key, cache_key(b"elf", &config, false, &features));
+        assert_ne!(key, cache_key(b"elf", &config, true, &[true, true]));
+        let config = Config {
+            stack_frame_size: config.stack_frame_size * 2,
+            ..config
+        };
+        assert_ne!(key, cache_key(b"elf", &config, true, &features));
+    }
+}
@@ -2,6 +2,7 @@
 pub mod alloc;
 pub mod allocator_bump;
 pub mod deprecated;
+pub mod executable_cache;
 pub mod serialization;
 pub mod syscalls;
 pub mod upgradeable;
@@ -31,17 +32,19 @@ use solana_sdk::{
     clock::Clock,
     entrypoint::{HEAP_LENGTH, SUCCESS},
     feature_set::{
-        add_missing_program_error_mappings, close_upgradeable_program_accounts, do_support_realloc,
-        fix_write_privs, reduce_required_deploy_balance, requestable_heap_size,
-        stop_verify_mul64_imm_nonzero,
+        add_invalid_realloc_program_error, add_missing_program_error_mappings,
+        close_upgradeable_program_accounts, do_support_realloc, fix_write_privs,
+        reduce_required_deploy_balance, requestable_heap_size, stop_verify_mul64_imm_nonzero,
     },
     ic_logger_msg, ic_msg,
     instruction::{AccountMeta, InstructionError},
     keyed_account::{from_keyed_account, keyed_account_at_index, KeyedAccount},
     loader_instruction::LoaderInstruction,
     loader_upgradeable_instruction::UpgradeableLoaderInstruction,
-    process_instruction::{stable_log, ComputeMeter, Executor, InvokeContext, Logger},
-    program_error::{ACCOUNT_NOT_RENT_EXEMPT, BORSH_IO_ERROR},
+    process_instruction::{
+        stable_log, ComputeMeter, Executor, InvokeContext, Logger, ProgramEvent,
+    },
+    program_error::{ACCOUNT_NOT_RENT_EXEMPT, BORSH_IO_ERROR, INVALID_ACCOUNT_DATA_REALLOC},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     rent::Rent,
@@ -77,10 +80,6 @@ pub fn create_executor(
     invoke_context: &mut dyn InvokeContext,
     use_jit: bool,
 ) -> Result<Arc<BpfExecutor>, InstructionError> {
-    let syscall_registry = syscalls::register_syscalls(invoke_context).map_err(|e| {
-        ic_msg!(invoke_context, "Failed to register syscalls: {}", e);
-        InstructionError::ProgramEnvironmentSetupFailure
-    })?;
     let compute_budget = invoke_context.get_compute_budget();
     let config = Config {
         max_call_depth: compute_budget.max_call_depth,
@@ -90,6 +89,27 @@ pub fn create_executor(
             .is_feature_active(&stop_verify_mul64_imm_nonzero::id()), // TODO: Feature gate and then remove me
         ..Config::default()
     };
+    let (cache_key, elf_len) = {
+        let keyed_accounts = invoke_context.get_keyed_accounts()?;
+        let programdata = keyed_account_at_index(keyed_accounts, programdata_account_index)?;
+        let account = programdata.try_account_ref()?;
+        let elf = &account.data()[programdata_offset..];
+        let syscall_features = executable_cache::active_syscall_features(|feature_id| {
+            invoke_context.is_feature_active(feature_id)
+        });
+        (
+            executable_cache::cache_key(elf, &config, use_jit, &syscall_features),
+            elf.len(),
+        )
+    };
+    if let Some(executor) = executable_cache::get(&cache_key) {
+        return Ok(executor);
+    }
+
+    let syscall_registry = syscalls::register_syscalls(invoke_context).map_err(|e| {
+        ic_msg!(invoke_context, "Failed to register syscalls: {}", e);
+        InstructionError::ProgramEnvironmentSetupFailure
+    })?;
     let mut executable = {
         let keyed_accounts = invoke_context.get_keyed_accounts()?;
         let programdata = keyed_account_at_index(keyed_accounts, programdata_account_index)?;
@@ -104,13 +124,17 @@ pub fn create_executor(
     let text_bytes = executable.get_text_bytes().1;
     verifier::check(text_bytes, &config)
         .map_err(|e| map_ebpf_error(invoke_context, EbpfError::UserError(e.into())))?;
+    let mut cached_len = elf_len;
     if use_jit {
+        cached_len += executable_cache::jit_code_len(text_bytes.len(), &config);
         if let Err(err) = executable.jit_compile() {
             ic_msg!(invoke_context, "Failed to compile program {:?}", err);
             return Err(InstructionError::ProgramFailedToCompile);
         }
     }
-    Ok(Arc::new(BpfExecutor { executable }))
+    let executor = Arc::new(BpfExecutor { executable });
+    executable_cache::put(cache_key, executor.clone(), cached_len);
+    Ok(executor)
 }
 
 fn write_program_data(
@@ -485,6 +509,7 @@ fn process_loader_upgradeable_instruction(
                 use_jit,
             )?;
             invoke_context.add_executor(&new_program_id, executor);
+            invoke_context.notify_program_event(&new_program_id, ProgramEvent::Deployed);
 
             let keyed_accounts = invoke_context.get_keyed_accounts()?;
             let payer = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
@@ -629,6 +654,7 @@ fn process_loader_upgradeable_instruction(
                 use_jit,
             )?;
             invoke_context.add_executor(&new_program_id, executor);
+            invoke_context.notify_program_event(&new_program_id, ProgramEvent::Upgraded);
 
             let keyed_accounts = invoke_context.get_keyed_accounts()?;
             let programdata = keyed_account_at_index(keyed_accounts, first_instruction_account)?;
@@ -824,6 +850,8 @@ fn process_loader_upgradeable_instruction(
                         }
                     }
 
+                    invoke_context
+                        .notify_program_event(program_account.unsigned_key(), ProgramEvent::Closed);
                     ic_logger_msg!(logger, "Closed Program {}", program_account.unsigned_key());
                 }
                 _ => {
@@ -967,6 +995,8 @@ impl Executor for BpfExecutor {
         let invoke_depth = invoke_context.invoke_depth();
         let add_missing_program_error_mappings =
             invoke_context.is_feature_active(&add_missing_program_error_mappings::id());
+        let add_invalid_realloc_program_error =
+            invoke_context.is_feature_active(&add_invalid_realloc_program_error::id());
 
         let mut serialize_time = Measure::start("serialize");
         let keyed_accounts = invoke_context.get_keyed_accounts()?;
@@ -1032,8 +1062,10 @@ impl Executor for BpfExecutor {
             match result {
                 Ok(status) => {
                     if status != SUCCESS {
-                        let error: InstructionError = if !add_missing_program_error_mappings
-                            && (status == ACCOUNT_NOT_RENT_EXEMPT || status == BORSH_IO_ERROR)
+                        let error: InstructionError = if (!add_missing_program_error_mappings
+                            && (status == ACCOUNT_NOT_RENT_EXEMPT || status == BORSH_IO_ERROR))
+                            || (!add_invalid_realloc_program_error
+                                && status == INVALID_ACCOUNT_DATA_REALLOC)
                         {
                             // map originally missing error mappings to InvalidError
                             InstructionError::InvalidError
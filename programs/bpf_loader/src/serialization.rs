@@ -176,7 +176,7 @@ pub fn get_serialized_account_size_aligned(
         size_of::<u8>() // is_signer
             + size_of::<u8>() // is_writable
             + size_of::<u8>() // executable
-            + 4 // padding to 128-bit aligned
+            + 4 // original data length, also padding to 128-bit aligned
             + size_of::<Pubkey>()  // key
             + size_of::<Pubkey>() // owner
             + size_of::<u64>()  // lamports
@@ -228,8 +228,8 @@ pub fn serialize_parameters_aligned(
                 .map_err(|_| InstructionError::InvalidArgument)?;
             v.write_u8(keyed_account.executable()? as u8)
                 .map_err(|_| InstructionError::InvalidArgument)?;
-            v.write_all(&[0u8, 0, 0, 0])
-                .map_err(|_| InstructionError::InvalidArgument)?; // 4 bytes of padding to make 128-bit aligned
+            v.write_u32::<LittleEndian>(keyed_account.data_len()? as u32)
+                .map_err(|_| InstructionError::InvalidArgument)?; // original data length, also padding to make 128-bit aligned
             v.write_all(keyed_account.unsigned_key().as_ref())
                 .map_err(|_| InstructionError::InvalidArgument)?;
             v.write_all(keyed_account.owner()?.as_ref())
@@ -321,6 +321,7 @@ mod tests {
         account_info::AccountInfo,
         bpf_loader,
         entrypoint::deserialize,
+        program_error::ProgramError,
     };
     use std::{
         cell::RefCell,
@@ -446,6 +447,16 @@ mod tests {
             assert_eq!(account.owner(), account_info.owner);
             assert_eq!(account.executable(), account_info.executable);
             assert_eq!(account.rent_epoch(), account_info.rent_epoch);
+            assert_eq!(account.data().len(), unsafe {
+                account_info.original_data_len()
+            });
+            assert_eq!(
+                account_info.realloc(
+                    account.data().len() + MAX_PERMITTED_DATA_INCREASE + 1,
+                    false
+                ),
+                Err(ProgramError::InvalidRealloc)
+            );
 
             assert_eq!(
                 (*account_info.lamports.borrow() as *const u64).align_offset(align_of::<u64>()),
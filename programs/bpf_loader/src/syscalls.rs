@@ -19,15 +19,17 @@ use solana_sdk::{
     entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     epoch_schedule::EpochSchedule,
     feature_set::{
-        allow_native_ids, blake3_syscall_enabled, check_seed_length,
-        close_upgradeable_program_accounts, demote_program_write_locks, disable_fees_sysvar,
-        do_support_realloc, libsecp256k1_0_5_upgrade_enabled, mem_overlap_fix,
-        prevent_calling_precompiles_as_programs, return_data_syscall_enabled,
-        secp256k1_recover_syscall_enabled, sol_log_data_syscall_enabled,
+        add_get_minimum_delegation_instruction_to_stake_program,
+        add_get_processed_sibling_instruction_syscall, allow_native_ids, blake3_syscall_enabled,
+        check_seed_length, close_upgradeable_program_accounts, cpi_depth_and_account_costs,
+        demote_program_write_locks, disable_fees_sysvar, do_support_realloc,
+        libsecp256k1_0_5_upgrade_enabled, mem_overlap_fix, prevent_calling_precompiles_as_programs,
+        return_data_syscall_enabled, secp256k1_recover_syscall_enabled,
+        sol_log_data_syscall_enabled,
     },
     hash::{Hasher, HASH_BYTES},
     ic_msg,
-    instruction::{AccountMeta, Instruction, InstructionError},
+    instruction::{AccountMeta, Instruction, InstructionError, ProcessedSiblingInstruction},
     keccak,
     message::Message,
     native_loader,
@@ -113,8 +115,25 @@ impl SyscallConsume for Rc<RefCell<dyn ComputeMeter>> {
 /// Simple bump allocator, never frees
 use crate::allocator_bump::BpfAllocator;
 
+/// The features `register_syscalls` checks, in the order it checks them
+pub fn syscall_features() -> Vec<Pubkey> {
+    let syscall_features = RefCell::new(vec![]);
+    register_syscalls_with_features(|feature_id| {
+        syscall_features.borrow_mut().push(*feature_id);
+        true
+    })
+    .expect("syscalls register");
+    syscall_features.into_inner()
+}
+
 pub fn register_syscalls(
     invoke_context: &mut dyn InvokeContext,
+) -> Result<SyscallRegistry, EbpfError<BpfError>> {
+    register_syscalls_with_features(|feature_id| invoke_context.is_feature_active(feature_id))
+}
+
+fn register_syscalls_with_features(
+    is_feature_active: impl Fn(&Pubkey) -> bool,
 ) -> Result<SyscallRegistry, EbpfError<BpfError>> {
     let mut syscall_registry = SyscallRegistry::default();
 
@@ -140,12 +159,12 @@ pub fn register_syscalls(
     syscall_registry.register_syscall_by_name(b"sol_sha256", SyscallSha256::call)?;
     syscall_registry.register_syscall_by_name(b"sol_keccak256", SyscallKeccak256::call)?;
 
-    if invoke_context.is_feature_active(&secp256k1_recover_syscall_enabled::id()) {
+    if is_feature_active(&secp256k1_recover_syscall_enabled::id()) {
         syscall_registry
             .register_syscall_by_name(b"sol_secp256k1_recover", SyscallSecp256k1Recover::call)?;
     }
 
-    if invoke_context.is_feature_active(&blake3_syscall_enabled::id()) {
+    if is_feature_active(&blake3_syscall_enabled::id()) {
         syscall_registry.register_syscall_by_name(b"sol_blake3", SyscallBlake3::call)?;
     }
 
@@ -155,7 +174,7 @@ pub fn register_syscalls(
         b"sol_get_epoch_schedule_sysvar",
         SyscallGetEpochScheduleSysvar::call,
     )?;
-    if invoke_context.is_feature_active(&disable_fees_sysvar::id()) {
+    if is_feature_active(&disable_fees_sysvar::id()) {
         syscall_registry
             .register_syscall_by_name(b"sol_get_fees_sysvar", SyscallGetFeesSysvar::call)?;
     }
@@ -177,7 +196,7 @@ pub fn register_syscalls(
     syscall_registry.register_syscall_by_name(b"sol_alloc_free_", SyscallAllocFree::call)?;
 
     // Return data
-    if invoke_context.is_feature_active(&return_data_syscall_enabled::id()) {
+    if is_feature_active(&return_data_syscall_enabled::id()) {
         syscall_registry
             .register_syscall_by_name(b"sol_set_return_data", SyscallSetReturnData::call)?;
         syscall_registry
@@ -185,10 +204,26 @@ pub fn register_syscalls(
     }
 
     // Log data
-    if invoke_context.is_feature_active(&sol_log_data_syscall_enabled::id()) {
+    if is_feature_active(&sol_log_data_syscall_enabled::id()) {
         syscall_registry.register_syscall_by_name(b"sol_log_data", SyscallLogData::call)?;
     }
 
+    // Processed sibling instructions
+    if is_feature_active(&add_get_processed_sibling_instruction_syscall::id()) {
+        syscall_registry.register_syscall_by_name(
+            b"sol_get_processed_sibling_instruction",
+            SyscallGetProcessedSiblingInstruction::call,
+        )?;
+    }
+
+    // Minimum stake delegation
+    if is_feature_active(&add_get_minimum_delegation_instruction_to_stake_program::id()) {
+        syscall_registry.register_syscall_by_name(
+            b"sol_get_minimum_stake_delegation",
+            SyscallGetMinimumStakeDelegation::call,
+        )?;
+    }
+
     Ok(syscall_registry)
 }
 
@@ -366,6 +401,10 @@ pub fn bind_syscall_context_objects<'a>(
         invoke_context.is_feature_active(&return_data_syscall_enabled::id());
     let is_sol_log_data_syscall_active =
         invoke_context.is_feature_active(&sol_log_data_syscall_enabled::id());
+    let is_get_processed_sibling_instruction_syscall_active =
+        invoke_context.is_feature_active(&add_get_processed_sibling_instruction_syscall::id());
+    let is_get_minimum_stake_delegation_syscall_active = invoke_context
+        .is_feature_active(&add_get_minimum_delegation_instruction_to_stake_program::id());
 
     let invoke_context = Rc::new(RefCell::new(invoke_context));
 
@@ -428,6 +467,25 @@ pub fn bind_syscall_context_objects<'a>(
         }),
     );
 
+    // Processed sibling instructions
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_get_processed_sibling_instruction_syscall_active,
+        Box::new(SyscallGetProcessedSiblingInstruction {
+            invoke_context: invoke_context.clone(),
+            loader_id,
+        }),
+    );
+
+    // Minimum stake delegation
+    bind_feature_gated_syscall_context_object!(
+        vm,
+        is_get_minimum_stake_delegation_syscall_active,
+        Box::new(SyscallGetMinimumStakeDelegation {
+            invoke_context: invoke_context.clone(),
+        }),
+    );
+
     // Cross-program invocation syscalls
     vm.bind_syscall_context_object(
         Box::new(SyscallInvokeSignedC {
@@ -2193,9 +2251,16 @@ fn call<'a>(
     memory_mapping: &MemoryMapping,
 ) -> Result<u64, EbpfError<BpfError>> {
     let mut invoke_context = syscall.get_context_mut()?;
-    invoke_context
-        .get_compute_meter()
-        .consume(invoke_context.get_compute_budget().invoke_units)?;
+    let charge_cpi_depth_and_accounts =
+        invoke_context.is_feature_active(&cpi_depth_and_account_costs::id());
+    let invoke_cost = if charge_cpi_depth_and_accounts {
+        invoke_context
+            .get_compute_budget()
+            .cpi_cost(invoke_context.get_stack_height(), account_infos_len)
+    } else {
+        invoke_context.get_compute_budget().invoke_units
+    };
+    invoke_context.get_compute_meter().consume(invoke_cost)?;
     let do_support_realloc = invoke_context.is_feature_active(&do_support_realloc::id());
 
     // Translate and verify caller's data
@@ -2222,8 +2287,10 @@ fn call<'a>(
         *invoke_context,
     )?;
 
-    // Record the instruction
+    // Record the instruction and what invoking it cost, so simulations can report the cost of
+    // each invocation separately from the units consumed by the called program
     invoke_context.record_instruction(&instruction);
+    invoke_context.record_invoke_units(invoke_cost);
 
     // Process instruction
     InstructionProcessor::process_cross_program_instruction(
@@ -2474,6 +2541,128 @@ impl<'a> SyscallObject<BpfError> for SyscallLogData<'a> {
     }
 }
 
+// Processed sibling instructions
+pub struct SyscallGetProcessedSiblingInstruction<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BpfError> for SyscallGetProcessedSiblingInstruction<'a> {
+    fn call(
+        &mut self,
+        index: u64,
+        meta_addr: u64,
+        program_id_addr: u64,
+        data_addr: u64,
+        accounts_addr: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+
+        let budget = invoke_context.get_compute_budget();
+
+        question_mark!(
+            invoke_context
+                .get_compute_meter()
+                .consume(budget.syscall_base_cost),
+            result
+        );
+
+        if let Some(instruction) = invoke_context.get_processed_sibling_instruction(index as usize)
+        {
+            let ProcessedSiblingInstruction {
+                data_len,
+                accounts_len,
+            } = question_mark!(
+                translate_type_mut::<ProcessedSiblingInstruction>(
+                    memory_mapping,
+                    meta_addr,
+                    self.loader_id,
+                ),
+                result
+            );
+
+            // Only copy the instruction out once the caller has sized its
+            // buffers to fit it
+            if *data_len == instruction.data.len() && *accounts_len == instruction.accounts.len() {
+                let program_id = question_mark!(
+                    translate_type_mut::<Pubkey>(memory_mapping, program_id_addr, self.loader_id),
+                    result
+                );
+                let data = question_mark!(
+                    translate_slice_mut::<u8>(
+                        memory_mapping,
+                        data_addr,
+                        *data_len as u64,
+                        self.loader_id,
+                    ),
+                    result
+                );
+                let accounts = question_mark!(
+                    translate_slice_mut::<AccountMeta>(
+                        memory_mapping,
+                        accounts_addr,
+                        *accounts_len as u64,
+                        self.loader_id,
+                    ),
+                    result
+                );
+
+                *program_id = instruction.program_id;
+                data.clone_from_slice(instruction.data.as_slice());
+                accounts.clone_from_slice(instruction.accounts.as_slice());
+            }
+            *data_len = instruction.data.len();
+            *accounts_len = instruction.accounts.len();
+            *result = Ok(true as u64);
+            return;
+        }
+        *result = Ok(false as u64);
+    }
+}
+
+// Minimum stake delegation
+pub struct SyscallGetMinimumStakeDelegation<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+}
+impl<'a> SyscallObject<BpfError> for SyscallGetMinimumStakeDelegation<'a> {
+    fn call(
+        &mut self,
+        _arg1: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        _memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BpfError>>,
+    ) {
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+
+        let budget = invoke_context.get_compute_budget();
+
+        question_mark!(
+            invoke_context
+                .get_compute_meter()
+                .consume(budget.syscall_base_cost),
+            result
+        );
+
+        *result = Ok(process_instruction::get_minimum_stake_delegation(
+            *invoke_context,
+        ));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2486,7 +2675,7 @@ mod tests {
         hash::hashv,
         process_instruction::{MockComputeMeter, MockInvokeContext, MockLogger},
     };
-    use std::str::FromStr;
+    use std::{collections::HashSet, str::FromStr};
 
     macro_rules! assert_access_violation {
         ($result:expr, $va:expr, $len:expr) => {
@@ -2505,6 +2694,27 @@ mod tests {
         pub len: usize,
     }
 
+    #[test]
+    fn test_syscall_features() {
+        let syscall_features = syscall_features();
+        assert!(syscall_features.contains(&blake3_syscall_enabled::id()));
+        assert_eq!(
+            syscall_features.iter().collect::<HashSet<_>>().len(),
+            syscall_features.len()
+        );
+
+        // each of the features changes the syscalls registered
+        let num_syscalls = |is_feature_active: &dyn Fn(&Pubkey) -> bool| {
+            register_syscalls_with_features(is_feature_active)
+                .unwrap()
+                .get_number_of_syscalls()
+        };
+        let num_ungated_syscalls = num_syscalls(&|_| false);
+        for feature_id in &syscall_features {
+            assert!(num_syscalls(&|id| id == feature_id) > num_ungated_syscalls);
+        }
+    }
+
     #[test]
     fn test_translate() {
         const START: u64 = 0x100000000;
@@ -3494,6 +3704,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_syscall_get_sysvar_compute_cost() {
+        let config = Config::default();
+        let got_rent = Rent::default();
+        let got_rent_va = 0x100000000;
+        let memory_mapping = MemoryMapping::new::<UserError>(
+            vec![
+                MemoryRegion::default(),
+                MemoryRegion {
+                    host_addr: &got_rent as *const _ as u64,
+                    vm_addr: got_rent_va,
+                    len: size_of::<Rent>() as u64,
+                    vm_gap_shift: 63,
+                    is_writable: true,
+                },
+            ],
+            &config,
+        )
+        .unwrap();
+
+        let mut invoke_context = MockInvokeContext::new(&Pubkey::default(), vec![]);
+        let mut data = vec![];
+        bincode::serialize_into(&mut data, &Rent::default()).unwrap();
+        invoke_context
+            .get_sysvars()
+            .borrow_mut()
+            .push((sysvar::rent::id(), Some(Rc::new(data))));
+        let cost = invoke_context.get_compute_budget().sysvar_base_cost + size_of::<Rent>() as u64;
+
+        // Not enough compute units remaining to read the sysvar
+        invoke_context.compute_meter = Rc::new(RefCell::new(MockComputeMeter {
+            remaining: cost - 1,
+        }));
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        {
+            let mut syscall = SyscallGetRentSysvar {
+                invoke_context: Rc::new(RefCell::new(&mut invoke_context)),
+                loader_id: &bpf_loader::id(),
+            };
+            syscall.call(got_rent_va, 0, 0, 0, 0, &memory_mapping, &mut result);
+        }
+        assert_eq!(
+            Err(EbpfError::UserError(BpfError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+
+        // Reading the sysvar consumes exactly the base cost plus its size
+        invoke_context.compute_meter = Rc::new(RefCell::new(MockComputeMeter { remaining: cost }));
+        let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+        {
+            let mut syscall = SyscallGetRentSysvar {
+                invoke_context: Rc::new(RefCell::new(&mut invoke_context)),
+                loader_id: &bpf_loader::id(),
+            };
+            syscall.call(got_rent_va, 0, 0, 0, 0, &memory_mapping, &mut result);
+        }
+        result.unwrap();
+        assert_eq!(invoke_context.compute_meter.borrow().get_remaining(), 0);
+    }
+
+    #[test]
+    fn test_syscall_get_minimum_stake_delegation() {
+        let config = Config::default();
+        let memory_mapping = MemoryMapping::new::<UserError>(vec![], &config).unwrap();
+        let mut invoke_context = MockInvokeContext::new(&Pubkey::default(), vec![]);
+        let mut get_minimum_stake_delegation = |disabled_features: &[Pubkey]| {
+            invoke_context.disabled_features = disabled_features.iter().cloned().collect();
+            let mut result: Result<u64, EbpfError<BpfError>> = Ok(0);
+            let mut syscall = SyscallGetMinimumStakeDelegation {
+                invoke_context: Rc::new(RefCell::new(&mut invoke_context)),
+            };
+            syscall.call(0, 0, 0, 0, 0, &memory_mapping, &mut result);
+            result.unwrap()
+        };
+
+        assert_eq!(
+            get_minimum_stake_delegation(&[]),
+            solana_sdk::feature_set::stake_raise_minimum_delegation_to_1_sol::MINIMUM_DELEGATION
+        );
+        assert_eq!(
+            get_minimum_stake_delegation(&[
+                solana_sdk::feature_set::stake_raise_minimum_delegation_to_1_sol::id()
+            ]),
+            1
+        );
+    }
+
     #[test]
     fn test_overlapping() {
         assert!(!check_overlapping(10, 7, 3));
@@ -1,6 +1,9 @@
 #![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(min_specialization))]
 #![allow(clippy::integer_arithmetic)]
-use solana_sdk::genesis_config::GenesisConfig;
+use solana_sdk::{
+    genesis_config::GenesisConfig,
+    process_instruction::{get_minimum_stake_delegation, InvokeContext},
+};
 
 #[deprecated(
     since = "1.8.0",
@@ -15,3 +18,10 @@ pub mod stake_state;
 pub fn add_genesis_accounts(genesis_config: &mut GenesisConfig) -> u64 {
     config::add_genesis_account(genesis_config)
 }
+
+/// The minimum number of lamports that can be delegated, as reported by the
+/// `GetMinimumDelegation` instruction and the `sol_get_minimum_stake_delegation`
+/// syscall
+pub fn get_minimum_delegation(invoke_context: &dyn InvokeContext) -> u64 {
+    get_minimum_stake_delegation(invoke_context)
+}
@@ -1,5 +1,5 @@
 use {
-    crate::{config, stake_state::StakeAccount},
+    crate::{config, get_minimum_delegation, stake_state::StakeAccount},
     log::*,
     solana_sdk::{
         feature_set,
@@ -27,6 +27,19 @@ pub fn process_instruction(
     data: &[u8],
     invoke_context: &mut dyn InvokeContext,
 ) -> Result<(), InstructionError> {
+    let instruction = limited_deserialize(data)?;
+
+    // GetMinimumDelegation takes no accounts, so it's processed before the stake account is
+    // looked up
+    if matches!(instruction, StakeInstruction::GetMinimumDelegation)
+        && invoke_context.is_feature_active(
+            &feature_set::add_get_minimum_delegation_instruction_to_stake_program::id(),
+        )
+    {
+        let minimum_delegation = get_minimum_delegation(invoke_context);
+        return invoke_context.set_return_data(minimum_delegation.to_le_bytes().to_vec());
+    }
+
     let keyed_accounts = invoke_context.get_keyed_accounts()?;
 
     trace!("process_instruction: {:?}", data);
@@ -38,7 +51,7 @@ pub fn process_instruction(
     }
 
     let signers = get_signers(&keyed_accounts[first_instruction_account..]);
-    match limited_deserialize(data)? {
+    match instruction {
         StakeInstruction::Initialize(authorized, lockup) => me.initialize(
             &authorized,
             &lockup,
@@ -126,6 +139,7 @@ pub fn process_instruction(
         StakeInstruction::DelegateStake => {
             let can_reverse_deactivation =
                 invoke_context.is_feature_active(&feature_set::stake_program_v4::id());
+            let minimum_delegation = enforced_minimum_delegation(invoke_context);
             let vote = keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
 
             me.delegate(
@@ -144,12 +158,18 @@ pub fn process_instruction(
                 )?)?,
                 &signers,
                 can_reverse_deactivation,
+                minimum_delegation,
             )
         }
         StakeInstruction::Split(lamports) => {
             let split_stake =
                 &keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
-            me.split(lamports, split_stake, &signers)
+            me.split(
+                lamports,
+                split_stake,
+                &signers,
+                enforced_minimum_delegation(invoke_context),
+            )
         }
         StakeInstruction::Merge => {
             let source_stake =
@@ -169,6 +189,7 @@ pub fn process_instruction(
                 )?)?,
                 &signers,
                 can_merge_expired_lockups,
+                enforced_minimum_delegation(invoke_context),
             )
         }
         StakeInstruction::Withdraw(lamports) => {
@@ -187,6 +208,7 @@ pub fn process_instruction(
                 keyed_account_at_index(keyed_accounts, first_instruction_account + 4)?,
                 keyed_account_at_index(keyed_accounts, first_instruction_account + 5).ok(),
                 invoke_context.is_feature_active(&feature_set::stake_program_v4::id()),
+                enforced_minimum_delegation(invoke_context),
             )
         }
         StakeInstruction::Deactivate => me.deactivate(
@@ -318,6 +340,42 @@ pub fn process_instruction(
                 Err(InstructionError::InvalidInstructionData)
             }
         }
+        StakeInstruction::Redelegate => {
+            if invoke_context.is_feature_active(&feature_set::stake_redelegate_instruction::id()) {
+                let uninitialized_stake =
+                    &keyed_account_at_index(keyed_accounts, first_instruction_account + 1)?;
+                let vote = keyed_account_at_index(keyed_accounts, first_instruction_account + 2)?;
+                let config = config::from_keyed_account(keyed_account_at_index(
+                    keyed_accounts,
+                    first_instruction_account + 3,
+                )?)?;
+                me.redelegate(
+                    uninitialized_stake,
+                    vote,
+                    &get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?,
+                    &get_sysvar::<StakeHistory>(invoke_context, &sysvar::stake_history::id())?,
+                    &config,
+                    &get_sysvar::<Rent>(invoke_context, &sysvar::rent::id())?,
+                    &signers,
+                    enforced_minimum_delegation(invoke_context),
+                )
+            } else {
+                Err(InstructionError::InvalidInstructionData)
+            }
+        }
+        // Only reached if the GetMinimumDelegation instruction isn't enabled yet
+        StakeInstruction::GetMinimumDelegation => Err(InstructionError::InvalidInstructionData),
+    }
+}
+
+/// The minimum delegation to enforce when delegating, splitting, merging or withdrawing stake, if
+/// any
+fn enforced_minimum_delegation(invoke_context: &dyn InvokeContext) -> Option<u64> {
+    if invoke_context.is_feature_active(&feature_set::stake_raise_minimum_delegation_to_1_sol::id())
+    {
+        Some(get_minimum_delegation(invoke_context))
+    } else {
+        None
     }
 }
 
@@ -340,7 +398,7 @@ mod tests {
         },
         sysvar::{stake_history::StakeHistory, Sysvar},
     };
-    use std::{cell::RefCell, rc::Rc, str::FromStr};
+    use std::{cell::RefCell, convert::TryInto, rc::Rc, str::FromStr};
 
     fn create_default_account() -> RefCell<AccountSharedData> {
         RefCell::new(AccountSharedData::default())
@@ -1120,4 +1178,40 @@ mod tests {
             Ok(()),
         );
     }
+
+    #[test]
+    fn test_get_minimum_delegation() {
+        let program_account = AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id());
+        let data = serialize(&StakeInstruction::GetMinimumDelegation).unwrap();
+        let program_id = id();
+        let mut invoke_context = MockInvokeContext::new(
+            &program_id,
+            create_keyed_accounts_unified(&[(false, false, &program_id, &program_account)]),
+        );
+        let mut process_get_minimum_delegation =
+            |disabled_features: &[Pubkey]| -> Result<u64, InstructionError> {
+                invoke_context.disabled_features = disabled_features.iter().cloned().collect();
+                super::process_instruction(1, &data, &mut invoke_context)?;
+                let (program_id, return_data) = invoke_context.get_return_data();
+                assert_eq!(program_id, id());
+                Ok(u64::from_le_bytes(return_data.try_into().unwrap()))
+            };
+
+        assert_eq!(
+            process_get_minimum_delegation(&[]),
+            Ok(feature_set::stake_raise_minimum_delegation_to_1_sol::MINIMUM_DELEGATION)
+        );
+        assert_eq!(
+            process_get_minimum_delegation(&[
+                feature_set::stake_raise_minimum_delegation_to_1_sol::id()
+            ]),
+            Ok(1)
+        );
+        assert_eq!(
+            process_get_minimum_delegation(&[
+                feature_set::add_get_minimum_delegation_instruction_to_stake_program::id()
+            ]),
+            Err(InstructionError::NotEnoughAccountKeys)
+        );
+    }
 }
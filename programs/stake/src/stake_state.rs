@@ -22,7 +22,7 @@ use {
         },
         stake_history::{StakeHistory, StakeHistoryEntry},
     },
-    solana_vote_program::vote_state::{VoteState, VoteStateVersions},
+    solana_vote_program::vote_state::{self, VoteState, VoteStateVersions},
     std::{collections::HashSet, convert::TryFrom},
 };
 
@@ -145,6 +145,19 @@ fn new_stake(
     }
 }
 
+/// Ensure `stake_amount` meets the minimum delegation, if one is enforced
+fn validate_delegated_amount(
+    stake_amount: u64,
+    minimum_delegation: Option<u64>,
+) -> Result<(), InstructionError> {
+    match minimum_delegation {
+        Some(minimum_delegation) if stake_amount < minimum_delegation => {
+            Err(StakeError::InsufficientDelegation.into())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// captures a rewards round as lamports to be awarded
 ///  and the total points over which those lamports
 ///  are to be distributed
@@ -297,6 +310,27 @@ fn calculate_stake_rewards(
         return Some((0, 0, credits_observed));
     }
 
+    calculate_points_rewards(
+        points,
+        point_value,
+        vote_state.commission,
+        inflation_point_calc_tracer,
+    )
+    .map(|(staker_rewards, voter_rewards)| (staker_rewards, voter_rewards, credits_observed))
+}
+
+/// for the points a stake earned, calculate what distributions should be made to the staker
+///   and, at `commission`, to the voter
+/// returns a tuple in the case of a payout of:
+///   * staker_rewards to be distributed
+///   * voter_rewards to be distributed
+/// returns None if there's no payout or if any deserved payout is < 1 lamport
+fn calculate_points_rewards(
+    points: u128,
+    point_value: &PointValue,
+    commission: u8,
+    inflation_point_calc_tracer: Option<impl Fn(&InflationPointCalculationEvent)>,
+) -> Option<(u64, u64)> {
     if points == 0 {
         if let Some(inflation_point_calc_tracer) = inflation_point_calc_tracer {
             inflation_point_calc_tracer(&SkippedReason::ZeroPoints.into());
@@ -325,7 +359,8 @@ fn calculate_stake_rewards(
         }
         return None;
     }
-    let (voter_rewards, staker_rewards, is_split) = vote_state.commission_split(rewards);
+    let (voter_rewards, staker_rewards, is_split) =
+        vote_state::commission_split(commission, rewards);
     if let Some(inflation_point_calc_tracer) = inflation_point_calc_tracer {
         inflation_point_calc_tracer(&InflationPointCalculationEvent::SplitRewards(
             rewards,
@@ -342,7 +377,7 @@ fn calculate_stake_rewards(
         return None;
     }
 
-    Some((staker_rewards, voter_rewards, credits_observed))
+    Some((staker_rewards, voter_rewards))
 }
 
 pub trait StakeAccount {
@@ -380,8 +415,21 @@ pub trait StakeAccount {
         config: &Config,
         signers: &HashSet<Pubkey>,
         can_reverse_deactivation: bool,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError>;
     fn deactivate(&self, clock: &Clock, signers: &HashSet<Pubkey>) -> Result<(), InstructionError>;
+    #[allow(clippy::too_many_arguments)]
+    fn redelegate(
+        &self,
+        uninitialized_stake_account: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        rent: &Rent,
+        signers: &HashSet<Pubkey>,
+        minimum_delegation: Option<u64>,
+    ) -> Result<(), InstructionError>;
     fn set_lockup(
         &self,
         lockup: &LockupArgs,
@@ -393,7 +441,9 @@ pub trait StakeAccount {
         lamports: u64,
         split_stake: &KeyedAccount,
         signers: &HashSet<Pubkey>,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError>;
+    #[allow(clippy::too_many_arguments)]
     fn merge(
         &self,
         invoke_context: &dyn InvokeContext,
@@ -402,7 +452,9 @@ pub trait StakeAccount {
         stake_history: &StakeHistory,
         signers: &HashSet<Pubkey>,
         can_merge_expired_lockups: bool,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError>;
+    #[allow(clippy::too_many_arguments)]
     fn withdraw(
         &self,
         lamports: u64,
@@ -412,6 +464,7 @@ pub trait StakeAccount {
         withdraw_authority: &KeyedAccount,
         custodian: Option<&KeyedAccount>,
         prevent_withdraw_to_zero: bool,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError>;
 }
 
@@ -520,6 +573,7 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         config: &Config,
         signers: &HashSet<Pubkey>,
         can_reverse_deactivation: bool,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError> {
         if vote_account.owner()? != solana_vote_program::id() {
             return Err(InstructionError::IncorrectProgramId);
@@ -528,8 +582,11 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         match self.state()? {
             StakeState::Initialized(meta) => {
                 meta.authorized.check(signers, StakeAuthorize::Staker)?;
+                // can't stake the rent ;)
+                let stake_amount = self.lamports()?.saturating_sub(meta.rent_exempt_reserve);
+                validate_delegated_amount(stake_amount, minimum_delegation)?;
                 let stake = new_stake(
-                    self.lamports()?.saturating_sub(meta.rent_exempt_reserve), // can't stake the rent ;)
+                    stake_amount,
                     vote_account.unsigned_key(),
                     &State::<VoteStateVersions>::state(vote_account)?.convert_to_current(),
                     clock.epoch,
@@ -539,9 +596,12 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
             }
             StakeState::Stake(meta, mut stake) => {
                 meta.authorized.check(signers, StakeAuthorize::Staker)?;
+                // can't stake the rent ;)
+                let stake_amount = self.lamports()?.saturating_sub(meta.rent_exempt_reserve);
+                validate_delegated_amount(stake_amount, minimum_delegation)?;
                 redelegate(
                     &mut stake,
-                    self.lamports()?.saturating_sub(meta.rent_exempt_reserve), // can't stake the rent ;)
+                    stake_amount,
                     vote_account.unsigned_key(),
                     &State::<VoteStateVersions>::state(vote_account)?.convert_to_current(),
                     clock,
@@ -564,6 +624,80 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
             Err(InstructionError::InvalidAccountData)
         }
     }
+    fn redelegate(
+        &self,
+        uninitialized_stake_account: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        rent: &Rent,
+        signers: &HashSet<Pubkey>,
+        minimum_delegation: Option<u64>,
+    ) -> Result<(), InstructionError> {
+        if uninitialized_stake_account.owner()? != id() {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        if uninitialized_stake_account.data_len()? != std::mem::size_of::<StakeState>() {
+            return Err(InstructionError::InvalidAccountData);
+        }
+        if !matches!(
+            uninitialized_stake_account.state()?,
+            StakeState::Uninitialized
+        ) {
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+        if vote_account.owner()? != solana_vote_program::id() {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+
+        let (meta, effective_stake) = if let StakeState::Stake(meta, stake) = self.state()? {
+            let status = stake
+                .delegation
+                .stake_activating_and_deactivating(clock.epoch, Some(stake_history));
+            if status.effective == 0 || status.activating != 0 || status.deactivating != 0 {
+                return Err(StakeError::RedelegateTransientOrInactiveStake.into());
+            }
+            // Redelegating to the same vote account would only churn the cluster's warmup and
+            // cooldown allowance
+            if stake.delegation.voter_pubkey == *vote_account.unsigned_key() {
+                return Err(StakeError::RedelegateToSameVoteAccount.into());
+            }
+            (meta, status.effective)
+        } else {
+            return Err(InstructionError::InvalidAccountData);
+        };
+
+        // Also checks that the stake authority signed
+        self.deactivate(clock, signers)?;
+
+        self.try_account_ref_mut()?
+            .checked_sub_lamports(effective_stake)?;
+        uninitialized_stake_account
+            .try_account_ref_mut()?
+            .checked_add_lamports(effective_stake)?;
+
+        let mut redelegated_meta = meta;
+        redelegated_meta.rent_exempt_reserve =
+            rent.minimum_balance(uninitialized_stake_account.data_len()?);
+        let stake_amount = uninitialized_stake_account
+            .lamports()?
+            .saturating_sub(redelegated_meta.rent_exempt_reserve);
+        if stake_amount == 0 {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        validate_delegated_amount(stake_amount, minimum_delegation)?;
+        uninitialized_stake_account.set_state(&StakeState::Stake(
+            redelegated_meta,
+            new_stake(
+                stake_amount,
+                vote_account.unsigned_key(),
+                &State::<VoteStateVersions>::state(vote_account)?.convert_to_current(),
+                clock.epoch,
+                config,
+            ),
+        ))
+    }
     fn set_lockup(
         &self,
         lockup: &LockupArgs,
@@ -588,6 +722,7 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         lamports: u64,
         split: &KeyedAccount,
         signers: &HashSet<Pubkey>,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError> {
         if split.owner()? != id() {
             return Err(InstructionError::IncorrectProgramId);
@@ -648,6 +783,15 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
                             lamports - split_rent_exempt_reserve.saturating_sub(split.lamports()?),
                         )
                     };
+                    // neither the split nor, unless it's emptied, the source may fall below
+                    // the minimum delegation
+                    if lamports != self.lamports()? {
+                        validate_delegated_amount(
+                            stake.delegation.stake.saturating_sub(remaining_stake_delta),
+                            minimum_delegation,
+                        )?;
+                    }
+                    validate_delegated_amount(split_stake_amount, minimum_delegation)?;
                     let split_stake = stake.split(remaining_stake_delta, split_stake_amount)?;
                     let mut split_meta = meta;
                     split_meta.rent_exempt_reserve = split_rent_exempt_reserve;
@@ -708,6 +852,7 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         stake_history: &StakeHistory,
         signers: &HashSet<Pubkey>,
         can_merge_expired_lockups: bool,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError> {
         // Ensure source isn't spoofed
         if source_account.owner()? != id() {
@@ -740,6 +885,9 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         if let Some(merged_state) =
             stake_merge_kind.merge(invoke_context, source_merge_kind, clock)?
         {
+            if let StakeState::Stake(_, stake) = &merged_state {
+                validate_delegated_amount(stake.delegation.stake, minimum_delegation)?;
+            }
             self.set_state(&merged_state)?;
         }
 
@@ -764,6 +912,7 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         withdraw_authority: &KeyedAccount,
         custodian: Option<&KeyedAccount>,
         prevent_withdraw_to_zero: bool,
+        minimum_delegation: Option<u64>,
     ) -> Result<(), InstructionError> {
         let mut signers = HashSet::new();
         let withdraw_authority_pubkey = withdraw_authority
@@ -791,7 +940,10 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
             StakeState::Initialized(meta) => {
                 meta.authorized
                     .check(&signers, StakeAuthorize::Withdrawer)?;
-                let reserve = if prevent_withdraw_to_zero {
+                let reserve = if let Some(minimum_delegation) = minimum_delegation {
+                    // stake accounts must keep enough to delegate the minimum
+                    checked_add(meta.rent_exempt_reserve, minimum_delegation)?
+                } else if prevent_withdraw_to_zero {
                     checked_add(meta.rent_exempt_reserve, 1)? // stake accounts must have a balance > rent_exempt_reserve
                 } else {
                     meta.rent_exempt_reserve
@@ -1239,6 +1391,37 @@ pub fn calculate_points(
     }
 }
 
+// utility function, used by runtime to calculate the rewards of a stake after its points
+// returns a tuple of (points, new value for credits_observed)
+#[doc(hidden)]
+pub fn calculate_points_and_credits(
+    stake_state: &StakeState,
+    vote_state: &VoteState,
+    stake_history: Option<&StakeHistory>,
+) -> Result<(u128, u64), InstructionError> {
+    if let StakeState::Stake(_meta, stake) = stake_state {
+        Ok(calculate_stake_points_and_credits(
+            stake,
+            vote_state,
+            stake_history,
+            null_tracer(),
+        ))
+    } else {
+        Err(InstructionError::InvalidAccountData)
+    }
+}
+
+// utility function, used by runtime
+// returns a tuple of (stakers_reward, voters_reward) for the points a stake earned
+#[doc(hidden)]
+pub fn calculate_rewards_from_points(
+    points: u128,
+    point_value: &PointValue,
+    commission: u8,
+) -> Option<(u64, u64)> {
+    calculate_points_rewards(points, point_value, commission, null_tracer())
+}
+
 // utility function, used by Split
 //This emulates current Rent math in order to preserve backward compatibility. In the future, and
 //to support variable rent, the Split instruction should pass in the Rent sysvar instead.
@@ -1618,6 +1801,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -1633,6 +1817,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             )
             .is_ok());
 
@@ -1662,7 +1847,8 @@ mod tests {
                 &StakeHistory::default(),
                 &Config::default(),
                 &signers,
-                true
+                true,
+                None
             ),
             Err(StakeError::TooSoonToRedelegate.into())
         );
@@ -1680,6 +1866,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Err(StakeError::TooSoonToRedelegate.into())
         );
@@ -1694,6 +1881,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             )
             .unwrap();
 
@@ -1711,6 +1899,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Err(StakeError::TooSoonToRedelegate.into())
         );
@@ -1730,6 +1919,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             )
             .is_ok());
 
@@ -1748,6 +1938,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Err(solana_sdk::instruction::InstructionError::IncorrectProgramId)
         );
@@ -1780,10 +1971,61 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             )
             .is_err());
     }
 
+    #[test]
+    fn test_stake_delegate_minimum_delegation() {
+        let vote_pubkey = solana_sdk::pubkey::new_rand();
+        let vote_account = RefCell::new(vote_state::create_account(
+            &vote_pubkey,
+            &solana_sdk::pubkey::new_rand(),
+            0,
+            100,
+        ));
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &vote_account);
+
+        let stake_pubkey = solana_sdk::pubkey::new_rand();
+        let rent_exempt_reserve = 10;
+        let minimum_delegation = 100;
+        let signers = vec![stake_pubkey].into_iter().collect();
+        let delegate = |stake_amount, minimum_delegation| {
+            let stake_account = AccountSharedData::new_ref_data_with_space(
+                rent_exempt_reserve + stake_amount,
+                &StakeState::Initialized(Meta {
+                    rent_exempt_reserve,
+                    authorized: Authorized::auto(&stake_pubkey),
+                    ..Meta::default()
+                }),
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .unwrap();
+            KeyedAccount::new(&stake_pubkey, true, &stake_account).delegate(
+                &vote_keyed_account,
+                &Clock::default(),
+                &StakeHistory::default(),
+                &Config::default(),
+                &signers,
+                true,
+                minimum_delegation,
+            )
+        };
+
+        assert_eq!(
+            delegate(minimum_delegation - 1, Some(minimum_delegation)),
+            Err(StakeError::InsufficientDelegation.into())
+        );
+        assert_eq!(
+            delegate(minimum_delegation, Some(minimum_delegation)),
+            Ok(())
+        );
+        // the minimum is only checked when enforced
+        assert_eq!(delegate(minimum_delegation - 1, None), Ok(()));
+    }
+
     fn create_stake_history_from_delegations(
         bootstrap: Option<u64>,
         epochs: std::ops::Range<Epoch>,
@@ -2505,6 +2747,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -2527,6 +2770,170 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_redelegate() {
+        let rent = Rent::default();
+        let rent_exempt_reserve = rent.minimum_balance(std::mem::size_of::<StakeState>());
+        let stake_amount = 1_000_000;
+        let stake_pubkey = solana_sdk::pubkey::new_rand();
+        let old_vote_pubkey = solana_sdk::pubkey::new_rand();
+        let meta = Meta {
+            rent_exempt_reserve,
+            ..Meta::auto(&stake_pubkey)
+        };
+        let stake_account = AccountSharedData::new_ref_data_with_space(
+            rent_exempt_reserve + stake_amount + 7,
+            &StakeState::Stake(
+                meta,
+                Stake {
+                    delegation: Delegation::new(
+                        &old_vote_pubkey,
+                        stake_amount,
+                        std::u64::MAX, // bootstrap stake is fully active
+                        Config::default().warmup_cooldown_rate,
+                    ),
+                    credits_observed: 0,
+                },
+            ),
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("stake_account");
+        let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
+        let new_uninitialized_stake_account = || {
+            AccountSharedData::new_ref_data_with_space(
+                rent_exempt_reserve,
+                &StakeState::Uninitialized,
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .expect("uninitialized_stake_account")
+        };
+        let redelegated_stake_pubkey = solana_sdk::pubkey::new_rand();
+        let redelegated_stake_account = new_uninitialized_stake_account();
+        let redelegated_stake_keyed_account =
+            KeyedAccount::new(&redelegated_stake_pubkey, false, &redelegated_stake_account);
+
+        let vote_pubkey = solana_sdk::pubkey::new_rand();
+        let vote_account = RefCell::new(vote_state::create_account(
+            &vote_pubkey,
+            &solana_sdk::pubkey::new_rand(),
+            0,
+            100,
+        ));
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &vote_account);
+        let old_vote_account = RefCell::new(vote_state::create_account(
+            &old_vote_pubkey,
+            &solana_sdk::pubkey::new_rand(),
+            0,
+            100,
+        ));
+        let old_vote_keyed_account = KeyedAccount::new(&old_vote_pubkey, false, &old_vote_account);
+
+        let clock = Clock {
+            epoch: 1,
+            ..Clock::default()
+        };
+        let signers = vec![stake_pubkey].into_iter().collect();
+        let redelegate = |redelegated_stake_keyed_account: &KeyedAccount,
+                          vote_keyed_account: &KeyedAccount,
+                          signers: &HashSet<Pubkey>| {
+            stake_keyed_account.redelegate(
+                redelegated_stake_keyed_account,
+                vote_keyed_account,
+                &clock,
+                &StakeHistory::default(),
+                &Config::default(),
+                &rent,
+                signers,
+                None,
+            )
+        };
+
+        // no signers fails
+        assert_eq!(
+            redelegate(
+                &redelegated_stake_keyed_account,
+                &vote_keyed_account,
+                &HashSet::default()
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+
+        // redelegating to the current vote account fails
+        assert_eq!(
+            redelegate(
+                &redelegated_stake_keyed_account,
+                &old_vote_keyed_account,
+                &signers
+            ),
+            Err(StakeError::RedelegateToSameVoteAccount.into())
+        );
+
+        assert_eq!(
+            redelegate(
+                &redelegated_stake_keyed_account,
+                &vote_keyed_account,
+                &signers
+            ),
+            Ok(())
+        );
+
+        // the effective stake moved to the new account, which activates to the new vote account
+        assert_eq!(
+            stake_keyed_account.lamports().unwrap(),
+            rent_exempt_reserve + 7
+        );
+        assert_eq!(
+            stake_from(&*stake_account.borrow())
+                .unwrap()
+                .delegation
+                .deactivation_epoch,
+            clock.epoch
+        );
+        assert_eq!(
+            redelegated_stake_keyed_account.lamports().unwrap(),
+            rent_exempt_reserve + stake_amount
+        );
+        assert_eq!(
+            from(&*redelegated_stake_account.borrow()).unwrap(),
+            StakeState::Stake(
+                meta,
+                Stake {
+                    delegation: Delegation::new(
+                        &vote_pubkey,
+                        stake_amount,
+                        clock.epoch,
+                        Config::default().warmup_cooldown_rate,
+                    ),
+                    credits_observed: 0,
+                },
+            )
+        );
+
+        // the destination must be uninitialized
+        assert_eq!(
+            redelegate(
+                &redelegated_stake_keyed_account,
+                &vote_keyed_account,
+                &signers
+            ),
+            Err(InstructionError::AccountAlreadyInitialized)
+        );
+
+        // deactivating stake can't be redelegated
+        let other_stake_pubkey = solana_sdk::pubkey::new_rand();
+        let other_stake_account = new_uninitialized_stake_account();
+        assert_eq!(
+            redelegate(
+                &KeyedAccount::new(&other_stake_pubkey, false, &other_stake_account),
+                &vote_keyed_account,
+                &signers
+            ),
+            Err(StakeError::RedelegateTransientOrInactiveStake.into())
+        );
+    }
+
     #[test]
     fn test_set_lockup() {
         let stake_pubkey = solana_sdk::pubkey::new_rand();
@@ -2599,6 +3006,7 @@ mod tests {
                 &Config::default(),
                 &vec![stake_pubkey].into_iter().collect(),
                 true,
+                None,
             )
             .unwrap();
 
@@ -2887,6 +3295,7 @@ mod tests {
                 &to_keyed_account, // unsigned account as withdraw authority
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -2903,6 +3312,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -2939,6 +3349,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -2964,6 +3375,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -2982,6 +3394,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -3000,6 +3413,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -3020,6 +3434,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -3035,6 +3450,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -3092,11 +3508,56 @@ mod tests {
                 &authority_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds),
         );
     }
 
+    #[test]
+    fn test_withdraw_minimum_delegation() {
+        let stake_pubkey = solana_sdk::pubkey::new_rand();
+        let to = solana_sdk::pubkey::new_rand();
+        let rent_exempt_reserve = 10;
+        let stake_lamports = rent_exempt_reserve + 300;
+        let minimum_delegation = 100;
+
+        let withdraw = |lamports, minimum_delegation| {
+            let stake_account = AccountSharedData::new_ref_data_with_space(
+                stake_lamports,
+                &StakeState::Initialized(Meta {
+                    rent_exempt_reserve,
+                    ..Meta::auto(&stake_pubkey)
+                }),
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .unwrap();
+            let to_account = AccountSharedData::new_ref(0, 0, &system_program::id());
+            let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
+            stake_keyed_account.withdraw(
+                lamports,
+                &KeyedAccount::new(&to, false, &to_account),
+                &Clock::default(),
+                &StakeHistory::default(),
+                &stake_keyed_account,
+                None,
+                true,
+                minimum_delegation,
+            )
+        };
+
+        // a partial withdrawal must leave enough to delegate the minimum
+        assert_eq!(
+            withdraw(201, Some(minimum_delegation)),
+            Err(InstructionError::InsufficientFunds)
+        );
+        assert_eq!(withdraw(200, Some(minimum_delegation)), Ok(()));
+        assert_eq!(withdraw(stake_lamports, Some(minimum_delegation)), Ok(()));
+        // the minimum is only checked when enforced
+        assert_eq!(withdraw(201, None), Ok(()));
+    }
+
     #[test]
     fn test_withdraw_stake_before_warmup() {
         let stake_pubkey = solana_sdk::pubkey::new_rand();
@@ -3141,6 +3602,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -3163,6 +3625,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -3193,6 +3656,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InvalidAccountData)
         );
@@ -3236,6 +3700,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(StakeError::LockupInForce.into())
         );
@@ -3252,6 +3717,7 @@ mod tests {
                     &stake_keyed_account,
                     Some(&custodian_keyed_account),
                     true,
+                    None,
                 ),
                 Ok(())
             );
@@ -3274,6 +3740,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -3318,6 +3785,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(StakeError::LockupInForce.into())
         );
@@ -3333,6 +3801,7 @@ mod tests {
                     &stake_keyed_account,
                     Some(&custodian_keyed_account),
                     true,
+                    None,
                 ),
                 Ok(())
             );
@@ -3374,6 +3843,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 false,
+                None,
             ),
             Ok(())
         );
@@ -3391,6 +3861,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -3406,6 +3877,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 false,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -3419,6 +3891,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::InsufficientFunds)
         );
@@ -3434,6 +3907,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -3942,6 +4416,7 @@ mod tests {
                 &stake_keyed_account, // old signer
                 None,
                 true,
+                None,
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -3959,6 +4434,7 @@ mod tests {
                 &stake_keyed_account2,
                 None,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -4179,7 +4655,8 @@ mod tests {
             stake_keyed_account.split(
                 stake_lamports / 2,
                 &split_stake_keyed_account,
-                &HashSet::default() // no signers
+                &HashSet::default(), // no signers
+                None
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -4187,7 +4664,12 @@ mod tests {
         // this should work
         let signers = vec![stake_pubkey].into_iter().collect();
         assert_eq!(
-            stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+            stake_keyed_account.split(
+                stake_lamports / 2,
+                &split_stake_keyed_account,
+                &signers,
+                None
+            ),
             Ok(())
         );
         assert_eq!(
@@ -4222,7 +4704,12 @@ mod tests {
         let split_stake_keyed_account =
             KeyedAccount::new(&split_stake_pubkey, true, &split_stake_account);
         assert_eq!(
-            stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+            stake_keyed_account.split(
+                stake_lamports / 2,
+                &split_stake_keyed_account,
+                &signers,
+                None
+            ),
             Err(InstructionError::InvalidAccountData)
         );
     }
@@ -4265,11 +4752,56 @@ mod tests {
         let split_stake_keyed_account =
             KeyedAccount::new(&split_stake_pubkey, true, &split_stake_account);
         assert_eq!(
-            stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+            stake_keyed_account.split(
+                stake_lamports / 2,
+                &split_stake_keyed_account,
+                &signers,
+                None
+            ),
             Err(StakeError::InsufficientStake.into())
         );
     }
 
+    #[test]
+    fn test_split_minimum_delegation() {
+        let stake_pubkey = solana_sdk::pubkey::new_rand();
+        let split_stake_pubkey = solana_sdk::pubkey::new_rand();
+        let stake_lamports = 300;
+        let minimum_delegation = Some(100);
+        let signers = vec![stake_pubkey].into_iter().collect();
+
+        let split = |lamports| {
+            let stake_account = AccountSharedData::new_ref_data_with_space(
+                stake_lamports,
+                &StakeState::Stake(Meta::auto(&stake_pubkey), just_stake(stake_lamports)),
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .unwrap();
+            let split_stake_account = AccountSharedData::new_ref_data_with_space(
+                0,
+                &StakeState::Uninitialized,
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .unwrap();
+            KeyedAccount::new(&stake_pubkey, true, &stake_account).split(
+                lamports,
+                &KeyedAccount::new(&split_stake_pubkey, true, &split_stake_account),
+                &signers,
+                minimum_delegation,
+            )
+        };
+
+        // the split stake must meet the minimum
+        assert_eq!(split(99), Err(StakeError::InsufficientDelegation.into()));
+        // and so must the remaining stake, unless the source is emptied
+        assert_eq!(split(201), Err(StakeError::InsufficientDelegation.into()));
+        assert_eq!(split(100), Ok(()));
+        assert_eq!(split(200), Ok(()));
+        assert_eq!(split(stake_lamports), Ok(()));
+    }
+
     #[test]
     fn test_split_with_rent() {
         let stake_pubkey = solana_sdk::pubkey::new_rand();
@@ -4315,7 +4847,8 @@ mod tests {
                 stake_keyed_account.split(
                     rent_exempt_reserve,
                     &split_stake_keyed_account,
-                    &signers
+                    &signers,
+                    None
                 ),
                 Err(InstructionError::InsufficientFunds)
             );
@@ -4325,7 +4858,8 @@ mod tests {
                 stake_keyed_account.split(
                     stake_lamports - rent_exempt_reserve,
                     &split_stake_keyed_account,
-                    &signers
+                    &signers,
+                    None
                 ),
                 Err(InstructionError::InsufficientFunds)
             );
@@ -4339,7 +4873,8 @@ mod tests {
                 stake_keyed_account.split(
                     stake_lamports - (rent_exempt_reserve + 1), // leave rent_exempt_reserve + 1 in original account
                     &split_stake_keyed_account,
-                    &signers
+                    &signers,
+                    None
                 ),
                 Ok(())
             );
@@ -4406,13 +4941,23 @@ mod tests {
 
             // split more than available fails
             assert_eq!(
-                stake_keyed_account.split(stake_lamports + 1, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports + 1,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Err(InstructionError::InsufficientFunds)
             );
 
             // should work
             assert_eq!(
-                stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports / 2,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Ok(())
             );
             // no lamport leakage
@@ -4495,7 +5040,12 @@ mod tests {
         let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
 
         assert_eq!(
-            stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+            stake_keyed_account.split(
+                stake_lamports / 2,
+                &split_stake_keyed_account,
+                &signers,
+                None
+            ),
             Err(InstructionError::IncorrectProgramId),
         );
     }
@@ -4544,13 +5094,23 @@ mod tests {
 
             // split more than available fails
             assert_eq!(
-                stake_keyed_account.split(stake_lamports + 1, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports + 1,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Err(InstructionError::InsufficientFunds)
             );
 
             // should work
             assert_eq!(
-                stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports / 2,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Ok(())
             );
             // no lamport leakage
@@ -4656,13 +5216,23 @@ mod tests {
 
             // split more than available fails
             assert_eq!(
-                stake_keyed_account.split(stake_lamports + 1, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports + 1,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Err(InstructionError::InsufficientFunds)
             );
 
             // should work
             assert_eq!(
-                stake_keyed_account.split(stake_lamports / 2, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports / 2,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Ok(())
             );
             // no lamport leakage
@@ -4771,12 +5341,16 @@ mod tests {
 
             // should always return error when splitting to larger account
             let split_result =
-                stake_keyed_account.split(split_amount, &split_stake_keyed_account, &signers);
+                stake_keyed_account.split(split_amount, &split_stake_keyed_account, &signers, None);
             assert_eq!(split_result, Err(InstructionError::InvalidAccountData));
 
             // Splitting 100% of source should not make a difference
-            let split_result =
-                stake_keyed_account.split(stake_lamports, &split_stake_keyed_account, &signers);
+            let split_result = stake_keyed_account.split(
+                stake_lamports,
+                &split_stake_keyed_account,
+                &signers,
+                None,
+            );
             assert_eq!(split_result, Err(InstructionError::InvalidAccountData));
         }
     }
@@ -4824,7 +5398,12 @@ mod tests {
 
             // split 100% over to dest
             assert_eq!(
-                stake_keyed_account.split(stake_lamports, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Ok(())
             );
 
@@ -4911,7 +5490,12 @@ mod tests {
 
             // split 100% over to dest
             assert_eq!(
-                stake_keyed_account.split(stake_lamports, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Ok(())
             );
 
@@ -4982,7 +5566,12 @@ mod tests {
             let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
 
             assert_eq!(
-                stake_keyed_account.split(stake_lamports, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Err(InstructionError::InvalidAccountData)
             );
 
@@ -5008,7 +5597,12 @@ mod tests {
             let stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &stake_account);
 
             assert_eq!(
-                stake_keyed_account.split(stake_lamports, &split_stake_keyed_account, &signers),
+                stake_keyed_account.split(
+                    stake_lamports,
+                    &split_stake_keyed_account,
+                    &signers,
+                    None
+                ),
                 Ok(())
             );
 
@@ -5113,6 +5707,7 @@ mod tests {
                         &StakeHistory::default(),
                         &HashSet::new(),
                         false,
+                        None,
                     ),
                     Err(InstructionError::MissingRequiredSignature)
                 );
@@ -5125,6 +5720,7 @@ mod tests {
                         &StakeHistory::default(),
                         &signers,
                         false,
+                        None,
                     ),
                     Ok(())
                 );
@@ -5177,6 +5773,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_merge_minimum_delegation() {
+        let stake_pubkey = solana_sdk::pubkey::new_rand();
+        let source_stake_pubkey = solana_sdk::pubkey::new_rand();
+        let authorized_pubkey = solana_sdk::pubkey::new_rand();
+        let stake_lamports = 42;
+        let signers = vec![authorized_pubkey].into_iter().collect();
+        let invoke_context = MockInvokeContext::new(&Pubkey::default(), vec![]);
+
+        let merge = |minimum_delegation| {
+            let state =
+                StakeState::Stake(Meta::auto(&authorized_pubkey), just_stake(stake_lamports));
+            let stake_account = AccountSharedData::new_ref_data_with_space(
+                stake_lamports,
+                &state,
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .unwrap();
+            let source_stake_account = AccountSharedData::new_ref_data_with_space(
+                stake_lamports,
+                &state,
+                std::mem::size_of::<StakeState>(),
+                &id(),
+            )
+            .unwrap();
+            KeyedAccount::new(&stake_pubkey, true, &stake_account).merge(
+                &invoke_context,
+                &KeyedAccount::new(&source_stake_pubkey, true, &source_stake_account),
+                &Clock::default(),
+                &StakeHistory::default(),
+                &signers,
+                false,
+                minimum_delegation,
+            )
+        };
+
+        // the merged delegation must meet the minimum
+        assert_eq!(
+            merge(Some(stake_lamports * 2 + 1)),
+            Err(StakeError::InsufficientDelegation.into())
+        );
+        assert_eq!(merge(Some(stake_lamports * 2)), Ok(()));
+    }
+
     #[test]
     fn test_merge_self_fails() {
         let invoke_context = MockInvokeContext::new(&Pubkey::default(), vec![]);
@@ -5217,6 +5858,7 @@ mod tests {
                 &StakeHistory::default(),
                 &signers,
                 false,
+                None,
             ),
             Err(InstructionError::InvalidArgument),
         );
@@ -5272,6 +5914,7 @@ mod tests {
                         &StakeHistory::default(),
                         &wrong_signers,
                         false,
+                        None,
                     ),
                     Err(InstructionError::MissingRequiredSignature)
                 );
@@ -5284,6 +5927,7 @@ mod tests {
                         &StakeHistory::default(),
                         &signers,
                         false,
+                        None,
                     ),
                     Err(StakeError::MergeMismatch.into())
                 );
@@ -5334,6 +5978,7 @@ mod tests {
                         &StakeHistory::default(),
                         &signers,
                         false,
+                        None,
                     ),
                     Err(InstructionError::InvalidAccountData)
                 );
@@ -5378,6 +6023,7 @@ mod tests {
                 &StakeHistory::default(),
                 &signers,
                 false,
+                None,
             ),
             Err(InstructionError::IncorrectProgramId)
         );
@@ -5475,6 +6121,7 @@ mod tests {
                 stake_history,
                 signers,
                 false,
+                None,
             );
             if result.is_ok() {
                 assert_eq!(test_source_keyed.state(), Ok(StakeState::Uninitialized),);
@@ -5749,6 +6396,7 @@ mod tests {
                 &Config::default(),
                 &signers,
                 true,
+                None,
             )
             .unwrap();
 
@@ -5796,6 +6444,7 @@ mod tests {
                 &Config::default(),
                 &other_signers,
                 true,
+                None,
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -5810,6 +6459,7 @@ mod tests {
                 &Config::default(),
                 &new_signers,
                 true,
+                None,
             ),
             Ok(())
         );
@@ -5862,6 +6512,7 @@ mod tests {
                 &config,
                 &signers,
                 true,
+                None,
             )
             .unwrap();
 
@@ -5883,6 +6534,7 @@ mod tests {
                 &stake_keyed_account,
                 None,
                 true,
+                None,
             )
             .unwrap();
         let expected_balance = rent_exempt_reserve + initial_lamports - withdraw_lamports;
@@ -5897,6 +6549,7 @@ mod tests {
                 &config,
                 &signers,
                 true,
+                None,
             )
             .unwrap();
         let stake = stake_from(&stake_account.borrow()).unwrap();
@@ -5924,6 +6577,7 @@ mod tests {
                 &config,
                 &signers,
                 true,
+                None,
             )
             .unwrap();
         let stake = stake_from(&stake_account.borrow()).unwrap();
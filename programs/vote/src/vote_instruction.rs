@@ -11,11 +11,12 @@ use serde_derive::{Deserialize, Serialize};
 use solana_metrics::inc_new_counter_info;
 use solana_sdk::{
     decode_error::DecodeError,
+    epoch_schedule::EpochSchedule,
     feature_set,
     hash::Hash,
     instruction::{AccountMeta, Instruction, InstructionError},
     keyed_account::{from_keyed_account, get_signers, keyed_account_at_index, KeyedAccount},
-    process_instruction::InvokeContext,
+    process_instruction::{get_sysvar, InvokeContext},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     system_instruction,
@@ -44,6 +45,9 @@ pub enum VoteError {
 
     #[error("authorized voter has already been changed this epoch")]
     TooSoonToReauthorize,
+
+    #[error("commission can only be increased in the first half of an epoch")]
+    CommissionUpdateTooLate,
 }
 
 impl<E> DecodeError<E> for VoteError {
@@ -98,6 +102,10 @@ pub enum VoteInstruction {
 
     /// Update the commission for the vote account
     ///
+    /// Once the `commission_increases_only_allowed_in_first_half_of_epoch` feature is active,
+    /// increases are rejected past the midpoint of an epoch so that delegators have at least half
+    /// an epoch to react before the new commission applies to the epoch's rewards
+    ///
     /// # Account references
     ///   0. `[WRITE]` Vote account to be updated
     ///   1. `[SIGNER]` Withdraw authority
@@ -355,7 +363,24 @@ pub fn process_instruction(
             &signers,
         ),
         VoteInstruction::UpdateCommission(commission) => {
-            vote_state::update_commission(me, commission, &signers)
+            let epoch_schedule_and_clock = if invoke_context.is_feature_active(
+                &feature_set::commission_increases_only_allowed_in_first_half_of_epoch::id(),
+            ) {
+                Some((
+                    get_sysvar::<EpochSchedule>(invoke_context, &sysvar::epoch_schedule::id())?,
+                    get_sysvar::<Clock>(invoke_context, &sysvar::clock::id())?,
+                ))
+            } else {
+                None
+            };
+            vote_state::update_commission(
+                me,
+                commission,
+                &signers,
+                epoch_schedule_and_clock
+                    .as_ref()
+                    .map(|(epoch_schedule, clock)| (epoch_schedule, clock)),
+            )
         }
         VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => {
             inc_new_counter_info!("vote-native", 1);
@@ -412,6 +437,7 @@ mod tests {
         rent::Rent,
     };
     use std::cell::RefCell;
+    use std::rc::Rc;
     use std::str::FromStr;
 
     fn create_default_account() -> RefCell<AccountSharedData> {
@@ -426,11 +452,19 @@ mod tests {
         let processor_account = AccountSharedData::new_ref(0, 0, &solana_sdk::native_loader::id());
         let mut keyed_accounts = keyed_accounts.to_vec();
         keyed_accounts.insert(0, (false, false, owner, &processor_account));
-        super::process_instruction(
-            1,
-            instruction_data,
-            &mut MockInvokeContext::new(owner, create_keyed_accounts_unified(&keyed_accounts)),
-        )
+        let mut invoke_context =
+            MockInvokeContext::new(owner, create_keyed_accounts_unified(&keyed_accounts));
+        invoke_context.sysvars = RefCell::new(vec![
+            (
+                sysvar::clock::id(),
+                Some(Rc::new(serialize(&Clock::default()).unwrap())),
+            ),
+            (
+                sysvar::epoch_schedule::id(),
+                Some(Rc::new(serialize(&EpochSchedule::default()).unwrap())),
+            ),
+        ]);
+        super::process_instruction(1, instruction_data, &mut invoke_context)
     }
 
     #[allow(clippy::same_item_push)]
@@ -9,7 +9,7 @@ use solana_sdk::{
     account::{AccountSharedData, ReadableAccount, WritableAccount},
     account_utils::State,
     clock::{Epoch, Slot, UnixTimestamp},
-    epoch_schedule::MAX_LEADER_SCHEDULE_EPOCH_OFFSET,
+    epoch_schedule::{EpochSchedule, MAX_LEADER_SCHEDULE_EPOCH_OFFSET},
     hash::Hash,
     instruction::InstructionError,
     keyed_account::KeyedAccount,
@@ -263,22 +263,7 @@ impl VoteState {
     ///  if commission calculation is 100% one way or other,
     ///   indicate with false for was_split
     pub fn commission_split(&self, on: u64) -> (u64, u64, bool) {
-        match self.commission.min(100) {
-            0 => (0, on, false),
-            100 => (on, 0, false),
-            split => {
-                let on = u128::from(on);
-                // Calculate mine and theirs independently and symmetrically instead of
-                // using the remainder of the other to treat them strictly equally.
-                // This is also to cancel the rewarding if either of the parties
-                // should receive only fractional lamports, resulting in not being rewarded at all.
-                // Thus, note that we intentionally discard any residual fractional lamports.
-                let mine = on * u128::from(split) / 100u128;
-                let theirs = on * u128::from(100 - split) / 100u128;
-
-                (mine as u64, theirs as u64, true)
-            }
-        }
+        commission_split(self.commission, on)
     }
 
     fn get_max_sized_vote_state() -> VoteState {
@@ -593,6 +578,29 @@ impl VoteState {
     }
 }
 
+/// returns the split of `on` at `commission` as (voter_portion, staker_portion, was_split) tuple
+///
+///  if commission calculation is 100% one way or other,
+///   indicate with false for was_split
+pub fn commission_split(commission: u8, on: u64) -> (u64, u64, bool) {
+    match commission.min(100) {
+        0 => (0, on, false),
+        100 => (on, 0, false),
+        split => {
+            let on = u128::from(on);
+            // Calculate mine and theirs independently and symmetrically instead of
+            // using the remainder of the other to treat them strictly equally.
+            // This is also to cancel the rewarding if either of the parties
+            // should receive only fractional lamports, resulting in not being rewarded at all.
+            // Thus, note that we intentionally discard any residual fractional lamports.
+            let mine = on * u128::from(split) / 100u128;
+            let theirs = on * u128::from(100 - split) / 100u128;
+
+            (mine as u64, theirs as u64, true)
+        }
+    }
+}
+
 /// Authorize the given pubkey to withdraw or sign votes. This may be called multiple times,
 /// but will implicitly withdraw authorization from the previously authorized
 /// key
@@ -645,11 +653,13 @@ pub fn update_validator_identity<S: std::hash::BuildHasher>(
     vote_account.set_state(&VoteStateVersions::new_current(vote_state))
 }
 
-/// Update the vote account's commission
+/// Update the vote account's commission.  If `epoch_schedule_and_clock` is provided, increases
+/// are only allowed in the first half of the current epoch
 pub fn update_commission<S: std::hash::BuildHasher>(
     vote_account: &KeyedAccount,
     commission: u8,
     signers: &HashSet<Pubkey, S>,
+    epoch_schedule_and_clock: Option<(&EpochSchedule, &Clock)>,
 ) -> Result<(), InstructionError> {
     let mut vote_state: VoteState =
         State::<VoteStateVersions>::state(vote_account)?.convert_to_current();
@@ -657,11 +667,29 @@ pub fn update_commission<S: std::hash::BuildHasher>(
     // current authorized withdrawer must say "yay"
     verify_authorized_signer(&vote_state.authorized_withdrawer, signers)?;
 
+    if commission > vote_state.commission {
+        if let Some((epoch_schedule, clock)) = epoch_schedule_and_clock {
+            if !is_commission_increase_allowed(clock.slot, epoch_schedule) {
+                return Err(VoteError::CommissionUpdateTooLate.into());
+            }
+        }
+    }
+
     vote_state.commission = commission;
 
     vote_account.set_state(&VoteStateVersions::new_current(vote_state))
 }
 
+/// Whether a commission increase at `slot` falls in the first half of its epoch.  Increases are
+/// always allowed during the warmup epochs
+pub fn is_commission_increase_allowed(slot: Slot, epoch_schedule: &EpochSchedule) -> bool {
+    if slot < epoch_schedule.first_normal_slot || epoch_schedule.slots_per_epoch == 0 {
+        return true;
+    }
+    let slot_index = (slot - epoch_schedule.first_normal_slot) % epoch_schedule.slots_per_epoch;
+    slot_index.saturating_mul(2) <= epoch_schedule.slots_per_epoch
+}
+
 fn verify_authorized_signer<S: std::hash::BuildHasher>(
     authorized: &Pubkey,
     signers: &HashSet<Pubkey, S>,
@@ -1106,7 +1134,7 @@ mod tests {
             ),
         ];
         let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
-        let res = update_commission(&keyed_accounts[0], 42, &signers);
+        let res = update_commission(&keyed_accounts[0], 42, &signers, None);
         assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
 
         let keyed_accounts = &[
@@ -1114,7 +1142,7 @@ mod tests {
             KeyedAccount::new(&authorized_withdrawer, true, &authorized_withdrawer_account),
         ];
         let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
-        let res = update_commission(&keyed_accounts[0], 42, &signers);
+        let res = update_commission(&keyed_accounts[0], 42, &signers, None);
         assert_eq!(res, Ok(()));
         let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
             .unwrap()
@@ -1126,7 +1154,7 @@ mod tests {
             KeyedAccount::new(&authorized_withdrawer, true, &authorized_withdrawer_account),
         ];
         let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
-        let res = update_commission(&keyed_accounts[0], u8::MAX, &signers);
+        let res = update_commission(&keyed_accounts[0], u8::MAX, &signers, None);
         assert_eq!(res, Ok(()));
         let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
             .unwrap()
@@ -1134,6 +1162,100 @@ mod tests {
         assert_eq!(vote_state.commission, u8::MAX);
     }
 
+    #[test]
+    fn test_vote_update_commission_late_in_epoch() {
+        let (vote_pubkey, _authorized_voter, authorized_withdrawer, vote_account) =
+            create_test_account_with_authorized();
+        let authorized_withdrawer_account = RefCell::new(AccountSharedData::default());
+        let keyed_accounts = &[
+            KeyedAccount::new(&vote_pubkey, true, &vote_account),
+            KeyedAccount::new(&authorized_withdrawer, true, &authorized_withdrawer_account),
+        ];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let commission = |vote_account: &RefCell<AccountSharedData>| {
+            StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+                .unwrap()
+                .convert_to_current()
+                .commission
+        };
+
+        let late_clock = Clock {
+            slot: epoch_schedule.slots_per_epoch * 3 - 1,
+            ..Clock::default()
+        };
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                42,
+                &signers,
+                Some((&epoch_schedule, &late_clock))
+            ),
+            Err(VoteError::CommissionUpdateTooLate.into())
+        );
+        assert_eq!(commission(&vote_account), 0);
+
+        let early_clock = Clock {
+            slot: epoch_schedule.slots_per_epoch * 3,
+            ..Clock::default()
+        };
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                42,
+                &signers,
+                Some((&epoch_schedule, &early_clock))
+            ),
+            Ok(())
+        );
+        assert_eq!(commission(&vote_account), 42);
+
+        // decreases are allowed at any point in the epoch
+        assert_eq!(
+            update_commission(
+                &keyed_accounts[0],
+                10,
+                &signers,
+                Some((&epoch_schedule, &late_clock))
+            ),
+            Ok(())
+        );
+        assert_eq!(commission(&vote_account), 10);
+    }
+
+    #[test]
+    fn test_is_commission_increase_allowed() {
+        let epoch_schedule = EpochSchedule::custom(64, 64, true);
+        // warmup epochs
+        assert!(is_commission_increase_allowed(0, &epoch_schedule));
+        assert!(is_commission_increase_allowed(
+            epoch_schedule.first_normal_slot - 1,
+            &epoch_schedule
+        ));
+
+        let first_normal_slot = epoch_schedule.first_normal_slot;
+        assert!(is_commission_increase_allowed(
+            first_normal_slot,
+            &epoch_schedule
+        ));
+        assert!(is_commission_increase_allowed(
+            first_normal_slot + 32,
+            &epoch_schedule
+        ));
+        assert!(!is_commission_increase_allowed(
+            first_normal_slot + 33,
+            &epoch_schedule
+        ));
+        assert!(!is_commission_increase_allowed(
+            first_normal_slot + 63,
+            &epoch_schedule
+        ));
+        assert!(is_commission_increase_allowed(
+            first_normal_slot + 64,
+            &epoch_schedule
+        ));
+    }
+
     #[test]
     fn test_vote_signature() {
         let (vote_pubkey, vote_account) = create_test_account();
@@ -0,0 +1,162 @@
+#![forbid(unsafe_code)]
+
+use {
+    solana_sdk::{ic_msg, instruction::InstructionError, process_instruction::InvokeContext},
+    solana_zk_token_sdk::zk_token_proof_instruction::*,
+};
+
+/// Compute units consumed by verifying a `RangeProof64Data`.
+pub const VERIFY_RANGE_PROOF_64_COMPUTE_UNITS: u64 = 105_000;
+
+/// Compute units consumed by verifying a `CtxtCommEqualityProofData`.
+pub const VERIFY_CTXT_COMM_EQUALITY_COMPUTE_UNITS: u64 = 10_000;
+
+fn verify<T: ZkProofData>(
+    input: &[u8],
+    invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    let proof = ProofInstruction::decode_data::<T>(input).ok_or_else(|| {
+        ic_msg!(invoke_context, "invalid proof data");
+        InstructionError::InvalidInstructionData
+    })?;
+
+    proof.verify().map_err(|err| {
+        ic_msg!(invoke_context, "proof verification failed: {:?}", err);
+        InstructionError::InvalidInstructionData
+    })
+}
+
+pub fn process_instruction(
+    _first_instruction_account: usize,
+    input: &[u8],
+    invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    if invoke_context.invoke_depth() != 1 {
+        // Not supported as an inner instruction
+        return Err(InstructionError::UnsupportedProgramId);
+    }
+
+    match ProofInstruction::decode_type(input).ok_or(InstructionError::InvalidInstructionData)? {
+        ProofInstruction::VerifyRangeProof64 => {
+            invoke_context
+                .get_compute_meter()
+                .borrow_mut()
+                .consume(VERIFY_RANGE_PROOF_64_COMPUTE_UNITS)?;
+            ic_msg!(invoke_context, "VerifyRangeProof64");
+            verify::<RangeProof64Data>(input, invoke_context)
+        }
+        ProofInstruction::VerifyCtxtCommEquality => {
+            invoke_context
+                .get_compute_meter()
+                .borrow_mut()
+                .consume(VERIFY_CTXT_COMM_EQUALITY_COMPUTE_UNITS)?;
+            ic_msg!(invoke_context, "VerifyCtxtCommEquality");
+            verify::<CtxtCommEqualityProofData>(input, invoke_context)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::process_instruction::{
+            InvokeContextStackFrame, MockComputeMeter, MockInvokeContext,
+        },
+        solana_zk_token_sdk::{
+            encryption::{
+                elgamal::{ElGamal, ElGamalKeypair},
+                pedersen::Pedersen,
+            },
+            zk_token_proof_program,
+        },
+        std::{cell::RefCell, rc::Rc},
+    };
+
+    fn range_proof_instruction_data(amount: u64) -> Vec<u8> {
+        let (commitment, opening) = Pedersen::new(amount);
+        verify_range_proof64(&RangeProof64Data::new(commitment, amount, &opening)).data
+    }
+
+    #[test]
+    fn test_verify_range_proof64() {
+        let data = range_proof_instruction_data(55);
+        let mut invoke_context = MockInvokeContext::new(&zk_token_proof_program::id(), vec![]);
+        assert_eq!(process_instruction(0, &data, &mut invoke_context), Ok(()));
+
+        // corrupt the proof scalars
+        let mut data = data;
+        let len = data.len();
+        data[len - 40] ^= 1;
+        assert_eq!(
+            process_instruction(0, &data, &mut invoke_context),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_verify_ctxt_comm_equality() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = ElGamal::encrypt(&keypair.public, 77_u64);
+        let (commitment, opening) = Pedersen::new(77_u64);
+        let data = verify_ctxt_comm_equality(&CtxtCommEqualityProofData::new(
+            &keypair, ciphertext, 77, commitment, &opening,
+        ))
+        .data;
+        let mut invoke_context = MockInvokeContext::new(&zk_token_proof_program::id(), vec![]);
+        assert_eq!(process_instruction(0, &data, &mut invoke_context), Ok(()));
+
+        let (other_commitment, other_opening) = Pedersen::new(78_u64);
+        let data = verify_ctxt_comm_equality(&CtxtCommEqualityProofData::new(
+            &keypair,
+            ciphertext,
+            78,
+            other_commitment,
+            &other_opening,
+        ))
+        .data;
+        assert_eq!(
+            process_instruction(0, &data, &mut invoke_context),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_invalid_instruction() {
+        let mut invoke_context = MockInvokeContext::new(&zk_token_proof_program::id(), vec![]);
+        assert_eq!(
+            process_instruction(0, &[], &mut invoke_context),
+            Err(InstructionError::InvalidInstructionData)
+        );
+        assert_eq!(
+            process_instruction(0, &[0, 1, 2, 3], &mut invoke_context),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_inner_instruction_unsupported() {
+        let data = range_proof_instruction_data(55);
+        let mut invoke_context = MockInvokeContext::new(&zk_token_proof_program::id(), vec![]);
+        invoke_context
+            .invoke_stack
+            .push(InvokeContextStackFrame::new(0, vec![]));
+        assert_eq!(
+            process_instruction(0, &data, &mut invoke_context),
+            Err(InstructionError::UnsupportedProgramId)
+        );
+    }
+
+    #[test]
+    fn test_compute_budget_exceeded() {
+        let data = range_proof_instruction_data(55);
+        let mut invoke_context = MockInvokeContext::new(&zk_token_proof_program::id(), vec![]);
+        invoke_context.compute_meter = Rc::new(RefCell::new(MockComputeMeter {
+            remaining: VERIFY_RANGE_PROOF_64_COMPUTE_UNITS - 1,
+        }));
+        assert_eq!(
+            process_instruction(0, &data, &mut invoke_context),
+            Err(InstructionError::ComputationalBudgetExceeded)
+        );
+    }
+}
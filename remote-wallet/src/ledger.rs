@@ -9,7 +9,9 @@ use {
     log::*,
     num_traits::FromPrimitive,
     semver::Version as FirmwareVersion,
-    solana_sdk::{derivation_path::DerivationPath, pubkey::Pubkey, signature::Signature},
+    solana_sdk::{
+        derivation_path::DerivationPath, offchain_message, pubkey::Pubkey, signature::Signature,
+    },
     std::{cmp::min, convert::TryFrom, fmt, sync::Arc},
 };
 
@@ -58,6 +60,7 @@ mod commands {
     pub const GET_APP_CONFIGURATION: u8 = 0x04;
     pub const GET_PUBKEY: u8 = 0x05;
     pub const SIGN_MESSAGE: u8 = 0x06;
+    pub const SIGN_OFFCHAIN_MESSAGE: u8 = 0x07;
 }
 
 enum ConfigurationVersion {
@@ -336,6 +339,49 @@ impl LedgerWallet {
         self.version < DEPRECATE_VERSION_BEFORE
     }
 
+    fn sign_offchain_message(
+        &self,
+        derivation_path: &DerivationPath,
+        message: &[u8],
+    ) -> Result<Signature, RemoteWalletError> {
+        if self.outdated_app() {
+            return Err(RemoteWalletError::InvalidInput(
+                "Signing off-chain messages requires a newer Solana Ledger app".to_string(),
+            ));
+        }
+        if message.len()
+            > offchain_message::SIGNING_DOMAIN.len()
+                + 1
+                + offchain_message::v0::OffchainMessage::HEADER_LEN
+                + offchain_message::v0::OffchainMessage::MAX_LEN_LEDGER
+        {
+            return Err(RemoteWalletError::InvalidInput(
+                "Off-chain message to sign is too long".to_string(),
+            ));
+        }
+
+        let mut data = extend_and_serialize_multiple(&[derivation_path]);
+        data.extend_from_slice(message);
+
+        let p1 = P1_CONFIRM;
+        let mut p2 = 0;
+        let mut payload = data.as_slice();
+        while payload.len() > MAX_CHUNK_SIZE {
+            let (chunk, remaining) = payload.split_at(MAX_CHUNK_SIZE);
+            self.send_apdu(commands::SIGN_OFFCHAIN_MESSAGE, p1, p2 | P2_MORE, chunk)?;
+            payload = remaining;
+            p2 |= P2_EXTEND;
+        }
+        let result = self.send_apdu(commands::SIGN_OFFCHAIN_MESSAGE, p1, p2, payload)?;
+
+        if result.len() != 64 {
+            return Err(RemoteWalletError::Protocol(
+                "Signature packet size mismatch",
+            ));
+        }
+        Ok(Signature::new(&result))
+    }
+
     fn parse_status(status: usize) -> Result<(), RemoteWalletError> {
         if status == APDU_SUCCESS_CODE {
             Ok(())
@@ -416,6 +462,12 @@ impl RemoteWallet for LedgerWallet {
         derivation_path: &DerivationPath,
         data: &[u8],
     ) -> Result<Signature, RemoteWalletError> {
+        // Off-chain messages begin with the 0xff of their signing domain, which can never begin a
+        // transaction message, and are signed with a separate command
+        if data.starts_with(offchain_message::SIGNING_DOMAIN) {
+            return self.sign_offchain_message(derivation_path, data);
+        }
+
         let mut payload = if self.outdated_app() {
             extend_and_serialize(derivation_path)
         } else {
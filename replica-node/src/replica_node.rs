@@ -135,6 +135,8 @@ fn initialize_from_snapshot(
         process_options.verify_index,
         process_options.accounts_db_config,
         None,
+        &process_options.snapshot_verification,
+        None,
     )
     .unwrap();
 
@@ -11,7 +11,10 @@ use {
         account::{AccountSharedData, ReadableAccount},
         pubkey::Pubkey,
     },
-    spl_token_v2_0::{solana_program::program_pack::Pack, state::Mint},
+    spl_token_v2_0::{
+        solana_program::program_pack::Pack,
+        state::{Account as TokenAccount, Mint},
+    },
     std::{collections::HashMap, sync::Arc},
 };
 
@@ -85,6 +88,16 @@ pub fn get_mint_owner_and_decimals(bank: &Arc<Bank>, mint: &Pubkey) -> Result<(P
     }
 }
 
+/// The amount held by `account` if it is an initialized token account of `mint`
+pub fn get_token_account_amount(account: &AccountSharedData, mint: &Pubkey) -> Option<u64> {
+    if account.owner() != &spl_token_id_v2_0() || get_token_account_mint(account.data())? != *mint {
+        return None;
+    }
+    TokenAccount::unpack(account.data())
+        .ok()
+        .map(|token_account| token_account.amount)
+}
+
 fn get_mint_decimals(data: &[u8]) -> Result<u8> {
     Mint::unpack(data)
         .map_err(|_| {
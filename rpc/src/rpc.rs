@@ -23,7 +23,8 @@ use {
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
             MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
-            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
+            MAX_GET_SIGNATURES_FOR_ADDRESS_MEMO_SCAN, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
+            MAX_GET_SLOT_LEADERS, MAX_GET_TOKEN_LARGEST_ACCOUNTS_LIMIT, MAX_MULTIPLE_ACCOUNTS,
             NUM_LARGEST_ACCOUNTS,
         },
         rpc_response::Response as RpcResponse,
@@ -32,35 +33,45 @@ use {
     solana_faucet::faucet::request_airdrop_transaction,
     solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
     solana_ledger::{
-        blockstore::Blockstore, blockstore_db::BlockstoreError, get_tmp_ledger_path,
+        blockstore::{Blockstore, ConfirmedSignaturesForAddressPage, MemoFilter},
+        blockstore_db::BlockstoreError,
+        blockstore_meta::EquivocationEvidence,
+        get_tmp_ledger_path,
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_metrics::inc_new_counter_info,
     solana_perf::packet::PACKET_DATA_SIZE,
     solana_runtime::{
-        accounts::AccountAddressFilter,
-        accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey},
+        accounts::{AccountAddressFilter, AccountScanMetadata},
+        accounts_db::LoadedAccount,
+        accounts_index::{
+            pubkey_range_from_prefix, AccountIndex, AccountSecondaryIndexes, IndexKey,
+        },
         bank::{Bank, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
         inline_spl_token_v2_0::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
-        non_circulating_supply::calculate_non_circulating_supply,
+        log_collector::log_messages,
+        non_circulating_supply::{calculate_non_circulating_supply, NonCirculatingSupply},
+        partitioned_rewards::get_reward_distribution_num_blocks,
         snapshot_config::SnapshotConfig,
         snapshot_utils,
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         account_utils::StateMut,
-        clock::{Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
+        clock::{Epoch, Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
         commitment_config::{CommitmentConfig, CommitmentLevel},
         epoch_info::EpochInfo,
         epoch_schedule::EpochSchedule,
         exit::Exit,
-        feature_set,
+        feature::{self, Feature},
+        feature_set::{self, FEATURE_NAMES},
         fee_calculator::FeeCalculator,
         hash::Hash,
-        message::{Message, SanitizedMessage},
-        pubkey::Pubkey,
+        message::{LoadedMessage, SanitizeMessageError, SanitizedMessage, VersionedMessage},
+        pubkey::{Pubkey, PUBKEY_BYTES},
+        sanitize::Sanitize,
         signature::{Keypair, Signature, Signer},
         stake::state::{StakeActivationStatus, StakeState},
         stake_history::StakeHistory,
@@ -84,8 +95,8 @@ use {
     },
     std::{
         any::type_name,
-        cmp::{max, min},
-        collections::{HashMap, HashSet},
+        cmp::{max, min, Reverse},
+        collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
         convert::TryFrom,
         net::SocketAddr,
         str::FromStr,
@@ -102,11 +113,107 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 
 pub const MAX_REQUEST_PAYLOAD_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+pub const EQUIVOCATION_EVIDENCE_LIMIT: usize = 1_000;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
 const MAX_RPC_EPOCH_CREDITS_HISTORY: usize = 5;
 
+// Number of epochs of rewards kept in memory to serve `get_inflation_reward`
+const MAX_CACHED_EPOCH_REWARDS: usize = 4;
+// Number of rewards kept in memory across those epochs, about 200 bytes each
+const MAX_CACHED_EPOCH_REWARDS_ENTRIES: usize = 1_000_000;
+
+// A cached non-circulating supply is recomputed once a bank this many slots away from the one it
+// was computed for is requested, since lockups expire and stake accounts are created mid-epoch
+const NON_CIRCULATING_SUPPLY_REFRESH_SLOTS: Slot = 1_000;
+
+// Number of blocks requested from long-term storage per query when serving a range of blocks
+const LONG_TERM_STORAGE_BLOCKS_PAGE_SIZE: usize = 1_000;
+// Most queries made to long-term storage to serve a range of blocks, the rest of the range is
+// left for the client to request next
+const MAX_LONG_TERM_STORAGE_BLOCKS_QUERIES: usize = 10;
+
+/// The staking and voting rewards of an epoch keyed by address, along with the slot of the
+/// block each reward was credited in
+type EpochRewards = HashMap<String, (Slot, Reward)>;
+
+struct EpochRewardsCache {
+    epochs: BTreeMap<Epoch, Arc<EpochRewards>>,
+    num_entries: usize,
+    max_entries: usize,
+}
+
+impl Default for EpochRewardsCache {
+    fn default() -> Self {
+        Self::new(MAX_CACHED_EPOCH_REWARDS_ENTRIES)
+    }
+}
+
+impl EpochRewardsCache {
+    fn new(max_entries: usize) -> Self {
+        Self {
+            epochs: BTreeMap::new(),
+            num_entries: 0,
+            max_entries,
+        }
+    }
+
+    fn get(&self, epoch: Epoch) -> Option<Arc<EpochRewards>> {
+        self.epochs.get(&epoch).cloned()
+    }
+
+    // Evicts the oldest epochs once full, recent epochs are the most requested.  An epoch with
+    // more rewards than `max_entries` isn't cached at all
+    fn insert(&mut self, epoch: Epoch, epoch_rewards: Arc<EpochRewards>) {
+        if epoch_rewards.len() > self.max_entries {
+            return;
+        }
+        self.num_entries += epoch_rewards.len();
+        if let Some(replaced) = self.epochs.insert(epoch, epoch_rewards) {
+            self.num_entries -= replaced.len();
+        }
+        while self.epochs.len() > MAX_CACHED_EPOCH_REWARDS || self.num_entries > self.max_entries {
+            let oldest_epoch = *self.epochs.keys().next().unwrap();
+            let oldest = self.epochs.remove(&oldest_epoch).unwrap();
+            self.num_entries -= oldest.len();
+        }
+    }
+}
+
+/// The non-circulating supply last computed, along with the epoch and slot of the bank it was
+/// computed for.  It is recomputed on demand, once a bank of another epoch or too many slots away
+/// is requested.  A supply computed for an older slot than the cached one is dropped, since
+/// concurrent requests may finish computing out of order
+#[derive(Default)]
+struct NonCirculatingSupplyCache {
+    entry: Option<(Epoch, Slot, Arc<NonCirculatingSupply>)>,
+}
+
+impl NonCirculatingSupplyCache {
+    fn get(&self, epoch: Epoch, slot: Slot) -> Option<Arc<NonCirculatingSupply>> {
+        let (cached_epoch, cached_slot, non_circulating_supply) = self.entry.as_ref()?;
+        let is_fresh = *cached_epoch == epoch
+            && slot.max(*cached_slot) - slot.min(*cached_slot)
+                < NON_CIRCULATING_SUPPLY_REFRESH_SLOTS;
+        is_fresh.then(|| non_circulating_supply.clone())
+    }
+
+    fn insert(
+        &mut self,
+        epoch: Epoch,
+        slot: Slot,
+        non_circulating_supply: Arc<NonCirculatingSupply>,
+    ) {
+        if let Some((_, cached_slot, _)) = self.entry {
+            if cached_slot > slot {
+                return;
+            }
+        }
+        self.entry = Some((epoch, slot, non_circulating_supply));
+    }
+}
+
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     let context = RpcResponseContext { slot: bank.slot() };
     Response { context, value }
@@ -168,6 +275,8 @@ pub struct JsonRpcRequestProcessor {
     max_slots: Arc<MaxSlots>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
+    epoch_rewards_cache: Arc<RwLock<EpochRewardsCache>>,
+    non_circulating_supply_cache: Arc<Mutex<NonCirculatingSupplyCache>>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -277,6 +386,8 @@ impl JsonRpcRequestProcessor {
                 max_slots,
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
+                epoch_rewards_cache: Arc::<RwLock<EpochRewardsCache>>::default(),
+                non_circulating_supply_cache: Arc::<Mutex<NonCirculatingSupplyCache>>::default(),
             },
             receiver,
         )
@@ -330,6 +441,8 @@ impl JsonRpcRequestProcessor {
             max_slots: Arc::new(MaxSlots::default()),
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
+            epoch_rewards_cache: Arc::<RwLock<EpochRewardsCache>>::default(),
+            non_circulating_supply_cache: Arc::<Mutex<NonCirculatingSupplyCache>>::default(),
         }
     }
 
@@ -343,7 +456,13 @@ impl JsonRpcRequestProcessor {
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
-        let response = get_encoded_account(&bank, pubkey, encoding, config.data_slice)?;
+        let response = get_encoded_account(
+            &bank,
+            pubkey,
+            encoding,
+            config.data_slice,
+            config.with_last_modified_slot.unwrap_or_default(),
+        )?;
         Ok(new_response(&bank, response))
     }
 
@@ -357,9 +476,18 @@ impl JsonRpcRequestProcessor {
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
         check_slice_and_encoding(&encoding, config.data_slice.is_some())?;
 
+        let with_last_modified_slot = config.with_last_modified_slot.unwrap_or_default();
         let accounts = pubkeys
             .into_iter()
-            .map(|pubkey| get_encoded_account(&bank, &pubkey, encoding, config.data_slice))
+            .map(|pubkey| {
+                get_encoded_account(
+                    &bank,
+                    &pubkey,
+                    encoding,
+                    config.data_slice,
+                    with_last_modified_slot,
+                )
+            })
             .collect::<Result<Vec<_>>>()?;
         Ok(new_response(&bank, accounts))
     }
@@ -385,6 +513,7 @@ impl JsonRpcRequestProcessor {
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        check_last_modified_slot_unsupported(config.with_last_modified_slot)?;
         let keyed_accounts = {
             if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
                 self.get_filtered_spl_token_accounts_by_owner(&bank, &owner, filters)?
@@ -394,25 +523,78 @@ impl JsonRpcRequestProcessor {
                 self.get_filtered_program_accounts(&bank, program_id, filters)?
             }
         };
-        let result = if program_id == &spl_token_id_v2_0()
-            && encoding == UiAccountEncoding::JsonParsed
-        {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
-        };
-        Ok(result).map(|result| match with_context {
-            true => OptionalContext::Context(new_response(&bank, result)),
-            false => OptionalContext::NoContext(result),
-        })
+        encode_program_accounts(
+            &bank,
+            program_id,
+            keyed_accounts,
+            encoding,
+            data_slice_config,
+            with_context,
+        )
+    }
+
+    /// Like `get_program_accounts`, but only returns the accounts whose addresses start with
+    /// `prefix`, in address order
+    pub fn get_program_accounts_by_prefix(
+        &self,
+        program_id: &Pubkey,
+        prefix: &[u8],
+        config: Option<RpcAccountInfoConfig>,
+        filters: Vec<RpcFilterType>,
+        with_context: bool,
+    ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+        let config = config.unwrap_or_default();
+        let bank = self.bank(config.commitment);
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
+        let data_slice_config = config.data_slice;
+        check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        check_last_modified_slot_unsupported(config.with_last_modified_slot)?;
+        let keyed_accounts =
+            self.get_filtered_program_accounts_by_prefix(&bank, program_id, prefix, filters)?;
+        encode_program_accounts(
+            &bank,
+            program_id,
+            keyed_accounts,
+            encoding,
+            data_slice_config,
+            with_context,
+        )
+    }
+
+    pub fn get_feature_statuses(
+        &self,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<RpcResponse<Vec<RpcFeatureStatus>>> {
+        let bank = self.bank(commitment);
+        let mut feature_ids: Vec<_> = FEATURE_NAMES.keys().collect();
+        feature_ids.sort_unstable();
+
+        let epoch_schedule = bank.epoch_schedule();
+        let feature_statuses = feature_ids
+            .into_iter()
+            .map(|feature_id| {
+                let feature = bank
+                    .get_account(feature_id)
+                    .and_then(|account| feature::from_account(&account));
+                let (status, activation_slot) = match feature {
+                    None => (RpcFeatureActivationStatus::Inactive, None),
+                    Some(Feature { activated_at: None }) => {
+                        (RpcFeatureActivationStatus::Pending, None)
+                    }
+                    Some(Feature {
+                        activated_at: Some(activation_slot),
+                    }) => (RpcFeatureActivationStatus::Active, Some(activation_slot)),
+                };
+                RpcFeatureStatus {
+                    id: feature_id.to_string(),
+                    description: FEATURE_NAMES[feature_id].to_string(),
+                    status,
+                    activation_slot,
+                    activation_epoch: activation_slot.map(|slot| epoch_schedule.get_epoch(slot)),
+                }
+            })
+            .collect();
+        Ok(new_response(&bank, feature_statuses))
     }
 
     pub async fn get_inflation_reward(
@@ -421,14 +603,45 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcEpochConfig>,
     ) -> Result<Vec<Option<RpcInflationReward>>> {
         let config = config.unwrap_or_default();
-        let epoch_schedule = self.get_epoch_schedule();
-        let first_available_block = self.get_first_available_block().await;
         let epoch = config.epoch.unwrap_or_else(|| {
-            epoch_schedule
+            self.get_epoch_schedule()
                 .get_epoch(self.get_slot(config.commitment))
                 .saturating_sub(1)
         });
 
+        let epoch_rewards = self.get_epoch_rewards(epoch, config.commitment).await?;
+        let rewards = addresses
+            .iter()
+            .map(|address| {
+                epoch_rewards
+                    .get(&address.to_string())
+                    .map(|(effective_slot, reward)| RpcInflationReward {
+                        epoch,
+                        effective_slot: *effective_slot,
+                        amount: reward.lamports.abs() as u64,
+                        post_balance: reward.post_balance,
+                        commission: reward.commission,
+                    })
+            })
+            .collect();
+
+        Ok(rewards)
+    }
+
+    /// The staking and voting rewards paid for `epoch`, read from the blocks they were credited
+    /// in.  The rewards are cached once all of those blocks are finalized
+    async fn get_epoch_rewards(
+        &self,
+        epoch: Epoch,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Arc<EpochRewards>> {
+        if let Some(epoch_rewards) = self.epoch_rewards_cache.read().unwrap().get(epoch) {
+            return Ok(epoch_rewards);
+        }
+
+        let epoch_schedule = self.get_epoch_schedule();
+        let first_available_block = self.get_first_available_block().await;
+
         // Rewards for this epoch are found in the first confirmed block of the next epoch
         let first_slot_in_epoch = epoch_schedule.get_first_slot_in_epoch(epoch.saturating_add(1));
         if first_slot_in_epoch < first_available_block {
@@ -447,62 +660,91 @@ impl JsonRpcRequestProcessor {
         }
 
         let first_confirmed_block_in_epoch = *self
-            .get_blocks_with_limit(first_slot_in_epoch, 1, config.commitment)
+            .get_blocks_with_limit(first_slot_in_epoch, 1, commitment)
             .await?
             .get(0)
             .ok_or(RpcCustomError::BlockNotAvailable {
                 slot: first_slot_in_epoch,
             })?;
 
-        let first_confirmed_block = if let Ok(Some(first_confirmed_block)) = self
-            .get_block(
-                first_confirmed_block_in_epoch,
-                Some(RpcBlockConfig::rewards_with_commitment(config.commitment).into()),
-            )
-            .await
-        {
-            first_confirmed_block
-        } else {
-            return Err(RpcCustomError::BlockNotAvailable {
-                slot: first_confirmed_block_in_epoch,
-            }
-            .into());
-        };
-
-        let addresses: Vec<String> = addresses
-            .into_iter()
-            .map(|pubkey| pubkey.to_string())
-            .collect();
-
-        let reward_hash: HashMap<String, Reward> = first_confirmed_block
-            .rewards
-            .unwrap_or_default()
-            .into_iter()
-            .filter_map(|reward| match reward.reward_type? {
-                RewardType::Staking | RewardType::Voting => addresses
-                    .contains(&reward.pubkey)
-                    .then(|| (reward.clone().pubkey, reward)),
-                _ => None,
-            })
-            .collect();
+        let mut reward_blocks = vec![first_confirmed_block_in_epoch];
+        let mut complete = true;
+        // With partitioned epoch rewards, stake and vote rewards are credited in the blocks
+        // following the first block of the epoch instead
+        let partitioned_epoch_rewards = self
+            .bank(commitment)
+            .feature_set
+            .activated_slot(&feature_set::partitioned_epoch_rewards::id())
+            .map(|activated_slot| activated_slot <= first_confirmed_block_in_epoch)
+            .unwrap_or(false);
+        if partitioned_epoch_rewards {
+            let num_blocks =
+                get_reward_distribution_num_blocks(&epoch_schedule, epoch.saturating_add(1))
+                    as usize;
+            let distribution_blocks = self
+                .get_blocks_with_limit(
+                    first_confirmed_block_in_epoch.saturating_add(1),
+                    num_blocks,
+                    commitment,
+                )
+                .await?;
+            complete = distribution_blocks.len() == num_blocks;
+            reward_blocks.extend(distribution_blocks);
+        }
 
-        let rewards = addresses
-            .iter()
-            .map(|address| {
-                if let Some(reward) = reward_hash.get(address) {
-                    return Some(RpcInflationReward {
-                        epoch,
-                        effective_slot: first_confirmed_block_in_epoch,
-                        amount: reward.lamports.abs() as u64,
-                        post_balance: reward.post_balance,
-                        commission: reward.commission,
-                    });
+        let mut epoch_rewards = EpochRewards::new();
+        for slot in &reward_blocks {
+            let block = if let Ok(Some(block)) = self
+                .get_block(
+                    *slot,
+                    Some(RpcBlockConfig::rewards_with_commitment(commitment).into()),
+                )
+                .await
+            {
+                block
+            } else {
+                return Err(RpcCustomError::BlockNotAvailable { slot: *slot }.into());
+            };
+            for reward in block.rewards.unwrap_or_default() {
+                match reward.reward_type {
+                    Some(RewardType::Staking) => {
+                        epoch_rewards.insert(reward.pubkey.clone(), (*slot, reward));
+                    }
+                    // a vote account is credited its share of each partition's rewards in turn
+                    Some(RewardType::Voting) => match epoch_rewards.entry(reward.pubkey.clone()) {
+                        Entry::Occupied(mut entry) => {
+                            let (reward_slot, total_reward) = entry.get_mut();
+                            *reward_slot = *slot;
+                            total_reward.lamports =
+                                total_reward.lamports.saturating_add(reward.lamports);
+                            total_reward.post_balance = reward.post_balance;
+                            total_reward.commission = reward.commission;
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert((*slot, reward));
+                        }
+                    },
+                    _ => {}
                 }
-                None
-            })
-            .collect();
+            }
+        }
+        let epoch_rewards = Arc::new(epoch_rewards);
 
-        Ok(rewards)
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let finalized = reward_blocks
+            .iter()
+            .all(|slot| *slot <= highest_confirmed_root);
+        if complete && finalized {
+            self.epoch_rewards_cache
+                .write()
+                .unwrap()
+                .insert(epoch, epoch_rewards.clone());
+        }
+        Ok(epoch_rewards)
     }
 
     pub fn get_inflation_governor(
@@ -744,13 +986,8 @@ impl JsonRpcRequestProcessor {
             })
         } else {
             let (addresses, address_filter) = if let Some(filter) = config.clone().filter {
-                let non_circulating_supply =
-                    calculate_non_circulating_supply(&bank).map_err(|e| {
-                        RpcCustomError::ScanError {
-                            message: e.to_string(),
-                        }
-                    })?;
-                let addresses = non_circulating_supply.accounts.into_iter().collect();
+                let non_circulating_supply = self.get_non_circulating_supply(&bank)?;
+                let addresses = non_circulating_supply.accounts.iter().cloned().collect();
                 let address_filter = match filter {
                     RpcLargestAccountsFilter::Circulating => AccountAddressFilter::Exclude,
                     RpcLargestAccountsFilter::NonCirculating => AccountAddressFilter::Include,
@@ -776,16 +1013,41 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    // The accounts scan runs without holding the cache lock, so that a request missing the cache
+    // doesn't block the requests hitting it
+    fn get_non_circulating_supply(
+        &self,
+        bank: &Bank,
+    ) -> RpcCustomResult<Arc<NonCirculatingSupply>> {
+        if let Some(non_circulating_supply) = self
+            .non_circulating_supply_cache
+            .lock()
+            .unwrap()
+            .get(bank.epoch(), bank.slot())
+        {
+            return Ok(non_circulating_supply);
+        }
+        let non_circulating_supply =
+            Arc::new(calculate_non_circulating_supply(bank).map_err(|e| {
+                RpcCustomError::ScanError {
+                    message: e.to_string(),
+                }
+            })?);
+        self.non_circulating_supply_cache.lock().unwrap().insert(
+            bank.epoch(),
+            bank.slot(),
+            non_circulating_supply.clone(),
+        );
+        Ok(non_circulating_supply)
+    }
+
     fn get_supply(
         &self,
         config: Option<RpcSupplyConfig>,
     ) -> RpcCustomResult<RpcResponse<RpcSupply>> {
         let config = config.unwrap_or_default();
         let bank = self.bank(config.commitment);
-        let non_circulating_supply =
-            calculate_non_circulating_supply(&bank).map_err(|e| RpcCustomError::ScanError {
-                message: e.to_string(),
-            })?;
+        let non_circulating_supply = self.get_non_circulating_supply(&bank)?;
         let total_supply = bank.capitalization();
         let non_circulating_accounts = if config.exclude_non_circulating_accounts_list {
             vec![]
@@ -825,6 +1087,10 @@ impl JsonRpcRequestProcessor {
         let epoch_vote_accounts = bank
             .epoch_vote_accounts(bank.get_epoch_and_slot_index(bank.slot()).0)
             .ok_or_else(Error::invalid_request)?;
+        // The stakes of the leader schedule epoch are snapshotted at the start of the current
+        // epoch, so they have the commissions the epoch started with
+        let epoch_start_vote_accounts =
+            bank.epoch_vote_accounts(bank.get_leader_schedule_epoch(bank.slot()));
         let default_vote_state = VoteState::default();
         let delinquent_validator_slot_distance = config
             .delinquent_slot_distance
@@ -860,11 +1126,24 @@ impl JsonRpcRequestProcessor {
                     epoch_credits.clone()
                 };
 
+                // The current commission is the one this epoch's rewards are paid at
+                let epoch_start_commission = epoch_start_vote_accounts
+                    .and_then(|vote_accounts| vote_accounts.get(vote_pubkey))
+                    .and_then(|(_, account)| {
+                        account
+                            .vote_state()
+                            .as_ref()
+                            .ok()
+                            .map(|vote_state| vote_state.commission)
+                    })
+                    .filter(|commission| *commission != vote_state.commission);
+
                 Some(RpcVoteAccountInfo {
                     vote_pubkey: vote_pubkey.to_string(),
                     node_pubkey: vote_state.node_pubkey.to_string(),
                     activated_stake: *activated_stake,
                     commission: vote_state.commission,
+                    epoch_start_commission,
                     root_slot: vote_state.root_slot.unwrap_or(0),
                     epoch_credits,
                     epoch_vote_account: epoch_vote_accounts.contains_key(vote_pubkey),
@@ -973,6 +1252,7 @@ impl JsonRpcRequestProcessor {
             let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
             let transaction_details = config.transaction_details.unwrap_or_default();
             let show_rewards = config.rewards.unwrap_or(true);
+            let max_supported_transaction_version = config.max_supported_transaction_version;
             let commitment = config.commitment.unwrap_or_default();
             check_is_at_least_confirmed(commitment)?;
 
@@ -986,25 +1266,32 @@ impl JsonRpcRequestProcessor {
             {
                 let result = self.blockstore.get_rooted_block(slot, true);
                 self.check_blockstore_root(&result, slot)?;
-                let configure_block = |confirmed_block: ConfirmedBlock| {
-                    let mut confirmed_block =
-                        confirmed_block.configure(encoding, transaction_details, show_rewards);
-                    if slot == 0 {
-                        confirmed_block.block_time = Some(self.genesis_creation_time());
-                        confirmed_block.block_height = Some(0);
-                    }
-                    confirmed_block
-                };
+                let configure_block =
+                    |confirmed_block: ConfirmedBlock| -> Result<UiConfirmedBlock> {
+                        let mut confirmed_block = confirmed_block
+                            .configure(
+                                encoding,
+                                transaction_details,
+                                show_rewards,
+                                max_supported_transaction_version,
+                            )
+                            .map_err(RpcCustomError::from)?;
+                        if slot == 0 {
+                            confirmed_block.block_time = Some(self.genesis_creation_time());
+                            confirmed_block.block_height = Some(0);
+                        }
+                        Ok(confirmed_block)
+                    };
                 if result.is_err() {
                     if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
                         let bigtable_result =
                             bigtable_ledger_storage.get_confirmed_block(slot).await;
                         self.check_bigtable_result(&bigtable_result)?;
-                        return Ok(bigtable_result.ok().map(configure_block));
+                        return bigtable_result.ok().map(configure_block).transpose();
                     }
                 }
                 self.check_slot_cleaned_up(&result, slot)?;
-                return Ok(result.ok().map(configure_block));
+                return result.ok().map(configure_block).transpose();
             } else if commitment.is_confirmed() {
                 // Check if block is confirmed
                 let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
@@ -1015,23 +1302,34 @@ impl JsonRpcRequestProcessor {
                             .load(Ordering::SeqCst)
                 {
                     let result = self.blockstore.get_complete_block(slot, true);
-                    return Ok(result.ok().map(|mut confirmed_block| {
-                        if confirmed_block.block_time.is_none()
-                            || confirmed_block.block_height.is_none()
-                        {
-                            let r_bank_forks = self.bank_forks.read().unwrap();
-                            let bank = r_bank_forks.get(slot).cloned();
-                            if let Some(bank) = bank {
-                                if confirmed_block.block_time.is_none() {
-                                    confirmed_block.block_time = Some(bank.clock().unix_timestamp);
-                                }
-                                if confirmed_block.block_height.is_none() {
-                                    confirmed_block.block_height = Some(bank.block_height());
+                    return result
+                        .ok()
+                        .map(|mut confirmed_block| -> Result<UiConfirmedBlock> {
+                            if confirmed_block.block_time.is_none()
+                                || confirmed_block.block_height.is_none()
+                            {
+                                let r_bank_forks = self.bank_forks.read().unwrap();
+                                let bank = r_bank_forks.get(slot).cloned();
+                                if let Some(bank) = bank {
+                                    if confirmed_block.block_time.is_none() {
+                                        confirmed_block.block_time =
+                                            Some(bank.clock().unix_timestamp);
+                                    }
+                                    if confirmed_block.block_height.is_none() {
+                                        confirmed_block.block_height = Some(bank.block_height());
+                                    }
                                 }
                             }
-                        }
-                        confirmed_block.configure(encoding, transaction_details, show_rewards)
-                    }));
+                            confirmed_block
+                                .configure(
+                                    encoding,
+                                    transaction_details,
+                                    show_rewards,
+                                    max_supported_transaction_version,
+                                )
+                                .map_err(|err| RpcCustomError::from(err).into())
+                        })
+                        .transpose();
                 }
             }
         } else {
@@ -1074,34 +1372,40 @@ impl JsonRpcRequestProcessor {
         }
 
         let lowest_blockstore_slot = self.blockstore.lowest_slot();
+        let mut blocks = vec![];
         if start_slot < lowest_blockstore_slot {
-            // If the starting slot is lower than what's available in blockstore assume the entire
-            // [start_slot..end_slot] can be fetched from BigTable. This range should not ever run
-            // into unfinalized confirmed blocks due to MAX_GET_CONFIRMED_BLOCKS_RANGE
+            // Fetch the part of the range older than what's available in blockstore from
+            // BigTable, the rest of the range is served from blockstore below. That part of the
+            // range should not ever run into unfinalized confirmed blocks due to
+            // MAX_GET_CONFIRMED_BLOCKS_RANGE
             if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
-                return bigtable_ledger_storage
-                    .get_confirmed_blocks(start_slot, (end_slot - start_slot) as usize + 1) // increment limit by 1 to ensure returned range is inclusive of both start_slot and end_slot
-                    .await
-                    .map(|mut bigtable_blocks| {
-                        bigtable_blocks.retain(|&slot| slot <= end_slot);
-                        bigtable_blocks
-                    })
-                    .map_err(|_| {
-                        Error::invalid_params(
-                            "BigTable query failed (maybe timeout due to too large range?)"
-                                .to_string(),
-                        )
-                    });
+                let (long_term_storage_blocks, is_complete) = get_long_term_storage_blocks(
+                    bigtable_ledger_storage,
+                    start_slot,
+                    min(end_slot, lowest_blockstore_slot - 1),
+                    usize::MAX,
+                )
+                .await
+                .map_err(|_| {
+                    Error::invalid_params(
+                        "BigTable query failed (maybe timeout due to too large range?)".to_string(),
+                    )
+                })?;
+                if !is_complete {
+                    // The client continues from the slot after the last block returned
+                    return Ok(long_term_storage_blocks);
+                }
+                blocks = long_term_storage_blocks;
             }
         }
 
         // Finalized blocks
-        let mut blocks: Vec<_> = self
-            .blockstore
-            .rooted_slot_iterator(max(start_slot, lowest_blockstore_slot))
-            .map_err(|_| Error::internal_error())?
-            .filter(|&slot| slot <= end_slot && slot <= highest_confirmed_root)
-            .collect();
+        blocks.extend(
+            self.blockstore
+                .rooted_slot_iterator(max(start_slot, lowest_blockstore_slot))
+                .map_err(|_| Error::internal_error())?
+                .filter(|&slot| slot <= end_slot && slot <= highest_confirmed_root),
+        );
         let last_element = blocks
             .last()
             .cloned()
@@ -1138,16 +1442,25 @@ impl JsonRpcRequestProcessor {
         }
 
         let lowest_blockstore_slot = self.blockstore.lowest_slot();
-
+        let mut blocks = vec![];
         if start_slot < lowest_blockstore_slot {
-            // If the starting slot is lower than what's available in blockstore assume the entire
-            // range can be fetched from BigTable. This range should not ever run into unfinalized
-            // confirmed blocks due to MAX_GET_CONFIRMED_BLOCKS_RANGE
+            // Fetch the blocks older than what's available in blockstore from BigTable, and
+            // continue with blockstore once those run out. Those blocks should not ever run
+            // into unfinalized confirmed blocks due to MAX_GET_CONFIRMED_BLOCKS_RANGE
             if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
-                return Ok(bigtable_ledger_storage
-                    .get_confirmed_blocks(start_slot, limit)
-                    .await
-                    .unwrap_or_default());
+                let (long_term_storage_blocks, is_complete) = get_long_term_storage_blocks(
+                    bigtable_ledger_storage,
+                    start_slot,
+                    lowest_blockstore_slot - 1,
+                    limit,
+                )
+                .await
+                .unwrap_or_else(|_| (vec![], true));
+                if !is_complete {
+                    // The client continues from the slot after the last block returned
+                    return Ok(long_term_storage_blocks);
+                }
+                blocks = long_term_storage_blocks;
             }
         }
 
@@ -1158,13 +1471,14 @@ impl JsonRpcRequestProcessor {
             .highest_confirmed_root();
 
         // Finalized blocks
-        let mut blocks: Vec<_> = self
-            .blockstore
-            .rooted_slot_iterator(max(start_slot, lowest_blockstore_slot))
-            .map_err(|_| Error::internal_error())?
-            .take(limit)
-            .filter(|&slot| slot <= highest_confirmed_root)
-            .collect();
+        let remaining_limit = limit - blocks.len();
+        blocks.extend(
+            self.blockstore
+                .rooted_slot_iterator(max(start_slot, lowest_blockstore_slot))
+                .map_err(|_| Error::internal_error())?
+                .take(remaining_limit)
+                .filter(|&slot| slot <= highest_confirmed_root),
+        );
 
         // Maybe add confirmed blocks
         if commitment.is_confirmed() && blocks.len() < limit {
@@ -1351,6 +1665,7 @@ impl JsonRpcRequestProcessor {
             .map(|config| config.convert_to_current())
             .unwrap_or_default();
         let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
+        let max_supported_transaction_version = config.max_supported_transaction_version;
         let commitment = config.commitment.unwrap_or_default();
         check_is_at_least_confirmed(commitment)?;
 
@@ -1376,7 +1691,11 @@ impl JsonRpcRequestProcessor {
                                 .get(confirmed_transaction.slot)
                                 .map(|bank| bank.clock().unix_timestamp);
                         }
-                        return Ok(Some(confirmed_transaction.encode(encoding)));
+                        return Ok(Some(
+                            confirmed_transaction
+                                .encode(encoding, max_supported_transaction_version)
+                                .map_err(RpcCustomError::from)?,
+                        ));
                     }
                     if confirmed_transaction.slot
                         <= self
@@ -1385,16 +1704,25 @@ impl JsonRpcRequestProcessor {
                             .unwrap()
                             .highest_confirmed_root()
                     {
-                        return Ok(Some(confirmed_transaction.encode(encoding)));
+                        return Ok(Some(
+                            confirmed_transaction
+                                .encode(encoding, max_supported_transaction_version)
+                                .map_err(RpcCustomError::from)?,
+                        ));
                     }
                 }
                 None => {
                     if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
-                        return Ok(bigtable_ledger_storage
+                        return bigtable_ledger_storage
                             .get_confirmed_transaction(&signature)
                             .await
                             .unwrap_or(None)
-                            .map(|confirmed| confirmed.encode(encoding)));
+                            .map(|confirmed| {
+                                confirmed
+                                    .encode(encoding, max_supported_transaction_version)
+                                    .map_err(|err| RpcCustomError::from(err).into())
+                            })
+                            .transpose();
                     }
                 }
             }
@@ -1434,8 +1762,12 @@ impl JsonRpcRequestProcessor {
         mut before: Option<Signature>,
         until: Option<Signature>,
         mut limit: usize,
+        memo: Option<String>,
         commitment: Option<CommitmentConfig>,
-    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    ) -> Result<(
+        Vec<RpcConfirmedTransactionStatusWithSignature>,
+        Option<Signature>,
+    )> {
         let commitment = commitment.unwrap_or_default();
         check_is_at_least_confirmed(commitment)?;
 
@@ -1452,12 +1784,26 @@ impl JsonRpcRequestProcessor {
                 highest_confirmed_root
             };
 
-            let mut results = self
+            let ConfirmedSignaturesForAddressPage {
+                infos: mut results,
+                before: mut next_before,
+            } = self
                 .blockstore
-                .get_confirmed_signatures_for_address2(address, highest_slot, before, until, limit)
+                .get_confirmed_signatures_for_address_with_memo(
+                    address,
+                    highest_slot,
+                    before,
+                    until,
+                    limit,
+                    memo.as_deref().map(|memo| MemoFilter {
+                        memo,
+                        max_scanned: MAX_GET_SIGNATURES_FOR_ADDRESS_MEMO_SCAN,
+                    }),
+                )
                 .map_err(|err| Error::invalid_params(format!("{}", err)))?;
 
-            if results.len() < limit {
+            // Unless the memo search stopped short of the end of the blockstore
+            if results.len() < limit && next_before.is_none() {
                 if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
                     if !results.is_empty() {
                         limit -= results.len();
@@ -1474,7 +1820,18 @@ impl JsonRpcRequestProcessor {
                         .await;
                     match bigtable_results {
                         Ok(bigtable_results) => {
-                            results.extend(bigtable_results.into_iter().map(|x| x.0));
+                            // Bigtable has no memo index, so matching transactions are only
+                            // filtered out of the fetched page, which the search continues
+                            // after if full
+                            if bigtable_results.len() == limit {
+                                next_before = bigtable_results.last().map(|x| x.0.signature);
+                            }
+                            results.extend(
+                                bigtable_results
+                                    .into_iter()
+                                    .map(|x| x.0)
+                                    .filter(|x| memo_matches(x.memo.as_deref(), memo.as_deref())),
+                            );
                         }
                         Err(err) => {
                             warn!("{:?}", err);
@@ -1483,7 +1840,7 @@ impl JsonRpcRequestProcessor {
                 }
             }
 
-            Ok(results
+            let results = results
                 .into_iter()
                 .map(|x| {
                     let mut item: RpcConfirmedTransactionStatusWithSignature = x.into();
@@ -1500,7 +1857,8 @@ impl JsonRpcRequestProcessor {
                     }
                     item
                 })
-                .collect())
+                .collect();
+            Ok((results, next_before))
         } else {
             Err(RpcCustomError::TransactionHistoryNotAvailable.into())
         }
@@ -1654,41 +2012,55 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, supply))
     }
 
+    /// The token accounts of `mint` by decreasing amount, then by address, a page at a time
     pub fn get_token_largest_accounts(
         &self,
         mint: &Pubkey,
-        commitment: Option<CommitmentConfig>,
+        config: Option<RpcTokenLargestAccountsConfig>,
     ) -> Result<RpcResponse<Vec<RpcTokenAccountBalance>>> {
-        let bank = self.bank(commitment);
+        let config = config.unwrap_or_default();
+        let limit = config.limit.unwrap_or(NUM_LARGEST_ACCOUNTS);
+        if limit > MAX_GET_TOKEN_LARGEST_ACCOUNTS_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {}",
+                MAX_GET_TOKEN_LARGEST_ACCOUNTS_LIMIT
+            )));
+        }
+        let bank = self.bank(config.commitment);
         let (mint_owner, decimals) = get_mint_owner_and_decimals(&bank, mint)?;
         if mint_owner != spl_token_id_v2_0() {
             return Err(Error::invalid_params(
                 "Invalid param: not a v2.0 Token mint".to_string(),
             ));
         }
-        let mut token_balances: Vec<RpcTokenAccountBalance> = self
-            .get_filtered_spl_token_accounts_by_mint(&bank, mint, vec![])?
+        let after = config
+            .after
+            .map(|after| {
+                let after = verify_pubkey(&after)?;
+                let amount = bank
+                    .get_account(&after)
+                    .and_then(|account| get_token_account_amount(&account, mint))
+                    .ok_or_else(|| {
+                        Error::invalid_params(format!(
+                            "Invalid param: {} is not a token account of mint {}",
+                            after, mint
+                        ))
+                    })?;
+                Ok((amount, Reverse(after)))
+            })
+            .transpose()?;
+
+        let token_balances = self
+            .get_largest_spl_token_accounts_by_mint(&bank, mint, limit, |address, account| {
+                let key = (get_token_account_amount(account, mint)?, Reverse(*address));
+                after.map_or(true, |after| key < after).then(|| key)
+            })?
             .into_iter()
-            .map(|(address, account)| {
-                let amount = TokenAccount::unpack(account.data())
-                    .map(|account| account.amount)
-                    .unwrap_or(0);
-                let amount = token_amount_to_ui_amount(amount, decimals);
-                RpcTokenAccountBalance {
-                    address: address.to_string(),
-                    amount,
-                }
+            .map(|(amount, Reverse(address))| RpcTokenAccountBalance {
+                address: address.to_string(),
+                amount: token_amount_to_ui_amount(amount, decimals),
             })
             .collect();
-        token_balances.sort_by(|a, b| {
-            a.amount
-                .amount
-                .parse::<u64>()
-                .unwrap()
-                .cmp(&b.amount.amount.parse::<u64>().unwrap())
-                .reverse()
-        });
-        token_balances.truncate(NUM_LARGEST_ACCOUNTS);
         Ok(new_response(&bank, token_balances))
     }
 
@@ -1703,6 +2075,7 @@ impl JsonRpcRequestProcessor {
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        check_last_modified_slot_unsupported(config.with_last_modified_slot)?;
         let (_, mint) = get_token_program_id_and_mint(&bank, token_account_filter)?;
 
         let mut filters = vec![];
@@ -1748,6 +2121,7 @@ impl JsonRpcRequestProcessor {
         let encoding = config.encoding.unwrap_or(UiAccountEncoding::Binary);
         let data_slice_config = config.data_slice;
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+        check_last_modified_slot_unsupported(config.with_last_modified_slot)?;
         let (token_program_id, mint) = get_token_program_id_and_mint(&bank, token_account_filter)?;
 
         let mut filters = vec![
@@ -1802,12 +2176,93 @@ impl JsonRpcRequestProcessor {
         mut filters: Vec<RpcFilterType>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         optimize_filters(&mut filters);
-        let filter_closure = |account: &AccountSharedData| {
-            filters.iter().all(|filter_type| match filter_type {
-                RpcFilterType::DataSize(size) => account.data().len() as u64 == *size,
-                RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
-            })
-        };
+        let filter_closure =
+            |account: &LoadedAccount| loaded_account_matches_filters(account, &filters);
+        let use_program_id_index = self
+            .config
+            .account_indexes
+            .contains(&AccountIndex::ProgramId);
+        if use_program_id_index && !self.config.account_indexes.include_key(program_id) {
+            return Err(RpcCustomError::KeyExcludedFromSecondaryIndex {
+                index_key: program_id.to_string(),
+            });
+        }
+        if !filters.is_empty()
+            && filters
+                .iter()
+                .all(|filter_type| matches!(filter_type, RpcFilterType::DataSize(_)))
+        {
+            // Data size filters only need the accounts' metadata, so scan that and only load the
+            // data of the matching accounts
+            let data_len_matches = |data_len: usize| {
+                filters.iter().all(|filter_type| match filter_type {
+                    RpcFilterType::DataSize(size) => data_len as u64 == *size,
+                    RpcFilterType::Memcmp(_) => false,
+                })
+            };
+            let metadata_filter =
+                |metadata: &AccountScanMetadata| data_len_matches(metadata.data_len);
+            let metadata = if use_program_id_index {
+                bank.get_filtered_indexed_accounts_metadata(
+                    &IndexKey::ProgramId(*program_id),
+                    |metadata| metadata.owner == *program_id && metadata_filter(metadata),
+                )
+            } else {
+                bank.get_filtered_program_accounts_metadata(program_id, metadata_filter)
+            }
+            .map_err(|e| RpcCustomError::ScanError {
+                message: e.to_string(),
+            })?;
+            return Ok(metadata
+                .into_iter()
+                .filter_map(|metadata| {
+                    // The account may have changed since it was scanned
+                    bank.get_account(&metadata.pubkey)
+                        .filter(|account| {
+                            account.owner() == program_id && data_len_matches(account.data().len())
+                        })
+                        .map(|account| (metadata.pubkey, account))
+                })
+                .collect());
+        }
+        if use_program_id_index {
+            Ok(bank
+                .get_indexed_accounts_with_loaded_filter(
+                    &IndexKey::ProgramId(*program_id),
+                    |account| {
+                        // The program-id account index checks for Account owner on inclusion. However, due
+                        // to the current AccountsDb implementation, an account may remain in storage as a
+                        // zero-lamport AccountSharedData::Default() after being wiped and reinitialized in later
+                        // updates. We include the redundant filters here to avoid returning these
+                        // accounts.
+                        account.owner() == program_id && filter_closure(account)
+                    },
+                )
+                .map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })?)
+        } else {
+            Ok(bank
+                .get_program_accounts_with_loaded_filter(program_id, filter_closure)
+                .map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })?)
+        }
+    }
+
+    /// Like `get_filtered_program_accounts`, but only returns the accounts whose addresses start
+    /// with `prefix`, in address order
+    fn get_filtered_program_accounts_by_prefix(
+        &self,
+        bank: &Arc<Bank>,
+        program_id: &Pubkey,
+        prefix: &[u8],
+        mut filters: Vec<RpcFilterType>,
+    ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
+        optimize_filters(&mut filters);
+        let range = pubkey_range_from_prefix(prefix);
+        let filter_closure =
+            |account: &LoadedAccount| loaded_account_matches_filters(account, &filters);
         if self
             .config
             .account_indexes
@@ -1818,21 +2273,24 @@ impl JsonRpcRequestProcessor {
                     index_key: program_id.to_string(),
                 });
             }
-            Ok(bank
-                .get_filtered_indexed_accounts(&IndexKey::ProgramId(*program_id), |account| {
-                    // The program-id account index checks for Account owner on inclusion. However, due
-                    // to the current AccountsDb implementation, an account may remain in storage as a
-                    // zero-lamport AccountSharedData::Default() after being wiped and reinitialized in later
-                    // updates. We include the redundant filters here to avoid returning these
-                    // accounts.
-                    account.owner() == program_id && filter_closure(account)
-                })
+            let mut keyed_accounts = bank
+                .get_indexed_accounts_with_loaded_filter(
+                    &IndexKey::ProgramId(*program_id),
+                    |account| {
+                        // See `get_filtered_program_accounts` for the redundant owner check
+                        account.owner() == program_id
+                            && range.contains(account.pubkey())
+                            && filter_closure(account)
+                    },
+                )
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
-                })?)
+                })?;
+            keyed_accounts.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+            Ok(keyed_accounts)
         } else {
             Ok(bank
-                .get_filtered_program_accounts(program_id, filter_closure)
+                .get_program_accounts_in_range(program_id, range, filter_closure)
                 .map_err(|e| RpcCustomError::ScanError {
                     message: e.to_string(),
                 })?)
@@ -1938,6 +2396,38 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// Get the `num` largest keys `rank` returns for the spl-token accounts of a mint, without
+    /// collecting all of them
+    fn get_largest_spl_token_accounts_by_mint<K, F>(
+        &self,
+        bank: &Arc<Bank>,
+        mint_key: &Pubkey,
+        num: usize,
+        rank: F,
+    ) -> RpcCustomResult<Vec<K>>
+    where
+        K: Ord,
+        F: Fn(&Pubkey, &AccountSharedData) -> Option<K>,
+    {
+        let largest = if self
+            .config
+            .account_indexes
+            .contains(&AccountIndex::SplTokenMint)
+        {
+            if !self.config.account_indexes.include_key(mint_key) {
+                return Err(RpcCustomError::KeyExcludedFromSecondaryIndex {
+                    index_key: mint_key.to_string(),
+                });
+            }
+            bank.get_largest_indexed_accounts(&IndexKey::SplTokenMint(*mint_key), num, rank)
+        } else {
+            bank.get_largest_program_accounts(&spl_token_id_v2_0(), num, rank)
+        };
+        largest.map_err(|e| RpcCustomError::ScanError {
+            message: e.to_string(),
+        })
+    }
+
     fn get_latest_blockhash(
         &self,
         commitment: Option<CommitmentConfig>,
@@ -1968,11 +2458,12 @@ impl JsonRpcRequestProcessor {
 
     fn get_fee_for_message(
         &self,
-        message: &SanitizedMessage,
+        message: VersionedMessage,
         commitment: Option<CommitmentConfig>,
     ) -> Result<RpcResponse<Option<u64>>> {
         let bank = self.bank(commitment);
-        let fee = bank.get_fee_for_message(message);
+        let message = sanitize_message(message, &bank)?;
+        let fee = bank.get_fee_for_message(&message);
         Ok(new_response(&bank, Some(fee)))
     }
 }
@@ -1995,6 +2486,34 @@ fn optimize_filters(filters: &mut Vec<RpcFilterType>) {
     })
 }
 
+// Segments the transactions dropped by a failed `sendTransaction` preflight simulation by the
+// most common reasons
+fn inc_preflight_failure_counter(err: &TransactionError) {
+    match err {
+        TransactionError::BlockhashNotFound => {
+            inc_new_counter_info!("rpc-send-tx_err-blockhash-not-found", 1);
+        }
+        TransactionError::AlreadyProcessed => {
+            inc_new_counter_info!("rpc-send-tx_err-already-processed", 1);
+        }
+        TransactionError::AccountNotFound => {
+            inc_new_counter_info!("rpc-send-tx_err-account-not-found", 1);
+        }
+        TransactionError::InsufficientFundsForFee => {
+            inc_new_counter_info!("rpc-send-tx_err-insufficient-funds-for-fee", 1);
+        }
+        TransactionError::InstructionError(..) => {
+            inc_new_counter_info!("rpc-send-tx_err-instruction-error", 1);
+        }
+        TransactionError::InvalidRentPayingAccount => {
+            inc_new_counter_info!("rpc-send-tx_err-invalid-rent-paying-account", 1);
+        }
+        _ => {
+            inc_new_counter_info!("rpc-send-tx_err-other", 1);
+        }
+    }
+}
+
 fn verify_transaction(
     transaction: &SanitizedTransaction,
     feature_set: &Arc<feature_set::FeatureSet>,
@@ -2015,6 +2534,43 @@ fn verify_transaction(
     Ok(())
 }
 
+// The filters are evaluated against the stored accounts, so that the data of the accounts they
+// reject is never copied
+fn loaded_account_matches_filters(account: &LoadedAccount, filters: &[RpcFilterType]) -> bool {
+    filters.iter().all(|filter_type| match filter_type {
+        RpcFilterType::DataSize(size) => account.data_len() as u64 == *size,
+        RpcFilterType::Memcmp(compare) => compare.bytes_match(account.data()),
+    })
+}
+
+fn encode_program_accounts(
+    bank: &Arc<Bank>,
+    program_id: &Pubkey,
+    keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    encoding: UiAccountEncoding,
+    data_slice_config: Option<UiDataSliceConfig>,
+    with_context: bool,
+) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+    let result = if program_id == &spl_token_id_v2_0() && encoding == UiAccountEncoding::JsonParsed
+    {
+        get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
+    } else {
+        keyed_accounts
+            .into_iter()
+            .map(|(pubkey, account)| {
+                Ok(RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    Ok(match with_context {
+        true => OptionalContext::Context(new_response(bank, result)),
+        false => OptionalContext::NoContext(result),
+    })
+}
+
 fn verify_filter(input: &RpcFilterType) -> Result<()> {
     input
         .verify()
@@ -2076,6 +2632,48 @@ fn verify_and_parse_signatures_for_address_params(
     Ok((address, before, until, limit))
 }
 
+fn memo_matches(memo: Option<&str>, memo_filter: Option<&str>) -> bool {
+    match memo_filter {
+        Some(memo_filter) => memo.map_or(false, |memo| memo.contains(memo_filter)),
+        None => true,
+    }
+}
+
+/// The blocks from `start_slot` to `end_slot` in long-term storage, up to `limit` of them,
+/// along with whether those are all of them.  The blocks are queried a page at a time, each page
+/// resuming after the last block of the previous one, so that a long range isn't fetched in a
+/// single query that may time out.  Once `MAX_LONG_TERM_STORAGE_BLOCKS_QUERIES` pages are
+/// fetched, the blocks so far are returned as incomplete
+async fn get_long_term_storage_blocks(
+    bigtable_ledger_storage: &solana_storage_bigtable::LedgerStorage,
+    start_slot: Slot,
+    end_slot: Slot,
+    limit: usize,
+) -> std::result::Result<(Vec<Slot>, bool), solana_storage_bigtable::Error> {
+    let mut blocks = vec![];
+    let mut cursor = start_slot;
+    for _ in 0..MAX_LONG_TERM_STORAGE_BLOCKS_QUERIES {
+        if cursor > end_slot || blocks.len() >= limit {
+            return Ok((blocks, true));
+        }
+        let page_size = min(limit - blocks.len(), LONG_TERM_STORAGE_BLOCKS_PAGE_SIZE);
+        let page = bigtable_ledger_storage
+            .get_confirmed_blocks(cursor, page_size)
+            .await?;
+        let is_last_page = page.len() < page_size;
+        let last_slot = match page.last() {
+            Some(last_slot) => *last_slot,
+            None => return Ok((blocks, true)),
+        };
+        blocks.extend(page.into_iter().filter(|&slot| slot <= end_slot));
+        if is_last_page || last_slot >= end_slot {
+            return Ok((blocks, true));
+        }
+        cursor = last_slot + 1;
+    }
+    Ok((blocks, cursor > end_slot || blocks.len() >= limit))
+}
+
 fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     if !commitment.is_at_least_confirmed() {
         return Err(Error::invalid_params(
@@ -2085,6 +2683,21 @@ fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     Ok(())
 }
 
+// The last modified slots of accounts are only available from the accounts index, one account at
+// a time, so scans of program and token accounts reject the option rather than ignore it
+pub(crate) fn check_last_modified_slot_unsupported(
+    with_last_modified_slot: Option<bool>,
+) -> Result<()> {
+    if with_last_modified_slot.unwrap_or_default() {
+        Err(Error::invalid_params(
+            "withLastModifiedSlot is only supported by getAccountInfo, getMultipleAccounts and \
+             accountSubscribe",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 fn check_slice_and_encoding(encoding: &UiAccountEncoding, data_slice_is_some: bool) -> Result<()> {
     match encoding {
         UiAccountEncoding::JsonParsed => {
@@ -2113,16 +2726,20 @@ fn get_encoded_account(
     pubkey: &Pubkey,
     encoding: UiAccountEncoding,
     data_slice: Option<UiDataSliceConfig>,
+    with_last_modified_slot: bool,
 ) -> Result<Option<UiAccount>> {
-    match bank.get_account(pubkey) {
-        Some(account) => {
-            let response = if account.owner() == &spl_token_id_v2_0()
+    match bank.get_account_modified_slot(pubkey) {
+        Some((account, last_modified_slot)) => {
+            let mut response = if account.owner() == &spl_token_id_v2_0()
                 && encoding == UiAccountEncoding::JsonParsed
             {
                 get_parsed_token_account(bank.clone(), pubkey, account)
             } else {
                 encode_account(&account, pubkey, encoding, data_slice)?
             };
+            if with_last_modified_slot {
+                response.last_modified_slot = Some(last_modified_slot);
+            }
             Ok(Some(response))
         }
         None => Ok(None),
@@ -2274,6 +2891,13 @@ pub mod rpc_minimal {
             commitment: Option<CommitmentConfig>,
         ) -> Result<RpcResponse<u64>>;
 
+        #[rpc(meta, name = "getEpochAccountsHash")]
+        fn get_epoch_accounts_hash(
+            &self,
+            meta: Self::Metadata,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcEpochAccountsHash>>;
+
         #[rpc(meta, name = "getEpochInfo")]
         fn get_epoch_info(
             &self,
@@ -2349,6 +2973,21 @@ pub mod rpc_minimal {
             Ok(meta.get_balance(&pubkey, commitment))
         }
 
+        fn get_epoch_accounts_hash(
+            &self,
+            meta: Self::Metadata,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcEpochAccountsHash>> {
+            debug!("get_epoch_accounts_hash rpc request received");
+            let bank = meta.bank(commitment);
+            Ok(bank
+                .epoch_accounts_hash()
+                .map(|epoch_accounts_hash| RpcEpochAccountsHash {
+                    slot: epoch_accounts_hash.slot,
+                    hash: epoch_accounts_hash.hash.to_string(),
+                }))
+        }
+
         fn get_epoch_info(
             &self,
             meta: Self::Metadata,
@@ -2742,6 +3381,22 @@ pub mod rpc_accounts {
             config: Option<RpcProgramAccountsConfig>,
         ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>>;
 
+        #[rpc(meta, name = "getProgramAccountsByPrefix")]
+        fn get_program_accounts_by_prefix(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            prefix_str: String,
+            config: Option<RpcProgramAccountsConfig>,
+        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>>;
+
+        #[rpc(meta, name = "getFeatureStatuses")]
+        fn get_feature_statuses(
+            &self,
+            meta: Self::Metadata,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<RpcResponse<Vec<RpcFeatureStatus>>>;
+
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
             &self,
@@ -2796,7 +3451,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             mint_str: String,
-            commitment: Option<CommitmentConfig>,
+            config: Option<RpcTokenLargestAccountsConfig>,
         ) -> Result<RpcResponse<Vec<RpcTokenAccountBalance>>>;
 
         #[rpc(meta, name = "getTokenAccountsByOwner")]
@@ -2893,6 +3548,62 @@ pub mod rpc_accounts {
             meta.get_program_accounts(&program_id, config, filters, with_context)
         }
 
+        fn get_program_accounts_by_prefix(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            prefix_str: String,
+            config: Option<RpcProgramAccountsConfig>,
+        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+            debug!(
+                "get_program_accounts_by_prefix rpc request received: {:?} {:?}",
+                program_id_str, prefix_str
+            );
+            let program_id = verify_pubkey(&program_id_str)?;
+            let prefix = bs58::decode(&prefix_str)
+                .into_vec()
+                .map_err(|e| Error::invalid_params(format!("Invalid param: {:?}", e)))?;
+            if prefix.is_empty() {
+                return Err(Error::invalid_params(
+                    "Prefix is empty; use getProgramAccounts to scan every account".to_string(),
+                ));
+            }
+            if prefix.len() > PUBKEY_BYTES {
+                return Err(Error::invalid_params(format!(
+                    "Prefix too long; max {} bytes",
+                    PUBKEY_BYTES
+                )));
+            }
+            let (config, filters, with_context) = if let Some(config) = config {
+                (
+                    Some(config.account_config),
+                    config.filters.unwrap_or_default(),
+                    config.with_context.unwrap_or_default(),
+                )
+            } else {
+                (None, vec![], false)
+            };
+            if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+                return Err(Error::invalid_params(format!(
+                    "Too many filters provided; max {}",
+                    MAX_GET_PROGRAM_ACCOUNT_FILTERS
+                )));
+            }
+            for filter in &filters {
+                verify_filter(filter)?;
+            }
+            meta.get_program_accounts_by_prefix(&program_id, &prefix, config, filters, with_context)
+        }
+
+        fn get_feature_statuses(
+            &self,
+            meta: Self::Metadata,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<RpcResponse<Vec<RpcFeatureStatus>>> {
+            debug!("get_feature_statuses rpc request received");
+            meta.get_feature_statuses(commitment)
+        }
+
         fn get_block_commitment(
             &self,
             meta: Self::Metadata,
@@ -2963,14 +3674,14 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             mint_str: String,
-            commitment: Option<CommitmentConfig>,
+            config: Option<RpcTokenLargestAccountsConfig>,
         ) -> Result<RpcResponse<Vec<RpcTokenAccountBalance>>> {
             debug!(
                 "get_token_largest_accounts rpc request received: {:?}",
                 mint_str
             );
             let mint = verify_pubkey(&mint_str)?;
-            meta.get_token_largest_accounts(&mint, commitment)
+            meta.get_token_largest_accounts(&mint, config)
         }
 
         fn get_token_accounts_by_owner(
@@ -3033,6 +3744,14 @@ pub mod rpc_full {
             limit: Option<usize>,
         ) -> Result<Vec<RpcPerfSample>>;
 
+        #[rpc(meta, name = "getEquivocationEvidence")]
+        fn get_equivocation_evidence(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Option<Slot>,
+            limit: Option<usize>,
+        ) -> Result<Vec<RpcEquivocationEvidence>>;
+
         #[rpc(meta, name = "getGenesisHash")]
         fn get_genesis_hash(&self, meta: Self::Metadata) -> Result<String>;
 
@@ -3125,7 +3844,7 @@ pub mod rpc_full {
             meta: Self::Metadata,
             address: String,
             config: Option<RpcSignaturesForAddressConfig>,
-        ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>;
+        ) -> BoxFuture<Result<RpcSignaturesForAddress>>;
 
         #[rpc(meta, name = "getFirstAvailableBlock")]
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>>;
@@ -3191,6 +3910,60 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_equivocation_evidence(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Option<Slot>,
+            limit: Option<usize>,
+        ) -> Result<Vec<RpcEquivocationEvidence>> {
+            debug!("get_equivocation_evidence request received");
+
+            let limit = limit.unwrap_or(EQUIVOCATION_EVIDENCE_LIMIT);
+
+            if limit > EQUIVOCATION_EVIDENCE_LIMIT {
+                return Err(Error::invalid_params(format!(
+                    "Invalid limit; max {}",
+                    EQUIVOCATION_EVIDENCE_LIMIT
+                )));
+            }
+
+            meta.blockstore
+                .get_equivocation_evidence_from_slot(start_slot.unwrap_or_default(), limit)
+                .map_err(|err| {
+                    warn!("get_equivocation_evidence failed: {:?}", err);
+                    Error::invalid_request()
+                })?
+                .into_iter()
+                .map(|(slot, offender, evidence)| -> bincode::Result<_> {
+                    let (kind, proof) = match evidence {
+                        EquivocationEvidence::DuplicateBlock { shred1, shred2 } => {
+                            (RpcEquivocationKind::DuplicateBlock, vec![shred1, shred2])
+                        }
+                        EquivocationEvidence::ConflictingVotes {
+                            vote_transaction1,
+                            vote_transaction2,
+                        } => (
+                            RpcEquivocationKind::ConflictingVotes,
+                            vec![
+                                serialize(&vote_transaction1)?,
+                                serialize(&vote_transaction2)?,
+                            ],
+                        ),
+                    };
+                    Ok(RpcEquivocationEvidence {
+                        slot,
+                        offender: offender.to_string(),
+                        kind,
+                        proof: proof.iter().map(base64::encode).collect(),
+                    })
+                })
+                .collect::<bincode::Result<Vec<_>>>()
+                .map_err(|err| {
+                    warn!("get_equivocation_evidence failed: {:?}", err);
+                    Error::internal_error()
+                })
+        }
+
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
             debug!("get_cluster_nodes rpc request received");
             let cluster_info = &meta.cluster_info;
@@ -3351,7 +4124,7 @@ pub mod rpc_full {
                 .preflight_commitment
                 .map(|commitment| CommitmentConfig { commitment });
             let preflight_bank = &*meta.bank(preflight_commitment);
-            let transaction = sanitize_transaction(unsanitized_tx)?;
+            let transaction = sanitize_transaction(unsanitized_tx, preflight_bank)?;
             let signature = *transaction.signature();
 
             let mut last_valid_block_height = preflight_bank
@@ -3370,8 +4143,11 @@ pub mod rpc_full {
                     preflight_bank.block_height() + MAX_RECENT_BLOCKHASHES as u64;
             }
 
-            if !config.skip_preflight {
+            if config.skip_preflight {
+                inc_new_counter_info!("rpc-send-tx_skip-preflight", 1);
+            } else {
                 if let Err(e) = verify_transaction(&transaction, &preflight_bank.feature_set) {
+                    inc_new_counter_info!("rpc-send-tx_err-verification", 1);
                     return Err(e);
                 }
 
@@ -3395,26 +4171,26 @@ pub mod rpc_full {
 
                 if let TransactionSimulationResult {
                     result: Err(err),
-                    logs,
+                    log_entries,
                     post_simulation_accounts: _,
                     units_consumed,
+                    return_data,
+                    inner_instructions_invoke_units,
                 } = preflight_bank.simulate_transaction(transaction)
                 {
-                    match err {
-                        TransactionError::BlockhashNotFound => {
-                            inc_new_counter_info!("rpc-send-tx_err-blockhash-not-found", 1);
-                        }
-                        _ => {
-                            inc_new_counter_info!("rpc-send-tx_err-other", 1);
-                        }
-                    }
+                    inc_preflight_failure_counter(&err);
                     return Err(RpcCustomError::SendTransactionPreflightFailure {
                         message: format!("Transaction simulation failed: {}", err),
                         result: RpcSimulateTransactionResult {
                             err: Some(err),
-                            logs: Some(logs),
+                            logs: Some(log_messages(&log_entries)),
                             accounts: None,
                             units_consumed: Some(units_consumed),
+                            return_data: return_data.map(|return_data| return_data.into()),
+                            log_entries: Some(
+                                log_entries.into_iter().map(|entry| entry.into()).collect(),
+                            ),
+                            inner_instructions_invoke_units: Some(inner_instructions_invoke_units),
                         },
                     }
                     .into());
@@ -3455,16 +4231,18 @@ pub mod rpc_full {
                     .set_recent_blockhash(bank.last_blockhash());
             }
 
-            let transaction = sanitize_transaction(unsanitized_tx)?;
+            let transaction = sanitize_transaction(unsanitized_tx, bank)?;
             if config.sig_verify {
                 verify_transaction(&transaction, &bank.feature_set)?;
             }
 
             let TransactionSimulationResult {
                 result,
-                logs,
+                log_entries,
                 post_simulation_accounts,
                 units_consumed,
+                return_data,
+                inner_instructions_invoke_units,
             } = bank.simulate_transaction(transaction);
 
             let accounts = if let Some(config_accounts) = config.accounts {
@@ -3508,9 +4286,12 @@ pub mod rpc_full {
                 bank,
                 RpcSimulateTransactionResult {
                     err: result.err(),
-                    logs: Some(logs),
+                    logs: Some(log_messages(&log_entries)),
                     accounts,
                     units_consumed: Some(units_consumed),
+                    return_data: return_data.map(|return_data| return_data.into()),
+                    log_entries: Some(log_entries.into_iter().map(|entry| entry.into()).collect()),
+                    inner_instructions_invoke_units: Some(inner_instructions_invoke_units),
                 },
             ))
         }
@@ -3593,9 +4374,15 @@ pub mod rpc_full {
             meta: Self::Metadata,
             address: String,
             config: Option<RpcSignaturesForAddressConfig>,
-        ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>> {
+        ) -> BoxFuture<Result<RpcSignaturesForAddress>> {
             let config = config.unwrap_or_default();
             let commitment = config.commitment;
+            if config.memo.as_deref() == Some("") {
+                return Box::pin(future::err(Error::invalid_params(
+                    "Invalid memo; must not be empty",
+                )));
+            }
+            let memo = config.memo;
             let verification = verify_and_parse_signatures_for_address_params(
                 address,
                 config.before,
@@ -3606,8 +4393,18 @@ pub mod rpc_full {
             match verification {
                 Err(err) => Box::pin(future::err(err)),
                 Ok((address, before, until, limit)) => Box::pin(async move {
-                    meta.get_signatures_for_address(address, before, until, limit, commitment)
-                        .await
+                    let is_memo_filtered = memo.is_some();
+                    let (signatures, before) = meta
+                        .get_signatures_for_address(address, before, until, limit, memo, commitment)
+                        .await?;
+                    Ok(if is_memo_filtered {
+                        RpcSignaturesForAddress::Page(RpcSignaturesForAddressPage {
+                            signatures,
+                            before: before.map(|signature| signature.to_string()),
+                        })
+                    } else {
+                        RpcSignaturesForAddress::Signatures(signatures)
+                    })
                 }),
             }
         }
@@ -3669,12 +4466,8 @@ pub mod rpc_full {
         ) -> Result<RpcResponse<Option<u64>>> {
             debug!("get_fee_for_message rpc request received");
             let (_, message) =
-                decode_and_deserialize::<Message>(data, UiTransactionEncoding::Base64)?;
-            SanitizedMessage::try_from(message)
-                .map_err(|err| {
-                    Error::invalid_params(format!("invalid transaction message: {}", err))
-                })
-                .and_then(|message| meta.get_fee_for_message(&message, commitment))
+                decode_and_deserialize::<VersionedMessage>(data, UiTransactionEncoding::Base64)?;
+            meta.get_fee_for_message(message, commitment)
         }
     }
 }
@@ -3922,8 +4715,9 @@ pub mod rpc_deprecated_v1_7 {
             match verification {
                 Err(err) => Box::pin(future::err(err)),
                 Ok((address, before, until, limit)) => Box::pin(async move {
-                    meta.get_signatures_for_address(address, before, until, limit, commitment)
+                    meta.get_signatures_for_address(address, before, until, limit, None, commitment)
                         .await
+                        .map(|(signatures, _before)| signatures)
                 }),
             }
         }
@@ -4135,14 +4929,45 @@ where
         .map(|output| (wire_output, output))
 }
 
-fn sanitize_transaction(transaction: VersionedTransaction) -> Result<SanitizedTransaction> {
+fn sanitize_transaction(
+    transaction: VersionedTransaction,
+    bank: &Bank,
+) -> Result<SanitizedTransaction> {
     let message_hash = transaction.message.hash();
-    SanitizedTransaction::try_create(transaction, message_hash, |_| {
-        Err(TransactionError::UnsupportedVersion)
+    SanitizedTransaction::try_create(transaction, message_hash, |address_table_lookups| {
+        bank.load_lookup_table_addresses(address_table_lookups)
     })
     .map_err(|err| Error::invalid_params(format!("invalid transaction: {}", err)))
 }
 
+/// Sanitize a legacy or v0 message, loading the addresses of a v0 message's lookup tables from
+/// `bank`
+fn sanitize_message(message: VersionedMessage, bank: &Bank) -> Result<SanitizedMessage> {
+    let invalid_message = |err: &dyn std::fmt::Display| {
+        Error::invalid_params(format!("invalid transaction message: {}", err))
+    };
+    let message = match message {
+        VersionedMessage::Legacy(message) => {
+            return SanitizedMessage::try_from(message).map_err(|err| invalid_message(&err));
+        }
+        VersionedMessage::V0(message) => {
+            message
+                .sanitize()
+                .map_err(|err| invalid_message(&SanitizeMessageError::from(err)))?;
+            SanitizedMessage::V0(LoadedMessage {
+                loaded_addresses: bank
+                    .load_lookup_table_addresses(&message.address_table_lookups)
+                    .map_err(|err| invalid_message(&err))?,
+                message,
+            })
+        }
+    };
+    if message.has_duplicates() {
+        return Err(invalid_message(&SanitizeMessageError::DuplicateAccountKey));
+    }
+    Ok(message)
+}
+
 pub(crate) fn create_validator_exit(exit: &Arc<AtomicBool>) -> Arc<RwLock<Exit>> {
     let mut validator_exit = Exit::default();
     let exit_ = exit.clone();
@@ -4203,6 +5028,8 @@ pub fn create_test_transactions_and_populate_blockstore(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
             blockstore,
+            None,
+            Arc::default(),
             &Arc::new(AtomicBool::new(false)),
         );
 
@@ -4216,6 +5043,13 @@ pub fn create_test_transactions_and_populate_blockstore(
             &solana_ledger::blockstore_processor::TransactionStatusSender {
                 sender: transaction_status_sender,
                 enable_cpi_and_log_storage: false,
+                token_program_ids: Arc::new(
+                    solana_transaction_status::token_balances::default_token_program_ids(),
+                ),
+                overflow_policy:
+                    solana_ledger::blockstore_processor::TransactionStatusOverflowPolicy::Block,
+                spill: None,
+                dropped_slots: Arc::default(),
             },
         ),
         Some(&replay_vote_sender),
@@ -4250,15 +5084,16 @@ pub mod tests {
         },
         solana_runtime::{
             accounts_background_service::AbsRequestSender, commitment::BlockCommitment,
-            non_circulating_supply::non_circulating_accounts,
+            genesis_utils::activate_all_features, non_circulating_supply::non_circulating_accounts,
         },
         solana_sdk::{
             account::Account,
             clock::MAX_RECENT_BLOCKHASHES,
+            compute_budget::ComputeBudgetInstruction,
             fee_calculator::DEFAULT_BURN_PERCENT,
             hash::{hash, Hash},
             instruction::InstructionError,
-            message::Message,
+            message::{v0, Message},
             nonce, rpc_port,
             signature::{Keypair, Signer},
             system_program, system_transaction,
@@ -4488,16 +5323,83 @@ pub mod tests {
         }
     }
 
-    #[test]
-    fn test_rpc_request_processor_new() {
-        let bob_pubkey = solana_sdk::pubkey::new_rand();
-        let genesis = create_genesis_config(100);
-        let bank = Arc::new(Bank::new_for_tests(&genesis.genesis_config));
-        bank.transfer(20, &genesis.mint_keypair, &bob_pubkey)
-            .unwrap();
-        let request_processor =
-            JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
-        assert_eq!(request_processor.get_transaction_count(None), 1);
+    #[test]
+    fn test_rpc_request_processor_new() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let genesis = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis.genesis_config));
+        bank.transfer(20, &genesis.mint_keypair, &bob_pubkey)
+            .unwrap();
+        let request_processor =
+            JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        assert_eq!(request_processor.get_transaction_count(None), 1);
+    }
+
+    #[test]
+    fn test_rpc_get_fee_for_message() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(TEST_MINT_LAMPORTS);
+        activate_all_features(&mut genesis_config);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+        let mut io = MetaIoHandler::default();
+        io.extend_with(rpc_full::FullImpl.to_delegate());
+
+        let get_fee = |message: &VersionedMessage| {
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getFeeForMessage",
+                "params": [base64::encode(serialize(message).unwrap())],
+            })
+            .to_string();
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            result["result"]["value"].as_u64()
+        };
+
+        // The fee includes the prioritization fee of the compute unit price
+        let legacy_message = Message::new_with_blockhash(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+                system_instruction::transfer(&mint_keypair.pubkey(), &Pubkey::new_unique(), 42),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &bank.last_blockhash(),
+        );
+        let expected_fee =
+            bank.get_fee_for_message(&SanitizedMessage::try_from(legacy_message.clone()).unwrap());
+        assert!(expected_fee > bank.get_lamports_per_signature());
+        assert_eq!(
+            get_fee(&VersionedMessage::Legacy(legacy_message.clone())),
+            Some(expected_fee)
+        );
+
+        let mut v0_message = v0::Message {
+            header: legacy_message.header,
+            account_keys: legacy_message.account_keys,
+            recent_blockhash: legacy_message.recent_blockhash,
+            instructions: legacy_message.instructions,
+            address_table_lookups: vec![],
+        };
+        assert_eq!(
+            get_fee(&VersionedMessage::V0(v0_message.clone())),
+            Some(expected_fee)
+        );
+
+        // The lookup tables of a v0 message must exist
+        v0_message
+            .address_table_lookups
+            .push(v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            });
+        assert_eq!(get_fee(&VersionedMessage::V0(v0_message)), None);
     }
 
     #[test]
@@ -4639,6 +5541,142 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_rpc_get_equivocation_evidence() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let leader_pubkey = solana_sdk::pubkey::new_rand();
+        let evidence = EquivocationEvidence::DuplicateBlock {
+            shred1: vec![1, 2, 3],
+            shred2: vec![4, 5, 6],
+        };
+        meta.blockstore
+            .store_equivocation_evidence(7, &leader_pubkey, &evidence)
+            .unwrap();
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getEquivocationEvidence"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Response = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [
+                {
+                    "slot": 7,
+                    "offender": leader_pubkey.to_string(),
+                    "kind": "duplicateBlock",
+                    "proof": ["AQID", "BAUG"]
+                }
+            ],
+        });
+        let expected: Response =
+            serde_json::from_value(expected).expect("expected response deserialization");
+        assert_eq!(expected, result);
+
+        // Evidence before the start slot is skipped
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getEquivocationEvidence","params":[8]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], json!([]));
+
+        let req =
+            r#"{"jsonrpc":"2.0","id":1,"method":"getEquivocationEvidence","params":[0, 10000]}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["error"]["message"], "Invalid limit; max 1000");
+    }
+
+    #[test]
+    fn test_non_circulating_supply_cache() {
+        let non_circulating_supply = |lamports| {
+            Arc::new(NonCirculatingSupply {
+                lamports,
+                accounts: vec![Pubkey::new_unique()],
+            })
+        };
+        let mut cache = NonCirculatingSupplyCache::default();
+        assert!(cache.get(0, 0).is_none());
+        cache.insert(1, 100, non_circulating_supply(42));
+        assert_eq!(cache.get(1, 100).unwrap().lamports, 42);
+        assert_eq!(cache.get(1, 50).unwrap().lamports, 42);
+        assert_eq!(
+            cache
+                .get(1, 100 + NON_CIRCULATING_SUPPLY_REFRESH_SLOTS - 1)
+                .unwrap()
+                .lamports,
+            42
+        );
+
+        // Stale once too many slots away or in another epoch
+        assert!(cache
+            .get(1, 100 + NON_CIRCULATING_SUPPLY_REFRESH_SLOTS)
+            .is_none());
+        assert!(cache.get(2, 100).is_none());
+
+        cache.insert(2, 200, non_circulating_supply(7));
+        assert!(cache.get(1, 100).is_none());
+        assert_eq!(cache.get(2, 200).unwrap().lamports, 7);
+
+        // A supply computed for an older slot doesn't replace a newer one
+        cache.insert(1, 150, non_circulating_supply(3));
+        assert_eq!(cache.get(2, 200).unwrap().lamports, 7);
+    }
+
+    #[test]
+    fn test_epoch_rewards_cache() {
+        let epoch_rewards_with_len = |lamports, len| {
+            let mut epoch_rewards = EpochRewards::new();
+            for _ in 0..len {
+                let reward = Reward {
+                    pubkey: Pubkey::new_unique().to_string(),
+                    lamports,
+                    post_balance: 42,
+                    reward_type: Some(RewardType::Staking),
+                    commission: Some(10),
+                };
+                epoch_rewards.insert(reward.pubkey.clone(), (1, reward));
+            }
+            Arc::new(epoch_rewards)
+        };
+        let epoch_rewards = |lamports| epoch_rewards_with_len(lamports, 1);
+
+        let mut cache = EpochRewardsCache::default();
+        assert!(cache.get(0).is_none());
+        let first_epoch_rewards = epoch_rewards(1);
+        cache.insert(0, first_epoch_rewards.clone());
+        assert_eq!(cache.get(0), Some(first_epoch_rewards));
+
+        // the oldest epochs are evicted once full
+        for epoch in 1..=MAX_CACHED_EPOCH_REWARDS as Epoch {
+            cache.insert(epoch, epoch_rewards(epoch as i64));
+        }
+        assert!(cache.get(0).is_none());
+        assert_eq!(cache.epochs.len(), MAX_CACHED_EPOCH_REWARDS);
+        assert_eq!(cache.num_entries, MAX_CACHED_EPOCH_REWARDS);
+        assert!(cache.get(MAX_CACHED_EPOCH_REWARDS as Epoch).is_some());
+
+        // the oldest epochs are evicted once too many rewards are cached, and an epoch with
+        // more rewards than the cache holds isn't cached
+        let mut cache = EpochRewardsCache::new(4);
+        cache.insert(0, epoch_rewards_with_len(1, 2));
+        cache.insert(1, epoch_rewards_with_len(1, 2));
+        assert_eq!(cache.num_entries, 4);
+        cache.insert(2, epoch_rewards_with_len(1, 1));
+        assert!(cache.get(0).is_none());
+        assert!(cache.get(1).is_some());
+        assert_eq!(cache.num_entries, 3);
+        cache.insert(2, epoch_rewards_with_len(1, 2));
+        assert_eq!(cache.num_entries, 4);
+        cache.insert(3, epoch_rewards_with_len(1, 5));
+        assert!(cache.get(3).is_none());
+        assert_eq!(cache.num_entries, 4);
+    }
+
     #[test]
     fn test_rpc_get_slot_leader() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -4903,6 +5941,95 @@ pub mod tests {
         assert_eq!(epoch_schedule, *bank.epoch_schedule());
     }
 
+    #[test]
+    fn test_rpc_get_feature_statuses() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, bank, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let known_pending_feature = feature_set::require_rent_exempt_accounts::id();
+        let known_active_feature = feature_set::pico_inflation::id();
+        let unknown_active_feature = solana_sdk::pubkey::new_rand();
+        let activation_slot = bank.epoch_schedule().get_first_slot_in_epoch(1) + 1;
+        bank.store_account(
+            &known_pending_feature,
+            &feature::create_account(&Feature { activated_at: None }, 42),
+        );
+        for feature_id in [&known_active_feature, &unknown_active_feature] {
+            bank.store_account(
+                feature_id,
+                &feature::create_account(
+                    &Feature {
+                        activated_at: Some(activation_slot),
+                    },
+                    42,
+                ),
+            );
+        }
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getFeatureStatuses"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let feature_statuses: Vec<RpcFeatureStatus> =
+            serde_json::from_value(result["result"]["value"].clone()).unwrap();
+        // Only the features known to the node are looked up
+        assert_eq!(feature_statuses.len(), FEATURE_NAMES.len());
+        assert!(feature_statuses
+            .iter()
+            .all(|feature_status| feature_status.id != unknown_active_feature.to_string()));
+        let feature_status = |feature_id: &Pubkey| {
+            feature_statuses
+                .iter()
+                .find(|feature_status| feature_status.id == feature_id.to_string())
+                .unwrap()
+                .clone()
+        };
+
+        assert_eq!(
+            feature_status(&known_pending_feature),
+            RpcFeatureStatus {
+                id: known_pending_feature.to_string(),
+                description: FEATURE_NAMES[&known_pending_feature].to_string(),
+                status: RpcFeatureActivationStatus::Pending,
+                activation_slot: None,
+                activation_epoch: None,
+            }
+        );
+        assert_eq!(
+            feature_status(&known_active_feature),
+            RpcFeatureStatus {
+                id: known_active_feature.to_string(),
+                description: FEATURE_NAMES[&known_active_feature].to_string(),
+                status: RpcFeatureActivationStatus::Active,
+                activation_slot: Some(activation_slot),
+                activation_epoch: Some(1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_epoch_accounts_hash() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getEpochAccountsHash"}"#;
+        let rep = io.handle_request_sync(req, meta);
+        let res: Response = serde_json::from_str(&rep.expect("actual response"))
+            .expect("actual response deserialization");
+
+        let epoch_accounts_hash: Option<RpcEpochAccountsHash> = if let Response::Single(res) = res {
+            if let Output::Success(res) = res {
+                serde_json::from_value(res.result).unwrap()
+            } else {
+                panic!("Expected success");
+            }
+        } else {
+            panic!("Expected single response");
+        };
+        // No epoch accounts hash has been calculated yet
+        assert_eq!(epoch_accounts_hash, None);
+    }
+
     #[test]
     fn test_rpc_get_leader_schedule() {
         let bob_pubkey = solana_sdk::pubkey::new_rand();
@@ -5091,6 +6218,19 @@ pub mod tests {
             result["result"]["value"]["data"],
             json!([base64::encode(&data), "base64"]),
         );
+        assert_eq!(result["result"]["value"]["lastModifiedSlot"], Value::Null);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"base64", "withLastModifiedSlot": true}}]}}"#,
+            address
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["result"]["value"]["lastModifiedSlot"],
+            json!(bank.slot()),
+        );
 
         let req = format!(
             r#"{{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["{}", {{"encoding":"base64", "dataSlice": {{"length": 2, "offset": 1}}}}]}}"#,
@@ -5309,6 +6449,15 @@ pub mod tests {
             .contains_key("slot");
         assert!(contains_slot);
 
+        // Test rejects the last modified slot
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccounts","params":["{}",{{"withLastModifiedSlot":true}}]}}"#,
+            new_program_id
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response")).unwrap();
+        assert_eq!(result["error"]["code"], ErrorCode::InvalidParams.code());
+
         // Set up nonce accounts to test filters
         let nonce_keypair0 = Keypair::new();
         let instruction = system_instruction::create_nonce_account(
@@ -5463,6 +6612,118 @@ pub mod tests {
         assert_eq!(accounts.len(), 0);
     }
 
+    #[test]
+    fn test_get_filtered_program_accounts_data_size() {
+        let genesis = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis.genesis_config));
+        let program_id = solana_sdk::pubkey::new_rand();
+        let pubkeys: Vec<_> = (0..4).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        let account0 = AccountSharedData::from(Account {
+            lamports: 1,
+            data: vec![7; 10],
+            owner: program_id,
+            ..Account::default()
+        });
+        let account1 = AccountSharedData::from(Account {
+            lamports: 2,
+            data: vec![7; 20],
+            owner: program_id,
+            ..Account::default()
+        });
+        bank.store_account(&pubkeys[0], &account0);
+        bank.store_account(&pubkeys[1], &account1);
+        // Neither zero-lamport accounts nor accounts of other programs match the data size
+        bank.store_account(&pubkeys[2], &AccountSharedData::new(0, 10, &program_id));
+        bank.store_account(
+            &pubkeys[3],
+            &AccountSharedData::new(1, 10, &solana_sdk::pubkey::new_rand()),
+        );
+        let meta = JsonRpcRequestProcessor::new_from_bank(&bank, SocketAddrSpace::Unspecified);
+
+        // Data size filters scan the accounts' metadata, then load the matching accounts
+        let keyed_accounts = meta
+            .get_filtered_program_accounts(&bank, &program_id, vec![RpcFilterType::DataSize(10)])
+            .unwrap();
+        assert_eq!(keyed_accounts, vec![(pubkeys[0], account0.clone())]);
+        let keyed_accounts = meta
+            .get_filtered_program_accounts(
+                &bank,
+                &program_id,
+                vec![RpcFilterType::DataSize(20), RpcFilterType::DataSize(20)],
+            )
+            .unwrap();
+        assert_eq!(keyed_accounts, vec![(pubkeys[1], account1)]);
+        let keyed_accounts = meta
+            .get_filtered_program_accounts(
+                &bank,
+                &program_id,
+                vec![RpcFilterType::DataSize(10), RpcFilterType::DataSize(20)],
+            )
+            .unwrap();
+        assert!(keyed_accounts.is_empty());
+
+        // A memcmp filter goes through the full account scan
+        let keyed_accounts = meta
+            .get_filtered_program_accounts(
+                &bank,
+                &program_id,
+                vec![
+                    RpcFilterType::DataSize(10),
+                    RpcFilterType::Memcmp(Memcmp {
+                        offset: 0,
+                        bytes: MemcmpEncodedBytes::Bytes(vec![7]),
+                        encoding: None,
+                    }),
+                ],
+            )
+            .unwrap();
+        assert_eq!(keyed_accounts, vec![(pubkeys[0], account0)]);
+    }
+
+    #[test]
+    fn test_rpc_get_program_accounts_by_prefix() {
+        let bob = Keypair::new();
+        let RpcHandler {
+            io,
+            meta,
+            bank,
+            blockhash,
+            ..
+        } = start_rpc_handler_with_tx(&bob.pubkey());
+
+        let new_program_id = solana_sdk::pubkey::new_rand();
+        let tx = system_transaction::assign(&bob, blockhash, &new_program_id);
+        bank.process_transaction(&tx).unwrap();
+        let get_accounts = |prefix: &[u8]| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getProgramAccountsByPrefix","params":["{}","{}"]}}"#,
+                new_program_id,
+                bs58::encode(prefix).into_string(),
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            serde_json::from_str::<Value>(&res.expect("actual response")).unwrap()
+        };
+
+        let bob_bytes = bob.pubkey().to_bytes();
+        let json = get_accounts(&bob_bytes[..2]);
+        let accounts: Vec<RpcKeyedAccount> =
+            serde_json::from_value(json["result"].clone()).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].pubkey, bob.pubkey().to_string());
+
+        let other_prefix = [bob_bytes[0].wrapping_add(1)];
+        let json = get_accounts(&other_prefix);
+        let accounts: Vec<RpcKeyedAccount> =
+            serde_json::from_value(json["result"].clone()).unwrap();
+        assert!(accounts.is_empty());
+
+        // The prefix can't be empty or longer than an address
+        let json = get_accounts(&[]);
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+        let json = get_accounts(&[0; PUBKEY_BYTES + 1]);
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_rpc_simulate_transaction() {
         let RpcHandler {
@@ -5525,6 +6786,19 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
+                    "logEntries":[
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 invoke [1]"
+                        },
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 success"
+                        }
+                    ],
+                    "innerInstructionsInvokeUnits":[[]],
                     "unitsConsumed":0
                 }
             },
@@ -5611,6 +6885,19 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
+                    "logEntries":[
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 invoke [1]"
+                        },
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 success"
+                        }
+                    ],
+                    "innerInstructionsInvokeUnits":[[]],
                     "unitsConsumed":0
                 }
             },
@@ -5639,6 +6926,19 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
+                    "logEntries":[
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 invoke [1]"
+                        },
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 success"
+                        }
+                    ],
+                    "innerInstructionsInvokeUnits":[[]],
                     "unitsConsumed":0
                 }
             },
@@ -5688,6 +6988,8 @@ pub mod tests {
                     "err":"BlockhashNotFound",
                     "accounts":null,
                     "logs":[],
+                    "logEntries":[],
+                    "innerInstructionsInvokeUnits":[],
                     "unitsConsumed":0
                 }
             },
@@ -5717,6 +7019,19 @@ pub mod tests {
                         "Program 11111111111111111111111111111111 invoke [1]",
                         "Program 11111111111111111111111111111111 success"
                     ],
+                    "logEntries":[
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 invoke [1]"
+                        },
+                        {
+                            "programId": "11111111111111111111111111111111",
+                            "invokeDepth": 1,
+                            "message": "Program 11111111111111111111111111111111 success"
+                        }
+                    ],
+                    "innerInstructionsInvokeUnits":[[]],
                     "unitsConsumed":0
                 }
             },
@@ -6070,7 +7385,7 @@ pub mod tests {
         assert_eq!(
             res,
             Some(
-                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","logs":[],"unitsConsumed":0}},"id":1}"#.to_string(),
+                r#"{"jsonrpc":"2.0","error":{"code":-32002,"message":"Transaction simulation failed: Blockhash not found","data":{"accounts":null,"err":"BlockhashNotFound","innerInstructionsInvokeUnits":[],"logEntries":[],"logs":[],"unitsConsumed":0}},"id":1}"#.to_string(),
             )
         );
 
@@ -6547,6 +7862,7 @@ pub mod tests {
                 transaction_details: Some(TransactionDetails::Signatures),
                 rewards: Some(false),
                 commitment: None,
+                max_supported_transaction_version: None,
             })
         );
         let res = io.handle_request_sync(&req, meta.clone());
@@ -6568,6 +7884,7 @@ pub mod tests {
                 transaction_details: Some(TransactionDetails::None),
                 rewards: Some(true),
                 commitment: None,
+                max_supported_transaction_version: None,
             })
         );
         let res = io.handle_request_sync(&req, meta);
@@ -7100,6 +8417,64 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_vote_accounts_epoch_start_commission() {
+        let RpcHandler {
+            io,
+            meta,
+            bank,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&solana_sdk::pubkey::new_rand());
+        let vote_pubkey = leader_vote_keypair.pubkey();
+
+        let get_vote_account_info = || {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteAccounts","params":{}}}"#,
+                json!([RpcGetVoteAccountsConfig {
+                    vote_pubkey: Some(vote_pubkey.to_string()),
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..RpcGetVoteAccountsConfig::default()
+                }])
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            let vote_account_status: RpcVoteAccountStatus =
+                serde_json::from_value(result["result"].clone()).unwrap();
+            vote_account_status
+                .current
+                .into_iter()
+                .chain(vote_account_status.delinquent)
+                .find(|vote_account_info| vote_account_info.vote_pubkey == vote_pubkey.to_string())
+                .unwrap()
+        };
+
+        let mut vote_account = bank.get_account(&vote_pubkey).unwrap();
+        let mut vote_state = VoteState::from(&vote_account).unwrap();
+        let epoch_start_commission = vote_state.commission;
+        let vote_account_info = get_vote_account_info();
+        assert_eq!(vote_account_info.commission, epoch_start_commission);
+        assert_eq!(vote_account_info.epoch_start_commission, None);
+
+        // Change the commission during the epoch
+        let commission = (epoch_start_commission + 10) % 100;
+        vote_state.commission = commission;
+        VoteState::to(
+            &VoteStateVersions::new_current(vote_state),
+            &mut vote_account,
+        )
+        .unwrap();
+        bank.store_account(&vote_pubkey, &vote_account);
+
+        let vote_account_info = get_vote_account_info();
+        assert_eq!(vote_account_info.commission, commission);
+        assert_eq!(
+            vote_account_info.epoch_start_commission,
+            Some(epoch_start_commission)
+        );
+    }
+
     #[test]
     fn test_is_finalized() {
         let bank = Arc::new(Bank::default_for_tests());
@@ -7516,7 +8891,7 @@ pub mod tests {
             r#"{{"jsonrpc":"2.0","id":1,"method":"getTokenLargestAccounts","params":["{}"]}}"#,
             new_mint,
         );
-        let res = io.handle_request_sync(&req, meta);
+        let res = io.handle_request_sync(&req, meta.clone());
         let result: Value = serde_json::from_str(&res.expect("actual response"))
             .expect("actual response deserialization");
         let largest_accounts: Vec<RpcTokenAccountBalance> =
@@ -7544,6 +8919,41 @@ pub mod tests {
                 }
             ]
         );
+
+        // Test largest token accounts a page at a time
+        let get_page = |after: Option<String>| {
+            let config = RpcTokenLargestAccountsConfig {
+                limit: Some(1),
+                after,
+                ..RpcTokenLargestAccountsConfig::default()
+            };
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getTokenLargestAccounts",
+                "params": [new_mint.to_string(), config],
+            })
+            .to_string();
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            serde_json::from_value::<Vec<RpcTokenAccountBalance>>(result["result"]["value"].clone())
+                .map(|page| {
+                    page.into_iter()
+                        .map(|balance| balance.address)
+                        .collect::<Vec<_>>()
+                })
+        };
+        let first_page = get_page(None).unwrap();
+        assert_eq!(
+            first_page,
+            vec![token_with_different_mint_pubkey.to_string()]
+        );
+        let second_page = get_page(first_page.last().cloned()).unwrap();
+        assert_eq!(second_page, vec![token_with_smaller_balance.to_string()]);
+        assert!(get_page(second_page.last().cloned()).unwrap().is_empty());
+        // The cursor must be a token account of the mint
+        assert!(get_page(Some(new_mint.to_string())).is_err());
     }
 
     #[test]
@@ -7924,7 +9334,7 @@ pub mod tests {
                 .to_string(),
         );
         assert_eq!(
-            sanitize_transaction(unsanitary_versioned_tx).unwrap_err(),
+            sanitize_transaction(unsanitary_versioned_tx, &Bank::default_for_tests()).unwrap_err(),
             expect58
         );
     }
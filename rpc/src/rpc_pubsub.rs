@@ -2,6 +2,7 @@
 
 use {
     crate::{
+        rpc::check_last_modified_slot_unsupported,
         rpc_pubsub_service::PubSubConfig,
         rpc_subscription_tracker::{
             AccountSubscriptionParams, LogsSubscriptionKind, LogsSubscriptionParams,
@@ -17,7 +18,7 @@ use {
     solana_account_decoder::{UiAccount, UiAccountEncoding},
     solana_client::{
         rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
+            RpcAccountSubscribeConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
             RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         rpc_response::{
@@ -57,7 +58,7 @@ pub trait RpcSolPubSub {
         meta: Self::Metadata,
         subscriber: Subscriber<RpcResponse<UiAccount>>,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     );
 
     // Unsubscribe from account notification subscription.
@@ -235,7 +236,7 @@ mod internal {
         fn account_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountSubscribeConfig>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -334,6 +335,17 @@ impl RpcSolPubSubImpl {
     }
 
     fn subscribe(&self, params: SubscriptionParams) -> Result<SubscriptionId> {
+        if self.current_subscriptions.len() >= self.config.max_subscriptions_per_connection {
+            inc_new_counter_info!("rpc-pubsub-connection-subscription-limit-reached", 1);
+            return Err(Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!(
+                    "Subscription refused. Connection subscription limit of {} reached",
+                    self.config.max_subscriptions_per_connection
+                ),
+                data: None,
+            });
+        }
         let token = self
             .subscription_control
             .subscribe(params)
@@ -361,6 +373,9 @@ impl RpcSolPubSubImpl {
     }
 }
 
+const MAX_LOGS_SUBSCRIPTION_ADDRESSES: usize = 32;
+const MAX_LOGS_SUBSCRIPTION_CONTAINS: usize = 8;
+
 fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     param_str.parse::<T>().map_err(|_e| Error {
         code: ErrorCode::InvalidParams,
@@ -369,18 +384,65 @@ fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     })
 }
 
+fn verify_logs_addresses(keys: &[String], thing: &str) -> Result<Vec<Pubkey>> {
+    if keys.is_empty() || keys.len() > MAX_LOGS_SUBSCRIPTION_ADDRESSES {
+        return Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: format!(
+                "Invalid Request: Between 1 and {} {} addresses supported",
+                MAX_LOGS_SUBSCRIPTION_ADDRESSES, thing
+            ),
+            data: None,
+        });
+    }
+    let mut addresses = keys
+        .iter()
+        .map(|key| param::<Pubkey>(key, thing))
+        .collect::<Result<Vec<_>>>()?;
+    // Equivalent filters should share a subscription
+    addresses.sort();
+    addresses.dedup();
+    Ok(addresses)
+}
+
+fn verify_logs_contains(mut contains: Vec<String>) -> Result<Vec<String>> {
+    if contains.len() > MAX_LOGS_SUBSCRIPTION_CONTAINS
+        || contains.iter().any(|substring| substring.is_empty())
+    {
+        return Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: format!(
+                "Invalid Request: Up to {} non-empty contains substrings supported",
+                MAX_LOGS_SUBSCRIPTION_CONTAINS
+            ),
+            data: None,
+        });
+    }
+    contains.sort();
+    contains.dedup();
+    Ok(contains)
+}
+
 impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn account_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     ) -> Result<SubscriptionId> {
         let config = config.unwrap_or_default();
         let params = AccountSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
-            commitment: config.commitment.unwrap_or_default(),
-            data_slice: config.data_slice,
-            encoding: config.encoding.unwrap_or(UiAccountEncoding::Binary),
+            commitment: config.account_config.commitment.unwrap_or_default(),
+            data_slice: config.account_config.data_slice,
+            encoding: config
+                .account_config
+                .encoding
+                .unwrap_or(UiAccountEncoding::Binary),
+            changed_data_only: config.changed_data_only.unwrap_or_default(),
+            with_last_modified_slot: config
+                .account_config
+                .with_last_modified_slot
+                .unwrap_or_default(),
         };
         self.subscribe(SubscriptionParams::Account(params))
     }
@@ -395,6 +457,7 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         config: Option<RpcProgramAccountsConfig>,
     ) -> Result<SubscriptionId> {
         let config = config.unwrap_or_default();
+        check_last_modified_slot_unsupported(config.account_config.with_last_modified_slot)?;
         let params = ProgramSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
             filters: config.filters.unwrap_or_default(),
@@ -418,22 +481,20 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         filter: RpcTransactionLogsFilter,
         config: Option<RpcTransactionLogsConfig>,
     ) -> Result<SubscriptionId> {
+        let config = config.unwrap_or_default();
         let params = LogsSubscriptionParams {
             kind: match filter {
                 RpcTransactionLogsFilter::All => LogsSubscriptionKind::All,
                 RpcTransactionLogsFilter::AllWithVotes => LogsSubscriptionKind::AllWithVotes,
                 RpcTransactionLogsFilter::Mentions(keys) => {
-                    if keys.len() != 1 {
-                        return Err(Error {
-                            code: ErrorCode::InvalidParams,
-                            message: "Invalid Request: Only 1 address supported".into(),
-                            data: None,
-                        });
-                    }
-                    LogsSubscriptionKind::Single(param::<Pubkey>(&keys[0], "mentions")?)
+                    LogsSubscriptionKind::Mentions(verify_logs_addresses(&keys, "mentions")?)
+                }
+                RpcTransactionLogsFilter::ProgramIds(keys) => {
+                    LogsSubscriptionKind::ProgramIds(verify_logs_addresses(&keys, "programIds")?)
                 }
             },
-            commitment: config.and_then(|c| c.commitment).unwrap_or_default(),
+            commitment: config.commitment.unwrap_or_default(),
+            contains: verify_logs_contains(config.contains.unwrap_or_default())?,
         };
         self.subscribe(SubscriptionParams::Logs(params))
     }
@@ -510,8 +571,11 @@ mod tests {
         jsonrpc_core::{IoHandler, Response},
         serial_test::serial,
         solana_account_decoder::{parse_account_data::parse_account_data, UiAccountEncoding},
-        solana_client::rpc_response::{
-            ProcessedSignatureResult, ReceivedSignatureResult, RpcSignatureResult, SlotInfo,
+        solana_client::{
+            rpc_config::RpcAccountInfoConfig,
+            rpc_response::{
+                ProcessedSignatureResult, ReceivedSignatureResult, RpcSignatureResult, SlotInfo,
+            },
         },
         solana_runtime::{
             bank::Bank,
@@ -773,10 +837,14 @@ mod tests {
 
         rpc.account_subscribe(
             stake_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(encoding),
-                data_slice: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(encoding),
+                    data_slice: None,
+                    with_last_modified_slot: None,
+                },
+                changed_data_only: None,
             }),
         )
         .unwrap();
@@ -888,10 +956,14 @@ mod tests {
 
         rpc.account_subscribe(
             nonce_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(UiAccountEncoding::JsonParsed),
-                data_slice: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::JsonParsed),
+                    data_slice: None,
+                    with_last_modified_slot: None,
+                },
+                changed_data_only: None,
             }),
         )
         .unwrap();
@@ -1019,10 +1091,14 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    with_last_modified_slot: None,
+                },
+                changed_data_only: None,
             }),
         )
         .unwrap();
@@ -1070,10 +1146,14 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    with_last_modified_slot: None,
+                },
+                changed_data_only: None,
             }),
         )
         .unwrap();
@@ -16,19 +16,28 @@ use {
         io,
         net::SocketAddr,
         str,
-        sync::Arc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
         thread::{self, Builder, JoinHandle},
     },
     stream_cancel::{Trigger, Tripwire},
     thiserror::Error,
-    tokio::{net::TcpStream, pin, select, sync::broadcast},
-    tokio_util::compat::TokioAsyncReadCompatExt,
+    tokio::{
+        net::TcpStream,
+        pin, select,
+        sync::{broadcast, mpsc},
+    },
+    tokio_util::compat::{Compat, TokioAsyncReadCompatExt},
 };
 
 pub const MAX_ACTIVE_SUBSCRIPTIONS: usize = 1_000_000;
 pub const DEFAULT_QUEUE_CAPACITY_ITEMS: usize = 10_000_000;
 pub const DEFAULT_TEST_QUEUE_CAPACITY_ITEMS: usize = 100;
 pub const DEFAULT_QUEUE_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 10_000;
+pub const DEFAULT_MAX_CONNECTION_QUEUE_BYTES: usize = 64 * 1024 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct PubSubConfig {
@@ -36,6 +45,11 @@ pub struct PubSubConfig {
     pub max_active_subscriptions: usize,
     pub queue_capacity_items: usize,
     pub queue_capacity_bytes: usize,
+    /// The maximum number of subscriptions a single connection may hold
+    pub max_subscriptions_per_connection: usize,
+    /// Connections whose unsent notifications and responses exceed this size are
+    /// disconnected, so a slow client can't hold on to an unbounded amount of memory
+    pub max_connection_queue_bytes: usize,
 }
 
 impl Default for PubSubConfig {
@@ -45,6 +59,8 @@ impl Default for PubSubConfig {
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
             queue_capacity_items: DEFAULT_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_connection_queue_bytes: DEFAULT_MAX_CONNECTION_QUEUE_BYTES,
         }
     }
 }
@@ -56,6 +72,8 @@ impl PubSubConfig {
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
             queue_capacity_items: DEFAULT_TEST_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
+            max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+            max_connection_queue_bytes: DEFAULT_MAX_CONNECTION_QUEUE_BYTES,
         }
     }
 }
@@ -230,6 +248,50 @@ enum Error {
     Broadcast(#[from] broadcast::error::RecvError),
     #[error("client has lagged behind (notification is gone)")]
     NotificationIsGone,
+    #[error("client is too slow to consume its notifications ({0} bytes queued)")]
+    SlowConsumer(usize),
+}
+
+/// Notifications and responses waiting to be written to a connection
+struct OutboundQueue {
+    sender: mpsc::UnboundedSender<Arc<String>>,
+    queued_bytes: Arc<AtomicUsize>,
+    max_queued_bytes: usize,
+}
+
+impl OutboundQueue {
+    fn new(max_queued_bytes: usize) -> (Self, mpsc::UnboundedReceiver<Arc<String>>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let queue = Self {
+            sender,
+            queued_bytes: Arc::default(),
+            max_queued_bytes,
+        };
+        (queue, receiver)
+    }
+
+    fn push(&self, json: Arc<String>) -> Result<(), Error> {
+        let queued_bytes = self.queued_bytes.fetch_add(json.len(), Ordering::Relaxed) + json.len();
+        if queued_bytes > self.max_queued_bytes {
+            inc_new_counter_info!("rpc-pubsub-slow-consumer-disconnects", 1);
+            return Err(Error::SlowConsumer(queued_bytes));
+        }
+        // The receiver is only dropped along with the connection
+        let _ = self.sender.send(json);
+        Ok(())
+    }
+}
+
+async fn write_outbound(
+    mut sender: soketto::connection::Sender<Compat<TcpStream>>,
+    mut receiver: mpsc::UnboundedReceiver<Arc<String>>,
+    queued_bytes: Arc<AtomicUsize>,
+) -> Result<(), Error> {
+    while let Some(json) = receiver.recv().await {
+        sender.send_text(&*json).await?;
+        queued_bytes.fetch_sub(json.len(), Ordering::Relaxed);
+    }
+    Ok(())
 }
 
 async fn handle_connection(
@@ -245,7 +307,17 @@ async fn handle_connection(
         protocol: None,
     };
     server.send_response(&accept).await?;
-    let (mut sender, mut receiver) = server.into_builder().finish();
+    let (sender, mut receiver) = server.into_builder().finish();
+
+    // Writes happen in their own future, so that a client that doesn't keep up with its
+    // notifications grows its queue instead of stalling the broadcast receiver
+    let (outbound_queue, outbound_receiver) = OutboundQueue::new(config.max_connection_queue_bytes);
+    let writer = write_outbound(
+        sender,
+        outbound_receiver,
+        Arc::clone(&outbound_queue.queued_bytes),
+    );
+    pin!(writer);
 
     let mut broadcast_receiver = subscription_control.broadcast_receiver();
     let mut data = Vec::new();
@@ -279,9 +351,10 @@ async fn handle_connection(
 
                         // In both possible error cases (closed or lagged) we disconnect the client.
                         if let Some(json) = broadcast_handler.handle(result?)? {
-                            sender.send_text(&*json).await?;
+                            outbound_queue.push(json)?;
                         }
                     },
+                    result = &mut writer => return result,
                     _ = &mut tripwire => {
                         warn!("disconnecting websocket client: shutting down");
                         return Ok(())
@@ -300,7 +373,7 @@ async fn handle_connection(
         };
 
         if let Some(response) = json_rpc_handler.handle_request(data_str).await {
-            sender.send_text(&response).await?;
+            outbound_queue.push(Arc::new(response))?;
         }
         data.clear();
     }
@@ -380,4 +453,16 @@ mod tests {
         let thread = pubsub_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solana-pubsub");
     }
+
+    #[test]
+    fn test_outbound_queue_limit() {
+        let (queue, _receiver) = OutboundQueue::new(10);
+        queue.push(Arc::new("12345".to_string())).unwrap();
+        queue.push(Arc::new("67890".to_string())).unwrap();
+        assert_eq!(queue.queued_bytes.load(Ordering::Relaxed), 10);
+        assert!(matches!(
+            queue.push(Arc::new("x".to_string())),
+            Err(Error::SlowConsumer(11))
+        ));
+    }
 }
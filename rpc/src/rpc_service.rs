@@ -17,7 +17,7 @@ use {
         RequestMiddlewareAction, ServerBuilder,
     },
     regex::Regex,
-    solana_client::rpc_cache::LargestAccountsCache,
+    solana_client::{connection_cache::ConnectionCache, rpc_cache::LargestAccountsCache},
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         bigtable_upload_service::BigTableUploadService, blockstore::Blockstore,
@@ -378,7 +378,7 @@ impl JsonRpcService {
             config,
             snapshot_config.clone(),
             bank_forks.clone(),
-            block_commitment_cache,
+            block_commitment_cache.clone(),
             blockstore,
             validator_exit.clone(),
             health.clone(),
@@ -397,8 +397,10 @@ impl JsonRpcService {
         let _send_transaction_service = Arc::new(SendTransactionService::new_with_config(
             tpu_address,
             &bank_forks,
+            Some(&block_commitment_cache),
             leader_info,
             receiver,
+            Arc::new(ConnectionCache::default()),
             send_transaction_service_config,
         ));
 
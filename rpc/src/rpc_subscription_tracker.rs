@@ -9,7 +9,8 @@ use {
         bank_forks::BankForks,
     },
     solana_sdk::{
-        clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+        clock::Slot, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+        signature::Signature,
     },
     std::{
         collections::{
@@ -125,19 +126,36 @@ pub struct AccountSubscriptionParams {
     pub encoding: UiAccountEncoding,
     pub data_slice: Option<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
+    /// Skip notifications of changes to the account's lamports only
+    pub changed_data_only: bool,
+    /// Include the slot the account was last modified in
+    pub with_last_modified_slot: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LogsSubscriptionParams {
     pub kind: LogsSubscriptionKind,
     pub commitment: CommitmentConfig,
+    /// Substrings of which at least one must appear in the transaction's log messages, if any
+    pub contains: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LogsSubscriptionKind {
     All,
     AllWithVotes,
-    Single(Pubkey),
+    Mentions(Vec<Pubkey>),
+    ProgramIds(Vec<Pubkey>),
+}
+
+impl LogsSubscriptionKind {
+    fn addresses(&self) -> &[Pubkey] {
+        match self {
+            LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => &[],
+            LogsSubscriptionKind::Mentions(addresses)
+            | LogsSubscriptionKind::ProgramIds(addresses) => addresses,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -274,6 +292,9 @@ pub struct SubscriptionInfo {
     params: SubscriptionParams,
     method: &'static str,
     pub last_notified_slot: RwLock<Slot>,
+    /// Hash of the account data last notified, for account subscriptions that only notify
+    /// data changes
+    pub last_notified_data_hash: RwLock<Option<Hash>>,
     commitment: Option<CommitmentConfig>,
 }
 
@@ -304,7 +325,7 @@ pub enum Error {
 struct LogsSubscriptionsIndex {
     all_count: usize,
     all_with_votes_count: usize,
-    single_count: HashMap<Pubkey, usize>,
+    mentioned_count: HashMap<Pubkey, usize>,
 
     bank_forks: Arc<RwLock<BankForks>>,
 }
@@ -314,8 +335,10 @@ impl LogsSubscriptionsIndex {
         match params.kind {
             LogsSubscriptionKind::All => self.all_count += 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count += 1,
-            LogsSubscriptionKind::Single(key) => {
-                *self.single_count.entry(key).or_default() += 1;
+            LogsSubscriptionKind::Mentions(_) | LogsSubscriptionKind::ProgramIds(_) => {
+                for key in params.kind.addresses() {
+                    *self.mentioned_count.entry(*key).or_default() += 1;
+                }
             }
         }
         self.update_config();
@@ -325,15 +348,19 @@ impl LogsSubscriptionsIndex {
         match params.kind {
             LogsSubscriptionKind::All => self.all_count -= 1,
             LogsSubscriptionKind::AllWithVotes => self.all_with_votes_count -= 1,
-            LogsSubscriptionKind::Single(key) => match self.single_count.entry(key) {
-                Entry::Occupied(mut entry) => {
-                    *entry.get_mut() -= 1;
-                    if *entry.get() == 0 {
-                        entry.remove();
+            LogsSubscriptionKind::Mentions(_) | LogsSubscriptionKind::ProgramIds(_) => {
+                for key in params.kind.addresses() {
+                    match self.mentioned_count.entry(*key) {
+                        Entry::Occupied(mut entry) => {
+                            *entry.get_mut() -= 1;
+                            if *entry.get() == 0 {
+                                entry.remove();
+                            }
+                        }
+                        Entry::Vacant(_) => error!("missing entry in mentioned_count"),
                     }
                 }
-                Entry::Vacant(_) => error!("missing entry in single_count"),
-            },
+            }
         }
         self.update_config();
     }
@@ -352,7 +379,7 @@ impl LogsSubscriptionsIndex {
         } else {
             TransactionLogCollectorConfig {
                 filter: TransactionLogCollectorFilter::OnlyMentionedAddresses,
-                mentioned_addresses: self.single_count.keys().copied().collect(),
+                mentioned_addresses: self.mentioned_count.keys().copied().collect(),
             }
         };
 
@@ -384,7 +411,7 @@ impl SubscriptionsTracker {
             logs_subscriptions_index: LogsSubscriptionsIndex {
                 all_count: 0,
                 all_with_votes_count: 0,
-                single_count: HashMap::new(),
+                mentioned_count: HashMap::new(),
                 bank_forks,
             },
             by_signature: HashMap::new(),
@@ -398,10 +425,12 @@ impl SubscriptionsTracker {
         &mut self,
         params: SubscriptionParams,
         id: SubscriptionId,
-        last_notified_slot: impl FnOnce() -> Slot,
+        last_notified: impl FnOnce() -> (Slot, Option<Hash>),
     ) {
+        let (last_notified_slot, last_notified_data_hash) = last_notified();
         let info = Arc::new(SubscriptionInfo {
-            last_notified_slot: RwLock::new(last_notified_slot()),
+            last_notified_slot: RwLock::new(last_notified_slot),
+            last_notified_data_hash: RwLock::new(last_notified_data_hash),
             id,
             commitment: params.commitment(),
             method: params.method(),
@@ -645,7 +674,7 @@ mod tests {
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
         let mut tracker = SubscriptionsTracker::new(bank_forks);
 
-        tracker.subscribe(SubscriptionParams::Slot, 0.into(), || 0);
+        tracker.subscribe(SubscriptionParams::Slot, 0.into(), || (0, None));
         let info = tracker
             .node_progress_watchers
             .get(&SubscriptionParams::Slot)
@@ -661,8 +690,10 @@ mod tests {
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            changed_data_only: false,
+            with_last_modified_slot: false,
         });
-        tracker.subscribe(account_params.clone(), 1.into(), || 42);
+        tracker.subscribe(account_params.clone(), 1.into(), || (42, None));
 
         let info = tracker
             .commitment_watchers
@@ -691,7 +722,7 @@ mod tests {
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
         let mut tracker = SubscriptionsTracker::new(bank_forks);
 
-        tracker.subscribe(SubscriptionParams::Slot, 0.into(), || 0);
+        tracker.subscribe(SubscriptionParams::Slot, 0.into(), || (0, None));
         assert_eq!(counts(&tracker), (0, 0, 0, 1));
         tracker.unsubscribe(SubscriptionParams::Slot, 0.into());
         assert_eq!(counts(&tracker), (0, 0, 0, 0));
@@ -701,8 +732,10 @@ mod tests {
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            changed_data_only: false,
+            with_last_modified_slot: false,
         });
-        tracker.subscribe(account_params.clone(), 1.into(), || 0);
+        tracker.subscribe(account_params.clone(), 1.into(), || (0, None));
         assert_eq!(counts(&tracker), (0, 1, 0, 0));
         tracker.unsubscribe(account_params, 1.into());
         assert_eq!(counts(&tracker), (0, 0, 0, 0));
@@ -712,8 +745,10 @@ mod tests {
             commitment: CommitmentConfig::confirmed(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            changed_data_only: false,
+            with_last_modified_slot: false,
         });
-        tracker.subscribe(account_params2.clone(), 2.into(), || 0);
+        tracker.subscribe(account_params2.clone(), 2.into(), || (0, None));
         assert_eq!(counts(&tracker), (0, 0, 1, 0));
         tracker.unsubscribe(account_params2, 2.into());
         assert_eq!(counts(&tracker), (0, 0, 0, 0));
@@ -723,9 +758,55 @@ mod tests {
             commitment: CommitmentConfig::processed(),
             enable_received_notification: false,
         });
-        tracker.subscribe(signature_params.clone(), 3.into(), || 0);
+        tracker.subscribe(signature_params.clone(), 3.into(), || (0, None));
         assert_eq!(counts(&tracker), (1, 1, 0, 0));
         tracker.unsubscribe(signature_params, 3.into());
         assert_eq!(counts(&tracker), (0, 0, 0, 0));
     }
+
+    #[test]
+    fn logs_subscriptions_mentioned_addresses() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let mut tracker = SubscriptionsTracker::new(bank_forks.clone());
+
+        let mentioned_addresses = || {
+            bank_forks
+                .read()
+                .unwrap()
+                .root_bank()
+                .transaction_log_collector_config
+                .read()
+                .unwrap()
+                .mentioned_addresses
+                .clone()
+        };
+
+        let address1 = Pubkey::new_unique();
+        let address2 = Pubkey::new_unique();
+        let mentions_params = SubscriptionParams::Logs(LogsSubscriptionParams {
+            kind: LogsSubscriptionKind::Mentions(vec![address1, address2]),
+            commitment: CommitmentConfig::processed(),
+            contains: vec![],
+        });
+        let program_ids_params = SubscriptionParams::Logs(LogsSubscriptionParams {
+            kind: LogsSubscriptionKind::ProgramIds(vec![address2]),
+            commitment: CommitmentConfig::processed(),
+            contains: vec!["Program log:".to_string()],
+        });
+
+        tracker.subscribe(mentions_params.clone(), 0.into(), || (0, None));
+        tracker.subscribe(program_ids_params.clone(), 1.into(), || (0, None));
+        assert_eq!(
+            mentioned_addresses(),
+            vec![address1, address2].into_iter().collect()
+        );
+
+        tracker.unsubscribe(mentions_params, 0.into());
+        assert_eq!(mentioned_addresses(), vec![address2].into_iter().collect());
+
+        tracker.unsubscribe(program_ids_params, 1.into());
+        assert!(mentioned_addresses().is_empty());
+    }
 }
@@ -13,7 +13,9 @@ use {
     },
     crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender},
     serde::Serialize,
-    solana_account_decoder::{parse_token::spl_token_id_v2_0, UiAccount, UiAccountEncoding},
+    solana_account_decoder::{
+        parse_token::spl_token_id_v2_0, slice_data, UiAccount, UiAccountEncoding, UiDataSliceConfig,
+    },
     solana_client::{
         rpc_filter::RpcFilterType,
         rpc_response::{
@@ -26,10 +28,12 @@ use {
         bank::{Bank, TransactionLogInfo},
         bank_forks::BankForks,
         commitment::{BlockCommitmentCache, CommitmentSlots},
+        log_collector::log_messages,
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::{Slot, UnixTimestamp},
+        hash::{hashv, Hash},
         pubkey::Pubkey,
         signature::Signature,
         timing::timestamp,
@@ -37,6 +41,7 @@ use {
     },
     solana_vote_program::vote_state::Vote,
     std::{
+        cell::RefCell,
         collections::{HashMap, VecDeque},
         io::Cursor,
         iter, str,
@@ -56,20 +61,43 @@ fn get_transaction_logs(
     bank: &Bank,
     params: &LogsSubscriptionParams,
 ) -> Option<Vec<TransactionLogInfo>> {
-    let pubkey = match &params.kind {
-        LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => None,
-        LogsSubscriptionKind::Single(pubkey) => Some(pubkey),
-    };
-    let mut logs = bank.get_transaction_logs(pubkey);
-    if matches!(params.kind, LogsSubscriptionKind::All) {
-        // Filter out votes if the subscriber doesn't want them
-        if let Some(logs) = &mut logs {
-            logs.retain(|log| !log.is_vote);
+    let mut logs = match &params.kind {
+        LogsSubscriptionKind::All | LogsSubscriptionKind::AllWithVotes => {
+            bank.get_transaction_logs(None)
+        }
+        LogsSubscriptionKind::Mentions(addresses) | LogsSubscriptionKind::ProgramIds(addresses) => {
+            bank.get_transaction_logs_for_addresses(addresses)
         }
+    };
+    if let Some(logs) = &mut logs {
+        logs.retain(|log| transaction_log_matches(log, params));
     }
     logs
 }
 
+fn transaction_log_matches(log: &TransactionLogInfo, params: &LogsSubscriptionParams) -> bool {
+    let kind_matches = match &params.kind {
+        // Filter out votes if the subscriber doesn't want them
+        LogsSubscriptionKind::All => !log.is_vote,
+        LogsSubscriptionKind::AllWithVotes | LogsSubscriptionKind::Mentions(_) => true,
+        // Mentioned programs must have actually been invoked
+        LogsSubscriptionKind::ProgramIds(program_ids) => log.log_entries.iter().any(|entry| {
+            entry
+                .program_id
+                .map(|program_id| program_ids.contains(&program_id))
+                .unwrap_or(false)
+        }),
+    };
+    kind_matches
+        && (params.contains.is_empty()
+            || log.log_entries.iter().any(|entry| {
+                params
+                    .contains
+                    .iter()
+                    .any(|substring| entry.message.contains(substring.as_str()))
+            }))
+}
+
 // A more human-friendly version of Vote, with the bank state signature base58 encoded.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RpcVote {
@@ -270,36 +298,54 @@ impl RpcNotifier {
     }
 }
 
+// Hash of the parts of an account that `changed_data_only` subscriptions are notified of
+fn account_data_hash(account: &AccountSharedData, data_slice: Option<UiDataSliceConfig>) -> Hash {
+    hashv(&[
+        account.owner().as_ref(),
+        &[account.executable() as u8],
+        slice_data(account.data(), data_slice),
+    ])
+}
+
 fn filter_account_result(
     result: Option<(AccountSharedData, Slot)>,
     params: &AccountSubscriptionParams,
     last_notified_slot: Slot,
+    last_notified_data_hash: &RwLock<Option<Hash>>,
     bank: Arc<Bank>,
 ) -> (Box<dyn Iterator<Item = UiAccount>>, Slot) {
     // If the account is not found, `last_modified_slot` will default to zero and
     // we will notify clients that the account no longer exists if we haven't already
+    let found = result.is_some();
     let (account, last_modified_slot) = result.unwrap_or_default();
 
     // If last_modified_slot < last_notified_slot this means that we last notified for a fork
     // and should notify that the account state has been reverted.
-    let results: Box<dyn Iterator<Item = UiAccount>> = if last_modified_slot != last_notified_slot {
-        if account.owner() == &spl_token_id_v2_0()
+    let mut should_notify = last_modified_slot != last_notified_slot;
+    if should_notify && params.changed_data_only {
+        let data_hash = Some(account_data_hash(&account, params.data_slice));
+        let mut last_notified_data_hash = last_notified_data_hash.write().unwrap();
+        should_notify = *last_notified_data_hash != data_hash;
+        *last_notified_data_hash = data_hash;
+    }
+    let results: Box<dyn Iterator<Item = UiAccount>> = if should_notify {
+        let mut ui_account = if account.owner() == &spl_token_id_v2_0()
             && params.encoding == UiAccountEncoding::JsonParsed
         {
-            Box::new(iter::once(get_parsed_token_account(
-                bank,
-                &params.pubkey,
-                account,
-            )))
+            get_parsed_token_account(bank, &params.pubkey, account)
         } else {
-            Box::new(iter::once(UiAccount::encode(
+            UiAccount::encode(
                 &params.pubkey,
                 &account,
                 params.encoding,
                 None,
-                None,
-            )))
+                params.data_slice,
+            )
+        };
+        if params.with_last_modified_slot && found {
+            ui_account.last_modified_slot = Some(last_modified_slot);
         }
+        Box::new(iter::once(ui_account))
     } else {
         Box::new(iter::empty())
     };
@@ -321,6 +367,42 @@ fn filter_signature_result(
     )
 }
 
+/// The accounts written in each slot notified in a round, grouped by owner, so that the slot's
+/// write set is scanned once instead of once per program subscription
+#[derive(Default)]
+struct SlotWriteSets {
+    by_slot: RefCell<HashMap<Slot, HashMap<Pubkey, Vec<(Pubkey, AccountSharedData)>>>>,
+}
+
+impl SlotWriteSets {
+    fn program_accounts(
+        &self,
+        bank: &Bank,
+        program_id: &Pubkey,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        self.by_slot
+            .borrow_mut()
+            .entry(bank.slot())
+            .or_insert_with(|| {
+                let mut by_owner: HashMap<_, Vec<_>> = HashMap::new();
+                for (pubkey, account) in bank.get_all_accounts_modified_since_parent() {
+                    by_owner
+                        .entry(*account.owner())
+                        .or_default()
+                        .push((pubkey, account));
+                }
+                by_owner
+            })
+            .get(program_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn num_slots(&self) -> usize {
+        self.by_slot.borrow().len()
+    }
+}
+
 fn filter_program_results(
     accounts: Vec<(Pubkey, AccountSharedData)>,
     params: &ProgramSubscriptionParams,
@@ -365,19 +447,20 @@ fn filter_logs_results(
             Box::new(logs.into_iter().map(|log| RpcLogsResponse {
                 signature: log.signature.to_string(),
                 err: log.result.err(),
-                logs: log.log_messages,
+                logs: log_messages(&log.log_entries),
+                log_entries: log.log_entries.into_iter().map(Into::into).collect(),
             })),
             last_notified_slot,
         ),
     }
 }
 
-fn initial_last_notified_slot(
+fn initial_last_notified(
     params: &SubscriptionParams,
     bank_forks: &RwLock<BankForks>,
     block_commitment_cache: &RwLock<BlockCommitmentCache>,
     optimistically_confirmed_bank: &RwLock<OptimisticallyConfirmedBank>,
-) -> Slot {
+) -> (Slot, Option<Hash>) {
     match params {
         SubscriptionParams::Account(params) => {
             let slot = if params.commitment.is_finalized() {
@@ -391,15 +474,18 @@ fn initial_last_notified_slot(
                 block_commitment_cache.read().unwrap().slot()
             };
 
-            if let Some((_account, slot)) = bank_forks
+            if let Some((account, slot)) = bank_forks
                 .read()
                 .unwrap()
                 .get(slot)
                 .and_then(|bank| bank.get_account_modified_slot(&params.pubkey))
             {
-                slot
+                let data_hash = params
+                    .changed_data_only
+                    .then(|| account_data_hash(&account, params.data_slice));
+                (slot, data_hash)
             } else {
-                0
+                (0, None)
             }
         }
         // last_notified_slot is not utilized for these subscriptions
@@ -409,7 +495,7 @@ fn initial_last_notified_slot(
         | SubscriptionParams::Slot
         | SubscriptionParams::SlotsUpdates
         | SubscriptionParams::Root
-        | SubscriptionParams::Vote => 0,
+        | SubscriptionParams::Vote => (0, None),
     }
 }
 
@@ -603,7 +689,7 @@ impl RpcSubscriptions {
                     match notification_entry {
                         NotificationEntry::Subscribed(params, id) => {
                             subscriptions.subscribe(params.clone(), id, || {
-                                initial_last_notified_slot(
+                                initial_last_notified(
                                     &params,
                                     &bank_forks,
                                     &block_commitment_cache,
@@ -744,6 +830,7 @@ impl RpcSubscriptions {
 
         let mut num_programs_found = 0;
         let mut num_programs_notified = 0;
+        let slot_write_sets = SlotWriteSets::default();
 
         for subscription in subscriptions.values() {
             match subscription.params() {
@@ -754,7 +841,15 @@ impl RpcSubscriptions {
                         bank_forks,
                         commitment_slots,
                         |bank, params| bank.get_account_modified_slot(&params.pubkey),
-                        filter_account_result,
+                        |result, params, last_notified_slot, bank| {
+                            filter_account_result(
+                                result,
+                                params,
+                                last_notified_slot,
+                                &subscription.last_notified_data_hash,
+                                bank,
+                            )
+                        },
                         notifier,
                         false,
                     );
@@ -788,9 +883,7 @@ impl RpcSubscriptions {
                         subscription,
                         bank_forks,
                         commitment_slots,
-                        |bank, params| {
-                            bank.get_program_accounts_modified_since_parent(&params.pubkey)
-                        },
+                        |bank, params| slot_write_sets.program_accounts(bank, &params.pubkey),
                         filter_program_results,
                         notifier,
                         false,
@@ -854,6 +947,7 @@ impl RpcSubscriptions {
                 ("num_logs_notified", num_logs_notified, i64),
                 ("num_program_subscriptions", num_programs_found, i64),
                 ("num_programs_notified", num_programs_notified, i64),
+                ("num_slot_write_sets", slot_write_sets.num_slots(), i64),
                 ("num_signature_subscriptions", num_signatures_found, i64),
                 ("num_signatures_notified", num_signatures_notified, i64),
                 ("notifications_time", total_time.as_us() as i64, i64),
@@ -908,15 +1002,18 @@ pub(crate) mod tests {
             rpc_pubsub_service,
         },
         serial_test::serial,
+        solana_account_decoder::UiAccountData,
         solana_client::rpc_config::{
-            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSignatureSubscribeConfig,
-            RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcProgramAccountsConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsFilter,
         },
         solana_runtime::{
             commitment::BlockCommitment,
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
+            log_collector::TransactionLogEntry,
         },
         solana_sdk::{
+            account::WritableAccount,
             commitment_config::CommitmentConfig,
             message::Message,
             signature::{Keypair, Signer},
@@ -1012,10 +1109,14 @@ pub(crate) mod tests {
             let sub_id = rpc
                 .account_subscribe(
                     pubkey.to_string(),
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        encoding: None,
-                        data_slice: None,
+                    Some(RpcAccountSubscribeConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            encoding: None,
+                            data_slice: None,
+                            with_last_modified_slot: None,
+                        },
+                        changed_data_only: None,
                     }),
                 )
                 .unwrap();
@@ -1026,6 +1127,8 @@ pub(crate) mod tests {
                     pubkey,
                     commitment: CommitmentConfig::processed(),
                     data_slice: None,
+                    changed_data_only: false,
+                    with_last_modified_slot: false,
                     encoding: UiAccountEncoding::Binary,
                 }));
 
@@ -1055,11 +1158,136 @@ pub(crate) mod tests {
                     pubkey,
                     commitment: CommitmentConfig::processed(),
                     data_slice: None,
+                    changed_data_only: false,
+                    with_last_modified_slot: false,
                     encoding: UiAccountEncoding::Binary,
                 }));
         }
     }
 
+    #[test]
+    fn test_filter_account_result_changed_data_only() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let params = AccountSubscriptionParams {
+            pubkey: Pubkey::new_unique(),
+            encoding: UiAccountEncoding::Base64,
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: 2,
+            }),
+            commitment: CommitmentConfig::processed(),
+            changed_data_only: true,
+            with_last_modified_slot: false,
+        };
+        let last_notified_data_hash = RwLock::new(None);
+        let mut account = AccountSharedData::new(10, 4, &system_program::id());
+        account.set_data(vec![1, 2, 3, 4]);
+        let notify = |account: &AccountSharedData, slot, last_notified_slot| {
+            let (results, _) = filter_account_result(
+                Some((account.clone(), slot)),
+                &params,
+                last_notified_slot,
+                &last_notified_data_hash,
+                bank.clone(),
+            );
+            results.map(|account| account.data).collect::<Vec<_>>()
+        };
+
+        // The sliced data is notified
+        assert_eq!(
+            notify(&account, 1, 0),
+            vec![UiAccountData::Binary(
+                base64::encode(&[1, 2]),
+                UiAccountEncoding::Base64
+            )]
+        );
+
+        // Changes to the lamports and to the data outside of the slice are not
+        account.set_lamports(20);
+        assert!(notify(&account, 2, 1).is_empty());
+        account.set_data(vec![1, 2, 5, 6]);
+        assert!(notify(&account, 3, 1).is_empty());
+
+        account.set_data(vec![7, 2, 5, 6]);
+        assert_eq!(
+            notify(&account, 4, 1),
+            vec![UiAccountData::Binary(
+                base64::encode(&[7, 2]),
+                UiAccountEncoding::Base64
+            )]
+        );
+    }
+
+    #[test]
+    fn test_filter_account_result_with_last_modified_slot() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let mut params = AccountSubscriptionParams {
+            pubkey: Pubkey::new_unique(),
+            encoding: UiAccountEncoding::Base64,
+            data_slice: None,
+            commitment: CommitmentConfig::processed(),
+            changed_data_only: false,
+            with_last_modified_slot: true,
+        };
+        let last_notified_data_hash = RwLock::new(None);
+        let account = AccountSharedData::new(10, 0, &system_program::id());
+        let notify = |params: &AccountSubscriptionParams, result, last_notified_slot| {
+            let (results, _) = filter_account_result(
+                result,
+                params,
+                last_notified_slot,
+                &last_notified_data_hash,
+                bank.clone(),
+            );
+            results
+                .map(|account| account.last_modified_slot)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            notify(&params, Some((account.clone(), 3)), 0),
+            vec![Some(3)]
+        );
+        // A removed account has no last modified slot
+        assert_eq!(notify(&params, None, 3), vec![None]);
+
+        params.with_last_modified_slot = false;
+        assert_eq!(notify(&params, Some((account, 4)), 3), vec![None]);
+    }
+
+    #[test]
+    fn test_slot_write_sets() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let program_id = Pubkey::new_unique();
+        let pubkeys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        bank1.store_account(&pubkeys[0], &AccountSharedData::new(1, 0, &program_id));
+        bank1.store_account(&pubkeys[1], &AccountSharedData::new(2, 0, &program_id));
+        bank1.store_account(
+            &pubkeys[2],
+            &AccountSharedData::new(3, 0, &Pubkey::default()),
+        );
+
+        let slot_write_sets = SlotWriteSets::default();
+        let mut program_accounts: Vec<_> = slot_write_sets
+            .program_accounts(&bank1, &program_id)
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey, account.lamports()))
+            .collect();
+        program_accounts.sort();
+        let mut expected = vec![(pubkeys[0], 1), (pubkeys[1], 2)];
+        expected.sort();
+        assert_eq!(program_accounts, expected);
+        assert!(slot_write_sets
+            .program_accounts(&bank1, &Pubkey::new_unique())
+            .is_empty());
+        // The slot is only scanned once
+        assert_eq!(slot_write_sets.num_slots(), 1);
+    }
+
     #[test]
     #[serial]
     fn test_check_program_subscribe() {
@@ -1986,10 +2214,14 @@ pub(crate) mod tests {
         let sub_id0 = rpc0
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
+                Some(RpcAccountSubscribeConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        with_last_modified_slot: None,
+                    },
+                    changed_data_only: None,
                 }),
             )
             .unwrap();
@@ -2073,10 +2305,14 @@ pub(crate) mod tests {
         let sub_id1 = rpc1
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
+                Some(RpcAccountSubscribeConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        with_last_modified_slot: None,
+                    },
+                    changed_data_only: None,
                 }),
             )
             .unwrap();
@@ -2201,4 +2437,66 @@ pub(crate) mod tests {
         rpc7.root_unsubscribe(sub_id7).unwrap();
         assert_eq!(subscriptions.total(), 0);
     }
+
+    #[test]
+    fn test_transaction_log_matches() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let log = TransactionLogInfo {
+            signature: Signature::default(),
+            result: Ok(()),
+            is_vote: false,
+            log_entries: vec![TransactionLogEntry {
+                program_id: Some(program_id),
+                invoke_depth: 1,
+                message: "Program log: hello".to_string(),
+            }],
+        };
+        let params = |kind, contains: &[&str]| LogsSubscriptionParams {
+            kind,
+            commitment: CommitmentConfig::processed(),
+            contains: contains.iter().map(|s| s.to_string()).collect(),
+        };
+
+        assert!(transaction_log_matches(
+            &log,
+            &params(LogsSubscriptionKind::All, &[])
+        ));
+        assert!(transaction_log_matches(
+            &log,
+            &params(LogsSubscriptionKind::Mentions(vec![other_program_id]), &[])
+        ));
+        assert!(transaction_log_matches(
+            &log,
+            &params(LogsSubscriptionKind::ProgramIds(vec![program_id]), &[])
+        ));
+        assert!(!transaction_log_matches(
+            &log,
+            &params(
+                LogsSubscriptionKind::ProgramIds(vec![other_program_id]),
+                &[]
+            )
+        ));
+        assert!(transaction_log_matches(
+            &log,
+            &params(LogsSubscriptionKind::All, &["goodbye", "hello"])
+        ));
+        assert!(!transaction_log_matches(
+            &log,
+            &params(LogsSubscriptionKind::All, &["goodbye"])
+        ));
+
+        let vote_log = TransactionLogInfo {
+            is_vote: true,
+            ..log
+        };
+        assert!(!transaction_log_matches(
+            &vote_log,
+            &params(LogsSubscriptionKind::All, &[])
+        ));
+        assert!(transaction_log_matches(
+            &vote_log,
+            &params(LogsSubscriptionKind::AllWithVotes, &[])
+        ));
+    }
 }
@@ -1,24 +1,59 @@
 use {
     crossbeam_channel::{Receiver, RecvTimeoutError},
-    itertools::izip,
     solana_ledger::{
-        blockstore::Blockstore,
-        blockstore_processor::{TransactionStatusBatch, TransactionStatusMessage},
-    },
-    solana_runtime::bank::{Bank, InnerInstructionsList, TransactionLogMessages},
-    solana_transaction_status::{
-        extract_and_fmt_memos, InnerInstructions, Reward, TransactionStatusMeta,
+        blockstore::{Blockstore, TransactionStatusRecord},
+        blockstore_processor::{DroppedTransactionStatusSlots, TransactionStatusMessage},
+        transaction_status_spill::{SpilledTransactionStatusMessage, TransactionStatusSpill},
     },
+    solana_measure::measure::Measure,
+    solana_sdk::clock::Slot,
     std::{
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
             Arc,
         },
         thread::{self, Builder, JoinHandle},
-        time::Duration,
+        time::{Duration, Instant},
     },
 };
 
+// Messages received before the statuses collected from them are written in one write batch
+const MAX_MESSAGES_PER_WRITE: usize = 64;
+const METRICS_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct TransactionStatusServiceStats {
+    num_batches: usize,
+    num_records: usize,
+    num_writes: usize,
+    num_spilled_files: usize,
+    write_us: u64,
+}
+
+impl TransactionStatusServiceStats {
+    fn report(
+        &mut self,
+        receiver: &Receiver<TransactionStatusMessage>,
+        spill: Option<&TransactionStatusSpill>,
+    ) {
+        let pending_spilled_files = spill
+            .and_then(|spill| spill.spilled_files().ok())
+            .map(|files| files.len())
+            .unwrap_or_default();
+        datapoint_info!(
+            "transaction_status_service",
+            ("queue_len", receiver.len(), i64),
+            ("pending_spilled_files", pending_spilled_files, i64),
+            ("num_batches", self.num_batches, i64),
+            ("num_records", self.num_records, i64),
+            ("num_writes", self.num_writes, i64),
+            ("num_spilled_files", self.num_spilled_files, i64),
+            ("write_us", self.write_us, i64),
+        );
+        *self = Self::default();
+    }
+}
+
 pub struct TransactionStatusService {
     thread_hdl: JoinHandle<()>,
 }
@@ -29,21 +64,36 @@ impl TransactionStatusService {
         write_transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         blockstore: Arc<Blockstore>,
+        spill: Option<Arc<TransactionStatusSpill>>,
+        dropped_slots: DroppedTransactionStatusSlots,
         exit: &Arc<AtomicBool>,
     ) -> Self {
         let exit = exit.clone();
         let thread_hdl = Builder::new()
             .name("solana-transaction-status-writer".to_string())
-            .spawn(move || loop {
-                if exit.load(Ordering::Relaxed) {
-                    break;
-                }
-                if let Err(RecvTimeoutError::Disconnected) = Self::write_transaction_status_batch(
-                    &write_transaction_status_receiver,
-                    &max_complete_transaction_status_slot,
-                    &blockstore,
-                ) {
-                    break;
+            .spawn(move || {
+                let mut stats = TransactionStatusServiceStats::default();
+                let mut last_report = Instant::now();
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if let Err(RecvTimeoutError::Disconnected) =
+                        Self::write_transaction_status_batch(
+                            &write_transaction_status_receiver,
+                            &max_complete_transaction_status_slot,
+                            &blockstore,
+                            spill.as_deref(),
+                            &dropped_slots,
+                            &mut stats,
+                        )
+                    {
+                        break;
+                    }
+                    if last_report.elapsed() > METRICS_REPORT_INTERVAL {
+                        stats.report(&write_transaction_status_receiver, spill.as_deref());
+                        last_report = Instant::now();
+                    }
                 }
             })
             .unwrap();
@@ -54,131 +104,128 @@ impl TransactionStatusService {
         write_transaction_status_receiver: &Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
         blockstore: &Arc<Blockstore>,
+        spill: Option<&TransactionStatusSpill>,
+        dropped_slots: &DroppedTransactionStatusSlots,
+        stats: &mut TransactionStatusServiceStats,
     ) -> Result<(), RecvTimeoutError> {
-        match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))? {
-            TransactionStatusMessage::Batch(TransactionStatusBatch {
-                bank,
-                transactions,
-                statuses,
-                balances,
-                token_balances,
-                inner_instructions,
-                transaction_logs,
-                rent_debits,
-            }) => {
-                let slot = bank.slot();
-                let inner_instructions_iter: Box<
-                    dyn Iterator<Item = Option<InnerInstructionsList>>,
-                > = if let Some(inner_instructions) = inner_instructions {
-                    Box::new(inner_instructions.into_iter())
-                } else {
-                    Box::new(std::iter::repeat_with(|| None))
-                };
-                let transaction_logs_iter: Box<
-                    dyn Iterator<Item = Option<TransactionLogMessages>>,
-                > = if let Some(transaction_logs) = transaction_logs {
-                    Box::new(transaction_logs.into_iter())
-                } else {
-                    Box::new(std::iter::repeat_with(|| None))
-                };
-                for (
-                    transaction,
-                    (status, nonce_rollback),
-                    pre_balances,
-                    post_balances,
-                    pre_token_balances,
-                    post_token_balances,
-                    inner_instructions,
-                    log_messages,
-                    rent_debits,
-                ) in izip!(
-                    transactions,
-                    statuses,
-                    balances.pre_balances,
-                    balances.post_balances,
-                    token_balances.pre_token_balances,
-                    token_balances.post_token_balances,
-                    inner_instructions_iter,
-                    transaction_logs_iter,
-                    rent_debits,
-                ) {
-                    if Bank::can_commit(&status) {
-                        let lamports_per_signature = nonce_rollback
-                            .map(|nonce_rollback| nonce_rollback.lamports_per_signature())
-                            .unwrap_or_else(|| {
-                                bank.get_lamports_per_signature_for_blockhash(
-                                    transaction.message().recent_blockhash(),
-                                )
-                            })
-                            .expect("lamports_per_signature must be available");
-                        let fee = Bank::get_fee_for_message_with_lamports_per_signature(
-                            transaction.message(),
-                            lamports_per_signature,
-                        );
-                        let tx_account_locks =
-                            transaction.get_account_locks(bank.demote_program_write_locks());
+        let message = match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1)) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => {
+                Self::write_spilled_messages(
+                    max_complete_transaction_status_slot,
+                    blockstore,
+                    spill,
+                    dropped_slots,
+                    stats,
+                );
+                return Err(RecvTimeoutError::Timeout);
+            }
+            Err(err) => return Err(err),
+        };
+
+        let mut records = vec![];
+        let messages = std::iter::once(message).chain(
+            write_transaction_status_receiver
+                .try_iter()
+                .take(MAX_MESSAGES_PER_WRITE - 1),
+        );
+        for message in messages {
+            match message {
+                TransactionStatusMessage::Batch(batch) => {
+                    stats.num_batches += 1;
+                    records.extend(batch.into_records());
+                }
+                TransactionStatusMessage::Freeze(slot) => {
+                    Self::write_records(blockstore, std::mem::take(&mut records), stats);
+                    Self::complete_slot(max_complete_transaction_status_slot, dropped_slots, slot);
+                }
+            }
+        }
+        Self::write_records(blockstore, records, stats);
 
-                        let inner_instructions = inner_instructions.map(|inner_instructions| {
-                            inner_instructions
-                                .into_iter()
-                                .enumerate()
-                                .map(|(index, instructions)| InnerInstructions {
-                                    index: index as u8,
-                                    instructions,
-                                })
-                                .filter(|i| !i.instructions.is_empty())
-                                .collect()
-                        });
+        // The spilled messages follow the ones in the channel, they're caught up on once the
+        // channel has been emptied
+        if write_transaction_status_receiver.is_empty() {
+            Self::write_spilled_messages(
+                max_complete_transaction_status_slot,
+                blockstore,
+                spill,
+                dropped_slots,
+                stats,
+            );
+        }
+        Ok(())
+    }
 
-                        let pre_token_balances = Some(pre_token_balances);
-                        let post_token_balances = Some(post_token_balances);
-                        let rewards = Some(
-                            rent_debits
-                                .0
-                                .into_iter()
-                                .map(|(pubkey, reward_info)| Reward {
-                                    pubkey: pubkey.to_string(),
-                                    lamports: reward_info.lamports,
-                                    post_balance: reward_info.post_balance,
-                                    reward_type: Some(reward_info.reward_type),
-                                    commission: reward_info.commission,
-                                })
-                                .collect(),
-                        );
+    fn complete_slot(
+        max_complete_transaction_status_slot: &AtomicU64,
+        dropped_slots: &DroppedTransactionStatusSlots,
+        slot: Slot,
+    ) {
+        if dropped_slots.lock().unwrap().remove(&slot) {
+            inc_new_counter_info!("transaction_status_service-incomplete_slots", 1);
+        } else {
+            max_complete_transaction_status_slot.fetch_max(slot, Ordering::SeqCst);
+        }
+    }
 
-                        if let Some(memos) = extract_and_fmt_memos(transaction.message()) {
-                            blockstore
-                                .write_transaction_memos(transaction.signature(), memos)
-                                .expect("Expect database write to succeed: TransactionMemos");
-                        }
+    fn write_records(
+        blockstore: &Blockstore,
+        records: Vec<TransactionStatusRecord>,
+        stats: &mut TransactionStatusServiceStats,
+    ) {
+        if records.is_empty() {
+            return;
+        }
+        let mut write_time = Measure::start("write_transaction_statuses");
+        stats.num_records += records.len();
+        stats.num_writes += 1;
+        blockstore
+            .write_transaction_statuses(records)
+            .expect("Expect database write to succeed: TransactionStatus");
+        write_time.stop();
+        stats.write_us += write_time.as_us();
+    }
 
-                        blockstore
-                            .write_transaction_status(
-                                slot,
-                                *transaction.signature(),
-                                tx_account_locks.writable,
-                                tx_account_locks.readonly,
-                                TransactionStatusMeta {
-                                    status,
-                                    fee,
-                                    pre_balances,
-                                    post_balances,
-                                    inner_instructions,
-                                    log_messages,
-                                    pre_token_balances,
-                                    post_token_balances,
-                                    rewards,
-                                },
-                            )
-                            .expect("Expect database write to succeed: TransactionStatus");
-                    }
+    fn write_spilled_messages(
+        max_complete_transaction_status_slot: &AtomicU64,
+        blockstore: &Blockstore,
+        spill: Option<&TransactionStatusSpill>,
+        dropped_slots: &DroppedTransactionStatusSlots,
+        stats: &mut TransactionStatusServiceStats,
+    ) {
+        let spill = match spill {
+            Some(spill) => spill,
+            None => return,
+        };
+        let spilled_files = match spill.spilled_files() {
+            Ok(spilled_files) => spilled_files,
+            Err(err) => {
+                warn!("Unable to list spilled transaction statuses: {:?}", err);
+                return;
+            }
+        };
+        for path in spilled_files {
+            match TransactionStatusSpill::read_spilled_file(&path) {
+                Ok(SpilledTransactionStatusMessage::Records(records)) => {
+                    Self::write_records(blockstore, records, stats)
+                }
+                Ok(SpilledTransactionStatusMessage::Freeze(slot)) => {
+                    Self::complete_slot(max_complete_transaction_status_slot, dropped_slots, slot)
                 }
+                Err(err) => warn!(
+                    "Dropping unreadable spilled transaction statuses {:?}: {:?}",
+                    path, err
+                ),
             }
-            TransactionStatusMessage::Freeze(slot) => {
-                max_complete_transaction_status_slot.fetch_max(slot, Ordering::SeqCst);
+            if let Err(err) = spill.remove_spilled_file(&path) {
+                warn!(
+                    "Unable to remove spilled transaction statuses {:?}: {:?}",
+                    path, err
+                );
             }
+            stats.num_spilled_files += 1;
         }
-        Ok(())
     }
 
     pub fn join(self) -> thread::Result<()> {
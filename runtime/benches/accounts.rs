@@ -408,3 +408,61 @@ fn bench_load_largest_accounts(b: &mut Bencher) {
         )
     });
 }
+
+// Filtering the stored accounts only pays off when copying the data of the rejected accounts is a
+// large part of the scan, so the accounts are large.  With 1 KiB accounts both scans take about
+// as long
+fn store_program_accounts_for_filter_bench(accounts: &Accounts, program_id: &Pubkey) {
+    for i in 0..10_000u32 {
+        let mut account = AccountSharedData::new(1, 0, program_id);
+        // One in a hundred accounts match the filter
+        let mut data = vec![0; 10_000];
+        data[0] = (i % 100 == 0) as u8;
+        account.set_data(data);
+        accounts.store_slow_uncached(0, &Pubkey::new_unique(), &account);
+    }
+}
+
+#[bench]
+fn bench_load_by_program_with_filter(b: &mut Bencher) {
+    let accounts = Accounts::new_with_config_for_benches(
+        Vec::new(),
+        &ClusterType::Development,
+        AccountSecondaryIndexes::default(),
+        false,
+        AccountShrinkThreshold::default(),
+    );
+    let program_id = Pubkey::new_unique();
+    store_program_accounts_for_filter_bench(&accounts, &program_id);
+    let ancestors = Ancestors::from(vec![0]);
+    b.iter(|| {
+        let loaded = accounts
+            .load_by_program_with_filter(&ancestors, 0, &program_id, |account| {
+                account.data()[0] == 1
+            })
+            .unwrap();
+        assert_eq!(loaded.len(), 100);
+    });
+}
+
+#[bench]
+fn bench_load_by_program_with_loaded_filter(b: &mut Bencher) {
+    let accounts = Accounts::new_with_config_for_benches(
+        Vec::new(),
+        &ClusterType::Development,
+        AccountSecondaryIndexes::default(),
+        false,
+        AccountShrinkThreshold::default(),
+    );
+    let program_id = Pubkey::new_unique();
+    store_program_accounts_for_filter_bench(&accounts, &program_id);
+    let ancestors = Ancestors::from(vec![0]);
+    b.iter(|| {
+        let loaded = accounts
+            .load_by_program_with_loaded_filter(&ancestors, 0, &program_id, |account| {
+                account.data()[0] == 1
+            })
+            .unwrap();
+        assert_eq!(loaded.len(), 100);
+    });
+}
@@ -0,0 +1,168 @@
+use {
+    log::*,
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        pubkey::Pubkey,
+        rent::Rent,
+        transaction::{Result, TransactionError},
+    },
+};
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum RentState {
+    /// account.lamports == 0
+    Uninitialized,
+    /// 0 < account.lamports < rent-exempt-minimum
+    RentPaying {
+        /// The account's data length
+        data_size: usize,
+    },
+    /// account.lamports >= rent-exempt-minimum
+    RentExempt,
+}
+
+impl RentState {
+    pub(crate) fn from_account(account: &AccountSharedData, rent: &Rent) -> Self {
+        if account.lamports() == 0 {
+            Self::Uninitialized
+        } else if !rent.is_exempt(account.lamports(), account.data().len()) {
+            Self::RentPaying {
+                data_size: account.data().len(),
+            }
+        } else {
+            Self::RentExempt
+        }
+    }
+
+    /// Only accounts which were already paying rent may be left paying rent,
+    /// and without growing their data; this lets existing rent-paying accounts
+    /// keep operating while they are migrated, but no new rent-paying accounts
+    /// can be created.
+    pub(crate) fn transition_allowed_from(&self, pre_rent_state: &RentState) -> bool {
+        match (self, pre_rent_state) {
+            (
+                Self::RentPaying { data_size },
+                Self::RentPaying {
+                    data_size: pre_data_size,
+                },
+            ) => data_size <= pre_data_size,
+            (Self::RentPaying { .. }, _) => false,
+            _ => true,
+        }
+    }
+}
+
+pub(crate) fn check_rent_state(
+    pre_rent_state: Option<&RentState>,
+    post_rent_state: Option<&RentState>,
+    address: &Pubkey,
+) -> Result<()> {
+    if let Some((pre_rent_state, post_rent_state)) = pre_rent_state.zip(post_rent_state) {
+        if !post_rent_state.transition_allowed_from(pre_rent_state) {
+            debug!(
+                "Account {} not rent exempt, state {:?}",
+                address, post_rent_state
+            );
+            return Err(TransactionError::InvalidRentPayingAccount);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_account() {
+        let program_id = Pubkey::new_unique();
+        let uninitialized_account = AccountSharedData::new(0, 0, &Pubkey::default());
+
+        let account_data_size = 100;
+
+        // if rent is free, all accounts with non-zero lamports are rent-exempt
+        let rent = Rent::free();
+        let rent_exempt_account = AccountSharedData::new(1, account_data_size, &program_id);
+
+        assert_eq!(
+            RentState::from_account(&uninitialized_account, &rent),
+            RentState::Uninitialized
+        );
+        assert_eq!(
+            RentState::from_account(&rent_exempt_account, &rent),
+            RentState::RentExempt
+        );
+
+        let rent = Rent::default();
+        let rent_minimum_balance = rent.minimum_balance(account_data_size);
+        let rent_paying_account = AccountSharedData::new(
+            rent_minimum_balance.saturating_sub(1),
+            account_data_size,
+            &program_id,
+        );
+        let rent_exempt_account =
+            AccountSharedData::new(rent_minimum_balance, account_data_size, &program_id);
+
+        assert_eq!(
+            RentState::from_account(&uninitialized_account, &rent),
+            RentState::Uninitialized
+        );
+        assert_eq!(
+            RentState::from_account(&rent_paying_account, &rent),
+            RentState::RentPaying {
+                data_size: account_data_size,
+            }
+        );
+        assert_eq!(
+            RentState::from_account(&rent_exempt_account, &rent),
+            RentState::RentExempt
+        );
+    }
+
+    #[test]
+    fn test_transition_allowed_from() {
+        let rent_paying = |data_size| RentState::RentPaying { data_size };
+        for post_rent_state in [RentState::Uninitialized, RentState::RentExempt] {
+            assert!(post_rent_state.transition_allowed_from(&RentState::Uninitialized));
+            assert!(post_rent_state.transition_allowed_from(&rent_paying(0)));
+            assert!(post_rent_state.transition_allowed_from(&RentState::RentExempt));
+        }
+
+        assert!(!rent_paying(0).transition_allowed_from(&RentState::Uninitialized));
+        assert!(rent_paying(10).transition_allowed_from(&rent_paying(10)));
+        assert!(rent_paying(5).transition_allowed_from(&rent_paying(10)));
+        // rent-paying accounts can't grow
+        assert!(!rent_paying(11).transition_allowed_from(&rent_paying(10)));
+        assert!(!rent_paying(0).transition_allowed_from(&RentState::RentExempt));
+    }
+
+    #[test]
+    fn test_check_rent_state() {
+        let address = Pubkey::new_unique();
+        assert_eq!(
+            check_rent_state(
+                Some(&RentState::Uninitialized),
+                Some(&RentState::RentPaying { data_size: 0 }),
+                &address
+            ),
+            Err(TransactionError::InvalidRentPayingAccount)
+        );
+        assert_eq!(
+            check_rent_state(
+                Some(&RentState::RentPaying { data_size: 0 }),
+                Some(&RentState::RentPaying { data_size: 0 }),
+                &address
+            ),
+            Ok(())
+        );
+        // accounts without a tracked state are not checked
+        assert_eq!(
+            check_rent_state(
+                None,
+                Some(&RentState::RentPaying { data_size: 0 }),
+                &address
+            ),
+            Ok(())
+        );
+    }
+}
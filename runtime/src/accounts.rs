@@ -1,4 +1,5 @@
 use crate::{
+    account_rent_state::RentState,
     accounts_db::{
         AccountShrinkThreshold, AccountsDb, AccountsDbConfig, BankHashInfo, ErrorCounters,
         LoadHint, LoadedAccount, ScanStorageResult, ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS,
@@ -12,6 +13,7 @@ use crate::{
         TransactionExecutionResult,
     },
     blockhash_queue::BlockhashQueue,
+    largest_accounts_index::LargestAccountsIndex,
     rent_collector::RentCollector,
     system_instruction_processor::{get_system_account_kind, SystemAccountKind},
 };
@@ -21,18 +23,22 @@ use dashmap::{
 };
 use log::*;
 use rand::{thread_rng, Rng};
+use solana_address_lookup_table_program::{error::AddressLookupError, state::AddressLookupTable};
 use solana_sdk::{
     account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
     account_utils::StateMut,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::{BankId, Slot, INITIAL_RENT_EPOCH},
     feature_set::{self, FeatureSet},
+    fee::FeeStructure,
     genesis_config::ClusterType,
     hash::Hash,
-    message::SanitizedMessage,
+    message::{v0::MessageAddressTableLookup, LoadedAddresses, SanitizedMessage},
     native_loader, nonce,
     nonce::NONCED_TX_MARKER_IX_INDEX,
+    process_instruction::ProgramEvent,
     pubkey::Pubkey,
+    slot_hashes::SlotHashes,
     system_program, sysvar,
     sysvar::instructions::construct_instructions_data,
     transaction::{Result, SanitizedTransaction, TransactionError},
@@ -109,15 +115,52 @@ pub struct LoadedTransaction {
     pub program_indices: TransactionProgramIndices,
     pub rent: TransactionRent,
     pub rent_debits: RentDebits,
+    /// Change in the total size of the accounts' data caused by successfully executing the
+    /// transaction, which is only known after execution
+    pub accounts_data_len_delta: i64,
+    /// Programs deployed, upgraded or closed by successfully executing the transaction, reported
+    /// once it's committed
+    pub program_events: Vec<(Pubkey, ProgramEvent)>,
 }
 
 pub type TransactionLoadResult = (Result<LoadedTransaction>, Option<NonceRollbackFull>);
+// Accounts ranked by lamports, smallest first
+type LargestAccountsHeap = BinaryHeap<Reverse<(u64, Pubkey)>>;
+
+// The accounts and their lamports, largest first
+fn into_account_balances(largest: LargestAccountsHeap) -> Vec<(Pubkey, u64)> {
+    largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((balance, pubkey))| (pubkey, balance))
+        .collect()
+}
 
 pub enum AccountAddressFilter {
     Exclude, // exclude all addresses matching the filter
     Include, // only include addresses matching the filter
 }
 
+/// The metadata of a scanned account, for scans that don't need the account data
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AccountScanMetadata {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data_len: usize,
+}
+
+impl AccountScanMetadata {
+    fn new(pubkey: &Pubkey, loaded_account: &LoadedAccount) -> Self {
+        Self {
+            pubkey: *pubkey,
+            owner: *loaded_account.owner(),
+            lamports: loaded_account.lamports(),
+            data_len: loaded_account.data_len(),
+        }
+    }
+}
+
 impl Accounts {
     pub fn default_for_tests() -> Self {
         Self {
@@ -359,10 +402,22 @@ impl Accounts {
                     error_counters.insufficient_funds += 1;
                     return Err(TransactionError::InsufficientFundsForFee);
                 }
+                let payer_pre_rent_state =
+                    RentState::from_account(payer_account, &rent_collector.rent);
                 payer_account
                     .checked_sub_lamports(fee)
                     .map_err(|_| TransactionError::InsufficientFundsForFee)?;
 
+                // Paying the fee must not leave a rent-exempt payer paying rent
+                if feature_set.is_active(&feature_set::require_rent_exempt_accounts::id()) {
+                    let payer_post_rent_state =
+                        RentState::from_account(payer_account, &rent_collector.rent);
+                    if !payer_post_rent_state.transition_allowed_from(&payer_pre_rent_state) {
+                        error_counters.insufficient_funds += 1;
+                        return Err(TransactionError::InsufficientFundsForFee);
+                    }
+                }
+
                 let program_indices = message
                     .instructions()
                     .iter()
@@ -380,6 +435,8 @@ impl Accounts {
                     program_indices,
                     rent: tx_rent,
                     rent_debits,
+                    accounts_data_len_delta: 0,
+                    program_events: vec![],
                 })
             } else {
                 error_counters.account_not_found += 1;
@@ -460,6 +517,7 @@ impl Accounts {
         Ok(account_indices)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn load_accounts(
         &self,
         ancestors: &Ancestors,
@@ -469,6 +527,8 @@ impl Accounts {
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
+        fee_structure: &FeeStructure,
+        write_lock_counts: &HashMap<Pubkey, u64>,
     ) -> Vec<TransactionLoadResult> {
         txs.iter()
             .zip(lock_results)
@@ -482,7 +542,16 @@ impl Accounts {
                             hash_queue.get_lamports_per_signature(tx.message().recent_blockhash())
                         });
                     let fee = if let Some(lamports_per_signature) = lamports_per_signature {
-                        Bank::calculate_fee(tx.message(), lamports_per_signature)
+                        Bank::calculate_fee(
+                            tx.message(),
+                            lamports_per_signature,
+                            fee_structure,
+                            write_lock_counts,
+                            feature_set.is_active(&feature_set::compute_unit_fee_structure::id()),
+                            feature_set
+                                .is_active(&feature_set::add_set_compute_unit_price_ix::id()),
+                            feature_set.is_active(&feature_set::demote_program_write_locks::id()),
+                        )
                     } else {
                         return (Err(TransactionError::BlockhashNotFound), None);
                     };
@@ -558,6 +627,42 @@ impl Accounts {
         self.load_slow(ancestors, pubkey, LoadHint::Unspecified)
     }
 
+    /// Load the addresses referenced by an address table lookup from the
+    /// lookup table account as of the latest slot in `ancestors`
+    pub fn load_lookup_table_addresses(
+        &self,
+        ancestors: &Ancestors,
+        address_table_lookup: &MessageAddressTableLookup,
+        slot_hashes: &SlotHashes,
+    ) -> std::result::Result<LoadedAddresses, AddressLookupError> {
+        let table_account = self
+            .accounts_db
+            .load_with_fixed_root(ancestors, &address_table_lookup.account_key)
+            .map(|(account, _rent)| account)
+            .ok_or(AddressLookupError::LookupTableAccountNotFound)?;
+
+        if table_account.owner() == &solana_address_lookup_table_program::id() {
+            let current_slot = ancestors.max_slot();
+            let lookup_table = AddressLookupTable::deserialize(table_account.data())
+                .map_err(|_ix_err| AddressLookupError::InvalidAccountData)?;
+
+            Ok(LoadedAddresses {
+                writable: lookup_table.lookup(
+                    current_slot,
+                    &address_table_lookup.writable_indexes,
+                    slot_hashes,
+                )?,
+                readonly: lookup_table.lookup(
+                    current_slot,
+                    &address_table_lookup.readonly_indexes,
+                    slot_hashes,
+                )?,
+            })
+        } else {
+            Err(AddressLookupError::InvalidAccountOwner)
+        }
+    }
+
     /// scans underlying accounts_db for this delta (slot) with a map function
     ///   from LoadedAccount to B
     /// returns only the latest/current version of B for this slot
@@ -627,6 +732,7 @@ impl Accounts {
         })
     }
 
+    /// The `num` accounts with the most lamports as of `ancestors`
     pub fn load_largest_accounts(
         &self,
         ancestors: &Ancestors,
@@ -638,40 +744,132 @@ impl Accounts {
         if num == 0 {
             return Ok(vec![]);
         }
-        let account_balances = self.accounts_db.scan_accounts(
+        let index = self
+            .accounts_db
+            .largest_accounts_index
+            .as_ref()
+            .filter(|index| num <= index.capacity() && !ancestors.is_empty());
+        if let Some(index) = index {
+            if let Some(largest) = self.load_largest_indexed_accounts(
+                index,
+                ancestors,
+                num,
+                filter_by_address,
+                &filter,
+            ) {
+                return Ok(largest);
+            }
+        }
+
+        // The index can't answer, scan the accounts instead.  If these are the accounts of the
+        // latest root, the same scan rebuilds the index.
+        let slot = ancestors.max_slot();
+        let rebuild = index.filter(|index| {
+            index.start_rebuild(slot, || self.accounts_db.accounts_index.max_root() == slot)
+        });
+        let result = self.scan_largest_accounts(
             ancestors,
             bank_id,
-            |collector: &mut BinaryHeap<Reverse<(u64, Pubkey)>>, option| {
+            num,
+            filter_by_address,
+            &filter,
+            rebuild.map(|index| index.capacity()),
+        );
+        match result {
+            Ok((largest, all_largest)) => {
+                if let Some(index) = rebuild {
+                    index.finish_rebuild(Some(into_account_balances(all_largest)));
+                }
+                Ok(into_account_balances(largest))
+            }
+            Err(err) => {
+                if let Some(index) = rebuild {
+                    index.finish_rebuild(None);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    // Answers from the largest accounts index if `ancestors` descend from the root it holds, with
+    // the writes of the unrooted slots since applied on top
+    fn load_largest_indexed_accounts(
+        &self,
+        index: &LargestAccountsIndex,
+        ancestors: &Ancestors,
+        num: usize,
+        filter_by_address: &HashSet<Pubkey>,
+        filter: &AccountAddressFilter,
+    ) -> Option<Vec<(Pubkey, u64)>> {
+        let root = index.root()?;
+        if !ancestors.contains_key(&root) {
+            return None;
+        }
+        let mut unrooted_slots: Vec<_> = ancestors
+            .keys()
+            .into_iter()
+            .filter(|slot| *slot > root)
+            .collect();
+        unrooted_slots.sort_unstable();
+        let mut unrooted_writes = HashMap::new();
+        for slot in unrooted_slots {
+            unrooted_writes.extend(self.scan_slot(slot, |loaded_account| {
+                Some((*loaded_account.pubkey(), loaded_account.lamports()))
+            }));
+        }
+        index.largest(root, &unrooted_writes, num, filter_by_address, filter)
+    }
+
+    // Scans for the `num` largest accounts passing `filter`, and in the same pass for the
+    // `all_num` largest accounts, none if unset
+    fn scan_largest_accounts(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        num: usize,
+        filter_by_address: &HashSet<Pubkey>,
+        filter: &AccountAddressFilter,
+        all_num: Option<usize>,
+    ) -> ScanResult<(LargestAccountsHeap, LargestAccountsHeap)> {
+        fn insert(collector: &mut LargestAccountsHeap, num: usize, entry: (u64, Pubkey)) {
+            if num == 0 {
+                return;
+            }
+            if collector.len() == num {
+                let Reverse(smallest) = collector
+                    .peek()
+                    .expect("BinaryHeap::peek should succeed when len > 0");
+                if *smallest >= entry {
+                    return;
+                }
+                collector.pop();
+            }
+            collector.push(Reverse(entry));
+        }
+
+        self.accounts_db.scan_accounts(
+            ancestors,
+            bank_id,
+            |collector: &mut (LargestAccountsHeap, LargestAccountsHeap), option| {
                 if let Some((pubkey, account, _slot)) = option {
                     if account.lamports() == 0 {
                         return;
                     }
+                    let entry = (account.lamports(), *pubkey);
+                    if let Some(all_num) = all_num {
+                        insert(&mut collector.1, all_num, entry);
+                    }
                     let contains_address = filter_by_address.contains(pubkey);
                     let collect = match filter {
                         AccountAddressFilter::Exclude => !contains_address,
                         AccountAddressFilter::Include => contains_address,
                     };
-                    if !collect {
-                        return;
+                    if collect {
+                        insert(&mut collector.0, num, entry);
                     }
-                    if collector.len() == num {
-                        let Reverse(entry) = collector
-                            .peek()
-                            .expect("BinaryHeap::peek should succeed when len > 0");
-                        if *entry >= (account.lamports(), *pubkey) {
-                            return;
-                        }
-                        collector.pop();
-                    }
-                    collector.push(Reverse((account.lamports(), *pubkey)));
                 }
             },
-        )?;
-        Ok(account_balances
-            .into_sorted_vec()
-            .into_iter()
-            .map(|Reverse((balance, pubkey))| (pubkey, balance))
-            .collect())
+        )
     }
 
     pub fn calculate_capitalization(
@@ -738,6 +936,36 @@ impl Accounts {
         }
     }
 
+    fn load_loaded_while_filtering<F: Fn(&LoadedAccount) -> bool>(
+        collector: &mut Vec<(Pubkey, AccountSharedData)>,
+        some_account_tuple: Option<(&Pubkey, LoadedAccount, Slot)>,
+        filter: F,
+    ) {
+        if let Some(mapped_account_tuple) = some_account_tuple
+            .filter(|(_, loaded_account, _)| {
+                Self::is_loadable(loaded_account.lamports()) && filter(loaded_account)
+            })
+            .map(|(pubkey, loaded_account, _slot)| (*pubkey, loaded_account.take_account()))
+        {
+            collector.push(mapped_account_tuple)
+        }
+    }
+
+    fn load_metadata_while_filtering<F: Fn(&AccountScanMetadata) -> bool>(
+        collector: &mut Vec<AccountScanMetadata>,
+        some_account_tuple: Option<(&Pubkey, LoadedAccount, Slot)>,
+        filter: F,
+    ) {
+        if let Some(metadata) = some_account_tuple
+            .map(|(pubkey, loaded_account, _slot)| {
+                AccountScanMetadata::new(pubkey, &loaded_account)
+            })
+            .filter(|metadata| Self::is_loadable(metadata.lamports) && filter(metadata))
+        {
+            collector.push(metadata)
+        }
+    }
+
     pub fn load_by_program(
         &self,
         ancestors: &Ancestors,
@@ -794,6 +1022,200 @@ impl Accounts {
             .map(|result| result.0)
     }
 
+    /// Like `load_by_program_with_filter`, but `filter` is evaluated against the accounts as
+    /// loaded from storage, so that the data of the accounts it rejects is never copied
+    pub fn load_by_program_with_loaded_filter<F: Fn(&LoadedAccount) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        filter: F,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        self.accounts_db.scan_loaded_accounts(
+            ancestors,
+            bank_id,
+            |collector: &mut Vec<(Pubkey, AccountSharedData)>, some_account_tuple| {
+                Self::load_loaded_while_filtering(collector, some_account_tuple, |account| {
+                    account.owner() == program_id && filter(account)
+                })
+            },
+        )
+    }
+
+    /// Like `load_by_index_key_with_filter`, but `filter` is evaluated against the accounts as
+    /// loaded from storage
+    pub fn load_by_index_key_with_loaded_filter<F: Fn(&LoadedAccount) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: &IndexKey,
+        filter: F,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        self.accounts_db
+            .index_scan_loaded_accounts(
+                ancestors,
+                bank_id,
+                *index_key,
+                |collector: &mut Vec<(Pubkey, AccountSharedData)>, some_account_tuple| {
+                    Self::load_loaded_while_filtering(collector, some_account_tuple, &filter)
+                },
+            )
+            .map(|result| result.0)
+    }
+
+    /// Like `load_by_program_with_loaded_filter`, but only scans the accounts whose pubkeys are
+    /// in `range`.  They are returned in pubkey order
+    pub fn load_by_program_in_range<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        range: R,
+        filter: F,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>>
+    where
+        F: Fn(&LoadedAccount) -> bool,
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        self.accounts_db.scan_loaded_accounts_in_range(
+            ancestors,
+            bank_id,
+            range,
+            |collector: &mut Vec<(Pubkey, AccountSharedData)>, some_account_tuple| {
+                Self::load_loaded_while_filtering(collector, some_account_tuple, |account| {
+                    account.owner() == program_id && filter(account)
+                })
+            },
+        )
+    }
+
+    /// Like `load_by_program_with_filter`, but only loads the metadata of the accounts
+    pub fn load_metadata_by_program_with_filter<F: Fn(&AccountScanMetadata) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        filter: F,
+    ) -> ScanResult<Vec<AccountScanMetadata>> {
+        self.accounts_db.scan_loaded_accounts(
+            ancestors,
+            bank_id,
+            |collector: &mut Vec<AccountScanMetadata>, some_account_tuple| {
+                Self::load_metadata_while_filtering(collector, some_account_tuple, |metadata| {
+                    metadata.owner == *program_id && filter(metadata)
+                })
+            },
+        )
+    }
+
+    /// Like `load_by_index_key_with_filter`, but only loads the metadata of the accounts
+    pub fn load_metadata_by_index_key_with_filter<F: Fn(&AccountScanMetadata) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: &IndexKey,
+        filter: F,
+    ) -> ScanResult<Vec<AccountScanMetadata>> {
+        self.accounts_db
+            .index_scan_loaded_accounts(
+                ancestors,
+                bank_id,
+                *index_key,
+                |collector: &mut Vec<AccountScanMetadata>, some_account_tuple| {
+                    Self::load_metadata_while_filtering(collector, some_account_tuple, &filter)
+                },
+            )
+            .map(|result| result.0)
+    }
+
+    // Keeps the `num` largest keys seen in `collector`
+    fn collect_largest<K: Ord>(collector: &mut BinaryHeap<Reverse<K>>, num: usize, key: K) {
+        if collector.len() == num {
+            match collector.peek() {
+                Some(Reverse(smallest)) if *smallest < key => {
+                    collector.pop();
+                }
+                _ => return,
+            }
+        }
+        collector.push(Reverse(key));
+    }
+
+    /// The `num` largest keys `rank` returns for the accounts of `program_id`, largest first.
+    /// Accounts it returns `None` for are skipped
+    pub fn load_largest_by_program<K, F>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        num: usize,
+        rank: F,
+    ) -> ScanResult<Vec<K>>
+    where
+        K: Ord,
+        F: Fn(&Pubkey, &AccountSharedData) -> Option<K>,
+    {
+        if num == 0 {
+            return Ok(vec![]);
+        }
+        let largest = self.accounts_db.scan_accounts(
+            ancestors,
+            bank_id,
+            |collector: &mut BinaryHeap<Reverse<K>>, some_account_tuple| {
+                if let Some(key) = some_account_tuple
+                    .filter(|(_, account, _)| {
+                        Self::is_loadable(account.lamports()) && account.owner() == program_id
+                    })
+                    .and_then(|(pubkey, account, _slot)| rank(pubkey, &account))
+                {
+                    Self::collect_largest(collector, num, key);
+                }
+            },
+        )?;
+        Ok(largest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(key)| key)
+            .collect())
+    }
+
+    /// The `num` largest keys `rank` returns for the accounts under `index_key` in the secondary
+    /// indexes, largest first.  Accounts it returns `None` for are skipped
+    pub fn load_largest_by_index_key<K, F>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: &IndexKey,
+        num: usize,
+        rank: F,
+    ) -> ScanResult<Vec<K>>
+    where
+        K: Ord,
+        F: Fn(&Pubkey, &AccountSharedData) -> Option<K>,
+    {
+        if num == 0 {
+            return Ok(vec![]);
+        }
+        let (largest, _used_index) = self.accounts_db.index_scan_accounts(
+            ancestors,
+            bank_id,
+            *index_key,
+            |collector: &mut BinaryHeap<Reverse<K>>, some_account_tuple| {
+                if let Some(key) = some_account_tuple
+                    .filter(|(_, account, _)| Self::is_loadable(account.lamports()))
+                    .and_then(|(pubkey, account, _slot)| rank(pubkey, &account))
+                {
+                    Self::collect_largest(collector, num, key);
+                }
+            },
+        )?;
+        Ok(largest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(key)| key)
+            .collect())
+    }
+
     pub fn account_indexes_include_key(&self, key: &Pubkey) -> bool {
         self.accounts_db.account_indexes.include_key(key)
     }
@@ -994,7 +1416,15 @@ impl Accounts {
 
     /// Add a slot to root.  Root slots cannot be purged
     pub fn add_root(&self, slot: Slot) {
-        self.accounts_db.add_root(slot)
+        self.accounts_db.add_root(slot);
+        if let Some(index) = &self.accounts_db.largest_accounts_index {
+            index.update(
+                slot,
+                self.scan_slot(slot, |loaded_account| {
+                    Some((*loaded_account.pubkey(), loaded_account.lamports()))
+                }),
+            );
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1035,6 +1465,14 @@ impl Accounts {
                     let maybe_fee_account = nonce_rollback.fee_account();
                     Some((pubkey, acc, maybe_fee_account, !nonce_marker_ix_failed))
                 }
+                // the transaction was executed, but its accounts are rolled back like for an
+                // instruction error
+                (Err(TransactionError::InvalidRentPayingAccount), Some(nonce_rollback)) => {
+                    let pubkey = nonce_rollback.nonce_address();
+                    let acc = nonce_rollback.nonce_account();
+                    let maybe_fee_account = nonce_rollback.fee_account();
+                    Some((pubkey, acc, maybe_fee_account, true))
+                }
                 (Ok(_), _nonce_rollback) => None,
                 (Err(_), _nonce_rollback) => continue,
             };
@@ -1166,7 +1604,7 @@ pub fn update_accounts_bench(accounts: &Accounts, pubkeys: &[Pubkey], slot: u64)
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rent_collector::RentCollector;
+    use crate::{accounts_index::pubkey_range_from_prefix, rent_collector::RentCollector};
     use solana_sdk::{
         account::{AccountSharedData, WritableAccount},
         epoch_schedule::EpochSchedule,
@@ -1225,6 +1663,8 @@ mod tests {
             error_counters,
             rent_collector,
             &FeatureSet::all_enabled(),
+            &FeeStructure::default(),
+            &HashMap::new(),
         )
     }
 
@@ -1387,6 +1827,11 @@ mod tests {
         let fee = Bank::calculate_fee(
             &SanitizedMessage::try_from(tx.message().clone()).unwrap(),
             10,
+            &FeeStructure::default(),
+            &HashMap::new(),
+            false,
+            false,
+            true,
         );
         assert_eq!(fee, 10);
 
@@ -1776,6 +2221,141 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_metadata_by_program_with_filter() {
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+        );
+        let program_id = Pubkey::new_unique();
+        let pubkeys: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        accounts.store_slow_uncached(0, &pubkeys[0], &AccountSharedData::new(1, 10, &program_id));
+        accounts.store_slow_uncached(0, &pubkeys[1], &AccountSharedData::new(2, 20, &program_id));
+        // Zero-lamport accounts and accounts of other programs aren't returned
+        accounts.store_slow_uncached(0, &pubkeys[2], &AccountSharedData::new(0, 10, &program_id));
+        accounts.store_slow_uncached(
+            0,
+            &pubkeys[3],
+            &AccountSharedData::new(1, 10, &Pubkey::new_unique()),
+        );
+
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let mut loaded = accounts
+            .load_metadata_by_program_with_filter(&ancestors, 0, &program_id, |_| true)
+            .unwrap();
+        loaded.sort_by_key(|metadata| metadata.lamports);
+        assert_eq!(
+            loaded,
+            vec![
+                AccountScanMetadata {
+                    pubkey: pubkeys[0],
+                    owner: program_id,
+                    lamports: 1,
+                    data_len: 10,
+                },
+                AccountScanMetadata {
+                    pubkey: pubkeys[1],
+                    owner: program_id,
+                    lamports: 2,
+                    data_len: 20,
+                },
+            ]
+        );
+        let loaded = accounts
+            .load_metadata_by_program_with_filter(&ancestors, 0, &program_id, |metadata| {
+                metadata.data_len == 20
+            })
+            .unwrap();
+        assert_eq!(
+            loaded
+                .iter()
+                .map(|metadata| metadata.pubkey)
+                .collect::<Vec<_>>(),
+            vec![pubkeys[1]]
+        );
+    }
+
+    #[test]
+    fn test_load_by_program_with_loaded_filter() {
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+        );
+        let program_id = Pubkey::new_unique();
+        let pubkeys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let stored_accounts: Vec<_> = (0..3)
+            .map(|i| {
+                let mut account = AccountSharedData::new(1, 4, &program_id);
+                account.set_data(vec![i; 4]);
+                account
+            })
+            .collect();
+        for (pubkey, account) in pubkeys.iter().zip(&stored_accounts) {
+            accounts.store_slow_uncached(0, pubkey, account);
+        }
+        // Owned by another program
+        let mut account = AccountSharedData::new(1, 4, &Pubkey::new_unique());
+        account.set_data(vec![1; 4]);
+        accounts.store_slow_uncached(0, &Pubkey::new_unique(), &account);
+
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let loaded = accounts
+            .load_by_program_with_loaded_filter(&ancestors, 0, &program_id, |account| {
+                account.data()[0] == 1
+            })
+            .unwrap();
+        assert_eq!(loaded, vec![(pubkeys[1], stored_accounts[1].clone())]);
+    }
+
+    #[test]
+    fn test_load_by_program_in_range() {
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+        );
+        let program_id = Pubkey::new_unique();
+        let pubkeys: Vec<_> = [[1; 32], [2; 32], [2; 32], [3; 32]]
+            .iter()
+            .enumerate()
+            .map(|(i, bytes)| {
+                let mut bytes = *bytes;
+                bytes[31] = 10 + i as u8;
+                Pubkey::new_from_array(bytes)
+            })
+            .collect();
+        let account = AccountSharedData::new(1, 0, &program_id);
+        for pubkey in &pubkeys {
+            accounts.store_slow_uncached(0, pubkey, &account);
+        }
+        // Owned by another program
+        let other_account = AccountSharedData::new(1, 0, &Pubkey::new_unique());
+        accounts.store_slow_uncached(0, &Pubkey::new(&[2; 32]), &other_account);
+
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let loaded = accounts
+            .load_by_program_in_range(
+                &ancestors,
+                0,
+                &program_id,
+                pubkey_range_from_prefix(&[2]),
+                |_| true,
+            )
+            .unwrap();
+        assert_eq!(
+            loaded,
+            vec![(pubkeys[1], account.clone()), (pubkeys[2], account)]
+        );
+    }
+
     #[test]
     fn test_load_accounts_executable_with_write_lock() {
         let mut accounts: Vec<(Pubkey, AccountSharedData)> = Vec::new();
@@ -2366,6 +2946,8 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                accounts_data_len_delta: 0,
+                program_events: vec![],
             }),
             None,
         );
@@ -2376,6 +2958,8 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                accounts_data_len_delta: 0,
+                program_events: vec![],
             }),
             None,
         );
@@ -2475,6 +3059,8 @@ mod tests {
             &mut error_counters,
             &rent_collector,
             &FeatureSet::all_enabled(),
+            &FeeStructure::default(),
+            &HashMap::new(),
         )
     }
 
@@ -2752,6 +3338,8 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                accounts_data_len_delta: 0,
+                program_events: vec![],
             }),
             nonce_rollback.clone(),
         );
@@ -2863,6 +3451,8 @@ mod tests {
                 program_indices: vec![],
                 rent: 0,
                 rent_debits: RentDebits::default(),
+                accounts_data_len_delta: 0,
+                program_events: vec![],
             }),
             nonce_rollback.clone(),
         );
@@ -2913,6 +3503,113 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_load_largest_accounts_with_index() {
+        let accounts = Accounts::new_with_config(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+            Some(AccountsDbConfig {
+                largest_accounts_index_capacity: Some(2),
+                ..ACCOUNTS_DB_CONFIG_FOR_TESTING
+            }),
+            None,
+        );
+        let pubkeys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for (pubkey, lamports) in pubkeys.iter().zip([10, 20, 30]) {
+            let account = AccountSharedData::new(lamports, 0, &Pubkey::default());
+            accounts.store_slow_uncached(0, pubkey, &account);
+        }
+        accounts.add_root(0);
+
+        let largest = |slots: &[Slot], num| {
+            let ancestors = slots.iter().map(|slot| (*slot, 0)).collect();
+            accounts
+                .load_largest_accounts(
+                    &ancestors,
+                    0,
+                    num,
+                    &HashSet::new(),
+                    AccountAddressFilter::Exclude,
+                )
+                .unwrap()
+        };
+        // The first query scans the latest root, which builds the index
+        assert_eq!(largest(&[0], 2), vec![(pubkeys[2], 30), (pubkeys[1], 20)]);
+        let index = accounts
+            .accounts_db
+            .largest_accounts_index
+            .as_ref()
+            .unwrap();
+        assert_eq!(index.root(), Some(0));
+
+        // Rooting a slot updates the index
+        let account = AccountSharedData::new(40, 0, &Pubkey::default());
+        accounts.store_slow_uncached(1, &pubkeys[0], &account);
+        accounts.add_root(1);
+        assert_eq!(index.root(), Some(1));
+        assert_eq!(
+            largest(&[0, 1], 2),
+            vec![(pubkeys[0], 40), (pubkeys[2], 30)]
+        );
+
+        // The writes of unrooted slots are applied on top of the index
+        let account = AccountSharedData::new(50, 0, &Pubkey::default());
+        accounts.store_slow_uncached(2, &pubkeys[1], &account);
+        assert_eq!(
+            largest(&[0, 1, 2], 2),
+            vec![(pubkeys[1], 50), (pubkeys[0], 40)]
+        );
+        assert_eq!(
+            largest(&[0, 1], 2),
+            vec![(pubkeys[0], 40), (pubkeys[2], 30)]
+        );
+    }
+
+    #[test]
+    fn test_load_largest_by_program() {
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+        );
+        let program_id = Pubkey::new_unique();
+        let pubkeys: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        for (pubkey, lamports) in pubkeys.iter().zip([3, 5, 0, 4, 9]) {
+            let account = AccountSharedData::new(lamports, 1, &program_id);
+            accounts.store_slow_uncached(0, pubkey, &account);
+        }
+        // Owned by another program
+        let account = AccountSharedData::new(100, 1, &Pubkey::new_unique());
+        accounts.store_slow_uncached(0, &Pubkey::new_unique(), &account);
+
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let rank = |pubkey: &Pubkey, account: &AccountSharedData| {
+            (*pubkey != pubkeys[4]).then(|| (account.lamports(), *pubkey))
+        };
+        assert_eq!(
+            accounts
+                .load_largest_by_program(&ancestors, 0, &program_id, 2, rank)
+                .unwrap(),
+            vec![(5, pubkeys[1]), (4, pubkeys[3])]
+        );
+        // Zero-lamport and skipped accounts aren't returned
+        assert_eq!(
+            accounts
+                .load_largest_by_program(&ancestors, 0, &program_id, 10, rank)
+                .unwrap(),
+            vec![(5, pubkeys[1]), (4, pubkeys[3]), (3, pubkeys[0])]
+        );
+        assert!(accounts
+            .load_largest_by_program(&ancestors, 0, &program_id, 0, rank)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_load_largest_accounts() {
         let accounts = Accounts::new_with_config_for_tests(
@@ -106,6 +106,14 @@ impl SnapshotRequestHandler {
                     status_cache_slot_deltas,
                 } = snapshot_request;
 
+                // The snapshot cleans past the root, so calculate any epoch accounts hash
+                // requested for an earlier root first
+                snapshot_root_bank
+                    .rc
+                    .accounts
+                    .accounts_db
+                    .calculate_requested_epoch_accounts_hash();
+
                 let previous_hash = if test_hash_calculation {
                     // We have to use the index version here.
                     // We cannot calculate the non-index way because cache has not been flushed and stores don't match reality.
@@ -276,6 +284,8 @@ impl SnapshotRequestHandler {
             SnapshotError::MismatchedBaseSlot(..) => true,
             SnapshotError::NoSnapshotArchives => true,
             SnapshotError::MismatchedSlotHash(..) => true,
+            SnapshotError::MismatchedStorageChecksum(..) => true,
+            SnapshotError::MissingStorageChecksums(..) => true,
         }
     }
 }
@@ -392,6 +402,7 @@ impl AccountsBackgroundService {
                     );
 
                     Self::expire_old_recycle_stores(&bank, &mut last_expiration_check_time);
+                    bank.preallocate_stores();
 
                     let non_snapshot_time = last_snapshot_end_time
                         .map(|last_snapshot_end_time: Instant| {
@@ -399,6 +410,14 @@ impl AccountsBackgroundService {
                         })
                         .unwrap_or_default();
 
+                    // Calculate the epoch accounts hash requested when its calculation bank was
+                    // rooted.  Any such bank is <= `bank`, so this runs before the flush and
+                    // clean below can drop the versions of the accounts it hashes.
+                    bank.rc
+                        .accounts
+                        .accounts_db
+                        .calculate_requested_epoch_accounts_hash();
+
                     // Check to see if there were any requests for snapshotting banks
                     // < the current root bank `bank` above.
 
@@ -23,10 +23,34 @@ pub struct SlotCacheInner {
     same_account_writes: AtomicU64,
     same_account_writes_size: AtomicU64,
     unique_account_writes_size: AtomicU64,
+    /// Bytes of account data currently held by this slot's cache
+    size: AtomicU64,
+    /// Bytes of account data held by all the slot caches of the owning `AccountsCache`
+    total_size: Arc<AtomicU64>,
     is_frozen: AtomicBool,
 }
 
+impl Drop for SlotCacheInner {
+    fn drop(&mut self) {
+        // The data of this slot is no longer dirty once it's been flushed or purged
+        self.total_size
+            .fetch_sub(*self.size.get_mut(), Ordering::Relaxed);
+    }
+}
+
 impl SlotCacheInner {
+    fn new(total_size: Arc<AtomicU64>) -> Self {
+        Self {
+            cache: DashMap::default(),
+            same_account_writes: AtomicU64::default(),
+            same_account_writes_size: AtomicU64::default(),
+            unique_account_writes_size: AtomicU64::default(),
+            size: AtomicU64::default(),
+            total_size,
+            is_frozen: AtomicBool::default(),
+        }
+    }
+
     pub fn report_slot_store_metrics(&self) {
         datapoint_info!(
             "slot_repeated_writes",
@@ -59,24 +83,42 @@ impl SlotCacheInner {
         hash: Option<impl Borrow<Hash>>,
         slot: Slot,
     ) -> CachedAccount {
-        if self.cache.contains_key(pubkey) {
-            self.same_account_writes.fetch_add(1, Ordering::Relaxed);
-            self.same_account_writes_size
-                .fetch_add(account.data().len() as u64, Ordering::Relaxed);
-        } else {
-            self.unique_account_writes_size
-                .fetch_add(account.data().len() as u64, Ordering::Relaxed);
-        }
+        let data_len = account.data().len() as u64;
         let item = Arc::new(CachedAccountInner {
             account,
             hash: RwLock::new(hash.map(|h| *h.borrow())),
             slot,
             pubkey: *pubkey,
         });
-        self.cache.insert(*pubkey, item.clone());
+        if let Some(old) = self.cache.insert(*pubkey, item.clone()) {
+            self.same_account_writes.fetch_add(1, Ordering::Relaxed);
+            self.same_account_writes_size
+                .fetch_add(data_len, Ordering::Relaxed);
+
+            let old_data_len = old.account.data().len() as u64;
+            if data_len > old_data_len {
+                self.add_size(data_len - old_data_len);
+            } else {
+                self.sub_size(old_data_len - data_len);
+            }
+        } else {
+            self.unique_account_writes_size
+                .fetch_add(data_len, Ordering::Relaxed);
+            self.add_size(data_len);
+        }
         item
     }
 
+    fn add_size(&self, bytes: u64) {
+        self.size.fetch_add(bytes, Ordering::Relaxed);
+        self.total_size.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn sub_size(&self, bytes: u64) {
+        self.size.fetch_sub(bytes, Ordering::Relaxed);
+        self.total_size.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
     pub fn get_cloned(&self, pubkey: &Pubkey) -> Option<CachedAccount> {
         self.cache
             .get(pubkey)
@@ -98,6 +140,11 @@ impl SlotCacheInner {
         self.unique_account_writes_size.load(Ordering::Relaxed)
             + self.same_account_writes_size.load(Ordering::Relaxed)
     }
+
+    /// Bytes of account data not yet flushed to storage
+    pub fn size(&self) -> u64 {
+        self.size.load(Ordering::Relaxed)
+    }
 }
 
 impl Deref for SlotCacheInner {
@@ -146,6 +193,8 @@ pub struct AccountsCache {
     // could have triggered a flush of this slot already
     maybe_unflushed_roots: RwLock<BTreeSet<Slot>>,
     max_flushed_root: AtomicU64,
+    // Bytes of account data held by all the slot caches, i.e. not yet flushed to storage
+    total_size: Arc<AtomicU64>,
 }
 
 impl AccountsCache {
@@ -169,6 +218,7 @@ impl AccountsCache {
             ),
             ("num_slots", self.cache.len(), i64),
             ("total_unique_writes_size", total_unique_writes_size, i64),
+            ("total_size", self.size(), i64),
         );
     }
 
@@ -187,7 +237,7 @@ impl AccountsCache {
             self
                 .cache
                 .entry(slot)
-                .or_insert(Arc::new(SlotCacheInner::default()))
+                .or_insert(Arc::new(SlotCacheInner::new(self.total_size.clone())))
                 .clone());
 
         slot_cache.insert(pubkey, account, hash, slot)
@@ -268,6 +318,11 @@ impl AccountsCache {
         self.cache.len()
     }
 
+    /// Bytes of account data held by the cache that haven't been flushed to storage yet
+    pub fn size(&self) -> u64 {
+        self.total_size.load(Ordering::Relaxed)
+    }
+
     pub fn fetch_max_flush_root(&self) -> Slot {
         self.max_flushed_root.load(Ordering::Relaxed)
     }
@@ -322,4 +377,35 @@ pub mod tests {
         // If the cache is told the size limit is 0, it should return the one frozen slot
         assert_eq!(cache.find_older_frozen_slots(0), vec![inserted_slot]);
     }
+
+    #[test]
+    fn test_cache_size() {
+        let cache = AccountsCache::default();
+        let pubkey = Pubkey::new_unique();
+        let store = |slot, pubkey: &Pubkey, data_len| {
+            cache.store(
+                slot,
+                pubkey,
+                AccountSharedData::new(1, data_len, &Pubkey::default()),
+                Some(&Hash::default()),
+            );
+        };
+        store(0, &pubkey, 100);
+        store(0, &Pubkey::new_unique(), 50);
+        assert_eq!(cache.size(), 150);
+
+        // Overwriting an account only counts its latest data
+        store(0, &pubkey, 20);
+        assert_eq!(cache.slot_cache(0).unwrap().size(), 70);
+        store(1, &pubkey, 30);
+        assert_eq!(cache.size(), 100);
+
+        // Removed slots no longer count once they're dropped
+        let removed = cache.remove_slot(0).unwrap();
+        assert_eq!(cache.size(), 100);
+        drop(removed);
+        assert_eq!(cache.size(), 30);
+        cache.remove_slots_le(1);
+        assert_eq!(cache.size(), 0);
+    }
 }
@@ -33,6 +33,8 @@ use crate::{
     append_vec::{AppendVec, StoredAccountMeta, StoredMeta, StoredMetaWriteVersion},
     cache_hash_data::CacheHashData,
     contains::Contains,
+    epoch_accounts_hash::{EpochAccountsHash, EpochAccountsHashManager},
+    largest_accounts_index::LargestAccountsIndex,
     pubkey_bins::PubkeyBinCalculator24,
     read_only_accounts_cache::ReadOnlyAccountsCache,
     sorted_storages::SortedStorages,
@@ -83,6 +85,7 @@ const PAGE_SIZE: u64 = 4 * 1024;
 const MAX_RECYCLE_STORES: usize = 1000;
 const STORE_META_OVERHEAD: usize = 256;
 const MAX_CACHE_SLOTS: usize = 200;
+pub const WRITE_CACHE_LIMIT_BYTES_DEFAULT: u64 = 15_000_000_000;
 const FLUSH_CACHE_RANDOM_THRESHOLD: usize = MAX_LOCKOUT_HISTORY;
 const SCAN_SLOT_PAR_ITER_THRESHOLD: usize = 4000;
 
@@ -127,12 +130,18 @@ pub const ACCOUNTS_DB_CONFIG_FOR_TESTING: AccountsDbConfig = AccountsDbConfig {
     accounts_hash_cache_path: None,
     filler_account_count: None,
     hash_calc_num_passes: None,
+    write_cache_limit_bytes: None,
+    preallocated_store_count: None,
+    largest_accounts_index_capacity: None,
 };
 pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig {
     index: Some(ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS),
     accounts_hash_cache_path: None,
     filler_account_count: None,
     hash_calc_num_passes: None,
+    write_cache_limit_bytes: None,
+    preallocated_store_count: None,
+    largest_accounts_index_capacity: None,
 };
 
 pub type BinnedHashData = Vec<Vec<CalculateHashIntermediate>>;
@@ -143,6 +152,14 @@ pub struct AccountsDbConfig {
     pub accounts_hash_cache_path: Option<PathBuf>,
     pub filler_account_count: Option<usize>,
     pub hash_calc_num_passes: Option<usize>,
+    /// Bytes of unflushed account data the write cache may hold before older slots are flushed
+    /// to storage, `WRITE_CACHE_LIMIT_BYTES_DEFAULT` if unset
+    pub write_cache_limit_bytes: Option<u64>,
+    /// Empty stores kept pre-faulted in the recycle stores so that storing accounts for a new
+    /// slot doesn't have to create and size a file, none if unset
+    pub preallocated_store_count: Option<usize>,
+    /// Keep an index of this many of the largest rooted accounts, none if unset
+    pub largest_accounts_index_capacity: Option<usize>,
 }
 
 struct FoundStoredAccount<'a> {
@@ -519,6 +536,22 @@ impl<'a> LoadedAccount<'a> {
         }
     }
 
+    pub fn data(&self) -> &[u8] {
+        match self {
+            LoadedAccount::Stored(stored_account_meta) => stored_account_meta.data,
+            LoadedAccount::Cached((_, cached_account)) => cached_account.account.data(),
+        }
+    }
+
+    pub fn data_len(&self) -> usize {
+        match self {
+            LoadedAccount::Stored(stored_account_meta) => {
+                stored_account_meta.meta.data_len as usize
+            }
+            LoadedAccount::Cached((_, cached_account)) => cached_account.account.data().len(),
+        }
+    }
+
     pub fn take_account(self) -> AccountSharedData {
         match self {
             LoadedAccount::Stored(stored_account_meta) => stored_account_meta.clone_account(),
@@ -1057,6 +1090,19 @@ pub struct AccountsDb {
     // lower passes = faster total time, higher dynamic memory usage
     // passes=2 cuts dynamic memory usage in approximately half.
     pub num_hash_scan_passes: Option<usize>,
+
+    /// Bytes of unflushed account data the write cache may hold before the background flush
+    /// starts flushing older slots regardless of the number of cached slots
+    write_cache_limit_bytes: u64,
+
+    /// Empty stores `preallocate_stores()` keeps in the recycle stores
+    preallocated_store_count: usize,
+
+    /// The largest rooted accounts, maintained by `Accounts::add_root()`
+    pub largest_accounts_index: Option<LargestAccountsIndex>,
+
+    /// The epoch accounts hash calculation of the most recently rooted calculation bank
+    pub epoch_accounts_hash_manager: EpochAccountsHashManager,
 }
 
 #[derive(Debug, Default)]
@@ -1077,6 +1123,8 @@ struct AccountsStats {
     store_total_data: AtomicU64,
     recycle_store_count: AtomicU64,
     create_store_count: AtomicU64,
+    preallocated_store_count: AtomicU64,
+    preallocate_stores_us: AtomicU64,
     store_get_slot_store: AtomicU64,
     store_find_existing: AtomicU64,
     dropped_stores: AtomicU64,
@@ -1586,6 +1634,10 @@ impl AccountsDb {
             filler_account_count: 0,
             filler_account_suffix: None,
             num_hash_scan_passes,
+            write_cache_limit_bytes: WRITE_CACHE_LIMIT_BYTES_DEFAULT,
+            preallocated_store_count: 0,
+            largest_accounts_index: None,
+            epoch_accounts_hash_manager: EpochAccountsHashManager::default(),
         }
     }
 
@@ -1634,6 +1686,18 @@ impl AccountsDb {
             accounts_update_notifier,
             filler_account_count,
             filler_account_suffix,
+            write_cache_limit_bytes: accounts_db_config
+                .as_ref()
+                .and_then(|cfg| cfg.write_cache_limit_bytes)
+                .unwrap_or(WRITE_CACHE_LIMIT_BYTES_DEFAULT),
+            preallocated_store_count: accounts_db_config
+                .as_ref()
+                .and_then(|cfg| cfg.preallocated_store_count)
+                .unwrap_or_default(),
+            largest_accounts_index: accounts_db_config
+                .as_ref()
+                .and_then(|cfg| cfg.largest_accounts_index_capacity)
+                .map(LargestAccountsIndex::new),
             ..Self::default_with_accounts_index(
                 accounts_index,
                 accounts_hash_cache_path,
@@ -3031,22 +3095,87 @@ impl AccountsDb {
     where
         F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
         A: Default,
+    {
+        self.scan_loaded_accounts(ancestors, bank_id, |collector, loaded_account_slot| {
+            scan_func(
+                collector,
+                loaded_account_slot.map(|(pubkey, loaded_account, slot)| {
+                    (pubkey, loaded_account.take_account(), slot)
+                }),
+            )
+        })
+    }
+
+    /// Like `scan_accounts`, but hands `scan_func` the accounts as loaded from storage so that
+    /// scans only interested in their metadata don't copy their data
+    pub fn scan_loaded_accounts<F, A>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        scan_func: F,
+    ) -> ScanResult<A>
+    where
+        F: Fn(&mut A, Option<(&Pubkey, LoadedAccount, Slot)>),
+        A: Default,
     {
         let mut collector = A::default();
 
         // This can error out if the slots being scanned over are aborted
         self.accounts_index
             .scan_accounts(ancestors, bank_id, |pubkey, (account_info, slot)| {
-                let account_slot = self
-                    .get_account_accessor(slot, pubkey, account_info.store_id, account_info.offset)
+                let mut account_accessor = self.get_account_accessor(
+                    slot,
+                    pubkey,
+                    account_info.store_id,
+                    account_info.offset,
+                );
+                let account_slot = account_accessor
                     .get_loaded_account()
-                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                    .map(|loaded_account| (pubkey, loaded_account, slot));
                 scan_func(&mut collector, account_slot)
             })?;
 
         Ok(collector)
     }
 
+    /// Like `scan_loaded_accounts`, but only scans the accounts whose pubkeys are in `range`, in
+    /// pubkey order
+    pub fn scan_loaded_accounts_in_range<F, A, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        range: R,
+        scan_func: F,
+    ) -> ScanResult<A>
+    where
+        F: Fn(&mut A, Option<(&Pubkey, LoadedAccount, Slot)>),
+        A: Default,
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        let mut collector = A::default();
+
+        // This can error out if the slots being scanned over are aborted
+        self.accounts_index.scan_accounts_in_range(
+            ancestors,
+            bank_id,
+            range,
+            |pubkey, (account_info, slot)| {
+                let mut account_accessor = self.get_account_accessor(
+                    slot,
+                    pubkey,
+                    account_info.store_id,
+                    account_info.offset,
+                );
+                let account_slot = account_accessor
+                    .get_loaded_account()
+                    .map(|loaded_account| (pubkey, loaded_account, slot));
+                scan_func(&mut collector, account_slot)
+            },
+        )?;
+
+        Ok(collector)
+    }
+
     pub fn unchecked_scan_accounts<F, A>(
         &self,
         metric_name: &'static str,
@@ -3125,6 +3254,33 @@ impl AccountsDb {
     where
         F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
         A: Default,
+    {
+        self.index_scan_loaded_accounts(
+            ancestors,
+            bank_id,
+            index_key,
+            |collector, loaded_account_slot| {
+                scan_func(
+                    collector,
+                    loaded_account_slot.map(|(pubkey, loaded_account, slot)| {
+                        (pubkey, loaded_account.take_account(), slot)
+                    }),
+                )
+            },
+        )
+    }
+
+    /// Like `index_scan_accounts`, but hands `scan_func` the accounts as loaded from storage
+    pub fn index_scan_loaded_accounts<F, A>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: IndexKey,
+        scan_func: F,
+    ) -> ScanResult<(A, bool)>
+    where
+        F: Fn(&mut A, Option<(&Pubkey, LoadedAccount, Slot)>),
+        A: Default,
     {
         let key = match &index_key {
             IndexKey::ProgramId(key) => key,
@@ -3134,7 +3290,7 @@ impl AccountsDb {
         if !self.account_indexes.include_key(key) {
             // the requested key was not indexed in the secondary index, so do a normal scan
             let used_index = false;
-            let scan_result = self.scan_accounts(ancestors, bank_id, scan_func)?;
+            let scan_result = self.scan_loaded_accounts(ancestors, bank_id, scan_func)?;
             return Ok((scan_result, used_index));
         }
 
@@ -3144,10 +3300,15 @@ impl AccountsDb {
             bank_id,
             index_key,
             |pubkey, (account_info, slot)| {
-                let account_slot = self
-                    .get_account_accessor(slot, pubkey, account_info.store_id, account_info.offset)
+                let mut account_accessor = self.get_account_accessor(
+                    slot,
+                    pubkey,
+                    account_info.store_id,
+                    account_info.offset,
+                );
+                let account_slot = account_accessor
                     .get_loaded_account()
-                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                    .map(|loaded_account| (pubkey, loaded_account, slot));
                 scan_func(&mut collector, account_slot)
             },
         )?;
@@ -4476,22 +4637,82 @@ impl AccountsDb {
             .fetch_add(recycle_stores_write_elapsed.as_us(), Ordering::Relaxed);
     }
 
+    /// Top up the recycle stores with empty, pre-faulted stores of the default file size, so
+    /// that the stores of new slots are recycled rather than created while storing accounts
+    pub fn preallocate_stores(&self) {
+        if self.preallocated_store_count == 0 {
+            return;
+        }
+        let (available, room) = {
+            let recycle_stores = self.recycle_stores.read().unwrap();
+            let available = recycle_stores
+                .iter()
+                .filter(|(_recycled_time, store)| {
+                    Arc::strong_count(store) == 1 && store.accounts.capacity() >= self.file_size
+                })
+                .count();
+            (
+                available,
+                MAX_RECYCLE_STORES.saturating_sub(recycle_stores.entry_count()),
+            )
+        };
+        let missing = self
+            .preallocated_store_count
+            .saturating_sub(available)
+            .min(room);
+        if missing == 0 {
+            return;
+        }
+
+        // Create the stores outside the lock, it's the file system latency this avoids paying
+        // while storing accounts
+        let mut preallocate_stores_time = Measure::start("preallocate_stores");
+        let stores: Vec<_> = (0..missing)
+            .map(|_| {
+                let store = self.create_store(0, self.file_size, "preallocate", &self.paths);
+                store.accounts.prefault();
+                store
+            })
+            .collect();
+        preallocate_stores_time.stop();
+        self.recycle_stores.write().unwrap().add_entries(stores);
+
+        self.stats
+            .preallocated_store_count
+            .fetch_add(missing as u64, Ordering::Relaxed);
+        self.stats
+            .preallocate_stores_us
+            .fetch_add(preallocate_stores_time.as_us(), Ordering::Relaxed);
+    }
+
     pub fn flush_accounts_cache_slot(&self, slot: Slot) {
         self.flush_slot_cache(slot, None::<&mut fn(&_, &_) -> bool>);
     }
 
+    fn is_write_cache_over_limit(&self) -> bool {
+        self.accounts_cache.size() > self.write_cache_limit_bytes
+    }
+
+    fn should_aggressively_flush_cache(&self) -> bool {
+        self.accounts_cache.num_slots() > MAX_CACHE_SLOTS || self.is_write_cache_over_limit()
+    }
+
     // `force_flush` flushes all the cached roots `<= requested_flush_root`. It also then
     // flushes:
-    // 1) Any remaining roots if there are > MAX_CACHE_SLOTS remaining slots in the cache,
+    // 1) Any remaining roots if there are > MAX_CACHE_SLOTS remaining slots in the cache, or
+    // the cache holds more than `write_cache_limit_bytes` of account data,
     // 2) It there are still > MAX_CACHE_SLOTS remaining slots in the cache, the excess
     // unrooted slots
+    // 3) The oldest unrooted slots until the cache holds at most `write_cache_limit_bytes`
     pub fn flush_accounts_cache(&self, force_flush: bool, requested_flush_root: Option<Slot>) {
         #[cfg(not(test))]
         assert!(requested_flush_root.is_some());
 
-        if !force_flush && self.accounts_cache.num_slots() <= MAX_CACHE_SLOTS {
+        if !force_flush && !self.should_aggressively_flush_cache() {
             return;
         }
+        let cache_size_before = self.accounts_cache.size();
+        let mut flush_elapsed = Measure::start("flush_elapsed");
 
         // Flush only the roots <= requested_flush_root, so that snapshotting has all
         // the relevant roots in storage.
@@ -4513,9 +4734,10 @@ impl AccountsDb {
         // for those slot, let the Bank::drop() implementation do cleanup instead on dead
         // banks
 
-        // If there are > MAX_CACHE_SLOTS, then flush the excess ones to storage
+        // If there are > MAX_CACHE_SLOTS or too many unflushed bytes, then flush the excess ones
+        // to storage
         let (total_new_excess_roots, num_excess_roots_flushed) =
-            if self.accounts_cache.num_slots() > MAX_CACHE_SLOTS {
+            if self.should_aggressively_flush_cache() {
                 // Start by flushing the roots
                 //
                 // Cannot do any cleaning on roots past `requested_flush_root` because future
@@ -4546,6 +4768,22 @@ impl AccountsDb {
             requested_flush_root, old_slot_flush_stats
         );
 
+        // If the cache still holds too many unflushed bytes, flush the oldest unrooted slots
+        // until it's back under the limit
+        let mut excess_bytes_slot_count = 0;
+        if self.is_write_cache_over_limit() {
+            for old_slot in self.accounts_cache.find_older_frozen_slots(0) {
+                if !self.is_write_cache_over_limit() {
+                    break;
+                }
+                if old_slot > max_flushed_root {
+                    self.flush_slot_cache(old_slot, None::<&mut fn(&_, &_) -> bool>);
+                    excess_bytes_slot_count += 1;
+                }
+            }
+        }
+        flush_elapsed.stop();
+
         datapoint_info!(
             "accounts_db-flush_accounts_cache",
             ("total_new_cleaned_roots", total_new_cleaned_roots, i64),
@@ -4565,6 +4803,11 @@ impl AccountsDb {
             ),
             ("account_bytes_saved", account_bytes_saved, i64),
             ("num_accounts_saved", num_accounts_saved, i64),
+            ("excess_bytes_slot_count", excess_bytes_slot_count, i64),
+            ("cache_size_before", cache_size_before, i64),
+            ("cache_size_after", self.accounts_cache.size(), i64),
+            ("write_cache_limit_bytes", self.write_cache_limit_bytes, i64),
+            ("flush_elapsed", flush_elapsed.as_us(), i64),
         );
 
         // Flush a random slot out after every force flush to catch any inconsistencies
@@ -5011,7 +5254,7 @@ impl AccountsDb {
         AccountsHash::checked_cast_for_capitalization(balances.map(|b| b as u128).sum::<u128>())
     }
 
-    fn calculate_accounts_hash(
+    pub(crate) fn calculate_accounts_hash(
         &self,
         slot: Slot,
         ancestors: &Ancestors,
@@ -5132,6 +5375,51 @@ impl AccountsDb {
         Ok((accumulated_hash, total_lamports))
     }
 
+    /// Calculate the epoch accounts hash of the calculation bank at `slot`
+    pub fn calculate_epoch_accounts_hash(
+        &self,
+        slot: Slot,
+        ancestors: &Ancestors,
+    ) -> EpochAccountsHash {
+        let mut calculate_time = Measure::start("calculate_epoch_accounts_hash");
+        let (hash, _total_lamports) = self
+            .calculate_accounts_hash(slot, ancestors, false)
+            .expect("calculate epoch accounts hash");
+        calculate_time.stop();
+
+        datapoint_info!(
+            "epoch_accounts_hash",
+            ("slot", slot, i64),
+            ("hash", hash.to_string(), String),
+            ("calculate_us", calculate_time.as_us(), i64),
+        );
+        EpochAccountsHash { slot, hash }
+    }
+
+    /// Calculate the epoch accounts hash requested when its calculation bank was rooted, if the
+    /// request is still pending.  This must run before cleaning or flushing past that root, which
+    /// can drop the versions of the accounts it hashes.
+    pub fn calculate_requested_epoch_accounts_hash(&self) {
+        if let Some((slot, ancestors)) = self.epoch_accounts_hash_manager.take_request() {
+            let epoch_accounts_hash = self.calculate_epoch_accounts_hash(slot, &ancestors);
+            self.epoch_accounts_hash_manager
+                .set_valid(epoch_accounts_hash);
+        }
+    }
+
+    /// Returns the epoch accounts hash of the rooted calculation bank at `slot`, calculating it
+    /// here if the background service hasn't started on it yet, and waiting for it otherwise.
+    /// Returns None if no calculation was requested for `slot`.
+    pub fn epoch_accounts_hash(&self, slot: Slot) -> Option<EpochAccountsHash> {
+        if let Some(ancestors) = self.epoch_accounts_hash_manager.take_request_at(slot) {
+            let epoch_accounts_hash = self.calculate_epoch_accounts_hash(slot, &ancestors);
+            self.epoch_accounts_hash_manager
+                .set_valid(epoch_accounts_hash);
+            return Some(epoch_accounts_hash);
+        }
+        self.epoch_accounts_hash_manager.wait_get(slot)
+    }
+
     pub fn get_accounts_hash(&self, slot: Slot) -> Hash {
         let bank_hashes = self.bank_hashes.read().unwrap();
         let bank_hash_info = bank_hashes.get(&slot).unwrap();
@@ -5840,11 +6128,18 @@ impl AccountsDb {
         };
         let dirty_keys = hashes.iter().map(|(pubkey, _hash)| *pubkey).collect();
 
-        let ret = AccountsHash::accumulate_account_hashes(hashes);
+        // Only clean needs the dirty keys, so record them while the hashes are accumulated
+        // rather than holding up the bank freeze
+        let (ret, uncleaned_time) = rayon::join(
+            || AccountsHash::accumulate_account_hashes(hashes),
+            || {
+                let mut uncleaned_time = Measure::start("uncleaned_index");
+                self.uncleaned_pubkeys.insert(slot, dirty_keys);
+                uncleaned_time.stop();
+                uncleaned_time
+            },
+        );
         accumulate.stop();
-        let mut uncleaned_time = Measure::start("uncleaned_index");
-        self.uncleaned_pubkeys.insert(slot, dirty_keys);
-        uncleaned_time.stop();
         self.stats
             .store_uncleaned_update
             .fetch_add(uncleaned_time.as_us(), Ordering::Relaxed);
@@ -6361,6 +6656,18 @@ impl AccountsDb {
                     self.stats.create_store_count.swap(0, Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "preallocated_store_count",
+                    self.stats
+                        .preallocated_store_count
+                        .swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "preallocate_stores_us",
+                    self.stats.preallocate_stores_us.swap(0, Ordering::Relaxed),
+                    i64
+                ),
                 (
                     "store_get_slot_store",
                     self.stats.store_get_slot_store.swap(0, Ordering::Relaxed),
@@ -11417,6 +11724,38 @@ pub mod tests {
         run_test_flush_accounts_cache_if_needed(MAX_CACHE_SLOTS, MAX_CACHE_SLOTS);
     }
 
+    #[test]
+    fn test_flush_accounts_cache_write_cache_limit() {
+        let mut db = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        db.caching_enabled = true;
+        db.write_cache_limit_bytes = 250;
+        let account = AccountSharedData::new(1, 100, &Pubkey::default());
+        let keys: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        for (slot, key) in keys.iter().enumerate() {
+            db.store_cached(slot as Slot, &[(key, &account)]);
+            db.mark_slot_frozen(slot as Slot);
+        }
+        assert_eq!(db.accounts_cache.size(), 500);
+
+        // Well below MAX_CACHE_SLOTS, but the oldest slots are flushed to get back under the
+        // byte limit
+        db.flush_accounts_cache(false, None);
+        assert_eq!(db.accounts_cache.size(), 200);
+        assert_eq!(db.accounts_cache.num_slots(), 2);
+        for slot in 0..3 {
+            assert!(db.accounts_cache.slot_cache(slot).is_none());
+        }
+
+        // Should still be able to fetch all the accounts after flush
+        for (slot, key) in keys.iter().enumerate() {
+            let ancestors = vec![(slot as Slot, 0)].into_iter().collect();
+            assert_eq!(
+                db.load_without_fixed_root(&ancestors, key).unwrap().0,
+                account
+            );
+        }
+    }
+
     fn run_test_flush_accounts_cache_if_needed(num_roots: usize, num_unrooted: usize) {
         let mut db = AccountsDb::new(Vec::new(), &ClusterType::Development);
         db.caching_enabled = true;
@@ -12419,6 +12758,45 @@ pub mod tests {
         assert!(!slot_stores(&db, 1).is_empty());
     }
 
+    #[test]
+    fn test_preallocate_stores() {
+        solana_logger::setup();
+        let mut accounts = AccountsDb::new_sized(vec![], 4096);
+        accounts.preallocate_stores();
+        assert_eq!(accounts.recycle_stores.read().unwrap().entry_count(), 0);
+
+        accounts.preallocated_store_count = 2;
+        accounts.preallocate_stores();
+        assert_eq!(accounts.recycle_stores.read().unwrap().entry_count(), 2);
+        assert_eq!(
+            accounts.recycle_stores.read().unwrap().total_bytes(),
+            2 * 4096
+        );
+        // already topped up
+        accounts.preallocate_stores();
+        assert_eq!(accounts.recycle_stores.read().unwrap().entry_count(), 2);
+
+        // the store of a new slot comes from the preallocated stores
+        let account = AccountSharedData::new(1, 0, &Pubkey::default());
+        accounts.store_uncached(0, &[(&solana_sdk::pubkey::new_rand(), &account)]);
+        assert_eq!(accounts.recycle_stores.read().unwrap().entry_count(), 1);
+        assert_eq!(
+            accounts.stats.recycle_store_count.load(Ordering::Relaxed),
+            1
+        );
+        assert_eq!(accounts.stats.create_store_count.load(Ordering::Relaxed), 0);
+
+        accounts.preallocate_stores();
+        assert_eq!(accounts.recycle_stores.read().unwrap().entry_count(), 2);
+        assert_eq!(
+            accounts
+                .stats
+                .preallocated_store_count
+                .load(Ordering::Relaxed),
+            3
+        );
+    }
+
     #[test]
     fn test_recycle_stores_expiration() {
         solana_logger::setup();
@@ -2,7 +2,9 @@ use {
     crate::{accounts_db::AccountsDb, append_vec::StoredAccountMeta},
     solana_measure::measure::Measure,
     solana_metrics::*,
-    solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey},
+    solana_sdk::{
+        account::AccountSharedData, clock::Slot, process_instruction::ProgramEvent, pubkey::Pubkey,
+    },
     std::collections::{hash_map::Entry, HashMap, HashSet},
 };
 
@@ -72,6 +74,13 @@ impl AccountsDb {
         }
     }
 
+    pub fn notify_program_event(&self, slot: Slot, program_id: &Pubkey, event: ProgramEvent) {
+        if let Some(accounts_update_notifier) = &self.accounts_update_notifier {
+            let notifier = &accounts_update_notifier.read().unwrap();
+            notifier.notify_program_event(slot, program_id, event);
+        }
+    }
+
     fn notify_accounts_in_slot(
         &self,
         slot: Slot,
@@ -160,6 +169,7 @@ pub mod tests {
         solana_sdk::{
             account::{AccountSharedData, ReadableAccount},
             clock::Slot,
+            process_instruction::ProgramEvent,
             pubkey::Pubkey,
         },
         std::sync::{
@@ -207,6 +217,8 @@ pub mod tests {
         /// Notified when a slot is rooted.
         fn notify_slot_rooted(&self, _slot: Slot, _parent: Option<Slot>) {}
 
+        fn notify_program_event(&self, _slot: Slot, _program_id: &Pubkey, _event: ProgramEvent) {}
+
         fn notify_end_of_restore_from_snapshot(&self) {
             self.is_startup_done.store(true, Ordering::Relaxed);
         }
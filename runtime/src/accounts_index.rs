@@ -24,7 +24,7 @@ use std::{
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
-        Range, RangeBounds,
+        Range, RangeBounds, RangeInclusive,
     },
     path::PathBuf,
     sync::{
@@ -90,6 +90,17 @@ pub enum IndexKey {
     SplTokenOwner(Pubkey),
 }
 
+/// The range of the pubkeys starting with `prefix`.  The ranges of distinct prefixes of the same
+/// length don't overlap, so a scan can be split across workers by prefix
+pub fn pubkey_range_from_prefix(prefix: &[u8]) -> RangeInclusive<Pubkey> {
+    assert!(prefix.len() <= PUBKEY_BYTES);
+    let mut start = [0u8; PUBKEY_BYTES];
+    let mut end = [u8::MAX; PUBKEY_BYTES];
+    start[..prefix.len()].copy_from_slice(prefix);
+    end[..prefix.len()].copy_from_slice(prefix);
+    Pubkey::new_from_array(start)..=Pubkey::new_from_array(end)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AccountIndex {
     ProgramId,
@@ -537,6 +548,15 @@ impl RollingBitField {
         }
         all
     }
+
+    /// Like `get_all`, but in ascending order and without collecting the keys in the bit field
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut excess: Vec<_> = self.excess.iter().cloned().collect();
+        excess.sort_unstable();
+        excess
+            .into_iter()
+            .chain((self.min..self.max).filter(move |key| self.contains_assume_in_range(key)))
+    }
 }
 
 #[derive(Debug)]
@@ -1228,6 +1248,30 @@ impl<T: IndexValue> AccountsIndex<T> {
         )
     }
 
+    /// Like `scan_accounts`, but only visits the pubkeys in `range`, in order
+    pub(crate) fn scan_accounts_in_range<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        scan_bank_id: BankId,
+        range: R,
+        func: F,
+    ) -> Result<(), ScanError>
+    where
+        F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        let collect_all_unsorted = false;
+        // Pass "" not to log metrics, so RPC doesn't get spammy
+        self.do_checked_scan_accounts(
+            "",
+            ancestors,
+            scan_bank_id,
+            func,
+            ScanTypes::Unindexed(Some(range)),
+            collect_all_unsorted,
+        )
+    }
+
     pub(crate) fn unchecked_scan_accounts<F>(
         &self,
         metric_name: &'static str,
@@ -2316,6 +2360,21 @@ pub mod tests {
         assert_eq!(tester.bitfield.excess.len(), 1);
     }
 
+    #[test]
+    fn test_bitfield_iter() {
+        let mut bitfield = RollingBitField::new(16);
+        assert_eq!(bitfield.iter().count(), 0);
+        for slot in [100, 105, 102, 98, 96] {
+            bitfield.insert(slot);
+        }
+        // 98 and 96 are in excess
+        assert_eq!(bitfield.excess.len(), 2);
+        assert_eq!(
+            bitfield.iter().collect::<Vec<_>>(),
+            vec![96, 98, 100, 102, 105]
+        );
+    }
+
     #[test]
     fn test_bitfield_excess() {
         solana_logger::setup();
@@ -3236,6 +3295,51 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_pubkey_range_from_prefix() {
+        let range = pubkey_range_from_prefix(&[]);
+        assert_eq!(*range.start(), Pubkey::new_from_array([0; PUBKEY_BYTES]));
+        assert_eq!(
+            *range.end(),
+            Pubkey::new_from_array([u8::MAX; PUBKEY_BYTES])
+        );
+
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let range = pubkey_range_from_prefix(pubkey.as_ref());
+        assert_eq!(range, pubkey..=pubkey);
+
+        let range = pubkey_range_from_prefix(&[7, 9]);
+        let mut inside = [0; PUBKEY_BYTES];
+        inside[..3].copy_from_slice(&[7, 9, 200]);
+        assert!(range.contains(&Pubkey::new_from_array(inside)));
+        let mut outside = [u8::MAX; PUBKEY_BYTES];
+        outside[..2].copy_from_slice(&[7, 8]);
+        assert!(!range.contains(&Pubkey::new_from_array(outside)));
+    }
+
+    #[test]
+    fn test_scan_accounts_in_range() {
+        let (index, pubkeys) = setup_accounts_index_keys(3 * ITER_BATCH_SIZE);
+        let ancestors = Ancestors::default();
+        let prefix = [pubkeys[0].as_ref()[0]];
+
+        let mut scanned_keys = vec![];
+        index
+            .scan_accounts_in_range(
+                &ancestors,
+                0,
+                pubkey_range_from_prefix(&prefix),
+                |pubkey, _index| scanned_keys.push(*pubkey),
+            )
+            .unwrap();
+        let mut expected_keys: Vec<_> = pubkeys
+            .into_iter()
+            .filter(|pubkey| pubkey.as_ref()[0] == prefix[0])
+            .collect();
+        expected_keys.sort();
+        assert_eq!(scanned_keys, expected_keys);
+    }
+
     fn run_test_scan_accounts(num_pubkeys: usize) {
         let (index, _) = setup_accounts_index_keys(num_pubkeys);
         let ancestors = Ancestors::default();
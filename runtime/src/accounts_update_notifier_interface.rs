@@ -1,6 +1,8 @@
 use {
     crate::append_vec::StoredAccountMeta,
-    solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey},
+    solana_sdk::{
+        account::AccountSharedData, clock::Slot, process_instruction::ProgramEvent, pubkey::Pubkey,
+    },
     std::sync::{Arc, RwLock},
 };
 
@@ -23,6 +25,10 @@ pub trait AccountsUpdateNotifierInterface: std::fmt::Debug {
 
     /// Notified when a slot is rooted.
     fn notify_slot_rooted(&self, slot: Slot, parent: Option<Slot>);
+
+    /// Notified when a transaction executed in a slot deploys, upgrades or closes a program,
+    /// before the updated program accounts are stored
+    fn notify_program_event(&self, slot: Slot, program_id: &Pubkey, event: ProgramEvent);
 }
 
 pub type AccountsUpdateNotifier = Arc<RwLock<dyn AccountsUpdateNotifierInterface + Sync + Send>>;
@@ -34,6 +34,7 @@ macro_rules! u64_align {
 }
 
 const MAXIMUM_APPEND_VEC_FILE_SIZE: usize = 16 * 1024 * 1024 * 1024; // 16 GiB
+const PAGE_SIZE: usize = 4 * 1024;
 
 pub type StoredMetaWriteVersion = u64;
 
@@ -282,6 +283,21 @@ impl AppendVec {
         self.current_len.store(0, Ordering::Relaxed);
     }
 
+    /// Write to every page of an empty append vec so that the file's blocks are allocated and
+    /// the pages mapped before accounts are appended to it
+    pub fn prefault(&self) {
+        let _lock = self.append_lock.lock().unwrap();
+        assert_eq!(self.len(), 0);
+        let data = self.map.as_ptr() as *mut u8;
+        for offset in (0..self.map.len()).step_by(PAGE_SIZE) {
+            //UNSAFE: Nothing has been appended yet so no reader can be looking at the data, and
+            //the append lock keeps any appender out
+            unsafe {
+                std::ptr::write_volatile(data.add(offset), 0);
+            }
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.current_len.load(Ordering::Relaxed)
     }
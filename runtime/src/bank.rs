@@ -34,9 +34,13 @@
 //! on behalf of the caller, and a low-level API for when they have
 //! already been signed and verified.
 use crate::{
-    accounts::{AccountAddressFilter, Accounts, TransactionAccounts, TransactionLoadResult},
+    account_rent_state::{check_rent_state, RentState},
+    accounts::{
+        AccountAddressFilter, AccountScanMetadata, Accounts, TransactionAccounts,
+        TransactionLoadResult,
+    },
     accounts_db::{
-        AccountShrinkThreshold, AccountsDbConfig, ErrorCounters, SnapshotStorages,
+        AccountShrinkThreshold, AccountsDbConfig, ErrorCounters, LoadedAccount, SnapshotStorages,
         ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS, ACCOUNTS_DB_CONFIG_FOR_TESTING,
     },
     accounts_index::{AccountSecondaryIndexes, IndexKey, ScanResult},
@@ -45,11 +49,16 @@ use crate::{
     blockhash_queue::BlockhashQueue,
     builtins::{self, ActivationType, Builtin, Builtins},
     cost_tracker::CostTracker,
+    epoch_accounts_hash::{self, EpochAccountsHash},
     epoch_stakes::{EpochStakes, NodeVoteAccounts},
     inline_spl_token_v2_0,
     instruction_recorder::InstructionRecorder,
-    log_collector::LogCollector,
+    log_collector::{LogCollector, TransactionLogEntry},
     message_processor::MessageProcessor,
+    partitioned_rewards::{
+        get_pending_stake_rewards_address, get_reward_distribution_num_blocks, PendingStakeRewards,
+        PendingStakeRewardsPartition, StakeReward, MAX_REWARD_DISTRIBUTION_BLOCKS,
+    },
     rent_collector::RentCollector,
     stake_weighted_timestamp::{
         calculate_stake_weighted_timestamp, MaxAllowableDrift, MAX_ALLOWABLE_DRIFT_PERCENTAGE,
@@ -90,7 +99,11 @@ use solana_sdk::{
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
-    feature_set::{self, disable_fee_calculator, tx_wide_compute_cap, FeatureSet},
+    feature_set::{
+        self, add_set_compute_unit_price_ix, compute_unit_fee_structure, disable_fee_calculator,
+        tx_wide_compute_cap, FeatureSet,
+    },
+    fee::FeeStructure,
     fee_calculator::{FeeCalculator, FeeRateGovernor},
     genesis_config::{ClusterType, GenesisConfig},
     hard_forks::HardForks,
@@ -99,13 +112,13 @@ use solana_sdk::{
     inflation::Inflation,
     instruction::{CompiledInstruction, InstructionError},
     lamports::LamportsError,
-    message::SanitizedMessage,
+    message::{v0::MessageAddressTableLookup, LoadedAddresses, Message, SanitizedMessage},
     native_loader,
     native_token::sol_to_lamports,
     nonce, nonce_account,
     packet::PACKET_DATA_SIZE,
     precompiles::get_precompiles,
-    process_instruction::{ComputeMeter, Executor, ProcessInstructionWithContext},
+    process_instruction::{ComputeMeter, Executor, ProcessInstructionWithContext, ProgramEvent},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     secp256k1_program,
@@ -132,12 +145,12 @@ use std::{
     collections::{HashMap, HashSet},
     convert::{TryFrom, TryInto},
     fmt, mem,
-    ops::RangeInclusive,
+    ops::{RangeBounds, RangeInclusive},
     path::PathBuf,
     ptr,
     rc::Rc,
     sync::{
-        atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+        atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering::Relaxed},
         LockResult, RwLockWriteGuard, {Arc, RwLock, RwLockReadGuard},
     },
     time::Duration,
@@ -169,6 +182,52 @@ impl RentDebits {
     }
 }
 
+/// Freezing a bank for longer than this delays its vote and the replay of its children, the
+/// breakdown of any bank going over it is debug logged
+const FREEZE_TIME_BUDGET_US: u64 = 100_000;
+
+/// Time spent in each step of `Bank::freeze()`
+#[derive(Default, Debug)]
+struct FreezeTimings {
+    hash_lock_wait_us: u64,
+    collect_rent_eagerly_us: u64,
+    collect_fees_us: u64,
+    distribute_rent_us: u64,
+    update_slot_history_us: u64,
+    run_incinerator_us: u64,
+    update_epoch_accounts_hash_us: u64,
+    hash_internal_state_us: u64,
+    total_us: u64,
+}
+
+impl FreezeTimings {
+    fn report(&self, slot: Slot) {
+        datapoint_info!(
+            "bank-freeze",
+            ("slot", slot, i64),
+            ("hash_lock_wait_us", self.hash_lock_wait_us, i64),
+            ("collect_rent_eagerly_us", self.collect_rent_eagerly_us, i64),
+            ("collect_fees_us", self.collect_fees_us, i64),
+            ("distribute_rent_us", self.distribute_rent_us, i64),
+            ("update_slot_history_us", self.update_slot_history_us, i64),
+            ("run_incinerator_us", self.run_incinerator_us, i64),
+            (
+                "update_epoch_accounts_hash_us",
+                self.update_epoch_accounts_hash_us,
+                i64
+            ),
+            ("hash_internal_state_us", self.hash_internal_state_us, i64),
+            ("total_us", self.total_us, i64),
+        );
+        if self.total_us > FREEZE_TIME_BUDGET_US {
+            debug!(
+                "freezing bank {} took {}us, over the {}us budget: {:?}",
+                slot, self.total_us, FREEZE_TIME_BUDGET_US, self
+            );
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct ExecuteTimings {
     pub check_us: u64,
@@ -463,14 +522,7 @@ impl StatusCacheRc {
     }
 
     pub fn roots(&self) -> Vec<Slot> {
-        self.status_cache
-            .read()
-            .unwrap()
-            .roots()
-            .iter()
-            .cloned()
-            .sorted()
-            .collect()
+        self.status_cache.read().unwrap().roots().collect()
     }
 
     pub fn append(&self, slot_deltas: &[BankSlotDelta]) {
@@ -489,9 +541,11 @@ pub struct TransactionResults {
 }
 pub struct TransactionSimulationResult {
     pub result: Result<()>,
-    pub logs: TransactionLogMessages,
+    pub log_entries: TransactionLogEntries,
     pub post_simulation_accounts: Vec<(Pubkey, AccountSharedData)>,
     pub units_consumed: u64,
+    pub return_data: Option<TransactionReturnData>,
+    pub inner_instructions_invoke_units: InnerInstructionsInvokeUnits,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -520,9 +574,25 @@ pub type InnerInstructions = Vec<CompiledInstruction>;
 /// A list of instructions that were invoked during each instruction of a transaction
 pub type InnerInstructionsList = Vec<InnerInstructions>;
 
+/// The compute units charged for invoking each instruction of an `InnerInstructionsList`
+pub type InnerInstructionsInvokeUnits = Vec<Vec<u64>>;
+
 /// A list of log messages emitted during a transaction
 pub type TransactionLogMessages = Vec<String>;
 
+/// The log messages emitted during a transaction, with the invocation that emitted each
+pub type TransactionLogEntries = Vec<TransactionLogEntry>;
+
+/// The compute units each instruction of a transaction consumed, as metered by its invoke context
+pub type InstructionComputeUnits = Vec<u64>;
+
+/// Data returned by the last program which called `sol_set_return_data` during a transaction
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TransactionReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
 #[derive(Serialize, Deserialize, AbiExample, AbiEnumVisitor, Debug, PartialEq)]
 pub enum TransactionLogCollectorFilter {
     All,
@@ -548,7 +618,7 @@ pub struct TransactionLogInfo {
     pub signature: Signature,
     pub result: Result<()>,
     pub is_vote: bool,
-    pub log_messages: TransactionLogMessages,
+    pub log_entries: TransactionLogEntries,
 }
 
 #[derive(AbiExample, Default, Debug)]
@@ -789,6 +859,7 @@ impl PartialEq for Bank {
             && *self.stakes.read().unwrap() == *other.stakes.read().unwrap()
             && self.epoch_stakes == other.epoch_stakes
             && self.is_delta.load(Relaxed) == other.is_delta.load(Relaxed)
+            && self.pending_stake_rewards == other.pending_stake_rewards
     }
 }
 
@@ -887,6 +958,10 @@ pub struct Bank {
     /// The max number of transaction in an entry in this slot
     transactions_per_entry_max: AtomicU64,
 
+    /// The change in the total size of all accounts' data caused by transactions committed in
+    /// this slot
+    accounts_data_len_delta: AtomicI64,
+
     /// Bank tick height
     tick_height: AtomicU64,
 
@@ -941,6 +1016,13 @@ pub struct Bank {
     /// Track cluster signature throughput and adjust fee rate
     fee_rate_governor: FeeRateGovernor,
 
+    /// Base fees charged per signature, write lock and requested compute units
+    fee_structure: FeeStructure,
+
+    /// Write locks each account took in the transactions committed to this bank,
+    /// which make it contended once there are enough of them
+    write_lock_counts: RwLock<HashMap<Pubkey, u64>>,
+
     /// Rent that has been collected
     collected_rent: AtomicU64,
 
@@ -1007,6 +1089,12 @@ pub struct Bank {
     vote_only_bank: bool,
 
     pub cost_tracker: RwLock<CostTracker>,
+
+    /// The most recent epoch accounts hash calculated on this fork
+    epoch_accounts_hash: RwLock<Option<EpochAccountsHash>>,
+
+    /// Stake rewards of the current epoch not yet credited, see `partitioned_rewards`
+    pending_stake_rewards: Option<Arc<PendingStakeRewards>>,
 }
 
 impl Default for BlockhashQueue {
@@ -1103,6 +1191,7 @@ impl Bank {
             transaction_error_count: AtomicU64::default(),
             transaction_entries_count: AtomicU64::default(),
             transactions_per_entry_max: AtomicU64::default(),
+            accounts_data_len_delta: AtomicI64::default(),
             tick_height: AtomicU64::default(),
             signature_count: AtomicU64::default(),
             capitalization: AtomicU64::default(),
@@ -1121,6 +1210,8 @@ impl Bank {
             collector_fees: AtomicU64::default(),
             fee_calculator: FeeCalculator::default(),
             fee_rate_governor: FeeRateGovernor::default(),
+            fee_structure: FeeStructure::default(),
+            write_lock_counts: RwLock::default(),
             collected_rent: AtomicU64::default(),
             rent_collector: RentCollector::default(),
             epoch_schedule: EpochSchedule::default(),
@@ -1146,6 +1237,8 @@ impl Bank {
             freeze_started: AtomicBool::default(),
             vote_only_bank: false,
             cost_tracker: RwLock::<CostTracker>::default(),
+            epoch_accounts_hash: RwLock::<Option<EpochAccountsHash>>::default(),
+            pending_stake_rewards: None,
         }
     }
 
@@ -1357,6 +1450,8 @@ impl Bank {
             block_height: parent.block_height + 1,
             fee_calculator,
             fee_rate_governor,
+            fee_structure: parent.fee_structure.clone(),
+            write_lock_counts: RwLock::default(),
             capitalization: AtomicU64::new(parent.capitalization()),
             vote_only_bank,
             inflation: parent.inflation.clone(),
@@ -1364,6 +1459,7 @@ impl Bank {
             transaction_error_count: AtomicU64::new(0),
             transaction_entries_count: AtomicU64::new(0),
             transactions_per_entry_max: AtomicU64::new(0),
+            accounts_data_len_delta: AtomicI64::new(0),
             // we will .clone_with_epoch() this soon after stake data update; so just .clone() for now
             stakes: RwLock::new(parent.stakes.read().unwrap().clone()),
             epoch_stakes: parent.epoch_stakes.clone(),
@@ -1403,6 +1499,8 @@ impl Bank {
             )),
             freeze_started: AtomicBool::new(false),
             cost_tracker: RwLock::new(CostTracker::default()),
+            epoch_accounts_hash: RwLock::new(parent.epoch_accounts_hash()),
+            pending_stake_rewards: parent.pending_stake_rewards.clone(),
         };
 
         datapoint_info!(
@@ -1425,6 +1523,10 @@ impl Bank {
             new.apply_feature_activations(false, false);
         }
 
+        // Rewards of the previous epoch still pending at an epoch boundary are all credited
+        // before the new rewards are calculated
+        new.distribute_pending_stake_rewards(parent_epoch < new.epoch());
+
         let optimize_epoch_boundary_updates = !disable_epoch_boundary_optimization
             && new
                 .feature_set
@@ -1545,6 +1647,7 @@ impl Bank {
             transaction_error_count: new(),
             transaction_entries_count: new(),
             transactions_per_entry_max: new(),
+            accounts_data_len_delta: new(),
             tick_height: AtomicU64::new(fields.tick_height),
             signature_count: AtomicU64::new(fields.signature_count),
             capitalization: AtomicU64::new(fields.capitalization),
@@ -1562,6 +1665,8 @@ impl Bank {
             collector_id: fields.collector_id,
             collector_fees: AtomicU64::new(fields.collector_fees),
             fee_calculator: fields.fee_calculator,
+            fee_structure: FeeStructure::from(&fields.fee_rate_governor),
+            write_lock_counts: RwLock::default(),
             fee_rate_governor: fields.fee_rate_governor,
             collected_rent: AtomicU64::new(fields.collected_rent),
             // clone()-ing is needed to consider a gated behavior in rent_collector
@@ -1590,12 +1695,15 @@ impl Bank {
             freeze_started: AtomicBool::new(fields.hash != Hash::default()),
             vote_only_bank: false,
             cost_tracker: RwLock::new(CostTracker::default()),
+            epoch_accounts_hash: RwLock::new(None),
+            pending_stake_rewards: None,
         };
         bank.finish_init(
             genesis_config,
             additional_builtins,
             debug_do_not_add_builtins,
         );
+        bank.pending_stake_rewards = bank.load_pending_stake_rewards();
 
         // Sanity assertions between bank snapshot and genesis config
         // Consider removing from serializable bank state
@@ -1716,17 +1824,16 @@ impl Bank {
     }
 
     pub fn status_cache_ancestors(&self) -> Vec<u64> {
-        let mut roots = self.src.status_cache.read().unwrap().roots().clone();
-        let min = roots.iter().min().cloned().unwrap_or(0);
-        for ancestor in self.ancestors.keys() {
-            if ancestor >= min {
-                roots.insert(ancestor);
-            }
-        }
-
-        let mut ancestors: Vec<_> = roots.into_iter().collect();
-        #[allow(clippy::stable_sort_primitive)]
-        ancestors.sort();
+        let mut ancestors: Vec<_> = self.src.status_cache.read().unwrap().roots().collect();
+        let min = ancestors.first().cloned().unwrap_or(0);
+        ancestors.extend(
+            self.ancestors
+                .keys()
+                .into_iter()
+                .filter(|ancestor| *ancestor >= min),
+        );
+        ancestors.sort_unstable();
+        ancestors.dedup();
         ancestors
     }
 
@@ -2157,14 +2264,46 @@ impl Bank {
             (validator_rate * capitalization as f64 * epoch_duration_in_years) as u64;
 
         let old_vote_balance_and_staked = self.stakes.read().unwrap().vote_balance_and_staked();
+        // rewards of the previous epoch credited in this block are already recorded
+        let num_rewards_before = self.rewards.read().unwrap().len();
 
-        let validator_point_value = self.pay_validator_rewards_with_thread_pool(
-            prev_epoch,
-            validator_rewards,
-            reward_calc_tracer,
-            self.stake_program_advance_activating_credits_observed(),
-            thread_pool,
-        );
+        // with partitioned epoch rewards, only the points the stakes earned are calculated
+        // here, the rewards are calculated and credited over the first blocks of the epoch
+        let (validator_point_value, pending_stake_rewards) = if self
+            .feature_set
+            .is_active(&feature_set::partitioned_epoch_rewards::id())
+        {
+            match self.calculate_pending_stake_rewards_with_thread_pool(
+                prev_epoch,
+                validator_rewards,
+                reward_calc_tracer,
+                self.stake_program_advance_activating_credits_observed(),
+                thread_pool,
+            ) {
+                Some((point_value, stake_rewards)) => (
+                    point_value.rewards as f64 / point_value.points as f64,
+                    (!stake_rewards.is_empty()).then(|| {
+                        PendingStakeRewards::new(
+                            self.block_height() + 1,
+                            &self.last_blockhash(),
+                            get_reward_distribution_num_blocks(&self.epoch_schedule, self.epoch),
+                            point_value,
+                            stake_rewards,
+                        )
+                    }),
+                ),
+                None => (0.0, None),
+            }
+        } else {
+            let validator_point_value = self.pay_validator_rewards_with_thread_pool(
+                prev_epoch,
+                validator_rewards,
+                reward_calc_tracer,
+                self.stake_program_advance_activating_credits_observed(),
+                thread_pool,
+            );
+            (validator_point_value, None)
+        };
 
         if !self
             .feature_set
@@ -2184,9 +2323,7 @@ impl Bank {
         assert_eq!(
             validator_rewards_paid,
             u64::try_from(
-                self.rewards
-                    .read()
-                    .unwrap()
+                self.rewards.read().unwrap()[num_rewards_before..]
                     .iter()
                     .map(|(_address, reward_info)| {
                         match reward_info.reward_type {
@@ -2199,16 +2336,26 @@ impl Bank {
             .unwrap()
         );
 
+        // stake and vote rewards credited over the first blocks of the epoch, and added to
+        // the capitalization as they are credited
+        let validator_rewards_deferred = pending_stake_rewards
+            .as_ref()
+            .map(PendingStakeRewards::total_lamports)
+            .unwrap_or_default();
+
         // verify that we didn't pay any more than we expected to
-        assert!(validator_rewards >= validator_rewards_paid);
+        assert!(validator_rewards >= validator_rewards_paid + validator_rewards_deferred);
 
         info!(
-            "distributed inflation: {} (rounded from: {})",
-            validator_rewards_paid, validator_rewards
+            "distributed inflation: {} (rounded from: {}), deferred: {}",
+            validator_rewards_paid, validator_rewards, validator_rewards_deferred
         );
 
         self.capitalization
             .fetch_add(validator_rewards_paid, Relaxed);
+        if let Some(pending_stake_rewards) = pending_stake_rewards {
+            self.store_pending_stake_rewards(pending_stake_rewards);
+        }
 
         let active_stake = if let Some(stake_history_entry) =
             self.stakes.read().unwrap().history().get(&prev_epoch)
@@ -2226,12 +2373,202 @@ impl Bank {
             ("foundation_rate", foundation_rate, f64),
             ("epoch_duration_in_years", epoch_duration_in_years, f64),
             ("validator_rewards", validator_rewards_paid, i64),
+            (
+                "deferred_validator_rewards",
+                validator_rewards_deferred,
+                i64
+            ),
             ("active_stake", active_stake, i64),
             ("pre_capitalization", capitalization, i64),
             ("post_capitalization", self.capitalization(), i64)
         );
     }
 
+    /// Credit the partition of pending stake rewards due at this block height, or all the
+    /// remaining partitions if `credit_all`
+    fn distribute_pending_stake_rewards(&mut self, credit_all: bool) {
+        let pending_stake_rewards = match &self.pending_stake_rewards {
+            Some(pending_stake_rewards) => Arc::clone(pending_stake_rewards),
+            None => return,
+        };
+        let start = pending_stake_rewards.distribution_start_block_height;
+        let end = start + pending_stake_rewards.partitions.len() as u64;
+        let partitions = if credit_all {
+            self.block_height().max(start)..end
+        } else if (start..end).contains(&self.block_height()) {
+            self.block_height()..self.block_height() + 1
+        } else {
+            return;
+        };
+
+        let mut measure = Measure::start("distribute_pending_stake_rewards");
+        let mut num_stake_accounts = 0;
+        let mut lamports_credited = 0;
+        let mut remaining_stake_rewards = (*pending_stake_rewards).clone();
+        for partition in partitions {
+            let index = (partition - start) as usize;
+            let stake_rewards = &pending_stake_rewards.partitions[index];
+            num_stake_accounts += stake_rewards.len();
+            lamports_credited += self.credit_stake_rewards(&pending_stake_rewards, stake_rewards);
+            self.store_account_and_update_capitalization(
+                &get_pending_stake_rewards_address(index),
+                &AccountSharedData::default(),
+            );
+            remaining_stake_rewards = remaining_stake_rewards.without_partition(index);
+        }
+        self.pending_stake_rewards = if credit_all || self.block_height() + 1 >= end {
+            None
+        } else {
+            Some(Arc::new(remaining_stake_rewards))
+        };
+        measure.stop();
+
+        datapoint_info!(
+            "distribute_pending_stake_rewards",
+            ("slot", self.slot, i64),
+            ("block_height", self.block_height(), i64),
+            ("num_stake_accounts", num_stake_accounts, i64),
+            ("lamports_credited", lamports_credited, i64),
+            ("distribute_us", measure.as_us(), i64),
+        );
+    }
+
+    /// Calculate the rewards of stakes from the points they earned in the previous epoch and
+    /// credit them to the stake and vote accounts, returning the lamports credited.  Stake
+    /// accounts that were closed or can no longer hold the reward since are skipped, and their
+    /// share of the reward is not minted
+    fn credit_stake_rewards(
+        &self,
+        pending_stake_rewards: &PendingStakeRewards,
+        stake_rewards: &[StakeReward],
+    ) -> u64 {
+        let mut rewards = Vec::with_capacity(stake_rewards.len());
+        let mut lamports_credited = 0;
+        let mut vote_rewards: HashMap<Pubkey, (u64, u8)> = HashMap::new();
+        for stake_reward in stake_rewards {
+            let (stakers_reward, voters_reward) =
+                match pending_stake_rewards.calculate_rewards(stake_reward) {
+                    Some(rewards) => rewards,
+                    None => continue,
+                };
+            if voters_reward > 0 {
+                let (vote_rewards_sum, commission) = vote_rewards
+                    .entry(stake_reward.vote_pubkey)
+                    .or_insert((0, stake_reward.commission));
+                *vote_rewards_sum = vote_rewards_sum.saturating_add(voters_reward);
+                *commission = stake_reward.commission;
+            }
+
+            let mut stake_account =
+                match self.get_account_with_fixed_root(&stake_reward.stake_pubkey) {
+                    Some(stake_account) => stake_account,
+                    None => continue,
+                };
+            if let Err(err) = stake_account.checked_add_lamports(stakers_reward) {
+                debug!(
+                    "reward redemption failed for {}: {:?}",
+                    stake_reward.stake_pubkey, err
+                );
+                continue;
+            }
+            // the stake may have been deactivated or redelegated since the reward was
+            // calculated, the lamports are credited regardless
+            if let Ok(StakeState::Stake(meta, mut stake)) = stake_account.state() {
+                if stake.delegation.voter_pubkey == stake_reward.vote_pubkey {
+                    stake.credits_observed =
+                        stake.credits_observed.max(stake_reward.credits_observed);
+                    stake.delegation.stake += stakers_reward;
+                    if let Err(err) = stake_account.set_state(&StakeState::Stake(meta, stake)) {
+                        debug!(
+                            "reward redemption failed for {}: {:?}",
+                            stake_reward.stake_pubkey, err
+                        );
+                        continue;
+                    }
+                }
+            }
+            self.store_account(&stake_reward.stake_pubkey, &stake_account);
+
+            if stakers_reward > 0 {
+                lamports_credited += stakers_reward;
+                rewards.push((
+                    stake_reward.stake_pubkey,
+                    RewardInfo {
+                        reward_type: RewardType::Staking,
+                        lamports: stakers_reward as i64,
+                        post_balance: stake_account.lamports(),
+                        commission: Some(stake_reward.commission),
+                    },
+                ));
+            }
+        }
+        for (vote_pubkey, (voters_reward, commission)) in vote_rewards {
+            let mut vote_account = match self.get_account_with_fixed_root(&vote_pubkey) {
+                Some(vote_account) => vote_account,
+                None => continue,
+            };
+            if let Err(err) = vote_account.checked_add_lamports(voters_reward) {
+                debug!("reward redemption failed for {}: {:?}", vote_pubkey, err);
+                continue;
+            }
+            self.store_account(&vote_pubkey, &vote_account);
+
+            lamports_credited += voters_reward;
+            rewards.push((
+                vote_pubkey,
+                RewardInfo {
+                    reward_type: RewardType::Voting,
+                    lamports: voters_reward as i64,
+                    post_balance: vote_account.lamports(),
+                    commission: Some(commission),
+                },
+            ));
+        }
+        self.rewards.write().unwrap().append(&mut rewards);
+        self.capitalization.fetch_add(lamports_credited, Relaxed);
+        lamports_credited
+    }
+
+    /// Stake rewards of the current epoch not yet credited
+    pub fn pending_stake_rewards(&self) -> Option<&PendingStakeRewards> {
+        self.pending_stake_rewards.as_deref()
+    }
+
+    /// Keep each partition of the pending stake rewards in its account, so that they're part of
+    /// the bank hash and are restored along with the accounts from a snapshot.  A partition's
+    /// account is deleted once it is credited
+    fn store_pending_stake_rewards(&mut self, pending_stake_rewards: PendingStakeRewards) {
+        for (index, stake_rewards) in pending_stake_rewards.partitions.iter().enumerate() {
+            let partition = PendingStakeRewardsPartition {
+                distribution_start_block_height: pending_stake_rewards
+                    .distribution_start_block_height,
+                total_rewards: pending_stake_rewards.total_rewards,
+                total_points: pending_stake_rewards.total_points,
+                stake_rewards: stake_rewards.to_vec(),
+            };
+            self.update_sysvar_account(&get_pending_stake_rewards_address(index), |account| {
+                let (lamports, rent_epoch) =
+                    self.inherit_specially_retained_account_fields(account);
+                let mut account =
+                    AccountSharedData::new_data(lamports, &partition, &sysvar::id()).unwrap();
+                account.set_rent_epoch(rent_epoch);
+                account
+            });
+        }
+        self.pending_stake_rewards = Some(Arc::new(pending_stake_rewards));
+    }
+
+    fn load_pending_stake_rewards(&self) -> Option<Arc<PendingStakeRewards>> {
+        PendingStakeRewards::from_partitions(
+            (0..MAX_REWARD_DISTRIBUTION_BLOCKS as usize).filter_map(|index| {
+                let account = self.get_account(&get_pending_stake_rewards_address(index))?;
+                let partition = bincode::deserialize(account.data()).ok()?;
+                Some((index, partition))
+            }),
+        )
+        .map(Arc::new)
+    }
+
     /// map stake delegations into resolved (pubkey, account) pairs
     ///  returns a map (has to be copied) of loaded
     ///   ( Vec<(staker info)> (voter account) ) keyed by voter pubkey
@@ -2694,6 +3031,88 @@ impl Bank {
         point_value.rewards as f64 / point_value.points as f64
     }
 
+    /// iterate over all stakes, calculate the points each stake we can successfully load and
+    ///   parse earned, return the point value and those points, which the stake and vote
+    ///   rewards are calculated from as the stakes are credited
+    fn calculate_pending_stake_rewards_with_thread_pool(
+        &self,
+        rewarded_epoch: Epoch,
+        rewards: u64,
+        reward_calc_tracer: Option<impl Fn(&RewardCalculationEvent) + Send + Sync>,
+        fix_activating_credits_observed: bool,
+        thread_pool: &ThreadPool,
+    ) -> Option<(PointValue, Vec<StakeReward>)> {
+        let stake_history = self.stakes.read().unwrap().history().clone();
+        let vote_and_stake_accounts = self.load_vote_and_stake_accounts_with_thread_pool(
+            thread_pool,
+            reward_calc_tracer.as_ref(),
+        );
+
+        let stake_rewards: Vec<(StakeReward, bool)> = thread_pool.install(|| {
+            vote_and_stake_accounts
+                .par_iter()
+                .flat_map(|entry| {
+                    let vote_pubkey = *entry.key();
+                    let VoteWithStakeDelegations {
+                        vote_state,
+                        delegations,
+                        ..
+                    } = entry.value();
+
+                    delegations
+                        .par_iter()
+                        .filter_map(|(stake_pubkey, (stake_state, _stake_account))| {
+                            let (points, credits_observed) =
+                                stake_state::calculate_points_and_credits(
+                                    stake_state,
+                                    vote_state,
+                                    Some(&stake_history),
+                                )
+                                .ok()?;
+                            // credits observed is driven forward unconditionally when rewards
+                            // are disabled or when this is the stake's activation epoch
+                            let skip_rewards = rewards == 0
+                                || (fix_activating_credits_observed
+                                    && stake_state
+                                        .stake()
+                                        .map(|stake| stake.delegation.activation_epoch)
+                                        == Some(rewarded_epoch));
+                            let stake_reward = StakeReward {
+                                stake_pubkey: *stake_pubkey,
+                                vote_pubkey,
+                                points,
+                                credits_observed,
+                                commission: vote_state.commission,
+                            };
+                            Some((stake_reward, skip_rewards))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        });
+
+        let points: u128 = stake_rewards
+            .iter()
+            .map(|(stake_reward, _skip_rewards)| stake_reward.points)
+            .sum();
+        if points == 0 {
+            return None;
+        }
+
+        let stake_rewards = stake_rewards
+            .into_iter()
+            .filter_map(|(mut stake_reward, skip_rewards)| {
+                if skip_rewards {
+                    stake_reward.points = 0;
+                } else if stake_reward.points == 0 {
+                    return None;
+                }
+                Some(stake_reward)
+            })
+            .collect();
+        Some((PointValue { rewards, points }, stake_rewards))
+    }
+
     fn update_recent_blockhashes_locked(&self, locked_blockhash_queue: &BlockhashQueue) {
         #[allow(deprecated)]
         self.update_sysvar_account(&sysvar::recent_blockhashes::id(), |account| {
@@ -2830,19 +3249,58 @@ impl Bank {
         // BankingStage doesn't release this hash lock until both
         // record and commit are finished, those transactions will be
         // committed before this write lock can be obtained here.
+        let mut total_time = Measure::start("freeze");
+        let mut hash_lock_time = Measure::start("hash_lock");
         let mut hash = self.hash.write().unwrap();
+        hash_lock_time.stop();
         if *hash == Hash::default() {
+            let mut timings = FreezeTimings {
+                hash_lock_wait_us: hash_lock_time.as_us(),
+                ..FreezeTimings::default()
+            };
+
             // finish up any deferred changes to account state
+            let mut time = Measure::start("collect_rent_eagerly");
             self.collect_rent_eagerly();
+            time.stop();
+            timings.collect_rent_eagerly_us = time.as_us();
+
+            let mut time = Measure::start("collect_fees");
             self.collect_fees();
+            time.stop();
+            timings.collect_fees_us = time.as_us();
+
+            let mut time = Measure::start("distribute_rent");
             self.distribute_rent();
+            time.stop();
+            timings.distribute_rent_us = time.as_us();
+
+            let mut time = Measure::start("update_slot_history");
             self.update_slot_history();
+            time.stop();
+            timings.update_slot_history_us = time.as_us();
+
+            let mut time = Measure::start("run_incinerator");
             self.run_incinerator();
+            time.stop();
+            timings.run_incinerator_us = time.as_us();
+
+            let mut time = Measure::start("update_epoch_accounts_hash");
+            self.update_epoch_accounts_hash();
+            time.stop();
+            timings.update_epoch_accounts_hash_us = time.as_us();
 
             // freeze is a one-way trip, idempotent
             self.freeze_started.store(true, Relaxed);
+            let mut time = Measure::start("hash_internal_state");
             *hash = self.hash_internal_state();
+            time.stop();
+            timings.hash_internal_state_us = time.as_us();
             self.rc.accounts.accounts_db.mark_slot_frozen(self.slot());
+
+            total_time.stop();
+            timings.total_us = total_time.as_us();
+            timings.report(self.slot());
         }
     }
 
@@ -2884,8 +3342,22 @@ impl Bank {
         self.freeze();
 
         //this bank and all its parents are now on the rooted path
+        let parents = self.parents();
         let mut roots = vec![self.slot()];
-        roots.append(&mut self.parents().iter().map(|p| p.slot()).collect());
+        roots.append(&mut parents.iter().map(|p| p.slot()).collect());
+
+        // Request the epoch accounts hash calculation as its calculation bank is rooted, before
+        // the root reaches the background service, which calculates it before cleaning past it
+        if let Some(bank) = std::iter::once(self)
+            .chain(parents.iter().map(|parent| parent.as_ref()))
+            .find(|bank| bank.is_epoch_accounts_hash_calculation_bank())
+        {
+            self.rc
+                .accounts
+                .accounts_db
+                .epoch_accounts_hash_manager
+                .request(bank.slot(), bank.ancestors.clone());
+        }
 
         let mut squash_accounts_time = Measure::start("squash_accounts_time");
         for slot in roots.iter().rev() {
@@ -2926,6 +3398,7 @@ impl Bank {
         // Bootstrap validator collects fees until `new_from_parent` is called.
         self.fee_rate_governor = genesis_config.fee_rate_governor.clone();
         self.fee_calculator = self.fee_rate_governor.create_fee_calculator();
+        self.fee_structure = FeeStructure::from(&self.fee_rate_governor);
 
         for (pubkey, account) in genesis_config.accounts.iter() {
             assert!(
@@ -3119,14 +3592,28 @@ impl Bank {
     }
 
     pub fn get_fee_for_message(&self, message: &SanitizedMessage) -> u64 {
-        Self::calculate_fee(message, self.fee_rate_governor.lamports_per_signature)
+        self.get_fee_for_message_with_lamports_per_signature(
+            message,
+            self.fee_rate_governor.lamports_per_signature,
+        )
     }
 
     pub fn get_fee_for_message_with_lamports_per_signature(
+        &self,
         message: &SanitizedMessage,
         lamports_per_signature: u64,
     ) -> u64 {
-        Self::calculate_fee(message, lamports_per_signature)
+        Self::calculate_fee(
+            message,
+            lamports_per_signature,
+            &self.fee_structure,
+            &self.write_lock_counts.read().unwrap(),
+            self.feature_set
+                .is_active(&compute_unit_fee_structure::id()),
+            self.feature_set
+                .is_active(&add_set_compute_unit_price_ix::id()),
+            self.demote_program_write_locks(),
+        )
     }
 
     #[deprecated(
@@ -3179,39 +3666,52 @@ impl Bank {
     pub fn can_commit(result: &Result<()>) -> bool {
         match result {
             Ok(_) => true,
-            Err(TransactionError::InstructionError(_, _)) => true,
-            Err(_) => false,
+            Err(err) => Self::is_execution_failure(err),
         }
     }
 
+    /// Whether a transaction that failed with `err` was executed, in which case it's committed
+    /// and charged its fee like a successful one
+    fn is_execution_failure(err: &TransactionError) -> bool {
+        matches!(
+            err,
+            TransactionError::InstructionError(_, _) | TransactionError::InvalidRentPayingAccount
+        )
+    }
+
     fn update_transaction_statuses(
         &self,
         sanitized_txs: &[SanitizedTransaction],
         res: &[TransactionExecutionResult],
     ) {
-        let mut status_cache = self.src.status_cache.write().unwrap();
         assert_eq!(sanitized_txs.len(), res.len());
+        let mut entries = Vec::with_capacity(sanitized_txs.len() * 2);
         for (tx, (res, _nonce_rollback)) in sanitized_txs.iter().zip(res) {
             if Self::can_commit(res) {
                 // Add the message hash to the status cache to ensure that this message
                 // won't be processed again with a different signature.
-                status_cache.insert(
+                entries.push((
                     tx.message().recent_blockhash(),
-                    tx.message_hash(),
-                    self.slot(),
+                    tx.message_hash().as_ref(),
                     res.clone(),
-                );
+                ));
                 // Add the transaction signature to the status cache so that transaction status
                 // can be queried by transaction signature over RPC. In the future, this should
                 // only be added for API nodes because voting validators don't need to do this.
-                status_cache.insert(
+                entries.push((
                     tx.message().recent_blockhash(),
-                    tx.signature(),
-                    self.slot(),
+                    tx.signature().as_ref(),
                     res.clone(),
-                );
+                ));
             }
         }
+        // The status cache locks its shards internally, so batches from banks replaying in
+        // parallel only contend when their blockhashes fall in the same shard
+        self.src
+            .status_cache
+            .read()
+            .unwrap()
+            .insert_batch(self.slot(), entries);
     }
 
     /// Tell the bank which Entry IDs exist on the ledger. This function
@@ -3270,8 +3770,8 @@ impl Bank {
             .into_iter()
             .map(|tx| {
                 let message_hash = tx.message.hash();
-                SanitizedTransaction::try_create(tx, message_hash, |_| {
-                    Err(TransactionError::UnsupportedVersion)
+                SanitizedTransaction::try_create(tx, message_hash, |address_table_lookups| {
+                    self.load_lookup_table_addresses(address_table_lookups)
                 })
             })
             .collect::<Result<Vec<_>>>()?;
@@ -3315,6 +3815,15 @@ impl Bank {
     ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
+        self.simulate_transaction_unchecked(transaction)
+    }
+
+    /// Run transactions against a bank without committing the results; does not check if the
+    /// bank is frozen, enabling use in single-bank test frameworks
+    pub fn simulate_transaction_unchecked(
+        &self,
+        transaction: SanitizedTransaction,
+    ) -> TransactionSimulationResult {
         let number_of_accounts = transaction.message().account_keys_len();
         let batch = self.prepare_simulation_batch(transaction);
         let mut timings = ExecuteTimings::default();
@@ -3323,7 +3832,10 @@ impl Bank {
             loaded_transactions,
             executed,
             _inner_instructions,
-            logs,
+            inner_instructions_invoke_units,
+            log_entries,
+            return_data,
+            _compute_units,
             _retryable_transactions,
             _transaction_count,
             _signature_count,
@@ -3333,13 +3845,19 @@ impl Bank {
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            true,
             true,
             &mut timings,
         );
 
         let result = executed[0].0.clone().map(|_| ());
-        let logs = logs.get(0).cloned().flatten().unwrap_or_default();
+        let log_entries = log_entries.into_iter().next().flatten().unwrap_or_default();
+        let return_data = return_data.into_iter().next().flatten();
+        let inner_instructions_invoke_units = inner_instructions_invoke_units
+            .into_iter()
+            .next()
+            .flatten()
+            .unwrap_or_default();
         let post_simulation_accounts = loaded_transactions
             .into_iter()
             .next()
@@ -3367,9 +3885,11 @@ impl Bank {
 
         TransactionSimulationResult {
             result,
-            logs,
+            log_entries,
             post_simulation_accounts,
             units_consumed,
+            return_data,
+            inner_instructions_invoke_units,
         }
     }
 
@@ -3622,6 +4142,13 @@ impl Bank {
         }
     }
 
+    fn accounts_data_len(accounts: &[(Pubkey, AccountSharedData)]) -> i64 {
+        accounts
+            .iter()
+            .map(|(_pubkey, account)| account.data().len() as i64)
+            .sum()
+    }
+
     /// Converts Accounts into RefCell<AccountSharedData>, this involves moving
     /// ownership by draining the source
     fn accounts_to_refcells(accounts: &mut TransactionAccounts) -> TransactionAccountRefCells {
@@ -3632,6 +4159,51 @@ impl Bank {
         account_refcells
     }
 
+    /// Returns the legacy message, program indices and accounts that a sanitized message is
+    /// executed with. Version #0 messages are converted to an equivalent legacy message, which
+    /// reorders their loaded accounts to match its account keys.
+    #[allow(clippy::type_complexity)]
+    fn legacy_message_for_execution<'a>(
+        message: &'a SanitizedMessage,
+        program_indices: &'a [Vec<usize>],
+        account_refcells: &'a [(Pubkey, Rc<RefCell<AccountSharedData>>)],
+    ) -> (
+        Cow<'a, Message>,
+        Cow<'a, [Vec<usize>]>,
+        Cow<'a, [(Pubkey, Rc<RefCell<AccountSharedData>>)]>,
+    ) {
+        match message {
+            SanitizedMessage::Legacy(message) => (
+                Cow::Borrowed(message),
+                Cow::Borrowed(program_indices),
+                Cow::Borrowed(account_refcells),
+            ),
+            SanitizedMessage::V0(message) => {
+                let (legacy_message, positions) = message.to_legacy_message();
+                // program accounts loaded after the message accounts keep their index
+                let mut legacy_indexes: Vec<usize> = (0..account_refcells.len()).collect();
+                for (legacy_index, position) in positions.iter().enumerate() {
+                    legacy_indexes[*position] = legacy_index;
+                }
+                let program_indices = program_indices
+                    .iter()
+                    .map(|indices| indices.iter().map(|index| legacy_indexes[*index]).collect())
+                    .collect();
+                let account_refcells = positions
+                    .iter()
+                    .copied()
+                    .chain(positions.len()..account_refcells.len())
+                    .map(|position| account_refcells[position].clone())
+                    .collect();
+                (
+                    Cow::Owned(legacy_message),
+                    Cow::Owned(program_indices),
+                    Cow::Owned(account_refcells),
+                )
+            }
+        }
+    }
+
     /// Converts back from RefCell<AccountSharedData> to AccountSharedData, this involves moving
     /// ownership by draining the sources
     fn refcells_to_accounts(
@@ -3650,9 +4222,50 @@ impl Bank {
         Ok(())
     }
 
-    fn collect_log_messages(
+    /// Returns the rent state of each writable account of a message. Read-only
+    /// accounts and the incinerator, whose balance is burned at the end of the
+    /// slot, are not tracked.
+    fn get_transaction_account_rent_states(
+        &self,
+        message: &SanitizedMessage,
+        accounts: &TransactionAccounts,
+    ) -> Vec<Option<RentState>> {
+        let demote_program_write_locks = self.demote_program_write_locks();
+        accounts
+            .iter()
+            .take(message.account_keys_len())
+            .enumerate()
+            .map(|(i, (pubkey, account))| {
+                if message.is_writable(i, demote_program_write_locks)
+                    && !incinerator::check_id(pubkey)
+                {
+                    Some(RentState::from_account(account, &self.rent_collector.rent))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Rejects a transaction that leaves any account it did not find paying rent
+    /// in the rent-paying state
+    fn verify_transaction_account_rent_states(
+        message: &SanitizedMessage,
+        pre_rent_states: &[Option<RentState>],
+        post_rent_states: &[Option<RentState>],
+    ) -> Result<()> {
+        for (address, (pre_rent_state, post_rent_state)) in message
+            .account_keys_iter()
+            .zip(pre_rent_states.iter().zip(post_rent_states))
+        {
+            check_rent_state(pre_rent_state.as_ref(), post_rent_state.as_ref(), address)?;
+        }
+        Ok(())
+    }
+
+    fn collect_log_entries(
         log_collector: Option<Rc<LogCollector>>,
-    ) -> Option<TransactionLogMessages> {
+    ) -> Option<TransactionLogEntries> {
         log_collector.and_then(|log_collector| Rc::try_unwrap(log_collector).map(Into::into).ok())
     }
 
@@ -3668,6 +4281,17 @@ impl Bank {
         })
     }
 
+    fn collect_recorded_invoke_units(
+        instruction_recorders: Option<&[InstructionRecorder]>,
+    ) -> Option<InnerInstructionsInvokeUnits> {
+        instruction_recorders.map(|instruction_recorders| {
+            instruction_recorders
+                .iter()
+                .map(InstructionRecorder::invoke_units)
+                .collect()
+        })
+    }
+
     /// Get any cached executors needed by the transaction
     fn get_executors(
         &self,
@@ -3700,10 +4324,11 @@ impl Bank {
         Rc::new(RefCell::new(Executors {
             executors,
             is_dirty: false,
+            program_events: vec![],
         }))
     }
 
-    /// Add executors back to the bank's cache if modified
+    /// Add executors back to the bank's cache if modified, and drop those of closed programs
     fn update_executors(&self, executors: Rc<RefCell<Executors>>) {
         let executors = executors.borrow();
         if executors.is_dirty {
@@ -3712,6 +4337,11 @@ impl Bank {
             for (key, executor) in executors.executors.iter() {
                 cache.put(key, (*executor).clone());
             }
+            for (key, event) in executors.program_events.iter() {
+                if *event == ProgramEvent::Closed && !executors.executors.contains_key(key) {
+                    cache.remove(key);
+                }
+            }
         }
     }
 
@@ -3734,7 +4364,10 @@ impl Bank {
         Vec<TransactionLoadResult>,
         Vec<TransactionExecutionResult>,
         Vec<Option<InnerInstructionsList>>,
-        Vec<Option<TransactionLogMessages>>,
+        Vec<Option<InnerInstructionsInvokeUnits>>,
+        Vec<Option<TransactionLogEntries>>,
+        Vec<Option<TransactionReturnData>>,
+        Vec<Option<InstructionComputeUnits>>,
         Vec<usize>,
         u64,
         u64,
@@ -3776,6 +4409,8 @@ impl Bank {
             &mut error_counters,
             &self.rent_collector,
             &self.feature_set,
+            &self.fee_structure,
+            &self.write_lock_counts.read().unwrap(),
         );
         load_time.stop();
 
@@ -3783,7 +4418,13 @@ impl Bank {
         let mut signature_count: u64 = 0;
         let mut inner_instructions: Vec<Option<InnerInstructionsList>> =
             Vec::with_capacity(sanitized_txs.len());
-        let mut transaction_log_messages: Vec<Option<Vec<String>>> =
+        let mut inner_instructions_invoke_units: Vec<Option<InnerInstructionsInvokeUnits>> =
+            Vec::with_capacity(sanitized_txs.len());
+        let mut transaction_log_entries: Vec<Option<TransactionLogEntries>> =
+            Vec::with_capacity(sanitized_txs.len());
+        let mut transaction_return_data: Vec<Option<TransactionReturnData>> =
+            Vec::with_capacity(sanitized_txs.len());
+        let mut transaction_compute_units: Vec<Option<InstructionComputeUnits>> =
             Vec::with_capacity(sanitized_txs.len());
 
         let executed: Vec<TransactionExecutionResult> = loaded_txs
@@ -3791,8 +4432,11 @@ impl Bank {
             .zip(sanitized_txs.iter())
             .map(|(accs, tx)| match accs {
                 (Err(e), _nonce_rollback) => {
-                    transaction_log_messages.push(None);
+                    transaction_log_entries.push(None);
                     inner_instructions.push(None);
+                    inner_instructions_invoke_units.push(None);
+                    transaction_return_data.push(None);
+                    transaction_compute_units.push(None);
                     (Err(e.clone()), None)
                 }
                 (Ok(loaded_transaction), nonce_rollback) => {
@@ -3814,6 +4458,12 @@ impl Bank {
                             &loaded_transaction.program_indices,
                         );
 
+                        let pre_accounts_data_len =
+                            Self::accounts_data_len(&loaded_transaction.accounts);
+                        let pre_rent_states = self.get_transaction_account_rent_states(
+                            tx.message(),
+                            &loaded_transaction.accounts,
+                        );
                         let account_refcells =
                             Self::accounts_to_refcells(&mut loaded_transaction.accounts);
 
@@ -3847,12 +4497,20 @@ impl Bank {
                             )
                         };
 
-                        if let Some(legacy_message) = tx.message().legacy_message() {
+                        let mut return_data = None;
+                        let mut instruction_compute_units = vec![];
+                        {
+                            let (legacy_message, program_indices, message_account_refcells) =
+                                Self::legacy_message_for_execution(
+                                    tx.message(),
+                                    &loaded_transaction.program_indices,
+                                    &account_refcells,
+                                );
                             process_result = MessageProcessor::process_message(
                                 &self.instruction_processor,
-                                legacy_message,
-                                &loaded_transaction.program_indices,
-                                &account_refcells,
+                                &legacy_message,
+                                &program_indices,
+                                &message_account_refcells,
                                 &self.rent_collector,
                                 log_collector.clone(),
                                 executors.clone(),
@@ -3865,13 +4523,17 @@ impl Bank {
                                 &self.ancestors,
                                 blockhash,
                                 lamports_per_signature,
+                                &mut return_data,
+                                &mut instruction_compute_units,
                             );
-                        } else {
-                            // TODO: support versioned messages
-                            process_result = Err(TransactionError::UnsupportedVersion);
                         }
 
-                        transaction_log_messages.push(Self::collect_log_messages(log_collector));
+                        transaction_log_entries.push(Self::collect_log_entries(log_collector));
+                        transaction_return_data.push(return_data);
+                        transaction_compute_units.push(Some(instruction_compute_units));
+                        inner_instructions_invoke_units.push(Self::collect_recorded_invoke_units(
+                            instruction_recorders.as_deref(),
+                        ));
                         inner_instructions.push(Self::compile_recorded_instructions(
                             instruction_recorders,
                             tx.message(),
@@ -3885,23 +4547,46 @@ impl Bank {
                             process_result = Err(e);
                         }
 
+                        if process_result.is_ok()
+                            && self
+                                .feature_set
+                                .is_active(&feature_set::require_rent_exempt_accounts::id())
+                        {
+                            let post_rent_states = self.get_transaction_account_rent_states(
+                                tx.message(),
+                                &loaded_transaction.accounts,
+                            );
+                            process_result = Self::verify_transaction_account_rent_states(
+                                tx.message(),
+                                &pre_rent_states,
+                                &post_rent_states,
+                            );
+                        }
+
                         if process_result.is_ok() {
+                            loaded_transaction.program_events =
+                                executors.borrow().program_events.clone();
                             self.update_executors(executors);
+                            loaded_transaction.accounts_data_len_delta =
+                                Self::accounts_data_len(&loaded_transaction.accounts)
+                                    .saturating_sub(pre_accounts_data_len);
                         }
                     } else {
-                        transaction_log_messages.push(None);
+                        transaction_log_entries.push(None);
                         inner_instructions.push(None);
+                        inner_instructions_invoke_units.push(None);
+                        transaction_return_data.push(None);
+                        transaction_compute_units.push(None);
                     }
 
-                    let nonce_rollback =
-                        if let Err(TransactionError::InstructionError(_, _)) = &process_result {
+                    let nonce_rollback = match &process_result {
+                        Err(TransactionError::InstructionError(_, _)) => {
                             error_counters.instruction_error += 1;
                             nonce_rollback.clone()
-                        } else if process_result.is_err() {
-                            None
-                        } else {
-                            nonce_rollback.clone()
-                        };
+                        }
+                        Err(err) if !Self::is_execution_failure(err) => None,
+                        _ => nonce_rollback.clone(),
+                    };
                     (process_result, nonce_rollback)
                 }
             })
@@ -3970,12 +4655,12 @@ impl Bank {
                 };
 
                 if store {
-                    if let Some(log_messages) = transaction_log_messages.get(i).cloned().flatten() {
+                    if let Some(log_entries) = transaction_log_entries.get(i).cloned().flatten() {
                         transaction_log_collector.logs.push(TransactionLogInfo {
                             signature: *tx.signature(),
                             result: r.clone(),
                             is_vote,
-                            log_messages,
+                            log_entries,
                         });
                     }
                 }
@@ -4002,15 +4687,65 @@ impl Bank {
             loaded_txs,
             executed,
             inner_instructions,
-            transaction_log_messages,
+            inner_instructions_invoke_units,
+            transaction_log_entries,
+            transaction_return_data,
+            transaction_compute_units,
             retryable_txs,
             tx_count,
             signature_count,
         )
     }
 
-    /// Calculate fee for `SanitizedMessage`
-    pub fn calculate_fee(message: &SanitizedMessage, lamports_per_signature: u64) -> u64 {
+    /// Calculate fee for `SanitizedMessage`, including the prioritization fee of a
+    /// compute unit price set by the message's compute budget instructions
+    ///
+    /// Signatures are charged `lamports_per_signature` each.  With the compute unit
+    /// fee structure, write locks and requested compute units are also charged
+    /// according to `fee_structure`, with write locks of accounts that already
+    /// took `write_lock_counts` of them in this block charged as contended.
+    pub fn calculate_fee(
+        message: &SanitizedMessage,
+        lamports_per_signature: u64,
+        fee_structure: &FeeStructure,
+        write_lock_counts: &HashMap<Pubkey, u64>,
+        use_fee_structure: bool,
+        support_set_compute_unit_price_ix: bool,
+        demote_program_write_locks: bool,
+    ) -> u64 {
+        let mut compute_budget = ComputeBudget::new();
+        let prioritization_fee = compute_budget
+            .process_message(message, true, support_set_compute_unit_price_ix)
+            .unwrap_or_default()
+            .get_fee();
+
+        let num_signatures = Self::get_num_signatures_in_message(message);
+        let mut base_fee = lamports_per_signature.saturating_mul(num_signatures);
+        if use_fee_structure {
+            let (num_write_locks, num_contended_write_locks) = Self::get_num_write_locks_in_message(
+                message,
+                fee_structure,
+                write_lock_counts,
+                demote_program_write_locks,
+            );
+            base_fee = base_fee
+                .saturating_add(
+                    fee_structure.get_write_lock_fee(num_write_locks, num_contended_write_locks),
+                )
+                .saturating_add(
+                    fee_structure.get_congestion_compute_fee(
+                        compute_budget.max_units,
+                        lamports_per_signature,
+                    ),
+                );
+        }
+
+        base_fee.saturating_add(prioritization_fee)
+    }
+
+    /// Number of signatures verified by a message, including those verified by
+    /// precompiles
+    fn get_num_signatures_in_message(message: &SanitizedMessage) -> u64 {
         let mut num_signatures = u64::from(message.header().num_required_signatures);
         for (program_id, instruction) in message.program_instructions_iter() {
             if secp256k1_program::check_id(program_id) || ed25519_program::check_id(program_id) {
@@ -4019,8 +4754,57 @@ impl Bank {
                 }
             }
         }
+        num_signatures
+    }
 
-        lamports_per_signature.saturating_mul(num_signatures)
+    /// Number of accounts write locked by a message, and how many of them are
+    /// contended
+    fn get_num_write_locks_in_message(
+        message: &SanitizedMessage,
+        fee_structure: &FeeStructure,
+        write_lock_counts: &HashMap<Pubkey, u64>,
+        demote_program_write_locks: bool,
+    ) -> (u64, u64) {
+        message
+            .account_keys_iter()
+            .enumerate()
+            .filter(|(i, _)| message.is_writable(*i, demote_program_write_locks))
+            .fold((0, 0), |(num_write_locks, num_contended), (_, key)| {
+                let write_locks = write_lock_counts.get(key).copied().unwrap_or_default();
+                let is_contended = fee_structure.is_contended(write_locks);
+                (num_write_locks + 1, num_contended + u64::from(is_contended))
+            })
+    }
+
+    /// Count the write locks of the transactions that were committed, which later
+    /// transactions of this block are charged for if they make an account contended
+    fn update_write_lock_counts(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+        fee_collection_results: &[Result<()>],
+    ) {
+        if !self
+            .feature_set
+            .is_active(&compute_unit_fee_structure::id())
+        {
+            return;
+        }
+        let demote_program_write_locks = self.demote_program_write_locks();
+        let mut write_lock_counts = self.write_lock_counts.write().unwrap();
+        for (tx, _) in sanitized_txs
+            .iter()
+            .zip(fee_collection_results)
+            .filter(|(_, result)| result.is_ok())
+        {
+            let message = tx.message();
+            for (_, key) in message
+                .account_keys_iter()
+                .enumerate()
+                .filter(|(i, _)| message.is_writable(*i, demote_program_write_locks))
+            {
+                *write_lock_counts.entry(*key).or_default() += 1;
+            }
+        }
     }
 
     fn filter_program_errors_and_collect_fee(
@@ -4048,10 +4832,13 @@ impl Bank {
 
                 let lamports_per_signature =
                     lamports_per_signature.ok_or(TransactionError::BlockhashNotFound)?;
-                let fee = Self::calculate_fee(tx.message(), lamports_per_signature);
+                let fee = self.get_fee_for_message_with_lamports_per_signature(
+                    tx.message(),
+                    lamports_per_signature,
+                );
 
-                match *res {
-                    Err(TransactionError::InstructionError(_, _)) => {
+                match res {
+                    Err(err) if Self::is_execution_failure(err) => {
                         // credit the transaction fee even in case of InstructionError
                         // necessary to withdraw from account[0] here because previous
                         // work of doing so (in accounts.load()) is ignored by store_account()
@@ -4128,6 +4915,8 @@ impl Bank {
             self.demote_program_write_locks(),
         );
         let rent_debits = self.collect_rent(executed, loaded_txs);
+        self.update_accounts_data_len(executed, loaded_txs);
+        self.notify_program_events(executed, loaded_txs);
 
         let mut update_stakes_cache_time = Measure::start("update_stakes_cache_time");
         let overwritten_vote_accounts =
@@ -4148,6 +4937,7 @@ impl Bank {
         self.update_transaction_statuses(sanitized_txs, executed);
         let fee_collection_results =
             self.filter_program_errors_and_collect_fee(sanitized_txs, executed);
+        self.update_write_lock_counts(sanitized_txs, &fee_collection_results);
 
         TransactionResults {
             fee_collection_results,
@@ -4338,6 +5128,42 @@ impl Bank {
         rent_debits
     }
 
+    fn update_accounts_data_len(
+        &self,
+        res: &[TransactionExecutionResult],
+        loaded_txs: &[TransactionLoadResult],
+    ) {
+        let accounts_data_len_delta = loaded_txs
+            .iter()
+            .zip(res)
+            .filter_map(|((raccs, _), (res, _))| res.as_ref().ok().and(raccs.as_ref().ok()))
+            .map(|loaded_transaction| loaded_transaction.accounts_data_len_delta)
+            .sum();
+        self.accounts_data_len_delta
+            .fetch_add(accounts_data_len_delta, Relaxed);
+    }
+
+    /// Notify the accounts update notifier of the programs deployed, upgraded or closed by the
+    /// committed transactions
+    fn notify_program_events(
+        &self,
+        res: &[TransactionExecutionResult],
+        loaded_txs: &[TransactionLoadResult],
+    ) {
+        for loaded_transaction in loaded_txs
+            .iter()
+            .zip(res)
+            .filter_map(|((raccs, _), (res, _))| res.as_ref().ok().and(raccs.as_ref().ok()))
+        {
+            for (program_id, event) in &loaded_transaction.program_events {
+                self.rc
+                    .accounts
+                    .accounts_db
+                    .notify_program_event(self.slot(), program_id, *event);
+            }
+        }
+    }
+
     fn run_incinerator(&self) {
         if let Some((account, _)) =
             self.get_account_modified_since_parent_with_fixed_root(&incinerator::id())
@@ -4355,14 +5181,17 @@ impl Bank {
         let mut measure = Measure::start("collect_rent_eagerly-ms");
         let partitions = self.rent_collection_partitions();
         let count = partitions.len();
-        let account_count: usize = partitions
+        let (account_count, rent_paying_count) = partitions
             .into_iter()
             .map(|partition| self.collect_rent_in_partition(partition))
-            .sum();
+            .fold((0, 0), |(accounts, rent_paying), (a, r)| {
+                (accounts + a, rent_paying + r)
+            });
         measure.stop();
         datapoint_info!(
             "collect_rent_eagerly",
             ("accounts", account_count, i64),
+            ("rent_paying_accounts", rent_paying_count, i64),
             ("partitions", count, i64)
         );
         inc_new_counter_info!("collect_rent_eagerly-ms", measure.as_ms() as usize);
@@ -4401,7 +5230,10 @@ impl Bank {
         }
     }
 
-    fn collect_rent_in_partition(&self, partition: Partition) -> usize {
+    /// Returns the number of accounts visited and how many of them are still
+    /// paying rent, so the migration of the remaining rent-paying accounts can
+    /// be tracked
+    fn collect_rent_in_partition(&self, partition: Partition) -> (usize, usize) {
         let subrange = Self::pubkey_range_from_partition(partition);
 
         self.rc.accounts.hold_range_in_memory(&subrange, true);
@@ -4416,6 +5248,7 @@ impl Bank {
         let rent_for_sysvars = self.rent_for_sysvars();
         let mut total_rent = 0;
         let mut rent_debits = RentDebits::default();
+        let mut rent_paying_count = 0;
         for (pubkey, mut account) in accounts {
             let rent = self.rent_collector.collect_from_existing_account(
                 &pubkey,
@@ -4424,6 +5257,12 @@ impl Bank {
                 self.rc.accounts.accounts_db.filler_account_suffix.as_ref(),
             );
             total_rent += rent;
+            if matches!(
+                RentState::from_account(&account, &self.rent_collector.rent),
+                RentState::RentPaying { .. }
+            ) {
+                rent_paying_count += 1;
+            }
             // Store all of them unconditionally to purge old AppendVec,
             // even if collected rent is 0 (= not updated).
             // Also, there's another subtle side-effect from this: this
@@ -4436,7 +5275,7 @@ impl Bank {
         self.rewards.write().unwrap().append(&mut rent_debits.0);
 
         self.rc.accounts.hold_range_in_memory(&subrange, false);
-        account_count
+        (account_count, rent_paying_count)
     }
 
     // Mostly, the pair (start_index & end_index) is equivalent to this range:
@@ -4776,7 +5615,9 @@ impl Bank {
         TransactionResults,
         TransactionBalancesSet,
         Vec<Option<InnerInstructionsList>>,
-        Vec<Option<TransactionLogMessages>>,
+        Vec<Option<TransactionLogEntries>>,
+        Vec<Option<TransactionReturnData>>,
+        Vec<Option<InstructionComputeUnits>>,
     ) {
         let pre_balances = if collect_balances {
             self.collect_balances(batch)
@@ -4788,7 +5629,10 @@ impl Bank {
             mut loaded_txs,
             executed,
             inner_instructions,
+            _inner_instructions_invoke_units,
             transaction_logs,
+            transaction_return_data,
+            transaction_compute_units,
             _,
             tx_count,
             signature_count,
@@ -4818,6 +5662,8 @@ impl Bank {
             TransactionBalancesSet::new(pre_balances, post_balances),
             inner_instructions,
             transaction_logs,
+            transaction_return_data,
+            transaction_compute_units,
         )
     }
 
@@ -4968,9 +5814,13 @@ impl Bank {
         self.rc.accounts.accounts_db.expire_old_recycle_stores()
     }
 
+    pub fn preallocate_stores(&self) {
+        self.rc.accounts.accounts_db.preallocate_stores()
+    }
+
     /// Technically this issues (or even burns!) new lamports,
     /// so be extra careful for its usage
-    fn store_account_and_update_capitalization(
+    pub fn store_account_and_update_capitalization(
         &self,
         pubkey: &Pubkey,
         new_account: &AccountSharedData,
@@ -5197,6 +6047,127 @@ impl Bank {
         )
     }
 
+    /// Like `get_filtered_program_accounts`, but `filter` is evaluated against the accounts as
+    /// loaded from storage, so that the data of the accounts it rejects is never copied
+    pub fn get_program_accounts_with_loaded_filter<F: Fn(&LoadedAccount) -> bool>(
+        &self,
+        program_id: &Pubkey,
+        filter: F,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        self.rc.accounts.load_by_program_with_loaded_filter(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            filter,
+        )
+    }
+
+    /// Like `get_filtered_indexed_accounts`, but `filter` is evaluated against the accounts as
+    /// loaded from storage
+    pub fn get_indexed_accounts_with_loaded_filter<F: Fn(&LoadedAccount) -> bool>(
+        &self,
+        index_key: &IndexKey,
+        filter: F,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>> {
+        self.rc.accounts.load_by_index_key_with_loaded_filter(
+            &self.ancestors,
+            self.bank_id,
+            index_key,
+            filter,
+        )
+    }
+
+    /// Like `get_program_accounts_with_loaded_filter`, but only scans the accounts whose pubkeys
+    /// are in `range`.  They are returned in pubkey order
+    pub fn get_program_accounts_in_range<F, R>(
+        &self,
+        program_id: &Pubkey,
+        range: R,
+        filter: F,
+    ) -> ScanResult<Vec<(Pubkey, AccountSharedData)>>
+    where
+        F: Fn(&LoadedAccount) -> bool,
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        self.rc.accounts.load_by_program_in_range(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            range,
+            filter,
+        )
+    }
+
+    /// Like `get_filtered_program_accounts`, but filters and returns only the metadata of the
+    /// accounts, without copying their data
+    pub fn get_filtered_program_accounts_metadata<F: Fn(&AccountScanMetadata) -> bool>(
+        &self,
+        program_id: &Pubkey,
+        filter: F,
+    ) -> ScanResult<Vec<AccountScanMetadata>> {
+        self.rc.accounts.load_metadata_by_program_with_filter(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            filter,
+        )
+    }
+
+    /// Like `get_filtered_indexed_accounts`, but filters and returns only the metadata of the
+    /// accounts, without copying their data
+    pub fn get_filtered_indexed_accounts_metadata<F: Fn(&AccountScanMetadata) -> bool>(
+        &self,
+        index_key: &IndexKey,
+        filter: F,
+    ) -> ScanResult<Vec<AccountScanMetadata>> {
+        self.rc.accounts.load_metadata_by_index_key_with_filter(
+            &self.ancestors,
+            self.bank_id,
+            index_key,
+            filter,
+        )
+    }
+
+    /// The `num` largest keys `rank` returns for the accounts of `program_id`, largest first
+    pub fn get_largest_program_accounts<K, F>(
+        &self,
+        program_id: &Pubkey,
+        num: usize,
+        rank: F,
+    ) -> ScanResult<Vec<K>>
+    where
+        K: Ord,
+        F: Fn(&Pubkey, &AccountSharedData) -> Option<K>,
+    {
+        self.rc.accounts.load_largest_by_program(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            num,
+            rank,
+        )
+    }
+
+    /// The `num` largest keys `rank` returns for the accounts under `index_key`, largest first
+    pub fn get_largest_indexed_accounts<K, F>(
+        &self,
+        index_key: &IndexKey,
+        num: usize,
+        rank: F,
+    ) -> ScanResult<Vec<K>>
+    where
+        K: Ord,
+        F: Fn(&Pubkey, &AccountSharedData) -> Option<K>,
+    {
+        self.rc.accounts.load_largest_by_index_key(
+            &self.ancestors,
+            self.bank_id,
+            index_key,
+            num,
+            rank,
+        )
+    }
+
     pub fn account_indexes_include_key(&self, key: &Pubkey) -> bool {
         self.rc.accounts.account_indexes_include_key(key)
     }
@@ -5236,6 +6207,32 @@ impl Bank {
         }
     }
 
+    /// Get the logs of transactions mentioning any of `addresses`, in execution order
+    pub fn get_transaction_logs_for_addresses(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Option<Vec<TransactionLogInfo>> {
+        let transaction_log_collector = self.transaction_log_collector.read().unwrap();
+
+        let mut log_indices: Vec<usize> = addresses
+            .iter()
+            .filter_map(|address| transaction_log_collector.mentioned_address_map.get(address))
+            .flatten()
+            .copied()
+            .collect();
+        if log_indices.is_empty() {
+            return None;
+        }
+        log_indices.sort_unstable();
+        log_indices.dedup();
+        Some(
+            log_indices
+                .into_iter()
+                .map(|i| transaction_log_collector.logs[i].clone())
+                .collect(),
+        )
+    }
+
     pub fn get_all_accounts_modified_since_parent(&self) -> Vec<(Pubkey, AccountSharedData)> {
         self.rc.accounts.load_by_program_slot(self.slot(), None)
     }
@@ -5285,6 +6282,12 @@ impl Bank {
         self.transactions_per_entry_max.load(Relaxed)
     }
 
+    /// Returns the change in the total size of all accounts' data, in bytes, caused by
+    /// transactions committed in this slot
+    pub fn accounts_data_len_delta(&self) -> i64 {
+        self.accounts_data_len_delta.load(Relaxed)
+    }
+
     fn increment_transaction_count(&self, tx_count: u64) {
         self.transaction_count.fetch_add(tx_count, Relaxed);
     }
@@ -5358,6 +6361,15 @@ impl Bank {
             hash = extend_and_hash(&hash, &buf)
         }
 
+        if let Some(epoch_accounts_hash) = self.integrated_epoch_accounts_hash() {
+            info!(
+                "epoch accounts hash at bank {}: {}",
+                self.slot(),
+                epoch_accounts_hash.hash
+            );
+            hash = hashv(&[hash.as_ref(), epoch_accounts_hash.hash.as_ref()]);
+        }
+
         info!(
             "bank frozen: {} hash: {} accounts_delta: {} signature_count: {} last_blockhash: {} capitalization: {}",
             self.slot(),
@@ -5368,12 +6380,175 @@ impl Bank {
             self.capitalization(),
         );
 
-        info!(
-            "accounts hash slot: {} stats: {:?}",
+        info!(
+            "accounts hash slot: {} stats: {:?}",
+            self.slot(),
+            accounts_delta_hash.stats,
+        );
+        hash
+    }
+
+    /// Returns the most recent epoch accounts hash mixed into a bank hash on this fork, if any.
+    /// Banks rebuilt from a snapshot only have one once the fork reaches the next integration
+    /// slot.
+    pub fn epoch_accounts_hash(&self) -> Option<EpochAccountsHash> {
+        *self.epoch_accounts_hash.read().unwrap()
+    }
+
+    /// Returns the epoch accounts hash if it was mixed into the hash of this bank
+    pub fn integrated_epoch_accounts_hash(&self) -> Option<EpochAccountsHash> {
+        if !epoch_accounts_hash::is_integration_bank(
+            &self.epoch_schedule,
+            self.parent_slot(),
+            self.slot(),
+        ) {
+            return None;
+        }
+        let calculation_slot =
+            epoch_accounts_hash::calculation_slot(&self.epoch_schedule, self.epoch());
+        self.epoch_accounts_hash()
+            .filter(|epoch_accounts_hash| epoch_accounts_hash.slot >= calculation_slot)
+    }
+
+    /// Returns true if this is the bank the epoch accounts hash of its epoch is calculated from
+    pub(crate) fn is_epoch_accounts_hash_calculation_bank(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::epoch_accounts_hash::id())
+            && epoch_accounts_hash::is_calculation_bank(
+                &self.epoch_schedule,
+                self.parent_slot(),
+                self.slot(),
+            )
+    }
+
+    /// Mix the epoch accounts hash into the bank hash if this is the first bank of its fork at or
+    /// past the integration slot of its epoch.  The calculation was started in the background
+    /// when the calculation bank was rooted, so this only blocks if it hasn't finished yet.
+    fn update_epoch_accounts_hash(&self) {
+        if !self
+            .feature_set
+            .is_active(&feature_set::epoch_accounts_hash::id())
+            || !epoch_accounts_hash::is_integration_bank(
+                &self.epoch_schedule,
+                self.parent_slot(),
+                self.slot(),
+            )
+        {
+            return;
+        }
+
+        let mut wait_time = Measure::start("wait_epoch_accounts_hash");
+        let epoch_accounts_hash = self.wait_get_epoch_accounts_hash();
+        wait_time.stop();
+
+        datapoint_info!(
+            "epoch_accounts_hash_integration",
+            ("slot", self.slot(), i64),
+            ("calculation_slot", epoch_accounts_hash.slot, i64),
+            ("hash", epoch_accounts_hash.hash.to_string(), String),
+            ("wait_us", wait_time.as_us(), i64),
+        );
+        *self.epoch_accounts_hash.write().unwrap() = Some(epoch_accounts_hash);
+    }
+
+    /// Returns the epoch accounts hash of the calculation bank on this fork
+    fn wait_get_epoch_accounts_hash(&self) -> EpochAccountsHash {
+        let accounts_db = &self.rc.accounts.accounts_db;
+
+        // Nothing requests the calculation until the calculation bank is rooted, which it may not
+        // be yet if it is this bank or one of its parents
+        let unrooted_calculation_bank = if self.is_epoch_accounts_hash_calculation_bank() {
+            Some((self.slot(), self.ancestors.clone()))
+        } else {
+            self.parents()
+                .into_iter()
+                .find(|parent| parent.is_epoch_accounts_hash_calculation_bank())
+                .map(|parent| (parent.slot(), parent.ancestors.clone()))
+        };
+        if let Some((slot, ancestors)) = unrooted_calculation_bank {
+            return accounts_db
+                .epoch_accounts_hash(slot)
+                .unwrap_or_else(|| accounts_db.calculate_epoch_accounts_hash(slot, &ancestors));
+        }
+
+        let calculation_slot =
+            epoch_accounts_hash::calculation_slot(&self.epoch_schedule, self.epoch());
+        accounts_db
+            .epoch_accounts_hash_manager
+            .slot()
+            .filter(|slot| (calculation_slot..self.slot()).contains(slot))
+            .and_then(|slot| accounts_db.epoch_accounts_hash(slot))
+            .unwrap_or_else(|| {
+                panic!(
+                    "epoch accounts hash for epoch {} is unavailable at slot {}, was the node \
+                     started from a snapshot without it?",
+                    self.epoch(),
+                    self.slot()
+                )
+            })
+    }
+
+    /// Returns the epoch accounts hash a snapshot of this bank must carry: the one mixed into its
+    /// hash if this is the integration bank, or the one calculated earlier in the epoch if this
+    /// bank is between the calculation and integration slots
+    pub(crate) fn epoch_accounts_hash_for_snapshot(&self) -> Option<EpochAccountsHash> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::epoch_accounts_hash::id())
+        {
+            return None;
+        }
+        if let Some(epoch_accounts_hash) = self.integrated_epoch_accounts_hash() {
+            return Some(epoch_accounts_hash);
+        }
+        let calculation_slot =
+            epoch_accounts_hash::calculation_slot(&self.epoch_schedule, self.epoch());
+        let integration_slot =
+            epoch_accounts_hash::integration_slot(&self.epoch_schedule, self.epoch());
+        if !(calculation_slot..integration_slot).contains(&self.slot()) {
+            return None;
+        }
+        let accounts_db = &self.rc.accounts.accounts_db;
+        accounts_db
+            .epoch_accounts_hash_manager
+            .slot()
+            .filter(|slot| (calculation_slot..=self.slot()).contains(slot))
+            .and_then(|slot| accounts_db.epoch_accounts_hash(slot))
+    }
+
+    /// Restore the epoch accounts hash carried by the snapshot this bank was rebuilt from
+    pub(crate) fn set_epoch_accounts_hash_from_snapshot(
+        &self,
+        epoch_accounts_hash: EpochAccountsHash,
+    ) {
+        self.rc
+            .accounts
+            .accounts_db
+            .epoch_accounts_hash_manager
+            .set_valid(epoch_accounts_hash);
+        if epoch_accounts_hash::is_integration_bank(
+            &self.epoch_schedule,
+            self.parent_slot(),
             self.slot(),
-            accounts_delta_hash.stats,
-        );
-        hash
+        ) {
+            *self.epoch_accounts_hash.write().unwrap() = Some(epoch_accounts_hash);
+        }
+    }
+
+    /// Whether a snapshot of this bank must carry an epoch accounts hash: one taken between the
+    /// calculation and integration slots of its epoch can't be loaded without it
+    pub(crate) fn snapshot_requires_epoch_accounts_hash(&self) -> bool {
+        if !self
+            .feature_set
+            .is_active(&feature_set::epoch_accounts_hash::id())
+        {
+            return false;
+        }
+        let calculation_slot =
+            epoch_accounts_hash::calculation_slot(&self.epoch_schedule, self.epoch());
+        let integration_slot =
+            epoch_accounts_hash::integration_slot(&self.epoch_schedule, self.epoch());
+        (calculation_slot..integration_slot).contains(&self.slot())
     }
 
     /// Recalculate the hash_internal_state from the account stores. Would be used to verify a
@@ -5416,6 +6591,32 @@ impl Bank {
         }
     }
 
+    /// Load the addresses referenced by the address table lookups of a version #0 message
+    pub fn load_lookup_table_addresses(
+        &self,
+        address_table_lookups: &[MessageAddressTableLookup],
+    ) -> Result<LoadedAddresses> {
+        if !self.versioned_tx_message_enabled() {
+            return Err(TransactionError::UnsupportedVersion);
+        }
+
+        let slot_hashes: SlotHashes = self
+            .get_account_with_fixed_root(&sysvar::slot_hashes::id())
+            .and_then(|account| from_account(&account))
+            .ok_or(TransactionError::AccountNotFound)?;
+
+        Ok(address_table_lookups
+            .iter()
+            .map(|address_table_lookup| {
+                self.rc.accounts.load_lookup_table_addresses(
+                    &self.ancestors,
+                    address_table_lookup,
+                    &slot_hashes,
+                )
+            })
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
     pub fn verify_transaction(
         &self,
         tx: VersionedTransaction,
@@ -5433,8 +6634,8 @@ impl Bank {
                 tx.message.hash()
             };
 
-            SanitizedTransaction::try_create(tx, message_hash, |_| {
-                Err(TransactionError::UnsupportedVersion)
+            SanitizedTransaction::try_create(tx, message_hash, |address_table_lookups| {
+                self.load_lookup_table_addresses(address_table_lookups)
             })
         }?;
 
@@ -5560,6 +6761,7 @@ impl Bank {
         test_hash_calculation: bool,
         accounts_db_skip_shrink: bool,
         last_full_snapshot_slot: Option<Slot>,
+        skip_accounts_hash_verification: bool,
     ) -> bool {
         info!("cleaning..");
         let mut clean_time = Measure::start("clean");
@@ -5580,9 +6782,14 @@ impl Bank {
         }
         shrink_all_slots_time.stop();
 
-        info!("verify_bank_hash..");
         let mut verify_time = Measure::start("verify_bank_hash");
-        let mut verify = self.verify_bank_hash(test_hash_calculation);
+        let mut verify = if skip_accounts_hash_verification {
+            info!("skipping verify_bank_hash..");
+            true
+        } else {
+            info!("verify_bank_hash..");
+            self.verify_bank_hash(test_hash_calculation)
+        };
         verify_time.stop();
         self.rc
             .accounts
@@ -5941,6 +7148,11 @@ impl Bank {
             .is_active(&feature_set::demote_program_write_locks::id())
     }
 
+    pub fn tx_wide_compute_cap(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::tx_wide_compute_cap::id())
+    }
+
     pub fn stakes_remove_delegation_if_inactive_enabled(&self) -> bool {
         self.feature_set
             .is_active(&feature_set::stakes_remove_delegation_if_inactive::id())
@@ -6315,10 +7527,13 @@ fn is_simple_vote_transaction(transaction: &SanitizedTransaction) -> bool {
 pub(crate) mod tests {
     use super::*;
     use crate::{
-        accounts_background_service::{AbsRequestHandler, SendDroppedBankCallback},
+        accounts_background_service::{
+            AbsRequestHandler, AbsRequestSender, SendDroppedBankCallback,
+        },
         accounts_db::DEFAULT_ACCOUNTS_SHRINK_RATIO,
         accounts_index::{AccountIndex, AccountSecondaryIndexes, ScanError, ITER_BATCH_SIZE},
         ancestors::Ancestors,
+        bank_forks::BankForks,
         genesis_utils::{
             activate_all_features, bootstrap_validator_stake_lamports,
             create_genesis_config_with_leader, create_genesis_config_with_vote_accounts,
@@ -6335,6 +7550,7 @@ pub(crate) mod tests {
         compute_budget::ComputeBudgetInstruction,
         epoch_schedule::MINIMUM_SLOTS_PER_EPOCH,
         feature::Feature,
+        fee::{FeeBin, DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS},
         genesis_config::create_genesis_config,
         hash,
         instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
@@ -7107,6 +8323,12 @@ pub(crate) mod tests {
             burn_percent: 10,
         };
 
+        // The payee's rent collection drains it before the transfer recreates it below the
+        // rent-exempt minimum, which is only allowed before rent-exempt accounts are required
+        genesis_config
+            .accounts
+            .remove(&feature_set::require_rent_exempt_accounts::id())
+            .unwrap();
         let mut bank = Bank::new_for_tests(&genesis_config);
         // Enable rent collection
         bank.rent_collector.epoch = 5;
@@ -8501,6 +9723,211 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_partitioned_epoch_rewards() {
+        solana_logger::setup();
+
+        // create a bank that ticks really slowly...
+        let mut genesis_config = GenesisConfig {
+            accounts: (0..42)
+                .map(|_| {
+                    (
+                        solana_sdk::pubkey::new_rand(),
+                        Account::new(1_000_000_000, 0, &Pubkey::default()),
+                    )
+                })
+                .collect(),
+            // set it up so the first epoch is a full year long
+            poh_config: PohConfig {
+                target_tick_duration: Duration::from_secs(
+                    SECONDS_PER_YEAR as u64
+                        / MINIMUM_SLOTS_PER_EPOCH as u64
+                        / DEFAULT_TICKS_PER_SLOT,
+                ),
+                hashes_per_tick: None,
+                target_tick_count: None,
+            },
+            cluster_type: ClusterType::MainnetBeta,
+
+            ..GenesisConfig::default()
+        };
+        for feature_id in &[
+            feature_set::optimize_epoch_boundary_updates::id(),
+            feature_set::partitioned_epoch_rewards::id(),
+        ] {
+            genesis_config.accounts.insert(
+                *feature_id,
+                Account::from(feature::create_account(
+                    &Feature {
+                        activated_at: Some(0),
+                    },
+                    42,
+                )),
+            );
+        }
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let vote_id = solana_sdk::pubkey::new_rand();
+        // enough lamports for the accounts to outlive the rent collected over the first epoch
+        let mut vote_account = vote_state::create_account(
+            &vote_id,
+            &solana_sdk::pubkey::new_rand(),
+            50,
+            1_000_000_000,
+        );
+        let stake_ids: Vec<_> = (0..32)
+            .map(|_| {
+                let (stake_id, stake_account) =
+                    crate::stakes::tests::create_stake_account(1_000_000_000, &vote_id);
+                bank.store_account_and_update_capitalization(&stake_id, &stake_account);
+                stake_id
+            })
+            .collect();
+
+        // generate some rewards
+        let mut vote_state = VoteState::from(&vote_account).unwrap();
+        for i in 0..MAX_LOCKOUT_HISTORY + 42 {
+            vote_state.process_slot_vote_unchecked(i as u64);
+        }
+        VoteState::to(
+            &VoteStateVersions::new_current(vote_state),
+            &mut vote_account,
+        )
+        .unwrap();
+        bank.store_account_and_update_capitalization(&vote_id, &vote_account);
+
+        // the first bank of epoch 1 only calculates the points the stakes earned
+        let bank1 = Arc::new(Bank::new_from_parent(
+            &bank,
+            &Pubkey::default(),
+            bank.get_slots_in_epoch(bank.epoch()) + 1,
+        ));
+        let num_blocks = get_reward_distribution_num_blocks(&bank1.epoch_schedule, bank1.epoch());
+        assert!(num_blocks > 1);
+        let pending_stake_rewards = bank1.pending_stake_rewards().unwrap().clone();
+        assert_eq!(
+            pending_stake_rewards.distribution_start_block_height,
+            bank1.block_height() + 1
+        );
+        assert_eq!(pending_stake_rewards.partitions.len() as u64, num_blocks);
+        assert_eq!(
+            pending_stake_rewards
+                .partitions
+                .iter()
+                .map(|partition| partition.len())
+                .sum::<usize>(),
+            stake_ids.len()
+        );
+        assert!(pending_stake_rewards.total_lamports() > 0);
+        // the pending rewards are kept in an account per partition, which is how they're
+        // restored from a snapshot
+        assert_eq!(
+            bank1.load_pending_stake_rewards().as_deref(),
+            Some(&pending_stake_rewards)
+        );
+        assert!(bank1.rewards.read().unwrap().is_empty());
+        let vote_balance = bank1.get_balance(&vote_id);
+
+        // stake accounts may still change while their rewards are pending, the last one is
+        // closed and forfeits its share of the reward, the voter still gets theirs
+        let closed_stake_id = *stake_ids.last().unwrap();
+        let (closed_stakers_reward, _closed_voters_reward) = pending_stake_rewards
+            .partitions
+            .iter()
+            .flat_map(|partition| partition.iter())
+            .find(|stake_reward| stake_reward.stake_pubkey == closed_stake_id)
+            .and_then(|stake_reward| pending_stake_rewards.calculate_rewards(stake_reward))
+            .unwrap();
+        bank1.store_account_and_update_capitalization(
+            &closed_stake_id,
+            &AccountSharedData::default(),
+        );
+        bank1.freeze();
+        assert!(bank1.calculate_and_verify_capitalization(true));
+
+        // each of the following blocks credits one partition
+        let mut bank = bank1;
+        let mut stake_rewards = HashMap::new();
+        let mut vote_rewards = 0;
+        for (index, partition) in pending_stake_rewards.partitions.iter().enumerate() {
+            bank = Arc::new(new_from_parent(&bank));
+            assert!(bank
+                .get_account(&get_pending_stake_rewards_address(index))
+                .is_none());
+            assert_eq!(
+                bank.load_pending_stake_rewards().as_deref(),
+                bank.pending_stake_rewards()
+            );
+            let partition: Vec<_> = partition
+                .iter()
+                .filter(|stake_reward| stake_reward.stake_pubkey != closed_stake_id)
+                .collect();
+            let partition_rewards: Vec<_> = bank
+                .rewards
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|(_address, reward)| reward.reward_type == RewardType::Staking)
+                .cloned()
+                .collect();
+            assert_eq!(partition_rewards.len(), partition.len());
+            for (stake_id, reward) in partition_rewards {
+                assert!(partition
+                    .iter()
+                    .any(|stake_reward| stake_reward.stake_pubkey == stake_id));
+                assert_eq!(reward.post_balance, bank.get_balance(&stake_id));
+                stake_rewards.insert(stake_id, reward.lamports as u64);
+            }
+            // the voter's share of the partition's rewards is credited along with it
+            let voters_reward: u64 = pending_stake_rewards.partitions[index]
+                .iter()
+                .filter_map(|stake_reward| pending_stake_rewards.calculate_rewards(stake_reward))
+                .map(|(_stakers_reward, voters_reward)| voters_reward)
+                .sum();
+            let partition_vote_rewards: Vec<_> = bank
+                .rewards
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|(_address, reward)| reward.reward_type == RewardType::Voting)
+                .cloned()
+                .collect();
+            if voters_reward > 0 {
+                assert_eq!(partition_vote_rewards.len(), 1);
+                let (address, reward) = &partition_vote_rewards[0];
+                assert_eq!(*address, vote_id);
+                assert_eq!(reward.lamports as u64, voters_reward);
+                assert_eq!(reward.post_balance, bank.get_balance(&vote_id));
+            } else {
+                assert!(partition_vote_rewards.is_empty());
+            }
+            vote_rewards += voters_reward;
+            bank.freeze();
+            assert!(bank.calculate_and_verify_capitalization(true));
+        }
+        assert!(bank.pending_stake_rewards().is_none());
+        assert!(bank.load_pending_stake_rewards().is_none());
+        assert_eq!(stake_rewards.len(), stake_ids.len() - 1);
+        assert!(vote_rewards > 0);
+        assert_eq!(bank.get_balance(&vote_id), vote_balance + vote_rewards);
+        assert_eq!(
+            stake_rewards.values().sum::<u64>() + vote_rewards,
+            pending_stake_rewards.total_lamports() - closed_stakers_reward
+        );
+        assert!(bank.get_account(&closed_stake_id).is_none());
+        for stake_id in &stake_ids[..stake_ids.len() - 1] {
+            let stake = stake_state::stake_from(&bank.get_account(stake_id).unwrap()).unwrap();
+            assert_eq!(
+                stake.delegation.stake,
+                1_000_000_000 + stake_rewards[stake_id]
+            );
+        }
+
+        // nothing is left to credit
+        let bank = new_from_parent(&bank);
+        assert!(bank.rewards.read().unwrap().is_empty());
+    }
+
     // Test that purging 0 lamports accounts works.
     #[test]
     fn test_purge_empty_accounts() {
@@ -8966,22 +10393,31 @@ pub(crate) mod tests {
         let key = Keypair::new();
         let initial_mint_balance = bank.get_balance(&mint_keypair.pubkey());
         let tx = system_transaction::transfer(&mint_keypair, &key.pubkey(), 1, cheap_blockhash);
+        let cheap_fee = bank.get_fee_for_message_with_lamports_per_signature(
+            &SanitizedMessage::try_from(tx.message().clone()).unwrap(),
+            cheap_lamports_per_signature,
+        );
         assert_eq!(bank.process_transaction(&tx), Ok(()));
         assert_eq!(bank.get_balance(&key.pubkey()), 1);
         assert_eq!(
             bank.get_balance(&mint_keypair.pubkey()),
-            initial_mint_balance - 1 - cheap_lamports_per_signature
+            initial_mint_balance - 1 - cheap_fee
         );
 
         // Send a transfer using expensive_blockhash
         let key = Keypair::new();
         let initial_mint_balance = bank.get_balance(&mint_keypair.pubkey());
         let tx = system_transaction::transfer(&mint_keypair, &key.pubkey(), 1, expensive_blockhash);
+        let expensive_fee = bank.get_fee_for_message_with_lamports_per_signature(
+            &SanitizedMessage::try_from(tx.message().clone()).unwrap(),
+            expensive_lamports_per_signature,
+        );
+        assert!(cheap_fee < expensive_fee);
         assert_eq!(bank.process_transaction(&tx), Ok(()));
         assert_eq!(bank.get_balance(&key.pubkey()), 1);
         assert_eq!(
             bank.get_balance(&mint_keypair.pubkey()),
-            initial_mint_balance - 1 - expensive_lamports_per_signature
+            initial_mint_balance - 1 - expensive_fee
         );
     }
 
@@ -9133,6 +10569,158 @@ pub(crate) mod tests {
         assert_eq!(results[1], Err(TransactionError::AccountInUse));
     }
 
+    #[test]
+    fn test_accounts_data_len_delta() {
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        assert_eq!(bank.accounts_data_len_delta(), 0);
+
+        let account_keypair = Keypair::new();
+        let space = 100;
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &account_keypair,
+            bank.last_blockhash(),
+            bank.get_minimum_balance_for_rent_exemption(space),
+            space as u64,
+            &Pubkey::new_unique(),
+        );
+        assert_eq!(bank.process_transaction(&tx), Ok(()));
+        assert_eq!(bank.accounts_data_len_delta(), space as i64);
+
+        // Failed transactions do not change the size of accounts data
+        let space = 200;
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &account_keypair,
+            bank.last_blockhash(),
+            bank.get_minimum_balance_for_rent_exemption(space),
+            space as u64,
+            &Pubkey::new_unique(),
+        );
+        assert!(bank.process_transaction(&tx).is_err());
+        assert_eq!(bank.accounts_data_len_delta(), 100);
+
+        // The delta is tracked per slot
+        let bank = Bank::new_from_parent(&Arc::new(bank), &Pubkey::default(), 1);
+        assert_eq!(bank.accounts_data_len_delta(), 0);
+    }
+
+    #[test]
+    fn test_require_rent_exempt_accounts() {
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000_000);
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        let rent_exempt_minimum = bank.get_minimum_balance_for_rent_exemption(0);
+
+        // Rent-paying accounts can be created until the feature is activated
+        let rent_paying_pubkey = solana_sdk::pubkey::new_rand();
+        assert!(bank
+            .transfer(rent_exempt_minimum - 1, &mint_keypair, &rent_paying_pubkey)
+            .is_ok());
+
+        bank.activate_feature(&feature_set::require_rent_exempt_accounts::id());
+
+        // New accounts must be rent-exempt
+        let new_pubkey = solana_sdk::pubkey::new_rand();
+        assert_eq!(
+            bank.transfer(rent_exempt_minimum - 1, &mint_keypair, &new_pubkey),
+            Err(TransactionError::InvalidRentPayingAccount)
+        );
+        assert_eq!(bank.get_balance(&new_pubkey), 0);
+        assert!(bank
+            .transfer(rent_exempt_minimum, &mint_keypair, &new_pubkey)
+            .is_ok());
+
+        // including when allocating data for them, which the system program
+        // rejects up front
+        let account_keypair = Keypair::new();
+        let tx = system_transaction::create_account(
+            &mint_keypair,
+            &account_keypair,
+            bank.last_blockhash(),
+            rent_exempt_minimum,
+            100,
+            &Pubkey::new_unique(),
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::AccountNotRentExempt
+            ))
+        );
+
+        // Existing rent-paying accounts may remain rent-paying
+        assert!(bank.transfer(1, &mint_keypair, &rent_paying_pubkey).is_ok());
+        assert_eq!(bank.get_balance(&rent_paying_pubkey), rent_exempt_minimum);
+
+        // but rent-exempt accounts cannot become rent-paying
+        let rent_exempt_keypair = Keypair::new();
+        assert!(bank
+            .transfer(
+                rent_exempt_minimum,
+                &mint_keypair,
+                &rent_exempt_keypair.pubkey()
+            )
+            .is_ok());
+        let message = Message::new(
+            &[system_instruction::transfer(
+                &rent_exempt_keypair.pubkey(),
+                &mint_keypair.pubkey(),
+                1,
+            )],
+            Some(&mint_keypair.pubkey()),
+        );
+        let tx = Transaction::new(
+            &[&mint_keypair, &rent_exempt_keypair],
+            message,
+            bank.last_blockhash(),
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InvalidRentPayingAccount)
+        );
+        assert_eq!(
+            bank.get_balance(&rent_exempt_keypair.pubkey()),
+            rent_exempt_minimum
+        );
+    }
+
+    #[test]
+    fn test_invalid_rent_paying_account_charges_fee() {
+        let (mut genesis_config, mint_keypair) = create_genesis_config(1_000_000_000);
+        genesis_config.fee_rate_governor = FeeRateGovernor::new(5_000, 0);
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        bank.activate_feature(&feature_set::require_rent_exempt_accounts::id());
+        let rent_exempt_minimum = bank.get_minimum_balance_for_rent_exemption(0);
+
+        let new_pubkey = solana_sdk::pubkey::new_rand();
+        let tx = system_transaction::transfer(
+            &mint_keypair,
+            &new_pubkey,
+            rent_exempt_minimum - 1,
+            bank.last_blockhash(),
+        );
+        let fee =
+            bank.get_fee_for_message(&SanitizedMessage::try_from(tx.message.clone()).unwrap());
+        assert!(fee > 0);
+        let mint_balance = bank.get_balance(&mint_keypair.pubkey());
+
+        // the transaction is committed as failed, and its fee payer pays for its execution
+        let results = bank.process_transactions(std::iter::once(&tx));
+        assert_eq!(results, vec![Ok(())]);
+        assert_eq!(
+            bank.get_signature_status(&tx.signatures[0]),
+            Some(Err(TransactionError::InvalidRentPayingAccount))
+        );
+        assert_eq!(bank.get_balance(&mint_keypair.pubkey()), mint_balance - fee);
+        assert_eq!(bank.get_balance(&new_pubkey), 0);
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::AlreadyProcessed)
+        );
+    }
+
     #[test]
     fn test_interleaving_locks() {
         let (genesis_config, mint_keypair) = create_genesis_config(3);
@@ -9384,6 +10972,105 @@ pub(crate) mod tests {
         assert!(bank2.verify_bank_hash(true));
     }
 
+    #[test]
+    fn test_epoch_accounts_hash() {
+        let (mut genesis_config, mint_keypair) = create_genesis_config(2_000);
+        genesis_config.epoch_schedule = EpochSchedule::custom(32, 32, false);
+        let calculation_slot =
+            epoch_accounts_hash::calculation_slot(&genesis_config.epoch_schedule, 0);
+        let integration_slot =
+            epoch_accounts_hash::integration_slot(&genesis_config.epoch_schedule, 0);
+        let pubkey = solana_sdk::pubkey::new_rand();
+
+        // Returns the banks at the calculation slot, right before the integration slot, and at
+        // the integration slot
+        let new_fork = |enable_epoch_accounts_hash: bool,
+                        root_calculation_bank: bool,
+                        transfer_after_calculation: bool| {
+            let mut bank0 = Bank::new_for_tests(&genesis_config);
+            if enable_epoch_accounts_hash {
+                bank0.activate_feature(&feature_set::epoch_accounts_hash::id());
+            }
+            let mut bank_forks = BankForks::new(bank0);
+            let bank1 = bank_forks.insert(Bank::new_from_parent(
+                &bank_forks[0],
+                &Pubkey::default(),
+                calculation_slot - 1,
+            ));
+            bank1.freeze();
+            let bank2 = bank_forks.insert(Bank::new_from_parent(
+                &bank1,
+                &Pubkey::default(),
+                calculation_slot,
+            ));
+            bank2.transfer(1_000, &mint_keypair, &pubkey).unwrap();
+            bank2.freeze();
+            if root_calculation_bank {
+                bank_forks.set_root(calculation_slot, &AbsRequestSender::default(), None);
+                // What the accounts background service does once it sees the new root
+                bank2
+                    .rc
+                    .accounts
+                    .accounts_db
+                    .calculate_requested_epoch_accounts_hash();
+            }
+            let bank3 = bank_forks.insert(Bank::new_from_parent(
+                &bank2,
+                &Pubkey::default(),
+                integration_slot - 1,
+            ));
+            if transfer_after_calculation {
+                bank3.transfer(500, &mint_keypair, &pubkey).unwrap();
+            }
+            bank3.freeze();
+            let bank4 = bank_forks.insert(Bank::new_from_parent(
+                &bank3,
+                &Pubkey::default(),
+                integration_slot,
+            ));
+            bank4.freeze();
+            (bank2, bank3, bank4)
+        };
+        let (bank2, bank3, bank4) = new_fork(true, false, false);
+        let (bank2_without, bank3_without, bank4_without) = new_fork(false, false, false);
+
+        // The epoch accounts hash isn't part of the bank hash until the integration slot
+        assert_eq!(bank2.epoch_accounts_hash(), None);
+        assert_eq!(bank2.hash(), bank2_without.hash());
+        assert_eq!(bank3.epoch_accounts_hash(), None);
+        assert_eq!(bank3.hash(), bank3_without.hash());
+
+        let epoch_accounts_hash = bank4.integrated_epoch_accounts_hash().unwrap();
+        assert_eq!(epoch_accounts_hash.slot, calculation_slot);
+        assert_eq!(bank4.epoch_accounts_hash(), Some(epoch_accounts_hash));
+        assert_eq!(bank4_without.epoch_accounts_hash(), None);
+        assert_ne!(bank4.hash(), bank4_without.hash());
+        assert!(bank4.verify_hash());
+
+        // Calculating it in the background once the calculation bank is rooted gives the same
+        // hash as calculating it at the integration slot
+        let (_, _, rooted_bank4) = new_fork(true, true, false);
+        assert_eq!(
+            rooted_bank4.integrated_epoch_accounts_hash(),
+            Some(epoch_accounts_hash)
+        );
+        assert_eq!(rooted_bank4.hash(), bank4.hash());
+
+        // The hash is of the accounts at the calculation slot, not at the integration slot
+        let (_, transfer_bank3, transfer_bank4) = new_fork(true, false, true);
+        assert_ne!(transfer_bank3.hash(), bank3.hash());
+        assert_eq!(
+            transfer_bank4.integrated_epoch_accounts_hash(),
+            Some(epoch_accounts_hash)
+        );
+
+        // Descendants carry the epoch accounts hash, but don't mix it in again
+        let bank5 = Bank::new_from_parent(&bank4, &Pubkey::default(), integration_slot + 1);
+        bank5.freeze();
+        assert_eq!(bank5.epoch_accounts_hash(), Some(epoch_accounts_hash));
+        assert_eq!(bank5.integrated_epoch_accounts_hash(), None);
+    }
+
     #[test]
     fn test_bank_hash_internal_state_verify() {
         solana_logger::setup();
@@ -9437,11 +11124,11 @@ pub(crate) mod tests {
         bank.transfer(1_000, &mint_keypair, &pubkey).unwrap();
         bank.freeze();
         bank.update_accounts_hash();
-        assert!(bank.verify_snapshot_bank(true, false, None));
+        assert!(bank.verify_snapshot_bank(true, false, None, false));
 
         // tamper the bank after freeze!
         bank.increment_signature_count(1);
-        assert!(!bank.verify_snapshot_bank(true, false, None));
+        assert!(!bank.verify_snapshot_bank(true, false, None, false));
     }
 
     // Test that two bank forks with the same accounts should not hash to the same value.
@@ -10115,10 +11802,15 @@ pub(crate) mod tests {
     #[test]
     fn test_bank_cloned_stake_delegations() {
         let GenesisConfigInfo {
-            genesis_config,
+            mut genesis_config,
             mint_keypair,
             ..
         } = create_genesis_config_with_leader(500, &solana_sdk::pubkey::new_rand(), 1);
+        // Delegate less than the 1 SOL minimum delegation
+        genesis_config
+            .accounts
+            .remove(&feature_set::stake_raise_minimum_delegation_to_1_sol::id())
+            .unwrap();
         let bank = Arc::new(Bank::new_for_tests(&genesis_config));
 
         let stake_delegations = bank.cloned_stake_delegations();
@@ -11244,22 +12936,29 @@ pub(crate) mod tests {
 
         let tx0 = system_transaction::transfer(&keypair0, &pubkey0, 2, blockhash);
         let tx1 = system_transaction::transfer(&Keypair::new(), &pubkey1, 2, blockhash);
-        let tx2 = system_transaction::transfer(&keypair1, &pubkey2, 12, blockhash);
-        let txs = vec![tx0, tx1, tx2];
-
-        let lock_result = bank0.prepare_batch(txs).unwrap();
-        let (transaction_results, transaction_balances_set, inner_instructions, transaction_logs) =
-            bank0.load_execute_and_commit_transactions(
-                &lock_result,
-                MAX_PROCESSING_AGE,
-                true,
-                false,
-                false,
-                &mut ExecuteTimings::default(),
-            );
+        let tx2 = system_transaction::transfer(&keypair1, &pubkey2, 12, blockhash);
+        let txs = vec![tx0, tx1, tx2];
+
+        let lock_result = bank0.prepare_batch(txs).unwrap();
+        let (
+            transaction_results,
+            transaction_balances_set,
+            inner_instructions,
+            transaction_logs,
+            transaction_return_data,
+            _compute_units,
+        ) = bank0.load_execute_and_commit_transactions(
+            &lock_result,
+            MAX_PROCESSING_AGE,
+            true,
+            false,
+            false,
+            &mut ExecuteTimings::default(),
+        );
 
         assert!(inner_instructions.iter().all(Option::is_none));
         assert!(transaction_logs.iter().all(Option::is_none));
+        assert!(transaction_return_data.iter().all(Option::is_none));
 
         assert_eq!(inner_instructions.len(), 3);
         assert_eq!(transaction_logs.len(), 3);
@@ -11383,6 +13082,51 @@ pub(crate) mod tests {
         assert_eq!(result, Ok(()));
     }
 
+    #[test]
+    fn test_simulate_transaction_inner_instructions_invoke_units() {
+        let (genesis_config, mint_keypair) = create_genesis_config(500);
+        let mut bank = Bank::new_for_tests(&genesis_config);
+
+        // Records an invocation for each byte of the instruction data, charged that many units,
+        // the way the invoke syscalls do
+        fn mock_process_instruction(
+            _first_instruction_account: usize,
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> result::Result<(), InstructionError> {
+            for units in data {
+                invoke_context.record_instruction(&Instruction::new_with_bytes(
+                    system_program::id(),
+                    &[],
+                    vec![],
+                ));
+                invoke_context.record_invoke_units(*units as u64);
+            }
+            Ok(())
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        bank.add_builtin("mock_program", &mock_program_id, mock_process_instruction);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                Instruction::new_with_bytes(mock_program_id, &[10, 20], vec![]),
+                Instruction::new_with_bytes(mock_program_id, &[], vec![]),
+                Instruction::new_with_bytes(mock_program_id, &[30], vec![]),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            bank.last_blockhash(),
+        );
+        let result =
+            bank.simulate_transaction_unchecked(SanitizedTransaction::try_from(tx).unwrap());
+        assert_eq!(result.result, Ok(()));
+        assert_eq!(
+            result.inner_instructions_invoke_units,
+            vec![vec![10, 20], vec![], vec![30]]
+        );
+    }
+
     #[test]
     fn test_account_ids_after_program_ids() {
         solana_logger::setup();
@@ -12745,6 +14489,53 @@ pub(crate) mod tests {
         assert!(!executors.borrow().executors.contains_key(&key4));
     }
 
+    #[test]
+    fn test_bank_executor_cache_program_events() {
+        let (genesis_config, _) = create_genesis_config(1);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let key1 = solana_sdk::pubkey::new_rand();
+        let key2 = solana_sdk::pubkey::new_rand();
+        let executor: Arc<dyn Executor> = Arc::new(TestExecutor {});
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![key1, key2],
+            recent_blockhash: Hash::default(),
+            instructions: vec![],
+        }
+        .try_into()
+        .unwrap();
+
+        let mut executors = Executors::default();
+        executors.insert(key1, executor.clone());
+        executors.insert(key2, executor);
+        bank.update_executors(Rc::new(RefCell::new(executors)));
+        let executors = bank.get_executors(&message, &[], &[]);
+        assert_eq!(executors.borrow().executors.len(), 2);
+
+        // Closing a program drops its cached executor, upgrading one replaces it
+        executors
+            .borrow_mut()
+            .record_program_event(key1, ProgramEvent::Closed);
+        executors
+            .borrow_mut()
+            .insert(key2, Arc::new(TestExecutor {}));
+        executors
+            .borrow_mut()
+            .record_program_event(key2, ProgramEvent::Upgraded);
+        assert_eq!(
+            executors.borrow().program_events,
+            vec![(key1, ProgramEvent::Closed), (key2, ProgramEvent::Upgraded)]
+        );
+        bank.update_executors(executors);
+        let executors = bank.get_executors(&message, &[], &[]);
+        assert!(!executors.borrow().executors.contains_key(&key1));
+        assert!(executors.borrow().executors.contains_key(&key2));
+    }
+
     #[test]
     fn test_bank_executor_cow() {
         solana_logger::setup();
@@ -14437,8 +16228,12 @@ pub(crate) mod tests {
             )
             .3;
         assert_eq!(log_results.len(), 3);
-        assert!(log_results[0].as_ref().unwrap()[1].contains(&"success".to_string()));
-        assert!(log_results[1].as_ref().unwrap()[2].contains(&"failed".to_string()));
+        assert!(log_results[0].as_ref().unwrap()[1]
+            .message
+            .contains(&"success".to_string()));
+        assert!(log_results[1].as_ref().unwrap()[2]
+            .message
+            .contains(&"failed".to_string()));
         assert!(log_results[2].as_ref().is_none());
 
         let stored_logs = &bank.transaction_log_collector.read().unwrap().logs;
@@ -14447,15 +16242,19 @@ pub(crate) mod tests {
             .find(|transaction_log_info| transaction_log_info.signature == success_sig)
             .unwrap();
         assert!(success_log_info.result.is_ok());
-        let success_log = success_log_info.log_messages.clone().pop().unwrap();
-        assert!(success_log.contains(&"success".to_string()));
+        let success_log = success_log_info.log_entries.last().unwrap();
+        assert!(success_log.message.contains(&"success".to_string()));
+        for entry in &success_log_info.log_entries {
+            assert_eq!(entry.program_id, Some(system_program::id()));
+            assert_eq!(entry.invoke_depth, 1);
+        }
         let failure_log_info = stored_logs
             .iter()
             .find(|transaction_log_info| transaction_log_info.signature == failure_sig)
             .unwrap();
         assert!(failure_log_info.result.is_err());
-        let failure_log = failure_log_info.log_messages.clone().pop().unwrap();
-        assert!(failure_log.contains(&"failed".to_string()));
+        let failure_log = failure_log_info.log_entries.last().unwrap();
+        assert!(failure_log.message.contains(&"failed".to_string()));
     }
 
     #[test]
@@ -15013,10 +16812,32 @@ pub(crate) mod tests {
         // Default: no fee.
         let message =
             SanitizedMessage::try_from(Message::new(&[], Some(&Pubkey::new_unique()))).unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 0), 0);
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                0,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                false,
+                true
+            ),
+            0
+        );
 
         // One signature, a fee.
-        assert_eq!(Bank::calculate_fee(&message, 1), 1);
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                1,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                false,
+                true
+            ),
+            1
+        );
 
         // Two signatures, double the fee.
         let key0 = Pubkey::new_unique();
@@ -15024,7 +16845,18 @@ pub(crate) mod tests {
         let ix0 = system_instruction::transfer(&key0, &key1, 1);
         let ix1 = system_instruction::transfer(&key1, &key0, 1);
         let message = SanitizedMessage::try_from(Message::new(&[ix0, ix1], Some(&key0))).unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 2), 4);
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                2,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                false,
+                true
+            ),
+            4
+        );
     }
 
     #[test]
@@ -15053,7 +16885,18 @@ pub(crate) mod tests {
             Some(&key0),
         ))
         .unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 1), 2);
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                1,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                false,
+                true
+            ),
+            2
+        );
 
         secp_instruction1.data = vec![0];
         secp_instruction2.data = vec![10];
@@ -15062,6 +16905,303 @@ pub(crate) mod tests {
             Some(&key0),
         ))
         .unwrap();
-        assert_eq!(Bank::calculate_fee(&message, 1), 11);
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                1,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                false,
+                true
+            ),
+            11
+        );
+    }
+
+    #[test]
+    fn test_calculate_fee_compute_unit_price() {
+        let key0 = Pubkey::new_unique();
+        let key1 = Pubkey::new_unique();
+        let message = SanitizedMessage::try_from(Message::new(
+            &[
+                ComputeBudgetInstruction::request_units(100_000),
+                ComputeBudgetInstruction::set_compute_unit_price(25_000),
+                system_instruction::transfer(&key0, &key1, 1),
+            ],
+            Some(&key0),
+        ))
+        .unwrap();
+
+        // Compute unit price is ignored until supported
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                1,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                false,
+                true
+            ),
+            1
+        );
+
+        // 25_000 micro-lamports per unit for 100_000 units
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                1,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                false,
+                true,
+                true
+            ),
+            2_501
+        );
+    }
+
+    #[test]
+    fn test_bank_compute_unit_fee_structure() {
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_leader(1_000_000_000, &Pubkey::new_unique(), 42);
+        genesis_config.fee_rate_governor = FeeRateGovernor::new(10_000, 0);
+        let mut bank = Bank::new_for_tests(&genesis_config);
+        bank.deactivate_feature(&compute_unit_fee_structure::id());
+
+        let mint_pubkey = mint_keypair.pubkey();
+        let key = Pubkey::new_unique();
+        let message = new_sanitized_message(
+            &[system_instruction::transfer(&mint_pubkey, &key, 1)],
+            Some(&mint_pubkey),
+        );
+        // Only the signature is charged before the feature is active
+        assert_eq!(bank.get_fee_for_message(&message), 10_000);
+
+        // Once it is, each write lock costs a tenth of the signature fee
+        bank.activate_feature(&compute_unit_fee_structure::id());
+        assert_eq!(bank.get_fee_for_message(&message), 10_000 + 2 * 1_000);
+        let balance = bank.get_balance(&mint_pubkey);
+        bank.process_transaction(&system_transaction::transfer(
+            &mint_keypair,
+            &key,
+            1,
+            bank.last_blockhash(),
+        ))
+        .unwrap();
+        assert_eq!(
+            bank.get_balance(&mint_pubkey),
+            balance - 1 - 10_000 - 2 * 1_000
+        );
+
+        // Both accounts become contended after enough write locks in this block,
+        // and each of their write locks costs as much as a signature on top
+        for lamports in 2..=DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS {
+            bank.process_transaction(&system_transaction::transfer(
+                &mint_keypair,
+                &key,
+                lamports,
+                bank.last_blockhash(),
+            ))
+            .unwrap();
+        }
+        let fee = 10_000 + 2 * 1_000 + 2 * 10_000;
+        assert_eq!(bank.get_fee_for_message(&message), fee);
+        let balance = bank.get_balance(&mint_pubkey);
+        bank.process_transaction(&system_transaction::transfer(
+            &mint_keypair,
+            &key,
+            DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS + 1,
+            bank.last_blockhash(),
+        ))
+        .unwrap();
+        assert_eq!(
+            bank.get_balance(&mint_pubkey),
+            balance - (DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS + 1) - fee
+        );
+
+        // Write locks are counted per block
+        let bank = Bank::new_from_parent(&Arc::new(bank), &Pubkey::default(), 1);
+        assert_eq!(bank.get_fee_for_message(&message), 10_000 + 2 * 1_000);
+    }
+
+    #[test]
+    fn test_calculate_fee_with_fee_structure() {
+        let key0 = Pubkey::new_unique();
+        let key1 = Pubkey::new_unique();
+        let fee_structure = FeeStructure {
+            lamports_per_signature: 5_000,
+            lamports_per_write_lock: 1_000,
+            lamports_per_contended_write_lock: 3_000,
+            contended_account_write_locks: 2,
+            compute_fee_bins: vec![
+                FeeBin {
+                    limit: 200_000,
+                    fee: 2_000,
+                },
+                FeeBin {
+                    limit: 1_400_000,
+                    fee: 20_000,
+                },
+            ],
+        };
+
+        // One signature and two write locks at the default compute budget
+        let message = SanitizedMessage::try_from(Message::new(
+            &[system_instruction::transfer(&key0, &key1, 1)],
+            Some(&key0),
+        ))
+        .unwrap();
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                5_000,
+                &fee_structure,
+                &HashMap::new(),
+                true,
+                true,
+                true
+            ),
+            5_000 + 2 * 1_000 + 2_000
+        );
+
+        // Only signatures are charged without the compute unit fee structure
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                5_000,
+                &fee_structure,
+                &HashMap::new(),
+                false,
+                true,
+                true
+            ),
+            5_000
+        );
+
+        // Congestion doubles the signature and compute fees, but not the write lock fee
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                10_000,
+                &fee_structure,
+                &HashMap::new(),
+                true,
+                true,
+                true
+            ),
+            2 * 5_000 + 2 * 1_000 + 2 * 2_000
+        );
+
+        // Free signatures don't make write locks or compute free
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                0,
+                &fee_structure,
+                &HashMap::new(),
+                true,
+                true,
+                true
+            ),
+            2 * 1_000 + 2_000
+        );
+
+        // Write locks of accounts that already took enough of them in this block
+        // are contended
+        let write_lock_counts = vec![(key0, 1), (key1, 2)].into_iter().collect();
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                5_000,
+                &fee_structure,
+                &write_lock_counts,
+                true,
+                true,
+                true
+            ),
+            5_000 + 2 * 1_000 + 3_000 + 2_000
+        );
+
+        // Requesting more compute units moves the message into a costlier bin,
+        // and the prioritization fee is not scaled by congestion
+        let message = SanitizedMessage::try_from(Message::new(
+            &[
+                ComputeBudgetInstruction::request_units(400_000),
+                ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+                system_instruction::transfer(&key0, &key1, 1),
+            ],
+            Some(&key0),
+        ))
+        .unwrap();
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                10_000,
+                &fee_structure,
+                &HashMap::new(),
+                true,
+                true,
+                true
+            ),
+            10_000 + 2 * 1_000 + 2 * 20_000 + 400_000
+        );
+
+        // The default fee structure only charges signatures
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                10_000,
+                &FeeStructure::default(),
+                &HashMap::new(),
+                true,
+                false,
+                true
+            ),
+            10_000
+        );
+
+        // A write locked program is only charged when program write locks aren't demoted
+        let program_id = Pubkey::new_unique();
+        let message = SanitizedMessage::try_from(Message::new(
+            &[Instruction::new_with_bincode(
+                program_id,
+                &(),
+                vec![
+                    AccountMeta::new(key1, false),
+                    AccountMeta::new(program_id, false),
+                ],
+            )],
+            Some(&key0),
+        ))
+        .unwrap();
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                5_000,
+                &fee_structure,
+                &HashMap::new(),
+                true,
+                true,
+                true
+            ),
+            5_000 + 2 * 1_000 + 2_000
+        );
+        assert_eq!(
+            Bank::calculate_fee(
+                &message,
+                5_000,
+                &fee_structure,
+                &HashMap::new(),
+                true,
+                true,
+                false
+            ),
+            5_000 + 3 * 1_000 + 2_000
+        );
     }
 }
@@ -45,9 +45,20 @@ pub fn find_and_send_votes(
                 .0
                 .is_ok());
             let tx = &sanitized_txs[old_account.transaction_index];
-            if let Some(parsed_vote) = vote_transaction::parse_sanitized_vote_transaction(tx) {
-                if parsed_vote.1.slots.last().is_some() {
-                    let _ = vote_sender.send(parsed_vote);
+            if let Some((vote_pubkey, vote, switch_proof_hash)) =
+                vote_transaction::parse_sanitized_vote_transaction(tx)
+            {
+                if vote.slots.last().is_some() {
+                    if let Some(vote_transaction) =
+                        tx.to_versioned_transaction().into_legacy_transaction()
+                    {
+                        let _ = vote_sender.send((
+                            vote_pubkey,
+                            vote,
+                            switch_proof_hash,
+                            vote_transaction,
+                        ));
+                    }
                 }
             }
         }
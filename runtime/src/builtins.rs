@@ -161,6 +161,26 @@ fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
             feature_set::tx_wide_compute_cap::id(),
             ActivationType::NewProgram,
         ),
+        (
+            Builtin::new(
+                "address_lookup_table_program",
+                solana_address_lookup_table_program::id(),
+                with_program_logging!(
+                    solana_address_lookup_table_program::processor::process_instruction
+                ),
+            ),
+            feature_set::versioned_tx_message_enabled::id(),
+            ActivationType::NewProgram,
+        ),
+        (
+            Builtin::new(
+                "zk_token_proof_program",
+                solana_zk_token_sdk::zk_token_proof_program::id(),
+                with_program_logging!(solana_zk_token_proof_program::process_instruction),
+            ),
+            feature_set::zk_token_sdk_enabled::id(),
+            ActivationType::NewProgram,
+        ),
         // TODO when feature `prevent_calling_precompiles_as_programs` is
         // cleaned up also remove "secp256k1_program" from the main builtins
         // list
@@ -6,7 +6,9 @@
 //!
 use crate::{block_cost_limits::*, execute_cost_table::ExecuteCostTable};
 use log::*;
-use solana_sdk::{pubkey::Pubkey, transaction::SanitizedTransaction};
+use solana_sdk::{
+    compute_budget::ComputeBudget, pubkey::Pubkey, transaction::SanitizedTransaction,
+};
 use std::collections::HashMap;
 
 const MAX_WRITABLE_ACCOUNTS: usize = 256;
@@ -17,6 +19,7 @@ pub struct TransactionCost {
     pub writable_accounts: Vec<Pubkey>,
     pub signature_cost: u64,
     pub write_lock_cost: u64,
+    pub read_lock_cost: u64,
     pub data_bytes_cost: u64,
     pub execution_cost: u64,
 }
@@ -33,27 +36,41 @@ impl TransactionCost {
         self.writable_accounts.clear();
         self.signature_cost = 0;
         self.write_lock_cost = 0;
+        self.read_lock_cost = 0;
         self.data_bytes_cost = 0;
         self.execution_cost = 0;
     }
 
     pub fn sum(&self) -> u64 {
-        self.signature_cost + self.write_lock_cost + self.data_bytes_cost + self.execution_cost
+        self.signature_cost
+            + self.write_lock_cost
+            + self.read_lock_cost
+            + self.data_bytes_cost
+            + self.execution_cost
     }
 }
 
 #[derive(Debug, Default)]
 pub struct CostModel {
     instruction_execution_cost_table: ExecuteCostTable,
+    // Compute units charged per read lock, none by default
+    read_lock_units: u64,
 }
 
 impl CostModel {
     pub fn new() -> Self {
         Self {
             instruction_execution_cost_table: ExecuteCostTable::default(),
+            read_lock_units: 0,
         }
     }
 
+    /// Charge `read_lock_units` per read lock a transaction takes, so transactions reading a
+    /// huge number of accounts also count against the block limits
+    pub fn set_read_lock_units(&mut self, read_lock_units: u64) {
+        self.read_lock_units = read_lock_units;
+    }
+
     pub fn initialize_cost_table(&mut self, cost_table: &[(Pubkey, u64)]) {
         cost_table
             .iter()
@@ -88,13 +105,14 @@ impl CostModel {
         &self,
         transaction: &SanitizedTransaction,
         demote_program_write_locks: bool,
+        tx_wide_compute_cap: bool,
     ) -> TransactionCost {
         let mut tx_cost = TransactionCost::new_with_capacity(MAX_WRITABLE_ACCOUNTS);
 
         tx_cost.signature_cost = self.get_signature_cost(transaction);
-        self.get_write_lock_cost(&mut tx_cost, transaction, demote_program_write_locks);
+        self.get_account_lock_cost(&mut tx_cost, transaction, demote_program_write_locks);
         tx_cost.data_bytes_cost = self.get_data_bytes_cost(transaction);
-        tx_cost.execution_cost = self.get_transaction_cost(transaction);
+        tx_cost.execution_cost = self.get_transaction_cost(transaction, tx_wide_compute_cap);
 
         debug!("transaction {:?} has cost {:?}", transaction, tx_cost);
         tx_cost
@@ -121,21 +139,19 @@ impl CostModel {
         transaction.signatures().len() as u64 * SIGNATURE_COST
     }
 
-    fn get_write_lock_cost(
+    // Charges for the same account locks `Accounts::lock_accounts` takes for the transaction
+    fn get_account_lock_cost(
         &self,
         tx_cost: &mut TransactionCost,
         transaction: &SanitizedTransaction,
         demote_program_write_locks: bool,
     ) {
-        let message = transaction.message();
-        message.account_keys_iter().enumerate().for_each(|(i, k)| {
-            let is_writable = message.is_writable(i, demote_program_write_locks);
-
-            if is_writable {
-                tx_cost.writable_accounts.push(*k);
-                tx_cost.write_lock_cost += WRITE_LOCK_UNITS;
-            }
-        });
+        let account_locks = transaction.get_account_locks(demote_program_write_locks);
+        tx_cost
+            .writable_accounts
+            .extend(account_locks.writable.iter().copied());
+        tx_cost.write_lock_cost = account_locks.writable.len() as u64 * WRITE_LOCK_UNITS;
+        tx_cost.read_lock_cost = account_locks.readonly.len() as u64 * self.read_lock_units;
     }
 
     fn get_data_bytes_cost(&self, transaction: &SanitizedTransaction) -> u64 {
@@ -149,7 +165,11 @@ impl CostModel {
         data_bytes_cost
     }
 
-    fn get_transaction_cost(&self, transaction: &SanitizedTransaction) -> u64 {
+    fn get_transaction_cost(
+        &self,
+        transaction: &SanitizedTransaction,
+        tx_wide_compute_cap: bool,
+    ) -> u64 {
         let mut cost: u64 = 0;
 
         for (program_id, instruction) in transaction.message().program_instructions_iter() {
@@ -161,6 +181,18 @@ impl CostModel {
             );
             cost = cost.saturating_add(instruction_cost);
         }
+
+        // With a transaction-wide compute cap, all instructions draw from a
+        // single budget, so execution can never cost more than that budget
+        if tx_wide_compute_cap {
+            let mut compute_budget = ComputeBudget::new();
+            if compute_budget
+                .process_message(transaction.message(), true, true)
+                .is_ok()
+            {
+                cost = cost.min(compute_budget.max_units);
+            }
+        }
         cost
     }
 
@@ -188,13 +220,17 @@ mod tests {
     };
     use solana_sdk::{
         bpf_loader,
+        compute_budget::{self, ComputeBudgetInstruction},
         hash::Hash,
         instruction::CompiledInstruction,
-        message::Message,
-        signature::{Keypair, Signer},
+        message::{
+            v0::{self, MessageAddressTableLookup},
+            LoadedAddresses, Message, MessageHeader, VersionedMessage,
+        },
+        signature::{Keypair, Signature, Signer},
         system_instruction::{self},
         system_program, system_transaction,
-        transaction::Transaction,
+        transaction::{Transaction, VersionedTransaction},
     };
     use std::{
         convert::{TryFrom, TryInto},
@@ -261,7 +297,7 @@ mod tests {
             .unwrap();
         assert_eq!(
             expected_cost,
-            testee.get_transaction_cost(&simple_transaction)
+            testee.get_transaction_cost(&simple_transaction, false)
         );
     }
 
@@ -287,7 +323,38 @@ mod tests {
         testee
             .upsert_instruction_cost(&system_program::id(), program_cost)
             .unwrap();
-        assert_eq!(expected_cost, testee.get_transaction_cost(&tx));
+        assert_eq!(expected_cost, testee.get_transaction_cost(&tx, false));
+    }
+
+    #[test]
+    fn test_cost_model_tx_wide_compute_cap() {
+        let (mint_keypair, start_hash) = test_setup();
+
+        let key1 = solana_sdk::pubkey::new_rand();
+        let key2 = solana_sdk::pubkey::new_rand();
+        let mut instructions = vec![ComputeBudgetInstruction::request_units(10)];
+        instructions.extend(system_instruction::transfer_many(
+            &mint_keypair.pubkey(),
+            &[(key1, 1), (key2, 1)],
+        ));
+        let message = Message::new(&instructions, Some(&mint_keypair.pubkey()));
+        let tx: SanitizedTransaction = Transaction::new(&[&mint_keypair], message, start_hash)
+            .try_into()
+            .unwrap();
+
+        let program_cost = 8;
+        let mut testee = CostModel::default();
+        testee
+            .upsert_instruction_cost(&system_program::id(), program_cost)
+            .unwrap();
+        testee
+            .upsert_instruction_cost(&compute_budget::id(), program_cost)
+            .unwrap();
+
+        // without a transaction-wide cap every instruction is costed separately
+        assert_eq!(program_cost * 3, testee.get_transaction_cost(&tx, false));
+        // with it, the estimate is bounded by the requested transaction budget
+        assert_eq!(10, testee.get_transaction_cost(&tx, true));
     }
 
     #[test]
@@ -315,7 +382,7 @@ mod tests {
         debug!("many random transaction {:?}", tx);
 
         let testee = CostModel::default();
-        let result = testee.get_transaction_cost(&tx);
+        let result = testee.get_transaction_cost(&tx, false);
 
         // expected cost for two random/unknown program is
         let expected_cost = testee.instruction_execution_cost_table.get_mode() * 2;
@@ -346,7 +413,9 @@ mod tests {
         .unwrap();
 
         let cost_model = CostModel::default();
-        let tx_cost = cost_model.calculate_cost(&tx, /*demote_program_write_locks=*/ true);
+        let tx_cost = cost_model.calculate_cost(
+            &tx, /*demote_program_write_locks=*/ true, /*tx_wide_compute_cap=*/ false,
+        );
         assert_eq!(2 + 2, tx_cost.writable_accounts.len());
         assert_eq!(signer1.pubkey(), tx_cost.writable_accounts[0]);
         assert_eq!(signer2.pubkey(), tx_cost.writable_accounts[1]);
@@ -388,12 +457,82 @@ mod tests {
         cost_model
             .upsert_instruction_cost(&system_program::id(), expected_execution_cost)
             .unwrap();
-        let tx_cost = cost_model.calculate_cost(&tx, /*demote_program_write_locks=*/ true);
+        let tx_cost = cost_model.calculate_cost(
+            &tx, /*demote_program_write_locks=*/ true, /*tx_wide_compute_cap=*/ false,
+        );
         assert_eq!(expected_account_cost, tx_cost.write_lock_cost);
         assert_eq!(expected_execution_cost, tx_cost.execution_cost);
         assert_eq!(2, tx_cost.writable_accounts.len());
     }
 
+    #[test]
+    fn test_cost_model_calculate_cost_with_loaded_addresses() {
+        let payer = Pubkey::new_unique();
+        let loaded_writable = Pubkey::new_unique();
+        let loaded_readonly = Pubkey::new_unique();
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![payer, system_program::id()],
+                recent_blockhash: Hash::default(),
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2, 3])],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![1],
+                }],
+            }),
+        };
+        let tx = SanitizedTransaction::try_create(tx, Hash::default(), |_| {
+            Ok(LoadedAddresses {
+                writable: vec![loaded_writable],
+                readonly: vec![loaded_readonly],
+            })
+        })
+        .unwrap();
+
+        let cost_model = CostModel::default();
+        let tx_cost = cost_model.calculate_cost(
+            &tx, /*demote_program_write_locks=*/ true, /*tx_wide_compute_cap=*/ false,
+        );
+        assert_eq!(WRITE_LOCK_UNITS * 2, tx_cost.write_lock_cost);
+        assert_eq!(vec![payer, loaded_writable], tx_cost.writable_accounts);
+        assert_eq!(0, tx_cost.read_lock_cost);
+    }
+
+    #[test]
+    fn test_cost_model_calculate_cost_with_read_locks() {
+        let (mint_keypair, start_hash) = test_setup();
+        // read locks the system program
+        let tx: SanitizedTransaction =
+            system_transaction::transfer(&mint_keypair, &Keypair::new().pubkey(), 2, start_hash)
+                .try_into()
+                .unwrap();
+        let num_read_locks = 1;
+
+        let mut cost_model = CostModel::default();
+        let tx_cost = cost_model.calculate_cost(
+            &tx, /*demote_program_write_locks=*/ true, /*tx_wide_compute_cap=*/ false,
+        );
+        assert_eq!(0, tx_cost.read_lock_cost);
+        let sum_without_read_locks = tx_cost.sum();
+
+        cost_model.set_read_lock_units(READ_LOCK_UNITS);
+        let tx_cost = cost_model.calculate_cost(
+            &tx, /*demote_program_write_locks=*/ true, /*tx_wide_compute_cap=*/ false,
+        );
+        assert_eq!(READ_LOCK_UNITS * num_read_locks, tx_cost.read_lock_cost);
+        assert_eq!(
+            sum_without_read_locks + READ_LOCK_UNITS * num_read_locks,
+            tx_cost.sum()
+        );
+    }
+
     #[test]
     fn test_cost_model_update_instruction_cost() {
         let key1 = Pubkey::new_unique();
@@ -457,8 +596,10 @@ mod tests {
                 } else {
                     thread::spawn(move || {
                         let cost_model = cost_model.write().unwrap();
-                        let tx_cost = cost_model
-                            .calculate_cost(&tx, /*demote_program_write_locks=*/ true);
+                        let tx_cost = cost_model.calculate_cost(
+                            &tx, /*demote_program_write_locks=*/ true,
+                            /*tx_wide_compute_cap=*/ false,
+                        );
                         assert_eq!(3, tx_cost.writable_accounts.len());
                         assert_eq!(expected_account_cost, tx_cost.write_lock_cost);
                     })
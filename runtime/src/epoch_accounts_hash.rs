@@ -0,0 +1,218 @@
+//! The epoch accounts hash is a hash of all accounts, calculated once per epoch at a
+//! deterministic slot and mixed into the bank hash of a later slot.  A node whose accounts state
+//! has diverged from the rest of the cluster will then fail to agree on that bank hash, instead
+//! of silently carrying the divergence forward.
+//!
+//! Hashing every account takes far longer than a slot, so the calculation is requested when the
+//! calculation bank is rooted and runs in the accounts background service.  Only the bank at the
+//! integration slot, half an epoch later, has to wait for the result.
+use {
+    crate::ancestors::Ancestors,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        epoch_schedule::EpochSchedule,
+        hash::Hash,
+    },
+    std::sync::{Condvar, Mutex},
+};
+
+#[derive(AbiExample, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochAccountsHash {
+    /// The slot of the bank the hash was calculated in
+    pub slot: Slot,
+    pub hash: Hash,
+}
+
+/// Calculating the epoch accounts hash a quarter of the way into the epoch keeps it well clear
+/// of the rewards and stakes calculations at the epoch boundary
+pub fn calculation_slot(epoch_schedule: &EpochSchedule, epoch: Epoch) -> Slot {
+    epoch_schedule.get_first_slot_in_epoch(epoch) + epoch_schedule.get_slots_in_epoch(epoch) / 4
+}
+
+/// The epoch accounts hash is mixed into the bank hash three quarters of the way into the epoch,
+/// which leaves the background calculation half an epoch to finish
+pub fn integration_slot(epoch_schedule: &EpochSchedule, epoch: Epoch) -> Slot {
+    epoch_schedule.get_first_slot_in_epoch(epoch) + epoch_schedule.get_slots_in_epoch(epoch) / 4 * 3
+}
+
+/// Returns true if the bank at `slot`, whose parent is at `parent_slot`, is the first bank of
+/// its fork at or past the calculation slot of its epoch
+pub fn is_calculation_bank(epoch_schedule: &EpochSchedule, parent_slot: Slot, slot: Slot) -> bool {
+    let calculation_slot = calculation_slot(epoch_schedule, epoch_schedule.get_epoch(slot));
+    parent_slot < calculation_slot && calculation_slot <= slot
+}
+
+/// Returns true if the bank at `slot`, whose parent is at `parent_slot`, is the first bank of
+/// its fork at or past the integration slot of its epoch
+pub fn is_integration_bank(epoch_schedule: &EpochSchedule, parent_slot: Slot, slot: Slot) -> bool {
+    let integration_slot = integration_slot(epoch_schedule, epoch_schedule.get_epoch(slot));
+    parent_slot < integration_slot && integration_slot <= slot
+}
+
+#[derive(Debug)]
+enum State {
+    Idle,
+    /// The calculation bank at this slot was rooted, but the calculation hasn't started
+    Requested(Slot, Ancestors),
+    InFlight(Slot),
+    Valid(EpochAccountsHash),
+}
+
+/// Hands the epoch accounts hash calculation requested when rooting the calculation bank over to
+/// whichever thread gets to it first, and lets the integration bank wait for the result
+#[derive(Debug)]
+pub struct EpochAccountsHashManager {
+    state: Mutex<State>,
+    calculated: Condvar,
+}
+
+impl Default for EpochAccountsHashManager {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(State::Idle),
+            calculated: Condvar::new(),
+        }
+    }
+}
+
+impl EpochAccountsHashManager {
+    /// Request the calculation for the calculation bank at `slot`, which was just rooted
+    pub fn request(&self, slot: Slot, ancestors: Ancestors) {
+        *self.state.lock().unwrap() = State::Requested(slot, ancestors);
+    }
+
+    /// Take the pending request, if any.  The caller must calculate the hash and pass it to
+    /// `set_valid()`, as anyone waiting for it blocks until then.
+    pub fn take_request(&self) -> Option<(Slot, Ancestors)> {
+        let mut state = self.state.lock().unwrap();
+        match &*state {
+            State::Requested(slot, _) => {
+                let slot = *slot;
+                match std::mem::replace(&mut *state, State::InFlight(slot)) {
+                    State::Requested(slot, ancestors) => Some((slot, ancestors)),
+                    _ => unreachable!(),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Like `take_request()`, but only takes the request for the calculation bank at `slot`
+    pub fn take_request_at(&self, slot: Slot) -> Option<Ancestors> {
+        let mut state = self.state.lock().unwrap();
+        match &*state {
+            State::Requested(requested_slot, _) if *requested_slot == slot => {
+                match std::mem::replace(&mut *state, State::InFlight(slot)) {
+                    State::Requested(_, ancestors) => Some(ancestors),
+                    _ => unreachable!(),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_valid(&self, epoch_accounts_hash: EpochAccountsHash) {
+        *self.state.lock().unwrap() = State::Valid(epoch_accounts_hash);
+        self.calculated.notify_all();
+    }
+
+    /// Returns the slot of the calculation bank most recently rooted, if any
+    pub fn slot(&self) -> Option<Slot> {
+        match &*self.state.lock().unwrap() {
+            State::Idle => None,
+            State::Requested(slot, _) | State::InFlight(slot) => Some(*slot),
+            State::Valid(epoch_accounts_hash) => Some(epoch_accounts_hash.slot),
+        }
+    }
+
+    /// Returns the epoch accounts hash of the calculation bank at `slot`, waiting for the
+    /// calculation if it is in flight.  Returns None if no calculation was requested for `slot`,
+    /// or if it was requested but nobody has taken the request yet.
+    pub fn wait_get(&self, slot: Slot) -> Option<EpochAccountsHash> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match &*state {
+                State::Valid(epoch_accounts_hash) if epoch_accounts_hash.slot == slot => {
+                    return Some(*epoch_accounts_hash);
+                }
+                State::InFlight(in_flight_slot) if *in_flight_slot == slot => {
+                    state = self.calculated.wait(state).unwrap();
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Arc};
+
+    #[test]
+    fn test_is_calculation_bank() {
+        let epoch_schedule = EpochSchedule::custom(32, 32, false);
+        assert_eq!(calculation_slot(&epoch_schedule, 0), 8);
+        assert_eq!(calculation_slot(&epoch_schedule, 1), 40);
+
+        assert!(!is_calculation_bank(&epoch_schedule, 0, 0));
+        assert!(!is_calculation_bank(&epoch_schedule, 6, 7));
+        assert!(is_calculation_bank(&epoch_schedule, 7, 8));
+        assert!(!is_calculation_bank(&epoch_schedule, 8, 9));
+
+        // the calculation slot was skipped
+        assert!(is_calculation_bank(&epoch_schedule, 5, 12));
+        // the parent is in the previous epoch
+        assert!(is_calculation_bank(&epoch_schedule, 30, 45));
+        assert!(!is_calculation_bank(&epoch_schedule, 30, 39));
+    }
+
+    #[test]
+    fn test_is_integration_bank() {
+        let epoch_schedule = EpochSchedule::custom(32, 32, false);
+        assert_eq!(integration_slot(&epoch_schedule, 0), 24);
+        assert_eq!(integration_slot(&epoch_schedule, 1), 56);
+
+        assert!(!is_integration_bank(&epoch_schedule, 8, 23));
+        assert!(is_integration_bank(&epoch_schedule, 23, 24));
+        assert!(!is_integration_bank(&epoch_schedule, 24, 25));
+
+        // the integration slot was skipped
+        assert!(is_integration_bank(&epoch_schedule, 20, 27));
+        // the calculation and integration slots were both skipped
+        assert!(is_integration_bank(&epoch_schedule, 5, 30));
+        assert!(is_calculation_bank(&epoch_schedule, 5, 30));
+    }
+
+    #[test]
+    fn test_epoch_accounts_hash_manager() {
+        let manager = Arc::new(EpochAccountsHashManager::default());
+        assert_eq!(manager.slot(), None);
+        assert_eq!(manager.take_request(), None);
+        assert_eq!(manager.wait_get(8), None);
+
+        let ancestors = Ancestors::from(vec![8]);
+        manager.request(8, ancestors.clone());
+        assert_eq!(manager.slot(), Some(8));
+        // nobody is calculating it, so there is nothing to wait for
+        assert_eq!(manager.wait_get(8), None);
+        assert_eq!(manager.take_request_at(9), None);
+        assert_eq!(manager.take_request(), Some((8, ancestors)));
+        assert_eq!(manager.take_request(), None);
+        assert_eq!(manager.take_request_at(8), None);
+
+        let epoch_accounts_hash = EpochAccountsHash {
+            slot: 8,
+            hash: Hash::new_unique(),
+        };
+        // the calculation is in flight, so waiting blocks until it is set
+        let waiter = {
+            let manager = manager.clone();
+            std::thread::spawn(move || manager.wait_get(8))
+        };
+        manager.set_valid(epoch_accounts_hash);
+        assert_eq!(waiter.join().unwrap(), Some(epoch_accounts_hash));
+        assert_eq!(manager.wait_get(8), Some(epoch_accounts_hash));
+        assert_eq!(manager.wait_get(40), None);
+    }
+}
@@ -3,15 +3,16 @@ use {
     bzip2::bufread::BzDecoder,
     log::*,
     rand::{thread_rng, Rng},
-    solana_sdk::genesis_config::GenesisConfig,
+    solana_sdk::{genesis_config::GenesisConfig, hash::Hash},
     std::{
         collections::HashMap,
         fs::{self, File},
-        io::{BufReader, Read},
+        io::{BufReader, Read, Write},
         path::{
             Component::{self, CurDir, Normal},
             Path, PathBuf,
         },
+        sync::atomic::{AtomicU64, Ordering},
         time::Instant,
     },
     tar::{
@@ -84,6 +85,15 @@ fn check_unpack_result(unpack_result: bool, path: String) -> Result<()> {
     Ok(())
 }
 
+/// What's been unpacked so far from an archive, which may be unpacked in several parts at once,
+/// so that the limits apply to the whole archive rather than to each part
+#[derive(Debug, Default)]
+pub struct UnpackTotals {
+    apparent_size: AtomicU64,
+    actual_size: AtomicU64,
+    count: AtomicU64,
+}
+
 pub enum UnpackPath<'a> {
     Valid(&'a Path),
     Ignore,
@@ -95,15 +105,13 @@ fn unpack_archive<'a, A: Read, C>(
     apparent_limit_size: u64,
     actual_limit_size: u64,
     limit_count: u64,
+    totals: &UnpackTotals,
     mut entry_checker: C,
+    mut storage_checksums: Option<&mut UnpackedStorageChecksums>,
 ) -> Result<()>
 where
     C: FnMut(&[&str], tar::EntryType) -> UnpackPath<'a>,
 {
-    let mut apparent_total_size: u64 = 0;
-    let mut actual_total_size: u64 = 0;
-    let mut total_count: u64 = 0;
-
     let mut total_entries = 0;
     let mut last_log_update = Instant::now();
     for entry in archive.entries()? {
@@ -135,6 +143,10 @@ where
         }
 
         let parts: Vec<_> = parts.map(|p| p.unwrap()).collect();
+        let storage_file_name = match parts.as_slice() {
+            ["accounts", file] if storage_checksums.is_some() => Some(file.to_string()),
+            _ => None,
+        };
         let unpack_dir = match entry_checker(parts.as_slice(), kind) {
             UnpackPath::Invalid => {
                 return Err(UnpackError::Archive(format!(
@@ -149,17 +161,21 @@ where
             UnpackPath::Valid(unpack_dir) => unpack_dir,
         };
 
-        apparent_total_size = checked_total_size_sum(
-            apparent_total_size,
-            entry.header().size()?,
+        let apparent_size = entry.header().size()?;
+        checked_total_size_sum(
+            totals
+                .apparent_size
+                .fetch_add(apparent_size, Ordering::Relaxed),
+            apparent_size,
             apparent_limit_size,
         )?;
-        actual_total_size = checked_total_size_sum(
-            actual_total_size,
-            entry.header().entry_size()?,
+        let actual_size = entry.header().entry_size()?;
+        checked_total_size_sum(
+            totals.actual_size.fetch_add(actual_size, Ordering::Relaxed),
+            actual_size,
             actual_limit_size,
         )?;
-        total_count = checked_total_count_increment(total_count, limit_count)?;
+        checked_total_count_increment(totals.count.fetch_add(1, Ordering::Relaxed), limit_count)?;
 
         let target = sanitize_path(&entry.path()?, unpack_dir)?; // ? handles file system errors
         if target.is_none() {
@@ -167,8 +183,16 @@ where
         }
         let target = target.unwrap();
 
-        let unpack = entry.unpack(target);
-        check_unpack_result(unpack.map(|_unpack| true)?, path_str)?;
+        match (storage_file_name, storage_checksums.as_mut()) {
+            (Some(file_name), Some(storage_checksums)) => {
+                let checksum = unpack_checksummed(&mut entry, &target)?;
+                storage_checksums.insert(file_name, checksum);
+            }
+            _ => {
+                let unpack = entry.unpack(target);
+                check_unpack_result(unpack.map(|_unpack| true)?, path_str)?;
+            }
+        }
 
         // Sanitize permissions.
         let mode = match entry.header().entry_type() {
@@ -204,6 +228,22 @@ where
     }
 }
 
+// Writes the contents of an account storage entry to `target`, hashing them on the way
+fn unpack_checksummed(entry: &mut impl Read, target: &Path) -> std::io::Result<Hash> {
+    let mut file = File::create(target)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let len = entry.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+        file.write_all(&buffer[..len])?;
+    }
+    Ok(Hash::new_from_array(*hasher.finalize().as_bytes()))
+}
+
 // return Err on file system error
 // return Some(path) if path is good
 // return None if we should skip this file
@@ -281,6 +321,9 @@ fn validate_inside_dst(dst: &Path, file_dst: &Path) -> Result<PathBuf> {
 /// Map from AppendVec file name to unpacked file system location
 pub type UnpackedAppendVecMap = HashMap<String, PathBuf>;
 
+/// Map from AppendVec file name to the checksum of its contents, hashed as it was unpacked
+pub type UnpackedStorageChecksums = HashMap<String, Hash>;
+
 // select/choose only 'index' out of each # of 'divisions' of total items.
 pub struct ParallelSelector {
     pub index: usize,
@@ -298,6 +341,29 @@ pub fn unpack_snapshot<A: Read>(
     ledger_dir: &Path,
     account_paths: &[PathBuf],
     parallel_selector: Option<ParallelSelector>,
+) -> Result<UnpackedAppendVecMap> {
+    unpack_snapshot_part(
+        archive,
+        ledger_dir,
+        account_paths,
+        parallel_selector,
+        &UnpackTotals::default(),
+        None,
+        None,
+    )
+}
+
+/// Unpack one of the parts of a snapshot archive unpacked in parallel, counting it towards the
+/// `totals` of the whole archive.  The path of each entry of the part is added to `entries`, and
+/// the checksum of each account storage file unpacked to `storage_checksums`
+pub fn unpack_snapshot_part<A: Read>(
+    archive: &mut Archive<A>,
+    ledger_dir: &Path,
+    account_paths: &[PathBuf],
+    parallel_selector: Option<ParallelSelector>,
+    totals: &UnpackTotals,
+    mut entries: Option<&mut Vec<String>>,
+    storage_checksums: Option<&mut UnpackedStorageChecksums>,
 ) -> Result<UnpackedAppendVecMap> {
     assert!(!account_paths.is_empty());
     let mut unpacked_append_vec_map = UnpackedAppendVecMap::new();
@@ -308,7 +374,11 @@ pub fn unpack_snapshot<A: Read>(
         MAX_SNAPSHOT_ARCHIVE_UNPACKED_APPARENT_SIZE,
         MAX_SNAPSHOT_ARCHIVE_UNPACKED_ACTUAL_SIZE,
         MAX_SNAPSHOT_ARCHIVE_UNPACKED_COUNT,
+        totals,
         |parts, kind| {
+            if let Some(entries) = entries.as_mut() {
+                entries.push(parts.join("/"));
+            }
             if is_valid_snapshot_archive_entry(parts, kind) {
                 i += 1;
                 match &parallel_selector {
@@ -337,6 +407,7 @@ pub fn unpack_snapshot<A: Read>(
                 UnpackPath::Invalid
             }
         },
+        storage_checksums,
     )
     .map(|_| unpacked_append_vec_map)
 }
@@ -383,6 +454,7 @@ fn is_valid_snapshot_archive_entry(parts: &[&str], kind: tar::EntryType) -> bool
         (["snapshots"], Directory) => true,
         (["snapshots", "status_cache"], GNUSparse) => true,
         (["snapshots", "status_cache"], Regular) => true,
+        (["snapshots", "accounts_checksums"], Regular) => true,
         (["snapshots", dir, file], GNUSparse) if all_digits(dir) && all_digits(file) => true,
         (["snapshots", dir, file], Regular) if all_digits(dir) && all_digits(file) => true,
         (["snapshots", dir], Directory) if all_digits(dir) => true,
@@ -449,6 +521,7 @@ fn unpack_genesis<A: Read>(
         max_genesis_archive_unpacked_size,
         max_genesis_archive_unpacked_size,
         MAX_GENESIS_ARCHIVE_UNPACKED_COUNT,
+        &UnpackTotals::default(),
         |p, k| {
             if is_valid_genesis_archive_entry(p, k) {
                 UnpackPath::Valid(unpack_dir)
@@ -456,6 +529,7 @@ fn unpack_genesis<A: Read>(
                 UnpackPath::Invalid
             }
         },
+        None,
     )
 }
 
@@ -496,6 +570,10 @@ mod tests {
             &["snapshots", "3", "3"],
             tar::EntryType::Regular
         ));
+        assert!(is_valid_snapshot_archive_entry(
+            &["snapshots", "accounts_checksums"],
+            tar::EntryType::Regular
+        ));
         assert!(is_valid_snapshot_archive_entry(
             &["version"],
             tar::EntryType::Regular
@@ -886,4 +964,69 @@ mod tests {
                 if message == "too many files in snapshot: 1000000000000"
         );
     }
+
+    #[test]
+    fn test_archive_unpack_totals_shared_across_parts() {
+        let mut header = Header::new_gnu();
+        header.set_path("version").unwrap();
+        header.set_size(4);
+        header.set_cksum();
+        let data: &[u8] = &[1, 2, 3, 4];
+        let mut archive = Builder::new(Vec::new());
+        archive.append(&header, data).unwrap();
+        let data = archive.into_inner().unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let totals = UnpackTotals::default();
+        let unpack_part = || {
+            unpack_archive(
+                &mut Archive::new(&data[..]),
+                6,
+                6,
+                2,
+                &totals,
+                |_, _| UnpackPath::Valid(temp_dir.path()),
+                None,
+            )
+        };
+        assert_matches!(unpack_part(), Ok(()));
+        // the second part takes the archive past its limits
+        assert_matches!(
+            unpack_part(),
+            Err(UnpackError::Archive(ref message))
+                if message == "too large archive: 8 than limit: 6"
+        );
+    }
+
+    #[test]
+    fn test_archive_unpack_snapshot_storage_checksums() {
+        let data = b"storage";
+        let mut header = Header::new_gnu();
+        header.set_path("accounts/123.456").unwrap();
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        let mut archive = Builder::new(Vec::new());
+        archive.append(&header, &data[..]).unwrap();
+        let data_archive = archive.into_inner().unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut storage_checksums = UnpackedStorageChecksums::new();
+        let unpacked_append_vec_map = unpack_snapshot_part(
+            &mut Archive::new(&data_archive[..]),
+            temp_dir.path(),
+            &[temp_dir.path().to_path_buf()],
+            None,
+            &UnpackTotals::default(),
+            None,
+            Some(&mut storage_checksums),
+        )
+        .unwrap();
+
+        let path = &unpacked_append_vec_map["123.456"];
+        assert_eq!(fs::read(path).unwrap(), data);
+        assert_eq!(
+            storage_checksums["123.456"],
+            Hash::new_from_array(*blake3::hash(data).as_bytes())
+        );
+    }
 }
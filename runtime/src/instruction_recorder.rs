@@ -5,10 +5,11 @@ use solana_sdk::{
     message::SanitizedMessage,
 };
 
-/// Records and compiles cross-program invoked instructions
+/// Records and compiles cross-program invoked instructions, along with the compute units charged
+/// for invoking each of them
 #[derive(Clone, Default)]
 pub struct InstructionRecorder {
-    inner: Rc<RefCell<Vec<Instruction>>>,
+    inner: Rc<RefCell<Vec<(Instruction, u64)>>>,
 }
 
 impl InstructionRecorder {
@@ -19,11 +20,27 @@ impl InstructionRecorder {
         self.inner
             .borrow()
             .iter()
-            .map(|ix| message.try_compile_instruction(ix))
+            .map(|(ix, _)| message.try_compile_instruction(ix))
+            .collect()
+    }
+
+    /// The compute units charged for invoking each recorded instruction, in recording order
+    pub fn invoke_units(&self) -> Vec<u64> {
+        self.inner
+            .borrow()
+            .iter()
+            .map(|(_, units)| *units)
             .collect()
     }
 
     pub fn record_instruction(&self, instruction: Instruction) {
-        self.inner.borrow_mut().push(instruction);
+        self.inner.borrow_mut().push((instruction, 0));
+    }
+
+    /// Records the compute units charged for invoking the last recorded instruction
+    pub fn record_invoke_units(&self, units: u64) {
+        if let Some((_, invoke_units)) = self.inner.borrow_mut().last_mut() {
+            *invoke_units = units;
+        }
     }
 }
@@ -0,0 +1,349 @@
+//! A bounded index of the rooted accounts holding the most lamports, so that largest accounts
+//! queries don't have to scan every account.  The index holds every rooted account with at
+//! least `threshold` lamports and is updated with the writes of each slot as it is rooted.  Once
+//! it grows to twice its capacity the smallest accounts are dropped and the threshold rises.  A
+//! bank descending from the latest root is answered from the index with the writes of its
+//! unrooted slots applied on top.  A query it can't answer, because too few indexed accounts
+//! remain above the threshold, is answered by a scan of the bank instead, which also rebuilds
+//! the index when the bank is the latest root.
+
+use {
+    crate::accounts::AccountAddressFilter,
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{BTreeSet, HashMap, HashSet},
+        sync::Mutex,
+    },
+};
+
+pub const DEFAULT_LARGEST_ACCOUNTS_INDEX_CAPACITY: usize = 1_000;
+
+#[derive(Debug, Default)]
+struct IndexState {
+    by_lamports: BTreeSet<(u64, Pubkey)>,
+    lamports: HashMap<Pubkey, u64>,
+    /// Every rooted account with at least this many lamports is indexed, `None` until the index
+    /// is built
+    threshold: Option<u64>,
+    /// The latest root whose writes were recorded
+    root: Option<Slot>,
+    /// Writes of the slots rooted while the index is being rebuilt, applied once it is
+    pending: Option<Vec<(Pubkey, u64)>>,
+}
+
+impl IndexState {
+    fn apply(&mut self, pubkey: Pubkey, lamports: u64, threshold: u64) {
+        if let Some(old_lamports) = self.lamports.remove(&pubkey) {
+            self.by_lamports.remove(&(old_lamports, pubkey));
+        }
+        if lamports >= threshold {
+            self.lamports.insert(pubkey, lamports);
+            self.by_lamports.insert((lamports, pubkey));
+        }
+    }
+
+    fn apply_all(&mut self, accounts: impl IntoIterator<Item = (Pubkey, u64)>, capacity: usize) {
+        let mut threshold = match self.threshold {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        for (pubkey, lamports) in accounts {
+            self.apply(pubkey, lamports, threshold);
+            if self.by_lamports.len() > capacity.saturating_mul(2) {
+                threshold = self.trim(capacity);
+            }
+        }
+    }
+
+    // Drops the smallest accounts down to `capacity`, along with any others holding as many
+    // lamports as the last dropped one, so that every account above the new threshold remains
+    fn trim(&mut self, capacity: usize) -> u64 {
+        let mut threshold = self.threshold.unwrap_or(1);
+        while let Some(&(lamports, pubkey)) = self.by_lamports.iter().next() {
+            if self.by_lamports.len() <= capacity && lamports >= threshold {
+                break;
+            }
+            self.by_lamports.remove(&(lamports, pubkey));
+            self.lamports.remove(&pubkey);
+            threshold = threshold.max(lamports.saturating_add(1));
+        }
+        self.threshold = Some(threshold);
+        threshold
+    }
+}
+
+#[derive(Debug)]
+pub struct LargestAccountsIndex {
+    capacity: usize,
+    state: Mutex<IndexState>,
+}
+
+impl LargestAccountsIndex {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The latest root whose writes were recorded, the index holds the accounts as of this slot
+    pub fn root(&self) -> Option<Slot> {
+        self.state.lock().unwrap().root
+    }
+
+    /// Record the lamports of the accounts written in the newly rooted `slot`
+    pub fn update(&self, slot: Slot, accounts: impl IntoIterator<Item = (Pubkey, u64)>) {
+        let mut state = self.state.lock().unwrap();
+        state.root = Some(slot);
+        if let Some(pending) = state.pending.as_mut() {
+            pending.extend(accounts);
+            return;
+        }
+        state.apply_all(accounts, self.capacity);
+    }
+
+    /// Start rebuilding the index from a scan of the root `slot`, if no other rebuild is in
+    /// progress and `is_latest_root` confirms the slot is still the latest root.  Roots added
+    /// until `finish_rebuild()` are applied on top of the scan.
+    pub fn start_rebuild(&self, slot: Slot, is_latest_root: impl FnOnce() -> bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.is_some() || !is_latest_root() {
+            return false;
+        }
+        state.root = Some(slot);
+        state.pending = Some(vec![]);
+        true
+    }
+
+    /// Replace the indexed accounts with the `capacity` largest ones the scan started by
+    /// `start_rebuild()` found, or keep the current ones if the scan failed
+    pub fn finish_rebuild(&self, largest: Option<Vec<(Pubkey, u64)>>) {
+        let mut state = self.state.lock().unwrap();
+        let pending = state.pending.take().unwrap_or_default();
+        if let Some(largest) = largest {
+            let is_complete = largest.len() < self.capacity;
+            state.by_lamports.clear();
+            state.lamports.clear();
+            state.threshold = Some(1);
+            for (pubkey, lamports) in largest {
+                state.apply(pubkey, lamports, 1);
+            }
+            if !is_complete {
+                // Other accounts holding as many lamports as the smallest returned one may be
+                // missing
+                let smallest = state
+                    .by_lamports
+                    .iter()
+                    .next()
+                    .map(|(lamports, _)| *lamports);
+                state.threshold = smallest.map(|lamports| lamports.saturating_add(1));
+                state.trim(self.capacity);
+            }
+        }
+        state.apply_all(pending, self.capacity);
+    }
+
+    /// The `num` largest accounts passing `filter` as of the indexed `root` with the lamports of
+    /// the accounts written since applied on top, largest first.  `None` if the index has moved
+    /// past `root`, or if accounts that aren't indexed may be among them.
+    pub fn largest(
+        &self,
+        root: Slot,
+        unrooted_writes: &HashMap<Pubkey, u64>,
+        num: usize,
+        filter_by_address: &HashSet<Pubkey>,
+        filter: &AccountAddressFilter,
+    ) -> Option<Vec<(Pubkey, u64)>> {
+        let state = self.state.lock().unwrap();
+        if state.pending.is_some() || state.root != Some(root) {
+            return None;
+        }
+        let threshold = state.threshold?;
+        let is_included = |pubkey: &Pubkey| {
+            let contains_address = filter_by_address.contains(pubkey);
+            match filter {
+                AccountAddressFilter::Exclude => !contains_address,
+                AccountAddressFilter::Include => contains_address,
+            }
+        };
+        let mut largest: Vec<_> = state
+            .by_lamports
+            .iter()
+            .rev()
+            .filter(|(_, pubkey)| !unrooted_writes.contains_key(pubkey) && is_included(pubkey))
+            .take(num)
+            .copied()
+            .collect();
+        // Accounts written since the root are above the threshold if they are among the largest
+        largest.extend(
+            unrooted_writes
+                .iter()
+                .filter(|(pubkey, lamports)| **lamports >= threshold.max(1) && is_included(pubkey))
+                .map(|(pubkey, lamports)| (*lamports, *pubkey)),
+        );
+        largest.sort_unstable_by(|a, b| b.cmp(a));
+        largest.truncate(num);
+        // With a threshold of one lamport every account is indexed
+        (largest.len() == num || threshold <= 1).then(|| {
+            largest
+                .into_iter()
+                .map(|(lamports, pubkey)| (pubkey, lamports))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(capacity: usize, accounts: &[(Pubkey, u64)]) -> LargestAccountsIndex {
+        let index = LargestAccountsIndex::new(capacity);
+        let mut largest = accounts.to_vec();
+        largest.sort_by_key(|(pubkey, lamports)| std::cmp::Reverse((*lamports, *pubkey)));
+        largest.truncate(capacity);
+        assert!(index.start_rebuild(0, || true));
+        index.finish_rebuild(Some(largest));
+        index
+    }
+
+    #[test]
+    fn test_largest_accounts_index() {
+        let pubkeys: Vec<_> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let accounts: Vec<_> = pubkeys.iter().zip(1..).map(|(p, l)| (*p, l * 10)).collect();
+        let index = build(3, &accounts);
+        assert_eq!(index.state.lock().unwrap().threshold, Some(41));
+        let no_filter = HashSet::new();
+        let no_writes = HashMap::new();
+        assert_eq!(
+            index.largest(0, &no_writes, 2, &no_filter, &AccountAddressFilter::Exclude),
+            Some(vec![(pubkeys[5], 60), (pubkeys[4], 50)])
+        );
+
+        // Excluding indexed accounts leaves too few to answer
+        let filter: HashSet<_> = vec![pubkeys[5]].into_iter().collect();
+        assert_eq!(
+            index.largest(0, &no_writes, 3, &filter, &AccountAddressFilter::Exclude),
+            None
+        );
+        assert_eq!(
+            index.largest(0, &no_writes, 3, &filter, &AccountAddressFilter::Include),
+            None
+        );
+
+        // Accounts rising above the threshold are indexed, falling below it are removed
+        index.update(1, vec![(pubkeys[0], 100), (pubkeys[5], 5)]);
+        assert_eq!(
+            index.largest(1, &no_writes, 2, &no_filter, &AccountAddressFilter::Exclude),
+            Some(vec![(pubkeys[0], 100), (pubkeys[4], 50)])
+        );
+        assert_eq!(
+            index.largest(1, &no_writes, 3, &no_filter, &AccountAddressFilter::Exclude),
+            None
+        );
+        // The index no longer holds the accounts as of the earlier root
+        assert_eq!(
+            index.largest(0, &no_writes, 2, &no_filter, &AccountAddressFilter::Exclude),
+            None
+        );
+    }
+
+    #[test]
+    fn test_largest_accounts_index_unrooted_writes() {
+        let pubkeys: Vec<_> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let accounts: Vec<_> = pubkeys.iter().zip(1..).map(|(p, l)| (*p, l * 10)).collect();
+        let index = build(3, &accounts);
+        let no_filter = HashSet::new();
+
+        // Unrooted writes replace the indexed lamports, and count if above the threshold
+        let writes: HashMap<_, _> = vec![(pubkeys[5], 5), (pubkeys[0], 70)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            index.largest(0, &writes, 2, &no_filter, &AccountAddressFilter::Exclude),
+            Some(vec![(pubkeys[0], 70), (pubkeys[4], 50)])
+        );
+        // The account that fell below the threshold leaves too few indexed accounts
+        assert_eq!(
+            index.largest(0, &writes, 3, &no_filter, &AccountAddressFilter::Exclude),
+            None
+        );
+    }
+
+    #[test]
+    fn test_largest_accounts_index_trim() {
+        let index = build(2, &[]);
+        // Fewer accounts than the capacity, every account is indexed
+        assert_eq!(
+            index.largest(
+                0,
+                &HashMap::new(),
+                5,
+                &HashSet::new(),
+                &AccountAddressFilter::Exclude
+            ),
+            Some(vec![])
+        );
+
+        let pubkeys: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        index.update(1, pubkeys.iter().zip(1..).map(|(p, l)| (*p, l)));
+        let state = index.state.lock().unwrap();
+        assert_eq!(state.threshold, Some(4));
+        assert_eq!(state.by_lamports.len(), 2);
+        assert_eq!(state.lamports.len(), 2);
+    }
+
+    #[test]
+    fn test_largest_accounts_index_pending() {
+        let index = LargestAccountsIndex::new(2);
+        let pubkeys: Vec<_> = (0..2).map(|_| Pubkey::new_unique()).collect();
+        // Updates before the index is built are ignored
+        index.update(0, vec![(pubkeys[0], 1)]);
+        // Only the latest root rebuilds the index
+        assert!(!index.start_rebuild(0, || false));
+        assert!(index.start_rebuild(0, || true));
+        assert!(!index.start_rebuild(0, || true));
+        // A root added during the rebuild
+        index.update(1, vec![(pubkeys[1], 7)]);
+        assert_eq!(
+            index.largest(
+                1,
+                &HashMap::new(),
+                2,
+                &HashSet::new(),
+                &AccountAddressFilter::Exclude
+            ),
+            None
+        );
+        index.finish_rebuild(Some(vec![(pubkeys[0], 5)]));
+        assert_eq!(
+            index.largest(
+                1,
+                &HashMap::new(),
+                2,
+                &HashSet::new(),
+                &AccountAddressFilter::Exclude
+            ),
+            Some(vec![(pubkeys[1], 7), (pubkeys[0], 5)])
+        );
+
+        // A failed scan keeps the indexed accounts, with the roots added meanwhile applied
+        assert!(index.start_rebuild(1, || true));
+        index.update(2, vec![(pubkeys[0], 9)]);
+        index.finish_rebuild(None);
+        assert_eq!(
+            index.largest(
+                2,
+                &HashMap::new(),
+                2,
+                &HashSet::new(),
+                &AccountAddressFilter::Exclude
+            ),
+            Some(vec![(pubkeys[0], 9), (pubkeys[1], 7)])
+        );
+    }
+}
@@ -1,5 +1,6 @@
 #![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(min_specialization))]
 #![allow(clippy::integer_arithmetic)]
+mod account_rent_state;
 pub mod accounts;
 pub mod accounts_background_service;
 pub mod accounts_cache;
@@ -26,6 +27,7 @@ pub mod commitment;
 pub mod contains;
 pub mod cost_model;
 pub mod cost_tracker;
+pub mod epoch_accounts_hash;
 pub mod epoch_stakes;
 pub mod execute_cost_table;
 pub mod genesis_utils;
@@ -33,15 +35,18 @@ pub mod hardened_unpack;
 pub mod in_mem_accounts_index;
 pub mod inline_spl_token_v2_0;
 pub mod instruction_recorder;
+pub mod largest_accounts_index;
 pub mod loader_utils;
 pub mod log_collector;
 pub mod message_processor;
 pub mod neon_evm_program;
 pub mod non_circulating_supply;
+pub mod partitioned_rewards;
 mod pubkey_bins;
 mod read_only_accounts_cache;
 pub mod rent_collector;
 pub mod secondary_index;
+pub mod seekable_snapshot_archive;
 pub mod serde_snapshot;
 mod shared_buffer_reader;
 pub mod snapshot_archive_info;
@@ -1,12 +1,45 @@
-use std::cell::RefCell;
+use {
+    serde::{Deserialize, Serialize},
+    solana_sdk::pubkey::Pubkey,
+    std::cell::RefCell,
+};
 
 const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
 
+const LOG_TRUNCATED_MESSAGE: &str = "Log truncated";
+
+/// A single log message along with the program invocation that emitted it
+#[derive(Serialize, Deserialize, AbiExample, Clone, Debug, PartialEq)]
+pub struct TransactionLogEntry {
+    /// The program executing when the message was logged, `None` outside of any invocation
+    pub program_id: Option<Pubkey>,
+    /// The invoke depth at which the message was logged, 1 for top-level instructions
+    pub invoke_depth: usize,
+    pub message: String,
+}
+
+/// The plain messages of `entries`, in order
+pub fn log_messages(entries: &[TransactionLogEntry]) -> Vec<String> {
+    entries.iter().map(|entry| entry.message.clone()).collect()
+}
+
 #[derive(Default)]
 struct LogCollectorInner {
-    messages: Vec<String>,
+    entries: Vec<TransactionLogEntry>,
     bytes_written: usize,
     limit_warning: bool,
+    program_id: Option<Pubkey>,
+    invoke_depth: usize,
+}
+
+impl LogCollectorInner {
+    fn push_entry(&mut self, message: String) {
+        self.entries.push(TransactionLogEntry {
+            program_id: self.program_id,
+            invoke_depth: self.invoke_depth,
+            message,
+        });
+    }
 }
 
 #[derive(Default)]
@@ -21,18 +54,37 @@ impl LogCollector {
         if inner.bytes_written + message.len() >= LOG_MESSAGES_BYTES_LIMIT {
             if !inner.limit_warning {
                 inner.limit_warning = true;
-                inner.messages.push(String::from("Log truncated"));
+                inner.push_entry(String::from(LOG_TRUNCATED_MESSAGE));
             }
         } else {
             inner.bytes_written += message.len();
-            inner.messages.push(message.to_string());
+            inner.push_entry(message.to_string());
         }
     }
+
+    /// Attribute subsequent messages to `program_id` executing at `invoke_depth`
+    pub fn set_invoke_frame(&self, program_id: Option<Pubkey>, invoke_depth: usize) {
+        let mut inner = self.inner.borrow_mut();
+        inner.program_id = program_id;
+        inner.invoke_depth = invoke_depth;
+    }
 }
 
 impl From<LogCollector> for Vec<String> {
     fn from(log_collector: LogCollector) -> Self {
-        log_collector.inner.into_inner().messages
+        log_collector
+            .inner
+            .into_inner()
+            .entries
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect()
+    }
+}
+
+impl From<LogCollector> for Vec<TransactionLogEntry> {
+    fn from(log_collector: LogCollector) -> Self {
+        log_collector.inner.into_inner().entries
     }
 }
 
@@ -47,12 +99,47 @@ pub(crate) mod tests {
         for _i in 0..LOG_MESSAGES_BYTES_LIMIT * 2 {
             lc.log("x");
         }
-
-        let logs: Vec<_> = lc.into();
+        let logs: Vec<String> = lc.into();
         assert_eq!(logs.len(), LOG_MESSAGES_BYTES_LIMIT);
         for log in logs.iter().take(LOG_MESSAGES_BYTES_LIMIT - 1) {
             assert_eq!(*log, "x".to_string());
         }
         assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
     }
+
+    #[test]
+    fn test_log_entries() {
+        let program_id = Pubkey::new_unique();
+        let lc = LogCollector::default();
+        lc.log("outer");
+        lc.set_invoke_frame(Some(program_id), 1);
+        lc.log("inner");
+        lc.log(&"x".repeat(LOG_MESSAGES_BYTES_LIMIT));
+
+        let entries: Vec<TransactionLogEntry> = lc.into();
+        assert_eq!(
+            entries,
+            vec![
+                TransactionLogEntry {
+                    program_id: None,
+                    invoke_depth: 0,
+                    message: "outer".to_string(),
+                },
+                TransactionLogEntry {
+                    program_id: Some(program_id),
+                    invoke_depth: 1,
+                    message: "inner".to_string(),
+                },
+                TransactionLogEntry {
+                    program_id: Some(program_id),
+                    invoke_depth: 1,
+                    message: "Log truncated".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            log_messages(&entries),
+            vec!["outer", "inner", "Log truncated"]
+        );
+    }
 }
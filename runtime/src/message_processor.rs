@@ -1,6 +1,7 @@
 use crate::{
-    accounts::Accounts, ancestors::Ancestors, instruction_recorder::InstructionRecorder,
-    log_collector::LogCollector, rent_collector::RentCollector,
+    accounts::Accounts, ancestors::Ancestors, bank::TransactionReturnData,
+    instruction_recorder::InstructionRecorder, log_collector::LogCollector,
+    rent_collector::RentCollector,
 };
 use log::*;
 use serde::{Deserialize, Serialize};
@@ -10,19 +11,22 @@ use solana_sdk::{
     account::{AccountSharedData, ReadableAccount, WritableAccount},
     compute_budget::ComputeBudget,
     feature_set::{
-        demote_program_write_locks, do_support_realloc, neon_evm_compute_budget,
-        prevent_calling_precompiles_as_programs, remove_native_loader, requestable_heap_size,
-        tx_wide_compute_cap, FeatureSet,
+        add_get_processed_sibling_instruction_syscall, demote_program_write_locks,
+        do_support_realloc, neon_evm_compute_budget, prevent_calling_precompiles_as_programs,
+        remove_native_loader, requestable_heap_size, tx_wide_compute_cap, FeatureSet,
     },
     hash::Hash,
     ic_logger_msg,
-    instruction::{AccountMeta, CompiledInstruction, Instruction, InstructionError},
+    instruction::{
+        AccountMeta, CompiledInstruction, Instruction, InstructionError,
+        TRANSACTION_LEVEL_STACK_HEIGHT,
+    },
     keyed_account::{create_keyed_accounts_unified, KeyedAccount},
     message::Message,
     precompiles::is_precompile,
     process_instruction::{
         ComputeMeter, Executor, InvokeContext, InvokeContextStackFrame, Logger,
-        ProcessInstructionWithContext,
+        ProcessInstructionWithContext, ProgramEvent,
     },
     pubkey::Pubkey,
     rent::Rent,
@@ -81,6 +85,7 @@ pub struct ThisInvokeContext<'a> {
     accounts: &'a [(Pubkey, Rc<RefCell<AccountSharedData>>)],
     programs: &'a [(Pubkey, ProcessInstructionWithContext)],
     logger: Rc<RefCell<dyn Logger>>,
+    log_collector: Option<Rc<LogCollector>>,
     compute_budget: ComputeBudget,
     compute_meter: Rc<RefCell<dyn ComputeMeter>>,
     executors: Rc<RefCell<Executors>>,
@@ -94,6 +99,7 @@ pub struct ThisInvokeContext<'a> {
     blockhash: Hash,
     lamports_per_signature: u64,
     return_data: (Pubkey, Vec<u8>),
+    instruction_trace: Vec<Vec<(usize, Instruction)>>,
 }
 impl<'a> ThisInvokeContext<'a> {
     #[allow(clippy::too_many_arguments)]
@@ -119,7 +125,8 @@ impl<'a> ThisInvokeContext<'a> {
             pre_accounts: Vec::new(),
             accounts,
             programs,
-            logger: ThisLogger::new_ref(log_collector),
+            logger: ThisLogger::new_ref(log_collector.clone()),
+            log_collector,
             compute_budget,
             compute_meter,
             executors,
@@ -132,6 +139,7 @@ impl<'a> ThisInvokeContext<'a> {
             blockhash,
             lamports_per_signature,
             return_data: (Pubkey::default(), Vec::new()),
+            instruction_trace: Vec::new(),
         }
     }
 
@@ -163,6 +171,30 @@ impl<'a> ThisInvokeContext<'a> {
     ) -> Self {
         Self::new_mock_with_features(accounts, programs, Arc::new(FeatureSet::all_enabled()))
     }
+
+    /// Attribute subsequent log messages to the program at the top of the invoke stack
+    fn update_log_invoke_frame(&self) {
+        if let Some(log_collector) = &self.log_collector {
+            log_collector.set_invoke_frame(
+                self.invoke_stack
+                    .last()
+                    .and_then(|frame| frame.program_id())
+                    .cloned(),
+                self.invoke_stack.len(),
+            );
+        }
+    }
+
+    /// Record an instruction processed at `stack_height`, transaction level
+    /// instructions start a new list of processed inner instructions
+    fn record_processed_instruction(&mut self, stack_height: usize, instruction: Instruction) {
+        if stack_height == TRANSACTION_LEVEL_STACK_HEIGHT {
+            self.instruction_trace
+                .push(vec![(stack_height, instruction)]);
+        } else if let Some(inner_instructions) = self.instruction_trace.last_mut() {
+            inner_instructions.push((stack_height, instruction));
+        }
+    }
 }
 impl<'a> InvokeContext for ThisInvokeContext<'a> {
     fn push(
@@ -241,14 +273,43 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             }))
             .collect::<Vec<_>>();
 
+        // The instruction's accounts are resolved like its keyed accounts, since the indices of a
+        // cross-program invocation are not into the message's account keys
+        let processed_instruction = program_indices
+            .last()
+            .filter(|_| {
+                self.feature_set
+                    .is_active(&add_get_processed_sibling_instruction_syscall::id())
+            })
+            .map(|index_of_program_id| {
+                Instruction::new_with_bytes(
+                    self.accounts[*index_of_program_id].0,
+                    &instruction.data,
+                    keyed_accounts
+                        .iter()
+                        .skip(program_indices.len())
+                        .map(|(is_signer, is_writable, pubkey, _)| AccountMeta {
+                            pubkey: **pubkey,
+                            is_signer: *is_signer,
+                            is_writable: *is_writable,
+                        })
+                        .collect(),
+                )
+            });
+
         self.invoke_stack.push(InvokeContextStackFrame::new(
             program_indices.len(),
             create_keyed_accounts_unified(keyed_accounts.as_slice()),
         ));
+        if let Some(processed_instruction) = processed_instruction {
+            self.record_processed_instruction(self.invoke_stack.len(), processed_instruction);
+        }
+        self.update_log_invoke_frame();
         Ok(())
     }
     fn pop(&mut self) {
         self.invoke_stack.pop();
+        self.update_log_invoke_frame();
     }
     fn invoke_depth(&self) -> usize {
         self.invoke_stack.len()
@@ -429,6 +490,11 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
             instruction_recorders[self.instruction_index].record_instruction(instruction.clone());
         }
     }
+    fn record_invoke_units(&self, units: u64) {
+        if let Some(instruction_recorders) = &self.instruction_recorders {
+            instruction_recorders[self.instruction_index].record_invoke_units(units);
+        }
+    }
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool {
         self.feature_set.is_active(feature_id)
     }
@@ -500,6 +566,40 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
     fn get_return_data(&self) -> (Pubkey, &[u8]) {
         (self.return_data.0, &self.return_data.1)
     }
+    fn get_stack_height(&self) -> usize {
+        self.invoke_stack.len()
+    }
+    fn get_processed_sibling_instruction(&self, index: usize) -> Option<&Instruction> {
+        let stack_height = self.get_stack_height();
+        if stack_height == TRANSACTION_LEVEL_STACK_HEIGHT {
+            // The current instruction's list is the last one
+            self.instruction_trace
+                .iter()
+                .rev()
+                .skip(1)
+                .nth(index)
+                .and_then(|inner_instructions| inner_instructions.first())
+                .map(|(_, instruction)| instruction)
+        } else {
+            // Skip the current instruction and everything it invoked, then
+            // stop at the parent instruction
+            self.instruction_trace
+                .last()?
+                .iter()
+                .rev()
+                .skip_while(|(this_stack_height, _)| *this_stack_height > stack_height)
+                .skip(1)
+                .take_while(|(this_stack_height, _)| *this_stack_height >= stack_height)
+                .filter(|(this_stack_height, _)| *this_stack_height == stack_height)
+                .nth(index)
+                .map(|(_, instruction)| instruction)
+        }
+    }
+    fn notify_program_event(&self, program_id: &Pubkey, event: ProgramEvent) {
+        self.executors
+            .borrow_mut()
+            .record_program_event(*program_id, event);
+    }
 }
 
 pub struct MockInvokeContextPreparation {
@@ -603,6 +703,7 @@ impl MessageProcessor {
     /// For each instruction it calls the program entrypoint method and verifies that the result of
     /// the call does not violate the bank's accounting rules.
     /// The accounts are committed back to the bank only if every instruction succeeds.
+    /// The compute units each instruction consumed are appended to `instruction_compute_units`.
     #[allow(clippy::too_many_arguments)]
     #[allow(clippy::type_complexity)]
     pub fn process_message(
@@ -622,6 +723,8 @@ impl MessageProcessor {
         ancestors: &Ancestors,
         blockhash: Hash,
         lamports_per_signature: u64,
+        return_data: &mut Option<TransactionReturnData>,
+        instruction_compute_units: &mut Vec<u64>,
     ) -> Result<(), TransactionError> {
         let mut invoke_context = ThisInvokeContext::new(
             rent_collector.rent,
@@ -652,6 +755,34 @@ impl MessageProcessor {
                 && is_precompile(program_id, |id| invoke_context.is_feature_active(id))
             {
                 // Precompiled programs don't have an instruction processor
+                if invoke_context
+                    .is_feature_active(&add_get_processed_sibling_instruction_syscall::id())
+                {
+                    let demote_program_write_locks =
+                        invoke_context.is_feature_active(&demote_program_write_locks::id());
+                    let processed_instruction = Instruction::new_with_bytes(
+                        *program_id,
+                        &instruction.data,
+                        instruction
+                            .accounts
+                            .iter()
+                            .map(|index| {
+                                let index = *index as usize;
+                                AccountMeta {
+                                    pubkey: message.account_keys[index],
+                                    is_signer: message.is_signer(index),
+                                    is_writable: message
+                                        .is_writable(index, demote_program_write_locks),
+                                }
+                            })
+                            .collect(),
+                    );
+                    invoke_context.record_processed_instruction(
+                        TRANSACTION_LEVEL_STACK_HEIGHT,
+                        processed_instruction,
+                    );
+                }
+                instruction_compute_units.push(0);
                 continue;
             }
 
@@ -702,16 +833,31 @@ impl MessageProcessor {
 
             time.stop();
             let post_remaining_units = compute_meter.borrow().get_remaining();
+            let units = pre_remaining_units - post_remaining_units;
             timings.accumulate_program(
                 instruction.program_id(&message.account_keys),
                 time.as_us(),
-                pre_remaining_units - post_remaining_units,
+                units,
             );
+            instruction_compute_units.push(units);
 
-            result?;
+            if let Err(err) = result {
+                *return_data = Self::collect_return_data(&invoke_context);
+                return Err(err);
+            }
         }
+        *return_data = Self::collect_return_data(&invoke_context);
         Ok(())
     }
+
+    /// Return data left by the last program which set it, or `None` if it is empty
+    fn collect_return_data(invoke_context: &ThisInvokeContext) -> Option<TransactionReturnData> {
+        let (program_id, data) = invoke_context.get_return_data();
+        (!data.is_empty()).then(|| TransactionReturnData {
+            program_id,
+            data: data.to_vec(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -885,6 +1031,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_invoke_context_processed_sibling_instructions() {
+        let program_id = solana_sdk::pubkey::new_rand();
+        let account_key = solana_sdk::pubkey::new_rand();
+        let accounts = vec![
+            (
+                account_key,
+                Rc::new(RefCell::new(AccountSharedData::default())),
+            ),
+            (
+                program_id,
+                Rc::new(RefCell::new(AccountSharedData::default())),
+            ),
+        ];
+        let instructions = (0..6u8)
+            .map(|data| {
+                Instruction::new_with_bytes(
+                    program_id,
+                    &[data],
+                    vec![AccountMeta::new(account_key, false)],
+                )
+            })
+            .collect::<Vec<_>>();
+        let messages = instructions
+            .iter()
+            .map(|instruction| Message::new(&[instruction.clone()], None))
+            .collect::<Vec<_>>();
+        let mut invoke_context = ThisInvokeContext::new_mock(&accounts, &[]);
+        let push = |invoke_context: &mut ThisInvokeContext, index: usize| {
+            invoke_context
+                .push(
+                    &messages[index],
+                    &messages[index].instructions[0],
+                    &[1],
+                    None,
+                )
+                .unwrap();
+        };
+
+        // A
+        push(&mut invoke_context, 0);
+        assert_eq!(invoke_context.get_stack_height(), 1);
+        assert_eq!(invoke_context.get_processed_sibling_instruction(0), None);
+        invoke_context.pop();
+
+        // B -> C -> D
+        push(&mut invoke_context, 1);
+        assert_eq!(
+            invoke_context.get_processed_sibling_instruction(0),
+            Some(&instructions[0])
+        );
+        assert_eq!(invoke_context.get_processed_sibling_instruction(1), None);
+        push(&mut invoke_context, 2);
+        assert_eq!(invoke_context.get_stack_height(), 2);
+        assert_eq!(invoke_context.get_processed_sibling_instruction(0), None);
+        push(&mut invoke_context, 3);
+        assert_eq!(invoke_context.get_stack_height(), 3);
+        assert_eq!(invoke_context.get_processed_sibling_instruction(0), None);
+        invoke_context.pop();
+        invoke_context.pop();
+
+        // B -> E
+        push(&mut invoke_context, 4);
+        assert_eq!(
+            invoke_context.get_processed_sibling_instruction(0),
+            Some(&instructions[2])
+        );
+        assert_eq!(invoke_context.get_processed_sibling_instruction(1), None);
+        invoke_context.pop();
+
+        // B -> F
+        push(&mut invoke_context, 5);
+        assert_eq!(
+            invoke_context.get_processed_sibling_instruction(0),
+            Some(&instructions[4])
+        );
+        assert_eq!(
+            invoke_context.get_processed_sibling_instruction(1),
+            Some(&instructions[2])
+        );
+        assert_eq!(invoke_context.get_processed_sibling_instruction(2), None);
+        invoke_context.pop();
+        invoke_context.pop();
+    }
+
     #[test]
     fn test_invoke_context_verify() {
         let accounts = vec![(
@@ -1011,6 +1242,8 @@ mod tests {
             &ancestors,
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].1.borrow().lamports(), 100);
@@ -1042,6 +1275,8 @@ mod tests {
             &ancestors,
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -1077,6 +1312,8 @@ mod tests {
             &ancestors,
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -1222,6 +1459,8 @@ mod tests {
             &ancestors,
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -1257,6 +1496,8 @@ mod tests {
             &ancestors,
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(result, Ok(()));
 
@@ -1290,6 +1531,8 @@ mod tests {
             &ancestors,
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].1.borrow().lamports(), 80);
@@ -1593,6 +1836,8 @@ mod tests {
             &Ancestors::default(),
             Hash::default(),
             0,
+            &mut None,
+            &mut Vec::new(),
         );
         assert_eq!(
             result,
@@ -1602,4 +1847,149 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_process_message_return_data() {
+        let mock_program_id = Pubkey::new_unique();
+        fn mock_process_instruction(
+            _first_instruction_account: usize,
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            invoke_context.set_return_data(data.to_vec())
+        }
+        let mut instruction_processor = InstructionProcessor::default();
+        instruction_processor.add_program(&mock_program_id, mock_process_instruction);
+
+        let mock_program_account = AccountSharedData::new_ref(1, 0, &native_loader::id());
+        mock_program_account.borrow_mut().set_executable(true);
+        let accounts = vec![(mock_program_id, mock_program_account)];
+
+        let process_message = |message: &Message| {
+            let mut return_data = None;
+            let result = MessageProcessor::process_message(
+                &instruction_processor,
+                message,
+                &vec![vec![0]; message.instructions.len()],
+                &accounts,
+                &RentCollector::default(),
+                None,
+                Rc::new(RefCell::new(Executors::default())),
+                None,
+                Arc::new(FeatureSet::all_enabled()),
+                ComputeBudget::new(),
+                ThisComputeMeter::new_ref(std::i64::MAX as u64),
+                &mut ExecuteDetailsTimings::default(),
+                Arc::new(Accounts::default_for_tests()),
+                &Ancestors::default(),
+                Hash::default(),
+                0,
+                &mut return_data,
+                &mut Vec::new(),
+            );
+            assert_eq!(result, Ok(()));
+            return_data
+        };
+
+        // Return data set by the last instruction is captured
+        let message = Message::new(
+            &[Instruction::new_with_bytes(
+                mock_program_id,
+                b"hello",
+                vec![],
+            )],
+            None,
+        );
+        assert_eq!(
+            process_message(&message),
+            Some(TransactionReturnData {
+                program_id: mock_program_id,
+                data: b"hello".to_vec(),
+            })
+        );
+
+        // Empty return data is not captured
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(mock_program_id, b"hello", vec![]),
+                Instruction::new_with_bytes(mock_program_id, &[], vec![]),
+            ],
+            None,
+        );
+        assert_eq!(process_message(&message), None);
+    }
+
+    #[test]
+    fn test_process_message_tx_wide_compute_cap() {
+        let mock_program_id = Pubkey::new_unique();
+        fn mock_process_instruction(
+            _first_instruction_account: usize,
+            _data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            invoke_context.get_compute_meter().borrow_mut().consume(100)
+        }
+        let mut instruction_processor = InstructionProcessor::default();
+        instruction_processor.add_program(&mock_program_id, mock_process_instruction);
+
+        let mock_program_account = AccountSharedData::new_ref(1, 0, &native_loader::id());
+        mock_program_account.borrow_mut().set_executable(true);
+        let accounts = vec![(mock_program_id, mock_program_account)];
+
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(mock_program_id, &[], vec![]),
+                Instruction::new_with_bytes(mock_program_id, &[], vec![]),
+            ],
+            None,
+        );
+        let compute_budget = ComputeBudget {
+            max_units: 150,
+            ..ComputeBudget::default()
+        };
+
+        let process_message = |feature_set: FeatureSet| {
+            let compute_meter = ThisComputeMeter::new_ref(compute_budget.max_units);
+            let result = MessageProcessor::process_message(
+                &instruction_processor,
+                &message,
+                &[vec![0], vec![0]],
+                &accounts,
+                &RentCollector::default(),
+                None,
+                Rc::new(RefCell::new(Executors::default())),
+                None,
+                Arc::new(feature_set),
+                compute_budget,
+                compute_meter.clone(),
+                &mut ExecuteDetailsTimings::default(),
+                Arc::new(Accounts::default_for_tests()),
+                &Ancestors::default(),
+                Hash::default(),
+                0,
+                &mut None,
+                &mut Vec::new(),
+            );
+            let remaining_units = compute_meter.borrow().get_remaining();
+            (result, remaining_units)
+        };
+
+        // Each instruction gets a fresh budget without the transaction-wide cap
+        let mut feature_set = FeatureSet::all_enabled();
+        feature_set.active.remove(&tx_wide_compute_cap::id());
+        feature_set.inactive.insert(tx_wide_compute_cap::id());
+        assert_eq!(process_message(feature_set).0, Ok(()));
+
+        // With the cap, both instructions draw from a single budget
+        assert_eq!(
+            process_message(FeatureSet::all_enabled()),
+            (
+                Err(TransactionError::InstructionError(
+                    1,
+                    InstructionError::ComputationalBudgetExceeded
+                )),
+                0
+            )
+        );
+    }
 }
@@ -0,0 +1,327 @@
+//! Calculating and crediting every stake account its epoch rewards in the first block of an
+//! epoch makes that block far slower to produce and replay than any other.  Instead, only the
+//! points each stake earned are calculated at the epoch boundary, which the value of a point
+//! depends on.  The stake accounts are split into partitions, and the rewards of a partition are
+//! calculated from those points and credited to the stake and vote accounts in one of the first
+//! blocks of the epoch, a partition per block.
+//!
+//! Each partition of pending rewards is kept in an account of its own, so that they're part of
+//! the bank hash and of snapshots without any single account growing with the number of stake
+//! accounts.  The stake program stays available meanwhile: a reward is credited to its stake
+//! account as it is when the partition is credited, and a stake account closed before then
+//! forfeits its share of the reward, which is never minted.
+use {
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        clock::Epoch,
+        epoch_schedule::EpochSchedule,
+        hash::{hashv, Hash},
+        pubkey::Pubkey,
+        sysvar,
+    },
+    solana_stake_program::stake_state::{self, PointValue},
+    std::{convert::TryInto, sync::Arc},
+};
+
+/// The base of the addresses of the accounts the pending stake rewards are kept in, see
+/// `get_pending_stake_rewards_address`
+pub mod pending_stake_rewards_account {
+    solana_sdk::declare_id!("SysvarStakeRewards1111111111111111111111111");
+}
+
+/// The account a partition of pending stake rewards is kept in, owned by the sysvar program so
+/// that no transaction can modify it
+pub fn get_pending_stake_rewards_address(partition: usize) -> Pubkey {
+    Pubkey::create_with_seed(
+        &pending_stake_rewards_account::id(),
+        &partition.to_string(),
+        &sysvar::id(),
+    )
+    .unwrap()
+}
+
+/// The most blocks the stake rewards of an epoch are credited over
+pub const MAX_REWARD_DISTRIBUTION_BLOCKS: u64 = 64;
+
+/// Stake rewards are credited over at most a tenth of the slots of the epoch
+const SLOTS_PER_REWARD_DISTRIBUTION_BLOCK: u64 = 10;
+
+/// The number of blocks the stake rewards calculated at the start of `epoch` are credited over
+pub fn get_reward_distribution_num_blocks(epoch_schedule: &EpochSchedule, epoch: Epoch) -> u64 {
+    (epoch_schedule.get_slots_in_epoch(epoch) / SLOTS_PER_REWARD_DISTRIBUTION_BLOCK)
+        .clamp(1, MAX_REWARD_DISTRIBUTION_BLOCKS)
+}
+
+/// The partition, out of `num_partitions`, a stake account's rewards are credited in.  Seeding
+/// with the blockhash of the parent of the epoch's first block keeps the partitions
+/// unpredictable ahead of time but lets anyone recompute them afterwards
+pub fn get_stake_reward_partition(
+    parent_blockhash: &Hash,
+    stake_pubkey: &Pubkey,
+    num_partitions: usize,
+) -> usize {
+    let hash = hashv(&[parent_blockhash.as_ref(), stake_pubkey.as_ref()]);
+    let prefix = u64::from_le_bytes(hash.as_ref()[0..8].try_into().unwrap());
+    (prefix % num_partitions as u64) as usize
+}
+
+/// The points a stake account earned, calculated at the epoch boundary.  Its reward is
+/// calculated from them when its partition is credited
+#[derive(Serialize, Deserialize, AbiExample, Clone, Debug, PartialEq)]
+pub struct StakeReward {
+    pub stake_pubkey: Pubkey,
+    pub vote_pubkey: Pubkey,
+    /// The points the stake earned, 0 if only its vote credits observed are to be updated
+    pub points: u128,
+    /// The vote credits the points were calculated up to
+    pub credits_observed: u64,
+    /// Vote account commission when the points were calculated
+    pub commission: u8,
+}
+
+/// The contents of the account a partition of pending stake rewards is kept in
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingStakeRewardsPartition {
+    pub distribution_start_block_height: u64,
+    pub total_rewards: u64,
+    pub total_points: u128,
+    pub stake_rewards: Vec<StakeReward>,
+}
+
+#[derive(AbiExample, Clone, Debug, Default, PartialEq)]
+pub struct PendingStakeRewards {
+    /// The block height the first partition is credited at, the partition credited at a block
+    /// height is its offset from this one
+    pub distribution_start_block_height: u64,
+    /// The lamports the rewards of the epoch are split over `total_points`
+    pub total_rewards: u64,
+    /// The points all the stakes earned
+    pub total_points: u128,
+    /// The rewards of each partition, those of the partitions already credited are empty
+    pub partitions: Vec<Arc<Vec<StakeReward>>>,
+}
+
+impl PendingStakeRewards {
+    pub fn new(
+        distribution_start_block_height: u64,
+        parent_blockhash: &Hash,
+        num_partitions: u64,
+        point_value: PointValue,
+        stake_rewards: Vec<StakeReward>,
+    ) -> Self {
+        let num_partitions = num_partitions.max(1) as usize;
+        let mut partitions = vec![Vec::new(); num_partitions];
+        for stake_reward in stake_rewards {
+            let partition = get_stake_reward_partition(
+                parent_blockhash,
+                &stake_reward.stake_pubkey,
+                num_partitions,
+            );
+            partitions[partition].push(stake_reward);
+        }
+        // the rewards were calculated in parallel, sort them to credit them in the same order
+        // on every node
+        for partition in partitions.iter_mut() {
+            partition.sort_by_key(|stake_reward| stake_reward.stake_pubkey);
+        }
+        Self {
+            distribution_start_block_height,
+            total_rewards: point_value.rewards,
+            total_points: point_value.points,
+            partitions: partitions.into_iter().map(Arc::new).collect(),
+        }
+    }
+
+    pub fn point_value(&self) -> PointValue {
+        PointValue {
+            rewards: self.total_rewards,
+            points: self.total_points,
+        }
+    }
+
+    /// The lamports a stake reward is split into, as (stakers_reward, voters_reward), or None
+    /// if neither gets any and the stake's credits observed aren't updated either
+    pub fn calculate_rewards(&self, stake_reward: &StakeReward) -> Option<(u64, u64)> {
+        if stake_reward.points == 0 {
+            return Some((0, 0));
+        }
+        stake_state::calculate_rewards_from_points(
+            stake_reward.points,
+            &self.point_value(),
+            stake_reward.commission,
+        )
+    }
+
+    /// Rebuild the pending stake rewards from the partitions not yet credited, as kept in their
+    /// accounts.  Partitions are credited in order, so the ones left are the last ones
+    pub fn from_partitions(
+        partitions: impl IntoIterator<Item = (usize, PendingStakeRewardsPartition)>,
+    ) -> Option<Self> {
+        let mut pending_stake_rewards: Option<Self> = None;
+        for (index, partition) in partitions {
+            let pending_stake_rewards = pending_stake_rewards.get_or_insert_with(|| Self {
+                distribution_start_block_height: partition.distribution_start_block_height,
+                total_rewards: partition.total_rewards,
+                total_points: partition.total_points,
+                partitions: vec![],
+            });
+            if pending_stake_rewards.partitions.len() <= index {
+                pending_stake_rewards
+                    .partitions
+                    .resize_with(index + 1, Arc::default);
+            }
+            pending_stake_rewards.partitions[index] = Arc::new(partition.stake_rewards);
+        }
+        pending_stake_rewards
+    }
+
+    /// The same pending stake rewards, without those of `partition`
+    pub fn without_partition(&self, partition: usize) -> Self {
+        let mut partitions = self.partitions.clone();
+        partitions[partition] = Arc::default();
+        Self {
+            distribution_start_block_height: self.distribution_start_block_height,
+            total_rewards: self.total_rewards,
+            total_points: self.total_points,
+            partitions,
+        }
+    }
+
+    /// The sum of the stakers' and voters' rewards left to credit
+    pub fn total_lamports(&self) -> u64 {
+        self.partitions
+            .iter()
+            .flat_map(|partition| partition.iter())
+            .filter_map(|stake_reward| self.calculate_rewards(stake_reward))
+            .map(|(stakers_reward, voters_reward)| stakers_reward + voters_reward)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_reward_distribution_num_blocks() {
+        let epoch_schedule = EpochSchedule::custom(32, 32, false);
+        assert_eq!(get_reward_distribution_num_blocks(&epoch_schedule, 0), 3);
+        let epoch_schedule = EpochSchedule::custom(320, 320, false);
+        assert_eq!(get_reward_distribution_num_blocks(&epoch_schedule, 0), 32);
+        let epoch_schedule = EpochSchedule::default();
+        assert_eq!(
+            get_reward_distribution_num_blocks(&epoch_schedule, 100),
+            MAX_REWARD_DISTRIBUTION_BLOCKS
+        );
+    }
+
+    #[test]
+    fn test_pending_stake_rewards_new() {
+        let parent_blockhash = Hash::new_unique();
+        let stake_rewards: Vec<_> = (0..100)
+            .map(|points| StakeReward {
+                stake_pubkey: Pubkey::new_unique(),
+                vote_pubkey: Pubkey::new_unique(),
+                points: points * 10,
+                credits_observed: 42,
+                commission: 10,
+            })
+            .collect();
+        // a lamport per 5 points
+        let point_value = PointValue {
+            rewards: 9_900,
+            points: 49_500,
+        };
+
+        let pending = PendingStakeRewards::new(
+            5,
+            &parent_blockhash,
+            4,
+            point_value.clone(),
+            stake_rewards.clone(),
+        );
+        assert_eq!(pending.distribution_start_block_height, 5);
+        assert_eq!(pending.point_value(), point_value);
+        assert_eq!(pending.partitions.len(), 4);
+        assert_eq!(pending.calculate_rewards(&stake_rewards[0]), Some((0, 0)));
+        // too small to split at the commission
+        assert_eq!(pending.calculate_rewards(&stake_rewards[1]), None);
+        assert_eq!(pending.calculate_rewards(&stake_rewards[10]), Some((18, 2)));
+        assert_eq!(
+            pending.total_lamports(),
+            stake_rewards
+                .iter()
+                .filter_map(|stake_reward| pending.calculate_rewards(stake_reward))
+                .map(|(stakers_reward, voters_reward)| stakers_reward + voters_reward)
+                .sum::<u64>()
+        );
+        for (i, partition) in pending.partitions.iter().enumerate() {
+            assert!(partition
+                .windows(2)
+                .all(|pair| pair[0].stake_pubkey < pair[1].stake_pubkey));
+            assert!(partition.iter().all(|stake_reward| {
+                get_stake_reward_partition(&parent_blockhash, &stake_reward.stake_pubkey, 4) == i
+            }));
+        }
+
+        // the partitions don't depend on the order the rewards were calculated in
+        let mut reversed = stake_rewards;
+        reversed.reverse();
+        assert_eq!(
+            PendingStakeRewards::new(5, &parent_blockhash, 4, point_value, reversed),
+            pending
+        );
+    }
+
+    #[test]
+    fn test_pending_stake_rewards_from_partitions() {
+        let stake_rewards: Vec<_> = (0..100)
+            .map(|points| StakeReward {
+                stake_pubkey: Pubkey::new_unique(),
+                vote_pubkey: Pubkey::new_unique(),
+                points,
+                credits_observed: 42,
+                commission: 10,
+            })
+            .collect();
+        let point_value = PointValue {
+            rewards: 1_000,
+            points: 4_950,
+        };
+        let pending =
+            PendingStakeRewards::new(5, &Hash::new_unique(), 4, point_value, stake_rewards);
+        let partitions = |pending: &PendingStakeRewards, from: usize| {
+            (from..4)
+                .map(|index| {
+                    let partition = PendingStakeRewardsPartition {
+                        distribution_start_block_height: pending.distribution_start_block_height,
+                        total_rewards: pending.total_rewards,
+                        total_points: pending.total_points,
+                        stake_rewards: pending.partitions[index].to_vec(),
+                    };
+                    (index, partition)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            PendingStakeRewards::from_partitions(partitions(&pending, 0)),
+            Some(pending.clone())
+        );
+
+        // the accounts of the partitions already credited are gone
+        let remaining = pending.without_partition(0).without_partition(1);
+        assert!(remaining.partitions[..2].iter().all(|p| p.is_empty()));
+        assert_eq!(remaining.partitions[2..], pending.partitions[2..]);
+        assert_eq!(
+            PendingStakeRewards::from_partitions(partitions(&pending, 2)),
+            Some(remaining)
+        );
+        assert_eq!(PendingStakeRewards::from_partitions(vec![]), None);
+
+        assert_ne!(
+            get_pending_stake_rewards_address(0),
+            get_pending_stake_rewards_address(1)
+        );
+    }
+}
@@ -0,0 +1,452 @@
+//! Seekable zstd snapshot archives
+//!
+//! The tar stream of the snapshot is split at entry boundaries into independently compressed
+//! zstd frames, followed by a manifest listing each frame's offset, size and entries.  The
+//! manifest is stored in a zstd skippable frame at the end of the file, so that the archive is
+//! still a valid zstd stream that decompresses to the same tar stream as a `.tar.zst` archive.
+//!
+//! The manifest is found from the fixed size footer at the very end of the file:
+//!
+//! ```text
+//! | frame 0 | ... | frame n | skippable frame header | manifest | manifest size | magic |
+//! ```
+//!
+//! This lets readers check the manifest without decompressing anything, and extract the frames
+//! in parallel.  The hardened unpack limits apply to all the frames together, and each frame
+//! must hold exactly the entries the manifest lists for it.
+use {
+    crate::{
+        hardened_unpack::{
+            unpack_snapshot_part, UnpackError, UnpackTotals, UnpackedAppendVecMap,
+            UnpackedStorageChecksums,
+        },
+        snapshot_utils::SnapshotVersion,
+    },
+    bincode::Options,
+    rayon::prelude::*,
+    serde::{Deserialize, Serialize},
+    std::{
+        convert::TryInto,
+        fs::{self, File},
+        io::{self, BufReader, Read, Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
+    },
+    tar::Archive,
+};
+
+/// Frames are ended at the first entry boundary past this much uncompressed data
+pub const SEEKABLE_ARCHIVE_FRAME_SIZE: u64 = 64 * 1024 * 1024;
+
+const SEEKABLE_ARCHIVE_MAGIC: u32 = 0x8F92_EAB1;
+const ZSTD_SKIPPABLE_FRAME_MAGIC: u32 = 0x184D_2A5E;
+const FOOTER_SIZE: u64 = 8;
+const MAX_MANIFEST_SIZE: u64 = 128 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SeekableArchiveManifest {
+    pub snapshot_version: String,
+    pub frames: Vec<SeekableArchiveFrame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SeekableArchiveFrame {
+    /// Offset of the frame from the start of the archive
+    pub compressed_offset: u64,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    /// Paths of the tar entries starting in this frame
+    pub entries: Vec<String>,
+}
+
+/// Counts the bytes written to the archive to locate the frames
+struct CountingWriter<W: Write> {
+    inner: W,
+    position: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Compresses what's written to it into the current frame.  The tar builder writing to it ends
+/// the frame, through `end_frame()`, once an entry has been completely written
+pub struct SeekableArchiveWriter<W: Write> {
+    // the encoder of the current frame, started on the first write after a frame ended
+    encoder: Option<zstd::stream::Encoder<'static, CountingWriter<W>>>,
+    writer: Option<CountingWriter<W>>,
+    frame: SeekableArchiveFrame,
+    frames: Vec<SeekableArchiveFrame>,
+}
+
+impl<W: Write> SeekableArchiveWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            encoder: None,
+            writer: Some(CountingWriter { inner, position: 0 }),
+            frame: SeekableArchiveFrame::default(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Record an entry written since the last frame ended
+    pub fn add_entry(&mut self, path: impl Into<String>) {
+        self.frame.entries.push(path.into());
+    }
+
+    /// Uncompressed bytes written to the current frame
+    pub fn frame_size(&self) -> u64 {
+        self.frame.uncompressed_size
+    }
+
+    /// Compress the data written since the last frame ended into a frame of its own
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        if let Some(encoder) = self.encoder.take() {
+            let writer = encoder.finish()?;
+            let mut frame = std::mem::take(&mut self.frame);
+            frame.compressed_size = writer.position - frame.compressed_offset;
+            self.frames.push(frame);
+            self.writer = Some(writer);
+        }
+        Ok(())
+    }
+
+    /// End the last frame and write the manifest
+    pub fn finish(mut self, snapshot_version: &str) -> io::Result<W> {
+        self.end_frame()?;
+        let mut writer = self.writer.take().unwrap();
+
+        let manifest = SeekableArchiveManifest {
+            snapshot_version: snapshot_version.to_string(),
+            frames: self.frames,
+        };
+        let manifest = bincode::serialize(&manifest)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        let manifest_size: u32 = manifest
+            .len()
+            .try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "manifest too large"))?;
+        writer.write_all(&ZSTD_SKIPPABLE_FRAME_MAGIC.to_le_bytes())?;
+        writer.write_all(&(manifest_size + FOOTER_SIZE as u32).to_le_bytes())?;
+        writer.write_all(&manifest)?;
+        writer.write_all(&manifest_size.to_le_bytes())?;
+        writer.write_all(&SEEKABLE_ARCHIVE_MAGIC.to_le_bytes())?;
+        writer.flush()?;
+        Ok(writer.inner)
+    }
+}
+
+/// Append the directory `src_dir` and everything under it as `path`, recording every entry
+pub fn append_dir_all<W: Write>(
+    builder: &mut tar::Builder<SeekableArchiveWriter<W>>,
+    path: &Path,
+    src_dir: &Path,
+) -> io::Result<()> {
+    builder.append_dir(path, src_dir)?;
+    builder.get_mut().add_entry(path.to_string_lossy());
+
+    let mut children = fs::read_dir(src_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<io::Result<Vec<_>>>()?;
+    children.sort();
+    for child in children {
+        let child_path = path.join(&child);
+        let child_src = src_dir.join(&child);
+        if child_src.is_dir() {
+            append_dir_all(builder, &child_path, &child_src)?;
+        } else {
+            builder.append_path_with_name(&child_src, &child_path)?;
+            builder.get_mut().add_entry(child_path.to_string_lossy());
+        }
+    }
+    Ok(())
+}
+
+impl<W: Write> Write for SeekableArchiveWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoder.is_none() {
+            let writer = self.writer.take().unwrap();
+            self.frame.compressed_offset = writer.position;
+            self.encoder = Some(zstd::stream::Encoder::new(writer, 0)?);
+        }
+        let written = self.encoder.as_mut().unwrap().write(buf)?;
+        self.frame.uncompressed_size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match (self.encoder.as_mut(), self.writer.as_mut()) {
+            (Some(encoder), _) => encoder.flush(),
+            (None, Some(writer)) => writer.flush(),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Read the manifest of a seekable archive without decompressing any of it
+pub fn read_manifest<R: Read + Seek>(mut reader: R) -> io::Result<SeekableArchiveManifest> {
+    let archive_size = reader.seek(SeekFrom::End(0))?;
+    if archive_size < 2 * FOOTER_SIZE {
+        return Err(invalid_data("archive too small to be seekable"));
+    }
+    let mut footer = [0u8; FOOTER_SIZE as usize];
+    reader.seek(SeekFrom::End(-(FOOTER_SIZE as i64)))?;
+    reader.read_exact(&mut footer)?;
+    let manifest_size = u64::from(u32::from_le_bytes(footer[0..4].try_into().unwrap()));
+    let magic = u32::from_le_bytes(footer[4..8].try_into().unwrap());
+    if magic != SEEKABLE_ARCHIVE_MAGIC {
+        return Err(invalid_data("archive is not seekable"));
+    }
+    if manifest_size > MAX_MANIFEST_SIZE || manifest_size + 2 * FOOTER_SIZE > archive_size {
+        return Err(invalid_data("invalid manifest size"));
+    }
+
+    let mut manifest = vec![0u8; manifest_size as usize];
+    reader.seek(SeekFrom::End(-((manifest_size + FOOTER_SIZE) as i64)))?;
+    reader.read_exact(&mut manifest)?;
+    let manifest: SeekableArchiveManifest = bincode::options()
+        .with_limit(MAX_MANIFEST_SIZE)
+        .with_fixint_encoding()
+        .deserialize(&manifest)
+        .map_err(|_| invalid_data("invalid manifest"))?;
+
+    let frames_end = archive_size - manifest_size - 2 * FOOTER_SIZE;
+    if manifest.frames.is_empty() {
+        return Err(invalid_data("archive without frames"));
+    }
+    let mut expected_offset = 0;
+    for frame in &manifest.frames {
+        if frame.compressed_offset != expected_offset {
+            return Err(invalid_data("frames are not contiguous"));
+        }
+        if frame.entries.is_empty() {
+            return Err(invalid_data("frame without entries"));
+        }
+        expected_offset = frame
+            .compressed_offset
+            .checked_add(frame.compressed_size)
+            .filter(|frame_end| *frame_end <= frames_end)
+            .ok_or_else(|| invalid_data("frame past the end of the archive"))?;
+    }
+    Ok(manifest)
+}
+
+/// Unpack a seekable snapshot archive, decompressing and extracting its frames in parallel.
+/// Returns the unpacked account storage files and the checksums of their contents.
+pub fn unpack_seekable_snapshot(
+    archive_path: &Path,
+    ledger_dir: &Path,
+    account_paths: &[PathBuf],
+) -> Result<(UnpackedAppendVecMap, UnpackedStorageChecksums), UnpackError> {
+    let manifest = read_manifest(File::open(archive_path)?)?;
+    if manifest
+        .snapshot_version
+        .parse::<SnapshotVersion>()
+        .is_err()
+    {
+        return Err(UnpackError::Archive(format!(
+            "unsupported snapshot version: {}",
+            manifest.snapshot_version
+        )));
+    }
+
+    let totals = UnpackTotals::default();
+    let all_unpacked_append_vec_map = manifest
+        .frames
+        .par_iter()
+        .map(|frame| -> Result<_, UnpackError> {
+            let mut file = File::open(archive_path)?;
+            file.seek(SeekFrom::Start(frame.compressed_offset))?;
+            let decoder =
+                zstd::stream::read::Decoder::new(BufReader::new(file.take(frame.compressed_size)))?;
+            let mut archive = Archive::new(decoder);
+            let mut entries = Vec::with_capacity(frame.entries.len());
+            let mut storage_checksums = UnpackedStorageChecksums::new();
+            let unpacked_append_vec_map = unpack_snapshot_part(
+                &mut archive,
+                ledger_dir,
+                account_paths,
+                None,
+                &totals,
+                Some(&mut entries),
+                Some(&mut storage_checksums),
+            )?;
+            if entries != frame.entries {
+                return Err(UnpackError::Archive(format!(
+                    "entries of the frame at offset {} don't match the manifest",
+                    frame.compressed_offset
+                )));
+            }
+            Ok((unpacked_append_vec_map, storage_checksums))
+        })
+        .collect::<Vec<_>>();
+
+    let mut unpacked_append_vec_map = UnpackedAppendVecMap::new();
+    let mut storage_checksums = UnpackedStorageChecksums::new();
+    for h in all_unpacked_append_vec_map {
+        let (frame_append_vec_map, frame_storage_checksums) = h?;
+        unpacked_append_vec_map.extend(frame_append_vec_map);
+        storage_checksums.extend(frame_storage_checksums);
+    }
+
+    // the version file unpacked is what the snapshot is loaded as
+    let snapshot_version = fs::read_to_string(ledger_dir.join("version"))?;
+    if snapshot_version.trim() != manifest.snapshot_version {
+        return Err(UnpackError::Archive(format!(
+            "snapshot version {} doesn't match the manifest version {}",
+            snapshot_version.trim(),
+            manifest.snapshot_version
+        )));
+    }
+    Ok((unpacked_append_vec_map, storage_checksums))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Cursor};
+
+    fn append_file(builder: &mut tar::Builder<SeekableArchiveWriter<Vec<u8>>>, path: &str) {
+        append_data(builder, path, path, path.as_bytes());
+    }
+
+    fn append_data(
+        builder: &mut tar::Builder<SeekableArchiveWriter<Vec<u8>>>,
+        path: &str,
+        manifest_path: &str,
+        data: &[u8],
+    ) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+        builder.get_mut().add_entry(manifest_path);
+    }
+
+    fn entry_paths<R: Read>(archive: &mut Archive<R>) -> Vec<String> {
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                entry.path().unwrap().to_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seekable_archive() {
+        let mut builder = tar::Builder::new(SeekableArchiveWriter::new(Vec::new()));
+        append_file(&mut builder, "version");
+        builder.get_mut().end_frame().unwrap();
+        append_file(&mut builder, "accounts/1.0");
+        append_file(&mut builder, "accounts/1.1");
+        builder.get_mut().end_frame().unwrap();
+        append_file(&mut builder, "accounts/2.2");
+        let archive = builder.into_inner().unwrap().finish("1.2.0").unwrap();
+
+        let manifest = read_manifest(Cursor::new(&archive)).unwrap();
+        assert_eq!(manifest.snapshot_version, "1.2.0");
+        let frame_entries: Vec<_> = manifest
+            .frames
+            .iter()
+            .map(|frame| frame.entries.clone())
+            .collect();
+        assert_eq!(
+            frame_entries,
+            vec![
+                vec!["version".to_string()],
+                vec!["accounts/1.0".to_string(), "accounts/1.1".to_string()],
+                vec!["accounts/2.2".to_string()],
+            ]
+        );
+
+        // each frame can be extracted on its own
+        for frame in &manifest.frames {
+            let start = frame.compressed_offset as usize;
+            let end = start + frame.compressed_size as usize;
+            let decoder = zstd::stream::read::Decoder::new(&archive[start..end]).unwrap();
+            assert_eq!(entry_paths(&mut Archive::new(decoder)), frame.entries);
+        }
+
+        // and the whole archive still reads as a .tar.zst
+        let decoder = zstd::stream::read::Decoder::new(&archive[..]).unwrap();
+        assert_eq!(
+            entry_paths(&mut Archive::new(decoder)),
+            vec!["version", "accounts/1.0", "accounts/1.1", "accounts/2.2"]
+        );
+    }
+
+    #[test]
+    fn test_read_manifest_invalid() {
+        assert!(read_manifest(Cursor::new(vec![0u8; 4])).is_err());
+
+        // a plain zstd archive has no manifest
+        let mut encoder = zstd::stream::Encoder::new(Vec::new(), 0).unwrap();
+        encoder.write_all(&[42u8; 1024]).unwrap();
+        let archive = encoder.finish().unwrap();
+        assert!(read_manifest(Cursor::new(archive)).is_err());
+
+        // a truncated archive is rejected
+        let mut builder = tar::Builder::new(SeekableArchiveWriter::new(Vec::new()));
+        append_file(&mut builder, "version");
+        let archive = builder.into_inner().unwrap().finish("1.2.0").unwrap();
+        assert!(read_manifest(Cursor::new(&archive)).is_ok());
+        let truncated = archive[archive.len() / 4..].to_vec();
+        assert!(read_manifest(Cursor::new(truncated)).is_err());
+
+        // as is a frame without entries
+        let mut builder = tar::Builder::new(SeekableArchiveWriter::new(Vec::new()));
+        append_file(&mut builder, "version");
+        builder.get_mut().end_frame().unwrap();
+        let archive = builder.into_inner().unwrap().finish("1.2.0").unwrap();
+        assert!(read_manifest(Cursor::new(&archive)).is_err());
+    }
+
+    #[test]
+    fn test_unpack_seekable_snapshot() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let unpack = |version: &[u8], manifest_path: &str, snapshot_version: &str| {
+            let mut builder = tar::Builder::new(SeekableArchiveWriter::new(Vec::new()));
+            append_data(&mut builder, "version", "version", version);
+            builder.get_mut().end_frame().unwrap();
+            append_data(&mut builder, "accounts/1.0", manifest_path, &[0; 16]);
+            let archive = builder
+                .into_inner()
+                .unwrap()
+                .finish(snapshot_version)
+                .unwrap();
+            let archive_path = dir.path().join("snapshot.tar.seekable.zst");
+            fs::write(&archive_path, archive).unwrap();
+
+            let unpack_dir = tempfile::TempDir::new_in(dir.path()).unwrap();
+            unpack_seekable_snapshot(
+                &archive_path,
+                unpack_dir.path(),
+                &[unpack_dir.path().to_path_buf()],
+            )
+        };
+
+        let (unpacked_append_vec_map, storage_checksums) =
+            unpack(b"1.2.0", "accounts/1.0", "1.2.0").unwrap();
+        assert!(unpacked_append_vec_map.contains_key("1.0"));
+        assert!(storage_checksums.contains_key("1.0"));
+
+        // the entries of each frame must be the ones the manifest lists
+        assert!(unpack(b"1.2.0", "accounts/1.1", "1.2.0").is_err());
+        // and the version must be supported and the one unpacked
+        assert!(unpack(b"1.2.0", "accounts/1.0", "1.0.0").is_err());
+        assert!(unpack(b"1.0.0", "accounts/1.0", "1.2.0").is_err());
+    }
+}
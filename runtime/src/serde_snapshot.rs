@@ -12,6 +12,7 @@ use {
         bank::{Bank, BankFieldsToDeserialize, BankRc},
         blockhash_queue::BlockhashQueue,
         builtins::Builtins,
+        epoch_accounts_hash::EpochAccountsHash,
         epoch_stakes::EpochStakes,
         hardened_unpack::UnpackedAppendVecMap,
         rent_collector::RentCollector,
@@ -37,7 +38,7 @@ use {
     },
     std::{
         collections::{HashMap, HashSet},
-        io::{self, BufReader, BufWriter, Read, Write},
+        io::{self, BufRead, BufReader, BufWriter, Read, Write},
         path::{Path, PathBuf},
         result::Result,
         sync::{
@@ -189,6 +190,22 @@ where
         .deserialize_from::<R, T>(reader)
 }
 
+/// The epoch accounts hash a bank still needs, or has just mixed into its hash, is appended after
+/// the bank and accounts db fields, so that snapshots without one remain readable by older
+/// versions.  It is only absent if the stream ends right after those fields, a truncated one is
+/// an error
+fn deserialize_epoch_accounts_hash<R>(
+    stream: &mut BufReader<R>,
+) -> Result<Option<EpochAccountsHash>, Error>
+where
+    R: Read,
+{
+    if stream.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    deserialize_from(stream).map(Some)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn bank_from_streams<R>(
     serde_style: SerdeStyle,
@@ -214,16 +231,27 @@ where
         ($x:ident) => {{
             let (full_snapshot_bank_fields, full_snapshot_accounts_db_fields) =
                 $x::deserialize_bank_fields(snapshot_streams.full_snapshot_stream)?;
-            let (incremental_snapshot_bank_fields, incremental_snapshot_accounts_db_fields) =
-                if let Some(ref mut incremental_snapshot_stream) =
-                    snapshot_streams.incremental_snapshot_stream
-                {
-                    let (bank_fields, accounts_db_fields) =
-                        $x::deserialize_bank_fields(incremental_snapshot_stream)?;
-                    (Some(bank_fields), Some(accounts_db_fields))
-                } else {
-                    (None, None)
-                };
+            let (
+                incremental_snapshot_bank_fields,
+                incremental_snapshot_accounts_db_fields,
+                epoch_accounts_hash,
+            ) = if let Some(ref mut incremental_snapshot_stream) =
+                snapshot_streams.incremental_snapshot_stream
+            {
+                let (bank_fields, accounts_db_fields) =
+                    $x::deserialize_bank_fields(incremental_snapshot_stream)?;
+                let epoch_accounts_hash =
+                    deserialize_epoch_accounts_hash(incremental_snapshot_stream)?;
+                (
+                    Some(bank_fields),
+                    Some(accounts_db_fields),
+                    epoch_accounts_hash,
+                )
+            } else {
+                let epoch_accounts_hash =
+                    deserialize_epoch_accounts_hash(snapshot_streams.full_snapshot_stream)?;
+                (None, None, epoch_accounts_hash)
+            };
 
             let snapshot_accounts_db_fields = SnapshotAccountsDbFields {
                 full_snapshot_accounts_db_fields,
@@ -246,6 +274,25 @@ where
                 accounts_db_config,
                 accounts_update_notifier,
             )?;
+            match epoch_accounts_hash {
+                Some(epoch_accounts_hash) => {
+                    bank.set_epoch_accounts_hash_from_snapshot(epoch_accounts_hash)
+                }
+                // The accounts state the hash is calculated from is gone by then, so a hash
+                // calculated from any other state would fork this node off the cluster
+                None if bank.snapshot_requires_epoch_accounts_hash() => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "snapshot at slot {} is missing the epoch accounts hash of epoch {}",
+                            bank.slot(),
+                            bank.epoch()
+                        ),
+                    )
+                    .into());
+                }
+                None => (),
+            }
             Ok(bank)
         }};
     }
@@ -270,13 +317,19 @@ where
     macro_rules! INTO {
         ($x:ident) => {
             bincode::serialize_into(
-                stream,
+                &mut *stream,
                 &SerializableBankAndStorage::<$x> {
                     bank,
                     snapshot_storages,
                     phantom: std::marker::PhantomData::default(),
                 },
             )
+            .and_then(|_| match bank.epoch_accounts_hash_for_snapshot() {
+                Some(epoch_accounts_hash) => {
+                    bincode::serialize_into(&mut *stream, &epoch_accounts_hash)
+                }
+                None => Ok(()),
+            })
         };
     }
     match serde_style {
@@ -5,6 +5,8 @@ use {
         accounts::{create_test_accounts, Accounts},
         accounts_db::{get_temp_accounts_paths, AccountShrinkThreshold},
         bank::{Bank, StatusCacheRc},
+        epoch_accounts_hash,
+        genesis_utils::activate_feature,
         hardened_unpack::UnpackedAppendVecMap,
     },
     bincode::serialize_into,
@@ -12,6 +14,8 @@ use {
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::Slot,
+        epoch_schedule::EpochSchedule,
+        feature_set,
         genesis_config::{create_genesis_config, ClusterType},
         pubkey::Pubkey,
         signature::{Keypair, Signer},
@@ -260,6 +264,174 @@ fn test_bank_serialize_style(serde_style: SerdeStyle) {
     assert!(bank2 == dbank);
 }
 
+#[test]
+fn test_bank_serialize_epoch_accounts_hash() {
+    solana_logger::setup();
+    let (mut genesis_config, _) = create_genesis_config(500);
+    genesis_config.epoch_schedule = EpochSchedule::custom(32, 32, false);
+    activate_feature(&mut genesis_config, feature_set::epoch_accounts_hash::id());
+    let calculation_slot = epoch_accounts_hash::calculation_slot(&genesis_config.epoch_schedule, 0);
+    let integration_slot = epoch_accounts_hash::integration_slot(&genesis_config.epoch_schedule, 0);
+
+    let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+    let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), calculation_slot);
+    assert!(bank1.is_epoch_accounts_hash_calculation_bank());
+    bank1.freeze();
+    bank1.squash();
+    // Rooting the calculation bank requests the calculation, which hasn't run yet, so
+    // serializing calculates it
+    let accounts_db = &bank1.rc.accounts.accounts_db;
+    accounts_db
+        .epoch_accounts_hash_manager
+        .request(calculation_slot, bank1.ancestors.clone());
+    bank1.force_flush_accounts_cache();
+
+    let snapshot_storages = bank1.get_snapshot_storages(None);
+    let mut buf = vec![];
+    let mut writer = Cursor::new(&mut buf);
+    crate::serde_snapshot::bank_to_stream(
+        SerdeStyle::Newer,
+        &mut std::io::BufWriter::new(&mut writer),
+        &bank1,
+        &snapshot_storages,
+    )
+    .unwrap();
+    let epoch_accounts_hash = accounts_db.epoch_accounts_hash(calculation_slot).unwrap();
+    assert_eq!(
+        bank1.epoch_accounts_hash_for_snapshot(),
+        Some(epoch_accounts_hash)
+    );
+
+    let mut reader = std::io::BufReader::new(&buf[..]);
+    let (_accounts_dir, dbank_paths) = get_temp_accounts_paths(4).unwrap();
+    let copied_accounts = TempDir::new().unwrap();
+    let unpacked_append_vec_map = copy_append_vecs(accounts_db, copied_accounts.path()).unwrap();
+    let mut snapshot_streams = SnapshotStreams {
+        full_snapshot_stream: &mut reader,
+        incremental_snapshot_stream: None,
+    };
+    let dbank = crate::serde_snapshot::bank_from_streams(
+        SerdeStyle::Newer,
+        &mut snapshot_streams,
+        &dbank_paths,
+        unpacked_append_vec_map,
+        &genesis_config,
+        &[],
+        None,
+        None,
+        AccountSecondaryIndexes::default(),
+        false,
+        None,
+        AccountShrinkThreshold::default(),
+        false,
+        Some(crate::accounts_db::ACCOUNTS_DB_CONFIG_FOR_TESTING),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        dbank
+            .rc
+            .accounts
+            .accounts_db
+            .epoch_accounts_hash(calculation_slot),
+        Some(epoch_accounts_hash)
+    );
+
+    // The rebuilt bank's fork mixes the restored epoch accounts hash in at the integration slot
+    let dbank = Arc::new(dbank);
+    let bank2 = Bank::new_from_parent(&dbank, &Pubkey::default(), integration_slot);
+    bank2.freeze();
+    assert_eq!(
+        bank2.integrated_epoch_accounts_hash(),
+        Some(epoch_accounts_hash)
+    );
+}
+
+#[test]
+fn test_bank_deserialize_without_epoch_accounts_hash() {
+    solana_logger::setup();
+    let (mut genesis_config, _) = create_genesis_config(500);
+    genesis_config.epoch_schedule = EpochSchedule::custom(32, 32, false);
+    activate_feature(&mut genesis_config, feature_set::epoch_accounts_hash::id());
+    let calculation_slot = epoch_accounts_hash::calculation_slot(&genesis_config.epoch_schedule, 0);
+    let integration_slot = epoch_accounts_hash::integration_slot(&genesis_config.epoch_schedule, 0);
+
+    // A snapshot between the calculation and integration slots, taken by a node that never
+    // requested the calculation, carries no epoch accounts hash.  Root the calculation bank
+    // without squashing it, which would request the calculation.
+    let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+    bank0.squash();
+    let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), calculation_slot + 1);
+    bank1.freeze();
+    bank1.rc.accounts.add_root(bank1.slot());
+    bank1.force_flush_accounts_cache();
+    assert!((calculation_slot..integration_slot).contains(&bank1.slot()));
+    assert_eq!(bank1.epoch_accounts_hash_for_snapshot(), None);
+
+    let snapshot_storages = bank1.get_snapshot_storages(None);
+    let mut buf = vec![];
+    let mut writer = Cursor::new(&mut buf);
+    crate::serde_snapshot::bank_to_stream(
+        SerdeStyle::Newer,
+        &mut std::io::BufWriter::new(&mut writer),
+        &bank1,
+        &snapshot_storages,
+    )
+    .unwrap();
+
+    let mut reader = std::io::BufReader::new(&buf[..]);
+    let (_accounts_dir, dbank_paths) = get_temp_accounts_paths(4).unwrap();
+    let copied_accounts = TempDir::new().unwrap();
+    let unpacked_append_vec_map =
+        copy_append_vecs(&bank1.rc.accounts.accounts_db, copied_accounts.path()).unwrap();
+    let mut snapshot_streams = SnapshotStreams {
+        full_snapshot_stream: &mut reader,
+        incremental_snapshot_stream: None,
+    };
+    // The accounts state the hash must be calculated from is gone, so the snapshot is unusable
+    let result = crate::serde_snapshot::bank_from_streams(
+        SerdeStyle::Newer,
+        &mut snapshot_streams,
+        &dbank_paths,
+        unpacked_append_vec_map,
+        &genesis_config,
+        &[],
+        None,
+        None,
+        AccountSecondaryIndexes::default(),
+        false,
+        None,
+        AccountShrinkThreshold::default(),
+        false,
+        Some(crate::accounts_db::ACCOUNTS_DB_CONFIG_FOR_TESTING),
+        None,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_deserialize_epoch_accounts_hash() {
+    let epoch_accounts_hash = EpochAccountsHash {
+        slot: 7,
+        hash: Hash::new_unique(),
+    };
+    let buf = bincode::serialize(&epoch_accounts_hash).unwrap();
+
+    let mut reader = BufReader::new(&buf[..]);
+    assert_eq!(
+        deserialize_epoch_accounts_hash(&mut reader).unwrap(),
+        Some(epoch_accounts_hash)
+    );
+
+    // Only a stream that ends before the record means it is absent
+    let mut reader = BufReader::new(&[][..]);
+    assert_eq!(deserialize_epoch_accounts_hash(&mut reader).unwrap(), None);
+    let mut reader = BufReader::new(&buf[..buf.len() - 1]);
+    assert!(deserialize_epoch_accounts_hash(&mut reader).is_err());
+    let mut reader = BufReader::new(&buf[..3]);
+    assert!(deserialize_epoch_accounts_hash(&mut reader).is_err());
+}
+
 #[cfg(test)]
 pub(crate) fn reconstruct_accounts_db_via_serialization(
     accounts: &AccountsDb,
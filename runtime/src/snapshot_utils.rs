@@ -5,7 +5,13 @@ use {
         accounts_update_notifier_interface::AccountsUpdateNotifier,
         bank::{Bank, BankSlotDelta},
         builtins::Builtins,
-        hardened_unpack::{unpack_snapshot, ParallelSelector, UnpackError, UnpackedAppendVecMap},
+        hardened_unpack::{
+            unpack_snapshot_part, ParallelSelector, UnpackError, UnpackTotals,
+            UnpackedAppendVecMap, UnpackedStorageChecksums,
+        },
+        seekable_snapshot_archive::{
+            self, unpack_seekable_snapshot, SeekableArchiveWriter, SEEKABLE_ARCHIVE_FRAME_SIZE,
+        },
         serde_snapshot::{
             bank_from_streams, bank_to_stream, SerdeStyle, SnapshotStorage, SnapshotStorages,
             SnapshotStreams,
@@ -19,7 +25,7 @@ use {
             SnapshotType,
         },
     },
-    bincode::{config::Options, serialize_into},
+    bincode::{config::Options, deserialize_from, serialize_into},
     bzip2::bufread::BzDecoder,
     flate2::read::GzDecoder,
     lazy_static::lazy_static,
@@ -30,7 +36,7 @@ use {
     solana_sdk::{clock::Slot, genesis_config::GenesisConfig, hash::Hash, pubkey::Pubkey},
     std::{
         cmp::{max, Ordering},
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         fmt,
         fs::{self, File},
         io::{BufReader, BufWriter, Error as IoError, ErrorKind, Read, Seek, Write},
@@ -45,22 +51,28 @@ use {
 };
 
 pub const SNAPSHOT_STATUS_CACHE_FILE_NAME: &str = "status_cache";
+pub const SNAPSHOT_STORAGE_CHECKSUMS_FILE_NAME: &str = "accounts_checksums";
 pub const DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot = 100_000;
 pub const DEFAULT_INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS: Slot = 100;
 const MAX_SNAPSHOT_DATA_FILE_SIZE: u64 = 32 * 1024 * 1024 * 1024; // 32 GiB
 const VERSION_STRING_V1_2_0: &str = "1.2.0";
+const VERSION_STRING_V1_3_0: &str = "1.3.0";
 const DEFAULT_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion::V1_2_0;
 pub(crate) const TMP_BANK_SNAPSHOT_PREFIX: &str = "tmp-bank-snapshot-";
 pub const TMP_SNAPSHOT_ARCHIVE_PREFIX: &str = "tmp-snapshot-archive-";
+pub const SNAPSHOT_ARCHIVE_QUARANTINE_DIR_NAME: &str = "quarantine";
+pub const LOCAL_SNAPSHOT_ARCHIVES_FILE_NAME: &str = "local-snapshot-archives";
 pub const MAX_BANK_SNAPSHOTS_TO_RETAIN: usize = 8; // Save some bank snapshots but not too many
 pub const DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 2;
 pub const DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 4;
-pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz)$";
-pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz)$";
+pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.seekable\.zst|tar\.gz)$";
+pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.seekable\.zst|tar\.gz)$";
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
     V1_2_0,
+    /// Same bank serialization as V1_2_0, plus the account storage checksums file
+    V1_3_0,
 }
 
 impl Default for SnapshotVersion {
@@ -79,6 +91,7 @@ impl From<SnapshotVersion> for &'static str {
     fn from(snapshot_version: SnapshotVersion) -> &'static str {
         match snapshot_version {
             SnapshotVersion::V1_2_0 => VERSION_STRING_V1_2_0,
+            SnapshotVersion::V1_3_0 => VERSION_STRING_V1_3_0,
         }
     }
 }
@@ -98,6 +111,7 @@ impl FromStr for SnapshotVersion {
         };
         match version_string {
             VERSION_STRING_V1_2_0 => Ok(SnapshotVersion::V1_2_0),
+            VERSION_STRING_V1_3_0 => Ok(SnapshotVersion::V1_3_0),
             _ => Err("unsupported snapshot version"),
         }
     }
@@ -111,6 +125,14 @@ impl SnapshotVersion {
     fn maybe_from_string(version_string: &str) -> Option<SnapshotVersion> {
         version_string.parse::<Self>().ok()
     }
+
+    /// Archives of this version carry a checksum for each account storage file
+    pub fn has_storage_checksums(self) -> bool {
+        match self {
+            SnapshotVersion::V1_2_0 => false,
+            SnapshotVersion::V1_3_0 => true,
+        }
+    }
 }
 
 /// The different archive formats used for snapshots
@@ -119,6 +141,8 @@ pub enum ArchiveFormat {
     TarBzip2,
     TarGzip,
     TarZstd,
+    /// zstd frames that can be extracted independently, see `seekable_snapshot_archive`
+    TarZstdSeekable,
     Tar,
 }
 
@@ -207,6 +231,12 @@ pub enum SnapshotError {
 
     #[error("snapshot has mismatch: deserialized bank: {:?}, snapshot archive info: {:?}", .0, .1)]
     MismatchedSlotHash((Slot, Hash), (Slot, Hash)),
+
+    #[error("snapshot archive {} has a corrupted account storage file: {1}", .0.display())]
+    MismatchedStorageChecksum(PathBuf, String),
+
+    #[error("snapshot archive {} has no account storage checksums", .0.display())]
+    MissingStorageChecksums(PathBuf),
 }
 pub type Result<T> = std::result::Result<T, SnapshotError>;
 
@@ -215,6 +245,7 @@ fn get_archive_ext(archive_format: ArchiveFormat) -> &'static str {
         ArchiveFormat::TarBzip2 => "tar.bz2",
         ArchiveFormat::TarGzip => "tar.gz",
         ArchiveFormat::TarZstd => "tar.zst",
+        ArchiveFormat::TarZstdSeekable => "tar.seekable.zst",
         ArchiveFormat::Tar => "tar",
     }
 }
@@ -242,6 +273,84 @@ pub fn remove_tmp_snapshot_archives(snapshot_archives_dir: impl AsRef<Path>) {
     }
 }
 
+/// Move a snapshot archive that failed to load into the quarantine directory next to it, where
+/// it's no longer picked up for loading or purged, but stays around for inspection.  Returns the
+/// quarantined archive's path.
+pub fn quarantine_snapshot_archive(snapshot_archive_path: impl AsRef<Path>) -> Result<PathBuf> {
+    let snapshot_archive_path = snapshot_archive_path.as_ref();
+    let file_name = snapshot_archive_path
+        .file_name()
+        .ok_or_else(|| SnapshotError::PathToFileNameError(snapshot_archive_path.to_path_buf()))?;
+    let quarantine_dir = snapshot_archive_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(SNAPSHOT_ARCHIVE_QUARANTINE_DIR_NAME);
+    fs::create_dir_all(&quarantine_dir)
+        .map_err(|e| SnapshotError::IoWithSource(e, "create quarantine dir"))?;
+    let quarantined_path = quarantine_dir.join(file_name);
+    fs::rename(snapshot_archive_path, &quarantined_path)
+        .map_err(|e| SnapshotError::IoWithSource(e, "quarantine snapshot archive"))?;
+    Ok(quarantined_path)
+}
+
+/// Add a snapshot archive this node created to the list of local snapshot archives kept next to
+/// it, dropping the archives that have been purged since
+fn record_local_snapshot_archive(
+    snapshot_archives_dir: &Path,
+    snapshot_archive_path: &Path,
+) -> Result<()> {
+    let file_name = snapshot_archive_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .ok_or_else(|| SnapshotError::FileNameToStrError(snapshot_archive_path.to_path_buf()))?;
+    let local_snapshot_archives_path =
+        snapshot_archives_dir.join(LOCAL_SNAPSHOT_ARCHIVES_FILE_NAME);
+    let mut local_snapshot_archives = fs::read_to_string(&local_snapshot_archives_path)
+        .unwrap_or_default()
+        .lines()
+        .filter(|local_file_name| {
+            *local_file_name != file_name && snapshot_archives_dir.join(local_file_name).is_file()
+        })
+        .map(|local_file_name| format!("{}\n", local_file_name))
+        .collect::<String>();
+    local_snapshot_archives.push_str(file_name);
+    local_snapshot_archives.push('\n');
+
+    // Replace the list atomically, a leftover temporary file is removed at startup
+    let tmp_path = snapshot_archives_dir.join(format!(
+        "{}{}",
+        TMP_SNAPSHOT_ARCHIVE_PREFIX, LOCAL_SNAPSHOT_ARCHIVES_FILE_NAME
+    ));
+    fs::write(&tmp_path, local_snapshot_archives)
+        .map_err(|e| SnapshotError::IoWithSource(e, "write local snapshot archives"))?;
+    fs::rename(&tmp_path, &local_snapshot_archives_path)
+        .map_err(|e| SnapshotError::IoWithSource(e, "rename local snapshot archives"))?;
+    Ok(())
+}
+
+/// Check whether this node created the snapshot archive itself, rather than downloading it or
+/// being handed it
+pub fn is_local_snapshot_archive(snapshot_archive_path: impl AsRef<Path>) -> bool {
+    let snapshot_archive_path = snapshot_archive_path.as_ref();
+    let (snapshot_archives_dir, file_name) = match (
+        snapshot_archive_path.parent(),
+        snapshot_archive_path
+            .file_name()
+            .and_then(|file_name| file_name.to_str()),
+    ) {
+        (Some(snapshot_archives_dir), Some(file_name)) => (snapshot_archives_dir, file_name),
+        _ => return false,
+    };
+    fs::read_to_string(snapshot_archives_dir.join(LOCAL_SNAPSHOT_ARCHIVES_FILE_NAME)).map_or(
+        false,
+        |local_snapshot_archives| {
+            local_snapshot_archives
+                .lines()
+                .any(|local_file_name| local_file_name == file_name)
+        },
+    )
+}
+
 /// Make a snapshot archive out of the snapshot package
 pub fn archive_snapshot_package(
     snapshot_package: &SnapshotPackage,
@@ -315,6 +424,17 @@ pub fn archive_snapshot_package(
         }
     }
 
+    if snapshot_package.snapshot_version.has_storage_checksums() {
+        serialize_storage_checksums(
+            snapshot_package.slot(),
+            &snapshot_package.snapshot_storages,
+            &snapshot_package
+                .snapshot_links
+                .path()
+                .join(SNAPSHOT_STORAGE_CHECKSUMS_FILE_NAME),
+        )?;
+    }
+
     // Write version file
     {
         let mut f = fs::File::create(staging_version_file)
@@ -364,6 +484,13 @@ pub fn archive_snapshot_package(
                 do_archive_files(&mut encoder)?;
                 encoder.finish()?;
             }
+            ArchiveFormat::TarZstdSeekable => {
+                archive_seekable_snapshot_files(
+                    archive_file,
+                    staging_dir.as_ref(),
+                    snapshot_package.snapshot_version.as_str(),
+                )?;
+            }
             ArchiveFormat::Tar => {
                 do_archive_files(&mut archive_file)?;
             }
@@ -381,6 +508,13 @@ pub fn archive_snapshot_package(
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
     );
+    if let Err(err) = record_local_snapshot_archive(tar_dir, snapshot_package.path()) {
+        warn!(
+            "Unable to record {} as a local snapshot archive: {}",
+            snapshot_package.path().display(),
+            err
+        );
+    }
 
     timer.stop();
     info!(
@@ -399,6 +533,45 @@ pub fn archive_snapshot_package(
     Ok(())
 }
 
+/// Tar the staging directory into a seekable archive, with the version and snapshots entries
+/// in a frame of their own and then a frame whenever the accounts files written exceed the
+/// frame size.  Frames are ended before an entry rather than after it, so that the end of the
+/// tar stream is in the last frame instead of in a frame without entries
+fn archive_seekable_snapshot_files(
+    archive_file: File,
+    staging_dir: &Path,
+    snapshot_version: &str,
+) -> Result<()> {
+    let mut archive = tar::Builder::new(SeekableArchiveWriter::new(archive_file));
+    archive.append_path_with_name(staging_dir.join("version"), "version")?;
+    archive.get_mut().add_entry("version");
+    seekable_snapshot_archive::append_dir_all(
+        &mut archive,
+        Path::new("snapshots"),
+        &staging_dir.join("snapshots"),
+    )?;
+    archive.append_dir("accounts", staging_dir.join("accounts"))?;
+    archive.get_mut().add_entry("accounts");
+
+    let mut account_file_names = fs::read_dir(staging_dir.join("accounts"))?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    account_file_names.sort();
+    if !account_file_names.is_empty() {
+        archive.get_mut().end_frame()?;
+    }
+    for file_name in account_file_names {
+        if archive.get_mut().frame_size() >= SEEKABLE_ARCHIVE_FRAME_SIZE {
+            archive.get_mut().end_frame()?;
+        }
+        let path = Path::new("accounts").join(&file_name);
+        archive.append_path_with_name(staging_dir.join(&path), &path)?;
+        archive.get_mut().add_entry(path.to_string_lossy());
+    }
+    archive.into_inner()?.finish(snapshot_version)?;
+    Ok(())
+}
+
 /// Get a list of bank snapshots in a directory
 pub fn get_bank_snapshots<P>(bank_snapshots_dir: P) -> Vec<BankSnapshotInfo>
 where
@@ -640,7 +813,7 @@ pub fn add_bank_snapshot<P: AsRef<Path>>(
     let mut bank_serialize = Measure::start("bank-serialize-ms");
     let bank_snapshot_serializer = move |stream: &mut BufWriter<File>| -> Result<()> {
         let serde_style = match snapshot_version {
-            SnapshotVersion::V1_2_0 => SerdeStyle::Newer,
+            SnapshotVersion::V1_2_0 | SnapshotVersion::V1_3_0 => SerdeStyle::Newer,
         };
         bank_to_stream(serde_style, stream.by_ref(), bank, snapshot_storages)?;
         Ok(())
@@ -695,6 +868,97 @@ fn serialize_status_cache(
     Ok(())
 }
 
+/// Checksums of the account storage files of a snapshot, keyed by file name
+type StorageChecksums = HashMap<String, Hash>;
+
+fn storage_file_checksum(path: &Path) -> std::io::Result<Hash> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(Hash::new_from_array(*hasher.finalize().as_bytes()))
+}
+
+fn serialize_storage_checksums(
+    slot: Slot,
+    snapshot_storages: &[SnapshotStorage],
+    storage_checksums_path: &Path,
+) -> Result<()> {
+    let mut storage_checksums_time = Measure::start("storage_checksums-ms");
+    let storage_checksums = snapshot_storages
+        .par_iter()
+        .flatten()
+        .map(|storage| {
+            let file_name =
+                crate::append_vec::AppendVec::file_name(storage.slot(), storage.append_vec_id());
+            let checksum = storage_file_checksum(&storage.get_path())?;
+            Ok((file_name, checksum))
+        })
+        .collect::<std::io::Result<StorageChecksums>>()?;
+    serialize_snapshot_data_file(storage_checksums_path, |stream| {
+        serialize_into(stream, &storage_checksums)?;
+        Ok(())
+    })?;
+    storage_checksums_time.stop();
+
+    datapoint_info!(
+        "snapshot-storage-checksums",
+        ("slot", slot, i64),
+        ("num_storages", storage_checksums.len(), i64),
+        ("duration_ms", storage_checksums_time.as_ms(), i64)
+    );
+    Ok(())
+}
+
+/// Check the checksums of the account storage files, hashed as they were unpacked, against the
+/// checksums recorded when the archive was made, so that a corrupted archive fails to load
+/// rather than propagating its accounts.  Checksums are required from snapshot version 1.3.0
+/// on; older archives are only checked if they happen to carry them
+fn verify_storage_checksums(
+    snapshot_archive_path: &Path,
+    snapshot_version: Option<SnapshotVersion>,
+    unpacked_snapshots_dir: &Path,
+    unpacked_storage_checksums: &UnpackedStorageChecksums,
+) -> Result<()> {
+    let storage_checksums_path = unpacked_snapshots_dir.join(SNAPSHOT_STORAGE_CHECKSUMS_FILE_NAME);
+    if !storage_checksums_path.is_file() {
+        if snapshot_version.map_or(false, SnapshotVersion::has_storage_checksums) {
+            return Err(SnapshotError::MissingStorageChecksums(
+                snapshot_archive_path.to_path_buf(),
+            ));
+        }
+        info!(
+            "Snapshot archive {} has no account storage checksums",
+            snapshot_archive_path.display()
+        );
+        return Ok(());
+    }
+    let mut measure_verify = Measure::start("verify_storage_checksums");
+    let storage_checksums: StorageChecksums =
+        deserialize_snapshot_data_file(&storage_checksums_path, |stream| {
+            Ok(deserialize_from(stream)?)
+        })?;
+
+    let mismatched_file_name = unpacked_storage_checksums
+        .keys()
+        .find(|file_name| !storage_checksums.contains_key(*file_name))
+        .or_else(|| {
+            storage_checksums
+                .iter()
+                .find(|(file_name, checksum)| {
+                    unpacked_storage_checksums.get(*file_name) != Some(*checksum)
+                })
+                .map(|(file_name, _)| file_name)
+        });
+    if let Some(file_name) = mismatched_file_name {
+        return Err(SnapshotError::MismatchedStorageChecksum(
+            snapshot_archive_path.to_path_buf(),
+            file_name.clone(),
+        ));
+    }
+    measure_verify.stop();
+    info!("{}", measure_verify);
+    Ok(())
+}
+
 /// Remove the snapshot directory for this slot
 pub fn remove_bank_snapshot<P>(slot: Slot, bank_snapshots_dir: P) -> Result<()>
 where
@@ -713,6 +977,55 @@ pub struct BankFromArchiveTimings {
     pub verify_snapshot_bank_us: u64,
 }
 
+/// How the accounts hash of a bank rebuilt from snapshot archives is verified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotVerification {
+    /// Recompute the full accounts hash and verify it against the snapshot
+    Full,
+    /// Skip recomputing the full accounts hash if the slot and hash of the snapshot are one of
+    /// these, which are trusted (eg. advertised by a known validator), otherwise recompute it
+    Trusted(HashSet<(Slot, Hash)>),
+    /// Skip recomputing the full accounts hash if the snapshot archives were created by this node,
+    /// eg. when restarting from its own snapshot, otherwise recompute it
+    Skip,
+}
+
+impl Default for SnapshotVerification {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
+/// Stages of rebuilding a bank from snapshot archives, reported to a
+/// `SnapshotLoadProgressCallback`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotLoadProgress {
+    /// Unpacking the snapshot archive for `slot`
+    Unarchiving { slot: Slot },
+    /// Rebuilding the bank and its accounts index from the unpacked append vecs
+    BuildingAccountsIndex,
+    /// Verifying the rebuilt bank against the snapshot
+    Verifying,
+}
+
+pub type SnapshotLoadProgressCallback = Arc<dyn Fn(SnapshotLoadProgress) + Sync + Send>;
+
+impl SnapshotVerification {
+    pub fn skip_accounts_hash_verification(
+        &self,
+        snapshot_hash: &(Slot, Hash),
+        is_local_snapshot: bool,
+    ) -> bool {
+        match self {
+            Self::Full => false,
+            Self::Trusted(trusted_snapshot_hashes) => {
+                trusted_snapshot_hashes.contains(snapshot_hash)
+            }
+            Self::Skip => is_local_snapshot,
+        }
+    }
+}
+
 // From testing, 4 seems to be a sweet spot for ranges of 60M-360M accounts and 16-64 cores. This may need to be tuned later.
 const PARALLEL_UNTAR_READERS_DEFAULT: usize = 4;
 
@@ -737,7 +1050,14 @@ pub fn bank_from_snapshot_archives(
     verify_index: bool,
     accounts_db_config: Option<AccountsDbConfig>,
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    snapshot_verification: &SnapshotVerification,
+    progress_callback: Option<&SnapshotLoadProgressCallback>,
 ) -> Result<(Bank, BankFromArchiveTimings)> {
+    let report_progress = |progress| {
+        if let Some(progress_callback) = progress_callback {
+            progress_callback(progress);
+        }
+    };
     check_are_snapshots_compatible(
         full_snapshot_archive_info,
         incremental_snapshot_archive_info,
@@ -748,6 +1068,9 @@ pub fn bank_from_snapshot_archives(
         std::cmp::max(1, num_cpus::get() / 4),
     );
 
+    report_progress(SnapshotLoadProgress::Unarchiving {
+        slot: full_snapshot_archive_info.slot(),
+    });
     let unarchived_full_snapshot = unarchive_snapshot(
         &bank_snapshots_dir,
         TMP_SNAPSHOT_ARCHIVE_PREFIX,
@@ -760,6 +1083,9 @@ pub fn bank_from_snapshot_archives(
 
     let mut unarchived_incremental_snapshot =
         if let Some(incremental_snapshot_archive_info) = incremental_snapshot_archive_info {
+            report_progress(SnapshotLoadProgress::Unarchiving {
+                slot: incremental_snapshot_archive_info.slot(),
+            });
             let unarchived_incremental_snapshot = unarchive_snapshot(
                 &bank_snapshots_dir,
                 TMP_SNAPSHOT_ARCHIVE_PREFIX,
@@ -781,6 +1107,7 @@ pub fn bank_from_snapshot_archives(
         unpacked_append_vec_map.extend(incremental_snapshot_unpacked_append_vec_map.into_iter());
     }
 
+    report_progress(SnapshotLoadProgress::BuildingAccountsIndex);
     let mut measure_rebuild = Measure::start("rebuild bank from snapshots");
     let bank = rebuild_bank_from_snapshots(
         &unarchived_full_snapshot.unpacked_snapshots_dir_and_version,
@@ -806,11 +1133,38 @@ pub fn bank_from_snapshot_archives(
     measure_rebuild.stop();
     info!("{}", measure_rebuild);
 
+    let snapshot_hash = incremental_snapshot_archive_info.map_or(
+        (
+            full_snapshot_archive_info.slot(),
+            *full_snapshot_archive_info.hash(),
+        ),
+        |incremental_snapshot_archive_info| {
+            (
+                incremental_snapshot_archive_info.slot(),
+                *incremental_snapshot_archive_info.hash(),
+            )
+        },
+    );
+    let is_local_snapshot = is_local_snapshot_archive(full_snapshot_archive_info.path())
+        && incremental_snapshot_archive_info.map_or(true, |incremental_snapshot_archive_info| {
+            is_local_snapshot_archive(incremental_snapshot_archive_info.path())
+        });
+    let skip_accounts_hash_verification =
+        snapshot_verification.skip_accounts_hash_verification(&snapshot_hash, is_local_snapshot);
+    if skip_accounts_hash_verification {
+        info!(
+            "Skipping accounts hash verification of snapshot: {:?}",
+            snapshot_hash
+        );
+    }
+
+    report_progress(SnapshotLoadProgress::Verifying);
     let mut measure_verify = Measure::start("verify");
     if !bank.verify_snapshot_bank(
         test_hash_calculation,
         accounts_db_skip_shrink,
         Some(full_snapshot_archive_info.slot()),
+        skip_accounts_hash_verification,
     ) && limit_load_slot_count_from_snapshot.is_none()
     {
         panic!("Snapshot bank for slot {} failed to verify", bank.slot());
@@ -849,6 +1203,8 @@ pub fn bank_from_latest_snapshot_archives(
     verify_index: bool,
     accounts_db_config: Option<AccountsDbConfig>,
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
+    snapshot_verification: &SnapshotVerification,
+    progress_callback: Option<&SnapshotLoadProgressCallback>,
 ) -> Result<(
     Bank,
     BankFromArchiveTimings,
@@ -893,6 +1249,8 @@ pub fn bank_from_latest_snapshot_archives(
         verify_index,
         accounts_db_config,
         accounts_update_notifier,
+        snapshot_verification,
+        progress_callback,
     )?;
 
     verify_bank_against_expected_slot_hash(
@@ -957,8 +1315,8 @@ where
     let unpacked_snapshots_dir = unpack_dir.path().join("snapshots");
 
     let mut measure_untar = Measure::start(measure_name);
-    let unpacked_append_vec_map = untar_snapshot_in(
-        snapshot_archive_path,
+    let (unpacked_append_vec_map, unpacked_storage_checksums) = untar_snapshot_in(
+        &snapshot_archive_path,
         unpack_dir.path(),
         account_paths,
         archive_format,
@@ -975,6 +1333,13 @@ where
         snapshot_version.trim().to_string()
     };
 
+    verify_storage_checksums(
+        snapshot_archive_path.as_ref(),
+        SnapshotVersion::maybe_from_string(&snapshot_version),
+        &unpacked_snapshots_dir,
+        &unpacked_storage_checksums,
+    )?;
+
     Ok(UnarchivedSnapshot {
         unpack_dir,
         unpacked_append_vec_map,
@@ -1056,6 +1421,7 @@ fn archive_format_from_str(archive_format: &str) -> Option<ArchiveFormat> {
         "tar.bz2" => Some(ArchiveFormat::TarBzip2),
         "tar.gz" => Some(ArchiveFormat::TarGzip),
         "tar.zst" => Some(ArchiveFormat::TarZstd),
+        "tar.seekable.zst" => Some(ArchiveFormat::TarZstdSeekable),
         "tar" => Some(ArchiveFormat::Tar),
         _ => None,
     }
@@ -1315,7 +1681,7 @@ fn unpack_snapshot_local<T: 'static + Read + std::marker::Send, F: Fn() -> T>(
     ledger_dir: &Path,
     account_paths: &[PathBuf],
     parallel_archivers: usize,
-) -> Result<UnpackedAppendVecMap> {
+) -> Result<(UnpackedAppendVecMap, UnpackedStorageChecksums)> {
     assert!(parallel_archivers > 0);
     // a shared 'reader' that reads the decompressed stream once, keeps some history, and acts as a reader for multiple parallel archive readers
     let shared_buffer = SharedBuffer::new(reader());
@@ -1327,6 +1693,8 @@ fn unpack_snapshot_local<T: 'static + Read + std::marker::Send, F: Fn() -> T>(
         .collect::<Vec<_>>();
 
     // create 'parallel_archivers' # of parallel workers, each responsible for 1/parallel_archivers of all the files to extract.
+    // the unpack limits apply to what all the workers extract together
+    let totals = UnpackTotals::default();
     let all_unpacked_append_vec_map = readers
         .into_par_iter()
         .enumerate()
@@ -1336,15 +1704,28 @@ fn unpack_snapshot_local<T: 'static + Read + std::marker::Send, F: Fn() -> T>(
                 divisions: parallel_archivers,
             });
             let mut archive = Archive::new(reader);
-            unpack_snapshot(&mut archive, ledger_dir, account_paths, parallel_selector)
+            let mut storage_checksums = UnpackedStorageChecksums::new();
+            unpack_snapshot_part(
+                &mut archive,
+                ledger_dir,
+                account_paths,
+                parallel_selector,
+                &totals,
+                None,
+                Some(&mut storage_checksums),
+            )
+            .map(|unpacked_append_vec_map| (unpacked_append_vec_map, storage_checksums))
         })
         .collect::<Vec<_>>();
     let mut unpacked_append_vec_map = UnpackedAppendVecMap::new();
+    let mut storage_checksums = UnpackedStorageChecksums::new();
     for h in all_unpacked_append_vec_map {
-        unpacked_append_vec_map.extend(h?);
+        let (part_append_vec_map, part_storage_checksums) = h?;
+        unpacked_append_vec_map.extend(part_append_vec_map);
+        storage_checksums.extend(part_storage_checksums);
     }
 
-    Ok(unpacked_append_vec_map)
+    Ok((unpacked_append_vec_map, storage_checksums))
 }
 
 fn untar_snapshot_in<P: AsRef<Path>>(
@@ -1353,7 +1734,7 @@ fn untar_snapshot_in<P: AsRef<Path>>(
     account_paths: &[PathBuf],
     archive_format: ArchiveFormat,
     parallel_divisions: usize,
-) -> Result<UnpackedAppendVecMap> {
+) -> Result<(UnpackedAppendVecMap, UnpackedStorageChecksums)> {
     let open_file = || File::open(&snapshot_tar).unwrap();
     let account_paths_map = match archive_format {
         ArchiveFormat::TarBzip2 => unpack_snapshot_local(
@@ -1374,6 +1755,9 @@ fn untar_snapshot_in<P: AsRef<Path>>(
             account_paths,
             parallel_divisions,
         )?,
+        ArchiveFormat::TarZstdSeekable => {
+            unpack_seekable_snapshot(snapshot_tar.as_ref(), unpack_dir, account_paths)?
+        }
         ArchiveFormat::Tar => unpack_snapshot_local(
             || BufReader::new(open_file()),
             unpack_dir,
@@ -1464,7 +1848,7 @@ fn rebuild_bank_from_snapshots(
     let bank = deserialize_snapshot_data_files(&snapshot_root_paths, |snapshot_streams| {
         Ok(
             match incremental_snapshot_version.unwrap_or(full_snapshot_version) {
-                SnapshotVersion::V1_2_0 => bank_from_streams(
+                SnapshotVersion::V1_2_0 | SnapshotVersion::V1_3_0 => bank_from_streams(
                     SerdeStyle::Newer,
                     snapshot_streams,
                     account_paths,
@@ -1545,8 +1929,8 @@ pub fn verify_snapshot_archive<P, Q, R>(
 {
     let temp_dir = tempfile::TempDir::new().unwrap();
     let unpack_dir = temp_dir.path();
-    untar_snapshot_in(
-        snapshot_archive,
+    let (_, unpacked_storage_checksums) = untar_snapshot_in(
+        &snapshot_archive,
         unpack_dir,
         &[unpack_dir.to_path_buf()],
         archive_format,
@@ -1554,8 +1938,27 @@ pub fn verify_snapshot_archive<P, Q, R>(
     )
     .unwrap();
 
-    // Check snapshots are the same
+    // Check the storages match their checksums, which are added by the packager rather than
+    // collected from the source dir for snapshots
     let unpacked_snapshots = unpack_dir.join("snapshots");
+    let snapshot_version = SnapshotVersion::maybe_from_string(
+        fs::read_to_string(unpack_dir.join("version"))
+            .unwrap()
+            .trim(),
+    )
+    .unwrap();
+    verify_storage_checksums(
+        snapshot_archive.as_ref(),
+        Some(snapshot_version),
+        &unpacked_snapshots,
+        &unpacked_storage_checksums,
+    )
+    .unwrap();
+    if snapshot_version.has_storage_checksums() {
+        fs::remove_file(unpacked_snapshots.join(SNAPSHOT_STORAGE_CHECKSUMS_FILE_NAME)).unwrap();
+    }
+
+    // Check snapshots are the same
     assert!(!dir_diff::is_different(&snapshots_to_verify, unpacked_snapshots).unwrap());
 
     // Check the account entries are the same
@@ -2020,6 +2423,14 @@ mod tests {
             .unwrap(),
             (43, Hash::default(), ArchiveFormat::TarZstd)
         );
+        assert_eq!(
+            parse_full_snapshot_archive_filename(&format!(
+                "snapshot-43-{}.tar.seekable.zst",
+                Hash::default()
+            ))
+            .unwrap(),
+            (43, Hash::default(), ArchiveFormat::TarZstdSeekable)
+        );
         assert_eq!(
             parse_full_snapshot_archive_filename(&format!("snapshot-44-{}.tar", Hash::default()))
                 .unwrap(),
@@ -2654,6 +3065,245 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(original_bank, roundtrip_bank);
+    }
+
+    #[test]
+    fn test_bank_from_snapshot_archive_with_corrupted_storage() {
+        solana_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let original_bank = Bank::new_for_tests(&genesis_config);
+
+        while !original_bank.is_complete() {
+            original_bank.register_tick(&Hash::new_unique());
+        }
+
+        let accounts_dir = tempfile::TempDir::new().unwrap();
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+
+        let snapshot_archive_info = bank_to_full_snapshot_archive(
+            &bank_snapshots_dir,
+            &original_bank,
+            Some(SnapshotVersion::V1_3_0),
+            snapshot_archives_dir.path(),
+            ArchiveFormat::Tar,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+
+        // Flip a byte of the first account storage file in the archive
+        let (storage_file_name, position) = {
+            let mut archive = Archive::new(File::open(snapshot_archive_info.path()).unwrap());
+            archive
+                .entries()
+                .unwrap()
+                .map(|entry| entry.unwrap())
+                .find_map(|entry| {
+                    let path = entry.path().unwrap().into_owned();
+                    (path.starts_with("accounts") && entry.size() > 0).then(|| {
+                        (
+                            path.file_name().unwrap().to_string_lossy().to_string(),
+                            entry.raw_file_position(),
+                        )
+                    })
+                })
+                .unwrap()
+        };
+        let mut archive_file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(snapshot_archive_info.path())
+            .unwrap();
+        let mut byte = [0];
+        archive_file
+            .seek(std::io::SeekFrom::Start(position))
+            .unwrap();
+        archive_file.read_exact(&mut byte).unwrap();
+        archive_file
+            .seek(std::io::SeekFrom::Start(position))
+            .unwrap();
+        archive_file.write_all(&[!byte[0]]).unwrap();
+        drop(archive_file);
+
+        let result = bank_from_snapshot_archives(
+            &[PathBuf::from(accounts_dir.path())],
+            &[],
+            bank_snapshots_dir.path(),
+            &snapshot_archive_info,
+            None,
+            &genesis_config,
+            None,
+            None,
+            AccountSecondaryIndexes::default(),
+            false,
+            None,
+            AccountShrinkThreshold::default(),
+            false,
+            false,
+            false,
+            Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
+            None,
+            &SnapshotVerification::Full,
+            None,
+        );
+        assert_matches!(
+            result,
+            Err(SnapshotError::MismatchedStorageChecksum(ref archive_path, ref file_name))
+                if archive_path == snapshot_archive_info.path() && *file_name == storage_file_name
+        );
+    }
+
+    #[test]
+    fn test_quarantine_snapshot_archive() {
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archive_path = snapshot_archives_dir
+            .path()
+            .join(format!("snapshot-42-{}.tar", Hash::default()));
+        File::create(&snapshot_archive_path).unwrap();
+        assert!(get_highest_full_snapshot_archive_info(&snapshot_archives_dir).is_some());
+
+        let quarantined_path = quarantine_snapshot_archive(&snapshot_archive_path).unwrap();
+        assert!(!snapshot_archive_path.exists());
+        assert!(quarantined_path.is_file());
+        assert_eq!(
+            quarantined_path,
+            snapshot_archives_dir
+                .path()
+                .join(SNAPSHOT_ARCHIVE_QUARANTINE_DIR_NAME)
+                .join(snapshot_archive_path.file_name().unwrap())
+        );
+        assert!(get_highest_full_snapshot_archive_info(&snapshot_archives_dir).is_none());
+    }
+
+    #[test]
+    fn test_snapshot_version_storage_checksums() {
+        let genesis_config = GenesisConfig::default();
+        let original_bank = Bank::new_for_tests(&genesis_config);
+
+        while !original_bank.is_complete() {
+            original_bank.register_tick(&Hash::new_unique());
+        }
+
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        for snapshot_version in [SnapshotVersion::V1_2_0, SnapshotVersion::V1_3_0] {
+            let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+            let snapshot_archive_info = bank_to_full_snapshot_archive(
+                &bank_snapshots_dir,
+                &original_bank,
+                Some(snapshot_version),
+                snapshot_archives_dir.path(),
+                ArchiveFormat::Tar,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            )
+            .unwrap();
+
+            let mut archive = Archive::new(File::open(snapshot_archive_info.path()).unwrap());
+            let has_storage_checksums = archive.entries().unwrap().any(|entry| {
+                entry.unwrap().path().unwrap().as_ref()
+                    == Path::new("snapshots").join(SNAPSHOT_STORAGE_CHECKSUMS_FILE_NAME)
+            });
+            assert_eq!(
+                has_storage_checksums,
+                snapshot_version.has_storage_checksums()
+            );
+        }
+
+        let snapshot_archive_path = Path::new("snapshot-1-1111.tar");
+        let unpacked_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let unpacked_storage_checksums = UnpackedStorageChecksums::default();
+        assert!(verify_storage_checksums(
+            snapshot_archive_path,
+            Some(SnapshotVersion::V1_2_0),
+            unpacked_snapshots_dir.path(),
+            &unpacked_storage_checksums,
+        )
+        .is_ok());
+        assert_matches!(
+            verify_storage_checksums(
+                snapshot_archive_path,
+                Some(SnapshotVersion::V1_3_0),
+                unpacked_snapshots_dir.path(),
+                &unpacked_storage_checksums,
+            ),
+            Err(SnapshotError::MissingStorageChecksums(ref archive_path))
+                if archive_path == snapshot_archive_path
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_bank_to_and_from_seekable_snapshot() {
+        solana_logger::setup();
+        let genesis_config = GenesisConfig::default();
+        let original_bank = Bank::new_for_tests(&genesis_config);
+
+        while !original_bank.is_complete() {
+            original_bank.register_tick(&Hash::new_unique());
+        }
+
+        let accounts_dir = tempfile::TempDir::new().unwrap();
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archive_format = ArchiveFormat::TarZstdSeekable;
+
+        let snapshot_archive_info = bank_to_full_snapshot_archive(
+            &bank_snapshots_dir,
+            &original_bank,
+            None,
+            snapshot_archives_dir.path(),
+            snapshot_archive_format,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+
+        // the manifest is readable without unpacking the archive
+        let manifest = crate::seekable_snapshot_archive::read_manifest(
+            File::open(snapshot_archive_info.path()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.snapshot_version,
+            SnapshotVersion::default().as_str()
+        );
+        let metadata_entries = &manifest.frames[0].entries;
+        assert_eq!(metadata_entries.first().unwrap(), "version");
+        assert_eq!(metadata_entries.last().unwrap(), "accounts");
+        assert!(metadata_entries[1..metadata_entries.len() - 1]
+            .iter()
+            .all(|entry| entry.starts_with("snapshots")));
+        assert!(manifest.frames[1..]
+            .iter()
+            .flat_map(|frame| &frame.entries)
+            .all(|entry| entry.starts_with("accounts/")));
+
+        let (roundtrip_bank, _) = bank_from_snapshot_archives(
+            &[PathBuf::from(accounts_dir.path())],
+            &[],
+            bank_snapshots_dir.path(),
+            &snapshot_archive_info,
+            None,
+            &genesis_config,
+            None,
+            None,
+            AccountSecondaryIndexes::default(),
+            false,
+            None,
+            AccountShrinkThreshold::default(),
+            false,
+            false,
+            false,
+            Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
+            None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
 
@@ -2746,6 +3396,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
 
@@ -2857,12 +3509,82 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
 
         assert_eq!(*bank4, roundtrip_bank);
     }
 
+    #[test]
+    fn test_snapshot_verification() {
+        let snapshot_hash = (42, Hash::new_unique());
+        let other_snapshot_hash = (42, Hash::new_unique());
+
+        for is_local_snapshot in [false, true] {
+            assert!(!SnapshotVerification::Full
+                .skip_accounts_hash_verification(&snapshot_hash, is_local_snapshot));
+        }
+        assert!(SnapshotVerification::Skip.skip_accounts_hash_verification(&snapshot_hash, true));
+        assert!(!SnapshotVerification::Skip.skip_accounts_hash_verification(&snapshot_hash, false));
+
+        let trusted = SnapshotVerification::Trusted(std::iter::once(snapshot_hash).collect());
+        assert!(trusted.skip_accounts_hash_verification(&snapshot_hash, false));
+        assert!(!trusted.skip_accounts_hash_verification(&other_snapshot_hash, true));
+    }
+
+    #[test]
+    fn test_local_snapshot_archives() {
+        let genesis_config = GenesisConfig::default();
+        let bank = Bank::new_for_tests(&genesis_config);
+        while !bank.is_complete() {
+            bank.register_tick(&Hash::new_unique());
+        }
+
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archive_info = bank_to_full_snapshot_archive(
+            &bank_snapshots_dir,
+            &bank,
+            None,
+            snapshot_archives_dir.path(),
+            ArchiveFormat::Tar,
+            1,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+        assert!(is_local_snapshot_archive(snapshot_archive_info.path()));
+
+        // An archive copied in under another name wasn't created by this node
+        let copied_path = snapshot_archives_dir.path().join(format!(
+            "snapshot-{}-{}.tar",
+            bank.slot() + 1,
+            Hash::default()
+        ));
+        fs::copy(snapshot_archive_info.path(), &copied_path).unwrap();
+        assert!(!is_local_snapshot_archive(&copied_path));
+
+        // Purged archives are dropped from the list when the next archive is recorded
+        let purged_path = snapshot_archive_info.path().clone();
+        fs::remove_file(&copied_path).unwrap();
+        fs::remove_file(&purged_path).unwrap();
+        File::create(&copied_path).unwrap();
+        record_local_snapshot_archive(snapshot_archives_dir.path(), &copied_path).unwrap();
+        assert!(is_local_snapshot_archive(&copied_path));
+        assert_eq!(
+            fs::read_to_string(
+                snapshot_archives_dir
+                    .path()
+                    .join(LOCAL_SNAPSHOT_ARCHIVES_FILE_NAME)
+            )
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>(),
+            vec![copied_path.file_name().unwrap().to_str().unwrap()]
+        );
+    }
+
     /// Test rebuilding bank from the latest snapshot archives
     #[test]
     fn test_bank_from_latest_snapshot_archives() {
@@ -2957,6 +3679,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
 
@@ -3097,6 +3821,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
         assert_eq!(
@@ -3160,6 +3886,8 @@ mod tests {
             false,
             Some(ACCOUNTS_DB_CONFIG_FOR_TESTING),
             None,
+            &SnapshotVerification::Full,
+            None,
         )
         .unwrap();
         assert_eq!(
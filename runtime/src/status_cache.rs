@@ -1,26 +1,38 @@
-use crate::ancestors::Ancestors;
+use crate::{accounts_index::RollingBitField, ancestors::Ancestors};
 
 use log::*;
 use rand::{thread_rng, Rng};
 use serde::Serialize;
+use solana_measure::measure::Measure;
 use solana_sdk::{
     clock::{Slot, MAX_RECENT_BLOCKHASHES},
     hash::Hash,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
-    sync::{Arc, Mutex},
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
 };
 
 pub const MAX_CACHE_ENTRIES: usize = MAX_RECENT_BLOCKHASHES;
 const CACHED_KEY_SIZE: usize = 20;
-
-// Store forks in a single chunk of memory to avoid another lookup.
+// Wide enough to hold the last MAX_CACHE_ENTRIES roots across long runs of skipped slots, older
+// roots that no longer fit are moved to the bit field's excess set
+const ROOTS_BIT_FIELD_WIDTH: u64 = 8192;
+// The blockhashes are spread over this many separately locked shards, so transactions with
+// different blockhashes can be checked and inserted concurrently
+const NUM_SHARDS: usize = 16;
+
+// Store forks in a single chunk of memory to avoid another lookup. Each fork carries its own
+// status, so unlike the roots these can't be kept in a bit field
 pub type ForkStatus<T> = Vec<(Slot, T)>;
 type KeySlice = [u8; CACHED_KEY_SIZE];
 type KeyMap<T> = HashMap<KeySlice, ForkStatus<T>>;
 // Map of Hash and status
-pub type Status<T> = Arc<Mutex<HashMap<Hash, (usize, Vec<(KeySlice, T)>)>>>;
+type StatusMap<T> = HashMap<Hash, (usize, Vec<(KeySlice, T)>)>;
+pub type Status<T> = Arc<Mutex<StatusMap<T>>>;
 // A Map of hash + the highest fork it's been observed on along with
 // the key offset and a Map of the key slice + Fork status for that key
 type KeyStatusMap<T> = HashMap<Hash, (Slot, usize, KeyMap<T>)>;
@@ -40,21 +52,60 @@ pub struct SignatureConfirmationStatus<T> {
     pub status: T,
 }
 
-#[derive(Clone, Debug, AbiExample)]
+// Time spent waiting for the locks of the status cache, reported when a root is added
+#[derive(Debug, Default, AbiExample)]
+struct StatusCacheLockStats {
+    shard_lock_wait_us: AtomicU64,
+    shard_lock_count: AtomicU64,
+    slot_deltas_lock_wait_us: AtomicU64,
+}
+
+impl StatusCacheLockStats {
+    fn report(&self) {
+        datapoint_info!(
+            "status_cache-lock-wait",
+            (
+                "shard_lock_wait_us",
+                self.shard_lock_wait_us.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "shard_lock_count",
+                self.shard_lock_count.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "slot_deltas_lock_wait_us",
+                self.slot_deltas_lock_wait_us.swap(0, Ordering::Relaxed),
+                i64
+            ),
+        );
+    }
+}
+
+#[derive(Debug, AbiExample)]
 pub struct StatusCache<T: Serialize + Clone> {
-    cache: KeyStatusMap<T>,
-    roots: HashSet<Slot>,
+    /// the statuses by blockhash, sharded by the first byte of the blockhash
+    shards: Vec<RwLock<KeyStatusMap<T>>>,
+    /// the rooted slots, which like the banks' `Ancestors` are kept in a bit field
+    roots: RollingBitField,
     /// all keys seen during a fork/slot
-    slot_deltas: SlotDeltaMap<T>,
+    slot_deltas: RwLock<SlotDeltaMap<T>>,
+    lock_stats: StatusCacheLockStats,
 }
 
 impl<T: Serialize + Clone> Default for StatusCache<T> {
     fn default() -> Self {
+        let mut roots = RollingBitField::new(ROOTS_BIT_FIELD_WIDTH);
+        // 0 is always a root
+        roots.insert(0);
         Self {
-            cache: HashMap::default(),
-            // 0 is always a root
-            roots: [0].iter().cloned().collect(),
-            slot_deltas: HashMap::default(),
+            shards: (0..NUM_SHARDS)
+                .map(|_| RwLock::new(HashMap::default()))
+                .collect(),
+            roots,
+            slot_deltas: RwLock::default(),
+            lock_stats: StatusCacheLockStats::default(),
         }
     }
 }
@@ -63,39 +114,98 @@ impl<T: Serialize + Clone + PartialEq> PartialEq for StatusCache<T> {
     fn eq(&self, other: &Self) -> bool {
         self.roots == other.roots
             && self
-                .cache
+                .shards
                 .iter()
-                .all(|(hash, (slot, key_index, hash_map))| {
-                    if let Some((other_slot, other_key_index, other_hash_map)) =
-                        other.cache.get(hash)
-                    {
-                        if slot == other_slot && key_index == other_key_index {
-                            return hash_map.iter().all(|(slice, fork_map)| {
-                                if let Some(other_fork_map) = other_hash_map.get(slice) {
-                                    // all this work just to compare the highest forks in the fork map
-                                    // per entry
-                                    return fork_map.last() == other_fork_map.last();
+                .zip(&other.shards)
+                .all(|(shard, other_shard)| {
+                    let other_shard = other_shard.read().unwrap();
+                    shard
+                        .read()
+                        .unwrap()
+                        .iter()
+                        .all(|(hash, (slot, key_index, hash_map))| {
+                            if let Some((other_slot, other_key_index, other_hash_map)) =
+                                other_shard.get(hash)
+                            {
+                                if slot == other_slot && key_index == other_key_index {
+                                    return hash_map.iter().all(|(slice, fork_map)| {
+                                        if let Some(other_fork_map) = other_hash_map.get(slice) {
+                                            // all this work just to compare the highest forks in the fork map
+                                            // per entry
+                                            return fork_map.last() == other_fork_map.last();
+                                        }
+                                        false
+                                    });
                                 }
-                                false
-                            });
-                        }
-                    }
-                    false
+                            }
+                            false
+                        })
                 })
     }
 }
 
 impl<T: Serialize + Clone> StatusCache<T> {
+    fn shard_index(transaction_blockhash: &Hash) -> usize {
+        transaction_blockhash.as_ref()[0] as usize % NUM_SHARDS
+    }
+
+    fn read_shard(&self, transaction_blockhash: &Hash) -> RwLockReadGuard<KeyStatusMap<T>> {
+        let mut lock_time = Measure::start("status_cache-shard-lock");
+        let shard = self.shards[Self::shard_index(transaction_blockhash)]
+            .read()
+            .unwrap();
+        lock_time.stop();
+        self.record_shard_lock_wait(&lock_time);
+        shard
+    }
+
+    fn write_shard(&self, shard_index: usize) -> RwLockWriteGuard<KeyStatusMap<T>> {
+        let mut lock_time = Measure::start("status_cache-shard-lock");
+        let shard = self.shards[shard_index].write().unwrap();
+        lock_time.stop();
+        self.record_shard_lock_wait(&lock_time);
+        shard
+    }
+
+    fn record_shard_lock_wait(&self, lock_time: &Measure) {
+        self.lock_stats
+            .shard_lock_wait_us
+            .fetch_add(lock_time.as_us(), Ordering::Relaxed);
+        self.lock_stats
+            .shard_lock_count
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Returns the statuses added during `slot`, creating them if they don't exist yet
+    fn slot_delta(&self, slot: Slot) -> Status<T> {
+        let mut lock_time = Measure::start("status_cache-slot-deltas-lock");
+        let existing_slot_delta = self.slot_deltas.read().unwrap().get(&slot).cloned();
+        let slot_delta = existing_slot_delta.unwrap_or_else(|| {
+            self.slot_deltas
+                .write()
+                .unwrap()
+                .entry(slot)
+                .or_default()
+                .clone()
+        });
+        lock_time.stop();
+        self.lock_stats
+            .slot_deltas_lock_wait_us
+            .fetch_add(lock_time.as_us(), Ordering::Relaxed);
+        slot_delta
+    }
+
     pub fn clear_slot_entries(&mut self, slot: Slot) {
-        let slot_deltas = self.slot_deltas.remove(&slot);
+        let slot_deltas = self.slot_deltas.get_mut().unwrap().remove(&slot);
         if let Some(slot_deltas) = slot_deltas {
             let slot_deltas = slot_deltas.lock().unwrap();
             for (blockhash, (_, key_list)) in slot_deltas.iter() {
                 // Any blockhash that exists in self.slot_deltas must also exist
-                // in self.cache, because in self.purge_roots(), when an entry
-                // (b, (max_slot, _, _)) is removed from self.cache, this implies
+                // in self.shards, because in self.purge_roots(), when an entry
+                // (b, (max_slot, _, _)) is removed from self.shards, this implies
                 // all entries in self.slot_deltas < max_slot are also removed
-                if let Entry::Occupied(mut o_blockhash_entries) = self.cache.entry(*blockhash) {
+                let shard = self.shards[Self::shard_index(blockhash)].get_mut().unwrap();
+                if let Entry::Occupied(mut o_blockhash_entries) = shard.entry(*blockhash) {
                     let (_, _, all_hash_maps) = o_blockhash_entries.get_mut();
 
                     for (key_slice, _) in key_list {
@@ -134,7 +244,8 @@ impl<T: Serialize + Clone> StatusCache<T> {
         transaction_blockhash: &Hash,
         ancestors: &Ancestors,
     ) -> Option<(Slot, T)> {
-        let map = self.cache.get(transaction_blockhash)?;
+        let shard = self.read_shard(transaction_blockhash);
+        let map = shard.get(transaction_blockhash)?;
         let (_, index, keymap) = map;
         let max_key_index = key.as_ref().len().saturating_sub(CACHED_KEY_SIZE + 1);
         let index = (*index).min(max_key_index);
@@ -143,7 +254,7 @@ impl<T: Serialize + Clone> StatusCache<T> {
         if let Some(stored_forks) = keymap.get(key_slice) {
             let res = stored_forks
                 .iter()
-                .find(|(f, _)| ancestors.get(f) || self.roots.get(f).is_some())
+                .find(|(f, _)| ancestors.get(f) || self.roots.contains(f))
                 .cloned();
             if res.is_some() {
                 return res;
@@ -160,9 +271,11 @@ impl<T: Serialize + Clone> StatusCache<T> {
         key: &K,
         ancestors: &Ancestors,
     ) -> Option<(Slot, T)> {
-        let mut keys = vec![];
-        let mut val: Vec<_> = self.cache.iter().map(|(k, _)| *k).collect();
-        keys.append(&mut val);
+        let keys: Vec<_> = self
+            .shards
+            .iter()
+            .flat_map(|shard| shard.read().unwrap().keys().copied().collect::<Vec<_>>())
+            .collect();
 
         for blockhash in keys.iter() {
             trace!("get_status_any_blockhash: trying {}", blockhash);
@@ -179,50 +292,90 @@ impl<T: Serialize + Clone> StatusCache<T> {
     pub fn add_root(&mut self, fork: Slot) {
         self.roots.insert(fork);
         self.purge_roots();
+        self.lock_stats.report();
     }
 
-    pub fn roots(&self) -> &HashSet<u64> {
-        &self.roots
+    /// The roots still in the cache, in ascending order
+    pub fn roots(&self) -> impl Iterator<Item = Slot> + '_ {
+        self.roots.iter()
     }
 
     /// Insert a new key for a specific slot.
     pub fn insert<K: AsRef<[u8]>>(
-        &mut self,
+        &self,
         transaction_blockhash: &Hash,
         key: &K,
         slot: Slot,
         res: T,
     ) {
-        let max_key_index = key.as_ref().len().saturating_sub(CACHED_KEY_SIZE + 1);
-        let hash_map = self.cache.entry(*transaction_blockhash).or_insert_with(|| {
-            let key_index = thread_rng().gen_range(0, max_key_index + 1);
-            (slot, key_index, HashMap::new())
-        });
+        self.insert_batch(slot, vec![(transaction_blockhash, key, res)]);
+    }
 
-        hash_map.0 = std::cmp::max(slot, hash_map.0);
-        let key_index = hash_map.1.min(max_key_index);
-        let mut key_slice = [0u8; CACHED_KEY_SIZE];
-        key_slice.clone_from_slice(&key.as_ref()[key_index..key_index + CACHED_KEY_SIZE]);
-        self.insert_with_slice(transaction_blockhash, slot, key_index, key_slice, res);
+    /// Insert new keys for a specific slot, locking each shard they fall in only once
+    pub fn insert_batch<K: AsRef<[u8]>>(&self, slot: Slot, entries: Vec<(&Hash, K, T)>) {
+        let mut entries_by_shard: Vec<Vec<_>> = (0..NUM_SHARDS).map(|_| vec![]).collect();
+        for entry in entries {
+            entries_by_shard[Self::shard_index(entry.0)].push(entry);
+        }
+
+        let slot_delta = self.slot_delta(slot);
+        for (shard_index, entries) in entries_by_shard.into_iter().enumerate() {
+            if entries.is_empty() {
+                continue;
+            }
+            let mut shard = self.write_shard(shard_index);
+            let mut slot_delta = slot_delta.lock().unwrap();
+            for (transaction_blockhash, key, res) in entries {
+                let max_key_index = key.as_ref().len().saturating_sub(CACHED_KEY_SIZE + 1);
+                let key_index = shard
+                    .get(transaction_blockhash)
+                    .map(|(_, key_index, _)| *key_index)
+                    .unwrap_or_else(|| thread_rng().gen_range(0, max_key_index + 1));
+                let key_index = key_index.min(max_key_index);
+                let mut key_slice = [0u8; CACHED_KEY_SIZE];
+                key_slice.clone_from_slice(&key.as_ref()[key_index..key_index + CACHED_KEY_SIZE]);
+                Self::insert_with_slice(
+                    &mut shard,
+                    &mut slot_delta,
+                    transaction_blockhash,
+                    slot,
+                    key_index,
+                    key_slice,
+                    res,
+                );
+            }
+        }
     }
 
     pub fn purge_roots(&mut self) {
         if self.roots.len() > MAX_CACHE_ENTRIES {
-            if let Some(min) = self.roots.iter().min().cloned() {
+            if let Some(min) = self.roots.min() {
                 self.roots.remove(&min);
-                self.cache.retain(|_, (fork, _, _)| *fork > min);
-                self.slot_deltas.retain(|slot, _| *slot > min);
+                for shard in self.shards.iter_mut() {
+                    shard
+                        .get_mut()
+                        .unwrap()
+                        .retain(|_, (fork, _, _)| *fork > min);
+                }
+                self.slot_deltas
+                    .get_mut()
+                    .unwrap()
+                    .retain(|slot, _| *slot > min);
             }
         }
     }
 
     /// Clear for testing
     pub fn clear(&mut self) {
-        for v in self.cache.values_mut() {
-            v.2 = HashMap::new();
+        for shard in self.shards.iter_mut() {
+            for v in shard.get_mut().unwrap().values_mut() {
+                v.2 = HashMap::new();
+            }
         }
 
         self.slot_deltas
+            .get_mut()
+            .unwrap()
             .iter_mut()
             .for_each(|(_, status)| status.lock().unwrap().clear());
     }
@@ -230,13 +383,14 @@ impl<T: Serialize + Clone> StatusCache<T> {
     // returns the statuses for each slot in the slots provided
     pub fn slot_deltas(&self, slots: &[Slot]) -> Vec<SlotDelta<T>> {
         let empty = Arc::new(Mutex::new(HashMap::new()));
+        let slot_deltas = self.slot_deltas.read().unwrap();
         slots
             .iter()
             .map(|slot| {
                 (
                     *slot,
                     self.roots.contains(slot),
-                    self.slot_deltas.get(slot).unwrap_or(&empty).clone(),
+                    slot_deltas.get(slot).unwrap_or(&empty).clone(),
                 )
             })
             .collect()
@@ -245,15 +399,27 @@ impl<T: Serialize + Clone> StatusCache<T> {
     // replay deltas into a status_cache allows "appending" data
     pub fn append(&mut self, slot_deltas: &[SlotDelta<T>]) {
         for (slot, is_root, statuses) in slot_deltas {
+            let slot_delta = self.slot_delta(*slot);
+            let mut slot_delta = slot_delta.lock().unwrap();
             statuses
                 .lock()
                 .unwrap()
                 .iter()
                 .for_each(|(tx_hash, (key_index, statuses))| {
+                    let shard = self.shards[Self::shard_index(tx_hash)].get_mut().unwrap();
                     for (key_slice, res) in statuses.iter() {
-                        self.insert_with_slice(tx_hash, *slot, *key_index, *key_slice, res.clone())
+                        Self::insert_with_slice(
+                            shard,
+                            &mut slot_delta,
+                            tx_hash,
+                            *slot,
+                            *key_index,
+                            *key_slice,
+                            res.clone(),
+                        )
                     }
                 });
+            drop(slot_delta);
             if *is_root {
                 self.add_root(*slot);
             }
@@ -268,7 +434,8 @@ impl<T: Serialize + Clone> StatusCache<T> {
     }
 
     fn insert_with_slice(
-        &mut self,
+        shard: &mut KeyStatusMap<T>,
+        slot_delta: &mut StatusMap<T>,
         transaction_blockhash: &Hash,
         slot: Slot,
         key_index: usize,
@@ -276,16 +443,14 @@ impl<T: Serialize + Clone> StatusCache<T> {
         res: T,
     ) {
         let hash_map =
-            self.cache
+            shard
                 .entry(*transaction_blockhash)
                 .or_insert((slot, key_index, HashMap::new()));
         hash_map.0 = std::cmp::max(slot, hash_map.0);
 
         let forks = hash_map.2.entry(key_slice).or_insert_with(Vec::new);
         forks.push((slot, res.clone()));
-        let slot_deltas = self.slot_deltas.entry(slot).or_default();
-        let mut fork_entry = slot_deltas.lock().unwrap();
-        let (_, hash_entry) = fork_entry
+        let (_, hash_entry) = slot_delta
             .entry(*transaction_blockhash)
             .or_insert((key_index, vec![]));
         hash_entry.push((key_slice, res))
@@ -317,7 +482,7 @@ mod tests {
     #[test]
     fn test_find_sig_with_ancestor_fork() {
         let sig = Signature::default();
-        let mut status_cache = BankStatusCache::default();
+        let status_cache = BankStatusCache::default();
         let blockhash = hash(Hash::default().as_ref());
         let ancestors = vec![(0, 1)].into_iter().collect();
         status_cache.insert(&blockhash, &sig, 0, ());
@@ -334,7 +499,7 @@ mod tests {
     #[test]
     fn test_find_sig_without_ancestor_fork() {
         let sig = Signature::default();
-        let mut status_cache = BankStatusCache::default();
+        let status_cache = BankStatusCache::default();
         let blockhash = hash(Hash::default().as_ref());
         let ancestors = Ancestors::default();
         status_cache.insert(&blockhash, &sig, 1, ());
@@ -421,7 +586,8 @@ mod tests {
         let blockhash = hash(Hash::default().as_ref());
         status_cache.clear();
         status_cache.insert(&blockhash, &sig, 0, ());
-        let (_, index, sig_map) = status_cache.cache.get(&blockhash).unwrap();
+        let shard = status_cache.read_shard(&blockhash);
+        let (_, index, sig_map) = shard.get(&blockhash).unwrap();
         let sig_slice: &[u8; CACHED_KEY_SIZE] =
             arrayref::array_ref![sig.as_ref(), *index, CACHED_KEY_SIZE];
         assert!(sig_map.get(sig_slice).is_some());
@@ -455,13 +621,39 @@ mod tests {
             status_cache.add_root(i as u64);
         }
         let slots: Vec<_> = (0_u64..MAX_CACHE_ENTRIES as u64 + 1).collect();
-        assert_eq!(status_cache.slot_deltas.len(), 1);
-        assert!(status_cache.slot_deltas.get(&1).is_some());
+        assert_eq!(status_cache.slot_deltas.read().unwrap().len(), 1);
+        assert!(status_cache.slot_deltas.read().unwrap().get(&1).is_some());
         let slot_deltas = status_cache.slot_deltas(&slots);
         let cache = StatusCache::from_slot_deltas(&slot_deltas);
         assert_eq!(cache, status_cache);
     }
 
+    #[test]
+    fn test_roots_purged_across_skipped_slots() {
+        let sig = Signature::default();
+        let mut status_cache = BankStatusCache::default();
+        let blockhash = hash(Hash::default().as_ref());
+        let ancestors = Ancestors::default();
+        status_cache.insert(&blockhash, &sig, 1, ());
+
+        // roots spread wider than the bit field still count as ancestors until purged
+        let roots: Vec<_> = (1..=MAX_CACHE_ENTRIES as u64)
+            .map(|i| i * (ROOTS_BIT_FIELD_WIDTH / 64))
+            .collect();
+        status_cache.add_root(1);
+        for root in &roots {
+            status_cache.add_root(*root);
+            if status_cache.roots().any(|root| root == 1) {
+                assert_eq!(
+                    status_cache.get_status(&sig, &blockhash, &ancestors),
+                    Some((1, ()))
+                );
+            }
+        }
+        assert_eq!(status_cache.roots().collect::<Vec<_>>(), roots);
+        assert_eq!(status_cache.get_status(&sig, &blockhash, &ancestors), None);
+    }
+
     #[test]
     #[allow(clippy::assertions_on_constants)]
     fn test_age_sanity() {
@@ -500,26 +692,55 @@ mod tests {
 
         // Check that the slot delta for slot 0 is gone, but slot 1 still
         // exists
-        assert!(status_cache.slot_deltas.get(&0).is_none());
-        assert!(status_cache.slot_deltas.get(&1).is_some());
+        assert!(status_cache.slot_deltas.read().unwrap().get(&0).is_none());
+        assert!(status_cache.slot_deltas.read().unwrap().get(&1).is_some());
 
         // Clear slot 1 related data
         status_cache.clear_slot_entries(1);
-        assert!(status_cache.slot_deltas.is_empty());
+        assert!(status_cache.slot_deltas.read().unwrap().is_empty());
         assert!(status_cache
             .get_status(&sig, &blockhash, &ancestors1)
             .is_none());
         assert!(status_cache
             .get_status(&sig, &blockhash2, &ancestors1)
             .is_none());
-        assert!(status_cache.cache.is_empty());
+        assert!(status_cache
+            .shards
+            .iter()
+            .all(|shard| shard.read().unwrap().is_empty()));
+    }
+
+    #[test]
+    fn test_insert_batch() {
+        let status_cache = BankStatusCache::default();
+        let ancestors = vec![(1, 0)].into_iter().collect();
+        let blockhashes: Vec<_> = (0..NUM_SHARDS * 2).map(|_| Hash::new_unique()).collect();
+        let keys: Vec<_> = (0..NUM_SHARDS * 2).map(|_| Hash::new_unique()).collect();
+        status_cache.insert_batch(
+            1,
+            blockhashes
+                .iter()
+                .zip(keys.iter())
+                .map(|(blockhash, key)| (blockhash, key, ()))
+                .collect(),
+        );
+
+        for (blockhash, key) in blockhashes.iter().zip(keys.iter()) {
+            assert_eq!(
+                status_cache.get_status(key, blockhash, &ancestors),
+                Some((1, ()))
+            );
+        }
+        let slot_deltas = status_cache.slot_deltas(&[1]);
+        assert_eq!(slot_deltas[0].2.lock().unwrap().len(), blockhashes.len());
+        assert_eq!(StatusCache::from_slot_deltas(&slot_deltas), status_cache);
     }
 
     // Status cache uses a random key offset for each blockhash. Ensure that shorter
     // keys can still be used if the offset if greater than the key length.
     #[test]
     fn test_different_sized_keys() {
-        let mut status_cache = BankStatusCache::default();
+        let status_cache = BankStatusCache::default();
         let ancestors = vec![(0, 0)].into_iter().collect();
         let blockhash = Hash::default();
         for _ in 0..100 {
@@ -7,7 +7,7 @@ use solana_sdk::{
     keyed_account::{from_keyed_account, get_signers, keyed_account_at_index, KeyedAccount},
     nonce,
     nonce_keyed_account::NonceKeyedAccount,
-    process_instruction::InvokeContext,
+    process_instruction::{get_sysvar, InvokeContext},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     system_instruction::{NonceError, SystemError, SystemInstruction, MAX_PERMITTED_DATA_LENGTH},
@@ -169,6 +169,23 @@ fn create_account(
             return Err(SystemError::AccountAlreadyInUse.into());
         }
 
+        if lamports != 0
+            && invoke_context.is_feature_active(&feature_set::require_rent_exempt_accounts::id())
+        {
+            let rent = get_sysvar::<Rent>(invoke_context, &sysvar::rent::id())?;
+            let minimum_balance = rent.minimum_balance(space as usize);
+            if lamports < minimum_balance {
+                ic_msg!(
+                    invoke_context,
+                    "Create Account: account {:?} would pay rent, need {} lamports, got {}",
+                    to_address,
+                    minimum_balance,
+                    lamports
+                );
+                return Err(InstructionError::AccountNotRentExempt);
+            }
+        }
+
         allocate_and_assign(to, to_address, space, owner, signers, invoke_context)?;
     }
     transfer(from, to, lamports, invoke_context)
@@ -498,8 +515,8 @@ mod tests {
         system_instruction, system_program, sysvar,
         transaction::TransactionError,
     };
-    use std::cell::RefCell;
     use std::sync::Arc;
+    use std::{cell::RefCell, rc::Rc};
 
     impl From<Pubkey> for Address {
         fn from(address: Pubkey) -> Self {
@@ -524,10 +541,25 @@ mod tests {
         super::process_instruction(
             1,
             instruction_data,
-            &mut MockInvokeContext::new(owner, create_keyed_accounts_unified(&keyed_accounts)),
+            &mut MockInvokeContext {
+                disabled_features: vec![feature_set::require_rent_exempt_accounts::id()]
+                    .into_iter()
+                    .collect(),
+                ..MockInvokeContext::new(owner, create_keyed_accounts_unified(&keyed_accounts))
+            },
         )
     }
 
+    // Most tests exercise account creation without a rent sysvar, so they run
+    // without the rent-exemption requirement
+    fn create_invoke_context<'a>() -> MockInvokeContext<'a> {
+        let mut invoke_context = MockInvokeContext::new(&Pubkey::default(), vec![]);
+        invoke_context
+            .disabled_features
+            .insert(feature_set::require_rent_exempt_accounts::id());
+        invoke_context
+    }
+
     fn create_default_account() -> RefCell<AccountSharedData> {
         RefCell::new(AccountSharedData::default())
     }
@@ -574,6 +606,58 @@ mod tests {
         assert_eq!(to_account.borrow().data(), &[0, 0]);
     }
 
+    #[test]
+    fn test_create_account_require_rent_exempt() {
+        let new_owner = Pubkey::new(&[9; 32]);
+        let from = solana_sdk::pubkey::new_rand();
+        let from_account = AccountSharedData::new_ref(10_000, 0, &system_program::id());
+        let to = solana_sdk::pubkey::new_rand();
+        let signers = [from, to].iter().cloned().collect::<HashSet<_>>();
+        let rent = Rent {
+            lamports_per_byte_year: 1,
+            ..Rent::default()
+        };
+        let minimum_balance = rent.minimum_balance(2);
+        let invoke_context = MockInvokeContext::new(&Pubkey::default(), vec![]);
+        invoke_context
+            .sysvars
+            .borrow_mut()
+            .push((sysvar::rent::id(), Some(Rc::new(serialize(&rent).unwrap()))));
+
+        let to_account = AccountSharedData::new_ref(0, 0, &Pubkey::default());
+        assert_eq!(
+            create_account(
+                &KeyedAccount::new(&from, true, &from_account),
+                &KeyedAccount::new(&to, false, &to_account),
+                &to.into(),
+                minimum_balance - 1,
+                2,
+                &new_owner,
+                &signers,
+                &invoke_context,
+            ),
+            Err(InstructionError::AccountNotRentExempt)
+        );
+        assert_eq!(from_account.borrow().lamports(), 10_000);
+        assert_eq!(to_account.borrow().data(), &[] as &[u8]);
+
+        assert_eq!(
+            create_account(
+                &KeyedAccount::new(&from, true, &from_account),
+                &KeyedAccount::new(&to, false, &to_account),
+                &to.into(),
+                minimum_balance,
+                2,
+                &new_owner,
+                &signers,
+                &invoke_context,
+            ),
+            Ok(())
+        );
+        assert_eq!(to_account.borrow().lamports(), minimum_balance);
+        assert_eq!(to_account.borrow().data(), &[0, 0]);
+    }
+
     #[test]
     fn test_create_account_with_seed() {
         let new_owner = Pubkey::new(&[9; 32]);
@@ -651,11 +735,7 @@ mod tests {
         let owner = solana_sdk::pubkey::new_rand();
 
         assert_eq!(
-            Address::create(
-                &to,
-                Some((&from, seed, &owner)),
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
-            ),
+            Address::create(&to, Some((&from, seed, &owner)), &create_invoke_context()),
             Err(SystemError::AddressWithSeedMismatch.into())
         );
     }
@@ -672,7 +752,7 @@ mod tests {
         let to_address = Address::create(
             &to,
             Some((&from, seed, &new_owner)),
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         )
         .unwrap();
 
@@ -685,7 +765,7 @@ mod tests {
                 2,
                 &new_owner,
                 &HashSet::new(),
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -712,7 +792,7 @@ mod tests {
                 2,
                 &new_owner,
                 &[from, to].iter().cloned().collect::<HashSet<_>>(),
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Ok(())
         );
@@ -744,7 +824,7 @@ mod tests {
             2,
             &new_owner,
             &[from, to].iter().cloned().collect::<HashSet<_>>(),
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::ResultWithNegativeLamports.into()));
     }
@@ -768,7 +848,7 @@ mod tests {
             MAX_PERMITTED_DATA_LENGTH + 1,
             &system_program::id(),
             signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert!(result.is_err());
         assert_eq!(
@@ -785,7 +865,7 @@ mod tests {
             MAX_PERMITTED_DATA_LENGTH,
             &system_program::id(),
             signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert!(result.is_ok());
         assert_eq!(to_account.borrow().lamports(), 50);
@@ -818,7 +898,7 @@ mod tests {
             2,
             &new_owner,
             signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::AccountAlreadyInUse.into()));
 
@@ -837,7 +917,7 @@ mod tests {
             2,
             &new_owner,
             signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::AccountAlreadyInUse.into()));
         let from_lamports = from_account.borrow().lamports();
@@ -855,7 +935,7 @@ mod tests {
             2,
             &new_owner,
             signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::AccountAlreadyInUse.into()));
         assert_eq!(from_lamports, 100);
@@ -883,7 +963,7 @@ mod tests {
             2,
             &new_owner,
             &[owned_key].iter().cloned().collect::<HashSet<_>>(),
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(InstructionError::MissingRequiredSignature));
 
@@ -897,7 +977,7 @@ mod tests {
             2,
             &new_owner,
             &[from].iter().cloned().collect::<HashSet<_>>(),
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(InstructionError::MissingRequiredSignature));
 
@@ -911,7 +991,7 @@ mod tests {
             2,
             &new_owner,
             &[owned_key].iter().cloned().collect::<HashSet<_>>(),
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(InstructionError::MissingRequiredSignature));
     }
@@ -937,7 +1017,7 @@ mod tests {
             2,
             &sysvar::id(),
             &signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Ok(()));
     }
@@ -963,9 +1043,12 @@ mod tests {
             &sysvar::id(),
             &signers,
             &MockInvokeContext {
-                disabled_features: vec![feature_set::rent_for_sysvars::id()]
-                    .into_iter()
-                    .collect(),
+                disabled_features: vec![
+                    feature_set::rent_for_sysvars::id(),
+                    feature_set::require_rent_exempt_accounts::id(),
+                ]
+                .into_iter()
+                .collect(),
                 ..MockInvokeContext::new(&Pubkey::default(), vec![])
             },
         );
@@ -1000,7 +1083,7 @@ mod tests {
             2,
             &new_owner,
             &signers,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::AccountAlreadyInUse.into()));
     }
@@ -1034,7 +1117,7 @@ mod tests {
                 0,
                 &solana_sdk::pubkey::new_rand(),
                 &signers,
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Err(InstructionError::InvalidArgument),
         );
@@ -1052,7 +1135,7 @@ mod tests {
                 &pubkey.into(),
                 &new_owner,
                 &HashSet::new(),
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -1064,7 +1147,7 @@ mod tests {
                 &pubkey.into(),
                 &system_program::id(),
                 &HashSet::new(),
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Ok(())
         );
@@ -1093,7 +1176,7 @@ mod tests {
                 &from.into(),
                 &new_owner,
                 &[from].iter().cloned().collect::<HashSet<_>>(),
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Ok(())
         );
@@ -1113,9 +1196,12 @@ mod tests {
                 &new_owner,
                 &[from].iter().cloned().collect::<HashSet<_>>(),
                 &MockInvokeContext {
-                    disabled_features: vec![feature_set::rent_for_sysvars::id()]
-                        .into_iter()
-                        .collect(),
+                    disabled_features: vec![
+                        feature_set::rent_for_sysvars::id(),
+                        feature_set::require_rent_exempt_accounts::id(),
+                    ]
+                    .into_iter()
+                    .collect(),
                     ..MockInvokeContext::new(&Pubkey::default(), vec![])
                 },
             ),
@@ -1158,7 +1244,7 @@ mod tests {
             &from_keyed_account,
             &to_keyed_account,
             50,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         )
         .unwrap();
         let from_lamports = from_keyed_account.account.borrow().lamports();
@@ -1172,7 +1258,7 @@ mod tests {
             &from_keyed_account,
             &to_keyed_account,
             100,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::ResultWithNegativeLamports.into()));
         assert_eq!(from_keyed_account.account.borrow().lamports(), 50);
@@ -1183,7 +1269,7 @@ mod tests {
             &from_keyed_account,
             &to_keyed_account,
             0,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         )
         .is_ok());
         assert_eq!(from_keyed_account.account.borrow().lamports(), 50);
@@ -1197,7 +1283,7 @@ mod tests {
                 &from_keyed_account,
                 &to_keyed_account,
                 0,
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Err(InstructionError::MissingRequiredSignature)
         );
@@ -1225,7 +1311,7 @@ mod tests {
             &from_owner,
             &to_keyed_account,
             50,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         )
         .unwrap();
         let from_lamports = from_keyed_account.account.borrow().lamports();
@@ -1242,7 +1328,7 @@ mod tests {
             &from_owner,
             &to_keyed_account,
             100,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         );
         assert_eq!(result, Err(SystemError::ResultWithNegativeLamports.into()));
         assert_eq!(from_keyed_account.account.borrow().lamports(), 50);
@@ -1257,7 +1343,7 @@ mod tests {
             &from_owner,
             &to_keyed_account,
             0,
-            &MockInvokeContext::new(&Pubkey::default(), vec![]),
+            &create_invoke_context(),
         )
         .is_ok());
         assert_eq!(from_keyed_account.account.borrow().lamports(), 50);
@@ -1288,7 +1374,7 @@ mod tests {
                 &KeyedAccount::new(&from, true, &from_account),
                 &KeyedAccount::new(&to, false, &to_account),
                 50,
-                &MockInvokeContext::new(&Pubkey::default(), vec![]),
+                &create_invoke_context(),
             ),
             Err(InstructionError::InvalidArgument),
         )
@@ -1,7 +1,8 @@
 use crossbeam_channel::{Receiver, Sender};
-use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, transaction::Transaction};
 use solana_vote_program::vote_state::Vote;
 
-pub type ReplayedVote = (Pubkey, Vote, Option<Hash>);
+/// A vote landed in a bank, along with the vote transaction itself
+pub type ReplayedVote = (Pubkey, Vote, Option<Hash>, Transaction);
 pub type ReplayVoteSender = Sender<ReplayedVote>;
 pub type ReplayVoteReceiver = Receiver<ReplayedVote>;
@@ -1,5 +1,6 @@
 use crate::{
-    clock::Epoch, program_error::ProgramError, program_memory::sol_memset, pubkey::Pubkey,
+    clock::Epoch, entrypoint::MAX_PERMITTED_DATA_INCREASE, program_error::ProgramError,
+    program_memory::sol_memset, pubkey::Pubkey,
 };
 use std::{
     cell::{Ref, RefCell, RefMut},
@@ -116,11 +117,32 @@ impl<'a> AccountInfo<'a> {
             .map_err(|_| ProgramError::AccountBorrowFailed)
     }
 
+    /// Return the account's original data length when it was serialized for the
+    /// current program invocation.
+    ///
+    /// # Safety
+    ///
+    /// This method assumes that the original data length was serialized as a u32
+    /// integer in the 4 bytes immediately preceding the serialized account key.
+    pub unsafe fn original_data_len(&self) -> usize {
+        let key_ptr = self.key as *const _ as *const u8;
+        let original_data_len_ptr = key_ptr.offset(-4) as *const u32;
+        *original_data_len_ptr as usize
+    }
+
     /// Realloc the account's data and optionally zero-initialize the new
     /// memory.
     ///
     /// Note:  Account data can be increased within a single call by up to
-    /// `solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE` bytes.
+    /// `solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE` bytes beyond
+    /// the data length the account had on entry, otherwise
+    /// `ProgramError::InvalidRealloc` is returned.
+    ///
+    /// Note: Realloc doesn't charge rent for the new size, the program must
+    /// fund the account.  Once the `require_rent_exempt_accounts` feature is
+    /// active, the runtime fails transactions that leave a resized account
+    /// paying rent, unless it was already paying rent before them and did not
+    /// grow.
     ///
     /// Note: Memory used to grow is already zero-initialized upon program
     /// entrypoint and re-zeroing it wastes compute units.  If within the same
@@ -130,6 +152,18 @@ impl<'a> AccountInfo<'a> {
     pub fn realloc(&self, new_len: usize, zero_init: bool) -> Result<(), ProgramError> {
         let orig_len = self.data_len();
 
+        // Return early if length hasn't changed
+        if new_len == orig_len {
+            return Ok(());
+        }
+
+        // Return early if the length increase from the original serialized data
+        // length is too large and would result in an out of bounds allocation.
+        let original_data_len = unsafe { self.original_data_len() };
+        if new_len.saturating_sub(original_data_len) > MAX_PERMITTED_DATA_INCREASE {
+            return Err(ProgramError::InvalidRealloc);
+        }
+
         // realloc
         unsafe {
             // First set new length in the serialized data
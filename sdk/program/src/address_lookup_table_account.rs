@@ -0,0 +1,12 @@
+//! The definition of an address lookup table account, as consumed by message
+//! compilation.
+
+use crate::pubkey::Pubkey;
+
+/// The addresses stored in an address lookup table, along with the address of
+/// the table account itself.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AddressLookupTableAccount {
+    pub key: Pubkey,
+    pub addresses: Vec<Pubkey>,
+}
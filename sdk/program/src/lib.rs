@@ -6,6 +6,7 @@
 extern crate self as solana_program;
 
 pub mod account_info;
+pub mod address_lookup_table_account;
 pub mod blake3;
 pub mod borsh;
 pub mod bpf_loader;
@@ -63,6 +64,10 @@ pub mod vote {
     }
 }
 
+pub mod zk_token_proof_program {
+    crate::declare_id!("ZkTokenProof1111111111111111111111111111111");
+}
+
 /// Same as `declare_id` except report that this id has been deprecated
 pub use solana_sdk_macro::program_declare_deprecated_id as declare_deprecated_id;
 /// Convenience macro to declare a static public key and functions to interact with it
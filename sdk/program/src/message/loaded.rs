@@ -1,41 +1,53 @@
 use {
     crate::{
         bpf_loader_upgradeable,
-        message::{legacy::BUILTIN_PROGRAMS_KEYS, v0},
+        instruction::CompiledInstruction,
+        message::{legacy::BUILTIN_PROGRAMS_KEYS, v0, Message, MessageHeader},
         pubkey::Pubkey,
         sysvar,
     },
-    std::{collections::HashSet, convert::TryFrom},
+    std::{collections::HashSet, convert::TryFrom, iter::FromIterator},
 };
 
-/// Combination of a version #0 message and its mapped addresses
+/// Combination of a version #0 message and its loaded addresses
 #[derive(Debug, Clone)]
-pub struct MappedMessage {
-    /// Message which loaded a collection of mapped addresses
+pub struct LoadedMessage {
+    /// Message which loaded a collection of lookup table addresses
     pub message: v0::Message,
-    /// Collection of mapped addresses loaded by this message
-    pub mapped_addresses: MappedAddresses,
+    /// Addresses loaded with on-chain address lookup tables
+    pub loaded_addresses: LoadedAddresses,
 }
 
-/// Collection of mapped addresses loaded succinctly by a transaction using
-/// on-chain address map accounts.
+/// Collection of addresses loaded succinctly by a transaction using
+/// on-chain address lookup table accounts.
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
-pub struct MappedAddresses {
+pub struct LoadedAddresses {
     /// List of addresses for writable loaded accounts
     pub writable: Vec<Pubkey>,
     /// List of addresses for read-only loaded accounts
     pub readonly: Vec<Pubkey>,
 }
 
-impl MappedMessage {
+impl FromIterator<LoadedAddresses> for LoadedAddresses {
+    fn from_iter<T: IntoIterator<Item = LoadedAddresses>>(iter: T) -> Self {
+        let mut loaded_addresses = LoadedAddresses::default();
+        for addresses in iter {
+            loaded_addresses.writable.extend(addresses.writable);
+            loaded_addresses.readonly.extend(addresses.readonly);
+        }
+        loaded_addresses
+    }
+}
+
+impl LoadedMessage {
     /// Returns an iterator of account key segments. The ordering of segments
     /// affects how account indexes from compiled instructions are resolved and
     /// so should not be changed.
     fn account_keys_segment_iter(&self) -> impl Iterator<Item = &Vec<Pubkey>> {
         vec![
             &self.message.account_keys,
-            &self.mapped_addresses.writable,
-            &self.mapped_addresses.readonly,
+            &self.loaded_addresses.writable,
+            &self.loaded_addresses.readonly,
         ]
         .into_iter()
     }
@@ -61,8 +73,8 @@ impl MappedMessage {
     }
 
     /// Returns the address of the account at the specified index of the list of
-    /// message account keys constructed from unmapped keys, followed by mapped
-    /// writable addresses, and lastly the list of mapped readonly addresses.
+    /// message account keys constructed from static keys, followed by loaded
+    /// writable addresses, and lastly the list of loaded readonly addresses.
     pub fn get_account_key(&self, mut index: usize) -> Option<&Pubkey> {
         for key_segment in self.account_keys_segment_iter() {
             if index < key_segment.len() {
@@ -81,8 +93,8 @@ impl MappedMessage {
         let num_account_keys = self.message.account_keys.len();
         let num_signed_accounts = usize::from(header.num_required_signatures);
         if key_index >= num_account_keys {
-            let mapped_addresses_index = key_index.saturating_sub(num_account_keys);
-            mapped_addresses_index < self.mapped_addresses.writable.len()
+            let loaded_addresses_index = key_index.saturating_sub(num_account_keys);
+            loaded_addresses_index < self.loaded_addresses.writable.len()
         } else if key_index >= num_signed_accounts {
             let num_unsigned_accounts = num_account_keys.saturating_sub(num_signed_accounts);
             let num_writable_unsigned_accounts = num_unsigned_accounts
@@ -127,6 +139,62 @@ impl MappedMessage {
         self.account_keys_iter()
             .any(|&key| key == bpf_loader_upgradeable::id())
     }
+
+    /// Returns a legacy message with the same signers, writable accounts and
+    /// instructions as this message, for processors which only support the
+    /// legacy format. Loaded writable addresses are moved in front of the
+    /// static read-only unsigned keys so that the legacy header can describe
+    /// them, and the returned positions map each legacy account index to its
+    /// index in this message.
+    pub fn to_legacy_message(&self) -> (Message, Vec<usize>) {
+        let header = &self.message.header;
+        let num_static_keys = self.message.account_keys.len();
+        let num_static_writable_keys =
+            num_static_keys.saturating_sub(usize::from(header.num_readonly_unsigned_accounts));
+        let num_loaded_writable_keys = self.loaded_addresses.writable.len();
+        let num_static_and_loaded_writable_keys =
+            num_static_keys.saturating_add(num_loaded_writable_keys);
+
+        let positions: Vec<usize> = (0..num_static_writable_keys)
+            .chain(num_static_keys..num_static_and_loaded_writable_keys)
+            .chain(num_static_writable_keys..num_static_keys)
+            .chain(num_static_and_loaded_writable_keys..self.account_keys_len())
+            .collect();
+        let mut legacy_indexes = vec![0u8; positions.len()];
+        for (legacy_index, position) in positions.iter().enumerate() {
+            legacy_indexes[*position] = legacy_index as u8;
+        }
+
+        let message = Message {
+            header: MessageHeader {
+                num_readonly_unsigned_accounts: header
+                    .num_readonly_unsigned_accounts
+                    .saturating_add(self.loaded_addresses.readonly.len() as u8),
+                ..header.clone()
+            },
+            account_keys: positions
+                .iter()
+                .filter_map(|position| self.get_account_key(*position).copied())
+                .collect(),
+            recent_blockhash: self.message.recent_blockhash,
+            instructions: self
+                .message
+                .instructions
+                .iter()
+                .map(|ix| CompiledInstruction {
+                    program_id_index: legacy_indexes[usize::from(ix.program_id_index)],
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|index| legacy_indexes[usize::from(*index)])
+                        .collect(),
+                    data: ix.data.clone(),
+                })
+                .collect(),
+        };
+
+        (message, positions)
+    }
 }
 
 #[cfg(test)]
@@ -135,7 +203,7 @@ mod tests {
     use crate::{instruction::CompiledInstruction, message::MessageHeader, system_program, sysvar};
     use itertools::Itertools;
 
-    fn create_test_mapped_message() -> (MappedMessage, [Pubkey; 6]) {
+    fn create_test_loaded_message() -> (LoadedMessage, [Pubkey; 6]) {
         let key0 = Pubkey::new_unique();
         let key1 = Pubkey::new_unique();
         let key2 = Pubkey::new_unique();
@@ -143,7 +211,7 @@ mod tests {
         let key4 = Pubkey::new_unique();
         let key5 = Pubkey::new_unique();
 
-        let message = MappedMessage {
+        let message = LoadedMessage {
             message: v0::Message {
                 header: MessageHeader {
                     num_required_signatures: 2,
@@ -153,7 +221,7 @@ mod tests {
                 account_keys: vec![key0, key1, key2, key3],
                 ..v0::Message::default()
             },
-            mapped_addresses: MappedAddresses {
+            loaded_addresses: LoadedAddresses {
                 writable: vec![key4],
                 readonly: vec![key5],
             },
@@ -164,7 +232,7 @@ mod tests {
 
     #[test]
     fn test_account_keys_segment_iter() {
-        let (message, keys) = create_test_mapped_message();
+        let (message, keys) = create_test_loaded_message();
 
         let expected_segments = vec![
             vec![keys[0], keys[1], keys[2], keys[3]],
@@ -180,14 +248,14 @@ mod tests {
 
     #[test]
     fn test_account_keys_len() {
-        let (message, keys) = create_test_mapped_message();
+        let (message, keys) = create_test_loaded_message();
 
         assert_eq!(message.account_keys_len(), keys.len());
     }
 
     #[test]
     fn test_account_keys_iter() {
-        let (message, keys) = create_test_mapped_message();
+        let (message, keys) = create_test_loaded_message();
 
         let mut iter = message.account_keys_iter();
         for expected_key in keys {
@@ -197,19 +265,19 @@ mod tests {
 
     #[test]
     fn test_has_duplicates() {
-        let message = create_test_mapped_message().0;
+        let message = create_test_loaded_message().0;
 
         assert!(!message.has_duplicates());
     }
 
     #[test]
     fn test_has_duplicates_with_dupe_keys() {
-        let create_message_with_dupe_keys = |mut keys: Vec<Pubkey>| MappedMessage {
+        let create_message_with_dupe_keys = |mut keys: Vec<Pubkey>| LoadedMessage {
             message: v0::Message {
                 account_keys: keys.split_off(2),
                 ..v0::Message::default()
             },
-            mapped_addresses: MappedAddresses {
+            loaded_addresses: LoadedAddresses {
                 writable: keys.split_off(2),
                 readonly: keys,
             },
@@ -231,7 +299,7 @@ mod tests {
 
     #[test]
     fn test_get_account_key() {
-        let (message, keys) = create_test_mapped_message();
+        let (message, keys) = create_test_loaded_message();
 
         assert_eq!(message.get_account_key(0), Some(&keys[0]));
         assert_eq!(message.get_account_key(1), Some(&keys[1]));
@@ -243,7 +311,7 @@ mod tests {
 
     #[test]
     fn test_is_writable_index() {
-        let message = create_test_mapped_message().0;
+        let message = create_test_loaded_message().0;
 
         assert!(message.is_writable_index(0));
         assert!(!message.is_writable_index(1));
@@ -255,15 +323,50 @@ mod tests {
 
     #[test]
     fn test_is_writable() {
-        let mut mapped_msg = create_test_mapped_message().0;
+        let mut loaded_msg = create_test_loaded_message().0;
 
-        mapped_msg.message.account_keys[0] = sysvar::clock::id();
-        assert!(mapped_msg.is_writable_index(0));
-        assert!(!mapped_msg.is_writable(0, /*demote_program_write_locks=*/ true));
+        loaded_msg.message.account_keys[0] = sysvar::clock::id();
+        assert!(loaded_msg.is_writable_index(0));
+        assert!(!loaded_msg.is_writable(0, /*demote_program_write_locks=*/ true));
 
-        mapped_msg.message.account_keys[0] = system_program::id();
-        assert!(mapped_msg.is_writable_index(0));
-        assert!(!mapped_msg.is_writable(0, /*demote_program_write_locks=*/ true));
+        loaded_msg.message.account_keys[0] = system_program::id();
+        assert!(loaded_msg.is_writable_index(0));
+        assert!(!loaded_msg.is_writable(0, /*demote_program_write_locks=*/ true));
+    }
+
+    #[test]
+    fn test_to_legacy_message() {
+        let (mut loaded_msg, keys) = create_test_loaded_message();
+        loaded_msg.message.instructions = vec![CompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![4, 5, 0],
+            data: vec![1, 2, 3],
+        }];
+
+        let (legacy_message, positions) = loaded_msg.to_legacy_message();
+        assert_eq!(positions, vec![0, 1, 2, 4, 3, 5]);
+        assert_eq!(
+            legacy_message.account_keys,
+            vec![keys[0], keys[1], keys[2], keys[4], keys[3], keys[5]]
+        );
+        assert_eq!(
+            legacy_message.instructions,
+            vec![CompiledInstruction {
+                program_id_index: 4,
+                accounts: vec![3, 5, 0],
+                data: vec![1, 2, 3],
+            }]
+        );
+        for (legacy_index, position) in positions.into_iter().enumerate() {
+            assert_eq!(
+                legacy_message.is_signer(legacy_index),
+                position < usize::from(loaded_msg.message.header.num_required_signatures)
+            );
+            assert_eq!(
+                legacy_message.is_writable(legacy_index, /*demote_program_write_locks=*/ true),
+                loaded_msg.is_writable(position, /*demote_program_write_locks=*/ true),
+            );
+        }
     }
 
     #[test]
@@ -271,7 +374,7 @@ mod tests {
         let key0 = Pubkey::new_unique();
         let key1 = Pubkey::new_unique();
         let key2 = Pubkey::new_unique();
-        let mapped_msg = MappedMessage {
+        let loaded_msg = LoadedMessage {
             message: v0::Message {
                 header: MessageHeader {
                     num_required_signatures: 1,
@@ -288,13 +391,13 @@ mod tests {
                 ],
                 ..v0::Message::default()
             },
-            mapped_addresses: MappedAddresses {
+            loaded_addresses: LoadedAddresses {
                 writable: vec![key1, key2],
                 readonly: vec![],
             },
         };
 
-        assert!(mapped_msg.is_writable_index(2));
-        assert!(!mapped_msg.is_writable(2, /*demote_program_write_locks=*/ true));
+        assert!(loaded_msg.is_writable_index(2));
+        assert!(!loaded_msg.is_writable(2, /*demote_program_write_locks=*/ true));
     }
 }
@@ -5,12 +5,12 @@ pub mod legacy;
 #[cfg(not(target_arch = "bpf"))]
 #[path = ""]
 mod non_bpf_modules {
-    mod mapped;
+    mod loaded;
     mod sanitized;
     pub mod v0;
     mod versions;
 
-    pub use mapped::*;
+    pub use loaded::*;
     pub use sanitized::*;
     pub use versions::*;
 }
@@ -2,7 +2,7 @@ use {
     crate::{
         hash::Hash,
         instruction::{CompiledInstruction, Instruction},
-        message::{MappedAddresses, MappedMessage, Message, MessageHeader},
+        message::{LoadedAddresses, LoadedMessage, Message, MessageHeader},
         pubkey::Pubkey,
         sanitize::{Sanitize, SanitizeError},
         serialize_utils::{append_slice, append_u16, append_u8},
@@ -18,8 +18,8 @@ use {
 pub enum SanitizedMessage {
     /// Sanitized legacy message
     Legacy(Message),
-    /// Sanitized version #0 message with mapped addresses
-    V0(MappedMessage),
+    /// Sanitized version #0 message with dynamically loaded addresses
+    V0(LoadedMessage),
 }
 
 #[derive(PartialEq, Debug, Error, Eq, Clone)]
@@ -80,7 +80,7 @@ impl SanitizedMessage {
     pub fn header(&self) -> &MessageHeader {
         match self {
             Self::Legacy(message) => &message.header,
-            Self::V0(mapped_msg) => &mapped_msg.message.header,
+            Self::V0(loaded_msg) => &loaded_msg.message.header,
         }
     }
 
@@ -103,7 +103,7 @@ impl SanitizedMessage {
     pub fn recent_blockhash(&self) -> &Hash {
         match self {
             Self::Legacy(message) => &message.recent_blockhash,
-            Self::V0(mapped_msg) => &mapped_msg.message.recent_blockhash,
+            Self::V0(loaded_msg) => &loaded_msg.message.recent_blockhash,
         }
     }
 
@@ -112,7 +112,7 @@ impl SanitizedMessage {
     pub fn instructions(&self) -> &[CompiledInstruction] {
         match self {
             Self::Legacy(message) => &message.instructions,
-            Self::V0(mapped_msg) => &mapped_msg.message.instructions,
+            Self::V0(loaded_msg) => &loaded_msg.message.instructions,
         }
     }
 
@@ -123,7 +123,7 @@ impl SanitizedMessage {
     ) -> impl Iterator<Item = (&Pubkey, &CompiledInstruction)> {
         match self {
             Self::Legacy(message) => message.instructions.iter(),
-            Self::V0(mapped_msg) => mapped_msg.message.instructions.iter(),
+            Self::V0(loaded_msg) => loaded_msg.message.instructions.iter(),
         }
         .map(move |ix| {
             (
@@ -134,19 +134,19 @@ impl SanitizedMessage {
         })
     }
 
-    /// Iterator of all account keys referenced in this message, included mapped keys.
+    /// Iterator of all account keys referenced in this message, included dynamically loaded keys.
     pub fn account_keys_iter(&self) -> Box<dyn Iterator<Item = &Pubkey> + '_> {
         match self {
             Self::Legacy(message) => Box::new(message.account_keys.iter()),
-            Self::V0(mapped_msg) => Box::new(mapped_msg.account_keys_iter()),
+            Self::V0(loaded_msg) => Box::new(loaded_msg.account_keys_iter()),
         }
     }
 
-    /// Length of all account keys referenced in this message, included mapped keys.
+    /// Length of all account keys referenced in this message, included dynamically loaded keys.
     pub fn account_keys_len(&self) -> usize {
         match self {
             Self::Legacy(message) => message.account_keys.len(),
-            Self::V0(mapped_msg) => mapped_msg.account_keys_len(),
+            Self::V0(loaded_msg) => loaded_msg.account_keys_len(),
         }
     }
 
@@ -251,21 +251,21 @@ impl SanitizedMessage {
         data
     }
 
-    /// Return the mapped addresses for this message if it has any.
-    fn mapped_addresses(&self) -> Option<&MappedAddresses> {
+    /// Return the dynamically loaded addresses for this message if it has any.
+    pub fn loaded_addresses(&self) -> Option<&LoadedAddresses> {
         match &self {
-            SanitizedMessage::V0(message) => Some(&message.mapped_addresses),
+            SanitizedMessage::V0(message) => Some(&message.loaded_addresses),
             _ => None,
         }
     }
 
     /// Return the number of readonly accounts loaded by this message.
     pub fn num_readonly_accounts(&self) -> usize {
-        let mapped_readonly_addresses = self
-            .mapped_addresses()
+        let loaded_readonly_addresses = self
+            .loaded_addresses()
             .map(|keys| keys.readonly.len())
             .unwrap_or_default();
-        mapped_readonly_addresses
+        loaded_readonly_addresses
             .saturating_add(usize::from(self.header().num_readonly_signed_accounts))
             .saturating_add(usize::from(self.header().num_readonly_unsigned_accounts))
     }
@@ -381,7 +381,7 @@ mod tests {
 
         assert_eq!(legacy_message.num_readonly_accounts(), 2);
 
-        let mapped_message = SanitizedMessage::V0(MappedMessage {
+        let loaded_message = SanitizedMessage::V0(LoadedMessage {
             message: v0::Message {
                 header: MessageHeader {
                     num_required_signatures: 2,
@@ -391,13 +391,13 @@ mod tests {
                 account_keys: vec![key0, key1, key2, key3],
                 ..v0::Message::default()
             },
-            mapped_addresses: MappedAddresses {
+            loaded_addresses: LoadedAddresses {
                 writable: vec![key4],
                 readonly: vec![key5],
             },
         });
 
-        assert_eq!(mapped_message.num_readonly_accounts(), 3);
+        assert_eq!(loaded_message.num_readonly_accounts(), 3);
     }
 
     #[test]
@@ -490,7 +490,7 @@ mod tests {
         })
         .unwrap();
 
-        let mapped_message = SanitizedMessage::V0(MappedMessage {
+        let loaded_message = SanitizedMessage::V0(LoadedMessage {
             message: v0::Message {
                 header: MessageHeader {
                     num_required_signatures: 1,
@@ -500,13 +500,13 @@ mod tests {
                 account_keys: vec![key0, key1],
                 ..v0::Message::default()
             },
-            mapped_addresses: MappedAddresses {
+            loaded_addresses: LoadedAddresses {
                 writable: vec![key2],
                 readonly: vec![program_id],
             },
         });
 
-        for message in vec![legacy_message, mapped_message] {
+        for message in vec![legacy_message, loaded_message] {
             assert_eq!(
                 message.try_compile_instruction(&valid_instruction),
                 Some(CompiledInstruction {
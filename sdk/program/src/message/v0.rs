@@ -1,25 +1,34 @@
-use crate::{
-    hash::Hash,
-    instruction::CompiledInstruction,
-    message::{MessageHeader, MESSAGE_VERSION_PREFIX},
-    pubkey::Pubkey,
-    sanitize::{Sanitize, SanitizeError},
-    short_vec,
+use {
+    crate::{
+        address_lookup_table_account::AddressLookupTableAccount,
+        hash::Hash,
+        instruction::{CompiledInstruction, Instruction},
+        message::{MessageHeader, MESSAGE_VERSION_PREFIX},
+        pubkey::Pubkey,
+        sanitize::{Sanitize, SanitizeError},
+        short_vec,
+    },
+    std::{collections::BTreeMap, convert::TryFrom},
+    thiserror::Error,
 };
 
-/// Indexes that are mapped to addresses using an on-chain address map for
-/// succinctly loading readonly and writable accounts.
+/// Address table lookups describe an on-chain address lookup table to use
+/// for loading more readonly and writable accounts in a single tx.
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
 #[serde(rename_all = "camelCase")]
-pub struct AddressMapIndexes {
+pub struct MessageAddressTableLookup {
+    /// Address lookup table account key
+    pub account_key: Pubkey,
+    /// List of indexes used to load writable account addresses
     #[serde(with = "short_vec")]
-    pub writable: Vec<u8>,
+    pub writable_indexes: Vec<u8>,
+    /// List of indexes used to load readonly account addresses
     #[serde(with = "short_vec")]
-    pub readonly: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
 }
 
 /// Transaction message format which supports succinct account loading with
-/// indexes for on-chain address maps.
+/// on-chain address lookup tables.
 #[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
 #[serde(rename_all = "camelCase")]
 pub struct Message {
@@ -38,31 +47,32 @@ pub struct Message {
     ///
     /// # Notes
     ///
-    /// Account and program indexes will index into the list of addresses
-    /// constructed from the concatenation of `account_keys`, flattened list of
-    /// `writable` address map indexes, and the flattened `readonly` address
-    /// map indexes.
+    /// Program indexes must index into the list of message `account_keys` because
+    /// program id's cannot be dynamically loaded from a lookup table.
+    ///
+    /// Account indexes must index into the list of addresses
+    /// constructed from the concatenation of three key lists:
+    ///   1) message `account_keys`
+    ///   2) ordered list of keys loaded from `writable` lookup table indexes
+    ///   3) ordered list of keys loaded from `readonly` lookup table indexes
     #[serde(with = "short_vec")]
     pub instructions: Vec<CompiledInstruction>,
 
-    /// List of address map indexes used to succinctly load additional accounts
+    /// List of address table lookups used to load additional accounts
     /// for this transaction.
-    ///
-    /// # Notes
-    ///
-    /// The last `address_map_indexes.len()` accounts of the read-only unsigned
-    /// accounts are loaded as address maps.
     #[serde(with = "short_vec")]
-    pub address_map_indexes: Vec<AddressMapIndexes>,
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
 }
 
 impl Sanitize for Message {
     fn sanitize(&self) -> Result<(), SanitizeError> {
+        let num_static_account_keys = self.account_keys.len();
+
         // signing area and read-only non-signing area should not
         // overlap
         if usize::from(self.header.num_required_signatures)
             .saturating_add(usize::from(self.header.num_readonly_unsigned_accounts))
-            > self.account_keys.len()
+            > num_static_account_keys
         {
             return Err(SanitizeError::IndexOutOfBounds);
         }
@@ -72,35 +82,37 @@ impl Sanitize for Message {
             return Err(SanitizeError::IndexOutOfBounds);
         }
 
-        // there cannot be more address maps than read-only unsigned accounts.
-        let num_address_map_indexes = self.address_map_indexes.len();
-        if num_address_map_indexes > usize::from(self.header.num_readonly_unsigned_accounts) {
-            return Err(SanitizeError::IndexOutOfBounds);
-        }
-
-        // each map must load at least one entry
-        let mut num_loaded_accounts = self.account_keys.len();
-        for indexes in &self.address_map_indexes {
-            let num_loaded_map_entries = indexes
-                .writable
+        // each lookup table must be used to load at least one account
+        let mut num_dynamic_account_keys: usize = 0;
+        for lookup in &self.address_table_lookups {
+            let num_lookup_indexes = lookup
+                .writable_indexes
                 .len()
-                .saturating_add(indexes.readonly.len());
+                .saturating_add(lookup.readonly_indexes.len());
 
-            if num_loaded_map_entries == 0 {
+            if num_lookup_indexes == 0 {
                 return Err(SanitizeError::InvalidValue);
             }
 
-            num_loaded_accounts = num_loaded_accounts.saturating_add(num_loaded_map_entries);
+            num_dynamic_account_keys = num_dynamic_account_keys.saturating_add(num_lookup_indexes);
         }
 
-        // the number of loaded accounts must be <= 256 since account indices are
-        // encoded as `u8`
-        if num_loaded_accounts > 256 {
+        // this is redundant with the above sanitization checks which require
+        // that the header describes at least 1 RW fee-payer account
+        if num_static_account_keys == 0 {
+            return Err(SanitizeError::InvalidValue);
+        }
+
+        // the combined number of static and dynamic account keys must be <= 256
+        // since account indices are encoded as `u8`
+        let total_account_keys = num_static_account_keys.saturating_add(num_dynamic_account_keys);
+        if total_account_keys > 256 {
             return Err(SanitizeError::IndexOutOfBounds);
         }
 
         for ci in &self.instructions {
-            if usize::from(ci.program_id_index) >= num_loaded_accounts {
+            // program ids cannot be loaded from lookup tables
+            if usize::from(ci.program_id_index) >= num_static_account_keys {
                 return Err(SanitizeError::IndexOutOfBounds);
             }
             // A program cannot be a payer.
@@ -108,7 +120,7 @@ impl Sanitize for Message {
                 return Err(SanitizeError::IndexOutOfBounds);
             }
             for ai in &ci.accounts {
-                if usize::from(*ai) >= num_loaded_accounts {
+                if usize::from(*ai) >= total_account_keys {
                     return Err(SanitizeError::IndexOutOfBounds);
                 }
             }
@@ -118,7 +130,147 @@ impl Sanitize for Message {
     }
 }
 
+#[derive(PartialEq, Debug, Error, Eq, Clone)]
+pub enum CompileError {
+    #[error("account index overflowed during compilation")]
+    AccountIndexOverflow,
+    #[error("encountered unknown account key `{0}` during instruction compilation")]
+    UnknownInstructionKey(Pubkey),
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+struct CompiledKeyMeta {
+    is_signer: bool,
+    is_writable: bool,
+    is_invoked: bool,
+}
+
 impl Message {
+    /// Create a version #0 message from a list of instructions, loading any
+    /// non-signer, non-program account keys which can be found in the given
+    /// address lookup tables through table lookups.
+    pub fn try_compile(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<Self, CompileError> {
+        let mut key_meta_map = BTreeMap::<Pubkey, CompiledKeyMeta>::new();
+        for ix in instructions {
+            key_meta_map.entry(ix.program_id).or_default().is_invoked = true;
+            for account_meta in &ix.accounts {
+                let meta = key_meta_map.entry(account_meta.pubkey).or_default();
+                meta.is_signer |= account_meta.is_signer;
+                meta.is_writable |= account_meta.is_writable;
+            }
+        }
+        // the payer is always the first static key, as a writable signer
+        key_meta_map.remove(payer);
+
+        let mut address_table_lookups = vec![];
+        let mut loaded_writable_keys = vec![];
+        let mut loaded_readonly_keys = vec![];
+        for lookup_table_account in address_lookup_table_accounts {
+            let mut lookup = MessageAddressTableLookup {
+                account_key: lookup_table_account.key,
+                ..MessageAddressTableLookup::default()
+            };
+            for (index, address) in lookup_table_account.addresses.iter().enumerate() {
+                let index = match u8::try_from(index) {
+                    Ok(index) => index,
+                    Err(_) => break,
+                };
+                match key_meta_map.get(address) {
+                    Some(meta) if !meta.is_signer && !meta.is_invoked => {
+                        if meta.is_writable {
+                            lookup.writable_indexes.push(index);
+                            loaded_writable_keys.push(*address);
+                        } else {
+                            lookup.readonly_indexes.push(index);
+                            loaded_readonly_keys.push(*address);
+                        }
+                        key_meta_map.remove(address);
+                    }
+                    _ => {}
+                }
+            }
+            if !lookup.writable_indexes.is_empty() || !lookup.readonly_indexes.is_empty() {
+                address_table_lookups.push(lookup);
+            }
+        }
+
+        let filter_keys = |is_signer: bool, is_writable: bool| -> Vec<Pubkey> {
+            key_meta_map
+                .iter()
+                .filter(|(_, meta)| meta.is_signer == is_signer && meta.is_writable == is_writable)
+                .map(|(key, _)| *key)
+                .collect()
+        };
+        let writable_signer_keys = filter_keys(true, true);
+        let readonly_signer_keys = filter_keys(true, false);
+        let writable_non_signer_keys = filter_keys(false, true);
+        let readonly_non_signer_keys = filter_keys(false, false);
+
+        let num_required_signatures = writable_signer_keys
+            .len()
+            .saturating_add(readonly_signer_keys.len())
+            .saturating_add(1);
+        let header = MessageHeader {
+            num_required_signatures: u8::try_from(num_required_signatures)
+                .map_err(|_| CompileError::AccountIndexOverflow)?,
+            num_readonly_signed_accounts: u8::try_from(readonly_signer_keys.len())
+                .map_err(|_| CompileError::AccountIndexOverflow)?,
+            num_readonly_unsigned_accounts: u8::try_from(readonly_non_signer_keys.len())
+                .map_err(|_| CompileError::AccountIndexOverflow)?,
+        };
+
+        let account_keys: Vec<Pubkey> = std::iter::once(*payer)
+            .chain(writable_signer_keys)
+            .chain(readonly_signer_keys)
+            .chain(writable_non_signer_keys)
+            .chain(readonly_non_signer_keys)
+            .collect();
+
+        let all_keys: Vec<&Pubkey> = account_keys
+            .iter()
+            .chain(&loaded_writable_keys)
+            .chain(&loaded_readonly_keys)
+            .collect();
+        if all_keys.len() > 256 {
+            return Err(CompileError::AccountIndexOverflow);
+        }
+        let position = |key: &Pubkey| -> Result<u8, CompileError> {
+            all_keys
+                .iter()
+                .position(|k| *k == key)
+                .map(|index| index as u8)
+                .ok_or(CompileError::UnknownInstructionKey(*key))
+        };
+
+        let instructions = instructions
+            .iter()
+            .map(|ix| {
+                Ok(CompiledInstruction {
+                    program_id_index: position(&ix.program_id)?,
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|account_meta| position(&account_meta.pubkey))
+                        .collect::<Result<_, _>>()?,
+                    data: ix.data.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+            address_table_lookups,
+        })
+    }
+
     /// Serialize this message with a version #0 prefix using bincode encoding.
     pub fn serialize(&self) -> Vec<u8> {
         bincode::serialize(&(MESSAGE_VERSION_PREFIX, self)).unwrap()
@@ -127,8 +279,10 @@ impl Message {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::message::VersionedMessage;
+    use {
+        super::*,
+        crate::{instruction::AccountMeta, message::VersionedMessage},
+    };
 
     fn simple_message() -> Message {
         Message {
@@ -138,34 +292,37 @@ mod tests {
                 num_readonly_unsigned_accounts: 1,
             },
             account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
-            address_map_indexes: vec![AddressMapIndexes {
-                writable: vec![],
-                readonly: vec![0],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![],
+                readonly_indexes: vec![0],
             }],
             ..Message::default()
         }
     }
 
-    fn two_map_message() -> Message {
+    fn two_lookup_message() -> Message {
         Message {
             header: MessageHeader {
                 num_required_signatures: 1,
                 num_readonly_signed_accounts: 0,
-                num_readonly_unsigned_accounts: 2,
+                num_readonly_unsigned_accounts: 1,
             },
             account_keys: vec![
                 Pubkey::new_unique(),
                 Pubkey::new_unique(),
                 Pubkey::new_unique(),
             ],
-            address_map_indexes: vec![
-                AddressMapIndexes {
-                    writable: vec![1],
-                    readonly: vec![0],
+            address_table_lookups: vec![
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![1],
+                    readonly_indexes: vec![0],
                 },
-                AddressMapIndexes {
-                    writable: vec![0],
-                    readonly: vec![1],
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![1],
                 },
             ],
             ..Message::default()
@@ -176,7 +333,7 @@ mod tests {
     fn test_sanitize_account_indices() {
         assert!(Message {
             account_keys: (0..=u8::MAX).map(|_| Pubkey::new_unique()).collect(),
-            address_map_indexes: vec![],
+            address_table_lookups: vec![],
             instructions: vec![CompiledInstruction {
                 program_id_index: 1,
                 accounts: vec![u8::MAX],
@@ -189,7 +346,7 @@ mod tests {
 
         assert!(Message {
             account_keys: (0..u8::MAX).map(|_| Pubkey::new_unique()).collect(),
-            address_map_indexes: vec![],
+            address_table_lookups: vec![],
             instructions: vec![CompiledInstruction {
                 program_id_index: 1,
                 accounts: vec![u8::MAX],
@@ -225,14 +382,16 @@ mod tests {
         .is_err());
 
         assert!(Message {
-            address_map_indexes: vec![
-                AddressMapIndexes {
-                    writable: (0..200).step_by(2).collect(),
-                    readonly: (1..200).step_by(2).collect(),
+            address_table_lookups: vec![
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..200).step_by(2).collect(),
+                    readonly_indexes: (1..200).step_by(2).collect(),
                 },
-                AddressMapIndexes {
-                    writable: (0..53).step_by(2).collect(),
-                    readonly: (1..53).step_by(2).collect(),
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..53).step_by(2).collect(),
+                    readonly_indexes: (1..53).step_by(2).collect(),
                 },
             ],
             instructions: vec![CompiledInstruction {
@@ -240,20 +399,22 @@ mod tests {
                 accounts: vec![u8::MAX],
                 data: vec![],
             }],
-            ..two_map_message()
+            ..two_lookup_message()
         }
         .sanitize()
         .is_ok());
 
         assert!(Message {
-            address_map_indexes: vec![
-                AddressMapIndexes {
-                    writable: (0..200).step_by(2).collect(),
-                    readonly: (1..200).step_by(2).collect(),
+            address_table_lookups: vec![
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..200).step_by(2).collect(),
+                    readonly_indexes: (1..200).step_by(2).collect(),
                 },
-                AddressMapIndexes {
-                    writable: (0..52).step_by(2).collect(),
-                    readonly: (1..52).step_by(2).collect(),
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..52).step_by(2).collect(),
+                    readonly_indexes: (1..52).step_by(2).collect(),
                 },
             ],
             instructions: vec![CompiledInstruction {
@@ -261,7 +422,45 @@ mod tests {
                 accounts: vec![u8::MAX],
                 data: vec![],
             }],
-            ..two_map_message()
+            ..two_lookup_message()
+        }
+        .sanitize()
+        .is_err());
+    }
+
+    #[test]
+    fn test_sanitize_program_id_index() {
+        // program ids must be static account keys
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![],
+                data: vec![],
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_ok());
+
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![],
+                data: vec![],
+            }],
+            ..simple_message()
+        }
+        .sanitize()
+        .is_err());
+
+        // a program cannot be the payer
+        assert!(Message {
+            instructions: vec![CompiledInstruction {
+                program_id_index: 0,
+                accounts: vec![],
+                data: vec![],
+            }],
+            ..simple_message()
         }
         .sanitize()
         .is_err());
@@ -271,7 +470,7 @@ mod tests {
     fn test_sanitize_excessive_loaded_accounts() {
         assert!(Message {
             account_keys: (0..=u8::MAX).map(|_| Pubkey::new_unique()).collect(),
-            address_map_indexes: vec![],
+            address_table_lookups: vec![],
             ..simple_message()
         }
         .sanitize()
@@ -279,7 +478,7 @@ mod tests {
 
         assert!(Message {
             account_keys: (0..257).map(|_| Pubkey::new_unique()).collect(),
-            address_map_indexes: vec![],
+            address_table_lookups: vec![],
             ..simple_message()
         }
         .sanitize()
@@ -300,67 +499,49 @@ mod tests {
         .is_err());
 
         assert!(Message {
-            address_map_indexes: vec![
-                AddressMapIndexes {
-                    writable: (0..200).step_by(2).collect(),
-                    readonly: (1..200).step_by(2).collect(),
+            address_table_lookups: vec![
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..200).step_by(2).collect(),
+                    readonly_indexes: (1..200).step_by(2).collect(),
                 },
-                AddressMapIndexes {
-                    writable: (0..53).step_by(2).collect(),
-                    readonly: (1..53).step_by(2).collect(),
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..53).step_by(2).collect(),
+                    readonly_indexes: (1..53).step_by(2).collect(),
                 }
             ],
-            ..two_map_message()
+            ..two_lookup_message()
         }
         .sanitize()
         .is_ok());
 
         assert!(Message {
-            address_map_indexes: vec![
-                AddressMapIndexes {
-                    writable: (0..200).step_by(2).collect(),
-                    readonly: (1..200).step_by(2).collect(),
+            address_table_lookups: vec![
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..200).step_by(2).collect(),
+                    readonly_indexes: (1..200).step_by(2).collect(),
                 },
-                AddressMapIndexes {
-                    writable: (0..200).step_by(2).collect(),
-                    readonly: (1..200).step_by(2).collect(),
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: (0..200).step_by(2).collect(),
+                    readonly_indexes: (1..200).step_by(2).collect(),
                 }
             ],
-            ..two_map_message()
-        }
-        .sanitize()
-        .is_err());
-    }
-
-    #[test]
-    fn test_sanitize_excessive_maps() {
-        assert!(Message {
-            header: MessageHeader {
-                num_readonly_unsigned_accounts: 1,
-                ..simple_message().header
-            },
-            ..simple_message()
-        }
-        .sanitize()
-        .is_ok());
-
-        assert!(Message {
-            header: MessageHeader {
-                num_readonly_unsigned_accounts: 0,
-                ..simple_message().header
-            },
-            ..simple_message()
+            ..two_lookup_message()
         }
         .sanitize()
         .is_err());
     }
 
     #[test]
-    fn test_sanitize_address_map() {
+    fn test_sanitize_address_table_lookup() {
         assert!(Message {
-            address_map_indexes: vec![AddressMapIndexes {
-                writable: vec![0],
-                readonly: vec![],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
             }],
             ..simple_message()
         }
@@ -368,9 +549,10 @@ mod tests {
         .is_ok());
 
         assert!(Message {
-            address_map_indexes: vec![AddressMapIndexes {
-                writable: vec![],
-                readonly: vec![0],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![],
+                readonly_indexes: vec![0],
             }],
             ..simple_message()
         }
@@ -378,9 +560,10 @@ mod tests {
         .is_ok());
 
         assert!(Message {
-            address_map_indexes: vec![AddressMapIndexes {
-                writable: vec![],
-                readonly: vec![],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![],
+                readonly_indexes: vec![],
             }],
             ..simple_message()
         }
@@ -388,6 +571,67 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn test_try_compile() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let writable = Pubkey::new_unique();
+        let readonly = Pubkey::new_unique();
+        let unlisted = Pubkey::new_unique();
+        let recent_blockhash = Hash::new_unique();
+        let instructions = [Instruction::new_with_bincode(
+            program_id,
+            &0,
+            vec![
+                AccountMeta::new_readonly(signer, true),
+                AccountMeta::new(writable, false),
+                AccountMeta::new_readonly(readonly, false),
+                AccountMeta::new(unlisted, false),
+            ],
+        )];
+        let lookup_table_account = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![readonly, program_id, signer, writable],
+        };
+
+        let message = Message::try_compile(
+            &payer,
+            &instructions,
+            &[lookup_table_account.clone()],
+            recent_blockhash,
+        )
+        .unwrap();
+        assert!(message.sanitize().is_ok());
+        assert_eq!(
+            message,
+            Message {
+                header: MessageHeader {
+                    num_required_signatures: 2,
+                    num_readonly_signed_accounts: 1,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![payer, signer, unlisted, program_id],
+                recent_blockhash,
+                instructions: vec![CompiledInstruction {
+                    program_id_index: 3,
+                    accounts: vec![1, 4, 5, 2],
+                    data: vec![0, 0, 0, 0],
+                }],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: lookup_table_account.key,
+                    writable_indexes: vec![3],
+                    readonly_indexes: vec![0],
+                }],
+            }
+        );
+
+        // without lookup tables, all keys are static
+        let message = Message::try_compile(&payer, &instructions, &[], recent_blockhash).unwrap();
+        assert_eq!(message.account_keys.len(), 6);
+        assert!(message.address_table_lookups.is_empty());
+    }
+
     #[test]
     fn test_serialize() {
         let message = simple_message();
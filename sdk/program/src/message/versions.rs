@@ -26,7 +26,7 @@ pub const MESSAGE_VERSION_PREFIX: u8 = 0x80;
 /// which message version is serialized starting from version `0`. If the first
 /// is bit is not set, all bytes are used to encode the legacy `Message`
 /// format.
-#[frozen_abi(digest = "x2F3RG2RhJQWN6L2N3jebvcAvNYFrhE3sKTPJ4sENvL")]
+#[frozen_abi(digest = "G4EAiqmGgBprgf5ePYemLJcoFfx4R7rhC1Weo2FVJ7fn")]
 #[derive(Debug, PartialEq, Eq, Clone, AbiEnumVisitor, AbiExample)]
 pub enum VersionedMessage {
     Legacy(Message),
@@ -41,27 +41,35 @@ impl VersionedMessage {
         }
     }
 
-    pub fn unmapped_keys(self) -> Vec<Pubkey> {
+    pub fn static_account_keys(self) -> Vec<Pubkey> {
         match self {
             Self::Legacy(message) => message.account_keys,
             Self::V0(message) => message.account_keys,
         }
     }
 
-    pub fn unmapped_keys_iter(&self) -> impl Iterator<Item = &Pubkey> {
+    pub fn static_account_keys_iter(&self) -> impl Iterator<Item = &Pubkey> {
         match self {
             Self::Legacy(message) => message.account_keys.iter(),
             Self::V0(message) => message.account_keys.iter(),
         }
     }
 
-    pub fn unmapped_keys_len(&self) -> usize {
+    pub fn static_account_keys_len(&self) -> usize {
         match self {
             Self::Legacy(message) => message.account_keys.len(),
             Self::V0(message) => message.account_keys.len(),
         }
     }
 
+    /// Returns the address table lookups of a version #0 message
+    pub fn address_table_lookups(&self) -> Option<&[v0::MessageAddressTableLookup]> {
+        match self {
+            Self::Legacy(_) => None,
+            Self::V0(message) => Some(&message.address_table_lookups),
+        }
+    }
+
     pub fn recent_blockhash(&self) -> &Hash {
         match self {
             Self::Legacy(message) => &message.recent_blockhash,
@@ -69,6 +77,13 @@ impl VersionedMessage {
         }
     }
 
+    pub fn instructions(&self) -> &[CompiledInstruction] {
+        match self {
+            Self::Legacy(message) => &message.instructions,
+            Self::V0(message) => &message.instructions,
+        }
+    }
+
     pub fn set_recent_blockhash(&mut self, recent_blockhash: Hash) {
         match self {
             Self::Legacy(message) => message.recent_blockhash = recent_blockhash,
@@ -247,7 +262,7 @@ mod tests {
     use super::*;
     use crate::{
         instruction::{AccountMeta, Instruction},
-        message::v0::AddressMapIndexes,
+        message::v0::MessageAddressTableLookup,
     };
 
     #[test]
@@ -306,14 +321,16 @@ mod tests {
                 Pubkey::new_unique(),
                 Pubkey::new_unique(),
             ],
-            address_map_indexes: vec![
-                AddressMapIndexes {
-                    writable: vec![1],
-                    readonly: vec![0],
+            address_table_lookups: vec![
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![1],
+                    readonly_indexes: vec![0],
                 },
-                AddressMapIndexes {
-                    writable: vec![0],
-                    readonly: vec![1],
+                MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![1],
                 },
             ],
             instructions: vec![CompiledInstruction {
@@ -89,6 +89,13 @@ pub trait SyscallStubs: Sync + Send {
     fn sol_log_data(&self, fields: &[&[u8]]) {
         println!("data: {}", fields.iter().map(base64::encode).join(" "));
     }
+    fn sol_get_processed_sibling_instruction(&self, _index: usize) -> Option<Instruction> {
+        None
+    }
+    fn sol_get_minimum_stake_delegation(&self) -> u64 {
+        sol_log("SyscallStubs: sol_get_minimum_stake_delegation() not available");
+        0
+    }
 }
 
 struct DefaultSyscallStubs {}
@@ -174,3 +181,17 @@ pub(crate) fn sol_set_return_data(data: &[u8]) {
 pub(crate) fn sol_log_data(data: &[&[u8]]) {
     SYSCALL_STUBS.read().unwrap().sol_log_data(data)
 }
+
+pub(crate) fn sol_get_processed_sibling_instruction(index: usize) -> Option<Instruction> {
+    SYSCALL_STUBS
+        .read()
+        .unwrap()
+        .sol_get_processed_sibling_instruction(index)
+}
+
+pub(crate) fn sol_get_minimum_stake_delegation() -> u64 {
+    SYSCALL_STUBS
+        .read()
+        .unwrap()
+        .sol_get_minimum_stake_delegation()
+}
@@ -29,6 +29,10 @@ impl SlotHashes {
             .ok()
             .map(|index| &self[index].1)
     }
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn position(&self, slot: &Slot) -> Option<usize> {
+        self.binary_search_by(|(probe, _)| slot.cmp(probe)).ok()
+    }
     pub fn new(slot_hashes: &[SlotHash]) -> Self {
         let mut slot_hashes = slot_hashes.to_vec();
         slot_hashes.sort_by(|(a, _), (b, _)| b.cmp(a));
@@ -46,6 +46,15 @@ pub enum StakeError {
 
     #[error("custodian signature not present")]
     CustodianSignatureMissing,
+
+    #[error("only fully activated stake can be redelegated")]
+    RedelegateTransientOrInactiveStake,
+
+    #[error("stake cannot be redelegated to the vote account it is delegated to")]
+    RedelegateToSameVoteAccount,
+
+    #[error("stake delegation is less than the minimum delegation")]
+    InsufficientDelegation,
 }
 
 impl<E> DecodeError<E> for StakeError {
@@ -222,6 +231,34 @@ pub enum StakeInstruction {
     ///   1. `[SIGNER]` Lockup authority or withdraw authority
     ///   2. Optional: `[SIGNER]` New lockup authority
     SetLockupChecked(LockupCheckedArgs),
+
+    /// Redelegate activated stake to another vote account, without waiting for it to fully
+    /// deactivate first
+    ///
+    /// Upon success:
+    ///   * the delegated stake account is scheduled for deactivation, and its balance is reduced
+    ///     to the lamports that weren't part of its effective stake, including the rent exempt
+    ///     reserve
+    ///   * the uninitialized stake account receives the effective stake, and is scheduled for
+    ///     activation to the new vote account with the same authorities and lockup as the
+    ///     delegated stake account
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` Delegated stake account to be redelegated, its stake must be fully active
+    ///   1. `[WRITE]` Uninitialized stake account that will hold the redelegated stake
+    ///   2. `[]` Vote account to which this stake will be redelegated
+    ///   3. `[]` Address of config account that carries stake config
+    ///   4. `[SIGNER]` Stake authority
+    Redelegate,
+
+    /// Get the minimum stake delegation, in lamports
+    ///
+    /// The minimum is returned via the transaction's return data as a little-endian `u64`, so
+    /// that programs can query it with a cross-program invocation instead of hard-coding it
+    ///
+    /// # Account references
+    ///   None
+    GetMinimumDelegation,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
@@ -611,6 +648,68 @@ pub fn delegate_stake(
     Instruction::new_with_bincode(id(), &StakeInstruction::DelegateStake, account_metas)
 }
 
+fn _redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*uninitialized_stake_pubkey, false),
+        AccountMeta::new_readonly(*vote_pubkey, false),
+        AccountMeta::new_readonly(config::id(), false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+    Instruction::new_with_bincode(id(), &StakeInstruction::Redelegate, account_metas)
+}
+
+pub fn redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::allocate(
+            uninitialized_stake_pubkey,
+            std::mem::size_of::<StakeState>() as u64,
+        ),
+        system_instruction::assign(uninitialized_stake_pubkey, &id()),
+        _redelegate(
+            stake_pubkey,
+            authorized_pubkey,
+            vote_pubkey,
+            uninitialized_stake_pubkey,
+        ),
+    ]
+}
+
+pub fn redelegate_with_seed(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey, // derived using create_with_seed()
+    base: &Pubkey,                       // base
+    seed: &str,                          // seed
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::allocate_with_seed(
+            uninitialized_stake_pubkey,
+            base,
+            seed,
+            std::mem::size_of::<StakeState>() as u64,
+            &id(),
+        ),
+        _redelegate(
+            stake_pubkey,
+            authorized_pubkey,
+            vote_pubkey,
+            uninitialized_stake_pubkey,
+        ),
+    ]
+}
+
 pub fn withdraw(
     stake_pubkey: &Pubkey,
     withdrawer_pubkey: &Pubkey,
@@ -678,6 +777,14 @@ pub fn set_lockup_checked(
     )
 }
 
+pub fn get_minimum_delegation() -> Instruction {
+    Instruction::new_with_bincode(
+        id(),
+        &StakeInstruction::GetMinimumDelegation,
+        Vec::default(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
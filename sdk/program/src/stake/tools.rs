@@ -0,0 +1,19 @@
+//! Utility functions for stake programs
+
+/// The minimum number of lamports a stake account can delegate
+///
+/// Pool programs should query this at runtime rather than hard-coding it, as
+/// the stake program raises the minimum by feature activation.
+pub fn get_minimum_delegation() -> u64 {
+    #[cfg(target_arch = "bpf")]
+    {
+        extern "C" {
+            fn sol_get_minimum_stake_delegation() -> u64;
+        }
+
+        unsafe { sol_get_minimum_stake_delegation() }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_minimum_stake_delegation()
+}
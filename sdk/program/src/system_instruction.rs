@@ -142,6 +142,9 @@ pub const MAX_PERMITTED_DATA_LENGTH: u64 = 10 * 1024 * 1024;
 pub enum SystemInstruction {
     /// Create a new account
     ///
+    /// Once rent-exempt accounts are required, a nonzero `lamports` must cover
+    /// the rent-exempt minimum balance for `space`.
+    ///
     /// # Account references
     ///   0. `[WRITE, SIGNER]` Funding account
     ///   1. `[WRITE, SIGNER]` New account
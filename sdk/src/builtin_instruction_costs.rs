@@ -0,0 +1,37 @@
+//! Compute units the cost model charges the instructions of builtin programs
+
+#![cfg(feature = "full")]
+
+use {
+    crate::{
+        config, feature, incinerator, native_loader, pubkey::Pubkey, secp256k1_program, stake,
+        system_program, vote, zk_token_proof_program,
+    },
+    lazy_static::lazy_static,
+    std::collections::HashMap,
+};
+
+/// Cluster data, method of collecting at https://github.com/solana-labs/solana/issues/19627
+/// Dashboard: https://metrics.solana.com:8889/sources/0/dashboards/10?refresh=Paused&lower=now%28%29%20-%2012h
+///
+/// cluster avergaed compute unit to microsec conversion rate
+pub const COMPUTE_UNIT_TO_US_RATIO: u64 = 40;
+
+lazy_static! {
+    /// Number of compute units for each built-in programs
+    pub static ref BUILT_IN_INSTRUCTION_COSTS: HashMap<Pubkey, u64> = [
+        (feature::id(), COMPUTE_UNIT_TO_US_RATIO * 2),
+        (incinerator::id(), COMPUTE_UNIT_TO_US_RATIO * 2),
+        (native_loader::id(), COMPUTE_UNIT_TO_US_RATIO * 2),
+        (stake::config::id(), COMPUTE_UNIT_TO_US_RATIO * 2),
+        (stake::program::id(), COMPUTE_UNIT_TO_US_RATIO * 25),
+        (config::program::id(), COMPUTE_UNIT_TO_US_RATIO * 15),
+        (vote::program::id(), COMPUTE_UNIT_TO_US_RATIO * 85),
+        (secp256k1_program::id(), COMPUTE_UNIT_TO_US_RATIO * 4),
+        (system_program::id(), COMPUTE_UNIT_TO_US_RATIO * 10),
+        (zk_token_proof_program::id(), COMPUTE_UNIT_TO_US_RATIO * 1_500),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+}
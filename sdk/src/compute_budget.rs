@@ -4,18 +4,21 @@ use {
     crate::{
         borsh::try_from_slice_unchecked,
         entrypoint::HEAP_LENGTH as MIN_HEAP_FRAME_BYTES,
-        feature_set::{requestable_heap_size, FeatureSet},
+        feature_set::{add_set_compute_unit_price_ix, requestable_heap_size, FeatureSet},
         instruction::{Instruction, InstructionError},
+        message::SanitizedMessage,
         transaction::{SanitizedTransaction, TransactionError},
     },
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-    std::sync::Arc,
+    std::{convert::TryFrom, sync::Arc},
 };
 
 crate::declare_id!("ComputeBudget111111111111111111111111111111");
 
-const MAX_UNITS: u32 = 1_000_000;
+/// Maximum number of compute units a transaction can request
+pub const MAX_UNITS: u32 = 1_000_000;
 const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
 
 /// Compute Budget Instructions
 #[derive(
@@ -38,6 +41,9 @@ pub enum ComputeBudgetInstruction {
     /// The value requested must be a multiple of 1024. This new heap frame size
     /// applies to each program executed, including all calls to CPIs.
     RequestHeapFrame(u32),
+    /// Set a compute unit price in "micro-lamports" to pay a higher transaction
+    /// fee for higher transaction prioritization.
+    SetComputeUnitPrice(u64),
 }
 impl ComputeBudgetInstruction {
     /// Create a `ComputeBudgetInstruction::RequestUnits` `Instruction`
@@ -52,6 +58,44 @@ impl ComputeBudgetInstruction {
             vec![],
         )
     }
+    /// Create a `ComputeBudgetInstruction::SetComputeUnitPrice` `Instruction`
+    pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+        Instruction::new_with_borsh(
+            id(),
+            &ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports),
+            vec![],
+        )
+    }
+}
+
+/// Additional fee and scheduling priority of a transaction which sets a
+/// compute unit price
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct PrioritizationFeeDetails {
+    fee: u64,
+    priority: u64,
+}
+impl PrioritizationFeeDetails {
+    /// The fee is the compute unit price, in micro-lamports, multiplied by the
+    /// maximum number of compute units and rounded up to the nearest lamport
+    pub fn new(compute_unit_price: u64, max_compute_units: u64) -> Self {
+        let micro_lamport_fee =
+            (compute_unit_price as u128).saturating_mul(max_compute_units as u128);
+        let fee = micro_lamport_fee
+            .saturating_add(MICRO_LAMPORTS_PER_LAMPORT.saturating_sub(1) as u128)
+            .checked_div(MICRO_LAMPORTS_PER_LAMPORT as u128)
+            .unwrap_or_default();
+        Self {
+            fee: u64::try_from(fee).unwrap_or(u64::MAX),
+            priority: compute_unit_price,
+        }
+    }
+    pub fn get_fee(&self) -> u64 {
+        self.fee
+    }
+    pub fn get_priority(&self) -> u64 {
+        self.priority
+    }
 }
 
 #[derive(Clone, Copy, Debug, AbiExample, PartialEq)]
@@ -66,6 +110,11 @@ pub struct ComputeBudget {
     /// Number of compute units consumed by an invoke call (not including the cost incurred by
     /// the called program)
     pub invoke_units: u64,
+    /// Number of compute units consumed by an invoke call for each level it is nested below the
+    /// first cross-program invocation
+    pub invoke_depth_units: u64,
+    /// Number of compute units consumed by an invoke call per account info passed to it
+    pub cpi_account_info_units: u64,
     /// Maximum cross-program invocation depth allowed
     pub max_invoke_depth: usize,
     /// Base number of compute units consumed to call SHA256
@@ -106,6 +155,8 @@ impl ComputeBudget {
             log_64_units: 100,
             create_program_address_units: 1500,
             invoke_units: 1000,
+            invoke_depth_units: 250,
+            cpi_account_info_units: 10,
             max_invoke_depth: 4,
             sha256_base_cost: 85,
             sha256_byte_cost: 1,
@@ -121,15 +172,40 @@ impl ComputeBudget {
             heap_cost: 8,
         }
     }
+    /// Number of compute units consumed by an invoke call made at `stack_height` with
+    /// `num_account_infos` account infos, not including the cost incurred by the called program
+    pub fn cpi_cost(&self, stack_height: usize, num_account_infos: u64) -> u64 {
+        let nested_depth = stack_height.saturating_sub(1) as u64;
+        self.invoke_units
+            .saturating_add(self.invoke_depth_units.saturating_mul(nested_depth))
+            .saturating_add(
+                self.cpi_account_info_units
+                    .saturating_mul(num_account_infos),
+            )
+    }
     pub fn process_transaction(
         &mut self,
         tx: &SanitizedTransaction,
         feature_set: Arc<FeatureSet>,
     ) -> Result<(), TransactionError> {
+        self.process_message(
+            tx.message(),
+            feature_set.is_active(&requestable_heap_size::id()),
+            feature_set.is_active(&add_set_compute_unit_price_ix::id()),
+        )
+        .map(|_| ())
+    }
+    pub fn process_message(
+        &mut self,
+        message: &SanitizedMessage,
+        requestable_heap_size: bool,
+        support_set_compute_unit_price_ix: bool,
+    ) -> Result<PrioritizationFeeDetails, TransactionError> {
         let error = TransactionError::InstructionError(0, InstructionError::InvalidInstructionData);
+        let mut compute_unit_price = 0;
         // Compute budget instruction must be in the 1st 3 instructions (avoid
         // nonce marker), otherwise ignored
-        for (program_id, instruction) in tx.message().program_instructions_iter().take(3) {
+        for (program_id, instruction) in message.program_instructions_iter().take(3) {
             if check_id(program_id) {
                 match try_from_slice_unchecked(&instruction.data) {
                     Ok(ComputeBudgetInstruction::RequestUnits(units)) => {
@@ -139,7 +215,7 @@ impl ComputeBudget {
                         self.max_units = units as u64;
                     }
                     Ok(ComputeBudgetInstruction::RequestHeapFrame(bytes)) => {
-                        if !feature_set.is_active(&requestable_heap_size::id())
+                        if !requestable_heap_size
                             || bytes > MAX_HEAP_FRAME_BYTES
                             || bytes < MIN_HEAP_FRAME_BYTES as u32
                             || bytes % 1024 != 0
@@ -148,11 +224,19 @@ impl ComputeBudget {
                         }
                         self.heap_size = Some(bytes as usize);
                     }
+                    Ok(ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports))
+                        if support_set_compute_unit_price_ix =>
+                    {
+                        compute_unit_price = micro_lamports;
+                    }
                     _ => return Err(error),
                 }
             }
         }
-        Ok(())
+        Ok(PrioritizationFeeDetails::new(
+            compute_unit_price,
+            self.max_units,
+        ))
     }
 }
 
@@ -163,7 +247,7 @@ mod tests {
         hash::Hash, message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer,
         transaction::Transaction,
     };
-    use std::convert::TryInto;
+    use std::convert::{TryFrom, TryInto};
 
     fn sanitize_tx(tx: Transaction) -> SanitizedTransaction {
         tx.try_into().unwrap()
@@ -301,6 +385,16 @@ mod tests {
             ComputeBudget::default()
         );
 
+        // ComputeUnitPrice
+        test!(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+            ],
+            Ok(()),
+            ComputeBudget::default()
+        );
+
         // Combined
         test!(
             &[
@@ -316,4 +410,66 @@ mod tests {
             }
         );
     }
+    #[test]
+    fn test_process_message_prioritization_fee() {
+        let payer = Pubkey::new_unique();
+        let message = |instructions: &[Instruction]| {
+            SanitizedMessage::try_from(Message::new(instructions, Some(&payer))).unwrap()
+        };
+
+        // Default compute unit limit
+        let message0 = message(&[
+            ComputeBudgetInstruction::set_compute_unit_price(5_000_000),
+            Instruction::new_with_bincode(Pubkey::new_unique(), &0, vec![]),
+        ]);
+        assert_eq!(
+            ComputeBudget::default().process_message(&message0, true, true),
+            Ok(PrioritizationFeeDetails {
+                fee: 1_000_000,
+                priority: 5_000_000,
+            })
+        );
+
+        // Requested compute unit limit, rounded up to the nearest lamport
+        let message1 = message(&[
+            ComputeBudgetInstruction::request_units(1_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1_001),
+        ]);
+        assert_eq!(
+            ComputeBudget::default().process_message(&message1, true, true),
+            Ok(PrioritizationFeeDetails {
+                fee: 2,
+                priority: 1_001,
+            })
+        );
+
+        // Compute unit price instruction not yet supported
+        assert_eq!(
+            ComputeBudget::default().process_message(&message0, true, false),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::InvalidInstructionData,
+            ))
+        );
+
+        // No compute unit price
+        let message2 = message(&[ComputeBudgetInstruction::request_units(1_000)]);
+        assert_eq!(
+            ComputeBudget::default().process_message(&message2, true, true),
+            Ok(PrioritizationFeeDetails::default())
+        );
+    }
+
+    #[test]
+    fn test_cpi_cost() {
+        let compute_budget = ComputeBudget::default();
+        assert_eq!(compute_budget.cpi_cost(1, 0), compute_budget.invoke_units);
+        assert_eq!(
+            compute_budget.cpi_cost(3, 4),
+            compute_budget.invoke_units
+                + 2 * compute_budget.invoke_depth_units
+                + 4 * compute_budget.cpi_account_info_units
+        );
+        assert_eq!(compute_budget.cpi_cost(1, u64::MAX), u64::MAX);
+    }
 }
@@ -253,6 +253,65 @@ pub mod disable_fee_calculator {
     solana_sdk::declare_id!("2jXx2yDmGysmBKfKYNgLj2DQyAQv6mMk2BPh4eSbyB4H");
 }
 
+pub mod epoch_accounts_hash {
+    solana_sdk::declare_id!("DJ66jn8Fb3AggBfvsmsdJffy8s6P6VFgAWR4En57kG7F");
+}
+
+pub mod add_set_compute_unit_price_ix {
+    solana_sdk::declare_id!("4UvcCmSiWTNNyPFvt8zhgR8nvtKr8kwuSJJfyNnR22hn");
+}
+
+pub mod add_get_processed_sibling_instruction_syscall {
+    solana_sdk::declare_id!("5sFfHkjkTqPWZyf3dDTammAF9EcpSqzJX67tnSBBN1ML");
+}
+
+pub mod add_invalid_realloc_program_error {
+    solana_sdk::declare_id!("Hm8vksGcJivpMmHrpiUVWMQXGQpFhuSn6BdRvibPrkT");
+}
+
+pub mod zk_token_sdk_enabled {
+    solana_sdk::declare_id!("2bNaehsk2Rz9z2NZ7eJ6C4mPGjjsymqTdufCEpjHNAre");
+}
+
+pub mod require_rent_exempt_accounts {
+    solana_sdk::declare_id!("2EijuVSmnpEX7ymSmJ3A7zMDMh9P3eLDTa97u7GfkwoG");
+}
+
+pub mod stake_redelegate_instruction {
+    solana_sdk::declare_id!("EqbhTz82gfqdjYNsKVcs11xS8DCuzBhdUwieQArhHW3R");
+}
+
+pub mod commission_increases_only_allowed_in_first_half_of_epoch {
+    solana_sdk::declare_id!("2jz2tWCnxnb23SopMvsAiRS3aeyjoWytPCTvMDvMiS75");
+}
+
+pub mod add_get_minimum_delegation_instruction_to_stake_program {
+    solana_sdk::declare_id!("A9uaonWF6z73diHnvN9MMN27hW3DkjfK8zDhrHK3P6xo");
+}
+
+pub mod stake_raise_minimum_delegation_to_1_sol {
+    solana_sdk::declare_id!("4EwEW5W8YfdYCvrKRLtkEohhLzrN86zXWFiTfU1hDwsL");
+
+    /// The minimum stake delegation, in lamports, once this feature is active
+    pub const MINIMUM_DELEGATION: u64 = 1_000_000_000;
+}
+
+pub mod partitioned_epoch_rewards {
+    solana_sdk::declare_id!("kosrtUDFQwDASrXk95sLaC4KV6vKSGEZJ3LQtVK7iy4");
+}
+
+pub mod merkle_shreds {
+    solana_sdk::declare_id!("FinsYbW4gCbcb7Tnx3THPq2vKg5mTagkEaSXToGUCJ8E");
+}
+
+pub mod cpi_depth_and_account_costs {
+    solana_sdk::declare_id!("2hpQWpKgKvppDMdqksFyWJ28jrsWvFzETbDc9EyiRN5n");
+}
+
+pub mod compute_unit_fee_structure {
+    solana_sdk::declare_id!("6DVikP2X97CD3osEqvxxgXb6iyPuJKgZAnEpihieaeMu");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -311,6 +370,20 @@ lazy_static! {
         (turbine_peers_shuffle::id(), "turbine peers shuffle patch"),
         (requestable_heap_size::id(), "Requestable heap frame size"),
         (disable_fee_calculator::id(), "deprecate fee calculator"),
+        (epoch_accounts_hash::id(), "include the epoch accounts hash in the bank hash"),
+        (add_set_compute_unit_price_ix::id(), "add compute budget ix for setting a compute unit price"),
+        (add_get_processed_sibling_instruction_syscall::id(), "add add_get_processed_sibling_instruction_syscall"),
+        (add_invalid_realloc_program_error::id(), "add invalid realloc program error"),
+        (zk_token_sdk_enabled::id(), "enable Zk Token proof program"),
+        (require_rent_exempt_accounts::id(), "require all new transaction accounts to be rent-exempt"),
+        (stake_redelegate_instruction::id(), "enable the redelegate stake instruction"),
+        (commission_increases_only_allowed_in_first_half_of_epoch::id(), "only allow vote account commission increases in the first half of an epoch"),
+        (add_get_minimum_delegation_instruction_to_stake_program::id(), "add GetMinimumDelegation instruction to stake program"),
+        (stake_raise_minimum_delegation_to_1_sol::id(), "raise the minimum stake delegation to 1 SOL"),
+        (partitioned_epoch_rewards::id(), "credit stake rewards over the first blocks of an epoch"),
+        (merkle_shreds::id(), "shred slots into merkle shreds, chaining the merkle roots of FEC sets"),
+        (cpi_depth_and_account_costs::id(), "charge cross-program invocations for their depth and the accounts passed"),
+        (compute_unit_fee_structure::id(), "charge fees per write lock and requested compute units"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
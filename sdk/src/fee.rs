@@ -0,0 +1,235 @@
+//! Fee structures.
+
+use {
+    crate::{fee_calculator::FeeRateGovernor, native_token::sol_to_lamports},
+    std::convert::TryFrom,
+};
+
+/// A fee and its associated compute unit limit
+#[derive(Debug, Default, Clone, PartialEq, Eq, AbiExample)]
+pub struct FeeBin {
+    /// maximum compute units for which this fee will be charged
+    pub limit: u64,
+    /// fee in lamports
+    pub fee: u64,
+}
+
+/// Number of write locks an account can take in a block before each further
+/// one is charged the contended write lock fee
+pub const DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS: u64 = 128;
+
+/// Information used to calculate fees
+///
+/// Signatures are charged at the current congestion level, as tracked by the
+/// bank's `FeeRateGovernor`.  Each write lock is charged a flat fee, plus a
+/// surcharge if the account is contended, and the compute fee is scaled up by
+/// the congestion relative to the reference level of `lamports_per_signature`.
+#[derive(Debug, Clone, PartialEq, Eq, AbiExample)]
+pub struct FeeStructure {
+    /// lamports per signature at the reference congestion level
+    pub lamports_per_signature: u64,
+    /// lamports_per_write_lock
+    pub lamports_per_write_lock: u64,
+    /// lamports charged on top of `lamports_per_write_lock` for write locking a
+    /// contended account
+    pub lamports_per_contended_write_lock: u64,
+    /// write locks an account takes in a block before it is contended
+    pub contended_account_write_locks: u64,
+    /// Compute unit fee bins, sorted by ascending `limit`
+    pub compute_fee_bins: Vec<FeeBin>,
+}
+
+impl FeeStructure {
+    pub fn new(
+        sol_per_signature: f64,
+        sol_per_write_lock: f64,
+        compute_fee_bins: Vec<(u64, f64)>,
+    ) -> Self {
+        let compute_fee_bins = compute_fee_bins
+            .iter()
+            .map(|(limit, sol)| FeeBin {
+                limit: *limit,
+                fee: sol_to_lamports(*sol),
+            })
+            .collect::<Vec<_>>();
+        FeeStructure {
+            lamports_per_signature: sol_to_lamports(sol_per_signature),
+            lamports_per_write_lock: sol_to_lamports(sol_per_write_lock),
+            lamports_per_contended_write_lock: 0,
+            contended_account_write_locks: DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS,
+            compute_fee_bins,
+        }
+    }
+
+    /// Fee for the smallest bin that covers `compute_units`, or for the
+    /// largest bin if none does
+    pub fn get_compute_fee(&self, compute_units: u64) -> u64 {
+        self.compute_fee_bins
+            .iter()
+            .find(|bin| compute_units <= bin.limit)
+            .or_else(|| self.compute_fee_bins.last())
+            .map(|bin| bin.fee)
+            .unwrap_or_default()
+    }
+
+    /// Fee for write locking `num_write_locks` accounts, of which
+    /// `num_contended_write_locks` are contended
+    pub fn get_write_lock_fee(&self, num_write_locks: u64, num_contended_write_locks: u64) -> u64 {
+        self.lamports_per_write_lock
+            .saturating_mul(num_write_locks)
+            .saturating_add(
+                self.lamports_per_contended_write_lock
+                    .saturating_mul(num_contended_write_locks),
+            )
+    }
+
+    /// Whether an account that already took `write_locks` write locks in the
+    /// current block is contended
+    pub fn is_contended(&self, write_locks: u64) -> bool {
+        write_locks >= self.contended_account_write_locks
+    }
+
+    /// Compute fee for `compute_units` at the congestion level of
+    /// `lamports_per_signature`.  Congestion below the reference level doesn't
+    /// discount the fee, and without a reference level the fee isn't scaled.
+    pub fn get_congestion_compute_fee(
+        &self,
+        compute_units: u64,
+        lamports_per_signature: u64,
+    ) -> u64 {
+        let compute_fee = self.get_compute_fee(compute_units);
+        if self.lamports_per_signature == 0 {
+            return compute_fee;
+        }
+        let congestion_fee = u128::from(compute_fee).saturating_mul(u128::from(
+            lamports_per_signature.max(self.lamports_per_signature),
+        )) / u128::from(self.lamports_per_signature);
+        u64::try_from(congestion_fee).unwrap_or(u64::MAX)
+    }
+}
+
+impl From<&FeeRateGovernor> for FeeStructure {
+    /// Fees scaled to the target signature fee of the genesis `fee_rate_governor`:
+    /// a tenth of it per write lock, as much again per write lock of a
+    /// contended account, and up to one more for requesting compute units
+    /// beyond the default budget
+    fn from(fee_rate_governor: &FeeRateGovernor) -> Self {
+        let lamports_per_signature = fee_rate_governor.target_lamports_per_signature;
+        Self {
+            lamports_per_signature,
+            lamports_per_write_lock: lamports_per_signature / 10,
+            lamports_per_contended_write_lock: lamports_per_signature,
+            contended_account_write_locks: DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS,
+            compute_fee_bins: vec![
+                FeeBin {
+                    limit: 200_000,
+                    fee: 0,
+                },
+                FeeBin {
+                    limit: 400_000,
+                    fee: lamports_per_signature / 2,
+                },
+                FeeBin {
+                    limit: 1_400_000,
+                    fee: lamports_per_signature,
+                },
+            ],
+        }
+    }
+}
+
+impl Default for FeeStructure {
+    fn default() -> Self {
+        Self::new(0.000005, 0.0, vec![(1_400_000, 0.0)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_fee_structure() {
+        let fee_structure = FeeStructure::default();
+        assert_eq!(fee_structure.lamports_per_signature, 5_000);
+        assert_eq!(fee_structure.get_write_lock_fee(3, 1), 0);
+        assert_eq!(fee_structure.get_congestion_compute_fee(200_000, 10_000), 0);
+    }
+
+    #[test]
+    fn test_get_compute_fee() {
+        let fee_structure = FeeStructure::new(
+            0.000005,
+            0.0,
+            vec![(200_000, 0.00001), (1_400_000, 0.00002)],
+        );
+        assert_eq!(fee_structure.get_compute_fee(0), 10_000);
+        assert_eq!(fee_structure.get_compute_fee(200_000), 10_000);
+        assert_eq!(fee_structure.get_compute_fee(200_001), 20_000);
+        // units beyond the last bin are charged the last bin's fee
+        assert_eq!(fee_structure.get_compute_fee(2_000_000), 20_000);
+
+        let fee_structure = FeeStructure::new(0.000005, 0.0, vec![]);
+        assert_eq!(fee_structure.get_compute_fee(200_000), 0);
+    }
+
+    #[test]
+    fn test_get_write_lock_fee() {
+        let mut fee_structure = FeeStructure::new(0.000005, 0.000001, vec![]);
+        assert_eq!(fee_structure.get_write_lock_fee(0, 0), 0);
+        assert_eq!(fee_structure.get_write_lock_fee(3, 0), 3 * 1_000);
+
+        fee_structure.lamports_per_contended_write_lock = 5_000;
+        assert_eq!(
+            fee_structure.get_write_lock_fee(3, 2),
+            3 * 1_000 + 2 * 5_000
+        );
+    }
+
+    #[test]
+    fn test_is_contended() {
+        let fee_structure = FeeStructure::default();
+        assert!(!fee_structure.is_contended(0));
+        assert!(!fee_structure.is_contended(DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS - 1));
+        assert!(fee_structure.is_contended(DEFAULT_CONTENDED_ACCOUNT_WRITE_LOCKS));
+    }
+
+    #[test]
+    fn test_get_congestion_compute_fee() {
+        let fee_structure = FeeStructure::new(0.000005, 0.0, vec![(1_400_000, 0.00001)]);
+        assert_eq!(
+            fee_structure.get_congestion_compute_fee(200_000, 5_000),
+            10_000
+        );
+        assert_eq!(
+            fee_structure.get_congestion_compute_fee(200_000, 10_000),
+            20_000
+        );
+        // free signatures don't make compute free
+        assert_eq!(fee_structure.get_congestion_compute_fee(200_000, 0), 10_000);
+
+        // without a reference level, the compute fee isn't scaled
+        let fee_structure = FeeStructure::new(0.0, 0.0, vec![(1_400_000, 0.00001)]);
+        assert_eq!(
+            fee_structure.get_congestion_compute_fee(200_000, 10_000),
+            10_000
+        );
+    }
+
+    #[test]
+    fn test_fee_structure_from_fee_rate_governor() {
+        let fee_rate_governor = FeeRateGovernor::new(10_000, 0);
+        let fee_structure = FeeStructure::from(&fee_rate_governor);
+        assert_eq!(fee_structure.lamports_per_signature, 10_000);
+        assert_eq!(fee_structure.get_write_lock_fee(2, 1), 2 * 1_000 + 10_000);
+        // the default compute budget costs nothing extra
+        assert_eq!(fee_structure.get_compute_fee(200_000), 0);
+        assert_eq!(fee_structure.get_compute_fee(400_000), 5_000);
+        assert_eq!(fee_structure.get_compute_fee(1_400_000), 10_000);
+
+        // free signatures make everything free
+        let fee_structure = FeeStructure::from(&FeeRateGovernor::new(0, 0));
+        assert_eq!(fee_structure.get_write_lock_fee(2, 1), 0);
+        assert_eq!(fee_structure.get_compute_fee(1_400_000), 0);
+    }
+}
@@ -11,6 +11,7 @@ pub use solana_program::*;
 
 pub mod account;
 pub mod account_utils;
+pub mod builtin_instruction_costs;
 pub mod builtins;
 pub mod client;
 pub mod commitment_config;
@@ -25,6 +26,7 @@ pub mod epoch_info;
 pub mod exit;
 pub mod feature;
 pub mod feature_set;
+pub mod fee;
 pub mod genesis_config;
 pub mod hard_forks;
 pub mod hash;
@@ -34,6 +36,7 @@ pub mod log;
 pub mod native_loader;
 pub mod nonce_account;
 pub mod nonce_keyed_account;
+pub mod offchain_message;
 pub mod packet;
 pub mod poh_config;
 pub mod precompiles;
@@ -118,6 +118,21 @@ impl<'a> NonceKeyedAccount for KeyedAccount<'a> {
                     );
                     return Err(InstructionError::InsufficientFunds);
                 }
+                if invoke_context
+                    .is_feature_active(&feature_set::require_rent_exempt_accounts::id())
+                {
+                    let remaining = self.lamports()? - lamports;
+                    let min_balance = rent.minimum_balance(self.data_len()?);
+                    if remaining != 0 && remaining < min_balance {
+                        ic_msg!(
+                            invoke_context,
+                            "Withdraw nonce account: remaining balance {} would leave the account paying rent, need {} or 0",
+                            remaining,
+                            min_balance,
+                        );
+                        return Err(InstructionError::InsufficientFunds);
+                    }
+                }
                 *self.unsigned_key()
             }
             State::Initialized(ref data) => {
@@ -623,7 +638,10 @@ mod test {
             with_test_keyed_account(42, false, |to_keyed| {
                 let mut signers = HashSet::new();
                 signers.insert(*nonce_keyed.signer_key().unwrap());
-                let invoke_context = create_invoke_context_with_blockhash(0);
+                let mut invoke_context = create_invoke_context_with_blockhash(0);
+                invoke_context
+                    .disabled_features
+                    .insert(feature_set::require_rent_exempt_accounts::id());
                 let withdraw_lamports = nonce_keyed.account.borrow().lamports() / 2;
                 let nonce_expect_lamports =
                     nonce_keyed.account.borrow().lamports() - withdraw_lamports;
@@ -672,6 +690,47 @@ mod test {
         })
     }
 
+    #[test]
+    fn withdraw_inx_uninitialized_acc_rent_paying_fail() {
+        let rent = Rent {
+            lamports_per_byte_year: 42,
+            ..Rent::default()
+        };
+        let min_lamports = rent.minimum_balance(State::size());
+        with_test_keyed_account(min_lamports + 42, true, |nonce_keyed| {
+            with_test_keyed_account(42, false, |to_keyed| {
+                let mut signers = HashSet::new();
+                signers.insert(*nonce_keyed.signer_key().unwrap());
+                let invoke_context = create_invoke_context_with_blockhash(0);
+                // Leaving the account paying rent fails
+                let result = nonce_keyed.withdraw_nonce_account(
+                    43,
+                    to_keyed,
+                    &rent,
+                    &signers,
+                    &invoke_context,
+                );
+                assert_eq!(result, Err(InstructionError::InsufficientFunds));
+                // Leaving the account rent-exempt succeeds
+                nonce_keyed
+                    .withdraw_nonce_account(42, to_keyed, &rent, &signers, &invoke_context)
+                    .unwrap();
+                assert_eq!(nonce_keyed.account.borrow().lamports(), min_lamports);
+                // Emptying the account succeeds
+                nonce_keyed
+                    .withdraw_nonce_account(
+                        min_lamports,
+                        to_keyed,
+                        &rent,
+                        &signers,
+                        &invoke_context,
+                    )
+                    .unwrap();
+                assert_eq!(nonce_keyed.account.borrow().lamports(), 0);
+            })
+        })
+    }
+
     #[test]
     fn withdraw_inx_initialized_acc_two_withdraws_ok() {
         let rent = Rent {
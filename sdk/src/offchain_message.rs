@@ -0,0 +1,294 @@
+//! Off-chain message container for storing non-transaction messages.
+//!
+//! Serialized messages are prefixed with a signing domain that can never begin a valid
+//! transaction message, so a signature over an off-chain message cannot be replayed as a
+//! transaction signature.
+#![cfg(feature = "full")]
+
+use crate::{
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    sanitize::SanitizeError,
+    signature::{Signature, Signer},
+    signer::SignerError,
+};
+
+/// Prefix of every serialized off-chain message
+pub const SIGNING_DOMAIN: &[u8] = b"\xffsolana offchain";
+
+/// Check if the given bytes contain only printable ASCII characters
+fn is_printable_ascii(data: &[u8]) -> bool {
+    data.iter().all(|&byte| (0x20..=0x7e).contains(&byte))
+}
+
+/// Check if the given bytes contain valid UTF-8
+fn is_utf8(data: &[u8]) -> bool {
+    std::str::from_utf8(data).is_ok()
+}
+
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MessageFormat {
+    /// Printable ASCII, short enough to be displayed and signed by a hardware wallet
+    RestrictedAscii = 0,
+    /// UTF-8, short enough to be signed by a hardware wallet
+    LimitedUtf8 = 1,
+    /// UTF-8 up to the maximum message length
+    ExtendedUtf8 = 2,
+}
+
+impl MessageFormat {
+    fn from_u8(format: u8) -> Result<Self, SanitizeError> {
+        match format {
+            0 => Ok(Self::RestrictedAscii),
+            1 => Ok(Self::LimitedUtf8),
+            2 => Ok(Self::ExtendedUtf8),
+            _ => Err(SanitizeError::InvalidValue),
+        }
+    }
+}
+
+pub mod v0 {
+    use super::*;
+
+    /// Version 0 of the off-chain message: a format byte and a little-endian u16 length
+    /// followed by the message itself
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct OffchainMessage {
+        format: MessageFormat,
+        message: Vec<u8>,
+    }
+
+    impl OffchainMessage {
+        /// Length of the version 0 header: format and message length
+        pub const HEADER_LEN: usize = 3;
+        /// Maximum message length, bounded by the u16 length of the whole serialized message
+        pub const MAX_LEN: usize = u16::MAX as usize - SIGNING_DOMAIN.len() - 1 - Self::HEADER_LEN;
+        /// Maximum message length a hardware wallet can sign
+        pub const MAX_LEN_LEDGER: usize =
+            PACKET_DATA_SIZE - SIGNING_DOMAIN.len() - 1 - Self::HEADER_LEN;
+
+        /// Construct a new message, choosing the most restrictive format that fits it
+        pub fn new(message: &[u8]) -> Result<Self, SanitizeError> {
+            let format = if message.is_empty() {
+                return Err(SanitizeError::InvalidValue);
+            } else if message.len() <= Self::MAX_LEN_LEDGER {
+                if is_printable_ascii(message) {
+                    MessageFormat::RestrictedAscii
+                } else if is_utf8(message) {
+                    MessageFormat::LimitedUtf8
+                } else {
+                    return Err(SanitizeError::InvalidValue);
+                }
+            } else if message.len() <= Self::MAX_LEN {
+                if is_utf8(message) {
+                    MessageFormat::ExtendedUtf8
+                } else {
+                    return Err(SanitizeError::InvalidValue);
+                }
+            } else {
+                return Err(SanitizeError::ValueOutOfBounds);
+            };
+            Ok(Self {
+                format,
+                message: message.to_vec(),
+            })
+        }
+
+        /// Serialize the message header and body, appending them to `data`
+        pub fn serialize(&self, data: &mut Vec<u8>) -> Result<(), SanitizeError> {
+            if self.message.is_empty() || self.message.len() > Self::MAX_LEN {
+                return Err(SanitizeError::ValueOutOfBounds);
+            }
+            data.reserve(Self::HEADER_LEN + self.message.len());
+            data.push(self.format as u8);
+            data.extend_from_slice(&(self.message.len() as u16).to_le_bytes());
+            data.extend_from_slice(&self.message);
+            Ok(())
+        }
+
+        /// Deserialize the message header and body, checking the body matches its format
+        pub fn deserialize(data: &[u8]) -> Result<Self, SanitizeError> {
+            if data.len() <= Self::HEADER_LEN {
+                return Err(SanitizeError::ValueOutOfBounds);
+            }
+            let format = MessageFormat::from_u8(data[0])?;
+            let message_len = u16::from_le_bytes([data[1], data[2]]) as usize;
+            let message = &data[Self::HEADER_LEN..];
+            if message.len() != message_len {
+                return Err(SanitizeError::ValueOutOfBounds);
+            }
+            let valid = match format {
+                MessageFormat::RestrictedAscii => {
+                    message.len() <= Self::MAX_LEN_LEDGER && is_printable_ascii(message)
+                }
+                MessageFormat::LimitedUtf8 => {
+                    message.len() <= Self::MAX_LEN_LEDGER && is_utf8(message)
+                }
+                MessageFormat::ExtendedUtf8 => message.len() <= Self::MAX_LEN && is_utf8(message),
+            };
+            if valid {
+                Ok(Self {
+                    format,
+                    message: message.to_vec(),
+                })
+            } else {
+                Err(SanitizeError::InvalidValue)
+            }
+        }
+
+        pub fn get_format(&self) -> MessageFormat {
+            self.format
+        }
+
+        pub fn get_message(&self) -> &[u8] {
+            &self.message
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OffchainMessage {
+    V0(v0::OffchainMessage),
+}
+
+impl OffchainMessage {
+    /// Construct a new message of the given version
+    pub fn new(version: u8, message: &[u8]) -> Result<Self, SanitizeError> {
+        match version {
+            0 => Ok(Self::V0(v0::OffchainMessage::new(message)?)),
+            _ => Err(SanitizeError::ValueOutOfBounds),
+        }
+    }
+
+    /// Serialize the message, including the signing domain and version, ready to be signed
+    pub fn serialize(&self) -> Result<Vec<u8>, SanitizeError> {
+        let mut data = SIGNING_DOMAIN.to_vec();
+        data.push(self.get_version());
+        match self {
+            Self::V0(message) => message.serialize(&mut data)?,
+        }
+        Ok(data)
+    }
+
+    /// Deserialize a message produced by `serialize`
+    pub fn deserialize(data: &[u8]) -> Result<Self, SanitizeError> {
+        if data.len() <= SIGNING_DOMAIN.len() || !data.starts_with(SIGNING_DOMAIN) {
+            return Err(SanitizeError::InvalidValue);
+        }
+        let version = data[SIGNING_DOMAIN.len()];
+        let data = &data[SIGNING_DOMAIN.len() + 1..];
+        match version {
+            0 => Ok(Self::V0(v0::OffchainMessage::deserialize(data)?)),
+            _ => Err(SanitizeError::ValueOutOfBounds),
+        }
+    }
+
+    pub fn get_version(&self) -> u8 {
+        match self {
+            Self::V0(_) => 0,
+        }
+    }
+
+    pub fn get_format(&self) -> MessageFormat {
+        match self {
+            Self::V0(message) => message.get_format(),
+        }
+    }
+
+    pub fn get_message(&self) -> &[u8] {
+        match self {
+            Self::V0(message) => message.get_message(),
+        }
+    }
+
+    /// Sign the serialized message
+    pub fn sign(&self, signer: &dyn Signer) -> Result<Signature, SignerError> {
+        let data = self
+            .serialize()
+            .map_err(|err| SignerError::InvalidInput(err.to_string()))?;
+        signer.try_sign_message(&data)
+    }
+
+    /// Verify that `signature` is `signer`'s signature of the serialized message
+    pub fn verify(&self, signer: &Pubkey, signature: &Signature) -> Result<bool, SanitizeError> {
+        Ok(signature.verify(signer.as_ref(), &self.serialize()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::signature::Keypair};
+
+    #[test]
+    fn test_offchain_message_formats() {
+        let message = OffchainMessage::new(0, b"Test Message").unwrap();
+        assert_eq!(message.get_version(), 0);
+        assert_eq!(message.get_format(), MessageFormat::RestrictedAscii);
+        assert_eq!(message.get_message(), b"Test Message");
+
+        let message = OffchainMessage::new(0, "Тестовое сообщение".as_bytes()).unwrap();
+        assert_eq!(message.get_format(), MessageFormat::LimitedUtf8);
+
+        let long_message = vec![b'a'; v0::OffchainMessage::MAX_LEN_LEDGER + 1];
+        let message = OffchainMessage::new(0, &long_message).unwrap();
+        assert_eq!(message.get_format(), MessageFormat::ExtendedUtf8);
+
+        assert_eq!(
+            OffchainMessage::new(0, &[]),
+            Err(SanitizeError::InvalidValue)
+        );
+        assert_eq!(
+            OffchainMessage::new(0, &[0xff, 0xfe]),
+            Err(SanitizeError::InvalidValue)
+        );
+        assert_eq!(
+            OffchainMessage::new(0, &vec![b'a'; v0::OffchainMessage::MAX_LEN + 1]),
+            Err(SanitizeError::ValueOutOfBounds)
+        );
+        assert_eq!(
+            OffchainMessage::new(1, b"Test Message"),
+            Err(SanitizeError::ValueOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_offchain_message_serialization() {
+        let message = OffchainMessage::new(0, b"Test Message").unwrap();
+        let serialized = message.serialize().unwrap();
+        assert!(serialized.starts_with(SIGNING_DOMAIN));
+        assert_eq!(
+            &serialized[SIGNING_DOMAIN.len()..],
+            &[&[0u8, 0, 12, 0][..], &b"Test Message"[..]].concat()[..]
+        );
+        assert_eq!(OffchainMessage::deserialize(&serialized).unwrap(), message);
+
+        // length mismatch
+        let mut truncated = serialized.clone();
+        truncated.pop();
+        assert!(OffchainMessage::deserialize(&truncated).is_err());
+
+        // restricted ascii format with a non-printable body
+        let mut tampered = serialized.clone();
+        *tampered.last_mut().unwrap() = b'\n';
+        assert_eq!(
+            OffchainMessage::deserialize(&tampered),
+            Err(SanitizeError::InvalidValue)
+        );
+
+        // missing signing domain
+        assert!(OffchainMessage::deserialize(&serialized[1..]).is_err());
+    }
+
+    #[test]
+    fn test_offchain_message_sign_and_verify() {
+        let keypair = Keypair::new();
+        let message = OffchainMessage::new(0, b"Test Message").unwrap();
+        let signature = message.sign(&keypair).unwrap();
+        assert!(message.verify(&keypair.pubkey(), &signature).unwrap());
+        assert!(!message.verify(&Pubkey::new_unique(), &signature).unwrap());
+
+        let other_message = OffchainMessage::new(0, b"Other Message").unwrap();
+        assert!(!other_message.verify(&keypair.pubkey(), &signature).unwrap());
+    }
+}
@@ -4,7 +4,7 @@ use itertools::Itertools;
 use solana_sdk::{
     account::AccountSharedData,
     compute_budget::ComputeBudget,
-    feature_set::remove_native_loader,
+    feature_set::{remove_native_loader, stake_raise_minimum_delegation_to_1_sol},
     hash::Hash,
     instruction::{CompiledInstruction, Instruction, InstructionError},
     keyed_account::{create_keyed_accounts_unified, KeyedAccount},
@@ -108,6 +108,8 @@ pub trait InvokeContext {
     fn set_instruction_index(&mut self, instruction_index: usize);
     /// Record invoked instruction
     fn record_instruction(&self, instruction: &Instruction);
+    /// Record the compute units charged for invoking the last recorded instruction
+    fn record_invoke_units(&self, units: u64);
     /// Get the bank's active feature set
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool;
     /// Find an account_index and account by its key
@@ -139,6 +141,15 @@ pub trait InvokeContext {
     fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError>;
     /// Get the return data
     fn get_return_data(&self) -> (Pubkey, &[u8]);
+    /// Get the stack height of the current invocation, transaction level
+    /// instructions are at height 1
+    fn get_stack_height(&self) -> usize;
+    /// Get a successfully processed instruction which shares the current
+    /// invocation's parent, `index` 0 being the most recently processed one
+    fn get_processed_sibling_instruction(&self, index: usize) -> Option<&Instruction>;
+    /// Record a change a loader made to one of its programs, any executor cached for the
+    /// program before it is stale once the transaction commits
+    fn notify_program_event(&self, program_id: &Pubkey, event: ProgramEvent);
 }
 
 /// Convenience macro to log a message with an `Rc<RefCell<dyn Logger>>`
@@ -186,6 +197,15 @@ pub fn get_sysvar<T: Sysvar>(
     })
 }
 
+/// The minimum number of lamports a stake account can delegate
+pub fn get_minimum_stake_delegation(invoke_context: &dyn InvokeContext) -> u64 {
+    if invoke_context.is_feature_active(&stake_raise_minimum_delegation_to_1_sol::id()) {
+        stake_raise_minimum_delegation_to_1_sol::MINIMUM_DELEGATION
+    } else {
+        1
+    }
+}
+
 /// Compute meter
 pub trait ComputeMeter {
     /// Consume compute units
@@ -305,6 +325,14 @@ pub mod stable_log {
     }
 }
 
+/// A change a loader made to one of its programs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgramEvent {
+    Deployed,
+    Upgraded,
+    Closed,
+}
+
 /// Program executor
 pub trait Executor: Debug + Send + Sync {
     /// Execute the program
@@ -471,6 +499,7 @@ impl<'a> InvokeContext for MockInvokeContext<'a> {
     }
     fn set_instruction_index(&mut self, _instruction_index: usize) {}
     fn record_instruction(&self, _instruction: &Instruction) {}
+    fn record_invoke_units(&self, _units: u64) {}
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool {
         !self.disabled_features.contains(feature_id)
     }
@@ -522,4 +551,11 @@ impl<'a> InvokeContext for MockInvokeContext<'a> {
     fn get_return_data(&self) -> (Pubkey, &[u8]) {
         (self.return_data.0, &self.return_data.1)
     }
+    fn get_stack_height(&self) -> usize {
+        self.invoke_stack.len()
+    }
+    fn get_processed_sibling_instruction(&self, _index: usize) -> Option<&Instruction> {
+        None
+    }
+    fn notify_program_event(&self, _program_id: &Pubkey, _event: ProgramEvent) {}
 }
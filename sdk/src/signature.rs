@@ -12,7 +12,7 @@ use std::{
 use thiserror::Error;
 
 // legacy module paths
-pub use crate::signer::{keypair::*, null_signer::*, presigner::*, *};
+pub use crate::signer::{keypair::*, null_signer::*, presigner::*, signer_set::*, *};
 
 /// Number of bytes in a signature
 pub const SIGNATURE_BYTES: usize = 64;
@@ -13,6 +13,7 @@ use {
 pub mod keypair;
 pub mod null_signer;
 pub mod presigner;
+pub mod signer_set;
 pub mod signers;
 
 #[derive(Debug, Error, PartialEq)]
@@ -23,6 +24,12 @@ pub enum SignerError {
     #[error("not enough signers")]
     NotEnoughSigners,
 
+    #[error("missing signatures for {0:?}")]
+    MissingSignatures(Vec<Pubkey>),
+
+    #[error("{0} is not a signer of the message")]
+    UnexpectedSigner(Pubkey),
+
     #[error("transaction error")]
     TransactionError(#[from] TransactionError),
 
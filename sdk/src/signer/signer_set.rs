@@ -0,0 +1,154 @@
+#![cfg(feature = "full")]
+
+use crate::{
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{unique_signers, Signer, SignerError},
+};
+
+/// The signers of a message, given in any order.  A signer may be offline, a `NullSigner`
+/// standing in for a signature that is added later, or a hardware wallet, which is only asked
+/// to sign once the other signers have signed.
+pub struct SignerSet<'a> {
+    signers: Vec<&'a dyn Signer>,
+}
+
+impl<'a> SignerSet<'a> {
+    /// Duplicates of a signer are dropped
+    pub fn new(signers: Vec<&'a dyn Signer>) -> Self {
+        Self {
+            signers: unique_signers(signers),
+        }
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.signers.iter().map(|signer| signer.pubkey()).collect()
+    }
+
+    /// The keys of `signer_keys` that no signer of the set is for
+    pub fn absent_signers(&self, signer_keys: &[Pubkey]) -> Vec<Pubkey> {
+        let pubkeys = self.pubkeys();
+        signer_keys
+            .iter()
+            .filter(|signer_key| !pubkeys.contains(signer_key))
+            .cloned()
+            .collect()
+    }
+
+    /// Sign `message` for each of `signer_keys`, the required signers of the message in order.
+    /// The signature of a signer that is absent from the set or offline is `None`
+    pub fn try_partial_sign(
+        &self,
+        signer_keys: &[Pubkey],
+        message: &[u8],
+    ) -> Result<Vec<Option<Signature>>, SignerError> {
+        let mut positions = vec![];
+        for signer in &self.signers {
+            let pubkey = signer.try_pubkey()?;
+            let position = signer_keys
+                .iter()
+                .position(|signer_key| *signer_key == pubkey)
+                .ok_or(SignerError::UnexpectedSigner(pubkey))?;
+            positions.push((*signer, position));
+        }
+        // Sign with the hardware wallets last, so that they aren't prompted if another signer
+        // fails
+        positions.sort_by_key(|(signer, _)| signer.is_interactive());
+
+        let mut signatures = vec![None; signer_keys.len()];
+        for (signer, position) in positions {
+            let signature = signer.try_sign_message(message)?;
+            if signature != Signature::default() {
+                signatures[position] = Some(signature);
+            }
+        }
+        Ok(signatures)
+    }
+
+    /// Sign `message` for each of `signer_keys`, failing with `SignerError::MissingSignatures`
+    /// if any of them is absent from the set or offline
+    pub fn try_sign(
+        &self,
+        signer_keys: &[Pubkey],
+        message: &[u8],
+    ) -> Result<Vec<Signature>, SignerError> {
+        let absent_signers = self.absent_signers(signer_keys);
+        if !absent_signers.is_empty() {
+            return Err(SignerError::MissingSignatures(absent_signers));
+        }
+        let signatures = self.try_partial_sign(signer_keys, message)?;
+        let missing_signatures: Vec<_> = signer_keys
+            .iter()
+            .zip(&signatures)
+            .filter(|(_, signature)| signature.is_none())
+            .map(|(signer_key, _)| *signer_key)
+            .collect();
+        if !missing_signatures.is_empty() {
+            return Err(SignerError::MissingSignatures(missing_signatures));
+        }
+        Ok(signatures.into_iter().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::signature::{Keypair, NullSigner, Presigner},
+    };
+
+    #[test]
+    fn test_signer_set_out_of_order() {
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let signer_keys = [alice.pubkey(), bob.pubkey()];
+        let message = b"message";
+
+        let signatures = SignerSet::new(vec![&bob, &alice, &bob])
+            .try_sign(&signer_keys, message)
+            .unwrap();
+        assert!(signatures
+            .iter()
+            .zip(&signer_keys)
+            .all(|(signature, pubkey)| signature.verify(pubkey.as_ref(), message)));
+
+        let stranger = Keypair::new();
+        assert_eq!(
+            SignerSet::new(vec![&alice, &stranger]).try_partial_sign(&signer_keys, message),
+            Err(SignerError::UnexpectedSigner(stranger.pubkey()))
+        );
+    }
+
+    #[test]
+    fn test_signer_set_missing_signatures() {
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Keypair::new();
+        let signer_keys = [alice.pubkey(), bob.pubkey(), carol.pubkey()];
+        let message = b"message";
+
+        // Bob signs offline, Carol is absent
+        let offline_bob = NullSigner::new(&bob.pubkey());
+        let signer_set = SignerSet::new(vec![&alice, &offline_bob]);
+        assert_eq!(
+            signer_set.absent_signers(&signer_keys),
+            vec![carol.pubkey()]
+        );
+        let signatures = signer_set.try_partial_sign(&signer_keys, message).unwrap();
+        assert_eq!(
+            signatures,
+            vec![Some(alice.sign_message(message)), None, None]
+        );
+        assert_eq!(
+            signer_set.try_sign(&signer_keys, message),
+            Err(SignerError::MissingSignatures(vec![carol.pubkey()]))
+        );
+
+        let presigned_carol = Presigner::new(&carol.pubkey(), &carol.sign_message(message));
+        assert_eq!(
+            SignerSet::new(vec![&alice, &offline_bob, &presigned_carol])
+                .try_sign(&signer_keys, message),
+            Err(SignerError::MissingSignatures(vec![bob.pubkey()]))
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Assembles a transaction from instructions, requesting the compute units it needs and
+//! paying a priority fee with compute budget instructions, and compiling it into a v0 message
+//! when address lookup tables are given.
+
+use {
+    crate::{
+        address_lookup_table_account::AddressLookupTableAccount,
+        builtin_instruction_costs::BUILT_IN_INSTRUCTION_COSTS,
+        compute_budget::{ComputeBudgetInstruction, MAX_UNITS},
+        hash::Hash,
+        instruction::Instruction,
+        message::{
+            v0::{self, CompileError},
+            Message, VersionedMessage,
+        },
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        signature::SignerError,
+        signers::Signers,
+        transaction::VersionedTransaction,
+    },
+    solana_program::{system_instruction::SystemInstruction, system_program},
+    thiserror::Error,
+};
+
+/// Compute units assumed for an instruction of a program that isn't a builtin, the default
+/// compute budget of an instruction
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u32 = 200_000;
+
+/// Percentage added to the units a simulation consumed, as execution may take a different path
+/// once the transaction lands
+pub const SIMULATION_COMPUTE_UNIT_MARGIN_PERCENT: u32 = 10;
+
+fn is_advance_nonce_instruction(instruction: &Instruction) -> bool {
+    system_program::check_id(&instruction.program_id)
+        && matches!(
+            limited_deserialize(&instruction.data),
+            Ok(SystemInstruction::AdvanceNonceAccount)
+        )
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TransactionBuilderError {
+    #[error("compile error: {0}")]
+    CompileError(#[from] CompileError),
+
+    #[error("signer error: {0}")]
+    SignerError(#[from] SignerError),
+
+    #[error("simulation failed: {0}")]
+    SimulationFailed(String),
+}
+
+/// How many compute units a built transaction requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeUnitLimit {
+    /// Don't request units, the transaction gets the default compute budget
+    Default,
+    /// Request exactly this many units
+    Fixed(u32),
+    /// Request the units the cost model would charge the instructions, the default compute
+    /// budget for each instruction of a program that isn't a builtin
+    Estimated,
+}
+
+impl Default for ComputeUnitLimit {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+/// Builds a transaction out of instructions.
+///
+/// ```
+/// # use solana_sdk::{
+/// #     hash::Hash,
+/// #     signature::{Keypair, Signer},
+/// #     system_instruction,
+/// #     transaction::{ComputeUnitLimit, TransactionBuilder},
+/// # };
+/// let payer = Keypair::new();
+/// let to = Keypair::new();
+/// let transaction = TransactionBuilder::new(&payer.pubkey())
+///     .instruction(system_instruction::transfer(&payer.pubkey(), &to.pubkey(), 42))
+///     .compute_unit_limit(ComputeUnitLimit::Estimated)
+///     .compute_unit_price(1_000)
+///     .build(&[&payer], Hash::default())
+///     .unwrap();
+/// # assert_eq!(transaction.message.instructions().len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    address_lookup_table_accounts: Vec<AddressLookupTableAccount>,
+    compute_unit_limit: ComputeUnitLimit,
+    compute_unit_price: Option<u64>,
+}
+
+impl TransactionBuilder {
+    pub fn new(payer: &Pubkey) -> Self {
+        Self {
+            payer: *payer,
+            instructions: vec![],
+            address_lookup_table_accounts: vec![],
+            compute_unit_limit: ComputeUnitLimit::default(),
+            compute_unit_price: None,
+        }
+    }
+
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn instructions<I: IntoIterator<Item = Instruction>>(mut self, instructions: I) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// Load the accounts found in `address_lookup_table_account` from the table, which
+    /// compiles the transaction into a v0 message
+    pub fn address_lookup_table(
+        mut self,
+        address_lookup_table_account: AddressLookupTableAccount,
+    ) -> Self {
+        self.address_lookup_table_accounts
+            .push(address_lookup_table_account);
+        self
+    }
+
+    pub fn compute_unit_limit(mut self, compute_unit_limit: ComputeUnitLimit) -> Self {
+        self.compute_unit_limit = compute_unit_limit;
+        self
+    }
+
+    /// Pay a priority fee of `micro_lamports` per requested compute unit
+    pub fn compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// The compute units the cost model would charge the instructions
+    pub fn estimate_compute_units(&self) -> u32 {
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                BUILT_IN_INSTRUCTION_COSTS
+                    .get(&instruction.program_id)
+                    .map_or(DEFAULT_INSTRUCTION_COMPUTE_UNITS, |units| *units as u32)
+            })
+            .fold(0u32, |units, instruction_units| {
+                units.saturating_add(instruction_units)
+            })
+            .min(MAX_UNITS)
+    }
+
+    fn requested_compute_units(&self) -> Option<u32> {
+        match self.compute_unit_limit {
+            ComputeUnitLimit::Default => None,
+            ComputeUnitLimit::Fixed(units) => Some(units),
+            ComputeUnitLimit::Estimated => Some(self.estimate_compute_units()),
+        }
+    }
+
+    /// The instructions, with the compute budget instructions first.  They follow the advance
+    /// nonce instruction of a transaction using a durable nonce, which must come first
+    fn instructions_with_compute_budget(&self, compute_units: Option<u32>) -> Vec<Instruction> {
+        let compute_budget_instructions = compute_units
+            .map(ComputeBudgetInstruction::request_units)
+            .into_iter()
+            .chain(
+                self.compute_unit_price
+                    .map(ComputeBudgetInstruction::set_compute_unit_price),
+            );
+        let num_nonce_instructions = self
+            .instructions
+            .first()
+            .filter(|instruction| is_advance_nonce_instruction(instruction))
+            .map_or(0, |_| 1);
+        let (nonce_instructions, instructions) = self.instructions.split_at(num_nonce_instructions);
+        nonce_instructions
+            .iter()
+            .cloned()
+            .chain(compute_budget_instructions)
+            .chain(instructions.iter().cloned())
+            .collect()
+    }
+
+    fn compile(
+        &self,
+        compute_units: Option<u32>,
+        recent_blockhash: Hash,
+    ) -> Result<VersionedMessage, CompileError> {
+        let instructions = self.instructions_with_compute_budget(compute_units);
+        if self.address_lookup_table_accounts.is_empty() {
+            let mut message = Message::new(&instructions, Some(&self.payer));
+            message.recent_blockhash = recent_blockhash;
+            Ok(VersionedMessage::Legacy(message))
+        } else {
+            v0::Message::try_compile(
+                &self.payer,
+                &instructions,
+                &self.address_lookup_table_accounts,
+                recent_blockhash,
+            )
+            .map(VersionedMessage::V0)
+        }
+    }
+
+    /// The unsigned message of the transaction
+    pub fn build_message(
+        &self,
+        recent_blockhash: Hash,
+    ) -> Result<VersionedMessage, TransactionBuilderError> {
+        Ok(self.compile(self.requested_compute_units(), recent_blockhash)?)
+    }
+
+    pub fn build<T: Signers>(
+        &self,
+        signers: &T,
+        recent_blockhash: Hash,
+    ) -> Result<VersionedTransaction, TransactionBuilderError> {
+        let message = self.build_message(recent_blockhash)?;
+        Ok(VersionedTransaction::try_new(message, signers)?)
+    }
+
+    /// Build a transaction requesting the units that `simulate` reports a simulation of it
+    /// consumed, plus a margin.  The simulated transaction requests the maximum units, so that
+    /// the simulation doesn't run out of them
+    pub fn build_with_simulation<T, F>(
+        &self,
+        signers: &T,
+        recent_blockhash: Hash,
+        simulate: F,
+    ) -> Result<VersionedTransaction, TransactionBuilderError>
+    where
+        T: Signers,
+        F: FnOnce(&VersionedTransaction) -> Result<u64, String>,
+    {
+        let message = self.compile(Some(MAX_UNITS), recent_blockhash)?;
+        let transaction = VersionedTransaction::try_new(message, signers)?;
+        let units_consumed =
+            simulate(&transaction).map_err(TransactionBuilderError::SimulationFailed)?;
+        let compute_units = units_consumed
+            .saturating_mul(u64::from(100 + SIMULATION_COMPUTE_UNIT_MARGIN_PERCENT))
+            / 100;
+        let compute_units = compute_units.min(u64::from(MAX_UNITS)) as u32;
+
+        let message = self.compile(Some(compute_units), recent_blockhash)?;
+        Ok(VersionedTransaction::try_new(message, signers)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            borsh::try_from_slice_unchecked,
+            compute_budget,
+            message::VersionedMessage,
+            signature::{Keypair, Signer},
+            system_instruction,
+        },
+    };
+
+    fn compute_budget_instructions(
+        transaction: &VersionedTransaction,
+    ) -> Vec<ComputeBudgetInstruction> {
+        let account_keys: Vec<_> = transaction.message.static_account_keys_iter().collect();
+        transaction
+            .message
+            .instructions()
+            .iter()
+            .filter(|instruction| {
+                compute_budget::check_id(account_keys[instruction.program_id_index as usize])
+            })
+            .map(|instruction| try_from_slice_unchecked(&instruction.data).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_build() {
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let builder = TransactionBuilder::new(&payer.pubkey())
+            .instruction(system_instruction::transfer(&payer.pubkey(), &to, 42))
+            .instruction(Instruction::new_with_bytes(program_id, &[], vec![]));
+
+        let transaction = builder.build(&[&payer], Hash::default()).unwrap();
+        assert!(transaction.verify_and_hash_message().is_ok());
+        assert!(matches!(transaction.message, VersionedMessage::Legacy(_)));
+        assert!(compute_budget_instructions(&transaction).is_empty());
+
+        assert_eq!(
+            builder.estimate_compute_units(),
+            400 + DEFAULT_INSTRUCTION_COMPUTE_UNITS
+        );
+        assert_eq!(
+            TransactionBuilder::new(&payer.pubkey())
+                .instruction(Instruction::new_with_bytes(
+                    crate::vote::program::id(),
+                    &[],
+                    vec![]
+                ))
+                .estimate_compute_units(),
+            3_400
+        );
+        let transaction = builder
+            .clone()
+            .compute_unit_limit(ComputeUnitLimit::Estimated)
+            .compute_unit_price(5)
+            .build(&[&payer], Hash::default())
+            .unwrap();
+        assert_eq!(
+            compute_budget_instructions(&transaction),
+            vec![
+                ComputeBudgetInstruction::RequestUnits(400 + DEFAULT_INSTRUCTION_COMPUTE_UNITS),
+                ComputeBudgetInstruction::SetComputeUnitPrice(5),
+            ]
+        );
+
+        assert_eq!(
+            builder.build(&[&Keypair::new()], Hash::default()),
+            Err(TransactionBuilderError::SignerError(
+                SignerError::KeypairPubkeyMismatch
+            ))
+        );
+    }
+
+    #[test]
+    fn test_build_after_advance_nonce() {
+        let payer = Keypair::new();
+        let nonce = Pubkey::new_unique();
+        let transaction = TransactionBuilder::new(&payer.pubkey())
+            .instruction(system_instruction::advance_nonce_account(
+                &nonce,
+                &payer.pubkey(),
+            ))
+            .instruction(system_instruction::transfer(
+                &payer.pubkey(),
+                &Pubkey::new_unique(),
+                42,
+            ))
+            .compute_unit_limit(ComputeUnitLimit::Fixed(1_000))
+            .build(&[&payer], Hash::default())
+            .unwrap();
+        let account_keys: Vec<_> = transaction.message.static_account_keys_iter().collect();
+        let program_ids: Vec<_> = transaction
+            .message
+            .instructions()
+            .iter()
+            .map(|instruction| *account_keys[instruction.program_id_index as usize])
+            .collect();
+        assert_eq!(
+            program_ids,
+            vec![
+                system_program::id(),
+                compute_budget::id(),
+                system_program::id()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_with_lookup_table() {
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let transaction = TransactionBuilder::new(&payer.pubkey())
+            .instruction(system_instruction::transfer(&payer.pubkey(), &to, 42))
+            .address_lookup_table(AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![to],
+            })
+            .build(&[&payer], Hash::default())
+            .unwrap();
+        assert!(transaction.verify_and_hash_message().is_ok());
+        match transaction.message {
+            VersionedMessage::V0(message) => {
+                assert!(!message.account_keys.contains(&to));
+                assert_eq!(message.address_table_lookups[0].writable_indexes, vec![0]);
+            }
+            message => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    #[test]
+    fn test_build_with_simulation() {
+        let payer = Keypair::new();
+        let builder = TransactionBuilder::new(&payer.pubkey()).instruction(
+            system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 42),
+        );
+
+        let transaction = builder
+            .build_with_simulation(&[&payer], Hash::default(), |transaction| {
+                assert_eq!(
+                    compute_budget_instructions(transaction),
+                    vec![ComputeBudgetInstruction::RequestUnits(MAX_UNITS)]
+                );
+                Ok(1_000)
+            })
+            .unwrap();
+        assert_eq!(
+            compute_budget_instructions(&transaction),
+            vec![ComputeBudgetInstruction::RequestUnits(1_100)]
+        );
+
+        assert_eq!(
+            builder.build_with_simulation(&[&payer], Hash::default(), |_| Err(
+                "insufficient funds".to_string()
+            )),
+            Err(TransactionBuilderError::SimulationFailed(
+                "insufficient funds".to_string()
+            ))
+        );
+    }
+}
@@ -13,7 +13,7 @@ use {
         pubkey::Pubkey,
         sanitize::{Sanitize, SanitizeError},
         short_vec,
-        signature::{Signature, SignerError},
+        signature::{Signature, SignerError, SignerSet},
         signers::Signers,
     },
     serde::Serialize,
@@ -24,9 +24,11 @@ use {
     thiserror::Error,
 };
 
+mod builder;
 mod sanitized;
 mod versioned;
 
+pub use builder::*;
 pub use sanitized::*;
 pub use versioned::*;
 
@@ -122,6 +124,26 @@ pub enum TransactionError {
     /// Transaction loads a writable account that cannot be written
     #[error("Transaction loads a writable account that cannot be written")]
     InvalidWritableAccount,
+
+    /// Transaction loads an address table account that doesn't exist
+    #[error("Transaction loads an address table account that doesn't exist")]
+    AddressLookupTableNotFound,
+
+    /// Transaction loads an address table account with an invalid owner
+    #[error("Transaction loads an address table account with an invalid owner")]
+    InvalidAddressLookupTableOwner,
+
+    /// Transaction loads an address table account with invalid data
+    #[error("Transaction loads an address table account with invalid data")]
+    InvalidAddressLookupTableData,
+
+    /// Transaction address table lookup uses an invalid index
+    #[error("Transaction address table lookup uses an invalid index")]
+    InvalidAddressLookupTableIndex,
+
+    /// Transaction leaves an account with a lower balance than rent-exempt minimum
+    #[error("Transaction leaves an account with a lower balance than rent-exempt minimum")]
+    InvalidRentPayingAccount,
 }
 
 pub type Result<T> = result::Result<T, TransactionError>;
@@ -372,6 +394,84 @@ impl Transaction {
         positions: Vec<usize>,
         recent_blockhash: Hash,
     ) -> result::Result<(), SignerError> {
+        self.update_recent_blockhash(recent_blockhash);
+
+        let signatures = keypairs.try_sign_message(&self.message_data())?;
+        for i in 0..positions.len() {
+            self.signatures[positions[i]] = signatures[i];
+        }
+        Ok(())
+    }
+
+    /// Sign with the signers of `signer_set`, keeping any signatures already in place, so
+    /// that signers that are absent or offline can add theirs later.  If recent_blockhash is
+    /// not the same as currently in the transaction, clear any prior signatures and update
+    /// recent_blockhash
+    pub fn try_partial_sign_with_signer_set(
+        &mut self,
+        signer_set: &SignerSet,
+        recent_blockhash: Hash,
+    ) -> result::Result<(), SignerError> {
+        self.update_recent_blockhash(recent_blockhash);
+        let signer_keys = self.signer_keys()?;
+        let signatures = signer_set.try_partial_sign(signer_keys, &self.message_data())?;
+        for (position, signature) in signatures.into_iter().enumerate() {
+            if let Some(signature) = signature {
+                self.signatures[position] = signature;
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`Transaction::try_partial_sign_with_signer_set`], failing with
+    /// `SignerError::MissingSignatures` naming the signers whose signatures are still missing.
+    /// No signer is asked to sign if a signer is absent from `signer_set`
+    pub fn try_sign_with_signer_set(
+        &mut self,
+        signer_set: &SignerSet,
+        recent_blockhash: Hash,
+    ) -> result::Result<(), SignerError> {
+        self.update_recent_blockhash(recent_blockhash);
+        let missing_signers = self.missing_signers();
+        let absent_signers: Vec<_> = signer_set
+            .absent_signers(self.signer_keys()?)
+            .into_iter()
+            .filter(|pubkey| missing_signers.contains(pubkey))
+            .collect();
+        if !absent_signers.is_empty() {
+            return Err(SignerError::MissingSignatures(absent_signers));
+        }
+
+        self.try_partial_sign_with_signer_set(signer_set, recent_blockhash)?;
+        let missing_signers = self.missing_signers();
+        if !missing_signers.is_empty() {
+            return Err(SignerError::MissingSignatures(missing_signers));
+        }
+        Ok(())
+    }
+
+    /// The required signers that haven't signed yet
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.signatures
+            .iter()
+            .zip(&self.message.account_keys)
+            .filter(|(signature, _)| **signature == Signature::default())
+            .map(|(_, pubkey)| *pubkey)
+            .collect()
+    }
+
+    fn signer_keys(&self) -> Result<&[Pubkey]> {
+        let num_required_signatures = self.message.header.num_required_signatures as usize;
+        if self.signatures.len() != num_required_signatures {
+            return Err(TransactionError::InvalidAccountIndex);
+        }
+        self.message
+            .account_keys
+            .get(..num_required_signatures)
+            .ok_or(TransactionError::InvalidAccountIndex)
+    }
+
+    fn update_recent_blockhash(&mut self, recent_blockhash: Hash) {
         // if you change the blockhash, you're re-signing...
         if recent_blockhash != self.message.recent_blockhash {
             self.message.recent_blockhash = recent_blockhash;
@@ -379,12 +479,6 @@ impl Transaction {
                 .iter_mut()
                 .for_each(|signature| *signature = Signature::default());
         }
-
-        let signatures = keypairs.try_sign_message(&self.message_data())?;
-        for i in 0..positions.len() {
-            self.signatures[positions[i]] = signatures[i];
-        }
-        Ok(())
     }
 
     /// Verify the transaction
@@ -528,7 +622,7 @@ mod tests {
     use crate::{
         hash::hash,
         instruction::AccountMeta,
-        signature::{Keypair, Presigner, Signer},
+        signature::{Keypair, NullSigner, Presigner, Signer},
         system_instruction,
     };
     use bincode::{deserialize, serialize, serialized_size};
@@ -828,6 +922,51 @@ mod tests {
         assert!(tx.is_signed());
     }
 
+    #[test]
+    fn test_sign_with_signer_set() {
+        let keypair0 = Keypair::new();
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let ix = Instruction::new_with_bincode(
+            Pubkey::default(),
+            &0,
+            vec![
+                AccountMeta::new(keypair0.pubkey(), true),
+                AccountMeta::new(keypair1.pubkey(), true),
+                AccountMeta::new(keypair2.pubkey(), true),
+            ],
+        );
+        let message = Message::new(&[ix], Some(&keypair0.pubkey()));
+        let mut tx = Transaction::new_unsigned(message);
+
+        // keypair1 signs offline later, keypair2 is absent
+        let offline_signer = NullSigner::new(&keypair1.pubkey());
+        let signer_set = SignerSet::new(vec![&offline_signer, &keypair0]);
+        assert_eq!(
+            tx.try_sign_with_signer_set(&signer_set, Hash::default()),
+            Err(SignerError::MissingSignatures(vec![keypair2.pubkey()]))
+        );
+        assert_eq!(tx.missing_signers().len(), 3);
+        tx.try_partial_sign_with_signer_set(&signer_set, Hash::default())
+            .unwrap();
+        assert_eq!(
+            tx.missing_signers(),
+            vec![keypair1.pubkey(), keypair2.pubkey()]
+        );
+
+        // The signatures already in place are kept
+        let signer_set = SignerSet::new(vec![&keypair2]);
+        assert_eq!(
+            tx.try_sign_with_signer_set(&signer_set, Hash::default()),
+            Err(SignerError::MissingSignatures(vec![keypair1.pubkey()]))
+        );
+        let signer_set = SignerSet::new(vec![&keypair2, &keypair1]);
+        tx.try_sign_with_signer_set(&signer_set, Hash::default())
+            .unwrap();
+        assert!(tx.is_signed());
+        assert!(tx.verify().is_ok());
+    }
+
     #[test]
     #[should_panic]
     fn test_transaction_missing_keypair() {
@@ -3,7 +3,10 @@
 use {
     crate::{
         hash::Hash,
-        message::{v0, MappedAddresses, MappedMessage, SanitizedMessage, VersionedMessage},
+        message::{
+            v0::MessageAddressTableLookup, LoadedAddresses, LoadedMessage, SanitizedMessage,
+            VersionedMessage,
+        },
         nonce::NONCED_TX_MARKER_IX_INDEX,
         precompiles::verify_if_precompile,
         program_utils::limited_deserialize,
@@ -54,20 +57,20 @@ impl TryFrom<Transaction> for SanitizedTransaction {
 
 impl SanitizedTransaction {
     /// Create a sanitized transaction from an unsanitized transaction.
-    /// If the input transaction uses address maps, attempt to map the
-    /// transaction keys to full addresses.
+    /// If the input transaction uses address lookup tables, attempt to load
+    /// the addresses referenced by its table lookups.
     pub fn try_create(
         tx: VersionedTransaction,
         message_hash: Hash,
-        address_mapper: impl Fn(&v0::Message) -> Result<MappedAddresses>,
+        address_loader: impl Fn(&[MessageAddressTableLookup]) -> Result<LoadedAddresses>,
     ) -> Result<Self> {
         tx.sanitize()?;
 
         let signatures = tx.signatures;
         let message = match tx.message {
             VersionedMessage::Legacy(message) => SanitizedMessage::Legacy(message),
-            VersionedMessage::V0(message) => SanitizedMessage::V0(MappedMessage {
-                mapped_addresses: address_mapper(&message)?,
+            VersionedMessage::V0(message) => SanitizedMessage::V0(LoadedMessage {
+                loaded_addresses: address_loader(&message.address_table_lookups)?,
                 message,
             }),
         };
@@ -114,9 +117,9 @@ impl SanitizedTransaction {
     pub fn to_versioned_transaction(&self) -> VersionedTransaction {
         let signatures = self.signatures.clone();
         match &self.message {
-            SanitizedMessage::V0(mapped_msg) => VersionedTransaction {
+            SanitizedMessage::V0(loaded_msg) => VersionedTransaction {
                 signatures,
-                message: VersionedMessage::V0(mapped_msg.message.clone()),
+                message: VersionedMessage::V0(loaded_msg.message.clone()),
             },
             SanitizedMessage::Legacy(message) => VersionedTransaction {
                 signatures,
@@ -178,7 +181,7 @@ impl SanitizedTransaction {
     fn message_data(&self) -> Vec<u8> {
         match &self.message {
             SanitizedMessage::Legacy(message) => message.serialize(),
-            SanitizedMessage::V0(mapped_msg) => mapped_msg.message.serialize(),
+            SanitizedMessage::V0(loaded_msg) => loaded_msg.message.serialize(),
         }
     }
 
@@ -8,7 +8,8 @@ use {
         message::VersionedMessage,
         sanitize::{Sanitize, SanitizeError},
         short_vec,
-        signature::Signature,
+        signature::{Signature, SignerError},
+        signers::Signers,
         transaction::{Result, Transaction, TransactionError},
     },
     serde::Serialize,
@@ -35,9 +36,9 @@ impl Sanitize for VersionedTransaction {
             return Err(SanitizeError::IndexOutOfBounds);
         }
 
-        // Signatures are verified before message keys are mapped so all signers
-        // must correspond to unmapped keys.
-        if self.signatures.len() > self.message.unmapped_keys_len() {
+        // Signatures are verified before message keys are loaded so all signers
+        // must correspond to static account keys.
+        if self.signatures.len() > self.message.static_account_keys_len() {
             return Err(SanitizeError::IndexOutOfBounds);
         }
 
@@ -55,6 +56,42 @@ impl From<Transaction> for VersionedTransaction {
 }
 
 impl VersionedTransaction {
+    /// Signs `message` with `keypairs`, which must be all of the message's signers, in any
+    /// order
+    pub fn try_new<T: Signers>(
+        message: VersionedMessage,
+        keypairs: &T,
+    ) -> std::result::Result<Self, SignerError> {
+        let num_required_signatures = usize::from(message.header().num_required_signatures);
+        let signer_keys: Vec<_> = message
+            .static_account_keys_iter()
+            .take(num_required_signatures)
+            .collect();
+        let positions = keypairs
+            .try_pubkeys()?
+            .iter()
+            .map(|pubkey| {
+                signer_keys
+                    .iter()
+                    .position(|signer_key| *signer_key == pubkey)
+                    .ok_or(SignerError::KeypairPubkeyMismatch)
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut signatures = vec![Signature::default(); num_required_signatures];
+        let message_signatures = keypairs.try_sign_message(&message.serialize())?;
+        for (position, signature) in positions.into_iter().zip(message_signatures) {
+            signatures[position] = signature;
+        }
+        if signatures.contains(&Signature::default()) {
+            return Err(SignerError::NotEnoughSigners);
+        }
+        Ok(Self {
+            signatures,
+            message,
+        })
+    }
+
     /// Returns a legacy transaction if the transaction message is legacy.
     pub fn into_legacy_transaction(self) -> Option<Transaction> {
         match self.message {
@@ -72,7 +109,7 @@ impl VersionedTransaction {
         if self
             .signatures
             .iter()
-            .zip(self.message.unmapped_keys_iter())
+            .zip(self.message.static_account_keys_iter())
             .map(|(signature, pubkey)| signature.verify(pubkey.as_ref(), &message_bytes))
             .any(|verified| !verified)
         {
@@ -1,12 +1,16 @@
 use {
     crate::tpu_info::TpuInfo,
     log::*,
+    solana_client::connection_cache::ConnectionCache,
     solana_metrics::{datapoint_warn, inc_new_counter_info},
-    solana_runtime::{bank::Bank, bank_forks::BankForks},
-    solana_sdk::{hash::Hash, nonce_account, pubkey::Pubkey, signature::Signature},
+    solana_runtime::{bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache},
+    solana_sdk::{
+        commitment_config::CommitmentLevel, hash::Hash, nonce_account, pubkey::Pubkey,
+        signature::Signature,
+    },
     std::{
         collections::HashMap,
-        net::{SocketAddr, UdpSocket},
+        net::SocketAddr,
         sync::{
             mpsc::{Receiver, RecvTimeoutError},
             Arc, RwLock,
@@ -24,6 +28,8 @@ const DEFAULT_RETRY_RATE_MS: u64 = 2_000;
 const DEFAULT_LEADER_FORWARD_COUNT: u64 = 2;
 /// Default max number of time the service will retry broadcast
 const DEFAULT_SERVICE_MAX_RETRIES: usize = usize::MAX;
+/// Default upper bound of the retry interval when backing off exponentially
+pub const DEFAULT_MAX_RETRY_INTERVAL_MS: u64 = 30_000;
 
 pub struct SendTransactionService {
     thread: JoinHandle<()>,
@@ -36,6 +42,7 @@ pub struct TransactionInfo {
     pub durable_nonce_info: Option<(Pubkey, Hash)>,
     pub max_retries: Option<usize>,
     retries: usize,
+    last_sent_time: Option<Instant>,
 }
 
 impl TransactionInfo {
@@ -53,6 +60,26 @@ impl TransactionInfo {
             durable_nonce_info,
             max_retries,
             retries: 0,
+            last_sent_time: None,
+        }
+    }
+
+    /// Returns true if enough time has passed since the transaction was last sent for it to be
+    /// retried again under `retry_backoff`
+    fn is_due_for_retry(&self, retry_rate_ms: u64, retry_backoff: RetryBackoff) -> bool {
+        match (retry_backoff, self.last_sent_time) {
+            (RetryBackoff::Constant, _) | (_, None) => true,
+            (
+                RetryBackoff::Exponential {
+                    max_retry_interval_ms,
+                },
+                Some(last_sent_time),
+            ) => {
+                let retry_interval_ms = retry_rate_ms
+                    .saturating_mul(2u64.saturating_pow(self.retries as u32))
+                    .min(max_retry_interval_ms);
+                last_sent_time.elapsed().as_millis() as u64 >= retry_interval_ms
+            }
         }
     }
 }
@@ -65,6 +92,16 @@ struct ProcessTransactionsResult {
     max_retries_elapsed: u64,
     failed: u64,
     retained: u64,
+    backed_off: u64,
+}
+
+/// How the interval between successive retries of the same transaction evolves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RetryBackoff {
+    /// Retry every `retry_rate_ms`
+    Constant,
+    /// Double the retry interval after every retry, up to `max_retry_interval_ms`
+    Exponential { max_retry_interval_ms: u64 },
 }
 
 #[derive(Clone, Debug)]
@@ -73,6 +110,10 @@ pub struct Config {
     pub leader_forward_count: u64,
     pub default_max_retries: Option<usize>,
     pub service_max_retries: usize,
+    /// The bank commitment against which a transaction is considered landed, and so no
+    /// longer retried. Requires a `BlockCommitmentCache` for `Confirmed`.
+    pub retry_commitment: CommitmentLevel,
+    pub retry_backoff: RetryBackoff,
 }
 
 impl Default for Config {
@@ -82,6 +123,8 @@ impl Default for Config {
             leader_forward_count: DEFAULT_LEADER_FORWARD_COUNT,
             default_max_retries: None,
             service_max_retries: DEFAULT_SERVICE_MAX_RETRIES,
+            retry_commitment: CommitmentLevel::Processed,
+            retry_backoff: RetryBackoff::Constant,
         }
     }
 }
@@ -100,37 +143,71 @@ impl SendTransactionService {
             leader_forward_count,
             ..Config::default()
         };
-        Self::new_with_config(tpu_address, bank_forks, leader_info, receiver, config)
+        Self::new_with_config(
+            tpu_address,
+            bank_forks,
+            None,
+            leader_info,
+            receiver,
+            Arc::new(ConnectionCache::default()),
+            config,
+        )
     }
 
     pub fn new_with_config<T: TpuInfo + std::marker::Send + 'static>(
         tpu_address: SocketAddr,
         bank_forks: &Arc<RwLock<BankForks>>,
+        block_commitment_cache: Option<&Arc<RwLock<BlockCommitmentCache>>>,
         leader_info: Option<T>,
         receiver: Receiver<TransactionInfo>,
+        connection_cache: Arc<ConnectionCache>,
         config: Config,
     ) -> Self {
         let thread = Self::retry_thread(
             tpu_address,
             receiver,
             bank_forks.clone(),
+            block_commitment_cache.cloned(),
             leader_info,
+            connection_cache,
             config,
         );
         Self { thread }
     }
 
+    /// Returns the bank whose signature statuses decide whether a transaction has landed
+    fn retry_status_bank(
+        bank_forks: &BankForks,
+        block_commitment_cache: Option<&RwLock<BlockCommitmentCache>>,
+        retry_commitment: CommitmentLevel,
+    ) -> Arc<Bank> {
+        match retry_commitment {
+            CommitmentLevel::Finalized => bank_forks.root_bank(),
+            CommitmentLevel::Confirmed => block_commitment_cache
+                .and_then(|block_commitment_cache| {
+                    let slot = block_commitment_cache
+                        .read()
+                        .unwrap()
+                        .slot_with_commitment(retry_commitment);
+                    bank_forks.get(slot).cloned()
+                })
+                .unwrap_or_else(|| bank_forks.root_bank()),
+            _ => bank_forks.working_bank(),
+        }
+    }
+
     fn retry_thread<T: TpuInfo + std::marker::Send + 'static>(
         tpu_address: SocketAddr,
         receiver: Receiver<TransactionInfo>,
         bank_forks: Arc<RwLock<BankForks>>,
+        block_commitment_cache: Option<Arc<RwLock<BlockCommitmentCache>>>,
         mut leader_info: Option<T>,
+        connection_cache: Arc<ConnectionCache>,
         config: Config,
     ) -> JoinHandle<()> {
         let mut last_status_check = Instant::now();
         let mut last_leader_refresh = Instant::now();
         let mut transactions = HashMap::new();
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
 
         if let Some(leader_info) = leader_info.as_mut() {
             leader_info.refresh_recent_peers();
@@ -142,7 +219,7 @@ impl SendTransactionService {
                 match receiver.recv_timeout(Duration::from_millis(1000.min(config.retry_rate_ms))) {
                     Err(RecvTimeoutError::Disconnected) => break,
                     Err(RecvTimeoutError::Timeout) => {}
-                    Ok(transaction_info) => {
+                    Ok(mut transaction_info) => {
                         inc_new_counter_info!("send_transaction_service-recv-tx", 1);
                         let addresses = leader_info.as_ref().map(|leader_info| {
                             leader_info.get_leader_tpus(config.leader_forward_count)
@@ -158,11 +235,12 @@ impl SendTransactionService {
                             .unwrap_or_else(|| vec![&tpu_address]);
                         for address in addresses {
                             Self::send_transaction(
-                                &send_socket,
+                                &connection_cache,
                                 address,
                                 &transaction_info.wire_transaction,
                             );
                         }
+                        transaction_info.last_sent_time = Some(Instant::now());
                         if transactions.len() < MAX_TRANSACTION_QUEUE_SIZE {
                             inc_new_counter_info!("send_transaction_service-insert-tx", 1);
                             transactions.insert(transaction_info.signature, transaction_info);
@@ -181,15 +259,19 @@ impl SendTransactionService {
                         let (root_bank, working_bank) = {
                             let bank_forks = bank_forks.read().unwrap();
                             (
-                                bank_forks.root_bank().clone(),
-                                bank_forks.working_bank().clone(),
+                                bank_forks.root_bank(),
+                                Self::retry_status_bank(
+                                    &bank_forks,
+                                    block_commitment_cache.as_deref(),
+                                    config.retry_commitment,
+                                ),
                             )
                         };
 
                         let _result = Self::process_transactions(
                             &working_bank,
                             &root_bank,
-                            &send_socket,
+                            &connection_cache,
                             &tpu_address,
                             &mut transactions,
                             &leader_info,
@@ -211,7 +293,7 @@ impl SendTransactionService {
     fn process_transactions<T: TpuInfo>(
         working_bank: &Arc<Bank>,
         root_bank: &Arc<Bank>,
-        send_socket: &UdpSocket,
+        connection_cache: &ConnectionCache,
         tpu_address: &SocketAddr,
         transactions: &mut HashMap<Signature, TransactionInfo>,
         leader_info: &Option<T>,
@@ -263,11 +345,18 @@ impl SendTransactionService {
 
             match working_bank.get_signature_status_slot(signature) {
                 None => {
+                    if !transaction_info
+                        .is_due_for_retry(config.retry_rate_ms, config.retry_backoff)
+                    {
+                        result.backed_off += 1;
+                        return true;
+                    }
                     // Transaction is unknown to the working bank, it might have been
                     // dropped or landed in another fork.  Re-send it
                     info!("Retrying transaction: {}", signature);
                     result.retried += 1;
                     transaction_info.retries += 1;
+                    transaction_info.last_sent_time = Some(Instant::now());
                     inc_new_counter_info!("send_transaction_service-retry", 1);
                     let addresses = leader_info.as_ref().map(|leader_info| {
                         leader_info.get_leader_tpus(config.leader_forward_count)
@@ -283,7 +372,7 @@ impl SendTransactionService {
                         .unwrap_or_else(|| vec![tpu_address]);
                     for address in addresses {
                         Self::send_transaction(
-                            send_socket,
+                            connection_cache,
                             address,
                             &transaction_info.wire_transaction,
                         );
@@ -308,11 +397,11 @@ impl SendTransactionService {
     }
 
     fn send_transaction(
-        send_socket: &UdpSocket,
+        connection_cache: &ConnectionCache,
         tpu_address: &SocketAddr,
         wire_transaction: &[u8],
     ) {
-        if let Err(err) = send_socket.send_to(wire_transaction, tpu_address) {
+        if let Err(err) = connection_cache.send_wire_transaction(tpu_address, wire_transaction) {
             warn!("Failed to send transaction to {}: {:?}", tpu_address, err);
         }
     }
@@ -361,7 +450,7 @@ mod test {
         let (genesis_config, mint_keypair) = create_genesis_config(4);
         let bank = Bank::new_for_tests(&genesis_config);
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+        let connection_cache = ConnectionCache::default();
         let tpu_address = "127.0.0.1:0".parse().unwrap();
         let config = Config {
             leader_forward_count: 1,
@@ -408,7 +497,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -437,7 +526,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -466,7 +555,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -495,7 +584,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -525,7 +614,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -565,7 +654,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -583,7 +672,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -606,7 +695,7 @@ mod test {
         let (genesis_config, mint_keypair) = create_genesis_config(4);
         let bank = Bank::new_for_tests(&genesis_config);
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
-        let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+        let connection_cache = ConnectionCache::default();
         let tpu_address = "127.0.0.1:0".parse().unwrap();
         let config = Config {
             leader_forward_count: 1,
@@ -663,7 +752,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -691,7 +780,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -721,7 +810,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -749,7 +838,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -778,7 +867,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -807,7 +896,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -837,7 +926,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -862,7 +951,7 @@ mod test {
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
             &root_bank,
-            &send_socket,
+            &connection_cache,
             &tpu_address,
             &mut transactions,
             &None,
@@ -877,4 +966,126 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_retry_backoff() {
+        let mut transaction_info =
+            TransactionInfo::new(Signature::default(), vec![], 0, None, None);
+        let exponential = RetryBackoff::Exponential {
+            max_retry_interval_ms: 4_000,
+        };
+
+        // Never sent, always due
+        assert!(transaction_info.is_due_for_retry(1_000, RetryBackoff::Constant));
+        assert!(transaction_info.is_due_for_retry(1_000, exponential));
+
+        transaction_info.last_sent_time = Some(Instant::now());
+        assert!(transaction_info.is_due_for_retry(1_000, RetryBackoff::Constant));
+        assert!(!transaction_info.is_due_for_retry(1_000, exponential));
+        assert!(transaction_info.is_due_for_retry(0, exponential));
+
+        // The retry interval is capped by `max_retry_interval_ms`
+        transaction_info.retries = 100;
+        assert!(transaction_info.is_due_for_retry(
+            1_000,
+            RetryBackoff::Exponential {
+                max_retry_interval_ms: 0
+            }
+        ));
+    }
+
+    #[test]
+    fn test_process_transactions_with_backoff() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(4);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let connection_cache = ConnectionCache::default();
+        let tpu_address = "127.0.0.1:0".parse().unwrap();
+        let config = Config {
+            leader_forward_count: 1,
+            retry_backoff: RetryBackoff::Exponential {
+                max_retry_interval_ms: DEFAULT_MAX_RETRY_INTERVAL_MS,
+            },
+            ..Config::default()
+        };
+
+        let mut transactions = HashMap::new();
+        transactions.insert(
+            Signature::default(),
+            TransactionInfo::new(
+                Signature::default(),
+                vec![],
+                bank.block_height(),
+                None,
+                None,
+            ),
+        );
+
+        let result = SendTransactionService::process_transactions::<NullTpuInfo>(
+            &bank,
+            &bank,
+            &connection_cache,
+            &tpu_address,
+            &mut transactions,
+            &None,
+            &config,
+        );
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                retried: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+
+        // Just retried, so the transaction is held back until its retry interval elapses
+        let result = SendTransactionService::process_transactions::<NullTpuInfo>(
+            &bank,
+            &bank,
+            &connection_cache,
+            &tpu_address,
+            &mut transactions,
+            &None,
+            &config,
+        );
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(
+            result,
+            ProcessTransactionsResult {
+                backed_off: 1,
+                ..ProcessTransactionsResult::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_retry_status_bank() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(4);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let mut bank_forks = BankForks::new(bank);
+        let bank1 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 1);
+        bank_forks.insert(bank1);
+        let bank2 = Bank::new_from_parent(&bank_forks[1], &Pubkey::default(), 2);
+        bank_forks.insert(bank2);
+        let block_commitment_cache = RwLock::new(BlockCommitmentCache::default());
+        block_commitment_cache
+            .write()
+            .unwrap()
+            .set_highest_confirmed_slot(1);
+
+        let status_bank_slot = |block_commitment_cache, retry_commitment| {
+            SendTransactionService::retry_status_bank(
+                &bank_forks,
+                block_commitment_cache,
+                retry_commitment,
+            )
+            .slot()
+        };
+        assert_eq!(status_bank_slot(None, CommitmentLevel::Processed), 2);
+        assert_eq!(
+            status_bank_slot(Some(&block_commitment_cache), CommitmentLevel::Confirmed),
+            1
+        );
+        assert_eq!(status_bank_slot(None, CommitmentLevel::Confirmed), 0);
+        assert_eq!(status_bank_slot(None, CommitmentLevel::Finalized), 0);
+    }
 }
@@ -785,7 +785,9 @@ mod tests {
     use super::*;
     use crate::StoredConfirmedBlock;
     use prost::Message;
-    use solana_sdk::{hash::Hash, signature::Keypair, system_transaction};
+    use solana_sdk::{
+        hash::Hash, message::LoadedAddresses, signature::Keypair, system_transaction,
+    };
     use solana_storage_proto::convert::generated;
     use solana_transaction_status::{
         ConfirmedBlock, TransactionStatusMeta, TransactionWithStatusMeta,
@@ -798,7 +800,7 @@ mod tests {
         let recipient = solana_sdk::pubkey::new_rand();
         let transaction = system_transaction::transfer(&from, &recipient, 42, Hash::default());
         let with_meta = TransactionWithStatusMeta {
-            transaction,
+            transaction: transaction.into(),
             meta: Some(TransactionStatusMeta {
                 status: Ok(()),
                 fee: 1,
@@ -809,6 +811,9 @@ mod tests {
                 pre_token_balances: Some(vec![]),
                 post_token_balances: Some(vec![]),
                 rewards: Some(vec![]),
+                return_data: None,
+                loaded_addresses: LoadedAddresses::default(),
+                log_entries: None,
             }),
         };
         let block = ConfirmedBlock {
@@ -5,10 +5,11 @@ use {
     solana_sdk::{
         clock::{Slot, UnixTimestamp},
         deserialize_utils::default_on_eof,
+        message::LoadedAddresses,
         pubkey::Pubkey,
         signature::Signature,
         sysvar::is_sysvar_id,
-        transaction::{Transaction, TransactionError},
+        transaction::{TransactionError, VersionedTransaction},
     },
     solana_storage_proto::convert::{generated, tx_by_addr},
     solana_transaction_status::{
@@ -160,7 +161,7 @@ impl From<StoredConfirmedBlock> for ConfirmedBlock {
 
 #[derive(Serialize, Deserialize)]
 struct StoredConfirmedBlockTransaction {
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     meta: Option<StoredConfirmedBlockTransactionStatusMeta>,
 }
 
@@ -212,6 +213,9 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            log_entries: None,
         }
     }
 }
@@ -605,7 +609,7 @@ impl LedgerStorage {
             let signature = transaction.signatures[0];
             let memo = extract_and_fmt_memos(&transaction.message);
 
-            for address in &transaction.message.account_keys {
+            for address in transaction_with_meta.account_keys_iter() {
                 if !is_sysvar_id(address) {
                     by_addr
                         .entry(address)
@@ -692,7 +696,7 @@ impl LedgerStorage {
             let index = index as u32;
             let err = meta.as_ref().and_then(|meta| meta.status.clone().err());
 
-            for address in &transaction.message.account_keys {
+            for address in transaction_with_meta.account_keys_iter() {
                 if !is_sysvar_id(address) {
                     addresses.insert(address);
                 }
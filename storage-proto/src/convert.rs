@@ -5,15 +5,18 @@ use {
         hash::Hash,
         instruction::CompiledInstruction,
         instruction::InstructionError,
-        message::{Message, MessageHeader},
+        message::{
+            v0::{self, MessageAddressTableLookup},
+            LoadedAddresses, Message, MessageHeader, VersionedMessage,
+        },
         pubkey::Pubkey,
         signature::Signature,
-        transaction::Transaction,
-        transaction::TransactionError,
+        transaction::{TransactionError, VersionedTransaction},
     },
     solana_transaction_status::{
         ConfirmedBlock, InnerInstructions, Reward, RewardType, TransactionByAddrInfo,
-        TransactionStatusMeta, TransactionTokenBalance, TransactionWithStatusMeta,
+        TransactionLogEntry, TransactionReturnData, TransactionStatusMeta, TransactionTokenBalance,
+        TransactionWithStatusMeta,
     },
     std::{
         convert::{TryFrom, TryInto},
@@ -188,8 +191,8 @@ impl TryFrom<generated::ConfirmedTransaction> for TransactionWithStatusMeta {
     }
 }
 
-impl From<Transaction> for generated::Transaction {
-    fn from(value: Transaction) -> Self {
+impl From<VersionedTransaction> for generated::Transaction {
+    fn from(value: VersionedTransaction) -> Self {
         Self {
             signatures: value
                 .signatures
@@ -201,7 +204,7 @@ impl From<Transaction> for generated::Transaction {
     }
 }
 
-impl From<generated::Transaction> for Transaction {
+impl From<generated::Transaction> for VersionedTransaction {
     fn from(value: generated::Transaction) -> Self {
         Self {
             signatures: value
@@ -225,21 +228,90 @@ impl From<Message> for generated::Message {
                 .collect(),
             recent_blockhash: value.recent_blockhash.to_bytes().into(),
             instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            versioned: false,
+            address_table_lookups: vec![],
         }
     }
 }
 
-impl From<generated::Message> for Message {
+impl From<VersionedMessage> for generated::Message {
+    fn from(value: VersionedMessage) -> Self {
+        match value {
+            VersionedMessage::Legacy(message) => message.into(),
+            VersionedMessage::V0(message) => Self {
+                header: Some(message.header.into()),
+                account_keys: message
+                    .account_keys
+                    .into_iter()
+                    .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+                    .collect(),
+                recent_blockhash: message.recent_blockhash.to_bytes().into(),
+                instructions: message
+                    .instructions
+                    .into_iter()
+                    .map(|ix| ix.into())
+                    .collect(),
+                versioned: true,
+                address_table_lookups: message
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl From<generated::Message> for VersionedMessage {
     fn from(value: generated::Message) -> Self {
+        let header = value.header.expect("header is required").into();
+        let account_keys = value
+            .account_keys
+            .into_iter()
+            .map(|key| Pubkey::new(&key))
+            .collect();
+        let recent_blockhash = Hash::new(&value.recent_blockhash);
+        let instructions = value.instructions.into_iter().map(|ix| ix.into()).collect();
+
+        if !value.versioned {
+            Self::Legacy(Message {
+                header,
+                account_keys,
+                recent_blockhash,
+                instructions,
+            })
+        } else {
+            Self::V0(v0::Message {
+                header,
+                account_keys,
+                recent_blockhash,
+                instructions,
+                address_table_lookups: value
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            })
+        }
+    }
+}
+
+impl From<MessageAddressTableLookup> for generated::MessageAddressTableLookup {
+    fn from(lookup: MessageAddressTableLookup) -> Self {
         Self {
-            header: value.header.expect("header is required").into(),
-            account_keys: value
-                .account_keys
-                .into_iter()
-                .map(|key| Pubkey::new(&key))
-                .collect(),
-            recent_blockhash: Hash::new(&value.recent_blockhash),
-            instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            account_key: <Pubkey as AsRef<[u8]>>::as_ref(&lookup.account_key).into(),
+            writable_indexes: lookup.writable_indexes,
+            readonly_indexes: lookup.readonly_indexes,
+        }
+    }
+}
+
+impl From<generated::MessageAddressTableLookup> for MessageAddressTableLookup {
+    fn from(value: generated::MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: Pubkey::new(&value.account_key),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
         }
     }
 }
@@ -276,6 +348,9 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            log_entries,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -288,7 +363,14 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .into_iter()
             .map(|ii| ii.into())
             .collect();
-        let log_messages = log_messages.unwrap_or_default();
+        // Messages with log entries are only stored in the entries
+        let (log_messages, log_entries) = match log_entries {
+            Some(log_entries) => (
+                vec![],
+                log_entries.into_iter().map(|entry| entry.into()).collect(),
+            ),
+            None => (log_messages.unwrap_or_default(), vec![]),
+        };
         let pre_token_balances = pre_token_balances
             .unwrap_or_default()
             .into_iter()
@@ -304,6 +386,17 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .into_iter()
             .map(|reward| reward.into())
             .collect();
+        let return_data = return_data.map(|return_data| return_data.into());
+        let loaded_writable_addresses = loaded_addresses
+            .writable
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let loaded_readonly_addresses = loaded_addresses
+            .readonly
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
 
         Self {
             err,
@@ -315,6 +408,10 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
+            log_entries,
         }
     }
 }
@@ -340,6 +437,10 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
+            log_entries,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -351,7 +452,21 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .map(|inner| inner.into())
                 .collect(),
         );
-        let log_messages = Some(log_messages);
+        let (log_messages, log_entries) = if log_entries.is_empty() {
+            (Some(log_messages), None)
+        } else {
+            let log_entries: Vec<TransactionLogEntry> =
+                log_entries.into_iter().map(|entry| entry.into()).collect();
+            (
+                Some(
+                    log_entries
+                        .iter()
+                        .map(|entry| entry.message.clone())
+                        .collect(),
+                ),
+                Some(log_entries),
+            )
+        };
         let pre_token_balances = Some(
             pre_token_balances
                 .into_iter()
@@ -365,6 +480,17 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .collect(),
         );
         let rewards = Some(rewards.into_iter().map(|reward| reward.into()).collect());
+        let return_data = return_data.map(|return_data| return_data.into());
+        let loaded_addresses = LoadedAddresses {
+            writable: loaded_writable_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+            readonly: loaded_readonly_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+        };
         Ok(Self {
             status,
             fee,
@@ -375,6 +501,9 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            log_entries,
         })
     }
 }
@@ -409,6 +538,7 @@ impl From<TransactionTokenBalance> for generated::TokenBalance {
                 ui_amount_string: value.ui_token_amount.ui_amount_string,
             }),
             owner: value.owner,
+            program_id: value.program_id,
         }
     }
 }
@@ -437,6 +567,7 @@ impl From<generated::TokenBalance> for TransactionTokenBalance {
                 },
             },
             owner: value.owner,
+            program_id: value.program_id,
         }
     }
 }
@@ -461,6 +592,51 @@ impl From<generated::CompiledInstruction> for CompiledInstruction {
     }
 }
 
+impl From<TransactionReturnData> for generated::ReturnData {
+    fn from(value: TransactionReturnData) -> Self {
+        Self {
+            program_id: value.program_id.to_bytes().into(),
+            data: value.data,
+        }
+    }
+}
+
+impl From<generated::ReturnData> for TransactionReturnData {
+    fn from(value: generated::ReturnData) -> Self {
+        Self {
+            program_id: Pubkey::new(&value.program_id),
+            data: value.data,
+        }
+    }
+}
+
+impl From<TransactionLogEntry> for generated::LogEntry {
+    fn from(value: TransactionLogEntry) -> Self {
+        Self {
+            program_id: value
+                .program_id
+                .map(|program_id| program_id.to_bytes().into())
+                .unwrap_or_default(),
+            invoke_depth: value.invoke_depth as u64,
+            message: value.message,
+        }
+    }
+}
+
+impl From<generated::LogEntry> for TransactionLogEntry {
+    fn from(value: generated::LogEntry) -> Self {
+        Self {
+            program_id: if value.program_id.is_empty() {
+                None
+            } else {
+                Some(Pubkey::new(&value.program_id))
+            },
+            invoke_depth: value.invoke_depth as usize,
+            message: value.message,
+        }
+    }
+}
+
 impl TryFrom<tx_by_addr::TransactionError> for TransactionError {
     type Error = &'static str;
 
@@ -553,6 +729,11 @@ impl TryFrom<tx_by_addr::TransactionError> for TransactionError {
             17 => TransactionError::WouldExceedMaxBlockCostLimit,
             18 => TransactionError::UnsupportedVersion,
             19 => TransactionError::InvalidWritableAccount,
+            20 => TransactionError::AddressLookupTableNotFound,
+            21 => TransactionError::InvalidAddressLookupTableOwner,
+            22 => TransactionError::InvalidAddressLookupTableData,
+            23 => TransactionError::InvalidAddressLookupTableIndex,
+            24 => TransactionError::InvalidRentPayingAccount,
             _ => return Err("Invalid TransactionError"),
         })
     }
@@ -620,6 +801,21 @@ impl From<TransactionError> for tx_by_addr::TransactionError {
                 TransactionError::InvalidWritableAccount => {
                     tx_by_addr::TransactionErrorType::InvalidWritableAccount
                 }
+                TransactionError::AddressLookupTableNotFound => {
+                    tx_by_addr::TransactionErrorType::AddressLookupTableNotFound
+                }
+                TransactionError::InvalidAddressLookupTableOwner => {
+                    tx_by_addr::TransactionErrorType::InvalidAddressLookupTableOwner
+                }
+                TransactionError::InvalidAddressLookupTableData => {
+                    tx_by_addr::TransactionErrorType::InvalidAddressLookupTableData
+                }
+                TransactionError::InvalidAddressLookupTableIndex => {
+                    tx_by_addr::TransactionErrorType::InvalidAddressLookupTableIndex
+                }
+                TransactionError::InvalidRentPayingAccount => {
+                    tx_by_addr::TransactionErrorType::InvalidRentPayingAccount
+                }
             } as i32,
             instruction_error: match transaction_error {
                 TransactionError::InstructionError(index, ref instruction_error) => {
@@ -879,6 +1075,117 @@ mod test {
         assert_eq!(reward, gen_reward.into());
     }
 
+    #[test]
+    fn test_versioned_transaction_with_status_meta_encode() {
+        let legacy_transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                recent_blockhash: Hash::new_unique(),
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0])],
+            }),
+        };
+        let v0_transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                recent_blockhash: Hash::new_unique(),
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2, 3])],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![1],
+                    readonly_indexes: vec![0],
+                }],
+            }),
+        };
+        let v0_meta = TransactionStatusMeta {
+            loaded_addresses: LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            },
+            ..TransactionStatusMeta::default()
+        };
+
+        for (transaction, meta) in vec![
+            (legacy_transaction, TransactionStatusMeta::default()),
+            (v0_transaction, v0_meta),
+        ] {
+            let with_meta = TransactionWithStatusMeta {
+                transaction,
+                meta: Some(meta),
+            };
+            let gen_with_meta: generated::ConfirmedTransaction = with_meta.clone().into();
+            let decoded: TransactionWithStatusMeta = gen_with_meta.try_into().unwrap();
+            assert_eq!(decoded.transaction, with_meta.transaction);
+            assert_eq!(
+                decoded.meta.unwrap().loaded_addresses,
+                with_meta.meta.unwrap().loaded_addresses
+            );
+        }
+    }
+
+    #[test]
+    fn test_transaction_status_meta_log_entries() {
+        let log_entries = vec![
+            TransactionLogEntry {
+                program_id: Some(Pubkey::new_unique()),
+                invoke_depth: 1,
+                message: "Program log: hello".to_string(),
+            },
+            TransactionLogEntry {
+                program_id: None,
+                invoke_depth: 0,
+                message: "Log truncated".to_string(),
+            },
+        ];
+        let meta = TransactionStatusMeta {
+            log_messages: Some(vec![
+                "Program log: hello".to_string(),
+                "Log truncated".to_string(),
+            ]),
+            log_entries: Some(log_entries),
+            ..TransactionStatusMeta::default()
+        };
+
+        // The messages are only stored in the log entries
+        let generated_meta: generated::TransactionStatusMeta = meta.clone().into();
+        assert!(generated_meta.log_messages.is_empty());
+        assert_eq!(generated_meta.log_entries.len(), 2);
+        let decoded: TransactionStatusMeta = generated_meta.try_into().unwrap();
+        assert_eq!(decoded.log_messages, meta.log_messages);
+        assert_eq!(decoded.log_entries, meta.log_entries);
+
+        let stored_meta: StoredTransactionStatusMeta = meta.clone().into();
+        assert!(stored_meta.log_messages.is_none());
+        let decoded: TransactionStatusMeta = bincode::deserialize::<StoredTransactionStatusMeta>(
+            &bincode::serialize(&stored_meta).unwrap(),
+        )
+        .unwrap()
+        .into();
+        assert_eq!(decoded.log_messages, meta.log_messages);
+        assert_eq!(decoded.log_entries, meta.log_entries);
+
+        // Metas without log entries keep storing their messages
+        let meta = TransactionStatusMeta {
+            log_messages: Some(vec!["Program log: hello".to_string()]),
+            ..TransactionStatusMeta::default()
+        };
+        let generated_meta: generated::TransactionStatusMeta = meta.clone().into();
+        let decoded: TransactionStatusMeta = generated_meta.try_into().unwrap();
+        assert_eq!(decoded.log_messages, meta.log_messages);
+        assert_eq!(decoded.log_entries, None);
+    }
+
     #[test]
     fn test_transaction_by_addr_encode() {
         let info = TransactionByAddrInfo {
@@ -1039,6 +1346,46 @@ mod test {
             tx_by_addr_transaction_error.try_into().unwrap()
         );
 
+        let transaction_error = TransactionError::AddressLookupTableNotFound;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::InvalidAddressLookupTableOwner;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::InvalidAddressLookupTableData;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::InvalidAddressLookupTableIndex;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
+        let transaction_error = TransactionError::InvalidRentPayingAccount;
+        let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
+            transaction_error.clone().into();
+        assert_eq!(
+            transaction_error,
+            tx_by_addr_transaction_error.try_into().unwrap()
+        );
+
         let transaction_error =
             TransactionError::InstructionError(10, InstructionError::AccountAlreadyInitialized);
         let tx_by_addr_transaction_error: tx_by_addr::TransactionError =
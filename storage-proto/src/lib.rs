@@ -4,9 +4,12 @@ use {
         parse_token::{real_number_string_trimmed, UiTokenAmount},
         StringAmount,
     },
-    solana_sdk::{deserialize_utils::default_on_eof, transaction::Result},
+    solana_sdk::{
+        deserialize_utils::default_on_eof, message::LoadedAddresses, transaction::Result,
+    },
     solana_transaction_status::{
-        InnerInstructions, Reward, RewardType, TransactionStatusMeta, TransactionTokenBalance,
+        InnerInstructions, Reward, RewardType, TransactionLogEntry, TransactionReturnData,
+        TransactionStatusMeta, TransactionTokenBalance,
     },
     std::str::FromStr,
 };
@@ -113,6 +116,8 @@ pub struct StoredTransactionTokenBalance {
     pub ui_token_amount: StoredTokenAmount,
     #[serde(deserialize_with = "default_on_eof")]
     pub owner: String,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub program_id: String,
 }
 
 impl From<StoredTransactionTokenBalance> for TransactionTokenBalance {
@@ -122,12 +127,14 @@ impl From<StoredTransactionTokenBalance> for TransactionTokenBalance {
             mint,
             ui_token_amount,
             owner,
+            program_id,
         } = value;
         Self {
             account_index,
             mint,
             ui_token_amount: ui_token_amount.into(),
             owner,
+            program_id,
         }
     }
 }
@@ -139,12 +146,14 @@ impl From<TransactionTokenBalance> for StoredTransactionTokenBalance {
             mint,
             ui_token_amount,
             owner,
+            program_id,
         } = value;
         Self {
             account_index,
             mint,
             ui_token_amount: ui_token_amount.into(),
             owner,
+            program_id,
         }
     }
 }
@@ -165,6 +174,13 @@ pub struct StoredTransactionStatusMeta {
     pub post_token_balances: Option<Vec<StoredTransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Vec<StoredExtendedReward>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: LoadedAddresses,
+    /// When set, `log_messages` is left out so that each message is only stored once
+    #[serde(deserialize_with = "default_on_eof")]
+    pub log_entries: Option<Vec<TransactionLogEntry>>,
 }
 
 impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
@@ -179,7 +195,15 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            log_entries,
         } = value;
+        let log_messages = log_messages.or_else(|| {
+            log_entries
+                .as_ref()
+                .map(|entries| entries.iter().map(|entry| entry.message.clone()).collect())
+        });
         Self {
             status,
             fee,
@@ -193,6 +217,9 @@ impl From<StoredTransactionStatusMeta> for TransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            loaded_addresses,
+            log_entries,
         }
     }
 }
@@ -209,7 +236,15 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
             pre_token_balances,
             post_token_balances,
             rewards,
+            return_data,
+            loaded_addresses,
+            log_entries,
         } = value;
+        let log_messages = if log_entries.is_some() {
+            None
+        } else {
+            log_messages
+        };
         Self {
             status,
             fee,
@@ -223,6 +258,9 @@ impl From<TransactionStatusMeta> for StoredTransactionStatusMeta {
                 .map(|balances| balances.into_iter().map(|balance| balance.into()).collect()),
             rewards: rewards
                 .map(|rewards| rewards.into_iter().map(|reward| reward.into()).collect()),
+            return_data,
+            loaded_addresses,
+            log_entries,
         }
     }
 }
@@ -113,6 +113,34 @@ pub fn receiver(
         .unwrap()
 }
 
+/// Start a receiver for each of `sockets`, usually bound to the same port with SO_REUSEPORT so
+/// that the kernel spreads the packets over their receive queues.  The receivers share
+/// `recycler`, so that the packet batches it retains don't grow with the number of sockets.
+pub fn receivers(
+    sockets: Vec<Arc<UdpSocket>>,
+    exit: &Arc<AtomicBool>,
+    packet_sender: PacketSender,
+    recycler: PacketsRecycler,
+    name: &'static str,
+    coalesce_ms: u64,
+    use_pinned_memory: bool,
+) -> Vec<JoinHandle<()>> {
+    sockets
+        .into_iter()
+        .map(|socket| {
+            receiver(
+                socket,
+                exit,
+                packet_sender.clone(),
+                recycler.clone(),
+                name,
+                coalesce_ms,
+                use_pinned_memory,
+            )
+        })
+        .collect()
+}
+
 fn recv_send(
     sock: &UdpSocket,
     r: &PacketReceiver,
@@ -180,7 +208,7 @@ pub fn responder(
 mod test {
     use super::*;
     use crate::packet::{Packet, Packets, PACKET_DATA_SIZE};
-    use crate::streamer::{receiver, responder};
+    use crate::streamer::{receiver, receivers, responder};
     use solana_perf::recycler::Recycler;
     use std::io;
     use std::io::Write;
@@ -257,4 +285,36 @@ mod test {
         t_receiver.join().expect("join");
         t_responder.join().expect("join");
     }
+
+    #[test]
+    fn streamer_receivers_test() {
+        let reads: Vec<_> = (0..2)
+            .map(|_| UdpSocket::bind("127.0.0.1:0").expect("bind"))
+            .collect();
+        let addrs: Vec<_> = reads.iter().map(|r| r.local_addr().unwrap()).collect();
+        let exit = Arc::new(AtomicBool::new(false));
+        let (s_reader, r_reader) = channel();
+        let t_receivers = receivers(
+            reads.into_iter().map(Arc::new).collect(),
+            &exit,
+            s_reader,
+            Recycler::default(),
+            "test",
+            1,
+            true,
+        );
+        assert_eq!(t_receivers.len(), 2);
+
+        let send = UdpSocket::bind("127.0.0.1:0").expect("bind");
+        for addr in &addrs {
+            send.send_to(&[1; 10], addr).unwrap();
+        }
+        let mut num = 2;
+        get_msgs(r_reader, &mut num);
+        assert_eq!(num, 0);
+        exit.store(true, Ordering::Relaxed);
+        for t_receiver in t_receivers {
+            t_receiver.join().expect("join");
+        }
+    }
 }
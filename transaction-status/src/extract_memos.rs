@@ -1,7 +1,7 @@
 use {
     crate::parse_instruction::parse_memo_data,
     solana_sdk::{
-        message::{Message, SanitizedMessage},
+        message::{Message, SanitizedMessage, VersionedMessage},
         pubkey::Pubkey,
     },
 };
@@ -69,6 +69,32 @@ impl ExtractMemos for SanitizedMessage {
     }
 }
 
+impl ExtractMemos for VersionedMessage {
+    fn extract_memos(&self) -> Vec<String> {
+        match self {
+            VersionedMessage::Legacy(message) => message.extract_memos(),
+            // Program ids can't be loaded from lookup tables, so the static account keys are
+            // enough to find memo instructions
+            VersionedMessage::V0(message) => {
+                let mut memos = vec![];
+                if message.account_keys.contains(&spl_memo_id_v1())
+                    || message.account_keys.contains(&spl_memo_id_v3())
+                {
+                    for instruction in &message.instructions {
+                        if let Some(program_id) = message
+                            .account_keys
+                            .get(instruction.program_id_index as usize)
+                        {
+                            maybe_push_parsed_memo(&mut memos, *program_id, &instruction.data);
+                        }
+                    }
+                }
+                memos
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use {
@@ -76,7 +102,7 @@ mod test {
         solana_sdk::{
             hash::Hash,
             instruction::CompiledInstruction,
-            message::{v0, MappedAddresses, MappedMessage, MessageHeader},
+            message::{v0, LoadedAddresses, LoadedMessage, MessageHeader},
         },
     };
 
@@ -122,10 +148,13 @@ mod test {
         );
         assert_eq!(message.extract_memos(), expected_memos);
 
+        let versioned_message = VersionedMessage::Legacy(message.clone());
+        assert_eq!(versioned_message.extract_memos(), expected_memos);
+
         let sanitized_message = SanitizedMessage::Legacy(message);
         assert_eq!(sanitized_message.extract_memos(), expected_memos);
 
-        let mapped_message = MappedMessage {
+        let loaded_message = LoadedMessage {
             message: v0::Message {
                 header: MessageHeader {
                     num_required_signatures: 1,
@@ -133,15 +162,32 @@ mod test {
                     num_readonly_unsigned_accounts: 3,
                 },
                 account_keys: vec![fee_payer],
-                instructions: memo_instructions,
+                instructions: memo_instructions.clone(),
                 ..v0::Message::default()
             },
-            mapped_addresses: MappedAddresses {
+            loaded_addresses: LoadedAddresses {
                 writable: vec![],
                 readonly: vec![spl_memo_id_v1(), another_program_id, spl_memo_id_v3()],
             },
         };
-        let sanitized_mapped_message = SanitizedMessage::V0(mapped_message);
-        assert_eq!(sanitized_mapped_message.extract_memos(), expected_memos);
+        let sanitized_loaded_message = SanitizedMessage::V0(loaded_message);
+        assert_eq!(sanitized_loaded_message.extract_memos(), expected_memos);
+
+        let versioned_v0_message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 3,
+            },
+            account_keys: vec![
+                fee_payer,
+                spl_memo_id_v1(),
+                another_program_id,
+                spl_memo_id_v3(),
+            ],
+            instructions: memo_instructions,
+            ..v0::Message::default()
+        });
+        assert_eq!(versioned_v0_message.extract_memos(), expected_memos);
     }
 }
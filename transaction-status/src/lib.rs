@@ -15,7 +15,13 @@ pub mod parse_token;
 pub mod parse_vote;
 pub mod token_balances;
 
-pub use {crate::extract_memos::extract_and_fmt_memos, solana_runtime::bank::RewardType};
+pub use {
+    crate::extract_memos::extract_and_fmt_memos,
+    solana_runtime::{
+        bank::{RewardType, TransactionReturnData},
+        log_collector::TransactionLogEntry,
+    },
+};
 use {
     crate::{
         parse_accounts::{parse_accounts, ParsedAccount},
@@ -27,14 +33,25 @@ use {
         commitment_config::CommitmentConfig,
         deserialize_utils::default_on_eof,
         instruction::CompiledInstruction,
-        message::{Message, MessageHeader},
+        message::{
+            v0::MessageAddressTableLookup, LoadedAddresses, Message, MessageHeader,
+            VersionedMessage,
+        },
         pubkey::Pubkey,
         sanitize::Sanitize,
         signature::Signature,
-        transaction::{Result, Transaction, TransactionError},
+        transaction::{Result, TransactionError, VersionedTransaction},
     },
     std::fmt,
+    thiserror::Error,
 };
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum EncodeError {
+    #[error("Encoding does not support transaction version {0}")]
+    UnsupportedTransactionVersion(u8),
+}
+
 /// A duplicate representation of an Instruction for pretty JSON serialization
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", untagged)]
@@ -128,6 +145,7 @@ pub struct TransactionTokenBalance {
     pub mint: String,
     pub ui_token_amount: UiTokenAmount,
     pub owner: String,
+    pub program_id: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -138,6 +156,8 @@ pub struct UiTransactionTokenBalance {
     pub ui_token_amount: UiTokenAmount,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
 }
 
 impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
@@ -151,6 +171,11 @@ impl From<TransactionTokenBalance> for UiTransactionTokenBalance {
             } else {
                 None
             },
+            program_id: if !token_balance.program_id.is_empty() {
+                Some(token_balance.program_id)
+            } else {
+                None
+            },
         }
     }
 }
@@ -198,6 +223,14 @@ pub struct TransactionStatusMeta {
     pub post_token_balances: Option<Vec<TransactionTokenBalance>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub rewards: Option<Rewards>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: LoadedAddresses,
+    /// The log messages along with the invocation that emitted each; `log_messages` holds the
+    /// same messages, stored metas only keep them once
+    #[serde(deserialize_with = "default_on_eof")]
+    pub log_entries: Option<Vec<TransactionLogEntry>>,
 }
 
 impl Default for TransactionStatusMeta {
@@ -212,6 +245,9 @@ impl Default for TransactionStatusMeta {
             pre_token_balances: None,
             post_token_balances: None,
             rewards: None,
+            return_data: None,
+            loaded_addresses: LoadedAddresses::default(),
+            log_entries: None,
         }
     }
 }
@@ -230,6 +266,93 @@ pub struct UiTransactionStatusMeta {
     pub pre_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub post_token_balances: Option<Vec<UiTransactionTokenBalance>>,
     pub rewards: Option<Rewards>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_addresses: Option<UiLoadedAddresses>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_entries: Option<Vec<UiTransactionLogEntry>>,
+}
+
+/// A log message with the program invocation that emitted it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionLogEntry {
+    pub program_id: Option<String>,
+    pub invoke_depth: usize,
+    pub message: String,
+}
+
+impl From<TransactionLogEntry> for UiTransactionLogEntry {
+    fn from(entry: TransactionLogEntry) -> Self {
+        Self {
+            program_id: entry.program_id.map(|program_id| program_id.to_string()),
+            invoke_depth: entry.invoke_depth,
+            message: entry.message,
+        }
+    }
+}
+
+/// A duplicate representation of LoadedAddresses
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl UiLoadedAddresses {
+    // Legacy transactions don't load any addresses, leave the field out of their metas
+    fn from_non_empty(loaded_addresses: &LoadedAddresses) -> Option<Self> {
+        if loaded_addresses.writable.is_empty() && loaded_addresses.readonly.is_empty() {
+            None
+        } else {
+            Some(Self::from(loaded_addresses))
+        }
+    }
+}
+
+impl From<&LoadedAddresses> for UiLoadedAddresses {
+    fn from(loaded_addresses: &LoadedAddresses) -> Self {
+        Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Program return data, with the data base64-encoded
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionReturnData {
+    pub program_id: String,
+    pub data: (String, UiReturnDataEncoding),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum UiReturnDataEncoding {
+    Base64,
+}
+
+impl From<TransactionReturnData> for UiTransactionReturnData {
+    fn from(return_data: TransactionReturnData) -> Self {
+        Self {
+            program_id: return_data.program_id.to_string(),
+            data: (
+                base64::encode(return_data.data),
+                UiReturnDataEncoding::Base64,
+            ),
+        }
+    }
 }
 
 impl UiTransactionStatusMeta {
@@ -253,6 +376,11 @@ impl UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            loaded_addresses: UiLoadedAddresses::from_non_empty(&meta.loaded_addresses),
+            log_entries: meta
+                .log_entries
+                .map(|entries| entries.into_iter().map(|entry| entry.into()).collect()),
         }
     }
 }
@@ -276,6 +404,11 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .post_token_balances
                 .map(|balance| balance.into_iter().map(|balance| balance.into()).collect()),
             rewards: meta.rewards,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            loaded_addresses: UiLoadedAddresses::from_non_empty(&meta.loaded_addresses),
+            log_entries: meta
+                .log_entries
+                .map(|entries| entries.into_iter().map(|entry| entry.into()).collect()),
         }
     }
 }
@@ -368,20 +501,24 @@ pub struct ConfirmedBlock {
 }
 
 impl ConfirmedBlock {
-    pub fn encode(self, encoding: UiTransactionEncoding) -> EncodedConfirmedBlock {
-        EncodedConfirmedBlock {
+    pub fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<EncodedConfirmedBlock, EncodeError> {
+        Ok(EncodedConfirmedBlock {
             previous_blockhash: self.previous_blockhash,
             blockhash: self.blockhash,
             parent_slot: self.parent_slot,
             transactions: self
                 .transactions
                 .into_iter()
-                .map(|tx| tx.encode(encoding))
-                .collect(),
+                .map(|tx| tx.encode(encoding, max_supported_transaction_version))
+                .collect::<std::result::Result<_, _>>()?,
             rewards: self.rewards,
             block_time: self.block_time,
             block_height: self.block_height,
-        }
+        })
     }
 
     pub fn configure(
@@ -389,14 +526,15 @@ impl ConfirmedBlock {
         encoding: UiTransactionEncoding,
         transaction_details: TransactionDetails,
         show_rewards: bool,
-    ) -> UiConfirmedBlock {
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<UiConfirmedBlock, EncodeError> {
         let (transactions, signatures) = match transaction_details {
             TransactionDetails::Full => (
                 Some(
                     self.transactions
                         .into_iter()
-                        .map(|tx| tx.encode(encoding))
-                        .collect(),
+                        .map(|tx| tx.encode(encoding, max_supported_transaction_version))
+                        .collect::<std::result::Result<_, _>>()?,
                 ),
                 None,
             ),
@@ -411,7 +549,7 @@ impl ConfirmedBlock {
             ),
             TransactionDetails::None => (None, None),
         };
-        UiConfirmedBlock {
+        Ok(UiConfirmedBlock {
             previous_blockhash: self.previous_blockhash,
             blockhash: self.blockhash,
             parent_slot: self.parent_slot,
@@ -424,7 +562,7 @@ impl ConfirmedBlock {
             },
             block_time: self.block_time,
             block_height: self.block_height,
-        }
+        })
     }
 }
 
@@ -509,12 +647,18 @@ pub struct ConfirmedTransaction {
 }
 
 impl ConfirmedTransaction {
-    pub fn encode(self, encoding: UiTransactionEncoding) -> EncodedConfirmedTransaction {
-        EncodedConfirmedTransaction {
+    pub fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<EncodedConfirmedTransaction, EncodeError> {
+        Ok(EncodedConfirmedTransaction {
             slot: self.slot,
-            transaction: self.transaction.encode(encoding),
+            transaction: self
+                .transaction
+                .encode(encoding, max_supported_transaction_version)?,
             block_time: self.block_time,
-        }
+        })
     }
 }
 
@@ -550,6 +694,58 @@ pub struct UiRawMessage {
     pub account_keys: Vec<String>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiCompiledInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
+}
+
+impl From<&VersionedMessage> for UiRawMessage {
+    fn from(message: &VersionedMessage) -> Self {
+        let (instructions, address_table_lookups) = match message {
+            VersionedMessage::Legacy(message) => (&message.instructions, None),
+            VersionedMessage::V0(message) => (
+                &message.instructions,
+                Some(
+                    message
+                        .address_table_lookups
+                        .iter()
+                        .map(UiAddressTableLookup::from)
+                        .collect(),
+                ),
+            ),
+        };
+        Self {
+            header: message.header().clone(),
+            account_keys: message
+                .static_account_keys_iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            recent_blockhash: message.recent_blockhash().to_string(),
+            instructions: instructions
+                .iter()
+                .map(|instruction| instruction.into())
+                .collect(),
+            address_table_lookups,
+        }
+    }
+}
+
+/// A duplicate representation of a MessageAddressTableLookup, in raw format, for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAddressTableLookup {
+    pub account_key: String,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+impl From<&MessageAddressTableLookup> for UiAddressTableLookup {
+    fn from(lookup: &MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: lookup.account_key.to_string(),
+            writable_indexes: lookup.writable_indexes.clone(),
+            readonly_indexes: lookup.readonly_indexes.clone(),
+        }
+    }
 }
 
 /// A duplicate representation of a Message, in parsed format, for pretty JSON serialization
@@ -564,18 +760,52 @@ pub struct UiParsedMessage {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionWithStatusMeta {
-    pub transaction: Transaction,
+    pub transaction: VersionedTransaction,
     pub meta: Option<TransactionStatusMeta>,
 }
 
 impl TransactionWithStatusMeta {
-    fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
-        let message = self.transaction.message();
+    /// Returns the static account keys of the transaction followed by the addresses it loaded
+    /// from address lookup tables
+    pub fn account_keys_iter(&self) -> impl Iterator<Item = &Pubkey> {
+        let loaded_addresses = self.meta.iter().flat_map(|meta| {
+            meta.loaded_addresses
+                .writable
+                .iter()
+                .chain(meta.loaded_addresses.readonly.iter())
+        });
+        self.transaction
+            .message
+            .static_account_keys_iter()
+            .chain(loaded_addresses)
+    }
+
+    /// Checks the transaction version against the highest version the requester supports,
+    /// requesters that don't opt in only support legacy transactions
+    fn validate_version(
+        &self,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<(), EncodeError> {
+        match &self.transaction.message {
+            VersionedMessage::Legacy(_) => Ok(()),
+            // Version 0 is the first versioned format, any opt-in supports it
+            VersionedMessage::V0(_) if max_supported_transaction_version.is_some() => Ok(()),
+            VersionedMessage::V0(_) => Err(EncodeError::UnsupportedTransactionVersion(0)),
+        }
+    }
+
+    fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> std::result::Result<EncodedTransactionWithStatusMeta, EncodeError> {
+        self.validate_version(max_supported_transaction_version)?;
+        let message = &self.transaction.message;
         let meta = self.meta.map(|meta| meta.encode(encoding, message));
-        EncodedTransactionWithStatusMeta {
+        Ok(EncodedTransactionWithStatusMeta {
             transaction: EncodedTransaction::encode(self.transaction, encoding),
             meta,
-        }
+        })
     }
 }
 
@@ -587,9 +817,17 @@ pub struct EncodedTransactionWithStatusMeta {
 }
 
 impl TransactionStatusMeta {
-    fn encode(self, encoding: UiTransactionEncoding, message: &Message) -> UiTransactionStatusMeta {
-        match encoding {
-            UiTransactionEncoding::JsonParsed => UiTransactionStatusMeta::parse(self, message),
+    fn encode(
+        self,
+        encoding: UiTransactionEncoding,
+        message: &VersionedMessage,
+    ) -> UiTransactionStatusMeta {
+        match (encoding, message) {
+            // Instructions of v0 messages may reference loaded addresses, which the parsers don't
+            // resolve yet
+            (UiTransactionEncoding::JsonParsed, VersionedMessage::Legacy(message)) => {
+                UiTransactionStatusMeta::parse(self, message)
+            }
             _ => self.into(),
         }
     }
@@ -622,7 +860,7 @@ pub enum EncodedTransaction {
 }
 
 impl EncodedTransaction {
-    pub fn encode(transaction: Transaction, encoding: UiTransactionEncoding) -> Self {
+    pub fn encode(transaction: VersionedTransaction, encoding: UiTransactionEncoding) -> Self {
         match encoding {
             UiTransactionEncoding::Binary => EncodedTransaction::LegacyBinary(
                 bs58::encode(bincode::serialize(&transaction).unwrap()).into_string(),
@@ -636,36 +874,21 @@ impl EncodedTransaction {
                 encoding,
             ),
             UiTransactionEncoding::Json | UiTransactionEncoding::JsonParsed => {
-                let message = if encoding == UiTransactionEncoding::Json {
-                    UiMessage::Raw(UiRawMessage {
-                        header: transaction.message.header,
-                        account_keys: transaction
-                            .message
-                            .account_keys
-                            .iter()
-                            .map(|pubkey| pubkey.to_string())
-                            .collect(),
-                        recent_blockhash: transaction.message.recent_blockhash.to_string(),
-                        instructions: transaction
-                            .message
-                            .instructions
-                            .iter()
-                            .map(|instruction| instruction.into())
-                            .collect(),
-                    })
-                } else {
-                    UiMessage::Parsed(UiParsedMessage {
-                        account_keys: parse_accounts(&transaction.message),
-                        recent_blockhash: transaction.message.recent_blockhash.to_string(),
-                        instructions: transaction
-                            .message
-                            .instructions
-                            .iter()
-                            .map(|instruction| {
-                                UiInstruction::parse(instruction, &transaction.message)
-                            })
-                            .collect(),
-                    })
+                let message = match (encoding, &transaction.message) {
+                    (UiTransactionEncoding::JsonParsed, VersionedMessage::Legacy(message)) => {
+                        UiMessage::Parsed(UiParsedMessage {
+                            account_keys: parse_accounts(message),
+                            recent_blockhash: message.recent_blockhash.to_string(),
+                            instructions: message
+                                .instructions
+                                .iter()
+                                .map(|instruction| UiInstruction::parse(instruction, message))
+                                .collect(),
+                        })
+                    }
+                    // v0 messages are always returned raw, their account keys aren't complete
+                    // without the loaded addresses
+                    (_, message) => UiMessage::Raw(UiRawMessage::from(message)),
                 };
                 EncodedTransaction::Json(UiTransaction {
                     signatures: transaction
@@ -678,8 +901,8 @@ impl EncodedTransaction {
             }
         }
     }
-    pub fn decode(&self) -> Option<Transaction> {
-        let transaction: Option<Transaction> = match self {
+    pub fn decode(&self) -> Option<VersionedTransaction> {
+        let transaction: Option<VersionedTransaction> = match self {
             EncodedTransaction::Json(_) => None,
             EncodedTransaction::LegacyBinary(blob) => bs58::decode(blob)
                 .into_vec()
@@ -731,6 +954,196 @@ mod test {
         assert!(unsanitary_transaction.decode().is_none());
     }
 
+    #[test]
+    fn test_ui_raw_message_from_v0_message() {
+        let lookup_table_key = Pubkey::new_unique();
+        let message = VersionedMessage::V0(solana_sdk::message::v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2])],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: lookup_table_key,
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+            ..solana_sdk::message::v0::Message::default()
+        });
+
+        let ui_message = UiRawMessage::from(&message);
+        assert_eq!(ui_message.account_keys.len(), 2);
+        assert_eq!(
+            ui_message.address_table_lookups,
+            Some(vec![UiAddressTableLookup {
+                account_key: lookup_table_key.to_string(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }])
+        );
+
+        let json = serde_json::to_value(&ui_message).unwrap();
+        assert!(json.get("addressTableLookups").is_some());
+    }
+
+    #[test]
+    fn test_encode_v0_transaction_with_status_meta() {
+        let lookup_table_key = Pubkey::new_unique();
+        let loaded_address = Pubkey::new_unique();
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(solana_sdk::message::v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2])],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: lookup_table_key,
+                    writable_indexes: vec![0],
+                    readonly_indexes: vec![],
+                }],
+                ..solana_sdk::message::v0::Message::default()
+            }),
+        };
+        let meta = TransactionStatusMeta {
+            loaded_addresses: LoadedAddresses {
+                writable: vec![loaded_address],
+                readonly: vec![],
+            },
+            ..TransactionStatusMeta::default()
+        };
+
+        for encoding in &[
+            UiTransactionEncoding::Json,
+            UiTransactionEncoding::JsonParsed,
+        ] {
+            let encoded = TransactionWithStatusMeta {
+                transaction: transaction.clone(),
+                meta: Some(meta.clone()),
+            }
+            .encode(*encoding, Some(0))
+            .unwrap();
+            match encoded.transaction {
+                EncodedTransaction::Json(UiTransaction {
+                    message: UiMessage::Raw(message),
+                    ..
+                }) => assert_eq!(message, UiRawMessage::from(&transaction.message)),
+                transaction => panic!("unexpected encoding: {:?}", transaction),
+            }
+            assert_eq!(
+                encoded.meta.unwrap().loaded_addresses,
+                Some(UiLoadedAddresses {
+                    writable: vec![loaded_address.to_string()],
+                    readonly: vec![],
+                })
+            );
+        }
+
+        match EncodedTransaction::encode(transaction.clone(), UiTransactionEncoding::Base64) {
+            EncodedTransaction::Binary(blob, UiTransactionEncoding::Base64) => {
+                let decoded: VersionedTransaction =
+                    bincode::deserialize(&base64::decode(blob).unwrap()).unwrap();
+                assert_eq!(decoded, transaction);
+            }
+            transaction => panic!("unexpected encoding: {:?}", transaction),
+        }
+        assert_eq!(
+            EncodedTransaction::encode(transaction.clone(), UiTransactionEncoding::Base64).decode(),
+            Some(transaction.clone())
+        );
+
+        let confirmed_transaction = ConfirmedTransaction {
+            slot: 1,
+            transaction: TransactionWithStatusMeta {
+                transaction,
+                meta: Some(meta),
+            },
+            block_time: None,
+        };
+        assert_eq!(
+            confirmed_transaction
+                .clone()
+                .encode(UiTransactionEncoding::Json, None),
+            Err(EncodeError::UnsupportedTransactionVersion(0))
+        );
+        assert!(confirmed_transaction
+            .encode(UiTransactionEncoding::Json, Some(0))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_ui_transaction_status_meta_legacy_omits_loaded_addresses() {
+        let ui_meta = UiTransactionStatusMeta::from(TransactionStatusMeta::default());
+        assert_eq!(ui_meta.loaded_addresses, None);
+        let json = serde_json::to_value(&ui_meta).unwrap();
+        assert!(json.get("loadedAddresses").is_none());
+    }
+
+    #[test]
+    fn test_ui_transaction_status_meta_log_entries() {
+        let program_id = Pubkey::new_unique();
+        let meta = TransactionStatusMeta {
+            log_messages: Some(vec!["Program log: hello".to_string()]),
+            log_entries: Some(vec![TransactionLogEntry {
+                program_id: Some(program_id),
+                invoke_depth: 1,
+                message: "Program log: hello".to_string(),
+            }]),
+            ..TransactionStatusMeta::default()
+        };
+        let json = serde_json::to_value(&UiTransactionStatusMeta::from(meta)).unwrap();
+        assert_eq!(
+            json["logEntries"],
+            serde_json::json!([{
+                "programId": program_id.to_string(),
+                "invokeDepth": 1,
+                "message": "Program log: hello",
+            }])
+        );
+
+        let ui_meta = UiTransactionStatusMeta::from(TransactionStatusMeta::default());
+        let json = serde_json::to_value(&ui_meta).unwrap();
+        assert!(json.get("logEntries").is_none());
+    }
+
+    #[test]
+    fn test_ui_transaction_status_meta_return_data() {
+        let program_id = Pubkey::new_unique();
+        let meta = TransactionStatusMeta {
+            return_data: Some(TransactionReturnData {
+                program_id,
+                data: vec![1, 2, 3],
+            }),
+            ..TransactionStatusMeta::default()
+        };
+        let ui_meta = UiTransactionStatusMeta::from(meta);
+        assert_eq!(
+            ui_meta.return_data,
+            Some(UiTransactionReturnData {
+                program_id: program_id.to_string(),
+                data: ("AQID".to_string(), UiReturnDataEncoding::Base64),
+            })
+        );
+        let json = serde_json::to_value(&ui_meta).unwrap();
+        assert_eq!(
+            json["returnData"],
+            serde_json::json!({
+                "programId": program_id.to_string(),
+                "data": ["AQID", "base64"],
+            })
+        );
+
+        // Metas without return data omit the field entirely
+        let ui_meta = UiTransactionStatusMeta::from(TransactionStatusMeta::default());
+        let json = serde_json::to_value(&ui_meta).unwrap();
+        assert!(json.get("returnData").is_none());
+    }
+
     #[test]
     fn test_satisfies_commitment() {
         let status = TransactionStatus {
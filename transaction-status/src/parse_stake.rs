@@ -3,7 +3,7 @@ use {
         check_num_accounts, ParsableProgram, ParseInstructionError, ParsedInstructionEnum,
     },
     bincode::deserialize,
-    serde_json::{json, Map},
+    serde_json::{json, Map, Value},
     solana_sdk::{
         instruction::CompiledInstruction, pubkey::Pubkey, stake::instruction::StakeInstruction,
     },
@@ -17,6 +17,8 @@ pub fn parse_stake(
         .map_err(|_| ParseInstructionError::InstructionNotParsable(ParsableProgram::Stake))?;
     match instruction.accounts.iter().max() {
         Some(index) if (*index as usize) < account_keys.len() => {}
+        // GetMinimumDelegation takes no accounts
+        None => {}
         _ => {
             // Runtime should prevent this from ever happening
             return Err(ParseInstructionError::InstructionKeyMismatch(
@@ -268,6 +270,23 @@ pub fn parse_stake(
                 }),
             })
         }
+        StakeInstruction::Redelegate => {
+            check_num_stake_accounts(&instruction.accounts, 5)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "redelegate".to_string(),
+                info: json!({
+                    "stakeAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "newStakeAccount": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "voteAccount": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "stakeConfigAccount": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "stakeAuthority": account_keys[instruction.accounts[4] as usize].to_string(),
+                }),
+            })
+        }
+        StakeInstruction::GetMinimumDelegation => Ok(ParsedInstructionEnum {
+            instruction_type: "getMinimumDelegation".to_string(),
+            info: Value::Null,
+        }),
     }
 }
 
@@ -685,6 +704,48 @@ mod test {
         assert!(parse_stake(&message.instructions[0], &keys[0..2]).is_err());
     }
 
+    #[test]
+    fn test_parse_stake_redelegate() {
+        let stake_pubkey = Pubkey::new_unique();
+        let authority_pubkey = Pubkey::new_unique();
+        let vote_pubkey = Pubkey::new_unique();
+        let new_stake_pubkey = Pubkey::new_unique();
+        let instructions = instruction::redelegate(
+            &stake_pubkey,
+            &authority_pubkey,
+            &vote_pubkey,
+            &new_stake_pubkey,
+        );
+        let message = Message::new(&instructions, None);
+        assert_eq!(
+            parse_stake(&message.instructions[2], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "redelegate".to_string(),
+                info: json!({
+                    "stakeAccount": stake_pubkey.to_string(),
+                    "newStakeAccount": new_stake_pubkey.to_string(),
+                    "voteAccount": vote_pubkey.to_string(),
+                    "stakeConfigAccount": solana_sdk::stake::config::id().to_string(),
+                    "stakeAuthority": authority_pubkey.to_string(),
+                }),
+            }
+        );
+        assert!(parse_stake(&message.instructions[2], &message.account_keys[0..3]).is_err());
+    }
+
+    #[test]
+    fn test_parse_stake_get_minimum_delegation() {
+        let instruction = instruction::get_minimum_delegation();
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_stake(&message.instructions[0], &message.account_keys).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "getMinimumDelegation".to_string(),
+                info: Value::Null,
+            }
+        );
+    }
+
     #[test]
     #[allow(clippy::same_item_push)]
     fn test_parse_stake_checked_instructions() {
@@ -10,11 +10,17 @@ use {
     solana_sdk::{account::ReadableAccount, pubkey::Pubkey},
     spl_token_v2_0::{
         solana_program::program_pack::Pack,
-        state::{Account as TokenAccount, Mint},
+        state::{Account as TokenAccount, Mint, Multisig},
     },
-    std::collections::HashMap,
+    std::collections::{HashMap, HashSet},
 };
 
+/// Offset of the account type byte in token accounts and mints extended beyond the SPL Token
+/// layout, as done by token-2022 style programs
+const ACCOUNT_TYPE_OFFSET: usize = TokenAccount::LEN;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
 pub type TransactionTokenBalances = Vec<Vec<TransactionTokenBalance>>;
 
 pub struct TransactionTokenBalancesSet {
@@ -35,8 +41,39 @@ impl TransactionTokenBalancesSet {
     }
 }
 
-fn is_token_program(program_id: &Pubkey) -> bool {
-    program_id == &spl_token_id_v2_0()
+/// Token programs whose accounts are recorded in transaction token balances by default
+pub fn default_token_program_ids() -> HashSet<Pubkey> {
+    vec![spl_token_id_v2_0()].into_iter().collect()
+}
+
+/// Whether the data is long enough to be extended beyond the SPL Token layout.  Multisigs are
+/// longer than token accounts but never extended
+fn is_extended(data: &[u8]) -> bool {
+    data.len() > ACCOUNT_TYPE_OFFSET && data.len() != Multisig::LEN
+}
+
+/// Unpack a token account, ignoring any extensions following the SPL Token layout
+fn unpack_token_account(data: &[u8]) -> Option<TokenAccount> {
+    if is_extended(data) {
+        if data[ACCOUNT_TYPE_OFFSET] != ACCOUNT_TYPE_ACCOUNT {
+            return None;
+        }
+        TokenAccount::unpack(&data[..TokenAccount::LEN]).ok()
+    } else {
+        TokenAccount::unpack(data).ok()
+    }
+}
+
+/// Unpack a mint, ignoring any extensions following the SPL Token layout
+fn unpack_mint(data: &[u8]) -> Option<Mint> {
+    if is_extended(data) {
+        if data[ACCOUNT_TYPE_OFFSET] != ACCOUNT_TYPE_MINT {
+            return None;
+        }
+        Mint::unpack(&data[..Mint::LEN]).ok()
+    } else {
+        Mint::unpack(data).ok()
+    }
 }
 
 fn get_mint_decimals(bank: &Bank, mint: &Pubkey) -> Option<u8> {
@@ -45,11 +82,7 @@ fn get_mint_decimals(bank: &Bank, mint: &Pubkey) -> Option<u8> {
     } else {
         let mint_account = bank.get_account(mint)?;
 
-        let decimals = Mint::unpack(mint_account.data())
-            .map(|mint| mint.decimals)
-            .ok()?;
-
-        Some(decimals)
+        unpack_mint(mint_account.data()).map(|mint| mint.decimals)
     }
 }
 
@@ -57,6 +90,7 @@ pub fn collect_token_balances(
     bank: &Bank,
     batch: &TransactionBatch,
     mint_decimals: &mut HashMap<Pubkey, u8>,
+    token_program_ids: &HashSet<Pubkey>,
 ) -> TransactionTokenBalances {
     let mut balances: TransactionTokenBalances = vec![];
     let mut collect_time = Measure::start("collect_token_balances");
@@ -65,12 +99,13 @@ pub fn collect_token_balances(
         let has_token_program = transaction
             .message()
             .account_keys_iter()
-            .any(is_token_program);
+            .any(|account_id| token_program_ids.contains(account_id));
 
         let mut transaction_balances: Vec<TransactionTokenBalance> = vec![];
         if has_token_program {
             for (index, account_id) in transaction.message().account_keys_iter().enumerate() {
-                if transaction.message().is_invoked(index) || is_token_program(account_id) {
+                if transaction.message().is_invoked(index) || token_program_ids.contains(account_id)
+                {
                     continue;
                 }
 
@@ -78,13 +113,19 @@ pub fn collect_token_balances(
                     mint,
                     ui_token_amount,
                     owner,
-                }) = collect_token_balance_from_account(bank, account_id, mint_decimals)
-                {
+                    program_id,
+                }) = collect_token_balance_from_account(
+                    bank,
+                    account_id,
+                    mint_decimals,
+                    token_program_ids,
+                ) {
                     transaction_balances.push(TransactionTokenBalance {
                         account_index: index as u8,
                         mint,
                         ui_token_amount,
                         owner,
+                        program_id,
                     });
                 }
             }
@@ -103,16 +144,21 @@ struct TokenBalanceData {
     mint: String,
     owner: String,
     ui_token_amount: UiTokenAmount,
+    program_id: String,
 }
 
 fn collect_token_balance_from_account(
     bank: &Bank,
     account_id: &Pubkey,
     mint_decimals: &mut HashMap<Pubkey, u8>,
+    token_program_ids: &HashSet<Pubkey>,
 ) -> Option<TokenBalanceData> {
     let account = bank.get_account(account_id)?;
+    if !token_program_ids.contains(account.owner()) {
+        return None;
+    }
 
-    let token_account = TokenAccount::unpack(account.data()).ok()?;
+    let token_account = unpack_token_account(account.data())?;
     let mint = pubkey_from_spl_token_v2_0(&token_account.mint);
 
     let decimals = mint_decimals.get(&mint).cloned().or_else(|| {
@@ -125,5 +171,54 @@ fn collect_token_balance_from_account(
         mint: token_account.mint.to_string(),
         owner: token_account.owner.to_string(),
         ui_token_amount: token_amount_to_ui_amount(token_account.amount, decimals),
+        program_id: account.owner().to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, spl_token_v2_0::state::AccountState};
+
+    #[test]
+    fn test_unpack_extended_token_accounts() {
+        let token_account = TokenAccount {
+            mint: spl_token_v2_0::solana_program::pubkey::Pubkey::new_unique(),
+            owner: spl_token_v2_0::solana_program::pubkey::Pubkey::new_unique(),
+            amount: 42,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        };
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(token_account, &mut data).unwrap();
+        assert_eq!(unpack_token_account(&data), Some(token_account));
+
+        data.extend_from_slice(&[ACCOUNT_TYPE_ACCOUNT, 0, 0]);
+        assert_eq!(unpack_token_account(&data), Some(token_account));
+        data[ACCOUNT_TYPE_OFFSET] = ACCOUNT_TYPE_MINT;
+        assert_eq!(unpack_token_account(&data), None);
+
+        // A multisig whose byte at the account type offset happens to match isn't a token account
+        data[ACCOUNT_TYPE_OFFSET] = ACCOUNT_TYPE_ACCOUNT;
+        data.resize(Multisig::LEN, 0);
+        assert_eq!(unpack_token_account(&data), None);
+
+        let mint = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        assert_eq!(unpack_mint(&data), Some(mint));
+
+        // Extended mints are padded to the token account length before the account type
+        data.resize(ACCOUNT_TYPE_OFFSET, 0);
+        data.extend_from_slice(&[ACCOUNT_TYPE_MINT, 0, 0]);
+        assert_eq!(unpack_mint(&data), Some(mint));
+        data[ACCOUNT_TYPE_OFFSET] = ACCOUNT_TYPE_ACCOUNT;
+        assert_eq!(unpack_mint(&data), None);
+        data[ACCOUNT_TYPE_OFFSET] = ACCOUNT_TYPE_MINT;
+        data.resize(Multisig::LEN, 0);
+        assert_eq!(unpack_mint(&data), None);
+    }
+}
@@ -5,23 +5,36 @@ use {
     jsonrpc_ipc_server::{RequestContext, ServerBuilder},
     jsonrpc_server_utils::tokio,
     log::*,
+    solana_clap_utils::keystore::read_keypair_file_or_keystore_without_prompt,
     solana_core::{
-        consensus::Tower, tower_storage::TowerStorage, validator::ValidatorStartProgress,
+        banking_stage,
+        consensus::Tower,
+        fork_choice_diagnostics::{ForkChoiceDiagnostics, SharedForkChoiceDiagnostics},
+        test_validator,
+        tower_storage::TowerStorage,
+        validator::ValidatorStartProgress,
     },
     solana_gossip::cluster_info::ClusterInfo,
+    solana_runtime::bank_forks::BankForks,
     solana_sdk::{
+        clock::Epoch,
         exit::Exit,
-        signature::{read_keypair_file, Keypair, Signer},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
     },
     std::{
         net::SocketAddr,
         path::{Path, PathBuf},
+        str::FromStr,
         sync::{Arc, RwLock},
         thread::{self, Builder},
         time::{Duration, SystemTime},
     },
 };
 
+// How long a forkChoiceDiagnostics request waits for ReplayStage to build fresh diagnostics
+const FORK_CHOICE_DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Clone)]
 pub struct AdminRpcRequestMetadata {
     pub rpc_addr: Option<SocketAddr>,
@@ -31,6 +44,10 @@ pub struct AdminRpcRequestMetadata {
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub cluster_info: Arc<RwLock<Option<Arc<ClusterInfo>>>>,
     pub tower_storage: Arc<dyn TowerStorage>,
+    pub fork_choice_diagnostics: Arc<SharedForkChoiceDiagnostics>,
+    /// Only provided by solana-test-validator, which is the only validator whose feature gates
+    /// may be changed at runtime
+    pub test_validator_bank_forks: Arc<RwLock<Option<Arc<RwLock<BankForks>>>>>,
 }
 impl Metadata for AdminRpcRequestMetadata {}
 
@@ -45,7 +62,10 @@ pub trait AdminRpc {
     fn rpc_addr(&self, meta: Self::Metadata) -> Result<Option<SocketAddr>>;
 
     #[rpc(name = "setLogFilter")]
-    fn set_log_filter(&self, filter: String) -> Result<()>;
+    fn set_log_filter(&self, filter: String, duration_secs: Option<u64>) -> Result<()>;
+
+    #[rpc(name = "logFilter")]
+    fn log_filter(&self) -> Result<String>;
 
     #[rpc(meta, name = "startTime")]
     fn start_time(&self, meta: Self::Metadata) -> Result<SystemTime>;
@@ -61,6 +81,18 @@ pub trait AdminRpc {
 
     #[rpc(meta, name = "setIdentity")]
     fn set_identity(&self, meta: Self::Metadata, keypair_file: String) -> Result<()>;
+
+    #[rpc(meta, name = "forkChoiceDiagnostics")]
+    fn fork_choice_diagnostics(&self, meta: Self::Metadata) -> Result<ForkChoiceDiagnostics>;
+
+    #[rpc(name = "bufferedPacketsCount")]
+    fn buffered_packets_count(&self) -> Result<usize>;
+
+    #[rpc(meta, name = "activateFeature")]
+    fn activate_feature(&self, meta: Self::Metadata, feature_id: String) -> Result<Epoch>;
+
+    #[rpc(meta, name = "deactivateFeature")]
+    fn deactivate_feature(&self, meta: Self::Metadata, feature_id: String) -> Result<()>;
 }
 
 pub struct AdminRpcImpl;
@@ -94,12 +126,22 @@ impl AdminRpc for AdminRpcImpl {
         Ok(meta.rpc_addr)
     }
 
-    fn set_log_filter(&self, filter: String) -> Result<()> {
+    fn set_log_filter(&self, filter: String, duration_secs: Option<u64>) -> Result<()> {
         debug!("set_log_filter admin rpc request received");
-        solana_logger::setup_with(&filter);
+        match duration_secs {
+            Some(duration_secs) => {
+                solana_logger::setup_with_for_duration(&filter, Duration::from_secs(duration_secs))
+            }
+            None => solana_logger::setup_with(&filter),
+        }
         Ok(())
     }
 
+    fn log_filter(&self) -> Result<String> {
+        debug!("log_filter admin rpc request received");
+        Ok(solana_logger::current_filter())
+    }
+
     fn start_time(&self, meta: Self::Metadata) -> Result<SystemTime> {
         debug!("start_time admin rpc request received");
         Ok(meta.start_time)
@@ -113,7 +155,7 @@ impl AdminRpc for AdminRpcImpl {
     fn add_authorized_voter(&self, meta: Self::Metadata, keypair_file: String) -> Result<()> {
         debug!("add_authorized_voter request received");
 
-        let authorized_voter = read_keypair_file(keypair_file)
+        let authorized_voter = read_keypair_file_or_keystore_without_prompt(keypair_file)
             .map_err(|err| jsonrpc_core::error::Error::invalid_params(format!("{}", err)))?;
 
         let mut authorized_voter_keypairs = meta.authorized_voter_keypairs.write().unwrap();
@@ -140,12 +182,13 @@ impl AdminRpc for AdminRpcImpl {
     fn set_identity(&self, meta: Self::Metadata, keypair_file: String) -> Result<()> {
         debug!("set_identity request received");
 
-        let identity_keypair = read_keypair_file(&keypair_file).map_err(|err| {
-            jsonrpc_core::error::Error::invalid_params(format!(
-                "Failed to read identity keypair from {}: {}",
-                keypair_file, err
-            ))
-        })?;
+        let identity_keypair = read_keypair_file_or_keystore_without_prompt(&keypair_file)
+            .map_err(|err| {
+                jsonrpc_core::error::Error::invalid_params(format!(
+                    "Failed to read identity keypair from {}: {}",
+                    keypair_file, err
+                ))
+            })?;
 
         // Ensure a Tower exists for the new identity and exit gracefully.
         // ReplayStage will be less forgiving if it fails to load the new tower.
@@ -167,6 +210,57 @@ impl AdminRpc for AdminRpcImpl {
             ))
         }
     }
+
+    fn fork_choice_diagnostics(&self, meta: Self::Metadata) -> Result<ForkChoiceDiagnostics> {
+        debug!("fork_choice_diagnostics admin rpc request received");
+        meta.fork_choice_diagnostics
+            .request(FORK_CHOICE_DIAGNOSTICS_TIMEOUT)
+            .ok_or_else(|| {
+                jsonrpc_core::error::Error::invalid_params(
+                    "Replay stage did not build the fork choice diagnostics in time, retry",
+                )
+            })
+    }
+
+    fn buffered_packets_count(&self) -> Result<usize> {
+        debug!("buffered_packets_count admin rpc request received");
+        Ok(banking_stage::buffered_packets_count())
+    }
+
+    fn activate_feature(&self, meta: Self::Metadata, feature_id: String) -> Result<Epoch> {
+        debug!("activate_feature admin rpc request received");
+        let bank_forks = test_validator_bank_forks(&meta)?;
+        test_validator::activate_feature(&bank_forks, &parse_feature_id(&feature_id)?)
+            .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
+
+    fn deactivate_feature(&self, meta: Self::Metadata, feature_id: String) -> Result<()> {
+        debug!("deactivate_feature admin rpc request received");
+        let bank_forks = test_validator_bank_forks(&meta)?;
+        test_validator::deactivate_feature(&bank_forks, &parse_feature_id(&feature_id)?)
+            .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
+}
+
+fn test_validator_bank_forks(meta: &AdminRpcRequestMetadata) -> Result<Arc<RwLock<BankForks>>> {
+    meta.test_validator_bank_forks
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params(
+                "Feature gates can only be changed on a running solana-test-validator",
+            )
+        })
+}
+
+fn parse_feature_id(feature_id: &str) -> Result<Pubkey> {
+    Pubkey::from_str(feature_id).map_err(|err| {
+        jsonrpc_core::error::Error::invalid_params(format!(
+            "Invalid feature id {}: {}",
+            feature_id, err
+        ))
+    })
 }
 
 // Start the Admin RPC interface
@@ -1,12 +1,13 @@
 use {
-    clap::{crate_name, value_t, value_t_or_exit, App, Arg},
+    clap::{crate_name, value_t, value_t_or_exit, App, Arg, SubCommand},
     log::*,
     solana_clap_utils::{
         input_parsers::{pubkey_of, pubkeys_of, value_of},
         input_validators::{
-            is_pubkey, is_pubkey_or_keypair, is_slot, is_url_or_moniker,
+            is_epoch, is_pubkey, is_pubkey_or_keypair, is_slot, is_url_or_moniker,
             normalize_to_url_if_moniker,
         },
+        keystore::read_keystore_pubkey,
     },
     solana_client::rpc_client::RpcClient,
     solana_core::tower_storage::FileTowerStorage,
@@ -14,8 +15,9 @@ use {
     solana_rpc::rpc::JsonRpcConfig,
     solana_sdk::{
         account::AccountSharedData,
-        clock::Slot,
+        clock::{Epoch, Slot},
         epoch_schedule::{EpochSchedule, MINIMUM_SLOTS_PER_EPOCH},
+        genesis_config::GenesisConfig,
         native_token::sol_to_lamports,
         pubkey::Pubkey,
         rent::Rent,
@@ -262,6 +264,27 @@ fn main() {
                         referenced by the --url argument will be used",
                 ),
         )
+        .arg(
+            Arg::with_name("warp_epoch")
+                .long("warp-epoch")
+                .takes_value(true)
+                .value_name("WARP_EPOCH")
+                .validator(is_epoch)
+                .conflicts_with("warp_slot")
+                .help("Warp the ledger to the first slot of WARP_EPOCH after starting the validator"),
+        )
+        .arg(
+            Arg::with_name("deactivate_feature")
+                .long("deactivate-feature")
+                .takes_value(true)
+                .value_name("FEATURE_PUBKEY")
+                .validator(is_pubkey)
+                .multiple(true)
+                .help(
+                    "Deactivate this feature in genesis. \
+                     If the ledger already exists then this parameter is silently ignored",
+                ),
+        )
         .arg(
             Arg::with_name("limit_ledger_size")
                 .long("limit-ledger-size")
@@ -281,6 +304,36 @@ fn main() {
                      If the ledger already exists then this parameter is silently ignored",
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("activate-feature")
+                .about("Activate a feature on the running test validator at the next epoch boundary")
+                .arg(
+                    Arg::with_name("feature_id")
+                        .index(1)
+                        .value_name("FEATURE_PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_pubkey)
+                        .help("Feature to activate"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("deactivate-feature")
+                .about("Cancel a pending feature activation on the running test validator")
+                .arg(
+                    Arg::with_name("feature_id")
+                        .index(1)
+                        .value_name("FEATURE_PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_pubkey)
+                        .help("Feature to deactivate"),
+                )
+                .after_help(
+                    "Note: features that are already active can only be deactivated when the \
+                     ledger is created, with --deactivate-feature",
+                ),
+        )
         .get_matches();
 
     let output = if matches.is_present("quiet") {
@@ -292,6 +345,45 @@ fn main() {
     };
 
     let ledger_path = value_t_or_exit!(matches, "ledger_path", PathBuf);
+
+    match matches.subcommand() {
+        ("activate-feature", Some(subcommand_matches)) => {
+            let feature_id = pubkey_of(subcommand_matches, "feature_id").unwrap();
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let epoch = admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .activate_feature(feature_id.to_string())
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    println!("Feature activation failed: {}", err);
+                    exit(1);
+                });
+            println!("Feature {} will be active in epoch {}", feature_id, epoch);
+            return;
+        }
+        ("deactivate-feature", Some(subcommand_matches)) => {
+            let feature_id = pubkey_of(subcommand_matches, "feature_id").unwrap();
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .deactivate_feature(feature_id.to_string())
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    println!("Feature deactivation failed: {}", err);
+                    exit(1);
+                });
+            println!("Feature {} activation cancelled", feature_id);
+            return;
+        }
+        _ => {}
+    }
+
     let reset_ledger = matches.is_present("reset");
 
     if !ledger_path.exists() {
@@ -360,9 +452,14 @@ fn main() {
     let (mint_address, random_mint) = pubkey_of(&matches, "mint_address")
         .map(|pk| (pk, false))
         .unwrap_or_else(|| {
-            read_keypair_file(&cli_config.keypair_path)
-                .map(|kp| (kp.pubkey(), false))
-                .unwrap_or_else(|_| (Keypair::new().pubkey(), true))
+            read_keystore_pubkey(&cli_config.keypair_path)
+                .or_else(|| {
+                    read_keypair_file(&cli_config.keypair_path)
+                        .ok()
+                        .map(|kp| kp.pubkey())
+                })
+                .map(|pubkey| (pubkey, false))
+                .unwrap_or_else(|| (Keypair::new().pubkey(), true))
         });
 
     let rpc_port = value_t_or_exit!(matches, "rpc_port", u16);
@@ -448,6 +545,20 @@ fn main() {
                     })
             }
         })
+    } else if let Some(warp_epoch) = value_t!(matches, "warp_epoch", Epoch).ok() {
+        let epoch_schedule = if TestValidatorGenesis::ledger_exists(&ledger_path) {
+            GenesisConfig::load(&ledger_path)
+                .unwrap_or_else(|err| {
+                    println!("Failed to load genesis config: {}", err);
+                    exit(1);
+                })
+                .epoch_schedule
+        } else if let Some(slots_per_epoch) = slots_per_epoch {
+            EpochSchedule::custom(slots_per_epoch, slots_per_epoch, false)
+        } else {
+            EpochSchedule::without_warmup()
+        };
+        Some(epoch_schedule.get_first_slot_in_epoch(warp_epoch))
     } else {
         None
     };
@@ -494,6 +605,7 @@ fn main() {
             ("mint_address", "--mint"),
             ("slots_per_epoch", "--slots-per-epoch"),
             ("faucet_sol", "--faucet-sol"),
+            ("deactivate_feature", "--deactivate-feature"),
         ] {
             if matches.is_present(name) {
                 println!("{} argument ignored, ledger already exists", long);
@@ -512,6 +624,7 @@ fn main() {
     let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()));
 
     let admin_service_cluster_info = Arc::new(RwLock::new(None));
+    let admin_service_bank_forks = Arc::new(RwLock::new(None));
     admin_rpc_service::run(
         &ledger_path,
         admin_rpc_service::AdminRpcRequestMetadata {
@@ -525,6 +638,8 @@ fn main() {
             authorized_voter_keypairs: genesis.authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
             tower_storage: tower_storage.clone(),
+            fork_choice_diagnostics: genesis.fork_choice_diagnostics.clone(),
+            test_validator_bank_forks: admin_service_bank_forks.clone(),
         },
     );
     let dashboard = if output == Output::Dashboard {
@@ -570,6 +685,10 @@ fn main() {
         genesis.warp_slot(warp_slot);
     }
 
+    if let Some(deactivate_features) = pubkeys_of(&matches, "deactivate_feature") {
+        genesis.deactivate_features(&deactivate_features);
+    }
+
     if let Some(slots_per_epoch) = slots_per_epoch {
         genesis.epoch_schedule(EpochSchedule::custom(
             slots_per_epoch,
@@ -599,6 +718,7 @@ fn main() {
     match genesis.start_with_mint_address(mint_address, socket_addr_space) {
         Ok(test_validator) => {
             *admin_service_cluster_info.write().unwrap() = Some(test_validator.cluster_info());
+            *admin_service_bank_forks.write().unwrap() = Some(test_validator.bank_forks());
             if let Some(dashboard) = dashboard {
                 dashboard.run(Duration::from_millis(250));
             }
@@ -15,7 +15,7 @@ use {
         snapshot_archive_info::SnapshotArchiveInfoGetter,
         snapshot_package::SnapshotType,
         snapshot_utils::{
-            self, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            self, SnapshotVerification, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
         },
     },
@@ -41,7 +41,7 @@ use {
     },
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RpcBootstrapConfig {
     pub no_genesis_fetch: bool,
     pub no_snapshot_fetch: bool,
@@ -49,6 +49,15 @@ pub struct RpcBootstrapConfig {
     pub max_genesis_archive_unpacked_size: u64,
     pub no_check_vote_account: bool,
     pub incremental_snapshot_fetch: bool,
+    /// Skip the startup accounts hash verification of a local snapshot advertised by a trusted
+    /// validator
+    pub skip_trusted_snapshot_verification: bool,
+}
+
+/// Validator config changes learnt from the cluster while bootstrapping
+struct RpcBootstrapUpdates {
+    expected_genesis_hash: Option<Hash>,
+    snapshot_verification: Option<SnapshotVerification>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -90,10 +99,114 @@ pub fn rpc_bootstrap(
         return;
     }
 
+    let RpcBootstrapUpdates {
+        expected_genesis_hash,
+        snapshot_verification,
+    } = bootstrap_from_rpc(
+        &node.info.gossip,
+        &node.sockets.gossip,
+        identity_keypair,
+        ledger_path,
+        snapshot_archives_dir,
+        vote_account,
+        authorized_voter_keypairs,
+        cluster_entrypoints,
+        validator_config,
+        bootstrap_config,
+        use_progress_bar,
+        maximum_local_snapshot_age,
+        should_check_duplicate_instance,
+        start_progress,
+        minimal_snapshot_download_speed,
+        maximum_snapshot_download_abort,
+        socket_addr_space,
+    );
+    validator_config.expected_genesis_hash = expected_genesis_hash;
+    if let Some(snapshot_verification) = snapshot_verification {
+        validator_config.snapshot_verification = snapshot_verification;
+    }
+}
+
+/// Download fresh snapshot archives from the cluster after every local one was found corrupted
+/// and quarantined while loading the ledger.  Returns whether a full snapshot archive is now
+/// available locally.
+#[allow(clippy::too_many_arguments)]
+pub fn fetch_snapshot_archives(
+    gossip_addr: &SocketAddr,
+    gossip_socket: &UdpSocket,
+    identity_keypair: &Arc<Keypair>,
+    ledger_path: &Path,
+    snapshot_archives_dir: &Path,
+    vote_account: &Pubkey,
+    authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
+    cluster_entrypoints: &[ContactInfo],
+    validator_config: &ValidatorConfig,
+    bootstrap_config: RpcBootstrapConfig,
+    use_progress_bar: bool,
+    should_check_duplicate_instance: bool,
+    start_progress: &Arc<RwLock<ValidatorStartProgress>>,
+    minimal_snapshot_download_speed: f32,
+    maximum_snapshot_download_abort: u64,
+    socket_addr_space: SocketAddrSpace,
+) -> bool {
+    if bootstrap_config.no_snapshot_fetch {
+        return false;
+    }
+    info!("Fetching snapshot archives to replace the quarantined ones");
+
+    // The genesis config was fetched when the validator started, and there's no local snapshot
+    // left to reuse, whatever its age
+    bootstrap_from_rpc(
+        gossip_addr,
+        gossip_socket,
+        identity_keypair,
+        ledger_path,
+        snapshot_archives_dir,
+        vote_account,
+        authorized_voter_keypairs,
+        cluster_entrypoints,
+        validator_config,
+        RpcBootstrapConfig {
+            no_genesis_fetch: true,
+            ..bootstrap_config
+        },
+        use_progress_bar,
+        0,
+        should_check_duplicate_instance,
+        start_progress,
+        minimal_snapshot_download_speed,
+        maximum_snapshot_download_abort,
+        socket_addr_space,
+    );
+    *start_progress.write().unwrap() = ValidatorStartProgress::LoadingLedger;
+    snapshot_utils::get_highest_full_snapshot_archive_info(snapshot_archives_dir).is_some()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bootstrap_from_rpc(
+    gossip_addr: &SocketAddr,
+    gossip_socket: &UdpSocket,
+    identity_keypair: &Arc<Keypair>,
+    ledger_path: &Path,
+    snapshot_archives_dir: &Path,
+    vote_account: &Pubkey,
+    authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
+    cluster_entrypoints: &[ContactInfo],
+    validator_config: &ValidatorConfig,
+    bootstrap_config: RpcBootstrapConfig,
+    use_progress_bar: bool,
+    maximum_local_snapshot_age: Slot,
+    should_check_duplicate_instance: bool,
+    start_progress: &Arc<RwLock<ValidatorStartProgress>>,
+    minimal_snapshot_download_speed: f32,
+    maximum_snapshot_download_abort: u64,
+    socket_addr_space: SocketAddrSpace,
+) -> RpcBootstrapUpdates {
     if bootstrap_config.incremental_snapshot_fetch {
         info!("rpc_bootstrap with incremental snapshot fetch");
         with_incremental_snapshots::rpc_bootstrap(
-            node,
+            gossip_addr,
+            gossip_socket,
             identity_keypair,
             ledger_path,
             snapshot_archives_dir,
@@ -113,7 +226,8 @@ pub fn rpc_bootstrap(
     } else {
         info!("rpc_bootstrap without incremental snapshot fetch");
         without_incremental_snapshots::rpc_bootstrap(
-            node,
+            gossip_addr,
+            gossip_socket,
             identity_keypair,
             ledger_path,
             snapshot_archives_dir,
@@ -400,19 +514,77 @@ fn get_highest_local_snapshot_hash(
     }
 }
 
+/// Pin the highest local snapshot as trusted if a trusted validator advertises its hash and the
+/// operator opted in, so that its full accounts hash doesn't have to be recomputed at startup
+fn pin_trusted_local_snapshot_hash(
+    cluster_info: &ClusterInfo,
+    validator_config: &ValidatorConfig,
+    bootstrap_config: &RpcBootstrapConfig,
+    snapshot_archives_dir: &Path,
+) -> Option<SnapshotVerification> {
+    if !bootstrap_config.skip_trusted_snapshot_verification
+        || validator_config.snapshot_verification != SnapshotVerification::Full
+    {
+        return None;
+    }
+    let trusted_validators = validator_config.trusted_validators.as_ref()?;
+    let local_snapshot_hash = get_highest_local_snapshot_hash(snapshot_archives_dir)?;
+
+    let is_trusted = trusted_validators.iter().any(|trusted_validator| {
+        cluster_info
+            .get_snapshot_hash_for_node(trusted_validator, |snapshot_hashes| {
+                snapshot_hashes.contains(&local_snapshot_hash)
+            })
+            .unwrap_or_default()
+            || cluster_info
+                .get_incremental_snapshot_hashes_for_node(trusted_validator)
+                .map_or(false, |incremental_snapshot_hashes| {
+                    incremental_snapshot_hashes.base == local_snapshot_hash
+                        || incremental_snapshot_hashes
+                            .hashes
+                            .contains(&local_snapshot_hash)
+                })
+    });
+    is_trusted.then(|| {
+        info!(
+            "Local snapshot {:?} is advertised by a trusted validator, \
+             skipping its accounts hash verification at startup",
+            local_snapshot_hash
+        );
+        SnapshotVerification::Trusted(std::iter::once(local_snapshot_hash).collect())
+    })
+}
+
+/// Record the bytes downloaded so far in the snapshot download reported by `start_progress`
+fn update_download_progress(
+    start_progress: &Arc<RwLock<ValidatorStartProgress>>,
+    download_progress: &DownloadProgressRecord,
+) {
+    if let ValidatorStartProgress::DownloadingSnapshot {
+        downloaded_bytes,
+        total_bytes,
+        ..
+    } = &mut *start_progress.write().unwrap()
+    {
+        *downloaded_bytes = download_progress.current_bytes as u64;
+        *total_bytes = download_progress.total_bytes as u64;
+    }
+}
+
 mod without_incremental_snapshots {
     use super::*;
 
     #[allow(clippy::too_many_arguments)]
     pub fn rpc_bootstrap(
-        node: &Node,
+        gossip_addr: &SocketAddr,
+        gossip_socket: &UdpSocket,
         identity_keypair: &Arc<Keypair>,
         ledger_path: &Path,
         snapshot_archives_dir: &Path,
         vote_account: &Pubkey,
         authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
         cluster_entrypoints: &[ContactInfo],
-        validator_config: &mut ValidatorConfig,
+        validator_config: &ValidatorConfig,
         bootstrap_config: RpcBootstrapConfig,
         use_progress_bar: bool,
         maximum_local_snapshot_age: Slot,
@@ -421,7 +593,11 @@ mod without_incremental_snapshots {
         minimal_snapshot_download_speed: f32,
         maximum_snapshot_download_abort: u64,
         socket_addr_space: SocketAddrSpace,
-    ) {
+    ) -> RpcBootstrapUpdates {
+        let mut updates = RpcBootstrapUpdates {
+            expected_genesis_hash: validator_config.expected_genesis_hash,
+            snapshot_verification: None,
+        };
         let mut blacklisted_rpc_nodes = HashSet::new();
         let mut gossip = None;
         let mut download_abort_count = 0;
@@ -433,8 +609,8 @@ mod without_incremental_snapshots {
                     identity_keypair.clone(),
                     cluster_entrypoints,
                     ledger_path,
-                    &node.info.gossip,
-                    node.sockets.gossip.try_clone().unwrap(),
+                    gossip_addr,
+                    gossip_socket.try_clone().unwrap(),
                     validator_config.expected_shred_version,
                     validator_config.gossip_validators.clone(),
                     should_check_duplicate_instance,
@@ -451,7 +627,7 @@ mod without_incremental_snapshots {
                 snapshot_archives_dir,
             );
             if rpc_node_details.is_none() {
-                return;
+                return updates;
             }
             let (rpc_contact_info, snapshot_hash) = rpc_node_details.unwrap();
 
@@ -472,7 +648,7 @@ mod without_incremental_snapshots {
             let genesis_config = download_then_check_genesis_hash(
                 &rpc_contact_info.rpc,
                 ledger_path,
-                validator_config.expected_genesis_hash,
+                updates.expected_genesis_hash,
                 bootstrap_config.max_genesis_archive_unpacked_size,
                 bootstrap_config.no_genesis_fetch,
                 use_progress_bar,
@@ -480,13 +656,13 @@ mod without_incremental_snapshots {
 
             if let Ok(genesis_config) = genesis_config {
                 let genesis_hash = genesis_config.hash();
-                if validator_config.expected_genesis_hash.is_none() {
+                if updates.expected_genesis_hash.is_none() {
                     info!("Expected genesis hash set to {}", genesis_hash);
-                    validator_config.expected_genesis_hash = Some(genesis_hash);
+                    updates.expected_genesis_hash = Some(genesis_hash);
                 }
             }
 
-            if let Some(expected_genesis_hash) = validator_config.expected_genesis_hash {
+            if let Some(expected_genesis_hash) = updates.expected_genesis_hash {
                 // Sanity check that the RPC node is using the expected genesis hash before
                 // downloading a snapshot from it
                 let rpc_genesis_hash = rpc_client
@@ -529,6 +705,12 @@ mod without_incremental_snapshots {
                 };
 
                 if use_local_snapshot {
+                    updates.snapshot_verification = pin_trusted_local_snapshot_hash(
+                        &gossip.as_ref().unwrap().0,
+                        validator_config,
+                        &bootstrap_config,
+                        snapshot_archives_dir,
+                    );
                     Ok(())
                 } else {
                     rpc_client
@@ -539,6 +721,8 @@ mod without_incremental_snapshots {
                                 ValidatorStartProgress::DownloadingSnapshot {
                                     slot: snapshot_hash.0,
                                     rpc_addr: rpc_contact_info.rpc,
+                                    downloaded_bytes: 0,
+                                    total_bytes: 0,
                                 };
                             info!("RPC node root slot: {}", slot);
                             let (cluster_info, gossip_exit_flag, gossip_service) =
@@ -562,6 +746,7 @@ mod without_incremental_snapshots {
                                 use_progress_bar,
                                 &mut Some(Box::new(|download_progress: &DownloadProgressRecord| {
                                     debug!("Download progress: {:?}", download_progress);
+                                    update_download_progress(start_progress, download_progress);
 
                                     if download_progress.last_throughput <  minimal_snapshot_download_speed
                                        && download_progress.notification_count <= 1
@@ -612,6 +797,12 @@ mod without_incremental_snapshots {
                         .unwrap()
                         .iter()
                         .map(|k| k.pubkey())
+                        .chain(
+                            validator_config
+                                .remote_authorized_voters
+                                .iter()
+                                .map(|signer| signer.pubkey()),
+                        )
                         .collect::<Vec<_>>(),
                 )
                 .unwrap_or_else(|err| {
@@ -649,6 +840,7 @@ mod without_incremental_snapshots {
             gossip_exit_flag.store(true, Ordering::Relaxed);
             gossip_service.join().unwrap();
         }
+        updates
     }
 
     fn get_rpc_node(
@@ -819,14 +1011,15 @@ mod with_incremental_snapshots {
 
     #[allow(clippy::too_many_arguments)]
     pub fn rpc_bootstrap(
-        node: &Node,
+        gossip_addr: &SocketAddr,
+        gossip_socket: &UdpSocket,
         identity_keypair: &Arc<Keypair>,
         ledger_path: &Path,
         snapshot_archives_dir: &Path,
         vote_account: &Pubkey,
         authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
         cluster_entrypoints: &[ContactInfo],
-        validator_config: &mut ValidatorConfig,
+        validator_config: &ValidatorConfig,
         bootstrap_config: RpcBootstrapConfig,
         use_progress_bar: bool,
         maximum_local_snapshot_age: Slot,
@@ -835,7 +1028,11 @@ mod with_incremental_snapshots {
         minimal_snapshot_download_speed: f32,
         maximum_snapshot_download_abort: u64,
         socket_addr_space: SocketAddrSpace,
-    ) {
+    ) -> RpcBootstrapUpdates {
+        let mut updates = RpcBootstrapUpdates {
+            expected_genesis_hash: validator_config.expected_genesis_hash,
+            snapshot_verification: None,
+        };
         let mut blacklisted_rpc_nodes = HashSet::new();
         let mut gossip = None;
         let mut download_abort_count = 0;
@@ -847,8 +1044,8 @@ mod with_incremental_snapshots {
                     identity_keypair.clone(),
                     cluster_entrypoints,
                     ledger_path,
-                    &node.info.gossip,
-                    node.sockets.gossip.try_clone().unwrap(),
+                    gossip_addr,
+                    gossip_socket.try_clone().unwrap(),
                     validator_config.expected_shred_version,
                     validator_config.gossip_validators.clone(),
                     should_check_duplicate_instance,
@@ -864,7 +1061,7 @@ mod with_incremental_snapshots {
                 &bootstrap_config,
             );
             if rpc_node_details.is_none() {
-                return;
+                return updates;
             }
             let GetRpcNodeResult {
                 rpc_contact_info,
@@ -888,7 +1085,7 @@ mod with_incremental_snapshots {
                 let genesis_config = download_then_check_genesis_hash(
                     &rpc_contact_info.rpc,
                     ledger_path,
-                    validator_config.expected_genesis_hash,
+                    updates.expected_genesis_hash,
                     bootstrap_config.max_genesis_archive_unpacked_size,
                     bootstrap_config.no_genesis_fetch,
                     use_progress_bar,
@@ -896,13 +1093,13 @@ mod with_incremental_snapshots {
 
                 if let Ok(genesis_config) = genesis_config {
                     let genesis_hash = genesis_config.hash();
-                    if validator_config.expected_genesis_hash.is_none() {
+                    if updates.expected_genesis_hash.is_none() {
                         info!("Expected genesis hash set to {}", genesis_hash);
-                        validator_config.expected_genesis_hash = Some(genesis_hash);
+                        updates.expected_genesis_hash = Some(genesis_hash);
                     }
                 }
 
-                if let Some(expected_genesis_hash) = validator_config.expected_genesis_hash {
+                if let Some(expected_genesis_hash) = updates.expected_genesis_hash {
                     // Sanity check that the RPC node is using the expected genesis hash before
                     // downloading a snapshot from it
                     let rpc_genesis_hash = rpc_client
@@ -918,6 +1115,12 @@ mod with_incremental_snapshots {
                 }
 
                 let (cluster_info, gossip_exit_flag, gossip_service) = gossip.take().unwrap();
+                updates.snapshot_verification = pin_trusted_local_snapshot_hash(
+                    &cluster_info,
+                    validator_config,
+                    &bootstrap_config,
+                    snapshot_archives_dir,
+                );
                 cluster_info.save_contact_info();
                 gossip_exit_flag.store(true, Ordering::Relaxed);
                 gossip_service.join().unwrap();
@@ -952,6 +1155,12 @@ mod with_incremental_snapshots {
                             .unwrap()
                             .iter()
                             .map(|k| k.pubkey())
+                            .chain(
+                                validator_config
+                                    .remote_authorized_voters
+                                    .iter()
+                                    .map(|signer| signer.pubkey()),
+                            )
                             .collect::<Vec<_>>(),
                     )
                     .unwrap_or_else(|err| {
@@ -989,6 +1198,7 @@ mod with_incremental_snapshots {
             gossip_exit_flag.store(true, Ordering::Relaxed);
             gossip_service.join().unwrap();
         }
+        updates
     }
 
     /// Get an RPC peer node to download from.
@@ -1507,6 +1717,8 @@ mod with_incremental_snapshots {
         *start_progress.write().unwrap() = ValidatorStartProgress::DownloadingSnapshot {
             slot: desired_snapshot_hash.0,
             rpc_addr: rpc_contact_info.rpc,
+            downloaded_bytes: 0,
+            total_bytes: 0,
         };
         download_snapshot_archive(
             &rpc_contact_info.rpc,
@@ -1518,6 +1730,7 @@ mod with_incremental_snapshots {
             use_progress_bar,
             &mut Some(Box::new(|download_progress: &DownloadProgressRecord| {
                 debug!("Download progress: {:?}", download_progress);
+                update_download_progress(start_progress, download_progress);
                 if download_progress.last_throughput < minimal_snapshot_download_speed
                     && download_progress.notification_count <= 1
                     && download_progress.percentage_done <= 2_f32
@@ -1,6 +1,6 @@
 use {
     crate::{admin_rpc_service, new_spinner_progress_bar, println_name_value, ProgressBar},
-    console::style,
+    console::{style, Term},
     solana_client::{
         client_error, rpc_client::RpcClient, rpc_request, rpc_response::RpcContactInfo,
     },
@@ -10,7 +10,7 @@ use {
         pubkey::Pubkey,
     },
     std::{
-        io,
+        io::{self, Write},
         net::SocketAddr,
         path::{Path, PathBuf},
         sync::{
@@ -18,7 +18,7 @@ use {
             Arc,
         },
         thread,
-        time::{Duration, SystemTime},
+        time::{Duration, Instant, SystemTime},
     },
 };
 
@@ -69,12 +69,9 @@ impl Dashboard {
             let progress_bar = new_spinner_progress_bar();
             progress_bar.set_message("Connecting...");
 
-            let (rpc_addr, start_time) = match runtime.block_on(wait_for_validator_startup(
-                &ledger_path,
-                &exit,
-                progress_bar,
-                refresh_interval,
-            )) {
+            let (admin_client, rpc_addr, start_time) = match runtime.block_on(
+                wait_for_validator_startup(&ledger_path, &exit, progress_bar, refresh_interval),
+            ) {
                 None => continue,
                 Some(results) => results,
             };
@@ -112,8 +109,9 @@ impl Dashboard {
                 }
             }
 
-            let progress_bar = new_spinner_progress_bar();
+            let mut panel = Panel::new();
             let mut snapshot_slot_info = None;
+            let mut last_transaction_count: Option<(u64, Instant)> = None;
             for i in 0.. {
                 if exit.load(Ordering::Relaxed) {
                     break;
@@ -122,80 +120,153 @@ impl Dashboard {
                     snapshot_slot_info = rpc_client.get_highest_snapshot_slot().ok();
                 }
 
-                match get_validator_stats(&rpc_client, &identity) {
-                    Ok((
-                        max_retransmit_slot,
-                        processed_slot,
-                        confirmed_slot,
-                        finalized_slot,
-                        transaction_count,
-                        identity_balance,
-                        health,
-                    )) => {
-                        let uptime = {
-                            let uptime =
-                                chrono::Duration::from_std(start_time.elapsed().unwrap()).unwrap();
-
-                            format!(
-                                "{:02}:{:02}:{:02} ",
-                                uptime.num_hours(),
-                                uptime.num_minutes() % 60,
-                                uptime.num_seconds() % 60
-                            )
-                        };
-
-                        progress_bar.set_message(format!(
-                            "{}{}{}| \
-                                    Processed Slot: {} | Confirmed Slot: {} | Finalized Slot: {} | \
-                                    Full Snapshot Slot: {} | Incremental Snapshot Slot: {} | \
-                                    Transactions: {} | {}",
-                            uptime,
-                            if health == "ok" {
-                                "".to_string()
-                            } else {
-                                format!("| {} ", style(health).bold().red())
-                            },
-                            if max_retransmit_slot == 0 {
-                                "".to_string()
-                            } else {
-                                format!("| Max Slot: {} ", max_retransmit_slot)
-                            },
-                            processed_slot,
-                            confirmed_slot,
-                            finalized_slot,
-                            snapshot_slot_info
-                                .as_ref()
-                                .map(|snapshot_slot_info| snapshot_slot_info.full.to_string())
-                                .unwrap_or_else(|| '-'.to_string()),
-                            snapshot_slot_info
-                                .as_ref()
-                                .map(|snapshot_slot_info| snapshot_slot_info
-                                    .incremental
-                                    .map(|incremental| incremental.to_string()))
-                                .flatten()
-                                .unwrap_or_else(|| '-'.to_string()),
-                            transaction_count,
-                            identity_balance
-                        ));
-                        thread::sleep(refresh_interval);
-                    }
+                let stats = match get_validator_stats(&rpc_client, &identity) {
+                    Ok(stats) => stats,
                     Err(err) => {
-                        progress_bar
-                            .abandon_with_message(format!("RPC connection failure: {}", err));
+                        println!("RPC connection failure: {}", err);
                         break;
                     }
-                }
+                };
+                // The admin RPC only adds detail, so the panel is still drawn without it.  The
+                // last vote is left out when the replay stage didn't report it in time
+                let (last_voted_slot, buffered_packets_count) = runtime.block_on(async {
+                    let last_voted_slot =
+                        admin_client.fork_choice_diagnostics().await.ok().and_then(
+                            |fork_choice_diagnostics| fork_choice_diagnostics.last_voted_slot,
+                        );
+                    let buffered_packets_count = admin_client.buffered_packets_count().await.ok();
+                    (last_voted_slot, buffered_packets_count)
+                });
+
+                let now = Instant::now();
+                let tps = last_transaction_count.map(|(last_transaction_count, last_time)| {
+                    stats
+                        .transaction_count
+                        .saturating_sub(last_transaction_count) as f64
+                        / now.duration_since(last_time).as_secs_f64()
+                });
+                last_transaction_count = Some((stats.transaction_count, now));
+
+                let uptime = chrono::Duration::from_std(start_time.elapsed().unwrap()).unwrap();
+                let unknown = || "-".to_string();
+                let full_snapshot_slot = snapshot_slot_info
+                    .as_ref()
+                    .map(|snapshot_slot_info| snapshot_slot_info.full);
+                let incremental_snapshot_slot = snapshot_slot_info
+                    .as_ref()
+                    .and_then(|snapshot_slot_info| snapshot_slot_info.incremental);
+                let snapshot_age = incremental_snapshot_slot
+                    .or(full_snapshot_slot)
+                    .map(|snapshot_slot| stats.processed_slot.saturating_sub(snapshot_slot));
+
+                panel.draw(&[
+                    format!(
+                        "{} {:02}:{:02}:{:02} | {} {} | {} {}",
+                        style("Uptime:").bold(),
+                        uptime.num_hours(),
+                        uptime.num_minutes() % 60,
+                        uptime.num_seconds() % 60,
+                        style("Health:").bold(),
+                        if stats.health == "ok" {
+                            style(stats.health).green()
+                        } else {
+                            style(stats.health).bold().red()
+                        },
+                        style("Identity Balance:").bold(),
+                        stats.identity_balance,
+                    ),
+                    format!(
+                        "{} Processed {} | Confirmed {} | Finalized {} | Max Retransmit {}",
+                        style("Slot:").bold(),
+                        stats.processed_slot,
+                        stats.confirmed_slot,
+                        stats.finalized_slot,
+                        if stats.max_retransmit_slot == 0 {
+                            unknown()
+                        } else {
+                            stats.max_retransmit_slot.to_string()
+                        },
+                    ),
+                    format!(
+                        "{} {} (Last Voted Slot: {})",
+                        style("Vote Distance:").bold(),
+                        last_voted_slot
+                            .map(|last_voted_slot| stats
+                                .processed_slot
+                                .saturating_sub(last_voted_slot)
+                                .to_string())
+                            .unwrap_or_else(unknown),
+                        last_voted_slot
+                            .map(|last_voted_slot| last_voted_slot.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                    format!(
+                        "{} {} | {} {} | {} {}",
+                        style("TPS:").bold(),
+                        tps.map(|tps| format!("{:.0}", tps)).unwrap_or_else(unknown),
+                        style("Transactions:").bold(),
+                        stats.transaction_count,
+                        style("Buffered Packets:").bold(),
+                        buffered_packets_count
+                            .map(|buffered_packets_count| buffered_packets_count.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                    format!(
+                        "{} Full {} | Incremental {} | Age {} slots",
+                        style("Snapshot:").bold(),
+                        full_snapshot_slot
+                            .map(|slot| slot.to_string())
+                            .unwrap_or_else(unknown),
+                        incremental_snapshot_slot
+                            .map(|slot| slot.to_string())
+                            .unwrap_or_else(unknown),
+                        snapshot_age
+                            .map(|snapshot_age| snapshot_age.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                ]);
+                thread::sleep(refresh_interval);
             }
         }
     }
 }
 
+/// A block of lines that is redrawn in place on every refresh when stdout is a terminal
+struct Panel {
+    term: Term,
+    num_lines: usize,
+}
+
+impl Panel {
+    fn new() -> Self {
+        Self {
+            term: Term::stdout(),
+            num_lines: 0,
+        }
+    }
+
+    fn draw(&mut self, lines: &[String]) {
+        if self.term.is_term() {
+            let _ = self.term.clear_last_lines(self.num_lines);
+            self.num_lines = lines.len();
+        }
+        for line in lines {
+            let _ = self.term.write_line(line);
+        }
+        let _ = self.term.flush();
+    }
+}
+
 async fn wait_for_validator_startup(
     ledger_path: &Path,
     exit: &Arc<AtomicBool>,
     progress_bar: ProgressBar,
     refresh_interval: Duration,
-) -> Option<(SocketAddr, SystemTime)> {
+) -> Option<(
+    admin_rpc_service::gen_client::Client,
+    SocketAddr,
+    SystemTime,
+)> {
     let mut admin_client = None;
     loop {
         if exit.load(Ordering::Relaxed) {
@@ -218,7 +289,7 @@ async fn wait_for_validator_startup(
                 if start_progress == ValidatorStartProgress::Running {
                     let admin_client = admin_client.take().unwrap();
 
-                    match async move {
+                    match async {
                         let rpc_addr = admin_client.rpc_addr().await?;
                         let start_time = admin_client.start_time().await?;
                         Ok::<_, jsonrpc_core_client::RpcError>((rpc_addr, start_time))
@@ -226,14 +297,16 @@ async fn wait_for_validator_startup(
                     .await
                     {
                         Ok((None, _)) => progress_bar.set_message("RPC service not available"),
-                        Ok((Some(rpc_addr), start_time)) => return Some((rpc_addr, start_time)),
+                        Ok((Some(rpc_addr), start_time)) => {
+                            return Some((admin_client, rpc_addr, start_time))
+                        }
                         Err(err) => {
                             progress_bar
                                 .set_message(format!("Failed to get validator info: {}", err));
                         }
                     }
                 } else {
-                    progress_bar.set_message(format!("Validator startup: {:?}...", start_progress));
+                    progress_bar.set_message(format!("Validator startup: {}...", start_progress));
                 }
             }
             Err(err) => {
@@ -255,10 +328,20 @@ fn get_contact_info(rpc_client: &RpcClient, identity: &Pubkey) -> Option<RpcCont
         .find(|node| node.pubkey == identity.to_string())
 }
 
+struct ValidatorStats {
+    max_retransmit_slot: Slot,
+    processed_slot: Slot,
+    confirmed_slot: Slot,
+    finalized_slot: Slot,
+    transaction_count: u64,
+    identity_balance: Sol,
+    health: String,
+}
+
 fn get_validator_stats(
     rpc_client: &RpcClient,
     identity: &Pubkey,
-) -> client_error::Result<(Slot, Slot, Slot, Slot, u64, Sol, String)> {
+) -> client_error::Result<ValidatorStats> {
     let finalized_slot = rpc_client.get_slot_with_commitment(CommitmentConfig::finalized())?;
     let confirmed_slot = rpc_client.get_slot_with_commitment(CommitmentConfig::confirmed())?;
     let processed_slot = rpc_client.get_slot_with_commitment(CommitmentConfig::processed())?;
@@ -290,13 +373,13 @@ fn get_validator_stats(
         }
     };
 
-    Ok((
+    Ok(ValidatorStats {
         max_retransmit_slot,
         processed_slot,
         confirmed_slot,
         finalized_slot,
         transaction_count,
-        Sol(identity_balance),
+        identity_balance: Sol(identity_balance),
         health,
-    ))
+    })
 }
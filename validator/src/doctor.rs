@@ -0,0 +1,409 @@
+//! System tuning checks for a validator host.  Each failed check comes with the commands that
+//! remediate it, and most of them can be applied directly when running as root.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Minimum values of the kernel parameters a validator needs, see `solana-sys-tuner`
+const SYSCTL_MINIMUMS: &[(&str, u64)] = &[
+    ("net.core.rmem_default", 134_217_728),
+    ("net.core.rmem_max", 134_217_728),
+    ("net.core.wmem_default", 134_217_728),
+    ("net.core.wmem_max", 134_217_728),
+    ("vm.max_map_count", 1_000_000),
+];
+
+/// Rocks DB and the accounts files need many open file descriptors
+const MIN_NOFILE: u64 = 1_000_000;
+const NOFILE_LIMITS_PATH: &str = "/etc/security/limits.d/90-solana-nofiles.conf";
+const SYSCTL_CONF_PATH: &str = "/etc/sysctl.d/21-solana-validator.conf";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Remediation {
+    /// Set a kernel parameter now and persist it across reboots
+    Sysctl { name: String, value: u64 },
+    /// Write `contents` to each of `paths`, `pattern` is how they are shown to the user
+    WriteFiles {
+        pattern: String,
+        paths: Vec<PathBuf>,
+        contents: String,
+    },
+    /// Append `line` to the file at `path`, unless the file already has it
+    AppendLine { path: PathBuf, line: String },
+    /// A change that can't be applied automatically
+    Manual(String),
+}
+
+impl Remediation {
+    /// The shell commands that apply this remediation
+    pub fn commands(&self) -> Vec<String> {
+        match self {
+            Remediation::Sysctl { name, value } => vec![
+                format!("sudo sysctl -w {}={}", name, value),
+                format!(
+                    "echo '{}={}' | sudo tee -a {}",
+                    name, value, SYSCTL_CONF_PATH
+                ),
+            ],
+            Remediation::WriteFiles {
+                pattern, contents, ..
+            } => vec![format!(
+                "echo '{}' | sudo tee {}",
+                contents.trim_end(),
+                pattern
+            )],
+            Remediation::AppendLine { path, line } => vec![format!(
+                "grep -qxF '{line}' {path} || echo '{line}' | sudo tee -a {path}",
+                line = line,
+                path = path.display()
+            )],
+            Remediation::Manual(instructions) => vec![instructions.clone()],
+        }
+    }
+
+    pub fn can_apply(&self) -> bool {
+        !matches!(self, Remediation::Manual(_))
+    }
+
+    pub fn apply(&self) -> io::Result<()> {
+        match self {
+            Remediation::Sysctl { name, value } => {
+                fs::write(sysctl_path(name), value.to_string())?;
+                append_line(Path::new(SYSCTL_CONF_PATH), &format!("{}={}", name, value))
+            }
+            Remediation::WriteFiles {
+                paths, contents, ..
+            } => paths.iter().try_for_each(|path| fs::write(path, contents)),
+            Remediation::AppendLine { path, line } => append_line(path, line),
+            Remediation::Manual(instructions) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Must be applied manually: {}", instructions),
+            )),
+        }
+    }
+}
+
+/// Append `line` to the file at `path`, creating it if needed, unless the file already has it
+fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    let mut contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    if contents.lines().any(|existing| existing.trim() == line) {
+        return Ok(());
+    }
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(line);
+    contents.push('\n');
+    fs::write(path, contents)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckFailure {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+    pub remediation: Remediation,
+    /// What else it takes for the remediation to take effect
+    pub follow_up: Option<String>,
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.name, self.expected, self.actual
+        )
+    }
+}
+
+fn sysctl_path(name: &str) -> PathBuf {
+    Path::new("/proc/sys").join(name.replace('.', "/"))
+}
+
+fn check_sysctl(name: &str, minimum: u64, value: Option<u64>) -> Option<CheckFailure> {
+    let actual = match value {
+        Some(value) if value >= minimum => return None,
+        Some(value) => value.to_string(),
+        None => "unreadable".to_string(),
+    };
+    Some(CheckFailure {
+        name: name.to_string(),
+        expected: format!("at least {}", minimum),
+        actual,
+        remediation: Remediation::Sysctl {
+            name: name.to_string(),
+            value: minimum,
+        },
+        follow_up: None,
+    })
+}
+
+fn check_nofile(limit: u64) -> Option<CheckFailure> {
+    (limit < MIN_NOFILE).then(|| CheckFailure {
+        name: "open file descriptor limit".to_string(),
+        expected: format!("at least {}", MIN_NOFILE),
+        actual: limit.to_string(),
+        remediation: if cfg!(target_os = "linux") {
+            Remediation::AppendLine {
+                path: PathBuf::from(NOFILE_LIMITS_PATH),
+                line: format!("* - nofile {}", MIN_NOFILE),
+            }
+        } else {
+            Remediation::Manual(format!(
+                "sudo launchctl limit maxfiles {} {}",
+                MIN_NOFILE, MIN_NOFILE
+            ))
+        },
+        follow_up: cfg!(target_os = "linux").then(|| {
+            format!(
+                "the limit only applies to new login sessions, so log out and in again. A \
+                 validator run by systemd needs `LimitNOFILE={}` in the [Service] section of \
+                 its unit and `sudo systemctl daemon-reload` instead",
+                MIN_NOFILE
+            )
+        }),
+    })
+}
+
+fn check_governors(governors: &[(PathBuf, String)]) -> Option<CheckFailure> {
+    let paths: Vec<_> = governors
+        .iter()
+        .filter(|(_, governor)| governor != "performance")
+        .map(|(path, _)| path.clone())
+        .collect();
+    let actual = governors
+        .iter()
+        .find(|(_, governor)| governor != "performance")
+        .map(|(_, governor)| governor.clone())?;
+    Some(CheckFailure {
+        name: "CPU frequency governor".to_string(),
+        expected: "performance".to_string(),
+        actual: format!("{} on {} of {} CPUs", actual, paths.len(), governors.len()),
+        remediation: Remediation::WriteFiles {
+            pattern: "/sys/devices/system/cpu/cpu*/cpufreq/scaling_governor".to_string(),
+            paths,
+            contents: "performance\n".to_string(),
+        },
+        follow_up: None,
+    })
+}
+
+fn check_numa_nodes(num_nodes: usize) -> Option<CheckFailure> {
+    (num_nodes > 1).then(|| CheckFailure {
+        name: "NUMA nodes".to_string(),
+        expected: "1".to_string(),
+        actual: num_nodes.to_string(),
+        remediation: Remediation::Manual(
+            "start the validator under `numactl --interleave=all`, or disable NUMA in the BIOS"
+                .to_string(),
+        ),
+        follow_up: None,
+    })
+}
+
+#[cfg(unix)]
+fn nofile_limit() -> u64 {
+    let mut nofile = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut nofile) } != 0 {
+        return 0;
+    }
+    nofile.rlim_max as u64
+}
+
+#[cfg(not(unix))]
+fn nofile_limit() -> u64 {
+    u64::MAX
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn cpu_governors() -> Vec<(PathBuf, String)> {
+    let mut governors: Vec<_> = fs::read_dir("/sys/devices/system/cpu")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("cpu") && name[3..].chars().all(|c| c.is_ascii_digit())
+        })
+        .filter_map(|entry| {
+            let path = entry.path().join("cpufreq/scaling_governor");
+            let governor = read_trimmed(&path)?;
+            Some((path, governor))
+        })
+        .collect();
+    governors.sort();
+    governors
+}
+
+fn num_numa_nodes() -> usize {
+    fs::read_dir("/sys/devices/system/node")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("node") && name[4..].chars().all(|c| c.is_ascii_digit())
+        })
+        .count()
+}
+
+/// Run every check that applies to this platform, returning the failed ones
+pub fn run_checks() -> Vec<CheckFailure> {
+    let mut failures = vec![];
+    if cfg!(target_os = "linux") {
+        failures.extend(SYSCTL_MINIMUMS.iter().filter_map(|(name, minimum)| {
+            let value = read_trimmed(&sysctl_path(name)).and_then(|value| value.parse().ok());
+            check_sysctl(name, *minimum, value)
+        }));
+    }
+    failures.extend(check_nofile(nofile_limit()));
+    if cfg!(target_os = "linux") {
+        failures.extend(check_governors(&cpu_governors()));
+        failures.extend(check_numa_nodes(num_numa_nodes()));
+    }
+    failures
+}
+
+/// Print the failed checks with their remediation, applying them if `apply` is set.  Returns
+/// whether every check passed or was remediated
+pub fn doctor(apply: bool) -> bool {
+    let failures = run_checks();
+    if failures.is_empty() {
+        println!("All system checks passed");
+        return true;
+    }
+
+    let mut all_remediated = true;
+    for failure in &failures {
+        println!("✗ {}", failure);
+        if apply && failure.remediation.can_apply() {
+            match failure.remediation.apply() {
+                Ok(()) => {
+                    println!("  remediated");
+                    if let Some(follow_up) = &failure.follow_up {
+                        println!("  Note: {}", follow_up);
+                    }
+                    continue;
+                }
+                Err(err) => println!("  failed to remediate: {}", err),
+            }
+        }
+        all_remediated = false;
+        println!("  To fix, run:");
+        for command in failure.remediation.commands() {
+            println!("    {}", command);
+        }
+        if let Some(follow_up) = &failure.follow_up {
+            println!("  Then: {}", follow_up);
+        }
+    }
+    if apply && !all_remediated {
+        println!("Some checks could not be remediated automatically");
+    }
+    all_remediated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_sysctl() {
+        assert_eq!(check_sysctl("vm.max_map_count", 10, Some(10)), None);
+        let failure = check_sysctl("vm.max_map_count", 10, Some(9)).unwrap();
+        assert_eq!(failure.actual, "9");
+        assert_eq!(
+            failure.remediation.commands()[0],
+            "sudo sysctl -w vm.max_map_count=10"
+        );
+        assert_eq!(
+            check_sysctl("vm.max_map_count", 10, None).unwrap().actual,
+            "unreadable"
+        );
+        assert_eq!(
+            sysctl_path("net.core.rmem_max"),
+            PathBuf::from("/proc/sys/net/core/rmem_max")
+        );
+    }
+
+    #[test]
+    fn test_check_governors() {
+        assert_eq!(check_governors(&[]), None);
+        let governors = vec![
+            (PathBuf::from("cpu0"), "performance".to_string()),
+            (PathBuf::from("cpu1"), "powersave".to_string()),
+        ];
+        let failure = check_governors(&governors).unwrap();
+        assert_eq!(failure.actual, "powersave on 1 of 2 CPUs");
+        match failure.remediation {
+            Remediation::WriteFiles { paths, .. } => assert_eq!(paths, vec![PathBuf::from("cpu1")]),
+            remediation => panic!("unexpected remediation {:?}", remediation),
+        }
+    }
+
+    #[test]
+    fn test_check_nofile_and_numa() {
+        assert_eq!(check_nofile(MIN_NOFILE), None);
+        assert!(check_nofile(MIN_NOFILE - 1).is_some());
+        assert_eq!(check_numa_nodes(1), None);
+        let failure = check_numa_nodes(2).unwrap();
+        assert!(!failure.remediation.can_apply());
+    }
+
+    #[test]
+    fn test_apply_write_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scaling_governor");
+        let remediation = Remediation::WriteFiles {
+            pattern: path.display().to_string(),
+            paths: vec![path.clone()],
+            contents: "performance\n".to_string(),
+        };
+        remediation.apply().unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "performance\n");
+    }
+
+    #[test]
+    fn test_apply_append_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("90-solana-nofiles.conf");
+        fs::write(&path, "root - nofile 4096").unwrap();
+        let remediation = Remediation::AppendLine {
+            path: path.clone(),
+            line: "* - nofile 1000000".to_string(),
+        };
+        remediation.apply().unwrap();
+        // Applying it again doesn't repeat the line
+        remediation.apply().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "root - nofile 4096\n* - nofile 1000000\n"
+        );
+
+        let path = dir.path().join("missing.conf");
+        Remediation::AppendLine {
+            path: path.clone(),
+            line: "* - nofile 1000000".to_string(),
+        }
+        .apply()
+        .unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "* - nofile 1000000\n");
+    }
+}
@@ -19,6 +19,7 @@ use {
 pub mod admin_rpc_service;
 pub mod bootstrap;
 pub mod dashboard;
+pub mod doctor;
 
 #[cfg(unix)]
 fn redirect_stderr(filename: &str) {
@@ -8,28 +8,37 @@ use {
     log::*,
     rand::{seq::SliceRandom, thread_rng},
     solana_clap_utils::{
-        input_parsers::{keypair_of, keypairs_of, pubkey_of, value_of},
+        input_parsers::{pubkey_of, value_of},
         input_validators::{
             is_keypair, is_keypair_or_ask_keyword, is_parsable, is_pow2, is_pubkey,
-            is_pubkey_or_keypair, is_slot, is_valid_percentage,
+            is_pubkey_or_keypair, is_slot, is_valid_percentage, is_within_range,
         },
-        keypair::SKIP_SEED_PHRASE_VALIDATION_ARG,
+        keypair::{keypair_from_path, SKIP_SEED_PHRASE_VALIDATION_ARG},
     },
     solana_client::{
         rpc_client::RpcClient, rpc_config::RpcLeaderScheduleConfig,
         rpc_request::MAX_MULTIPLE_ACCOUNTS,
     },
     solana_core::{
+        banking_trace::DEFAULT_BANKING_TRACE_DIR_BYTE_LIMIT,
+        cluster_version_monitor_service::DEFAULT_NEWER_FEATURE_SET_WARN_STAKE_PERCENT,
+        fork_choice_diagnostics::ForkChoiceDiagnostics,
         ledger_cleanup_service::{DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS},
         tower_storage,
         tpu::DEFAULT_TPU_COALESCE_MS,
-        validator::{is_snapshot_config_valid, Validator, ValidatorConfig, ValidatorStartProgress},
+        validator::{
+            is_snapshot_config_valid, PohSpeedTestConfig, Validator, ValidatorConfig,
+            ValidatorStartProgress,
+        },
     },
     solana_gossip::{
-        cluster_info::{Node, VALIDATOR_PORT_RANGE},
+        cluster_info::{Node, NumReceiveSockets, VALIDATOR_PORT_RANGE},
         contact_info::ContactInfo,
     },
-    solana_ledger::blockstore_db::BlockstoreRecoveryMode,
+    solana_ledger::{
+        blockstore_db::BlockstoreRecoveryMode,
+        blockstore_processor::TransactionStatusOverflowPolicy,
+    },
     solana_perf::recycler::enable_recycler_warming,
     solana_poh::poh_service,
     solana_replica_lib::accountsdb_repl_server::AccountsDbReplServiceConfig,
@@ -44,9 +53,11 @@ use {
             AccountsIndexConfig,
         },
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
+        largest_accounts_index::DEFAULT_LARGEST_ACCOUNTS_INDEX_CAPACITY,
         snapshot_config::SnapshotConfig,
         snapshot_utils::{
-            self, ArchiveFormat, SnapshotVersion, DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
+            self, ArchiveFormat, SnapshotVerification, SnapshotVersion,
+            DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
             DEFAULT_INCREMENTAL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
             DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
@@ -54,7 +65,7 @@ use {
     },
     solana_sdk::{
         clock::{Slot, DEFAULT_S_PER_SLOT},
-        commitment_config::CommitmentConfig,
+        commitment_config::{CommitmentConfig, CommitmentLevel},
         hash::Hash,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
@@ -62,7 +73,7 @@ use {
     solana_send_transaction_service::send_transaction_service,
     solana_streamer::socket::SocketAddrSpace,
     solana_validator::{
-        admin_rpc_service, bootstrap, dashboard::Dashboard, ledger_lockfile, lock_ledger,
+        admin_rpc_service, bootstrap, dashboard::Dashboard, doctor, ledger_lockfile, lock_ledger,
         new_spinner_progress_bar, println_name_value, redirect_stderr_to_file,
     },
     std::{
@@ -80,6 +91,8 @@ use {
 
 #[cfg(not(target_env = "msvc"))]
 use jemallocator::Jemalloc;
+#[cfg(unix)]
+use solana_core::remote_signer::{self, RemoteSigner};
 
 #[cfg(not(target_env = "msvc"))]
 #[global_allocator]
@@ -110,6 +123,60 @@ fn monitor_validator(ledger_path: &Path) {
     dashboard.run(Duration::from_secs(2));
 }
 
+fn print_fork_choice_diagnostics(diagnostics: &ForkChoiceDiagnostics) {
+    println!("Root slot: {}", diagnostics.root);
+    println!("Heaviest slot: {}", diagnostics.heaviest_slot);
+    if let Some(slot) = diagnostics.heaviest_slot_on_same_voted_fork {
+        println!("Heaviest slot on same voted fork: {}", slot);
+    }
+    match diagnostics.last_voted_slot {
+        Some(slot) => println!("Last voted slot: {}", slot),
+        None => println!("Last voted slot: none"),
+    }
+    if let Some((slot, decision)) = &diagnostics.last_switch_threshold_check {
+        println!("Last switch threshold check: slot {}: {:?}", slot, decision);
+    }
+    if diagnostics.heaviest_fork_failures.is_empty() {
+        println!("Not voting reasons: none");
+    } else {
+        println!("Not voting reasons:");
+        for failure in &diagnostics.heaviest_fork_failures {
+            println!("  {:?}", failure);
+        }
+    }
+
+    println!("\nLockouts:");
+    println!(
+        "  {:>12}  {:>18}  {:>20}",
+        "Slot", "Confirmation Count", "Last Locked Out Slot"
+    );
+    for lockout in diagnostics.lockouts.iter().rev() {
+        println!(
+            "  {:>12}  {:>18}  {:>20}",
+            lockout.slot, lockout.confirmation_count, lockout.last_locked_out_slot
+        );
+    }
+
+    println!("\nFork weights:");
+    println!(
+        "  {:>12}  {:>20}  {:>20}  {:<44}",
+        "Slot", "Stake Voted At", "Stake Voted Subtree", "Hash"
+    );
+    for fork_weight in &diagnostics.fork_weights {
+        println!(
+            "  {:>12}  {:>20}  {:>20}  {:<44}{}",
+            fork_weight.slot,
+            fork_weight.stake_voted_at,
+            fork_weight.stake_voted_subtree,
+            fork_weight.hash,
+            fork_weight
+                .latest_invalid_ancestor
+                .map(|slot| format!(" (invalid ancestor: {})", slot))
+                .unwrap_or_default(),
+        );
+    }
+}
+
 fn wait_for_restart_window(
     ledger_path: &Path,
     identity: Option<Pubkey>,
@@ -411,11 +478,64 @@ fn get_cluster_shred_version(entrypoints: &[SocketAddr]) -> Option<u16> {
     None
 }
 
+/// Reads the keypair at `path`, exiting with the reason if it can't be, such as an incorrect
+/// keystore password
+fn read_keypair_arg(matches: &ArgMatches<'_>, path: &str, keypair_name: &str) -> Keypair {
+    keypair_from_path(matches, path, keypair_name, true).unwrap_or_else(|err| {
+        eprintln!("Unable to read the {} keypair: {}", keypair_name, err);
+        exit(1);
+    })
+}
+
+/// Connect to the signing service given by --remote-signer and return a signer for each of its keys
+#[cfg(unix)]
+fn remote_authorized_voters_of(matches: &ArgMatches<'_>) -> Vec<Arc<dyn Signer + Send + Sync>> {
+    let socket_path = match matches.value_of("remote_signer") {
+        Some(socket_path) => socket_path,
+        None => return Vec::new(),
+    };
+    let latency_budget = Duration::from_millis(value_t_or_exit!(
+        matches,
+        "remote_signer_latency_budget_ms",
+        u64
+    ));
+    let remote_signer = RemoteSigner::connect(socket_path, latency_budget).unwrap_or_else(|err| {
+        eprintln!(
+            "Unable to connect to the remote signer at {}: {}",
+            socket_path, err
+        );
+        exit(1);
+    });
+    Arc::new(remote_signer)
+        .signers()
+        .into_iter()
+        .map(|signer| Arc::new(signer) as Arc<dyn Signer + Send + Sync>)
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn remote_authorized_voters_of(matches: &ArgMatches<'_>) -> Vec<Arc<dyn Signer + Send + Sync>> {
+    if matches.is_present("remote_signer") {
+        eprintln!("--remote-signer is only supported on unix");
+        exit(1);
+    }
+    Vec::new()
+}
+
 pub fn main() {
     let default_dynamic_port_range =
         &format!("{}-{}", VALIDATOR_PORT_RANGE.0, VALIDATOR_PORT_RANGE.1);
     let default_genesis_archive_unpacked_size = &MAX_GENESIS_ARCHIVE_UNPACKED_SIZE.to_string();
+    let default_num_receive_sockets = NumReceiveSockets::default();
+    let default_tpu_receive_sockets = &default_num_receive_sockets.tpu.to_string();
+    let default_tvu_receive_sockets = &default_num_receive_sockets.tvu.to_string();
+    let default_tpu_forwards_receive_sockets =
+        &default_num_receive_sockets.tpu_forwards.to_string();
+    let default_tvu_forwards_receive_sockets =
+        &default_num_receive_sockets.tvu_forwards.to_string();
     let default_rpc_max_multiple_accounts = &MAX_MULTIPLE_ACCOUNTS.to_string();
+    let default_newer_feature_set_warn_stake_percent =
+        &DEFAULT_NEWER_FEATURE_SET_WARN_STAKE_PERCENT.to_string();
 
     let default_rpc_pubsub_max_active_subscriptions =
         PubSubConfig::default().max_active_subscriptions.to_string();
@@ -423,6 +543,12 @@ pub fn main() {
         PubSubConfig::default().queue_capacity_items.to_string();
     let default_rpc_pubsub_queue_capacity_bytes =
         PubSubConfig::default().queue_capacity_bytes.to_string();
+    let default_rpc_pubsub_max_subscriptions_per_connection = PubSubConfig::default()
+        .max_subscriptions_per_connection
+        .to_string();
+    let default_rpc_pubsub_max_connection_queue_bytes = PubSubConfig::default()
+        .max_connection_queue_bytes
+        .to_string();
     let default_send_transaction_service_config = send_transaction_service::Config::default();
     let default_rpc_send_transaction_retry_ms = default_send_transaction_service_config
         .retry_rate_ms
@@ -433,6 +559,8 @@ pub fn main() {
     let default_rpc_send_transaction_service_max_retries = default_send_transaction_service_config
         .service_max_retries
         .to_string();
+    let default_rpc_send_transaction_max_retry_interval_ms =
+        send_transaction_service::DEFAULT_MAX_RETRY_INTERVAL_MS.to_string();
     let default_rpc_threads = num_cpus::get().to_string();
     let default_accountsdb_repl_threads = num_cpus::get().to_string();
     let default_maximum_full_snapshot_archives_to_retain =
@@ -448,6 +576,12 @@ pub fn main() {
     let default_accounts_shrink_optimize_total_space =
         &DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE.to_string();
     let default_accounts_shrink_ratio = &DEFAULT_ACCOUNTS_SHRINK_RATIO.to_string();
+    let default_banking_trace_dir_byte_limit = &DEFAULT_BANKING_TRACE_DIR_BYTE_LIMIT.to_string();
+    #[cfg(unix)]
+    let default_remote_signer_latency_budget_ms =
+        &remote_signer::DEFAULT_LATENCY_BUDGET_MS.to_string();
+    #[cfg(not(unix))]
+    let default_remote_signer_latency_budget_ms = "0";
 
     let matches = App::new(crate_name!()).about(crate_description!())
         .version(solana_version::version!())
@@ -479,6 +613,26 @@ pub fn main() {
                        May be specified multiple times. \
                        [default: the --identity keypair]"),
         )
+        .arg(
+            Arg::with_name("remote_signer")
+                .long("remote-signer")
+                .value_name("SOCKET_PATH")
+                .takes_value(true)
+                .requires("vote_account")
+                .help("Sign votes for the authorized voters without an --authorized-voter \
+                       keypair with the signing service listening on this unix socket. \
+                       These votes are paid for by the authorized voter rather than the \
+                       identity, which still signs gossip messages"),
+        )
+        .arg(
+            Arg::with_name("remote_signer_latency_budget_ms")
+                .long("remote-signer-latency-budget-ms")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value(default_remote_signer_latency_budget_ms)
+                .help("Skip a vote that the remote signer doesn't sign within this time"),
+        )
         .arg(
             Arg::with_name("vote_account")
                 .long("vote-account")
@@ -635,6 +789,37 @@ pub fn main() {
                 .help("Include CPI inner instructions and logs in the \
                         historical transaction info stored"),
         )
+        .arg(
+            Arg::with_name("token_program_ids")
+                .long("token-program-id")
+                .value_name("PROGRAM ID")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_pubkey)
+                .requires("enable_rpc_transaction_history")
+                .help("Record pre and post token balances for the accounts of this SPL Token \
+                       compatible program, in addition to SPL Token. May be specified multiple times"),
+        )
+        .arg(
+            Arg::with_name("transaction_status_channel_capacity")
+                .long("transaction-status-channel-capacity")
+                .value_name("BATCHES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .default_value("10000")
+                .help("How many batches of transaction statuses may be queued for the \
+                       transaction history before the --transaction-status-overflow-policy applies"),
+        )
+        .arg(
+            Arg::with_name("transaction_status_overflow_policy")
+                .long("transaction-status-overflow-policy")
+                .value_name("POLICY")
+                .takes_value(true)
+                .possible_values(&["block", "drop", "spill"])
+                .default_value("spill")
+                .help("What to do with transaction statuses while the transaction history queue \
+                       is full: wait for room, drop them, or spill them to the ledger directory"),
+        )
         .arg(
             Arg::with_name("rpc_max_multiple_accounts")
                 .long("rpc-max-multiple-accounts")
@@ -781,6 +966,64 @@ pub fn main() {
                 .validator(solana_validator::port_range_validator)
                 .help("Range to use for dynamically assigned ports"),
         )
+        .arg(
+            Arg::with_name("tpu_receive_sockets")
+                .long("tpu-receive-sockets")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value(default_tpu_receive_sockets)
+                .validator(|value| is_within_range(value, 1, 256))
+                .help("Number of sockets bound to the TPU port, each received by its own \
+                       thread. The kernel spreads incoming packets over their receive queues"),
+        )
+        .arg(
+            Arg::with_name("tpu_forwards_receive_sockets")
+                .long("tpu-forwards-receive-sockets")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value(default_tpu_forwards_receive_sockets)
+                .validator(|value| is_within_range(value, 1, 256))
+                .help("Number of sockets bound to the TPU forwards port, each received by its \
+                       own thread"),
+        )
+        .arg(
+            Arg::with_name("tvu_receive_sockets")
+                .long("tvu-receive-sockets")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value(default_tvu_receive_sockets)
+                .validator(|value| is_within_range(value, 1, 256))
+                .help("Number of sockets bound to the TVU port, each received by its own \
+                       thread. The kernel spreads incoming shreds over their receive queues"),
+        )
+        .arg(
+            Arg::with_name("tvu_forwards_receive_sockets")
+                .long("tvu-forwards-receive-sockets")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value(default_tvu_forwards_receive_sockets)
+                .validator(|value| is_within_range(value, 1, 256))
+                .help("Number of sockets bound to the TVU forwards port, each received by its \
+                       own thread"),
+        )
+        .arg(
+            Arg::with_name("socket_recv_buffer_size")
+                .long("socket-recv-buffer-size")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Set the receive buffer size (SO_RCVBUF) of the validator's UDP sockets. \
+                       The kernel caps it at net.core.rmem_max [default: net.core.rmem_default]"),
+        )
+        .arg(
+            Arg::with_name("socket_send_buffer_size")
+                .long("socket-send-buffer-size")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Set the send buffer size (SO_SNDBUF) of the validator's UDP sockets. \
+                       The kernel caps it at net.core.wmem_max [default: net.core.wmem_default]"),
+        )
         .arg(
             Arg::with_name("maximum_local_snapshot_age")
                 .long("maximum-local-snapshot-age")
@@ -867,6 +1110,31 @@ pub fn main() {
                 .long("no-poh-speed-test")
                 .help("Skip the check for PoH speed."),
         )
+        .arg(
+            Arg::with_name("poh_speed_test_margin")
+                .long("poh-speed-test-margin")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value("0")
+                .help("Require PoH to hash this many percent faster than the target hash rate \
+                       in the PoH speed check"),
+        )
+        .arg(
+            Arg::with_name("poh_speed_test_target_hash_rate")
+                .long("poh-speed-test-target-hash-rate")
+                .value_name("HASHES_PER_SECOND")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Hash rate the PoH speed check tests against \
+                       [default: the rate of the cluster's hashes per tick]"),
+        )
+        .arg(
+            Arg::with_name("poh_speed_test_warn_only")
+                .long("poh-speed-test-warn-only")
+                .conflicts_with("no_poh_speed_test")
+                .help("Warn instead of refusing to start when PoH fails the speed check"),
+        )
         .arg(
             Arg::with_name("accounts-hash-interval-slots")
                 .long("accounts-hash-interval-slots")
@@ -954,6 +1222,17 @@ pub fn main() {
                        Sending the SIGUSR1 signal to the validator process will cause it \
                        to re-open the log file"),
         )
+        .arg(
+            Arg::with_name("log_format")
+                .long("log-format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Format of the log lines. \
+                       json writes one JSON object per line with the timestamp, level, \
+                       module, slot being processed if any, and message"),
+        )
         .arg(
             Arg::with_name("wait_for_supermajority")
                 .long("wait-for-supermajority")
@@ -1049,6 +1328,44 @@ pub fn main() {
                 .validator(is_parsable::<u64>)
                 .help("Milliseconds to wait in the TPU receiver for packet coalescing."),
         )
+        .arg(
+            Arg::with_name("enable_banking_trace")
+                .long("enable-banking-trace")
+                .takes_value(false)
+                .help("Record the packets entering banking stage around this node's leader \
+                       slots to the banking_trace directory in the ledger, for replaying \
+                       with solana-banking-bench"),
+        )
+        .arg(
+            Arg::with_name("banking_trace_dir_byte_limit")
+                .long("banking-trace-dir-byte-limit")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value(default_banking_trace_dir_byte_limit)
+                .help("Delete the oldest banking traces once they exceed this many bytes"),
+        )
+        .arg(
+            Arg::with_name("cost_model_read_lock_units")
+                .long("cost-model-read-lock-units")
+                .value_name("UNITS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value("0")
+                .help("Compute units to charge per account read lock when packing blocks, \
+                       so transactions reading many accounts also count against the block \
+                       cost limits"),
+        )
+        .arg(
+            Arg::with_name("newer_feature_set_warn_stake_percent")
+                .long("newer-feature-set-warn-stake-percent")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .validator(is_valid_percentage)
+                .default_value(default_newer_feature_set_warn_stake_percent)
+                .help("Warn once this percentage of the stake runs a newer release with a \
+                       different feature set, which may activate features this build lacks"),
+        )
         .arg(
             Arg::with_name("rocksdb_max_compaction_jitter")
                 .long("rocksdb-max-compaction-jitter-slots")
@@ -1073,6 +1390,16 @@ pub fn main() {
                 .validator(solana_net_utils::is_host)
                 .help("IP address to bind the RPC port [default: 127.0.0.1 if --private-rpc is present, otherwise use --bind-address]"),
         )
+        .arg(
+            Arg::with_name("metrics_prometheus_bind")
+                .long("metrics-prometheus-bind")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host_port)
+                .help("Serve the validator metrics in the Prometheus exposition format at \
+                       http://HOST:PORT/metrics, in addition to submitting them to \
+                       SOLANA_METRICS_CONFIG"),
+        )
         .arg(
             Arg::with_name("rpc_threads")
                 .long("rpc-threads")
@@ -1168,6 +1495,26 @@ pub fn main() {
                 .help("The maximum total size of notifications that RPC PubSub will store \
                        across all connections."),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_subscriptions_per_connection")
+                .long("rpc-pubsub-max-subscriptions-per-connection")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_subscriptions_per_connection)
+                .help("The maximum number of subscriptions that RPC PubSub will accept \
+                       from a single connection."),
+        )
+        .arg(
+            Arg::with_name("rpc_pubsub_max_connection_queue_bytes")
+                .long("rpc-pubsub-max-connection-queue-bytes")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_pubsub_max_connection_queue_bytes)
+                .help("The maximum total size of notifications waiting to be sent to a \
+                       single RPC PubSub connection. Slower connections are disconnected."),
+        )
         .arg(
             Arg::with_name("rpc_send_transaction_retry_ms")
                 .long("rpc-send-retry-ms")
@@ -1203,6 +1550,36 @@ pub fn main() {
                 .default_value(&default_rpc_send_transaction_service_max_retries)
                 .help("The maximum number of transaction broadcast retries, regardless of requested value."),
         )
+        .arg(
+            Arg::with_name("rpc_send_transaction_retry_commitment")
+                .long("rpc-send-retry-commitment")
+                .value_name("COMMITMENT_LEVEL")
+                .takes_value(true)
+                .possible_values(&["processed", "confirmed", "finalized"])
+                .default_value("processed")
+                .help("Stop retrying a transaction sent via rpc service once it has landed in a \
+                       bank of this commitment level."),
+        )
+        .arg(
+            Arg::with_name("rpc_send_transaction_retry_backoff")
+                .long("rpc-send-retry-backoff")
+                .value_name("POLICY")
+                .takes_value(true)
+                .possible_values(&["constant", "exponential"])
+                .default_value("constant")
+                .help("How the interval between retries of a transaction sent via rpc service \
+                       evolves. \"exponential\" doubles the interval after every retry."),
+        )
+        .arg(
+            Arg::with_name("rpc_send_transaction_max_retry_interval_ms")
+                .long("rpc-send-max-retry-interval-ms")
+                .value_name("MILLISECS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value(&default_rpc_send_transaction_max_retry_interval_ms)
+                .help("The maximum interval between retries of a transaction sent via rpc \
+                       service when backing off exponentially."),
+        )
         .arg(
             Arg::with_name("rpc_scan_and_fix_roots")
                 .long("rpc-scan-and-fix-roots")
@@ -1277,7 +1654,7 @@ pub fn main() {
             Arg::with_name("snapshot_archive_format")
                 .long("snapshot-archive-format")
                 .alias("snapshot-compression") // Legacy name used by Solana v1.5.x and older
-                .possible_values(&["bz2", "gzip", "zstd", "tar", "none"])
+                .possible_values(&["bz2", "gzip", "zstd", "zstd-seekable", "tar", "none"])
                 .default_value("zstd")
                 .value_name("ARCHIVE_TYPE")
                 .takes_value(true)
@@ -1345,6 +1722,17 @@ pub fn main() {
                 .value_name("NUM")
                 .help("Specify hashes per batch in PoH service"),
         )
+        .arg(
+            Arg::with_name("poh_tick_spin_threshold_us")
+                .hidden(true)
+                .long("experimental-poh-tick-spin-threshold-us")
+                .takes_value(true)
+                .value_name("MICROSECONDS")
+                .validator(is_parsable::<u64>)
+                .help("EXPERIMENTAL: While PoH waits to catch up to its ideal tick time, \
+                       yield the core until this many microseconds remain and busy wait \
+                       for the rest, instead of busy waiting the whole time"),
+        )
         .arg(
             Arg::with_name("account_indexes")
                 .long("account-index")
@@ -1384,6 +1772,20 @@ pub fn main() {
                 .help("Enables faster starting of validators by skipping shrink. \
                       This option is for use during testing."),
         )
+        .arg(
+            Arg::with_name("skip_startup_ledger_verification")
+                .long("skip-startup-ledger-verification")
+                .help("Skip recomputing the full accounts hash of the local snapshot at startup \
+                       when this node created the snapshot itself, eg. when restarting. \
+                       Downloaded snapshots are always verified"),
+        )
+        .arg(
+            Arg::with_name("skip_trusted_snapshot_verification")
+                .long("skip-trusted-snapshot-verification")
+                .requires("trusted_validators")
+                .help("Skip recomputing the full accounts hash of the local snapshot at startup \
+                       when a --trusted-validator advertises its hash"),
+        )
         .arg(
             Arg::with_name("accounts_index_memory_limit_mb")
                 .long("accounts-index-memory-limit-mb")
@@ -1408,6 +1810,31 @@ pub fn main() {
                 .takes_value(true)
                 .help("Number of passes to calculate the hash of all accounts"),
         )
+        .arg(
+            Arg::with_name("accounts_db_cache_limit_mb")
+                .long("accounts-db-cache-limit-mb")
+                .value_name("MEGABYTES")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("How much unflushed account data the accounts write cache may hold before \
+                       older slots are flushed to storage in the background"),
+        )
+        .arg(
+            Arg::with_name("accounts_db_preallocated_stores")
+                .long("accounts-db-preallocated-stores")
+                .value_name("COUNT")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("How many empty account storage files to keep allocated ahead of time, \
+                       so that storing accounts for new slots doesn't wait on the file system"),
+        )
+        .arg(
+            Arg::with_name("enable_largest_accounts_index")
+                .long("enable-largest-accounts-index")
+                .takes_value(false)
+                .help("Maintain an index of the largest rooted accounts as slots are rooted, so \
+                       that getLargestAccounts doesn't scan every account"),
+        )
         .arg(
             Arg::with_name("accounts_index_path")
                 .long("accounts-index-path")
@@ -1558,6 +1985,24 @@ pub fn main() {
                              currently running validator instance")
             )
         )
+        .subcommand(
+            SubCommand::with_name("doctor")
+            .about("Check the system tuning of this host and print the commands \
+                    that fix any failed check")
+            .arg(
+                Arg::with_name("apply")
+                    .long("apply")
+                    .takes_value(false)
+                    .help("Apply the fixes that can be applied automatically, requires root")
+            )
+            .after_help("Note: If this command exits with a non-zero status \
+                         then some checks failed and were not fixed")
+        )
+        .subcommand(
+            SubCommand::with_name("fork-choice")
+            .about("Display the validator's fork choice weights, tower lockouts and \
+                    the reasons for not voting on the heaviest fork")
+        )
         .subcommand(
             SubCommand::with_name("init")
             .about("Initialize the ledger directory then exit")
@@ -1591,7 +2036,18 @@ pub fn main() {
                 Arg::with_name("filter")
                     .takes_value(true)
                     .index(1)
-                    .help("New filter using the same format as the RUST_LOG environment variable")
+                    .help("New filter using the same format as the RUST_LOG environment variable, \
+                           for example solana=info,solana_runtime::cost_model=trace. \
+                           Prints the current filter if not provided")
+            )
+            .arg(
+                Arg::with_name("duration")
+                    .long("duration")
+                    .takes_value(true)
+                    .requires("filter")
+                    .validator(is_parsable::<u64>)
+                    .value_name("SECONDS")
+                    .help("Restore the current filter after this many seconds")
             )
             .after_help("Note: the new filter only applies to the currently running validator instance")
         )
@@ -1685,7 +2141,24 @@ pub fn main() {
                 _ => unreachable!(),
             }
         }
+        ("fork-choice", _) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let fork_choice_diagnostics = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.fork_choice_diagnostics().await })
+                .unwrap_or_else(|err| {
+                    println!("forkChoiceDiagnostics request failed: {}", err);
+                    exit(1);
+                });
+            print_fork_choice_diagnostics(&fork_choice_diagnostics);
+            return;
+        }
         ("init", _) => Operation::Initialize,
+        ("doctor", Some(subcommand_matches)) => {
+            if !doctor::doctor(subcommand_matches.is_present("apply")) {
+                exit(1);
+            }
+            return;
+        }
         ("exit", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
             let force = subcommand_matches.is_present("force");
@@ -1743,14 +2216,33 @@ pub fn main() {
             return;
         }
         ("set-log-filter", Some(subcommand_matches)) => {
-            let filter = value_t_or_exit!(subcommand_matches, "filter", String);
+            let filter = value_t!(subcommand_matches, "filter", String).ok();
+            let duration_secs = value_t!(subcommand_matches, "duration", u64).ok();
             let admin_client = admin_rpc_service::connect(&ledger_path);
-            admin_rpc_service::runtime()
-                .block_on(async move { admin_client.await?.set_log_filter(filter).await })
-                .unwrap_or_else(|err| {
-                    println!("set log filter failed: {}", err);
-                    exit(1);
-                });
+            match filter {
+                Some(filter) => {
+                    admin_rpc_service::runtime()
+                        .block_on(async move {
+                            admin_client
+                                .await?
+                                .set_log_filter(filter, duration_secs)
+                                .await
+                        })
+                        .unwrap_or_else(|err| {
+                            println!("set log filter failed: {}", err);
+                            exit(1);
+                        });
+                }
+                None => {
+                    let filter = admin_rpc_service::runtime()
+                        .block_on(async move { admin_client.await?.log_filter().await })
+                        .unwrap_or_else(|err| {
+                            println!("log filter query failed: {}", err);
+                            exit(1);
+                        });
+                    println!("{}", filter);
+                }
+            }
             return;
         }
         ("wait-for-restart-window", Some(subcommand_matches)) => {
@@ -1769,13 +2261,14 @@ pub fn main() {
         _ => unreachable!(),
     };
 
-    let identity_keypair = keypair_of(&matches, "identity").unwrap_or_else(|| {
-        clap::Error::with_description(
+    let identity_keypair = match matches.value_of("identity") {
+        Some(identity) => read_keypair_arg(&matches, identity, "identity"),
+        None => clap::Error::with_description(
             "The --identity <KEYPAIR> argument is required",
             clap::ErrorKind::ArgumentNotFound,
         )
-        .exit();
-    });
+        .exit(),
+    };
 
     let logfile = {
         let logfile = matches
@@ -1791,6 +2284,11 @@ pub fn main() {
         }
     };
     let use_progress_bar = logfile.is_none();
+    solana_logger::set_format(value_t_or_exit!(
+        matches,
+        "log_format",
+        solana_logger::LogFormat
+    ));
     let _logger_thread = redirect_stderr_to_file(logfile);
 
     info!("{} {}", crate_name!(), solana_version::version!());
@@ -1804,11 +2302,24 @@ pub fn main() {
 
     solana_core::validator::report_target_features();
 
-    let authorized_voter_keypairs = keypairs_of(&matches, "authorized_voter_keypairs")
-        .map(|keypairs| keypairs.into_iter().map(Arc::new).collect())
+    for failure in doctor::run_checks() {
+        warn!(
+            "System check failed, {}. Run `solana-validator doctor` for the fix",
+            failure
+        );
+    }
+
+    let authorized_voter_keypairs = matches
+        .values_of("authorized_voter_keypairs")
+        .map(|paths| {
+            paths
+                .map(|path| Arc::new(read_keypair_arg(&matches, path, "authorized voter")))
+                .collect()
+        })
         .unwrap_or_else(|| {
+            // Copied rather than read again, so that a keystore isn't unlocked twice
             vec![Arc::new(
-                keypair_of(&matches, "identity").expect("identity"),
+                Keypair::from_bytes(&identity_keypair.to_bytes()).expect("identity"),
             )]
         });
     let authorized_voter_keypairs = Arc::new(RwLock::new(authorized_voter_keypairs));
@@ -1827,6 +2338,8 @@ pub fn main() {
         ),
         incremental_snapshot_fetch: matches.is_present("incremental_snapshots")
             && !matches.is_present("no_incremental_snapshot_fetch"),
+        skip_trusted_snapshot_verification: matches
+            .is_present("skip_trusted_snapshot_verification"),
     };
 
     let private_rpc = matches.is_present("private_rpc");
@@ -2005,6 +2518,16 @@ pub fn main() {
     if let Some(passes) = value_t!(matches, "accounts_hash_num_passes", usize).ok() {
         accounts_db_config.hash_calc_num_passes = Some(passes);
     }
+    if let Some(limit_mb) = value_t!(matches, "accounts_db_cache_limit_mb", u64).ok() {
+        accounts_db_config.write_cache_limit_bytes = Some(limit_mb * 1024 * 1024);
+    }
+    if let Some(count) = value_t!(matches, "accounts_db_preallocated_stores", usize).ok() {
+        accounts_db_config.preallocated_store_count = Some(count);
+    }
+    if matches.is_present("enable_largest_accounts_index") {
+        accounts_db_config.largest_accounts_index_capacity =
+            Some(DEFAULT_LARGEST_ACCOUNTS_INDEX_CAPACITY);
+    }
     let accounts_db_config = Some(accounts_db_config);
 
     let accountsdb_repl_service_config = if matches.is_present("enable_accountsdb_repl") {
@@ -2106,6 +2629,16 @@ pub fn main() {
                 "rpc_pubsub_queue_capacity_bytes",
                 usize
             ),
+            max_subscriptions_per_connection: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_subscriptions_per_connection",
+                usize
+            ),
+            max_connection_queue_bytes: value_t_or_exit!(
+                matches,
+                "rpc_pubsub_max_connection_queue_bytes",
+                usize
+            ),
         },
         voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
@@ -2139,25 +2672,85 @@ pub fn main() {
                 "rpc_send_transaction_service_max_retries",
                 usize
             ),
+            retry_commitment: value_t_or_exit!(
+                matches,
+                "rpc_send_transaction_retry_commitment",
+                CommitmentLevel
+            ),
+            retry_backoff: match matches
+                .value_of("rpc_send_transaction_retry_backoff")
+                .unwrap()
+            {
+                "exponential" => send_transaction_service::RetryBackoff::Exponential {
+                    max_retry_interval_ms: value_t_or_exit!(
+                        matches,
+                        "rpc_send_transaction_max_retry_interval_ms",
+                        u64
+                    ),
+                },
+                _ => send_transaction_service::RetryBackoff::Constant,
+            },
         },
         no_poh_speed_test: matches.is_present("no_poh_speed_test"),
+        poh_speed_test: PohSpeedTestConfig {
+            margin_percent: value_t_or_exit!(matches, "poh_speed_test_margin", u64),
+            target_hash_rate: value_of(&matches, "poh_speed_test_target_hash_rate"),
+            warn_only: matches.is_present("poh_speed_test_warn_only"),
+        },
         poh_pinned_cpu_core: value_of(&matches, "poh_pinned_cpu_core")
             .unwrap_or(poh_service::DEFAULT_PINNED_CPU_CORE),
         poh_hashes_per_batch: value_of(&matches, "poh_hashes_per_batch")
             .unwrap_or(poh_service::DEFAULT_HASHES_PER_BATCH),
+        poh_tick_spin_threshold: value_of(&matches, "poh_tick_spin_threshold_us")
+            .map(Duration::from_micros),
         account_indexes,
         accounts_db_caching_enabled: !matches.is_present("no_accounts_db_caching"),
         accounts_db_test_hash_calculation: matches.is_present("accounts_db_test_hash_calculation"),
         accounts_db_config,
         accounts_db_skip_shrink: matches.is_present("accounts_db_skip_shrink"),
         accounts_db_use_index_hash_calculation: matches.is_present("accounts_db_index_hashing"),
+        snapshot_verification: if matches.is_present("skip_startup_ledger_verification") {
+            SnapshotVerification::Skip
+        } else {
+            SnapshotVerification::Full
+        },
         tpu_coalesce_ms,
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
         accounts_shrink_ratio,
         disable_epoch_boundary_optimization: matches
             .is_present("disable_epoch_boundary_optimization"),
+        banking_trace_dir_byte_limit: if matches.is_present("enable_banking_trace") {
+            Some(value_t_or_exit!(
+                matches,
+                "banking_trace_dir_byte_limit",
+                u64
+            ))
+        } else {
+            None
+        },
+        cost_model_read_lock_units: value_t_or_exit!(matches, "cost_model_read_lock_units", u64),
+        newer_feature_set_warn_stake_percent: value_t_or_exit!(
+            matches,
+            "newer_feature_set_warn_stake_percent",
+            u8
+        ),
+        remote_authorized_voters: remote_authorized_voters_of(&matches),
         ..ValidatorConfig::default()
     };
+    validator_config
+        .token_program_ids
+        .extend(values_t!(matches, "token_program_ids", Pubkey).unwrap_or_default());
+    validator_config.transaction_status_channel_capacity = Some(value_t_or_exit!(
+        matches,
+        "transaction_status_channel_capacity",
+        usize
+    ));
+    validator_config.transaction_status_overflow_policy =
+        match matches.value_of("transaction_status_overflow_policy") {
+            Some("drop") => TransactionStatusOverflowPolicy::Drop,
+            Some("spill") => TransactionStatusOverflowPolicy::SpillToDisk,
+            _ => TransactionStatusOverflowPolicy::Block,
+        };
 
     let vote_account = pubkey_of(&matches, "vote_account").unwrap_or_else(|| {
         if !validator_config.voting_disabled {
@@ -2253,6 +2846,7 @@ pub fn main() {
             "bz2" => ArchiveFormat::TarBzip2,
             "gzip" => ArchiveFormat::TarGzip,
             "zstd" => ArchiveFormat::TarZstd,
+            "zstd-seekable" => ArchiveFormat::TarZstdSeekable,
             "tar" | "none" => ArchiveFormat::Tar,
             _ => panic!("Archive format not recognized: {}", archive_format_str),
         }
@@ -2360,6 +2954,8 @@ pub fn main() {
             authorized_voter_keypairs: authorized_voter_keypairs.clone(),
             cluster_info: admin_service_cluster_info.clone(),
             tower_storage: validator_config.tower_storage.clone(),
+            fork_choice_diagnostics: validator_config.fork_choice_diagnostics.clone(),
+            test_validator_bank_forks: Arc::default(),
         },
     );
 
@@ -2420,12 +3016,28 @@ pub fn main() {
         .map(ContactInfo::new_gossip_entry_point)
         .collect::<Vec<_>>();
 
-    let mut node = Node::new_with_external_ip(
+    let num_receive_sockets = NumReceiveSockets {
+        tpu: value_t_or_exit!(matches, "tpu_receive_sockets", usize),
+        tpu_forwards: value_t_or_exit!(matches, "tpu_forwards_receive_sockets", usize),
+        tvu: value_t_or_exit!(matches, "tvu_receive_sockets", usize),
+        tvu_forwards: value_t_or_exit!(matches, "tvu_forwards_receive_sockets", usize),
+    };
+    let mut node = Node::new_with_external_ip_and_receive_sockets(
         &identity_keypair.pubkey(),
         &gossip_addr,
         dynamic_port_range,
         bind_address,
+        num_receive_sockets,
     );
+    node.sockets
+        .set_buffer_sizes(
+            value_t!(matches, "socket_recv_buffer_size", usize).ok(),
+            value_t!(matches, "socket_send_buffer_size", usize).ok(),
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to set the socket buffer sizes: {}", err);
+            exit(1);
+        });
 
     if restricted_repair_only_mode {
         let any = SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0)), 0);
@@ -2454,6 +3066,17 @@ pub fn main() {
 
     solana_metrics::set_host_id(identity_keypair.pubkey().to_string());
     solana_metrics::set_panic_hook("validator");
+    if let Some(metrics_prometheus_bind) = matches.value_of("metrics_prometheus_bind") {
+        let bind_addr = solana_net_utils::parse_host_port(metrics_prometheus_bind)
+            .expect("invalid metrics_prometheus_bind");
+        solana_metrics::prometheus::start_exporter(bind_addr).unwrap_or_else(|err| {
+            eprintln!(
+                "Unable to serve Prometheus metrics on {}: {}",
+                bind_addr, err
+            );
+            exit(1);
+        });
+    }
 
     solana_entry::entry::init_poh();
     snapshot_utils::remove_tmp_snapshot_archives(&snapshot_archives_dir);
@@ -2462,6 +3085,38 @@ pub fn main() {
 
     let should_check_duplicate_instance = !matches.is_present("no_duplicate_instance_check");
     if !cluster_entrypoints.is_empty() {
+        // Download fresh snapshots if every local one turns out to be corrupted while loading
+        validator_config.snapshot_archives_fetcher = Some({
+            let gossip_addr = node.info.gossip;
+            let gossip_socket = node.sockets.gossip.try_clone().unwrap();
+            let identity_keypair = identity_keypair.clone();
+            let ledger_path = ledger_path.clone();
+            let snapshot_archives_dir = snapshot_archives_dir.clone();
+            let authorized_voter_keypairs = authorized_voter_keypairs.clone();
+            let cluster_entrypoints = cluster_entrypoints.clone();
+            let rpc_bootstrap_config = rpc_bootstrap_config.clone();
+            let start_progress = start_progress.clone();
+            Arc::new(move |validator_config: &ValidatorConfig| {
+                bootstrap::fetch_snapshot_archives(
+                    &gossip_addr,
+                    &gossip_socket,
+                    &identity_keypair,
+                    &ledger_path,
+                    &snapshot_archives_dir,
+                    &vote_account,
+                    authorized_voter_keypairs.clone(),
+                    &cluster_entrypoints,
+                    validator_config,
+                    rpc_bootstrap_config.clone(),
+                    use_progress_bar,
+                    should_check_duplicate_instance,
+                    &start_progress,
+                    minimal_snapshot_download_speed,
+                    maximum_snapshot_download_abort,
+                    socket_addr_space,
+                )
+            })
+        });
         bootstrap::rpc_bootstrap(
             &node,
             &identity_keypair,
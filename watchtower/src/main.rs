@@ -1,7 +1,10 @@
 //! A command-line executable for monitoring the health of a cluster
 #![allow(clippy::integer_arithmetic)]
 
+mod rules;
+
 use {
+    crate::rules::{DelinquencyTracker, ValidatorRules},
     clap::{crate_description, crate_name, value_t, value_t_or_exit, App, Arg},
     log::*,
     solana_clap_utils::{
@@ -9,7 +12,11 @@ use {
         input_validators::{is_parsable, is_pubkey_or_keypair, is_url},
     },
     solana_cli_output::display::format_labeled_address,
-    solana_client::{client_error, rpc_client::RpcClient, rpc_response::RpcVoteAccountStatus},
+    solana_client::{
+        client_error,
+        rpc_client::RpcClient,
+        rpc_response::{RpcBlockProduction, RpcVoteAccountStatus},
+    },
     solana_metrics::{datapoint_error, datapoint_info},
     solana_notifier::Notifier,
     solana_sdk::{
@@ -34,6 +41,17 @@ struct Config {
     monitor_active_stake: bool,
     unhealthy_threshold: usize,
     validator_identity_pubkeys: Vec<Pubkey>,
+    validator_rules: ValidatorRules,
+}
+
+struct ClusterInfo {
+    transaction_count: u64,
+    recent_blockhash: Hash,
+    vote_accounts: RpcVoteAccountStatus,
+    validator_balances: HashMap<Pubkey, u64>,
+    // Only fetched when the rules that need them are enabled
+    slot: Option<u64>,
+    block_production: Option<RpcBlockProduction>,
 }
 
 fn get_config() -> Config {
@@ -56,7 +74,15 @@ fn get_config() -> Config {
         and a sending number owned by that account,
         define environment variable before running `solana-watchtower`:
 
-        export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'")
+        export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'
+
+        To post notifications as JSON to any other webhook:
+
+        export WEBHOOK=...
+
+        To trigger PagerDuty incidents, resolved once all checks pass again:
+
+        export PAGERDUTY_INTEGRATION_KEY=...")
         .arg({
             let arg = Arg::with_name("config_file")
                 .short("C")
@@ -113,6 +139,33 @@ fn get_config() -> Config {
                 .validator(is_parsable::<f64>)
                 .help("Alert when the validator identity balance is less than this amount of SOL")
         )
+        .arg(
+            Arg::with_name("delinquency_duration")
+                .long("delinquency-duration")
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("0")
+                .validator(is_parsable::<u64>)
+                .help("Alert when a validator identity has been delinquent for at least this long")
+        )
+        .arg(
+            Arg::with_name("max_skip_rate")
+                .long("max-skip-rate")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .validator(is_parsable::<f64>)
+                .help("Alert when a validator identity skips more than this percentage of its \
+                    leader slots in the current epoch")
+        )
+        .arg(
+            Arg::with_name("max_slots_behind")
+                .long("max-slots-behind")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Alert when the last vote of a validator identity is more than this many \
+                    slots behind the cluster")
+        )
         .arg(
             // Deprecated parameter, now always enabled
             Arg::with_name("no_duplicate_notifications")
@@ -158,6 +211,15 @@ fn get_config() -> Config {
 
     let monitor_active_stake = matches.is_present("monitor_active_stake");
     let ignore_http_bad_gateway = matches.is_present("ignore_http_bad_gateway");
+    let validator_rules = ValidatorRules {
+        delinquency_duration: Duration::from_secs(value_t_or_exit!(
+            matches,
+            "delinquency_duration",
+            u64
+        )),
+        max_skip_rate: value_t!(matches, "max_skip_rate", f64).ok(),
+        max_slots_behind: value_t!(matches, "max_slots_behind", u64).ok(),
+    };
 
     let config = Config {
         address_labels: config.address_labels,
@@ -168,6 +230,7 @@ fn get_config() -> Config {
         monitor_active_stake,
         unhealthy_threshold,
         validator_identity_pubkeys,
+        validator_rules,
     };
 
     info!("RPC URL: {}", config.json_rpc_url);
@@ -178,10 +241,7 @@ fn get_config() -> Config {
     config
 }
 
-fn get_cluster_info(
-    config: &Config,
-    rpc_client: &RpcClient,
-) -> client_error::Result<(u64, Hash, RpcVoteAccountStatus, HashMap<Pubkey, u64>)> {
+fn get_cluster_info(config: &Config, rpc_client: &RpcClient) -> client_error::Result<ClusterInfo> {
     let transaction_count = rpc_client.get_transaction_count()?;
     let recent_blockhash = rpc_client.get_latest_blockhash()?;
     let vote_accounts = rpc_client.get_vote_accounts()?;
@@ -194,12 +254,24 @@ fn get_cluster_info(
         );
     }
 
-    Ok((
+    let rules = &config.validator_rules;
+    let slot = match rules.max_slots_behind {
+        Some(_) => Some(rpc_client.get_slot()?),
+        None => None,
+    };
+    let block_production = match rules.max_skip_rate {
+        Some(_) => Some(rpc_client.get_block_production()?.value),
+        None => None,
+    };
+
+    Ok(ClusterInfo {
         transaction_count,
         recent_blockhash,
         vote_accounts,
         validator_balances,
-    ))
+        slot,
+        block_production,
+    })
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -215,10 +287,18 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let mut last_notification_msg = "".into();
     let mut num_consecutive_failures = 0;
     let mut last_success = Instant::now();
+    let mut delinquency_tracker = DelinquencyTracker::default();
 
     loop {
         let failure = match get_cluster_info(&config, &rpc_client) {
-            Ok((transaction_count, recent_blockhash, vote_accounts, validator_balances)) => {
+            Ok(ClusterInfo {
+                transaction_count,
+                recent_blockhash,
+                vote_accounts,
+                validator_balances,
+                slot,
+                block_production,
+            }) => {
                 info!("Current transaction count: {}", transaction_count);
                 info!("Recent blockhash: {}", recent_blockhash);
                 info!("Current validator count: {}", vote_accounts.current.len());
@@ -278,27 +358,75 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     ));
                 }
 
+                let rules = &config.validator_rules;
+                let now = Instant::now();
                 let mut validator_errors = vec![];
                 for validator_identity in config.validator_identity_pubkeys.iter() {
                     let formatted_validator_identity = format_labeled_address(
                         &validator_identity.to_string(),
                         &config.address_labels,
                     );
-                    if vote_accounts
-                        .delinquent
-                        .iter()
-                        .any(|vai| vai.node_pubkey == *validator_identity.to_string())
-                    {
-                        validator_errors
-                            .push(format!("{} delinquent", formatted_validator_identity));
-                    } else if !vote_accounts
+                    let current_vote_account = vote_accounts
                         .current
                         .iter()
-                        .any(|vai| vai.node_pubkey == *validator_identity.to_string())
-                    {
+                        .find(|vai| vai.node_pubkey == *validator_identity.to_string());
+                    let is_delinquent = vote_accounts
+                        .delinquent
+                        .iter()
+                        .any(|vai| vai.node_pubkey == *validator_identity.to_string());
+                    let delinquent_duration =
+                        delinquency_tracker.update(validator_identity, is_delinquent, now);
+                    if let Some(delinquent_duration) = delinquent_duration {
+                        if delinquent_duration >= rules.delinquency_duration {
+                            validator_errors
+                                .push(format!("{} delinquent", formatted_validator_identity));
+                        } else {
+                            info!(
+                                "{} delinquent for {}",
+                                formatted_validator_identity,
+                                humantime::format_duration(Duration::from_secs(
+                                    delinquent_duration.as_secs()
+                                ))
+                            );
+                        }
+                    } else if current_vote_account.is_none() {
                         validator_errors.push(format!("{} missing", formatted_validator_identity));
                     }
 
+                    if let (Some(slot), Some(vote_account)) = (slot, current_vote_account) {
+                        if let Some(slots_behind) =
+                            rules.check_slots_behind(slot, vote_account.last_vote)
+                        {
+                            failures.push((
+                                "behind",
+                                format!(
+                                    "{} is {} slots behind",
+                                    formatted_validator_identity, slots_behind
+                                ),
+                            ));
+                        }
+                    }
+
+                    if let Some((leader_slots, blocks_produced)) =
+                        block_production.as_ref().and_then(|block_production| {
+                            block_production
+                                .by_identity
+                                .get(&validator_identity.to_string())
+                        })
+                    {
+                        if let Some(skip_rate) =
+                            rules.check_skip_rate(*leader_slots, *blocks_produced)
+                        {
+                            failures.push((
+                                "skip-rate",
+                                format!(
+                                    "{} skipped {:.2}% of its leader slots",
+                                    formatted_validator_identity, skip_rate
+                                ),
+                            ));
+                        }
+                    }
+
                     if let Some(balance) = validator_balances.get(validator_identity) {
                         if *balance < config.minimum_validator_identity_balance {
                             failures.push((
@@ -370,7 +498,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     humantime::format_duration(alarm_duration)
                 );
                 info!("{}", all_clear_msg);
-                notifier.send(&format!("solana-watchtower: {}", all_clear_msg));
+                notifier.resolve(&format!("solana-watchtower: {}", all_clear_msg));
             }
             last_notification_msg = "".into();
             last_success = Instant::now();
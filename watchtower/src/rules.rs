@@ -0,0 +1,123 @@
+//! Alert rules for the monitored validators, on top of the fixed cluster sanity checks
+use {
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        time::{Duration, Instant},
+    },
+};
+
+// The skip rate of a validator with fewer leader slots this epoch is too noisy to alert on
+const MIN_LEADER_SLOTS_FOR_SKIP_RATE: usize = 10;
+
+#[derive(Debug, Default)]
+pub struct ValidatorRules {
+    /// Alert once a validator has been delinquent for this long, instead of on the first check
+    /// that finds it delinquent
+    pub delinquency_duration: Duration,
+    /// Alert when the percentage of a validator's leader slots this epoch that it skipped
+    /// exceeds this
+    pub max_skip_rate: Option<f64>,
+    /// Alert when a validator's last vote is more than this many slots behind the cluster
+    pub max_slots_behind: Option<u64>,
+}
+
+impl ValidatorRules {
+    pub fn check_skip_rate(&self, leader_slots: usize, blocks_produced: usize) -> Option<f64> {
+        let max_skip_rate = self.max_skip_rate?;
+        if leader_slots < MIN_LEADER_SLOTS_FOR_SKIP_RATE {
+            return None;
+        }
+        let skipped_slots = leader_slots.saturating_sub(blocks_produced);
+        let skip_rate = skipped_slots as f64 * 100. / leader_slots as f64;
+        (skip_rate > max_skip_rate).then(|| skip_rate)
+    }
+
+    pub fn check_slots_behind(&self, cluster_slot: u64, last_vote: u64) -> Option<u64> {
+        let slots_behind = cluster_slot.saturating_sub(last_vote);
+        (slots_behind > self.max_slots_behind?).then(|| slots_behind)
+    }
+}
+
+/// Tracks how long each monitored validator has been delinquent
+#[derive(Default)]
+pub struct DelinquencyTracker {
+    delinquent_since: HashMap<Pubkey, Instant>,
+}
+
+impl DelinquencyTracker {
+    /// Record whether `validator_identity` is delinquent at `now`, returning for how long it has
+    /// been delinquent if it is
+    pub fn update(
+        &mut self,
+        validator_identity: &Pubkey,
+        is_delinquent: bool,
+        now: Instant,
+    ) -> Option<Duration> {
+        if is_delinquent {
+            let delinquent_since = self
+                .delinquent_since
+                .entry(*validator_identity)
+                .or_insert(now);
+            Some(now.duration_since(*delinquent_since))
+        } else {
+            self.delinquent_since.remove(validator_identity);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_skip_rate() {
+        let rules = ValidatorRules {
+            max_skip_rate: Some(25.),
+            ..ValidatorRules::default()
+        };
+        assert_eq!(rules.check_skip_rate(100, 80), None);
+        assert_eq!(rules.check_skip_rate(100, 70), Some(30.));
+        // Too few leader slots
+        assert_eq!(rules.check_skip_rate(4, 0), None);
+        assert_eq!(ValidatorRules::default().check_skip_rate(100, 0), None);
+    }
+
+    #[test]
+    fn test_check_slots_behind() {
+        let rules = ValidatorRules {
+            max_slots_behind: Some(100),
+            ..ValidatorRules::default()
+        };
+        assert_eq!(rules.check_slots_behind(1_000, 950), None);
+        assert_eq!(rules.check_slots_behind(1_000, 850), Some(150));
+        assert_eq!(rules.check_slots_behind(850, 1_000), None);
+        assert_eq!(ValidatorRules::default().check_slots_behind(1_000, 0), None);
+    }
+
+    #[test]
+    fn test_delinquency_tracker() {
+        let mut tracker = DelinquencyTracker::default();
+        let validator_identity = Pubkey::new_unique();
+        let start = Instant::now();
+
+        assert_eq!(tracker.update(&validator_identity, false, start), None);
+        assert_eq!(
+            tracker.update(&validator_identity, true, start),
+            Some(Duration::default())
+        );
+        assert_eq!(
+            tracker.update(&validator_identity, true, start + Duration::from_secs(30)),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            tracker.update(&validator_identity, false, start + Duration::from_secs(60)),
+            None
+        );
+        assert_eq!(
+            tracker.update(&validator_identity, true, start + Duration::from_secs(90)),
+            Some(Duration::default())
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Twisted ElGamal encryption over the Ristretto group.
+//!
+//! A ciphertext consists of a Pedersen commitment `C = x*G + r*H` to the
+//! encrypted amount together with a decryption handle `D = r*P`, where the
+//! public key is `P = s^-1 * H`. Since the commitment is an ordinary Pedersen
+//! commitment, proofs about the encrypted amount can be stated against `C`.
+use {
+    crate::{
+        encryption::pedersen::{Pedersen, PedersenCommitment, PedersenOpening, G, H},
+        errors::ProofError,
+    },
+    curve25519_dalek::{
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+        traits::Identity,
+    },
+    rand::rngs::OsRng,
+    std::{collections::HashMap, convert::TryInto},
+};
+
+/// Number of bits recovered by each step of the discrete log search.
+const DISCRETE_LOG_STEP_BITS: u32 = 16;
+
+lazy_static::lazy_static! {
+    /// Table of `j*G` for `j < 2^16`, used to decrypt amounts that fit in a `u32`.
+    static ref DISCRETE_LOG_TABLE: HashMap<[u8; 32], u32> = {
+        let mut table = HashMap::with_capacity(1 << DISCRETE_LOG_STEP_BITS);
+        let mut point = RistrettoPoint::identity();
+        for j in 0..(1u32 << DISCRETE_LOG_STEP_BITS) {
+            table.insert(point.compress().to_bytes(), j);
+            point += *G;
+        }
+        table
+    };
+}
+
+/// Algorithm handle for the twisted ElGamal encryption scheme.
+pub struct ElGamal;
+impl ElGamal {
+    /// Encrypts an amount under a public key with a fresh opening.
+    pub fn encrypt<T: Into<Scalar>>(pubkey: &ElGamalPubkey, amount: T) -> ElGamalCiphertext {
+        let opening = PedersenOpening::new_rand();
+        Self::encrypt_with(pubkey, amount, &opening)
+    }
+
+    /// Encrypts an amount under a public key using a caller-supplied opening.
+    pub fn encrypt_with<T: Into<Scalar>>(
+        pubkey: &ElGamalPubkey,
+        amount: T,
+        opening: &PedersenOpening,
+    ) -> ElGamalCiphertext {
+        let commitment = Pedersen::with(amount, opening);
+        let handle = DecryptHandle(opening.get_scalar() * pubkey.get_point());
+        ElGamalCiphertext { commitment, handle }
+    }
+
+    /// Decrypts a ciphertext to the group element `x*G`.
+    pub fn decrypt(secret: &ElGamalSecretKey, ciphertext: &ElGamalCiphertext) -> RistrettoPoint {
+        ciphertext.commitment.get_point() - secret.get_scalar() * ciphertext.handle.get_point()
+    }
+
+    /// Decrypts a ciphertext whose amount is known to fit in a `u32`.
+    pub fn decrypt_u32(
+        secret: &ElGamalSecretKey,
+        ciphertext: &ElGamalCiphertext,
+    ) -> Result<u32, ProofError> {
+        let target = Self::decrypt(secret, ciphertext);
+        let giant_step = Scalar::from(1u64 << DISCRETE_LOG_STEP_BITS) * *G;
+
+        let mut point = target;
+        for i in 0..(1u32 << DISCRETE_LOG_STEP_BITS) {
+            if let Some(j) = DISCRETE_LOG_TABLE.get(&point.compress().to_bytes()) {
+                return Ok((i << DISCRETE_LOG_STEP_BITS) | j);
+            }
+            point -= giant_step;
+        }
+        Err(ProofError::DecryptionError)
+    }
+}
+
+/// An ElGamal keypair.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElGamalKeypair {
+    pub public: ElGamalPubkey,
+    pub secret: ElGamalSecretKey,
+}
+impl ElGamalKeypair {
+    /// Generates a fresh keypair.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new_rand() -> Self {
+        let mut s = Scalar::random(&mut OsRng);
+        while s == Scalar::zero() {
+            s = Scalar::random(&mut OsRng);
+        }
+        Self {
+            public: ElGamalPubkey(s.invert() * *H),
+            secret: ElGamalSecretKey(s),
+        }
+    }
+}
+
+/// Public key for the ElGamal encryption scheme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ElGamalPubkey(RistrettoPoint);
+impl ElGamalPubkey {
+    pub fn get_point(&self) -> &RistrettoPoint {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.compress().to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ElGamalPubkey, ProofError> {
+        if bytes.len() != 32 {
+            return Err(ProofError::FormatError);
+        }
+        CompressedRistretto::from_slice(bytes)
+            .decompress()
+            .map(ElGamalPubkey)
+            .ok_or(ProofError::FormatError)
+    }
+}
+
+/// Secret key for the ElGamal encryption scheme.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ElGamalSecretKey(Scalar);
+impl ElGamalSecretKey {
+    pub fn get_scalar(&self) -> &Scalar {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<ElGamalSecretKey> {
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        Scalar::from_canonical_bytes(bytes).map(ElGamalSecretKey)
+    }
+}
+
+/// Decryption handle `r*P` of an ElGamal ciphertext.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecryptHandle(RistrettoPoint);
+impl DecryptHandle {
+    pub fn get_point(&self) -> &RistrettoPoint {
+        &self.0
+    }
+}
+
+/// Ciphertext for the ElGamal encryption scheme.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ElGamalCiphertext {
+    pub commitment: PedersenCommitment,
+    pub handle: DecryptHandle,
+}
+impl ElGamalCiphertext {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.commitment.to_bytes());
+        bytes[32..].copy_from_slice(&self.handle.0.compress().to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ElGamalCiphertext, ProofError> {
+        if bytes.len() != 64 {
+            return Err(ProofError::FormatError);
+        }
+        let commitment = PedersenCommitment::from_bytes(&bytes[..32])?;
+        let handle = CompressedRistretto::from_slice(&bytes[32..])
+            .decompress()
+            .map(DecryptHandle)
+            .ok_or(ProofError::FormatError)?;
+        Ok(ElGamalCiphertext { commitment, handle })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_correctness() {
+        let ElGamalKeypair { public, secret } = ElGamalKeypair::new_rand();
+        let amount: u32 = 57;
+        let ciphertext = ElGamal::encrypt(&public, amount);
+
+        assert_eq!(
+            ElGamal::decrypt(&secret, &ciphertext),
+            Scalar::from(amount) * *G
+        );
+        assert_eq!(ElGamal::decrypt_u32(&secret, &ciphertext).unwrap(), amount);
+    }
+
+    #[test]
+    fn test_decrypt_u32_large_amount() {
+        let ElGamalKeypair { public, secret } = ElGamalKeypair::new_rand();
+        let amount: u32 = 3_000_000;
+        let ciphertext = ElGamal::encrypt(&public, amount);
+        assert_eq!(ElGamal::decrypt_u32(&secret, &ciphertext).unwrap(), amount);
+    }
+
+    #[test]
+    fn test_decrypt_wrong_key() {
+        let ElGamalKeypair { public, .. } = ElGamalKeypair::new_rand();
+        let ElGamalKeypair { secret, .. } = ElGamalKeypair::new_rand();
+        let ciphertext = ElGamal::encrypt(&public, 10_u64);
+        assert_ne!(
+            ElGamal::decrypt(&secret, &ciphertext),
+            Scalar::from(10_u64) * *G
+        );
+    }
+
+    #[test]
+    fn test_ciphertext_bytes() {
+        let ElGamalKeypair { public, .. } = ElGamalKeypair::new_rand();
+        let ciphertext = ElGamal::encrypt(&public, 77_u64);
+        assert_eq!(
+            ElGamalCiphertext::from_bytes(&ciphertext.to_bytes()).unwrap(),
+            ciphertext
+        );
+        assert_eq!(
+            ElGamalPubkey::from_bytes(&public.to_bytes()).unwrap(),
+            public
+        );
+    }
+}
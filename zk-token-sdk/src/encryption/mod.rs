@@ -0,0 +1,3 @@
+//! Encryption primitives used by the zk-token proofs.
+pub mod elgamal;
+pub mod pedersen;
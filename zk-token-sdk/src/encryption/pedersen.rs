@@ -0,0 +1,118 @@
+//! Pedersen commitments over the Ristretto group.
+use {
+    crate::errors::ProofError,
+    curve25519_dalek::{
+        constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT},
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+        traits::MultiscalarMul,
+    },
+    rand::rngs::OsRng,
+    sha3::Sha3_512,
+    std::convert::TryInto,
+};
+
+lazy_static::lazy_static! {
+    /// Pedersen base point for encoding messages to be committed.
+    pub static ref G: RistrettoPoint = RISTRETTO_BASEPOINT_POINT;
+    /// Pedersen base point for encoding the commitment openings.
+    pub static ref H: RistrettoPoint =
+        RistrettoPoint::hash_from_bytes::<Sha3_512>(RISTRETTO_BASEPOINT_COMPRESSED.as_bytes());
+}
+
+/// Algorithm handle for the Pedersen commitment scheme.
+pub struct Pedersen;
+impl Pedersen {
+    /// Commits to a value with a freshly sampled opening.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<T: Into<Scalar>>(amount: T) -> (PedersenCommitment, PedersenOpening) {
+        let opening = PedersenOpening::new_rand();
+        let commitment = Pedersen::with(amount, &opening);
+        (commitment, opening)
+    }
+
+    /// Commits to a value using a caller-supplied opening.
+    pub fn with<T: Into<Scalar>>(amount: T, opening: &PedersenOpening) -> PedersenCommitment {
+        let x: Scalar = amount.into();
+        let r = opening.get_scalar();
+        PedersenCommitment(RistrettoPoint::multiscalar_mul(&[x, *r], &[*G, *H]))
+    }
+}
+
+/// Opening (randomness) of a Pedersen commitment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PedersenOpening(pub(crate) Scalar);
+impl PedersenOpening {
+    pub fn new_rand() -> Self {
+        PedersenOpening(Scalar::random(&mut OsRng))
+    }
+
+    pub fn get_scalar(&self) -> &Scalar {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<PedersenOpening> {
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        Scalar::from_canonical_bytes(bytes).map(PedersenOpening)
+    }
+}
+
+/// A Pedersen commitment `x*G + r*H`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PedersenCommitment(pub(crate) RistrettoPoint);
+impl PedersenCommitment {
+    pub fn get_point(&self) -> &RistrettoPoint {
+        &self.0
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.compress().to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<PedersenCommitment, ProofError> {
+        if bytes.len() != 32 {
+            return Err(ProofError::FormatError);
+        }
+        CompressedRistretto::from_slice(bytes)
+            .decompress()
+            .map(PedersenCommitment)
+            .ok_or(ProofError::FormatError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pedersen_homomorphic_addition() {
+        let (comm_0, open_0) = Pedersen::new(77_u64);
+        let (comm_1, open_1) = Pedersen::new(57_u64);
+        let open_sum = PedersenOpening(open_0.0 + open_1.0);
+
+        assert_eq!(Pedersen::with(134_u64, &open_sum).0, comm_0.0 + comm_1.0);
+    }
+
+    #[test]
+    fn test_pedersen_bytes() {
+        let (comm, open) = Pedersen::new(5_u64);
+        assert_eq!(
+            PedersenCommitment::from_bytes(&comm.to_bytes()).unwrap(),
+            comm
+        );
+        assert_eq!(PedersenOpening::from_bytes(&open.to_bytes()).unwrap(), open);
+        assert!(PedersenCommitment::from_bytes(&[0u8; 31]).is_err());
+    }
+
+    #[test]
+    fn test_pedersen_base_reference_vector() {
+        assert_eq!(
+            hex::encode(H.compress().as_bytes()),
+            "8c9240b456a9e6dc65c377a1048d745f94a08cdb7f44cbcd7b46f34048871134"
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Errors related to proving and verifying proofs.
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub enum ProofError {
+    #[error("proof verification failed")]
+    VerificationError,
+    #[error("malformed proof")]
+    FormatError,
+    #[error("failed to decrypt ciphertext")]
+    DecryptionError,
+    #[error("invalid proof data")]
+    InvalidProofData,
+}
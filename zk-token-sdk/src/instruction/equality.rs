@@ -0,0 +1,67 @@
+use crate::{
+    encryption::{
+        elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+        pedersen::{PedersenCommitment, PedersenOpening},
+    },
+    errors::ProofError,
+    instruction::ZkProofData,
+    sigma_proofs::equality_proof::{CtxtCommEqualityProof, CTXT_COMM_EQUALITY_PROOF_LEN},
+};
+
+const CTXT_COMM_EQUALITY_PROOF_DATA_LEN: usize = 32 + 64 + 32 + CTXT_COMM_EQUALITY_PROOF_LEN;
+
+/// Proof that an ElGamal ciphertext and a Pedersen commitment encode the same
+/// amount.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CtxtCommEqualityProofData {
+    pub pubkey: ElGamalPubkey,
+    pub ciphertext: ElGamalCiphertext,
+    pub commitment: PedersenCommitment,
+    pub proof: CtxtCommEqualityProof,
+}
+
+impl CtxtCommEqualityProofData {
+    pub fn new(
+        keypair: &ElGamalKeypair,
+        ciphertext: ElGamalCiphertext,
+        amount: u64,
+        commitment: PedersenCommitment,
+        opening: &PedersenOpening,
+    ) -> Self {
+        let proof = CtxtCommEqualityProof::new(keypair, &ciphertext, amount, opening, &commitment);
+        Self {
+            pubkey: keypair.public,
+            ciphertext,
+            commitment,
+            proof,
+        }
+    }
+}
+
+impl ZkProofData for CtxtCommEqualityProofData {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(CTXT_COMM_EQUALITY_PROOF_DATA_LEN);
+        buf.extend_from_slice(&self.pubkey.to_bytes());
+        buf.extend_from_slice(&self.ciphertext.to_bytes());
+        buf.extend_from_slice(&self.commitment.to_bytes());
+        buf.extend_from_slice(&self.proof.to_bytes());
+        buf
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != CTXT_COMM_EQUALITY_PROOF_DATA_LEN {
+            return Err(ProofError::InvalidProofData);
+        }
+        Ok(Self {
+            pubkey: ElGamalPubkey::from_bytes(&bytes[..32])?,
+            ciphertext: ElGamalCiphertext::from_bytes(&bytes[32..96])?,
+            commitment: PedersenCommitment::from_bytes(&bytes[96..128])?,
+            proof: CtxtCommEqualityProof::from_bytes(&bytes[128..])?,
+        })
+    }
+
+    fn verify(&self) -> Result<(), ProofError> {
+        self.proof
+            .verify(&self.pubkey, &self.ciphertext, &self.commitment)
+    }
+}
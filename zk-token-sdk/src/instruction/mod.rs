@@ -0,0 +1,17 @@
+//! Proof data carried by instructions to the ZkTokenProof program.
+mod equality;
+mod range_proof;
+
+pub use {equality::CtxtCommEqualityProofData, range_proof::RangeProof64Data};
+
+use crate::errors::ProofError;
+
+/// Self-contained proof data that can be serialized into an instruction and
+/// verified without access to any accounts.
+pub trait ZkProofData: Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError>;
+
+    fn verify(&self) -> Result<(), ProofError>;
+}
@@ -0,0 +1,43 @@
+use crate::{
+    encryption::pedersen::{PedersenCommitment, PedersenOpening},
+    errors::ProofError,
+    instruction::ZkProofData,
+    range_proof::{RangeProof, RANGE_PROOF_LEN},
+};
+
+/// Proof that a Pedersen commitment encodes a 64-bit value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeProof64Data {
+    pub commitment: PedersenCommitment,
+    pub proof: RangeProof,
+}
+
+impl RangeProof64Data {
+    pub fn new(commitment: PedersenCommitment, amount: u64, opening: &PedersenOpening) -> Self {
+        let proof = RangeProof::new(amount, &commitment, opening);
+        Self { commitment, proof }
+    }
+}
+
+impl ZkProofData for RangeProof64Data {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(32 + RANGE_PROOF_LEN);
+        buf.extend_from_slice(&self.commitment.to_bytes());
+        buf.extend_from_slice(&self.proof.to_bytes());
+        buf
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != 32 + RANGE_PROOF_LEN {
+            return Err(ProofError::InvalidProofData);
+        }
+        Ok(Self {
+            commitment: PedersenCommitment::from_bytes(&bytes[..32])?,
+            proof: RangeProof::from_bytes(&bytes[32..])?,
+        })
+    }
+
+    fn verify(&self) -> Result<(), ProofError> {
+        self.proof.verify(&self.commitment)
+    }
+}
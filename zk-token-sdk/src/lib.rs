@@ -0,0 +1,17 @@
+#![allow(clippy::integer_arithmetic, clippy::op_ref)]
+
+//! Cryptographic primitives and proof data for the Solana zk-token program.
+//!
+//! The encryption scheme is a twisted variant of ElGamal over the Ristretto
+//! group whose ciphertexts carry a Pedersen commitment of the encrypted amount.
+//! This lets range proofs and equality proofs be expressed directly against the
+//! commitment component of a ciphertext.
+
+pub mod encryption;
+pub mod errors;
+pub mod instruction;
+pub mod range_proof;
+pub mod sigma_proofs;
+mod transcript;
+pub mod zk_token_proof_instruction;
+pub mod zk_token_proof_program;
@@ -0,0 +1,88 @@
+//! Vector generators for the range proof.
+use {
+    crate::range_proof::RANGE_PROOF_BIT_LENGTH,
+    curve25519_dalek::ristretto::RistrettoPoint,
+    sha3::{
+        digest::{ExtendableOutput, Update, XofReader},
+        Shake256,
+    },
+};
+
+lazy_static::lazy_static! {
+    pub static ref RANGE_PROOF_GENS: RangeProofGens = RangeProofGens::new(RANGE_PROOF_BIT_LENGTH);
+}
+
+/// Independent generators `G_i` and `H_i` committing to the bit vectors of a
+/// range proof.  These are the generators of the first party of the dalek
+/// bulletproofs, so that proofs are interchangeable with that implementation.
+#[allow(non_snake_case)]
+pub struct RangeProofGens {
+    pub G_vec: Vec<RistrettoPoint>,
+    pub H_vec: Vec<RistrettoPoint>,
+}
+
+impl RangeProofGens {
+    fn new(n: usize) -> Self {
+        Self {
+            G_vec: generators_chain(b"G", n),
+            H_vec: generators_chain(b"H", n),
+        }
+    }
+}
+
+/// The first `n` points squeezed out of SHAKE256 over `label` and the party
+/// index, which is always zero for a single value.
+fn generators_chain(label: &[u8], n: usize) -> Vec<RistrettoPoint> {
+    let mut shake = Shake256::default();
+    shake.update(b"GeneratorsChain");
+    shake.update(label);
+    shake.update(0u32.to_le_bytes());
+    let mut reader = shake.finalize_xof();
+    (0..n)
+        .map(|_| {
+            let mut uniform_bytes = [0u8; 64];
+            reader.read(&mut uniform_bytes);
+            RistrettoPoint::from_uniform_bytes(&uniform_bytes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::HashSet};
+
+    #[test]
+    fn test_generators_are_distinct() {
+        let gens = &*RANGE_PROOF_GENS;
+        let points: HashSet<_> = gens
+            .G_vec
+            .iter()
+            .chain(&gens.H_vec)
+            .map(|p| p.compress().to_bytes())
+            .collect();
+        assert_eq!(points.len(), 2 * RANGE_PROOF_BIT_LENGTH);
+    }
+
+    #[test]
+    fn test_generators_reference_vectors() {
+        // the generators of `BulletproofGens::new(64, 1)` in the dalek bulletproofs
+        let gens = &*RANGE_PROOF_GENS;
+        let compressed = |point: &RistrettoPoint| hex::encode(point.compress().as_bytes());
+        assert_eq!(
+            compressed(&gens.G_vec[0]),
+            "fc3b25801422672a6a8d3adb5d8457d4301fe92324b4fc56ae934c8713ddfe2d"
+        );
+        assert_eq!(
+            compressed(&gens.H_vec[0]),
+            "ba698f6dd08c501e32b55d2ee7259f6019d629fa2ba4d7039c5de157cba4df73"
+        );
+        assert_eq!(
+            compressed(&gens.G_vec[RANGE_PROOF_BIT_LENGTH - 1]),
+            "2878518757fc0f2ae3b991b499f9fdcd1a2d483b663c128b9183556a7155732b"
+        );
+        assert_eq!(
+            compressed(&gens.H_vec[RANGE_PROOF_BIT_LENGTH - 1]),
+            "1626c3a94a56343cf2916ba68e2e4a49b280a29dc73264473e342cc3df4e8263"
+        );
+    }
+}
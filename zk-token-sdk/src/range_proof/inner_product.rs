@@ -0,0 +1,191 @@
+//! Inner product argument used to compress the vector openings of a range
+//! proof to a logarithmic number of points.
+use {
+    crate::{errors::ProofError, range_proof::canonical_scalar, transcript::TranscriptProtocol},
+    curve25519_dalek::{
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+        traits::MultiscalarMul,
+    },
+    merlin::Transcript,
+};
+
+/// Number of folding rounds for a 64-element argument.
+const INNER_PRODUCT_ROUNDS: usize = 6;
+
+/// Byte length of a serialized `InnerProductProof`.
+pub const INNER_PRODUCT_PROOF_LEN: usize = (2 * INNER_PRODUCT_ROUNDS + 2) * 32;
+
+/// The folding challenges squared, their inverses squared, and the coefficients
+/// of the `G` generators in the fully folded generator.
+type VerificationScalars = (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>);
+
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InnerProductProof {
+    pub(crate) L_vec: Vec<CompressedRistretto>,
+    pub(crate) R_vec: Vec<CompressedRistretto>,
+    pub(crate) a: Scalar,
+    pub(crate) b: Scalar,
+}
+
+#[allow(non_snake_case)]
+impl InnerProductProof {
+    /// Proves knowledge of `a` and `b` such that
+    /// `P = <a, G> + <b, H> + <a, b>*Q`.
+    ///
+    /// The lengths of all vectors must be equal and a power of two.
+    pub fn new(
+        Q: &RistrettoPoint,
+        mut G: Vec<RistrettoPoint>,
+        mut H: Vec<RistrettoPoint>,
+        mut a: Vec<Scalar>,
+        mut b: Vec<Scalar>,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let mut n = G.len();
+        assert!(n.is_power_of_two());
+        assert_eq!(H.len(), n);
+        assert_eq!(a.len(), n);
+        assert_eq!(b.len(), n);
+
+        transcript.innerproduct_domain_sep(n as u64);
+
+        let mut L_vec = Vec::new();
+        let mut R_vec = Vec::new();
+
+        while n > 1 {
+            n /= 2;
+            let (a_L, a_R) = a.split_at(n);
+            let (b_L, b_R) = b.split_at(n);
+            let (G_L, G_R) = G.split_at(n);
+            let (H_L, H_R) = H.split_at(n);
+
+            let c_L: Scalar = a_L.iter().zip(b_R).map(|(a, b)| a * b).sum();
+            let c_R: Scalar = a_R.iter().zip(b_L).map(|(a, b)| a * b).sum();
+
+            let L = RistrettoPoint::multiscalar_mul(
+                a_L.iter().chain(b_R).chain(Some(&c_L)),
+                G_R.iter().chain(H_L).chain(Some(Q)),
+            )
+            .compress();
+            let R = RistrettoPoint::multiscalar_mul(
+                a_R.iter().chain(b_L).chain(Some(&c_R)),
+                G_L.iter().chain(H_R).chain(Some(Q)),
+            )
+            .compress();
+
+            transcript.append_point(b"L", &L);
+            transcript.append_point(b"R", &R);
+            L_vec.push(L);
+            R_vec.push(R);
+
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.invert();
+
+            a = (0..n).map(|i| a_L[i] * u + a_R[i] * u_inv).collect();
+            b = (0..n).map(|i| b_L[i] * u_inv + b_R[i] * u).collect();
+            G = (0..n).map(|i| G_L[i] * u_inv + G_R[i] * u).collect();
+            H = (0..n).map(|i| H_L[i] * u + H_R[i] * u_inv).collect();
+        }
+
+        Self {
+            L_vec,
+            R_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    /// Replays the folding challenges and returns `(u_j^2, u_j^-2, s_i)`,
+    /// where `s_i` is the coefficient of the `i`-th `G` generator in the fully
+    /// folded generator.
+    pub(crate) fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Result<VerificationScalars, ProofError> {
+        let rounds = self.L_vec.len();
+        if rounds != self.R_vec.len() || n != 1 << rounds {
+            return Err(ProofError::FormatError);
+        }
+
+        transcript.innerproduct_domain_sep(n as u64);
+
+        let mut challenges = Vec::with_capacity(rounds);
+        for (L, R) in self.L_vec.iter().zip(&self.R_vec) {
+            transcript.validate_and_append_point(b"L", L)?;
+            transcript.validate_and_append_point(b"R", R)?;
+            challenges.push(transcript.challenge_scalar(b"u"));
+        }
+
+        let challenges_inv: Vec<Scalar> = challenges.iter().map(|u| u.invert()).collect();
+        let u_sq = challenges.iter().map(|u| u * u).collect();
+        let u_inv_sq = challenges_inv.iter().map(|u| u * u).collect();
+
+        // the first round splits on the most significant bit of the index
+        let s = (0..n)
+            .map(|i| {
+                (0..rounds)
+                    .map(|j| {
+                        if (i >> (rounds - 1 - j)) & 1 == 1 {
+                            challenges[j]
+                        } else {
+                            challenges_inv[j]
+                        }
+                    })
+                    .product()
+            })
+            .collect();
+
+        Ok((u_sq, u_inv_sq, s))
+    }
+
+    pub(crate) fn decompressed_L_vec(&self) -> Result<Vec<RistrettoPoint>, ProofError> {
+        decompress_all(&self.L_vec)
+    }
+
+    pub(crate) fn decompressed_R_vec(&self) -> Result<Vec<RistrettoPoint>, ProofError> {
+        decompress_all(&self.R_vec)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity((2 * self.L_vec.len() + 2) * 32);
+        for (L, R) in self.L_vec.iter().zip(&self.R_vec) {
+            buf.extend_from_slice(L.as_bytes());
+            buf.extend_from_slice(R.as_bytes());
+        }
+        buf.extend_from_slice(self.a.as_bytes());
+        buf.extend_from_slice(self.b.as_bytes());
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() % 64 != 0 || bytes.len() < 64 {
+            return Err(ProofError::FormatError);
+        }
+        let rounds = (bytes.len() - 64) / 64;
+
+        let mut L_vec = Vec::with_capacity(rounds);
+        let mut R_vec = Vec::with_capacity(rounds);
+        for round in bytes[..rounds * 64].chunks(64) {
+            L_vec.push(CompressedRistretto::from_slice(&round[..32]));
+            R_vec.push(CompressedRistretto::from_slice(&round[32..]));
+        }
+
+        let pos = rounds * 64;
+        Ok(Self {
+            L_vec,
+            R_vec,
+            a: canonical_scalar(&bytes[pos..pos + 32])?,
+            b: canonical_scalar(&bytes[pos + 32..])?,
+        })
+    }
+}
+
+fn decompress_all(points: &[CompressedRistretto]) -> Result<Vec<RistrettoPoint>, ProofError> {
+    points
+        .iter()
+        .map(|p| p.decompress().ok_or(ProofError::FormatError))
+        .collect()
+}
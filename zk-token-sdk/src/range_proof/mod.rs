@@ -0,0 +1,421 @@
+//! Bulletproofs range proof showing that a Pedersen commitment encodes a value
+//! in `[0, 2^64)`.
+//!
+//! The proof follows the single-value construction of Bünz et al. with the
+//! inner product argument compressing the final vector openings. The
+//! commitment uses the Pedersen bases `G` (value) and `H` (opening).
+//!
+//! The generators, transcript and encoding are those of the dalek bulletproofs
+//! for a single 64-bit value, so proofs created by either implementation verify
+//! with the other.
+use {
+    crate::{
+        encryption::pedersen::{PedersenCommitment, PedersenOpening, G, H},
+        errors::ProofError,
+        range_proof::{generators::RANGE_PROOF_GENS, inner_product::InnerProductProof},
+        transcript::TranscriptProtocol,
+    },
+    curve25519_dalek::{
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+        traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul},
+    },
+    merlin::Transcript,
+    rand::rngs::OsRng,
+    std::{convert::TryInto, iter},
+};
+
+pub mod generators;
+pub mod inner_product;
+
+/// Number of bits covered by a range proof.
+pub const RANGE_PROOF_BIT_LENGTH: usize = 64;
+
+/// Byte length of a serialized `RangeProof`.
+pub const RANGE_PROOF_LEN: usize = 7 * 32 + inner_product::INNER_PRODUCT_PROOF_LEN;
+
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeProof {
+    A: CompressedRistretto,
+    S: CompressedRistretto,
+    T_1: CompressedRistretto,
+    T_2: CompressedRistretto,
+    t_x: Scalar,
+    t_x_blinding: Scalar,
+    e_blinding: Scalar,
+    ipp_proof: InnerProductProof,
+}
+
+/// Returns `[1, x, x^2, ..., x^(n-1)]`.
+fn powers(x: Scalar, n: usize) -> Vec<Scalar> {
+    iter::successors(Some(Scalar::one()), |p| Some(p * x))
+        .take(n)
+        .collect()
+}
+
+fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Computes `(z - z^2) * <1, y^n> - z^3 * <1, 2^n>`.
+fn delta(y: &Scalar, z: &Scalar, n: usize) -> Scalar {
+    let sum_y: Scalar = powers(*y, n).into_iter().sum();
+    let sum_2: Scalar = powers(Scalar::from(2u64), n).into_iter().sum();
+    let z_sq = z * z;
+    (z - z_sq) * sum_y - z * z_sq * sum_2
+}
+
+#[allow(non_snake_case)]
+impl RangeProof {
+    fn transcript(commitment: &PedersenCommitment) -> Transcript {
+        let mut transcript = Transcript::new(b"range-proof");
+        transcript.rangeproof_domain_sep(RANGE_PROOF_BIT_LENGTH as u64, 1);
+        transcript.append_point(b"V", &commitment.get_point().compress());
+        transcript
+    }
+
+    /// Proves that `commitment`, opened by `opening`, encodes `amount`.
+    pub fn new(amount: u64, commitment: &PedersenCommitment, opening: &PedersenOpening) -> Self {
+        let n = RANGE_PROOF_BIT_LENGTH;
+        let gens = &*RANGE_PROOF_GENS;
+        let mut transcript = Self::transcript(commitment);
+
+        // commit to the bits of the amount
+        let a_L: Vec<Scalar> = (0..n).map(|i| Scalar::from((amount >> i) & 1)).collect();
+        let a_R: Vec<Scalar> = a_L.iter().map(|a| a - Scalar::one()).collect();
+        let a_blinding = Scalar::random(&mut OsRng);
+        let A = RistrettoPoint::multiscalar_mul(
+            iter::once(&a_blinding).chain(&a_L).chain(&a_R),
+            iter::once(&*H).chain(&gens.G_vec).chain(&gens.H_vec),
+        )
+        .compress();
+
+        // commit to the blinding vectors
+        let s_L: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut OsRng)).collect();
+        let s_R: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut OsRng)).collect();
+        let s_blinding = Scalar::random(&mut OsRng);
+        let S = RistrettoPoint::multiscalar_mul(
+            iter::once(&s_blinding).chain(&s_L).chain(&s_R),
+            iter::once(&*H).chain(&gens.G_vec).chain(&gens.H_vec),
+        )
+        .compress();
+
+        transcript.append_point(b"A", &A);
+        transcript.append_point(b"S", &S);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        let z_sq = z * z;
+
+        // l(X) = l_0 + l_1 X and r(X) = r_0 + r_1 X
+        let y_n = powers(y, n);
+        let two_n = powers(Scalar::from(2u64), n);
+        let l_0: Vec<Scalar> = a_L.iter().map(|a| a - z).collect();
+        let l_1 = s_L;
+        let r_0: Vec<Scalar> = (0..n)
+            .map(|i| y_n[i] * (a_R[i] + z) + z_sq * two_n[i])
+            .collect();
+        let r_1: Vec<Scalar> = (0..n).map(|i| y_n[i] * s_R[i]).collect();
+
+        // commit to the coefficients of t(X) = <l(X), r(X)>
+        let t_1 = inner_product(&l_0, &r_1) + inner_product(&l_1, &r_0);
+        let t_2 = inner_product(&l_1, &r_1);
+        let t_1_blinding = Scalar::random(&mut OsRng);
+        let t_2_blinding = Scalar::random(&mut OsRng);
+        let T_1 = RistrettoPoint::multiscalar_mul(&[t_1, t_1_blinding], &[*G, *H]).compress();
+        let T_2 = RistrettoPoint::multiscalar_mul(&[t_2, t_2_blinding], &[*G, *H]).compress();
+
+        transcript.append_point(b"T_1", &T_1);
+        transcript.append_point(b"T_2", &T_2);
+        let x = transcript.challenge_scalar(b"x");
+
+        let l: Vec<Scalar> = (0..n).map(|i| l_0[i] + l_1[i] * x).collect();
+        let r: Vec<Scalar> = (0..n).map(|i| r_0[i] + r_1[i] * x).collect();
+        let t_x = inner_product(&l, &r);
+        let t_x_blinding = t_2_blinding * x * x + t_1_blinding * x + z_sq * opening.get_scalar();
+        let e_blinding = a_blinding + s_blinding * x;
+
+        transcript.append_scalar(b"t_x", &t_x);
+        transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+        let Q = w * *G;
+
+        let y_inv = y.invert();
+        let H_prime: Vec<RistrettoPoint> = powers(y_inv, n)
+            .iter()
+            .zip(&gens.H_vec)
+            .map(|(y_inv_i, H_i)| y_inv_i * H_i)
+            .collect();
+
+        let ipp_proof =
+            InnerProductProof::new(&Q, gens.G_vec.clone(), H_prime, l, r, &mut transcript);
+
+        Self {
+            A,
+            S,
+            T_1,
+            T_2,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp_proof,
+        }
+    }
+
+    /// Verifies that `commitment` encodes a value in `[0, 2^64)`.
+    pub fn verify(&self, commitment: &PedersenCommitment) -> Result<(), ProofError> {
+        let n = RANGE_PROOF_BIT_LENGTH;
+        let gens = &*RANGE_PROOF_GENS;
+        let mut transcript = Self::transcript(commitment);
+
+        transcript.validate_and_append_point(b"A", &self.A)?;
+        transcript.validate_and_append_point(b"S", &self.S)?;
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        transcript.validate_and_append_point(b"T_1", &self.T_1)?;
+        transcript.validate_and_append_point(b"T_2", &self.T_2)?;
+        let x = transcript.challenge_scalar(b"x");
+        transcript.append_scalar(b"t_x", &self.t_x);
+        transcript.append_scalar(b"t_x_blinding", &self.t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &self.e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+
+        let (u_sq, u_inv_sq, s) = self.ipp_proof.verification_scalars(n, &mut transcript)?;
+
+        let A = self.A.decompress().ok_or(ProofError::FormatError)?;
+        let S = self.S.decompress().ok_or(ProofError::FormatError)?;
+        let T_1 = self.T_1.decompress().ok_or(ProofError::FormatError)?;
+        let T_2 = self.T_2.decompress().ok_or(ProofError::FormatError)?;
+        let L_vec = self.ipp_proof.decompressed_L_vec()?;
+        let R_vec = self.ipp_proof.decompressed_R_vec()?;
+        let V = commitment.get_point();
+        let z_sq = z * z;
+
+        // t_x*G + t_x_blinding*H == z^2*V + delta(y, z)*G + x*T_1 + x^2*T_2
+        let check_t = RistrettoPoint::vartime_multiscalar_mul(
+            &[
+                self.t_x - delta(&y, &z, n),
+                self.t_x_blinding,
+                -z_sq,
+                -x,
+                -x * x,
+            ],
+            &[*G, *H, *V, T_1, T_2],
+        );
+        if !check_t.is_identity() {
+            return Err(ProofError::VerificationError);
+        }
+
+        // A + x*S - e_blinding*H + w*(t_x - a*b)*G + sum(u^2*L + u^-2*R)
+        //   - sum((z + a*s_i)*G_i) + sum((z + (z^2*2^i - b/s_i)*y^-i)*H_i) == 0
+        let a = self.ipp_proof.a;
+        let b = self.ipp_proof.b;
+        let two_n = powers(Scalar::from(2u64), n);
+        let y_inv_n = powers(y.invert(), n);
+        let g_scalars = s.iter().map(|s_i| -z - a * s_i);
+        let h_scalars = (0..n).map(|i| z + (z_sq * two_n[i] - b * s[n - 1 - i]) * y_inv_n[i]);
+
+        let check_ipp = RistrettoPoint::vartime_multiscalar_mul(
+            [Scalar::one(), x, -self.e_blinding, w * (self.t_x - a * b)]
+                .iter()
+                .cloned()
+                .chain(u_sq)
+                .chain(u_inv_sq)
+                .chain(g_scalars)
+                .chain(h_scalars),
+            [A, S, *H, *G]
+                .iter()
+                .chain(&L_vec)
+                .chain(&R_vec)
+                .chain(&gens.G_vec)
+                .chain(&gens.H_vec),
+        );
+        if !check_ipp.is_identity() {
+            return Err(ProofError::VerificationError);
+        }
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> [u8; RANGE_PROOF_LEN] {
+        let mut buf = [0u8; RANGE_PROOF_LEN];
+        buf[..32].copy_from_slice(self.A.as_bytes());
+        buf[32..64].copy_from_slice(self.S.as_bytes());
+        buf[64..96].copy_from_slice(self.T_1.as_bytes());
+        buf[96..128].copy_from_slice(self.T_2.as_bytes());
+        buf[128..160].copy_from_slice(self.t_x.as_bytes());
+        buf[160..192].copy_from_slice(self.t_x_blinding.as_bytes());
+        buf[192..224].copy_from_slice(self.e_blinding.as_bytes());
+        buf[224..].copy_from_slice(&self.ipp_proof.to_bytes());
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != RANGE_PROOF_LEN {
+            return Err(ProofError::FormatError);
+        }
+        Ok(Self {
+            A: CompressedRistretto::from_slice(&bytes[..32]),
+            S: CompressedRistretto::from_slice(&bytes[32..64]),
+            T_1: CompressedRistretto::from_slice(&bytes[64..96]),
+            T_2: CompressedRistretto::from_slice(&bytes[96..128]),
+            t_x: canonical_scalar(&bytes[128..160])?,
+            t_x_blinding: canonical_scalar(&bytes[160..192])?,
+            e_blinding: canonical_scalar(&bytes[192..224])?,
+            ipp_proof: InnerProductProof::from_bytes(&bytes[224..])?,
+        })
+    }
+}
+
+pub(crate) fn canonical_scalar(bytes: &[u8]) -> Result<Scalar, ProofError> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| ProofError::FormatError)?;
+    Scalar::from_canonical_bytes(bytes).ok_or(ProofError::FormatError)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::encryption::pedersen::Pedersen, curve25519_dalek::traits::Identity};
+
+    #[test]
+    fn test_range_proof_correctness() {
+        for amount in [0, 55, u64::MAX] {
+            let (commitment, opening) = Pedersen::new(amount);
+            let proof = RangeProof::new(amount, &commitment, &opening);
+            assert!(proof.verify(&commitment).is_ok());
+
+            let proof = RangeProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert!(proof.verify(&commitment).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_range_proof_wrong_commitment() {
+        let (commitment, opening) = Pedersen::new(55_u64);
+        let proof = RangeProof::new(55, &commitment, &opening);
+
+        let (other_commitment, _) = Pedersen::new(55_u64);
+        assert_eq!(
+            proof.verify(&other_commitment),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn test_range_proof_negative_amount() {
+        // a commitment to -1 cannot be opened to any 64-bit value
+        let opening = PedersenOpening::new_rand();
+        let commitment = PedersenCommitment(-Scalar::one() * *G + opening.get_scalar() * *H);
+        let proof = RangeProof::new(u64::MAX, &commitment, &opening);
+        assert!(proof.verify(&commitment).is_err());
+    }
+
+    // Proofs for the amounts 55 and 2^64 - 1, created by the dalek bulletproofs
+    // with `RangeProof::prove_single` over a `range-proof` transcript
+    const REFERENCE_COMMITMENT_55: &str =
+        "024914cdb2b5f21c12ca8f29b7280e1ee3676a11f2ca602bc08cdb6ec846800d";
+    const REFERENCE_PROOF_55: &str = concat!(
+        "d2cfc7fee374214f62ade04e37898d527def5d8508b495d2168a7c5fab2f8838",
+        "1252bab7045aeee21f73ecd6fb414535ce6c6243b6d0b84424caa4f5da76a809",
+        "0c95fdbdedacea4865b38e32e8bb7729094876da8c198c16d248664a2c40d40a",
+        "96e18b4737b70eff1f2d696be2b8d725a360fb7527653acf8285e27ee3b84468",
+        "439a809682d1d4aeff9891814bc458ec741e51f1d608155960984dccfcab6d08",
+        "f453fe05aa489f05eb4ba804e25c3af49b2608cb6b256e73a1a1b2914acf5500",
+        "b19e44f924018710f18b1bc12b112b748feaa7aaeb24d5940fdf19f0cbdd9f03",
+        "3203b4a730371bf475a79a9c250275dd06008ca87ef578707478234060c8a05f",
+        "acc870fc2a080a299080f7a0e6b364d41b0aeba5ec034666ee32b2f92ac8ff38",
+        "52184bad697cc613630c2e8900f47f8db8f26e0427a844572d069465b476aa59",
+        "42620c33483e429fef38e6f9ae14eda7580fd2e96d1808876b6b78b9fff36379",
+        "42052e57bd33209e0c3b651f1a1ad1722af0ceab2d1b16f87bf94cc1720ad954",
+        "50b2f4cb27a637f9c5704f73fdf1a64f9bfe5b55c08942bfadcb80725831d056",
+        "6eb30d89c27103783c30b896c9eacc02cf597d0af8c91c87a70bf9f2b1e66d2f",
+        "561387f333590ec9267f9dbb6833091befb02c43d65c183b4643e9db02f56464",
+        "4afe5277ef20ba1d114fe8ddcf224dbdf53626d59c8fc9264c1050593ef6802f",
+        "763ee15a2a58ab194a1dd8b5d53fab3c2d82dd645af1ad875a138647df79fd65",
+        "0a6dfe751a6e4bea96947792473aeb40f255dc024c529fb729674445518a7602",
+        "fa86994fa6a968bcedcbd625565b91b61f95d7ed89c1b514f5895773a6497d6c",
+        "fe23bf4868ef041c679f7c40e4c8876c2b700c596291b87903615705ed882507",
+        "44f4dcd9ab2d30fdedc818af05cc20a3adab99226e2ab63dd9b60a5e3d83050d",
+    );
+    const REFERENCE_COMMITMENT_MAX: &str =
+        "a45bb7e2a732a7a894425a6e04ccb08b843be29ec7ebe0fc1180bee771f02c13";
+    const REFERENCE_PROOF_MAX: &str = concat!(
+        "feecd3ab35ec28a3306a36c46ca630aef92b8406c3c5e78b6328a8d8dcb64868",
+        "e84fd0002ff0383e59e2499f88c5cc65d917714145d6688b1612f374e2c47b4e",
+        "d2c424e2563f872d8c8d5a490495dd7cebdb8c349b3b9937db7750c0ff9a9c6a",
+        "f068fb2d0c6d97de6c31936f1c2faf69017011ab820b0b26dfdcb09a43c67d55",
+        "60a74af55cd38ff9519535f01b08d6e09da5d170ac33aea00f6568b364c01a07",
+        "715d753f0c86765f2b04181cac502dfcde13222472c1ddd539a390052ae81809",
+        "2edcdd6578ca7f346f283d2e2df98aeb91a86a56ae0a160ece06dbb48982fb02",
+        "a0102508a2db70540d95c3d8b7d8229c4483e2c55572049e75a157d4663d482b",
+        "44e7ee24defd33a57f2ec430100f54707c7e0a2f6b5e946cca9cd4c6eb607a16",
+        "d8b1d5f115bbe598add0d1441804ddc35073e94ff948b1845bac3db040164c36",
+        "02b5ac66e3dd69ba0fbe516875975b6f31dc7d2d78cad69f741c740ccc0a3b5e",
+        "2ce3190e9bf71f0d12c642ebd999b37ef3a87ce0beaaa8376733e827f0e14c4b",
+        "4c8225f17f7c51a7febe9a8834c62d6255748e51c237293635fcde5ac4427d2b",
+        "7a439ea57bd4c1ff3b280cee3bf727e27dd238efbfb40cc510520c6c2cf23543",
+        "2894908339cfd5b9587bf2070ad67a8ec656b22e6e0096f91dcc1e3be1258e6b",
+        "c6663a9466a34a8c842960643f328a0f56d004042e61b1f66ca330161de57820",
+        "4c76e0caf6aa4eadb6873d88e15d43408c85622a6620c700c95486524d911d56",
+        "1e816a6dbb803de61d45a37e81dc65c6dc429b2222d72a0fade541512c562555",
+        "8cc0ac558ee893aabc6b01a59bbef6c2a4dd65ad8a9c811c3dda0912d1f3aa5d",
+        "ca412dfb2ffc8d01aec7cf4029efd21cc7212fb7f5f9d9bacd23b2f08a894c0f",
+        "61683fd90538d36dc94fbea36ae72d30d9b5cceaba76110437fce2c26925970d",
+    );
+
+    #[test]
+    fn test_range_proof_reference_vectors() {
+        for (commitment, proof) in [
+            (REFERENCE_COMMITMENT_55, REFERENCE_PROOF_55),
+            (REFERENCE_COMMITMENT_MAX, REFERENCE_PROOF_MAX),
+        ] {
+            let commitment =
+                PedersenCommitment::from_bytes(&hex::decode(commitment).unwrap()).unwrap();
+            let proof_bytes = hex::decode(proof).unwrap();
+            let proof = RangeProof::from_bytes(&proof_bytes).unwrap();
+            assert_eq!(proof.to_bytes().to_vec(), proof_bytes);
+            assert!(proof.verify(&commitment).is_ok());
+
+            let (other_commitment, _) = Pedersen::new(55_u64);
+            assert!(proof.verify(&other_commitment).is_err());
+        }
+    }
+
+    #[test]
+    fn test_range_proof_identity_points() {
+        let (commitment, opening) = Pedersen::new(55_u64);
+        let proof = RangeProof::new(55, &commitment, &opening);
+        let identity = CompressedRistretto::identity();
+
+        for point in 0..4 {
+            let mut proof = proof.clone();
+            match point {
+                0 => proof.A = identity,
+                1 => proof.S = identity,
+                2 => proof.T_1 = identity,
+                _ => proof.T_2 = identity,
+            }
+            assert_eq!(
+                proof.verify(&commitment),
+                Err(ProofError::VerificationError)
+            );
+        }
+
+        let mut proof = proof;
+        proof.ipp_proof.L_vec[0] = identity;
+        assert_eq!(
+            proof.verify(&commitment),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn test_range_proof_tampered_bytes() {
+        let (commitment, opening) = Pedersen::new(77_u64);
+        let mut bytes = RangeProof::new(77, &commitment, &opening).to_bytes();
+        bytes[0] ^= 1;
+        assert!(RangeProof::from_bytes(&bytes)
+            .and_then(|proof| proof.verify(&commitment))
+            .is_err());
+    }
+}
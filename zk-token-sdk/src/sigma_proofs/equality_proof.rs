@@ -0,0 +1,263 @@
+//! Proof that an ElGamal ciphertext and a Pedersen commitment encode the same
+//! amount.
+//!
+//! The prover holds the ElGamal secret key `s` and the opening `r` of the
+//! Pedersen commitment. Since `C_eg - s*D = x*G`, the protocol proves knowledge
+//! of `(s, x, r)` such that `s*P = H`, `x*G + s*D = C_eg` and `x*G + r*H = C`.
+use {
+    crate::{
+        encryption::{
+            elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey},
+            pedersen::{PedersenCommitment, PedersenOpening, G, H},
+        },
+        errors::ProofError,
+        transcript::TranscriptProtocol,
+    },
+    curve25519_dalek::{
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+        traits::{IsIdentity, MultiscalarMul},
+    },
+    merlin::Transcript,
+    rand::rngs::OsRng,
+    std::convert::TryInto,
+};
+
+/// Byte length of a serialized `CtxtCommEqualityProof`.
+pub const CTXT_COMM_EQUALITY_PROOF_LEN: usize = 192;
+
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CtxtCommEqualityProof {
+    Y_0: CompressedRistretto,
+    Y_1: CompressedRistretto,
+    Y_2: CompressedRistretto,
+    z_s: Scalar,
+    z_x: Scalar,
+    z_r: Scalar,
+}
+
+#[allow(non_snake_case)]
+impl CtxtCommEqualityProof {
+    fn transcript(
+        pubkey: &ElGamalPubkey,
+        ciphertext: &ElGamalCiphertext,
+        commitment: &PedersenCommitment,
+    ) -> Transcript {
+        let mut transcript = Transcript::new(b"ctxt-comm-equality-proof");
+        transcript.append_point(b"pubkey", &pubkey.get_point().compress());
+        transcript.append_point(
+            b"ciphertext-commitment",
+            &ciphertext.commitment.get_point().compress(),
+        );
+        transcript.append_point(
+            b"ciphertext-handle",
+            &ciphertext.handle.get_point().compress(),
+        );
+        transcript.append_point(b"commitment", &commitment.get_point().compress());
+        transcript
+    }
+
+    /// Proves that `ciphertext` and `commitment` both encode `amount`.
+    pub fn new(
+        keypair: &ElGamalKeypair,
+        ciphertext: &ElGamalCiphertext,
+        amount: u64,
+        opening: &PedersenOpening,
+        commitment: &PedersenCommitment,
+    ) -> Self {
+        let mut transcript = Self::transcript(&keypair.public, ciphertext, commitment);
+
+        let P = keypair.public.get_point();
+        let D = ciphertext.handle.get_point();
+        let s = keypair.secret.get_scalar();
+        let x = Scalar::from(amount);
+        let r = opening.get_scalar();
+
+        let y_s = Scalar::random(&mut OsRng);
+        let y_x = Scalar::random(&mut OsRng);
+        let y_r = Scalar::random(&mut OsRng);
+
+        let Y_0 = (y_s * P).compress();
+        let Y_1 = RistrettoPoint::multiscalar_mul(&[y_x, y_s], &[*G, *D]).compress();
+        let Y_2 = RistrettoPoint::multiscalar_mul(&[y_x, y_r], &[*G, *H]).compress();
+
+        transcript.append_point(b"Y_0", &Y_0);
+        transcript.append_point(b"Y_1", &Y_1);
+        transcript.append_point(b"Y_2", &Y_2);
+        let c = transcript.challenge_scalar(b"c");
+
+        Self {
+            Y_0,
+            Y_1,
+            Y_2,
+            z_s: c * s + y_s,
+            z_x: c * x + y_x,
+            z_r: c * r + y_r,
+        }
+    }
+
+    /// Verifies the proof against the public key, ciphertext and commitment.
+    pub fn verify(
+        &self,
+        pubkey: &ElGamalPubkey,
+        ciphertext: &ElGamalCiphertext,
+        commitment: &PedersenCommitment,
+    ) -> Result<(), ProofError> {
+        let mut transcript = Self::transcript(pubkey, ciphertext, commitment);
+        transcript.append_point(b"Y_0", &self.Y_0);
+        transcript.append_point(b"Y_1", &self.Y_1);
+        transcript.append_point(b"Y_2", &self.Y_2);
+        let c = transcript.challenge_scalar(b"c");
+
+        let Y_0 = self.Y_0.decompress().ok_or(ProofError::FormatError)?;
+        let Y_1 = self.Y_1.decompress().ok_or(ProofError::FormatError)?;
+        let Y_2 = self.Y_2.decompress().ok_or(ProofError::FormatError)?;
+
+        let P = pubkey.get_point();
+        let C_eg = ciphertext.commitment.get_point();
+        let D = ciphertext.handle.get_point();
+        let C = commitment.get_point();
+
+        // z_s*P - c*H - Y_0 == 0
+        let check_0 =
+            RistrettoPoint::multiscalar_mul(&[self.z_s, -c, -Scalar::one()], &[*P, *H, Y_0]);
+        // z_x*G + z_s*D - c*C_eg - Y_1 == 0
+        let check_1 = RistrettoPoint::multiscalar_mul(
+            &[self.z_x, self.z_s, -c, -Scalar::one()],
+            &[*G, *D, *C_eg, Y_1],
+        );
+        // z_x*G + z_r*H - c*C - Y_2 == 0
+        let check_2 = RistrettoPoint::multiscalar_mul(
+            &[self.z_x, self.z_r, -c, -Scalar::one()],
+            &[*G, *H, *C, Y_2],
+        );
+
+        if check_0.is_identity() && check_1.is_identity() && check_2.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; CTXT_COMM_EQUALITY_PROOF_LEN] {
+        let mut buf = [0u8; CTXT_COMM_EQUALITY_PROOF_LEN];
+        buf[..32].copy_from_slice(self.Y_0.as_bytes());
+        buf[32..64].copy_from_slice(self.Y_1.as_bytes());
+        buf[64..96].copy_from_slice(self.Y_2.as_bytes());
+        buf[96..128].copy_from_slice(self.z_s.as_bytes());
+        buf[128..160].copy_from_slice(self.z_x.as_bytes());
+        buf[160..192].copy_from_slice(self.z_r.as_bytes());
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        if bytes.len() != CTXT_COMM_EQUALITY_PROOF_LEN {
+            return Err(ProofError::FormatError);
+        }
+        let scalar = |b: &[u8]| -> Result<Scalar, ProofError> {
+            let b: [u8; 32] = b.try_into().map_err(|_| ProofError::FormatError)?;
+            Scalar::from_canonical_bytes(b).ok_or(ProofError::FormatError)
+        };
+        Ok(Self {
+            Y_0: CompressedRistretto::from_slice(&bytes[..32]),
+            Y_1: CompressedRistretto::from_slice(&bytes[32..64]),
+            Y_2: CompressedRistretto::from_slice(&bytes[64..96]),
+            z_s: scalar(&bytes[96..128])?,
+            z_x: scalar(&bytes[128..160])?,
+            z_r: scalar(&bytes[160..192])?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::encryption::{elgamal::ElGamal, pedersen::Pedersen},
+    };
+
+    #[test]
+    fn test_ctxt_comm_equality_proof_correctness() {
+        let keypair = ElGamalKeypair::new_rand();
+        let amount: u64 = 55;
+        let ciphertext = ElGamal::encrypt(&keypair.public, amount);
+        let (commitment, opening) = Pedersen::new(amount);
+
+        let proof =
+            CtxtCommEqualityProof::new(&keypair, &ciphertext, amount, &opening, &commitment);
+        assert!(proof
+            .verify(&keypair.public, &ciphertext, &commitment)
+            .is_ok());
+
+        let proof = CtxtCommEqualityProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(proof
+            .verify(&keypair.public, &ciphertext, &commitment)
+            .is_ok());
+    }
+
+    // A proof that a ciphertext and a commitment both encode the amount 55,
+    // generated by this implementation
+    const REFERENCE_PUBKEY: &str =
+        "1e38d2b002a0ad17adc7f2a6deb11f17a5ca8e14afac1a484c9da94981dabb47";
+    const REFERENCE_CIPHERTEXT: &str = concat!(
+        "1818a23db6b3c1c5c0e045b73a9fa1004cd3e2267fcdd6f032699c100d48805f",
+        "3ad9ac640268a8793c69d02acb25b504b701a3bda8bd7fe92ffeae28ad08e969",
+    );
+    const REFERENCE_COMMITMENT: &str =
+        "8a937587f7dfbabb15b7b29c83a38d33cdaf911fe5abad67f42467cc87ccbd28";
+    const REFERENCE_PROOF: &str = concat!(
+        "109309e9ca52fb3e128e1705d822aa87b018c641c5bfd8bb8976a2bef580f77e",
+        "1c11906aa0182d9391fdd431706efc736fe5a13777b9149ef9935d73b067e23c",
+        "8caa49188b7b4e9541dfe8eca62a28a9fc32f57895008f3dc1443b0e6c82090d",
+        "d83453788905b2316ea75cd3ff31450f3365dea7de18bb9cdd2b42fd99693006",
+        "dcd3a7821ae988aceb04d6dc6071e6740f22e41cd97f61df5b991c19eaec2a08",
+        "509781610520109d0e1063be692ec68385b46c2cf103bef7842639f43acada0a",
+    );
+
+    #[test]
+    fn test_ctxt_comm_equality_proof_reference_vector() {
+        let pubkey = ElGamalPubkey::from_bytes(&hex::decode(REFERENCE_PUBKEY).unwrap()).unwrap();
+        let ciphertext =
+            ElGamalCiphertext::from_bytes(&hex::decode(REFERENCE_CIPHERTEXT).unwrap()).unwrap();
+        let commitment =
+            PedersenCommitment::from_bytes(&hex::decode(REFERENCE_COMMITMENT).unwrap()).unwrap();
+        let proof_bytes = hex::decode(REFERENCE_PROOF).unwrap();
+        let proof = CtxtCommEqualityProof::from_bytes(&proof_bytes).unwrap();
+        assert_eq!(proof.to_bytes().to_vec(), proof_bytes);
+        assert!(proof.verify(&pubkey, &ciphertext, &commitment).is_ok());
+
+        let (other_commitment, _) = Pedersen::new(55_u64);
+        assert!(proof
+            .verify(&pubkey, &ciphertext, &other_commitment)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ctxt_comm_equality_proof_mismatched_amount() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = ElGamal::encrypt(&keypair.public, 55_u64);
+        let (commitment, opening) = Pedersen::new(77_u64);
+
+        let proof = CtxtCommEqualityProof::new(&keypair, &ciphertext, 77, &opening, &commitment);
+        assert_eq!(
+            proof.verify(&keypair.public, &ciphertext, &commitment),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn test_ctxt_comm_equality_proof_wrong_pubkey() {
+        let keypair = ElGamalKeypair::new_rand();
+        let other = ElGamalKeypair::new_rand();
+        let amount: u64 = 55;
+        let ciphertext = ElGamal::encrypt(&keypair.public, amount);
+        let (commitment, opening) = Pedersen::new(amount);
+
+        let proof =
+            CtxtCommEqualityProof::new(&keypair, &ciphertext, amount, &opening, &commitment);
+        assert!(proof
+            .verify(&other.public, &ciphertext, &commitment)
+            .is_err());
+    }
+}
@@ -0,0 +1,2 @@
+//! Sigma protocols proving relations between ciphertexts and commitments.
+pub mod equality_proof;
@@ -0,0 +1,122 @@
+//! Fiat-Shamir transcripts for the proofs.
+//!
+//! Proofs are made non-interactive with merlin transcripts, which absorb every
+//! labelled message into a STROBE-128 duplex so that two different sequences of
+//! messages never produce the same challenges.  This is the transcript the dalek
+//! bulletproofs use, which the range proof follows.
+use {
+    crate::errors::ProofError,
+    curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar, traits::IsIdentity},
+    merlin::Transcript,
+};
+
+pub(crate) trait TranscriptProtocol {
+    /// Separates a range proof over `m` values of `n` bits each.
+    fn rangeproof_domain_sep(&mut self, n: u64, m: u64);
+
+    /// Separates an inner product argument over vectors of length `n`.
+    fn innerproduct_domain_sep(&mut self, n: u64);
+
+    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto);
+
+    /// Appends a point received from a prover, rejecting the identity, which
+    /// would let the prover cancel terms of the verification equations.
+    fn validate_and_append_point(
+        &mut self,
+        label: &'static [u8],
+        point: &CompressedRistretto,
+    ) -> Result<(), ProofError>;
+
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
+
+    /// Derives a challenge scalar from everything absorbed so far.
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+}
+
+impl TranscriptProtocol for Transcript {
+    fn rangeproof_domain_sep(&mut self, n: u64, m: u64) {
+        self.append_message(b"dom-sep", b"rangeproof v1");
+        self.append_u64(b"n", n);
+        self.append_u64(b"m", m);
+    }
+
+    fn innerproduct_domain_sep(&mut self, n: u64) {
+        self.append_message(b"dom-sep", b"ipp v1");
+        self.append_u64(b"n", n);
+    }
+
+    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto) {
+        self.append_message(label, point.as_bytes());
+    }
+
+    fn validate_and_append_point(
+        &mut self,
+        label: &'static [u8],
+        point: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        if point.is_identity() {
+            return Err(ProofError::VerificationError);
+        }
+        self.append_point(label, point);
+        Ok(())
+    }
+
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
+        self.append_message(label, scalar.as_bytes());
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        let mut wide = [0u8; 64];
+        self.challenge_bytes(label, &mut wide);
+        Scalar::from_bytes_mod_order_wide(&wide)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, curve25519_dalek::traits::Identity};
+
+    #[test]
+    fn test_transcript_is_deterministic() {
+        let mut a = Transcript::new(b"test");
+        let mut b = Transcript::new(b"test");
+        a.append_message(b"msg", b"hello");
+        b.append_message(b"msg", b"hello");
+        assert_eq!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        let mut c = Transcript::new(b"test");
+        c.append_message(b"msg", b"hellp");
+        assert_ne!(a.challenge_scalar(b"c"), c.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn test_challenge_scalar_reference_vector() {
+        let mut transcript = Transcript::new(b"test protocol");
+        transcript.append_message(b"some label", b"some data");
+        transcript.append_u64(b"n", 64);
+        assert_eq!(
+            transcript.challenge_scalar(b"challenge").to_bytes(),
+            REFERENCE_CHALLENGE,
+        );
+    }
+
+    #[test]
+    fn test_validate_and_append_point_rejects_identity() {
+        let mut transcript = Transcript::new(b"test");
+        let mut expected = transcript.clone();
+        assert_eq!(
+            transcript.validate_and_append_point(b"A", &CompressedRistretto::identity()),
+            Err(ProofError::VerificationError)
+        );
+        // the rejected point is not absorbed
+        assert_eq!(
+            transcript.challenge_scalar(b"c"),
+            expected.challenge_scalar(b"c")
+        );
+    }
+
+    const REFERENCE_CHALLENGE: [u8; 32] = [
+        77, 118, 169, 155, 207, 116, 29, 180, 101, 106, 108, 200, 76, 10, 130, 65, 82, 197, 8, 47,
+        211, 120, 223, 145, 85, 78, 54, 220, 204, 206, 22, 13,
+    ];
+}
@@ -0,0 +1,121 @@
+//! Instructions provided by the ZkTokenProof program.
+//!
+//! Each instruction carries a single proof in its data and takes no accounts.
+//! The instruction succeeds if and only if the proof verifies, so other
+//! programs can rely on a successfully executed proof instruction in the same
+//! transaction via instruction introspection.
+pub use crate::instruction::*;
+use {
+    num_derive::{FromPrimitive, ToPrimitive},
+    num_traits::{FromPrimitive, ToPrimitive},
+    solana_program::instruction::Instruction,
+};
+
+#[derive(Clone, Copy, Debug, FromPrimitive, ToPrimitive, PartialEq)]
+#[repr(u8)]
+pub enum ProofInstruction {
+    /// Verify a `RangeProof64Data`.
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `RangeProof64Data`
+    ///
+    VerifyRangeProof64,
+
+    /// Verify a `CtxtCommEqualityProofData`.
+    ///
+    /// Accounts expected by this instruction:
+    ///   None
+    ///
+    /// Data expected by this instruction:
+    ///   `CtxtCommEqualityProofData`
+    ///
+    VerifyCtxtCommEquality,
+}
+
+impl ProofInstruction {
+    pub fn encode<T: ZkProofData>(&self, proof_data: &T) -> Instruction {
+        let mut data = vec![ToPrimitive::to_u8(self).unwrap()];
+        data.extend_from_slice(&proof_data.to_bytes());
+        Instruction {
+            program_id: crate::zk_token_proof_program::id(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    pub fn decode_type(input: &[u8]) -> Option<Self> {
+        input.first().and_then(|x| FromPrimitive::from_u8(*x))
+    }
+
+    pub fn decode_data<T: ZkProofData>(input: &[u8]) -> Option<T> {
+        if input.is_empty() {
+            None
+        } else {
+            T::from_bytes(&input[1..]).ok()
+        }
+    }
+}
+
+pub fn verify_range_proof64(proof_data: &RangeProof64Data) -> Instruction {
+    ProofInstruction::VerifyRangeProof64.encode(proof_data)
+}
+
+pub fn verify_ctxt_comm_equality(proof_data: &CtxtCommEqualityProofData) -> Instruction {
+    ProofInstruction::VerifyCtxtCommEquality.encode(proof_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::encryption::{
+            elgamal::{ElGamal, ElGamalKeypair},
+            pedersen::Pedersen,
+        },
+    };
+
+    #[test]
+    fn test_encode_decode_range_proof64() {
+        let (commitment, opening) = Pedersen::new(55_u64);
+        let proof_data = RangeProof64Data::new(commitment, 55, &opening);
+        let instruction = verify_range_proof64(&proof_data);
+
+        assert_eq!(instruction.program_id, crate::zk_token_proof_program::id());
+        assert!(instruction.accounts.is_empty());
+        assert_eq!(
+            ProofInstruction::decode_type(&instruction.data),
+            Some(ProofInstruction::VerifyRangeProof64)
+        );
+        let decoded = ProofInstruction::decode_data::<RangeProof64Data>(&instruction.data).unwrap();
+        assert_eq!(decoded, proof_data);
+        assert!(decoded.verify().is_ok());
+    }
+
+    #[test]
+    fn test_encode_decode_ctxt_comm_equality() {
+        let keypair = ElGamalKeypair::new_rand();
+        let ciphertext = ElGamal::encrypt(&keypair.public, 77_u64);
+        let (commitment, opening) = Pedersen::new(77_u64);
+        let proof_data =
+            CtxtCommEqualityProofData::new(&keypair, ciphertext, 77, commitment, &opening);
+        let instruction = verify_ctxt_comm_equality(&proof_data);
+
+        assert_eq!(
+            ProofInstruction::decode_type(&instruction.data),
+            Some(ProofInstruction::VerifyCtxtCommEquality)
+        );
+        let decoded =
+            ProofInstruction::decode_data::<CtxtCommEqualityProofData>(&instruction.data).unwrap();
+        assert!(decoded.verify().is_ok());
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(ProofInstruction::decode_type(&[]), None);
+        assert_eq!(ProofInstruction::decode_type(&[255]), None);
+        assert!(ProofInstruction::decode_data::<RangeProof64Data>(&[0, 1, 2]).is_none());
+    }
+}
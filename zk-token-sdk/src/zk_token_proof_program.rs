@@ -0,0 +1,2 @@
+//! The native ZkTokenProof program ID.
+pub use solana_program::zk_token_proof_program::{check_id, id, ID};